@@ -11,6 +11,8 @@ use many_modules::account::features::{FeatureSet, TryCreateFeature};
 use many_modules::account::{Account, AddressRoleMap, Role};
 use many_modules::events::{AccountMultisigTransaction, EventInfo, EventLog};
 use many_modules::ledger::extended_info::TokenExtendedInfo;
+use many_modules::ledger::ScheduleTrigger;
+use many_modules::watchlist::WatchlistAction;
 use many_types::identity::Address;
 use many_types::ledger::{
     LedgerTokensAddressMap, TokenAmount, TokenInfo, TokenInfoSummary, TokenMaybeOwner,
@@ -297,6 +299,8 @@ fn extract_identity(merk: &merk::Merk) -> IdentityJsonRoot {
     }
 }
 
+/// The token identity is read from the state, so a genesis exported after a
+/// `ledger.updateTokenIdentity` uses the new identity.
 fn extract_token_identity(merk: &merk::Merk) -> TokenIdentityJsonRoot {
     const TOKEN_IDENTITY_ROOT: &str = "/config/token_identity";
 
@@ -786,6 +790,28 @@ enum EventInfoJson {
     TokenUpdate(TokenUpdateEventJson),
     TokenMint(TokenMintEventJson),
     TokenBurn(TokenBurnEventJson),
+    TokenIdentityUpdate(TokenIdentityUpdateEventJson),
+    AddressFreeze(AddressFreezeEventJson),
+    AddressUnfreeze(AddressUnfreezeEventJson),
+    ScheduledSendAdd(ScheduledSendAddEventJson),
+    ScheduledSendExecute(ScheduledSendExecuteEventJson),
+    ScheduledSendCancel(ScheduledSendCancelEventJson),
+    StreamCreate(StreamCreateEventJson),
+    StreamWithdraw(StreamWithdrawEventJson),
+    StreamCancel(StreamCancelEventJson),
+    WatchlistAdd(WatchlistAddEventJson),
+    WatchlistRemove(WatchlistRemoveEventJson),
+    WatchlistSetApprovers(WatchlistSetApproversEventJson),
+    WatchlistScreen(WatchlistScreenEventJson),
+    DustReap(DustReapEventJson),
+    LabelClaim(LabelClaimEventJson),
+    LabelRelease(LabelReleaseEventJson),
+    TokenOwnershipOffer(TokenOwnershipOfferEventJson),
+    TokenOwnershipAccept(TokenOwnershipAcceptEventJson),
+    TokenOwnershipCancel(TokenOwnershipCancelEventJson),
+    AccountRemoveFeatures(AccountRemoveFeaturesEventJson),
+    ScriptDeploy(ScriptDeployEventJson),
+    ScriptEmit(ScriptEmitEventJson),
 }
 
 #[derive(Debug, Serialize)]
@@ -854,6 +880,7 @@ struct AccountMultisigApproveEventJson {
     account: Address,
     token: String,
     approver: Address,
+    approved_as: Option<Address>,
 }
 
 #[derive(Debug, Serialize)]
@@ -861,6 +888,7 @@ struct AccountMultisigRevokeEventJson {
     account: Address,
     token: String,
     revoker: Address,
+    revoked_as: Option<Address>,
 }
 
 #[derive(Debug, Serialize)]
@@ -929,6 +957,225 @@ struct TokenBurnEventJson {
     memo: Option<String>,
 }
 
+#[derive(Debug, Serialize)]
+struct TokenIdentityUpdateEventJson {
+    previous: Address,
+    identity: Address,
+    memo: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct AddressFreezeEventJson {
+    address: Address,
+    memo: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct AddressUnfreezeEventJson {
+    address: Address,
+    memo: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+enum ScheduleTriggerJson {
+    Time(u64),
+    Height(u64),
+    After(u64),
+}
+
+// Implement From ScheduleTrigger for ScheduleTriggerJson
+impl From<ScheduleTrigger> for ScheduleTriggerJson {
+    fn from(at: ScheduleTrigger) -> Self {
+        match at {
+            ScheduleTrigger::Time(time) => ScheduleTriggerJson::Time(time.secs()),
+            ScheduleTrigger::Height(height) => ScheduleTriggerJson::Height(height),
+            ScheduleTrigger::After(duration) => ScheduleTriggerJson::After(duration.secs()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ScheduledSendAddEventJson {
+    id: u64,
+    sender: Address,
+    from: Address,
+    to: Address,
+    symbol: Address,
+    amount: TokenAmount,
+    at: ScheduleTriggerJson,
+    memo: Option<String>,
+    deposit_symbol: Address,
+    deposit: TokenAmount,
+}
+
+#[derive(Debug, Serialize)]
+struct ScheduledSendExecuteEventJson {
+    id: u64,
+    from: Address,
+    to: Address,
+    symbol: Address,
+    amount: TokenAmount,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ScheduledSendCancelEventJson {
+    id: u64,
+    canceller: Address,
+    memo: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct StreamCreateEventJson {
+    id: u64,
+    payer: Address,
+    payee: Address,
+    symbol: Address,
+    rate: TokenAmount,
+    start: u64,
+    end: u64,
+    memo: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct StreamWithdrawEventJson {
+    id: u64,
+    payee: Address,
+    symbol: Address,
+    amount: TokenAmount,
+    memo: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct StreamCancelEventJson {
+    id: u64,
+    payer: Address,
+    payee: Address,
+    symbol: Address,
+    paid: TokenAmount,
+    refunded: TokenAmount,
+    memo: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+enum WatchlistActionJson {
+    Alert,
+    RequireApproval,
+    Reject,
+}
+
+// Implement From WatchlistAction for WatchlistActionJson
+impl From<WatchlistAction> for WatchlistActionJson {
+    fn from(action: WatchlistAction) -> Self {
+        match action {
+            WatchlistAction::Alert => WatchlistActionJson::Alert,
+            WatchlistAction::RequireApproval => WatchlistActionJson::RequireApproval,
+            WatchlistAction::Reject => WatchlistActionJson::Reject,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct WatchlistAddEventJson {
+    address: Address,
+    action: WatchlistActionJson,
+    memo: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct WatchlistRemoveEventJson {
+    address: Address,
+    memo: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct WatchlistSetApproversEventJson {
+    approvers: BTreeSet<Address>,
+}
+
+#[derive(Debug, Serialize)]
+struct WatchlistScreenEventJson {
+    sender: Address,
+    from: Address,
+    to: Address,
+    address: Address,
+    action: WatchlistActionJson,
+    allowed: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct DustReapEventJson {
+    account: Address,
+    symbol: Address,
+    amount: TokenAmount,
+    pool: Address,
+}
+
+#[derive(Debug, Serialize)]
+struct LabelClaimEventJson {
+    label: String,
+    owner: Address,
+    symbol: Address,
+    deposit: TokenAmount,
+    memo: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct LabelReleaseEventJson {
+    label: String,
+    owner: Address,
+    symbol: Address,
+    refund: TokenAmount,
+    memo: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct TokenOwnershipOfferEventJson {
+    symbol: Address,
+    owner: Address,
+    new_owner: Address,
+    expires: u64,
+    memo: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct TokenOwnershipAcceptEventJson {
+    symbol: Address,
+    previous_owner: Address,
+    owner: Address,
+    memo: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct TokenOwnershipCancelEventJson {
+    symbol: Address,
+    owner: Address,
+    new_owner: Address,
+    memo: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct AccountRemoveFeaturesEventJson {
+    account: Address,
+    features: BTreeSet<u32>,
+    roles: AddressRoleMapJson,
+}
+
+#[derive(Debug, Serialize)]
+struct ScriptDeployEventJson {
+    id: u64,
+    owner: Address,
+    code_hash: String, // Hex encoded
+    memo: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ScriptEmitEventJson {
+    id: u64,
+    caller: Address,
+    data: String, // Hex encoded
+}
+
 // Implement From EventInfo for EventInfoJson
 impl From<EventInfo> for EventInfoJson {
     fn from(e: EventInfo) -> Self {
@@ -1017,19 +1264,23 @@ impl From<EventInfo> for EventInfoJson {
                 account,
                 token,
                 approver,
+                approved_as,
             } => Self::AccountMultisigApprove(AccountMultisigApproveEventJson {
                 account,
                 token: hex::encode(token.to_vec()),
                 approver,
+                approved_as,
             }),
             EventInfo::AccountMultisigRevoke {
                 account,
                 token,
                 revoker,
+                revoked_as,
             } => Self::AccountMultisigRevoke(AccountMultisigRevokeEventJson {
                 account,
                 token: hex::encode(token.to_vec()),
                 revoker,
+                revoked_as,
             }),
             EventInfo::AccountMultisigExecute {
                 account,
@@ -1125,6 +1376,253 @@ impl From<EventInfo> for EventInfoJson {
                 distribution,
                 memo: get_str_memo(&memo),
             }),
+            EventInfo::TokenIdentityUpdate {
+                previous,
+                identity,
+                memo,
+            } => Self::TokenIdentityUpdate(TokenIdentityUpdateEventJson {
+                previous,
+                identity,
+                memo: get_str_memo(&memo),
+            }),
+            EventInfo::AddressFreeze { address, memo } => {
+                Self::AddressFreeze(AddressFreezeEventJson {
+                    address,
+                    memo: get_str_memo(&memo),
+                })
+            }
+            EventInfo::AddressUnfreeze { address, memo } => {
+                Self::AddressUnfreeze(AddressUnfreezeEventJson {
+                    address,
+                    memo: get_str_memo(&memo),
+                })
+            }
+            EventInfo::ScheduledSendAdd {
+                id,
+                sender,
+                from,
+                to,
+                symbol,
+                amount,
+                at,
+                memo,
+                deposit_symbol,
+                deposit,
+            } => Self::ScheduledSendAdd(ScheduledSendAddEventJson {
+                id,
+                sender,
+                from,
+                to,
+                symbol,
+                amount,
+                at: at.into(),
+                memo: get_str_memo(&memo),
+                deposit_symbol,
+                deposit,
+            }),
+            EventInfo::ScheduledSendExecute {
+                id,
+                from,
+                to,
+                symbol,
+                amount,
+                error,
+            } => Self::ScheduledSendExecute(ScheduledSendExecuteEventJson {
+                id,
+                from,
+                to,
+                symbol,
+                amount,
+                error: error.map(|e| e.to_string()),
+            }),
+            EventInfo::ScheduledSendCancel {
+                id,
+                canceller,
+                memo,
+            } => Self::ScheduledSendCancel(ScheduledSendCancelEventJson {
+                id,
+                canceller,
+                memo: get_str_memo(&memo),
+            }),
+            EventInfo::StreamCreate {
+                id,
+                payer,
+                payee,
+                symbol,
+                rate,
+                start,
+                end,
+                memo,
+            } => Self::StreamCreate(StreamCreateEventJson {
+                id,
+                payer,
+                payee,
+                symbol,
+                rate,
+                start: start.secs(),
+                end: end.secs(),
+                memo: get_str_memo(&memo),
+            }),
+            EventInfo::StreamWithdraw {
+                id,
+                payee,
+                symbol,
+                amount,
+                memo,
+            } => Self::StreamWithdraw(StreamWithdrawEventJson {
+                id,
+                payee,
+                symbol,
+                amount,
+                memo: get_str_memo(&memo),
+            }),
+            EventInfo::StreamCancel {
+                id,
+                payer,
+                payee,
+                symbol,
+                paid,
+                refunded,
+                memo,
+            } => Self::StreamCancel(StreamCancelEventJson {
+                id,
+                payer,
+                payee,
+                symbol,
+                paid,
+                refunded,
+                memo: get_str_memo(&memo),
+            }),
+            EventInfo::WatchlistAdd {
+                address,
+                action,
+                memo,
+            } => Self::WatchlistAdd(WatchlistAddEventJson {
+                address,
+                action: action.into(),
+                memo: get_str_memo(&memo),
+            }),
+            EventInfo::WatchlistRemove { address, memo } => {
+                Self::WatchlistRemove(WatchlistRemoveEventJson {
+                    address,
+                    memo: get_str_memo(&memo),
+                })
+            }
+            EventInfo::WatchlistSetApprovers { approvers } => {
+                Self::WatchlistSetApprovers(WatchlistSetApproversEventJson { approvers })
+            }
+            EventInfo::WatchlistScreen {
+                sender,
+                from,
+                to,
+                address,
+                action,
+                allowed,
+            } => Self::WatchlistScreen(WatchlistScreenEventJson {
+                sender,
+                from,
+                to,
+                address,
+                action: action.into(),
+                allowed,
+            }),
+            EventInfo::DustReap {
+                account,
+                symbol,
+                amount,
+                pool,
+            } => Self::DustReap(DustReapEventJson {
+                account,
+                symbol,
+                amount,
+                pool,
+            }),
+            EventInfo::LabelClaim {
+                label,
+                owner,
+                symbol,
+                deposit,
+                memo,
+            } => Self::LabelClaim(LabelClaimEventJson {
+                label,
+                owner,
+                symbol,
+                deposit,
+                memo: get_str_memo(&memo),
+            }),
+            EventInfo::LabelRelease {
+                label,
+                owner,
+                symbol,
+                refund,
+                memo,
+            } => Self::LabelRelease(LabelReleaseEventJson {
+                label,
+                owner,
+                symbol,
+                refund,
+                memo: get_str_memo(&memo),
+            }),
+            EventInfo::TokenOwnershipOffer {
+                symbol,
+                owner,
+                new_owner,
+                expires,
+                memo,
+            } => Self::TokenOwnershipOffer(TokenOwnershipOfferEventJson {
+                symbol,
+                owner,
+                new_owner,
+                expires: expires.secs(),
+                memo: get_str_memo(&memo),
+            }),
+            EventInfo::TokenOwnershipAccept {
+                symbol,
+                previous_owner,
+                owner,
+                memo,
+            } => Self::TokenOwnershipAccept(TokenOwnershipAcceptEventJson {
+                symbol,
+                previous_owner,
+                owner,
+                memo: get_str_memo(&memo),
+            }),
+            EventInfo::TokenOwnershipCancel {
+                symbol,
+                owner,
+                new_owner,
+                memo,
+            } => Self::TokenOwnershipCancel(TokenOwnershipCancelEventJson {
+                symbol,
+                owner,
+                new_owner,
+                memo: get_str_memo(&memo),
+            }),
+            EventInfo::AccountRemoveFeatures {
+                account,
+                features,
+                roles,
+            } => Self::AccountRemoveFeatures(AccountRemoveFeaturesEventJson {
+                account,
+                features,
+                roles: get_roles(roles),
+            }),
+            EventInfo::ScriptDeploy {
+                id,
+                owner,
+                code_hash,
+                memo,
+            } => Self::ScriptDeploy(ScriptDeployEventJson {
+                id,
+                owner,
+                code_hash: hex::encode(code_hash.to_vec()),
+                memo: get_str_memo(&memo),
+            }),
+            EventInfo::ScriptEmit { id, caller, data } => Self::ScriptEmit(ScriptEmitEventJson {
+                id,
+                caller,
+                data: hex::encode(data.to_vec()),
+            }),
             _ => todo!(),
         }
     }
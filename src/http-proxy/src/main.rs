@@ -3,8 +3,12 @@ use many_client::client::blocking::ManyClient;
 use many_identity::{Address, AnonymousIdentity, Identity};
 use many_identity_dsa::CoseKeyIdentity;
 use many_modules::kvstore::{GetArgs, GetReturns};
+use many_modules::web::{
+    ConfigArgs, ConfigReturns, ContentHashArgs, ContentHashReturns, ResolveArgs, ResolveReturns,
+};
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::thread;
 use tiny_http::{Header, Method, Request, Response, Server, StatusCode};
@@ -60,6 +64,7 @@ fn process_request(http: Arc<Server>, client: Client) -> impl Fn() {
 
 fn handle_get_request(client: &Client, request: Request) {
     let mut path = "/http".to_string();
+    let mut site = None;
     let mut url = request.url().to_string();
     if url == "/" {
         url.push_str("index.html");
@@ -71,46 +76,181 @@ fn handle_get_request(client: &Client, request: Request) {
             let parts = site_name_and_addr.rsplitn(2, '-').collect::<Vec<_>>();
             if let [addr, site_name] = parts.as_slice() {
                 path = format!("{path}/{addr}/{site_name}");
+                site = Address::from_str(addr)
+                    .ok()
+                    .map(|owner| (owner, site_name.to_string()));
             }
         }
     }
+
+    // Follow the records of the website, serving the content of the website
+    // they point to, or redirecting every request.
+    if let Some(resolved) = site
+        .as_ref()
+        .and_then(|(owner, site_name)| resolve(client, owner, site_name))
+    {
+        if let Some(redirect) = resolved.redirect {
+            match Header::from_bytes("Location", redirect.to.as_bytes()) {
+                Ok(header) => {
+                    respond(
+                        request,
+                        Response::empty(redirect.status).with_header(header),
+                    );
+                    return;
+                }
+                Err(_) => warn!("Failed to create header for redirect: {}", redirect.to),
+            }
+        }
+        let owner = resolved.site.owner;
+        let site_name = resolved.site.site_name;
+        path = format!("/http/{owner}/{site_name}");
+        site = Some((owner, site_name));
+    }
     debug!("Received request for path: {path}{url}");
-    let result = client.call_(
-        "kvstore.get",
-        GetArgs {
-            key: format!("{path}{url}").into_bytes().into(),
-        },
-    );
-    match result {
-        Ok(result) => process_result(result, request),
-        Err(_) => {
-            let _ = request.respond(Response::empty(500));
+
+    let etag = site
+        .as_ref()
+        .and_then(|(owner, site_name)| get_etag(client, owner, site_name, &url));
+    if let Some(etag) = &etag {
+        if matches_if_none_match(&request, etag) {
+            let mut response = Response::empty(304);
+            if let Ok(header) = Header::from_bytes("ETag", etag.as_bytes()) {
+                response = response.with_header(header);
+            }
+            respond(request, response);
+            return;
         }
     }
+
+    match get_value(client, format!("{path}{url}")) {
+        Ok(Some(value)) => {
+            let file_path = request.url().to_string();
+            respond_with_value(value, file_path, 200, etag, request)
+        }
+        Ok(None) => respond_not_found(client, site, &path, &url, request),
+        Err(e) => {
+            warn!("Failed to get value: {}", e);
+            respond(request, Response::empty(500));
+        }
+    }
+}
+
+fn get_value(client: &Client, key: String) -> Result<Option<Vec<u8>>, String> {
+    let result = client
+        .call_(
+            "kvstore.get",
+            GetArgs {
+                key: key.into_bytes().into(),
+            },
+        )
+        .map_err(|e| e.to_string())?;
+    let GetReturns { value } = minicbor::decode(&result).map_err(|e| e.to_string())?;
+    Ok(value.map(Into::into))
+}
+
+/// Follow the records of a website to the website to serve, or the redirect to
+/// respond with. `None` if the server does not support records.
+fn resolve(client: &Client, owner: &Address, site_name: &str) -> Option<ResolveReturns> {
+    let result = client
+        .call_(
+            "web.resolve",
+            ResolveArgs {
+                owner: *owner,
+                site_name: site_name.to_string(),
+            },
+        )
+        .ok()?;
+    minicbor::decode(&result).ok()
+}
+
+/// The entity tag of a file of a website, from its content hash. `None` if the
+/// server does not know the hash of the file.
+fn get_etag(client: &Client, owner: &Address, site_name: &str, url: &str) -> Option<String> {
+    let result = client
+        .call_(
+            "web.contentHash",
+            ContentHashArgs {
+                owner: *owner,
+                site_name: site_name.to_string(),
+                path: Some(url.to_string()),
+            },
+        )
+        .ok()?;
+    let ContentHashReturns { hash, .. } = minicbor::decode(&result).ok()?;
+    Some(format!("\"{}\"", hex::encode(hash.as_slice())))
 }
 
-fn process_result(result: Vec<u8>, request: Request) {
-    match minicbor::decode::<GetReturns>(&result) {
-        Ok(GetReturns { value }) => match value {
-            None => {
-                if let Err(e) = request.respond(Response::empty(404)) {
-                    warn!("Failed to send response: {}", e);
+/// Returns true if the `If-None-Match` header of the request matches the
+/// entity tag, in which case the client already has the file.
+fn matches_if_none_match(request: &Request, etag: &str) -> bool {
+    request
+        .headers()
+        .iter()
+        .filter(|h| h.field.equiv("if-none-match"))
+        .flat_map(|h| h.value.as_str().split(','))
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// Respond to a path which does not exist, using the redirects and the 404
+/// document of the website, if any.
+fn respond_not_found(
+    client: &Client,
+    site: Option<(Address, String)>,
+    path: &str,
+    url: &str,
+    request: Request,
+) {
+    let config = site.and_then(|(owner, site_name)| {
+        let result = client
+            .call_("web.config", ConfigArgs { owner, site_name })
+            .ok()?;
+        minicbor::decode::<ConfigReturns>(&result)
+            .map(|returns| returns.config)
+            .ok()
+    });
+
+    if let Some(config) = config {
+        if let Some(redirect) = config.redirect_for(url) {
+            match Header::from_bytes("Location", redirect.to.as_bytes()) {
+                Ok(header) => {
+                    respond(
+                        request,
+                        Response::empty(redirect.status).with_header(header),
+                    );
+                    return;
                 }
+                Err(_) => warn!("Failed to create header for redirect: {}", redirect.to),
             }
-            Some(value) => respond_with_value(value.into(), request),
-        },
-        Err(e) => {
-            warn!("Failed to decode result: {}", e);
-            if let Err(e) = request.respond(Response::empty(500)) {
-                warn!("Failed to send response: {}", e);
+        }
+
+        if let Some(not_found) = config.not_found {
+            if let Ok(Some(value)) = get_value(client, format!("{path}{not_found}")) {
+                respond_with_value(value, not_found, 404, None, request);
+                return;
             }
         }
     }
+
+    respond(request, Response::empty(404));
+}
+
+fn respond<R: std::io::Read>(request: Request, response: Response<R>) {
+    if let Err(e) = request.respond(response) {
+        warn!("Failed to send response: {}", e);
+    }
 }
 
-fn respond_with_value(value: Vec<u8>, request: Request) {
-    let mimetype = new_mime_guess::from_path(request.url()).first_raw();
-    let mut response = Response::empty(200).with_data(value.as_slice(), Some(value.len()));
+/// Respond with the content of a file. The MIME type is guessed from its path.
+fn respond_with_value(
+    value: Vec<u8>,
+    file_path: String,
+    status: u16,
+    etag: Option<String>,
+    request: Request,
+) {
+    let mimetype = new_mime_guess::from_path(file_path).first_raw();
+    let mut response = Response::empty(status).with_data(value.as_slice(), Some(value.len()));
 
     if let Some(mimetype) = mimetype {
         if let Ok(header) = Header::from_bytes("Content-Type", mimetype) {
@@ -120,9 +260,15 @@ fn respond_with_value(value: Vec<u8>, request: Request) {
         }
     }
 
-    if let Err(e) = request.respond(response) {
-        warn!("Failed to send response: {}", e);
+    if let Some(etag) = etag {
+        if let Ok(header) = Header::from_bytes("ETag", etag.as_bytes()) {
+            response = response.with_header(header);
+        } else {
+            warn!("Failed to create header for ETag: {}", etag);
+        }
     }
+
+    respond(request, response);
 }
 
 fn main() {
@@ -1,14 +1,18 @@
 use clap::Parser;
 use many_client::client::blocking::ManyClient;
+use many_client::client::RetryPolicy;
 use many_error::{ManyError, Reason};
 use many_identity::{Address, AnonymousIdentity, Identity};
 use many_identity_dsa::CoseKeyIdentity;
+use many_kvstore::storage::{key_for_metadata, key_for_value};
+use many_ledger_verify::proof::verify_proof;
 use many_modules::kvstore::list::{ListArgs, ListReturns};
 use many_modules::kvstore::{KeyFilterType, TransferArgs};
 use many_modules::r#async::{StatusArgs, StatusReturn};
-use many_modules::{kvstore, r#async};
-use many_protocol::ResponseMessage;
-use many_types::{Either, SortOrder};
+use many_modules::{blockchain, kvstore, r#async};
+use many_protocol::{ContentEncoding, ResponseMessage};
+use many_types::proof::Proof;
+use many_types::{Either, SortOrder, Timestamp, PROOF};
 use std::collections::BTreeMap;
 use std::io::Read;
 use std::path::PathBuf;
@@ -25,15 +29,16 @@ enum LogStrategy {
 #[derive(Debug, Parser)]
 struct Opts {
     /// Many server URL to connect to.
-    #[clap(default_value = "http://localhost:8000")]
+    #[clap(default_value = "http://localhost:8000", env = "MANY_SERVER")]
     server: String,
 
     /// The identity of the server (an identity string), or anonymous if you don't know it.
     #[clap(default_value_t)]
-    #[clap(long)]
+    #[clap(long, env = "MANY_SERVER_ID")]
     server_id: Address,
 
-    /// A PEM file for the identity. If not specified, anonymous will be used.
+    /// A PEM file for the identity. If not specified, the `MANY_PEM` environment
+    /// variable is used, or anonymous if it is not set.
     #[clap(long)]
     pem: Option<PathBuf>,
 
@@ -41,6 +46,10 @@ struct Opts {
     #[clap(long)]
     alt_owner: Option<Address>,
 
+    /// Compress large requests with zstd. The server must support it.
+    #[clap(long)]
+    compress: bool,
+
     /// Increase output logging verbosity to DEBUG level.
     #[clap(short, long, parse(from_occurrences))]
     verbose: i8,
@@ -74,8 +83,11 @@ enum SubCommand {
     /// Transfer ownership of a key.
     Transfer(TransferOpt),
 
-    /// List key owned by sender
+    /// List keys, optionally under a prefix.
     List(ListOpt),
+
+    /// Put a value in the store, only if the current value is the expected one.
+    Cas(CasOpt),
 }
 
 #[derive(Debug, Parser)]
@@ -90,6 +102,16 @@ struct GetOpt {
     /// Whether to output using hexadecimal, or regular value.
     #[clap(long)]
     hex: bool,
+
+    /// Request a proof of the key from the server and verify it against a
+    /// trusted root hash.
+    #[clap(long)]
+    verify_proof: bool,
+
+    /// The trusted root hash to verify the proof against, in hexadecimal. If
+    /// omitted, the application hash of the latest block is used.
+    #[clap(long, requires = "verify-proof")]
+    root: Option<String>,
 }
 
 #[derive(Debug, Parser)]
@@ -100,12 +122,23 @@ struct QueryOpt {
     /// If the key is passed as an hexadecimal string, pass this key.
     #[clap(long)]
     hex_key: bool,
+
+    /// Request a proof of the key from the server and verify it against a
+    /// trusted root hash.
+    #[clap(long)]
+    verify_proof: bool,
+
+    /// The trusted root hash to verify the proof against, in hexadecimal. If
+    /// omitted, the application hash of the latest block is used.
+    #[clap(long, requires = "verify-proof")]
+    root: Option<String>,
 }
 
 #[derive(Debug, Parser)]
 struct PutOpt {
     /// The key to set.
-    key: String,
+    #[clap(required_unless_present = "from-json")]
+    key: Option<String>,
 
     /// If the key is a hexadecimal string, pass this flag.
     #[clap(long)]
@@ -118,6 +151,38 @@ struct PutOpt {
     /// Use this flag to use STDIN to get the value.
     #[clap(long, conflicts_with = "value")]
     stdin: bool,
+
+    /// The time at which the key expires, in seconds since the UNIX epoch.
+    #[clap(long)]
+    expires_at: Option<u64>,
+
+    /// Put all the keys and values of a JSON object of strings at once,
+    /// instead of a single key.
+    #[clap(long, conflicts_with_all(&["key", "value", "stdin"]))]
+    from_json: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+struct CasOpt {
+    /// The key to set.
+    key: String,
+
+    /// If the key is a hexadecimal string, pass this flag.
+    #[clap(long)]
+    hex_key: bool,
+
+    /// The value to set.
+    value: String,
+
+    /// The expected current value. If neither this nor `--expected-hash` is
+    /// passed, the key must not exist.
+    #[clap(long)]
+    expected: Option<String>,
+
+    /// The expected hash of the current value, as returned by `query`, in
+    /// hexadecimal.
+    #[clap(long)]
+    expected_hash: Option<String>,
 }
 
 #[derive(Debug, Parser)]
@@ -149,6 +214,21 @@ struct TransferOpt {
 
 #[derive(Debug, Parser)]
 struct ListOpt {
+    /// Only list the keys starting with this prefix.
+    prefix: Option<String>,
+
+    /// The maximum number of keys to list.
+    #[clap(long)]
+    count: Option<u64>,
+
+    /// Start listing after this key, e.g. the last key of a previous page.
+    #[clap(long)]
+    start_after: Option<String>,
+
+    /// Also output the value of every key.
+    #[clap(long)]
+    values: bool,
+
     /// The order in which to list the keys
     #[clap(long)]
     order: Option<SortOrder>,
@@ -157,23 +237,89 @@ struct ListOpt {
     #[clap(long)]
     filter: Option<Vec<KeyFilterType>>,
 
-    /// Use this flag if the keys are hexadecimal
+    /// Use this flag if the keys are hexadecimal. This applies to the prefix
+    /// and `--start-after` too.
     #[clap(long)]
     hex_key: bool,
 }
 
-fn get(client: ManyClient<impl Identity>, key: &[u8], hex: bool) -> Result<(), ManyError> {
+/// The root hash to verify proofs against, either given by the user or the
+/// application hash of the latest block.
+fn trusted_root(
+    client: &ManyClient<impl Identity>,
+    root: Option<String>,
+) -> Result<Vec<u8>, ManyError> {
+    if let Some(root) = root {
+        return hex::decode(root).map_err(|e| ManyError::unknown(format!("Invalid root: {e}")));
+    }
+    let payload = client.call_("blockchain.info", ())?;
+    let info: blockchain::InfoReturns =
+        minicbor::decode(&payload).map_err(ManyError::deserialization_error)?;
+    info.app_hash
+        .ok_or_else(|| ManyError::unknown("The latest block has no application hash."))
+}
+
+/// Call a method with a proof requested if `proof` is set, and verify the
+/// proof against the trusted root. Returns the payload of the response and
+/// the values proven for the storage keys.
+fn call_with_proof<A: minicbor::Encode<()>>(
+    client: &ManyClient<impl Identity>,
+    method: &str,
+    argument: A,
+    proof: Option<Option<String>>,
+    keys: Vec<Vec<u8>>,
+) -> Result<(Vec<u8>, BTreeMap<Vec<u8>, Option<Vec<u8>>>), ManyError> {
+    let root = match proof {
+        Some(root) => trusted_root(client, root)?,
+        None => return Ok((client.call_(method, argument)?, BTreeMap::new())),
+    };
+    let response = client.call_with_attributes(method, argument, [PROOF].into_iter().collect())?;
+    let payload = response.data.clone()?;
+
+    let argument = response
+        .attributes
+        .get_attribute(PROOF.id)
+        .and_then(|attribute| attribute.arguments().first())
+        .ok_or_else(|| ManyError::unknown("The server did not return a proof."))?;
+    let bytes = minicbor::to_vec(argument).map_err(ManyError::serialization_error)?;
+    let proof: Proof = minicbor::decode(&bytes).map_err(ManyError::deserialization_error)?;
+    let values = verify_proof(proof.operations, &root, keys).map_err(|e| {
+        ManyError::unknown(format!(
+            "The proof is invalid for root {}: {e}",
+            hex::encode(&root)
+        ))
+    })?;
+    info!("The proof is valid for root {}.", hex::encode(&root));
+    Ok((payload, values))
+}
+
+fn get(
+    client: ManyClient<impl Identity>,
+    key: &[u8],
+    hex: bool,
+    proof: Option<Option<String>>,
+) -> Result<(), ManyError> {
     let arguments = kvstore::GetArgs {
         key: key.to_vec().into(),
     };
 
-    let payload = client.call_("kvstore.get", arguments)?;
+    let verify = proof.is_some();
+    let (payload, values) = call_with_proof(
+        &client,
+        "kvstore.get",
+        arguments,
+        proof,
+        vec![key_for_value(key)],
+    )?;
     if payload.is_empty() {
         Err(ManyError::unexpected_empty_response())
     } else {
         let result: kvstore::GetReturns =
             minicbor::decode(&payload).map_err(ManyError::deserialization_error)?;
         let value = result.value;
+        if verify && values.get(&key_for_value(key)) != Some(&value.as_ref().map(|v| v.to_vec())) {
+            return Err(ManyError::unknown("The proof does not match the value."));
+        }
 
         if let Some(value) = value {
             if hex {
@@ -189,17 +335,37 @@ fn get(client: ManyClient<impl Identity>, key: &[u8], hex: bool) -> Result<(), M
     }
 }
 
-fn query(client: ManyClient<impl Identity>, key: &[u8]) -> Result<(), ManyError> {
+fn query(
+    client: ManyClient<impl Identity>,
+    key: &[u8],
+    proof: Option<Option<String>>,
+) -> Result<(), ManyError> {
     let arguments = kvstore::QueryArgs {
         key: key.to_vec().into(),
     };
 
-    let payload = client.call_("kvstore.query", arguments)?;
+    let verify = proof.is_some();
+    let (payload, values) = call_with_proof(
+        &client,
+        "kvstore.query",
+        arguments,
+        proof,
+        vec![key_for_metadata(key)],
+    )?;
     if payload.is_empty() {
         Err(ManyError::unexpected_empty_response())
     } else {
         let result: kvstore::QueryReturns =
             minicbor::decode(&payload).map_err(ManyError::deserialization_error)?;
+        if verify {
+            let proven: Option<kvstore::QueryReturns> = values
+                .get(&key_for_metadata(key))
+                .and_then(Option::as_ref)
+                .and_then(|bytes| minicbor::decode(bytes).ok());
+            if proven.map(|p| p.owner) != Some(result.owner) {
+                return Err(ManyError::unknown("The proof does not match the owner."));
+            }
+        }
 
         let owner = result.owner.to_string();
 
@@ -218,11 +384,13 @@ fn put(
     alt_owner: Option<Address>,
     key: &[u8],
     value: Vec<u8>,
+    expires_at: Option<u64>,
 ) -> Result<(), ManyError> {
     let arguments = kvstore::PutArgs {
         key: key.to_vec().into(),
         value: value.into(),
         alternative_owner: alt_owner,
+        expires_at: expires_at.map(Timestamp::new).transpose()?,
     };
 
     let response = client.call("kvstore.put", arguments)?;
@@ -231,6 +399,53 @@ fn put(
     Ok(())
 }
 
+fn cas(
+    client: ManyClient<impl Identity>,
+    alt_owner: Option<Address>,
+    key: &[u8],
+    value: Vec<u8>,
+    expected_value: Option<Vec<u8>>,
+    expected_hash: Option<Vec<u8>>,
+) -> Result<(), ManyError> {
+    let arguments = kvstore::CasArgs {
+        key: key.to_vec().into(),
+        value: value.into(),
+        expected_value: expected_value.map(Into::into),
+        expected_hash: expected_hash.map(Into::into),
+        alternative_owner: alt_owner,
+    };
+
+    let response = client.call("kvstore.cas", arguments)?;
+    let payload = wait_response(client, response)?;
+    println!("{}", minicbor::display(&payload));
+    Ok(())
+}
+
+fn multi_put(
+    client: ManyClient<impl Identity>,
+    alt_owner: Option<Address>,
+    entries: Vec<(Vec<u8>, Vec<u8>)>,
+    expires_at: Option<u64>,
+) -> Result<(), ManyError> {
+    let expires_at = expires_at.map(Timestamp::new).transpose()?;
+    let arguments = kvstore::MultiPutArgs {
+        entries: entries
+            .into_iter()
+            .map(|(key, value)| kvstore::MultiPutEntry {
+                key: key.into(),
+                value: value.into(),
+                expires_at,
+            })
+            .collect(),
+        alternative_owner: alt_owner,
+    };
+
+    let response = client.call("kvstore.multiPut", arguments)?;
+    let payload = wait_response(client, response)?;
+    println!("{}", minicbor::display(&payload));
+    Ok(())
+}
+
 fn disable(
     client: ManyClient<impl Identity>,
     alt_owner: Option<Address>,
@@ -267,17 +482,7 @@ fn transfer(
     Ok(())
 }
 
-fn list(
-    client: ManyClient<impl Identity>,
-    order: Option<SortOrder>,
-    filter: Option<Vec<KeyFilterType>>,
-    hex_key: bool,
-) -> Result<(), ManyError> {
-    let args = ListArgs {
-        count: None,
-        order,
-        filter,
-    };
+fn list(client: ManyClient<impl Identity>, args: ListArgs, hex_key: bool) -> Result<(), ManyError> {
     let response = client.call("kvstore.list", args)?;
     let payload = wait_response(client, response)?;
     if payload.is_empty() {
@@ -286,15 +491,32 @@ fn list(
         let result: ListReturns =
             minicbor::decode(&payload).map_err(ManyError::deserialization_error)?;
 
-        let keys = result.keys;
-
-        for key in keys {
+        let format_key = |key: Vec<u8>| {
             if hex_key {
-                println!("{}", hex::encode(key.as_slice()));
+                Ok(hex::encode(key))
             } else {
-                let key = String::from_utf8(key.into()).map_err(ManyError::unknown)?;
-                println!("{key}");
+                String::from_utf8(key).map_err(ManyError::unknown)
             }
+        };
+
+        let mut values = result.values.map(|v| v.into_iter());
+        for key in result.keys {
+            let key = format_key(key.into())?;
+            match values.as_mut().and_then(Iterator::next) {
+                Some(Some(value)) if hex_key => {
+                    println!("{key}: {}", hex::encode(value.as_slice()))
+                }
+                Some(Some(value)) => println!("{key}: {}", String::from_utf8_lossy(&value)),
+                Some(None) => println!("{key}: disabled"),
+                None => println!("{key}"),
+            }
+        }
+
+        if let Some(next) = result.next {
+            eprintln!(
+                "More keys are available, use `--start-after {}`.",
+                format_key(next.into())?
+            );
         }
 
         Ok(())
@@ -372,6 +594,7 @@ fn main() {
         alt_owner,
         server,
         server_id,
+        compress,
         subcommand,
         verbose,
         quiet,
@@ -408,48 +631,90 @@ fn main() {
 
     debug!("{:?}", Opts::parse());
 
-    let key = pem.map_or_else(
-        || Box::new(AnonymousIdentity) as Box<dyn Identity>,
-        |p| Box::new(CoseKeyIdentity::from_pem(std::fs::read_to_string(p).unwrap()).unwrap()),
-    );
+    let key = pem
+        .or_else(|| std::env::var_os(many_cli_helpers::config::MANY_PEM_ENV).map(PathBuf::from))
+        .map_or_else(
+            || Box::new(AnonymousIdentity) as Box<dyn Identity>,
+            |p| Box::new(CoseKeyIdentity::from_pem(std::fs::read_to_string(p).unwrap()).unwrap()),
+        );
 
-    let client = ManyClient::new(server, server_id, key).unwrap();
+    let mut builder = many_client::ManyClient::builder()
+        .url(server)
+        .to(server_id)
+        .identity(key)
+        .retry_policy(RetryPolicy::default());
+    if compress {
+        builder = builder.compress_requests(ContentEncoding::Zstd);
+    }
+    let client = ManyClient::from(builder.build().unwrap());
     let result = match subcommand {
-        SubCommand::Get(GetOpt { key, hex_key, hex }) => {
+        SubCommand::Get(GetOpt {
+            key,
+            hex_key,
+            hex,
+            verify_proof,
+            root,
+        }) => {
             let key = if hex_key {
                 hex::decode(&key).unwrap()
             } else {
                 key.into_bytes()
             };
-            get(client, &key, hex)
+            get(client, &key, hex, verify_proof.then_some(root))
         }
-        SubCommand::Query(QueryOpt { key, hex_key }) => {
+        SubCommand::Query(QueryOpt {
+            key,
+            hex_key,
+            verify_proof,
+            root,
+        }) => {
             let key = if hex_key {
                 hex::decode(&key).unwrap()
             } else {
                 key.into_bytes()
             };
-            query(client, &key)
+            query(client, &key, verify_proof.then_some(root))
         }
         SubCommand::Put(PutOpt {
             key,
             hex_key,
             value,
             stdin,
+            expires_at,
+            from_json,
         }) => {
-            let key = if hex_key {
-                hex::decode(&key).unwrap()
+            if let Some(path) = from_json {
+                let content = std::fs::read_to_string(path).expect("Could not read the file");
+                let entries: BTreeMap<String, String> =
+                    serde_json::from_str(&content).expect("Invalid JSON object of strings");
+                let entries = entries
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let key = if hex_key {
+                            hex::decode(&key).unwrap()
+                        } else {
+                            key.into_bytes()
+                        };
+                        (key, value.into_bytes())
+                    })
+                    .collect();
+                multi_put(client, alt_owner, entries, expires_at)
             } else {
-                key.into_bytes()
-            };
-            let value = if stdin {
-                let mut value = Vec::new();
-                std::io::stdin().read_to_end(&mut value).unwrap();
-                value
-            } else {
-                value.expect("Must pass a value").into_bytes()
-            };
-            put(client, alt_owner, &key, value)
+                let key = key.expect("Must pass a key");
+                let key = if hex_key {
+                    hex::decode(&key).unwrap()
+                } else {
+                    key.into_bytes()
+                };
+                let value = if stdin {
+                    let mut value = Vec::new();
+                    std::io::stdin().read_to_end(&mut value).unwrap();
+                    value
+                } else {
+                    value.expect("Must pass a value").into_bytes()
+                };
+                put(client, alt_owner, &key, value, expires_at)
+            }
         }
         SubCommand::Disable(DisableOpt {
             key,
@@ -477,10 +742,53 @@ fn main() {
             transfer(client, alt_owner, key, new_owner)
         }
         SubCommand::List(ListOpt {
+            prefix,
+            count,
+            start_after,
+            values,
             order,
             filter,
             hex_key,
-        }) => list(client, order, filter, hex_key),
+        }) => {
+            let to_bytes = |key: String| {
+                if hex_key {
+                    hex::decode(key).unwrap()
+                } else {
+                    key.into_bytes()
+                }
+            };
+            let args = ListArgs {
+                count,
+                order,
+                filter,
+                prefix: prefix.map(|p| to_bytes(p).into()),
+                start_after: start_after.map(|k| to_bytes(k).into()),
+                with_values: Some(values),
+            };
+            list(client, args, hex_key)
+        }
+        SubCommand::Cas(CasOpt {
+            key,
+            hex_key,
+            value,
+            expected,
+            expected_hash,
+        }) => {
+            let key = if hex_key {
+                hex::decode(&key).unwrap()
+            } else {
+                key.into_bytes()
+            };
+            let expected_hash = expected_hash.map(|h| hex::decode(h).unwrap());
+            cas(
+                client,
+                alt_owner,
+                &key,
+                value.into_bytes(),
+                expected.map(String::into_bytes),
+                expected_hash,
+            )
+        }
     };
 
     if let Err(err) = result {
@@ -0,0 +1,111 @@
+use anyhow::anyhow;
+use clap::Parser;
+use many_cli_helpers::error::ClientServerError;
+use many_ledger_verify::genesis::verify_genesis;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::process::Command;
+use tracing::{error, info, warn};
+
+#[derive(Parser)]
+pub struct VerifyGenesisOpt {
+    /// The persistent store (rocksdb) to verify.
+    store: PathBuf,
+
+    /// The genesis state file the store was created from.
+    state: PathBuf,
+
+    /// Path to the migrations configuration the store was created with, if any.
+    #[clap(long)]
+    migrations_config: Option<PathBuf>,
+
+    /// The many-ledger binary used to replay the genesis state.
+    #[clap(long, default_value = "many-ledger")]
+    many_ledger: PathBuf,
+}
+
+/// Replay the genesis state into a fresh store with many-ledger, and compare
+/// it with the given store. Fails if they don't match.
+pub fn verify(opts: VerifyGenesisOpt) -> Result<(), ClientServerError> {
+    let VerifyGenesisOpt {
+        store,
+        state,
+        migrations_config,
+        many_ledger,
+    } = opts;
+
+    // The hash is verified against both stores below, instead of failing the
+    // replay.
+    let content = std::fs::read_to_string(&state).map_err(|e| anyhow!(e))?;
+    let mut state: Value = json5::from_str(&content).map_err(|e| anyhow!("{e}"))?;
+    let state_hash = match state.as_object_mut().and_then(|s| s.remove("hash")) {
+        Some(Value::String(h)) => {
+            Some(hex::decode(h).map_err(|e| anyhow!("Invalid state file hash: {e}"))?)
+        }
+        Some(Value::Null) | None => None,
+        Some(h) => return Err(anyhow!("Invalid state file hash: {h}").into()),
+    };
+
+    let scratch = tempfile::tempdir().map_err(|e| anyhow!(e))?;
+    let replayed_state = scratch.path().join("state.json");
+    let replayed_store = scratch.path().join("store");
+    std::fs::write(&replayed_state, state.to_string()).map_err(|e| anyhow!(e))?;
+
+    let mut command = Command::new(&many_ledger);
+    command
+        .arg("--genesis-only")
+        .arg("--state")
+        .arg(&replayed_state)
+        .arg("--persistent")
+        .arg(&replayed_store);
+    if let Some(path) = migrations_config {
+        command.arg("--migrations-config").arg(path);
+    }
+    let status = command
+        .status()
+        .map_err(|e| anyhow!("Could not run {}: {e}", many_ledger.display()))?;
+    if !status.success() {
+        return Err(anyhow!("Could not replay the genesis state: {status}").into());
+    }
+
+    let result = verify_genesis(store, replayed_store, state_hash)?;
+    if result.store_height > 0 && result.recorded_hash.is_none() {
+        warn!(
+            "The store is at height {} and does not record its genesis hash; it will only match the genesis at height 0.",
+            result.store_height
+        );
+    }
+
+    info!(
+        "Replayed genesis hash: {}",
+        hex::encode(&result.replayed_hash)
+    );
+    if let Some(h) = &result.state_hash {
+        info!("State file hash:       {}", hex::encode(h));
+    }
+    if let Some(h) = &result.recorded_hash {
+        info!("Store genesis hash:    {}", hex::encode(h));
+    }
+    info!("Store hash:            {}", hex::encode(&result.store_hash));
+
+    if result.is_valid() {
+        println!("Store matches the genesis state.");
+        return Ok(());
+    }
+
+    if result
+        .state_hash
+        .as_ref()
+        .map_or(false, |h| *h != result.replayed_hash)
+    {
+        error!("The state file hash does not match its content.");
+    }
+    println!(
+        "{} key(s) differ ('-' genesis only, '+' store only, '~' changed):",
+        result.diff.len()
+    );
+    for diff in &result.diff {
+        println!("{diff}");
+    }
+    Err(anyhow!("The store does not match the genesis state.").into())
+}
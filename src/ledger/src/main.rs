@@ -2,14 +2,22 @@ use anyhow::anyhow;
 use clap::{ArgGroup, Parser};
 use many_cli_helpers::error::ClientServerError;
 use many_client::client::blocking::ManyClient;
+use many_client::client::RetryPolicy;
 use many_identity::{Address, AnonymousIdentity, Identity};
 use many_identity_dsa::CoseKeyIdentity;
-use many_identity_hsm::{Hsm, HsmIdentity, HsmMechanismType, HsmSessionType, HsmUserType};
+use many_identity_hsm::{
+    parse_mechanism, Hsm, HsmIdentity, HsmMechanismType, HsmSessionType, HsmUserType,
+};
+use many_identity_ledger_hw::{DerivationPath, LedgerHwDevice, LedgerHwIdentity};
+use many_ledger_verify::proof::verify_balances;
 use many_modules::r#async::{StatusArgs, StatusReturn};
-use many_modules::{ledger, r#async};
+use many_modules::{blockchain, ledger, r#async};
 use many_protocol::ResponseMessage;
+use many_types::attributes::AttributeSet;
 use many_types::ledger::{Symbol, TokenAmount};
+use many_types::proof::Proof;
 use many_types::Memo;
+use many_types::PROOF;
 use minicbor::data::Tag;
 use minicbor::encode::{Error, Write};
 use minicbor::{Decoder, Encoder};
@@ -21,6 +29,7 @@ use std::str::FromStr;
 use std::time::Duration;
 use tracing::{debug, error, info, trace};
 
+mod genesis;
 mod multisig;
 mod tokens;
 
@@ -64,15 +73,16 @@ struct Opts {
     common_flags: many_cli_helpers::CommonCliFlags,
 
     /// Many server URL to connect to.
-    #[clap(default_value = "http://localhost:8000")]
+    #[clap(default_value = "http://localhost:8000", env = "MANY_SERVER")]
     server: String,
 
     /// The identity of the server (an identity string), or anonymous if you don't know it.
     #[clap(default_value_t)]
-    #[clap(long)]
+    #[clap(long, env = "MANY_SERVER_ID")]
     server_id: Address,
 
-    /// A PEM file for the identity. If not specified, anonymous will be used.
+    /// A PEM file for the identity. If not specified, the `MANY_PEM` environment
+    /// variable is used, or anonymous if it is not set.
     #[clap(long)]
     pem: Option<PathBuf>,
 
@@ -88,6 +98,18 @@ struct Opts {
     #[clap(long, conflicts_with("pem"))]
     keyid: Option<String>,
 
+    /// HSM PKCS#11 mechanism of the key, `ecdsa` or `eddsa`.
+    #[clap(long, default_value = "ecdsa", value_parser = parse_mechanism)]
+    mechanism: HsmMechanismType,
+
+    /// Use a key on a Ledger device for the identity.
+    #[clap(long, conflicts_with_all(&["pem", "module"]))]
+    ledger_hw: bool,
+
+    /// The derivation path of the key on the Ledger device.
+    #[clap(long, requires("ledger-hw"), default_value_t)]
+    ledger_hw_path: DerivationPath,
+
     #[clap(subcommand)]
     subcommand: SubCommand,
 }
@@ -105,6 +127,10 @@ enum SubCommand {
 
     /// Perform a token operation
     Token(tokens::CommandOpt),
+
+    /// Verify that a persistent store matches a genesis state file, and show
+    /// the differences if it does not. This does not connect to the server.
+    VerifyGenesis(genesis::VerifyGenesisOpt),
 }
 
 #[derive(Parser)]
@@ -118,6 +144,31 @@ struct BalanceOpt {
     /// additional call will be made to retrieve local names.
     #[clap(last = true)]
     symbols: Vec<String>,
+
+    /// Request a proof of the balances from the server and verify it against
+    /// a trusted root hash.
+    #[clap(long)]
+    proof: bool,
+
+    /// The trusted root hash to verify the proof against, in hexadecimal. If
+    /// omitted, the application hash of the latest block is used.
+    #[clap(long, requires = "proof")]
+    expected_root: Option<String>,
+
+    /// A server to fetch the latest block from, using `blockchain.info`, if
+    /// it should not be the server returning the balances.
+    #[clap(long, requires = "proof", conflicts_with = "expected-root")]
+    root_server: Option<String>,
+}
+
+/// Where to get the root hash a proof is verified against.
+enum TrustedRoot {
+    /// A root hash given by the user, in hexadecimal.
+    Expected(String),
+
+    /// The application hash of the latest block, from another server if
+    /// given.
+    LatestBlock(Option<String>),
 }
 
 #[derive(Parser)]
@@ -161,10 +212,67 @@ pub fn resolve_symbol(
     }
 }
 
+/// Returns the root hash, with the height of the block it was read from if it
+/// was read from the chain.
+fn trusted_root(
+    client: &ManyClient<impl Identity>,
+    root: &TrustedRoot,
+) -> Result<(Option<u64>, Vec<u8>), ClientServerError> {
+    let payload = match root {
+        TrustedRoot::Expected(hash) => {
+            return hex::decode(hash)
+                .map(|hash| (None, hash))
+                .map_err(|e| anyhow!("Unable to decode the expected root hash: {e}").into())
+        }
+        TrustedRoot::LatestBlock(None) => client.call_("blockchain.info", ())?,
+        TrustedRoot::LatestBlock(Some(server)) => {
+            ManyClient::new(server, Address::anonymous(), AnonymousIdentity)
+                .map_err(|e| anyhow!(e))?
+                .call_("blockchain.info", ())?
+        }
+    };
+    let info: blockchain::InfoReturns = minicbor::decode(&payload)?;
+    info.app_hash
+        .map(|hash| (Some(info.latest_block.height), hash))
+        .ok_or_else(|| anyhow!("The latest block has no application hash.").into())
+}
+
+/// Verify the proof attached to a `ledger.balance` response against a trusted
+/// root hash, and check that it proves the balances returned for `symbols`.
+fn verify_balance_proof(
+    response: &ResponseMessage,
+    root: &[u8],
+    account: &Address,
+    symbols: &[Symbol],
+    balances: &BTreeMap<Symbol, TokenAmount>,
+) -> anyhow::Result<()> {
+    let attribute = response
+        .attributes
+        .get_attribute(PROOF.id)
+        .ok_or_else(|| anyhow!("The server did not return a proof."))?;
+    let argument = attribute
+        .arguments()
+        .first()
+        .ok_or_else(|| anyhow!("The proof attribute is empty."))?;
+    let bytes = minicbor::to_vec(argument).map_err(|e| anyhow!("{e}"))?;
+    let proof: Proof = minicbor::decode(&bytes)?;
+
+    verify_balances(
+        proof.operations,
+        root,
+        account,
+        symbols.iter().copied(),
+        balances,
+    )
+    .map_err(|e| anyhow!("{e}"))
+}
+
 fn balance(
     client: ManyClient<impl Identity>,
+    address: Address,
     account: Option<Address>,
     symbols: Vec<String>,
+    proof: Option<TrustedRoot>,
 ) -> Result<(), ClientServerError> {
     // Get info.
     let info: ledger::InfoReturns = minicbor::decode(&client.call_("ledger.info", ())?)?;
@@ -174,42 +282,89 @@ fn balance(
         .map(|(x, y)| (y.clone(), *x))
         .collect();
 
+    let mut symbols = symbols
+        .iter()
+        .map(|x| {
+            if let Ok(i) = Address::from_str(x) {
+                Ok(i)
+            } else if let Some(i) = local_names.get(x.as_str()) {
+                Ok(*i)
+            } else {
+                Err(anyhow!("Could not resolve symbol '{x}'"))
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if symbols.is_empty() && proof.is_some() {
+        // Request every symbol, so the symbols the account does not hold are
+        // proven absent too.
+        symbols = info.symbols.clone();
+    }
+
     let argument = ledger::BalanceArgs {
         account,
         symbols: if symbols.is_empty() {
             None
         } else {
-            Some(
-                symbols
-                    .iter()
-                    .map(|x| {
-                        if let Ok(i) = Address::from_str(x) {
-                            Ok(i)
-                        } else if let Some(i) = local_names.get(x.as_str()) {
-                            Ok(*i)
-                        } else {
-                            Err(anyhow!("Could not resolve symbol '{x}'"))
-                        }
-                    })
-                    .collect::<Result<Vec<_>, _>>()?
-                    .into(),
-            )
+            Some(symbols.clone().into())
         },
     };
-    let payload = client.call_("ledger.balance", argument)?;
+    let call = || {
+        client.call_with_attributes(
+            "ledger.balance",
+            argument.clone(),
+            if proof.is_some() {
+                vec![PROOF].into_iter().collect()
+            } else {
+                AttributeSet::new()
+            },
+        )
+    };
+    // The latest block is read before and after the query, so the proof and
+    // the root are of the same block. The query is retried once if a block
+    // was committed in between.
+    let (response, root) = match &proof {
+        None => (call()?, None),
+        Some(trusted) => {
+            let mut retried = false;
+            loop {
+                let (before, _) = trusted_root(&client, trusted)?;
+                let response = call()?;
+                let (after, root) = trusted_root(&client, trusted)?;
+                if before == after {
+                    break (response, Some(root));
+                }
+                if retried {
+                    return Err(anyhow!(
+                        "New blocks were committed during the query. Please try again."
+                    )
+                    .into());
+                }
+                retried = true;
+            }
+        }
+    };
+    let payload = response.data.clone()?;
 
     if payload.is_empty() {
         Err(anyhow!("Unexpected empty response.").into())
     } else {
         let balance: ledger::BalanceReturns = minicbor::decode(&payload).unwrap();
-        for (symbol, amount) in balance.balances {
-            if let Some(symbol_name) = info.local_names.get(&symbol) {
+        for (symbol, amount) in &balance.balances {
+            if let Some(symbol_name) = info.local_names.get(symbol) {
                 println!("{amount:>12} {symbol_name} ({symbol})");
             } else {
                 println!("{amount:>12} {symbol}");
             }
         }
 
+        if let Some(root) = root {
+            let account = account.unwrap_or(address);
+            verify_balance_proof(&response, &root, &account, &symbols, &balance.balances).map_err(
+                |e| anyhow!("The proof is invalid for root {}: {e}", hex::encode(&root)),
+            )?;
+            println!("The proof is valid for root {}.", hex::encode(&root));
+        }
+
         Ok(())
     }
 }
@@ -307,6 +462,9 @@ fn main() {
         module,
         slot,
         keyid,
+        mechanism,
+        ledger_hw,
+        ledger_hw_path,
         server,
         server_id,
         subcommand,
@@ -333,22 +491,43 @@ fn main() {
         }
 
         trace!("Creating CoseKeyIdentity");
-        // Only ECDSA is supported at the moment. It should be easy to add support for new EC mechanisms
+        Box::new(HsmIdentity::new(mechanism).expect("Unable to create CoseKeyIdentity from HSM"))
+    } else if ledger_hw {
+        let device = LedgerHwDevice::connect().expect("Unable to connect to the Ledger device");
         Box::new(
-            HsmIdentity::new(HsmMechanismType::ECDSA)
-                .expect("Unable to create CoseKeyIdentity from HSM"),
+            LedgerHwIdentity::new(device, ledger_hw_path, false)
+                .expect("Unable to create identity from the Ledger device"),
         )
     } else {
-        pem.map_or_else(
-            || Box::new(AnonymousIdentity) as Box<dyn Identity>,
-            |p| Box::new(CoseKeyIdentity::from_pem(std::fs::read_to_string(p).unwrap()).unwrap()),
-        )
+        pem.or_else(|| std::env::var_os(many_cli_helpers::config::MANY_PEM_ENV).map(PathBuf::from))
+            .map_or_else(
+                || Box::new(AnonymousIdentity) as Box<dyn Identity>,
+                |p| {
+                    Box::new(
+                        CoseKeyIdentity::from_pem(std::fs::read_to_string(p).unwrap()).unwrap(),
+                    )
+                },
+            )
     };
 
     let client_address = key.address();
-    let client = ManyClient::new(server, server_id, key).unwrap();
+    let client = ManyClient::from(
+        many_client::ManyClient::builder()
+            .url(server)
+            .to(server_id)
+            .identity(key)
+            .retry_policy(RetryPolicy::default())
+            .build()
+            .unwrap(),
+    );
     let result = match subcommand {
-        SubCommand::Balance(BalanceOpt { identity, symbols }) => {
+        SubCommand::Balance(BalanceOpt {
+            identity,
+            symbols,
+            proof,
+            expected_root,
+            root_server,
+        }) => {
             let identity = identity.map(|identity| {
                 Address::from_str(&identity)
                     .or_else(|_| {
@@ -360,7 +539,12 @@ fn main() {
                     .expect("Unable to decode identity command-line argument")
             });
 
-            balance(client, identity, symbols)
+            let proof = proof.then(|| match expected_root {
+                Some(root) => TrustedRoot::Expected(root),
+                None => TrustedRoot::LatestBlock(root_server),
+            });
+
+            balance(client, client_address, identity, symbols, proof)
         }
         SubCommand::Send(TargetCommandOpt {
             account,
@@ -381,6 +565,7 @@ fn main() {
         }
         SubCommand::Multisig(opts) => multisig::multisig(client, opts),
         SubCommand::Token(opts) => tokens::tokens(client, opts),
+        SubCommand::VerifyGenesis(opts) => genesis::verify(opts),
     };
 
     if let Err(err) = result {
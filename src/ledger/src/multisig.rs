@@ -51,10 +51,10 @@ enum SubcommandOpt {
     },
 
     /// Approve a transaction.
-    Approve(TransactionOpt),
+    Approve(ApproveOpt),
 
     /// Revoke approval of a transaction.
-    Revoke(TransactionOpt),
+    Revoke(RevokeOpt),
 
     /// Execute a transaction.
     Execute(TransactionOpt),
@@ -62,6 +62,9 @@ enum SubcommandOpt {
     /// Show the information of a multisig transaction.
     Info(TransactionOpt),
 
+    /// Show the response of an executed multisig transaction.
+    Result(TransactionOpt),
+
     /// Set new defaults for the multisig account.
     SetDefaults(SetDefaultsOpt),
 }
@@ -86,6 +89,26 @@ struct TransactionOpt {
     token: ByteVec,
 }
 
+#[derive(Parser)]
+struct ApproveOpt {
+    #[clap(flatten)]
+    transaction: TransactionOpt,
+
+    /// Approve on behalf of an account that is a member of the multisig account.
+    #[clap(long = "as")]
+    approve_as: Option<Address>,
+}
+
+#[derive(Parser)]
+struct RevokeOpt {
+    #[clap(flatten)]
+    transaction: TransactionOpt,
+
+    /// Revoke the approval made on behalf of a member account.
+    #[clap(long = "as")]
+    revoke_as: Option<Address>,
+}
+
 #[derive(Parser)]
 struct MultisigArgOpt {
     /// The number of approvals needed to execute a transaction.
@@ -213,11 +236,11 @@ fn submit(
     }
 }
 
-fn approve(
-    client: ManyClient<impl Identity>,
-    opts: TransactionOpt,
-) -> Result<(), ClientServerError> {
-    let arguments = multisig::ApproveArgs { token: opts.token };
+fn approve(client: ManyClient<impl Identity>, opts: ApproveOpt) -> Result<(), ClientServerError> {
+    let arguments = multisig::ApproveArgs {
+        token: opts.transaction.token,
+        approve_as: opts.approve_as,
+    };
     let response = client.call("account.multisigApprove", arguments)?;
 
     let payload = crate::wait_response(client, response)?;
@@ -228,11 +251,11 @@ fn approve(
     Ok(())
 }
 
-fn revoke(
-    client: ManyClient<impl Identity>,
-    opts: TransactionOpt,
-) -> Result<(), ClientServerError> {
-    let arguments = multisig::RevokeArgs { token: opts.token };
+fn revoke(client: ManyClient<impl Identity>, opts: RevokeOpt) -> Result<(), ClientServerError> {
+    let arguments = multisig::RevokeArgs {
+        token: opts.transaction.token,
+        revoke_as: opts.revoke_as,
+    };
     let response = client.call("account.multisigRevoke", arguments)?;
 
     let payload = crate::wait_response(client, response)?;
@@ -269,6 +292,21 @@ fn info(client: ManyClient<impl Identity>, opts: TransactionOpt) -> Result<(), C
     Ok(())
 }
 
+fn result(
+    client: ManyClient<impl Identity>,
+    opts: TransactionOpt,
+) -> Result<(), ClientServerError> {
+    let arguments = multisig::ResultArgs { token: opts.token };
+    let response = client.call("account.multisigResult", arguments)?;
+
+    let payload = crate::wait_response(client, response)?;
+    let result: multisig::ResultReturn = minicbor::decode(&payload)?;
+
+    info!("State: {:?}", result.state);
+    println!("{}", minicbor::display(&result.response.data?));
+    Ok(())
+}
+
 fn set_defaults(
     client: ManyClient<impl Identity>,
     account: Address,
@@ -305,6 +343,7 @@ pub fn multisig(
         SubcommandOpt::Revoke(sub_opts) => revoke(client, sub_opts),
         SubcommandOpt::Execute(sub_opts) => execute(client, sub_opts),
         SubcommandOpt::Info(sub_opts) => info(client, sub_opts),
+        SubcommandOpt::Result(sub_opts) => result(client, sub_opts),
         SubcommandOpt::SetDefaults(SetDefaultsOpt {
             target_account,
             opts,
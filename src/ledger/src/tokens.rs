@@ -3,7 +3,10 @@ use clap::{Args, Parser};
 use many_cli_helpers::error::ClientServerError;
 use many_client::client::blocking::ManyClient;
 use many_identity::{Address, Identity};
-use many_modules::ledger::extended_info::visual_logo::VisualTokenLogo;
+use many_modules::ledger::extended_info::attachment::{Attachment, Attachments};
+use many_modules::ledger::extended_info::visual_logo::{
+    LogoPurpose, SingleVisualTokenLogo, VisualTokenLogo,
+};
 use many_modules::ledger::extended_info::TokenExtendedInfo;
 use many_modules::ledger::{
     TokenAddExtendedInfoArgs, TokenAddExtendedInfoReturns, TokenBurnArgs, TokenBurnReturns,
@@ -13,8 +16,8 @@ use many_modules::ledger::{
 };
 use many_types::cbor::CborNull;
 use many_types::ledger::{LedgerTokensAddressMap, TokenAmount, TokenInfoSummary, TokenMaybeOwner};
-use many_types::{AttributeRelatedIndex, Memo};
-use std::collections::BTreeMap;
+use many_types::{AttributeRelatedIndex, Memo, MemoSection};
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -38,6 +41,9 @@ enum SubcommandOpt {
     /// Remove extended information from token
     RemoveExtInfo(RemoveExtInfoOpt),
 
+    /// Show or change the extended information of a token
+    ExtInfo(ExtInfoOpt),
+
     /// Get token info
     Info(InfoOpt),
 
@@ -141,6 +147,7 @@ struct UpdateTokenOpt {
 enum CreateExtInfoOpt {
     Memo(MemoOpt),
     Logo(LogoOpt),
+    Attachment(AttachmentOpt),
 }
 
 #[derive(Parser)]
@@ -169,6 +176,27 @@ struct UnicodeLogoOpt {
 #[derive(Parser)]
 struct ImageLogoOpt {
     image: PathBuf,
+
+    /// Where the image should be used (light, dark or small). Can be repeated.
+    #[clap(long = "purpose")]
+    purposes: Vec<LogoPurpose>,
+}
+
+#[derive(Parser)]
+struct AttachmentOpt {
+    /// The index of the attachment. An attachment at the same index is
+    /// replaced.
+    index: u32,
+
+    file: PathBuf,
+
+    /// Only store the hash of the file on the ledger.
+    #[clap(long)]
+    reference: bool,
+
+    /// Where the file can be found.
+    #[clap(long)]
+    url: Option<String>,
 }
 
 #[derive(Parser)]
@@ -195,6 +223,62 @@ struct RemoveExtInfoOpt {
     memo: Option<Memo>,
 }
 
+#[derive(Parser)]
+struct ExtInfoOpt {
+    #[clap(subcommand)]
+    subcommand: ExtInfoSubcommandOpt,
+}
+
+#[derive(Parser)]
+enum ExtInfoSubcommandOpt {
+    /// Show the extended information of a token
+    Get(ExtInfoGetOpt),
+
+    /// Set the memo of a token, replacing the current one
+    SetMemo(SetMemoOpt),
+
+    /// Set the logo of a token, replacing the current one
+    SetLogo(SetLogoOpt),
+}
+
+#[derive(Parser)]
+struct ExtInfoGetOpt {
+    symbol: Address,
+}
+
+#[derive(Parser)]
+struct SetMemoOpt {
+    symbol: Address,
+
+    /// The new memo of the token.
+    #[clap(parse(try_from_str = Memo::try_from))]
+    value: Memo,
+
+    /// Submit without showing the changes and asking for confirmation.
+    #[clap(long, short)]
+    yes: bool,
+
+    #[clap(long)]
+    #[clap(parse(try_from_str = Memo::try_from))]
+    memo: Option<Memo>,
+}
+
+#[derive(Parser)]
+struct SetLogoOpt {
+    symbol: Address,
+
+    #[clap(subcommand)]
+    logo_type: CreateLogoOpt,
+
+    /// Submit without showing the changes and asking for confirmation.
+    #[clap(long, short)]
+    yes: bool,
+
+    #[clap(long)]
+    #[clap(parse(try_from_str = Memo::try_from))]
+    memo: Option<Memo>,
+}
+
 /// Create `TokenMaybeOwner` from CLI `str`
 fn token_maybe_owner(s: &str) -> Result<TokenMaybeOwner, String> {
     match s {
@@ -206,31 +290,214 @@ fn token_maybe_owner(s: &str) -> Result<TokenMaybeOwner, String> {
 }
 
 fn attribute_related_index(s: &str) -> Result<AttributeRelatedIndex, String> {
-    Ok(AttributeRelatedIndex::new(
-        s.parse::<u32>().map_err(|e| e.to_string())?,
-    ))
+    s.parse::<AttributeRelatedIndex>()
+        .map_err(|e| e.to_string())
+}
+
+fn create_logo(opts: CreateLogoOpt) -> VisualTokenLogo {
+    let mut logo = VisualTokenLogo::new();
+    match opts {
+        CreateLogoOpt::Unicode(opts) => {
+            logo.unicode_front(opts.glyph);
+        }
+        CreateLogoOpt::Image(opts) => {
+            let content_type = mime_guess::from_path(&opts.image)
+                .first_raw()
+                .expect("Unable to guess the MIME type of image");
+            let binary = std::fs::read(opts.image).expect("Unable to read image");
+            logo.push_front(
+                SingleVisualTokenLogo::image(content_type, binary).with_purposes(opts.purposes),
+            );
+        }
+    }
+    logo
 }
 
 fn create_ext_info(opts: CreateExtInfoOpt) -> TokenExtendedInfo {
     match opts {
         CreateExtInfoOpt::Memo(opts) => TokenExtendedInfo::new().with_memo(opts.memo).unwrap(),
-        CreateExtInfoOpt::Logo(opts) => {
-            let mut logo = VisualTokenLogo::new();
-            match opts.logo_type {
-                CreateLogoOpt::Unicode(opts) => {
-                    logo.unicode_front(opts.glyph);
-                }
-                CreateLogoOpt::Image(opts) => {
-                    let content_type = mime_guess::from_path(&opts.image)
-                        .first_raw()
-                        .expect("Unable to guess the MIME type of image");
-                    let binary = std::fs::read(opts.image).expect("Unable to read image");
-                    logo.image_front(content_type, binary);
+        CreateExtInfoOpt::Logo(opts) => TokenExtendedInfo::new()
+            .with_visual_logo(create_logo(opts.logo_type))
+            .unwrap(),
+        CreateExtInfoOpt::Attachment(opts) => {
+            let content_type = mime_guess::from_path(&opts.file).first_or_octet_stream();
+            let data = std::fs::read(opts.file).expect("Unable to read attachment");
+            let mut attachment = if opts.reference {
+                Attachment::reference(content_type, Attachment::hash_of(&data), None)
+            } else {
+                Attachment::inline(content_type, data)
+            };
+            attachment.url = opts.url;
+            TokenExtendedInfo::new()
+                .with_attachment(opts.index, attachment)
+                .unwrap()
+        }
+    }
+}
+
+fn describe_memo(memo: Option<&Memo>) -> String {
+    match memo {
+        None => "(none)".to_string(),
+        Some(memo) => memo
+            .iter()
+            .map(|section| match section {
+                MemoSection::String(s) => format!("{s:?}"),
+                MemoSection::Bytes(b) => format!("h'{}'", hex::encode(b)),
+                MemoSection::Typed { content_type, data } => {
+                    format!("{content_type} ({} bytes)", data.len())
                 }
-            }
-            TokenExtendedInfo::new().with_visual_logo(logo).unwrap()
+            })
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+fn describe_logo(logo: Option<&VisualTokenLogo>) -> String {
+    let describe_purposes = |purposes: &BTreeSet<LogoPurpose>| {
+        if purposes.is_empty() {
+            String::new()
+        } else {
+            format!(", purposes: {purposes:?}")
         }
+    };
+
+    match logo {
+        None => "(none)".to_string(),
+        Some(logo) => logo
+            .iter()
+            .map(|single| match single {
+                SingleVisualTokenLogo::UnicodeChar(c) => format!("unicode {c:?}"),
+                SingleVisualTokenLogo::Image {
+                    content_type,
+                    binary,
+                    purposes,
+                } => format!(
+                    "image ({content_type}, {} bytes{})",
+                    binary.len(),
+                    describe_purposes(purposes)
+                ),
+                SingleVisualTokenLogo::ImageRef {
+                    content_type,
+                    hash,
+                    uri,
+                    purposes,
+                } => format!(
+                    "image reference ({content_type}, hash {}{}{})",
+                    hex::encode(hash),
+                    uri.as_ref().map_or(String::new(), |uri| format!(", {uri}")),
+                    describe_purposes(purposes)
+                ),
+            })
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+fn describe_attachments(attachments: Option<&Attachments>) -> String {
+    match attachments {
+        None => "(none)".to_string(),
+        Some(attachments) => attachments
+            .iter()
+            .map(|(index, attachment)| {
+                format!(
+                    "{index}: {} ({}, hash {}{})",
+                    attachment
+                        .data
+                        .as_ref()
+                        .map_or("reference".to_string(), |d| format!("{} bytes", d.len())),
+                    attachment.content_type,
+                    hex::encode(attachment.hash.as_slice()),
+                    attachment
+                        .url
+                        .as_ref()
+                        .map_or(String::new(), |url| format!(", {url}")),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+/// Show the change to the user and ask for confirmation, unless `yes` is set.
+fn confirm_change(name: &str, current: String, new: String, yes: bool) -> bool {
+    println!("{name}:");
+    println!("- {current}");
+    println!("+ {new}");
+    if yes {
+        return true;
     }
+
+    print!("Submit this change? [y/N] ");
+    std::io::Write::flush(&mut std::io::stdout()).expect("Unable to flush stdout");
+    let mut answer = String::new();
+    std::io::stdin()
+        .read_line(&mut answer)
+        .expect("Unable to read the answer");
+    matches!(answer.trim(), "y" | "Y" | "yes")
+}
+
+fn get_ext_info(
+    client: &ManyClient<impl Identity>,
+    symbol: Address,
+) -> Result<TokenExtendedInfo, ClientServerError> {
+    let args = TokenInfoArgs {
+        symbol,
+        extended_info: None,
+    };
+    let result: TokenInfoReturns = minicbor::decode(&client.call_("tokens.info", args)?)?;
+    Ok(result.extended_info)
+}
+
+fn ext_info(client: ManyClient<impl Identity>, opts: ExtInfoOpt) -> Result<(), ClientServerError> {
+    let (symbol, extended_info, memo) = match opts.subcommand {
+        ExtInfoSubcommandOpt::Get(opts) => {
+            let current = get_ext_info(&client, opts.symbol)?;
+            println!("Memo: {}", describe_memo(current.memo()));
+            println!("Logo: {}", describe_logo(current.visual_logo()));
+            println!(
+                "Attachments: {}",
+                describe_attachments(current.attachments())
+            );
+            return Ok(());
+        }
+        ExtInfoSubcommandOpt::SetMemo(opts) => {
+            let current = get_ext_info(&client, opts.symbol)?;
+            if !confirm_change(
+                "Memo",
+                describe_memo(current.memo()),
+                describe_memo(Some(&opts.value)),
+                opts.yes,
+            ) {
+                return Ok(());
+            }
+            let extended_info = TokenExtendedInfo::new().with_memo(opts.value)?;
+            (opts.symbol, extended_info, opts.memo)
+        }
+        ExtInfoSubcommandOpt::SetLogo(opts) => {
+            let current = get_ext_info(&client, opts.symbol)?;
+            let logo = create_logo(opts.logo_type);
+            if !confirm_change(
+                "Logo",
+                describe_logo(current.visual_logo()),
+                describe_logo(Some(&logo)),
+                opts.yes,
+            ) {
+                return Ok(());
+            }
+            let extended_info = TokenExtendedInfo::new().with_visual_logo(logo)?;
+            (opts.symbol, extended_info, opts.memo)
+        }
+    };
+
+    let args = TokenAddExtendedInfoArgs {
+        symbol,
+        extended_info,
+        memo,
+    };
+    let response = client.call("tokens.addExtendedInfo", args)?;
+    let payload = crate::wait_response(client, response)?;
+    let _result: TokenAddExtendedInfoReturns = minicbor::decode(&payload)?;
+    Ok(())
 }
 
 fn create_token(
@@ -389,6 +656,7 @@ pub fn tokens(
         SubcommandOpt::Update(opts) => update_token(client, opts),
         SubcommandOpt::AddExtInfo(opts) => add_ext_info(client, opts),
         SubcommandOpt::RemoveExtInfo(opts) => remove_ext_info(client, opts),
+        SubcommandOpt::ExtInfo(opts) => ext_info(client, opts),
         SubcommandOpt::Info(opts) => info_token(client, opts),
         SubcommandOpt::Mint(opts) => mint_token(client, opts),
         SubcommandOpt::Burn(opts) => burn_token(client, opts),
@@ -5,11 +5,18 @@ use many_client::client::blocking::{block_on, ManyClient};
 use many_error::{ManyError, ManyErrorCode};
 use many_identity::{Address, AnonymousIdentity};
 use many_migration::MigrationConfig;
-use many_modules::abci_backend::{AbciBlock, AbciCommitInfo, AbciInfo};
+use many_modules::abci_backend::{
+    AbciApplySnapshotChunk, AbciApplySnapshotChunkResult, AbciApplySnapshotChunkReturn, AbciBlock,
+    AbciCheckTx, AbciCheckTxPriority, AbciCommitInfo, AbciInfo, AbciListSnapshots,
+    AbciLoadSnapshotChunk, AbciOfferSnapshot, AbciOfferSnapshotResult, AbciOfferSnapshotReturn,
+    AbciSnapshot, AbciSnapshotChunk,
+};
 use many_protocol::{RequestMessage, ResponseMessage};
-use many_server::RequestValidator;
+use many_server::server::RejectionLog;
+use many_server::{MissingChainId, RequestValidator};
 use reqwest::{IntoUrl, Url};
-use std::sync::{Arc, RwLock};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, RwLock};
 use tendermint_abci::Application;
 use tendermint_proto::abci::*;
 use tracing::{debug, error};
@@ -35,6 +42,7 @@ enum ManyAbciCheckErrorCodes {
     CannotGetSystemTimeError = 8,
     TimestampOutsideOfRangeError = 9,
     ValidationError = 10,
+    ChainIdError = 11,
 }
 
 enum ManyAbciDeliverErrorCodes {
@@ -48,6 +56,16 @@ enum ManyAbciDeliverErrorCodes {
 
 pub const MANYABCI_DEFAULT_TIMEOUT: u64 = 300;
 
+fn call_backend<R: for<'b> minicbor::Decode<'b, ()>>(
+    client: &ManyClient<AnonymousIdentity>,
+    method: &str,
+    argument: impl minicbor::Encode<()>,
+) -> Result<R, ManyError> {
+    client
+        .call_(method, argument)
+        .and_then(|payload| minicbor::decode(&payload).map_err(ManyError::deserialization_error))
+}
+
 fn get_abci_info_(client: &ManyClient<AnonymousIdentity>) -> Result<AbciInfo, ManyError> {
     client
         .call_("abci.info", ())
@@ -60,6 +78,10 @@ pub struct AbciApp {
     many_client: ManyClient<AnonymousIdentity>,
     many_url: Url,
     cache: Arc<RwLock<dyn RequestValidator + Send + Sync>>,
+    rejections: Option<Arc<Mutex<RejectionLog>>>,
+    endpoint_priorities: BTreeMap<String, i64>,
+    chain_id: Option<String>,
+    missing_chain_id: MissingChainId,
 
     /// We need interior mutability, safely.
     migrations: Arc<RwLock<AbciAppMigrations>>,
@@ -88,6 +110,7 @@ impl AbciApp {
         let many_client = ManyClient::new(many_url.clone(), server_id, AnonymousIdentity)?;
         let status = many_client.status().map_err(|x| x.to_string())?;
         let app_name = status.name;
+        let chain_id = status.chain_id;
 
         let migrations = RwLock::new({
             let AbciInfo { height, .. } = get_abci_info_(&many_client)
@@ -107,17 +130,95 @@ impl AbciApp {
             many_url,
             many_client,
             cache: Arc::new(RwLock::new(())),
+            rejections: None,
+            endpoint_priorities: BTreeMap::new(),
+            chain_id,
+            missing_chain_id: MissingChainId::Refuse,
             migrations: Arc::new(migrations),
             block_time: Arc::new(RwLock::new(None)),
         })
     }
 
+    /// Returns the time of the current block, or the system time before the
+    /// first block. The request cache uses it to expire requests along with
+    /// the time checked by `check_tx`, rather than the clock of the node.
+    pub fn block_time_fn(
+        &self,
+    ) -> impl Fn() -> Result<std::time::SystemTime, ManyError> + Send + Sync + 'static {
+        let block_time = self.block_time.clone();
+        move || {
+            let time = *block_time
+                .read()
+                .map_err(|e| ManyError::unknown(e.to_string()))?;
+            Ok(time.map_or_else(std::time::SystemTime::now, |secs| {
+                std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs)
+            }))
+        }
+    }
+
     pub fn with_validator<C: RequestValidator + Send + Sync + 'static>(mut self, cache: C) -> Self {
         self.cache = Arc::new(RwLock::new(cache));
         self
     }
 
-    fn do_check_tx(&self, tx: impl AsRef<[u8]>) -> Result<(), (ManyAbciCheckErrorCodes, String)> {
+    /// Whether `check_tx` accepts transactions without a chain ID, if the
+    /// backend has one. The time is checked against the last block time.
+    pub fn with_missing_chain_id(mut self, missing: MissingChainId) -> Self {
+        self.missing_chain_id = missing;
+        self
+    }
+
+    /// Record the transactions refused by `check_tx` in `log`.
+    pub fn with_rejection_log(mut self, log: Arc<Mutex<RejectionLog>>) -> Self {
+        self.rejections = Some(log);
+        self
+    }
+
+    /// Use these mempool priorities for transactions calling the given
+    /// endpoints, instead of the ones declared by the backend.
+    pub fn with_endpoint_priorities(mut self, priorities: BTreeMap<String, i64>) -> Self {
+        self.endpoint_priorities = priorities;
+        self
+    }
+
+    /// The mempool priority of a transaction. Backends which do not support
+    /// priorities give all transactions the same one.
+    fn priority(&self, tx: &[u8], message: &RequestMessage) -> i64 {
+        if let Some(priority) = self.endpoint_priorities.get(&message.method) {
+            return *priority;
+        }
+
+        self.many_client
+            .call_(
+                "abci.checkTxPriority",
+                AbciCheckTx {
+                    tx: tx.to_vec().into(),
+                },
+            )
+            .and_then(|payload| {
+                minicbor::decode::<AbciCheckTxPriority>(&payload)
+                    .map_err(ManyError::deserialization_error)
+            })
+            .map_or_else(
+                |e| {
+                    debug!("Unable to get the priority of a transaction: {e}");
+                    0
+                },
+                |AbciCheckTxPriority { priority }| priority,
+            )
+    }
+
+    fn record_rejection(&self, tx: &[u8], reason: &str) {
+        if let Some(log) = &self.rejections {
+            let mut log = log.lock().unwrap();
+            match CoseSign1::from_slice(tx) {
+                Ok(envelope) => log.record_envelope(&envelope, reason),
+                Err(_) => log.record(None, reason),
+            }
+        }
+    }
+
+    fn do_check_tx(&self, tx: impl AsRef<[u8]>) -> Result<i64, (ManyAbciCheckErrorCodes, String)> {
         use many_types::Timestamp;
         let cose = CoseSign1::from_slice(tx.as_ref()).map_err(|log| {
             (
@@ -140,13 +241,11 @@ impl AbciApp {
                     log.to_string(),
                 )
             })?;
-            // Validate the envelope.
-            if validator.validate_envelope(&cose).is_err() {
-                return Err((
-                    ManyAbciCheckErrorCodes::ValidationError,
-                    "Transaction already in cache".to_string(),
-                ));
-            }
+            // Validate the envelope. The cache refuses transactions it already
+            // has, and new ones while it is full.
+            validator
+                .validate_envelope(&cose)
+                .map_err(|log| (ManyAbciCheckErrorCodes::ValidationError, log.to_string()))?;
 
             // Validate the message.
             validator
@@ -181,7 +280,13 @@ impl AbciApp {
                     log.to_string(),
                 )
             })?;
-        Ok(())
+
+        if let Some(chain_id) = &self.chain_id {
+            message
+                .validate_chain_id(chain_id, self.missing_chain_id.is_accepted(now))
+                .map_err(|log| (ManyAbciCheckErrorCodes::ChainIdError, log.to_string()))?;
+        }
+        Ok(self.priority(tx.as_ref(), &message))
     }
 }
 
@@ -254,15 +359,18 @@ impl Application for AbciApp {
     }
 
     fn begin_block(&self, request: RequestBeginBlock) -> ResponseBeginBlock {
-        let (time, height) = request
+        let (time, height, proposer) = request
             .header
             .map(|x| {
                 let time = x.time.map(|x| x.seconds as u64);
                 let height = Some(if x.height > 0 { x.height as u64 } else { 0 });
+                let proposer = Some(x.proposer_address)
+                    .filter(|address| !address.is_empty())
+                    .map(Into::into);
 
-                (time, height)
+                (time, height, proposer)
             })
-            .unwrap_or((None, None));
+            .unwrap_or((None, None, None));
 
         if let Some(height) = height {
             if let Ok(mut m) = self.migrations.write() {
@@ -274,7 +382,7 @@ impl Application for AbciApp {
             }
         }
 
-        let block = AbciBlock { time };
+        let block = AbciBlock { time, proposer };
         self.block_time
             .write()
             .map(|mut block_time| *block_time = time)
@@ -285,12 +393,14 @@ impl Application for AbciApp {
 
     fn check_tx(&self, request: RequestCheckTx) -> ResponseCheckTx {
         self.do_check_tx(&request.tx)
-            .map(|_| ResponseCheckTx {
+            .map(|priority| ResponseCheckTx {
                 code: ManyAbciCheckErrorCodes::Success as u32,
+                priority,
                 ..Default::default()
             })
             .unwrap_or_else(|(code, log)| {
                 debug!("check_tx failed: {}", log);
+                self.record_rejection(&request.tx, &log);
                 ResponseCheckTx {
                     code: code as u32,
                     log,
@@ -410,4 +520,125 @@ impl Application for AbciApp {
             },
         )
     }
+
+    fn list_snapshots(&self) -> ResponseListSnapshots {
+        match call_backend::<AbciListSnapshots>(&self.many_client, "abci.listSnapshots", ()) {
+            Ok(list) => ResponseListSnapshots {
+                snapshots: list
+                    .snapshots
+                    .into_iter()
+                    .map(|snapshot| Snapshot {
+                        height: snapshot.height,
+                        format: snapshot.format,
+                        chunks: snapshot.chunks,
+                        hash: snapshot.hash.to_vec().into(),
+                        metadata: snapshot.metadata.to_vec().into(),
+                    })
+                    .collect(),
+            },
+            Err(err) => {
+                debug!("Unable to list snapshots: {err}");
+                Default::default()
+            }
+        }
+    }
+
+    fn offer_snapshot(&self, request: RequestOfferSnapshot) -> ResponseOfferSnapshot {
+        use response_offer_snapshot::Result;
+
+        let snapshot = match request.snapshot {
+            Some(snapshot) => snapshot,
+            None => {
+                return ResponseOfferSnapshot {
+                    result: Result::Reject as i32,
+                }
+            }
+        };
+        let args = AbciOfferSnapshot {
+            snapshot: AbciSnapshot {
+                height: snapshot.height,
+                format: snapshot.format,
+                chunks: snapshot.chunks,
+                hash: snapshot.hash.to_vec().into(),
+                metadata: snapshot.metadata.to_vec().into(),
+            },
+            app_hash: request.app_hash.to_vec().into(),
+        };
+
+        let result = match call_backend::<AbciOfferSnapshotReturn>(
+            &self.many_client,
+            "abci.offerSnapshot",
+            args,
+        ) {
+            Ok(AbciOfferSnapshotReturn { result }) => match result {
+                AbciOfferSnapshotResult::Unknown => Result::Unknown,
+                AbciOfferSnapshotResult::Accept => Result::Accept,
+                AbciOfferSnapshotResult::Abort => Result::Abort,
+                AbciOfferSnapshotResult::Reject => Result::Reject,
+                AbciOfferSnapshotResult::RejectFormat => Result::RejectFormat,
+                AbciOfferSnapshotResult::RejectSender => Result::RejectSender,
+            },
+            Err(err) => {
+                error!("Unable to offer a snapshot: {err}");
+                Result::Abort
+            }
+        };
+        ResponseOfferSnapshot {
+            result: result as i32,
+        }
+    }
+
+    fn load_snapshot_chunk(&self, request: RequestLoadSnapshotChunk) -> ResponseLoadSnapshotChunk {
+        let args = AbciLoadSnapshotChunk {
+            height: request.height,
+            format: request.format,
+            chunk: request.chunk,
+        };
+        match call_backend::<AbciSnapshotChunk>(&self.many_client, "abci.loadSnapshotChunk", args) {
+            Ok(AbciSnapshotChunk { chunk }) => ResponseLoadSnapshotChunk {
+                chunk: chunk.to_vec().into(),
+            },
+            Err(err) => {
+                debug!("Unable to load a snapshot chunk: {err}");
+                Default::default()
+            }
+        }
+    }
+
+    fn apply_snapshot_chunk(
+        &self,
+        request: RequestApplySnapshotChunk,
+    ) -> ResponseApplySnapshotChunk {
+        use response_apply_snapshot_chunk::Result;
+
+        let args = AbciApplySnapshotChunk {
+            index: request.index,
+            chunk: request.chunk.to_vec().into(),
+            sender: request.sender,
+        };
+        let AbciApplySnapshotChunkReturn {
+            result,
+            refetch_chunks,
+            reject_senders,
+        } = call_backend(&self.many_client, "abci.applySnapshotChunk", args).unwrap_or_else(
+            |err| {
+                error!("Unable to apply a snapshot chunk: {err}");
+                AbciApplySnapshotChunkReturn::new(AbciApplySnapshotChunkResult::Abort)
+            },
+        );
+
+        let result = match result {
+            AbciApplySnapshotChunkResult::Unknown => Result::Unknown,
+            AbciApplySnapshotChunkResult::Accept => Result::Accept,
+            AbciApplySnapshotChunkResult::Abort => Result::Abort,
+            AbciApplySnapshotChunkResult::Retry => Result::Retry,
+            AbciApplySnapshotChunkResult::RetrySnapshot => Result::RetrySnapshot,
+            AbciApplySnapshotChunkResult::RejectSnapshot => Result::RejectSnapshot,
+        };
+        ResponseApplySnapshotChunk {
+            result: result as i32,
+            refetch_chunks,
+            reject_senders,
+        }
+    }
 }
@@ -0,0 +1,435 @@
+use crate::rpc::TendermintRpc;
+use async_trait::async_trait;
+use coset::{CborSerializable, CoseSign1};
+use many_error::ManyError;
+use many_identity::Address;
+use many_protocol::{RequestMessage, ResponseMessage};
+use many_types::Timestamp;
+use minicbor::{Decode, Encode};
+use reqwest::Url;
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// The default interval between two checks for new blocks.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A transaction of an exported block.
+///
+/// The raw request and response are always exported. The method, sender and
+/// error are decoded from them for convenience, and are missing if decoding
+/// failed.
+#[derive(Clone, Debug, Encode, Decode, PartialEq)]
+#[cbor(map)]
+pub struct ExportedTransaction {
+    #[cbor(n(0), with = "minicbor::bytes")]
+    pub hash: Vec<u8>,
+
+    #[cbor(n(1), with = "minicbor::bytes")]
+    pub request: Vec<u8>,
+
+    #[cbor(n(2), with = "minicbor::bytes")]
+    pub response: Vec<u8>,
+
+    #[n(3)]
+    pub method: Option<String>,
+
+    #[n(4)]
+    pub from: Option<Address>,
+
+    #[n(5)]
+    pub error: Option<ManyError>,
+}
+
+impl ExportedTransaction {
+    pub fn new(hash: Vec<u8>, request: Vec<u8>, response: Vec<u8>) -> Self {
+        let message = CoseSign1::from_slice(&request)
+            .ok()
+            .and_then(|envelope| RequestMessage::try_from(envelope).ok());
+        let error = ResponseMessage::from_bytes(&response)
+            .ok()
+            .and_then(|response| response.data.err());
+
+        Self {
+            hash,
+            request,
+            response,
+            method: message.as_ref().map(|m| m.method.clone()),
+            from: message.as_ref().map(|m| m.from()),
+            error,
+        }
+    }
+}
+
+/// A committed block, as published to an export sink.
+#[derive(Clone, Debug, Encode, Decode, PartialEq)]
+#[cbor(map)]
+pub struct ExportedBlock {
+    #[n(0)]
+    pub height: u64,
+
+    #[cbor(n(1), with = "minicbor::bytes")]
+    pub hash: Vec<u8>,
+
+    #[cbor(n(2), with = "minicbor::bytes")]
+    pub app_hash: Option<Vec<u8>>,
+
+    #[n(3)]
+    pub timestamp: Timestamp,
+
+    #[n(4)]
+    pub transactions: Vec<ExportedTransaction>,
+}
+
+/// A destination for exported blocks.
+///
+/// Blocks are published in order, and a block is only considered exported
+/// once `publish` returns successfully. A block can be published more than
+/// once (e.g. if the process stops before its offset is saved), so consumers
+/// should deduplicate on the block height.
+#[async_trait]
+pub trait ExportSink: Send + Sync {
+    async fn publish(&self, block: &ExportedBlock) -> Result<(), ManyError>;
+}
+
+/// Append blocks to a file, as a CBOR sequence (RFC 8742).
+#[derive(Debug)]
+pub struct FileSink {
+    path: PathBuf,
+}
+
+impl FileSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl ExportSink for FileSink {
+    async fn publish(&self, block: &ExportedBlock) -> Result<(), ManyError> {
+        let bytes = minicbor::to_vec(block).map_err(ManyError::serialization_error)?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(ManyError::unknown)?;
+        file.write_all(&bytes).map_err(ManyError::unknown)?;
+        file.sync_data().map_err(ManyError::unknown)
+    }
+}
+
+/// POST each block, CBOR encoded, to a URL. Any response other than a success
+/// status is considered a failure to publish.
+#[derive(Debug)]
+pub struct HttpSink {
+    url: Url,
+    client: reqwest::Client,
+}
+
+impl HttpSink {
+    pub fn new(url: Url) -> Self {
+        Self {
+            url,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl ExportSink for HttpSink {
+    async fn publish(&self, block: &ExportedBlock) -> Result<(), ManyError> {
+        let bytes = minicbor::to_vec(block).map_err(ManyError::serialization_error)?;
+        self.client
+            .post(self.url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/cbor")
+            .body(bytes)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(ManyError::unknown)?;
+        Ok(())
+    }
+}
+
+/// The sink to export blocks to, as given on the command line; either
+/// `file://<path>` or an `http(s)://` URL.
+#[derive(Clone, Debug)]
+pub enum ExportSinkConfig {
+    File(PathBuf),
+    Http(Url),
+}
+
+impl FromStr for ExportSinkConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let url = Url::parse(s).map_err(|e| e.to_string())?;
+        match url.scheme() {
+            "file" => url
+                .to_file_path()
+                .map(ExportSinkConfig::File)
+                .map_err(|_| format!("Invalid file path: {s}")),
+            "http" | "https" => Ok(ExportSinkConfig::Http(url)),
+            scheme => Err(format!("Unsupported export sink scheme: {scheme}")),
+        }
+    }
+}
+
+impl ExportSinkConfig {
+    pub fn into_sink(self) -> Box<dyn ExportSink> {
+        match self {
+            ExportSinkConfig::File(path) => Box::new(FileSink::new(path)),
+            ExportSinkConfig::Http(url) => Box::new(HttpSink::new(url)),
+        }
+    }
+}
+
+#[async_trait]
+impl ExportSink for Box<dyn ExportSink> {
+    async fn publish(&self, block: &ExportedBlock) -> Result<(), ManyError> {
+        self.as_ref().publish(block).await
+    }
+}
+
+/// Persists the height of the last block published to the sink, so the export
+/// can resume where it stopped.
+#[derive(Clone, Debug)]
+pub struct OffsetStore {
+    path: PathBuf,
+}
+
+impl OffsetStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The height of the last exported block, or 0 if nothing was exported.
+    pub fn load(&self) -> Result<u64, ManyError> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(content) => u64::from_str(content.trim()).map_err(ManyError::unknown),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(ManyError::unknown(e)),
+        }
+    }
+
+    /// Save the offset. The file is replaced atomically, so a crash leaves
+    /// either the old or the new offset.
+    pub fn store(&self, height: u64) -> Result<(), ManyError> {
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, height.to_string()).map_err(ManyError::unknown)?;
+        std::fs::rename(&tmp, &self.path).map_err(ManyError::unknown)
+    }
+}
+
+/// Publishes committed blocks and their transactions to a sink.
+///
+/// Delivery is at-least-once; the offset is saved after the sink accepted a
+/// block, and on failure the same block is retried on the next pass.
+pub struct Exporter<C: TendermintRpc, S: ExportSink> {
+    client: C,
+    sink: S,
+    offsets: OffsetStore,
+    poll_interval: Duration,
+}
+
+impl<C: TendermintRpc, S: ExportSink> Exporter<C, S> {
+    pub fn new(client: C, sink: S, offsets: OffsetStore) -> Self {
+        Self {
+            client,
+            sink,
+            offsets,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    async fn export_block(&self, height: u64) -> Result<ExportedBlock, ManyError> {
+        let block = self.client.block(height).await?;
+
+        let mut transactions = Vec::with_capacity(block.txs.len());
+        for tx in block.txs {
+            let hash: [u8; 32] = tx.id.hash.as_slice().try_into().map_err(|_| {
+                ManyError::unknown(format!(
+                    "Invalid transaction hash x'{}'.",
+                    hex::encode(&tx.id.hash)
+                ))
+            })?;
+            // Transactions might not be indexed yet; the block will be retried.
+            let (request, response) = self.client.tx(hash).await?.ok_or_else(|| {
+                ManyError::unknown(format!(
+                    "Transaction x'{}' of block {height} not found.",
+                    hex::encode(hash)
+                ))
+            })?;
+            transactions.push(ExportedTransaction::new(hash.to_vec(), request, response));
+        }
+
+        Ok(ExportedBlock {
+            height,
+            hash: block.id.hash,
+            app_hash: block.app_hash,
+            timestamp: block.timestamp,
+            transactions,
+        })
+    }
+
+    /// Publish every committed block after the saved offset. Returns the
+    /// height of the last exported block.
+    pub async fn export_pending(&self) -> Result<u64, ManyError> {
+        let latest = self.client.status().await?.latest_block.height;
+        let mut offset = self.offsets.load()?;
+
+        while offset < latest {
+            let block = self.export_block(offset + 1).await?;
+            self.sink.publish(&block).await?;
+            offset += 1;
+            self.offsets.store(offset)?;
+            debug!("Exported block {offset}");
+        }
+
+        Ok(offset)
+    }
+
+    /// Export blocks as they are committed. This never returns.
+    pub async fn run(self) {
+        loop {
+            if let Err(e) = self.export_pending().await {
+                warn!("Block export failed, will retry: {e}");
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::mock::MockTendermintRpc;
+    use many_client::client::blocking::block_on;
+    use std::sync::Mutex;
+
+    /// Keeps published blocks in memory, and fails the first `failures`
+    /// publications.
+    #[derive(Default)]
+    struct MemorySink {
+        blocks: Mutex<Vec<ExportedBlock>>,
+        failures: Mutex<usize>,
+    }
+
+    #[async_trait]
+    impl<'a> ExportSink for &'a MemorySink {
+        async fn publish(&self, block: &ExportedBlock) -> Result<(), ManyError> {
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(ManyError::unknown("Sink unavailable."));
+            }
+            self.blocks.lock().unwrap().push(block.clone());
+            Ok(())
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("many-abci-export-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn offsets(name: &str) -> OffsetStore {
+        OffsetStore::new(temp_path(&format!("{name}.offset")))
+    }
+
+    fn heights(sink: &MemorySink) -> Vec<u64> {
+        sink.blocks
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|b| b.height)
+            .collect()
+    }
+
+    fn chain(blocks: usize) -> MockTendermintRpc {
+        let client = MockTendermintRpc::new();
+        for _ in 0..blocks {
+            block_on(client.broadcast_tx_sync(b"request".to_vec())).unwrap();
+            client.commit(vec![ResponseMessage::default().to_bytes().unwrap()]);
+        }
+        client
+    }
+
+    #[test]
+    fn export() {
+        let sink = MemorySink::default();
+        let offsets = offsets("export");
+        let exporter = Exporter::new(chain(3), &sink, offsets.clone());
+
+        assert_eq!(block_on(exporter.export_pending()).unwrap(), 3);
+        assert_eq!(heights(&sink), vec![1, 2, 3]);
+        assert_eq!(offsets.load().unwrap(), 3);
+
+        let blocks = sink.blocks.lock().unwrap();
+        assert_eq!(blocks[0].transactions.len(), 1);
+        assert_eq!(blocks[0].transactions[0].request, b"request");
+        assert_eq!(blocks[0].transactions[0].error, None);
+    }
+
+    #[test]
+    fn resume() {
+        let sink = MemorySink::default();
+        let offsets = offsets("resume");
+        offsets.store(2).unwrap();
+
+        let exporter = Exporter::new(chain(4), &sink, offsets.clone());
+        assert_eq!(block_on(exporter.export_pending()).unwrap(), 4);
+        assert_eq!(heights(&sink), vec![3, 4]);
+    }
+
+    #[test]
+    fn retry() {
+        let sink = MemorySink {
+            failures: Mutex::new(1),
+            ..Default::default()
+        };
+        let offsets = offsets("retry");
+        let exporter = Exporter::new(chain(2), &sink, offsets.clone());
+
+        assert!(block_on(exporter.export_pending()).is_err());
+        assert_eq!(offsets.load().unwrap(), 0);
+
+        assert_eq!(block_on(exporter.export_pending()).unwrap(), 2);
+        assert_eq!(heights(&sink), vec![1, 2]);
+    }
+
+    #[test]
+    fn file_sink() {
+        let path = temp_path("file.cbor");
+        let exporter = Exporter::new(chain(2), FileSink::new(&path), offsets("file"));
+        block_on(exporter.export_pending()).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let mut decoder = minicbor::Decoder::new(&bytes);
+        let first: ExportedBlock = decoder.decode().unwrap();
+        let second: ExportedBlock = decoder.decode().unwrap();
+        assert_eq!((first.height, second.height), (1, 2));
+    }
+
+    #[test]
+    fn sink_config() {
+        assert!(matches!(
+            ExportSinkConfig::from_str("file:///tmp/blocks.cbor").unwrap(),
+            ExportSinkConfig::File(_)
+        ));
+        assert!(matches!(
+            ExportSinkConfig::from_str("https://indexer.example/blocks").unwrap(),
+            ExportSinkConfig::Http(_)
+        ));
+        assert!(ExportSinkConfig::from_str("nats://localhost").is_err());
+    }
+}
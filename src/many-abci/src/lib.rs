@@ -1,6 +1,8 @@
 #![feature(used_with_arg)]
 
 pub mod abci_app;
+pub mod export;
 pub mod many_app;
 pub mod migration;
 pub mod module;
+pub mod rpc;
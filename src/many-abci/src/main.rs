@@ -2,16 +2,17 @@
 
 use clap::Parser;
 use many_client::ManyClient;
+use many_identity::delegation::DelegatedVerifier;
 use many_identity::verifiers::AnonymousVerifier;
 use many_identity::{Address, AnonymousIdentity, Identity};
 use many_identity_dsa::{CoseKeyIdentity, CoseKeyVerifier};
-use many_identity_webauthn::WebAuthnVerifier;
+use many_identity_webauthn::{DomainPattern, OriginPattern, WebAuthnPolicy, WebAuthnVerifier};
 use many_migration::MigrationConfig;
 use many_modules::{base, blockchain, r#async};
-use many_protocol::ManyUrl;
+use many_server::server::RejectionLog;
 use many_server::transport::http::HttpServer;
-use many_server::ManyServer;
-use many_server_cache::{RequestCacheValidator, SharedRocksDbCacheBackend};
+use many_server::{ManyServer, MissingChainId};
+use many_server_cache::{ReplayPolicy, RequestCacheValidator, SharedRocksDbCacheBackend};
 use std::collections::BTreeSet;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
@@ -20,11 +21,14 @@ use tendermint_rpc::Client;
 use tracing::{debug, error, info, trace};
 
 mod abci_app;
+mod export;
 mod many_app;
 mod migration;
 mod module;
+mod rpc;
 
 use abci_app::AbciApp;
+use export::{ExportSinkConfig, Exporter, OffsetStore};
 use many_app::AbciModuleMany;
 use many_server::validator::ValidateOnlyRequestValidator;
 use module::AbciBlockchainModuleImpl;
@@ -60,9 +64,23 @@ struct Opts {
 
     /// Application absolute URLs allowed to communicate with this server. Any
     /// application will be able to communicate with this server if left empty.
+    /// All the subdomains of a domain can be allowed with a `*.` prefix, e.g.
+    /// `https://*.example.com`.
     /// Multiple occurences of this argument can be given.
     #[clap(long)]
-    allow_origin: Option<Vec<ManyUrl>>,
+    allow_origin: Option<Vec<OriginPattern>>,
+
+    /// WebAuthn relying party IDs allowed to communicate with this server,
+    /// e.g. `example.com` or `*.example.com`. Any RP ID will be allowed if
+    /// left empty.
+    /// Multiple occurences of this argument can be given.
+    #[clap(long)]
+    allow_rp_id: Option<Vec<DomainPattern>>,
+
+    /// Refuse WebAuthn envelopes whose authenticator did not verify the user
+    /// (e.g. with a PIN or biometrics).
+    #[clap(long)]
+    require_user_verification: bool,
 
     /// Path to a JSON file containing an array of MANY addresses
     /// Only addresses from this array will be able to execute commands, e.g., send, put, ...
@@ -70,6 +88,12 @@ struct Opts {
     #[clap(long)]
     allow_addrs: Option<PathBuf>,
 
+    /// Endpoints to disable on this server, e.g. `idstore.store`. Requests to
+    /// them are refused and they are not listed by `endpoints`.
+    /// Multiple occurences of this argument can be given.
+    #[clap(long = "disable-endpoint")]
+    disable_endpoint: Vec<String>,
+
     /// Path to a JSON file containing the configurations for the
     /// migrations. Migrations are DISABLED unless this configuration file
     /// is given.
@@ -80,6 +104,73 @@ struct Opts {
     /// verify transactions for duplicate requests.
     #[clap(long)]
     cache_db: PathBuf,
+
+    /// Remove requests from the cache after this many seconds. This must be
+    /// longer than the timeout of the server, or requests could be replayed.
+    /// Requests are kept forever by default.
+    #[clap(long)]
+    cache_ttl: Option<u64>,
+
+    /// Answer requests that were already executed with their original
+    /// response, instead of a duplicated message error. Responses are kept
+    /// in the cache with their request.
+    #[clap(long)]
+    cache_replay_responses: bool,
+
+    /// Publish committed blocks and their transactions to a sink, either
+    /// `file://<path>` (a CBOR sequence) or an `http(s)://` URL (one POST per
+    /// block).
+    #[clap(long, requires("export-offset"))]
+    export_sink: Option<ExportSinkConfig>,
+
+    /// Path to the file storing the height of the last exported block. The
+    /// export resumes from this height on restart.
+    #[clap(long, requires("export-sink"))]
+    export_offset: Option<PathBuf>,
+
+    /// Interval, in seconds, between two checks for new blocks to export.
+    #[clap(long, default_value = "1")]
+    export_poll_interval: u64,
+
+    /// The number of recent transactions refused by the server or CheckTx to
+    /// keep for `rejections.list`. Set to 0 to disable the endpoint.
+    #[clap(long, default_value = "1000")]
+    rejection_log_size: usize,
+
+    /// An address allowed to call `rejections.list`, in addition to the
+    /// identity of the server.
+    /// Multiple occurences of this argument can be given.
+    #[clap(long)]
+    operator: Vec<Address>,
+
+    /// Maximum time, in milliseconds, to wait for the state to reach the
+    /// minimum height requested by a query before returning an error.
+    #[clap(long, default_value = "5000")]
+    min_height_timeout: u64,
+
+    /// The mempool priority of transactions calling an endpoint, as
+    /// `ENDPOINT=PRIORITY`, e.g. `account.disable=100`. Overrides the priority
+    /// declared by the backend. Higher priorities are included in blocks first.
+    /// Multiple occurences of this argument can be given.
+    #[clap(long = "endpoint-priority", value_parser = parse_endpoint_priority)]
+    endpoint_priority: Vec<(String, i64)>,
+
+    /// Accept transactions without a chain ID until this time, in seconds
+    /// since the UNIX epoch, so clients have time to add it. Transactions
+    /// without a chain ID are refused if unspecified. The chain ID is the one
+    /// of the backend, if it has one.
+    #[clap(long)]
+    missing_chain_id_allowed_until: Option<u64>,
+}
+
+fn parse_endpoint_priority(s: &str) -> Result<(String, i64), String> {
+    let (endpoint, priority) = s
+        .split_once('=')
+        .ok_or_else(|| format!("Expected ENDPOINT=PRIORITY, got '{s}'"))?;
+    let priority = priority
+        .parse()
+        .map_err(|e| format!("Invalid priority '{priority}': {e}"))?;
+    Ok((endpoint.to_string(), priority))
 }
 
 #[tokio::main]
@@ -93,9 +184,22 @@ async fn main() {
         many_pem,
         abci_read_buf_size,
         allow_origin,
+        allow_rp_id,
+        require_user_verification,
         allow_addrs,
+        disable_endpoint,
         migrations_config,
         cache_db,
+        cache_ttl,
+        cache_replay_responses,
+        export_sink,
+        export_offset,
+        export_poll_interval,
+        rejection_log_size,
+        operator,
+        min_height_timeout,
+        endpoint_priority,
+        missing_chain_id_allowed_until,
     } = Opts::parse();
 
     common_flags.init_logging().unwrap();
@@ -142,13 +246,46 @@ async fn main() {
         std::thread::sleep(std::time::Duration::from_secs(1));
     };
 
+    // The backend accepts transactions without a chain ID, so its blocks do
+    // not depend on its clock. They are refused here, before reaching a block.
+    let missing_chain_id = match missing_chain_id_allowed_until {
+        Some(secs) => MissingChainId::AcceptUntil(
+            std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs),
+        ),
+        None => MissingChainId::Refuse,
+    };
+
     let rocksdb_cache = SharedRocksDbCacheBackend::new(cache_db);
+    let replay_policy = if cache_replay_responses {
+        ReplayPolicy::ReturnCachedResponse
+    } else {
+        ReplayPolicy::Reject
+    };
+    let rejection_log = (rejection_log_size > 0).then(|| {
+        Arc::new(Mutex::new(
+            RejectionLog::new(rejection_log_size).with_operators(operator),
+        ))
+    });
     let abci_app = {
         let rocksdb_cache = rocksdb_cache.clone();
+        let rejection_log = rejection_log.clone();
         tokio::task::spawn_blocking(move || {
-            AbciApp::create(many_app, Address::anonymous(), maybe_migrations)
+            let app = AbciApp::create(many_app, Address::anonymous(), maybe_migrations)
                 .unwrap()
-                .with_validator(RequestCacheValidator::new(rocksdb_cache))
+                .with_endpoint_priorities(endpoint_priority.into_iter().collect())
+                .with_missing_chain_id(missing_chain_id);
+            // Requests expire with the block time, like `check_tx` checks them.
+            let cache = RequestCacheValidator::new(rocksdb_cache)
+                .with_replay_policy(replay_policy)
+                .with_time_fn(app.block_time_fn());
+            let app = app.with_validator(match cache_ttl {
+                Some(ttl) => cache.with_ttl(std::time::Duration::from_secs(ttl)),
+                None => cache,
+            });
+            match rejection_log {
+                Some(log) => app.with_rejection_log(log),
+                None => app,
+            }
         })
         .await
         .unwrap()
@@ -176,28 +313,47 @@ async fn main() {
         std::thread::sleep(std::time::Duration::from_secs(1));
     }
 
+    if let (Some(sink), Some(offset)) = (export_sink, export_offset) {
+        info!("Exporting blocks to {sink:?}");
+        let exporter = Exporter::new(
+            abci_client.clone(),
+            sink.into_sink(),
+            OffsetStore::new(offset),
+        )
+        .with_poll_interval(std::time::Duration::from_secs(export_poll_interval));
+        tokio::spawn(exporter.run());
+    }
+
+    let chain_id = status.chain_id.clone();
     let key = CoseKeyIdentity::from_pem(std::fs::read_to_string(many_pem).unwrap()).unwrap();
     info!(many_address = key.address().to_string().as_str());
+    let webauthn_policy = WebAuthnPolicy {
+        origins: allow_origin,
+        rp_ids: allow_rp_id,
+        require_user_presence: false,
+        require_user_verification,
+    };
     let server = ManyServer::new(
         format!("AbciModule({})", &status.name),
         key.clone(),
-        (
+        DelegatedVerifier::new((
             AnonymousVerifier,
             CoseKeyVerifier,
-            WebAuthnVerifier::new(allow_origin.clone()),
-        ),
+            WebAuthnVerifier::with_policy(webauthn_policy.clone()),
+        )),
         key.public_key(),
     );
     let allowed_addrs: Option<BTreeSet<Address>> =
         allow_addrs.map(|path| json5::from_str(&std::fs::read_to_string(path).unwrap()).unwrap());
-    let backend = AbciModuleMany::new(
+    let mut backend = AbciModuleMany::new(
         abci_client.clone(),
         status,
         key,
         allowed_addrs,
-        allow_origin,
+        webauthn_policy,
     )
     .await;
+    backend.set_min_height_timeout(std::time::Duration::from_millis(min_height_timeout));
     let blockchain_impl = Arc::new(Mutex::new(AbciBlockchainModuleImpl::new(abci_client)));
 
     {
@@ -206,6 +362,16 @@ async fn main() {
         s.add_module(blockchain::BlockchainModule::new(blockchain_impl.clone()));
         s.add_module(r#async::AsyncModule::new(blockchain_impl));
         s.set_fallback_module(backend);
+        if let Some(chain_id) = chain_id {
+            s.set_chain_id(chain_id, missing_chain_id);
+        }
+        if let Some(log) = rejection_log {
+            s.set_rejection_log(log);
+        }
+
+        for method in disable_endpoint {
+            s.disable_endpoint(method);
+        }
 
         // The message is executed by the _server_ itself after it's been
         // added to tendermint.
@@ -213,7 +379,7 @@ async fn main() {
         // as we might still have to check those again, and the cache is
         // updated only after the message has been sent to the MANY backend.
         s.add_validator(ValidateOnlyRequestValidator::new(
-            RequestCacheValidator::new(rocksdb_cache.clone()),
+            RequestCacheValidator::new(rocksdb_cache.clone()).with_replay_policy(replay_policy),
         ));
     }
 
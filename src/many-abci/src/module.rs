@@ -1,7 +1,5 @@
-use base64::{engine::general_purpose, Engine as _};
-use clap::__macro_refs::once_cell;
+use crate::rpc::TendermintRpc;
 use coset::CborSerializable;
-use itertools::Itertools;
 use many_client::client::blocking::block_on;
 use many_error::ManyError;
 use many_identity::{Address, AnonymousIdentity};
@@ -9,114 +7,23 @@ use many_modules::r#async::{StatusArgs, StatusReturn};
 use many_modules::{abci_frontend, blockchain, r#async};
 use many_protocol::{encode_cose_sign1_from_response, ResponseMessage};
 use many_types::blockchain::{
-    Block, BlockIdentifier, SingleBlockQuery, SingleTransactionQuery, Transaction,
-    TransactionIdentifier,
+    SingleBlockQuery, SingleTransactionQuery, Transaction, TransactionIdentifier,
 };
-use many_types::{blockchain::RangeBlockQuery, SortOrder, Timestamp};
-use once_cell::sync::Lazy;
-use std::ops::{Bound, RangeBounds};
-use tendermint::Time;
-use tendermint_rpc::endpoint::tx;
-use tendermint_rpc::error::ErrorDetail;
-use tendermint_rpc::{query::Query, Client, Error};
+use many_types::SortOrder;
 
 const MAXIMUM_BLOCK_COUNT: u64 = 100;
-static DEFAULT_BLOCK_LIST_QUERY: Lazy<Query> = Lazy::new(|| Query::gte("block.height", 0));
-
-fn _many_block_from_tendermint_block(block: tendermint::Block) -> Block {
-    let height = block.header.height.value();
-    let txs_count = block.data.len() as u64;
-    let txs = block
-        .data
-        .into_iter()
-        .map(|b| {
-            use sha2::Digest;
-            let mut hasher = sha2::Sha256::new();
-            hasher.update(b);
-            Transaction {
-                id: TransactionIdentifier {
-                    hash: hasher.finalize().to_vec(),
-                },
-                request: None,
-                response: None,
-            }
-        })
-        .collect();
-    Block {
-        id: BlockIdentifier {
-            hash: block.header.hash().into(),
-            height,
-        },
-        parent: if height <= 1 {
-            BlockIdentifier::genesis()
-        } else {
-            BlockIdentifier::new(block.header.last_block_id.unwrap().hash.into(), height - 1)
-        },
-        app_hash: Some(block.header.app_hash.as_bytes().to_vec()),
-        timestamp: Timestamp::new(
-            block
-                .header
-                .time
-                .duration_since(Time::unix_epoch())
-                .unwrap()
-                .as_secs(),
-        )
-        .unwrap(),
-        txs_count,
-        txs,
-    }
-}
-
-fn _tm_order_from_many_order(order: SortOrder) -> tendermint_rpc::Order {
-    match order {
-        SortOrder::Ascending => tendermint_rpc::Order::Ascending,
-        SortOrder::Descending => tendermint_rpc::Order::Descending,
-        _ => tendermint_rpc::Order::Ascending,
-    }
-}
-
-fn _tm_query_from_many_filter(
-    filter: RangeBlockQuery,
-) -> Result<tendermint_rpc::query::Query, ManyError> {
-    let mut query = tendermint_rpc::query::Query::default();
-    query = match filter {
-        RangeBlockQuery::Height(range) => {
-            query = match range.start_bound() {
-                Bound::Included(x) => query.and_gte("block.height", *x),
-                Bound::Excluded(x) => query.and_gt("block.height", *x),
-                _ => query,
-            };
-            query = match range.end_bound() {
-                Bound::Included(x) => query.and_lte("block.height", *x),
-                Bound::Excluded(x) => query.and_lt("block.height", *x),
-                _ => query,
-            };
-            query
-        }
-        RangeBlockQuery::Time(_range) => return Err(ManyError::unknown("Unimplemented")),
-    };
-
-    // The default query returns an error (TM 0.35)
-    // Return all blocks
-    // TODO: Test on TM 0.34 and report an issue in TM-rs if reproducible
-    if query.to_string().is_empty() {
-        query = DEFAULT_BLOCK_LIST_QUERY.clone();
-    }
 
-    Ok(query)
-}
-
-pub struct AbciBlockchainModuleImpl<C: Client> {
+pub struct AbciBlockchainModuleImpl<C: TendermintRpc> {
     client: C,
 }
 
-impl<C: Client> AbciBlockchainModuleImpl<C> {
+impl<C: TendermintRpc> AbciBlockchainModuleImpl<C> {
     pub fn new(client: C) -> Self {
         Self { client }
     }
 }
 
-impl<C: Client + Sync> AbciBlockchainModuleImpl<C> {
+impl<C: TendermintRpc> AbciBlockchainModuleImpl<C> {
     /// Call the Tendermint backend to get a transaction based on its query.
     /// Returns a result of Option. Option will be None if the transaction was
     /// not found.
@@ -127,27 +34,7 @@ impl<C: Client + Sync> AbciBlockchainModuleImpl<C> {
         match query {
             SingleTransactionQuery::Hash(hash) => {
                 if let Ok(hash) = TryInto::<[u8; 32]>::try_into(hash.clone()) {
-                    let tx::Response {
-                        tx: tx_request,
-                        tx_result,
-                        ..
-                    } = match self.client.tx(tendermint::Hash::Sha256(hash), true).await {
-                        Ok(response) => response,
-                        // Cannot get more details than response error when the hash is not found.
-                        Err(Error(ErrorDetail::Response(_), _tracer)) => return Ok(None),
-                        Err(e @ Error(_, _)) => {
-                            tracing::error!("abci transport: {e}");
-                            return Err(abci_frontend::abci_transport_error(e));
-                        }
-                    };
-
-                    // Base64 decode is required because of an issue in `tendermint-rs` 0.28.0
-                    // TODO: Remove when https://github.com/informalsystems/tendermint-rs/issues/1251 is fixed
-                    let result_tx = general_purpose::STANDARD
-                        .decode(&tx_result.data)
-                        .map_err(abci_frontend::abci_transport_error)?;
-
-                    Ok(Some((tx_request, result_tx)))
+                    self.client.tx(hash).await
                 } else {
                     Err(ManyError::unknown(format!(
                         "Invalid transaction hash x'{}'.",
@@ -159,13 +46,13 @@ impl<C: Client + Sync> AbciBlockchainModuleImpl<C> {
     }
 }
 
-impl<C: Client> Drop for AbciBlockchainModuleImpl<C> {
+impl<C: TendermintRpc> Drop for AbciBlockchainModuleImpl<C> {
     fn drop(&mut self) {
         tracing::info!("ABCI Blockchain Module being dropped.");
     }
 }
 
-impl<C: Client + Send + Sync> r#async::AsyncModuleBackend for AbciBlockchainModuleImpl<C> {
+impl<C: TendermintRpc> r#async::AsyncModuleBackend for AbciBlockchainModuleImpl<C> {
     fn status(&self, _sender: &Address, args: StatusArgs) -> Result<StatusReturn, ManyError> {
         let hash: Vec<u8> = args.token.into();
 
@@ -190,19 +77,13 @@ impl<C: Client + Send + Sync> r#async::AsyncModuleBackend for AbciBlockchainModu
     }
 }
 
-impl<C: Client + Send + Sync> blockchain::BlockchainModuleBackend for AbciBlockchainModuleImpl<C> {
+impl<C: TendermintRpc> blockchain::BlockchainModuleBackend for AbciBlockchainModuleImpl<C> {
     fn info(&self) -> Result<blockchain::InfoReturns, ManyError> {
-        let status = block_on(async { self.client.status().await }).map_err(|e| {
-            tracing::error!("abci transport: {}", e.to_string());
-            abci_frontend::abci_transport_error(e.to_string())
-        })?;
+        let status = block_on(async { self.client.status().await })?;
 
         Ok(blockchain::InfoReturns {
-            latest_block: BlockIdentifier {
-                hash: status.sync_info.latest_block_hash.as_bytes().to_vec(),
-                height: status.sync_info.latest_block_height.value(),
-            },
-            app_hash: Some(status.sync_info.latest_app_hash.as_bytes().to_vec()),
+            latest_block: status.latest_block,
+            app_hash: Some(status.latest_app_hash),
             retained_height: None,
         })
     }
@@ -211,25 +92,12 @@ impl<C: Client + Send + Sync> blockchain::BlockchainModuleBackend for AbciBlockc
         &self,
         args: blockchain::TransactionArgs,
     ) -> Result<blockchain::TransactionReturns, ManyError> {
-        let block = block_on(async {
-            match args.query {
-                SingleTransactionQuery::Hash(hash) => {
-                    if let Ok(hash) = TryInto::<[u8; 32]>::try_into(hash) {
-                        self.client
-                            .tx(tendermint::Hash::Sha256(hash), true)
-                            .await
-                            .map_err(|e| {
-                                tracing::error!("abci transport: {}", e.to_string());
-                                abci_frontend::abci_transport_error(e.to_string())
-                            })
-                    } else {
-                        Err(ManyError::unknown("Invalid transaction hash .".to_string()))
-                    }
-                }
-            }
-        })?;
+        let tx_hash = match &args.query {
+            SingleTransactionQuery::Hash(hash) => hash.clone(),
+        };
+        block_on(async { self.tx(args.query).await })?
+            .ok_or_else(|| ManyError::unknown("Hash not found."))?;
 
-        let tx_hash = block.hash.as_bytes().to_vec();
         Ok(blockchain::TransactionReturns {
             txn: Transaction {
                 id: TransactionIdentifier { hash: tx_hash },
@@ -244,32 +112,16 @@ impl<C: Client + Send + Sync> blockchain::BlockchainModuleBackend for AbciBlockc
             match args.query {
                 SingleBlockQuery::Hash(hash) => {
                     if let Ok(hash) = TryInto::<[u8; 32]>::try_into(hash) {
-                        self.client
-                            .block_by_hash(tendermint::Hash::Sha256(hash))
-                            .await
-                            .map_err(|e| {
-                                tracing::error!("abci transport: {}", e.to_string());
-                                abci_frontend::abci_transport_error(e.to_string())
-                            })
-                            .map(|search| search.block)
+                        self.client.block_by_hash(hash).await
                     } else {
                         Err(ManyError::unknown("Invalid hash length.".to_string()))
                     }
                 }
-                SingleBlockQuery::Height(height) => self
-                    .client
-                    .block(height as u32)
-                    .await
-                    .map_err(|e| {
-                        tracing::error!("abci transport: {}", e.to_string());
-                        abci_frontend::abci_transport_error(e.to_string())
-                    })
-                    .map(|x| Some(x.block)),
+                SingleBlockQuery::Height(height) => self.client.block(height).await.map(Some),
             }
         })?;
 
         if let Some(block) = block {
-            let block = _many_block_from_tendermint_block(block);
             Ok(blockchain::BlockReturns { block })
         } else {
             Err(blockchain::unknown_block())
@@ -300,33 +152,17 @@ impl<C: Client + Send + Sync> blockchain::BlockchainModuleBackend for AbciBlockc
             (1u32, count as u8)
         };
 
-        let order = order.map_or(tendermint_rpc::Order::Ascending, _tm_order_from_many_order);
-
-        let query = filter.map_or(
-            Ok(DEFAULT_BLOCK_LIST_QUERY.clone()),
-            _tm_query_from_many_filter,
-        )?;
+        let order = order.unwrap_or(SortOrder::Ascending);
 
-        let (status, block) = block_on(async move {
+        let (status, blocks) = block_on(async move {
             let status = self.client.status().await;
-            let block = self.client.block_search(query, pages, count, order).await;
-            (status, block)
+            let blocks = self.client.block_search(filter, pages, count, order).await;
+            (status, blocks)
         });
 
-        let blocks = block
-            .map_err(ManyError::unknown)?
-            .blocks
-            .into_iter()
-            .map(|x| _many_block_from_tendermint_block(x.block))
-            .collect_vec();
-
         Ok(blockchain::ListReturns {
-            height: status
-                .map_err(ManyError::unknown)?
-                .sync_info
-                .latest_block_height
-                .value(),
-            blocks,
+            height: status?.latest_block.height,
+            blocks: blocks?,
         })
     }
 
@@ -358,3 +194,163 @@ impl<C: Client + Send + Sync> blockchain::BlockchainModuleBackend for AbciBlockc
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::mock::MockTendermintRpc;
+    use many_modules::blockchain::BlockchainModuleBackend;
+    use many_modules::r#async::AsyncModuleBackend;
+    use many_types::blockchain::{Block, RangeBlockQuery};
+    use many_types::{CborRange, Timestamp};
+    use std::ops::Bound;
+
+    fn response_bytes() -> Vec<u8> {
+        ResponseMessage {
+            data: Ok(b"response".to_vec()),
+            timestamp: Some(Timestamp::new(1).unwrap()),
+            ..Default::default()
+        }
+        .to_bytes()
+        .unwrap()
+    }
+
+    /// Create a chain with `count` blocks, and a single transaction in the
+    /// first block. Returns the module and the transaction hash.
+    fn setup(count: u64) -> (AbciBlockchainModuleImpl<MockTendermintRpc>, Vec<u8>) {
+        let module = AbciBlockchainModuleImpl::new(MockTendermintRpc::new());
+        let hash = block_on(module.client.broadcast_tx_sync(b"request".to_vec())).unwrap();
+        module.client.commit(vec![response_bytes()]);
+        for _ in 1..count {
+            module.client.commit(vec![]);
+        }
+        (module, hash)
+    }
+
+    fn heights(blocks: Vec<Block>) -> Vec<u64> {
+        blocks.into_iter().map(|b| b.id.height).collect()
+    }
+
+    #[test]
+    fn info() {
+        let (module, _) = setup(3);
+        let info = module.info().unwrap();
+        assert_eq!(info.latest_block.height, 3);
+        assert!(info.app_hash.is_some());
+    }
+
+    #[test]
+    fn block() {
+        let (module, hash) = setup(2);
+        let block = module
+            .block(blockchain::BlockArgs {
+                query: SingleBlockQuery::Height(1),
+            })
+            .unwrap()
+            .block;
+        assert_eq!(block.txs_count, 1);
+        assert_eq!(block.txs[0].id, TransactionIdentifier { hash });
+
+        let by_hash = module
+            .block(blockchain::BlockArgs {
+                query: SingleBlockQuery::Hash(block.id.hash.clone()),
+            })
+            .unwrap()
+            .block;
+        assert_eq!(by_hash, block);
+
+        let unknown = module.block(blockchain::BlockArgs {
+            query: SingleBlockQuery::Hash(vec![0; 32]),
+        });
+        assert_eq!(
+            unknown.unwrap_err().code(),
+            blockchain::unknown_block().code()
+        );
+    }
+
+    #[test]
+    fn list() {
+        let (module, _) = setup(5);
+        let list = |count, order, filter| {
+            module
+                .list(blockchain::ListArgs {
+                    count,
+                    order,
+                    filter,
+                })
+                .unwrap()
+        };
+
+        let all = list(None, None, None);
+        assert_eq!(all.height, 5);
+        assert_eq!(heights(all.blocks), vec![1, 2, 3, 4, 5]);
+
+        let last = list(Some(2), Some(SortOrder::Descending), None);
+        assert_eq!(heights(last.blocks), vec![5, 4]);
+
+        let range = list(
+            None,
+            None,
+            Some(RangeBlockQuery::Height(CborRange {
+                start: Bound::Excluded(1),
+                end: Bound::Included(3),
+            })),
+        );
+        assert_eq!(heights(range.blocks), vec![2, 3]);
+    }
+
+    #[test]
+    fn transaction() {
+        let (module, hash) = setup(1);
+        let txn = module
+            .transaction(blockchain::TransactionArgs {
+                query: SingleTransactionQuery::Hash(hash.clone()),
+            })
+            .unwrap()
+            .txn;
+        assert_eq!(txn.id.hash, hash);
+
+        let request = module
+            .request(blockchain::RequestArgs {
+                query: SingleTransactionQuery::Hash(hash.clone()),
+            })
+            .unwrap()
+            .request;
+        assert_eq!(request, b"request");
+
+        let response = module
+            .response(blockchain::ResponseArgs {
+                query: SingleTransactionQuery::Hash(hash),
+            })
+            .unwrap()
+            .response;
+        assert!(!response.is_empty());
+
+        assert!(module
+            .transaction(blockchain::TransactionArgs {
+                query: SingleTransactionQuery::Hash(vec![0; 32]),
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn status() {
+        let (module, hash) = setup(1);
+        let sender = Address::anonymous();
+
+        let done = module
+            .status(&sender, StatusArgs { token: hash.into() })
+            .unwrap();
+        assert!(matches!(done, StatusReturn::Done { .. }));
+
+        let unknown = module
+            .status(
+                &sender,
+                StatusArgs {
+                    token: vec![0; 32].into(),
+                },
+            )
+            .unwrap();
+        assert!(matches!(unknown, StatusReturn::Unknown));
+    }
+}
@@ -0,0 +1,245 @@
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use clap::__macro_refs::once_cell;
+use many_error::ManyError;
+use many_modules::abci_frontend;
+use many_types::blockchain::{
+    Block, BlockIdentifier, RangeBlockQuery, Transaction, TransactionIdentifier,
+};
+use many_types::{SortOrder, Timestamp};
+use once_cell::sync::Lazy;
+use std::ops::{Bound, RangeBounds};
+use tendermint::Time;
+use tendermint_rpc::endpoint::tx;
+use tendermint_rpc::error::ErrorDetail;
+use tendermint_rpc::{query::Query, Client, Error, HttpClient};
+
+#[cfg(test)]
+pub mod mock;
+
+static DEFAULT_BLOCK_LIST_QUERY: Lazy<Query> = Lazy::new(|| Query::gte("block.height", 0));
+
+/// The status of the Tendermint node, as far as MANY is concerned.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NodeStatus {
+    pub latest_block: BlockIdentifier,
+    pub latest_app_hash: Vec<u8>,
+}
+
+/// The Tendermint RPC calls used by the ABCI frontend.
+///
+/// Values are converted to MANY types at this boundary, so the modules never
+/// see a Tendermint type. This is implemented for the Tendermint HTTP client,
+/// and by an in-process mock for tests.
+#[async_trait]
+pub trait TendermintRpc: Send + Sync {
+    /// The latest block and app hash known to the node.
+    async fn status(&self) -> Result<NodeStatus, ManyError>;
+
+    /// Get a block by its height.
+    async fn block(&self, height: u64) -> Result<Block, ManyError>;
+
+    /// Get a block by its hash. Returns None if the block was not found.
+    async fn block_by_hash(&self, hash: [u8; 32]) -> Result<Option<Block>, ManyError>;
+
+    /// Search blocks matching a filter. All blocks are matched if there is no
+    /// filter.
+    async fn block_search(
+        &self,
+        filter: Option<RangeBlockQuery>,
+        page: u32,
+        per_page: u8,
+        order: SortOrder,
+    ) -> Result<Vec<Block>, ManyError>;
+
+    /// Get the request and response bytes of a transaction by its hash.
+    /// Returns None if the transaction was not found.
+    async fn tx(&self, hash: [u8; 32]) -> Result<Option<(Vec<u8>, Vec<u8>)>, ManyError>;
+
+    /// Broadcast a transaction and return its hash.
+    async fn broadcast_tx_sync(&self, data: Vec<u8>) -> Result<Vec<u8>, ManyError>;
+
+    /// Query the ABCI application and return the response value.
+    async fn abci_query(&self, data: Vec<u8>) -> Result<Vec<u8>, ManyError>;
+}
+
+fn transport_error(e: Error) -> ManyError {
+    tracing::error!("abci transport: {}", e.to_string());
+    abci_frontend::abci_transport_error(e.to_string())
+}
+
+fn _many_block_from_tendermint_block(block: tendermint::Block) -> Block {
+    let height = block.header.height.value();
+    let txs_count = block.data.len() as u64;
+    let txs = block
+        .data
+        .into_iter()
+        .map(|b| {
+            use sha2::Digest;
+            let mut hasher = sha2::Sha256::new();
+            hasher.update(b);
+            Transaction {
+                id: TransactionIdentifier {
+                    hash: hasher.finalize().to_vec(),
+                },
+                request: None,
+                response: None,
+            }
+        })
+        .collect();
+    Block {
+        id: BlockIdentifier {
+            hash: block.header.hash().into(),
+            height,
+        },
+        parent: if height <= 1 {
+            BlockIdentifier::genesis()
+        } else {
+            BlockIdentifier::new(block.header.last_block_id.unwrap().hash.into(), height - 1)
+        },
+        app_hash: Some(block.header.app_hash.as_bytes().to_vec()),
+        timestamp: Timestamp::new(
+            block
+                .header
+                .time
+                .duration_since(Time::unix_epoch())
+                .unwrap()
+                .as_secs(),
+        )
+        .unwrap(),
+        txs_count,
+        txs,
+    }
+}
+
+fn _tm_order_from_many_order(order: SortOrder) -> tendermint_rpc::Order {
+    match order {
+        SortOrder::Ascending => tendermint_rpc::Order::Ascending,
+        SortOrder::Descending => tendermint_rpc::Order::Descending,
+        _ => tendermint_rpc::Order::Ascending,
+    }
+}
+
+fn _tm_query_from_many_filter(
+    filter: RangeBlockQuery,
+) -> Result<tendermint_rpc::query::Query, ManyError> {
+    let mut query = tendermint_rpc::query::Query::default();
+    query = match filter {
+        RangeBlockQuery::Height(range) => {
+            query = match range.start_bound() {
+                Bound::Included(x) => query.and_gte("block.height", *x),
+                Bound::Excluded(x) => query.and_gt("block.height", *x),
+                _ => query,
+            };
+            query = match range.end_bound() {
+                Bound::Included(x) => query.and_lte("block.height", *x),
+                Bound::Excluded(x) => query.and_lt("block.height", *x),
+                _ => query,
+            };
+            query
+        }
+        RangeBlockQuery::Time(_range) => return Err(ManyError::unknown("Unimplemented")),
+    };
+
+    // The default query returns an error (TM 0.35)
+    // Return all blocks
+    // TODO: Test on TM 0.34 and report an issue in TM-rs if reproducible
+    if query.to_string().is_empty() {
+        query = DEFAULT_BLOCK_LIST_QUERY.clone();
+    }
+
+    Ok(query)
+}
+
+#[async_trait]
+impl TendermintRpc for HttpClient {
+    async fn status(&self) -> Result<NodeStatus, ManyError> {
+        let status = Client::status(self).await.map_err(transport_error)?;
+        Ok(NodeStatus {
+            latest_block: BlockIdentifier {
+                hash: status.sync_info.latest_block_hash.as_bytes().to_vec(),
+                height: status.sync_info.latest_block_height.value(),
+            },
+            latest_app_hash: status.sync_info.latest_app_hash.as_bytes().to_vec(),
+        })
+    }
+
+    async fn block(&self, height: u64) -> Result<Block, ManyError> {
+        let height: u32 = height
+            .try_into()
+            .map_err(|_| ManyError::unknown("Invalid block height."))?;
+        Client::block(self, height)
+            .await
+            .map_err(transport_error)
+            .map(|x| _many_block_from_tendermint_block(x.block))
+    }
+
+    async fn block_by_hash(&self, hash: [u8; 32]) -> Result<Option<Block>, ManyError> {
+        Client::block_by_hash(self, tendermint::Hash::Sha256(hash))
+            .await
+            .map_err(transport_error)
+            .map(|search| search.block.map(_many_block_from_tendermint_block))
+    }
+
+    async fn block_search(
+        &self,
+        filter: Option<RangeBlockQuery>,
+        page: u32,
+        per_page: u8,
+        order: SortOrder,
+    ) -> Result<Vec<Block>, ManyError> {
+        let query = filter.map_or(
+            Ok(DEFAULT_BLOCK_LIST_QUERY.clone()),
+            _tm_query_from_many_filter,
+        )?;
+
+        Ok(Client::block_search(
+            self,
+            query,
+            page,
+            per_page,
+            _tm_order_from_many_order(order),
+        )
+        .await
+        .map_err(transport_error)?
+        .blocks
+        .into_iter()
+        .map(|x| _many_block_from_tendermint_block(x.block))
+        .collect())
+    }
+
+    async fn tx(&self, hash: [u8; 32]) -> Result<Option<(Vec<u8>, Vec<u8>)>, ManyError> {
+        let tx::Response {
+            tx: tx_request,
+            tx_result,
+            ..
+        } = match Client::tx(self, tendermint::Hash::Sha256(hash), true).await {
+            Ok(response) => response,
+            // Cannot get more details than response error when the hash is not found.
+            Err(Error(ErrorDetail::Response(_), _tracer)) => return Ok(None),
+            Err(e @ Error(_, _)) => return Err(transport_error(e)),
+        };
+
+        // Base64 decode is required because of an issue in `tendermint-rs` 0.28.0
+        // TODO: Remove when https://github.com/informalsystems/tendermint-rs/issues/1251 is fixed
+        let result_tx = general_purpose::STANDARD
+            .decode(&tx_result.data)
+            .map_err(abci_frontend::abci_transport_error)?;
+
+        Ok(Some((tx_request, result_tx)))
+    }
+
+    async fn broadcast_tx_sync(&self, data: Vec<u8>) -> Result<Vec<u8>, ManyError> {
+        Client::broadcast_tx_sync(self, data)
+            .await
+            .map_err(transport_error)
+            .map(|response| response.hash.as_bytes().to_vec())
+    }
+
+    async fn abci_query(&self, data: Vec<u8>) -> Result<Vec<u8>, ManyError> {
+        Client::abci_query(self, None, data, None, false)
+            .await
+            .map_err(transport_error)
+            .map(|response| response.value)
+    }
+}
@@ -0,0 +1,184 @@
+use super::{NodeStatus, TendermintRpc};
+use async_trait::async_trait;
+use many_error::ManyError;
+use many_types::blockchain::{
+    Block, BlockIdentifier, RangeBlockQuery, Transaction, TransactionIdentifier,
+};
+use many_types::{SortOrder, Timestamp};
+use sha2::Digest;
+use std::collections::BTreeMap;
+use std::ops::RangeBounds;
+use std::sync::Mutex;
+
+type QueryHandler = Box<dyn Fn(Vec<u8>) -> Result<Vec<u8>, ManyError> + Send + Sync>;
+
+fn sha256(bytes: &[u8]) -> Vec<u8> {
+    sha2::Sha256::digest(bytes).to_vec()
+}
+
+#[derive(Default)]
+struct MockChain {
+    blocks: Vec<Block>,
+    txs: BTreeMap<Vec<u8>, (Vec<u8>, Vec<u8>)>,
+    mempool: Vec<Vec<u8>>,
+}
+
+/// An in-process Tendermint node, to test the ABCI frontend without running
+/// Tendermint.
+///
+/// Blocks are only created by calling [MockTendermintRpc::commit]; broadcast
+/// transactions are kept in a mempool until then. ABCI queries are answered by
+/// a handler, if one was set.
+#[derive(Default)]
+pub struct MockTendermintRpc {
+    chain: Mutex<MockChain>,
+    query_handler: Option<QueryHandler>,
+}
+
+impl std::fmt::Debug for MockTendermintRpc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockTendermintRpc").finish_non_exhaustive()
+    }
+}
+
+impl MockTendermintRpc {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer ABCI queries with `handler`.
+    pub fn with_query_handler(
+        mut self,
+        handler: impl Fn(Vec<u8>) -> Result<Vec<u8>, ManyError> + Send + Sync + 'static,
+    ) -> Self {
+        self.query_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// The transactions broadcast since the last commit.
+    pub fn mempool(&self) -> Vec<Vec<u8>> {
+        self.chain.lock().unwrap().mempool.clone()
+    }
+
+    /// Create a new block containing the transactions in the mempool, with
+    /// `responses` as their results (in the same order). Missing responses
+    /// are empty. Returns the new block.
+    pub fn commit(&self, responses: Vec<Vec<u8>>) -> Block {
+        let mut chain = self.chain.lock().unwrap();
+        let requests = std::mem::take(&mut chain.mempool);
+        let mut responses = responses.into_iter();
+
+        let txs = requests
+            .into_iter()
+            .map(|request| {
+                let hash = sha256(&request);
+                let response = responses.next().unwrap_or_default();
+                chain.txs.insert(hash.clone(), (request, response));
+                Transaction {
+                    id: TransactionIdentifier { hash },
+                    request: None,
+                    response: None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let height = chain.blocks.len() as u64 + 1;
+        let parent = chain
+            .blocks
+            .last()
+            .map_or_else(BlockIdentifier::genesis, |b| b.id.clone());
+        let block = Block {
+            id: BlockIdentifier::new(sha256(&height.to_be_bytes()), height),
+            parent,
+            app_hash: Some(sha256(&chain.txs.len().to_be_bytes())),
+            timestamp: Timestamp::new(height).unwrap(),
+            txs_count: txs.len() as u64,
+            txs,
+        };
+        chain.blocks.push(block.clone());
+        block
+    }
+}
+
+#[async_trait]
+impl TendermintRpc for MockTendermintRpc {
+    async fn status(&self) -> Result<NodeStatus, ManyError> {
+        let chain = self.chain.lock().unwrap();
+        Ok(match chain.blocks.last() {
+            Some(block) => NodeStatus {
+                latest_block: block.id.clone(),
+                latest_app_hash: block.app_hash.clone().unwrap_or_default(),
+            },
+            None => NodeStatus {
+                latest_block: BlockIdentifier::genesis(),
+                latest_app_hash: vec![],
+            },
+        })
+    }
+
+    async fn block(&self, height: u64) -> Result<Block, ManyError> {
+        let chain = self.chain.lock().unwrap();
+        height
+            .checked_sub(1)
+            .and_then(|i| chain.blocks.get(i as usize))
+            .cloned()
+            .ok_or_else(|| ManyError::unknown(format!("Block {height} not found.")))
+    }
+
+    async fn block_by_hash(&self, hash: [u8; 32]) -> Result<Option<Block>, ManyError> {
+        let chain = self.chain.lock().unwrap();
+        Ok(chain
+            .blocks
+            .iter()
+            .find(|b| b.id.hash == hash.as_slice())
+            .cloned())
+    }
+
+    async fn block_search(
+        &self,
+        filter: Option<RangeBlockQuery>,
+        page: u32,
+        per_page: u8,
+        order: SortOrder,
+    ) -> Result<Vec<Block>, ManyError> {
+        let chain = self.chain.lock().unwrap();
+        let mut blocks = match filter {
+            None => chain.blocks.clone(),
+            Some(RangeBlockQuery::Height(range)) => chain
+                .blocks
+                .iter()
+                .filter(|b| range.contains(&b.id.height))
+                .cloned()
+                .collect(),
+            Some(RangeBlockQuery::Time(_)) => return Err(ManyError::unknown("Unimplemented")),
+        };
+        if order == SortOrder::Descending {
+            blocks.reverse();
+        }
+
+        // Pages are 1-indexed, like Tendermint.
+        let skip = (page.max(1) as usize - 1) * per_page as usize;
+        Ok(blocks
+            .into_iter()
+            .skip(skip)
+            .take(per_page as usize)
+            .collect())
+    }
+
+    async fn tx(&self, hash: [u8; 32]) -> Result<Option<(Vec<u8>, Vec<u8>)>, ManyError> {
+        Ok(self.chain.lock().unwrap().txs.get(hash.as_slice()).cloned())
+    }
+
+    async fn broadcast_tx_sync(&self, data: Vec<u8>) -> Result<Vec<u8>, ManyError> {
+        let hash = sha256(&data);
+        self.chain.lock().unwrap().mempool.push(data);
+        Ok(hash)
+    }
+
+    async fn abci_query(&self, data: Vec<u8>) -> Result<Vec<u8>, ManyError> {
+        match &self.query_handler {
+            Some(handler) => handler(data),
+            None => Err(ManyError::unknown("No query handler.")),
+        }
+    }
+}
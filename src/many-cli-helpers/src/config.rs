@@ -0,0 +1,188 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Environment variable pointing to the configuration file. Overrides the
+/// default location.
+pub const MANY_CONFIG_ENV: &str = "MANY_CONFIG";
+
+/// Environment variables used to pass the shared defaults to subcommands and
+/// plugins. The CLI tools read these as defaults for their own flags.
+pub const MANY_SERVER_ENV: &str = "MANY_SERVER";
+pub const MANY_SERVER_ID_ENV: &str = "MANY_SERVER_ID";
+pub const MANY_PEM_ENV: &str = "MANY_PEM";
+
+/// Defaults shared by all the MANY CLI tools.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Defaults {
+    /// URL of the server to connect to.
+    pub server: Option<String>,
+
+    /// Identity of the server.
+    pub server_id: Option<String>,
+
+    /// Name of a key in the keystore, or path to a PEM file.
+    pub key: Option<String>,
+}
+
+/// The configuration file of the MANY CLI tools, in JSON5. For example:
+///
+/// ```json5
+/// {
+///   defaults: { server: "http://localhost:8000", key: "alice" },
+///   aliases: { bal: ["ledger", "balance"] },
+///   keys: { alice: "~/.many/alice.pem" },
+/// }
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(default)]
+pub struct CliConfig {
+    pub defaults: Defaults,
+
+    /// Aliases expand to a list of arguments, e.g. `many bal` can be made to
+    /// run `many ledger balance`.
+    pub aliases: BTreeMap<String, Vec<String>>,
+
+    /// Named PEM files (the keystore).
+    pub keys: BTreeMap<String, PathBuf>,
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+impl CliConfig {
+    /// The location of the configuration file; `$MANY_CONFIG` if set, else
+    /// `many/config.json5` in the user configuration directory.
+    pub fn default_path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os(MANY_CONFIG_ENV) {
+            return Some(PathBuf::from(path));
+        }
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))
+            .map(|dir| dir.join("many").join("config.json5"))
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+        Self::from_str(&content)
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(content: &str) -> Result<Self, String> {
+        json5::from_str(content).map_err(|e| format!("Invalid configuration: {e}"))
+    }
+
+    /// Load the configuration from its default location. A missing file is an
+    /// empty configuration.
+    pub fn load() -> Result<Self, String> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::from_file(path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Resolve a key name from the keystore to its PEM file. Anything that is
+    /// not a key name is considered a path.
+    pub fn resolve_key(&self, name_or_path: &str) -> PathBuf {
+        self.keys.get(name_or_path).map_or_else(
+            || expand_home(Path::new(name_or_path)),
+            |path| expand_home(path),
+        )
+    }
+
+    /// The PEM file of the default key, if any.
+    pub fn default_pem(&self) -> Option<PathBuf> {
+        self.defaults.key.as_deref().map(|k| self.resolve_key(k))
+    }
+
+    /// Replace the first argument (after the binary name) by its alias
+    /// expansion, if it is an alias. Aliases are not expanded recursively.
+    pub fn expand_aliases(&self, args: Vec<OsString>) -> Vec<OsString> {
+        let mut args = args.into_iter();
+        let bin = args.next();
+        let first = args.next();
+
+        let expansion = first
+            .as_ref()
+            .and_then(|f| f.to_str())
+            .and_then(|f| self.aliases.get(f));
+        match expansion {
+            Some(expansion) => bin
+                .into_iter()
+                .chain(expansion.iter().map(OsString::from))
+                .chain(args)
+                .collect(),
+            None => bin.into_iter().chain(first).chain(args).collect(),
+        }
+    }
+
+    /// The environment variables passed to subcommands and plugins, for the
+    /// defaults that are set.
+    pub fn env(&self) -> Vec<(&'static str, OsString)> {
+        let mut env = vec![];
+        if let Some(server) = &self.defaults.server {
+            env.push((MANY_SERVER_ENV, server.into()));
+        }
+        if let Some(server_id) = &self.defaults.server_id {
+            env.push((MANY_SERVER_ID_ENV, server_id.into()));
+        }
+        if let Some(pem) = self.default_pem() {
+            env.push((MANY_PEM_ENV, pem.into()));
+        }
+        env
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> CliConfig {
+        CliConfig::from_str(
+            r#"{
+                defaults: { server: "http://localhost:8001", key: "alice" },
+                aliases: { bal: ["ledger", "balance"] },
+                keys: { alice: "/keys/alice.pem" },
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn parse() {
+        let config = config();
+        assert_eq!(
+            config.defaults.server.as_deref(),
+            Some("http://localhost:8001")
+        );
+        assert_eq!(config.default_pem(), Some(PathBuf::from("/keys/alice.pem")));
+        assert_eq!(config.resolve_key("other.pem"), PathBuf::from("other.pem"));
+        assert_eq!(CliConfig::from_str("{}").unwrap(), CliConfig::default());
+        assert!(CliConfig::from_str("{ aliases: 1 }").is_err());
+    }
+
+    #[test]
+    fn aliases() {
+        let config = config();
+        let args = |a: &[&str]| a.iter().map(OsString::from).collect::<Vec<_>>();
+
+        assert_eq!(
+            config.expand_aliases(args(&["many", "bal", "--pem", "a.pem"])),
+            args(&["many", "ledger", "balance", "--pem", "a.pem"])
+        );
+        assert_eq!(
+            config.expand_aliases(args(&["many", "id", "bal"])),
+            args(&["many", "id", "bal"])
+        );
+        assert_eq!(config.expand_aliases(args(&["many"])), args(&["many"]));
+    }
+}
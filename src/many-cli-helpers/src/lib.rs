@@ -2,7 +2,9 @@ use std::os::unix::ffi::OsStrExt;
 use tracing::metadata::LevelFilter;
 use tracing_subscriber::fmt::Subscriber;
 
+pub mod config;
 pub mod error;
+pub mod plugin;
 
 #[derive(clap::ArgEnum, Clone, Debug)]
 enum LogStrategy {
@@ -0,0 +1,115 @@
+use crate::config::CliConfig;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+
+/// Prefix of the executables that are discovered as plugins; `many-foo` is
+/// run by `many foo`.
+pub const PLUGIN_PREFIX: &str = "many-";
+
+/// Tools shipped with MANY that are available as subcommands, with the name
+/// of their executable. These take precedence over plugins, since some of the
+/// `many-<cmd>` names are taken by servers (e.g. `many-ledger`).
+pub const BUNDLED_TOOLS: &[(&str, &str)] = &[
+    ("ledger", "ledger"),
+    ("kvstore", "kvstore"),
+    ("idstore", "idstore-export"),
+    ("web", "web"),
+];
+
+/// Server executables, which are never considered plugins.
+const SERVERS: &[&str] = &[
+    "many-abci",
+    "many-kvstore",
+    "many-ledger",
+    "many-web",
+    "many-compute",
+];
+
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+/// The directories searched for tools and plugins; the directory of the
+/// current executable first, then `$PATH`.
+fn search_path() -> Vec<PathBuf> {
+    let current = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf));
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    current
+        .into_iter()
+        .chain(std::env::split_paths(&path))
+        .collect()
+}
+
+fn find_executable(name: &str) -> Option<PathBuf> {
+    search_path()
+        .into_iter()
+        .map(|dir| dir.join(name))
+        .find(|p| is_executable(p))
+}
+
+/// Find the executable implementing a subcommand, either a bundled tool or a
+/// `many-<cmd>` plugin.
+pub fn find(command: &str) -> Option<PathBuf> {
+    if let Some((_, bin)) = BUNDLED_TOOLS.iter().find(|(name, _)| *name == command) {
+        return find_executable(bin);
+    }
+
+    let bin = format!("{PLUGIN_PREFIX}{command}");
+    if SERVERS.contains(&bin.as_str()) {
+        return None;
+    }
+    find_executable(&bin)
+}
+
+/// List all subcommands available as bundled tools or plugins, with their
+/// executable.
+pub fn list() -> BTreeMap<String, PathBuf> {
+    let mut commands = BTreeMap::new();
+    for dir in search_path().into_iter().rev() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let file_name = entry.file_name();
+            let name = match file_name.to_str() {
+                Some(name) => name,
+                None => continue,
+            };
+            let command = match name.strip_prefix(PLUGIN_PREFIX) {
+                Some(command) if !command.is_empty() && !SERVERS.contains(&name) => command,
+                _ => match BUNDLED_TOOLS.iter().find(|(_, bin)| *bin == name) {
+                    Some((command, _)) => *command,
+                    None => continue,
+                },
+            };
+            if is_executable(&entry.path()) {
+                // Directories earlier in the search path take precedence.
+                commands.insert(command.to_string(), entry.path());
+            }
+        }
+    }
+    commands
+}
+
+/// Run a plugin with the given arguments, passing the shared configuration
+/// defaults as environment variables. Variables already set are kept.
+pub fn run(path: &Path, args: &[OsString], config: &CliConfig) -> Result<ExitStatus, String> {
+    let mut command = std::process::Command::new(path);
+    command.args(args);
+    for (key, value) in config.env() {
+        if std::env::var_os(key).is_none() {
+            command.env(key, value);
+        }
+    }
+    command
+        .status()
+        .map_err(|e| format!("Could not run {}: {e}", path.display()))
+}
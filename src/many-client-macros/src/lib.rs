@@ -1,3 +1,4 @@
+use inflections::Inflect;
 use proc_macro::TokenStream;
 use proc_macro2::Punct;
 use proc_macro2::TokenStream as TokenStream2;
@@ -41,7 +42,8 @@ pub fn many_client(attr: TokenStream, input: TokenStream) -> TokenStream {
         let func = func.to_token_stream();
         let method: syn::TraitItemFn =
             parse2(func)?;
-        let mut method = method.sig;
+        let blocking_method = method.sig;
+        let mut method = blocking_method.clone();
         method.asyncness = parse_quote! { async };
         let mut args_iter = method.inputs.iter();
         let _self_arg = args_iter.next().ok_or_else(|| syn::Error::new(method.span(), "Should have a &self argument"))?;
@@ -52,10 +54,13 @@ pub fn many_client(attr: TokenStream, input: TokenStream) -> TokenStream {
         } else {
             quote! { () }
         };
+        // Endpoints are named in camel case, e.g. `fn add_features` calls
+        // `account.addFeatures`.
+        let name = method.ident.to_string().to_camel_case();
         let server_method = if let Some(namespace) = namespace {
-            format!("{}.{}", namespace.value(), method.ident)
+            format!("{}.{}", namespace.value(), name)
         } else {
-            format!("{}", method.ident)
+            name
         };
         let server_method: LitStr = parse_quote! { #server_method };
         let q = quote! {
@@ -64,22 +69,33 @@ pub fn many_client(attr: TokenStream, input: TokenStream) -> TokenStream {
                 minicbor::decode(&response).map_err(many_error::ManyError::deserialization_error)
             }
         };
-        Ok(q.into_token_stream())
-    }).try_fold(vec![], |mut acc, curr: syn::Result<TokenStream2>| {
+        let blocking_q = quote! {
+            pub #blocking_method {
+                let response = self.0.call_(#server_method, #args_var)?;
+                minicbor::decode(&response).map_err(many_error::ManyError::deserialization_error)
+            }
+        };
+        Ok((q.into_token_stream(), blocking_q.into_token_stream()))
+    }).try_fold((vec![], vec![]), |mut acc, curr: syn::Result<(TokenStream2, TokenStream2)>| {
         match curr {
-            Ok(c) => acc.push(c),
+            Ok((c, b)) => {
+                acc.0.push(c);
+                acc.1.push(b);
+            }
             Err(e) => return Err(e)
         }
         Ok(acc)
     });
-    let methods_vec = match methods_vec {
+    let (methods_vec, blocking_methods_vec) = match methods_vec {
         Ok(v) => v,
         Err(e) => return e.to_compile_error().into(),
     };
-    let methods_iter = methods_vec.into_iter();
 
-    let methods = TokenStream2::from_iter(methods_iter);
+    let methods = TokenStream2::from_iter(methods_vec);
+    let blocking_methods = TokenStream2::from_iter(blocking_methods_vec);
 
+    // The same client, over the blocking transport. It lives in a `blocking`
+    // module next to the trait, e.g. `ledger::blocking::LedgerClient`.
     let q = quote! {
         impl<I: many_identity::Identity> #r#type<I> {
             #methods
@@ -88,6 +104,28 @@ pub fn many_client(attr: TokenStream, input: TokenStream) -> TokenStream {
                 Self(client)
             }
         }
+
+        #[cfg(feature = "blocking")]
+        pub mod blocking {
+            use super::*;
+
+            #[derive(Debug, Clone)]
+            pub struct #r#type<I: many_identity::Identity>(crate::client::blocking::ManyClient<I>);
+
+            impl<I: many_identity::Identity> #r#type<I> {
+                #blocking_methods
+
+                pub fn new(client: crate::client::blocking::ManyClient<I>) -> Self {
+                    Self(client)
+                }
+            }
+
+            impl<I: many_identity::Identity> From<super::#r#type<I>> for #r#type<I> {
+                fn from(client: super::#r#type<I>) -> Self {
+                    Self(client.0.into())
+                }
+            }
+        }
     };
     q.into()
 }
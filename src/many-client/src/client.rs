@@ -1,9 +1,28 @@
+pub mod account;
 pub mod base;
 pub mod blockchain;
+#[cfg(feature = "blocking")]
 pub mod blocking;
+pub mod echo;
+pub mod events;
+pub mod idstore;
+pub mod kvstore;
 pub mod ledger;
+pub mod multisig;
+pub mod nonce;
+pub mod outbox;
+pub mod retry;
 
+pub use account::AccountClient;
+pub use echo::EchoClient;
+pub use events::EventsClient;
+pub use idstore::IdStoreClient;
+pub use kvstore::KvStoreClient;
 pub use ledger::LedgerClient;
+pub use multisig::MultisigClient;
+pub use nonce::RequestClock;
+pub use outbox::{Outbox, OutboxSubmitter, SubmissionStatus};
+pub use retry::{CircuitBreakerConfig, RetryPolicy};
 
 use coset::{CoseSign1, TaggedCborSerializable};
 use many_error::ManyError;
@@ -11,12 +30,20 @@ use many_identity::verifiers::AnonymousVerifier;
 use many_identity::{verifiers, Address, Identity};
 use many_identity_dsa::CoseKeyVerifier;
 use many_modules::base::Status;
+use many_protocol::delegation::DelegationChain;
 use many_protocol::{
-    encode_cose_sign1_from_request, RequestMessage, RequestMessageBuilder, ResponseMessage,
+    encode_cose_sign1_from_request_batch_compressed, encode_cose_sign1_from_request_compressed,
+    ContentEncoding, RequestMessage, RequestMessageBatch, RequestMessageBuilder, ResponseMessage,
+    ResponseMessageBatch,
 };
+use many_types::attributes::AttributeSet;
+use many_types::priority::{PriorityHint, PRIORITY};
+use many_types::Timestamp;
 use minicbor::Encode;
 use reqwest::{IntoUrl, Url};
+use retry::{Retrier, TransportError};
 use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct ManyClient<I: Identity> {
@@ -24,6 +51,28 @@ pub struct ManyClient<I: Identity> {
     to: Option<Address>,
     url: Url,
     verifier: (AnonymousVerifier, CoseKeyVerifier),
+    retrier: Retrier,
+
+    /// The nonces and timestamps of requests. The timestamps are adjusted by
+    /// the skew measured by [ManyClient::sync_clock].
+    clock: Arc<RequestClock>,
+
+    /// Whether to sync the clock and send a request again when the server
+    /// rejects its timestamp.
+    auto_sync_clock: bool,
+
+    /// The encoding to compress large requests with, if any.
+    request_encoding: Option<ContentEncoding>,
+
+    /// The network requests are for, if any.
+    chain_id: Option<String>,
+
+    /// The identity requests are sent on behalf of, and the delegation chain
+    /// allowing it, if any.
+    delegation: Option<(Address, DelegationChain)>,
+
+    /// The priority hint of the requests.
+    priority: PriorityHint,
 }
 
 impl<I: Identity + Debug> Debug for ManyClient<I> {
@@ -40,63 +89,273 @@ pub async fn send_envelope<S: IntoUrl>(url: S, message: CoseSign1) -> Result<Cos
     let bytes = message
         .to_tagged_vec()
         .map_err(|_| ManyError::internal_server_error())?;
+    post_envelope(url, bytes).await.map_err(|e| e.error)
+}
+
+async fn post_envelope<S: IntoUrl>(url: S, bytes: Vec<u8>) -> Result<CoseSign1, TransportError> {
     let len = bytes.len();
     tracing::debug!("Message length in bytes: {}", len);
 
     let client = reqwest::Client::new();
     tracing::debug!("request {}", hex::encode(&bytes));
-    let response = client
-        .post(url)
-        .body(bytes)
-        .send()
-        .await
-        .map_err(|e| ManyError::unexpected_transport_error(e.to_string()))?;
+    let response = client.post(url).body(bytes).send().await.map_err(|e| {
+        let transient = e.is_connect() || e.is_timeout();
+        TransportError {
+            error: ManyError::unexpected_transport_error(e.to_string()),
+            transient,
+        }
+    })?;
     // The HTTP request handler can return errors if the request invalid.
     match response.status().as_u16() {
         413 => {
-            return Err(ManyError::unexpected_transport_error(format!(
-                "413: Content Too Large : {len} bytes"
-            )))
+            return Err(TransportError::fatal(
+                ManyError::unexpected_transport_error(format!(
+                    "413: Content Too Large : {len} bytes"
+                )),
+            ))
         }
         500 => {
-            return Err(ManyError::unexpected_transport_error(
-                "500: Internal Server Error".to_string(),
+            return Err(TransportError::transient(
+                ManyError::unexpected_transport_error("500: Internal Server Error".to_string()),
+            ))
+        }
+        502..=504 => {
+            return Err(TransportError::transient(
+                ManyError::unexpected_transport_error(format!("{}", response.status())),
             ))
         }
         _ => {}
     }
-    let body = response
-        .bytes()
-        .await
-        .map_err(|e| ManyError::unexpected_transport_error(e.to_string()))?;
+    let body = response.bytes().await.map_err(|e| {
+        TransportError::transient(ManyError::unexpected_transport_error(e.to_string()))
+    })?;
     let bytes = body.to_vec();
     tracing::debug!("Response body length: {}", bytes.len());
     CoseSign1::from_tagged_slice(&bytes)
-        .map_err(|e| ManyError::deserialization_error(e.to_string()))
+        .map_err(|e| TransportError::fatal(ManyError::deserialization_error(e.to_string())))
 }
 
-impl<I: Identity> ManyClient<I> {
-    pub fn new<S: IntoUrl>(url: S, to: Address, identity: I) -> Result<Self, String> {
-        let verifier = (verifiers::AnonymousVerifier, CoseKeyVerifier);
+/// Whether the server rejected a request because of its timestamp.
+fn is_timestamp_rejection(response: &Result<ResponseMessage, ManyError>) -> bool {
+    let code = ManyError::timestamp_out_of_range().code();
+    match response {
+        Ok(ResponseMessage { data: Err(e), .. }) | Err(e) => e.code() == code,
+        _ => false,
+    }
+}
+
+/// Builds a [ManyClient] with a retry policy and an optional circuit breaker.
+/// By default, requests are not retried.
+pub struct ManyClientBuilder<I: Identity> {
+    url: Option<Result<Url, String>>,
+    to: Option<Address>,
+    identity: Option<I>,
+    retry_policy: RetryPolicy,
+    circuit_breaker: Option<CircuitBreakerConfig>,
+    auto_sync_clock: bool,
+    request_encoding: Option<ContentEncoding>,
+    chain_id: Option<String>,
+    delegation: Option<DelegationChain>,
+    priority: PriorityHint,
+}
+
+impl<I: Identity> Default for ManyClientBuilder<I> {
+    fn default() -> Self {
+        Self {
+            url: None,
+            to: None,
+            identity: None,
+            retry_policy: RetryPolicy::none(),
+            circuit_breaker: None,
+            auto_sync_clock: true,
+            request_encoding: None,
+            chain_id: None,
+            delegation: None,
+            priority: PriorityHint::Normal,
+        }
+    }
+}
 
-        Ok(Self {
+impl<I: Identity> ManyClientBuilder<I> {
+    pub fn url<S: IntoUrl>(mut self, url: S) -> Self {
+        self.url = Some(url.into_url().map_err(|e| e.to_string()));
+        self
+    }
+
+    pub fn to(mut self, to: Address) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    pub fn identity(mut self, identity: I) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    pub fn circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = Some(config);
+        self
+    }
+
+    /// Whether to sync the clock and send a request again, once, when the
+    /// server rejects its timestamp. Enabled by default.
+    pub fn auto_sync_clock(mut self, enabled: bool) -> Self {
+        self.auto_sync_clock = enabled;
+        self
+    }
+
+    /// Compress requests larger than the compression threshold with this
+    /// encoding, which the server must support. Requests are not compressed
+    /// by default.
+    pub fn compress_requests(mut self, encoding: ContentEncoding) -> Self {
+        self.request_encoding = Some(encoding);
+        self
+    }
+
+    /// Bind requests to a network, so they cannot be replayed on another one.
+    /// See the `chain_id` of the server status.
+    pub fn chain_id(mut self, chain_id: impl Into<String>) -> Self {
+        self.chain_id = Some(chain_id.into());
+        self
+    }
+
+    /// Send requests on behalf of the root delegator of a delegation chain,
+    /// whose last certificate delegates to the identity of the client.
+    pub fn delegation(mut self, chain: DelegationChain) -> Self {
+        self.delegation = Some(chain);
+        self
+    }
+
+    /// Hint at the priority of the requests, e.g. low for a batch indexer so
+    /// the server answers interactive clients first. Normal by default.
+    pub fn priority(mut self, priority: PriorityHint) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn build(self) -> Result<ManyClient<I>, String> {
+        let url = self.url.ok_or("A URL is required.")??;
+        let identity = self.identity.ok_or("An identity is required.")?;
+        let delegation = match self.delegation {
+            Some(chain) => {
+                let root = chain
+                    .delegations()
+                    .map_err(|e| e.to_string())?
+                    .first()
+                    .map(|d| d.from)
+                    .ok_or("A delegation chain cannot be empty.")?;
+                Some((root, chain))
+            }
+            None => None,
+        };
+
+        Ok(ManyClient {
             identity,
-            to: Some(to),
-            url: url.into_url().map_err(|e| e.to_string())?,
-            verifier,
+            to: self.to,
+            url,
+            verifier: (verifiers::AnonymousVerifier, CoseKeyVerifier),
+            retrier: Retrier::new(self.retry_policy, self.circuit_breaker),
+            clock: Arc::new(RequestClock::new()),
+            auto_sync_clock: self.auto_sync_clock,
+            request_encoding: self.request_encoding,
+            chain_id: self.chain_id,
+            delegation,
+            priority: self.priority,
         })
     }
+}
+
+impl<I: Identity> ManyClient<I> {
+    pub fn new<S: IntoUrl>(url: S, to: Address, identity: I) -> Result<Self, String> {
+        Self::builder().url(url).to(to).identity(identity).build()
+    }
+
+    pub fn builder() -> ManyClientBuilder<I> {
+        ManyClientBuilder::default()
+    }
 
     pub async fn send_message(
         &self,
         message: RequestMessage,
     ) -> Result<ResponseMessage, ManyError> {
-        let cose = encode_cose_sign1_from_request(message, &self.identity).unwrap();
-        let cose_sign1 = send_envelope(self.url.clone(), cose).await?;
+        let cose = encode_cose_sign1_from_request_compressed(
+            message,
+            &self.identity,
+            self.request_encodings(),
+        )
+        .unwrap();
+        self.send_signed(&cose).await
+    }
+
+    /// Send an envelope signed beforehand, e.g. by [Self::sign], as is. Unlike
+    /// [Self::call], it is not sent again with a new timestamp if the server
+    /// rejects it.
+    pub async fn send_signed(&self, envelope: &CoseSign1) -> Result<ResponseMessage, ManyError> {
+        let bytes = envelope
+            .clone()
+            .to_tagged_vec()
+            .map_err(|_| ManyError::internal_server_error())?;
+        let cose_sign1 = self
+            .retrier
+            .run(|| post_envelope(self.url.clone(), bytes.clone()))
+            .await?;
 
         ResponseMessage::decode_and_verify(&cose_sign1, &self.verifier)
     }
 
+    /// Sign a request without sending it, e.g. to add it to an [Outbox].
+    pub fn sign<M, A>(&self, method: M, argument: A) -> Result<CoseSign1, ManyError>
+    where
+        M: Into<String>,
+        A: Encode<()>,
+    {
+        let bytes: Vec<u8> = minicbor::to_vec(argument)
+            .map_err(|e| ManyError::serialization_error(e.to_string()))?;
+        let message = self.request_message(method.into(), &bytes, AttributeSet::new())?;
+        encode_cose_sign1_from_request_compressed(message, &self.identity, self.request_encodings())
+    }
+
+    fn request_encodings(&self) -> &[ContentEncoding] {
+        self.request_encoding
+            .as_ref()
+            .map_or(&[], std::slice::from_ref)
+    }
+
+    /// Send multiple messages in a single envelope. The server executes them in
+    /// order and returns one response per message, in the same order.
+    pub async fn send_batch(
+        &self,
+        messages: RequestMessageBatch,
+    ) -> Result<ResponseMessageBatch, ManyError> {
+        let cose = encode_cose_sign1_from_request_batch_compressed(
+            messages,
+            &self.identity,
+            self.request_encodings(),
+        )?;
+        let bytes = cose
+            .to_tagged_vec()
+            .map_err(|_| ManyError::internal_server_error())?;
+        let cose_sign1 = self
+            .retrier
+            .run(|| post_envelope(self.url.clone(), bytes.clone()))
+            .await?;
+
+        // If the batch itself was refused, the server replies with a single error.
+        match ResponseMessageBatch::decode_and_verify(&cose_sign1, &self.verifier) {
+            Ok(responses) => Ok(responses),
+            Err(batch_err) => match ResponseMessage::decode_and_verify(&cose_sign1, &self.verifier)
+            {
+                Ok(ResponseMessage { data: Err(e), .. }) => Err(e),
+                _ => Err(batch_err),
+            },
+        }
+    }
+
     pub async fn call_raw<M>(
         &self,
         method: M,
@@ -105,27 +364,88 @@ impl<I: Identity> ManyClient<I> {
     where
         M: Into<String>,
     {
-        let mut nonce = [0u8; 16];
-        rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut nonce);
+        self.call_raw_with_attributes(method, argument, AttributeSet::new())
+            .await
+    }
 
+    /// Like [Self::call_raw], with attributes on the request, e.g. to request
+    /// a proof of the response.
+    ///
+    /// If the server rejects the timestamp of the request, the clock is synced
+    /// and the request sent again with a new timestamp and nonce, unless
+    /// disabled with [ManyClientBuilder::auto_sync_clock]. The clock is also
+    /// synced beforehand when its last measure is stale, see
+    /// [RequestClock::is_stale].
+    pub async fn call_raw_with_attributes<M>(
+        &self,
+        method: M,
+        argument: &[u8],
+        attributes: AttributeSet,
+    ) -> Result<ResponseMessage, ManyError>
+    where
+        M: Into<String>,
+    {
+        let method = method.into();
+        if self.auto_sync_clock && self.clock.is_stale() {
+            if let Err(e) = self.sync_clock().await {
+                tracing::debug!("Unable to sync the clock before the request: {e}");
+            }
+        }
+        let message = self.request_message(method.clone(), argument, attributes.clone())?;
+        let response = self.send_message(message).await;
+        if !self.auto_sync_clock || !is_timestamp_rejection(&response) {
+            return response;
+        }
+
+        tracing::debug!("The server rejected the timestamp of the request, syncing the clock.");
+        self.sync_clock().await?;
+        let message = self.request_message(method, argument, attributes)?;
+        self.send_message(message).await
+    }
+
+    fn request_message(
+        &self,
+        method: String,
+        argument: &[u8],
+        attributes: AttributeSet,
+    ) -> Result<RequestMessage, ManyError> {
         let mut builder = RequestMessageBuilder::default();
+        let (from, mut attributes) = match &self.delegation {
+            Some((root, chain)) => {
+                let mut attributes = attributes;
+                attributes.insert(chain.clone().into());
+                (*root, attributes)
+            }
+            None => (self.identity.address(), attributes),
+        };
+        // Requests without a hint have a normal priority.
+        if self.priority != PriorityHint::Normal && !attributes.has_id(PRIORITY.id) {
+            attributes.insert(self.priority.into());
+        }
 
         builder
             .version(1)
-            .from(self.identity.address())
-            .method(method.into())
+            .from(from)
+            .method(method)
             .data(argument.to_vec())
-            .nonce(nonce.to_vec());
+            .nonce(self.clock.next_nonce())
+            .attributes(attributes);
+
+        if let Some(timestamp) = self.clock.timestamp()? {
+            builder.timestamp(timestamp);
+        }
+
+        if let Some(chain_id) = &self.chain_id {
+            builder.chain_id(chain_id.clone());
+        }
 
-        let message: RequestMessage = if let Some(to) = self.to {
+        if let Some(to) = self.to {
             builder.to(to)
         } else {
             &mut builder
         }
         .build()
-        .map_err(|_| ManyError::internal_server_error())?;
-
-        self.send_message(message).await
+        .map_err(|_| ManyError::internal_server_error())
     }
 
     pub async fn call<M, A>(&self, method: M, argument: A) -> Result<ResponseMessage, ManyError>
@@ -139,6 +459,23 @@ impl<I: Identity> ManyClient<I> {
         self.call_raw(method, bytes.as_slice()).await
     }
 
+    pub async fn call_with_attributes<M, A>(
+        &self,
+        method: M,
+        argument: A,
+        attributes: AttributeSet,
+    ) -> Result<ResponseMessage, ManyError>
+    where
+        M: Into<String>,
+        A: Encode<()>,
+    {
+        let bytes: Vec<u8> = minicbor::to_vec(argument)
+            .map_err(|e| ManyError::serialization_error(e.to_string()))?;
+
+        self.call_raw_with_attributes(method, bytes.as_slice(), attributes)
+            .await
+    }
+
     pub async fn call_<M, A>(&self, method: M, argument: A) -> Result<Vec<u8>, ManyError>
     where
         M: Into<String>,
@@ -154,4 +491,52 @@ impl<I: Identity> ManyClient<I> {
             .map_err(|e| ManyError::deserialization_error(e.to_string()))?;
         Ok(status)
     }
+
+    /// The number of seconds the server clock is ahead of ours, as last
+    /// measured by [Self::sync_clock].
+    pub fn clock_skew(&self) -> i64 {
+        self.clock.skew()
+    }
+
+    /// The nonces and timestamps of the requests of this client.
+    pub fn clock(&self) -> &RequestClock {
+        &self.clock
+    }
+
+    /// Measure how far the server clock is from ours, using the time in its
+    /// status, and adjust the timestamp of the next requests accordingly. A
+    /// warning is logged if the skew is large enough that the server would
+    /// have rejected our requests. Returns the skew, in seconds.
+    pub async fn sync_clock(&self) -> Result<i64, ManyError> {
+        // The status is sent directly, as syncing the clock again when it is
+        // rejected would never end.
+        let argument = minicbor::to_vec(()).map_err(ManyError::serialization_error)?;
+        let message = self.request_message("status".to_string(), &argument, AttributeSet::new())?;
+
+        let before = Timestamp::now().secs();
+        let response = self.send_message(message).await?.data?;
+        let after = Timestamp::now().secs();
+
+        let status: Status = minicbor::decode(response.as_slice())
+            .map_err(|e| ManyError::deserialization_error(e.to_string()))?;
+
+        let server_time = status
+            .time
+            .ok_or_else(|| ManyError::required_field_missing("time".to_string()))?;
+        // Assume the server read its clock halfway through the round trip.
+        let local_time = before + (after - before) / 2;
+        let skew = server_time.secs() as i64 - local_time as i64;
+
+        if let Some(timeout) = status.timeout {
+            if skew.unsigned_abs() >= timeout {
+                tracing::warn!(
+                    "The clock is {skew} seconds away from the server's, which only accepts \
+                     {timeout} seconds. The timestamp of requests will be adjusted."
+                );
+            }
+        }
+
+        self.clock.update(skew, status.timeout);
+        Ok(skew)
+    }
 }
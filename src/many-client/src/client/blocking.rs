@@ -1,7 +1,8 @@
 use many_error::ManyError;
 use many_identity::{Address, Identity};
 use many_modules::base::Status;
-use many_protocol::{RequestMessage, ResponseMessage};
+use many_protocol::{RequestMessage, RequestMessageBatch, ResponseMessage, ResponseMessageBatch};
+use many_types::attributes::AttributeSet;
 use minicbor::Encode;
 use reqwest::IntoUrl;
 
@@ -25,16 +26,50 @@ where
     }
 }
 
+impl<I: Identity> From<AsyncClient<I>> for ManyClient<I> {
+    fn from(client: AsyncClient<I>) -> Self {
+        Self { client }
+    }
+}
+
 impl<I: Identity> ManyClient<I> {
     pub fn new<S: IntoUrl>(url: S, to: Address, identity: I) -> Result<Self, String> {
         let client = AsyncClient::new(url, to, identity)?;
         Ok(Self { client })
     }
 
+    /// See [AsyncClient::builder]. The built client converts into a blocking
+    /// one with [ManyClient::from].
+    pub fn builder() -> crate::client::ManyClientBuilder<I> {
+        AsyncClient::builder()
+    }
+
+    /// The async client this client blocks on.
+    pub fn as_async(&self) -> &AsyncClient<I> {
+        &self.client
+    }
+
     pub fn send_message(&self, message: RequestMessage) -> Result<ResponseMessage, ManyError> {
         block_on(self.client.send_message(message))
     }
 
+    pub fn send_batch(
+        &self,
+        messages: RequestMessageBatch,
+    ) -> Result<ResponseMessageBatch, ManyError> {
+        block_on(self.client.send_batch(messages))
+    }
+
+    /// See [AsyncClient::clock_skew].
+    pub fn clock_skew(&self) -> i64 {
+        self.client.clock_skew()
+    }
+
+    /// See [AsyncClient::sync_clock].
+    pub fn sync_clock(&self) -> Result<i64, ManyError> {
+        block_on(self.client.sync_clock())
+    }
+
     pub fn call_raw<M>(&self, method: M, argument: &[u8]) -> Result<ResponseMessage, ManyError>
     where
         M: Into<String>,
@@ -42,6 +77,22 @@ impl<I: Identity> ManyClient<I> {
         block_on(self.client.call_raw(method, argument))
     }
 
+    /// See [AsyncClient::call_raw_with_attributes].
+    pub fn call_raw_with_attributes<M>(
+        &self,
+        method: M,
+        argument: &[u8],
+        attributes: AttributeSet,
+    ) -> Result<ResponseMessage, ManyError>
+    where
+        M: Into<String>,
+    {
+        block_on(
+            self.client
+                .call_raw_with_attributes(method, argument, attributes),
+        )
+    }
+
     pub fn call<M, A>(&self, method: M, argument: A) -> Result<ResponseMessage, ManyError>
     where
         M: Into<String>,
@@ -50,6 +101,22 @@ impl<I: Identity> ManyClient<I> {
         block_on(self.client.call(method, argument))
     }
 
+    pub fn call_with_attributes<M, A>(
+        &self,
+        method: M,
+        argument: A,
+        attributes: AttributeSet,
+    ) -> Result<ResponseMessage, ManyError>
+    where
+        M: Into<String>,
+        A: Encode<()>,
+    {
+        block_on(
+            self.client
+                .call_with_attributes(method, argument, attributes),
+        )
+    }
+
     pub fn call_<M, A>(&self, method: M, argument: A) -> Result<Vec<u8>, ManyError>
     where
         M: Into<String>,
@@ -0,0 +1,16 @@
+use many_client_macros::many_client;
+use many_error::ManyError;
+pub use many_identity::Identity;
+pub use many_modules::base::{EchoArgs, EchoReturns};
+
+use crate::ManyClient;
+
+// The echo endpoint is in the base namespace, but has its own client so the
+// `blocking` module of the base client is not declared twice.
+#[many_client(EchoClient, "base")]
+trait EchoClientTrait {
+    fn echo(&self, args: EchoArgs) -> Result<EchoReturns, ManyError>;
+}
+
+#[derive(Debug, Clone)]
+pub struct EchoClient<I: Identity>(ManyClient<I>);
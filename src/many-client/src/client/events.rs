@@ -0,0 +1,22 @@
+use many_client_macros::many_client;
+use many_error::ManyError;
+pub use many_identity::Identity;
+pub use many_modules::events::{
+    ArchiveInfoReturn, InfoArgs, InfoReturn, ListArgs, ListReturns, PollArgs, PollReturn,
+    SubscribeArgs, SubscribeReturn, UnsubscribeArgs, UnsubscribeReturn,
+};
+
+use crate::ManyClient;
+
+#[many_client(EventsClient, "events")]
+trait EventsClientTrait {
+    fn info(&self, args: InfoArgs) -> Result<InfoReturn, ManyError>;
+    fn list(&self, args: ListArgs) -> Result<ListReturns, ManyError>;
+    fn subscribe(&self, args: SubscribeArgs) -> Result<SubscribeReturn, ManyError>;
+    fn poll(&self, args: PollArgs) -> Result<PollReturn, ManyError>;
+    fn unsubscribe(&self, args: UnsubscribeArgs) -> Result<UnsubscribeReturn, ManyError>;
+    fn archive_info(&self) -> Result<ArchiveInfoReturn, ManyError>;
+}
+
+#[derive(Debug, Clone)]
+pub struct EventsClient<I: Identity>(ManyClient<I>);
@@ -0,0 +1,23 @@
+use many_client_macros::many_client;
+use many_error::ManyError;
+pub use many_identity::Identity;
+pub use many_modules::idstore::{
+    GetFromAddressArgs, GetFromRecallPhraseArgs, GetReturns, StatsArgs, StatsReturns, StoreArgs,
+    StoreReturns,
+};
+
+use crate::ManyClient;
+
+#[many_client(IdStoreClient, "idstore")]
+trait IdStoreClientTrait {
+    fn store(&self, args: StoreArgs) -> Result<StoreReturns, ManyError>;
+    fn get_from_recall_phrase(
+        &self,
+        args: GetFromRecallPhraseArgs,
+    ) -> Result<GetReturns, ManyError>;
+    fn get_from_address(&self, args: GetFromAddressArgs) -> Result<GetReturns, ManyError>;
+    fn stats(&self, args: StatsArgs) -> Result<StatsReturns, ManyError>;
+}
+
+#[derive(Debug, Clone)]
+pub struct IdStoreClient<I: Identity>(ManyClient<I>);
@@ -0,0 +1,28 @@
+use many_client_macros::many_client;
+use many_error::ManyError;
+pub use many_identity::Identity;
+pub use many_modules::kvstore::list::{ListArgs, ListReturns};
+pub use many_modules::kvstore::{
+    CasArgs, CasReturn, DisableArgs, DisableReturn, GetArgs, GetReturns, InfoReturns, MultiGetArgs,
+    MultiGetReturns, MultiPutArgs, MultiPutReturn, PutArgs, PutReturn, QueryArgs, QueryReturns,
+    TransferArgs, TransferReturn,
+};
+
+use crate::ManyClient;
+
+#[many_client(KvStoreClient, "kvstore")]
+trait KvStoreClientTrait {
+    fn info(&self) -> Result<InfoReturns, ManyError>;
+    fn get(&self, args: GetArgs) -> Result<GetReturns, ManyError>;
+    fn query(&self, args: QueryArgs) -> Result<QueryReturns, ManyError>;
+    fn list(&self, args: ListArgs) -> Result<ListReturns, ManyError>;
+    fn multi_get(&self, args: MultiGetArgs) -> Result<MultiGetReturns, ManyError>;
+    fn put(&self, args: PutArgs) -> Result<PutReturn, ManyError>;
+    fn disable(&self, args: DisableArgs) -> Result<DisableReturn, ManyError>;
+    fn cas(&self, args: CasArgs) -> Result<CasReturn, ManyError>;
+    fn multi_put(&self, args: MultiPutArgs) -> Result<MultiPutReturn, ManyError>;
+    fn transfer(&self, args: TransferArgs) -> Result<TransferReturn, ManyError>;
+}
+
+#[derive(Debug, Clone)]
+pub struct KvStoreClient<I: Identity>(ManyClient<I>);
@@ -0,0 +1,49 @@
+use many_client_macros::many_client;
+use many_error::ManyError;
+pub use many_identity::Identity;
+pub use many_modules::account::features::multisig::{
+    ApproveArgs, ApproveReturn, ExecuteArgs, InfoArgs, InfoReturn, ListNotificationsArgs,
+    ListNotificationsReturn, ListPendingArgs, ListPendingReturn, ProvideNotificationEndpointArgs,
+    ProvideNotificationEndpointReturn, RegisterNotificationArgs, RegisterNotificationReturn,
+    ResultArgs, ResultReturn, RevokeArgs, RevokeReturn, SetDefaultsArgs, SetDefaultsReturn,
+    SubmitTransactionArgs, SubmitTransactionReturn, UnregisterNotificationArgs,
+    UnregisterNotificationReturn, WithdrawArgs, WithdrawReturn,
+};
+pub use many_protocol::ResponseMessage;
+
+use crate::ManyClient;
+
+#[many_client(MultisigClient, "account")]
+trait MultisigClientTrait {
+    fn multisig_submit_transaction(
+        &self,
+        args: SubmitTransactionArgs,
+    ) -> Result<SubmitTransactionReturn, ManyError>;
+    fn multisig_info(&self, args: InfoArgs) -> Result<InfoReturn, ManyError>;
+    fn multisig_set_defaults(&self, args: SetDefaultsArgs) -> Result<SetDefaultsReturn, ManyError>;
+    fn multisig_approve(&self, args: ApproveArgs) -> Result<ApproveReturn, ManyError>;
+    fn multisig_revoke(&self, args: RevokeArgs) -> Result<RevokeReturn, ManyError>;
+    fn multisig_execute(&self, args: ExecuteArgs) -> Result<ResponseMessage, ManyError>;
+    fn multisig_withdraw(&self, args: WithdrawArgs) -> Result<WithdrawReturn, ManyError>;
+    fn multisig_result(&self, args: ResultArgs) -> Result<ResultReturn, ManyError>;
+    fn multisig_register_notification(
+        &self,
+        args: RegisterNotificationArgs,
+    ) -> Result<RegisterNotificationReturn, ManyError>;
+    fn multisig_provide_notification_endpoint(
+        &self,
+        args: ProvideNotificationEndpointArgs,
+    ) -> Result<ProvideNotificationEndpointReturn, ManyError>;
+    fn multisig_unregister_notification(
+        &self,
+        args: UnregisterNotificationArgs,
+    ) -> Result<UnregisterNotificationReturn, ManyError>;
+    fn multisig_list_notifications(
+        &self,
+        args: ListNotificationsArgs,
+    ) -> Result<ListNotificationsReturn, ManyError>;
+    fn multisig_list_pending(&self, args: ListPendingArgs) -> Result<ListPendingReturn, ManyError>;
+}
+
+#[derive(Debug, Clone)]
+pub struct MultisigClient<I: Identity>(ManyClient<I>);
@@ -0,0 +1,141 @@
+use many_error::ManyError;
+use many_types::Timestamp;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+
+/// The nonces and timestamps of the requests of a client.
+///
+/// Nonces are a random prefix, drawn when the client is created, followed by
+/// a counter. They never repeat for a client, and increase with every request.
+///
+/// Timestamps are adjusted by the skew between our clock and the server's,
+/// as measured from its `status`, along with the window of time the server
+/// accepts requests in.
+#[derive(Debug)]
+pub struct RequestClock {
+    prefix: [u8; 8],
+    counter: AtomicU64,
+    skew: AtomicI64,
+
+    /// The `timeout` of the server status, or 0 if unknown.
+    timeout: AtomicU64,
+
+    /// When the skew was last measured, in seconds of our clock.
+    synced_at: AtomicU64,
+}
+
+impl Default for RequestClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RequestClock {
+    pub fn new() -> Self {
+        let mut prefix = [0u8; 8];
+        rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut prefix);
+        Self {
+            prefix,
+            counter: AtomicU64::new(0),
+            skew: AtomicI64::new(0),
+            timeout: AtomicU64::new(0),
+            synced_at: AtomicU64::new(0),
+        }
+    }
+
+    /// The nonce of the next request.
+    pub fn next_nonce(&self) -> Vec<u8> {
+        let counter = self.counter.fetch_add(1, Ordering::Relaxed);
+        [self.prefix.as_slice(), &counter.to_be_bytes()].concat()
+    }
+
+    /// The number of seconds the server clock is ahead of ours.
+    pub fn skew(&self) -> i64 {
+        self.skew.load(Ordering::Relaxed)
+    }
+
+    /// The number of seconds around its own time the server accepts
+    /// requests in, if known.
+    pub fn timeout(&self) -> Option<u64> {
+        match self.timeout.load(Ordering::Relaxed) {
+            0 => None,
+            timeout => Some(timeout),
+        }
+    }
+
+    pub fn update(&self, skew: i64, timeout: Option<u64>) {
+        self.skew.store(skew, Ordering::Relaxed);
+        self.timeout.store(timeout.unwrap_or(0), Ordering::Relaxed);
+        self.synced_at
+            .store(Timestamp::now().secs(), Ordering::Relaxed);
+    }
+
+    /// Whether the skew should be measured again before the next request.
+    /// Clocks drift, so the skew is refreshed once half of the window the
+    /// server accepts requests in has passed since it was measured. Without a
+    /// known window, the skew is only measured when a request is rejected.
+    pub fn is_stale(&self) -> bool {
+        match self.timeout() {
+            Some(timeout) => {
+                let elapsed = Timestamp::now()
+                    .secs()
+                    .saturating_sub(self.synced_at.load(Ordering::Relaxed));
+                elapsed >= timeout / 2
+            }
+            None => false,
+        }
+    }
+
+    /// The timestamp of a request sent now, or `None` if our clock matches the
+    /// server's and the request can use the default timestamp.
+    pub fn timestamp(&self) -> Result<Option<Timestamp>, ManyError> {
+        match self.skew() {
+            0 => Ok(None),
+            skew => Ok(Some(Timestamp::new(
+                Timestamp::now().secs().saturating_add_signed(skew),
+            )?)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn monotonic_nonces() {
+        let clock = RequestClock::new();
+        let first = clock.next_nonce();
+        let second = clock.next_nonce();
+        assert_eq!(first.len(), 16);
+        assert_eq!(first[..8], second[..8]);
+        assert!(second > first);
+
+        // Another client uses another prefix.
+        assert_ne!(RequestClock::new().next_nonce()[..8], first[..8]);
+    }
+
+    #[test]
+    fn timestamps() {
+        let clock = RequestClock::new();
+        assert_eq!(clock.timestamp().unwrap(), None);
+        assert_eq!(clock.timeout(), None);
+
+        clock.update(-100, Some(300));
+        let now = Timestamp::now().secs();
+        let timestamp = clock.timestamp().unwrap().unwrap().secs();
+        assert!(timestamp <= now - 100 && timestamp >= now - 101);
+        assert_eq!(clock.timeout(), Some(300));
+    }
+
+    #[test]
+    fn staleness() {
+        let clock = RequestClock::new();
+        assert!(!clock.is_stale());
+
+        clock.update(10, Some(300));
+        assert!(!clock.is_stale());
+
+        clock.update(10, Some(1));
+        assert!(clock.is_stale());
+    }
+}
@@ -0,0 +1,436 @@
+use crate::client::ManyClient;
+use coset::{CoseSign1, TaggedCborSerializable};
+use many_error::ManyError;
+use many_identity::Identity;
+use many_modules::r#async::attributes::AsyncAttribute;
+use many_modules::r#async::{AsyncToken, StatusArgs, StatusReturn};
+use many_protocol::{RequestMessage, ResponseMessage};
+use many_types::Timestamp;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const ENTRY_EXTENSION: &str = "cbor";
+const TEMP_EXTENSION: &str = "tmp";
+
+/// Where a request of the outbox is in its submission.
+#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+pub enum SubmissionStatus {
+    /// Not sent yet, or the server could not be reached.
+    #[n(0)]
+    Queued,
+
+    /// Accepted by the server, which executes it asynchronously.
+    #[n(1)]
+    Pending(#[n(0)] AsyncToken),
+
+    /// The server answered. The response can still be an error, e.g. if the
+    /// request was invalid.
+    #[n(2)]
+    Done(#[n(0)] ResponseMessage),
+
+    /// No answer could be obtained from the server, e.g. it could not be
+    /// reached after the maximum number of attempts, or the async token of
+    /// the request expired.
+    #[n(3)]
+    Failed(#[n(0)] ManyError),
+}
+
+impl SubmissionStatus {
+    /// Whether there is nothing left to do for the request.
+    pub fn is_final(&self) -> bool {
+        matches!(self, Self::Done(_) | Self::Failed(_))
+    }
+
+    fn from_response(response: ResponseMessage) -> Self {
+        match (&response.data, response.attributes.get::<AsyncAttribute>()) {
+            (Ok(data), Ok(AsyncAttribute { token })) if data.is_empty() => Self::Pending(token),
+            _ => Self::Done(response),
+        }
+    }
+}
+
+/// A signed request in the outbox.
+#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct OutboxEntry {
+    /// The tagged COSE envelope of the request.
+    #[n(0)]
+    pub envelope: ByteVec,
+
+    #[n(1)]
+    pub status: SubmissionStatus,
+
+    /// The number of times the envelope was sent.
+    #[n(2)]
+    pub attempts: u32,
+
+    #[n(3)]
+    pub enqueued: Timestamp,
+
+    #[n(4)]
+    pub last_attempt: Option<Timestamp>,
+}
+
+impl OutboxEntry {
+    pub fn envelope(&self) -> Result<CoseSign1, ManyError> {
+        CoseSign1::from_tagged_slice(&self.envelope)
+            .map_err(|e| ManyError::deserialization_error(e.to_string()))
+    }
+}
+
+/// A durable queue of signed requests, kept in a directory with one file per
+/// request, so they survive restarts of the application.
+///
+/// Requests are identified by the hex of their intent hash, as used by
+/// `events.findByIntentHash`. Enqueueing the same envelope twice is a no-op.
+/// The envelope is stored as signed and is always sent as is, so a server
+/// with a request cache executes it at most once. Depending on its replay
+/// policy, sending it again after a restart returns the original response or
+/// a duplicated message error.
+///
+/// Only one process should use a directory at a time.
+#[derive(Clone, Debug)]
+pub struct Outbox {
+    dir: PathBuf,
+    lock: Arc<Mutex<()>>,
+}
+
+fn io_error(e: std::io::Error) -> ManyError {
+    ManyError::unknown(format!("Outbox I/O error: {e}"))
+}
+
+impl Outbox {
+    /// Open the outbox in `dir`, creating the directory if needed.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, ManyError> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir).map_err(io_error)?;
+        Ok(Self {
+            dir,
+            lock: Arc::new(Mutex::new(())),
+        })
+    }
+
+    fn path(&self, id: &str) -> Result<PathBuf, ManyError> {
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ManyError::unknown(format!(
+                "Invalid outbox entry id '{id}'."
+            )));
+        }
+        Ok(self.dir.join(id).with_extension(ENTRY_EXTENSION))
+    }
+
+    /// Add a signed request to the outbox and return its id.
+    pub fn enqueue(&self, envelope: &CoseSign1) -> Result<String, ManyError> {
+        let message = RequestMessage::try_from(envelope)?;
+        let id = hex::encode(
+            message
+                .intent_hash()
+                .map_err(ManyError::serialization_error)?,
+        );
+        let envelope = envelope
+            .clone()
+            .to_tagged_vec()
+            .map_err(|e| ManyError::serialization_error(e.to_string()))?;
+
+        let _guard = self.lock.lock().unwrap();
+        if self.path(&id)?.exists() {
+            return Ok(id);
+        }
+        self.write(
+            &id,
+            &OutboxEntry {
+                envelope: envelope.into(),
+                status: SubmissionStatus::Queued,
+                attempts: 0,
+                enqueued: Timestamp::now(),
+                last_attempt: None,
+            },
+        )?;
+        Ok(id)
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<OutboxEntry>, ManyError> {
+        let _guard = self.lock.lock().unwrap();
+        self.read(id)
+    }
+
+    pub fn status(&self, id: &str) -> Result<Option<SubmissionStatus>, ManyError> {
+        Ok(self.get(id)?.map(|entry| entry.status))
+    }
+
+    /// All the entries of the outbox with their ids, oldest first.
+    pub fn entries(&self) -> Result<Vec<(String, OutboxEntry)>, ManyError> {
+        let _guard = self.lock.lock().unwrap();
+        let mut entries = Vec::new();
+        for file in std::fs::read_dir(&self.dir).map_err(io_error)? {
+            let path = file.map_err(io_error)?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(ENTRY_EXTENSION) {
+                continue;
+            }
+            let id = match path.file_stem().and_then(|s| s.to_str()) {
+                Some(id) => id.to_string(),
+                None => continue,
+            };
+            if let Some(entry) = self.read(&id)? {
+                entries.push((id, entry));
+            }
+        }
+        entries.sort_by(|(a_id, a), (b_id, b)| (a.enqueued, a_id).cmp(&(b.enqueued, b_id)));
+        Ok(entries)
+    }
+
+    /// Remove an entry, e.g. once its response was handled. Returns whether
+    /// the entry existed.
+    pub fn remove(&self, id: &str) -> Result<bool, ManyError> {
+        let _guard = self.lock.lock().unwrap();
+        match std::fs::remove_file(self.path(id)?) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(io_error(e)),
+        }
+    }
+
+    fn update(&self, id: &str, entry: &OutboxEntry) -> Result<(), ManyError> {
+        let _guard = self.lock.lock().unwrap();
+        self.write(id, entry)
+    }
+
+    fn read(&self, id: &str) -> Result<Option<OutboxEntry>, ManyError> {
+        match std::fs::read(self.path(id)?) {
+            Ok(bytes) => minicbor::decode(&bytes)
+                .map(Some)
+                .map_err(ManyError::deserialization_error),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(io_error(e)),
+        }
+    }
+
+    /// Write an entry to a temporary file first, so a crash never leaves a
+    /// partially written entry.
+    fn write(&self, id: &str, entry: &OutboxEntry) -> Result<(), ManyError> {
+        let path = self.path(id)?;
+        let temp = path.with_extension(TEMP_EXTENSION);
+        let bytes =
+            minicbor::to_vec(entry).map_err(|e| ManyError::serialization_error(e.to_string()))?;
+        std::fs::write(&temp, bytes).map_err(io_error)?;
+        std::fs::rename(&temp, &path).map_err(io_error)
+    }
+}
+
+/// Sends the requests of an [Outbox] and follows their async tokens until
+/// the server answers.
+pub struct OutboxSubmitter<I: Identity> {
+    outbox: Outbox,
+    client: ManyClient<I>,
+    max_attempts: u32,
+    interval: Duration,
+}
+
+impl<I: Identity> OutboxSubmitter<I> {
+    pub fn new(outbox: Outbox, client: ManyClient<I>) -> Self {
+        Self {
+            outbox,
+            client,
+            max_attempts: 10,
+            interval: Duration::from_secs(1),
+        }
+    }
+
+    /// The number of times a request is sent before it is marked as failed.
+    /// Each attempt is retried according to the retry policy of the client.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// The time to wait between passes of [Self::run].
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Send the queued requests and check the pending ones, once. Returns
+    /// the number of requests not final yet.
+    pub async fn submit(&self) -> Result<usize, ManyError> {
+        let mut remaining = 0;
+        for (id, mut entry) in self.outbox.entries()? {
+            if entry.status.is_final() {
+                continue;
+            }
+            self.step(&id, &mut entry).await?;
+            if !entry.status.is_final() {
+                remaining += 1;
+            }
+        }
+        Ok(remaining)
+    }
+
+    /// Submit the requests of the outbox forever, every interval.
+    pub async fn run(&self) {
+        loop {
+            if let Err(e) = self.submit().await {
+                tracing::warn!("Could not submit the outbox: {e}");
+            }
+            tokio::time::sleep(self.interval).await;
+        }
+    }
+
+    async fn step(&self, id: &str, entry: &mut OutboxEntry) -> Result<(), ManyError> {
+        match entry.status.clone() {
+            SubmissionStatus::Queued => {
+                // Record the attempt before sending, so it is counted even if
+                // the application stops before the server answers.
+                entry.attempts += 1;
+                entry.last_attempt = Some(Timestamp::now());
+                self.outbox.update(id, entry)?;
+
+                match self.client.send_signed(&entry.envelope()?).await {
+                    Ok(response) => entry.status = SubmissionStatus::from_response(response),
+                    Err(e) if entry.attempts >= self.max_attempts => {
+                        entry.status = SubmissionStatus::Failed(e)
+                    }
+                    Err(e) => {
+                        tracing::debug!("Could not send outbox entry {id}: {e}");
+                        return Ok(());
+                    }
+                }
+            }
+            SubmissionStatus::Pending(token) => {
+                let status = match self.async_status(token).await {
+                    Ok(status) => status,
+                    Err(e) => {
+                        tracing::debug!("Could not check outbox entry {id}: {e}");
+                        return Ok(());
+                    }
+                };
+                entry.status = match status {
+                    StatusReturn::Done { response } => {
+                        let payload = response.payload.ok_or_else(|| {
+                            ManyError::deserialization_error(
+                                "Empty payload. Expected ResponseMessage.",
+                            )
+                        })?;
+                        SubmissionStatus::from_response(
+                            minicbor::decode(&payload).map_err(ManyError::deserialization_error)?,
+                        )
+                    }
+                    StatusReturn::Expired => SubmissionStatus::Failed(ManyError::unknown(
+                        "The async token expired before the response was read.",
+                    )),
+                    StatusReturn::Unknown => SubmissionStatus::Failed(ManyError::unknown(
+                        "The async token is unknown to the server.",
+                    )),
+                    StatusReturn::Queued | StatusReturn::Processing => return Ok(()),
+                };
+            }
+            SubmissionStatus::Done(_) | SubmissionStatus::Failed(_) => return Ok(()),
+        }
+        self.outbox.update(id, entry)
+    }
+
+    async fn async_status(&self, token: AsyncToken) -> Result<StatusReturn, ManyError> {
+        let data = self
+            .client
+            .call("async.status", StatusArgs { token })
+            .await?
+            .data?;
+        minicbor::decode(&data).map_err(ManyError::deserialization_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use many_identity::{Address, AnonymousIdentity};
+    use many_protocol::{encode_cose_sign1_from_request, RequestMessageBuilder};
+    use many_types::attributes::AttributeSet;
+
+    fn envelope(nonce: u8) -> CoseSign1 {
+        let message = RequestMessageBuilder::default()
+            .method("ledger.send".to_string())
+            .nonce(vec![nonce])
+            .build()
+            .unwrap();
+        encode_cose_sign1_from_request(message, &AnonymousIdentity).unwrap()
+    }
+
+    #[test]
+    fn enqueue() {
+        let dir = tempfile::tempdir().unwrap();
+        let outbox = Outbox::open(dir.path()).unwrap();
+
+        let id = outbox.enqueue(&envelope(1)).unwrap();
+        assert_eq!(outbox.enqueue(&envelope(1)).unwrap(), id);
+        let other = outbox.enqueue(&envelope(2)).unwrap();
+        assert_ne!(other, id);
+
+        let entries = outbox.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        let entry = outbox.get(&id).unwrap().unwrap();
+        assert_eq!(entry.status, SubmissionStatus::Queued);
+        assert_eq!(entry.attempts, 0);
+        assert_eq!(
+            RequestMessage::try_from(entry.envelope().unwrap())
+                .unwrap()
+                .nonce,
+            Some(vec![1])
+        );
+
+        assert!(outbox.remove(&other).unwrap());
+        assert!(!outbox.remove(&other).unwrap());
+        assert_eq!(outbox.status(&other).unwrap(), None);
+        assert!(outbox.get("../outside").is_err());
+    }
+
+    #[test]
+    fn survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let id = {
+            let outbox = Outbox::open(dir.path()).unwrap();
+            let id = outbox.enqueue(&envelope(1)).unwrap();
+            let mut entry = outbox.get(&id).unwrap().unwrap();
+            entry.status = SubmissionStatus::Pending(vec![1, 2, 3].into());
+            entry.attempts = 1;
+            outbox.update(&id, &entry).unwrap();
+            id
+        };
+
+        let outbox = Outbox::open(dir.path()).unwrap();
+        let entries = outbox.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, id);
+        assert_eq!(
+            entries[0].1.status,
+            SubmissionStatus::Pending(vec![1, 2, 3].into())
+        );
+        assert_eq!(entries[0].1.attempts, 1);
+    }
+
+    #[test]
+    fn status_from_response() {
+        let response = ResponseMessage {
+            from: Address::anonymous(),
+            data: Ok(vec![1]),
+            ..Default::default()
+        };
+        assert_eq!(
+            SubmissionStatus::from_response(response.clone()),
+            SubmissionStatus::Done(response)
+        );
+
+        let token: AsyncToken = vec![4, 5].into();
+        let response = ResponseMessage {
+            from: Address::anonymous(),
+            data: Ok(vec![]),
+            attributes: AttributeSet::from_iter([AsyncAttribute::new(token.clone()).into()]),
+            ..Default::default()
+        };
+        assert_eq!(
+            SubmissionStatus::from_response(response),
+            SubmissionStatus::Pending(token)
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use many_error::ManyError;
+use rand::Rng;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How transient transport failures (connection errors, timeouts, 5xx
+/// responses) are retried. The delay between attempts grows exponentially
+/// up to `max_backoff`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Number of retries after the first attempt.
+    pub max_retries: u32,
+
+    /// Delay before the first retry.
+    pub initial_backoff: Duration,
+
+    /// Maximum delay between two attempts.
+    pub max_backoff: Duration,
+
+    /// Factor applied to the delay after every retry.
+    pub multiplier: u32,
+
+    /// Randomize half of the delay so clients don't retry in lockstep.
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2,
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// The delay before the `retry`th retry (starting at 0), without jitter.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(self.multiplier.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+
+    fn delay(&self, retry: u32) -> Duration {
+        let backoff = self.backoff(retry);
+        if !self.jitter {
+            return backoff;
+        }
+
+        let half = backoff / 2;
+        let jitter = rand::thread_rng().gen_range(0..=half.as_millis() as u64);
+        half + Duration::from_millis(jitter)
+    }
+}
+
+/// Stop sending requests for `reset_timeout` after `failure_threshold`
+/// consecutive transient failures. Once the timeout elapsed, the next request
+/// is let through; the circuit closes again if it succeeds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    pub failure_threshold: u32,
+    pub reset_timeout: Duration,
+}
+
+#[derive(Debug, Default)]
+struct CircuitState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+/// The circuit breaker state is shared between clones of a client.
+#[derive(Clone, Debug)]
+struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: Arc<Mutex<CircuitState>>,
+}
+
+impl CircuitBreaker {
+    fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            state: Default::default(),
+        }
+    }
+
+    fn check(&self) -> Result<(), ManyError> {
+        let state = self.state.lock().unwrap();
+        match state.open_until {
+            Some(until) if Instant::now() < until => {
+                Err(ManyError::unexpected_transport_error(format!(
+                    "Circuit breaker open after {} consecutive failures.",
+                    state.consecutive_failures
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn success(&self) {
+        *self.state.lock().unwrap() = CircuitState::default();
+    }
+
+    fn failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        if state.consecutive_failures >= self.config.failure_threshold {
+            state.open_until = Some(Instant::now() + self.config.reset_timeout);
+        }
+    }
+}
+
+/// A transport failure, and whether it is worth trying again.
+#[derive(Debug)]
+pub(crate) struct TransportError {
+    pub error: ManyError,
+    pub transient: bool,
+}
+
+impl TransportError {
+    pub fn transient(error: ManyError) -> Self {
+        Self {
+            error,
+            transient: true,
+        }
+    }
+
+    pub fn fatal(error: ManyError) -> Self {
+        Self {
+            error,
+            transient: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Retrier {
+    policy: RetryPolicy,
+    circuit_breaker: Option<CircuitBreaker>,
+}
+
+impl Retrier {
+    pub fn new(policy: RetryPolicy, circuit_breaker: Option<CircuitBreakerConfig>) -> Self {
+        Self {
+            policy,
+            circuit_breaker: circuit_breaker.map(CircuitBreaker::new),
+        }
+    }
+
+    /// Run `f` until it succeeds, fails with a non-transient error, or the
+    /// policy runs out of retries.
+    pub async fn run<F, Fut, T>(&self, mut f: F) -> Result<T, ManyError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, TransportError>>,
+    {
+        let mut retry = 0;
+        loop {
+            if let Some(cb) = &self.circuit_breaker {
+                cb.check()?;
+            }
+
+            match f().await {
+                Ok(value) => {
+                    if let Some(cb) = &self.circuit_breaker {
+                        cb.success();
+                    }
+                    return Ok(value);
+                }
+                Err(TransportError { error, transient }) => {
+                    if !transient {
+                        return Err(error);
+                    }
+                    if let Some(cb) = &self.circuit_breaker {
+                        cb.failure();
+                    }
+                    if retry >= self.policy.max_retries {
+                        return Err(error);
+                    }
+
+                    let delay = self.policy.delay(retry);
+                    tracing::debug!("Transport error, retrying in {delay:?}: {error}");
+                    tokio::time::sleep(delay).await;
+                    retry += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn no_delay(max_retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            initial_backoff: Duration::ZERO,
+            jitter: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn backoff() {
+        let policy = RetryPolicy {
+            jitter: false,
+            ..Default::default()
+        };
+        assert_eq!(policy.backoff(0), Duration::from_millis(200));
+        assert_eq!(policy.backoff(1), Duration::from_millis(400));
+        assert_eq!(policy.backoff(3), Duration::from_millis(1600));
+        assert_eq!(policy.backoff(10), Duration::from_secs(10));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(10));
+
+        let policy = RetryPolicy::default();
+        for retry in 0..5 {
+            let delay = policy.delay(retry);
+            assert!(delay >= policy.backoff(retry) / 2);
+            assert!(delay <= policy.backoff(retry));
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors() {
+        let calls = &AtomicU32::new(0);
+        let retrier = Retrier::new(no_delay(3), None);
+
+        let result = retrier
+            .run(move || async move {
+                if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                    Err(TransportError::transient(ManyError::unknown("flaky")))
+                } else {
+                    Ok(42)
+                }
+            })
+            .await;
+        assert_eq!(result, Ok(42));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        calls.store(0, Ordering::SeqCst);
+        let result: Result<(), _> = retrier
+            .run(move || async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(TransportError::transient(ManyError::unknown("down")))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_fatal_errors() {
+        let calls = &AtomicU32::new(0);
+        let retrier = Retrier::new(no_delay(3), None);
+
+        let result: Result<(), _> = retrier
+            .run(move || async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(TransportError::fatal(ManyError::unknown("bad request")))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn circuit_breaker() {
+        let calls = &AtomicU32::new(0);
+        let retrier = Retrier::new(
+            no_delay(0),
+            Some(CircuitBreakerConfig {
+                failure_threshold: 2,
+                reset_timeout: Duration::from_millis(50),
+            }),
+        );
+        let failing = move || async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(TransportError::transient(ManyError::unknown("down")))
+        };
+
+        assert!(retrier.run(failing).await.is_err());
+        assert!(retrier.run(failing).await.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // The circuit is open, requests fail without being sent.
+        assert!(retrier.run(failing).await.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // After the timeout, one request goes through and closes the circuit.
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(retrier.run(move || async move { Ok(()) }).await, Ok(()));
+        assert!(retrier.run(failing).await.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -66,6 +66,12 @@ struct Opts {
     #[clap(long)]
     allow_addrs: Option<PathBuf>,
 
+    /// Endpoints to disable on this server, e.g. `idstore.store`. Requests to
+    /// them are refused and they are not listed by `endpoints`.
+    /// Multiple occurences of this argument can be given.
+    #[clap(long = "disable-endpoint")]
+    disable_endpoint: Vec<String>,
+
     #[clap(flatten)]
     akash_opt: AkashOpt,
 }
@@ -80,6 +86,7 @@ fn main() {
         clean,
         allow_origin,
         allow_addrs,
+        disable_endpoint,
         akash_opt,
         ..
     } = Opts::parse();
@@ -156,6 +163,10 @@ fn main() {
         } else {
             s.add_module(compute_module);
         }
+
+        for method in disable_endpoint {
+            s.disable_endpoint(method);
+        }
     }
     let mut many_server = HttpServer::new(many);
 
@@ -0,0 +1,172 @@
+//! Stable numeric error codes, and the ranges of codes reserved for each
+//! module.
+//!
+//! Clients should match errors on their codes, which are part of the
+//! protocol, rather than on their messages, which might change.
+pub use crate::error::ERROR_CODES;
+
+/// An error code defined by this crate.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct NamedCode {
+    pub code: i64,
+    /// The name of the [ManyErrorCode](crate::ManyErrorCode) variant.
+    pub name: &'static str,
+    /// The message template of the error.
+    pub message: &'static str,
+}
+
+/// An inclusive range of error codes reserved for a module.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct CodeRange {
+    pub name: &'static str,
+    pub min: i64,
+    pub max: i64,
+}
+
+impl CodeRange {
+    pub const fn new(name: &'static str, min: i64, max: i64) -> Self {
+        Self { name, min, max }
+    }
+
+    /// All the codes of an attribute, as defined by
+    /// [define_attribute_many_error](crate::define_attribute_many_error).
+    pub const fn attribute(name: &'static str, attribute: u32) -> Self {
+        Self::attribute_codes(name, attribute, 0, 9999)
+    }
+
+    /// The codes `first` to `last` of an attribute.
+    pub const fn attribute_codes(
+        name: &'static str,
+        attribute: u32,
+        first: u32,
+        last: u32,
+    ) -> Self {
+        let base = attribute as i64 * -10000;
+        Self::new(name, base - last as i64, base - first as i64)
+    }
+
+    #[inline]
+    pub const fn contains(&self, code: i64) -> bool {
+        self.min <= code && code <= self.max
+    }
+
+    #[inline]
+    pub const fn overlaps(&self, other: &CodeRange) -> bool {
+        self.min <= other.max && other.min <= self.max
+    }
+}
+
+pub const GENERIC: CodeRange = CodeRange::new("generic", -99, -1);
+pub const IDENTITY: CodeRange = CodeRange::new("identity", -199, -100);
+pub const HSM: CodeRange = CodeRange::new("hsm", -299, -200);
+pub const LEDGER_HW: CodeRange = CodeRange::new("ledger-hw", -399, -300);
+pub const WEBAUTHN: CodeRange = CodeRange::new("webauthn", -499, -400);
+pub const REQUEST: CodeRange = CodeRange::new("request", -1999, -1000);
+pub const SERVER: CodeRange = CodeRange::new("server", -2999, -2000);
+
+/// The ranges of the codes defined by this crate.
+pub const RESERVED_RANGES: &[CodeRange] =
+    &[GENERIC, IDENTITY, HSM, LEDGER_HW, WEBAUTHN, REQUEST, SERVER];
+
+pub const LEDGER: CodeRange = CodeRange::attribute("ledger", 2);
+pub const KVSTORE: CodeRange = CodeRange::attribute("kvstore", 3);
+pub const ACCOUNT: CodeRange = CodeRange::attribute_codes("account", 9, 0, 99);
+pub const ACCOUNT_MULTISIG: CodeRange = CodeRange::attribute_codes("account.multisig", 9, 100, 199);
+pub const ACCOUNT_HOOKS: CodeRange = CodeRange::attribute_codes("account.hooks", 9, 200, 299);
+pub const ACCOUNT_VESTING: CodeRange = CodeRange::attribute_codes("account.vesting", 9, 300, 399);
+
+/// The ranges of the codes of the attributes, i.e. the codes defined with
+/// [define_attribute_many_error](crate::define_attribute_many_error). New
+/// attributes must register their range here.
+pub const ATTRIBUTE_RANGES: &[CodeRange] = &[
+    CodeRange::attribute("blockchain", 1),
+    LEDGER,
+    KVSTORE,
+    CodeRange::attribute("events", 4),
+    CodeRange::attribute("data", 5),
+    ACCOUNT,
+    ACCOUNT_MULTISIG,
+    ACCOUNT_HOOKS,
+    ACCOUNT_VESTING,
+    CodeRange::attribute("ledger.tokens", 11),
+    CodeRange::attribute("ledger.mintburn", 12),
+    CodeRange::attribute("compute", 15),
+    CodeRange::attribute("web", 16),
+    CodeRange::attribute("watchlist", 18),
+    CodeRange::attribute("ledger.freeze", 19),
+    CodeRange::attribute("ledger.schedule", 20),
+    CodeRange::attribute("ledger.receipts", 21),
+    CodeRange::attribute("ledger.streams", 22),
+    CodeRange::attribute("ledger.tokenIdentity", 23),
+    CodeRange::attribute("scripts", 24),
+    CodeRange::attribute("labels", 25),
+    CodeRange::attribute("rejections", 26),
+    CodeRange::attribute("abci.frontend", 1001),
+    CodeRange::attribute("idstore", 1002),
+];
+
+/// The range a code belongs to, if it was reserved. Application specific
+/// (positive) codes are never reserved.
+pub fn range_of(code: i64) -> Option<&'static CodeRange> {
+    RESERVED_RANGES
+        .iter()
+        .chain(ATTRIBUTE_RANGES)
+        .find(|range| range.contains(code))
+}
+
+/// The error code defined by this crate with this value, if any.
+pub fn named_code(code: i64) -> Option<&'static NamedCode> {
+    ERROR_CODES.iter().find(|named| named.code == code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ManyErrorCode;
+
+    #[test]
+    fn ranges_do_not_overlap() {
+        let ranges: Vec<_> = RESERVED_RANGES.iter().chain(ATTRIBUTE_RANGES).collect();
+        for (i, a) in ranges.iter().enumerate() {
+            assert!(a.min <= a.max, "{a:?}");
+            for b in &ranges[i + 1..] {
+                assert!(!a.overlaps(b), "{a:?} overlaps {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn codes_are_unique_and_reserved() {
+        for (i, named) in ERROR_CODES.iter().enumerate() {
+            assert!(
+                ERROR_CODES[i + 1..].iter().all(|n| n.code != named.code),
+                "Duplicated code {}",
+                named.code
+            );
+            assert!(
+                RESERVED_RANGES.iter().any(|r| r.contains(named.code)),
+                "{named:?} is outside of the reserved ranges"
+            );
+        }
+    }
+
+    #[test]
+    fn codes_match_error_codes() {
+        for named in ERROR_CODES {
+            let code = ManyErrorCode::from(named.code);
+            assert_eq!(i64::from(code), named.code);
+            assert_eq!(code.message(), Some(named.message));
+        }
+        assert_eq!(named_code(-1).map(|n| n.name), Some("Unknown"));
+        assert_eq!(named_code(-2999), None);
+    }
+
+    #[test]
+    fn range_of_code() {
+        assert_eq!(range_of(-204), Some(&HSM));
+        assert_eq!(range_of(-20_001), Some(&LEDGER));
+        assert_eq!(range_of(-90_101), Some(&ACCOUNT_MULTISIG));
+        assert_eq!(range_of(-90_500), None);
+        assert_eq!(range_of(1), None);
+    }
+}
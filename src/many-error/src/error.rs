@@ -1,7 +1,9 @@
 use crate::Reason;
-use std::collections::BTreeMap;
-use std::fmt::{Display, Formatter};
-use std::iter::FromIterator;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use core::fmt::{Display, Formatter};
+use core::iter::FromIterator;
 
 #[cfg(feature = "minicbor")]
 mod minicbor;
@@ -12,6 +14,18 @@ macro_rules! many_error {
             $v: literal: $name: ident $(as $snake_name: ident ( $($arg: ident),* ))? => $description: literal,
         )*
     } => {
+        /// Every error code defined by this crate. See [codes](crate::codes)
+        /// for the ranges they belong to.
+        pub const ERROR_CODES: &[$crate::codes::NamedCode] = &[
+            $(
+                $crate::codes::NamedCode {
+                    code: $v,
+                    name: stringify!($name),
+                    message: $description,
+                },
+            )*
+        ];
+
         #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
         pub enum ManyErrorCode {
             $( $name, )*
@@ -30,7 +44,7 @@ macro_rules! many_error {
         }
 
         impl Display for ManyErrorCode {
-            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
                 match self.message() {
                     Some(msg) => f.write_str(msg),
                     None => write!(f, "{}", Into::<i64>::into(*self)),
@@ -142,6 +156,16 @@ many_error! {
      -209: HSMMutexPoisoned as hsm_mutex_poisoned(details)
             => "PKCS#11 global instance mutex poisoned:\n{details}",
 
+     // Ledger hardware wallet errors
+     -300: LedgerHwTransportError as ledger_hw_transport_error(details)
+            => "Ledger device transport error:\n{details}",
+     -301: LedgerHwDeviceError as ledger_hw_device_error(code, details)
+            => "Ledger device returned status {code}:\n{details}",
+     -302: LedgerHwInvalidPath as ledger_hw_invalid_path(path)
+            => "Invalid Ledger derivation path: {path}.",
+     -303: LedgerHwInvalidResponse as ledger_hw_invalid_response(details)
+            => "Invalid response from the Ledger device:\n{details}",
+
     // -1000 - -1999 is for request errors.
     -1000: InvalidMethodName as invalid_method_name(method)
             => r#"Invalid method name: "{method}"."#,
@@ -163,13 +187,36 @@ many_error! {
             => "Non-WebAuthn request denied for endpoint '{endpoint}'.",
     -1009: DuplicatedMessage as duplicated_message()
             => "This message was already processed.",
+    -1010: AtomicGroupsUnsupported as atomic_groups_unsupported()
+            => "This server does not support atomic groups of requests.",
+    -1011: UnsupportedContentEncoding as unsupported_content_encoding(encoding)
+            => "Unsupported payload content encoding: '{encoding}'.",
+    -1012: InvalidChainId as invalid_chain_id(expected, actual)
+            => "The message is for the chain '{actual}', expected '{expected}'.",
+    -1013: InvalidDelegation as invalid_delegation(reason)
+            => "Invalid delegation: {reason}.",
+    -1014: MultiQueriesUnsupported as multi_queries_unsupported()
+            => "This server does not support multi-queries.",
+    -1015: MultiQueryCommand as multi_query_command(method)
+            => "'{method}' is not read-only and cannot be part of a multi-query.",
 
     // -2000 - -2999 is for server errors.
     -2000: InternalServerError as internal_server_error()
             => "An internal server error happened.",
+    -2001: ExecutionTimeout as execution_timeout(method, limit)
+            => "Execution of '{method}' exceeded its time limit of {limit} ms.",
+    -2002: MemoryBudgetExceeded as memory_budget_exceeded(method, limit)
+            => "Execution of '{method}' exceeded its memory budget of {limit} bytes.",
+    -2003: AtomicGroupAborted as atomic_group_aborted(index)
+            => "The atomic group was aborted because its request {index} failed.",
+    -2004: MultiQueryStateChanged as multi_query_state_changed(attempts)
+            => "The state changed during each of the {attempts} attempts of the multi-query.",
+    -2005: RequestCacheFull as request_cache_full(limit)
+            => "The request cache is full ({limit} requests). Try again later.",
 
     // Negative 10000+ are reserved for attribute specified codes and are defined separately.
     // The method to use these is ATTRIBUTE_ID * -10000.
+    // Their ranges are registered in `codes::ATTRIBUTE_RANGES`.
 
     // Positive error codes are reserved for application specific errors and custom
     // server-specific error messages.
@@ -181,12 +228,15 @@ macro_rules! define_attribute_many_error {
     ( $( attribute $module_id: literal => { $( $id: literal : $vis: vis fn $name: ident ($( $var_name: ident ),*) => $message: literal ),* $(,)? } );* ) => {
         $(
         $(
-            $vis fn $name( $($var_name: impl ToString),* ) -> $crate::ManyError {
+            $vis fn $name( $($var_name: impl $crate::__private::ToString),* ) -> $crate::ManyError {
                 $crate::ManyError::attribute_specific(
                     ($module_id as i32) * -10000i32 - ($id as i32),
-                    String::from($message),
-                    std::iter::FromIterator::from_iter(vec![
-                        $( (stringify!($var_name).to_string(), ($var_name).to_string()) ),*
+                    $crate::__private::String::from($message),
+                    $crate::__private::FromIterator::from_iter($crate::__private::vec![
+                        $( (
+                            $crate::__private::ToString::to_string(stringify!($var_name)),
+                            $crate::__private::ToString::to_string(&$var_name),
+                        ) ),*
                     ]),
                 )
             }
@@ -200,12 +250,15 @@ macro_rules! define_application_many_error {
     ( $( { $( $id: literal : $vis: vis fn $name: ident ($( $var_name: ident ),*) => $message: literal ),* $(,)? } );* ) => {
         $(
         $(
-            $vis fn $name ( $($var_name: impl ToString),* ) -> $crate::ManyError {
+            $vis fn $name ( $($var_name: impl $crate::__private::ToString),* ) -> $crate::ManyError {
                 $crate::ManyError::application_specific(
                     $id as u32,
-                    String::from($message),
-                    std::iter::FromIterator::from_iter(vec![
-                        $( (stringify!($var_name).to_string(), ($var_name).to_string()) ),*
+                    $crate::__private::String::from($message),
+                    $crate::__private::FromIterator::from_iter($crate::__private::vec![
+                        $( (
+                            $crate::__private::ToString::to_string(stringify!($var_name)),
+                            $crate::__private::ToString::to_string(&$var_name),
+                        ) ),*
                     ]),
                 )
             }
@@ -310,7 +363,7 @@ impl ManyError {
 }
 
 impl Display for ManyError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         self.0.fmt(f)
     }
 }
@@ -324,6 +377,7 @@ impl Default for ManyErrorCode {
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for ManyError {}
 
 impl Default for ManyError {
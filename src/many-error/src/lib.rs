@@ -1,5 +1,20 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod codes;
+
 pub mod error;
 pub use error::{ManyError, ManyErrorCode};
 
 pub mod reason;
 pub use reason::Reason;
+
+/// Re-exports used by the macros of this crate, so they work in crates
+/// without `std`.
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::string::{String, ToString};
+    pub use alloc::vec;
+    pub use core::iter::FromIterator;
+}
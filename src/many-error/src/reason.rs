@@ -1,5 +1,7 @@
-use std::collections::BTreeMap;
-use std::fmt::{Display, Formatter};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use core::fmt::{Display, Formatter};
 
 #[cfg(feature = "minicbor")]
 pub mod minicbor;
@@ -72,35 +74,44 @@ impl<T> Reason<T> {
 }
 
 impl<T: Display> Display for Reason<T> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    /// Replace `{field}` in the message with the argument named `field`, or
+    /// nothing if there is no such argument. `{{` and `}}` are escapes for
+    /// `{` and `}`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         let message = self
             .message
             .clone()
             .unwrap_or_else(|| format!("Error '{}'", self.code));
 
-        let re = regex::Regex::new(r"\{\{|\}\}|\{[^\}\s]*\}").unwrap();
-        let mut current = 0;
+        let mut rest = message.as_str();
+        while let Some(start) = rest.find(|c| c == '{' || c == '}') {
+            f.write_str(&rest[..start])?;
+            rest = &rest[start..];
 
-        for mat in re.find_iter(&message) {
-            let std::ops::Range { start, end } = mat.range();
-            f.write_str(&message[current..start])?;
-            current = end;
-
-            let s = mat.as_str();
-            if s == "{{" {
+            if rest.starts_with("{{") {
                 f.write_str("{")?;
-            } else if s == "}}" {
+                rest = &rest[2..];
+            } else if rest.starts_with("}}") {
                 f.write_str("}")?;
+                rest = &rest[2..];
             } else {
-                let field = &message[start + 1..end - 1];
-                f.write_str(
-                    self.arguments
-                        .get(field)
-                        .unwrap_or(&"".to_string())
-                        .as_str(),
-                )?;
+                let end = rest[1..]
+                    .find(|c: char| c == '}' || c.is_whitespace())
+                    .map(|end| end + 1)
+                    .filter(|end| rest[*end..].starts_with('}'));
+                match end {
+                    Some(end) => {
+                        let field = &rest[1..end];
+                        f.write_str(self.arguments.get(field).map_or("", |x| x.as_str()))?;
+                        rest = &rest[end + 1..];
+                    }
+                    None => {
+                        f.write_str(&rest[..1])?;
+                        rest = &rest[1..];
+                    }
+                }
             }
         }
-        f.write_str(&message[current..])
+        f.write_str(rest)
     }
 }
@@ -0,0 +1,111 @@
+//! Scans the crates of the workspace for the codes defined with
+//! `define_attribute_many_error`, and checks them against the ranges reserved
+//! in `many_error::codes`.
+use many_error::codes::{range_of, ATTRIBUTE_RANGES};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+struct DefinedCode {
+    code: i64,
+    name: String,
+    file: PathBuf,
+}
+
+fn rust_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let file_name = path.file_name().unwrap().to_string_lossy();
+        if path.is_dir() {
+            if !file_name.starts_with('.') && file_name != "target" {
+                rust_files(&path, files);
+            }
+        } else if file_name.ends_with(".rs") {
+            files.push(path);
+        }
+    }
+}
+
+/// Parse the `N: pub fn name(...)` lines of the `attribute N => { ... }`
+/// blocks of the macro invocations of a source file.
+fn parse_codes(file: &Path, source: &str) -> Vec<DefinedCode> {
+    let mut codes = Vec::new();
+    for block in source.split("define_attribute_many_error!(").skip(1) {
+        let mut attribute = None;
+        for line in block.lines().map(str::trim) {
+            if line.starts_with(')') {
+                break;
+            }
+            if let Some(rest) = line.strip_prefix("attribute ") {
+                attribute = rest.trim_end_matches("=> {").trim().parse::<i64>().ok();
+                continue;
+            }
+            let (id, definition) = match line.split_once(':') {
+                Some(x) => x,
+                None => continue,
+            };
+            let (id, attribute) = match (id.trim().parse::<i64>(), attribute) {
+                (Ok(id), Some(attribute)) => (id, attribute),
+                _ => continue,
+            };
+            let name = definition
+                .split_once("fn ")
+                .and_then(|(_, rest)| rest.split_once('('))
+                .map(|(name, _)| name.trim().to_string())
+                .unwrap_or_default();
+            codes.push(DefinedCode {
+                code: attribute * -10000 - id,
+                name,
+                file: file.to_path_buf(),
+            });
+        }
+    }
+    codes
+}
+
+fn defined_codes() -> Vec<DefinedCode> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+    let mut files = Vec::new();
+    rust_files(root, &mut files);
+
+    files
+        .iter()
+        .filter(|file| !file.starts_with(env!("CARGO_MANIFEST_DIR")))
+        .flat_map(|file| parse_codes(file, &std::fs::read_to_string(file).unwrap()))
+        .collect()
+}
+
+#[test]
+fn attribute_codes_are_registered() {
+    let codes = defined_codes();
+    assert!(!codes.is_empty());
+
+    for code in &codes {
+        let range = range_of(code.code);
+        assert!(
+            range.map_or(false, |r| ATTRIBUTE_RANGES.contains(r)),
+            "{} ({}) of {} is not in a registered attribute range",
+            code.name,
+            code.code,
+            code.file.display()
+        );
+    }
+}
+
+#[test]
+fn attribute_codes_do_not_collide() {
+    // Crates may declare the same error again (e.g. the ledger server and
+    // the ledger module), but not reuse its code for another one.
+    let mut names: BTreeMap<i64, &DefinedCode> = BTreeMap::new();
+    for code in &defined_codes() {
+        if let Some(previous) = names.insert(code.code, code) {
+            assert_eq!(
+                previous.name,
+                code.name,
+                "Code {} defined by both {} and {}",
+                code.code,
+                previous.file.display(),
+                code.file.display()
+            );
+        }
+    }
+}
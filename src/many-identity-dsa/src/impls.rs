@@ -3,6 +3,9 @@ use coset::iana::{EnumI64, OkpKeyParameter};
 use coset::{CoseKey, Label};
 use many_error::ManyError;
 
+#[cfg(feature = "bls")]
+pub mod bls;
+
 #[cfg(feature = "ed25519")]
 pub mod ed25519;
 
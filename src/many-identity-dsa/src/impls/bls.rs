@@ -0,0 +1,508 @@
+//! BLS signatures on the BLS12-381 curve, with public keys in G1 and
+//! signatures in G2 (the "minimal public key" variant).
+//!
+//! Signatures from multiple keys over the same envelope can be aggregated into
+//! a single signature of the same size (see [aggregate]). Aggregated signatures
+//! are only secure if every public key was registered with a proof of
+//! possession (see [BlsIdentity::proof_of_possession]).
+use coset::cbor::value::Value;
+use coset::iana::{EnumI64, OkpKeyParameter};
+use coset::{CoseKey, CoseKeySet, CoseSign1, CoseSign1Builder, Label};
+use many_error::ManyError;
+use many_identity::{cose, Address, Identity, Verifier};
+use std::collections::{BTreeMap, BTreeSet};
+
+use blst::min_pk::{AggregateSignature, PublicKey, SecretKey, Signature};
+use blst::BLST_ERROR;
+
+/// The COSE algorithm of BLS signatures. There is no assigned value yet, so this
+/// uses the private use range.
+pub const BLS_ALGORITHM: i64 = -65_537;
+
+/// The COSE curve `BLS12381G1`, from the IANA COSE Elliptic Curves registry.
+pub const BLS12_381_G1_CURVE: i64 = 13;
+
+/// Domain separation tag of signatures, for the proof of possession scheme.
+const DST_SIGNATURE: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Domain separation tag of proofs of possession.
+const DST_POP: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+fn algorithm() -> coset::Algorithm {
+    coset::Algorithm::PrivateUse(BLS_ALGORITHM)
+}
+
+fn check(result: BLST_ERROR) -> Result<(), ManyError> {
+    match result {
+        BLST_ERROR::BLST_SUCCESS => Ok(()),
+        err => Err(ManyError::could_not_verify_signature(format!("{err:?}"))),
+    }
+}
+
+/// Build a BLS CoseKey
+///
+/// # Arguments
+///
+/// * `x` - Public key (compressed G1 point)
+/// * `d` - Private key
+fn bls_cose_key(x: Vec<u8>, d: Option<Vec<u8>>) -> CoseKey {
+    let mut params: Vec<(Label, Value)> = Vec::from([
+        (
+            Label::Int(OkpKeyParameter::Crv.to_i64()),
+            Value::from(BLS12_381_G1_CURVE),
+        ),
+        (Label::Int(OkpKeyParameter::X.to_i64()), Value::Bytes(x)),
+    ]);
+
+    let mut key_ops: BTreeSet<coset::KeyOperation> =
+        BTreeSet::from([coset::KeyOperation::Assigned(
+            coset::iana::KeyOperation::Verify,
+        )]);
+
+    if let Some(d) = d {
+        params.push((Label::Int(OkpKeyParameter::D.to_i64()), Value::Bytes(d)));
+        key_ops.insert(coset::KeyOperation::Assigned(
+            coset::iana::KeyOperation::Sign,
+        ));
+    }
+
+    // The CoseKeyBuilder is too limited to be used here
+    CoseKey {
+        kty: coset::KeyType::Assigned(coset::iana::KeyType::OKP),
+        alg: Some(algorithm()),
+        key_ops,
+        params,
+        ..Default::default()
+    }
+}
+
+/// Assert a COSE key is a valid BLS key.
+fn check_key(cose_key: &CoseKey, sign: bool) -> Result<(), ManyError> {
+    let op = if sign {
+        coset::iana::KeyOperation::Sign
+    } else {
+        coset::iana::KeyOperation::Verify
+    };
+    if !cose_key
+        .key_ops
+        .contains(&coset::KeyOperation::Assigned(op))
+    {
+        return Err(ManyError::unknown(format!("Key cannot {op:?}")));
+    }
+    if cose_key.kty != coset::KeyType::Assigned(coset::iana::KeyType::OKP) {
+        return Err(ManyError::unknown(format!(
+            "Wrong key type: {:?}",
+            cose_key.kty
+        )));
+    }
+    if cose_key.alg != Some(algorithm()) {
+        return Err(ManyError::unknown(format!(
+            "Wrong key algorihm: {:?}",
+            cose_key.alg
+        )));
+    }
+
+    let params = BTreeMap::from_iter(cose_key.params.iter().cloned());
+    let crv = params
+        .get(&Label::Int(OkpKeyParameter::Crv.to_i64()))
+        .and_then(Value::as_integer)
+        .ok_or_else(|| ManyError::unknown("Crv parameter not found."))?;
+    if crv != BLS12_381_G1_CURVE.into() {
+        return Err(ManyError::unknown("Curve unsupported. Expected BLS12381G1"));
+    }
+
+    Ok(())
+}
+
+fn key_param<'a>(
+    cose_key: &'a CoseKey,
+    param: OkpKeyParameter,
+    name: &str,
+) -> Result<&'a [u8], ManyError> {
+    cose_key
+        .params
+        .iter()
+        .find(|(k, _)| k == &Label::Int(param.to_i64()))
+        .ok_or_else(|| ManyError::unknown(format!("Could not find the {name} parameter in key")))?
+        .1
+        .as_bytes()
+        .map(Vec::as_slice)
+        .ok_or_else(|| {
+            ManyError::unknown(format!("Could not convert the {name} parameter to bytes"))
+        })
+}
+
+pub fn public_key(key: &CoseKey) -> Result<Option<CoseKey>, ManyError> {
+    if key.alg != Some(algorithm()) {
+        return Ok(None);
+    }
+    let x = key_param(key, OkpKeyParameter::X, "X")?;
+    Ok(Some(bls_cose_key(x.to_vec(), None)))
+}
+
+fn bls_public_key(cose_key: &CoseKey) -> Result<PublicKey, ManyError> {
+    check_key(cose_key, false)?;
+    let x = key_param(cose_key, OkpKeyParameter::X, "X")?;
+    PublicKey::key_validate(x)
+        .map_err(|e| ManyError::unknown(format!("Invalid BLS public key: {e:?}")))
+}
+
+/// Extract the address of a CoseKey, if it implements BLS.
+pub fn address(key: &CoseKey) -> Result<Address, ManyError> {
+    let public_key = public_key(key)?.ok_or_else(|| ManyError::unknown("Could not load key."))?;
+    // The key is safe as [public_key] sanitizes and normalizes it.
+    unsafe { cose::address_unchecked(&public_key) }
+}
+
+/// A BLS identity that sign messages and include the public key in the
+/// protected headers.
+#[derive(Clone)]
+pub struct BlsIdentity {
+    address: Address,
+    public_key: CoseKey,
+    sk: SecretKey,
+}
+
+impl BlsIdentity {
+    pub fn from_key(cose_key: &CoseKey) -> Result<Self, ManyError> {
+        check_key(cose_key, true)?;
+
+        let public_key = public_key(cose_key)?.ok_or_else(|| ManyError::unknown("Invalid key."))?;
+        let address = unsafe { cose::address_unchecked(&public_key) }?;
+
+        let d = key_param(cose_key, OkpKeyParameter::D, "D")?;
+        let sk = SecretKey::from_bytes(d)
+            .map_err(|e| ManyError::unknown(format!("Invalid BLS secret key: {e:?}")))?;
+        if sk.sk_to_pk().compress().as_slice() != key_param(cose_key, OkpKeyParameter::X, "X")? {
+            return Err(ManyError::unknown(
+                "BLS public key does not match the secret key.",
+            ));
+        }
+
+        Ok(Self {
+            address,
+            public_key,
+            sk,
+        })
+    }
+
+    /// Derive a key from input keying material, which needs to be at least 32
+    /// bytes of secret randomness.
+    pub fn from_ikm(ikm: &[u8]) -> Result<Self, ManyError> {
+        let sk = SecretKey::key_gen(ikm, &[])
+            .map_err(|e| ManyError::unknown(format!("Could not generate BLS key: {e:?}")))?;
+        Self::from_key(&bls_cose_key(
+            sk.sk_to_pk().compress().to_vec(),
+            Some(sk.to_bytes().to_vec()),
+        ))
+    }
+
+    /// Generate a new random key.
+    pub fn generate() -> Result<Self, ManyError> {
+        use rand::RngCore;
+        let mut ikm = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut ikm);
+        Self::from_ikm(&ikm)
+    }
+
+    /// The COSE key, including the private key.
+    pub fn cose_key(&self) -> CoseKey {
+        bls_cose_key(
+            self.sk.sk_to_pk().compress().to_vec(),
+            Some(self.sk.to_bytes().to_vec()),
+        )
+    }
+
+    pub fn public_key(&self) -> CoseKey {
+        self.public_key.clone()
+    }
+
+    pub fn try_sign(&self, bytes: &[u8]) -> Result<Vec<u8>, ManyError> {
+        Ok(self.sk.sign(bytes, DST_SIGNATURE, &[]).compress().to_vec())
+    }
+
+    /// A signature of the public key, proving ownership of the private key.
+    /// Verify it with [verify_proof_of_possession] before accepting the public
+    /// key in an aggregated signature.
+    pub fn proof_of_possession(&self) -> Vec<u8> {
+        let pk = self.sk.sk_to_pk().compress();
+        self.sk.sign(&pk, DST_POP, &[]).compress().to_vec()
+    }
+
+    /// Sign an envelope prepared by [prepare_aggregate], returning the
+    /// signature to pass to [aggregate].
+    pub fn sign_aggregate(&self, envelope: &CoseSign1) -> Result<Vec<u8>, ManyError> {
+        let keyset = aggregate_keyset(envelope)?;
+        if !keyset.iter().any(|(address, _)| *address == self.address) {
+            return Err(ManyError::unknown(
+                "The key is not part of the aggregated keyset.",
+            ));
+        }
+        let payload = envelope.payload.as_deref().unwrap_or_default();
+        self.try_sign(&coset::sig_structure_data(
+            coset::SignatureContext::CoseSign1,
+            envelope.protected.clone(),
+            None,
+            &[],
+            payload,
+        ))
+    }
+}
+
+impl Identity for BlsIdentity {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn public_key(&self) -> Option<CoseKey> {
+        Some(self.public_key.clone())
+    }
+
+    fn sign_1(&self, envelope: CoseSign1) -> Result<CoseSign1, ManyError> {
+        let mut envelope = cose::add_keyset_header(envelope, self)?;
+
+        // Add the algorithm and key id.
+        envelope.protected.header.alg = Some(algorithm());
+        envelope.protected.header.key_id = self.address.to_vec();
+
+        let builder = CoseSign1Builder::new()
+            .protected(envelope.protected.header)
+            .unprotected(envelope.unprotected);
+
+        let builder = if let Some(payload) = envelope.payload {
+            builder.payload(payload)
+        } else {
+            builder
+        };
+
+        Ok(builder
+            .try_create_signature(&[], |bytes| self.try_sign(bytes))?
+            .build())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BlsVerifier {
+    address: Address,
+    public_key: PublicKey,
+}
+
+impl BlsVerifier {
+    pub fn from_key(cose_key: &CoseKey) -> Result<Self, ManyError> {
+        let public_key = public_key(cose_key)?.ok_or_else(|| ManyError::unknown("Key not BLS."))?;
+        let address = unsafe { cose::address_unchecked(&public_key) }?;
+
+        Ok(Self {
+            address,
+            public_key: bls_public_key(&public_key)?,
+        })
+    }
+
+    pub fn verify_signature(&self, signature: &[u8], data: &[u8]) -> Result<(), ManyError> {
+        let sig = Signature::sig_validate(signature, true)
+            .map_err(|e| ManyError::could_not_verify_signature(format!("{e:?}")))?;
+        check(sig.verify(true, data, DST_SIGNATURE, &[], &self.public_key, false))
+    }
+}
+
+impl Verifier for BlsVerifier {
+    fn verify_1(&self, envelope: &CoseSign1) -> Result<Address, ManyError> {
+        let address = Address::from_bytes(&envelope.protected.header.key_id)?;
+        if self.address.matches(&address) {
+            envelope
+                .verify_signature(&[], |signature, msg| self.verify_signature(signature, msg))?;
+            Ok(address)
+        } else {
+            Err(ManyError::unknown(format!(
+                "Address in envelope does not match expected address. Expected: {}, Actual: {address}",
+                self.address
+            )))
+        }
+    }
+}
+
+/// Verify the proof of possession of a public key.
+pub fn verify_proof_of_possession(key: &CoseKey, proof: &[u8]) -> Result<(), ManyError> {
+    let public_key =
+        bls_public_key(&public_key(key)?.ok_or_else(|| ManyError::unknown("Key not BLS."))?)?;
+    let sig = Signature::sig_validate(proof, true)
+        .map_err(|e| ManyError::could_not_verify_signature(format!("{e:?}")))?;
+    check(sig.verify(
+        true,
+        &public_key.compress(),
+        DST_POP,
+        &[],
+        &public_key,
+        false,
+    ))
+}
+
+/// The signers of an aggregated envelope, from its keyset header.
+fn aggregate_keyset(envelope: &CoseSign1) -> Result<Vec<(Address, PublicKey)>, ManyError> {
+    if envelope.protected.header.alg != Some(algorithm()) {
+        return Err(ManyError::unknown("Envelope is not signed with BLS."));
+    }
+    let keyset = cose::keyset_from_cose_sign1(envelope)
+        .ok_or_else(|| ManyError::unknown("Could not find keyset in headers."))?;
+
+    keyset
+        .0
+        .iter()
+        .map(|key| {
+            let public_key = public_key(key)?.ok_or_else(|| ManyError::unknown("Key not BLS."))?;
+            let address = unsafe { cose::address_unchecked(&public_key) }?;
+            Ok((address, bls_public_key(&public_key)?))
+        })
+        .collect()
+}
+
+/// Prepare an envelope to be signed by multiple BLS keys, which all sign the
+/// same bytes. The keyset header lists the public keys of all signers, and the
+/// key id is left empty.
+pub fn prepare_aggregate(envelope: CoseSign1, keys: &[CoseKey]) -> Result<CoseSign1, ManyError> {
+    let mut keyset = CoseKeySet::default();
+    for key in keys {
+        let mut key = public_key(key)?.ok_or_else(|| ManyError::unknown("Key not BLS."))?;
+        key.key_id = address(&key)?.to_vec();
+        keyset.0.push(key);
+    }
+
+    let mut protected = envelope.protected.header;
+    protected
+        .rest
+        .retain(|(k, _)| k != &Label::Text("keyset".to_string()));
+    protected.rest.push((
+        Label::Text("keyset".to_string()),
+        Value::Bytes(coset::CborSerializable::to_vec(keyset).map_err(ManyError::unknown)?),
+    ));
+    protected.alg = Some(algorithm());
+    protected.key_id = vec![];
+
+    let builder = CoseSign1Builder::new()
+        .protected(protected)
+        .unprotected(envelope.unprotected);
+    Ok(match envelope.payload {
+        Some(payload) => builder.payload(payload),
+        None => builder,
+    }
+    .build())
+}
+
+/// Aggregate the signatures of all the signers of an envelope prepared by
+/// [prepare_aggregate] into a single signature.
+pub fn aggregate(mut envelope: CoseSign1, signatures: &[Vec<u8>]) -> Result<CoseSign1, ManyError> {
+    let signatures = signatures
+        .iter()
+        .map(|s| {
+            Signature::sig_validate(s, true)
+                .map_err(|e| ManyError::could_not_verify_signature(format!("{e:?}")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let signatures = signatures.iter().collect::<Vec<_>>();
+    let aggregated = AggregateSignature::aggregate(&signatures, false)
+        .map_err(|e| ManyError::unknown(format!("Could not aggregate signatures: {e:?}")))?;
+
+    envelope.signature = aggregated.to_signature().compress().to_vec();
+    verify_aggregate(&envelope)?;
+    Ok(envelope)
+}
+
+/// Verify an aggregated envelope, returning the addresses of all the signers.
+pub fn verify_aggregate(envelope: &CoseSign1) -> Result<Vec<Address>, ManyError> {
+    let keyset = aggregate_keyset(envelope)?;
+    if keyset.is_empty() {
+        return Err(ManyError::unknown("No signer in keyset."));
+    }
+    let public_keys = keyset.iter().map(|(_, pk)| pk).collect::<Vec<_>>();
+
+    envelope.verify_signature(&[], |signature, msg| {
+        let sig = Signature::sig_validate(signature, true)
+            .map_err(|e| ManyError::could_not_verify_signature(format!("{e:?}")))?;
+        check(sig.fast_aggregate_verify(true, msg, DST_SIGNATURE, &public_keys))
+    })?;
+
+    Ok(keyset.into_iter().map(|(address, _)| address).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope() -> CoseSign1 {
+        CoseSign1Builder::new().payload(b"FOOBAR".to_vec()).build()
+    }
+
+    #[test]
+    fn sign_verify() {
+        let id = BlsIdentity::generate().unwrap();
+        let verifier = BlsVerifier::from_key(&id.public_key()).unwrap();
+
+        let signature = id.try_sign(b"FOOBAR").unwrap();
+        verifier.verify_signature(&signature, b"FOOBAR").unwrap();
+        assert!(verifier.verify_signature(&signature, b"BARFOO").is_err());
+    }
+
+    #[test]
+    fn cose_key_roundtrip() {
+        let id = BlsIdentity::from_ikm(&[1; 32]).unwrap();
+        let id2 = BlsIdentity::from_key(&id.cose_key()).unwrap();
+        assert_eq!(id.address(), id2.address());
+        assert_eq!(address(&id.public_key()).unwrap(), id.address());
+
+        // Cannot sign with a public key.
+        assert!(BlsIdentity::from_key(&id.public_key()).is_err());
+    }
+
+    #[test]
+    fn identity_invalid_alg() {
+        let mut cose_key = BlsIdentity::generate().unwrap().cose_key();
+        cose_key.alg = Some(coset::Algorithm::Assigned(coset::iana::Algorithm::EdDSA));
+        assert!(BlsIdentity::from_key(&cose_key).is_err());
+    }
+
+    #[test]
+    fn sign_and_verify_envelope() {
+        let id = BlsIdentity::generate().unwrap();
+        let envelope = id.sign_1(envelope()).unwrap();
+        let verifier = BlsVerifier::from_key(&id.public_key()).unwrap();
+        assert_eq!(verifier.verify_1(&envelope).unwrap(), id.address());
+    }
+
+    #[test]
+    fn proof_of_possession() {
+        let id = BlsIdentity::generate().unwrap();
+        let other = BlsIdentity::generate().unwrap();
+        verify_proof_of_possession(&id.public_key(), &id.proof_of_possession()).unwrap();
+        assert!(
+            verify_proof_of_possession(&id.public_key(), &other.proof_of_possession()).is_err()
+        );
+    }
+
+    #[test]
+    fn aggregate_signatures() {
+        let ids = (0..3)
+            .map(|_| BlsIdentity::generate().unwrap())
+            .collect::<Vec<_>>();
+        let keys = ids.iter().map(|id| id.public_key()).collect::<Vec<_>>();
+
+        let prepared = prepare_aggregate(envelope(), &keys).unwrap();
+        let signatures = ids
+            .iter()
+            .map(|id| id.sign_aggregate(&prepared).unwrap())
+            .collect::<Vec<_>>();
+
+        let envelope = aggregate(prepared.clone(), &signatures).unwrap();
+        assert_eq!(envelope.signature.len(), 96);
+        assert_eq!(
+            verify_aggregate(&envelope).unwrap(),
+            ids.iter().map(|id| id.address()).collect::<Vec<_>>()
+        );
+
+        // Missing a signer.
+        assert!(aggregate(prepared.clone(), &signatures[1..]).is_err());
+
+        // Not a signer.
+        let other = BlsIdentity::generate().unwrap();
+        assert!(other.sign_aggregate(&prepared).is_err());
+    }
+}
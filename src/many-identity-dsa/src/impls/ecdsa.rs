@@ -261,11 +261,8 @@ impl Verifier for EcDsaVerifier {
 }
 
 #[cfg(feature = "testing")]
-pub fn generate_random_ecdsa_cose_key() -> CoseKey {
-    use rand::rngs::OsRng;
-
-    let mut csprng = OsRng {};
-    let privkey = p256::ecdsa::SigningKey::random(&mut csprng);
+fn generate_ecdsa_cose_key(rng: &mut (impl rand::RngCore + rand::CryptoRng)) -> CoseKey {
+    let privkey = p256::ecdsa::SigningKey::random(rng);
     let pubkey = privkey.verifying_key();
 
     let x = pubkey.to_encoded_point(false).x().unwrap().to_vec();
@@ -274,11 +271,27 @@ pub fn generate_random_ecdsa_cose_key() -> CoseKey {
     ecdsa_cose_key((x, y), Some(privkey.to_bytes().to_vec()))
 }
 
+#[cfg(feature = "testing")]
+pub fn generate_random_ecdsa_cose_key() -> CoseKey {
+    use rand::rngs::OsRng;
+    generate_ecdsa_cose_key(&mut OsRng {})
+}
+
 #[cfg(feature = "testing")]
 pub fn generate_random_ecdsa_identity() -> EcDsaIdentity {
     EcDsaIdentity::from_key(&generate_random_ecdsa_cose_key()).unwrap()
 }
 
+/// Generate an identity deterministically from a seed. The same seed always
+/// generates the same identity, so tests using it can be replayed exactly.
+/// This is NOT secure and must only be used for tests and simulations.
+#[cfg(feature = "testing")]
+pub fn generate_ecdsa_identity_from_seed(seed: u64) -> EcDsaIdentity {
+    use rand::{rngs::StdRng, SeedableRng};
+    let cose_key = generate_ecdsa_cose_key(&mut StdRng::seed_from_u64(seed));
+    EcDsaIdentity::from_key(&cose_key).unwrap()
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -362,6 +375,19 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn identity_from_seed() {
+        let id = generate_ecdsa_identity_from_seed(42);
+        assert_eq!(
+            id.address(),
+            generate_ecdsa_identity_from_seed(42).address()
+        );
+        assert_ne!(
+            id.address(),
+            generate_ecdsa_identity_from_seed(43).address()
+        );
+    }
+
     #[test]
     fn identity_invalid_key_no_sign() {
         let mut cose_key = generate_random_ecdsa_cose_key();
@@ -14,7 +14,7 @@ use std::collections::BTreeSet;
 ///
 /// * `x` - Public key
 /// * `d` - Private key
-fn eddsa_cose_key(x: Vec<u8>, d: Option<Vec<u8>>) -> CoseKey {
+pub fn eddsa_cose_key(x: Vec<u8>, d: Option<Vec<u8>>) -> CoseKey {
     let mut params: Vec<(Label, Value)> = Vec::from([
         (
             Label::Int(OkpKeyParameter::Crv.to_i64()),
@@ -293,10 +293,8 @@ impl Verifier for Ed25519Verifier {
 }
 
 #[cfg(feature = "testing")]
-pub(crate) fn generate_random_ed25519_cose_key() -> CoseKey {
-    use rand::rngs::OsRng;
-    let mut csprng = OsRng;
-    let signing_key: SigningKey = SigningKey::generate(&mut csprng);
+fn generate_ed25519_cose_key(rng: &mut (impl rand::RngCore + rand::CryptoRng)) -> CoseKey {
+    let signing_key: SigningKey = SigningKey::generate(rng);
     let verifying_key = signing_key.verifying_key();
 
     eddsa_cose_key(
@@ -305,11 +303,27 @@ pub(crate) fn generate_random_ed25519_cose_key() -> CoseKey {
     )
 }
 
+#[cfg(feature = "testing")]
+pub(crate) fn generate_random_ed25519_cose_key() -> CoseKey {
+    use rand::rngs::OsRng;
+    generate_ed25519_cose_key(&mut OsRng)
+}
+
 #[cfg(feature = "testing")]
 pub fn generate_random_ed25519_identity() -> Ed25519Identity {
     Ed25519Identity::from_key(&generate_random_ed25519_cose_key()).unwrap()
 }
 
+/// Generate an identity deterministically from a seed. The same seed always
+/// generates the same identity, so tests using it can be replayed exactly.
+/// This is NOT secure and must only be used for tests and simulations.
+#[cfg(feature = "testing")]
+pub fn generate_ed25519_identity_from_seed(seed: u64) -> Ed25519Identity {
+    use rand::{rngs::StdRng, SeedableRng};
+    let cose_key = generate_ed25519_cose_key(&mut StdRng::seed_from_u64(seed));
+    Ed25519Identity::from_key(&cose_key).unwrap()
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -344,6 +358,19 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn identity_from_seed() {
+        let id = generate_ed25519_identity_from_seed(42);
+        assert_eq!(
+            id.address(),
+            generate_ed25519_identity_from_seed(42).address()
+        );
+        assert_ne!(
+            id.address(),
+            generate_ed25519_identity_from_seed(43).address()
+        );
+    }
+
     #[test]
     fn identity_invalid_key_no_sign() {
         let mut cose_key = generate_random_ed25519_cose_key();
@@ -11,6 +11,9 @@ pub use impls::ed25519;
 
 #[cfg(feature = "ecdsa")]
 pub use impls::ecdsa;
+
+#[cfg(feature = "bls")]
+pub use impls::bls;
 use many_identity::cose::keyset_from_cose_sign1;
 
 #[non_exhaustive]
@@ -22,6 +25,9 @@ enum CoseKeyImpl {
     #[cfg(feature = "ecdsa")]
     EcDsa(ecdsa::EcDsaIdentity),
 
+    #[cfg(feature = "bls")]
+    Bls(bls::BlsIdentity),
+
     /// This should never be constructed, but in some cases the other enum
     /// values might not exist and an empty enum is illegal.
     #[allow(unused)]
@@ -40,6 +46,11 @@ impl CoseKeyImpl {
             return Some(Self::EcDsa(i));
         }
 
+        #[cfg(feature = "bls")]
+        if let Ok(i) = bls::BlsIdentity::from_key(key) {
+            return Some(Self::Bls(i));
+        }
+
         None
     }
 
@@ -65,6 +76,9 @@ impl CoseKeyImpl {
             #[cfg(feature = "ecdsa")]
             CoseKeyImpl::EcDsa(i) => i.address(),
 
+            #[cfg(feature = "bls")]
+            CoseKeyImpl::Bls(i) => i.address(),
+
             CoseKeyImpl::Illegal_ => unreachable!(),
         }
     }
@@ -77,6 +91,9 @@ impl CoseKeyImpl {
             #[cfg(feature = "ecdsa")]
             CoseKeyImpl::EcDsa(i) => Identity::public_key(i),
 
+            #[cfg(feature = "bls")]
+            CoseKeyImpl::Bls(i) => Identity::public_key(i),
+
             CoseKeyImpl::Illegal_ => unreachable!(),
         }
     }
@@ -89,6 +106,9 @@ impl CoseKeyImpl {
             #[cfg(feature = "ecdsa")]
             CoseKeyImpl::EcDsa(i) => i.sign_1(envelope),
 
+            #[cfg(feature = "bls")]
+            CoseKeyImpl::Bls(i) => i.sign_1(envelope),
+
             CoseKeyImpl::Illegal_ => unreachable!(),
         }
     }
@@ -174,6 +194,9 @@ impl Verifier for CoseKeyVerifier {
             #[cfg(feature = "ecdsa")]
             try_verify!(ecdsa::EcDsaVerifier::from_key(key), envelope, "ecdsa");
 
+            #[cfg(feature = "bls")]
+            try_verify!(bls::BlsVerifier::from_key(key), envelope, "bls");
+
             Err(ManyError::unknown("Algorithm unsupported."))
         })()?;
 
@@ -191,6 +214,9 @@ impl Debug for CoseKeyVerifier {
         #[cfg(feature = "ed25519")]
         x.field(&"ed25519");
 
+        #[cfg(feature = "bls")]
+        x.field(&"bls");
+
         x.finish()
     }
 }
@@ -38,6 +38,23 @@ pub type HsmMechanism = Mechanism;
 /// Same as cryptoki::mechanism::MechanismType
 pub type HsmMechanismType = MechanismType;
 
+/// DER encoded OID of the Ed25519 curve (1.3.101.112), as found in EC_PARAMS
+const ED25519_OID: &[u8] = &[0x06, 0x03, 0x2B, 0x65, 0x70];
+
+/// DER encoded printable string of the Ed25519 curve name, as found in EC_PARAMS
+const ED25519_NAME: &[u8] = b"\x13\x0cedwards25519";
+
+/// Parse the name of a supported HSM mechanism, i.e., `ecdsa` or `eddsa`
+pub fn parse_mechanism(name: &str) -> Result<HsmMechanismType, ManyError> {
+    match name.to_lowercase().as_str() {
+        "ecdsa" => Ok(HsmMechanismType::ECDSA),
+        "eddsa" => Ok(HsmMechanismType::EDDSA),
+        _ => Err(ManyError::unknown(format!(
+            "Unsupported HSM mechanism: {name}"
+        ))),
+    }
+}
+
 /// HSM session type.
 pub enum HsmSessionType {
     /// Read-only
@@ -167,9 +184,10 @@ impl Hsm {
 
     /// Retrieve the EC_POINT and EC_PARAMS key parameters
     ///
-    /// EC_POINT is returned in raw, uncompressed form, i.e., NOT ASN.1 DER
+    /// EC_POINT is returned in raw, uncompressed form, i.e., NOT ASN.1 DER.
+    /// For Edwards curves, this is the raw public key.
     ///
-    /// Note: Only works with EC keys
+    /// Note: Only works with EC and EC Edwards keys
     pub fn ec_info(&self, mechanism: HsmMechanismType) -> Result<(Vec<u8>, Vec<u8>), ManyError> {
         let pkcs11 = self
             .pkcs11
@@ -179,18 +197,21 @@ impl Hsm {
             ManyError::hsm_session_error("No PKCS#11 open session found".to_string())
         })?;
 
-        trace!("Making sure we can fetch uncompressed EC_POINT");
-        let slot = session
-            .get_session_info()
-            .map_err(|e| ManyError::hsm_ec_point_error(e.to_string()))?
-            .slot_id();
-        let uncompress = pkcs11
-            .get_mechanism_info(slot, mechanism)
-            .map_err(|e| ManyError::hsm_ec_point_error(e.to_string()))?
-            .flags()
-            .ec_uncompress();
-        if !uncompress {
-            panic!("Could not fetch uncompressed EC_POINT");
+        // Edwards curve points have a single encoding
+        if mechanism != HsmMechanismType::EDDSA {
+            trace!("Making sure we can fetch uncompressed EC_POINT");
+            let slot = session
+                .get_session_info()
+                .map_err(|e| ManyError::hsm_ec_point_error(e.to_string()))?
+                .slot_id();
+            let uncompress = pkcs11
+                .get_mechanism_info(slot, mechanism)
+                .map_err(|e| ManyError::hsm_ec_point_error(e.to_string()))?
+                .flags()
+                .ec_uncompress();
+            if !uncompress {
+                panic!("Could not fetch uncompressed EC_POINT");
+            }
         }
 
         let verifier = self.verifier()?;
@@ -314,23 +335,59 @@ impl Hsm {
 pub struct HsmIdentity {
     address: Address,
     key: CoseKey,
+    mechanism: HsmMechanismType,
+}
+
+/// Build the COSE public key of the HSM key for the given mechanism
+fn hsm_cose_key(hsm: &Hsm, mechanism: HsmMechanismType) -> Result<CoseKey, ManyError> {
+    let (raw_points, ec_params) = hsm.ec_info(mechanism)?;
+    match mechanism {
+        HsmMechanismType::ECDSA => {
+            trace!("Creating NIST P-256 SEC1 encoded point");
+            let points = p256::EncodedPoint::from_bytes(raw_points).map_err(ManyError::unknown)?;
+
+            let key = many_identity_dsa::ecdsa::ecdsa_cose_key(
+                (points.x().unwrap().to_vec(), points.y().unwrap().to_vec()),
+                None,
+            );
+            many_identity_dsa::ecdsa::public_key(&key)?
+                .ok_or_else(|| ManyError::unknown("Could not load key."))
+        }
+        HsmMechanismType::EDDSA => {
+            trace!("Making sure the EC Edwards key is on Ed25519");
+            if ec_params != ED25519_OID && ec_params != ED25519_NAME {
+                return Err(ManyError::hsm_ec_params_error(format!(
+                    "Unsupported EC Edwards curve: {}",
+                    hex::encode(ec_params)
+                )));
+            }
+            if raw_points.len() != 32 {
+                return Err(ManyError::hsm_ec_point_error(format!(
+                    "Invalid Ed25519 public key length: {}",
+                    raw_points.len()
+                )));
+            }
+
+            Ok(many_identity_dsa::ed25519::eddsa_cose_key(raw_points, None))
+        }
+        _ => Err(ManyError::unknown(format!(
+            "Unsupported HSM mechanism: {mechanism:?}"
+        ))),
+    }
 }
 
 impl HsmIdentity {
+    /// Create an identity from the HSM key. Supported mechanisms are ECDSA
+    /// (NIST P-256) and EdDSA (Ed25519).
     pub fn new(mechanism: HsmMechanismType) -> Result<Self, ManyError> {
         let hsm = Hsm::get_instance()?;
-        let (raw_points, _) = hsm.ec_info(mechanism)?;
-        trace!("Creating NIST P-256 SEC1 encoded point");
-        let points = p256::EncodedPoint::from_bytes(raw_points).map_err(ManyError::unknown)?;
-
-        let key = many_identity_dsa::ecdsa::ecdsa_cose_key(
-            (points.x().unwrap().to_vec(), points.y().unwrap().to_vec()),
-            None,
-        );
-        let public_key = many_identity_dsa::ecdsa::public_key(&key)?
-            .ok_or_else(|| ManyError::unknown("Could not load key."))?;
-        let address = unsafe { cose::address_unchecked(&public_key) }?;
-        Ok(Self { address, key })
+        let key = hsm_cose_key(&hsm, mechanism)?;
+        let address = unsafe { cose::address_unchecked(&key) }?;
+        Ok(Self {
+            address,
+            key,
+            mechanism,
+        })
     }
 }
 
@@ -348,8 +405,10 @@ impl many_identity::Identity for HsmIdentity {
         let mut envelope = add_keyset_header(envelope, self)?;
 
         // Add the algorithm and key id.
-        envelope.protected.header.alg =
-            Some(coset::Algorithm::Assigned(coset::iana::Algorithm::ES256));
+        envelope.protected.header.alg = Some(coset::Algorithm::Assigned(match self.mechanism {
+            HsmMechanismType::EDDSA => coset::iana::Algorithm::EdDSA,
+            _ => coset::iana::Algorithm::ES256,
+        }));
         envelope.protected.header.key_id = self.address.to_vec();
 
         let builder = CoseSign1Builder::new()
@@ -366,11 +425,20 @@ impl many_identity::Identity for HsmIdentity {
             .try_create_signature(&[], |bytes| {
                 use sha2::Digest;
 
-                trace!("Digesting message using SHA256 (CPU)");
-                let digest = sha2::Sha256::digest(bytes);
+                let msg_signature = match self.mechanism {
+                    // EdDSA signs the whole message
+                    HsmMechanismType::EDDSA => {
+                        trace!("Signing message using HSM");
+                        hsm.sign(bytes, &HsmMechanism::Eddsa)?
+                    }
+                    _ => {
+                        trace!("Digesting message using SHA256 (CPU)");
+                        let digest = sha2::Sha256::digest(bytes);
 
-                trace!("Singning message using HSM");
-                let msg_signature = hsm.sign(digest.as_slice(), &HsmMechanism::Ecdsa)?;
+                        trace!("Singning message using HSM");
+                        hsm.sign(digest.as_slice(), &HsmMechanism::Ecdsa)?
+                    }
+                };
                 trace!("Message signature is {}", hex::encode(&msg_signature));
 
                 Ok(msg_signature)
@@ -411,6 +479,17 @@ mod tests {
             Attribute::Id(KEYPAIR_TEST_ID.to_vec()),
         ]
     });
+    static EDDSA_PUB_KEY_TEMPLATE: Lazy<Vec<Attribute>> = Lazy::new(|| {
+        vec![
+            Attribute::Token(true),
+            Attribute::Private(false),
+            Attribute::KeyType(KeyType::EC_EDWARDS),
+            Attribute::Verify(true),
+            Attribute::EcParams(ED25519_OID.to_vec()),
+            Attribute::Id(KEYPAIR_TEST_ID.to_vec()),
+        ]
+    });
+
     static ECDSA_PRIV_KEY_TEMPLATE: Lazy<Vec<Attribute>> = Lazy::new(|| {
         vec![
             Attribute::Token(true),
@@ -640,4 +719,42 @@ mod tests {
         hsm.close_session();
         Ok(())
     }
+
+    /// Test that EdDSA message signing works on the HSM and that the
+    /// resulting signature can be verified on the CPU with the COSE key
+    /// built for the identity
+    ///
+    /// This test will initialize a new token and generate a new Ed25519 keypair.
+    /// The keypair will be destroyed at the end of the test, but the token will remain initialized.
+    #[test]
+    fn hsm_eddsa_sign_verify() -> Result<(), ManyError> {
+        let slot = init()?;
+
+        let mut hsm = Hsm::get_instance()?;
+        hsm.open_session(
+            slot,
+            HsmSessionType::RW, // We need to open a RW session since we're destroying the keys at the end of the test
+            Some(HsmUserType::User),
+            Some(USER_PIN.to_string()),
+        )?;
+
+        let (public, private) = hsm.generate_key_pair(
+            &Mechanism::EccEdwardsKeyPairGen,
+            &EDDSA_PUB_KEY_TEMPLATE,
+            &ECDSA_PRIV_KEY_TEMPLATE,
+        )?;
+
+        let hsm_signature = hsm.sign(MSG.as_bytes(), &HsmMechanism::Eddsa)?;
+        hsm.verify(MSG.as_bytes(), &hsm_signature, &HsmMechanism::Eddsa)?;
+
+        let key = hsm_cose_key(&hsm, HsmMechanismType::EDDSA)?;
+        many_identity_dsa::ed25519::Ed25519Verifier::from_key(&key)?
+            .verify_signature(&hsm_signature, MSG.as_bytes())?;
+
+        hsm.destroy(private)?;
+        hsm.destroy(public)?;
+
+        hsm.close_session();
+        Ok(())
+    }
 }
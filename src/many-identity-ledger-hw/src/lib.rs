@@ -0,0 +1,402 @@
+use coset::{CoseKey, CoseSign1, CoseSign1Builder};
+use ledger_apdu::APDUCommand;
+use ledger_transport::Exchange;
+use ledger_transport_hid::hidapi::HidApi;
+use ledger_transport_hid::TransportNativeHID;
+use ledger_zondax_generic::{App, AppExt, ChunkPayloadType, LedgerAppError};
+use many_error::ManyError;
+use many_identity::cose::add_keyset_header;
+use many_identity::{cose, Address};
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::trace;
+
+mod path;
+pub use path::{DerivationPath, DEFAULT_DERIVATION_PATH};
+
+/// Instruction class of the MANY application on the device.
+pub const CLA: u8 = 0xE0;
+
+/// Return the Ed25519 public key and address derived at a path.
+pub const INS_GET_ADDR: u8 = 0x01;
+
+/// Sign a message with the Ed25519 key derived at a path.
+pub const INS_SIGN: u8 = 0x02;
+
+/// Return the public key without asking the user to confirm it on the device.
+const P1_NO_CONFIRM: u8 = 0x00;
+
+/// Show the address on the device and wait for the user to confirm it.
+const P1_CONFIRM: u8 = 0x01;
+
+/// The size of an Ed25519 public key.
+const PUBLIC_KEY_LEN: usize = 32;
+
+/// The size of an Ed25519 signature.
+const SIGNATURE_LEN: usize = 64;
+
+/// Convert an error of the application framework to a [ManyError].
+fn app_error<E: std::error::Error>(error: LedgerAppError<E>) -> ManyError {
+    match error {
+        LedgerAppError::TransportError(e) => ManyError::ledger_hw_transport_error(e.to_string()),
+        LedgerAppError::AppSpecific(code, details) => {
+            ManyError::ledger_hw_device_error(format!("{code:#06x}"), details)
+        }
+        LedgerAppError::Unknown(code) => {
+            ManyError::ledger_hw_device_error(format!("{code:#06x}"), "Unknown error")
+        }
+        e => ManyError::ledger_hw_invalid_response(e.to_string()),
+    }
+}
+
+/// A Ledger device running the MANY application.
+///
+/// The device only holds the keys; it derives them from a BIP32 path and
+/// signs messages streamed to it in chunks, using the APDU protocol of the
+/// Zondax application framework. Every signature must be confirmed by the
+/// user on the device.
+pub struct LedgerHwDevice<E = TransportNativeHID> {
+    transport: E,
+}
+
+impl<E> std::fmt::Debug for LedgerHwDevice<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LedgerHwDevice").finish_non_exhaustive()
+    }
+}
+
+impl<E> App for LedgerHwDevice<E> {
+    const CLA: u8 = CLA;
+}
+
+impl<E> AppExt<E> for LedgerHwDevice<E>
+where
+    E: Exchange + Send + Sync,
+    E::Error: std::error::Error,
+{
+}
+
+impl LedgerHwDevice {
+    /// Connect to the first Ledger device found over HID.
+    pub fn connect() -> Result<Self, ManyError> {
+        trace!("Connecting to Ledger device over HID");
+        let api = HidApi::new().map_err(|e| ManyError::ledger_hw_transport_error(e.to_string()))?;
+        let transport = TransportNativeHID::new(&api)
+            .map_err(|e| ManyError::ledger_hw_transport_error(e.to_string()))?;
+        Ok(Self::new(transport))
+    }
+}
+
+impl<E> LedgerHwDevice<E>
+where
+    E: Exchange + Send + Sync,
+    E::Error: std::error::Error,
+    E::AnswerType: Send + Sync,
+{
+    pub fn new(transport: E) -> Self {
+        Self { transport }
+    }
+
+    /// Return the raw Ed25519 public key and the address derived at `path`.
+    /// If `confirm` is true, the user has to confirm the address on the
+    /// device.
+    pub fn address(
+        &self,
+        path: &DerivationPath,
+        confirm: bool,
+    ) -> Result<(Vec<u8>, Address), ManyError> {
+        trace!("Fetching address at {path}");
+        let command = APDUCommand {
+            cla: CLA,
+            ins: INS_GET_ADDR,
+            p1: if confirm { P1_CONFIRM } else { P1_NO_CONFIRM },
+            p2: 0x00,
+            data: path.to_bytes(),
+        };
+        let answer = futures::executor::block_on(self.transport.exchange(&command))
+            .map_err(|e| ManyError::ledger_hw_transport_error(e.to_string()))?;
+        <Self as AppExt<E>>::handle_response_error(&answer).map_err(app_error)?;
+
+        // The public key is followed by the textual address.
+        let invalid = || ManyError::ledger_hw_invalid_response(hex::encode(answer.data()));
+        if answer.data().len() <= PUBLIC_KEY_LEN {
+            return Err(invalid());
+        }
+        let (public_key, address) = answer.data().split_at(PUBLIC_KEY_LEN);
+        let address = std::str::from_utf8(address)
+            .ok()
+            .and_then(|a| Address::from_str(a).ok())
+            .ok_or_else(invalid)?;
+        Ok((public_key.to_vec(), address))
+    }
+
+    /// Sign `message` with the Ed25519 key derived at `path`.
+    ///
+    /// The first chunk contains the path, and the message follows in as many
+    /// chunks as needed.
+    pub fn sign(&self, path: &DerivationPath, message: &[u8]) -> Result<Vec<u8>, ManyError> {
+        trace!("Signing {} bytes at {path}", message.len());
+        let command = APDUCommand {
+            cla: CLA,
+            ins: INS_SIGN,
+            p1: ChunkPayloadType::Init as u8,
+            p2: 0x00,
+            data: path.to_bytes(),
+        };
+        let answer = futures::executor::block_on(<Self as AppExt<E>>::send_chunks(
+            &self.transport,
+            command,
+            message,
+        ))
+        .map_err(app_error)?;
+
+        match answer.data() {
+            signature if signature.len() == SIGNATURE_LEN => Ok(signature.to_vec()),
+            data => Err(ManyError::ledger_hw_invalid_response(hex::encode(data))),
+        }
+    }
+}
+
+/// An identity whose key lives on a Ledger device.
+pub struct LedgerHwIdentity<E = TransportNativeHID> {
+    address: Address,
+    key: CoseKey,
+    path: DerivationPath,
+    device: Arc<LedgerHwDevice<E>>,
+}
+
+impl<E> Clone for LedgerHwIdentity<E> {
+    fn clone(&self) -> Self {
+        Self {
+            address: self.address,
+            key: self.key.clone(),
+            path: self.path.clone(),
+            device: self.device.clone(),
+        }
+    }
+}
+
+impl<E> std::fmt::Debug for LedgerHwIdentity<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LedgerHwIdentity")
+            .field("address", &self.address)
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<E> LedgerHwIdentity<E>
+where
+    E: Exchange + Send + Sync,
+    E::Error: std::error::Error,
+    E::AnswerType: Send + Sync,
+{
+    /// Create an identity from the key derived at `path` on the device. If
+    /// `confirm` is true, the user has to confirm the address on the device.
+    pub fn new(
+        device: LedgerHwDevice<E>,
+        path: DerivationPath,
+        confirm: bool,
+    ) -> Result<Self, ManyError> {
+        let (x, device_address) = device.address(&path, confirm)?;
+        let key = many_identity_dsa::ed25519::eddsa_cose_key(x, None);
+        let address = unsafe { cose::address_unchecked(&key) }?;
+        if address != device_address {
+            return Err(ManyError::ledger_hw_invalid_response(format!(
+                "The device address {device_address} does not match its public key."
+            )));
+        }
+        Ok(Self {
+            address,
+            key,
+            path,
+            device: Arc::new(device),
+        })
+    }
+
+    /// The derivation path of the key on the device.
+    pub fn path(&self) -> &DerivationPath {
+        &self.path
+    }
+}
+
+impl<E> many_identity::Identity for LedgerHwIdentity<E>
+where
+    E: Exchange + Send + Sync,
+    E::Error: std::error::Error,
+    E::AnswerType: Send + Sync,
+{
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn public_key(&self) -> Option<CoseKey> {
+        Some(self.key.clone())
+    }
+
+    fn sign_1(&self, envelope: CoseSign1) -> Result<CoseSign1, ManyError> {
+        let mut envelope = add_keyset_header(envelope, self)?;
+
+        // Add the algorithm and key id.
+        envelope.protected.header.alg =
+            Some(coset::Algorithm::Assigned(coset::iana::Algorithm::EdDSA));
+        envelope.protected.header.key_id = self.address.to_vec();
+
+        let builder = CoseSign1Builder::new()
+            .protected(envelope.protected.header)
+            .unprotected(envelope.unprotected);
+
+        let builder = if let Some(payload) = envelope.payload {
+            builder.payload(payload)
+        } else {
+            builder
+        };
+
+        Ok(builder
+            .try_create_signature(&[], |bytes| {
+                trace!("Signing message using Ledger device, confirm on the device");
+                let signature = self.device.sign(&self.path, bytes)?;
+                trace!("Message signature is {}", hex::encode(&signature));
+                Ok(signature)
+            })?
+            .build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use ed25519_dalek::{Signer, SigningKey};
+    use ledger_apdu::APDUAnswer;
+    use many_identity::{Identity, Verifier};
+    use many_identity_dsa::ed25519::Ed25519Verifier;
+    use std::ops::Deref;
+    use std::sync::Mutex;
+
+    /// The size of the chunks `send_chunks` splits messages in.
+    const CHUNK_SIZE: usize = 250;
+
+    /// Emulates the MANY application, with a single key whatever the path.
+    struct MockDevice {
+        key: SigningKey,
+        message: Mutex<Vec<u8>>,
+        reject: bool,
+    }
+
+    impl MockDevice {
+        fn new(reject: bool) -> Self {
+            Self {
+                key: SigningKey::from_bytes(&[7; 32]),
+                message: Mutex::new(vec![]),
+                reject,
+            }
+        }
+
+        fn address(&self) -> Address {
+            let key = many_identity_dsa::ed25519::eddsa_cose_key(
+                self.key.verifying_key().to_bytes().to_vec(),
+                None,
+            );
+            unsafe { cose::address_unchecked(&key) }.unwrap()
+        }
+
+        fn answer(data: Vec<u8>, status: u16) -> APDUAnswer<Vec<u8>> {
+            APDUAnswer::from_answer([data, status.to_be_bytes().to_vec()].concat()).unwrap()
+        }
+    }
+
+    #[async_trait]
+    impl Exchange for MockDevice {
+        type Error = std::io::Error;
+        type AnswerType = Vec<u8>;
+
+        async fn exchange<I>(
+            &self,
+            command: &APDUCommand<I>,
+        ) -> Result<APDUAnswer<Self::AnswerType>, Self::Error>
+        where
+            I: Deref<Target = [u8]> + Send + Sync,
+        {
+            assert_eq!(command.cla, CLA);
+            let mut message = self.message.lock().unwrap();
+            let init = ChunkPayloadType::Init as u8;
+            let add = ChunkPayloadType::Add as u8;
+            let last = ChunkPayloadType::Last as u8;
+            Ok(match (command.ins, command.p1) {
+                (INS_GET_ADDR, _) => {
+                    let key = self.key.verifying_key().to_bytes();
+                    let address = self.address().to_string().into_bytes();
+                    Self::answer([key.to_vec(), address].concat(), 0x9000)
+                }
+                (INS_SIGN, p1) if p1 == init => {
+                    assert_eq!(&*command.data, DerivationPath::default().to_bytes());
+                    message.clear();
+                    Self::answer(vec![], 0x9000)
+                }
+                (INS_SIGN, p1) if p1 == add => {
+                    assert_eq!(command.data.len(), CHUNK_SIZE);
+                    message.extend_from_slice(&command.data);
+                    Self::answer(vec![], 0x9000)
+                }
+                (INS_SIGN, p1) if p1 == last && self.reject => {
+                    // The user rejected the transaction.
+                    Self::answer(vec![], 0x6986)
+                }
+                (INS_SIGN, p1) if p1 == last => {
+                    message.extend_from_slice(&command.data);
+                    Self::answer(self.key.sign(&message).to_bytes().to_vec(), 0x9000)
+                }
+                _ => Self::answer(vec![], 0x6d00),
+            })
+        }
+    }
+
+    fn identity(reject: bool) -> LedgerHwIdentity<MockDevice> {
+        LedgerHwIdentity::new(
+            LedgerHwDevice::new(MockDevice::new(reject)),
+            DerivationPath::default(),
+            false,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn address() {
+        let id = identity(false);
+        assert_eq!(id.address(), MockDevice::new(false).address());
+
+        let verifier = Ed25519Verifier::from_key(&id.public_key().unwrap()).unwrap();
+        let envelope = id.sign_1(CoseSign1::default()).unwrap();
+        assert_eq!(verifier.verify_1(&envelope).unwrap(), id.address());
+    }
+
+    #[test]
+    fn sign_chunks() {
+        let id = identity(false);
+        let verifier = Ed25519Verifier::from_key(&id.public_key().unwrap()).unwrap();
+
+        for len in [0, 1, CHUNK_SIZE, CHUNK_SIZE * 3 + 12] {
+            let envelope = CoseSign1 {
+                payload: Some(vec![1; len]),
+                ..Default::default()
+            };
+            let envelope = id.sign_1(envelope).unwrap();
+            assert!(verifier.verify_1(&envelope).is_ok(), "{len}");
+        }
+    }
+
+    #[test]
+    fn message_too_long() {
+        let device = LedgerHwDevice::new(MockDevice::new(false));
+        let message = vec![0; CHUNK_SIZE * u8::MAX as usize];
+        assert!(device.sign(&DerivationPath::default(), &message).is_err());
+    }
+
+    #[test]
+    fn rejected() {
+        let id = identity(true);
+        let err = id.sign_1(CoseSign1::default()).unwrap_err();
+        assert_eq!(err.code(), ManyError::ledger_hw_device_error("", "").code());
+    }
+}
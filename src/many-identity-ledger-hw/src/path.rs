@@ -0,0 +1,118 @@
+use many_error::ManyError;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The first hardened child index.
+const HARDENED: u32 = 0x8000_0000;
+
+/// The number of components of the paths the device derives keys from.
+const DEPTH: usize = 5;
+
+/// The default path used to derive keys on the device.
+pub const DEFAULT_DERIVATION_PATH: &str = "m/44'/1337'/0'/0'/0'";
+
+/// A BIP32 derivation path, e.g. `m/44'/1337'/0'/0'/0'`.
+///
+/// Ed25519 (SLIP-10) only supports hardened derivation, so every component
+/// must be hardened. Both `'` and `h` are accepted as hardened markers. The
+/// device only accepts paths of 5 components.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+    /// Serialize the path the way the device expects it; each component in
+    /// little endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.iter().flat_map(|c| c.to_le_bytes()).collect()
+    }
+}
+
+impl Default for DerivationPath {
+    fn default() -> Self {
+        DerivationPath::from_str(DEFAULT_DERIVATION_PATH).unwrap()
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = ManyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('/');
+        if parts.next() != Some("m") {
+            return Err(ManyError::ledger_hw_invalid_path(s));
+        }
+
+        let components = parts
+            .map(|part| {
+                let index = part
+                    .strip_suffix('\'')
+                    .or_else(|| part.strip_suffix('h'))
+                    .ok_or_else(|| ManyError::ledger_hw_invalid_path(s))?;
+                let index =
+                    u32::from_str(index).map_err(|_| ManyError::ledger_hw_invalid_path(s))?;
+                if index >= HARDENED {
+                    return Err(ManyError::ledger_hw_invalid_path(s));
+                }
+                Ok(index | HARDENED)
+            })
+            .collect::<Result<Vec<u32>, ManyError>>()?;
+
+        if components.len() != DEPTH {
+            return Err(ManyError::ledger_hw_invalid_path(s));
+        }
+
+        Ok(Self(components))
+    }
+}
+
+impl Display for DerivationPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("m")?;
+        for component in &self.0 {
+            write!(f, "/{}'", component & !HARDENED)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let path = DerivationPath::from_str("m/44'/1337'/0h/1'/2'").unwrap();
+        assert_eq!(path.to_string(), "m/44'/1337'/0'/1'/2'");
+        assert_eq!(
+            path.to_bytes(),
+            [
+                (44 | HARDENED).to_le_bytes(),
+                (1337 | HARDENED).to_le_bytes(),
+                HARDENED.to_le_bytes(),
+                (1 | HARDENED).to_le_bytes(),
+                (2 | HARDENED).to_le_bytes(),
+            ]
+            .concat()
+        );
+        assert_eq!(
+            DerivationPath::default().to_string(),
+            DEFAULT_DERIVATION_PATH
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        for path in [
+            "",
+            "m",
+            "44'/1337'",
+            "m/44'/1337'",
+            "m/44'/1337'/0",
+            "m/44'/foo'",
+            "m/2147483648'",
+            "m/0'/0'/0'/0'/0'/0'",
+        ] {
+            assert!(DerivationPath::from_str(path).is_err(), "{path}");
+        }
+    }
+}
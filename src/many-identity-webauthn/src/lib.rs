@@ -3,6 +3,9 @@ extern crate core;
 // Do not expose this. There's no need to know the internal works.
 mod challenge;
 
+mod policy;
+pub use policy::*;
+
 mod verifier;
 pub use verifier::*;
 
@@ -0,0 +1,273 @@
+use many_error::ManyError;
+use many_protocol::ManyUrl;
+use sha2::digest::Digest;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Authenticator data flag set when the user was present.
+const FLAG_USER_PRESENT: u8 = 0x01;
+
+/// Authenticator data flag set when the user was verified (e.g. PIN or
+/// biometrics).
+const FLAG_USER_VERIFIED: u8 = 0x04;
+
+/// Length of the authenticator data up to and including the signature counter.
+const MIN_AUTH_DATA_LENGTH: usize = 37;
+
+/// A domain name, or all the subdomains of a domain with a `*.` prefix (the
+/// domain itself is not matched).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DomainPattern {
+    domain: String,
+    subdomains: bool,
+}
+
+impl DomainPattern {
+    pub fn matches(&self, domain: &str) -> bool {
+        if self.subdomains {
+            domain
+                .strip_suffix(self.domain.as_str())
+                .map_or(false, |prefix| prefix.len() > 1 && prefix.ends_with('.'))
+        } else {
+            domain == self.domain
+        }
+    }
+}
+
+impl FromStr for DomainPattern {
+    type Err = ManyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (domain, subdomains) = match s.strip_prefix("*.") {
+            Some(domain) => (domain, true),
+            None => (s, false),
+        };
+        if domain.is_empty() || domain.contains('*') {
+            return Err(ManyError::unknown(format!(
+                "Invalid domain pattern: {s:?}."
+            )));
+        }
+        Ok(Self {
+            domain: domain.to_ascii_lowercase(),
+            subdomains,
+        })
+    }
+}
+
+impl Display for DomainPattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.subdomains {
+            f.write_str("*.")?;
+        }
+        f.write_str(&self.domain)
+    }
+}
+
+/// An allowed WebAuthn origin, e.g. `https://example.com:3000`, or
+/// `https://*.example.com` for all the subdomains of a domain.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OriginPattern {
+    scheme: String,
+    host: DomainPattern,
+    port: Option<u16>,
+}
+
+impl OriginPattern {
+    pub fn matches(&self, origin: &ManyUrl) -> bool {
+        origin.scheme() == self.scheme
+            && origin.port_or_known_default() == self.port
+            && origin.host_str().map_or(false, |h| self.host.matches(h))
+    }
+}
+
+impl FromStr for OriginPattern {
+    type Err = ManyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (url, subdomains) = match s.split_once("://*.") {
+            Some((scheme, rest)) => (format!("{scheme}://{rest}"), true),
+            None => (s.to_string(), false),
+        };
+        let url = ManyUrl::parse(&url).map_err(ManyError::unknown)?;
+        let host = url
+            .host_str()
+            .ok_or_else(|| ManyError::unknown(format!("Origin without a host: {s:?}.")))?;
+        let host = if subdomains {
+            format!("*.{host}")
+        } else {
+            host.to_string()
+        };
+
+        Ok(Self {
+            scheme: url.scheme().to_string(),
+            host: host.parse()?,
+            port: url.port_or_known_default(),
+        })
+    }
+}
+
+impl From<ManyUrl> for OriginPattern {
+    fn from(url: ManyUrl) -> Self {
+        Self {
+            scheme: url.scheme().to_string(),
+            host: DomainPattern {
+                domain: url.host_str().unwrap_or_default().to_string(),
+                subdomains: false,
+            },
+            port: url.port_or_known_default(),
+        }
+    }
+}
+
+/// What the WebAuthn verifier accepts, beyond a valid signature.
+///
+/// The relying party (RP) ID an envelope was signed for is only known by its
+/// hash in the authenticator data. As WebAuthn requires it to be the host of
+/// the origin or one of its parent domains, those are tried against the hash.
+#[derive(Clone, Debug, Default)]
+pub struct WebAuthnPolicy {
+    /// Allowed origins. All origins are allowed if [None].
+    pub origins: Option<Vec<OriginPattern>>,
+
+    /// Allowed RP IDs. All RP IDs are allowed if [None].
+    pub rp_ids: Option<Vec<DomainPattern>>,
+
+    /// Refuse envelopes whose user presence flag is not set.
+    pub require_user_presence: bool,
+
+    /// Refuse envelopes whose user verification flag is not set.
+    pub require_user_verification: bool,
+}
+
+impl WebAuthnPolicy {
+    pub fn with_origins(mut self, origins: impl IntoIterator<Item = OriginPattern>) -> Self {
+        self.origins = Some(origins.into_iter().collect());
+        self
+    }
+
+    pub fn with_rp_ids(mut self, rp_ids: impl IntoIterator<Item = DomainPattern>) -> Self {
+        self.rp_ids = Some(rp_ids.into_iter().collect());
+        self
+    }
+
+    pub fn with_user_presence(mut self, required: bool) -> Self {
+        self.require_user_presence = required;
+        self
+    }
+
+    pub fn with_user_verification(mut self, required: bool) -> Self {
+        self.require_user_verification = required;
+        self
+    }
+
+    pub fn check_origin(&self, origin: &ManyUrl) -> Result<(), ManyError> {
+        match &self.origins {
+            Some(patterns) if !patterns.iter().any(|p| p.matches(origin)) => {
+                Err(ManyError::unknown("Origin not allowed"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the RP ID hash and flags of the authenticator data.
+    pub fn check_auth_data(&self, origin: &ManyUrl, auth_data: &[u8]) -> Result<(), ManyError> {
+        if auth_data.len() < MIN_AUTH_DATA_LENGTH {
+            return Err(ManyError::unknown("`authData` is too short"));
+        }
+        let (rp_id_hash, flags) = (&auth_data[..32], auth_data[32]);
+
+        if let Some(patterns) = &self.rp_ids {
+            let host = origin.host_str().unwrap_or_default();
+            let rp_id = std::iter::successors(Some(host), |h| h.split_once('.').map(|(_, p)| p))
+                .find(|candidate| {
+                    sha2::Sha256::digest(candidate.as_bytes()).as_slice() == rp_id_hash
+                });
+            match rp_id {
+                Some(rp_id) if patterns.iter().any(|p| p.matches(rp_id)) => {}
+                _ => return Err(ManyError::unknown("RP ID not allowed")),
+            }
+        }
+
+        if self.require_user_presence && flags & FLAG_USER_PRESENT == 0 {
+            return Err(ManyError::unknown("User presence flag not set"));
+        }
+        if self.require_user_verification && flags & FLAG_USER_VERIFIED == 0 {
+            return Err(ManyError::unknown("User verification flag not set"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(s: &str) -> ManyUrl {
+        ManyUrl::parse(s).unwrap()
+    }
+
+    fn origin(s: &str) -> OriginPattern {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn domain_pattern() {
+        let exact: DomainPattern = "example.com".parse().unwrap();
+        assert!(exact.matches("example.com"));
+        assert!(!exact.matches("app.example.com"));
+
+        let wildcard: DomainPattern = "*.example.com".parse().unwrap();
+        assert!(wildcard.matches("app.example.com"));
+        assert!(wildcard.matches("a.b.example.com"));
+        assert!(!wildcard.matches("example.com"));
+        assert!(!wildcard.matches("badexample.com"));
+        assert_eq!(wildcard.to_string(), "*.example.com");
+
+        assert!("*".parse::<DomainPattern>().is_err());
+        assert!("a.*.com".parse::<DomainPattern>().is_err());
+    }
+
+    #[test]
+    fn origin_pattern() {
+        let exact = origin("https://example.com");
+        assert!(exact.matches(&url("https://example.com")));
+        assert!(exact.matches(&url("https://example.com:443")));
+        assert!(!exact.matches(&url("http://example.com")));
+        assert!(!exact.matches(&url("https://example.com:3000")));
+        assert!(!exact.matches(&url("https://app.example.com")));
+
+        let wildcard = origin("https://*.example.com:3000");
+        assert!(wildcard.matches(&url("https://app.example.com:3000")));
+        assert!(!wildcard.matches(&url("https://app.example.com")));
+        assert!(!wildcard.matches(&url("https://example.com:3000")));
+
+        assert_eq!(
+            OriginPattern::from(url("https://example.com")),
+            origin("https://example.com")
+        );
+        assert!("https://".parse::<OriginPattern>().is_err());
+    }
+
+    #[test]
+    fn auth_data() {
+        let origin = url("https://app.example.com");
+        let mut auth_data = sha2::Sha256::digest(b"example.com").to_vec();
+        auth_data.extend([FLAG_USER_PRESENT, 0, 0, 0, 1]);
+
+        let policy = WebAuthnPolicy::default();
+        assert!(policy.check_auth_data(&origin, &auth_data).is_ok());
+        assert!(policy.check_auth_data(&origin, &auth_data[..36]).is_err());
+
+        let policy = policy.with_rp_ids(["example.com".parse().unwrap()]);
+        assert!(policy.check_auth_data(&origin, &auth_data).is_ok());
+        let other = WebAuthnPolicy::default().with_rp_ids(["*.example.com".parse().unwrap()]);
+        assert!(other.check_auth_data(&origin, &auth_data).is_err());
+
+        let policy = policy.with_user_presence(true);
+        assert!(policy.check_auth_data(&origin, &auth_data).is_ok());
+        let policy = policy.with_user_verification(true);
+        assert!(policy.check_auth_data(&origin, &auth_data).is_err());
+        auth_data[32] |= FLAG_USER_VERIFIED;
+        assert!(policy.check_auth_data(&origin, &auth_data).is_ok());
+    }
+}
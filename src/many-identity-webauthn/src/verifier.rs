@@ -1,4 +1,5 @@
 use crate::challenge::Challenge;
+use crate::policy::WebAuthnPolicy;
 use base64::{engine::general_purpose, Engine as _};
 use coset::cbor::value::Value;
 use coset::{CborSerializable, CoseKey, CoseKeySet, CoseSign1, Label};
@@ -20,12 +21,20 @@ struct ClientData {
 /// Provide utility functions surrounding request and response messages.
 #[derive(Clone, Debug, Default)]
 pub struct WebAuthnVerifier {
-    allowed_origins: Option<Vec<ManyUrl>>,
+    policy: WebAuthnPolicy,
 }
 
 impl WebAuthnVerifier {
+    /// A verifier only accepting the given origins, or all origins if [None].
     pub fn new(allowed_origins: Option<Vec<ManyUrl>>) -> Self {
-        Self { allowed_origins }
+        Self::with_policy(WebAuthnPolicy {
+            origins: allowed_origins.map(|urls| urls.into_iter().map(Into::into).collect()),
+            ..Default::default()
+        })
+    }
+
+    pub fn with_policy(policy: WebAuthnPolicy) -> Self {
+        Self { policy }
     }
 
     pub fn get_keyset(&self, sign1: &CoseSign1) -> Option<CoseKeySet> {
@@ -66,8 +75,6 @@ impl WebAuthnVerifier {
         unprotected: BTreeMap<Label, Value>,
         key: CoseKey,
     ) -> Result<(), ManyError> {
-        let allowed_origins = &self.allowed_origins;
-
         tracing::trace!("We got a WebAuthn request");
         tracing::trace!("Getting `clientData` from unprotected header");
         let client_data = unprotected
@@ -87,11 +94,7 @@ impl WebAuthnVerifier {
 
         tracing::trace!("Verifying origin");
         let origin = ManyUrl::parse(&client_data_json.origin).map_err(ManyError::unknown)?;
-        if let Some(urls) = allowed_origins {
-            if !urls.contains(&origin) {
-                return Err(ManyError::unknown("Origin not allowed"));
-            }
-        }
+        self.policy.check_origin(&origin)?;
 
         tracing::trace!("Getting `authData` from unprotected header");
         let auth_data = unprotected
@@ -100,6 +103,9 @@ impl WebAuthnVerifier {
             .as_bytes()
             .ok_or_else(|| ManyError::unknown("`authData` entry is not Bytes"))?;
 
+        tracing::trace!("Verifying RP ID and flags");
+        self.policy.check_auth_data(&origin, auth_data)?;
+
         tracing::trace!("Getting `signature` from unprotected header");
         let signature = unprotected
             .get(&Label::Text("signature".to_string()))
@@ -337,6 +343,13 @@ mod tests {
         assert_eq!(request.map_err(|e| e.to_string()), Err(msg.to_string()));
     }
 
+    fn verify_with_policy(policy: WebAuthnPolicy, envelope: &CoseSign1) -> Result<(), String> {
+        WebAuthnVerifier::with_policy(policy)
+            .verify_1(envelope)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
     #[test]
     fn webauthn_ok() {
         let envelope = ENVELOPE.clone();
@@ -458,4 +471,54 @@ mod tests {
             "Unknown error: Protected header doesn't match `challenge`",
         );
     }
+
+    #[test]
+    fn webauthn_policy_rp_id() {
+        let policy = WebAuthnPolicy::default().with_rp_ids(["localhost".parse().unwrap()]);
+        assert_eq!(verify_with_policy(policy, &ENVELOPE), Ok(()));
+
+        let policy = WebAuthnPolicy::default().with_rp_ids(["*.localhost".parse().unwrap()]);
+        assert_eq!(
+            verify_with_policy(policy, &ENVELOPE),
+            Err("Unknown error: RP ID not allowed".to_string())
+        );
+    }
+
+    #[test]
+    fn webauthn_policy_flags() {
+        let policy = WebAuthnPolicy::default().with_user_presence(true);
+        assert_eq!(verify_with_policy(policy, &ENVELOPE), Ok(()));
+
+        let policy = WebAuthnPolicy::default().with_user_verification(true);
+        assert_eq!(
+            verify_with_policy(policy, &ENVELOPE),
+            Err("Unknown error: User verification flag not set".to_string())
+        );
+    }
+
+    #[test]
+    fn webauthn_policy_origins() {
+        let policy = WebAuthnPolicy::default().with_origins([
+            "https://foobar.com".parse().unwrap(),
+            "https://localhost:3000".parse().unwrap(),
+        ]);
+        assert_eq!(verify_with_policy(policy, &ENVELOPE), Ok(()));
+
+        // The origin is allowed, but does not match the signature.
+        let policy =
+            WebAuthnPolicy::default().with_origins(["https://*.foobar.com".parse().unwrap()]);
+        let envelope = get_tampered_request(Cose1FieldType::Unprotected(
+            UnprotectedHeaderFieldType::ClientData(ClientDataFieldType::Origin(
+                "https://app.foobar.com".to_string(),
+            )),
+        ));
+        assert_eq!(
+            verify_with_policy(policy.clone(), &envelope),
+            Err("Could not verify the signature: signature error.".to_string())
+        );
+        assert_eq!(
+            verify_with_policy(policy, &ENVELOPE),
+            Err("Unknown error: Origin not allowed".to_string())
+        );
+    }
 }
@@ -1,10 +1,14 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt::{Debug, Formatter};
+use core::str::FromStr;
 use many_error::ManyError;
 use sha3::digest::generic_array::typenum::Unsigned;
 use sha3::digest::OutputSizeUser;
 use sha3::Sha3_224;
-use std::convert::TryFrom;
-use std::fmt::{Debug, Formatter};
-use std::str::FromStr;
 
 #[cfg(feature = "minicbor")]
 mod minicbor;
@@ -248,7 +252,7 @@ impl PartialEq<Address> for Option<Address> {
 }
 
 impl Debug for Address {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_tuple("Identity")
             .field(&if self.is_anonymous() {
                 "anonymous".to_string()
@@ -272,8 +276,8 @@ impl Default for Address {
     }
 }
 
-impl std::fmt::Display for Address {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for Address {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.write_str(&self.0.to_string())
     }
 }
@@ -312,7 +316,7 @@ impl FromStr for Address {
 
 impl AsRef<[u8; MAX_IDENTITY_BYTE_LEN]> for Address {
     fn as_ref(&self) -> &[u8; MAX_IDENTITY_BYTE_LEN] {
-        let result: &[u8; MAX_IDENTITY_BYTE_LEN] = unsafe { std::mem::transmute(self) };
+        let result: &[u8; MAX_IDENTITY_BYTE_LEN] = unsafe { core::mem::transmute(self) };
         result
     }
 }
@@ -546,8 +550,8 @@ impl InnerAddress {
     }
 }
 
-impl std::fmt::Display for InnerAddress {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for InnerAddress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         if self.is_anonymous() {
             // Special case this.
             return write!(f, "maa");
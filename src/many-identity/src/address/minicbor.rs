@@ -1,7 +1,7 @@
+use core::str::FromStr;
 use minicbor::data::Type;
 use minicbor::encode::Write;
 use minicbor::{Decode, Decoder, Encode, Encoder};
-use std::str::FromStr;
 
 impl<C> Encode<C> for crate::Address {
     fn encode<W: Write>(
@@ -1,8 +1,10 @@
 use crate::address::{Address, InnerAddress};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Formatter;
+use core::str::FromStr;
 use serde::de::Error;
 use serde::{Deserialize, Serialize, Serializer};
-use std::fmt::Formatter;
-use std::str::FromStr;
 
 impl Serialize for Address {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -27,7 +29,7 @@ impl<'de> Deserialize<'de> for Address {
         impl<'de> serde::de::Visitor<'de> for Visitor {
             type Value = Address;
 
-            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+            fn expecting(&self, formatter: &mut Formatter) -> core::fmt::Result {
                 formatter.write_str("identity string or bytes")
             }
 
@@ -87,7 +89,7 @@ struct HumanReadableInnerIdentityVisitor;
 impl serde::de::Visitor<'_> for HumanReadableInnerIdentityVisitor {
     type Value = InnerAddress;
 
-    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut Formatter) -> core::fmt::Result {
         formatter.write_str("a textual MANY identity")
     }
 
@@ -111,7 +113,7 @@ struct InnerIdentityVisitor;
 impl serde::de::Visitor<'_> for InnerIdentityVisitor {
     type Value = InnerAddress;
 
-    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut Formatter) -> core::fmt::Result {
         formatter.write_str("a byte buffer")
     }
 
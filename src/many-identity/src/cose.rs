@@ -1,4 +1,5 @@
 use crate::{Address, Identity};
+use alloc::string::ToString;
 use coset::cbor::value::Value;
 use coset::{AsCborValue, CborSerializable, CoseKey, CoseKeySet, CoseSign1, Label};
 use many_error::ManyError;
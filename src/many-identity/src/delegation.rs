@@ -0,0 +1,326 @@
+//! Delegation certificates, allowing an identity to send requests on behalf
+//! of another one (e.g. a session key of a web wallet, without exposing the
+//! root key).
+//!
+//! A certificate is signed by its delegator (`from`), and allows its delegate
+//! (`to`) to act as the delegator until it expires. Delegates can delegate
+//! further, forming a chain from the root delegator to the signer of a request.
+use crate::{Address, Identity, Verifier};
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use coset::{CoseSign1, CoseSign1Builder};
+use many_error::ManyError;
+use minicbor::data::{Tag, Type};
+use minicbor::encode::Write;
+use minicbor::{Decode, Decoder, Encode, Encoder};
+
+/// The maximum number of certificates in a delegation chain.
+pub const MAXIMUM_DELEGATION_DEPTH: usize = 8;
+
+/// A delegation certificate.
+///
+/// ```cddl
+/// delegation = {
+///     0 => address,           ; The delegator.
+///     1 => address,           ; The delegate.
+///     2 => time,              ; Expiration, in seconds since the epoch.
+///     ? 3 => [* tstr],        ; Endpoints the delegate can call. All if absent.
+///     ? 4 => { * address => uint },   ; Maximum amount per token symbol the
+///                                     ; delegate can send per request. Other
+///                                     ; symbols cannot be sent.
+/// }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Delegation {
+    pub from: Address,
+    pub to: Address,
+    pub expiration: u64,
+    pub endpoints: Option<BTreeSet<String>>,
+    pub max_amounts: Option<BTreeMap<Address, u64>>,
+}
+
+impl Delegation {
+    pub fn new(from: Address, to: Address, expiration: u64) -> Self {
+        Self {
+            from,
+            to,
+            expiration,
+            endpoints: None,
+            max_amounts: None,
+        }
+    }
+
+    pub fn with_endpoints<S: ToString>(mut self, endpoints: impl IntoIterator<Item = S>) -> Self {
+        self.endpoints = Some(endpoints.into_iter().map(|e| e.to_string()).collect());
+        self
+    }
+
+    pub fn with_max_amount(mut self, symbol: Address, max_amount: u64) -> Self {
+        self.max_amounts
+            .get_or_insert_with(BTreeMap::new)
+            .insert(symbol, max_amount);
+        self
+    }
+
+    /// Whether the delegate can call `endpoint`.
+    pub fn allows_endpoint(&self, endpoint: &str) -> bool {
+        self.endpoints
+            .as_ref()
+            .map_or(true, |endpoints| endpoints.contains(endpoint))
+    }
+
+    /// Sign this certificate. The identity must be the delegator.
+    pub fn sign(&self, identity: &impl Identity) -> Result<CoseSign1, ManyError> {
+        if !identity.address().matches(&self.from) {
+            return Err(ManyError::invalid_delegation(
+                "certificates must be signed by their delegator",
+            ));
+        }
+        let payload = minicbor::to_vec(self).map_err(ManyError::serialization_error)?;
+        identity.sign_1(CoseSign1Builder::new().payload(payload).build())
+    }
+
+    /// Decode the certificate of an envelope, without verifying it.
+    pub fn from_envelope(envelope: &CoseSign1) -> Result<Self, ManyError> {
+        let payload = envelope
+            .payload
+            .as_ref()
+            .ok_or_else(ManyError::empty_envelope)?;
+        minicbor::decode(payload).map_err(ManyError::deserialization_error)
+    }
+}
+
+impl<C> Encode<C> for Delegation {
+    fn encode<W: Write>(
+        &self,
+        e: &mut Encoder<W>,
+        ctx: &mut C,
+    ) -> Result<(), minicbor::encode::Error<W::Error>> {
+        let len = 3 + self.endpoints.is_some() as u64 + self.max_amounts.is_some() as u64;
+        e.map(len)?;
+        e.u8(0)?.encode_with(self.from, ctx)?;
+        e.u8(1)?.encode_with(self.to, ctx)?;
+        e.u8(2)?.tag(Tag::Timestamp)?.u64(self.expiration)?;
+        if let Some(endpoints) = &self.endpoints {
+            e.u8(3)?.array(endpoints.len() as u64)?;
+            for endpoint in endpoints {
+                e.str(endpoint)?;
+            }
+        }
+        if let Some(max_amounts) = &self.max_amounts {
+            e.u8(4)?.map(max_amounts.len() as u64)?;
+            for (symbol, max_amount) in max_amounts {
+                e.encode_with(symbol, ctx)?.u64(*max_amount)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'b, C> Decode<'b, C> for Delegation {
+    fn decode(d: &mut Decoder<'b>, ctx: &mut C) -> Result<Self, minicbor::decode::Error> {
+        let len = d
+            .map()?
+            .ok_or_else(|| minicbor::decode::Error::message("Indefinite maps not supported"))?;
+
+        let mut from = None;
+        let mut to = None;
+        let mut expiration = None;
+        let mut endpoints = None;
+        let mut max_amounts = None;
+        for _ in 0..len {
+            match d.u8()? {
+                0 => from = Some(d.decode_with(ctx)?),
+                1 => to = Some(d.decode_with(ctx)?),
+                2 => {
+                    if d.datatype()? == Type::Tag && d.tag()? != Tag::Timestamp {
+                        return Err(minicbor::decode::Error::message("Invalid expiration tag"));
+                    }
+                    expiration = Some(d.u64()?);
+                }
+                3 => {
+                    let mut set = BTreeSet::new();
+                    for endpoint in d.array_iter::<String>()? {
+                        set.insert(endpoint?);
+                    }
+                    endpoints = Some(set);
+                }
+                4 => {
+                    let len = d.map()?.ok_or_else(|| {
+                        minicbor::decode::Error::message("Indefinite maps not supported")
+                    })?;
+                    let mut map = BTreeMap::new();
+                    for _ in 0..len {
+                        map.insert(d.decode_with(ctx)?, d.u64()?);
+                    }
+                    max_amounts = Some(map);
+                }
+                _ => return Err(minicbor::decode::Error::message("Unknown key")),
+            }
+        }
+
+        match (from, to, expiration) {
+            (Some(from), Some(to), Some(expiration)) => Ok(Self {
+                from,
+                to,
+                expiration,
+                endpoints,
+                max_amounts,
+            }),
+            _ => Err(minicbor::decode::Error::message("Missing delegation field")),
+        }
+    }
+}
+
+/// A verifier accepting delegation chains whose certificates are verified by
+/// an inner verifier. Envelopes themselves are also verified by the inner
+/// verifier.
+#[derive(Clone, Debug)]
+pub struct DelegatedVerifier<V: Verifier> {
+    inner: V,
+}
+
+impl<V: Verifier> DelegatedVerifier<V> {
+    pub fn new(inner: V) -> Self {
+        Self { inner }
+    }
+}
+
+impl<V: Verifier> Verifier for DelegatedVerifier<V> {
+    fn verify_1(&self, envelope: &CoseSign1) -> Result<Address, ManyError> {
+        self.inner.verify_1(envelope)
+    }
+
+    fn verify_delegation(
+        &self,
+        chain: &[CoseSign1],
+        signer: &Address,
+    ) -> Result<Vec<Delegation>, ManyError> {
+        if chain.is_empty() || chain.len() > MAXIMUM_DELEGATION_DEPTH {
+            return Err(ManyError::invalid_delegation(alloc::format!(
+                "chains must contain between 1 and {MAXIMUM_DELEGATION_DEPTH} certificates"
+            )));
+        }
+
+        // Walk the chain back from the signer to the root delegator.
+        let mut delegate = *signer;
+        let mut delegations = Vec::with_capacity(chain.len());
+        for envelope in chain.iter().rev() {
+            let delegator = self.inner.verify_1(envelope)?;
+            let delegation = Delegation::from_envelope(envelope)?;
+            if delegation.from.is_anonymous()
+                || delegation.from.is_illegal()
+                || !delegator.matches(&delegation.from)
+            {
+                return Err(ManyError::invalid_delegation(
+                    "certificate not signed by its delegator",
+                ));
+            }
+            if !delegate.matches(&delegation.to) {
+                return Err(ManyError::invalid_delegation(
+                    "certificate not delegating to the next identity in the chain",
+                ));
+            }
+            delegate = delegation.from;
+            delegations.push(delegation);
+        }
+
+        delegations.reverse();
+        Ok(delegations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::identity;
+    use crate::AcceptAllVerifier;
+    use coset::CoseKey;
+
+    /// Sets the key ID of envelopes to its address, for [AcceptAllVerifier].
+    struct KeyIdIdentity(Address);
+
+    impl Identity for KeyIdIdentity {
+        fn address(&self) -> Address {
+            self.0
+        }
+
+        fn public_key(&self) -> Option<CoseKey> {
+            None
+        }
+
+        fn sign_1(&self, mut envelope: CoseSign1) -> Result<CoseSign1, ManyError> {
+            envelope.protected.header.key_id = self.0.to_vec();
+            Ok(envelope)
+        }
+    }
+
+    fn sign(delegation: &Delegation) -> CoseSign1 {
+        delegation.sign(&KeyIdIdentity(delegation.from)).unwrap()
+    }
+
+    #[test]
+    fn encode_decode() {
+        let delegation = Delegation::new(identity(1), identity(2), 1_000)
+            .with_endpoints(["ledger.send"])
+            .with_max_amount(identity(9), 100)
+            .with_max_amount(identity(10), 5);
+        let envelope = sign(&delegation);
+        assert_eq!(Delegation::from_envelope(&envelope), Ok(delegation.clone()));
+
+        // Only the delegator can sign.
+        assert!(delegation.sign(&KeyIdIdentity(identity(3))).is_err());
+    }
+
+    #[test]
+    fn allows_endpoint() {
+        let delegation = Delegation::new(identity(1), identity(2), 1_000);
+        assert!(delegation.allows_endpoint("ledger.send"));
+        let delegation = delegation.with_endpoints(["ledger.balance"]);
+        assert!(delegation.allows_endpoint("ledger.balance"));
+        assert!(!delegation.allows_endpoint("ledger.send"));
+    }
+
+    #[test]
+    fn chain() {
+        let verifier = DelegatedVerifier::new(AcceptAllVerifier);
+        let first = Delegation::new(identity(1), identity(2), 1_000);
+        let second = Delegation::new(identity(2), identity(3), 1_000);
+        let chain = [sign(&first), sign(&second)];
+
+        assert_eq!(
+            verifier.verify_delegation(&chain, &identity(3)),
+            Ok(vec![first, second])
+        );
+        // Wrong signer, or certificates out of order.
+        assert!(verifier.verify_delegation(&chain, &identity(2)).is_err());
+        let reversed = [chain[1].clone(), chain[0].clone()];
+        assert!(verifier.verify_delegation(&reversed, &identity(3)).is_err());
+        assert!(verifier.verify_delegation(&[], &identity(3)).is_err());
+    }
+
+    #[test]
+    fn chain_signed_by_other() {
+        let verifier = DelegatedVerifier::new(AcceptAllVerifier);
+        let delegation = Delegation::new(identity(1), identity(2), 1_000);
+        let forged = CoseSign1Builder::new()
+            .payload(minicbor::to_vec(delegation).unwrap())
+            .build();
+        let forged = KeyIdIdentity(identity(2)).sign_1(forged).unwrap();
+        assert!(verifier.verify_delegation(&[forged], &identity(2)).is_err());
+    }
+
+    #[test]
+    fn unsupported() {
+        let chain = [sign(&Delegation::new(identity(1), identity(2), 1_000))];
+        assert!(AcceptAllVerifier
+            .verify_delegation(&chain, &identity(2))
+            .is_err());
+        assert!(
+            (AcceptAllVerifier, DelegatedVerifier::new(AcceptAllVerifier))
+                .verify_delegation(&chain, &identity(2))
+                .is_ok()
+        );
+    }
+}
@@ -1,5 +1,11 @@
 //! An Identity is a signer that also has an address on the MANY protocol.
+#[cfg(feature = "minicbor")]
+use crate::delegation::Delegation;
 use crate::Address;
+use alloc::boxed::Box;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
 use coset::{CoseKey, CoseSign1};
 use many_error::ManyError;
 
@@ -21,6 +27,25 @@ pub trait Identity: Send + Sync {
 /// the envelope, and returns it.
 pub trait Verifier: Send {
     fn verify_1(&self, envelope: &CoseSign1) -> Result<Address, ManyError>;
+
+    /// Verify a chain of delegation certificates, from the root delegator to
+    /// the `signer` of an envelope, and returns the certificates in the same
+    /// order. Delegation is refused unless the verifier supports it, see
+    /// [crate::delegation::DelegatedVerifier].
+    #[cfg(feature = "minicbor")]
+    fn verify_delegation(
+        &self,
+        chain: &[CoseSign1],
+        signer: &Address,
+    ) -> Result<Vec<Delegation>, ManyError> {
+        let _ = (chain, signer);
+        Err(delegation_unsupported())
+    }
+}
+
+#[cfg(feature = "minicbor")]
+fn delegation_unsupported() -> ManyError {
+    ManyError::invalid_delegation("delegation is not supported by this server")
 }
 
 #[derive(Debug, Clone)]
@@ -112,6 +137,15 @@ macro_rules! decl_verifier_impl {
             decl_redirection!(
                 fn verify_1(&self, envelope: &CoseSign1) -> Result<Address, ManyError>,
             );
+
+            #[cfg(feature = "minicbor")]
+            fn verify_delegation(
+                &self,
+                chain: &[CoseSign1],
+                signer: &Address,
+            ) -> Result<Vec<Delegation>, ManyError> {
+                (&**self).verify_delegation(chain, signer)
+            }
         }
         )+
     };
@@ -120,13 +154,13 @@ macro_rules! decl_verifier_impl {
 decl_identity_impl!(
     impl for Box<dyn Identity>;
     impl<I: Identity> for Box<I>;
-    impl<I: Identity + Sync> for std::sync::Arc<I>;
+    impl<I: Identity + Sync> for Arc<I>;
 );
 
 decl_verifier_impl!(
     impl for Box<dyn Verifier>;
     impl<I: Verifier> for Box<I>;
-    impl<I: Verifier + Sync> for std::sync::Arc<I>;
+    impl<I: Verifier + Sync> for Arc<I>;
 );
 
 macro_rules! declare_tuple_verifiers {
@@ -136,6 +170,16 @@ macro_rules! declare_tuple_verifiers {
             fn verify_1(&self, envelope: &CoseSign1) -> Result<Address, ManyError> {
                 self.0.verify_1(envelope)
             }
+
+            #[cfg(feature = "minicbor")]
+            #[inline]
+            fn verify_delegation(
+                &self,
+                chain: &[CoseSign1],
+                signer: &Address,
+            ) -> Result<Vec<Delegation>, ManyError> {
+                self.0.verify_delegation(chain, signer)
+            }
         }
     };
 
@@ -153,6 +197,25 @@ macro_rules! declare_tuple_verifiers {
 
                 Err(ManyError::could_not_verify_signature(errs.join(", ")))
             }
+
+            #[cfg(feature = "minicbor")]
+            fn verify_delegation(
+                &self,
+                chain: &[CoseSign1],
+                signer: &Address,
+            ) -> Result<Vec<Delegation>, ManyError> {
+                let mut errs = Vec::new();
+                $(
+                    match self. $index . verify_delegation(chain, signer) {
+                        Ok(d) => return Ok(d),
+                        Err(e) => errs.push(e),
+                    }
+                )*
+
+                // Report why a verifier supporting delegation refused it.
+                let unsupported = delegation_unsupported();
+                Err(errs.into_iter().find(|e| *e != unsupported).unwrap_or(unsupported))
+            }
         }
     };
 }
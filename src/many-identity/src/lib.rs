@@ -1,3 +1,7 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 mod address;
 pub use address::{Address, MAX_SUBRESOURCE_ID};
 
@@ -6,6 +10,9 @@ pub use identity::*;
 
 pub mod cose;
 
+#[cfg(feature = "minicbor")]
+pub mod delegation;
+
 #[cfg(feature = "testing")]
 pub mod testing {
     use super::Address;
@@ -10,6 +10,12 @@ define_attribute_many_error!(
         5: pub fn subres_alt_unsupported() => "Subresource alternative owner unsupported.",
         6: pub fn key_not_found() => "The key was not found.",
         7: pub fn cannot_disable_empty_key() => "Unable to disable an empty key.",
+        8: pub fn key_expired(expires_at) => "The key expired at {expires_at}.",
+        9: pub fn expiration_in_the_past() => "A key cannot expire in the past.",
+        10: pub fn cas_precondition_failed()
+            => "The current value of the key does not match the expected value.",
+        11: pub fn too_many_keys(count, max) => "Too many keys ({count}), limit is {max}.",
+        12: pub fn duplicate_key() => "The same key cannot be put more than once in a batch.",
     }
 );
 
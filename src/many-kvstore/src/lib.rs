@@ -1,3 +1,4 @@
 pub mod error;
 pub mod module;
+pub mod registry;
 pub mod storage;
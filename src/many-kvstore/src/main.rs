@@ -9,7 +9,7 @@ use many_modules::{abci_backend, account, events, kvstore};
 use many_protocol::ManyUrl;
 use many_server::transport::http::HttpServer;
 use many_server::ManyServer;
-use many_server_cache::{RequestCacheValidator, RocksDbCacheBackend};
+use many_server_cache::{ReplayPolicy, RequestCacheValidator, RocksDbCacheBackend};
 use std::collections::BTreeSet;
 use std::net::SocketAddr;
 use std::path::PathBuf;
@@ -22,6 +22,11 @@ mod storage;
 
 use module::*;
 
+#[cfg(feature = "memory-tracking")]
+#[global_allocator]
+static ALLOCATOR: many_server::server::TrackingAllocator =
+    many_server::server::TrackingAllocator::system();
+
 #[derive(Debug, Parser)]
 struct Opts {
     #[clap(flatten)]
@@ -58,6 +63,32 @@ struct Opts {
     #[clap(long)]
     allow_addrs: Option<PathBuf>,
 
+    /// Endpoints to disable on this server, e.g. `idstore.store`. Requests to
+    /// them are refused and they are not listed by `endpoints`.
+    /// Multiple occurences of this argument can be given.
+    #[clap(long = "disable-endpoint")]
+    disable_endpoint: Vec<String>,
+
+    /// Reject queries which allocate more than this many bytes during their
+    /// execution. Memory usage is not deterministic, so this cannot be used
+    /// in ABCI mode.
+    /// This requires the feature "memory-tracking" to be enabled.
+    #[cfg(feature = "memory-tracking")]
+    #[clap(long, conflicts_with = "abci")]
+    memory_budget: Option<usize>,
+
+    /// The memory budget of an endpoint, as `ENDPOINT=BYTES`, e.g.
+    /// `kvstore.query=10000000`, instead of `--memory-budget`.
+    /// Multiple occurences of this argument can be given.
+    /// This requires the feature "memory-tracking" to be enabled.
+    #[cfg(feature = "memory-tracking")]
+    #[clap(
+        long = "endpoint-memory-budget",
+        value_parser = parse_endpoint_memory_budget,
+        conflicts_with = "abci"
+    )]
+    endpoint_memory_budget: Vec<(String, usize)>,
+
     /// Application absolute URLs allowed to communicate with this server. Any
     /// application will be able to communicate with this server if left empty.
     /// Multiple occurences of this argument can be given.
@@ -69,6 +100,29 @@ struct Opts {
     /// messages.
     #[clap(long)]
     cache_db: Option<PathBuf>,
+
+    /// Remove requests from the cache after this many seconds. This must be
+    /// longer than the timeout of the server, or requests could be replayed.
+    /// Requests are kept forever by default.
+    #[clap(long, requires("cache-db"))]
+    cache_ttl: Option<u64>,
+
+    /// Answer requests that were already executed with their original
+    /// response, instead of a duplicated message error. Responses are kept
+    /// in the cache with their request.
+    #[clap(long, requires("cache-db"))]
+    cache_replay_responses: bool,
+}
+
+#[cfg(feature = "memory-tracking")]
+fn parse_endpoint_memory_budget(s: &str) -> Result<(String, usize), String> {
+    let (endpoint, bytes) = s
+        .split_once('=')
+        .ok_or_else(|| format!("Expected ENDPOINT=BYTES, got '{s}'"))?;
+    let bytes = bytes
+        .parse()
+        .map_err(|e| format!("Invalid budget '{bytes}': {e}"))?;
+    Ok((endpoint.to_string(), bytes))
 }
 
 fn main() {
@@ -81,8 +135,11 @@ fn main() {
         persistent,
         clean,
         allow_addrs,
+        disable_endpoint,
         allow_origin,
         cache_db,
+        cache_ttl,
+        cache_replay_responses,
     } = Opts::parse();
 
     common_flags.init_logging().unwrap();
@@ -162,11 +219,41 @@ fn main() {
         ));
         if abci {
             s.set_timeout(u64::MAX);
+            let height_module = module.clone();
+            s.set_block_height_fn(move || Some(height_module.lock().ok()?.height()));
             s.add_module(abci_backend::AbciModule::new(module));
+            s.add_module(abci_backend::AbciPriorityModule::new(many.clone()));
+        }
+
+        for method in disable_endpoint {
+            s.disable_endpoint(method);
+        }
+
+        #[cfg(feature = "memory-tracking")]
+        {
+            let Opts {
+                memory_budget,
+                endpoint_memory_budget,
+                ..
+            } = Opts::parse();
+            s.set_memory_budget(memory_budget);
+            for (method, bytes) in endpoint_memory_budget {
+                s.set_endpoint_memory_budget(method, bytes);
+            }
         }
 
         if let Some(p) = cache_db {
-            s.add_validator(RequestCacheValidator::new(RocksDbCacheBackend::new(p)));
+            let cache = RequestCacheValidator::new(RocksDbCacheBackend::new(p)).with_replay_policy(
+                if cache_replay_responses {
+                    ReplayPolicy::ReturnCachedResponse
+                } else {
+                    ReplayPolicy::Reject
+                },
+            );
+            s.add_validator(match cache_ttl {
+                Some(ttl) => cache.with_ttl(std::time::Duration::from_secs(ttl)),
+                None => cache,
+            });
         }
     }
     let mut many_server = HttpServer::new(many);
@@ -1,6 +1,6 @@
 use crate::{
     error,
-    storage::{AclMap, KvStoreStorage},
+    storage::{key_for_metadata, key_for_value, AclMap, KvStoreStorage},
 };
 use many_error::{ManyError, Reason};
 use many_identity::Address;
@@ -9,14 +9,18 @@ use many_modules::abci_backend::{
     ManyAbciModuleBackend,
 };
 use many_modules::account::Role;
-use many_modules::kvstore::list::{ListArgs, ListReturns};
+use many_modules::kvstore::list::{ListArgs, ListReturns, MAXIMUM_LIST_COUNT};
 use many_modules::kvstore::{
-    DisableArgs, DisableReturn, GetArgs, GetReturns, InfoArg, InfoReturns,
-    KvStoreCommandsModuleBackend, KvStoreModuleBackend, KvStoreTransferModuleBackend, PutArgs,
-    PutReturn, QueryArgs, QueryReturns, TransferArgs, TransferReturn,
+    CasArgs, CasReturn, DisableArgs, DisableReturn, GetArgs, GetReturns, InfoArg, InfoReturns,
+    KvStoreCommandsModuleBackend, KvStoreModuleBackend, KvStoreTransferModuleBackend, MultiGetArgs,
+    MultiGetReturns, MultiPutArgs, MultiPutReturn, PutArgs, PutReturn, QueryArgs, QueryReturns,
+    TransferArgs, TransferReturn, KVSTORE_MULTI_MAX_KEYS,
 };
+use many_protocol::context::Context;
 use many_types::{Either, Timestamp};
-use std::collections::BTreeMap;
+use minicbor::bytes::ByteVec;
+use sha3::{Digest, Sha3_256};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Debug;
 use std::path::Path;
 use tracing::info;
@@ -53,6 +57,27 @@ pub struct KvStoreMetadata {
 
     #[n(2)]
     pub previous_owner: Option<Address>,
+
+    #[n(3)]
+    #[serde(skip_deserializing)]
+    pub expires_at: Option<Timestamp>,
+
+    /// This is only set in `kvstore.query` responses, and never stored.
+    #[n(4)]
+    #[serde(skip_deserializing)]
+    pub hash: Option<ByteVec>,
+}
+
+impl KvStoreMetadata {
+    pub fn is_expired(&self, now: Timestamp) -> bool {
+        self.expires_at
+            .map_or(false, |expires_at| expires_at <= now)
+    }
+}
+
+/// The hash of a value, used to compare values in `kvstore.cas`.
+pub fn value_hash(value: &[u8]) -> Vec<u8> {
+    Sha3_256::digest(value).to_vec()
 }
 
 #[derive(Debug, serde::Deserialize, minicbor::Encode, minicbor::Decode)]
@@ -103,6 +128,11 @@ impl KvStoreModuleImpl {
 
         Ok(Self { storage })
     }
+
+    /// The height of the latest block committed.
+    pub fn height(&self) -> u64 {
+        self.storage.get_height()
+    }
 }
 
 // This module is always supported, but will only be added when created using an ABCI
@@ -117,6 +147,9 @@ impl ManyAbciModuleBackend for KvStoreModuleImpl {
                 ("kvstore.query".to_string(), EndpointInfo { is_command: false }),
                 ("kvstore.put".to_string(), EndpointInfo { is_command: true }),
                 ("kvstore.disable".to_string(), EndpointInfo { is_command: true }),
+                ("kvstore.cas".to_string(), EndpointInfo { is_command: true }),
+                ("kvstore.multiGet".to_string(), EndpointInfo { is_command: false }),
+                ("kvstore.multiPut".to_string(), EndpointInfo { is_command: true }),
                 ("kvstore.transfer".to_string(), EndpointInfo { is_command: true }),
                 ("kvstore.list".to_string(), EndpointInfo { is_command: false }),
 
@@ -130,6 +163,7 @@ impl ManyAbciModuleBackend for KvStoreModuleImpl {
                 ("account.info".to_string(), EndpointInfo { is_command: false }),
                 ("account.disable".to_string(), EndpointInfo { is_command: true }),
                 ("account.addFeatures".to_string(), EndpointInfo { is_command: true }),
+                ("account.removeFeatures".to_string(), EndpointInfo { is_command: true }),
 
                 // Events
                 ("events.info".to_string(), EndpointInfo { is_command: false }),
@@ -193,63 +227,276 @@ impl KvStoreModuleBackend for KvStoreModuleImpl {
         Ok(InfoReturns { hash: hash.into() })
     }
 
-    fn get(&self, _sender: &Address, args: GetArgs) -> Result<GetReturns, ManyError> {
+    fn get(
+        &self,
+        _sender: &Address,
+        args: GetArgs,
+        context: Context,
+    ) -> Result<GetReturns, ManyError> {
         let value = self.storage.get(&args.key)?;
+        self.storage.prove_state(context, proof_keys(&args.key))?;
         Ok(GetReturns {
             value: value.map(|x| x.into()),
         })
     }
 
-    fn query(&self, _sender: &Address, args: QueryArgs) -> Result<QueryReturns, ManyError> {
-        minicbor::decode(
+    fn query(
+        &self,
+        _sender: &Address,
+        args: QueryArgs,
+        context: Context,
+    ) -> Result<QueryReturns, ManyError> {
+        let mut returns: QueryReturns = minicbor::decode(
             &self
                 .storage
                 .get_metadata(&args.key)?
                 .ok_or_else(error::key_not_found)?,
         )
-        .map_err(|e| ManyError::deserialization_error(e.to_string()))
+        .map_err(|e| ManyError::deserialization_error(e.to_string()))?;
+        self.storage.prove_state(context, proof_keys(&args.key))?;
+
+        // An expired key is reported as disabled until it is removed.
+        if let Some(expires_at) = returns.expires_at {
+            if expires_at <= self.storage.now() {
+                let err = error::key_expired(expires_at.secs());
+                returns.disabled = Some(Either::Right(Reason::new(
+                    0,
+                    err.message().map(ToString::to_string),
+                    err.arguments().clone(),
+                )));
+                return Ok(returns);
+            }
+        }
+
+        if let Ok(Some(value)) = self.storage.get(&args.key) {
+            returns.hash = Some(value_hash(&value).into());
+        }
+        Ok(returns)
     }
 
     fn list(&self, _sender: &Address, args: ListArgs) -> Result<ListReturns, ManyError> {
+        let ListArgs {
+            count,
+            order,
+            filter,
+            prefix,
+            start_after,
+            with_values,
+        } = args;
+
+        let prefix: Vec<u8> = prefix.map(Into::into).unwrap_or_default();
+        let count = count.map_or(MAXIMUM_LIST_COUNT, |c| c.min(MAXIMUM_LIST_COUNT)) as usize;
+        let mut keys: Vec<Vec<u8>> = self
+            .storage
+            .list(
+                &prefix,
+                start_after.as_deref().map(|k| k.as_slice()),
+                order.unwrap_or_default(),
+                filter,
+            )
+            .map(|item| item.into_iter().skip(1).collect()) // Skip the delimiter
+            .take(count.saturating_add(1))
+            .collect();
+
+        // We listed one more key than requested to know if there are more.
+        let next = if keys.len() > count {
+            keys.truncate(count);
+            keys.last().cloned().map(Into::into)
+        } else {
+            None
+        };
+
+        let values = if with_values.unwrap_or(false) {
+            Some(
+                keys.iter()
+                    .map(|key| self.get_enabled(key).map(|v| v.map(Into::into)))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )
+        } else {
+            None
+        };
+
         Ok(ListReturns {
-            keys: self
-                .storage
-                .list(args.order.unwrap_or_default(), args.filter)
-                .map(|item| item.into_iter().skip(1).collect::<Vec<_>>().into()) // Skip the delimiter
-                .collect(),
+            keys: keys.into_iter().map(Into::into).collect(),
+            values,
+            next,
+        })
+    }
+
+    fn multi_get(
+        &self,
+        _sender: &Address,
+        args: MultiGetArgs,
+    ) -> Result<MultiGetReturns, ManyError> {
+        let MultiGetArgs { keys } = args;
+        if keys.len() > KVSTORE_MULTI_MAX_KEYS {
+            return Err(error::too_many_keys(keys.len(), KVSTORE_MULTI_MAX_KEYS));
+        }
+
+        Ok(MultiGetReturns {
+            values: keys
+                .iter()
+                .map(|key| self.get_enabled(key).map(|v| v.map(Into::into)))
+                .collect::<Result<_, _>>()?,
         })
     }
 }
 
-impl KvStoreCommandsModuleBackend for KvStoreModuleImpl {
-    fn put(&mut self, sender: &Address, args: PutArgs) -> Result<PutReturn, ManyError> {
-        let PutArgs {
-            key,
-            value,
-            alternative_owner,
-        } = args;
-        let owner = if let Some(alternative_owner) = alternative_owner {
+/// The storage keys proving the metadata and value of a key.
+fn proof_keys(key: &[u8]) -> Vec<Vec<u8>> {
+    vec![key_for_metadata(key), key_for_value(key)]
+}
+
+impl KvStoreModuleImpl {
+    /// The value of a key, or `None` if it is disabled or expired.
+    fn get_enabled(&self, key: &[u8]) -> Result<Option<Vec<u8>>, ManyError> {
+        match self.storage.get(key) {
+            Err(e)
+                if e.code() == error::key_disabled().code()
+                    || e.code() == error::key_expired("").code() =>
+            {
+                Ok(None)
+            }
+            value => value,
+        }
+    }
+
+    fn put_owner(
+        &self,
+        sender: &Address,
+        alternative_owner: Option<Address>,
+    ) -> Result<Address, ManyError> {
+        if let Some(alternative_owner) = alternative_owner {
             self.validate_alternative_owner(
                 sender,
                 &alternative_owner,
                 [Role::CanKvStorePut, Role::Owner],
             )?;
-            alternative_owner
+            Ok(alternative_owner)
         } else {
-            *sender
-        };
+            Ok(*sender)
+        }
+    }
 
-        self.verify_acl(&owner, &key)?;
+    /// Validate that `owner` can put `key`, and return the metadata to store.
+    fn put_metadata(
+        &self,
+        owner: Address,
+        key: &[u8],
+        expires_at: Option<Timestamp>,
+    ) -> Result<KvStoreMetadata, ManyError> {
+        if expires_at.map_or(false, |expires_at| expires_at <= self.storage.now()) {
+            return Err(error::expiration_in_the_past());
+        }
+        self.verify_acl(&owner, key)?;
 
-        let meta = KvStoreMetadata {
+        Ok(KvStoreMetadata {
             owner,
             disabled: Some(Either::Left(false)),
             previous_owner: None,
-        };
+            expires_at,
+            hash: None,
+        })
+    }
+}
+
+impl KvStoreCommandsModuleBackend for KvStoreModuleImpl {
+    fn put(&mut self, sender: &Address, args: PutArgs) -> Result<PutReturn, ManyError> {
+        let PutArgs {
+            key,
+            value,
+            alternative_owner,
+            expires_at,
+        } = args;
+
+        let owner = self.put_owner(sender, alternative_owner)?;
+        let meta = self.put_metadata(owner, &key, expires_at)?;
         self.storage.put(&meta, &key, value.into())?;
         Ok(PutReturn {})
     }
 
+    fn multi_put(
+        &mut self,
+        sender: &Address,
+        args: MultiPutArgs,
+    ) -> Result<MultiPutReturn, ManyError> {
+        let MultiPutArgs {
+            entries,
+            alternative_owner,
+        } = args;
+        if entries.len() > KVSTORE_MULTI_MAX_KEYS {
+            return Err(error::too_many_keys(entries.len(), KVSTORE_MULTI_MAX_KEYS));
+        }
+
+        // Validate every entry before putting any, so the batch is atomic.
+        let owner = self.put_owner(sender, alternative_owner)?;
+        let mut keys = BTreeSet::new();
+        let mut metas = Vec::with_capacity(entries.len());
+        for entry in &entries {
+            if !keys.insert(entry.key.as_slice()) {
+                return Err(error::duplicate_key());
+            }
+            metas.push(self.put_metadata(owner, &entry.key, entry.expires_at)?);
+        }
+
+        self.storage.put_many(
+            entries
+                .iter()
+                .zip(&metas)
+                .map(|(entry, meta)| (meta, entry.key.as_slice(), entry.value.to_vec())),
+        )?;
+        Ok(MultiPutReturn {})
+    }
+
+    fn cas(&mut self, sender: &Address, args: CasArgs) -> Result<CasReturn, ManyError> {
+        let CasArgs {
+            key,
+            value,
+            expected_value,
+            expected_hash,
+            alternative_owner,
+        } = args;
+
+        // An expired key is considered absent, as it is about to be removed.
+        let current = match self.storage.get(&key) {
+            Err(e) if e.code() == error::key_expired("").code() => None,
+            current => current?,
+        };
+
+        let matches = match current.as_deref() {
+            None => expected_value.is_none() && expected_hash.is_none(),
+            Some(_) if expected_value.is_none() && expected_hash.is_none() => false,
+            Some(current) => {
+                expected_value
+                    .as_ref()
+                    .map_or(true, |v| v.as_slice() == current)
+                    && expected_hash
+                        .as_ref()
+                        .map_or(true, |h| h.as_slice() == value_hash(current))
+            }
+        };
+        if !matches {
+            return Err(error::cas_precondition_failed());
+        }
+
+        // Keep the expiration of the current value.
+        let expires_at = if current.is_some() {
+            self.storage
+                .get_metadata(&key)?
+                .map(|cbor| minicbor::decode::<KvStoreMetadata>(&cbor))
+                .transpose()
+                .map_err(|e| ManyError::deserialization_error(e.to_string()))?
+                .and_then(|meta| meta.expires_at)
+        } else {
+            None
+        };
+
+        let owner = self.put_owner(sender, alternative_owner)?;
+        let meta = self.put_metadata(owner, &key, expires_at)?;
+        self.storage.put(&meta, &key, value.into())?;
+        Ok(CasReturn {})
+    }
+
     fn disable(&mut self, sender: &Address, args: DisableArgs) -> Result<DisableReturn, ManyError> {
         let DisableArgs {
             key,
@@ -272,6 +519,14 @@ impl KvStoreCommandsModuleBackend for KvStoreModuleImpl {
 
         self.verify_acl(owner, &key)?;
 
+        let metadata: KvStoreMetadata = minicbor::decode(
+            &self
+                .storage
+                .get_metadata(&key)?
+                .ok_or_else(error::key_not_found)?,
+        )
+        .map_err(|e| ManyError::deserialization_error(e.to_string()))?;
+
         let maybe_reason = if let Some(reason) = reason {
             Either::Right(reason)
         } else {
@@ -282,6 +537,8 @@ impl KvStoreCommandsModuleBackend for KvStoreModuleImpl {
             owner: *owner,
             disabled: Some(maybe_reason),
             previous_owner: None,
+            expires_at: metadata.expires_at,
+            hash: None,
         };
 
         self.storage.disable(&meta, &key)?;
@@ -329,6 +586,8 @@ impl KvStoreTransferModuleBackend for KvStoreModuleImpl {
             owner: args.new_owner,
             disabled: metadata.disabled,
             previous_owner: Some(metadata.owner),
+            expires_at: metadata.expires_at,
+            hash: None,
         };
         self.storage.transfer(&key, *owner, meta)?;
 
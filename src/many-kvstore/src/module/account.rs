@@ -33,14 +33,11 @@ fn validate_features_for_account(account: &account::Account) -> Result<(), ManyE
     Ok(())
 }
 
-fn validate_roles_for_account(account: &account::Account) -> Result<(), ManyError> {
+/// The roles allowed by the features of an account.
+pub(crate) fn allowed_roles_for_account(account: &account::Account) -> BTreeSet<account::Role> {
     let features = account.features();
 
     let mut allowed_roles = BTreeSet::from([account::Role::Owner]);
-    let mut account_roles = BTreeSet::<account::Role>::new();
-    for (_, r) in account.roles.iter() {
-        account_roles.extend(r.iter())
-    }
 
     // TODO: somehow keep this list updated with the above.
     if features
@@ -50,6 +47,16 @@ fn validate_roles_for_account(account: &account::Account) -> Result<(), ManyErro
         allowed_roles.append(&mut account::features::kvstore::AccountKvStore::roles());
     }
 
+    allowed_roles
+}
+
+fn validate_roles_for_account(account: &account::Account) -> Result<(), ManyError> {
+    let allowed_roles = allowed_roles_for_account(account);
+    let mut account_roles = BTreeSet::<account::Role>::new();
+    for (_, r) in account.roles.iter() {
+        account_roles.extend(r.iter())
+    }
+
     for r in account_roles {
         if !allowed_roles.contains(&r) {
             return Err(account::errors::unknown_role(r));
@@ -285,6 +292,20 @@ impl AccountModuleBackend for KvStoreModuleImpl {
             })
         }
     }
+
+    fn remove_features(
+        &mut self,
+        sender: &Address,
+        args: account::RemoveFeaturesArgs,
+    ) -> Result<account::RemoveFeaturesReturn, ManyError> {
+        let (account, _) = self.storage.get_account(&args.account);
+        let account = account.ok_or_else(|| account::errors::unknown_account(args.account))?;
+
+        account.needs_role(sender, [Role::Owner])?;
+        self.storage
+            .remove_features(account, args)
+            .map(|_| EmptyReturn)
+    }
 }
 
 impl KvStoreModuleImpl {
@@ -317,7 +338,8 @@ impl KvStoreModuleImpl {
             let meta: KvStoreMetadata = minicbor::decode(&meta_cbor)
                 .map_err(|e| ManyError::deserialization_error(e.to_string()))?;
 
-            if &meta.owner == sender {
+            // An expired key can be claimed by anyone.
+            if &meta.owner == sender || meta.is_expired(self.storage.now()) {
                 return Ok(());
             }
 
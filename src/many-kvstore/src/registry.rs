@@ -0,0 +1,78 @@
+//! The modules a kvstore server can serve, by name.
+use crate::module::account::AccountFeatureModule;
+use crate::module::allow_addrs::AllowAddrsModule;
+use crate::module::KvStoreModuleImpl;
+use many_identity::Address;
+use many_modules::account::features::Feature;
+use many_modules::{account, events, kvstore};
+use many_server::{ModuleConfig, ModuleRegistry};
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+
+/// What the kvstore modules are constructed from.
+pub struct KvStoreModuleContext {
+    pub module_impl: Arc<Mutex<KvStoreModuleImpl>>,
+
+    /// If set, only these addresses can execute kvstore commands.
+    pub allow_addrs: Option<BTreeSet<Address>>,
+}
+
+impl KvStoreModuleContext {
+    pub fn new(module_impl: Arc<Mutex<KvStoreModuleImpl>>) -> Self {
+        Self {
+            module_impl,
+            allow_addrs: None,
+        }
+    }
+}
+
+/// The names of the modules served by default, in the order they are added.
+pub fn default_modules() -> Vec<ModuleConfig> {
+    [
+        "kvstore",
+        "kvstore.commands",
+        "kvstore.transfer",
+        "events",
+        "account",
+    ]
+    .into_iter()
+    .map(ModuleConfig::new)
+    .collect()
+}
+
+pub fn registry() -> ModuleRegistry<KvStoreModuleContext> {
+    let mut registry = ModuleRegistry::new();
+    registry.register("kvstore", |server, context: &KvStoreModuleContext, _| {
+        server.add_module(kvstore::KvStoreModule::new(context.module_impl.clone()));
+        Ok(())
+    });
+    registry.register("kvstore.commands", |server, context, _| {
+        let inner = kvstore::KvStoreCommandsModule::new(context.module_impl.clone());
+        match &context.allow_addrs {
+            Some(allow_addrs) => server.add_module(AllowAddrsModule {
+                inner,
+                allow_addrs: allow_addrs.clone(),
+            }),
+            None => server.add_module(inner),
+        };
+        Ok(())
+    });
+    registry.register("kvstore.transfer", |server, context, _| {
+        server.add_module(kvstore::KvStoreTransferModule::new(
+            context.module_impl.clone(),
+        ));
+        Ok(())
+    });
+    registry.register("events", |server, context, _| {
+        server.add_module(events::EventsModule::new(context.module_impl.clone()));
+        Ok(())
+    });
+    registry.register("account", |server, context, _| {
+        server.add_module(AccountFeatureModule::new(
+            account::AccountModule::new(context.module_impl.clone()),
+            [Feature::with_id(2)],
+        ));
+        Ok(())
+    });
+    registry
+}
@@ -10,6 +10,7 @@ use merk::{
         Node::{Hash, KVHash, KV},
         Query,
     },
+    rocksdb::{self, IteratorMode, ReadOptions},
     BatchEntry, Op,
 };
 use serde::{Deserialize, Serialize};
@@ -27,6 +28,28 @@ use many_modules::kvstore::KeyFilterType;
 
 const KVSTORE_ROOT: &[u8] = b"s";
 const KVSTORE_ACL_ROOT: &[u8] = b"a";
+const KVSTORE_EXPIRATION_ROOT: &[u8] = b"/expirations/";
+
+/// The storage key of the value of a key.
+pub fn key_for_value(key: &[u8]) -> Vec<u8> {
+    [KVSTORE_ROOT, key].concat()
+}
+
+/// The storage key of the metadata (owner, expiration, ...) of a key.
+pub fn key_for_metadata(key: &[u8]) -> Vec<u8> {
+    [KVSTORE_ACL_ROOT, key].concat()
+}
+
+/// Keys are indexed by expiration time so expired keys can be found without
+/// scanning the whole store.
+fn key_for_expiration(expires_at: Timestamp, key: &[u8]) -> Vec<u8> {
+    [
+        KVSTORE_EXPIRATION_ROOT,
+        &expires_at.secs().to_be_bytes(),
+        key,
+    ]
+    .concat()
+}
 
 #[derive(Serialize, Deserialize, Debug, Eq, Ord, PartialEq, PartialOrd)]
 #[serde(transparent)]
@@ -207,7 +230,39 @@ impl KvStoreStorage {
             })
     }
 
+    /// Remove the keys that expired, with their metadata.
+    fn remove_expired_keys(&mut self) {
+        let now = self.now().secs();
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(KVSTORE_EXPIRATION_ROOT));
+
+        let mut batch: Vec<BatchEntry> = Vec::new();
+        for item in self.persistent_store.iter_opt(IteratorMode::Start, options) {
+            let (index_key, _) = item.expect("Error while reading the DB");
+            let rest = &index_key[KVSTORE_EXPIRATION_ROOT.len()..];
+            let (secs, key) = rest.split_at(8);
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(secs);
+            if u64::from_be_bytes(bytes) > now {
+                break;
+            }
+
+            batch.push((index_key.to_vec(), Op::Delete));
+            batch.push(([KVSTORE_ACL_ROOT, key].concat(), Op::Delete));
+            batch.push(([KVSTORE_ROOT, key].concat(), Op::Delete));
+        }
+
+        if !batch.is_empty() {
+            // Keys in batch must be sorted.
+            batch.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+            self.persistent_store
+                .apply(&batch)
+                .expect("Could not remove expired keys");
+        }
+    }
+
     pub fn commit(&mut self) -> AbciCommitInfo {
+        self.remove_expired_keys();
         let _ = self.inc_height();
         self.persistent_store
             .apply(&[(
@@ -252,29 +307,43 @@ impl KvStoreStorage {
                     _ => return Err(error::key_disabled()),
                 }
             }
+            if let Some(expires_at) = meta.expires_at {
+                if expires_at <= self.now() {
+                    return Err(error::key_expired(expires_at.secs()));
+                }
+            }
         }
         self._get(key, KVSTORE_ROOT)
     }
 
-    pub fn list(
-        &self,
+    /// List the keys starting with `prefix`, after the key `start_after` if
+    /// set. Keys are returned with their storage prefix.
+    pub fn list<'a>(
+        &'a self,
+        prefix: &[u8],
+        start_after: Option<&[u8]>,
         order: SortOrder,
         filter: Option<Vec<KeyFilterType>>,
-    ) -> impl Iterator<Item = Vec<u8>> + '_ {
-        KvStoreIterator::all_keys(&self.persistent_store, order).filter_map(move |item| {
-            let (k, v) = item.ok()?;
-            if let Some(filters) = &filter {
-                if !filters.is_empty() {
-                    let meta: KvStoreMetadata = minicbor::decode(&v).ok()?;
-                    if filters.iter().all(|f| filter_key(f, &k, &meta)) {
-                        return Some(k.into_vec());
-                    } else {
-                        return None;
+    ) -> impl Iterator<Item = Vec<u8>> + 'a {
+        let start_key = start_after.map(|key| [KVSTORE_ACL_ROOT, key].concat());
+        KvStoreIterator::keys_with_prefix(&self.persistent_store, prefix, start_after, order)
+            .filter_map(move |item| {
+                let (k, v) = item.ok()?;
+                if start_key.as_deref() == Some(&*k) {
+                    return None;
+                }
+                if let Some(filters) = &filter {
+                    if !filters.is_empty() {
+                        let meta: KvStoreMetadata = minicbor::decode(&v).ok()?;
+                        if filters.iter().all(|f| filter_key(f, &k, &meta)) {
+                            return Some(k.into_vec());
+                        } else {
+                            return None;
+                        }
                     }
                 }
-            }
-            Some(k.into_vec())
-        })
+                Some(k.into_vec())
+            })
     }
 
     pub fn get_metadata(&self, key: &[u8]) -> Result<Option<Vec<u8>>, ManyError> {
@@ -287,27 +356,35 @@ impl KvStoreStorage {
         key: &[u8],
         value: Vec<u8>,
     ) -> Result<(), ManyError> {
+        self.put_many([(meta, key, value)])
+    }
+
+    /// Put every entry in a single batch, so either all or none of them are
+    /// stored. Keys must be unique.
+    pub fn put_many<'a>(
+        &mut self,
+        entries: impl IntoIterator<Item = (&'a KvStoreMetadata, &'a [u8], Vec<u8>)>,
+    ) -> Result<(), ManyError> {
+        let mut batch: Vec<BatchEntry> = Vec::new();
+        let mut events = Vec::new();
+        for (meta, key, value) in entries {
+            self.put_batch(&mut batch, meta, key, value.clone())?;
+            events.push(EventInfo::KvStorePut {
+                key: key.to_vec().into(),
+                value: value.into(),
+                owner: meta.owner,
+            });
+        }
+
+        // Keys in batch must be sorted.
+        batch.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
         self.persistent_store
-            .apply(&[
-                (
-                    [KVSTORE_ACL_ROOT.to_vec(), key.to_vec()].concat(),
-                    Op::Put(
-                        minicbor::to_vec(meta)
-                            .map_err(|e| ManyError::serialization_error(e.to_string()))?,
-                    ),
-                ),
-                (
-                    [KVSTORE_ROOT.to_vec(), key.to_vec()].concat(),
-                    Op::Put(value.clone()),
-                ),
-            ])
+            .apply(&batch)
             .map_err(|e| ManyError::unknown(e.to_string()))?;
 
-        self.log_event(EventInfo::KvStorePut {
-            key: key.to_vec().into(),
-            value: value.into(),
-            owner: meta.owner,
-        });
+        for event in events {
+            self.log_event(event);
+        }
 
         if !self.blockchain {
             self.persistent_store.commit(&[]).unwrap();
@@ -315,6 +392,46 @@ impl KvStoreStorage {
         Ok(())
     }
 
+    /// Add the operations putting `key` to `batch`.
+    fn put_batch(
+        &self,
+        batch: &mut Vec<BatchEntry>,
+        meta: &KvStoreMetadata,
+        key: &[u8],
+        value: Vec<u8>,
+    ) -> Result<(), ManyError> {
+        batch.extend([
+            (
+                [KVSTORE_ACL_ROOT.to_vec(), key.to_vec()].concat(),
+                Op::Put(
+                    minicbor::to_vec(meta)
+                        .map_err(|e| ManyError::serialization_error(e.to_string()))?,
+                ),
+            ),
+            (
+                [KVSTORE_ROOT.to_vec(), key.to_vec()].concat(),
+                Op::Put(value),
+            ),
+        ]);
+
+        // Replace the expiration of the previous value, if any.
+        let previous_expiration = self
+            .get_metadata(key)?
+            .map(|cbor| minicbor::decode::<KvStoreMetadata>(&cbor))
+            .transpose()
+            .map_err(|e| ManyError::deserialization_error(e.to_string()))?
+            .and_then(|previous| previous.expires_at);
+        if previous_expiration != meta.expires_at {
+            if let Some(expires_at) = previous_expiration {
+                batch.push((key_for_expiration(expires_at, key), Op::Delete));
+            }
+            if let Some(expires_at) = meta.expires_at {
+                batch.push((key_for_expiration(expires_at, key), Op::Put(vec![])));
+            }
+        }
+        Ok(())
+    }
+
     pub fn disable(&mut self, meta: &KvStoreMetadata, key: &[u8]) -> Result<(), ManyError> {
         self.persistent_store
             .apply(&[(
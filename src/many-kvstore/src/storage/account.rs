@@ -1,5 +1,5 @@
 use super::KvStoreStorage;
-use crate::module::account::validate_account;
+use crate::module::account::{allowed_roles_for_account, validate_account};
 use many_error::ManyError;
 use many_identity::Address;
 use many_modules::{account, events};
@@ -159,6 +159,26 @@ impl KvStoreStorage {
         self.commit_account(&args.account, account)
     }
 
+    /// Remove features from an account, with the roles only these features
+    /// allowed.
+    pub fn remove_features(
+        &mut self,
+        mut account: account::Account,
+        args: account::RemoveFeaturesArgs,
+    ) -> Result<Vec<u8>, ManyError> {
+        account.remove_features(&args.features)?;
+        let roles = account.retain_roles(&allowed_roles_for_account(&account));
+
+        validate_account(&account)?;
+
+        self.log_event(events::EventInfo::AccountRemoveFeatures {
+            account: args.account,
+            features: args.features,
+            roles,
+        });
+        self.commit_account(&args.account, account)
+    }
+
     pub fn commit_account(
         &mut self,
         id: &Address,
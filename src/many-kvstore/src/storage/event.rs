@@ -52,6 +52,7 @@ impl KvStoreStorage {
             id: self.new_event_id(),
             time: self.now(),
             content,
+            intent_hash: None,
         };
 
         self.persistent_store
@@ -1,6 +1,6 @@
 use many_types::SortOrder;
 use merk::rocksdb;
-use merk::rocksdb::{IteratorMode, ReadOptions};
+use merk::rocksdb::{Direction, IteratorMode, ReadOptions};
 use merk::tree::Tree;
 
 pub struct KvStoreIterator<'a> {
@@ -9,15 +9,33 @@ pub struct KvStoreIterator<'a> {
 
 impl<'a> KvStoreIterator<'a> {
     pub fn all_keys(merk: &'a merk::Merk, order: SortOrder) -> Self {
+        Self::keys_with_prefix(merk, &[], None, order)
+    }
+
+    /// Iterate the metadata of the keys starting with `prefix`. If `start_after`
+    /// is set, iteration starts at the key following it in the order requested.
+    /// The key `start_after` itself may still be returned first and should be
+    /// skipped by the caller.
+    pub fn keys_with_prefix(
+        merk: &'a merk::Merk,
+        prefix: &[u8],
+        start_after: Option<&[u8]>,
+        order: SortOrder,
+    ) -> Self {
         use crate::storage::KVSTORE_ACL_ROOT;
 
-        // Set the iterator bounds to iterate all multisig transactions.
+        // Set the iterator bounds to iterate all keys under the prefix.
         let mut options = ReadOptions::default();
-        options.set_iterate_range(rocksdb::PrefixRange(KVSTORE_ACL_ROOT));
-
-        let it_mode = match order {
-            SortOrder::Indeterminate | SortOrder::Ascending => IteratorMode::Start,
-            SortOrder::Descending => IteratorMode::End,
+        options.set_iterate_range(rocksdb::PrefixRange([KVSTORE_ACL_ROOT, prefix].concat()));
+
+        let start = start_after.map(|key| [KVSTORE_ACL_ROOT, key].concat());
+        let it_mode = match (order, &start) {
+            (SortOrder::Indeterminate | SortOrder::Ascending, None) => IteratorMode::Start,
+            (SortOrder::Descending, None) => IteratorMode::End,
+            (SortOrder::Indeterminate | SortOrder::Ascending, Some(key)) => {
+                IteratorMode::From(key, Direction::Forward)
+            }
+            (SortOrder::Descending, Some(key)) => IteratorMode::From(key, Direction::Reverse),
         };
 
         let inner = merk.iter_opt(it_mode, options);
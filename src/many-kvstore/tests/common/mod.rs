@@ -1,3 +1,4 @@
+use async_channel::unbounded;
 use many_error::{ManyError, Reason};
 use many_identity::testing::identity;
 use many_identity::{Address, Identity};
@@ -12,6 +13,7 @@ use many_modules::kvstore::{
     DisableArgs, DisableReturn, GetArgs, GetReturns, KeyFilterType, KvStoreCommandsModuleBackend,
     KvStoreModuleBackend, PutArgs, QueryArgs, QueryReturns,
 };
+use many_protocol::{context::Context, RequestMessage};
 use many_types::SortOrder;
 use once_cell::sync::Lazy;
 use std::cell::{Ref, RefCell, RefMut};
@@ -63,7 +65,10 @@ impl Setup {
         self.module_impl.init().expect("Could not init block");
 
         self.module_impl
-            .begin_block(AbciBlock { time: self.time })
+            .begin_block(AbciBlock {
+                time: self.time,
+                proposer: None,
+            })
             .expect("Could not begin block");
 
         let r = inner_f(self);
@@ -89,13 +94,15 @@ impl Setup {
                 key: key.into(),
                 value: value.into(),
                 alternative_owner: alt_owner,
+                expires_at: None,
             },
         )?;
         Ok(())
     }
 
     pub fn get(&self, sender: &Address, key: Vec<u8>) -> Result<GetReturns, ManyError> {
-        self.module_impl.get(sender, GetArgs { key: key.into() })
+        self.module_impl
+            .get(sender, GetArgs { key: key.into() }, context())
     }
 
     pub fn list(
@@ -110,6 +117,9 @@ impl Setup {
                 count: None,
                 order: Some(order),
                 filter,
+                prefix: None,
+                start_after: None,
+                with_values: None,
             },
         )
     }
@@ -134,10 +144,14 @@ impl Setup {
 
     pub fn query(&self, sender: &Address, key: Vec<u8>) -> Result<QueryReturns, ManyError> {
         self.module_impl
-            .query(sender, QueryArgs { key: key.into() })
+            .query(sender, QueryArgs { key: key.into() }, context())
     }
 }
 
+pub fn context() -> Context {
+    Context::new(RequestMessage::default(), unbounded().0)
+}
+
 pub fn setup() -> Setup {
     Setup::default()
 }
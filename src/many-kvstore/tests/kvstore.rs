@@ -5,10 +5,13 @@ use many_error::Reason;
 use many_identity::testing::identity;
 use many_identity::Address;
 use many_kvstore::error;
+use many_kvstore::module::value_hash;
+use many_modules::kvstore::list::{ListArgs, MAXIMUM_LIST_COUNT};
 use many_modules::kvstore::{
-    InfoArg, KeyFilterType, KvStoreModuleBackend, KvStoreTransferModuleBackend, TransferArgs,
+    CasArgs, InfoArg, KeyFilterType, KvStoreCommandsModuleBackend, KvStoreModuleBackend,
+    KvStoreTransferModuleBackend, MultiGetArgs, MultiPutArgs, MultiPutEntry, PutArgs, TransferArgs,
 };
-use many_types::{Either, SortOrder};
+use many_types::{Either, SortOrder, Timestamp};
 use minicbor::bytes::ByteVec;
 use std::collections::BTreeMap;
 
@@ -57,6 +60,55 @@ fn put_get_disable_block() {
     assert_eq!(get_value.unwrap_err().code(), error::key_disabled().code());
 }
 
+#[test]
+fn put_expires() {
+    let mut setup = Setup::new(true);
+    let id = setup.id;
+    let put = |setup: &mut Setup, expires_at| {
+        setup.module_impl.put(
+            &id,
+            PutArgs {
+                key: vec![1].into(),
+                value: vec![2].into(),
+                alternative_owner: None,
+                expires_at: Some(Timestamp::new(expires_at).unwrap()),
+            },
+        )
+    };
+
+    // Blocks start at 1_000_001 seconds and advance one second each.
+    let (_, put_past) = setup.block(|setup| put(setup, 1_000_000));
+    assert_eq!(
+        put_past.unwrap_err().code(),
+        error::expiration_in_the_past().code()
+    );
+    let (_, put) = setup.block(|setup| put(setup, 1_000_004));
+    assert!(put.is_ok());
+    setup.block(|setup| assert!(setup.get(&id, vec![1]).is_ok()));
+
+    setup.block(|setup| {
+        let get_value = setup.get(&id, vec![1]);
+        assert_eq!(
+            get_value.unwrap_err().code(),
+            error::key_expired(1_000_004).code()
+        );
+        let query_value = setup.query(&id, vec![1]).unwrap();
+        assert!(matches!(query_value.disabled, Some(Either::Right(_))));
+        assert_eq!(
+            query_value.expires_at,
+            Some(Timestamp::new(1_000_004).unwrap())
+        );
+    });
+
+    // The key was removed when the block was committed.
+    assert_eq!(
+        setup.query(&id, vec![1]).unwrap_err().code(),
+        error::key_not_found().code()
+    );
+    let put = setup.put(&identity(2), vec![1], vec![3], None);
+    assert!(put.is_ok());
+}
+
 #[test]
 fn put_put() {
     let mut setup = setup();
@@ -74,6 +126,108 @@ fn put_put() {
     assert_eq!(ByteVec::from(vec![3]), get_value);
 }
 
+#[test]
+fn cas() {
+    let mut setup = setup();
+    let id = setup.id;
+    let cas = |setup: &mut Setup, value: u8, expected_value, expected_hash| {
+        setup.module_impl.cas(
+            &id,
+            CasArgs {
+                key: vec![1].into(),
+                value: vec![value].into(),
+                expected_value,
+                expected_hash,
+                alternative_owner: None,
+            },
+        )
+    };
+
+    // Without expectations, the key must not exist.
+    assert!(cas(&mut setup, 2, None, None).is_ok());
+    assert_eq!(
+        cas(&mut setup, 3, None, None).unwrap_err().code(),
+        error::cas_precondition_failed().code()
+    );
+
+    assert_eq!(
+        cas(&mut setup, 3, Some(vec![4].into()), None)
+            .unwrap_err()
+            .code(),
+        error::cas_precondition_failed().code()
+    );
+    assert!(cas(&mut setup, 3, Some(vec![2].into()), None).is_ok());
+
+    let hash = setup.query(&id, vec![1]).unwrap().hash.unwrap();
+    assert_eq!(hash, ByteVec::from(value_hash(&[3])));
+    assert!(cas(&mut setup, 4, None, Some(hash.clone())).is_ok());
+    assert_eq!(
+        cas(&mut setup, 5, None, Some(hash)).unwrap_err().code(),
+        error::cas_precondition_failed().code()
+    );
+
+    let get_value = setup.get(&id, vec![1]).unwrap().value.unwrap();
+    assert_eq!(ByteVec::from(vec![4]), get_value);
+}
+
+#[test]
+fn multi_put_get() {
+    let mut setup = setup();
+    let id = setup.id;
+    let entry = |key: u8, value: u8| MultiPutEntry {
+        key: vec![key].into(),
+        value: vec![value].into(),
+        expires_at: None,
+    };
+
+    // Another owner holds key 3, so the whole batch fails.
+    assert!(setup.put(&identity(2), vec![3], vec![30], None).is_ok());
+    let multi_put = setup.module_impl.multi_put(
+        &id,
+        MultiPutArgs {
+            entries: vec![entry(1, 10), entry(2, 20), entry(3, 30)],
+            alternative_owner: None,
+        },
+    );
+    assert_eq!(
+        multi_put.unwrap_err().code(),
+        error::permission_denied().code()
+    );
+    assert!(setup.get(&id, vec![1]).unwrap().value.is_none());
+
+    let multi_put = setup.module_impl.multi_put(
+        &id,
+        MultiPutArgs {
+            entries: vec![entry(1, 10), entry(1, 20)],
+            alternative_owner: None,
+        },
+    );
+    assert_eq!(multi_put.unwrap_err().code(), error::duplicate_key().code());
+
+    let multi_put = setup.module_impl.multi_put(
+        &id,
+        MultiPutArgs {
+            entries: vec![entry(1, 10), entry(2, 20)],
+            alternative_owner: None,
+        },
+    );
+    assert!(multi_put.is_ok());
+
+    let multi_get = setup
+        .module_impl
+        .multi_get(
+            &id,
+            MultiGetArgs {
+                keys: vec![vec![1].into(), vec![2].into(), vec![4].into()],
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        multi_get.values,
+        vec![Some(vec![10].into()), Some(vec![20].into()), None]
+    );
+}
+
 #[test]
 fn put_put_unauthorized() {
     let mut setup = setup();
@@ -233,6 +387,83 @@ fn list_order() {
     );
 }
 
+#[test]
+fn list_prefix_paginated() {
+    let mut setup = setup();
+    let id = setup.id;
+    for k in ["a/1", "a/2", "a/3", "b/1"] {
+        setup
+            .put(&id, k.as_bytes().to_vec(), k.as_bytes().to_vec(), None)
+            .unwrap();
+    }
+    setup.disable(&id, b"a/2".to_vec(), None, None).unwrap();
+
+    let list = |start_after: Option<&str>, order: SortOrder| {
+        setup
+            .module_impl
+            .list(
+                &id,
+                ListArgs {
+                    count: Some(2),
+                    order: Some(order),
+                    filter: None,
+                    prefix: Some(b"a/".to_vec().into()),
+                    start_after: start_after.map(|k| k.as_bytes().to_vec().into()),
+                    with_values: Some(true),
+                },
+            )
+            .unwrap()
+    };
+
+    let page = list(None, SortOrder::Ascending);
+    assert_eq!(
+        page.keys,
+        vec![b"a/1".to_vec().into(), b"a/2".to_vec().into()]
+    );
+    assert_eq!(page.values, Some(vec![Some(b"a/1".to_vec().into()), None]));
+    assert_eq!(page.next, Some(b"a/2".to_vec().into()));
+
+    let page = list(Some("a/2"), SortOrder::Ascending);
+    assert_eq!(page.keys, vec![b"a/3".to_vec().into()]);
+    assert_eq!(page.next, None);
+
+    let page = list(Some("a/3"), SortOrder::Descending);
+    assert_eq!(
+        page.keys,
+        vec![b"a/2".to_vec().into(), b"a/1".to_vec().into()]
+    );
+    assert_eq!(page.next, None);
+}
+
+#[test]
+fn list_count_is_capped() {
+    let mut setup = setup();
+    let id = setup.id;
+    for i in 0..=MAXIMUM_LIST_COUNT {
+        let key = format!("{i:05}").into_bytes();
+        setup.put(&id, key.clone(), key, None).unwrap();
+    }
+
+    for count in [None, Some(u64::MAX)] {
+        let page = setup
+            .module_impl
+            .list(
+                &id,
+                ListArgs {
+                    count,
+                    order: None,
+                    filter: None,
+                    prefix: None,
+                    start_after: None,
+                    with_values: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(page.keys.len() as u64, MAXIMUM_LIST_COUNT);
+        assert_eq!(page.next, page.keys.last().cloned());
+    }
+}
+
 #[test]
 fn list_filter_with_owner() {
     let mut setup = setup();
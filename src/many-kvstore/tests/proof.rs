@@ -0,0 +1,123 @@
+pub mod common;
+
+use crate::common::setup;
+use async_channel::unbounded;
+use many_identity::Address;
+use many_kvstore::storage::{key_for_metadata, key_for_value};
+use many_ledger_verify::proof::verify_proof;
+use many_modules::kvstore::{GetArgs, InfoArg, KvStoreModuleBackend, QueryArgs};
+use many_protocol::context::{Context, ProofResult};
+use many_protocol::RequestMessage;
+use many_types::{ProofOperation, PROOF};
+
+/// A context requesting a proof, and the channel the proof is sent to.
+fn proof_context() -> (Context, async_channel::Receiver<ProofResult>) {
+    let (transmitter, receiver) = unbounded();
+    let request = RequestMessage::default()
+        .with_from(Address::anonymous())
+        .with_attribute(PROOF);
+    (Context::new(request, transmitter), receiver)
+}
+
+fn operations(receiver: async_channel::Receiver<ProofResult>) -> Vec<ProofOperation> {
+    match receiver.try_recv() {
+        Ok(ProofResult::Proof(operations)) => operations,
+        _ => panic!("No proof was sent."),
+    }
+}
+
+#[test]
+fn anonymous_get() {
+    let mut setup = setup();
+    let id = setup.id;
+    setup.put(&id, vec![1], vec![2], None).unwrap();
+    let root = setup.module_impl.info(&id, InfoArg {}).unwrap().hash;
+
+    let (context, receiver) = proof_context();
+    let value = setup
+        .module_impl
+        .get(
+            &Address::anonymous(),
+            GetArgs {
+                key: vec![1].into(),
+            },
+            context,
+        )
+        .unwrap()
+        .value;
+    assert_eq!(value, Some(vec![2].into()));
+
+    let values = verify_proof(
+        operations(receiver),
+        &root,
+        [key_for_value(&[1]), key_for_metadata(&[1])],
+    )
+    .unwrap();
+    assert_eq!(values[&key_for_value(&[1])], Some(vec![2]));
+    assert!(values[&key_for_metadata(&[1])].is_some());
+
+    // The proof does not hold for another root.
+    let (context, receiver) = proof_context();
+    setup
+        .module_impl
+        .get(
+            &Address::anonymous(),
+            GetArgs {
+                key: vec![1].into(),
+            },
+            context,
+        )
+        .unwrap();
+    assert!(verify_proof(operations(receiver), &[0; 32], [key_for_value(&[1])]).is_err());
+}
+
+#[test]
+fn anonymous_query() {
+    let mut setup = setup();
+    let id = setup.id;
+    setup.put(&id, vec![1], vec![2], None).unwrap();
+    let root = setup.module_impl.info(&id, InfoArg {}).unwrap().hash;
+
+    let (context, receiver) = proof_context();
+    let owner = setup
+        .module_impl
+        .query(
+            &Address::anonymous(),
+            QueryArgs {
+                key: vec![1].into(),
+            },
+            context,
+        )
+        .unwrap()
+        .owner;
+    assert_eq!(owner, id);
+
+    let values = verify_proof(operations(receiver), &root, [key_for_metadata(&[1])]).unwrap();
+    let metadata: many_modules::kvstore::QueryReturns =
+        minicbor::decode(values[&key_for_metadata(&[1])].as_ref().unwrap()).unwrap();
+    assert_eq!(metadata.owner, id);
+}
+
+#[test]
+fn no_proof_requested() {
+    let mut setup = setup();
+    let id = setup.id;
+    setup.put(&id, vec![1], vec![2], None).unwrap();
+
+    let (transmitter, receiver) = unbounded();
+    let context = Context::new(RequestMessage::default(), transmitter);
+    setup
+        .module_impl
+        .get(
+            &Address::anonymous(),
+            GetArgs {
+                key: vec![1].into(),
+            },
+            context,
+        )
+        .unwrap();
+    assert!(matches!(
+        receiver.try_recv(),
+        Ok(ProofResult::ProofNotRequested)
+    ));
+}
@@ -1,7 +1,9 @@
+use async_channel::unbounded;
 use many_identity::testing::identity;
 use many_kvstore::error;
 use many_kvstore::module::KvStoreModuleImpl;
 use many_modules::kvstore::{GetArgs, KvStoreCommandsModuleBackend, KvStoreModuleBackend, PutArgs};
+use many_protocol::{context::Context, RequestMessage};
 
 /// Verify persistent storage can be re-loaded
 #[test]
@@ -32,6 +34,7 @@ fn load() {
                     key: vec![2, 3, 4].into(),
                     value: vec![0, 1, 2, 3].into(),
                     alternative_owner: None,
+                    expires_at: None,
                 },
             )
             .expect("Unable to put new data in DB");
@@ -46,6 +49,7 @@ fn load() {
             GetArgs {
                 key: vec![2, 3, 4].into(),
             },
+            Context::new(RequestMessage::default(), unbounded().0),
         )
         .unwrap()
         .value
@@ -60,6 +64,7 @@ fn load() {
             key: vec![1, 2, 3].into(),
             value: vec![0].into(),
             alternative_owner: None,
+            expires_at: None,
         },
     );
     assert!(p.is_err());
@@ -73,6 +78,7 @@ fn load() {
             key: vec![1, 2, 3].into(),
             value: vec![0].into(),
             alternative_owner: None,
+            expires_at: None,
         },
     );
     assert!(p.is_ok());
@@ -84,6 +90,7 @@ fn load() {
             GetArgs {
                 key: vec![1, 2, 3].into(),
             },
+            Context::new(RequestMessage::default(), unbounded().0),
         )
         .unwrap()
         .value
@@ -0,0 +1,309 @@
+use crate::proof::BALANCES_ROOT;
+use many_error::ManyError;
+use many_types::cbor::CborAny;
+use many_types::ledger::TokenAmount;
+use merk::rocksdb::{IteratorMode, ReadOptions};
+use merk::tree::Tree;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+/// The auxiliary key under which a ledger store records its root hash at
+/// genesis. The auxiliary data is not part of the state hash.
+pub const GENESIS_HASH_AUX_KEY: &[u8] = b"/genesis_hash";
+
+/// The key of the block height in a ledger store.
+pub const HEIGHT_KEY: &[u8] = b"/height";
+
+/// A key whose value differs between the replayed genesis and a store.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyDiff {
+    pub key: Vec<u8>,
+
+    /// The value in the replayed genesis, if the key exists there.
+    pub expected: Option<Vec<u8>>,
+
+    /// The value in the store, if the key exists there.
+    pub actual: Option<Vec<u8>>,
+}
+
+fn display_key(key: &[u8]) -> String {
+    match std::str::from_utf8(key) {
+        Ok(k) if k.chars().all(|c| !c.is_control()) => k.to_string(),
+        _ => format!("0x{}", hex::encode(key)),
+    }
+}
+
+fn display_value(key: &[u8], value: &[u8]) -> String {
+    if key.starts_with(BALANCES_ROOT.as_bytes()) {
+        return TokenAmount::from(value.to_vec()).to_string();
+    }
+
+    let mut d = minicbor::Decoder::new(value);
+    if d.skip().is_ok() && d.position() == value.len() {
+        minicbor::display(value).to_string()
+    } else {
+        format!("0x{}", hex::encode(value))
+    }
+}
+
+impl KeyDiff {
+    /// The fields that differ between the two values, if both are CBOR maps.
+    pub fn fields(&self) -> Option<Vec<(CborAny, Option<CborAny>, Option<CborAny>)>> {
+        let decode_map = |v: &Option<Vec<u8>>| match minicbor::decode::<CborAny>(v.as_ref()?) {
+            Ok(CborAny::Map(map)) => Some(map),
+            _ => None,
+        };
+        let mut expected = decode_map(&self.expected)?;
+        let mut actual = decode_map(&self.actual)?;
+
+        let keys: Vec<CborAny> = expected.keys().chain(actual.keys()).cloned().collect();
+        let mut fields = BTreeMap::new();
+        for k in keys {
+            let (e, a) = (expected.remove(&k), actual.remove(&k));
+            if e != a {
+                fields.insert(k, (e, a));
+            }
+        }
+        Some(fields.into_iter().map(|(k, (e, a))| (k, e, a)).collect())
+    }
+}
+
+impl Display for KeyDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let key = &self.key;
+        match (&self.expected, &self.actual) {
+            (Some(e), None) => write!(f, "- {} => {}", display_key(key), display_value(key, e)),
+            (None, Some(a)) => write!(f, "+ {} => {}", display_key(key), display_value(key, a)),
+            (Some(e), Some(a)) => match self.fields() {
+                Some(fields) => {
+                    write!(f, "~ {}", display_key(key))?;
+                    for (field, e, a) in fields {
+                        write!(f, "\n    {field:?}: {e:?} != {a:?}")?;
+                    }
+                    Ok(())
+                }
+                None => write!(
+                    f,
+                    "~ {} => {} != {}",
+                    display_key(key),
+                    display_value(key, e),
+                    display_value(key, a)
+                ),
+            },
+            (None, None) => write!(f, "  {}", display_key(key)),
+        }
+    }
+}
+
+/// The result of verifying a store against a genesis state file.
+#[derive(Clone, Debug)]
+pub struct GenesisVerification {
+    /// Root hash of the genesis replayed into a fresh store.
+    pub replayed_hash: Vec<u8>,
+
+    /// Hash recorded in the state file, if any.
+    pub state_hash: Option<Vec<u8>>,
+
+    /// Genesis hash recorded in the store verified, if any. Stores created
+    /// before it was recorded don't have one.
+    pub recorded_hash: Option<Vec<u8>>,
+
+    /// Root hash of the store verified.
+    pub store_hash: Vec<u8>,
+
+    /// Height of the store verified.
+    pub store_height: u64,
+
+    /// Keys that differ between the replayed genesis and the store, in order.
+    pub diff: Vec<KeyDiff>,
+}
+
+impl GenesisVerification {
+    /// The genesis hash of the store verified. Without a recorded genesis
+    /// hash, it is only known while the store is at height 0.
+    pub fn genesis_hash(&self) -> Option<&[u8]> {
+        self.recorded_hash
+            .as_deref()
+            .or_else(|| (self.store_height == 0).then_some(self.store_hash.as_slice()))
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.genesis_hash() == Some(self.replayed_hash.as_slice())
+            && self
+                .state_hash
+                .as_ref()
+                .map_or(true, |h| *h == self.replayed_hash)
+    }
+}
+
+struct Store {
+    root_hash: Vec<u8>,
+    genesis_hash: Option<Vec<u8>>,
+    values: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+fn read_store(path: &Path) -> Result<Store, ManyError> {
+    if !path.exists() {
+        return Err(ManyError::unknown(format!(
+            "{} does not exist",
+            path.display()
+        )));
+    }
+    let merk = merk::Merk::open(path).map_err(ManyError::unknown)?;
+
+    let mut values = BTreeMap::new();
+    for kv in merk.iter_opt(IteratorMode::Start, ReadOptions::default()) {
+        let (k, v) = kv.map_err(ManyError::unknown)?;
+        let tree = Tree::decode(k.to_vec(), v.as_ref());
+        values.insert(k.to_vec(), tree.value().to_vec());
+    }
+    Ok(Store {
+        root_hash: merk.root_hash().to_vec(),
+        genesis_hash: merk
+            .get_aux(GENESIS_HASH_AUX_KEY)
+            .map_err(ManyError::unknown)?,
+        values,
+    })
+}
+
+fn read_height(values: &BTreeMap<Vec<u8>, Vec<u8>>) -> Result<u64, ManyError> {
+    values.get(HEIGHT_KEY).map_or(Ok(0), |x| {
+        let bytes = <[u8; 8]>::try_from(x.as_slice()).map_err(|_| {
+            ManyError::deserialization_error(format!("Invalid height of {} bytes.", x.len()))
+        })?;
+        Ok(u64::from_be_bytes(bytes))
+    })
+}
+
+fn diff(
+    mut expected: BTreeMap<Vec<u8>, Vec<u8>>,
+    mut actual: BTreeMap<Vec<u8>, Vec<u8>>,
+) -> Vec<KeyDiff> {
+    let mut keys: Vec<Vec<u8>> = expected.keys().chain(actual.keys()).cloned().collect();
+    keys.sort();
+    keys.dedup();
+
+    keys.into_iter()
+        .filter_map(|key| {
+            let (e, a) = (expected.remove(&key), actual.remove(&key));
+            (e != a).then_some(KeyDiff {
+                key,
+                expected: e,
+                actual: a,
+            })
+        })
+        .collect()
+}
+
+/// Compare the store at `store_path` with a store the genesis was replayed
+/// into at `replayed_path`. `state_hash` is the hash recorded in the state
+/// file, if any.
+pub fn verify_genesis(
+    store_path: impl AsRef<Path>,
+    replayed_path: impl AsRef<Path>,
+    state_hash: Option<Vec<u8>>,
+) -> Result<GenesisVerification, ManyError> {
+    let replayed = read_store(replayed_path.as_ref())?;
+    let store = read_store(store_path.as_ref())?;
+    let store_height = read_height(&store.values)?;
+
+    Ok(GenesisVerification {
+        replayed_hash: replayed.root_hash,
+        state_hash,
+        recorded_hash: store.genesis_hash,
+        store_hash: store.root_hash,
+        store_height,
+        diff: diff(replayed.values, store.values),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_diff() {
+        let expected = BTreeMap::from([
+            (b"/a".to_vec(), vec![1]),
+            (b"/b".to_vec(), vec![2]),
+            (b"/c".to_vec(), vec![3]),
+        ]);
+        let actual = BTreeMap::from([
+            (b"/b".to_vec(), vec![2]),
+            (b"/c".to_vec(), vec![4]),
+            (b"/d".to_vec(), vec![5]),
+        ]);
+
+        let diff = diff(expected, actual);
+        assert_eq!(
+            diff.iter().map(|d| d.key.as_slice()).collect::<Vec<_>>(),
+            vec![b"/a".as_slice(), b"/c", b"/d"]
+        );
+        assert_eq!(diff[0].to_string(), "- /a => 1");
+        assert_eq!(diff[1].to_string(), "~ /c => 3 != 4");
+        assert_eq!(diff[2].to_string(), "+ /d => 5");
+    }
+
+    #[test]
+    fn field_diff() {
+        let map = |entries: &[(i64, i64)]| {
+            minicbor::to_vec(CborAny::Map(
+                entries
+                    .iter()
+                    .map(|(k, v)| (CborAny::Int(*k), CborAny::Int(*v)))
+                    .collect(),
+            ))
+            .unwrap()
+        };
+        let diff = KeyDiff {
+            key: b"/accounts/x".to_vec(),
+            expected: Some(map(&[(0, 1), (1, 2)])),
+            actual: Some(map(&[(0, 1), (1, 3), (2, 4)])),
+        };
+
+        assert_eq!(
+            diff.fields().unwrap(),
+            vec![
+                (
+                    CborAny::Int(1),
+                    Some(CborAny::Int(2)),
+                    Some(CborAny::Int(3))
+                ),
+                (CborAny::Int(2), None, Some(CborAny::Int(4))),
+            ]
+        );
+    }
+
+    #[test]
+    fn height() {
+        let values = |v: &[u8]| BTreeMap::from([(HEIGHT_KEY.to_vec(), v.to_vec())]);
+        assert_eq!(read_height(&BTreeMap::new()).unwrap(), 0);
+        assert_eq!(read_height(&values(&5u64.to_be_bytes())).unwrap(), 5);
+        assert!(read_height(&values(&[1, 2, 3])).is_err());
+    }
+
+    #[test]
+    fn genesis_hash() {
+        let verification = |recorded_hash: Option<Vec<u8>>, store_height| GenesisVerification {
+            replayed_hash: vec![1],
+            state_hash: None,
+            recorded_hash,
+            store_hash: vec![2],
+            store_height,
+            diff: vec![],
+        };
+
+        assert!(verification(Some(vec![1]), 10).is_valid());
+        assert!(!verification(Some(vec![2]), 0).is_valid());
+        assert!(!verification(None, 0).is_valid());
+        assert!(!verification(None, 10).is_valid());
+        assert_eq!(verification(None, 10).genesis_hash(), None);
+
+        let mut at_genesis = verification(None, 0);
+        at_genesis.store_hash = vec![1];
+        assert!(at_genesis.is_valid());
+        at_genesis.state_hash = Some(vec![3]);
+        assert!(!at_genesis.is_valid());
+    }
+}
@@ -0,0 +1,90 @@
+use many_error::ManyError;
+use many_identity::Address;
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::ProofOperation;
+use merk::proofs::{
+    encode_into,
+    query::verify,
+    Node::{Hash, KVHash, KV},
+    Op::{Child, Parent, Push},
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub const BALANCES_ROOT: &str = "/balances/";
+
+pub fn key_for_account_balance(id: &Address, symbol: &Symbol) -> Vec<u8> {
+    format!("{BALANCES_ROOT}{id}/{symbol}").into_bytes()
+}
+
+/// Verify a proof returned by a store, e.g. a ledger or a key-value store,
+/// against a trusted root hash. Returns the value proven for each storage key,
+/// or `None` if the proof shows the key is absent. Keys the proof does not
+/// cover are an error.
+pub fn verify_proof(
+    operations: Vec<ProofOperation>,
+    root: &[u8],
+    keys: impl IntoIterator<Item = Vec<u8>>,
+) -> Result<BTreeMap<Vec<u8>, Option<Vec<u8>>>, ManyError> {
+    fn to_hash(bytes: Vec<u8>) -> Result<merk::Hash, ManyError> {
+        bytes.try_into().map_err(|bytes: Vec<u8>| {
+            ManyError::unknown(format!("Invalid hash length {}.", bytes.len()))
+        })
+    }
+
+    let operations = operations
+        .into_iter()
+        .map(|operation| {
+            Ok(match operation {
+                ProofOperation::Child => Child,
+                ProofOperation::Parent => Parent,
+                ProofOperation::NodeHash(hash) => Push(Hash(to_hash(hash)?)),
+                ProofOperation::KeyValueHash(hash) => Push(KVHash(to_hash(hash)?)),
+                ProofOperation::KeyValuePair(key, value) => Push(KV(key.into(), value.into())),
+            })
+        })
+        .collect::<Result<Vec<_>, ManyError>>()?;
+    let mut bytes = Vec::new();
+    encode_into(operations.iter(), &mut bytes);
+
+    let map = verify(&bytes, to_hash(root.to_vec())?)
+        .map_err(|error| ManyError::unknown(error.to_string()))?;
+    keys.into_iter()
+        .map(|key| {
+            let value = map
+                .get(&key)
+                .map_err(|error| ManyError::unknown(error.to_string()))?
+                .map(<[u8]>::to_vec);
+            Ok((key, value))
+        })
+        .collect()
+}
+
+/// Verify that a proof attached to a `ledger.balance` response proves the
+/// balances returned for `symbols`. A symbol without a balance returned must
+/// be proven absent, so a server cannot hide a balance.
+pub fn verify_balances(
+    operations: Vec<ProofOperation>,
+    root: &[u8],
+    account: &Address,
+    symbols: impl IntoIterator<Item = Symbol>,
+    balances: &BTreeMap<Symbol, TokenAmount>,
+) -> Result<(), ManyError> {
+    let symbols: BTreeSet<Symbol> = balances.keys().copied().chain(symbols).collect();
+    let values = verify_proof(
+        operations,
+        root,
+        symbols
+            .iter()
+            .map(|symbol| key_for_account_balance(account, symbol)),
+    )?;
+
+    for symbol in symbols {
+        let expected = balances.get(&symbol).map(TokenAmount::to_vec);
+        if values.get(&key_for_account_balance(account, &symbol)) != Some(&expected) {
+            return Err(ManyError::unknown(format!(
+                "The proof does not match the balance of {symbol}."
+            )));
+        }
+    }
+    Ok(())
+}
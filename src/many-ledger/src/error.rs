@@ -17,6 +17,15 @@ define_attribute_many_error!(
         9: pub fn amount_is_zero()
             => "Unable to send zero (0) token.",
         10: pub fn storage_key_not_found(key) => "Key not found in storage: {key:?}.",
+        11: pub fn memo_too_large(size, max) => "Memo part size ({size}) over limit ({max}).",
+        12: pub fn memo_too_many_parts(count, max) => "Memo has too many parts ({count}), limit is {max}.",
+        13: pub fn invalid_memo_limits(max) => "Memo size limit cannot be over {max}.",
+        14: pub fn invalid_imported_event(id, reason) => "Invalid imported event {id}: {reason}.",
+        15: pub fn snapshot_not_found(height) => "No snapshot at height {height}.",
+        16: pub fn snapshot_chunk_not_found(height, chunk) => "The snapshot at height {height} has no chunk {chunk}.",
+        17: pub fn invalid_snapshot(reason) => "Invalid snapshot: {reason}.",
+        18: pub fn memo_total_too_large(size, max) => "Memo total size ({size}) over limit ({max}).",
+        19: pub fn typed_memo_not_enabled() => "Typed memo sections are not enabled on this ledger.",
     }
 );
 
@@ -28,6 +37,15 @@ define_attribute_many_error!(
         4: pub fn ticker_exists(ticker) => "Token ticker already exists on this network: {ticker}.",
         5: pub fn subresource_exhausted(key) => "Subresources are exhausted for: {key}.",
         6: pub fn invalid_ticker_length(ticker) => "Token ticker length is invalid (<3 or >5): {ticker}.",
+        7: pub fn invalid_visual_logo(reason) => "Token visual logo is invalid: {reason}.",
+        8: pub fn invalid_attachment(reason) => "Token attachment is invalid: {reason}.",
+        9: pub fn ownership_offer_not_found(symbol) => "No pending ownership offer for token {symbol}.",
+        10: pub fn ownership_offer_expired(symbol) => "The ownership offer for token {symbol} expired.",
+        11: pub fn logo_image_too_large(size, max) => "Token logo image size ({size}) over limit ({max}).",
+        12: pub fn logo_content_type_not_allowed(content_type) => "Token logo content type not allowed: {content_type}.",
+        13: pub fn logo_char_not_allowed(code_point) => "Token logo character not allowed: {code_point}.",
+        14: pub fn extended_info_memo_too_large(size, max) => "Token extended info memo part size ({size}) over limit ({max}).",
+        15: pub fn invalid_extended_info_policy(reason) => "Token extended info policy is invalid: {reason}.",
     }
 );
 
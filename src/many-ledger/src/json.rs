@@ -1,4 +1,6 @@
 use crate::storage::account::AccountMeta;
+use crate::storage::event::ImportedEvent;
+use crate::storage::extended_info_policy::ExtendedInfoPolicy;
 use crate::storage::ledger_tokens::SymbolMeta;
 use many_error::ManyError;
 use many_identity::Address;
@@ -6,6 +8,7 @@ use many_modules::account;
 use many_modules::account::features;
 use many_modules::account::features::{FeatureInfo, TryCreateFeature};
 use many_types::ledger::{Symbol, TokenAmount};
+use many_types::{MemoLimits, Timestamp};
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
@@ -128,6 +131,71 @@ impl From<SymbolMetaJson> for SymbolMeta {
     }
 }
 
+/// Memo limits of the server
+#[derive(serde::Deserialize, Clone, Debug, Default)]
+pub struct MemoLimitsJson {
+    pub max_size: u64,
+    pub max_count: Option<u64>,
+    pub max_total_size: Option<u64>,
+}
+
+/// Converts the JSON memo limits to our internal representation
+impl From<MemoLimitsJson> for MemoLimits {
+    fn from(value: MemoLimitsJson) -> Self {
+        Self {
+            max_size: value.max_size,
+            max_count: value.max_count,
+            max_total_size: value.max_total_size,
+        }
+    }
+}
+
+/// Token extended info policy of the server
+#[derive(serde::Deserialize, Clone, Debug, Default)]
+pub struct ExtendedInfoPolicyJson {
+    pub max_image_size: Option<u64>,
+    pub content_types: Option<BTreeSet<String>>,
+    /// Inclusive ranges of code points, e.g. `[[0x2190, 0x23FF]]`.
+    pub unicode_ranges: Option<Vec<(u32, u32)>>,
+    pub max_memo_size: Option<u64>,
+}
+
+/// Converts the JSON extended info policy to our internal representation
+impl From<ExtendedInfoPolicyJson> for ExtendedInfoPolicy {
+    fn from(value: ExtendedInfoPolicyJson) -> Self {
+        let default = ExtendedInfoPolicy::default();
+        Self {
+            max_image_size: value.max_image_size.unwrap_or(default.max_image_size),
+            content_types: value.content_types,
+            unicode_ranges: value.unicode_ranges,
+            max_memo_size: value.max_memo_size,
+        }
+    }
+}
+
+/// An event imported from a legacy ledger
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct ImportedEventJson {
+    pub id: u64,
+    pub time: u64,
+    /// The content of the event, as hexadecimal CBOR.
+    pub content: String,
+}
+
+/// Converts the JSON imported event to our internal representation
+impl TryFrom<ImportedEventJson> for ImportedEvent {
+    type Error = ManyError;
+
+    fn try_from(value: ImportedEventJson) -> Result<Self, Self::Error> {
+        let bytes = hex::decode(value.content).map_err(ManyError::deserialization_error)?;
+        Ok(Self {
+            id: value.id,
+            time: Timestamp::new(value.time)?,
+            content: minicbor::decode(&bytes).map_err(ManyError::deserialization_error)?,
+        })
+    }
+}
+
 /// The initial state schema, loaded from JSON.
 #[derive(serde::Deserialize, Clone, Debug, Default)]
 pub struct InitialStateJson {
@@ -141,6 +209,9 @@ pub struct InitialStateJson {
     pub accounts: Option<Vec<AccountJson>>,
     pub id_store_seed: Option<u64>,
     pub id_store_keys: Option<BTreeMap<String, String>>,
+    pub memo_limits: Option<MemoLimitsJson>,
+    pub extended_info_policy: Option<ExtendedInfoPolicyJson>,
+    pub imported_events: Option<Vec<ImportedEventJson>>,
     pub hash: Option<String>,
 }
 
@@ -6,4 +6,8 @@ pub mod error;
 pub mod json;
 pub mod migration;
 pub mod module;
+pub mod notifier;
+pub mod registry;
+#[cfg(feature = "scripting")]
+pub mod scripts;
 pub mod storage;
@@ -2,38 +2,45 @@
 
 use clap::Parser;
 use many_cli_helpers::CommonCliFlags;
+use many_identity::delegation::DelegatedVerifier;
 use many_identity::verifiers::AnonymousVerifier;
-use many_identity::{Address, Identity};
+use many_identity::Identity;
 use many_identity_dsa::{CoseKeyIdentity, CoseKeyVerifier};
-use many_identity_webauthn::WebAuthnVerifier;
+use many_identity_webauthn::{DomainPattern, OriginPattern, WebAuthnPolicy, WebAuthnVerifier};
 use many_migration::MigrationConfig;
-use many_modules::account::features::Feature;
-use many_modules::{abci_backend, account, data, events, idstore, ledger};
-use many_protocol::ManyUrl;
+use many_modules::abci_backend;
+use many_server::server::EndpointTimeout;
 use many_server::transport::http::HttpServer;
-use many_server::ManyServer;
-use many_server_cache::{RequestCacheValidator, RocksDbCacheBackend};
-use std::collections::BTreeSet;
+use many_server::{ManyServer, MissingChainId, ModuleConfig};
+use many_server_cache::{ReplayPolicy, RequestCacheValidator, RocksDbCacheBackend};
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use tracing::{debug, info, warn};
 
-use crate::allow_addrs::AllowAddrsModule;
-
-#[cfg(feature = "webauthn_testing")]
-use crate::idstore_webauthn::IdStoreWebAuthnModule;
 use crate::json::InitialStateJson;
 use crate::migration::MIGRATIONS;
-use crate::module::account::AccountFeatureModule;
+use crate::module::atomic::LedgerAtomicGroups;
+use crate::module::idstore_wordlist::{Wordlist, Wordlists};
+use crate::module::query_state::LedgerQueryState;
+use crate::registry::LedgerModuleContext;
 use module::*;
 
 mod error;
 mod json;
 mod migration;
 mod module;
+mod notifier;
+mod registry;
+#[cfg(feature = "scripting")]
+mod scripts;
 mod storage;
 
+#[cfg(feature = "memory-tracking")]
+#[global_allocator]
+static ALLOCATOR: many_server::server::TrackingAllocator =
+    many_server::server::TrackingAllocator::system();
+
 #[derive(Parser, Debug)]
 #[clap(args_override_self(true))]
 struct Opts {
@@ -42,7 +49,7 @@ struct Opts {
 
     /// The location of a PEM file for the identity of this server.
     // The field needs to be an Option for the clap derive to work properly.
-    #[clap(long, required = true)]
+    #[clap(long, required_unless_present = "genesis-only")]
     pem: Option<PathBuf>,
 
     /// The address and port to bind to for the MANY Http server.
@@ -69,9 +76,23 @@ struct Opts {
 
     /// Application absolute URLs allowed to communicate with this server. Any
     /// application will be able to communicate with this server if left empty.
+    /// All the subdomains of a domain can be allowed with a `*.` prefix, e.g.
+    /// `https://*.example.com`.
+    /// Multiple occurences of this argument can be given.
+    #[clap(long)]
+    allow_origin: Option<Vec<OriginPattern>>,
+
+    /// WebAuthn relying party IDs allowed to communicate with this server,
+    /// e.g. `example.com` or `*.example.com`. Any RP ID will be allowed if
+    /// left empty.
     /// Multiple occurences of this argument can be given.
     #[clap(long)]
-    allow_origin: Option<Vec<ManyUrl>>,
+    allow_rp_id: Option<Vec<DomainPattern>>,
+
+    /// Refuse WebAuthn envelopes whose authenticator did not verify the user
+    /// (e.g. with a PIN or biometrics).
+    #[clap(long)]
+    require_user_verification: bool,
 
     /// A list of initial balances. This will be in addition to the genesis
     /// state file in --state and should only be used for testing.
@@ -100,17 +121,161 @@ struct Opts {
     #[clap(long, exclusive = true)]
     list_migrations: bool,
 
+    /// Create the persistent store from the state file and exit, without
+    /// serving it. Used to replay a genesis, e.g. to verify a store.
+    #[clap(long, requires_all = &["state", "persistent"])]
+    genesis_only: bool,
+
     /// Path to a JSON file containing an array of MANY addresses
     /// Only addresses from this array will be able to execute commands, e.g., send, put, ...
     /// Any addresses will be able to execute queries, e.g., balance, get, ...
     #[clap(long)]
     allow_addrs: Option<PathBuf>,
 
+    /// Path to a JSON file containing an array of the modules to serve, by
+    /// name, e.g. `["ledger", "ledger.commands", "events"]`. All modules are
+    /// served if unspecified.
+    #[clap(long)]
+    modules: Option<PathBuf>,
+
+    /// List the modules supported by this binary, for use with --modules.
+    #[clap(long, exclusive = true)]
+    list_modules: bool,
+
+    /// Endpoints to disable on this server, e.g. `idstore.store`. Requests to
+    /// them are refused and they are not listed by `endpoints`.
+    /// Multiple occurences of this argument can be given.
+    #[clap(long = "disable-endpoint")]
+    disable_endpoint: Vec<String>,
+
+    /// The maximum time to execute an endpoint, as `ENDPOINT=MILLISECONDS`,
+    /// e.g. `ledger.list=2000`. Executions exceeding it are cancelled and
+    /// return a timeout error. In ABCI mode, they are only logged, since
+    /// cancelling the execution of a block would fork the state.
+    /// Multiple occurences of this argument can be given.
+    #[clap(long = "endpoint-timeout", value_parser = parse_endpoint_timeout)]
+    endpoint_timeout: Vec<(String, u64)>,
+
+    /// Reject queries which allocate more than this many bytes during their
+    /// execution. Memory usage is not deterministic, so this cannot be used
+    /// in ABCI mode.
+    /// This requires the feature "memory-tracking" to be enabled.
+    #[cfg(feature = "memory-tracking")]
+    #[clap(long, conflicts_with = "abci")]
+    memory_budget: Option<usize>,
+
+    /// The memory budget of an endpoint, as `ENDPOINT=BYTES`, e.g.
+    /// `events.list=10000000`, instead of `--memory-budget`.
+    /// Multiple occurences of this argument can be given.
+    /// This requires the feature "memory-tracking" to be enabled.
+    #[cfg(feature = "memory-tracking")]
+    #[clap(
+        long = "endpoint-memory-budget",
+        value_parser = parse_endpoint_memory_budget,
+        conflicts_with = "abci"
+    )]
+    endpoint_memory_budget: Vec<(String, usize)>,
+
     /// Database path to the request cache to validate duplicate messages.
     /// If unspecified, the server will not verify transactions for duplicate
     /// messages.
     #[clap(long)]
     cache_db: Option<PathBuf>,
+
+    /// Remove requests from the cache after this many seconds. This must be
+    /// longer than the timeout of the server, or requests could be replayed.
+    /// Requests are kept forever by default.
+    #[clap(long, requires("cache-db"))]
+    cache_ttl: Option<u64>,
+
+    /// Answer requests that were already executed with their original
+    /// response, instead of a duplicated message error. Responses are kept
+    /// in the cache with their request.
+    #[clap(long, requires("cache-db"))]
+    cache_replay_responses: bool,
+
+    /// Path to a JSON file where the multisig notification endpoints registered
+    /// by account members are kept. Multisig notices are only delivered if this
+    /// is specified.
+    #[clap(long)]
+    multisig_notifications: Option<PathBuf>,
+
+    /// URL of a relay that will send multisig notices to members who registered
+    /// an email address. Notices for email endpoints are dropped if unspecified.
+    #[clap(long, requires = "multisig-notifications")]
+    multisig_email_relay: Option<String>,
+
+    /// Path to a CBOR file where the durable event subscriptions are kept,
+    /// along with their delivery cursors. The `events.createSubscription`
+    /// endpoint is only supported if this is specified.
+    #[clap(long)]
+    durable_subscriptions: Option<PathBuf>,
+
+    /// An address allowed to create durable subscriptions. This can be
+    /// repeated. Without operators, no durable subscription can be created.
+    #[clap(long, requires = "durable-subscriptions")]
+    durable_subscriptions_operator: Vec<many_identity::Address>,
+
+    /// A custom wordlist for recall phrases, as `<NAME>=<PATH>` where the file
+    /// has 2048 distinct words, one per line. The wordlist must be part of the
+    /// idstore wordlists migration config, with the hash of these words.
+    /// Multiple occurences of this argument can be given.
+    #[clap(long = "idstore-custom-wordlist")]
+    idstore_custom_wordlist: Vec<String>,
+
+    /// The network identifier of this ledger, e.g. `manifest-mainnet`.
+    /// Requests for another network are refused. All the nodes of a network
+    /// must use the same chain ID.
+    #[clap(long)]
+    chain_id: Option<String>,
+
+    /// Accept requests without a chain ID until this time, in seconds since
+    /// the UNIX epoch, so clients have time to add it. Requests without a
+    /// chain ID are refused if unspecified. In ABCI mode, requests without a
+    /// chain ID are always accepted, and this is set on `many-abci` instead.
+    #[clap(long, requires("chain-id"), conflicts_with("abci"))]
+    missing_chain_id_allowed_until: Option<u64>,
+
+    /// Take a snapshot of the state every this many blocks, which new nodes
+    /// can start from instead of replaying every block (state sync).
+    /// Snapshots are not taken if unspecified.
+    #[clap(long, requires("abci"))]
+    snapshot_interval: Option<u64>,
+
+    /// The directory to keep the snapshots in. Defaults to the path of the
+    /// persistent store with a `.snapshots` extension.
+    #[clap(long, requires("snapshot-interval"))]
+    snapshot_dir: Option<PathBuf>,
+
+    /// The number of snapshots to keep. Older snapshots are deleted.
+    #[clap(long, default_value = "2")]
+    snapshot_keep: usize,
+
+    /// Start with an empty persistent store if neither it nor a staging file
+    /// exist, to restore it from a snapshot of another node (state sync).
+    #[clap(long, requires("abci"))]
+    state_sync: bool,
+}
+
+fn parse_endpoint_timeout(s: &str) -> Result<(String, u64), String> {
+    let (endpoint, millis) = s
+        .split_once('=')
+        .ok_or_else(|| format!("Expected ENDPOINT=MILLISECONDS, got '{s}'"))?;
+    let millis = millis
+        .parse()
+        .map_err(|e| format!("Invalid duration '{millis}': {e}"))?;
+    Ok((endpoint.to_string(), millis))
+}
+
+#[cfg(feature = "memory-tracking")]
+fn parse_endpoint_memory_budget(s: &str) -> Result<(String, usize), String> {
+    let (endpoint, bytes) = s
+        .split_once('=')
+        .ok_or_else(|| format!("Expected ENDPOINT=BYTES, got '{s}'"))?;
+    let bytes = bytes
+        .parse()
+        .map_err(|e| format!("Invalid budget '{bytes}': {e}"))?;
+    Ok((endpoint.to_string(), bytes))
 }
 
 fn main() {
@@ -124,9 +289,29 @@ fn main() {
         clean,
         migrations_config,
         allow_origin,
+        allow_rp_id,
+        require_user_verification,
         allow_addrs,
+        modules,
+        list_modules,
+        disable_endpoint,
+        endpoint_timeout,
         list_migrations,
+        genesis_only,
         cache_db,
+        cache_ttl,
+        cache_replay_responses,
+        multisig_notifications,
+        multisig_email_relay,
+        durable_subscriptions,
+        durable_subscriptions_operator,
+        idstore_custom_wordlist,
+        chain_id,
+        missing_chain_id_allowed_until,
+        snapshot_interval,
+        snapshot_dir,
+        snapshot_keep,
+        state_sync,
         ..
     } = Opts::parse();
 
@@ -146,9 +331,15 @@ fn main() {
         return;
     }
 
+    if list_modules {
+        for name in registry::registry().names() {
+            println!("{name}");
+        }
+        return;
+    }
+
     // Safe unwrap.
     // At this point the Options should contain a value.
-    let pem = pem.unwrap();
     let persistent = persistent.unwrap();
 
     if clean {
@@ -166,10 +357,6 @@ fn main() {
         state = None;
     }
 
-    let pem = std::fs::read_to_string(pem).expect("Could not read PEM file.");
-    let key = CoseKeyIdentity::from_pem(pem).expect("Could not generate identity from PEM file.");
-    info!(address = key.address().to_string().as_str());
-
     let state: Option<InitialStateJson> =
         state.map(|p| InitialStateJson::read(p).expect("Could not read state file."));
 
@@ -181,6 +368,19 @@ fn main() {
         config.strict()
     });
 
+    if genesis_only {
+        let state = state.expect("The persistent store already exists.");
+        LedgerModuleImpl::new(state, maybe_migrations, persistent, abci)
+            .expect("Could not create the persistent store.");
+        return;
+    }
+
+    // Safe unwrap.
+    // The PEM file is required unless --genesis-only is given.
+    let pem = std::fs::read_to_string(pem.unwrap()).expect("Could not read PEM file.");
+    let key = CoseKeyIdentity::from_pem(pem).expect("Could not generate identity from PEM file.");
+    info!(address = key.address().to_string().as_str());
+
     let module_impl = if persistent.exists() {
         if state.is_some() {
             warn!(
@@ -203,13 +403,14 @@ fn main() {
             }
         }
 
-        LedgerModuleImpl::load(maybe_migrations, persistent, abci).unwrap()
+        LedgerModuleImpl::load(maybe_migrations, persistent.clone(), abci).unwrap()
     } else if let Some(state) = state {
         #[cfg(feature = "balance_testing")]
         {
             let mut module_impl =
-                LedgerModuleImpl::new(state, maybe_migrations, persistent, abci).unwrap();
+                LedgerModuleImpl::new(state, maybe_migrations, persistent.clone(), abci).unwrap();
 
+            use many_identity::Address;
             use std::str::FromStr;
 
             let Opts {
@@ -237,76 +438,158 @@ fn main() {
         }
 
         #[cfg(not(feature = "balance_testing"))]
-        LedgerModuleImpl::new(state, maybe_migrations, persistent, abci).unwrap()
+        LedgerModuleImpl::new(state, maybe_migrations, persistent.clone(), abci).unwrap()
+    } else if state_sync {
+        info!("Starting from an empty persistent store, waiting for a snapshot.");
+        LedgerModuleImpl::load(maybe_migrations, persistent.clone(), abci).unwrap()
     } else {
         panic!("Persistent store or staging file not found.")
     };
+    let mut module_impl = module_impl;
+    if let Some(interval) = snapshot_interval {
+        module_impl.set_snapshots(storage::snapshots::SnapshotConfig {
+            path: snapshot_dir.unwrap_or_else(|| persistent.with_extension("snapshots")),
+            interval,
+            keep: snapshot_keep,
+        });
+    }
+    module_impl.set_receipt_signer(Arc::new(key.clone()));
+    let mut wordlists = Wordlists::default();
+    for custom in idstore_custom_wordlist {
+        let (name, path) = custom
+            .split_once('=')
+            .expect("Custom wordlists must be given as <NAME>=<PATH>.");
+        let wordlist = Wordlist::from_file(path)
+            .unwrap_or_else(|e| panic!("Could not load the wordlist {name}: {e}"));
+        wordlists.insert(name, wordlist);
+    }
+    module_impl
+        .set_idstore_wordlists(wordlists)
+        .expect("Invalid idstore wordlists.");
+    let notifications_receiver = multisig_notifications.map(|path| {
+        let (notifier, receiver) = notifier::WebhookNotifier::new(path, multisig_email_relay)
+            .expect("Could not load multisig notification endpoints.");
+        module_impl.set_multisig_notifier(Arc::new(notifier));
+        receiver
+    });
+    if let Some(path) = &durable_subscriptions {
+        module_impl
+            .set_durable_subscriptions(path, durable_subscriptions_operator.into_iter().collect())
+            .expect("Could not load the durable subscriptions.");
+    }
     let module_impl = Arc::new(Mutex::new(module_impl));
+    let subscriptions_module_impl = durable_subscriptions.map(|_| module_impl.clone());
 
     let many = ManyServer::simple(
         "many-ledger",
         key,
-        (
+        DelegatedVerifier::new((
             AnonymousVerifier,
             CoseKeyVerifier,
-            WebAuthnVerifier::new(allow_origin),
-        ),
+            WebAuthnVerifier::with_policy(WebAuthnPolicy {
+                origins: allow_origin,
+                rp_ids: allow_rp_id,
+                require_user_presence: false,
+                require_user_verification,
+            }),
+        )),
         Some(env!("CARGO_PKG_VERSION").to_string()),
     );
 
     {
         let mut s = many.lock().unwrap();
-        s.add_module(ledger::LedgerModule::new(module_impl.clone()));
-        let ledger_command_module = ledger::LedgerCommandsModule::new(module_impl.clone());
-        if let Some(path) = allow_addrs {
-            let allow_addrs: BTreeSet<Address> =
-                json5::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
-            s.add_module(AllowAddrsModule {
-                inner: ledger_command_module,
-                allow_addrs,
-            });
-        } else {
-            s.add_module(ledger_command_module);
-        }
-        s.add_module(events::EventsModule::new(module_impl.clone()));
-        s.add_module(ledger::LedgerTokensModule::new(module_impl.clone()));
-        s.add_module(ledger::LedgerMintBurnModule::new(module_impl.clone()));
-
-        let idstore_module = idstore::IdStoreModule::new(module_impl.clone());
+        let mut context = LedgerModuleContext::new(module_impl.clone());
+        context.allow_addrs = allow_addrs
+            .map(|path| json5::from_str(&std::fs::read_to_string(path).unwrap()).unwrap());
         #[cfg(feature = "webauthn_testing")]
         {
             let Opts {
                 disable_webauthn_only_for_testing,
                 ..
             } = Opts::parse();
-
-            if disable_webauthn_only_for_testing {
-                s.add_module(IdStoreWebAuthnModule {
-                    inner: idstore_module,
-                    check_webauthn: false,
-                });
-            } else {
-                s.add_module(idstore_module);
-            }
+            context.check_webauthn = !disable_webauthn_only_for_testing;
         }
-        #[cfg(not(feature = "webauthn_testing"))]
-        s.add_module(idstore_module);
-
-        s.add_module(AccountFeatureModule::new(
-            account::AccountModule::new(module_impl.clone()),
-            [Feature::with_id(0), Feature::with_id(1)],
-        ));
-        s.add_module(account::features::multisig::AccountMultisigModule::new(
-            module_impl.clone(),
-        ));
-        s.add_module(data::DataModule::new(module_impl.clone()));
+
+        let modules: Vec<ModuleConfig> = match modules {
+            Some(path) => json5::from_str(
+                &std::fs::read_to_string(path).expect("Could not read the modules file."),
+            )
+            .expect("Invalid modules file."),
+            None => registry::default_modules(),
+        };
+        registry::registry()
+            .instantiate(&mut s, &context, &modules)
+            .expect("Could not add the modules to the server.");
+        s.set_atomic_group_coordinator(
+            LedgerAtomicGroups::new(module_impl.clone())
+                .expect("Could not list the ledger endpoints."),
+        );
+        s.set_query_state_coordinator(
+            LedgerQueryState::new(module_impl.clone()).expect("Could not list the ledger queries."),
+        );
+
         if abci {
             s.set_timeout(u64::MAX);
+            let height_module_impl = module_impl.clone();
+            s.set_block_height_fn(move || height_module_impl.lock().ok()?.height().ok());
             s.add_module(abci_backend::AbciModule::new(module_impl));
+            s.add_module(abci_backend::AbciPriorityModule::new(many.clone()));
+        }
+
+        for method in disable_endpoint {
+            s.disable_endpoint(method);
+        }
+
+        for (method, millis) in endpoint_timeout {
+            let limit = std::time::Duration::from_millis(millis);
+            s.set_endpoint_timeout(
+                method,
+                if abci {
+                    EndpointTimeout::flag(limit)
+                } else {
+                    EndpointTimeout::cancel(limit)
+                },
+            );
+        }
+
+        #[cfg(feature = "memory-tracking")]
+        {
+            let Opts {
+                memory_budget,
+                endpoint_memory_budget,
+                ..
+            } = Opts::parse();
+            s.set_memory_budget(memory_budget);
+            for (method, bytes) in endpoint_memory_budget {
+                s.set_endpoint_memory_budget(method, bytes);
+            }
+        }
+
+        if let Some(chain_id) = chain_id {
+            // Blocks must execute the same way on every node, whatever their
+            // clock, so the transition is checked by `many-abci`.
+            let missing = match missing_chain_id_allowed_until {
+                _ if abci => MissingChainId::Accept,
+                Some(secs) => MissingChainId::AcceptUntil(
+                    std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs),
+                ),
+                None => MissingChainId::Refuse,
+            };
+            s.set_chain_id(chain_id, missing);
         }
 
         if let Some(p) = cache_db {
-            s.add_validator(RequestCacheValidator::new(RocksDbCacheBackend::new(p)));
+            let cache = RequestCacheValidator::new(RocksDbCacheBackend::new(p)).with_replay_policy(
+                if cache_replay_responses {
+                    ReplayPolicy::ReturnCachedResponse
+                } else {
+                    ReplayPolicy::Reject
+                },
+            );
+            s.add_validator(match cache_ttl {
+                Some(ttl) => cache.with_ttl(std::time::Duration::from_secs(ttl)),
+                None => cache,
+            });
         }
     }
 
@@ -320,5 +603,11 @@ fn main() {
         .expect("Could not register signal handler");
 
     let runtime = tokio::runtime::Runtime::new().unwrap();
+    if let Some(receiver) = notifications_receiver {
+        runtime.spawn(notifier::deliver_webhooks(receiver));
+    }
+    if let Some(module_impl) = subscriptions_module_impl {
+        runtime.spawn(notifier::deliver_subscriptions(module_impl));
+    }
     runtime.block_on(many_server.bind(addr)).unwrap();
 }
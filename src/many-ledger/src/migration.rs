@@ -3,14 +3,40 @@ use linkme::distributed_slice;
 use many_error::ManyError;
 use many_migration::{InnerMigration, MigrationSet};
 
+pub mod account_hooks;
+pub mod account_remove_features;
+pub mod account_vesting;
 pub mod block_9400;
+pub mod canonical_cbor;
 pub mod data;
+pub mod data_history;
 pub mod disable_token_create;
 pub mod disable_token_mint;
+pub mod dust;
+pub mod event_ids;
+pub mod event_pruning;
+pub mod freeze;
+pub mod idstore_namespaces;
+pub mod idstore_wordlists;
+pub mod intent_hashes;
+pub mod labels;
+pub mod legacy_memo;
 pub mod legacy_remove_roles;
 pub mod memo;
+pub mod multisig_account_index;
+pub mod multisig_expired_events;
+pub mod multisig_results;
+pub mod nested_accounts;
+pub mod scheduled_send;
+pub mod scripts;
+pub mod streams;
 pub mod token_create;
+pub mod token_identity;
+pub mod token_ownership_offers;
 pub mod tokens;
+pub mod typed_memo;
+pub mod visual_logo;
+pub mod watchlist;
 
 #[cfg(feature = "migration_testing")]
 pub mod dummy_hotfix;
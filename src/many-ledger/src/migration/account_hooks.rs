@@ -0,0 +1,13 @@
+use crate::migration::MIGRATIONS;
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_migration::InnerMigration;
+
+#[distributed_slice(MIGRATIONS)]
+pub static ACCOUNT_HOOKS_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_trigger(
+        false,
+        "Account Hooks Migration",
+        "Enables account hooks, which write records when events happen on an account",
+    );
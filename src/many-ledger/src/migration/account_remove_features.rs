@@ -0,0 +1,13 @@
+use crate::migration::MIGRATIONS;
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_migration::InnerMigration;
+
+#[distributed_slice(MIGRATIONS)]
+pub static ACCOUNT_REMOVE_FEATURES_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_trigger(
+        false,
+        "Account Remove Features Migration",
+        "Enables account.removeFeatures, which removes features from an account",
+    );
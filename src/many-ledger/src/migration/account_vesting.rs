@@ -0,0 +1,13 @@
+use crate::migration::MIGRATIONS;
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_migration::InnerMigration;
+
+#[distributed_slice(MIGRATIONS)]
+pub static ACCOUNT_VESTING_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_trigger(
+        false,
+        "Account Vesting Migration",
+        "Enables vesting accounts, whose tokens unlock over time following a schedule",
+    );
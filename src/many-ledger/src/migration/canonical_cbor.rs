@@ -0,0 +1,13 @@
+use crate::migration::MIGRATIONS;
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_migration::InnerMigration;
+
+#[distributed_slice(MIGRATIONS)]
+pub static CANONICAL_CBOR_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_trigger(
+        false,
+        "Canonical CBOR Migration",
+        "Encodes the events, multisig transactions and accounts of the store with the deterministic CBOR encoding",
+    );
@@ -9,12 +9,14 @@ use many_modules::data::{DataIndex, DataInfo, DataValue};
 use many_types::ledger::TokenAmount;
 use merk::rocksdb::{IteratorMode, ReadOptions};
 use merk::{rocksdb, Op};
+use once_cell::sync::Lazy;
 use serde_json::Value;
 use std::collections::{BTreeMap, HashMap};
 
-pub static ACCOUNT_TOTAL_COUNT_INDEX: DataIndex = DataIndex::new(0).with_index(2).with_index(0);
-pub static NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX: DataIndex =
-    DataIndex::new(0).with_index(2).with_index(1);
+pub static ACCOUNT_TOTAL_COUNT_INDEX: Lazy<DataIndex> =
+    Lazy::new(|| DataIndex::new(0).with_index(2).with_index(0));
+pub static NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX: Lazy<DataIndex> =
+    Lazy::new(|| DataIndex::new(0).with_index(2).with_index(1));
 
 const BALANCES_ROOT_BYTES: &[u8] = b"/balances";
 
@@ -42,14 +44,14 @@ fn get_data_from_db(storage: &InnerStorage) -> Result<(u64, u64), ManyError> {
 fn data_info() -> BTreeMap<DataIndex, DataInfo> {
     BTreeMap::from([
         (
-            ACCOUNT_TOTAL_COUNT_INDEX,
+            ACCOUNT_TOTAL_COUNT_INDEX.clone(),
             DataInfo {
                 r#type: many_modules::data::DataType::Counter,
                 shortname: "accountTotalCount".to_string(),
             },
         ),
         (
-            NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX,
+            NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX.clone(),
             DataInfo {
                 r#type: many_modules::data::DataType::Counter,
                 shortname: "nonZeroAccountTotalCount".to_string(),
@@ -64,11 +66,11 @@ fn data_value(
 ) -> BTreeMap<DataIndex, DataValue> {
     BTreeMap::from([
         (
-            ACCOUNT_TOTAL_COUNT_INDEX,
+            ACCOUNT_TOTAL_COUNT_INDEX.clone(),
             DataValue::Counter(num_unique_accounts),
         ),
         (
-            NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX,
+            NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX.clone(),
             DataValue::Counter(num_non_zero_account),
         ),
     ])
@@ -0,0 +1,13 @@
+use crate::migration::MIGRATIONS;
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_migration::InnerMigration;
+
+#[distributed_slice(MIGRATIONS)]
+pub static DATA_HISTORY_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_trigger(
+        false,
+        "Data History Migration",
+        "Keeps a snapshot of the data attributes at every block where they change",
+    );
@@ -1,10 +1,11 @@
 use crate::migration::MIGRATIONS;
+use crate::storage::InnerStorage;
 use linkme::distributed_slice;
 use many_error::ManyError;
 use many_migration::InnerMigration;
 
 #[distributed_slice(MIGRATIONS)]
-pub static DISABLE_TOKEN_MINT_MIGRATION: InnerMigration<merk::Merk, ManyError> =
+pub static DISABLE_TOKEN_MINT_MIGRATION: InnerMigration<InnerStorage, ManyError> =
     InnerMigration::new_trigger(
         false,
         "Disable Token Mint Migration",
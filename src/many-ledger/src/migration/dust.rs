@@ -0,0 +1,91 @@
+use crate::migration::MIGRATIONS;
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_identity::Address;
+use many_migration::InnerMigration;
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::Duration;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+/// The `extra` field of the migration metadata holding the dust threshold of
+/// each symbol, as an object of symbols to amounts.
+pub const DUST_THRESHOLDS_KEY: &str = "dust_thresholds";
+
+/// The `extra` field of the migration metadata holding how long an address
+/// must stay inactive before its dust can be reaped, either as a number of
+/// seconds or as a duration string like `"365d"`.
+pub const INACTIVITY_KEY: &str = "inactivity";
+
+/// The `extra` field of the migration metadata holding the address receiving
+/// the reaped dust.
+pub const COMMUNITY_POOL_KEY: &str = "community_pool";
+
+/// Which balances are dust, and where they go once reaped.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DustPolicy {
+    /// Balances strictly below these thresholds are dust. Symbols without a
+    /// threshold are never reaped.
+    pub thresholds: BTreeMap<Symbol, TokenAmount>,
+    pub inactivity: Duration,
+    pub community_pool: Address,
+}
+
+fn missing(key: &str) -> ManyError {
+    ManyError::unknown(format!(
+        "The dust reaping migration requires a valid `{key}` field."
+    ))
+}
+
+impl DustPolicy {
+    pub(crate) fn from_extra(extra: &HashMap<String, Value>) -> Result<Self, ManyError> {
+        let thresholds = extra
+            .get(DUST_THRESHOLDS_KEY)
+            .and_then(Value::as_object)
+            .ok_or_else(|| missing(DUST_THRESHOLDS_KEY))?
+            .iter()
+            .map(|(symbol, amount)| {
+                Some((
+                    Address::from_str(symbol).ok()?,
+                    TokenAmount::from(amount.as_u64()?),
+                ))
+            })
+            .collect::<Option<BTreeMap<_, _>>>()
+            .ok_or_else(|| missing(DUST_THRESHOLDS_KEY))?;
+        let inactivity = extra
+            .get(INACTIVITY_KEY)
+            .and_then(|v| Duration::deserialize(v).ok())
+            .ok_or_else(|| missing(INACTIVITY_KEY))?;
+        let community_pool = extra
+            .get(COMMUNITY_POOL_KEY)
+            .and_then(Value::as_str)
+            .and_then(|address| Address::from_str(address).ok())
+            .ok_or_else(|| missing(COMMUNITY_POOL_KEY))?;
+
+        Ok(Self {
+            thresholds,
+            inactivity,
+            community_pool,
+        })
+    }
+
+    /// Whether a balance of `symbol` is dust.
+    pub fn is_dust(&self, symbol: &Symbol, balance: &TokenAmount) -> bool {
+        !balance.is_zero()
+            && self
+                .thresholds
+                .get(symbol)
+                .map_or(false, |threshold| balance < threshold)
+    }
+}
+
+#[distributed_slice(MIGRATIONS)]
+pub static DUST_REAPING_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_trigger(
+        false,
+        "Dust Reaping Migration",
+        "Sweeps balances below a per-symbol threshold from inactive addresses into a community pool",
+    );
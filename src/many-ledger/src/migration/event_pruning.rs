@@ -0,0 +1,89 @@
+use crate::error;
+use crate::migration::MIGRATIONS;
+use crate::storage::event::EVENTS_ARCHIVED_COUNT_ROOT;
+use crate::storage::iterator::LedgerIterator;
+use crate::storage::{InnerStorage, HEIGHT_ROOT};
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_migration::InnerMigration;
+use many_modules::events::EventId;
+use many_types::{CborRange, SortOrder};
+use merk::Op;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::ops::Bound;
+
+/// The `extra` field of the migration metadata holding the number of blocks
+/// of events to keep in the live store.
+pub const RETENTION_BLOCKS_KEY: &str = "retention_blocks";
+
+fn read_u64(storage: &InnerStorage, key: &[u8]) -> Result<u64, ManyError> {
+    Ok(storage
+        .get(key)
+        .map_err(error::storage_get_failed)?
+        .map_or(0, |x| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(x.as_slice());
+            u64::from_be_bytes(bytes)
+        }))
+}
+
+pub(crate) fn retention_blocks(extra: &HashMap<String, Value>) -> Result<u64, ManyError> {
+    extra
+        .get(RETENTION_BLOCKS_KEY)
+        .and_then(Value::as_u64)
+        .ok_or_else(|| {
+            ManyError::unknown(format!(
+                "The event pruning migration requires a `{RETENTION_BLOCKS_KEY}` integer."
+            ))
+        })
+}
+
+/// Move the events older than the retention window out of the Merk tree and
+/// into its auxiliary column family. The auxiliary data is not part of the
+/// state hash, so nodes can keep or drop the archive as they see fit.
+fn update(storage: &mut InnerStorage, extra: &HashMap<String, Value>) -> Result<(), ManyError> {
+    let retention = retention_blocks(extra)?;
+    let height = read_u64(storage, HEIGHT_ROOT.as_bytes())?;
+    let cutoff = height.saturating_sub(retention);
+    if cutoff == 0 {
+        return Ok(());
+    }
+
+    let range = CborRange {
+        start: Bound::Unbounded,
+        end: Bound::Excluded(EventId::new(cutoff, 0)),
+    };
+    let mut batch = Vec::new();
+    let mut archive = Vec::new();
+    for item in LedgerIterator::events_scoped_by_id(storage, range, SortOrder::Ascending) {
+        let (key, value) = item.map_err(ManyError::unknown)?;
+        batch.push((key.to_vec(), Op::Delete));
+        archive.push((key.to_vec(), Op::Put(value)));
+    }
+    if batch.is_empty() {
+        return Ok(());
+    }
+
+    let archived = read_u64(storage, EVENTS_ARCHIVED_COUNT_ROOT)? + batch.len() as u64;
+    batch.push((
+        EVENTS_ARCHIVED_COUNT_ROOT.to_vec(),
+        Op::Put(archived.to_be_bytes().to_vec()),
+    ));
+
+    // Keys in batch must be sorted.
+    batch.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+    storage.apply(&batch).map_err(error::storage_apply_failed)?;
+    storage
+        .commit(&archive)
+        .map_err(error::storage_commit_failed)?;
+    Ok(())
+}
+
+#[distributed_slice(MIGRATIONS)]
+pub static EVENT_PRUNING_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_update(
+        update,
+        "Event Pruning Migration",
+        "Archives events older than `retention_blocks` blocks at the end of every block",
+    );
@@ -0,0 +1,12 @@
+use crate::migration::MIGRATIONS;
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_migration::InnerMigration;
+
+#[distributed_slice(MIGRATIONS)]
+pub static FREEZE_MIGRATION: InnerMigration<InnerStorage, ManyError> = InnerMigration::new_trigger(
+    false,
+    "Freeze Migration",
+    "Enables freezing addresses, which blocks their transfers, mints and multisig executions",
+);
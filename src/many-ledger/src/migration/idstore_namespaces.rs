@@ -0,0 +1,47 @@
+use crate::error;
+use crate::migration::MIGRATIONS;
+use crate::storage::idstore::{IdStoreStats, IDSTORE_RECALL_PHRASE_ROOT, IDSTORE_STATS_ROOT};
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_migration::InnerMigration;
+use merk::rocksdb::{IteratorMode, ReadOptions};
+use merk::{rocksdb, Op};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Initialize the statistics of the default namespace from the recall phrases
+/// stored so far.
+fn initialize(storage: &mut InnerStorage, _: &HashMap<String, Value>) -> Result<(), ManyError> {
+    let mut opts = ReadOptions::default();
+    opts.set_iterate_range(rocksdb::PrefixRange(IDSTORE_RECALL_PHRASE_ROOT));
+
+    let mut count = 0u64;
+    for item in storage.iter_opt(IteratorMode::Start, opts) {
+        item.map_err(error::storage_get_failed)?;
+        count += 1;
+    }
+
+    let stats = IdStoreStats {
+        count,
+        last_assignment: None,
+    };
+    storage
+        .apply(&[(
+            IDSTORE_STATS_ROOT.to_vec(),
+            Op::Put(minicbor::to_vec(stats).map_err(ManyError::serialization_error)?),
+        )])
+        .map_err(error::storage_apply_failed)?;
+    Ok(())
+}
+
+#[distributed_slice(MIGRATIONS)]
+pub static IDSTORE_NAMESPACES_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_initialize(
+        initialize,
+        "Idstore Namespaces Migration",
+        r#"
+            Allows recall phrases in separate namespaces, each with its own seed.
+            Keeps statistics of the recall phrases of each namespace.
+            "#,
+    );
@@ -0,0 +1,86 @@
+use crate::migration::MIGRATIONS;
+use crate::module::idstore_wordlist::DEFAULT_WORDLIST;
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_migration::InnerMigration;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// The `extra` field of the migration metadata holding the wordlist of the
+/// recall phrases generated when a request does not name one. This is either
+/// a BIP-39 language, e.g. `english` or `french`, or the name of a custom
+/// wordlist. [DEFAULT_WORDLIST] if unspecified.
+pub const DEFAULT_WORDLIST_KEY: &str = "default_wordlist";
+
+/// The `extra` field of the migration metadata holding the custom wordlists
+/// of the network, as an object of names to the hexadecimal SHA-256 hash of
+/// their words, see [crate::module::idstore_wordlist::Wordlist::hash]. Every
+/// node must load these wordlists, with the same words.
+pub const CUSTOM_WORDLISTS_KEY: &str = "custom_wordlists";
+
+/// Which wordlists recall phrases can be generated from. This is part of the
+/// migration config so all the nodes of a network generate the same recall
+/// phrases.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WordlistPolicy {
+    pub default: String,
+
+    /// The hashes of the custom wordlists, by name.
+    pub custom: BTreeMap<String, String>,
+}
+
+impl Default for WordlistPolicy {
+    fn default() -> Self {
+        Self {
+            default: DEFAULT_WORDLIST.to_string(),
+            custom: BTreeMap::new(),
+        }
+    }
+}
+
+fn invalid(key: &str) -> ManyError {
+    ManyError::unknown(format!(
+        "The idstore wordlists migration requires a valid `{key}` field."
+    ))
+}
+
+impl WordlistPolicy {
+    pub(crate) fn from_extra(extra: &HashMap<String, Value>) -> Result<Self, ManyError> {
+        let default = match extra.get(DEFAULT_WORDLIST_KEY) {
+            None => DEFAULT_WORDLIST.to_string(),
+            Some(v) => v
+                .as_str()
+                .ok_or_else(|| invalid(DEFAULT_WORDLIST_KEY))?
+                .to_string(),
+        };
+        let custom = match extra.get(CUSTOM_WORDLISTS_KEY) {
+            None => BTreeMap::new(),
+            Some(v) => v
+                .as_object()
+                .ok_or_else(|| invalid(CUSTOM_WORDLISTS_KEY))?
+                .iter()
+                .map(|(name, hash)| {
+                    let hash = hash.as_str()?.to_lowercase();
+                    let valid = hex::decode(&hash).map_or(false, |bytes| bytes.len() == 32);
+                    valid.then(|| (name.clone(), hash))
+                })
+                .collect::<Option<BTreeMap<_, _>>>()
+                .ok_or_else(|| invalid(CUSTOM_WORDLISTS_KEY))?,
+        };
+
+        Ok(Self { default, custom })
+    }
+}
+
+#[distributed_slice(MIGRATIONS)]
+pub static IDSTORE_WORDLISTS_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_trigger(
+        false,
+        "Idstore Wordlists Migration",
+        r#"
+            Allows recall phrases from other wordlists than english, as listed in the migration
+            metadata. Before this migration, all recall phrases are generated from the english
+            BIP-39 wordlist.
+            "#,
+    );
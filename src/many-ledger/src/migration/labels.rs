@@ -0,0 +1,51 @@
+use crate::migration::MIGRATIONS;
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_identity::Address;
+use many_migration::InnerMigration;
+use many_types::ledger::{Symbol, TokenAmount};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// The `extra` field of the migration metadata holding the symbol of the
+/// deposit to claim a label, or to schedule a send.
+pub const DEPOSIT_SYMBOL_KEY: &str = "deposit_symbol";
+
+/// The `extra` field of the migration metadata holding the amount of the
+/// deposit to claim a label, or to schedule a send.
+pub const DEPOSIT_AMOUNT_KEY: &str = "deposit_amount";
+
+/// The deposit configured in the metadata of `migration`.
+pub(crate) fn deposit(
+    migration: &str,
+    extra: &HashMap<String, Value>,
+) -> Result<(Symbol, TokenAmount), ManyError> {
+    let symbol = extra
+        .get(DEPOSIT_SYMBOL_KEY)
+        .and_then(Value::as_str)
+        .and_then(|symbol| Address::from_str(symbol).ok())
+        .ok_or_else(|| {
+            ManyError::unknown(format!(
+                "The {migration} requires a `{DEPOSIT_SYMBOL_KEY}` symbol."
+            ))
+        })?;
+    let amount = extra
+        .get(DEPOSIT_AMOUNT_KEY)
+        .and_then(Value::as_u64)
+        .ok_or_else(|| {
+            ManyError::unknown(format!(
+                "The {migration} requires a `{DEPOSIT_AMOUNT_KEY}` integer."
+            ))
+        })?;
+    Ok((symbol, amount.into()))
+}
+
+#[distributed_slice(MIGRATIONS)]
+pub static ADDRESS_LABELS_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_trigger(
+        false,
+        "Address Labels Migration",
+        "Enables address labels, claimed by address owners against a deposit",
+    );
@@ -1,10 +1,11 @@
 use crate::migration::MIGRATIONS;
+use crate::storage::InnerStorage;
 use linkme::distributed_slice;
 use many_error::ManyError;
 use many_migration::InnerMigration;
 
 #[distributed_slice(MIGRATIONS)]
-pub static LEGACY_REMOVE_ROLES_TRIGGER: InnerMigration<merk::Merk, ManyError> =
+pub static LEGACY_REMOVE_ROLES_TRIGGER: InnerMigration<InnerStorage, ManyError> =
     InnerMigration::new_trigger(
         true,
         "LegacyRemoveRoles",
@@ -7,7 +7,6 @@ use crate::storage::InnerStorage;
 use linkme::distributed_slice;
 use many_error::ManyError;
 use many_migration::InnerMigration;
-use many_modules::account::features::multisig::InfoReturn;
 use many_modules::events::{EventInfo, EventLog};
 use many_types::{Memo, SortOrder};
 use merk::Op;
@@ -45,7 +44,15 @@ fn update_multisig_submit_events(storage: &mut InnerStorage) -> Result<(), ManyE
     let mut batch = Vec::new();
 
     for log in iter_through_events(storage) {
-        let (key, EventLog { id, time, content }) = log?;
+        let (
+            key,
+            EventLog {
+                id,
+                time,
+                content,
+                intent_hash,
+            },
+        ) = log?;
 
         if let EventInfo::AccountMultisigSubmit {
             submitter,
@@ -63,18 +70,7 @@ fn update_multisig_submit_events(storage: &mut InnerStorage) -> Result<(), ManyE
             if memo.is_some() {
                 continue;
             }
-            let memo = match (memo_, data_) {
-                (Some(m), Some(d)) => {
-                    let mut m = Memo::from(m);
-                    m.push_bytes(d.as_bytes().to_vec())?;
-                    Some(m)
-                }
-                (Some(m), _) => Some(Memo::from(m)),
-                (_, Some(d)) => Some(Memo::from(d)),
-                _ => None,
-            };
-
-            if let Some(memo) = memo {
+            if let Some(memo) = Memo::from_legacy(memo_, data_)? {
                 let new_log = EventLog {
                     id,
                     time,
@@ -90,6 +86,7 @@ fn update_multisig_submit_events(storage: &mut InnerStorage) -> Result<(), ManyE
                         data_: None,
                         memo: Some(memo),
                     },
+                    intent_hash,
                 };
                 batch.push((
                     key,
@@ -120,6 +117,7 @@ fn update_multisig_storage(storage: &mut InnerStorage) -> Result<(), ManyError>
                 info,
                 creation,
                 disabled,
+                member_approvals,
             },
         ) = multisig?;
 
@@ -127,28 +125,13 @@ fn update_multisig_storage(storage: &mut InnerStorage) -> Result<(), ManyError>
             continue;
         }
 
-        let new_memo = match (info.memo_, info.data_) {
-            (Some(m), Some(d)) => {
-                let mut memo = Memo::from(m);
-                memo.push_bytes(d.as_bytes().to_vec())?;
-                Some(memo)
-            }
-            (Some(m), _) => Some(Memo::from(m)),
-            (_, Some(d)) => Some(Memo::from(d)),
-            _ => None,
-        };
-
-        if let Some(memo) = new_memo {
+        if info.memo_.is_some() || info.data_.is_some() {
             let new_multisig = MultisigTransactionStorage {
                 account,
                 creation,
-                info: InfoReturn {
-                    memo_: None,
-                    data_: None,
-                    memo: Some(memo),
-                    ..info
-                },
+                info: info.without_legacy_memo()?,
                 disabled,
+                member_approvals,
             };
 
             batch.push((
@@ -0,0 +1,49 @@
+use crate::error;
+use crate::error::storage_commit_failed;
+use crate::migration::MIGRATIONS;
+use crate::storage::iterator::LedgerIterator;
+use crate::storage::multisig::{
+    key_for_account_multisig, token_from_key, MultisigTransactionStorage,
+};
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_migration::InnerMigration;
+use many_types::SortOrder;
+use merk::Op;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Index the pending transactions of every multisig account.
+fn initialize(storage: &mut InnerStorage, _: &HashMap<String, Value>) -> Result<(), ManyError> {
+    let mut batch = Vec::new();
+
+    for item in LedgerIterator::all_multisig(storage, SortOrder::Ascending) {
+        let (k, v) = item.map_err(ManyError::unknown)?;
+        let tx = minicbor::decode::<MultisigTransactionStorage>(v.as_slice())
+            .map_err(ManyError::deserialization_error)?;
+        if tx.disabled {
+            continue;
+        }
+        batch.push((
+            key_for_account_multisig(&tx.account, &token_from_key(&k)),
+            Op::Put(k.to_vec()),
+        ));
+    }
+
+    // The index is ordered by account first.
+    batch.sort_by(|(a, _), (b, _)| a.cmp(b));
+    storage
+        .apply(batch.as_slice())
+        .map_err(error::storage_apply_failed)?;
+    storage.commit(&[]).map_err(storage_commit_failed)?;
+    Ok(())
+}
+
+#[distributed_slice(MIGRATIONS)]
+pub static MULTISIG_ACCOUNT_INDEX_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_initialize(
+        initialize,
+        "Multisig Account Index Migration",
+        "Index the pending multisig transactions by account.",
+    );
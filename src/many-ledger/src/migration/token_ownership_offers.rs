@@ -0,0 +1,37 @@
+use crate::migration::MIGRATIONS;
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_migration::InnerMigration;
+use many_types::Duration;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The `extra` field of the migration metadata holding how long the new owner
+/// of a token has to accept its ownership, either as a number of seconds or
+/// as a duration string like `"7d"`. Offers last [DEFAULT_OFFER_WINDOW_DAYS]
+/// days if unspecified.
+pub const OFFER_WINDOW_KEY: &str = "offer_window";
+
+pub const DEFAULT_OFFER_WINDOW_DAYS: u64 = 7;
+
+/// How long an ownership offer lasts.
+pub(crate) fn offer_window(extra: &HashMap<String, Value>) -> Result<Duration, ManyError> {
+    match extra.get(OFFER_WINDOW_KEY) {
+        None => Duration::from_days(DEFAULT_OFFER_WINDOW_DAYS),
+        Some(v) => Duration::deserialize(v).map_err(|_| {
+            ManyError::unknown(format!(
+                "The token ownership offers migration requires a valid `{OFFER_WINDOW_KEY}` duration."
+            ))
+        }),
+    }
+}
+
+#[distributed_slice(MIGRATIONS)]
+pub static TOKEN_OWNERSHIP_OFFERS_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_trigger(
+        false,
+        "Token Ownership Offers Migration",
+        "Transfers the ownership of tokens only once the new owner accepts it",
+    );
@@ -1,29 +1,60 @@
 use crate::error;
 use crate::json::InitialStateJson;
+use crate::storage::event::ImportedEvent;
 use crate::storage::LedgerStorage;
 use many_error::ManyError;
+use many_identity::{Address, Identity};
 use many_migration::MigrationConfig;
+use std::collections::BTreeSet;
 use std::fmt::Debug;
 use std::path::Path;
+use std::sync::Arc;
 use tracing::info;
 
 mod abci;
 pub mod account;
 pub mod allow_addrs;
+pub mod atomic;
 mod data;
 mod event;
+mod freeze;
+mod hooks;
 mod idstore;
 pub mod idstore_webauthn;
+pub mod idstore_wordlist;
+mod labels;
 mod ledger;
 mod ledger_commands;
 mod ledger_mintburn;
 mod ledger_tokens;
 mod multisig;
+pub mod query_state;
+mod receipts;
+mod scheduled_send;
+#[cfg(feature = "scripting")]
+mod scripts;
+mod streams;
+mod token_identity;
+mod vesting;
+mod watchlist;
 
 /// A simple ledger that keeps transactions in memory.
-#[derive(Debug)]
 pub struct LedgerModuleImpl {
     storage: LedgerStorage,
+
+    /// The identity signing receipts, usually the server's.
+    receipt_signer: Option<Arc<dyn Identity>>,
+
+    /// The wordlists of the recall phrases of the id store.
+    idstore_wordlists: idstore_wordlist::Wordlists,
+}
+
+impl Debug for LedgerModuleImpl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LedgerModuleImpl")
+            .field("storage", &self.storage)
+            .finish_non_exhaustive()
+    }
 }
 
 impl LedgerModuleImpl {
@@ -41,6 +72,14 @@ impl LedgerModuleImpl {
         let accounts = state
             .accounts
             .map(|a| a.into_iter().map(|v| v.into()).collect());
+        let imported_events = state
+            .imported_events
+            .map(|e| {
+                e.into_iter()
+                    .map(ImportedEvent::try_from)
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?;
 
         let storage = LedgerStorage::new(persistence_store_path, blockchain)?
             .with_migrations(migration_config)?
@@ -54,6 +93,9 @@ impl LedgerModuleImpl {
                 balances,
             )?
             .with_account(state.account_identity, accounts)?
+            .with_memo_limits(state.memo_limits.map(Into::into))?
+            .with_extended_info_policy(state.extended_info_policy.map(Into::into))?
+            .with_imported_events(imported_events)?
             .build()?;
 
         if let Some(h) = state.hash {
@@ -71,7 +113,11 @@ impl LedgerModuleImpl {
 
         tracing::debug!("Final migrations: {:?}", storage.migrations());
 
-        Ok(Self { storage })
+        Ok(Self {
+            storage,
+            receipt_signer: None,
+            idstore_wordlists: Default::default(),
+        })
     }
 
     pub fn load<P: AsRef<Path>>(
@@ -83,7 +129,109 @@ impl LedgerModuleImpl {
 
         tracing::debug!("Final migrations: {:?}", storage.migrations());
 
-        Ok(Self { storage })
+        Ok(Self {
+            storage,
+            receipt_signer: None,
+            idstore_wordlists: Default::default(),
+        })
+    }
+
+    /// Set the notifier used to deliver multisig notices to account members.
+    pub fn set_multisig_notifier(
+        &mut self,
+        notifier: std::sync::Arc<dyn crate::storage::multisig_notifications::MultisigNotifier>,
+    ) {
+        self.storage.set_multisig_notifier(notifier)
+    }
+
+    /// The height of the latest block committed.
+    pub fn height(&self) -> Result<u64, ManyError> {
+        self.storage.get_height()
+    }
+
+    /// Returns the value of `key` in the kvstore namespace of an account.
+    pub fn account_kvstore_get(
+        &self,
+        account: &Address,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, ManyError> {
+        self.storage.account_kvstore_get(account, key)
+    }
+
+    /// Take snapshots of the state, which new nodes can start from.
+    pub fn set_snapshots(&mut self, config: crate::storage::snapshots::SnapshotConfig) {
+        self.storage.set_snapshots(config)
+    }
+
+    /// Keep durable event subscriptions in the file at `path`. Only the
+    /// `operators` can create them.
+    pub fn set_durable_subscriptions(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+        operators: BTreeSet<Address>,
+    ) -> Result<(), ManyError> {
+        self.storage.set_durable_subscriptions(path, operators)
+    }
+
+    /// The ids of the durable subscriptions of this node.
+    pub fn durable_subscription_ids(&self) -> Vec<many_modules::events::SubscriptionId> {
+        self.storage.durable_subscription_ids()
+    }
+
+    /// The events to deliver to the target of a durable subscription.
+    pub fn pending_delivery(
+        &self,
+        id: many_modules::events::SubscriptionId,
+    ) -> Result<Option<crate::storage::durable_subscriptions::PendingDelivery>, ManyError> {
+        self.storage.pending_delivery(id)
+    }
+
+    /// The events to deliver to the targets of all durable subscriptions.
+    pub fn pending_deliveries(
+        &self,
+    ) -> Result<Vec<crate::storage::durable_subscriptions::PendingDelivery>, ManyError> {
+        self.storage.pending_deliveries()
+    }
+
+    /// Record that the target of a durable subscription acknowledged the
+    /// events up to `cursor`.
+    pub fn acknowledge_delivery(
+        &mut self,
+        id: many_modules::events::SubscriptionId,
+        cursor: many_modules::events::EventId,
+    ) -> Result<(), ManyError> {
+        self.storage.acknowledge_delivery(id, cursor)
+    }
+
+    /// Set the identity signing receipts returned by `ledger.getReceipt`.
+    pub fn set_receipt_signer(&mut self, signer: Arc<dyn Identity>) {
+        self.receipt_signer = Some(signer);
+    }
+
+    /// Set the wordlists recall phrases are generated from by `idstore.store`.
+    /// They must match the wordlists of the migration config, if any. Custom
+    /// wordlists cannot be used without it.
+    pub fn set_idstore_wordlists(
+        &mut self,
+        wordlists: idstore_wordlist::Wordlists,
+    ) -> Result<(), ManyError> {
+        wordlists.check(&self.storage.idstore_wordlist_policy()?.unwrap_or_default())?;
+        self.idstore_wordlists = wordlists;
+        Ok(())
+    }
+
+    /// The address of the validator which proposed the current block, if
+    /// known.
+    pub fn block_proposer(&self) -> Option<&minicbor::bytes::ByteVec> {
+        self.storage.proposer()
+    }
+
+    /// The ledger invariants broken by the committed state, if any.
+    #[cfg_attr(not(feature = "invariant_checks"), allow(dead_code))]
+    pub fn check_invariants(
+        &self,
+    ) -> Result<Vec<crate::storage::invariants::InvariantViolation>, ManyError> {
+        self.storage.check_invariants()
     }
 
     #[cfg(feature = "balance_testing")]
@@ -1,8 +1,10 @@
+use crate::migration::multisig_expired_events::MULTISIG_EXPIRED_EVENTS_MIGRATION;
 use crate::module::LedgerModuleImpl;
 use many_error::ManyError;
 use many_modules::abci_backend::{
-    AbciBlock, AbciCommitInfo, AbciInfo, AbciInit, BeginBlockReturn, EndpointInfo, InitChainReturn,
-    ManyAbciModuleBackend,
+    AbciApplySnapshotChunk, AbciApplySnapshotChunkReturn, AbciBlock, AbciCommitInfo, AbciInfo,
+    AbciInit, AbciListSnapshots, AbciLoadSnapshotChunk, AbciOfferSnapshot, AbciOfferSnapshotReturn,
+    AbciSnapshotChunk, BeginBlockReturn, EndpointInfo, InitChainReturn, ManyAbciModuleBackend,
 };
 use many_types::Timestamp;
 use std::collections::BTreeMap;
@@ -20,13 +22,19 @@ impl ManyAbciModuleBackend for LedgerModuleImpl {
                 ("ledger.send".to_string(), EndpointInfo { is_command: true }),
 
                 // Events
+                // Subscriptions are local to a node and not part of the state,
+                // so they are not routed through consensus. Clients manage them
+                // on the ledger server of their node.
                 ("events.info".to_string(), EndpointInfo { is_command: false }),
                 ("events.list".to_string(), EndpointInfo { is_command: false }),
+                ("events.archiveInfo".to_string(), EndpointInfo { is_command: false }),
+                ("events.findByIntentHash".to_string(), EndpointInfo { is_command: false }),
 
                 // IdStore
                 ("idstore.store".to_string(), EndpointInfo { is_command: true }),
                 ("idstore.getFromRecallPhrase".to_string(), EndpointInfo { is_command: false }),
                 ("idstore.getFromAddress".to_string(), EndpointInfo { is_command: false }),
+                ("idstore.stats".to_string(), EndpointInfo { is_command: false }),
 
                 // Accounts
                 ("account.create".to_string(), EndpointInfo { is_command: true }),
@@ -38,8 +46,11 @@ impl ManyAbciModuleBackend for LedgerModuleImpl {
                 ("account.info".to_string(), EndpointInfo { is_command: false }),
                 ("account.disable".to_string(), EndpointInfo { is_command: true }),
                 ("account.addFeatures".to_string(), EndpointInfo { is_command: true }),
+                ("account.removeFeatures".to_string(), EndpointInfo { is_command: true }),
 
                 // Account Features - Multisig
+                // Notification endpoints are only provided to the local node and
+                // are never part of the state; only their hash is registered.
                 ("account.multisigSetDefaults".to_string(), EndpointInfo { is_command: true }),
                 ("account.multisigSubmitTransaction".to_string(), EndpointInfo { is_command: true }),
                 ("account.multisigInfo".to_string(), EndpointInfo { is_command: false }),
@@ -47,6 +58,18 @@ impl ManyAbciModuleBackend for LedgerModuleImpl {
                 ("account.multisigRevoke".to_string(), EndpointInfo { is_command: true }),
                 ("account.multisigExecute".to_string(), EndpointInfo { is_command: true }),
                 ("account.multisigWithdraw".to_string(), EndpointInfo { is_command: true }),
+                ("account.multisigResult".to_string(), EndpointInfo { is_command: false }),
+                ("account.multisigRegisterNotification".to_string(), EndpointInfo { is_command: true }),
+                ("account.multisigUnregisterNotification".to_string(), EndpointInfo { is_command: true }),
+                ("account.multisigListNotifications".to_string(), EndpointInfo { is_command: false }),
+                ("account.multisigListPending".to_string(), EndpointInfo { is_command: false }),
+
+                // Account Features - Hooks
+                ("account.hookRecords".to_string(), EndpointInfo { is_command: false }),
+                ("account.hookClear".to_string(), EndpointInfo { is_command: true }),
+
+                // Account Features - Vesting
+                ("vesting.info".to_string(), EndpointInfo { is_command: false }),
 
                 // Data Attributes
                 ("data.info".to_string(), EndpointInfo { is_command: false }),
@@ -61,6 +84,35 @@ impl ManyAbciModuleBackend for LedgerModuleImpl {
                 ("tokens.removeExtendedInfo".to_string(), EndpointInfo { is_command : true }),
                 ("tokens.mint".to_string(), EndpointInfo { is_command : true }),
                 ("tokens.burn".to_string(), EndpointInfo { is_command : true }),
+
+                // Watchlist
+                ("watchlist.info".to_string(), EndpointInfo { is_command: false }),
+                ("watchlist.add".to_string(), EndpointInfo { is_command: true }),
+                ("watchlist.remove".to_string(), EndpointInfo { is_command: true }),
+                ("watchlist.setApprovers".to_string(), EndpointInfo { is_command: true }),
+                ("ledger.freeze".to_string(), EndpointInfo { is_command: true }),
+                ("ledger.unfreeze".to_string(), EndpointInfo { is_command: true }),
+                ("ledger.frozen".to_string(), EndpointInfo { is_command: false }),
+                ("ledger.scheduleSend".to_string(), EndpointInfo { is_command: true }),
+                ("ledger.cancelScheduled".to_string(), EndpointInfo { is_command: true }),
+                ("ledger.scheduled".to_string(), EndpointInfo { is_command: false }),
+                ("ledger.getReceipt".to_string(), EndpointInfo { is_command: false }),
+                ("ledger.streamCreate".to_string(), EndpointInfo { is_command: true }),
+                ("ledger.streamWithdraw".to_string(), EndpointInfo { is_command: true }),
+                ("ledger.streamCancel".to_string(), EndpointInfo { is_command: true }),
+                ("ledger.streamInfo".to_string(), EndpointInfo { is_command: false }),
+                ("ledger.updateTokenIdentity".to_string(), EndpointInfo { is_command: true }),
+
+                // Address labels
+                ("labels.claim".to_string(), EndpointInfo { is_command: true }),
+                ("labels.release".to_string(), EndpointInfo { is_command: true }),
+                ("labels.resolve".to_string(), EndpointInfo { is_command: false }),
+                ("labels.lookup".to_string(), EndpointInfo { is_command: false }),
+
+                // Scripts (experimental)
+                ("scripts.deploy".to_string(), EndpointInfo { is_command: true }),
+                ("scripts.invoke".to_string(), EndpointInfo { is_command: true }),
+                ("scripts.info".to_string(), EndpointInfo { is_command: false }),
             ]),
         })
     }
@@ -82,8 +134,23 @@ impl ManyAbciModuleBackend for LedgerModuleImpl {
             let time = Timestamp::new(time)?;
             self.storage.set_time(time);
         }
+        self.storage.set_proposer(info.proposer);
 
-        Ok(BeginBlockReturn {})
+        // Events logged here are not caused by the `abci.beginBlock` request.
+        many_protocol::context::without_intent(|| {
+            self.execute_scheduled_sends()?;
+            self.storage.reap_dust()?;
+
+            if self
+                .storage
+                .migrations()
+                .is_active(&MULTISIG_EXPIRED_EVENTS_MIGRATION)
+            {
+                self.storage.check_timed_out_multisig_transactions()?;
+            }
+
+            Ok(BeginBlockReturn {})
+        })
     }
 
     fn info(&self) -> Result<AbciInfo, ManyError> {
@@ -111,4 +178,35 @@ impl ManyAbciModuleBackend for LedgerModuleImpl {
         );
         Ok(result)
     }
+
+    fn list_snapshots(&self) -> Result<AbciListSnapshots, ManyError> {
+        self.storage.list_snapshots()
+    }
+
+    fn offer_snapshot(
+        &mut self,
+        args: AbciOfferSnapshot,
+    ) -> Result<AbciOfferSnapshotReturn, ManyError> {
+        info!(
+            "abci.offer_snapshot(): height={} chunks={}",
+            args.snapshot.height, args.snapshot.chunks
+        );
+        Ok(AbciOfferSnapshotReturn {
+            result: self.storage.offer_snapshot(args)?,
+        })
+    }
+
+    fn load_snapshot_chunk(
+        &self,
+        args: AbciLoadSnapshotChunk,
+    ) -> Result<AbciSnapshotChunk, ManyError> {
+        self.storage.load_snapshot_chunk(args)
+    }
+
+    fn apply_snapshot_chunk(
+        &mut self,
+        args: AbciApplySnapshotChunk,
+    ) -> Result<AbciApplySnapshotChunkReturn, ManyError> {
+        self.storage.apply_snapshot_chunk(args)
+    }
 }
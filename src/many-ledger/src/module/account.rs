@@ -1,7 +1,10 @@
+use crate::migration::account_remove_features::ACCOUNT_REMOVE_FEATURES_MIGRATION;
 use crate::module::LedgerModuleImpl;
+use crate::storage::LedgerStorage;
 use coset::CoseSign1;
 use many_error::{ManyError, ManyErrorCode};
 use many_identity::Address;
+use many_modules::account::features::vesting::AccountVesting;
 use many_modules::account::features::{multisig, FeatureId, FeatureInfo, TryCreateFeature};
 use many_modules::account::{Account, AccountModuleBackend, Role};
 use many_modules::{account, EmptyReturn, ManyModule, ManyModuleInfo};
@@ -10,7 +13,7 @@ use many_types::cbor::CborAny;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{Debug, Formatter};
 
-fn get_roles_for_account(account: &account::Account) -> BTreeSet<account::Role> {
+pub(crate) fn get_roles_for_account(account: &account::Account) -> BTreeSet<account::Role> {
     let features = account.features();
 
     let mut roles = BTreeSet::new();
@@ -50,6 +53,18 @@ pub(crate) fn validate_features_for_account(account: &account::Account) -> Resul
         }
     }
 
+    if let Err(e) = features.get::<account::features::hooks::AccountHooks>() {
+        if e.code() != ManyErrorCode::AttributeNotFound {
+            return Err(e);
+        }
+    }
+
+    if let Err(e) = features.get::<account::features::vesting::AccountVesting>() {
+        if e.code() != ManyErrorCode::AttributeNotFound {
+            return Err(e);
+        }
+    }
+
     Ok(())
 }
 
@@ -98,15 +113,17 @@ pub(crate) fn validate_account(account: &account::Account) -> Result<(), ManyErr
     Ok(())
 }
 
-pub(crate) fn verify_account_role<R: TryInto<Role> + std::fmt::Display + Copy>(
+pub(crate) fn verify_account_role(
+    storage: &LedgerStorage,
+    account_id: &Address,
     account: &Account,
     sender: &Address,
     feature_id: FeatureId,
-    role: impl IntoIterator<Item = R>,
+    role: impl IntoIterator<Item = Role>,
 ) -> Result<(), ManyError> {
-    if !account.has_role(sender, account::Role::Owner) {
+    if !storage.has_account_role(account_id, account, sender, [account::Role::Owner]) {
         if account.features.has_id(feature_id) {
-            account.needs_role(sender, role)?;
+            storage.needs_account_role(account_id, account, sender, role)?;
         } else {
             return Err(super::error::unauthorized());
         }
@@ -278,6 +295,54 @@ impl AccountModuleBackend for LedgerModuleImpl {
                 .map(|_| EmptyReturn)
         }
     }
+
+    fn remove_features(
+        &mut self,
+        sender: &Address,
+        args: account::RemoveFeaturesArgs,
+    ) -> Result<account::RemoveFeaturesReturn, ManyError> {
+        if !self
+            .storage
+            .migrations()
+            .is_active(&ACCOUNT_REMOVE_FEATURES_MIGRATION)
+        {
+            return Err(ManyError::invalid_method_name("account.removeFeatures"));
+        }
+
+        let (account, _) = self.storage.get_account(&args.account)?;
+        account.needs_role(sender, [account::Role::Owner])?;
+
+        // Pending transactions could not be approved or executed anymore.
+        if args
+            .features
+            .contains(&multisig::MultisigAccountFeature::ID)
+            && !self
+                .storage
+                .list_pending_multisig_transactions(&args.account)?
+                .is_empty()
+        {
+            return Err(account::errors::feature_in_use(
+                multisig::MultisigAccountFeature::ID,
+                "the account has pending multisig transactions",
+            ));
+        }
+        if args.features.contains(&AccountVesting::ID) {
+            let locked = self
+                .storage
+                .vesting_schedule(&args.account)
+                .map(|schedule| schedule.locked(self.storage.now()));
+            if locked.map_or(false, |locked| !locked.is_zero()) {
+                return Err(account::errors::feature_in_use(
+                    AccountVesting::ID,
+                    "the account still has locked tokens",
+                ));
+            }
+        }
+
+        self.storage
+            .remove_features(account, args)
+            .map(|_| EmptyReturn)
+    }
 }
 
 /// A module for returning the features by this account.
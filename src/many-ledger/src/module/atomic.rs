@@ -0,0 +1,65 @@
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use many_modules::abci_backend::ManyAbciModuleBackend;
+use many_server::server::AtomicGroupCoordinator;
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+
+impl LedgerModuleImpl {
+    /// Start an atomic group. Its writes are kept in memory until it ends.
+    pub fn begin_atomic_group(&mut self) -> Result<(), ManyError> {
+        self.storage.begin_atomic_group()
+    }
+
+    pub fn commit_atomic_group(&mut self) -> Result<(), ManyError> {
+        self.storage.commit_atomic_group()
+    }
+
+    /// Discard the writes and the events of the atomic group.
+    pub fn rollback_atomic_group(&mut self) -> Result<(), ManyError> {
+        self.storage.rollback_atomic_group()
+    }
+}
+
+/// Stages the storage writes of atomic groups of requests in the ledger
+/// storage, so that a group either changes the ledger entirely or not at all.
+/// Groups can only contain the endpoints of the ledger state; the writes of
+/// other modules, e.g. a kvstore served by the same node, are not staged.
+pub struct LedgerAtomicGroups {
+    module_impl: Arc<Mutex<LedgerModuleImpl>>,
+    endpoints: BTreeSet<String>,
+}
+
+impl LedgerAtomicGroups {
+    pub fn new(module_impl: Arc<Mutex<LedgerModuleImpl>>) -> Result<Self, ManyError> {
+        let endpoints = module_impl
+            .lock()
+            .unwrap()
+            .init()?
+            .endpoints
+            .into_keys()
+            .collect();
+        Ok(Self {
+            module_impl,
+            endpoints,
+        })
+    }
+}
+
+impl AtomicGroupCoordinator for LedgerAtomicGroups {
+    fn supports(&self, method: &str) -> bool {
+        self.endpoints.contains(method)
+    }
+
+    fn begin(&self) -> Result<(), ManyError> {
+        self.module_impl.lock().unwrap().begin_atomic_group()
+    }
+
+    fn commit(&self) -> Result<(), ManyError> {
+        self.module_impl.lock().unwrap().commit_atomic_group()
+    }
+
+    fn rollback(&self) -> Result<(), ManyError> {
+        self.module_impl.lock().unwrap().rollback_atomic_group()
+    }
+}
@@ -34,16 +34,27 @@ impl DataModuleBackend for LedgerModuleImpl {
         args: DataGetInfoArgs,
         context: Context,
     ) -> Result<DataGetInfoReturns, ManyError> {
+        let mut keys = vec![crate::storage::data::DATA_INFO_KEY.to_vec()];
+        let historical = args
+            .height
+            .map(|height| self.storage.data_attributes_at_height(height))
+            .transpose()?;
+
         let filtered = self
             .storage
             .data_info()?
             .unwrap_or_default()
             .into_iter()
             .filter(|(k, _)| args.indices.0.contains(k))
+            .filter(|(k, _)| match &historical {
+                Some((_, attributes)) => attributes.contains_key(k),
+                None => true,
+            })
             .collect();
-        self.storage
-            .prove_state(context, vec![crate::storage::data::DATA_INFO_KEY.into()])
-            .map(|_| filtered)
+        if let Some((key, _)) = historical {
+            keys.push(key);
+        }
+        self.storage.prove_state(context, keys).map(|_| filtered)
     }
 
     fn query(
@@ -52,18 +63,19 @@ impl DataModuleBackend for LedgerModuleImpl {
         args: DataQueryArgs,
         context: Context,
     ) -> Result<DataQueryReturns, ManyError> {
-        let filtered = self
-            .storage
-            .data_attributes()?
-            .unwrap_or_default()
+        let (key, attributes) = match args.height {
+            Some(height) => self.storage.data_attributes_at_height(height)?,
+            None => (
+                crate::storage::data::DATA_ATTRIBUTES_KEY.to_vec(),
+                self.storage.data_attributes()?.unwrap_or_default(),
+            ),
+        };
+        let filtered = attributes
             .into_iter()
             .filter(|(k, _)| args.indices.0.contains(k))
             .collect();
         self.storage
-            .prove_state(
-                context,
-                vec![crate::storage::data::DATA_ATTRIBUTES_KEY.into()],
-            )
+            .prove_state(context, vec![key])
             .map(|_| filtered)
     }
 }
@@ -1,3 +1,4 @@
+use crate::migration::legacy_memo::LEGACY_MEMO_MIGRATION;
 use crate::module::LedgerModuleImpl;
 use many_error::ManyError;
 use many_identity::Address;
@@ -6,6 +7,7 @@ use many_modules::events;
 use many_modules::events::{
     EventFilterAttributeSpecific, EventFilterAttributeSpecificIndex, EventInfo, EventLog,
 };
+use many_modules::EmptyReturn;
 use many_types::{CborRange, Timestamp, VecOrSingle};
 use std::collections::BTreeMap;
 
@@ -137,8 +139,99 @@ impl events::EventsModuleBackend for LedgerModuleImpl {
         let iter = filter_date(iter, filter.date_range.unwrap_or_default());
         let iter = filter_attribute_specific(iter, &filter.events_filter_attribute_specific);
 
-        let events: Vec<events::EventLog> = iter.take(count).collect::<Result<_, _>>()?;
+        let mut events: Vec<events::EventLog> = iter.take(count).collect::<Result<_, _>>()?;
+        if storage.migrations().is_active(&LEGACY_MEMO_MIGRATION) {
+            events = events
+                .into_iter()
+                .map(|event| {
+                    Ok(events::EventLog {
+                        content: event.content.without_legacy_memo()?,
+                        ..event
+                    })
+                })
+                .collect::<Result<_, ManyError>>()?;
+        }
 
         Ok(events::ListReturns { nb_events, events })
     }
 }
+
+impl events::EventsArchiveModuleBackend for LedgerModuleImpl {
+    fn archive_info(
+        &self,
+        _args: events::ArchiveInfoArgs,
+    ) -> Result<events::ArchiveInfoReturn, ManyError> {
+        self.storage.events_archive_info()
+    }
+}
+
+impl events::EventsIntentModuleBackend for LedgerModuleImpl {
+    fn find_by_intent_hash(
+        &self,
+        args: events::FindByIntentHashArgs,
+    ) -> Result<events::FindByIntentHashReturns, ManyError> {
+        Ok(events::FindByIntentHashReturns {
+            events: self.storage.find_events_by_intent(&args.intent_hash)?,
+        })
+    }
+}
+
+impl events::EventsSubscriptionModuleBackend for LedgerModuleImpl {
+    fn subscribe(
+        &mut self,
+        sender: &Address,
+        args: events::SubscribeArgs,
+    ) -> Result<events::SubscribeReturn, ManyError> {
+        let id = self.storage.subscribe_events(sender, args.filter)?;
+        Ok(events::SubscribeReturn { id })
+    }
+
+    fn poll(
+        &mut self,
+        sender: &Address,
+        args: events::PollArgs,
+    ) -> Result<events::PollReturn, ManyError> {
+        self.storage.poll_events(sender, args.id, args.count)
+    }
+
+    fn unsubscribe(
+        &mut self,
+        sender: &Address,
+        args: events::UnsubscribeArgs,
+    ) -> Result<events::UnsubscribeReturn, ManyError> {
+        self.storage
+            .unsubscribe_events(sender, args.id)
+            .map(|_| EmptyReturn)
+    }
+}
+
+impl events::EventsDurableSubscriptionModuleBackend for LedgerModuleImpl {
+    fn create_subscription(
+        &mut self,
+        sender: &Address,
+        args: events::CreateSubscriptionArgs,
+    ) -> Result<events::CreateSubscriptionReturn, ManyError> {
+        let id = self.storage.create_durable_subscription(sender, args)?;
+        Ok(events::CreateSubscriptionReturn { id })
+    }
+
+    fn list_subscriptions(
+        &self,
+        sender: &Address,
+        _args: events::ListSubscriptionsArgs,
+    ) -> Result<events::ListSubscriptionsReturns, ManyError> {
+        Ok(events::ListSubscriptionsReturns {
+            subscriptions: self.storage.durable_subscriptions_of(sender)?,
+        })
+    }
+
+    fn delete_subscription(
+        &mut self,
+        sender: &Address,
+        args: events::DeleteSubscriptionArgs,
+    ) -> Result<events::DeleteSubscriptionReturn, ManyError> {
+        self.storage
+            .delete_durable_subscription(sender, args.id)
+            .map(|_| EmptyReturn)
+    }
+}
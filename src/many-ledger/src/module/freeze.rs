@@ -0,0 +1,69 @@
+use crate::error;
+use crate::migration::freeze::FREEZE_MIGRATION;
+use crate::module::LedgerModuleImpl;
+use crate::storage::ledger_tokens::TOKEN_IDENTITY_ROOT;
+use crate::storage::IDENTITY_ROOT;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::ledger::{
+    FreezeArgs, FreezeReturns, FrozenArgs, FrozenReturns, LedgerFreezeModuleBackend, UnfreezeArgs,
+    UnfreezeReturns,
+};
+use many_modules::EmptyReturn;
+
+impl LedgerModuleImpl {
+    fn check_freeze_enabled(&self, method: &str) -> Result<(), ManyError> {
+        if self.storage.migrations().is_active(&FREEZE_MIGRATION) {
+            Ok(())
+        } else {
+            Err(ManyError::invalid_method_name(method))
+        }
+    }
+
+    /// Only the server identity or the token identity freeze addresses.
+    fn verify_freeze_admin(&self, sender: &Address) -> Result<(), ManyError> {
+        if *sender == self.storage.get_identity(IDENTITY_ROOT)?
+            || self
+                .storage
+                .get_identity(TOKEN_IDENTITY_ROOT)
+                .map_or(false, |id| *sender == id)
+        {
+            Ok(())
+        } else {
+            Err(error::unauthorized())
+        }
+    }
+}
+
+impl LedgerFreezeModuleBackend for LedgerModuleImpl {
+    fn freeze(&mut self, sender: &Address, args: FreezeArgs) -> Result<FreezeReturns, ManyError> {
+        self.check_freeze_enabled("ledger.freeze")?;
+        self.verify_freeze_admin(sender)?;
+        self.storage.check_memo(args.memo.as_ref())?;
+
+        self.storage
+            .freeze_address(args.address, args.memo)
+            .map(|_| EmptyReturn)
+    }
+
+    fn unfreeze(
+        &mut self,
+        sender: &Address,
+        args: UnfreezeArgs,
+    ) -> Result<UnfreezeReturns, ManyError> {
+        self.check_freeze_enabled("ledger.unfreeze")?;
+        self.verify_freeze_admin(sender)?;
+        self.storage.check_memo(args.memo.as_ref())?;
+
+        self.storage
+            .unfreeze_address(args.address, args.memo)
+            .map(|_| EmptyReturn)
+    }
+
+    fn frozen(&self, _sender: &Address, _args: FrozenArgs) -> Result<FrozenReturns, ManyError> {
+        self.check_freeze_enabled("ledger.frozen")?;
+        Ok(FrozenReturns {
+            addresses: self.storage.get_frozen_addresses()?,
+        })
+    }
+}
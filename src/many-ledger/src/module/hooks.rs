@@ -0,0 +1,65 @@
+use crate::migration::account_hooks::ACCOUNT_HOOKS_MIGRATION;
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::account;
+use many_modules::account::features::hooks::{
+    errors, AccountHooks, AccountHooksModuleBackend, HookClearArgs, HookClearReturn,
+    HookRecordsArgs, HookRecordsReturn, HOOK_RECORDS_MAX_COUNT,
+};
+use many_modules::account::features::TryCreateFeature;
+use many_modules::EmptyReturn;
+
+impl LedgerModuleImpl {
+    fn check_hooks_enabled(
+        &self,
+        method: &str,
+        account_id: &Address,
+        account: &account::Account,
+    ) -> Result<(), ManyError> {
+        if !self
+            .storage
+            .migrations()
+            .is_active(&ACCOUNT_HOOKS_MIGRATION)
+        {
+            return Err(ManyError::invalid_method_name(method));
+        }
+        if !account.features.has_id(AccountHooks::ID) {
+            return Err(errors::hooks_not_enabled(account_id));
+        }
+        Ok(())
+    }
+}
+
+impl AccountHooksModuleBackend for LedgerModuleImpl {
+    fn hook_records(
+        &self,
+        _sender: &Address,
+        args: HookRecordsArgs,
+    ) -> Result<HookRecordsReturn, ManyError> {
+        let (account, _) = self.storage.get_account(&args.account)?;
+        self.check_hooks_enabled("account.hookRecords", &args.account, &account)?;
+
+        let count = args
+            .count
+            .map_or(HOOK_RECORDS_MAX_COUNT, |c| c.min(HOOK_RECORDS_MAX_COUNT));
+        let records = self
+            .storage
+            .hook_records(&args.account, args.after, count as usize)?;
+        Ok(HookRecordsReturn { records })
+    }
+
+    fn hook_clear(
+        &mut self,
+        sender: &Address,
+        args: HookClearArgs,
+    ) -> Result<HookClearReturn, ManyError> {
+        let (account, _) = self.storage.get_account(&args.account)?;
+        self.check_hooks_enabled("account.hookClear", &args.account, &account)?;
+        self.storage
+            .needs_account_role(&args.account, &account, sender, [account::Role::Owner])?;
+
+        self.storage.clear_hook_records(&args.account, args.up_to)?;
+        Ok(EmptyReturn)
+    }
+}
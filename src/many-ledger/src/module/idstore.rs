@@ -1,34 +1,52 @@
+use crate::migration::idstore_namespaces::IDSTORE_NAMESPACES_MIGRATION;
+use crate::migration::idstore_wordlists::IDSTORE_WORDLISTS_MIGRATION;
+use crate::module::idstore_wordlist::{Bip39Language, Wordlist};
 use crate::{module::LedgerModuleImpl, storage::idstore::IDSTORE_ROOT};
 use coset::{CborSerializable, CoseKey};
 use many_error::ManyError;
 use many_identity::Address;
 use many_modules::idstore;
 
-/// Return a recall phrase
-//
-/// The following relation need to hold for having a valid decoding/encoding:
-///
-///     // length_bytes(data) * 8 + checksum = number_of(words) * 11
-///
-/// See [bip39-dict](https://github.com/vincenthz/bip39-dict) for details
-///
-/// # Generic Arguments
-///
-/// * `W` - Word cound
-/// * `FB` - Full Bytes
-/// * `CS` - Checksum Bytes
-pub fn generate_recall_phrase<const W: usize, const FB: usize, const CS: usize>(
-    seed: &[u8],
-) -> Result<Vec<String>, ManyError> {
-    let entropy = bip39_dict::Entropy::<FB>::from_slice(seed)
-        .ok_or_else(|| ManyError::unknown("Unable to generate entropy"))?;
-    let mnemonic = entropy.to_mnemonics::<W, CS>().unwrap();
-    let recall_phrase = mnemonic
-        .to_string(&bip39_dict::ENGLISH)
-        .split_whitespace()
-        .map(|e| e.to_string())
-        .collect::<Vec<String>>();
-    Ok(recall_phrase)
+impl LedgerModuleImpl {
+    /// Verify a namespace can be used on this network.
+    fn check_idstore_namespace(&self, namespace: Option<&str>) -> Result<(), ManyError> {
+        if let Some(namespace) = namespace {
+            if !self
+                .storage
+                .migrations()
+                .is_active(&IDSTORE_NAMESPACES_MIGRATION)
+            {
+                return Err(ManyError::unknown(
+                    "Recall phrase namespaces are disabled on this network",
+                ));
+            }
+            idstore::check_namespace(namespace)?;
+        }
+        Ok(())
+    }
+
+    /// The wordlist of a new recall phrase. Recall phrases are always english
+    /// until the wordlists migration is active, then its policy applies.
+    fn idstore_wordlist(&self, name: Option<&str>) -> Result<Wordlist, ManyError> {
+        if !self
+            .storage
+            .migrations()
+            .is_active(&IDSTORE_WORDLISTS_MIGRATION)
+        {
+            return match name {
+                None => Ok(Wordlist::Bip39(Bip39Language::English)),
+                Some(_) => Err(ManyError::unknown(
+                    "Recall phrase wordlists are disabled on this network",
+                )),
+            };
+        }
+        let policy = self.storage.idstore_wordlist_policy()?.ok_or_else(|| {
+            ManyError::unknown("The idstore wordlists migration is not configured.")
+        })?;
+        self.idstore_wordlists
+            .get(name.unwrap_or(&policy.default))
+            .cloned()
+    }
 }
 
 impl idstore::IdStoreModuleBackend for LedgerModuleImpl {
@@ -39,6 +57,8 @@ impl idstore::IdStoreModuleBackend for LedgerModuleImpl {
             address,
             cred_id,
             public_key,
+            wordlist,
+            namespace,
         }: idstore::StoreArgs,
     ) -> Result<idstore::StoreReturns, ManyError> {
         if sender.is_anonymous() {
@@ -56,6 +76,11 @@ impl idstore::IdStoreModuleBackend for LedgerModuleImpl {
         let _: CoseKey =
             CoseKey::from_slice(&public_key.0).map_err(ManyError::deserialization_error)?;
 
+        let namespace = namespace.as_deref();
+        self.check_idstore_namespace(namespace)?;
+
+        let wordlist = self.idstore_wordlist(wordlist.as_deref())?;
+
         let mut current_try = 1u8;
         let mut keys: Vec<Vec<u8>> = vec![IDSTORE_ROOT.into()];
         let recall_phrase = loop {
@@ -63,23 +88,26 @@ impl idstore::IdStoreModuleBackend for LedgerModuleImpl {
                 return Err(idstore::recall_phrase_generation_failed());
             }
 
-            let seed = self.storage.inc_idstore_seed()?;
+            let seed = self.storage.inc_idstore_seed(namespace)?;
             // Entropy can only be generated if the seed array contains the
             // EXACT amount of full bytes, i.e., the FB parameter of
-            // `generate_recall_phrase`
+            // `Wordlist::recall_phrase`
             let recall_phrase = match seed {
-                0..=0xFFFF => generate_recall_phrase::<2, 2, 6>(&seed.to_be_bytes()[6..]),
-                0x10000..=0xFFFFFF => generate_recall_phrase::<3, 4, 1>(&seed.to_be_bytes()[4..]),
+                0..=0xFFFF => wordlist.recall_phrase::<2, 2, 6>(&seed.to_be_bytes()[6..]),
+                0x10000..=0xFFFFFF => wordlist.recall_phrase::<3, 4, 1>(&seed.to_be_bytes()[4..]),
                 0x1000000..=0xFFFFFFFF => {
-                    generate_recall_phrase::<4, 5, 4>(&seed.to_be_bytes()[3..])
+                    wordlist.recall_phrase::<4, 5, 4>(&seed.to_be_bytes()[3..])
                 }
                 0x100000000..=0xFFFFFFFFFF => {
-                    generate_recall_phrase::<5, 6, 7>(&seed.to_be_bytes()[2..])
+                    wordlist.recall_phrase::<5, 6, 7>(&seed.to_be_bytes()[2..])
                 }
-                _ => unimplemented!(),
+                _ => return Err(idstore::recall_phrase_generation_failed()),
             }?;
 
-            if let Ok((_, _, key)) = self.storage.get_from_recall_phrase(&recall_phrase) {
+            if let Ok((_, _, key)) = self
+                .storage
+                .get_from_recall_phrase(&recall_phrase, namespace)
+            {
                 keys.push(key);
                 current_try += 1;
                 tracing::debug!("Recall phrase generation failed, retrying...")
@@ -90,7 +118,14 @@ impl idstore::IdStoreModuleBackend for LedgerModuleImpl {
 
         let _ = self
             .storage
-            .store(&recall_phrase, &address, cred_id, public_key)?;
+            .store(&recall_phrase, namespace, &address, cred_id, public_key)?;
+        if self
+            .storage
+            .migrations()
+            .is_active(&IDSTORE_NAMESPACES_MIGRATION)
+        {
+            self.storage.inc_idstore_stats(namespace)?;
+        }
         Ok(idstore::StoreReturns(recall_phrase))
     }
 
@@ -98,7 +133,9 @@ impl idstore::IdStoreModuleBackend for LedgerModuleImpl {
         &self,
         args: idstore::GetFromRecallPhraseArgs,
     ) -> Result<idstore::GetReturns, ManyError> {
-        let (cred_id, public_key, _) = self.storage.get_from_recall_phrase(&args.0)?;
+        let namespace = args.1.as_deref();
+        self.check_idstore_namespace(namespace)?;
+        let (cred_id, public_key, _) = self.storage.get_from_recall_phrase(&args.0, namespace)?;
         Ok(idstore::GetReturns {
             cred_id,
             public_key,
@@ -115,6 +152,24 @@ impl idstore::IdStoreModuleBackend for LedgerModuleImpl {
             public_key,
         })
     }
+
+    fn stats(&self, args: idstore::StatsArgs) -> Result<idstore::StatsReturns, ManyError> {
+        if !self
+            .storage
+            .migrations()
+            .is_active(&IDSTORE_NAMESPACES_MIGRATION)
+        {
+            return Err(ManyError::invalid_method_name("idstore.stats"));
+        }
+
+        let namespace = args.namespace.as_deref();
+        self.check_idstore_namespace(namespace)?;
+        let stats = self.storage.idstore_stats(namespace)?;
+        Ok(idstore::StatsReturns {
+            count: stats.count,
+            last_assignment: stats.last_assignment,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -152,6 +207,8 @@ mod tests {
                 address: id,
                 cred_id: cred_id.clone(),
                 public_key: public_key.clone(),
+                wordlist: None,
+                namespace: None,
             },
         );
         assert!(result.is_ok());
@@ -165,6 +222,8 @@ mod tests {
                 address: id,
                 cred_id: cred_id.clone(),
                 public_key: public_key.clone(),
+                wordlist: None,
+                namespace: None,
             },
         );
         assert!(result2.is_ok());
@@ -180,6 +239,8 @@ mod tests {
                     address: id,
                     cred_id: cred_id.clone(),
                     public_key: public_key.clone(),
+                    wordlist: None,
+                    namespace: None,
                 },
             );
             assert!(result3.is_ok());
@@ -198,6 +259,8 @@ mod tests {
                 address: id,
                 cred_id: cred_id.clone(),
                 public_key: public_key.clone(),
+                wordlist: None,
+                namespace: None,
             },
         );
         assert!(result4.is_err());
@@ -217,6 +280,8 @@ mod tests {
                 address: id,
                 cred_id: cred_id.clone(),
                 public_key: public_key.clone(),
+                wordlist: None,
+                namespace: None,
             },
         );
         assert!(result.is_ok());
@@ -234,6 +299,8 @@ mod tests {
                 address: id,
                 cred_id: cred_id.clone(),
                 public_key: public_key.clone(),
+                wordlist: None,
+                namespace: None,
             },
         );
         assert!(result.is_ok());
@@ -251,6 +318,8 @@ mod tests {
                 address: id,
                 cred_id,
                 public_key,
+                wordlist: None,
+                namespace: None,
             },
         );
         assert!(result.is_ok());
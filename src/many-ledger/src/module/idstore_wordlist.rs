@@ -0,0 +1,341 @@
+use crate::migration::idstore_wordlists::WordlistPolicy;
+use many_error::ManyError;
+use many_modules::idstore;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::Arc;
+
+/// The number of words of a wordlist. Each word of a recall phrase encodes
+/// 11 bits.
+pub const WORDLIST_LENGTH: usize = 2048;
+
+/// The wordlist used when none is given, which recall phrases were always
+/// generated from before wordlists were configurable.
+pub const DEFAULT_WORDLIST: &str = "english";
+
+/// The languages of the BIP-39 wordlists.
+#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+pub enum Bip39Language {
+    English,
+    French,
+    Italian,
+    Japanese,
+    Korean,
+    Spanish,
+    ChineseSimplified,
+    ChineseTraditional,
+}
+
+impl Bip39Language {
+    pub const ALL: [Self; 8] = [
+        Self::English,
+        Self::French,
+        Self::Italian,
+        Self::Japanese,
+        Self::Korean,
+        Self::Spanish,
+        Self::ChineseSimplified,
+        Self::ChineseTraditional,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::English => "english",
+            Self::French => "french",
+            Self::Italian => "italian",
+            Self::Japanese => "japanese",
+            Self::Korean => "korean",
+            Self::Spanish => "spanish",
+            Self::ChineseSimplified => "chinese-simplified",
+            Self::ChineseTraditional => "chinese-traditional",
+        }
+    }
+}
+
+/// A list of words recall phrases are made of.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Wordlist {
+    Bip39(Bip39Language),
+
+    /// A list of [WORDLIST_LENGTH] distinct words.
+    Custom(Arc<Vec<String>>),
+}
+
+impl Wordlist {
+    /// A custom wordlist. The words must be distinct, and must not be empty or
+    /// contain whitespace.
+    pub fn custom(words: Vec<String>) -> Result<Self, String> {
+        if words.len() != WORDLIST_LENGTH {
+            return Err(format!(
+                "A wordlist must have {WORDLIST_LENGTH} words, found {}.",
+                words.len()
+            ));
+        }
+        if let Some(word) = words
+            .iter()
+            .find(|w| w.is_empty() || w.contains(char::is_whitespace))
+        {
+            return Err(format!("Invalid word {word:?}."));
+        }
+        if words.iter().collect::<BTreeSet<_>>().len() != words.len() {
+            return Err("The words of a wordlist must be distinct.".to_string());
+        }
+        Ok(Self::Custom(Arc::new(words)))
+    }
+
+    /// Read a custom wordlist from a file with one word per line.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::custom(
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(String::from)
+                .collect(),
+        )
+    }
+
+    /// The hexadecimal SHA-256 hash of the words of a custom wordlist, each
+    /// followed by a newline, which identifies it in the migration config.
+    /// `None` for BIP-39 wordlists.
+    pub fn hash(&self) -> Option<String> {
+        match self {
+            Self::Bip39(_) => None,
+            Self::Custom(words) => {
+                let mut hasher = Sha256::new();
+                for word in words.iter() {
+                    hasher.update(word.as_bytes());
+                    hasher.update(b"\n");
+                }
+                Some(hex::encode(hasher.finalize()))
+            }
+        }
+    }
+
+    /// Return the recall phrase of a seed.
+    ///
+    /// The following relation need to hold for having a valid decoding/encoding:
+    ///
+    ///     // length_bytes(data) * 8 + checksum = number_of(words) * 11
+    ///
+    /// As in BIP-39, the bits of the phrase are those of the seed followed by
+    /// the first bits of its SHA-256 hash. See
+    /// [bip39-dict](https://github.com/vincenthz/bip39-dict) for details.
+    ///
+    /// # Generic Arguments
+    ///
+    /// * `W` - Word count
+    /// * `FB` - Full Bytes
+    /// * `CS` - Checksum Bits
+    pub fn recall_phrase<const W: usize, const FB: usize, const CS: usize>(
+        &self,
+        seed: &[u8],
+    ) -> Result<Vec<String>, ManyError> {
+        let words = match self {
+            Self::Bip39(language) => {
+                let entropy = bip39_dict::Entropy::<FB>::from_slice(seed)
+                    .ok_or_else(|| ManyError::unknown("Unable to generate entropy"))?;
+                let mnemonic = entropy.to_mnemonics::<W, CS>().unwrap();
+                let dictionary = match language {
+                    Bip39Language::English => &bip39_dict::ENGLISH,
+                    Bip39Language::French => &bip39_dict::FRENCH,
+                    Bip39Language::Italian => &bip39_dict::ITALIAN,
+                    Bip39Language::Japanese => &bip39_dict::JAPANESE,
+                    Bip39Language::Korean => &bip39_dict::KOREAN,
+                    Bip39Language::Spanish => &bip39_dict::SPANISH,
+                    Bip39Language::ChineseSimplified => &bip39_dict::CHINESE_SIMPLIFIED,
+                    Bip39Language::ChineseTraditional => &bip39_dict::CHINESE_TRADITIONAL,
+                };
+                mnemonic
+                    .to_string(dictionary)
+                    .split_whitespace()
+                    .map(|e| e.to_string())
+                    .collect()
+            }
+            Self::Custom(words) => {
+                if seed.len() != FB || FB * 8 + CS != W * 11 {
+                    return Err(ManyError::unknown("Unable to generate entropy"));
+                }
+                let checksum = Sha256::digest(seed);
+                let bit = |i: usize| {
+                    let byte = seed.get(i / 8).unwrap_or_else(|| &checksum[i / 8 - FB]);
+                    usize::from((byte >> (7 - i % 8)) & 1)
+                };
+                (0..W)
+                    .map(|w| (0..11).fold(0, |index, b| index << 1 | bit(w * 11 + b)))
+                    .map(|index| words[index].clone())
+                    .collect()
+            }
+        };
+        Ok(words)
+    }
+}
+
+/// The wordlists recall phrases can be generated from, by name. All BIP-39
+/// languages are available, and custom wordlists can be added.
+///
+/// All the nodes of a network must use the same wordlists, or they would
+/// generate different recall phrases, which [Wordlists::check] verifies
+/// against the migration config.
+#[derive(Clone, Debug)]
+pub struct Wordlists {
+    wordlists: BTreeMap<String, Wordlist>,
+}
+
+impl Default for Wordlists {
+    fn default() -> Self {
+        Self {
+            wordlists: Bip39Language::ALL
+                .into_iter()
+                .map(|l| (l.name().to_string(), Wordlist::Bip39(l)))
+                .collect(),
+        }
+    }
+}
+
+impl Wordlists {
+    /// Add a wordlist, replacing the one with the same name if any.
+    pub fn insert(&mut self, name: impl Into<String>, wordlist: Wordlist) {
+        self.wordlists.insert(name.into(), wordlist);
+    }
+
+    /// The wordlist with this name.
+    pub fn get(&self, name: &str) -> Result<&Wordlist, ManyError> {
+        self.wordlists
+            .get(name)
+            .ok_or_else(|| idstore::unknown_wordlist(name))
+    }
+
+    /// Verify these are the wordlists of the network: its default wordlist
+    /// exists, and the custom wordlists are exactly those of the policy, with
+    /// the same words.
+    pub fn check(&self, policy: &WordlistPolicy) -> Result<(), ManyError> {
+        self.get(&policy.default)?;
+        for (name, wordlist) in &self.wordlists {
+            let Some(hash) = wordlist.hash() else {
+                continue;
+            };
+            if policy.custom.get(name) != Some(&hash) {
+                return Err(ManyError::unknown(format!(
+                    "The custom wordlist {name} is not part of the migration config, or its words differ."
+                )));
+            }
+        }
+        if let Some(name) = policy.custom.keys().find(|n| self.get(n).is_err()) {
+            return Err(idstore::unknown_wordlist(name));
+        }
+        Ok(())
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.wordlists.keys().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom() -> Wordlist {
+        Wordlist::custom((0..WORDLIST_LENGTH).map(|i| format!("w{i}")).collect()).unwrap()
+    }
+
+    #[test]
+    fn custom_wordlist() {
+        assert!(Wordlist::custom(vec!["foo".to_string(); WORDLIST_LENGTH]).is_err());
+        assert!(Wordlist::custom(vec!["foo".to_string()]).is_err());
+
+        // The first 11 bits of 0x0000 are 0, the next 5 bits of the seed and
+        // first 6 bits of its hash are the second word.
+        let phrase = custom().recall_phrase::<2, 2, 6>(&[0, 0]).unwrap();
+        let checksum = Sha256::digest([0u8, 0]);
+        assert_eq!(phrase, ["w0".to_string(), format!("w{}", checksum[0] >> 2)]);
+
+        // Deterministic and distinct for distinct seeds.
+        assert_eq!(custom().recall_phrase::<2, 2, 6>(&[0, 0]).unwrap(), phrase);
+        assert_ne!(custom().recall_phrase::<2, 2, 6>(&[0, 1]).unwrap(), phrase);
+        assert_eq!(
+            custom()
+                .recall_phrase::<5, 6, 7>(&[1, 2, 3, 4, 5, 6])
+                .unwrap()
+                .len(),
+            5
+        );
+        assert!(custom().recall_phrase::<2, 2, 6>(&[0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn bip39_wordlists() {
+        let seed = 0x1234u16.to_be_bytes();
+        let english = Wordlist::Bip39(Bip39Language::English)
+            .recall_phrase::<2, 2, 6>(&seed)
+            .unwrap();
+        assert_eq!(english.len(), 2);
+        for language in Bip39Language::ALL {
+            let phrase = Wordlist::Bip39(language)
+                .recall_phrase::<2, 2, 6>(&seed)
+                .unwrap();
+            assert_eq!(phrase.len(), 2, "{}", language.name());
+            if language != Bip39Language::English {
+                assert_ne!(phrase, english, "{}", language.name());
+            }
+        }
+    }
+
+    #[test]
+    fn wordlists() {
+        let mut wordlists = Wordlists::default();
+        assert_eq!(
+            wordlists.get(DEFAULT_WORDLIST).unwrap(),
+            &Wordlist::Bip39(Bip39Language::English)
+        );
+
+        wordlists.insert("custom", custom());
+        assert_eq!(wordlists.get("custom").unwrap(), &custom());
+        assert_eq!(
+            wordlists.get("french").unwrap(),
+            &Wordlist::Bip39(Bip39Language::French)
+        );
+        assert_eq!(
+            wordlists.get("klingon").unwrap_err().code(),
+            idstore::unknown_wordlist("").code()
+        );
+    }
+
+    #[test]
+    fn check() {
+        let hash = custom().hash().unwrap();
+        assert_eq!(Wordlist::Bip39(Bip39Language::English).hash(), None);
+
+        let mut policy = WordlistPolicy {
+            default: "custom".to_string(),
+            custom: BTreeMap::from([("custom".to_string(), hash)]),
+        };
+        let mut wordlists = Wordlists::default();
+        // The custom wordlist of the network is missing.
+        assert!(wordlists.check(&policy).is_err());
+
+        wordlists.insert("custom", custom());
+        wordlists.check(&policy).unwrap();
+
+        // Different words.
+        let mut words = (0..WORDLIST_LENGTH)
+            .map(|i| format!("w{i}"))
+            .collect::<Vec<_>>();
+        words.swap(0, 1);
+        let mut other = wordlists.clone();
+        other.insert("custom", Wordlist::custom(words).unwrap());
+        assert!(other.check(&policy).is_err());
+
+        // Not part of the network.
+        policy.custom.clear();
+        policy.default = DEFAULT_WORDLIST.to_string();
+        assert!(wordlists.check(&policy).is_err());
+        assert!(Wordlists::default().check(&policy).is_ok());
+
+        policy.default = "klingon".to_string();
+        assert!(Wordlists::default().check(&policy).is_err());
+    }
+}
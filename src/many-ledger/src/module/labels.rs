@@ -0,0 +1,88 @@
+use crate::error;
+use crate::migration::labels::ADDRESS_LABELS_MIGRATION;
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::labels::{
+    address_already_labeled, address_not_labeled, label_not_found, label_taken, validate_label,
+    ClaimArgs, ClaimReturns, Label, LabelsModuleBackend, LookupArgs, LookupReturns, ReleaseArgs,
+    ReleaseReturns, ResolveArgs, ResolveReturns,
+};
+use many_modules::EmptyReturn;
+
+impl LedgerModuleImpl {
+    fn check_labels_enabled(&self, method: &str) -> Result<(), ManyError> {
+        if self
+            .storage
+            .migrations()
+            .is_active(&ADDRESS_LABELS_MIGRATION)
+        {
+            Ok(())
+        } else {
+            Err(ManyError::invalid_method_name(method))
+        }
+    }
+}
+
+impl LabelsModuleBackend for LedgerModuleImpl {
+    fn claim(&mut self, sender: &Address, args: ClaimArgs) -> Result<ClaimReturns, ManyError> {
+        self.check_labels_enabled("labels.claim")?;
+        let ClaimArgs { label, memo } = args;
+
+        self.storage.check_memo(memo.as_ref())?;
+        if sender.is_anonymous() || sender.is_illegal() {
+            return Err(error::unauthorized());
+        }
+        validate_label(&label)?;
+        if self.storage.get_label(&label)?.is_some() {
+            return Err(label_taken(label));
+        }
+        if self.storage.get_address_label(sender)?.is_some() {
+            return Err(address_already_labeled(sender));
+        }
+
+        let (symbol, deposit) = self.storage.label_deposit()?;
+        self.storage.check_not_frozen([sender])?;
+        self.storage.claim_label(
+            Label {
+                label,
+                owner: *sender,
+                symbol,
+                deposit,
+            },
+            memo,
+        )?;
+        Ok(EmptyReturn)
+    }
+
+    fn release(
+        &mut self,
+        sender: &Address,
+        args: ReleaseArgs,
+    ) -> Result<ReleaseReturns, ManyError> {
+        self.check_labels_enabled("labels.release")?;
+        self.storage.check_memo(args.memo.as_ref())?;
+        let label = self
+            .storage
+            .get_address_label(sender)?
+            .ok_or_else(|| address_not_labeled(sender))?;
+
+        self.storage.release_label(label, args.memo)?;
+        Ok(EmptyReturn)
+    }
+
+    fn resolve(&self, _sender: &Address, args: ResolveArgs) -> Result<ResolveReturns, ManyError> {
+        self.check_labels_enabled("labels.resolve")?;
+        self.storage
+            .get_label(&args.label)?
+            .map(|label| ResolveReturns { label })
+            .ok_or_else(|| label_not_found(args.label))
+    }
+
+    fn lookup(&self, _sender: &Address, args: LookupArgs) -> Result<LookupReturns, ManyError> {
+        self.check_labels_enabled("labels.lookup")?;
+        Ok(LookupReturns {
+            label: self.storage.get_address_label(&args.address)?,
+        })
+    }
+}
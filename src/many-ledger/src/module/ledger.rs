@@ -35,6 +35,7 @@ impl ledger::LedgerModuleBackend for LedgerModuleImpl {
             hash: hash.into(),
             local_names: symbols,
             tokens: storage.get_token_info_summary()?,
+            memo_limits: Some(storage.memo_limits()?),
         })
     }
 
@@ -6,6 +6,7 @@ use many_identity::Address;
 use many_modules::account::features::TryCreateFeature;
 use many_modules::account::Role;
 use many_modules::{account, ledger, EmptyReturn};
+use many_types::ledger::TokenAmount;
 
 impl ledger::LedgerCommandsModuleBackend for LedgerModuleImpl {
     fn send(&mut self, sender: &Address, args: ledger::SendArgs) -> Result<EmptyReturn, ManyError> {
@@ -17,6 +18,17 @@ impl ledger::LedgerCommandsModuleBackend for LedgerModuleImpl {
             memo,
         } = args;
 
+        self.storage.check_memo(memo.as_ref())?;
+
+        // A delegate may only send up to the amount its certificates allow.
+        if let Some(max_amount) = many_protocol::context::current_delegated_max_amount(&symbol) {
+            if amount > TokenAmount::from(max_amount) {
+                return Err(ManyError::invalid_delegation(format!(
+                    "amount exceeds the delegated maximum of {max_amount}"
+                )));
+            }
+        }
+
         let from = from.as_ref().unwrap_or(sender);
         // We check here to make sure there isn't a code path that might ends up here without
         // proper validation (e.g. multisig or delayed execution). This should normally
@@ -31,6 +43,8 @@ impl ledger::LedgerCommandsModuleBackend for LedgerModuleImpl {
                 .get_account(from)
                 .map_err(|_| error::unauthorized())?;
             verify_account_role(
+                &self.storage,
+                from,
                 &account,
                 sender,
                 account::features::ledger::AccountLedger::ID,
@@ -39,8 +53,10 @@ impl ledger::LedgerCommandsModuleBackend for LedgerModuleImpl {
             keys_to_prove.extend(keys);
         }
 
-        self.storage
-            .send(from, &to, &symbol, amount, memo)
-            .map(|_| EmptyReturn)
+        self.storage.check_not_frozen([sender, from, &to])?;
+        let screening = self.storage.screen_transfer(sender, from, &to)?;
+        self.storage.send(from, &to, &symbol, amount, memo)?;
+        self.storage.log_screening(screening)?;
+        Ok(EmptyReturn)
     }
 }
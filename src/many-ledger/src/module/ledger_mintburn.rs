@@ -46,21 +46,31 @@ impl ledger::LedgerMintBurnModuleBackend for LedgerModuleImpl {
             memo,
         } = args;
 
+        self.storage.check_memo(memo.as_ref())?;
         self.verify_mint_burn_identity(sender, &symbol)?;
 
         check_symbol_exists(&symbol, self.storage.get_symbols()?)?;
+        self.storage.check_not_frozen(distribution.keys())?;
+
+        // Minting is screened as a transfer from the token to each holder.
+        let screenings = distribution
+            .keys()
+            .map(|to| self.storage.screen_transfer(sender, &symbol, to))
+            .collect::<Result<Vec<_>, _>>()?;
 
         // Mint into storage
         let _ = self.storage.mint_token(symbol, &distribution)?;
 
         // Log event
-        self.storage
-            .log_event(EventInfo::TokenMint {
-                symbol,
-                distribution,
-                memo,
-            })
-            .map(|_| TokenMintReturns {})
+        self.storage.log_event(EventInfo::TokenMint {
+            symbol,
+            distribution,
+            memo,
+        })?;
+        for screening in screenings {
+            self.storage.log_screening(screening)?;
+        }
+        Ok(TokenMintReturns {})
     }
 
     fn burn(
@@ -79,6 +89,7 @@ impl ledger::LedgerMintBurnModuleBackend for LedgerModuleImpl {
             error_on_under_burn,
         } = args;
 
+        self.storage.check_memo(memo.as_ref())?;
         self.verify_mint_burn_identity(sender, &symbol)?;
 
         check_symbol_exists(&symbol, self.storage.get_symbols()?)?;
@@ -90,17 +101,25 @@ impl ledger::LedgerMintBurnModuleBackend for LedgerModuleImpl {
             }
         }
 
+        // Burning is screened as a transfer from each holder to the token.
+        let screenings = distribution
+            .keys()
+            .map(|from| self.storage.screen_transfer(sender, from, &symbol))
+            .collect::<Result<Vec<_>, _>>()?;
+
         // Burn from storage
         let _ = self.storage.burn_token(symbol, &distribution)?;
 
         // Log event
-        self.storage
-            .log_event(EventInfo::TokenBurn {
-                symbol,
-                distribution: distribution.clone(),
-                memo,
-            })
-            .map(|_| TokenBurnReturns { distribution })
+        self.storage.log_event(EventInfo::TokenBurn {
+            symbol,
+            distribution: distribution.clone(),
+            memo,
+        })?;
+        for screening in screenings {
+            self.storage.log_screening(screening)?;
+        }
+        Ok(TokenBurnReturns { distribution })
     }
 }
 
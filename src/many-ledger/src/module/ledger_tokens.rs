@@ -1,7 +1,9 @@
 use crate::error;
 use crate::migration::disable_token_create::DISABLE_TOKEN_CREATE_MIGRATION;
 use crate::migration::token_create::TOKEN_CREATE_MIGRATION;
+use crate::migration::token_ownership_offers::TOKEN_OWNERSHIP_OFFERS_MIGRATION;
 use crate::migration::tokens::TOKEN_MIGRATION;
+use crate::migration::visual_logo::VISUAL_LOGO_MIGRATION;
 use crate::module::LedgerModuleImpl;
 use crate::storage::account::verify_acl;
 use many_error::ManyError;
@@ -9,9 +11,12 @@ use many_identity::Address;
 use many_modules::account::features::tokens::TokenAccountLedger;
 use many_modules::account::features::TryCreateFeature;
 use many_modules::account::Role;
+use many_modules::ledger::extended_info::TokenExtendedInfo;
 use many_modules::ledger::{
-    LedgerTokensModuleBackend, TokenAddExtendedInfoArgs, TokenAddExtendedInfoReturns,
-    TokenCreateArgs, TokenCreateReturns, TokenInfoArgs, TokenInfoReturns,
+    LedgerTokensModuleBackend, TokenAcceptOwnershipArgs, TokenAcceptOwnershipReturns,
+    TokenAddExtendedInfoArgs, TokenAddExtendedInfoReturns, TokenCancelOwnershipOfferArgs,
+    TokenCancelOwnershipOfferReturns, TokenCreateArgs, TokenCreateReturns, TokenInfoArgs,
+    TokenInfoReturns, TokenOwnershipOffersArgs, TokenOwnershipOffersReturns,
     TokenRemoveExtendedInfoArgs, TokenRemoveExtendedInfoReturns, TokenUpdateArgs,
     TokenUpdateReturns,
 };
@@ -24,6 +29,26 @@ fn check_ticker_length(ticker: &String) -> Result<(), ManyError> {
     Ok(())
 }
 
+impl LedgerModuleImpl {
+    /// Logos were accepted without limits before the visual logo migration.
+    fn check_visual_logo(&self, extended_info: &TokenExtendedInfo) -> Result<(), ManyError> {
+        if !self.storage.migrations().is_active(&VISUAL_LOGO_MIGRATION) {
+            return Ok(());
+        }
+        if let Some(logo) = extended_info.visual_logo() {
+            logo.check().map_err(error::invalid_visual_logo)?;
+        }
+        Ok(())
+    }
+}
+
+fn check_attachments(extended_info: &TokenExtendedInfo) -> Result<(), ManyError> {
+    if let Some(attachments) = extended_info.attachments() {
+        attachments.check().map_err(error::invalid_attachment)?;
+    }
+    Ok(())
+}
+
 impl LedgerTokensModuleBackend for LedgerModuleImpl {
     fn create(
         &mut self,
@@ -64,9 +89,17 @@ impl LedgerTokensModuleBackend for LedgerModuleImpl {
             )?;
         }
 
+        self.storage.check_memo(args.memo.as_ref())?;
+
         let ticker = &args.summary.ticker;
         check_ticker_length(ticker)?;
 
+        if let Some(extended_info) = &args.extended_info {
+            self.check_visual_logo(extended_info)?;
+            check_attachments(extended_info)?;
+            self.storage.check_extended_info(extended_info)?;
+        }
+
         if self
             .storage
             .get_symbols_and_tickers()?
@@ -107,7 +140,7 @@ impl LedgerTokensModuleBackend for LedgerModuleImpl {
 
         // Get the current owner and check if we're allowed to update this token
         let (current_owner, _) = self.storage.get_owner(&args.symbol)?;
-        match current_owner {
+        let current_owner = match current_owner {
             Some(addr) => {
                 let _ = verify_acl(
                     &self.storage,
@@ -116,13 +149,14 @@ impl LedgerTokensModuleBackend for LedgerModuleImpl {
                     [Role::CanTokensUpdate],
                     TokenAccountLedger::ID,
                 )?;
+                addr
             }
             None => {
                 return Err(ManyError::unknown(
                     "Unable to update, this token is immutable",
                 ))
             }
-        }
+        };
 
         // Check the memory symbol cache for requested symbol
         let symbol = &args.symbol;
@@ -132,10 +166,38 @@ impl LedgerTokensModuleBackend for LedgerModuleImpl {
             )));
         }
 
+        self.storage.check_memo(args.memo.as_ref())?;
+
         if let Some(ticker) = &args.ticker {
             check_ticker_length(ticker)?;
         }
 
+        // Transferring the ownership to another address only offers it, the
+        // new owner needs to accept it. Renouncing it is immediate.
+        if self
+            .storage
+            .migrations()
+            .is_active(&TOKEN_OWNERSHIP_OFFERS_MIGRATION)
+        {
+            if let Some(Either::Left(new_owner)) = args.owner {
+                if new_owner != current_owner {
+                    let mut args = args;
+                    args.owner = None;
+                    let memo = args.memo.clone();
+                    if args.name.is_some() || args.ticker.is_some() || args.decimals.is_some() {
+                        self.storage.update_token(sender, args.clone())?;
+                    }
+                    self.storage.offer_token_ownership(
+                        args.symbol,
+                        current_owner,
+                        new_owner,
+                        memo,
+                    )?;
+                    return Ok(TokenUpdateReturns {});
+                }
+            }
+        }
+
         let (result, _) = self.storage.update_token(sender, args)?;
         Ok(result)
     }
@@ -167,6 +229,11 @@ impl LedgerTokensModuleBackend for LedgerModuleImpl {
             }
         }
 
+        self.storage.check_memo(args.memo.as_ref())?;
+        self.check_visual_logo(&args.extended_info)?;
+        check_attachments(&args.extended_info)?;
+        self.storage.check_extended_info(&args.extended_info)?;
+
         let (result, _) = self.storage.add_extended_info(args)?;
         Ok(result)
     }
@@ -198,7 +265,100 @@ impl LedgerTokensModuleBackend for LedgerModuleImpl {
             }
         }
 
+        self.storage.check_memo(args.memo.as_ref())?;
+
         let (result, _) = self.storage.remove_extended_info(args)?;
         Ok(result)
     }
+
+    fn accept_ownership(
+        &mut self,
+        sender: &Address,
+        args: TokenAcceptOwnershipArgs,
+    ) -> Result<TokenAcceptOwnershipReturns, ManyError> {
+        if !self
+            .storage
+            .migrations()
+            .is_active(&TOKEN_OWNERSHIP_OFFERS_MIGRATION)
+        {
+            return Err(ManyError::invalid_method_name("tokens.acceptOwnership"));
+        }
+
+        let offer = self
+            .storage
+            .get_ownership_offer(&args.symbol)?
+            .ok_or_else(|| error::ownership_offer_not_found(args.symbol))?;
+        verify_acl(
+            &self.storage,
+            sender,
+            &offer.new_owner,
+            [Role::CanTokensUpdate],
+            TokenAccountLedger::ID,
+        )?;
+
+        self.storage.check_memo(args.memo.as_ref())?;
+
+        self.storage
+            .accept_token_ownership(args.symbol, args.memo)?;
+        Ok(TokenAcceptOwnershipReturns {})
+    }
+
+    fn cancel_ownership_offer(
+        &mut self,
+        sender: &Address,
+        args: TokenCancelOwnershipOfferArgs,
+    ) -> Result<TokenCancelOwnershipOfferReturns, ManyError> {
+        if !self
+            .storage
+            .migrations()
+            .is_active(&TOKEN_OWNERSHIP_OFFERS_MIGRATION)
+        {
+            return Err(ManyError::invalid_method_name(
+                "tokens.cancelOwnershipOffer",
+            ));
+        }
+
+        let (current_owner, _) = self.storage.get_owner(&args.symbol)?;
+        match current_owner {
+            Some(addr) => {
+                verify_acl(
+                    &self.storage,
+                    sender,
+                    &addr,
+                    [Role::CanTokensUpdate],
+                    TokenAccountLedger::ID,
+                )?;
+            }
+            None => {
+                return Err(ManyError::unknown(
+                    "Unable to update, this token is immutable",
+                ))
+            }
+        }
+
+        self.storage.check_memo(args.memo.as_ref())?;
+
+        self.storage
+            .cancel_token_ownership_offer(args.symbol, args.memo)?;
+        Ok(TokenCancelOwnershipOfferReturns {})
+    }
+
+    fn ownership_offers(
+        &self,
+        _sender: &Address,
+        args: TokenOwnershipOffersArgs,
+    ) -> Result<TokenOwnershipOffersReturns, ManyError> {
+        if !self
+            .storage
+            .migrations()
+            .is_active(&TOKEN_OWNERSHIP_OFFERS_MIGRATION)
+        {
+            return Err(ManyError::invalid_method_name("tokens.ownershipOffers"));
+        }
+
+        let offers = self
+            .storage
+            .token_ownership_offers(args.symbol.as_ref(), args.new_owner.as_ref())?;
+        Ok(TokenOwnershipOffersReturns { offers })
+    }
 }
@@ -1,7 +1,9 @@
+use crate::migration::legacy_memo::LEGACY_MEMO_MIGRATION;
 use crate::module::LedgerModuleImpl;
 use many_error::ManyError;
 use many_identity::Address;
 use many_modules::account::features::multisig;
+use many_modules::events::AccountMultisigTransaction;
 use many_modules::EmptyReturn;
 use many_protocol::ResponseMessage;
 use minicbor::bytes::ByteVec;
@@ -12,6 +14,21 @@ impl multisig::AccountMultisigModuleBackend for LedgerModuleImpl {
         sender: &Address,
         arg: multisig::SubmitTransactionArgs,
     ) -> Result<multisig::SubmitTransactionReturn, ManyError> {
+        self.storage.check_memo(arg.memo.as_ref())?;
+
+        // Check the memos of the transaction, and of the transactions nested
+        // in it.
+        let mut transaction = arg.transaction.as_ref();
+        loop {
+            self.storage.check_memo(transaction.memo())?;
+            match transaction {
+                AccountMultisigTransaction::AccountMultisigSubmit(args) => {
+                    transaction = args.transaction.as_ref();
+                }
+                _ => break,
+            }
+        }
+
         let token = self.storage.create_multisig_transaction(sender, arg)?;
         Ok(multisig::SubmitTransactionReturn {
             token: ByteVec::from(token),
@@ -23,8 +40,12 @@ impl multisig::AccountMultisigModuleBackend for LedgerModuleImpl {
         _sender: &Address,
         args: multisig::InfoArgs,
     ) -> Result<multisig::InfoReturn, ManyError> {
-        let info = self.storage.get_multisig_info(&args.token)?;
-        Ok(info.info)
+        let info = self.storage.get_multisig_info(&args.token)?.info;
+        if self.storage.migrations().is_active(&LEGACY_MEMO_MIGRATION) {
+            info.without_legacy_memo()
+        } else {
+            Ok(info)
+        }
     }
 
     fn multisig_set_defaults(
@@ -43,7 +64,7 @@ impl multisig::AccountMultisigModuleBackend for LedgerModuleImpl {
         args: multisig::ApproveArgs,
     ) -> Result<EmptyReturn, ManyError> {
         self.storage
-            .approve_multisig(sender, args.token.as_slice())
+            .approve_multisig(sender, args.token.as_slice(), args.approve_as)
             .map(|_| EmptyReturn)
     }
 
@@ -53,7 +74,7 @@ impl multisig::AccountMultisigModuleBackend for LedgerModuleImpl {
         args: multisig::RevokeArgs,
     ) -> Result<EmptyReturn, ManyError> {
         self.storage
-            .revoke_multisig(sender, args.token.as_slice())
+            .revoke_multisig(sender, args.token.as_slice(), args.revoke_as)
             .map(|_| EmptyReturn)
     }
 
@@ -74,4 +95,66 @@ impl multisig::AccountMultisigModuleBackend for LedgerModuleImpl {
             .withdraw_multisig(sender, args.token.as_slice())
             .map(|_| EmptyReturn)
     }
+
+    fn multisig_result(
+        &self,
+        sender: &Address,
+        args: multisig::ResultArgs,
+    ) -> Result<multisig::ResultReturn, ManyError> {
+        self.storage
+            .get_multisig_result(sender, args.token.as_slice())
+    }
+
+    fn multisig_register_notification(
+        &mut self,
+        sender: &Address,
+        args: multisig::RegisterNotificationArgs,
+    ) -> Result<EmptyReturn, ManyError> {
+        self.storage
+            .register_multisig_notification(sender, args)
+            .map(|_| EmptyReturn)
+    }
+
+    fn multisig_provide_notification_endpoint(
+        &self,
+        sender: &Address,
+        args: multisig::ProvideNotificationEndpointArgs,
+    ) -> Result<EmptyReturn, ManyError> {
+        self.storage
+            .provide_multisig_notification_endpoint(sender, args)
+            .map(|_| EmptyReturn)
+    }
+
+    fn multisig_unregister_notification(
+        &mut self,
+        sender: &Address,
+        args: multisig::UnregisterNotificationArgs,
+    ) -> Result<EmptyReturn, ManyError> {
+        self.storage
+            .unregister_multisig_notification(sender, args)
+            .map(|_| EmptyReturn)
+    }
+
+    fn multisig_list_notifications(
+        &self,
+        _sender: &Address,
+        args: multisig::ListNotificationsArgs,
+    ) -> Result<multisig::ListNotificationsReturn, ManyError> {
+        Ok(multisig::ListNotificationsReturn {
+            registrations: self.storage.list_multisig_notifications(&args.account)?,
+        })
+    }
+
+    fn multisig_list_pending(
+        &self,
+        _sender: &Address,
+        args: multisig::ListPendingArgs,
+    ) -> Result<multisig::ListPendingReturn, ManyError> {
+        self.storage.get_account(&args.account)?;
+        Ok(multisig::ListPendingReturn {
+            transactions: self
+                .storage
+                .list_pending_multisig_transactions(&args.account)?,
+        })
+    }
 }
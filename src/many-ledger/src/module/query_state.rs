@@ -0,0 +1,51 @@
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use many_modules::abci_backend::ManyAbciModuleBackend;
+use many_server::server::{QueryState, QueryStateCoordinator};
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+
+impl LedgerModuleImpl {
+    /// The height of the latest block and the hash of the ledger state.
+    pub fn query_state(&self) -> Result<QueryState, ManyError> {
+        Ok(QueryState {
+            height: self.storage.get_height()?,
+            hash: self.storage.hash(),
+        })
+    }
+}
+
+/// Lets multi-queries call the ledger endpoints which are not commands, and
+/// execute them against the same ledger state.
+pub struct LedgerQueryState {
+    module_impl: Arc<Mutex<LedgerModuleImpl>>,
+    queries: BTreeSet<String>,
+}
+
+impl LedgerQueryState {
+    pub fn new(module_impl: Arc<Mutex<LedgerModuleImpl>>) -> Result<Self, ManyError> {
+        let queries = module_impl
+            .lock()
+            .unwrap()
+            .init()?
+            .endpoints
+            .into_iter()
+            .filter(|(_, info)| !info.is_command)
+            .map(|(method, _)| method)
+            .collect();
+        Ok(Self {
+            module_impl,
+            queries,
+        })
+    }
+}
+
+impl QueryStateCoordinator for LedgerQueryState {
+    fn is_query(&self, method: &str) -> bool {
+        self.queries.contains(method)
+    }
+
+    fn state(&self) -> Result<QueryState, ManyError> {
+        self.module_impl.lock().unwrap().query_state()
+    }
+}
@@ -0,0 +1,31 @@
+use crate::module::LedgerModuleImpl;
+use coset::{CoseSign1Builder, TaggedCborSerializable};
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::ledger::{GetReceiptArgs, GetReceiptReturns, LedgerReceiptsModuleBackend};
+use minicbor::bytes::ByteVec;
+
+impl LedgerReceiptsModuleBackend for LedgerModuleImpl {
+    fn get_receipt(
+        &self,
+        _sender: &Address,
+        args: GetReceiptArgs,
+    ) -> Result<GetReceiptReturns, ManyError> {
+        let receipt = self.storage.get_receipt(args.id)?;
+
+        let envelope = self
+            .receipt_signer
+            .as_ref()
+            .map(|signer| {
+                let payload = minicbor::to_vec(&receipt).map_err(ManyError::serialization_error)?;
+                signer
+                    .sign_1(CoseSign1Builder::new().payload(payload).build())?
+                    .to_tagged_vec()
+                    .map(ByteVec::from)
+                    .map_err(ManyError::serialization_error)
+            })
+            .transpose()?;
+
+        Ok(GetReceiptReturns { receipt, envelope })
+    }
+}
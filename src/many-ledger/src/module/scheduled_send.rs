@@ -0,0 +1,180 @@
+use crate::error;
+use crate::migration::scheduled_send::SCHEDULED_SEND_MIGRATION;
+use crate::module::account::verify_account_role;
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::account::features::TryCreateFeature;
+use many_modules::account::Role;
+use many_modules::ledger::{
+    schedule_in_the_past, scheduled_send_not_found, CancelScheduledArgs, CancelScheduledReturns,
+    LedgerScheduleModuleBackend, ScheduleSendArgs, ScheduleSendReturns, ScheduledArgs,
+    ScheduledReturns, ScheduledSend,
+};
+use many_modules::{account, EmptyReturn};
+
+impl LedgerModuleImpl {
+    fn check_scheduled_send_enabled(&self, method: &str) -> Result<(), ManyError> {
+        if self
+            .storage
+            .migrations()
+            .is_active(&SCHEDULED_SEND_MIGRATION)
+        {
+            Ok(())
+        } else {
+            Err(ManyError::invalid_method_name(method))
+        }
+    }
+
+    /// The sender can send from an address if it is the address itself, or
+    /// it can transact on behalf of the account, like `ledger.send`.
+    fn verify_scheduled_sender(&self, sender: &Address, from: &Address) -> Result<(), ManyError> {
+        if from.is_illegal() {
+            return Err(error::unauthorized());
+        }
+        if from != sender {
+            let (account, _) = self
+                .storage
+                .get_account(from)
+                .map_err(|_| error::unauthorized())?;
+            verify_account_role(
+                &self.storage,
+                from,
+                &account,
+                sender,
+                account::features::ledger::AccountLedger::ID,
+                [Role::CanLedgerTransact],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Execute the scheduled sends which are due in the current block, up to
+    /// a limit per block. A send which fails is removed all the same, and its
+    /// error is logged.
+    pub(crate) fn execute_scheduled_sends(&mut self) -> Result<(), ManyError> {
+        if !self
+            .storage
+            .migrations()
+            .is_active(&SCHEDULED_SEND_MIGRATION)
+        {
+            return Ok(());
+        }
+
+        let height = self.storage.get_height()? + 1;
+        let time = self.storage.now();
+        for (id, send) in self.storage.get_due_scheduled_sends(height, time)? {
+            let error = self.execute_scheduled_send(&send).err();
+            self.storage.complete_scheduled_send(id, send, error)?;
+        }
+        Ok(())
+    }
+
+    /// The sender's permissions are verified again, as they might have
+    /// changed since the send was scheduled.
+    fn execute_scheduled_send(&mut self, send: &ScheduledSend) -> Result<(), ManyError> {
+        self.verify_scheduled_sender(&send.sender, &send.from)?;
+        self.storage
+            .check_not_frozen([&send.sender, &send.from, &send.to])?;
+        let screening = self
+            .storage
+            .screen_transfer(&send.sender, &send.from, &send.to)?;
+        self.storage.send(
+            &send.from,
+            &send.to,
+            &send.symbol,
+            send.amount.clone(),
+            send.memo.clone(),
+        )?;
+        self.storage.log_screening(screening)
+    }
+}
+
+impl LedgerScheduleModuleBackend for LedgerModuleImpl {
+    fn schedule_send(
+        &mut self,
+        sender: &Address,
+        args: ScheduleSendArgs,
+    ) -> Result<ScheduleSendReturns, ManyError> {
+        self.check_scheduled_send_enabled("ledger.scheduleSend")?;
+        let ScheduleSendArgs {
+            from,
+            to,
+            amount,
+            symbol,
+            at,
+            memo,
+        } = args;
+
+        self.storage.check_memo(memo.as_ref())?;
+
+        let from = from.unwrap_or(*sender);
+        self.verify_scheduled_sender(sender, &from)?;
+
+        // Refuse what the send would refuse anyway when executed.
+        if from == to {
+            return Err(error::destination_is_source());
+        }
+        if amount.is_zero() {
+            return Err(error::amount_is_zero());
+        }
+        if to.is_anonymous() || from.is_anonymous() {
+            return Err(error::anonymous_cannot_hold_funds());
+        }
+        let at = at.resolve(self.storage.now());
+        if at.is_due(self.storage.get_height()? + 1, self.storage.now()) {
+            return Err(schedule_in_the_past());
+        }
+        let (deposit_symbol, deposit) = self.storage.scheduled_send_deposit()?;
+
+        self.storage
+            .add_scheduled_send(ScheduledSend {
+                sender: *sender,
+                from,
+                to,
+                symbol,
+                amount,
+                at,
+                memo,
+                deposit_symbol,
+                deposit,
+            })
+            .map(|id| ScheduleSendReturns { id })
+    }
+
+    fn cancel_scheduled(
+        &mut self,
+        sender: &Address,
+        args: CancelScheduledArgs,
+    ) -> Result<CancelScheduledReturns, ManyError> {
+        self.check_scheduled_send_enabled("ledger.cancelScheduled")?;
+        let CancelScheduledArgs { id, memo } = args;
+
+        self.storage.check_memo(memo.as_ref())?;
+
+        let send = self
+            .storage
+            .get_scheduled_send(id)?
+            .ok_or_else(|| scheduled_send_not_found(id))?;
+        if *sender != send.sender {
+            self.verify_scheduled_sender(sender, &send.from)?;
+        }
+
+        self.storage
+            .cancel_scheduled_send(id, *sender, memo)
+            .map(|_| EmptyReturn)
+    }
+
+    fn scheduled(
+        &self,
+        _sender: &Address,
+        args: ScheduledArgs,
+    ) -> Result<ScheduledReturns, ManyError> {
+        self.check_scheduled_send_enabled("ledger.scheduled")?;
+        let mut sends = self.storage.get_scheduled_sends()?;
+        if let Some(from) = args.from {
+            sends.retain(|_, send| send.from == from);
+        }
+        Ok(ScheduledReturns { sends })
+    }
+}
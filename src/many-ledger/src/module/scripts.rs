@@ -0,0 +1,99 @@
+use crate::error;
+use crate::migration::scripts::SCRIPTS_MIGRATION;
+use crate::module::LedgerModuleImpl;
+use crate::storage::scripts::StoredScript;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::scripts::{
+    script_not_found, script_too_large, Script, ScriptDeployArgs, ScriptDeployReturns,
+    ScriptInfoArgs, ScriptInfoReturns, ScriptInvokeArgs, ScriptInvokeReturns, ScriptsModuleBackend,
+    SCRIPT_DEFAULT_FUEL, SCRIPT_MAX_FUEL, SCRIPT_MAX_SIZE,
+};
+use sha3::{Digest, Sha3_256};
+
+impl LedgerModuleImpl {
+    fn check_scripts_enabled(&self, method: &str) -> Result<(), ManyError> {
+        if self.storage.migrations().is_active(&SCRIPTS_MIGRATION) {
+            Ok(())
+        } else {
+            Err(ManyError::invalid_method_name(method))
+        }
+    }
+
+    fn get_script(&self, id: u64) -> Result<StoredScript, ManyError> {
+        self.storage
+            .get_script(id)?
+            .ok_or_else(|| script_not_found(id))
+    }
+}
+
+impl ScriptsModuleBackend for LedgerModuleImpl {
+    fn deploy(
+        &mut self,
+        sender: &Address,
+        args: ScriptDeployArgs,
+    ) -> Result<ScriptDeployReturns, ManyError> {
+        self.check_scripts_enabled("scripts.deploy")?;
+        let ScriptDeployArgs { code, memo } = args;
+
+        self.storage.check_memo(memo.as_ref())?;
+        if sender.is_anonymous() || sender.is_illegal() {
+            return Err(error::unauthorized());
+        }
+        if code.len() > SCRIPT_MAX_SIZE {
+            return Err(script_too_large(code.len(), SCRIPT_MAX_SIZE));
+        }
+        crate::scripts::validate(&code)?;
+
+        self.storage
+            .create_script(*sender, code.into(), memo)
+            .map(|id| ScriptDeployReturns { id })
+    }
+
+    fn invoke(
+        &mut self,
+        sender: &Address,
+        args: ScriptInvokeArgs,
+    ) -> Result<ScriptInvokeReturns, ManyError> {
+        self.check_scripts_enabled("scripts.invoke")?;
+        let ScriptInvokeArgs {
+            id,
+            function,
+            input,
+            fuel,
+        } = args;
+
+        let script = self.get_script(id)?;
+        let fuel = fuel.unwrap_or(SCRIPT_DEFAULT_FUEL).min(SCRIPT_MAX_FUEL);
+        let effects = crate::scripts::execute(
+            &self.storage,
+            id,
+            &script.code,
+            &function,
+            input.map(Into::into).unwrap_or_default(),
+            fuel,
+        )?;
+
+        self.storage.apply_script_effects(id, sender, &effects)?;
+        Ok(ScriptInvokeReturns {
+            output: effects.output.into(),
+            fuel_used: effects.fuel_used,
+        })
+    }
+
+    fn info(
+        &self,
+        _sender: &Address,
+        args: ScriptInfoArgs,
+    ) -> Result<ScriptInfoReturns, ManyError> {
+        self.check_scripts_enabled("scripts.info")?;
+        let StoredScript { owner, code } = self.get_script(args.id)?;
+        Ok(ScriptInfoReturns {
+            script: Script {
+                owner,
+                code_hash: Sha3_256::digest(&code).to_vec().into(),
+                size: code.len() as u64,
+            },
+        })
+    }
+}
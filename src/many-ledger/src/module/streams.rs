@@ -0,0 +1,136 @@
+use crate::error;
+use crate::migration::streams::STREAMS_MIGRATION;
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::ledger::{
+    stream_end_in_the_past, stream_not_found, stream_rate_is_zero, LedgerStreamsModuleBackend,
+    Stream, StreamCancelArgs, StreamCancelReturns, StreamCreateArgs, StreamCreateReturns,
+    StreamInfoArgs, StreamInfoReturns, StreamWithdrawArgs, StreamWithdrawReturns,
+};
+use many_types::ledger::TokenAmount;
+
+impl LedgerModuleImpl {
+    fn check_streams_enabled(&self, method: &str) -> Result<(), ManyError> {
+        if self.storage.migrations().is_active(&STREAMS_MIGRATION) {
+            Ok(())
+        } else {
+            Err(ManyError::invalid_method_name(method))
+        }
+    }
+
+    fn get_stream(&self, id: u64) -> Result<Stream, ManyError> {
+        self.storage
+            .get_stream(id)?
+            .ok_or_else(|| stream_not_found(id))
+    }
+}
+
+impl LedgerStreamsModuleBackend for LedgerModuleImpl {
+    fn stream_create(
+        &mut self,
+        sender: &Address,
+        args: StreamCreateArgs,
+    ) -> Result<StreamCreateReturns, ManyError> {
+        self.check_streams_enabled("ledger.streamCreate")?;
+        let StreamCreateArgs {
+            payee,
+            symbol,
+            rate,
+            end,
+            memo,
+        } = args;
+
+        self.storage.check_memo(memo.as_ref())?;
+
+        if sender.is_illegal() {
+            return Err(error::unauthorized());
+        }
+        if *sender == payee {
+            return Err(error::destination_is_source());
+        }
+        if sender.is_anonymous() || payee.is_anonymous() {
+            return Err(error::anonymous_cannot_hold_funds());
+        }
+        if rate.is_zero() {
+            return Err(stream_rate_is_zero());
+        }
+        let start = self.storage.now();
+        if end <= start {
+            return Err(stream_end_in_the_past());
+        }
+
+        self.storage.check_not_frozen([sender, &payee])?;
+        let screening = self.storage.screen_transfer(sender, sender, &payee)?;
+        let id = self.storage.create_stream(
+            Stream {
+                payer: *sender,
+                payee,
+                symbol,
+                rate,
+                start,
+                end,
+                withdrawn: TokenAmount::zero(),
+            },
+            memo,
+        )?;
+        self.storage.log_screening(screening)?;
+        Ok(StreamCreateReturns { id })
+    }
+
+    fn stream_withdraw(
+        &mut self,
+        sender: &Address,
+        args: StreamWithdrawArgs,
+    ) -> Result<StreamWithdrawReturns, ManyError> {
+        self.check_streams_enabled("ledger.streamWithdraw")?;
+        let StreamWithdrawArgs { id, memo } = args;
+
+        self.storage.check_memo(memo.as_ref())?;
+
+        let stream = self.get_stream(id)?;
+        if *sender != stream.payee {
+            return Err(error::unauthorized());
+        }
+
+        self.storage.check_not_frozen([&stream.payee])?;
+        self.storage
+            .withdraw_stream(id, stream, memo)
+            .map(|amount| StreamWithdrawReturns { amount })
+    }
+
+    fn stream_cancel(
+        &mut self,
+        sender: &Address,
+        args: StreamCancelArgs,
+    ) -> Result<StreamCancelReturns, ManyError> {
+        self.check_streams_enabled("ledger.streamCancel")?;
+        let StreamCancelArgs { id, memo } = args;
+
+        self.storage.check_memo(memo.as_ref())?;
+
+        let stream = self.get_stream(id)?;
+        if *sender != stream.payer && *sender != stream.payee {
+            return Err(error::unauthorized());
+        }
+
+        self.storage
+            .check_not_frozen([&stream.payer, &stream.payee])?;
+        self.storage
+            .cancel_stream(id, stream, memo)
+            .map(|(paid, refunded)| StreamCancelReturns { paid, refunded })
+    }
+
+    fn stream_info(
+        &self,
+        _sender: &Address,
+        args: StreamInfoArgs,
+    ) -> Result<StreamInfoReturns, ManyError> {
+        self.check_streams_enabled("ledger.streamInfo")?;
+        let stream = self.get_stream(args.id)?;
+        Ok(StreamInfoReturns {
+            withdrawable: stream.withdrawable(self.storage.now()),
+            stream,
+        })
+    }
+}
@@ -0,0 +1,39 @@
+use crate::migration::token_identity::TOKEN_IDENTITY_MIGRATION;
+use crate::module::LedgerModuleImpl;
+use crate::storage::ledger_tokens::{verify_tokens_sender, TOKEN_IDENTITY_ROOT};
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::ledger::{
+    invalid_token_identity, LedgerTokenIdentityModuleBackend, UpdateTokenIdentityArgs,
+    UpdateTokenIdentityReturns,
+};
+use many_modules::EmptyReturn;
+
+impl LedgerTokenIdentityModuleBackend for LedgerModuleImpl {
+    fn update_token_identity(
+        &mut self,
+        sender: &Address,
+        args: UpdateTokenIdentityArgs,
+    ) -> Result<UpdateTokenIdentityReturns, ManyError> {
+        if !self
+            .storage
+            .migrations()
+            .is_active(&TOKEN_IDENTITY_MIGRATION)
+        {
+            return Err(ManyError::invalid_method_name("ledger.updateTokenIdentity"));
+        }
+
+        // Only the current token identity can hand over its role.
+        verify_tokens_sender(sender, self.storage.get_identity(TOKEN_IDENTITY_ROOT)?)?;
+        self.storage.check_memo(args.memo.as_ref())?;
+
+        let UpdateTokenIdentityArgs { identity, memo } = args;
+        if identity.is_anonymous() || identity.is_illegal() {
+            return Err(invalid_token_identity(identity));
+        }
+
+        self.storage
+            .update_token_identity(identity, memo)
+            .map(|_| EmptyReturn)
+    }
+}
@@ -0,0 +1,30 @@
+use crate::migration::account_vesting::ACCOUNT_VESTING_MIGRATION;
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::account::features::vesting::{
+    errors, AccountVestingModuleBackend, InfoArgs, InfoReturn,
+};
+
+impl AccountVestingModuleBackend for LedgerModuleImpl {
+    fn info(&self, _sender: &Address, args: InfoArgs) -> Result<InfoReturn, ManyError> {
+        if !self
+            .storage
+            .migrations()
+            .is_active(&ACCOUNT_VESTING_MIGRATION)
+        {
+            return Err(ManyError::invalid_method_name("vesting.info"));
+        }
+
+        let schedule = self
+            .storage
+            .vesting_schedule(&args.account)
+            .ok_or_else(|| errors::vesting_not_enabled(args.account))?;
+        let now = self.storage.now();
+        Ok(InfoReturn {
+            unlocked: schedule.vested(now),
+            remaining: schedule.locked(now),
+            schedule,
+        })
+    }
+}
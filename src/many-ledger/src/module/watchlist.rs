@@ -0,0 +1,78 @@
+use crate::error;
+use crate::migration::watchlist::WATCHLIST_MIGRATION;
+use crate::module::LedgerModuleImpl;
+use crate::storage::IDENTITY_ROOT;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::watchlist::{
+    AddArgs, AddReturn, InfoArgs, InfoReturn, RemoveArgs, RemoveReturn, SetApproversArgs,
+    SetApproversReturn, WatchlistEntry, WatchlistModuleBackend,
+};
+use many_modules::EmptyReturn;
+
+impl LedgerModuleImpl {
+    fn check_watchlist_enabled(&self, method: &str) -> Result<(), ManyError> {
+        if self.storage.migrations().is_active(&WATCHLIST_MIGRATION) {
+            Ok(())
+        } else {
+            Err(ManyError::invalid_method_name(method))
+        }
+    }
+
+    /// Only the server identity administers the watchlist.
+    fn verify_watchlist_admin(&self, sender: &Address) -> Result<(), ManyError> {
+        if *sender == self.storage.get_identity(IDENTITY_ROOT)? {
+            Ok(())
+        } else {
+            Err(error::unauthorized())
+        }
+    }
+}
+
+impl WatchlistModuleBackend for LedgerModuleImpl {
+    fn info(&self, _sender: &Address, _args: InfoArgs) -> Result<InfoReturn, ManyError> {
+        self.check_watchlist_enabled("watchlist.info")?;
+        Ok(InfoReturn {
+            entries: self.storage.get_watchlist_entries()?,
+            approvers: self.storage.get_watchlist_approvers()?,
+        })
+    }
+
+    fn add(&mut self, sender: &Address, args: AddArgs) -> Result<AddReturn, ManyError> {
+        self.check_watchlist_enabled("watchlist.add")?;
+        self.verify_watchlist_admin(sender)?;
+
+        let AddArgs {
+            address,
+            action,
+            memo,
+        } = args;
+        self.storage.check_memo(memo.as_ref())?;
+        self.storage
+            .add_watchlist_entry(address, WatchlistEntry { action, memo })
+            .map(|_| EmptyReturn)
+    }
+
+    fn remove(&mut self, sender: &Address, args: RemoveArgs) -> Result<RemoveReturn, ManyError> {
+        self.check_watchlist_enabled("watchlist.remove")?;
+        self.verify_watchlist_admin(sender)?;
+        self.storage.check_memo(args.memo.as_ref())?;
+
+        self.storage
+            .remove_watchlist_entry(args.address, args.memo)
+            .map(|_| EmptyReturn)
+    }
+
+    fn set_approvers(
+        &mut self,
+        sender: &Address,
+        args: SetApproversArgs,
+    ) -> Result<SetApproversReturn, ManyError> {
+        self.check_watchlist_enabled("watchlist.setApprovers")?;
+        self.verify_watchlist_admin(sender)?;
+
+        self.storage
+            .set_watchlist_approvers(args.approvers)
+            .map(|_| EmptyReturn)
+    }
+}
@@ -0,0 +1,389 @@
+use crate::module::LedgerModuleImpl;
+use crate::storage::durable_subscriptions::PendingDelivery;
+use crate::storage::multisig_notifications::{MultisigNotification, MultisigNotifier};
+use many_error::ManyError;
+use many_modules::account::features::multisig::{MultisigNotificationKind, NotificationEndpoint};
+use many_modules::events::{is_public_ip, SubscriptionDelivery, SubscriptionId};
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Time between two rounds of durable subscription deliveries.
+pub const SUBSCRIPTION_DELIVERY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Maximum time before retrying to deliver events to a target that failed.
+pub const SUBSCRIPTION_MAXIMUM_BACKOFF: Duration = Duration::from_secs(300);
+
+/// Maximum time for the target of a subscription to answer a delivery.
+pub const SUBSCRIPTION_DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Maximum number of multisig notices waiting to be posted. Notices sent
+/// while the queue is full are dropped.
+pub const WEBHOOK_QUEUE_CAPACITY: usize = 1000;
+
+/// Maximum time for a webhook or the email relay to answer a notice.
+pub const WEBHOOK_DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A notice ready to be posted.
+#[derive(Debug)]
+pub struct WebhookDelivery {
+    pub url: String,
+    pub payload: serde_json::Value,
+
+    /// Whether the URL is the email relay of the node, which can be private,
+    /// rather than a webhook given by a member.
+    pub relay: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum EndpointJson {
+    Email(String),
+    Webhook(String),
+}
+
+impl From<&NotificationEndpoint> for EndpointJson {
+    fn from(endpoint: &NotificationEndpoint) -> Self {
+        match endpoint {
+            NotificationEndpoint::Email(email) => EndpointJson::Email(email.clone()),
+            NotificationEndpoint::Webhook(url) => EndpointJson::Webhook(url.clone()),
+        }
+    }
+}
+
+/// A notifier that posts multisig notices as JSON over HTTP.
+///
+/// Webhook endpoints receive the notice directly. Email endpoints are posted to
+/// an email relay, if one is configured, which is responsible for sending the
+/// actual email.
+///
+/// Endpoints are kept in a local JSON file, indexed by their hash, and are never
+/// part of the persistent store.
+pub struct WebhookNotifier {
+    path: PathBuf,
+    email_relay: Option<String>,
+    endpoints: RwLock<BTreeMap<String, EndpointJson>>,
+    sender: async_channel::Sender<WebhookDelivery>,
+}
+
+impl WebhookNotifier {
+    pub fn new(
+        path: impl AsRef<Path>,
+        email_relay: Option<String>,
+    ) -> Result<(Self, async_channel::Receiver<WebhookDelivery>), ManyError> {
+        let path = path.as_ref().to_path_buf();
+        let endpoints = if path.exists() {
+            let content = std::fs::read_to_string(&path).map_err(ManyError::unknown)?;
+            serde_json::from_str(&content).map_err(ManyError::deserialization_error)?
+        } else {
+            BTreeMap::new()
+        };
+        let (sender, receiver) = async_channel::bounded(WEBHOOK_QUEUE_CAPACITY);
+
+        Ok((
+            Self {
+                path,
+                email_relay,
+                endpoints: RwLock::new(endpoints),
+                sender,
+            },
+            receiver,
+        ))
+    }
+
+    fn save(&self, endpoints: &BTreeMap<String, EndpointJson>) {
+        let result = serde_json::to_string_pretty(endpoints)
+            .map_err(|e| e.to_string())
+            .and_then(|content| std::fs::write(&self.path, content).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            warn!("Could not save notification endpoints: {e}");
+        }
+    }
+
+    fn payload(notification: &MultisigNotification) -> serde_json::Value {
+        serde_json::json!({
+            "kind": match notification.kind {
+                MultisigNotificationKind::Submitted => "submitted",
+                MultisigNotificationKind::ApproachingExpiry => "approachingExpiry",
+            },
+            "account": notification.account.to_string(),
+            "token": hex::encode(notification.token.as_slice()),
+            "timeout": notification.timeout.secs(),
+        })
+    }
+}
+
+impl MultisigNotifier for WebhookNotifier {
+    fn register(&self, endpoint_hash: &[u8], endpoint: &NotificationEndpoint) {
+        let mut endpoints = self.endpoints.write().unwrap();
+        endpoints.insert(hex::encode(endpoint_hash), endpoint.into());
+        self.save(&endpoints);
+    }
+
+    fn notify(&self, endpoint_hash: &[u8], notification: &MultisigNotification) {
+        let endpoints = self.endpoints.read().unwrap();
+        let delivery = match endpoints.get(&hex::encode(endpoint_hash)) {
+            Some(EndpointJson::Webhook(url)) => Some(WebhookDelivery {
+                url: url.clone(),
+                payload: Self::payload(notification),
+                relay: false,
+            }),
+            Some(EndpointJson::Email(email)) => self.email_relay.as_ref().map(|relay| {
+                let mut payload = Self::payload(notification);
+                payload["email"] = serde_json::Value::String(email.clone());
+                WebhookDelivery {
+                    url: relay.clone(),
+                    payload,
+                    relay: true,
+                }
+            }),
+            None => None,
+        };
+
+        match delivery {
+            Some(delivery) => {
+                if let Err(e) = self.sender.try_send(delivery) {
+                    warn!(
+                        "Dropping multisig notice to {}: {}",
+                        e.into_inner().url,
+                        if self.sender.is_closed() {
+                            "delivery stopped"
+                        } else {
+                            "too many notices waiting"
+                        }
+                    );
+                }
+            }
+            None => debug!(
+                "No deliverable endpoint for hash {}",
+                hex::encode(endpoint_hash)
+            ),
+        }
+    }
+}
+
+/// Post a notice. Webhooks are only posted to if they resolve to public
+/// addresses, and like subscription targets, redirects are not followed.
+async fn post_notice(delivery: &WebhookDelivery) -> Result<(), String> {
+    let client = if delivery.relay {
+        client_builder(WEBHOOK_DELIVERY_TIMEOUT)
+    } else {
+        public_client_builder(&delivery.url, WEBHOOK_DELIVERY_TIMEOUT).await?
+    };
+    client
+        .build()
+        .map_err(|e| e.to_string())?
+        .post(&delivery.url)
+        .header("content-type", "application/json")
+        .body(delivery.payload.to_string())
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Post the notices sent by a [WebhookNotifier]. This runs until the notifier
+/// is dropped.
+pub async fn deliver_webhooks(receiver: async_channel::Receiver<WebhookDelivery>) {
+    while let Ok(delivery) = receiver.recv().await {
+        if let Err(e) = post_notice(&delivery).await {
+            warn!("Could not deliver multisig notice to {}: {e}", delivery.url);
+        }
+    }
+}
+
+/// Resolve the host of a subscription target, refusing it unless all its
+/// addresses are public.
+async fn resolve_public_target(target: &str) -> Result<(String, Vec<SocketAddr>), String> {
+    let url = reqwest::Url::parse(target).map_err(|e| e.to_string())?;
+    let host = url.host_str().ok_or("The target has no host.")?.to_string();
+    let port = url
+        .port_or_known_default()
+        .ok_or("The target has no port.")?;
+    let addrs: Vec<SocketAddr> =
+        tokio::net::lookup_host((host.trim_start_matches('[').trim_end_matches(']'), port))
+            .await
+            .map_err(|e| e.to_string())?
+            .collect();
+    if addrs.is_empty() || !addrs.iter().all(|addr| is_public_ip(addr.ip())) {
+        return Err(format!("{host} does not only resolve to public addresses."));
+    }
+    Ok((host, addrs))
+}
+
+/// An HTTP client which does not follow redirects.
+fn client_builder(timeout: Duration) -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(timeout)
+}
+
+/// An HTTP client for a target given by a user, which only connects to the
+/// public addresses its host resolved to, and does not follow redirects.
+async fn public_client_builder(
+    target: &str,
+    timeout: Duration,
+) -> Result<reqwest::ClientBuilder, String> {
+    let (host, addrs) = resolve_public_target(target).await?;
+    let mut builder = client_builder(timeout);
+    if host.parse::<IpAddr>().is_err() {
+        builder = builder.resolve_to_addrs(&host, &addrs);
+    }
+    Ok(builder)
+}
+
+/// Post the events of a delivery to its target. The connection only goes to
+/// the addresses that were checked, and redirects are not followed.
+async fn post_delivery(delivery: &PendingDelivery) -> Result<(), String> {
+    let body = minicbor::to_vec(SubscriptionDelivery {
+        id: delivery.id,
+        events: delivery.events.clone(),
+    })
+    .map_err(|e| e.to_string())?;
+
+    public_client_builder(&delivery.target, SUBSCRIPTION_DELIVERY_TIMEOUT)
+        .await?
+        .build()
+        .map_err(|e| e.to_string())?
+        .post(&delivery.target)
+        .header("content-type", "application/cbor")
+        .body(body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Post the events of durable subscriptions to their targets, as CBOR. The
+/// cursor of a subscription only moves once its target answers with a success
+/// status, so events are delivered at least once, even across restarts. Targets
+/// that fail are retried with an exponential backoff. This never returns.
+///
+/// Targets are posted to concurrently. The ledger is only locked to read the
+/// events of one subscription at a time.
+pub async fn deliver_subscriptions(module_impl: Arc<Mutex<LedgerModuleImpl>>) {
+    let mut backoffs: BTreeMap<SubscriptionId, (Instant, Duration)> = BTreeMap::new();
+    loop {
+        let ids = module_impl.lock().unwrap().durable_subscription_ids();
+        backoffs.retain(|id, _| ids.contains(id));
+
+        let now = Instant::now();
+        let mut deliveries = tokio::task::JoinSet::new();
+        for id in ids {
+            if backoffs
+                .get(&id)
+                .map_or(false, |(retry_at, _)| now < *retry_at)
+            {
+                continue;
+            }
+
+            let delivery = module_impl.lock().unwrap().pending_delivery(id);
+            match delivery {
+                Ok(Some(delivery)) => {
+                    deliveries.spawn(async move {
+                        // Events which do not match the filter are acknowledged
+                        // directly.
+                        let result = if delivery.events.is_empty() {
+                            Ok(())
+                        } else {
+                            post_delivery(&delivery).await
+                        };
+                        (delivery, result)
+                    });
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Could not read the pending deliveries of subscription {id}: {e}"),
+            }
+        }
+
+        while let Some(joined) = deliveries.join_next().await {
+            let (delivery, result) = match joined {
+                Ok(joined) => joined,
+                Err(e) => {
+                    warn!("A subscription delivery failed: {e}");
+                    continue;
+                }
+            };
+
+            match result {
+                Ok(()) => {
+                    backoffs.remove(&delivery.id);
+                    let result = module_impl
+                        .lock()
+                        .unwrap()
+                        .acknowledge_delivery(delivery.id, delivery.cursor);
+                    if let Err(e) = result {
+                        warn!("Could not acknowledge subscription {}: {e}", delivery.id);
+                    }
+                }
+                Err(e) => {
+                    warn!(
+                        "Could not deliver the events of subscription {} to {}: {e}",
+                        delivery.id, delivery.target
+                    );
+                    let backoff = backoffs
+                        .get(&delivery.id)
+                        .map_or(SUBSCRIPTION_DELIVERY_INTERVAL, |(_, backoff)| {
+                            std::cmp::min(*backoff * 2, SUBSCRIPTION_MAXIMUM_BACKOFF)
+                        });
+                    backoffs.insert(delivery.id, (Instant::now() + backoff, backoff));
+                }
+            }
+        }
+
+        tokio::time::sleep(SUBSCRIPTION_DELIVERY_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::multisig_notifications::notification_endpoint_hash;
+    use many_identity::Address;
+    use many_types::Timestamp;
+
+    #[test]
+    fn register_and_notify() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("endpoints.json");
+        let (notifier, receiver) = WebhookNotifier::new(&path, None).unwrap();
+
+        let webhook = NotificationEndpoint::Webhook("https://example.com/hook".to_string());
+        let email = NotificationEndpoint::Email("foo@example.com".to_string());
+        let webhook_hash = notification_endpoint_hash(&webhook).unwrap();
+        let email_hash = notification_endpoint_hash(&email).unwrap();
+        notifier.register(&webhook_hash, &webhook);
+        notifier.register(&email_hash, &email);
+
+        let notification = MultisigNotification {
+            kind: MultisigNotificationKind::Submitted,
+            account: Address::anonymous(),
+            token: vec![1, 2, 3].into(),
+            timeout: Timestamp::new(1000).unwrap(),
+        };
+        notifier.notify(&webhook_hash, &notification);
+        // No email relay configured, so this is dropped.
+        notifier.notify(&email_hash, &notification);
+
+        let delivery = receiver.try_recv().unwrap();
+        assert_eq!(delivery.url, "https://example.com/hook");
+        assert_eq!(delivery.payload["token"], "010203");
+        assert!(!delivery.relay);
+        assert!(receiver.try_recv().is_err());
+
+        // Notices are dropped while the queue is full.
+        for _ in 0..WEBHOOK_QUEUE_CAPACITY + 1 {
+            notifier.notify(&webhook_hash, &notification);
+        }
+        assert_eq!(receiver.len(), WEBHOOK_QUEUE_CAPACITY);
+
+        // Endpoints survive a restart.
+        let (notifier, _) = WebhookNotifier::new(&path, None).unwrap();
+        assert_eq!(notifier.endpoints.read().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,146 @@
+//! The modules a ledger server can serve, by name. The `--modules` flag of the
+//! server selects which of them are served; all of them are by default.
+use crate::module::account::AccountFeatureModule;
+use crate::module::allow_addrs::AllowAddrsModule;
+use crate::module::idstore_webauthn::IdStoreWebAuthnModule;
+use crate::module::LedgerModuleImpl;
+use many_identity::Address;
+use many_modules::account::features::Feature;
+use many_modules::{account, data, events, idstore, labels, ledger, watchlist};
+use many_server::{ModuleConfig, ModuleRegistry};
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+
+/// What the ledger modules are constructed from.
+pub struct LedgerModuleContext {
+    pub module_impl: Arc<Mutex<LedgerModuleImpl>>,
+
+    /// If set, only these addresses can execute ledger commands.
+    pub allow_addrs: Option<BTreeSet<Address>>,
+
+    /// Whether the id store only accepts WebAuthn requests. Only disabled for
+    /// testing.
+    pub check_webauthn: bool,
+}
+
+impl LedgerModuleContext {
+    pub fn new(module_impl: Arc<Mutex<LedgerModuleImpl>>) -> Self {
+        Self {
+            module_impl,
+            allow_addrs: None,
+            check_webauthn: true,
+        }
+    }
+}
+
+/// The names of the modules served by default, in the order they are added.
+pub fn default_modules() -> Vec<ModuleConfig> {
+    let mut names = vec![
+        "ledger",
+        "ledger.commands",
+        "events",
+        "tokens",
+        "tokens.mintburn",
+        "ledger.freeze",
+        "ledger.schedule",
+        "ledger.receipts",
+        "ledger.streams",
+        "ledger.tokenIdentity",
+        "watchlist",
+        "idstore",
+        "account",
+        "account.multisig",
+        "account.hooks",
+        "vesting",
+        "data",
+        "labels",
+    ];
+    if cfg!(feature = "scripting") {
+        names.push("scripts");
+    }
+    names.into_iter().map(ModuleConfig::new).collect()
+}
+
+/// Register a constructor adding a module created by `new` from the module
+/// implementation.
+macro_rules! register {
+    ($registry: ident, $( $name: literal => $new: path, )*) => {
+        $(
+            $registry.register($name, |server, context: &LedgerModuleContext, _| {
+                server.add_module($new(context.module_impl.clone()));
+                Ok(())
+            });
+        )*
+    };
+}
+
+pub fn registry() -> ModuleRegistry<LedgerModuleContext> {
+    let mut registry = ModuleRegistry::new();
+    register!(registry,
+        "ledger" => ledger::LedgerModule::new,
+        "tokens" => ledger::LedgerTokensModule::new,
+        "tokens.mintburn" => ledger::LedgerMintBurnModule::new,
+        "ledger.freeze" => ledger::LedgerFreezeModule::new,
+        "ledger.schedule" => ledger::LedgerScheduleModule::new,
+        "ledger.receipts" => ledger::LedgerReceiptsModule::new,
+        "ledger.streams" => ledger::LedgerStreamsModule::new,
+        "ledger.tokenIdentity" => ledger::LedgerTokenIdentityModule::new,
+        "watchlist" => watchlist::WatchlistModule::new,
+        "account.multisig" => account::features::multisig::AccountMultisigModule::new,
+        "account.hooks" => account::features::hooks::AccountHooksModule::new,
+        "vesting" => account::features::vesting::AccountVestingModule::new,
+        "data" => data::DataModule::new,
+        "labels" => labels::LabelsModule::new,
+    );
+    #[cfg(feature = "scripting")]
+    register!(registry,
+        "scripts" => many_modules::scripts::ScriptsModule::new,
+    );
+
+    registry.register("ledger.commands", |server, context, _| {
+        let inner = ledger::LedgerCommandsModule::new(context.module_impl.clone());
+        match &context.allow_addrs {
+            Some(allow_addrs) => server.add_module(AllowAddrsModule {
+                inner,
+                allow_addrs: allow_addrs.clone(),
+            }),
+            None => server.add_module(inner),
+        };
+        Ok(())
+    });
+    registry.register("events", |server, context, _| {
+        server
+            .add_module(events::EventsModule::new(context.module_impl.clone()))
+            .add_module(events::EventsSubscriptionModule::new(
+                context.module_impl.clone(),
+            ))
+            .add_module(events::EventsArchiveModule::new(
+                context.module_impl.clone(),
+            ))
+            .add_module(events::EventsIntentModule::new(context.module_impl.clone()))
+            .add_module(events::EventsDurableSubscriptionModule::new(
+                context.module_impl.clone(),
+            ));
+        Ok(())
+    });
+    registry.register("idstore", |server, context, _| {
+        let inner = idstore::IdStoreModule::new(context.module_impl.clone());
+        if context.check_webauthn {
+            server.add_module(inner);
+        } else {
+            server.add_module(IdStoreWebAuthnModule {
+                inner,
+                check_webauthn: false,
+            });
+        }
+        Ok(())
+    });
+    registry.register("account", |server, context, _| {
+        server.add_module(AccountFeatureModule::new(
+            account::AccountModule::new(context.module_impl.clone()),
+            [Feature::with_id(0), Feature::with_id(1)],
+        ));
+        Ok(())
+    });
+    registry
+}
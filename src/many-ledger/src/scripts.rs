@@ -0,0 +1,305 @@
+//! Execution of WASM scripts.
+//!
+//! Scripts are interpreted by `wasmi`, which is deterministic and meters the
+//! fuel used by every instruction. Scripts import their host API from the
+//! `env` module, and must export their memory as `memory`:
+//!
+//! - `input_len() -> i32`: the length of the input of the invocation.
+//! - `input_read(ptr: i32)`: copy the input to `ptr`.
+//! - `output(ptr: i32, len: i32)`: set the output of the invocation.
+//! - `balance(address_ptr: i32, address_len: i32, symbol_ptr: i32, symbol_len: i32) -> i64`:
+//!   the balance of an address, both given in their textual form. Returns -1 if
+//!   either is invalid, and saturates at `i64::MAX`.
+//! - `emit(ptr: i32, len: i32)`: emit an event with some data.
+//! - `kv_get(key_ptr: i32, key_len: i32, value_ptr: i32, value_len: i32) -> i32`:
+//!   read a key in the namespace of the script, copying up to `value_len` bytes
+//!   of its value to `value_ptr`. Returns the length of the value, or -1 if the
+//!   key does not exist.
+//! - `kv_put(key_ptr: i32, key_len: i32, value_ptr: i32, value_len: i32)`:
+//!   write a key in the namespace of the script.
+//!
+//! Writes and events are only applied if the invocation succeeds. Host calls
+//! consume [SCRIPT_HOST_FUEL_PER_BYTE] fuel for every byte they copy in or out
+//! of the memory of the script, and the memory cannot grow past
+//! [SCRIPT_MAX_MEMORY_SIZE].
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::scripts::{invalid_script, out_of_fuel, script_failed};
+use num_traits::ToPrimitive;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use wasmi::core::{Trap, TrapCode};
+use wasmi::{
+    Caller, Config, Engine, Extern, Linker, Module, Store, StoreLimits, StoreLimitsBuilder,
+};
+
+/// The maximum size of a key in the namespace of a script.
+pub const SCRIPT_KV_MAX_KEY_SIZE: usize = 256;
+
+/// The maximum size of a value in the namespace of a script.
+pub const SCRIPT_KV_MAX_VALUE_SIZE: usize = 4096;
+
+/// The maximum size of the data of an event emitted by a script.
+pub const SCRIPT_EVENT_MAX_DATA_SIZE: usize = 1024;
+
+/// The maximum size of the memory of a script, in bytes.
+pub const SCRIPT_MAX_MEMORY_SIZE: usize = 16 * 1024 * 1024;
+
+/// The fuel consumed by host calls for every byte they copy.
+pub const SCRIPT_HOST_FUEL_PER_BYTE: u64 = 1;
+
+/// The state changes of a successful invocation, to apply to the storage.
+#[derive(Debug, Default)]
+pub struct ScriptEffects {
+    pub output: Vec<u8>,
+    pub fuel_used: u64,
+    pub writes: BTreeMap<Vec<u8>, Vec<u8>>,
+    pub events: Vec<Vec<u8>>,
+}
+
+struct HostState<'a> {
+    storage: &'a LedgerStorage,
+    id: u64,
+    input: Vec<u8>,
+    effects: ScriptEffects,
+    limits: StoreLimits,
+}
+
+impl<'a> HostState<'a> {
+    fn kv_get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, ManyError> {
+        match self.effects.writes.get(key) {
+            Some(value) => Ok(Some(value.clone())),
+            None => self.storage.get_script_value(self.id, key),
+        }
+    }
+}
+
+/// Check that some code is a valid WASM module.
+pub fn validate(code: &[u8]) -> Result<(), ManyError> {
+    let engine = Engine::new(&config());
+    Module::new(&engine, code)
+        .map(|_| ())
+        .map_err(invalid_script)
+}
+
+fn config() -> Config {
+    let mut config = Config::default();
+    config.consume_fuel(true);
+    config
+}
+
+fn trap(reason: impl ToString) -> Trap {
+    Trap::new(reason.to_string())
+}
+
+fn consume_fuel(caller: &mut Caller<'_, HostState<'_>>, len: usize) -> Result<(), Trap> {
+    caller
+        .consume_fuel((len as u64).saturating_mul(SCRIPT_HOST_FUEL_PER_BYTE))
+        .map(|_| ())
+        .map_err(|_| Trap::from(TrapCode::OutOfFuel))
+}
+
+/// Copy a range of the memory of the script. The range is checked against the
+/// memory, and its fuel consumed, before anything is allocated.
+fn read_memory(
+    caller: &mut Caller<'_, HostState<'_>>,
+    ptr: i32,
+    len: i32,
+) -> Result<Vec<u8>, Trap> {
+    let memory = caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| trap("The script does not export its memory"))?;
+    let start = usize::try_from(ptr).map_err(trap)?;
+    let len = usize::try_from(len).map_err(trap)?;
+    let end = start
+        .checked_add(len)
+        .filter(|end| *end <= memory.data(&*caller).len())
+        .ok_or_else(|| trap("Memory access out of bounds"))?;
+
+    consume_fuel(caller, len)?;
+    Ok(memory.data(&*caller)[start..end].to_vec())
+}
+
+fn write_memory(caller: &mut Caller<'_, HostState<'_>>, ptr: i32, data: &[u8]) -> Result<(), Trap> {
+    let memory = caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| trap("The script does not export its memory"))?;
+    consume_fuel(caller, data.len())?;
+    memory
+        .write(caller, usize::try_from(ptr).map_err(trap)?, data)
+        .map_err(trap)
+}
+
+fn read_str(caller: &mut Caller<'_, HostState<'_>>, ptr: i32, len: i32) -> Result<String, Trap> {
+    String::from_utf8(read_memory(caller, ptr, len)?).map_err(trap)
+}
+
+fn linker<'a>(engine: &Engine) -> Result<Linker<HostState<'a>>, ManyError> {
+    let mut linker = <Linker<HostState<'a>>>::new(engine);
+    linker
+        .func_wrap(
+            "env",
+            "input_len",
+            |caller: Caller<'_, HostState<'a>>| -> i32 { caller.data().input.len() as i32 },
+        )
+        .map_err(ManyError::unknown)?;
+    linker
+        .func_wrap(
+            "env",
+            "input_read",
+            |mut caller: Caller<'_, HostState<'a>>, ptr: i32| -> Result<(), Trap> {
+                let input = caller.data().input.clone();
+                write_memory(&mut caller, ptr, &input)
+            },
+        )
+        .map_err(ManyError::unknown)?;
+    linker
+        .func_wrap(
+            "env",
+            "output",
+            |mut caller: Caller<'_, HostState<'a>>, ptr: i32, len: i32| -> Result<(), Trap> {
+                let output = read_memory(&mut caller, ptr, len)?;
+                caller.data_mut().effects.output = output;
+                Ok(())
+            },
+        )
+        .map_err(ManyError::unknown)?;
+    linker
+        .func_wrap(
+            "env",
+            "balance",
+            |mut caller: Caller<'_, HostState<'a>>,
+             address_ptr: i32,
+             address_len: i32,
+             symbol_ptr: i32,
+             symbol_len: i32|
+             -> Result<i64, Trap> {
+                let address = Address::from_str(&read_str(&mut caller, address_ptr, address_len)?);
+                let symbol = Address::from_str(&read_str(&mut caller, symbol_ptr, symbol_len)?);
+                match (address, symbol) {
+                    (Ok(address), Ok(symbol)) => {
+                        let balance = caller
+                            .data()
+                            .storage
+                            .get_balance(&address, &symbol)
+                            .map_err(trap)?;
+                        Ok(balance.as_ref().to_i64().unwrap_or(i64::MAX))
+                    }
+                    _ => Ok(-1),
+                }
+            },
+        )
+        .map_err(ManyError::unknown)?;
+    linker
+        .func_wrap(
+            "env",
+            "emit",
+            |mut caller: Caller<'_, HostState<'a>>, ptr: i32, len: i32| -> Result<(), Trap> {
+                if usize::try_from(len).map_err(trap)? > SCRIPT_EVENT_MAX_DATA_SIZE {
+                    return Err(trap("Event data is too large"));
+                }
+                let data = read_memory(&mut caller, ptr, len)?;
+                caller.data_mut().effects.events.push(data);
+                Ok(())
+            },
+        )
+        .map_err(ManyError::unknown)?;
+    linker
+        .func_wrap(
+            "env",
+            "kv_get",
+            |mut caller: Caller<'_, HostState<'a>>,
+             key_ptr: i32,
+             key_len: i32,
+             value_ptr: i32,
+             value_len: i32|
+             -> Result<i32, Trap> {
+                if usize::try_from(key_len).map_err(trap)? > SCRIPT_KV_MAX_KEY_SIZE {
+                    return Err(trap("Key is too large"));
+                }
+                let key = read_memory(&mut caller, key_ptr, key_len)?;
+                match caller.data().kv_get(&key).map_err(trap)? {
+                    Some(value) => {
+                        let len = value.len().min(usize::try_from(value_len).map_err(trap)?);
+                        write_memory(&mut caller, value_ptr, &value[..len])?;
+                        Ok(value.len() as i32)
+                    }
+                    None => Ok(-1),
+                }
+            },
+        )
+        .map_err(ManyError::unknown)?;
+    linker
+        .func_wrap(
+            "env",
+            "kv_put",
+            |mut caller: Caller<'_, HostState<'a>>,
+             key_ptr: i32,
+             key_len: i32,
+             value_ptr: i32,
+             value_len: i32|
+             -> Result<(), Trap> {
+                if usize::try_from(key_len).map_err(trap)? > SCRIPT_KV_MAX_KEY_SIZE
+                    || usize::try_from(value_len).map_err(trap)? > SCRIPT_KV_MAX_VALUE_SIZE
+                {
+                    return Err(trap("Key or value is too large"));
+                }
+                let key = read_memory(&mut caller, key_ptr, key_len)?;
+                let value = read_memory(&mut caller, value_ptr, value_len)?;
+                caller.data_mut().effects.writes.insert(key, value);
+                Ok(())
+            },
+        )
+        .map_err(ManyError::unknown)?;
+    Ok(linker)
+}
+
+/// Call a function exported by a script, with some fuel. The storage is only
+/// read; the changes the script made are returned to be applied by the caller.
+pub fn execute(
+    storage: &LedgerStorage,
+    id: u64,
+    code: &[u8],
+    function: &str,
+    input: Vec<u8>,
+    fuel: u64,
+) -> Result<ScriptEffects, ManyError> {
+    let engine = Engine::new(&config());
+    let module = Module::new(&engine, code).map_err(invalid_script)?;
+    let mut store = Store::new(
+        &engine,
+        HostState {
+            storage,
+            id,
+            input,
+            effects: ScriptEffects::default(),
+            limits: StoreLimitsBuilder::new()
+                .memory_size(SCRIPT_MAX_MEMORY_SIZE)
+                .build(),
+        },
+    );
+    store.limiter(|state| &mut state.limits);
+    store.add_fuel(fuel).map_err(ManyError::unknown)?;
+
+    let instance = linker(&engine)?
+        .instantiate(&mut store, &module)
+        .and_then(|instance| instance.start(&mut store))
+        .map_err(invalid_script)?;
+    let func = instance
+        .get_typed_func::<(), ()>(&store, function)
+        .map_err(invalid_script)?;
+
+    match func.call(&mut store, ()) {
+        Ok(()) => {
+            let fuel_used = store.fuel_consumed().unwrap_or(fuel);
+            let mut effects = store.into_data().effects;
+            effects.fuel_used = fuel_used;
+            Ok(effects)
+        }
+        Err(e) if e.trap_code() == Some(TrapCode::OutOfFuel) => Err(out_of_fuel(fuel)),
+        Err(e) => Err(script_failed(e)),
+    }
+}
@@ -1,37 +1,70 @@
 use crate::error;
+use crate::migration::canonical_cbor::CANONICAL_CBOR_MIGRATION;
 use crate::migration::tokens::TOKEN_MIGRATION;
 use crate::migration::{LedgerMigrations, MIGRATIONS};
 use crate::storage::account::ACCOUNT_SUBRESOURCE_ID_ROOT;
-use crate::storage::event::HEIGHT_EVENTID_SHIFT;
 use many_error::ManyError;
 use many_identity::{Address, MAX_SUBRESOURCE_ID};
+use many_ledger_verify::genesis::GENESIS_HASH_AUX_KEY;
 use many_migration::{MigrationConfig, MigrationSet};
-use many_modules::events::EventId;
+use many_modules::events::{EventId, EventLog, EventSubscriptions};
+use many_types::cbor::canonical;
 use many_types::ledger::Symbol;
 use many_types::Timestamp;
 use merk::Op;
+use minicbor::bytes::ByteVec;
+use minicbor::Encode;
 use std::collections::{BTreeMap, BTreeSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 mod abci;
 pub mod account;
+mod atomic;
 pub mod data;
+pub mod durable_subscriptions;
+pub mod dust;
 pub mod event;
+pub mod extended_info_policy;
+pub mod freeze;
+pub mod hooks;
 pub(crate) mod idstore;
+pub mod invariants;
 pub mod iterator;
+pub mod kvstore;
+pub mod labels;
 mod ledger;
 mod ledger_commands;
 pub mod ledger_mintburn;
 pub mod ledger_tokens;
+pub mod memo;
 mod migrations;
 pub mod multisig;
+pub mod multisig_notifications;
+pub mod scheduled_send;
+#[cfg(feature = "scripting")]
+pub mod scripts;
+pub mod snapshots;
+pub mod streams;
+pub mod token_ownership;
+pub mod vesting;
+pub mod watchlist;
 
 pub const SYMBOLS_ROOT: &str = "/config/symbols";
 pub const IDENTITY_ROOT: &str = "/config/identity";
 pub const HEIGHT_ROOT: &str = "/height";
 
-pub(super) fn key_for_account_balance(id: &Address, symbol: &Symbol) -> Vec<u8> {
-    format!("/balances/{id}/{symbol}").into_bytes()
+/// The prefix of the balance keys of an address. Balances are keyed by address
+/// first, so this range holds exactly the symbols the address has a balance of.
+pub(crate) fn key_prefix_for_account_balances(id: &Address) -> Vec<u8> {
+    format!("{BALANCES_ROOT}{id}/").into_bytes()
+}
+
+/// The address and symbol of a balance key.
+pub(crate) fn parse_balance_key(key: &[u8]) -> Option<(Address, Symbol)> {
+    let key = std::str::from_utf8(key).ok()?.strip_prefix(BALANCES_ROOT)?;
+    let (id, symbol) = key.split_once('/')?;
+    Some((Address::from_str(id).ok()?, Address::from_str(symbol).ok()?))
 }
 
 pub(super) fn key_for_subresource_counter(id: &Address, token_migration_active: bool) -> Vec<u8> {
@@ -43,10 +76,12 @@ pub(super) fn key_for_subresource_counter(id: &Address, token_migration_active:
     }
 }
 
-pub type InnerStorage = merk::Merk;
+pub use atomic::InnerStorage;
+pub use many_ledger_verify::proof::{key_for_account_balance, verify_proof, BALANCES_ROOT};
 
 pub struct LedgerStorage {
     persistent_store: InnerStorage,
+    persistent_path: PathBuf,
 
     /// When this is true, we do not commit every transactions as they come,
     /// but wait for a `commit` call before committing the batch to the
@@ -56,9 +91,28 @@ pub struct LedgerStorage {
     latest_tid: EventId,
 
     current_time: Option<Timestamp>,
+    current_proposer: Option<ByteVec>,
     current_hash: Option<Vec<u8>>,
 
     migrations: LedgerMigrations,
+
+    multisig_notifications: Option<multisig_notifications::MultisigNotifications>,
+
+    /// Events subscriptions are local to this node and are not part of the state.
+    event_subscriptions: EventSubscriptions,
+
+    /// Events logged since the last commit, to publish to subscriptions once
+    /// they are committed.
+    pending_events: Vec<EventLog>,
+
+    atomic_group: Option<atomic::AtomicGroup>,
+
+    /// Snapshots are local to this node and are not part of the state.
+    snapshots: Option<snapshots::SnapshotConfig>,
+    restore: Option<snapshots::SnapshotRestore>,
+
+    /// Durable subscriptions are local to this node and are not part of the state.
+    durable_subscriptions: Option<durable_subscriptions::DurableSubscriptions>,
 }
 
 impl LedgerStorage {
@@ -106,13 +160,36 @@ impl LedgerStorage {
         self.current_time.unwrap_or_else(Timestamp::now)
     }
 
+    #[inline]
+    pub fn set_proposer(&mut self, proposer: Option<ByteVec>) {
+        self.current_proposer = proposer;
+    }
+
+    /// The address of the validator which proposed the current block, if
+    /// known.
+    #[inline]
+    pub fn proposer(&self) -> Option<&ByteVec> {
+        self.current_proposer.as_ref()
+    }
+
     pub fn migrations(&self) -> &LedgerMigrations {
         &self.migrations
     }
 
+    /// Encode a value to put in the persistent store. Once the Canonical CBOR
+    /// Migration is active, values are encoded deterministically so the root
+    /// hash doesn't depend on how nodes encode them.
+    pub(crate) fn encode_value<T: Encode<()>>(&self, value: T) -> Result<Vec<u8>, ManyError> {
+        if self.migrations.is_active(&CANONICAL_CBOR_MIGRATION) {
+            canonical::to_canonical_vec(value)
+        } else {
+            minicbor::to_vec(value).map_err(ManyError::serialization_error)
+        }
+    }
+
     #[inline]
     fn maybe_commit(&mut self) -> Result<(), ManyError> {
-        if !self.blockchain {
+        if !self.blockchain && self.atomic_group.is_none() {
             self.commit_storage()
         } else {
             Ok(())
@@ -123,7 +200,11 @@ impl LedgerStorage {
     fn commit_storage(&mut self) -> Result<(), ManyError> {
         self.persistent_store
             .commit(&[])
-            .map_err(error::storage_commit_failed)
+            .map_err(error::storage_commit_failed)?;
+
+        let pending_events = std::mem::take(&mut self.pending_events);
+        self.event_subscriptions.publish(&pending_events);
+        Ok(())
     }
 
     pub fn load<P: AsRef<Path>>(
@@ -131,8 +212,9 @@ impl LedgerStorage {
         blockchain: bool,
         migration_config: Option<MigrationConfig>,
     ) -> Result<Self, ManyError> {
+        let persistent_path = persistent_path.as_ref().to_path_buf();
         let persistent_store =
-            InnerStorage::open(persistent_path).map_err(error::storage_open_failed)?;
+            InnerStorage::open(&persistent_path).map_err(error::storage_open_failed)?;
 
         let height = persistent_store
             .get(HEIGHT_ROOT.as_bytes())
@@ -143,47 +225,66 @@ impl LedgerStorage {
                 u64::from_be_bytes(bytes)
             });
 
-        // The call to `saturating_sub()` is required to fix
-        // https://github.com/liftedinit/many-framework/issues/289
-        //
-        // The `commit()` function computes the `latest_tid` using the previous height while
-        // the following line computes the `latest_tid` using the current height.
-        //
-        // The discrepancy will lead to an application hash mismatch if the block following the `load()` contains
-        // a transaction.
-        let latest_tid = EventId::from(height.saturating_sub(1) << HEIGHT_EVENTID_SHIFT);
         let migrations = migration_config
             .map_or_else(MigrationSet::empty, |config| {
                 LedgerMigrations::load(&MIGRATIONS, config, height)
             })
             .map_err(error::unable_to_load_migrations)?;
 
-        Ok(Self {
+        let mut storage = Self {
             persistent_store,
+            persistent_path,
             blockchain,
-            latest_tid,
+            latest_tid: EventId::from(vec![0]),
             current_time: None,
+            current_proposer: None,
             current_hash: None,
             migrations,
-        })
+            multisig_notifications: None,
+            event_subscriptions: Default::default(),
+            pending_events: vec![],
+            atomic_group: None,
+            snapshots: None,
+            restore: None,
+            durable_subscriptions: None,
+        };
+        storage.reset_event_ids(height)?;
+        Ok(storage)
     }
 
     pub fn new<P: AsRef<Path>>(persistent_path: P, blockchain: bool) -> Result<Self, ManyError> {
-        let persistent_store = InnerStorage::open(persistent_path).map_err(ManyError::unknown)?; // TODO: Custom error
+        let persistent_path = persistent_path.as_ref().to_path_buf();
+        let persistent_store = InnerStorage::open(&persistent_path).map_err(ManyError::unknown)?; // TODO: Custom error
 
         Ok(Self {
             persistent_store,
+            persistent_path,
             blockchain,
             latest_tid: EventId::from(vec![0]),
             current_time: None,
+            current_proposer: None,
             current_hash: None,
             migrations: MigrationSet::empty().map_err(ManyError::unknown)?, // TODO: Custom error
+            multisig_notifications: None,
+            event_subscriptions: Default::default(),
+            pending_events: vec![],
+            atomic_group: None,
+            snapshots: None,
+            restore: None,
+            durable_subscriptions: None,
         })
     }
 
     pub fn build(mut self) -> Result<Self, ManyError> {
         self.persistent_store
             .commit(&[])
+            .map_err(error::storage_commit_failed)?;
+
+        // Record the genesis hash, so the store can be verified against its
+        // genesis state once it moved past it.
+        let genesis_hash = self.persistent_store.root_hash().to_vec();
+        self.persistent_store
+            .commit(&[(GENESIS_HASH_AUX_KEY.to_vec(), Op::Put(genesis_hash))])
             .map_err(error::storage_commit_failed)
             .map(|_| self)
     }
@@ -1,7 +1,5 @@
-use crate::storage::event::HEIGHT_EVENTID_SHIFT;
 use crate::storage::LedgerStorage;
 use many_modules::abci_backend::AbciCommitInfo;
-use many_modules::events::EventId;
 
 impl LedgerStorage {
     pub fn commit(&mut self) -> AbciCommitInfo {
@@ -12,6 +10,9 @@ impl LedgerStorage {
         let height = self.inc_height().expect("Unable to increment height.");
         let retain_height = 0;
 
+        self.snapshot_data_attributes(height + 1)
+            .expect("Unable to snapshot data attributes.");
+
         // Committing before the migration so that the migration has
         // the actual state of the database when setting its
         // attributes.
@@ -24,10 +25,16 @@ impl LedgerStorage {
 
         self.commit_storage().expect("Unable to commit to storage.");
 
+        #[cfg(feature = "invariant_checks")]
+        self.assert_invariants(height + 1);
+
+        self.maybe_take_snapshot(height + 1);
+
         let hash = self.persistent_store.root_hash().to_vec();
         self.current_hash = Some(hash.clone());
 
-        self.latest_tid = EventId::from(height << HEIGHT_EVENTID_SHIFT);
+        self.reset_event_ids(height + 1)
+            .expect("Unable to reset event IDs.");
 
         AbciCommitInfo {
             retain_height,
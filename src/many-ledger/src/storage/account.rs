@@ -1,7 +1,8 @@
 use crate::error;
 use crate::migration::legacy_remove_roles::LEGACY_REMOVE_ROLES_TRIGGER;
+use crate::migration::nested_accounts::NESTED_ACCOUNTS_MIGRATION;
 use crate::migration::tokens::TOKEN_MIGRATION;
-use crate::module::account::{validate_account, verify_account_role};
+use crate::module::account::{get_roles_for_account, validate_account, verify_account_role};
 use crate::storage::multisig::{
     MULTISIG_DEFAULT_EXECUTE_AUTOMATICALLY, MULTISIG_DEFAULT_TIMEOUT_IN_SECS,
     MULTISIG_MAXIMUM_TIMEOUT_IN_SECS,
@@ -9,7 +10,8 @@ use crate::storage::multisig::{
 use crate::storage::{LedgerStorage, IDENTITY_ROOT};
 use many_error::ManyError;
 use many_identity::Address;
-use many_modules::account::features::{FeatureId, FeatureInfo, FeatureSet};
+use many_modules::account::features::hooks::AccountHooks;
+use many_modules::account::features::{FeatureId, FeatureInfo, FeatureSet, TryCreateFeature};
 use many_modules::account::Role;
 use many_modules::{account, events};
 use many_types::Either;
@@ -19,6 +21,10 @@ use std::collections::{BTreeMap, BTreeSet};
 pub const ACCOUNT_IDENTITY_ROOT: &str = "/config/account_identity";
 pub const ACCOUNT_SUBRESOURCE_ID_ROOT: &str = "/config/account_id";
 
+/// The maximum number of member accounts followed when resolving the roles of an
+/// identity in an account.
+pub const ACCOUNT_MAXIMUM_NESTING_DEPTH: usize = 4;
+
 /// Internal representation of Account metadata
 #[derive(Clone, Debug)]
 pub struct AccountMeta {
@@ -44,7 +50,8 @@ pub fn verify_acl(
         let (account, keys) = storage
             .get_account(addr)
             .map_err(|_| error::unauthorized())?;
-        verify_account_role(&account, sender, feature_id, roles).map(|_| keys.into_iter().collect())
+        verify_account_role(storage, addr, &account, sender, feature_id, roles)
+            .map(|_| keys.into_iter().collect())
     } else {
         Ok(Vec::<Vec<u8>>::new())
     }
@@ -285,6 +292,34 @@ impl LedgerStorage {
         .and_then(|_| self.commit_account(&args.account, account))
     }
 
+    /// Remove features from an account, with the roles only these features
+    /// allowed. The hook records of the account are deleted with its hooks
+    /// feature.
+    pub fn remove_features(
+        &mut self,
+        mut account: account::Account,
+        args: account::RemoveFeaturesArgs,
+    ) -> Result<Vec<u8>, ManyError> {
+        account.remove_features(&args.features)?;
+
+        let mut allowed_roles = get_roles_for_account(&account);
+        allowed_roles.insert(Role::Owner);
+        let roles = account.retain_roles(&allowed_roles);
+
+        validate_account(&account)?;
+
+        if args.features.contains(&AccountHooks::ID) {
+            self.delete_hook_records(&args.account)?;
+        }
+
+        self.log_event(events::EventInfo::AccountRemoveFeatures {
+            account: args.account,
+            features: args.features,
+            roles,
+        })
+        .and_then(|_| self.commit_account(&args.account, account))
+    }
+
     pub fn get_account(
         &self,
         id: &Address,
@@ -314,6 +349,88 @@ impl LedgerStorage {
             .unwrap_or_else(|| Err(account::errors::unknown_account(id)))
     }
 
+    /// Returns whether an identity has one of the roles in an account.
+    ///
+    /// Accounts can be members of other accounts. An identity inherits a role
+    /// that a member account has if it is an owner of that member account, or
+    /// has the same role in it. Roles are resolved through at most
+    /// [ACCOUNT_MAXIMUM_NESTING_DEPTH] member accounts, and an account is never
+    /// followed twice in the same chain, so cycles are ignored.
+    pub fn has_account_role(
+        &self,
+        account_id: &Address,
+        account: &account::Account,
+        id: &Address,
+        roles: impl IntoIterator<Item = Role>,
+    ) -> bool {
+        let max_depth = if self.migrations.is_active(&NESTED_ACCOUNTS_MIGRATION) {
+            ACCOUNT_MAXIMUM_NESTING_DEPTH
+        } else {
+            0
+        };
+        let roles = roles.into_iter().collect();
+        self.resolve_account_role(account, id, &roles, &mut vec![*account_id], max_depth)
+    }
+
+    fn resolve_account_role(
+        &self,
+        account: &account::Account,
+        id: &Address,
+        roles: &BTreeSet<Role>,
+        chain: &mut Vec<Address>,
+        max_depth: usize,
+    ) -> bool {
+        if roles.iter().any(|r| account.has_role(id, *r)) {
+            return true;
+        }
+        if chain.len() > max_depth {
+            return false;
+        }
+
+        for (member, member_roles) in account.roles() {
+            if member == id || chain.contains(member) {
+                continue;
+            }
+            let mut inherited: BTreeSet<Role> = member_roles.intersection(roles).copied().collect();
+            if inherited.is_empty() {
+                continue;
+            }
+            let member_account = match self.get_account(member) {
+                Ok((member_account, _)) => member_account,
+                Err(_) => continue,
+            };
+
+            inherited.insert(Role::Owner);
+            chain.push(*member);
+            let found =
+                self.resolve_account_role(&member_account, id, &inherited, chain, max_depth);
+            chain.pop();
+            if found {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Verify that an identity has one of the roles in an account, resolving
+    /// roles through member accounts. See [LedgerStorage::has_account_role].
+    pub fn needs_account_role(
+        &self,
+        account_id: &Address,
+        account: &account::Account,
+        id: &Address,
+        roles: impl IntoIterator<Item = Role>,
+    ) -> Result<(), ManyError> {
+        let roles: Vec<Role> = roles.into_iter().collect();
+        if self.has_account_role(account_id, account, id, roles.iter().copied()) {
+            Ok(())
+        } else {
+            Err(account::errors::user_needs_role(
+                roles.first().copied().unwrap_or(Role::Owner),
+            ))
+        }
+    }
+
     pub fn commit_account(
         &mut self,
         id: &Address,
@@ -323,10 +440,7 @@ impl LedgerStorage {
         let key = key_for_account(id);
 
         self.persistent_store
-            .apply(&[(
-                key.clone(),
-                Op::Put(minicbor::to_vec(account).map_err(ManyError::serialization_error)?),
-            )])
+            .apply(&[(key.clone(), Op::Put(self.encode_value(account)?))])
             .map_err(|e| ManyError::unknown(e.to_string()))?;
 
         self.maybe_commit().map(|_| key)
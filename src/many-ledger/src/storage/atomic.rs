@@ -0,0 +1,149 @@
+use crate::error;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_modules::events::EventId;
+use merk::{BatchEntry, Op};
+use std::collections::BTreeMap;
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+
+/// The persistent store of the ledger. While an atomic group is open, the
+/// value every written key had before the group is kept, so the writes of
+/// the group can be undone.
+pub struct InnerStorage {
+    merk: merk::Merk,
+    undo_log: Option<BTreeMap<Vec<u8>, Option<Vec<u8>>>>,
+}
+
+impl InnerStorage {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        Ok(Self {
+            merk: merk::Merk::open(path).map_err(|e| e.to_string())?,
+            undo_log: None,
+        })
+    }
+
+    pub(super) fn from_merk(merk: merk::Merk) -> Self {
+        Self {
+            merk,
+            undo_log: None,
+        }
+    }
+
+    /// Close the store and delete its files.
+    pub(super) fn destroy(self) -> Result<(), String> {
+        self.merk.destroy().map_err(|e| e.to_string())
+    }
+
+    pub fn apply(&mut self, batch: &[BatchEntry]) -> Result<(), String> {
+        if let Some(undo_log) = &mut self.undo_log {
+            for (key, _) in batch {
+                if !undo_log.contains_key(key) {
+                    let previous = self.merk.get(key).map_err(|e| e.to_string())?;
+                    undo_log.insert(key.clone(), previous);
+                }
+            }
+        }
+        self.merk.apply(batch).map_err(|e| e.to_string())
+    }
+
+    fn start_undo_log(&mut self) {
+        self.undo_log = Some(BTreeMap::new());
+    }
+
+    fn drop_undo_log(&mut self) {
+        self.undo_log = None;
+    }
+
+    /// Restore the keys written since the undo log was started.
+    fn undo(&mut self) -> Result<(), String> {
+        let mut batch = Vec::new();
+        for (key, previous) in self.undo_log.take().unwrap_or_default() {
+            match previous {
+                Some(value) => batch.push((key, Op::Put(value))),
+                // Keys which did not exist are deleted, unless they were
+                // already deleted by the group.
+                None => {
+                    if self.merk.get(&key).map_err(|e| e.to_string())?.is_some() {
+                        batch.push((key, Op::Delete));
+                    }
+                }
+            }
+        }
+
+        if batch.is_empty() {
+            Ok(())
+        } else {
+            self.merk.apply(&batch).map_err(|e| e.to_string())
+        }
+    }
+}
+
+impl Deref for InnerStorage {
+    type Target = merk::Merk;
+
+    fn deref(&self) -> &Self::Target {
+        &self.merk
+    }
+}
+
+impl DerefMut for InnerStorage {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.merk
+    }
+}
+
+/// The in-memory state of the ledger when an atomic group started.
+pub(super) struct AtomicGroup {
+    latest_tid: EventId,
+    pending_events: usize,
+}
+
+impl LedgerStorage {
+    pub fn is_in_atomic_group(&self) -> bool {
+        self.atomic_group.is_some()
+    }
+
+    /// Start an atomic group. Until it ends, writes are not committed to the
+    /// persistent store, even outside a blockchain.
+    pub fn begin_atomic_group(&mut self) -> Result<(), ManyError> {
+        if self.atomic_group.is_some() {
+            return Err(ManyError::unknown("An atomic group is already open."));
+        }
+
+        self.persistent_store.start_undo_log();
+        self.atomic_group = Some(AtomicGroup {
+            latest_tid: self.latest_tid.clone(),
+            pending_events: self.pending_events.len(),
+        });
+        Ok(())
+    }
+
+    /// Keep the writes of the atomic group.
+    pub fn commit_atomic_group(&mut self) -> Result<(), ManyError> {
+        if self.atomic_group.take().is_none() {
+            return Err(ManyError::unknown("No atomic group is open."));
+        }
+
+        self.persistent_store.drop_undo_log();
+        self.maybe_commit()
+    }
+
+    /// Discard the writes and the events of the atomic group.
+    pub fn rollback_atomic_group(&mut self) -> Result<(), ManyError> {
+        let AtomicGroup {
+            latest_tid,
+            pending_events,
+        } = self
+            .atomic_group
+            .take()
+            .ok_or_else(|| ManyError::unknown("No atomic group is open."))?;
+
+        self.persistent_store
+            .undo()
+            .map_err(error::storage_apply_failed)?;
+        self.latest_tid = latest_tid;
+        self.pending_events.truncate(pending_events);
+        Ok(())
+    }
+}
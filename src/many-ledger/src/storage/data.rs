@@ -1,17 +1,76 @@
 use crate::error;
 use crate::migration::data::{ACCOUNT_TOTAL_COUNT_INDEX, NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX};
+use crate::migration::data_history::DATA_HISTORY_MIGRATION;
+use crate::storage::iterator::LedgerIterator;
 use crate::storage::{key_for_account_balance, LedgerStorage};
 use many_error::ManyError;
 use many_identity::Address;
-use many_modules::data::{DataIndex, DataInfo, DataValue};
+use many_modules::data::{data_not_available_at_height, DataIndex, DataInfo, DataValue};
 use many_types::ledger::TokenAmount;
 use merk::Op;
 use std::collections::BTreeMap;
 
 pub const DATA_ATTRIBUTES_KEY: &[u8] = b"/data/attributes";
 pub const DATA_INFO_KEY: &[u8] = b"/data/info";
+pub const DATA_HISTORY_ROOT: &[u8] = b"/data/history/";
+
+/// Returns the storage key of the snapshot of the data attributes at the end
+/// of a block.
+pub(crate) fn key_for_data_snapshot(height: u64) -> Vec<u8> {
+    [DATA_HISTORY_ROOT, &height.to_be_bytes()].concat()
+}
 
 impl LedgerStorage {
+    /// Returns the key of the latest snapshot of the data attributes at or
+    /// before `height`, and the snapshot itself.
+    fn latest_data_snapshot(&self, height: u64) -> Result<Option<(Vec<u8>, Vec<u8>)>, ManyError> {
+        LedgerIterator::data_snapshots(&self.persistent_store, height)
+            .next()
+            .transpose()
+            .map(|item| item.map(|(key, value)| (key.to_vec(), value)))
+            .map_err(error::storage_get_failed)
+    }
+
+    /// Returns the data attributes as they were at the end of the block at
+    /// `height`, with the storage key they were read from.
+    pub(crate) fn data_attributes_at_height(
+        &self,
+        height: u64,
+    ) -> Result<(Vec<u8>, BTreeMap<DataIndex, DataValue>), ManyError> {
+        if !self.migrations.is_active(&DATA_HISTORY_MIGRATION) || height > self.get_height()? {
+            return Err(data_not_available_at_height(height));
+        }
+
+        let (key, value) = self
+            .latest_data_snapshot(height)?
+            .ok_or_else(|| data_not_available_at_height(height))?;
+        minicbor::decode(&value)
+            .map(|attributes| (key, attributes))
+            .map_err(ManyError::deserialization_error)
+    }
+
+    /// Keep a snapshot of the data attributes at the end of the block at
+    /// `height`, if they changed since the latest snapshot.
+    pub(crate) fn snapshot_data_attributes(&mut self, height: u64) -> Result<(), ManyError> {
+        if !self.migrations.is_active(&DATA_HISTORY_MIGRATION) {
+            return Ok(());
+        }
+
+        let attributes = self
+            .persistent_store
+            .get(DATA_ATTRIBUTES_KEY)
+            .map_err(error::storage_get_failed)?;
+        if let Some(attributes) = attributes {
+            let latest = self.latest_data_snapshot(height)?.map(|(_, value)| value);
+            if latest.as_ref() != Some(&attributes) {
+                self.persistent_store
+                    .apply(&[(key_for_data_snapshot(height), Op::Put(attributes))])
+                    .map_err(error::storage_apply_failed)?;
+            }
+        }
+        Ok(())
+    }
+
     pub(crate) fn data_info(&self) -> Result<Option<BTreeMap<DataIndex, DataInfo>>, ManyError> {
         Ok(self
             .persistent_store
@@ -49,17 +108,19 @@ impl LedgerStorage {
             // If the destination account does not exist, increase
             // account total count
             if destination_is_empty {
-                attributes.entry(ACCOUNT_TOTAL_COUNT_INDEX).and_modify(|x| {
-                    if let DataValue::Counter(count) = x {
-                        *count += 1;
-                    }
-                });
+                attributes
+                    .entry(ACCOUNT_TOTAL_COUNT_INDEX.clone())
+                    .and_modify(|x| {
+                        if let DataValue::Counter(count) = x {
+                            *count += 1;
+                        }
+                    });
             }
             // If the destination account either is empty or is zero,
             // the amount of non zero accounts increases
             if destination_is_zero || destination_is_empty {
                 attributes
-                    .entry(NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX)
+                    .entry(NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX.clone())
                     .and_modify(|x| {
                         if let DataValue::Counter(count) = x {
                             *count += 1;
@@ -72,7 +133,7 @@ impl LedgerStorage {
             let origin_balance = self.get_balance(from, symbol)?;
             if origin_balance == amount {
                 attributes
-                    .entry(NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX)
+                    .entry(NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX.clone())
                     .and_modify(|x| {
                         if let DataValue::Counter(count) = x {
                             *count -= 1;
@@ -82,7 +143,7 @@ impl LedgerStorage {
             self.persistent_store
                 .apply(&[(
                     DATA_ATTRIBUTES_KEY.to_vec(),
-                    Op::Put(minicbor::to_vec(attributes).unwrap()),
+                    Op::Put(self.encode_value(attributes)?),
                 )])
                 .map_err(error::storage_apply_failed)?
         }
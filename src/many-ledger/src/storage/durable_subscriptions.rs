@@ -0,0 +1,271 @@
+use crate::storage::event::key_for_event;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::events::{
+    self, errors, is_public_target, DurableSubscription, EventId, EventLog, SubscriptionId,
+    MAXIMUM_SUBSCRIPTION_COUNT,
+};
+use many_types::{CborRange, SortOrder};
+use minicbor::{Decode, Encode};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+
+/// Maximum number of events posted to a target at once.
+pub const MAXIMUM_DELIVERY_SIZE: usize = 100;
+
+/// Maximum number of events read for a subscription at once. Events which do
+/// not match its filter are acknowledged without being delivered.
+pub const MAXIMUM_DELIVERY_SCAN: usize = 1000;
+
+#[derive(Debug, Default, Encode, Decode)]
+#[cbor(map)]
+struct Registry {
+    #[n(0)]
+    next_id: SubscriptionId,
+
+    #[n(1)]
+    subscriptions: BTreeMap<SubscriptionId, DurableSubscription>,
+}
+
+/// The durable subscriptions of a node, kept in a local CBOR file. They are
+/// local to this node and are not part of the state. Only the operators of
+/// the node can create them.
+#[derive(Debug)]
+pub(crate) struct DurableSubscriptions {
+    path: PathBuf,
+    operators: BTreeSet<Address>,
+    registry: Registry,
+}
+
+impl DurableSubscriptions {
+    fn load(path: &Path, operators: BTreeSet<Address>) -> Result<Self, ManyError> {
+        let registry = match std::fs::read(path) {
+            Ok(bytes) => minicbor::decode(&bytes).map_err(ManyError::deserialization_error)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Registry::default(),
+            Err(e) => return Err(ManyError::unknown(e)),
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            operators,
+            registry,
+        })
+    }
+
+    /// Write the registry to a temporary file first, so a crash never leaves
+    /// a partially written registry behind.
+    fn save(&self) -> Result<(), ManyError> {
+        let bytes = minicbor::to_vec(&self.registry).map_err(ManyError::serialization_error)?;
+        let mut temporary = self.path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        std::fs::write(&temporary, bytes)
+            .and_then(|_| std::fs::rename(&temporary, &self.path))
+            .map_err(ManyError::unknown)
+    }
+
+    fn get_owned(
+        &mut self,
+        owner: &Address,
+        id: SubscriptionId,
+    ) -> Result<&mut DurableSubscription, ManyError> {
+        let subscription = self
+            .registry
+            .subscriptions
+            .get_mut(&id)
+            .ok_or_else(|| errors::unknown_subscription(id))?;
+        if &subscription.owner != owner {
+            return Err(errors::not_subscription_owner(id));
+        }
+        Ok(subscription)
+    }
+}
+
+/// The events to post to the target of a subscription. Once acknowledged, the
+/// cursor of the subscription moves to `cursor`.
+#[derive(Debug)]
+pub struct PendingDelivery {
+    pub id: SubscriptionId,
+    pub target: String,
+    pub events: Vec<EventLog>,
+    pub cursor: EventId,
+}
+
+impl LedgerStorage {
+    /// Keep durable subscriptions in the file at `path`, loading the ones
+    /// already there. Only the `operators` can create new subscriptions.
+    pub fn set_durable_subscriptions(
+        &mut self,
+        path: impl AsRef<Path>,
+        operators: BTreeSet<Address>,
+    ) -> Result<(), ManyError> {
+        self.durable_subscriptions = Some(DurableSubscriptions::load(path.as_ref(), operators)?);
+        Ok(())
+    }
+
+    fn durable_subscriptions_mut(&mut self) -> Result<&mut DurableSubscriptions, ManyError> {
+        self.durable_subscriptions
+            .as_mut()
+            .ok_or_else(errors::durable_subscriptions_unsupported)
+    }
+
+    pub fn create_durable_subscription(
+        &mut self,
+        owner: &Address,
+        args: events::CreateSubscriptionArgs,
+    ) -> Result<SubscriptionId, ManyError> {
+        if owner.is_anonymous() {
+            return Err(ManyError::sender_cannot_be_anonymous());
+        }
+        if !self.durable_subscriptions_mut()?.operators.contains(owner) {
+            return Err(errors::not_subscription_operator());
+        }
+        if !DurableSubscription::is_valid_target(&args.target) {
+            return Err(errors::invalid_subscription_target(args.target));
+        }
+        if !is_public_target(&args.target) {
+            return Err(errors::private_subscription_target(args.target));
+        }
+        let cursor = match args.after {
+            Some(after) => Some(after),
+            None => self.last_event_id()?,
+        };
+
+        let subscriptions = self.durable_subscriptions_mut()?;
+        if subscriptions.registry.subscriptions.len() >= MAXIMUM_SUBSCRIPTION_COUNT {
+            return Err(errors::too_many_subscriptions());
+        }
+        let id = subscriptions.registry.next_id;
+        subscriptions.registry.next_id += 1;
+        subscriptions.registry.subscriptions.insert(
+            id,
+            DurableSubscription {
+                owner: *owner,
+                filter: args.filter,
+                target: args.target,
+                cursor,
+            },
+        );
+        subscriptions.save()?;
+        Ok(id)
+    }
+
+    pub fn durable_subscriptions_of(
+        &self,
+        owner: &Address,
+    ) -> Result<BTreeMap<SubscriptionId, DurableSubscription>, ManyError> {
+        let subscriptions = self
+            .durable_subscriptions
+            .as_ref()
+            .ok_or_else(errors::durable_subscriptions_unsupported)?;
+        Ok(subscriptions
+            .registry
+            .subscriptions
+            .iter()
+            .filter(|(_, s)| &s.owner == owner)
+            .map(|(id, s)| (*id, s.clone()))
+            .collect())
+    }
+
+    pub fn delete_durable_subscription(
+        &mut self,
+        owner: &Address,
+        id: SubscriptionId,
+    ) -> Result<(), ManyError> {
+        let subscriptions = self.durable_subscriptions_mut()?;
+        subscriptions.get_owned(owner, id)?;
+        subscriptions.registry.subscriptions.remove(&id);
+        subscriptions.save()
+    }
+
+    /// The ids of the durable subscriptions of this node.
+    pub fn durable_subscription_ids(&self) -> Vec<SubscriptionId> {
+        self.durable_subscriptions
+            .as_ref()
+            .map_or_else(Vec::new, |s| {
+                s.registry.subscriptions.keys().copied().collect()
+            })
+    }
+
+    /// The committed events to deliver to the target of a durable
+    /// subscription, after its cursor, or [None] if there are no new events
+    /// or the subscription does not exist. This reads at most
+    /// [MAXIMUM_DELIVERY_SCAN] events.
+    pub fn pending_delivery(
+        &self,
+        id: SubscriptionId,
+    ) -> Result<Option<PendingDelivery>, ManyError> {
+        let subscription = match self
+            .durable_subscriptions
+            .as_ref()
+            .and_then(|s| s.registry.subscriptions.get(&id))
+        {
+            Some(subscription) => subscription,
+            None => return Ok(None),
+        };
+
+        let range = CborRange {
+            start: subscription
+                .cursor
+                .clone()
+                .map_or(Bound::Unbounded, Bound::Excluded),
+            end: Bound::Unbounded,
+        };
+
+        let mut events = Vec::new();
+        let mut cursor = None;
+        for item in self
+            .iter_events(range, SortOrder::Ascending)
+            .take(MAXIMUM_DELIVERY_SCAN)
+        {
+            let (_, value) = item.map_err(ManyError::unknown)?;
+            let event = minicbor::decode::<EventLog>(value.as_slice())
+                .map_err(ManyError::deserialization_error)?;
+            cursor = Some(event.id.clone());
+            if subscription.filter.matches(&event) {
+                events.push(event);
+                if events.len() >= MAXIMUM_DELIVERY_SIZE {
+                    break;
+                }
+            }
+        }
+
+        Ok(cursor.map(|cursor| PendingDelivery {
+            id,
+            target: subscription.target.clone(),
+            events,
+            cursor,
+        }))
+    }
+
+    /// The pending deliveries of all the durable subscriptions, see
+    /// [LedgerStorage::pending_delivery].
+    pub fn pending_deliveries(&self) -> Result<Vec<PendingDelivery>, ManyError> {
+        self.durable_subscription_ids()
+            .into_iter()
+            .filter_map(|id| self.pending_delivery(id).transpose())
+            .collect()
+    }
+
+    /// Move the cursor of a durable subscription once its target acknowledged
+    /// the events up to `cursor`. Cursors never move back, and subscriptions
+    /// deleted in the meantime are ignored.
+    pub fn acknowledge_delivery(
+        &mut self,
+        id: SubscriptionId,
+        cursor: EventId,
+    ) -> Result<(), ManyError> {
+        let subscriptions = self.durable_subscriptions_mut()?;
+        let subscription = match subscriptions.registry.subscriptions.get_mut(&id) {
+            Some(subscription) => subscription,
+            None => return Ok(()),
+        };
+        if subscription.cursor.as_ref().map_or(false, |current| {
+            key_for_event(current.clone()) >= key_for_event(cursor.clone())
+        }) {
+            return Ok(());
+        }
+        subscription.cursor = Some(cursor);
+        subscriptions.save()
+    }
+}
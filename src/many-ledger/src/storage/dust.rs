@@ -0,0 +1,140 @@
+use crate::error;
+use crate::migration::dust::{DustPolicy, DUST_REAPING_MIGRATION};
+use crate::storage::iterator::LedgerIterator;
+use crate::storage::{key_for_account_balance, parse_balance_key, LedgerStorage};
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::events::{AddressContainer, EventInfo, EventLog};
+use many_types::ledger::TokenAmount;
+use merk::{BatchEntry, Op};
+
+pub const ACTIVITY_ROOT: &str = "/activity/";
+pub const DUST_CURSOR_ROOT: &str = "/config/dust_cursor";
+
+/// The maximum number of balances checked for dust in a block. The sweep
+/// resumes where it stopped in the next block, and restarts once it reaches
+/// the last balance.
+pub const DUST_REAP_BATCH_SIZE: usize = 100;
+
+fn key_for_activity(address: &Address) -> Vec<u8> {
+    format!("{ACTIVITY_ROOT}{address}").into_bytes()
+}
+
+impl LedgerStorage {
+    pub fn dust_policy(&self) -> Result<Option<DustPolicy>, ManyError> {
+        if !self.migrations.is_active(&DUST_REAPING_MIGRATION) {
+            return Ok(None);
+        }
+        self.migrations
+            .values()
+            .find(|m| m.name() == DUST_REAPING_MIGRATION.name())
+            .map(|m| DustPolicy::from_extra(&m.metadata().extra))
+            .transpose()
+    }
+
+    /// The last time an address was part of an event, in seconds.
+    pub fn get_last_activity(&self, address: &Address) -> Result<Option<u64>, ManyError> {
+        Ok(self
+            .persistent_store
+            .get(&key_for_activity(address))
+            .map_err(error::storage_get_failed)?
+            .map(|x| {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(x.as_slice());
+                u64::from_be_bytes(bytes)
+            }))
+    }
+
+    /// The entries recording the activity of the addresses of an event, to
+    /// write with the event.
+    pub(crate) fn activity_for_event(
+        &self,
+        event: &EventLog,
+    ) -> Result<Vec<BatchEntry>, ManyError> {
+        if !self.migrations.is_active(&DUST_REAPING_MIGRATION) {
+            return Ok(vec![]);
+        }
+        let time = event.time.secs().to_be_bytes().to_vec();
+        Ok(event
+            .content
+            .addresses()
+            .iter()
+            .map(|address| (key_for_activity(address), Op::Put(time.clone())))
+            .collect())
+    }
+
+    /// Check the next balances for dust, and sweep the dust of inactive
+    /// addresses into the community pool. Addresses without any recorded
+    /// activity start being tracked instead.
+    pub fn reap_dust(&mut self) -> Result<(), ManyError> {
+        let policy = match self.dust_policy()? {
+            Some(policy) => policy,
+            None => return Ok(()),
+        };
+        let now = self.now().secs();
+
+        let cursor = self
+            .persistent_store
+            .get(DUST_CURSOR_ROOT.as_bytes())
+            .map_err(error::storage_get_failed)?;
+        let balances = LedgerIterator::balances_after(&self.persistent_store, cursor)
+            .take(DUST_REAP_BATCH_SIZE)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(ManyError::unknown)?;
+
+        for (key, value) in &balances {
+            let (address, symbol) = match parse_balance_key(key) {
+                Some(x) => x,
+                None => continue,
+            };
+            let amount = TokenAmount::from(value.clone());
+            if address == policy.community_pool || !policy.is_dust(&symbol, &amount) {
+                continue;
+            }
+
+            match self.get_last_activity(&address)? {
+                None => {
+                    self.persistent_store
+                        .apply(&[(
+                            key_for_activity(&address),
+                            Op::Put(now.to_be_bytes().to_vec()),
+                        )])
+                        .map_err(error::storage_apply_failed)?;
+                }
+                Some(last) if last.saturating_add(policy.inactivity.secs()) <= now => {
+                    let pool_key = key_for_account_balance(&policy.community_pool, &symbol);
+                    let mut pool_balance = self.get_balance(&policy.community_pool, &symbol)?;
+                    pool_balance += &amount;
+
+                    let mut batch = vec![
+                        (key.to_vec(), Op::Delete),
+                        (pool_key, Op::Put(pool_balance.to_vec())),
+                    ];
+                    // Keys in batch must be sorted.
+                    batch.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+                    self.persistent_store
+                        .apply(&batch)
+                        .map_err(error::storage_apply_failed)?;
+
+                    self.log_event(EventInfo::DustReap {
+                        account: address,
+                        symbol,
+                        amount,
+                        pool: policy.community_pool,
+                    })?;
+                }
+                Some(_) => {}
+            }
+        }
+
+        // Restart from the first balance once the sweep reached the last one.
+        let cursor = match balances.last() {
+            Some((key, _)) if balances.len() == DUST_REAP_BATCH_SIZE => Op::Put(key.to_vec()),
+            _ => Op::Delete,
+        };
+        self.persistent_store
+            .apply(&[(DUST_CURSOR_ROOT.as_bytes().to_vec(), cursor)])
+            .map_err(error::storage_apply_failed)?;
+        self.maybe_commit()
+    }
+}
@@ -1,17 +1,32 @@
 use crate::error;
+use crate::migration::event_ids::EVENT_IDS_MIGRATION;
+use crate::migration::event_pruning::{retention_blocks, EVENT_PRUNING_MIGRATION};
+use crate::migration::intent_hashes::INTENT_HASHES_MIGRATION;
 use crate::storage::iterator::LedgerIterator;
 use crate::storage::LedgerStorage;
 use many_error::ManyError;
+use many_identity::Address;
 use many_modules::events;
 use many_modules::events::EventId;
-use many_types::{CborRange, SortOrder};
-use merk::Op;
+use many_modules::ledger::{event_not_found, Receipt};
+use many_types::proof::Proof;
+use many_types::{CborRange, SortOrder, Timestamp};
+use merk::{BatchEntry, Op};
+use minicbor::bytes::ByteVec;
+use sha3::{Digest, Sha3_256};
 
 pub(crate) const EVENTS_ROOT: &[u8] = b"/events/";
 pub(crate) const EVENT_COUNT_ROOT: &[u8] = b"/events_count";
+pub(crate) const EVENTS_ARCHIVED_COUNT_ROOT: &[u8] = b"/events_archived_count";
+pub(crate) const EVENTS_BY_INTENT_ROOT: &[u8] = b"/events_by_intent/";
 
 // Left-shift the height by this amount of bits
-pub(crate) const HEIGHT_EVENTID_SHIFT: u64 = 32;
+pub(crate) const HEIGHT_EVENTID_SHIFT: u64 = events::EVENT_ID_HEIGHT_SHIFT;
+
+/// Events imported from a legacy ledger keep their original IDs, which must be
+/// below this value. Blocks only use event IDs starting at height 1, so this
+/// range is never used by the ledger itself.
+pub const IMPORTED_EVENT_ID_LIMIT: u64 = 1 << HEIGHT_EVENTID_SHIFT;
 
 /// Number of bytes in an event ID when serialized. Keys smaller than this
 /// will have `\0` prepended, and keys larger will be cut to this number of
@@ -32,7 +47,165 @@ pub(super) fn key_for_event(id: events::EventId) -> Vec<u8> {
     [EVENTS_ROOT.to_vec(), exp_id.to_vec()].concat()
 }
 
+pub(super) fn key_prefix_for_intent(intent_hash: &[u8]) -> Vec<u8> {
+    [EVENTS_BY_INTENT_ROOT, intent_hash].concat()
+}
+
+/// Returns the key indexing an event by the intent hash of its request. Keys
+/// of a same intent sort by event ID.
+fn key_for_intent_index(intent_hash: &[u8], id: events::EventId) -> Vec<u8> {
+    [
+        key_prefix_for_intent(intent_hash),
+        key_for_event(id)[EVENTS_ROOT.len()..].to_vec(),
+    ]
+    .concat()
+}
+
+/// An event imported from a legacy ledger, with its original ID and time.
+#[derive(Clone, Debug)]
+pub struct ImportedEvent {
+    pub id: u64,
+    pub time: Timestamp,
+    pub content: events::EventInfo,
+}
+
 impl LedgerStorage {
+    /// Import the history of a legacy ledger at genesis. Imported events are
+    /// only added to the event log; they are not executed and do not change
+    /// balances or any other state.
+    pub fn with_imported_events(
+        mut self,
+        imported: Option<Vec<ImportedEvent>>,
+    ) -> Result<Self, ManyError> {
+        let imported = match imported {
+            Some(imported) => imported,
+            None => return Ok(self),
+        };
+        if let Some(event) = imported.first() {
+            if self.nb_events()? != 0 {
+                return Err(error::invalid_imported_event(
+                    event.id,
+                    "events can only be imported at genesis",
+                ));
+            }
+            // Without the event IDs migration, the IDs of the first blocks
+            // could collide with the imported ones.
+            if !self.migrations.is_active(&EVENT_IDS_MIGRATION) {
+                return Err(error::invalid_imported_event(
+                    event.id,
+                    "imports require the Event IDs Migration to be active at genesis",
+                ));
+            }
+        }
+
+        let mut batch: Vec<BatchEntry> = Vec::new();
+        let mut previous: Option<&ImportedEvent> = None;
+        for event in &imported {
+            if event.id >= IMPORTED_EVENT_ID_LIMIT {
+                return Err(error::invalid_imported_event(
+                    event.id,
+                    format!("ID must be below {IMPORTED_EVENT_ID_LIMIT}"),
+                ));
+            }
+            if let Some(previous) = previous {
+                if event.id <= previous.id {
+                    return Err(error::invalid_imported_event(
+                        event.id,
+                        "IDs must be strictly increasing",
+                    ));
+                }
+                if event.time < previous.time {
+                    return Err(error::invalid_imported_event(
+                        event.id,
+                        "timestamps must not decrease",
+                    ));
+                }
+            }
+            previous = Some(event);
+
+            let log = events::EventLog {
+                id: EventId::from(event.id),
+                time: event.time,
+                content: event.content.clone(),
+                intent_hash: None,
+            };
+            batch.push((
+                key_for_event(log.id.clone()),
+                Op::Put(self.encode_value(&log)?),
+            ));
+        }
+
+        if let Some(last) = previous {
+            // Events logged after the import follow the imported ones.
+            let last = EventId::from(last.id);
+            if key_for_event(last.clone()) > key_for_event(self.latest_tid.clone()) {
+                self.latest_tid = last;
+            }
+        }
+
+        batch.push((
+            EVENT_COUNT_ROOT.to_vec(),
+            Op::Put((imported.len() as u64).to_be_bytes().to_vec()),
+        ));
+        batch.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+
+        self.persistent_store
+            .apply(batch.as_slice())
+            .map_err(error::storage_apply_failed)?;
+
+        Ok(self)
+    }
+
+    /// Reset the event IDs for the block following `height`.
+    ///
+    /// Once the event IDs migration is active, the events of a block have the
+    /// IDs `(height, 1)`, `(height, 2)`, etc. Before, IDs use the height of
+    /// the previous block, so the first two blocks share the same IDs.
+    ///
+    /// Without a blockchain, events are committed as they are logged, so IDs
+    /// follow the last event in storage to survive a restart.
+    pub(crate) fn reset_event_ids(&mut self, height: u64) -> Result<(), ManyError> {
+        let base = if self.migrations.is_active(&EVENT_IDS_MIGRATION) {
+            EventId::new(height + 1, 0)
+        } else {
+            EventId::from(height.saturating_sub(1) << HEIGHT_EVENTID_SHIFT)
+        };
+
+        self.latest_tid = if self.blockchain {
+            base
+        } else {
+            // Keys are padded, so they compare like the IDs' values.
+            match self.last_event_id()? {
+                Some(last) if key_for_event(last.clone()) > key_for_event(base.clone()) => last,
+                _ => base,
+            }
+        };
+        Ok(())
+    }
+
+    pub(super) fn last_event_id(&self) -> Result<Option<EventId>, ManyError> {
+        LedgerIterator::events_scoped_by_id(
+            &self.persistent_store,
+            CborRange::default(),
+            SortOrder::Descending,
+        )
+        .next()
+        .transpose()
+        .map_err(ManyError::unknown)?
+        .map(|(_, v)| {
+            minicbor::decode::<events::EventLog>(v.as_slice())
+                .map(|log| log.id)
+                .map_err(ManyError::deserialization_error)
+        })
+        .transpose()
+    }
+
+    /// The ID of the next event, without allocating it. IDs are only allocated
+    /// when an event is logged, so failed transactions do not leave gaps.
+    pub(crate) fn next_event_id(&self) -> events::EventId {
+        self.latest_tid.clone() + 1
+    }
+
     pub(crate) fn new_event_id(&mut self) -> events::EventId {
         self.latest_tid += 1;
         self.latest_tid.clone()
@@ -51,28 +224,172 @@ impl LedgerStorage {
 
     pub(crate) fn log_event(&mut self, content: events::EventInfo) -> Result<(), ManyError> {
         let current_nb_events = self.nb_events()?;
+        let intent_hash = if self.migrations.is_active(&INTENT_HASHES_MIGRATION) {
+            many_protocol::context::current_intent_hash().map(ByteVec::from)
+        } else {
+            None
+        };
         let event = events::EventLog {
             id: self.new_event_id(),
             time: self.now(),
             content,
+            intent_hash,
         };
 
+        // Hook records, activity and indices are written in the same batch as
+        // the event.
+        let mut batch = self.hook_records_for_event(&event)?;
+        batch.extend(self.activity_for_event(&event)?);
+        if let Some(intent_hash) = &event.intent_hash {
+            batch.push((
+                key_for_intent_index(intent_hash, event.id.clone()),
+                Op::Put(key_for_event(event.id.clone())),
+            ));
+        }
+        batch.push((
+            key_for_event(event.id.clone()),
+            Op::Put(self.encode_value(&event)?),
+        ));
+        batch.push((
+            EVENT_COUNT_ROOT.to_vec(),
+            Op::Put((current_nb_events + 1).to_be_bytes().to_vec()),
+        ));
+
+        // Keys in batch must be sorted.
+        batch.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
         self.persistent_store
-            .apply(&[
-                (
-                    key_for_event(event.id.clone()),
-                    Op::Put(minicbor::to_vec(&event).map_err(ManyError::serialization_error)?),
-                ),
-                (
-                    EVENT_COUNT_ROOT.to_vec(),
-                    Op::Put((current_nb_events + 1).to_be_bytes().to_vec()),
-                ),
-            ])
+            .apply(&batch)
             .map_err(error::storage_apply_failed)?;
 
+        if !self.event_subscriptions.is_empty() {
+            self.pending_events.push(event);
+        }
+
         self.maybe_commit()
     }
 
+    pub fn subscribe_events(
+        &mut self,
+        owner: &Address,
+        filter: events::SubscriptionFilter,
+    ) -> Result<events::SubscriptionId, ManyError> {
+        self.event_subscriptions.subscribe(owner, filter)
+    }
+
+    pub fn poll_events(
+        &mut self,
+        owner: &Address,
+        id: events::SubscriptionId,
+        count: Option<u64>,
+    ) -> Result<events::PollReturn, ManyError> {
+        self.event_subscriptions.poll(owner, id, count)
+    }
+
+    pub fn unsubscribe_events(
+        &mut self,
+        owner: &Address,
+        id: events::SubscriptionId,
+    ) -> Result<(), ManyError> {
+        self.event_subscriptions.unsubscribe(owner, id)
+    }
+
+    /// A receipt of the event, with a proof of its inclusion in the current
+    /// state.
+    pub fn get_receipt(&self, id: EventId) -> Result<Receipt, ManyError> {
+        let key = key_for_event(id.clone());
+        let event = self
+            .persistent_store
+            .get(&key)
+            .map_err(error::storage_get_failed)?
+            .ok_or_else(|| event_not_found(hex::encode(&id)))?;
+
+        Ok(Receipt {
+            id,
+            event_hash: Sha3_256::digest(event).to_vec().into(),
+            height: self.get_height()?,
+            app_hash: self.persistent_store.root_hash().to_vec().into(),
+            proof: Proof {
+                operations: self.prove_keys([key])?,
+            },
+        })
+    }
+
+    pub fn nb_archived_events(&self) -> Result<u64, ManyError> {
+        self.persistent_store
+            .get(EVENTS_ARCHIVED_COUNT_ROOT)
+            .map_err(error::storage_get_failed)?
+            .map_or(Ok(0), |x| {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(x.as_slice());
+                Ok(u64::from_be_bytes(bytes))
+            })
+    }
+
+    pub fn events_archive_info(&self) -> Result<events::ArchiveInfoReturn, ManyError> {
+        let archived = self.nb_archived_events()?;
+        let oldest_retained = LedgerIterator::events_scoped_by_id(
+            &self.persistent_store,
+            CborRange::default(),
+            SortOrder::Ascending,
+        )
+        .next()
+        .transpose()
+        .map_err(ManyError::unknown)?
+        .map(|(_, v)| {
+            minicbor::decode::<events::EventLog>(v.as_slice())
+                .map(|log| log.id)
+                .map_err(ManyError::deserialization_error)
+        })
+        .transpose()?;
+
+        let retention_blocks = if self.migrations.is_active(&EVENT_PRUNING_MIGRATION) {
+            self.migrations
+                .values()
+                .find(|m| m.name() == EVENT_PRUNING_MIGRATION.name())
+                .map(|m| retention_blocks(&m.metadata().extra))
+                .transpose()?
+        } else {
+            None
+        };
+
+        Ok(events::ArchiveInfoReturn {
+            retained: self.nb_events()?.saturating_sub(archived),
+            archived,
+            oldest_retained,
+            retention_blocks,
+        })
+    }
+
+    /// The first [events::MAXIMUM_INTENT_EVENTS] events caused by the request
+    /// with this intent hash, in order. Events which were pruned are skipped.
+    pub fn find_events_by_intent(
+        &self,
+        intent_hash: &[u8],
+    ) -> Result<Vec<events::EventLog>, ManyError> {
+        // Another length would match the index of every hash it prefixes.
+        if intent_hash.len() != events::INTENT_HASH_LENGTH {
+            return Err(events::errors::invalid_intent_hash(intent_hash.len()));
+        }
+        LedgerIterator::events_by_intent(&self.persistent_store, intent_hash)
+            .filter_map(|item| {
+                let event_key = match item {
+                    Ok((_, event_key)) => event_key,
+                    Err(e) => return Some(Err(ManyError::unknown(e))),
+                };
+                self.persistent_store
+                    .get(&event_key)
+                    .map_err(error::storage_get_failed)
+                    .and_then(|value| {
+                        value
+                            .map(|v| minicbor::decode(&v).map_err(ManyError::deserialization_error))
+                            .transpose()
+                    })
+                    .transpose()
+            })
+            .take(events::MAXIMUM_INTENT_EVENTS)
+            .collect()
+    }
+
     pub fn iter_multisig(&self, order: SortOrder) -> LedgerIterator {
         LedgerIterator::all_multisig(&self.persistent_store, order)
     }
@@ -0,0 +1,151 @@
+use crate::error;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_modules::ledger::extended_info::visual_logo::{SingleVisualTokenLogo, MAX_IMAGE_SIZE};
+use many_modules::ledger::extended_info::TokenExtendedInfo;
+use merk::Op;
+use minicbor::{Decode, Encode};
+use std::collections::BTreeSet;
+
+pub const EXTENDED_INFO_POLICY_ROOT: &str = "/config/extended_info_policy";
+
+/// Limits on the contents of token extended info, on top of the structural
+/// limits of the extended info types themselves.
+#[derive(Clone, Debug, Decode, Encode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ExtendedInfoPolicy {
+    /// Maximum size of an image logo, in bytes. Cannot be over
+    /// [MAX_IMAGE_SIZE].
+    #[n(0)]
+    pub max_image_size: u64,
+
+    /// Content types allowed for image logos (inline or referenced). All if
+    /// unset.
+    #[n(1)]
+    pub content_types: Option<BTreeSet<String>>,
+
+    /// Inclusive ranges of code points allowed for character logos. All
+    /// non-control characters if unset.
+    #[n(2)]
+    pub unicode_ranges: Option<Vec<(u32, u32)>>,
+
+    /// Maximum size of each part of the extended info memo, in bytes. Only the
+    /// memo limits of the server apply if unset.
+    #[n(3)]
+    pub max_memo_size: Option<u64>,
+}
+
+impl Default for ExtendedInfoPolicy {
+    fn default() -> Self {
+        Self {
+            max_image_size: MAX_IMAGE_SIZE as u64,
+            content_types: None,
+            unicode_ranges: None,
+            max_memo_size: None,
+        }
+    }
+}
+
+impl ExtendedInfoPolicy {
+    fn check_logo(&self, logo: &SingleVisualTokenLogo) -> Result<(), ManyError> {
+        let content_type = match logo {
+            SingleVisualTokenLogo::UnicodeChar(c) => {
+                let allowed = !c.is_control()
+                    && self.unicode_ranges.as_ref().map_or(true, |ranges| {
+                        ranges
+                            .iter()
+                            .any(|(start, end)| (*start..=*end).contains(&u32::from(*c)))
+                    });
+                return if allowed {
+                    Ok(())
+                } else {
+                    Err(error::logo_char_not_allowed(format!("U+{:04X}", *c as u32)))
+                };
+            }
+            SingleVisualTokenLogo::Image {
+                content_type,
+                binary,
+                ..
+            } => {
+                if binary.len() as u64 > self.max_image_size {
+                    return Err(error::logo_image_too_large(
+                        binary.len(),
+                        self.max_image_size,
+                    ));
+                }
+                content_type
+            }
+            SingleVisualTokenLogo::ImageRef { content_type, .. } => content_type,
+        };
+
+        match &self.content_types {
+            Some(allowed) if !allowed.contains(content_type) => {
+                Err(error::logo_content_type_not_allowed(content_type))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl LedgerStorage {
+    /// Store the extended info policy of this server. Nothing is stored if
+    /// unset, so existing stores keep the defaults and their hash.
+    pub fn with_extended_info_policy(
+        mut self,
+        policy: Option<ExtendedInfoPolicy>,
+    ) -> Result<Self, ManyError> {
+        if let Some(policy) = policy {
+            if policy.max_image_size > MAX_IMAGE_SIZE as u64 {
+                return Err(error::invalid_extended_info_policy(format!(
+                    "image size limit cannot be over {MAX_IMAGE_SIZE}"
+                )));
+            }
+            if let Some(ranges) = &policy.unicode_ranges {
+                if ranges.iter().any(|(start, end)| start > end) {
+                    return Err(error::invalid_extended_info_policy(
+                        "unicode ranges must start before they end",
+                    ));
+                }
+            }
+
+            self.persistent_store
+                .apply(&[(
+                    EXTENDED_INFO_POLICY_ROOT.as_bytes().to_vec(),
+                    Op::Put(self.encode_value(policy)?),
+                )])
+                .map_err(error::storage_apply_failed)?;
+        }
+
+        Ok(self)
+    }
+
+    pub fn extended_info_policy(&self) -> Result<ExtendedInfoPolicy, ManyError> {
+        self.persistent_store
+            .get(EXTENDED_INFO_POLICY_ROOT.as_bytes())
+            .map_err(error::storage_get_failed)?
+            .map_or(Ok(ExtendedInfoPolicy::default()), |bytes| {
+                minicbor::decode(&bytes).map_err(ManyError::deserialization_error)
+            })
+    }
+
+    /// Verify the contents of token extended info are allowed by the policy
+    /// and memo limits of this server.
+    pub fn check_extended_info(&self, extended_info: &TokenExtendedInfo) -> Result<(), ManyError> {
+        let policy = self.extended_info_policy()?;
+
+        if let Some(memo) = extended_info.memo() {
+            self.check_memo(Some(memo))?;
+            if let Some(max) = policy.max_memo_size {
+                let size = memo.max_part_size() as u64;
+                if size > max {
+                    return Err(error::extended_info_memo_too_large(size, max));
+                }
+            }
+        }
+
+        if let Some(logos) = extended_info.visual_logo() {
+            logos.iter().try_for_each(|logo| policy.check_logo(logo))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use crate::error;
+use crate::migration::freeze::FREEZE_MIGRATION;
+use crate::storage::iterator::LedgerIterator;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::events::EventInfo;
+use many_modules::ledger::{address_already_frozen, address_frozen, address_not_frozen};
+use many_types::Memo;
+use merk::Op;
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+pub const FROZEN_ROOT: &str = "/frozen/";
+
+fn key_for_frozen(address: &Address) -> Vec<u8> {
+    format!("{FROZEN_ROOT}{address}").into_bytes()
+}
+
+impl LedgerStorage {
+    pub fn is_frozen(&self, address: &Address) -> Result<bool, ManyError> {
+        Ok(self
+            .persistent_store
+            .get(&key_for_frozen(address))
+            .map_err(error::storage_get_failed)?
+            .is_some())
+    }
+
+    pub fn get_frozen_addresses(&self) -> Result<BTreeSet<Address>, ManyError> {
+        let mut addresses = BTreeSet::new();
+        for item in LedgerIterator::all_frozen_addresses(&self.persistent_store) {
+            let (k, _) = item.map_err(ManyError::unknown)?;
+            addresses.insert(Address::from_str(
+                std::str::from_utf8(&k.as_ref()[FROZEN_ROOT.len()..])
+                    .map_err(ManyError::deserialization_error)?,
+            )?);
+        }
+        Ok(addresses)
+    }
+
+    pub fn freeze_address(
+        &mut self,
+        address: Address,
+        memo: Option<Memo>,
+    ) -> Result<(), ManyError> {
+        if self.is_frozen(&address)? {
+            return Err(address_already_frozen(address));
+        }
+
+        self.persistent_store
+            .apply(&[(key_for_frozen(&address), Op::Put(vec![]))])
+            .map_err(error::storage_apply_failed)?;
+
+        self.log_event(EventInfo::AddressFreeze { address, memo })?;
+        self.maybe_commit()
+    }
+
+    pub fn unfreeze_address(
+        &mut self,
+        address: Address,
+        memo: Option<Memo>,
+    ) -> Result<(), ManyError> {
+        if !self.is_frozen(&address)? {
+            return Err(address_not_frozen(address));
+        }
+
+        self.persistent_store
+            .apply(&[(key_for_frozen(&address), Op::Delete)])
+            .map_err(error::storage_apply_failed)?;
+
+        self.log_event(EventInfo::AddressUnfreeze { address, memo })?;
+        self.maybe_commit()
+    }
+
+    /// Refuse the operation if any of the addresses is frozen. Does nothing
+    /// until the freeze migration is active.
+    pub fn check_not_frozen<'a>(
+        &self,
+        addresses: impl IntoIterator<Item = &'a Address>,
+    ) -> Result<(), ManyError> {
+        if !self.migrations.is_active(&FREEZE_MIGRATION) {
+            return Ok(());
+        }
+
+        for address in addresses {
+            if self.is_frozen(address)? {
+                return Err(address_frozen(address));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use crate::error;
+use crate::migration::account_hooks::ACCOUNT_HOOKS_MIGRATION;
+use crate::storage::event::{key_for_event, EVENTS_ROOT};
+use crate::storage::iterator::LedgerIterator;
+use crate::storage::kvstore::key_for_account_kvstore;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::account::features::hooks::{AccountHooks, HookRecord};
+use many_modules::events::{AddressContainer, EventId, EventLog};
+use merk::{BatchEntry, Op};
+
+/// The prefix of the hook records in the kvstore namespace of an account.
+pub const HOOK_RECORDS_KEY_PREFIX: &[u8] = b"hooks/";
+
+/// Returns the prefix of all the hook records of an account, in its kvstore
+/// namespace. Nothing else writes under it.
+pub(crate) fn key_prefix_for_hook_records(account: &Address) -> Vec<u8> {
+    key_for_account_kvstore(account, HOOK_RECORDS_KEY_PREFIX)
+}
+
+/// Returns the key, in the kvstore namespace of the account, of the record
+/// written when an event fired a hook. Records are sorted by event ID, like
+/// events.
+pub fn hook_record_key(id: EventId) -> Vec<u8> {
+    let event_key = key_for_event(id);
+    [HOOK_RECORDS_KEY_PREFIX, &event_key[EVENTS_ROOT.len()..]].concat()
+}
+
+/// Returns the storage key for the record written when an event fired a hook.
+pub(crate) fn key_for_hook_record(account: &Address, id: EventId) -> Vec<u8> {
+    key_for_account_kvstore(account, &hook_record_key(id))
+}
+
+impl LedgerStorage {
+    /// Returns the hook records to write with an event, for every account the
+    /// event is about which registered a matching trigger.
+    pub(super) fn hook_records_for_event(
+        &self,
+        event: &EventLog,
+    ) -> Result<Vec<BatchEntry>, ManyError> {
+        if !self.migrations.is_active(&ACCOUNT_HOOKS_MIGRATION) {
+            return Ok(vec![]);
+        }
+
+        let mut batch = Vec::new();
+        for address in event.content.addresses() {
+            if let Ok((account, _)) = self.get_account(&address) {
+                let trigger = account
+                    .features
+                    .get::<AccountHooks>()
+                    .ok()
+                    .and_then(|hooks| hooks.trigger_for(&address, &event.content));
+                if let Some(trigger) = trigger {
+                    let record = HookRecord {
+                        trigger,
+                        event: event.clone(),
+                    };
+                    batch.push((
+                        key_for_hook_record(&address, event.id.clone()),
+                        Op::Put(self.encode_value(record)?),
+                    ));
+                }
+            }
+        }
+        Ok(batch)
+    }
+
+    /// Returns up to `count` hook records of an account, oldest first,
+    /// starting after the event `after`.
+    pub fn hook_records(
+        &self,
+        account: &Address,
+        after: Option<EventId>,
+        count: usize,
+    ) -> Result<Vec<HookRecord>, ManyError> {
+        LedgerIterator::hook_records(&self.persistent_store, account, after, None)
+            .take(count)
+            .map(|item| {
+                let (_, value) = item.map_err(error::storage_get_failed)?;
+                minicbor::decode(&value).map_err(ManyError::deserialization_error)
+            })
+            .collect()
+    }
+
+    /// Remove the hook records of an account up to, and including, the event
+    /// `up_to`. Returns the number of records removed.
+    pub fn clear_hook_records(
+        &mut self,
+        account: &Address,
+        up_to: EventId,
+    ) -> Result<u64, ManyError> {
+        let batch =
+            LedgerIterator::hook_records(&self.persistent_store, account, None, Some(up_to))
+                .map(|item| {
+                    item.map(|(key, _)| (key.to_vec(), Op::Delete))
+                        .map_err(error::storage_get_failed)
+                })
+                .collect::<Result<Vec<BatchEntry>, _>>()?;
+        let count = batch.len() as u64;
+
+        self.persistent_store
+            .apply(&batch)
+            .map_err(error::storage_apply_failed)?;
+        self.maybe_commit().map(|_| count)
+    }
+
+    /// Remove all the hook records of an account, without committing.
+    pub(crate) fn delete_hook_records(&mut self, account: &Address) -> Result<(), ManyError> {
+        let batch = LedgerIterator::hook_records(&self.persistent_store, account, None, None)
+            .map(|item| {
+                item.map(|(key, _)| (key.to_vec(), Op::Delete))
+                    .map_err(error::storage_get_failed)
+            })
+            .collect::<Result<Vec<BatchEntry>, _>>()?;
+
+        self.persistent_store
+            .apply(&batch)
+            .map_err(error::storage_apply_failed)
+    }
+}
@@ -1,14 +1,29 @@
 use crate::error;
+use crate::migration::idstore_wordlists::{WordlistPolicy, IDSTORE_WORDLISTS_MIGRATION};
 use crate::storage::LedgerStorage;
 use base64::{engine::general_purpose, Engine as _};
 use many_error::ManyError;
 use many_identity::Address;
 use many_modules::idstore;
+use many_types::Timestamp;
 use merk::Op;
 use std::collections::BTreeMap;
 
 pub(crate) const IDSTORE_ROOT: &[u8] = b"/idstore/";
+pub(crate) const IDSTORE_RECALL_PHRASE_ROOT: &[u8] = b"/idstore/00";
 pub(crate) const IDSTORE_SEED_ROOT: &[u8] = b"/config/idstore_seed";
+pub(crate) const IDSTORE_STATS_ROOT: &[u8] = b"/config/idstore_stats";
+
+/// Statistics of the recall phrases of a namespace.
+#[derive(Clone, Debug, Default, minicbor::Encode, minicbor::Decode)]
+#[cbor(map)]
+pub(crate) struct IdStoreStats {
+    #[n(0)]
+    pub count: u64,
+
+    #[n(1)]
+    pub last_assignment: Option<Timestamp>,
+}
 
 #[derive(Clone, minicbor::Encode, minicbor::Decode)]
 #[cbor(map)]
@@ -23,6 +38,7 @@ struct CredentialStorage {
 enum IdStoreRootSeparator {
     RecallPhrase,
     Address,
+    NamespacedRecallPhrase,
 }
 
 impl IdStoreRootSeparator {
@@ -30,11 +46,52 @@ impl IdStoreRootSeparator {
         match *self {
             IdStoreRootSeparator::RecallPhrase => b"00",
             IdStoreRootSeparator::Address => b"01",
+            IdStoreRootSeparator::NamespacedRecallPhrase => b"02",
         }
     }
 }
 
+/// The key of a value of a namespace. Values of the default namespace keep
+/// their original key.
+fn namespaced_key(root: &[u8], namespace: Option<&str>) -> Vec<u8> {
+    match namespace {
+        None => root.to_vec(),
+        Some(namespace) => [root, b"/", namespace.as_bytes()].concat(),
+    }
+}
+
+/// The storage key of a recall phrase. Namespaces cannot contain `/`, so
+/// phrases of different namespaces cannot collide.
+fn recall_phrase_key(recall_phrase_cbor: &[u8], namespace: Option<&str>) -> Vec<u8> {
+    match namespace {
+        None => [
+            IDSTORE_ROOT,
+            IdStoreRootSeparator::RecallPhrase.value(),
+            recall_phrase_cbor,
+        ]
+        .concat(),
+        Some(namespace) => [
+            IDSTORE_ROOT,
+            IdStoreRootSeparator::NamespacedRecallPhrase.value(),
+            namespace.as_bytes(),
+            b"/",
+            recall_phrase_cbor,
+        ]
+        .concat(),
+    }
+}
+
 impl LedgerStorage {
+    /// The wordlists recall phrases can be generated from, from the migration
+    /// config, whether the migration is active yet or not.
+    pub fn idstore_wordlist_policy(&self) -> Result<Option<WordlistPolicy>, ManyError> {
+        self.migrations
+            .values()
+            .find(|m| m.name() == IDSTORE_WORDLISTS_MIGRATION.name())
+            .map(|m| WordlistPolicy::from_extra(&m.metadata().extra))
+            .transpose()
+    }
+
     pub fn with_idstore(
         mut self,
         maybe_seed: Option<u64>,
@@ -54,8 +111,13 @@ impl LedgerStorage {
                 .collect::<BTreeMap<_, _>>()
         });
 
-        // Apply keys and seed.
+        // Apply keys and seed. The seed can never decrease, or recall phrases
+        // would be assigned again.
         if let Some(seed) = maybe_seed {
+            let current = self.idstore_seed(None)?;
+            if seed < current {
+                return Err(idstore::seed_decreased(current, seed));
+            }
             self.persistent_store
                 .apply(&[(
                     IDSTORE_SEED_ROOT.to_vec(),
@@ -74,61 +136,85 @@ impl LedgerStorage {
         Ok(self)
     }
 
-    pub(crate) fn inc_idstore_seed(&mut self) -> Result<u64, ManyError> {
-        let idstore_seed = self
-            .persistent_store
-            .get(IDSTORE_SEED_ROOT)
+    fn idstore_seed(&self, namespace: Option<&str>) -> Result<u64, ManyError> {
+        self.persistent_store
+            .get(&namespaced_key(IDSTORE_SEED_ROOT, namespace))
             .map_err(error::storage_get_failed)?
-            .map_or(0u64, |x| {
-                let mut bytes = [0u8; 8];
-                bytes.copy_from_slice(x.as_slice());
-                u64::from_be_bytes(bytes)
-            });
+            .map_or(Ok(0u64), |x| {
+                x.try_into()
+                    .map(u64::from_be_bytes)
+                    .map_err(|_| ManyError::deserialization_error("Invalid idstore seed"))
+            })
+    }
+
+    pub(crate) fn inc_idstore_seed(&mut self, namespace: Option<&str>) -> Result<u64, ManyError> {
+        let idstore_seed = self.idstore_seed(namespace)?;
+        let next = idstore_seed
+            .checked_add(1)
+            .ok_or_else(idstore::recall_phrase_generation_failed)?;
 
         self.persistent_store
             .apply(&[(
-                IDSTORE_SEED_ROOT.to_vec(),
-                Op::Put((idstore_seed + 1).to_be_bytes().to_vec()),
+                namespaced_key(IDSTORE_SEED_ROOT, namespace),
+                Op::Put(next.to_be_bytes().to_vec()),
             )])
             .map_err(error::storage_apply_failed)?;
 
         self.maybe_commit().map(|_| idstore_seed)
     }
 
+    pub(crate) fn idstore_stats(&self, namespace: Option<&str>) -> Result<IdStoreStats, ManyError> {
+        self.persistent_store
+            .get(&namespaced_key(IDSTORE_STATS_ROOT, namespace))
+            .map_err(error::storage_get_failed)?
+            .map_or(Ok(IdStoreStats::default()), |bytes| {
+                minicbor::decode(&bytes).map_err(ManyError::deserialization_error)
+            })
+    }
+
+    /// Count a recall phrase assigned in a namespace.
+    pub(crate) fn inc_idstore_stats(&mut self, namespace: Option<&str>) -> Result<(), ManyError> {
+        let mut stats = self.idstore_stats(namespace)?;
+        stats.count += 1;
+        stats.last_assignment = Some(self.now());
+
+        self.persistent_store
+            .apply(&[(
+                namespaced_key(IDSTORE_STATS_ROOT, namespace),
+                Op::Put(self.encode_value(stats)?),
+            )])
+            .map_err(error::storage_apply_failed)?;
+
+        self.maybe_commit()
+    }
+
     pub fn store(
         &mut self,
         recall_phrase: &idstore::RecallPhrase,
+        namespace: Option<&str>,
         address: &Address,
         cred_id: idstore::CredentialId,
         public_key: idstore::PublicKey,
     ) -> Result<Vec<Vec<u8>>, ManyError> {
         let recall_phrase_cbor =
             minicbor::to_vec(recall_phrase).map_err(ManyError::serialization_error)?;
+        let recall_phrase_key = recall_phrase_key(&recall_phrase_cbor, namespace);
         if self
             .persistent_store
-            .get(&recall_phrase_cbor)
+            .get(&recall_phrase_key)
             .map_err(error::storage_get_failed)?
             .is_some()
         {
             return Err(idstore::existing_entry());
         }
 
-        let value = minicbor::to_vec(CredentialStorage {
+        let value = self.encode_value(CredentialStorage {
             cred_id,
             public_key,
-        })
-        .map_err(ManyError::serialization_error)?;
+        })?;
 
         let batch = vec![
-            (
-                [
-                    IDSTORE_ROOT,
-                    IdStoreRootSeparator::RecallPhrase.value(),
-                    &recall_phrase_cbor,
-                ]
-                .concat(),
-                Op::Put(value.clone()),
-            ),
+            (recall_phrase_key.clone(), Op::Put(value.clone())),
             (
                 [
                     IDSTORE_ROOT,
@@ -147,12 +233,7 @@ impl LedgerStorage {
         self.maybe_commit().map(|_| {
             vec![
                 recall_phrase_cbor.clone(),
-                [
-                    IDSTORE_ROOT,
-                    IdStoreRootSeparator::RecallPhrase.value(),
-                    &recall_phrase_cbor,
-                ]
-                .concat(),
+                recall_phrase_key,
                 [
                     IDSTORE_ROOT,
                     IdStoreRootSeparator::Address.value(),
@@ -163,12 +244,7 @@ impl LedgerStorage {
         })
     }
 
-    fn get_from_storage(
-        &self,
-        key: &Vec<u8>,
-        sep: IdStoreRootSeparator,
-    ) -> Result<(Option<Vec<u8>>, Vec<u8>), ManyError> {
-        let key = [IDSTORE_ROOT, sep.value(), key].concat();
+    fn get_from_storage(&self, key: Vec<u8>) -> Result<(Option<Vec<u8>>, Vec<u8>), ManyError> {
         self.persistent_store
             .get(&key)
             .map_err(error::storage_get_failed)
@@ -178,11 +254,12 @@ impl LedgerStorage {
     pub fn get_from_recall_phrase(
         &self,
         recall_phrase: &idstore::RecallPhrase,
+        namespace: Option<&str>,
     ) -> Result<(idstore::CredentialId, idstore::PublicKey, Vec<u8>), ManyError> {
         let recall_phrase_cbor =
             minicbor::to_vec(recall_phrase).map_err(ManyError::serialization_error)?;
         if let (Some(value), storage_key) =
-            self.get_from_storage(&recall_phrase_cbor, IdStoreRootSeparator::RecallPhrase)?
+            self.get_from_storage(recall_phrase_key(&recall_phrase_cbor, namespace))?
         {
             let value: CredentialStorage =
                 minicbor::decode(&value).map_err(ManyError::deserialization_error)?;
@@ -196,9 +273,14 @@ impl LedgerStorage {
         &self,
         address: &Address,
     ) -> Result<(idstore::CredentialId, idstore::PublicKey, Vec<u8>), ManyError> {
-        if let (Some(value), storage_key) =
-            self.get_from_storage(&address.to_vec(), IdStoreRootSeparator::Address)?
-        {
+        if let (Some(value), storage_key) = self.get_from_storage(
+            [
+                IDSTORE_ROOT,
+                IdStoreRootSeparator::Address.value(),
+                &address.to_vec(),
+            ]
+            .concat(),
+        )? {
             let value: CredentialStorage =
                 minicbor::decode(&value).map_err(ManyError::deserialization_error)?;
             Ok((value.cred_id, value.public_key, storage_key))
@@ -0,0 +1,268 @@
+// The checks only run on their own with the `invariant_checks` feature.
+#![cfg_attr(not(feature = "invariant_checks"), allow(dead_code))]
+
+use crate::migration::tokens::TOKEN_MIGRATION;
+use crate::storage::iterator::LedgerIterator;
+use crate::storage::ledger_tokens::SYMBOLS_ROOT_DASH;
+use crate::storage::multisig::{token_from_key, MultisigTransactionStorage};
+use crate::storage::{parse_balance_key, LedgerStorage};
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::account::Role;
+use many_modules::labels::Label;
+use many_modules::ledger::Stream;
+use many_types::ledger::{Symbol, TokenAmount, TokenInfo};
+use many_types::SortOrder;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// A broken ledger invariant, with what is needed to investigate it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InvariantViolation {
+    /// A key under the balances root which is not `{address}/{symbol}`.
+    InvalidBalanceKey { key: String },
+
+    /// The balances of a token, plus the amounts held by labels and streams,
+    /// differ from its circulating supply. Amounts are unsigned, so this is
+    /// also how a balance that went negative shows up.
+    SupplyMismatch {
+        symbol: Symbol,
+        balances: TokenAmount,
+        escrowed: TokenAmount,
+        circulating: TokenAmount,
+    },
+
+    /// The circulating supply of a token is above its total supply.
+    CirculatingAboveTotal {
+        symbol: Symbol,
+        circulating: TokenAmount,
+        total: TokenAmount,
+    },
+
+    /// The total supply of a token is above its maximum supply.
+    TotalAboveMaximum {
+        symbol: Symbol,
+        total: TokenAmount,
+        maximum: TokenAmount,
+    },
+
+    /// A stream paid more than its deposit.
+    StreamOverdrawn {
+        id: Vec<u8>,
+        deposit: TokenAmount,
+        withdrawn: TokenAmount,
+    },
+
+    /// A pending multisig transaction was approved by an identity which is
+    /// not a member of its account (anymore).
+    ApproverNotMember {
+        account: Address,
+        token: Vec<u8>,
+        approver: Address,
+    },
+}
+
+impl Display for InvariantViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidBalanceKey { key } => write!(f, "Invalid balance key '{key}'."),
+            Self::SupplyMismatch {
+                symbol,
+                balances,
+                escrowed,
+                circulating,
+            } => write!(
+                f,
+                "Balances of {symbol} ({balances}) plus escrowed amounts ({escrowed}) do not \
+                 match its circulating supply ({circulating})."
+            ),
+            Self::CirculatingAboveTotal {
+                symbol,
+                circulating,
+                total,
+            } => write!(
+                f,
+                "Circulating supply of {symbol} ({circulating}) is above its total supply \
+                 ({total})."
+            ),
+            Self::TotalAboveMaximum {
+                symbol,
+                total,
+                maximum,
+            } => write!(
+                f,
+                "Total supply of {symbol} ({total}) is above its maximum supply ({maximum})."
+            ),
+            Self::StreamOverdrawn {
+                id,
+                deposit,
+                withdrawn,
+            } => write!(
+                f,
+                "Stream {} withdrew {withdrawn}, more than its deposit ({deposit}).",
+                hex::encode(id)
+            ),
+            Self::ApproverNotMember {
+                account,
+                token,
+                approver,
+            } => write!(
+                f,
+                "Multisig transaction {} of {account} is approved by {approver}, which is not \
+                 a member of the account.",
+                hex::encode(token)
+            ),
+        }
+    }
+}
+
+impl LedgerStorage {
+    /// Check the invariants of the committed state. This reads every balance,
+    /// token, label, stream and multisig transaction, so it is meant for test
+    /// networks only; see the `invariant_checks` feature.
+    pub fn check_invariants(&self) -> Result<Vec<InvariantViolation>, ManyError> {
+        let mut violations = Vec::new();
+
+        let mut balances: BTreeMap<Symbol, TokenAmount> = BTreeMap::new();
+        for item in LedgerIterator::balances_after(&self.persistent_store, None) {
+            let (key, value) = item.map_err(ManyError::unknown)?;
+            match parse_balance_key(&key) {
+                Some((_, symbol)) => {
+                    *balances.entry(symbol).or_default() += TokenAmount::from(value);
+                }
+                None => violations.push(InvariantViolation::InvalidBalanceKey {
+                    key: String::from_utf8_lossy(&key).to_string(),
+                }),
+            }
+        }
+
+        let mut escrowed: BTreeMap<Symbol, TokenAmount> = BTreeMap::new();
+        for item in LedgerIterator::all_labels(&self.persistent_store) {
+            let (_, value) = item.map_err(ManyError::unknown)?;
+            let label: Label =
+                minicbor::decode(&value).map_err(ManyError::deserialization_error)?;
+            *escrowed.entry(label.symbol).or_default() += label.deposit;
+        }
+        for item in LedgerIterator::all_streams(&self.persistent_store) {
+            let (key, value) = item.map_err(ManyError::unknown)?;
+            let stream: Stream =
+                minicbor::decode(&value).map_err(ManyError::deserialization_error)?;
+            let deposit = stream.deposit();
+            match deposit.checked_sub(&stream.withdrawn) {
+                Some(remaining) => *escrowed.entry(stream.symbol).or_default() += remaining,
+                None => violations.push(InvariantViolation::StreamOverdrawn {
+                    id: key[crate::storage::streams::STREAMS_ROOT.len()..].to_vec(),
+                    deposit,
+                    withdrawn: stream.withdrawn,
+                }),
+            }
+        }
+
+        // Supplies are only tracked since the token migration.
+        if self.migrations.is_active(&TOKEN_MIGRATION) {
+            let it = LedgerIterator::all_symbols(&self.persistent_store, SortOrder::Indeterminate);
+            for item in it {
+                let (key, value) = item.map_err(ManyError::unknown)?;
+                let symbol = Symbol::from_str(
+                    std::str::from_utf8(&key[SYMBOLS_ROOT_DASH.len()..])
+                        .map_err(ManyError::deserialization_error)?,
+                )?;
+                let info: TokenInfo =
+                    minicbor::decode(&value).map_err(ManyError::deserialization_error)?;
+                let supply = info.supply;
+
+                let balances = balances.remove(&symbol).unwrap_or_default();
+                let escrowed = escrowed.remove(&symbol).unwrap_or_default();
+                if &balances + &escrowed != supply.circulating {
+                    violations.push(InvariantViolation::SupplyMismatch {
+                        symbol,
+                        balances,
+                        escrowed,
+                        circulating: supply.circulating.clone(),
+                    });
+                }
+                if supply.circulating > supply.total {
+                    violations.push(InvariantViolation::CirculatingAboveTotal {
+                        symbol,
+                        circulating: supply.circulating,
+                        total: supply.total.clone(),
+                    });
+                }
+                if let Some(maximum) = supply.maximum.filter(|m| &supply.total > m) {
+                    violations.push(InvariantViolation::TotalAboveMaximum {
+                        symbol,
+                        total: supply.total,
+                        maximum,
+                    });
+                }
+            }
+
+            // Tokens held without being created have no supply at all.
+            for (symbol, balances) in balances {
+                let escrowed = escrowed.remove(&symbol).unwrap_or_default();
+                if balances.is_zero() && escrowed.is_zero() {
+                    continue;
+                }
+                violations.push(InvariantViolation::SupplyMismatch {
+                    symbol,
+                    balances,
+                    escrowed,
+                    circulating: TokenAmount::zero(),
+                });
+            }
+        }
+
+        for item in self.iter_multisig(SortOrder::Ascending) {
+            let (key, value) = item.map_err(ManyError::unknown)?;
+            let storage: MultisigTransactionStorage =
+                minicbor::decode(&value).map_err(ManyError::deserialization_error)?;
+            if storage.disabled {
+                continue;
+            }
+
+            let account = self.get_account_even_disabled(&storage.account).ok();
+            for (approver, info) in &storage.info.approvers {
+                let is_member = account.as_ref().map_or(false, |(account, _)| {
+                    self.has_account_role(
+                        &storage.account,
+                        account,
+                        approver,
+                        [
+                            Role::CanMultisigApprove,
+                            Role::CanMultisigSubmit,
+                            Role::Owner,
+                        ],
+                    )
+                });
+                if info.approved && !is_member {
+                    violations.push(InvariantViolation::ApproverNotMember {
+                        account: storage.account,
+                        token: token_from_key(&key).into(),
+                        approver: *approver,
+                    });
+                }
+            }
+        }
+
+        Ok(violations)
+    }
+
+    /// Halt the node if the invariants of the committed state are broken,
+    /// with a report of every violation.
+    #[cfg(feature = "invariant_checks")]
+    pub(super) fn assert_invariants(&self, height: u64) {
+        let violations = self
+            .check_invariants()
+            .expect("Unable to check the ledger invariants.");
+        if !violations.is_empty() {
+            let report = violations
+                .iter()
+                .map(|v| format!("  - {v}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            tracing::error!("Ledger invariants broken at height {height}:\n{report}");
+            panic!("Ledger invariants broken at height {height}:\n{report}");
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::storage::event::{key_for_event, EVENTS_ROOT};
 use crate::storage::InnerStorage;
+use many_identity::Address;
 use many_modules::events::EventId;
 use many_types::{CborRange, SortOrder};
 use merk::rocksdb;
@@ -31,6 +32,19 @@ impl<'a> LedgerIterator<'a> {
         Self { inner }
     }
 
+    pub fn multisig_of_account(merk: &'a InnerStorage, account: &Address) -> Self {
+        use crate::storage::multisig::key_prefix_for_account_multisig;
+
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(key_prefix_for_account_multisig(
+            account,
+        )));
+
+        let inner = merk.iter_opt(IteratorMode::Start, options);
+
+        Self { inner }
+    }
+
     pub fn all_symbols(merk: &'a InnerStorage, order: SortOrder) -> Self {
         use crate::storage::ledger_tokens::SYMBOLS_ROOT_DASH;
 
@@ -47,6 +61,181 @@ impl<'a> LedgerIterator<'a> {
         Self { inner }
     }
 
+    pub fn all_watchlist_entries(merk: &'a InnerStorage) -> Self {
+        use crate::storage::watchlist::WATCHLIST_ENTRIES_ROOT;
+
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(WATCHLIST_ENTRIES_ROOT.as_bytes()));
+
+        let inner = merk.iter_opt(IteratorMode::Start, options);
+
+        Self { inner }
+    }
+
+    pub fn all_frozen_addresses(merk: &'a InnerStorage) -> Self {
+        use crate::storage::freeze::FROZEN_ROOT;
+
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(FROZEN_ROOT.as_bytes()));
+
+        let inner = merk.iter_opt(IteratorMode::Start, options);
+
+        Self { inner }
+    }
+
+    pub fn all_scheduled_sends(merk: &'a InnerStorage) -> Self {
+        use crate::storage::scheduled_send::SCHEDULED_SENDS_ROOT;
+
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(SCHEDULED_SENDS_ROOT.as_bytes()));
+
+        let inner = merk.iter_opt(IteratorMode::Start, options);
+
+        Self { inner }
+    }
+
+    /// Iterate the index of the scheduled sends under `root`, for the sends
+    /// due at or before `due`, in order.
+    pub fn scheduled_sends_due(merk: &'a InnerStorage, root: &str, due: u64) -> Self {
+        let mut opts = ReadOptions::default();
+        opts.set_iterate_lower_bound(root.as_bytes());
+        opts.set_iterate_upper_bound(
+            [root.as_bytes(), &due.saturating_add(1).to_be_bytes()].concat(),
+        );
+
+        Self {
+            inner: merk.iter_opt(IteratorMode::Start, opts),
+        }
+    }
+
+    #[cfg_attr(not(feature = "invariant_checks"), allow(dead_code))]
+    pub fn all_labels(merk: &'a InnerStorage) -> Self {
+        use crate::storage::labels::LABELS_ROOT;
+
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(LABELS_ROOT.as_bytes()));
+
+        let inner = merk.iter_opt(IteratorMode::Start, options);
+
+        Self { inner }
+    }
+
+    #[cfg_attr(not(feature = "invariant_checks"), allow(dead_code))]
+    pub fn all_streams(merk: &'a InnerStorage) -> Self {
+        use crate::storage::streams::STREAMS_ROOT;
+
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(STREAMS_ROOT.as_bytes()));
+
+        let inner = merk.iter_opt(IteratorMode::Start, options);
+
+        Self { inner }
+    }
+
+    pub fn all_token_ownership_offers(merk: &'a InnerStorage) -> Self {
+        use crate::storage::token_ownership::TOKEN_OWNERSHIP_OFFERS_ROOT;
+
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(TOKEN_OWNERSHIP_OFFERS_ROOT.as_bytes()));
+
+        let inner = merk.iter_opt(IteratorMode::Start, options);
+
+        Self { inner }
+    }
+
+    /// Iterate the hook records of an account, from the event after `after` to
+    /// the event `up_to`, inclusive.
+    pub fn hook_records(
+        merk: &'a InnerStorage,
+        account: &Address,
+        after: Option<EventId>,
+        up_to: Option<EventId>,
+    ) -> Self {
+        use crate::storage::hooks::{key_for_hook_record, key_prefix_for_hook_records};
+
+        let mut opts = ReadOptions::default();
+        match after {
+            Some(id) => opts.set_iterate_lower_bound(key_for_hook_record(account, id + 1)),
+            None => opts.set_iterate_lower_bound(key_prefix_for_hook_records(account)),
+        }
+        match up_to {
+            Some(id) => opts.set_iterate_upper_bound(key_for_hook_record(account, id + 1)),
+            None => {
+                let mut bound = key_prefix_for_hook_records(account);
+                let last = bound.len() - 1;
+                bound[last] += 1;
+                opts.set_iterate_upper_bound(bound);
+            }
+        }
+
+        Self {
+            inner: merk.iter_opt(IteratorMode::Start, opts),
+        }
+    }
+
+    /// Iterate the snapshots of the data attributes at or before `height`,
+    /// latest first.
+    pub fn data_snapshots(merk: &'a InnerStorage, height: u64) -> Self {
+        use crate::storage::data::{key_for_data_snapshot, DATA_HISTORY_ROOT};
+
+        let mut opts = ReadOptions::default();
+        opts.set_iterate_lower_bound(DATA_HISTORY_ROOT);
+        opts.set_iterate_upper_bound(key_for_data_snapshot(height + 1));
+
+        Self {
+            inner: merk.iter_opt(IteratorMode::End, opts),
+        }
+    }
+
+    /// Iterate all balances, from the key after `after`.
+    pub fn balances_after(merk: &'a InnerStorage, after: Option<Vec<u8>>) -> Self {
+        use crate::storage::BALANCES_ROOT;
+
+        let mut opts = ReadOptions::default();
+        match after {
+            Some(mut key) => {
+                key.push(0);
+                opts.set_iterate_lower_bound(key);
+            }
+            None => opts.set_iterate_lower_bound(BALANCES_ROOT.as_bytes()),
+        }
+        let mut bound = BALANCES_ROOT.as_bytes().to_vec();
+        let last = bound.len() - 1;
+        bound[last] += 1;
+        opts.set_iterate_upper_bound(bound);
+
+        Self {
+            inner: merk.iter_opt(IteratorMode::Start, opts),
+        }
+    }
+
+    /// Iterate the index entries of the events caused by a request. Values
+    /// are the keys of the events.
+    pub fn events_by_intent(merk: &'a InnerStorage, intent_hash: &[u8]) -> Self {
+        use crate::storage::event::key_prefix_for_intent;
+
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(key_prefix_for_intent(intent_hash)));
+
+        Self {
+            inner: merk.iter_opt(IteratorMode::Start, options),
+        }
+    }
+
+    /// Iterate the balances of an address, by symbol.
+    pub fn balances_of(merk: &'a InnerStorage, address: &Address) -> Self {
+        use crate::storage::key_prefix_for_account_balances;
+
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(key_prefix_for_account_balances(
+            address,
+        )));
+
+        Self {
+            inner: merk.iter_opt(IteratorMode::Start, options),
+        }
+    }
+
     pub fn all_events(merk: &'a InnerStorage) -> Self {
         Self::events_scoped_by_id(merk, CborRange::default(), SortOrder::Indeterminate)
     }
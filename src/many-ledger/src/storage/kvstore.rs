@@ -0,0 +1,31 @@
+use crate::error;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_identity::Address;
+
+pub(crate) const ACCOUNT_KVSTORE_ROOT: &str = "/kvstore/";
+
+/// Returns the prefix of the kvstore namespace of an account. Every account
+/// has its own namespace, which the ledger writes to on its behalf, e.g. from
+/// account hooks.
+pub(crate) fn key_prefix_for_account_kvstore(account: &Address) -> Vec<u8> {
+    format!("{ACCOUNT_KVSTORE_ROOT}{account}/").into_bytes()
+}
+
+/// Returns the storage key of `key` in the kvstore namespace of an account.
+pub(crate) fn key_for_account_kvstore(account: &Address, key: &[u8]) -> Vec<u8> {
+    [key_prefix_for_account_kvstore(account), key.to_vec()].concat()
+}
+
+impl LedgerStorage {
+    /// Returns the value of `key` in the kvstore namespace of an account.
+    pub fn account_kvstore_get(
+        &self,
+        account: &Address,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, ManyError> {
+        self.persistent_store
+            .get(&key_for_account_kvstore(account, key))
+            .map_err(error::storage_get_failed)
+    }
+}
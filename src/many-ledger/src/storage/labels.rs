@@ -0,0 +1,114 @@
+use crate::error;
+use crate::migration::labels::{deposit, ADDRESS_LABELS_MIGRATION};
+use crate::storage::{key_for_account_balance, LedgerStorage};
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::events::EventInfo;
+use many_modules::labels::Label;
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::Memo;
+use merk::{BatchEntry, Op};
+
+pub const LABELS_ROOT: &str = "/labels/";
+pub const ADDRESS_LABELS_ROOT: &str = "/address_labels/";
+
+fn key_for_label(label: &str) -> Vec<u8> {
+    format!("{LABELS_ROOT}{label}").into_bytes()
+}
+
+fn key_for_address_label(address: &Address) -> Vec<u8> {
+    format!("{ADDRESS_LABELS_ROOT}{address}").into_bytes()
+}
+
+impl LedgerStorage {
+    /// The deposit to lock when claiming a label, from the migration config.
+    pub fn label_deposit(&self) -> Result<(Symbol, TokenAmount), ManyError> {
+        self.migrations
+            .values()
+            .find(|m| m.name() == ADDRESS_LABELS_MIGRATION.name())
+            .ok_or_else(|| ManyError::unknown("The address labels migration is not configured."))
+            .and_then(|m| deposit(m.name(), &m.metadata().extra))
+    }
+
+    pub fn get_label(&self, label: &str) -> Result<Option<Label>, ManyError> {
+        self.persistent_store
+            .get(&key_for_label(label))
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+
+    pub fn get_address_label(&self, address: &Address) -> Result<Option<Label>, ManyError> {
+        self.persistent_store
+            .get(&key_for_address_label(address))
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| String::from_utf8(bytes).map_err(ManyError::deserialization_error))
+            .transpose()?
+            .map_or(Ok(None), |label| self.get_label(&label))
+    }
+
+    fn apply_label_batch(&mut self, mut batch: Vec<BatchEntry>) -> Result<(), ManyError> {
+        // Keys in batch must be sorted.
+        batch.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+        self.persistent_store
+            .apply(&batch)
+            .map_err(error::storage_apply_failed)
+    }
+
+    /// Claim a label for its owner, withdrawing the deposit from the owner.
+    pub fn claim_label(&mut self, label: Label, memo: Option<Memo>) -> Result<(), ManyError> {
+        let balance = self.get_balance(&label.owner, &label.symbol)?;
+        let remaining = balance
+            .checked_sub(&label.deposit)
+            .ok_or_else(error::insufficient_funds)?;
+        self.check_vesting(&label.owner, &label.symbol, &label.deposit, &balance)?;
+
+        self.apply_label_batch(vec![
+            (
+                key_for_account_balance(&label.owner, &label.symbol),
+                Op::Put(remaining.to_vec()),
+            ),
+            (
+                key_for_label(&label.label),
+                Op::Put(self.encode_value(&label)?),
+            ),
+            (
+                key_for_address_label(&label.owner),
+                Op::Put(label.label.clone().into_bytes()),
+            ),
+        ])?;
+
+        self.log_event(EventInfo::LabelClaim {
+            label: label.label,
+            owner: label.owner,
+            symbol: label.symbol,
+            deposit: label.deposit,
+            memo,
+        })?;
+        self.maybe_commit()
+    }
+
+    /// Release a label, refunding the deposit to its owner.
+    pub fn release_label(&mut self, label: Label, memo: Option<Memo>) -> Result<(), ManyError> {
+        let mut balance = self.get_balance(&label.owner, &label.symbol)?;
+        balance += &label.deposit;
+
+        self.apply_label_batch(vec![
+            (
+                key_for_account_balance(&label.owner, &label.symbol),
+                Op::Put(balance.to_vec()),
+            ),
+            (key_for_label(&label.label), Op::Delete),
+            (key_for_address_label(&label.owner), Op::Delete),
+        ])?;
+
+        self.log_event(EventInfo::LabelRelease {
+            label: label.label,
+            owner: label.owner,
+            symbol: label.symbol,
+            refund: label.deposit,
+            memo,
+        })?;
+        self.maybe_commit()
+    }
+}
@@ -1,5 +1,8 @@
 use crate::error;
-use crate::storage::{key_for_account_balance, LedgerStorage, IDENTITY_ROOT, SYMBOLS_ROOT};
+use crate::storage::iterator::LedgerIterator;
+use crate::storage::{
+    key_for_account_balance, parse_balance_key, LedgerStorage, IDENTITY_ROOT, SYMBOLS_ROOT,
+};
 use many_error::ManyError;
 use many_identity::Address;
 use many_protocol::context::Context;
@@ -45,7 +48,7 @@ impl LedgerStorage {
         ));
         batch.push((
             SYMBOLS_ROOT.as_bytes().to_vec(),
-            Op::Put(minicbor::to_vec(symbols).map_err(ManyError::serialization_error)?),
+            Op::Put(self.encode_value(symbols)?),
         ));
 
         batch.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
@@ -57,64 +60,58 @@ impl LedgerStorage {
         Ok(self)
     }
 
+    /// The balances of all the symbols an address holds. This only reads the
+    /// balance keys of the address instead of checking every known symbol.
     fn get_all_balances(
         &self,
         identity: &Address,
-    ) -> Result<
-        (
-            BTreeMap<Symbol, TokenAmount>,
-            impl IntoIterator<Item = Vec<u8>>,
-        ),
-        ManyError,
-    > {
-        Ok(if identity.is_anonymous() {
-            // Anonymous cannot hold funds.
-            (BTreeMap::new(), vec![])
-        } else {
-            let mut result = BTreeMap::new();
-            for symbol in self.get_symbols()? {
-                self.persistent_store
-                    .get(&key_for_account_balance(identity, &symbol))
-                    .map_err(error::storage_get_failed)?
-                    .map(|value| result.insert(symbol, TokenAmount::from(value)))
-                    .map(|_| ())
-                    .unwrap_or_default()
-            }
+    ) -> Result<(BTreeMap<Symbol, TokenAmount>, Vec<Vec<u8>>), ManyError> {
+        let mut balances = BTreeMap::new();
+        let mut keys = Vec::new();
+        // Anonymous cannot hold funds.
+        if identity.is_anonymous() {
+            return Ok((balances, keys));
+        }
 
-            (
-                result,
-                self.get_symbols()?
-                    .into_iter()
-                    .map(|symbol| key_for_account_balance(identity, &symbol))
-                    .collect(),
-            )
-        })
+        for item in LedgerIterator::balances_of(&self.persistent_store, identity) {
+            let (key, value) = item.map_err(ManyError::unknown)?;
+            if let Some((_, symbol)) = parse_balance_key(&key) {
+                balances.insert(symbol, TokenAmount::from(value));
+                keys.push(key.to_vec());
+            }
+        }
+        Ok((balances, keys))
     }
 
+    /// The balances of an address for each of `symbols`, or for all the
+    /// symbols it holds if `symbols` is empty, with the keys to prove them.
     pub fn get_multiple_balances(
         &self,
         identity: &Address,
         symbols: &BTreeSet<Symbol>,
-    ) -> Result<
-        (
-            BTreeMap<Symbol, TokenAmount>,
-            impl IntoIterator<Item = Vec<u8>>,
-        ),
-        ManyError,
-    > {
-        self.get_all_balances(identity).map(|(balances, keys)| {
-            (
-                if symbols.is_empty() {
-                    balances
-                } else {
-                    balances
-                        .into_iter()
-                        .filter(|(k, _v)| symbols.contains(k))
-                        .collect()
-                },
-                keys,
-            )
-        })
+    ) -> Result<(BTreeMap<Symbol, TokenAmount>, Vec<Vec<u8>>), ManyError> {
+        if symbols.is_empty() {
+            return self.get_all_balances(identity);
+        }
+
+        let mut balances = BTreeMap::new();
+        let mut keys = Vec::new();
+        if identity.is_anonymous() {
+            return Ok((balances, keys));
+        }
+
+        for symbol in symbols {
+            let key = key_for_account_balance(identity, symbol);
+            if let Some(value) = self
+                .persistent_store
+                .get(&key)
+                .map_err(error::storage_get_failed)?
+            {
+                balances.insert(*symbol, TokenAmount::from(value));
+            }
+            keys.push(key);
+        }
+        Ok((balances, keys))
     }
 
     pub fn prove_state(
@@ -122,30 +119,36 @@ impl LedgerStorage {
         context: impl AsRef<Context>,
         keys: impl IntoIterator<Item = Vec<u8>>,
     ) -> Result<(), ManyError> {
-        context.as_ref().prove(|| {
-            self.persistent_store
-                .prove(
-                    keys.into_iter()
-                        .map(QueryItem::Key)
-                        .collect::<Vec<_>>()
-                        .into(),
-                )
-                .and_then(|proof| {
-                    Decoder::new(proof.as_slice())
-                        .map(|fallible_operation| {
-                            fallible_operation.map(|operation| match operation {
-                                Child => ProofOperation::Child,
-                                Parent => ProofOperation::Parent,
-                                Push(Hash(hash)) => ProofOperation::NodeHash(hash.to_vec()),
-                                Push(KV(key, value)) => {
-                                    ProofOperation::KeyValuePair(key.into(), value.into())
-                                }
-                                Push(KVHash(hash)) => ProofOperation::KeyValueHash(hash.to_vec()),
-                            })
+        context.as_ref().prove(|| self.prove_keys(keys))
+    }
+
+    /// Prove the values of the keys in the current state.
+    pub fn prove_keys(
+        &self,
+        keys: impl IntoIterator<Item = Vec<u8>>,
+    ) -> Result<Vec<ProofOperation>, ManyError> {
+        self.persistent_store
+            .prove(
+                keys.into_iter()
+                    .map(QueryItem::Key)
+                    .collect::<Vec<_>>()
+                    .into(),
+            )
+            .and_then(|proof| {
+                Decoder::new(proof.as_slice())
+                    .map(|fallible_operation| {
+                        fallible_operation.map(|operation| match operation {
+                            Child => ProofOperation::Child,
+                            Parent => ProofOperation::Parent,
+                            Push(Hash(hash)) => ProofOperation::NodeHash(hash.to_vec()),
+                            Push(KV(key, value)) => {
+                                ProofOperation::KeyValuePair(key.into(), value.into())
+                            }
+                            Push(KVHash(hash)) => ProofOperation::KeyValueHash(hash.to_vec()),
                         })
-                        .collect::<Result<Vec<_>, _>>()
-                })
-                .map_err(|error| ManyError::unknown(error.to_string()))
-        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(|error| ManyError::unknown(error.to_string()))
     }
 }
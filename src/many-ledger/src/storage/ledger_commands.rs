@@ -52,16 +52,16 @@ impl LedgerStorage {
             return Err(error::anonymous_cannot_hold_funds());
         }
 
-        let mut amount_from = self.get_balance(from, symbol)?;
-        if amount > amount_from {
-            return Err(error::insufficient_funds());
-        }
+        let balance_from = self.get_balance(from, symbol)?;
+        let amount_from = balance_from
+            .checked_sub(&amount)
+            .ok_or_else(error::insufficient_funds)?;
+        self.check_vesting(from, symbol, &amount, &balance_from)?;
 
         info!("send({} => {}, {} {})", from, to, &amount, symbol);
 
         let mut amount_to = self.get_balance(to, symbol)?;
         amount_to += amount.clone();
-        amount_from -= amount.clone();
 
         // Keys in batch must be sorted.
         let key_from = key_for_account_balance(from, symbol);
@@ -64,10 +64,7 @@ impl LedgerStorage {
         info.supply.total += circulating;
         let symbol_key = key_for_symbol(&symbol);
         keys.push(symbol_key.clone().into_bytes());
-        batch.push((
-            symbol_key.into(),
-            Op::Put(minicbor::to_vec(&info).map_err(ManyError::serialization_error)?),
-        ));
+        batch.push((symbol_key.into(), Op::Put(self.encode_value(&info)?)));
 
         // We need to sort here because `distribution` is sorted by Address (bytes)
         // while the `merk` Ops are sorted by String
@@ -125,10 +122,7 @@ impl LedgerStorage {
         let symbol_key = key_for_symbol(&symbol);
         keys.push(symbol_key.clone().into_bytes());
 
-        batch.push((
-            symbol_key.into(),
-            Op::Put(minicbor::to_vec(&info).map_err(ManyError::serialization_error)?),
-        ));
+        batch.push((symbol_key.into(), Op::Put(self.encode_value(&info)?)));
 
         // We need to sort here because `distribution` is sorted by Address (bytes)
         // while the `merk` Ops are sorted by String
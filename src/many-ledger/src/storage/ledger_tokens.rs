@@ -16,7 +16,7 @@ use many_modules::ledger::{
     TokenUpdateArgs, TokenUpdateReturns,
 };
 use many_types::ledger::{Symbol, TokenAmount, TokenInfo, TokenInfoSummary, TokenInfoSupply};
-use many_types::{AttributeRelatedIndex, Either, SortOrder};
+use many_types::{AttributeRelatedIndex, Either, Memo, SortOrder};
 use merk::{BatchEntry, Op};
 use std::collections::{BTreeMap, BTreeSet};
 use std::str::FromStr;
@@ -121,15 +121,9 @@ impl LedgerStorage {
 
                 batch.push((
                     key_for_ext_info(&k),
-                    Op::Put(
-                        minicbor::to_vec(TokenExtendedInfo::default())
-                            .map_err(ManyError::serialization_error)?,
-                    ),
-                ));
-                batch.push((
-                    key_for_symbol(&k).into(),
-                    Op::Put(minicbor::to_vec(info).map_err(ManyError::serialization_error)?),
+                    Op::Put(self.encode_value(TokenExtendedInfo::default())?),
                 ));
+                batch.push((key_for_symbol(&k).into(), Op::Put(self.encode_value(info)?)));
             }
             batch.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
             self.persistent_store
@@ -223,14 +217,34 @@ impl LedgerStorage {
         symbols.insert(symbol, ticker);
         let symbols_key = b"/config/symbols".to_vec();
         self.persistent_store
-            .apply(&[(
-                symbols_key.clone(),
-                Op::Put(minicbor::to_vec(&symbols).map_err(ManyError::serialization_error)?),
-            )])
+            .apply(&[(symbols_key.clone(), Op::Put(self.encode_value(&symbols)?))])
             .map_err(error::storage_apply_failed)
             .map(|_| vec![symbols_key])
     }
 
+    /// Replace the token identity, which administers tokens and is the
+    /// parent of new token symbols.
+    pub fn update_token_identity(
+        &mut self,
+        identity: Address,
+        memo: Option<Memo>,
+    ) -> Result<(), ManyError> {
+        let previous = self.get_identity(TOKEN_IDENTITY_ROOT)?;
+        self.persistent_store
+            .apply(&[(
+                TOKEN_IDENTITY_ROOT.as_bytes().to_vec(),
+                Op::Put(identity.to_vec()),
+            )])
+            .map_err(error::storage_apply_failed)?;
+
+        self.log_event(EventInfo::TokenIdentityUpdate {
+            previous,
+            identity,
+            memo,
+        })?;
+        self.maybe_commit()
+    }
+
     pub fn create_token(
         &mut self,
         sender: &Address,
@@ -284,20 +298,14 @@ impl LedgerStorage {
         };
         let symbol_key = key_for_symbol(&symbol);
         keys.push(symbol_key.clone().into_bytes());
-        batch.push((
-            symbol_key.into(),
-            Op::Put(minicbor::to_vec(&info).map_err(ManyError::serialization_error)?),
-        ));
+        batch.push((symbol_key.into(), Op::Put(self.encode_value(&info)?)));
 
         let ext_info_key = key_for_ext_info(&symbol);
         keys.push(ext_info_key.clone());
         let ext_info = extended_info
             .clone()
             .map_or(TokenExtendedInfo::default(), |e| e);
-        batch.push((
-            ext_info_key,
-            Op::Put(minicbor::to_vec(&ext_info).map_err(ManyError::serialization_error)?),
-        ));
+        batch.push((ext_info_key, Op::Put(self.encode_value(&ext_info)?)));
 
         self.log_event(EventInfo::TokenCreate {
             summary,
@@ -406,10 +414,7 @@ impl LedgerStorage {
             };
 
             self.persistent_store
-                .apply(&[(
-                    symbol_key.into(),
-                    Op::Put(minicbor::to_vec(&info).map_err(ManyError::serialization_error)?),
-                )])
+                .apply(&[(symbol_key.into(), Op::Put(self.encode_value(&info)?))])
                 .map_err(error::storage_apply_failed)?;
 
             self.log_event(EventInfo::TokenUpdate {
@@ -459,12 +464,22 @@ impl LedgerStorage {
             ext_info = ext_info.with_visual_logo(logos.clone())?;
             indices.push(AttributeRelatedIndex::from(ExtendedInfoKey::VisualLogo));
         }
+        if let Some(attachments) = extended_info.attachments() {
+            for (index, attachment) in attachments.iter() {
+                ext_info = ext_info.with_attachment(*index, attachment.clone())?;
+                indices.push(
+                    AttributeRelatedIndex::from(ExtendedInfoKey::Attachments).with_index(*index),
+                );
+            }
+            // The limits apply to the attachments of the token, not only the
+            // added ones.
+            if let Some(attachments) = ext_info.attachments() {
+                attachments.check().map_err(error::invalid_attachment)?;
+            }
+        }
 
         self.persistent_store
-            .apply(&[(
-                ext_info_key.clone(),
-                Op::Put(minicbor::to_vec(&ext_info).map_err(ManyError::serialization_error)?),
-            )])
+            .apply(&[(ext_info_key.clone(), Op::Put(self.encode_value(&ext_info)?))])
             .map_err(error::storage_apply_failed)?;
 
         self.log_event(EventInfo::TokenAddExtendedInfo {
@@ -504,10 +519,7 @@ impl LedgerStorage {
         }
 
         self.persistent_store
-            .apply(&[(
-                ext_info_key.clone(),
-                Op::Put(minicbor::to_vec(&ext_info).map_err(ManyError::serialization_error)?),
-            )])
+            .apply(&[(ext_info_key.clone(), Op::Put(self.encode_value(&ext_info)?))])
             .map_err(error::storage_apply_failed)?;
 
         self.log_event(EventInfo::TokenRemoveExtendedInfo {
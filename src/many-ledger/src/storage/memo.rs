@@ -0,0 +1,74 @@
+use crate::error;
+use crate::migration::typed_memo::TYPED_MEMO_MIGRATION;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_types::memo::MEMO_DATA_DEFAULT_MAX_SIZE;
+use many_types::{Memo, MemoLimits, MemoSection};
+use merk::Op;
+
+pub const MEMO_LIMITS_ROOT: &str = "/config/memo_limits";
+
+impl LedgerStorage {
+    /// Store the memo limits of this server. Nothing is stored if unset, so
+    /// existing stores keep the defaults and their hash.
+    pub fn with_memo_limits(mut self, limits: Option<MemoLimits>) -> Result<Self, ManyError> {
+        if let Some(limits) = limits {
+            if limits.max_size > MEMO_DATA_DEFAULT_MAX_SIZE as u64 {
+                return Err(error::invalid_memo_limits(MEMO_DATA_DEFAULT_MAX_SIZE));
+            }
+
+            self.persistent_store
+                .apply(&[(
+                    MEMO_LIMITS_ROOT.as_bytes().to_vec(),
+                    Op::Put(self.encode_value(limits)?),
+                )])
+                .map_err(error::storage_apply_failed)?;
+        }
+
+        Ok(self)
+    }
+
+    pub fn memo_limits(&self) -> Result<MemoLimits, ManyError> {
+        self.persistent_store
+            .get(MEMO_LIMITS_ROOT.as_bytes())
+            .map_err(error::storage_get_failed)?
+            .map_or(Ok(MemoLimits::default()), |bytes| {
+                minicbor::decode(&bytes).map_err(ManyError::deserialization_error)
+            })
+    }
+
+    /// Verify a memo is within the limits of this server. Typed sections are
+    /// refused until the Typed Memo Migration is active.
+    pub fn check_memo(&self, memo: Option<&Memo>) -> Result<(), ManyError> {
+        let memo = match memo {
+            Some(memo) => memo,
+            None => return Ok(()),
+        };
+        if !self.migrations.is_active(&TYPED_MEMO_MIGRATION)
+            && memo
+                .iter()
+                .any(|section| matches!(section, MemoSection::Typed { .. }))
+        {
+            return Err(error::typed_memo_not_enabled());
+        }
+
+        let limits = self.memo_limits()?;
+
+        let size = memo.max_part_size() as u64;
+        if size > limits.max_size {
+            return Err(error::memo_too_large(size, limits.max_size));
+        }
+        if let Some(max_count) = limits.max_count {
+            if memo.len() as u64 > max_count {
+                return Err(error::memo_too_many_parts(memo.len(), max_count));
+            }
+        }
+        if let Some(max_total_size) = limits.max_total_size {
+            let total_size = memo.total_size() as u64;
+            if total_size > max_total_size {
+                return Err(error::memo_total_too_large(total_size, max_total_size));
+            }
+        }
+        Ok(())
+    }
+}
@@ -15,6 +15,7 @@ impl LedgerStorage {
                 LedgerMigrations::load(&MIGRATIONS, config, 0)
             })
             .map_err(ManyError::unknown)?; // TODO: Custom error
+        self.reset_event_ids(0)?;
 
         Ok(self)
     }
@@ -1,23 +1,36 @@
 use crate::error;
 use crate::migration::block_9400::Block9400Tx;
+use crate::migration::event_ids::EVENT_IDS_MIGRATION;
+use crate::migration::legacy_memo::LEGACY_MEMO_MIGRATION;
 use crate::migration::memo::MEMO_MIGRATION;
+use crate::migration::multisig_account_index::MULTISIG_ACCOUNT_INDEX_MIGRATION;
+use crate::migration::multisig_expired_events::MULTISIG_EXPIRED_EVENTS_MIGRATION;
+use crate::migration::multisig_results::MULTISIG_RESULTS_MIGRATION;
 use crate::module::account::validate_account;
 use crate::storage::event::EVENT_ID_KEY_SIZE_IN_BYTES;
+use crate::storage::iterator::LedgerIterator;
+use crate::storage::multisig_notifications::{
+    MultisigNotification, MULTISIG_EXPIRY_NOTICE_IN_SECS,
+};
 use crate::storage::LedgerStorage;
 use many_error::ManyError;
 use many_identity::Address;
+use many_modules::account::features::multisig::MultisigNotificationKind;
 use many_modules::account::features::FeatureInfo;
 use many_modules::{account, events, EmptyReturn};
 use many_protocol::ResponseMessage;
 use many_types::{SortOrder, Timestamp};
 use merk::Op;
-use std::collections::BTreeMap;
+use minicbor::bytes::ByteVec;
+use num_bigint::BigUint;
+use std::collections::{BTreeMap, BTreeSet};
 use tracing::debug;
 
 pub(crate) const MULTISIG_TRANSACTIONS_ROOT: &[u8] = b"/multisig/";
+pub(crate) const MULTISIG_RESULTS_ROOT: &[u8] = b"/multisig_results/";
+pub(crate) const MULTISIG_ACCOUNT_INDEX_ROOT: &str = "/multisig_by_account/";
 
-/// Returns the storage key for a multisig pending transaction.
-pub(super) fn key_for_multisig_transaction(token: &[u8]) -> Vec<u8> {
+fn expand_token(token: &[u8]) -> [u8; EVENT_ID_KEY_SIZE_IN_BYTES] {
     let token = if token.len() > EVENT_ID_KEY_SIZE_IN_BYTES {
         &token[0..EVENT_ID_KEY_SIZE_IN_BYTES]
     } else {
@@ -26,18 +39,53 @@ pub(super) fn key_for_multisig_transaction(token: &[u8]) -> Vec<u8> {
 
     let mut exp_token = [0u8; EVENT_ID_KEY_SIZE_IN_BYTES];
     exp_token[(EVENT_ID_KEY_SIZE_IN_BYTES - token.len())..].copy_from_slice(token);
+    exp_token
+}
+
+/// Returns the storage key for a multisig pending transaction.
+pub(super) fn key_for_multisig_transaction(token: &[u8]) -> Vec<u8> {
+    [MULTISIG_TRANSACTIONS_ROOT, &expand_token(token)[..]]
+        .concat()
+        .to_vec()
+}
 
-    [MULTISIG_TRANSACTIONS_ROOT, &exp_token[..]]
+/// Returns the storage key for the response of an executed multisig transaction.
+pub(super) fn key_for_multisig_result(token: &[u8]) -> Vec<u8> {
+    [MULTISIG_RESULTS_ROOT, &expand_token(token)[..]]
         .concat()
         .to_vec()
 }
 
+/// Returns the prefix of the index of the pending transactions of an account.
+pub(crate) fn key_prefix_for_account_multisig(account: &Address) -> Vec<u8> {
+    format!("{MULTISIG_ACCOUNT_INDEX_ROOT}{account}/").into_bytes()
+}
+
+/// Returns the key of a pending transaction in the index of its account. The
+/// value is the storage key of the transaction.
+pub(crate) fn key_for_account_multisig(account: &Address, token: &[u8]) -> Vec<u8> {
+    [
+        key_prefix_for_account_multisig(account),
+        expand_token(token).to_vec(),
+    ]
+    .concat()
+}
+
+/// Returns the token of a multisig transaction from its storage key. Tokens are
+/// the IDs of the submit events, without the padding of the key.
+pub(crate) fn token_from_key(key: &[u8]) -> ByteVec {
+    BigUint::from_bytes_be(&key[MULTISIG_TRANSACTIONS_ROOT.len()..])
+        .to_bytes_be()
+        .into()
+}
+
 fn _execute_multisig_tx(
     ledger: &mut LedgerStorage,
     _tx_id: &[u8],
     storage: &MultisigTransactionStorage,
 ) -> Result<Vec<u8>, ManyError> {
     let sender = &storage.account;
+    ledger.check_not_frozen([sender])?;
     match &storage.info.transaction {
         events::AccountMultisigTransaction::Send(many_modules::ledger::SendArgs {
             from,
@@ -51,12 +99,17 @@ fn _execute_multisig_tx(
 
             // The account executing the transaction should have the rights to send the funds
             let (account, _) = ledger.get_account(&from)?;
-            account.needs_role(
+            ledger.needs_account_role(
+                &from,
+                &account,
                 sender,
                 [account::Role::CanLedgerTransact, account::Role::Owner],
             )?;
 
+            ledger.check_not_frozen([&from, to])?;
+            let screening = ledger.screen_transfer(sender, &from, to)?;
             ledger.send(&from, to, symbol, amount.clone(), memo.clone())?;
+            ledger.log_screening(screening)?;
             minicbor::to_vec(EmptyReturn)
         }
 
@@ -119,12 +172,12 @@ fn _execute_multisig_tx(
         }
 
         events::AccountMultisigTransaction::AccountMultisigApprove(arg) => {
-            ledger.approve_multisig(sender, &arg.token)?;
+            ledger.approve_multisig(sender, &arg.token, arg.approve_as)?;
             minicbor::to_vec(EmptyReturn)
         }
 
         events::AccountMultisigTransaction::AccountMultisigRevoke(arg) => {
-            ledger.revoke_multisig(sender, &arg.token)?;
+            ledger.revoke_multisig(sender, &arg.token, arg.revoke_as)?;
             minicbor::to_vec(EmptyReturn)
         }
 
@@ -159,6 +212,11 @@ pub struct MultisigTransactionStorage {
 
     #[n(3)]
     pub disabled: bool,
+
+    /// The members of each member account that approved on its behalf. A
+    /// member account only approves once its own threshold is reached.
+    #[n(4)]
+    pub member_approvals: Option<BTreeMap<Address, BTreeSet<Address>>>,
 }
 
 impl MultisigTransactionStorage {
@@ -181,22 +239,33 @@ impl LedgerStorage {
     pub fn check_timed_out_multisig_transactions(&mut self) -> Result<(), ManyError> {
         let it = self.iter_multisig(SortOrder::Descending);
         let mut batch = vec![];
+        let mut expired = vec![];
+        let mut expiring = vec![];
+        let now = self.now();
 
         for item in it {
             let (k, v) = item.map_err(ManyError::unknown)?;
 
             let mut storage: MultisigTransactionStorage =
                 minicbor::decode(v.as_slice()).map_err(ManyError::deserialization_error)?;
-            let now = self.now();
 
             if now >= storage.info.timeout {
                 if !storage.disabled {
                     storage.disable(account::features::multisig::MultisigTransactionState::Expired);
+                    let token = token_from_key(&k);
+                    if let Some(op) = self.multisig_account_index_op(&token, &storage)? {
+                        batch.push(op);
+                    }
+                    expired.push((token, storage.account));
 
-                    if let Ok(v) = minicbor::to_vec(storage) {
+                    if let Ok(v) = self.encode_value(storage) {
                         batch.push((k.to_vec(), Op::Put(v)));
                     }
                 }
+            } else if !storage.disabled
+                && storage.info.timeout.secs() - now.secs() <= MULTISIG_EXPIRY_NOTICE_IN_SECS
+            {
+                expiring.push((k[MULTISIG_TRANSACTIONS_ROOT.len()..].to_vec(), storage));
             } else if let Ok(d) = now.as_system_time()?.duration_since(storage.creation) {
                 // Since the DB is ordered by event ID (keys), at this point we don't need
                 // to continue since we know that the rest is all timed out anyway.
@@ -207,13 +276,50 @@ impl LedgerStorage {
         }
 
         if !batch.is_empty() {
-            // Reverse the batch so keys are in sorted order.
-            batch.reverse();
+            batch.sort_by(|(a, _), (b, _)| a.cmp(b));
             self.persistent_store
                 .apply(&batch)
                 .map_err(error::storage_apply_failed)?;
         }
 
+        if self
+            .migrations
+            .is_active(&MULTISIG_EXPIRED_EVENTS_MIGRATION)
+        {
+            // Log the events in the order the transactions were submitted.
+            for (token, account) in expired.into_iter().rev() {
+                self.log_event(events::EventInfo::AccountMultisigExpired {
+                    account,
+                    token,
+                    time: now,
+                })?;
+            }
+        }
+
+        // Members who already approved do not need to be reminded. Notices are best
+        // effort and never prevent the transactions from expiring.
+        self.retain_expiry_notified(&expiring.iter().map(|(tx_id, _)| tx_id.clone()).collect());
+        for (tx_id, storage) in expiring {
+            if self.mark_expiry_notified(&tx_id) {
+                let approved = storage
+                    .info
+                    .approvers
+                    .iter()
+                    .filter(|(_, info)| info.approved)
+                    .map(|(id, _)| *id)
+                    .collect();
+                self.notify_multisig_members(
+                    MultisigNotification {
+                        kind: MultisigNotificationKind::ApproachingExpiry,
+                        account: storage.account,
+                        token: tx_id.into(),
+                        timeout: storage.info.timeout,
+                    },
+                    &approved,
+                );
+            }
+        }
+
         self.maybe_commit()
     }
 
@@ -225,7 +331,7 @@ impl LedgerStorage {
         // Verify the sender has the rights to the account.
         let (mut account, _) = self.get_account(&args.account)?;
 
-        account.needs_role(sender, [account::Role::Owner])?;
+        self.needs_account_role(&args.account, &account, sender, [account::Role::Owner])?;
 
         // Set the multisig threshold properly.
         if let Ok(mut multisig) = account
@@ -264,28 +370,65 @@ impl LedgerStorage {
         tx: &MultisigTransactionStorage,
     ) -> Result<(), ManyError> {
         debug!("{:?}", tx);
+        let mut batch = vec![(
+            key_for_multisig_transaction(tx_id),
+            Op::Put(self.encode_value(tx)?),
+        )];
+        if let Some(op) = self.multisig_account_index_op(tx_id, tx)? {
+            batch.push(op);
+            batch.sort_by(|(a, _), (b, _)| a.cmp(b));
+        }
         self.persistent_store
-            .apply(&[(
-                key_for_multisig_transaction(tx_id),
-                Op::Put(minicbor::to_vec(tx).map_err(ManyError::serialization_error)?),
-            )])
+            .apply(&batch)
             .map_err(error::storage_apply_failed)?;
 
         self.maybe_commit()
     }
 
+    /// Returns the operation keeping the index of the pending transactions of
+    /// an account up to date with `tx`, if any.
+    fn multisig_account_index_op(
+        &self,
+        tx_id: &[u8],
+        tx: &MultisigTransactionStorage,
+    ) -> Result<Option<(Vec<u8>, Op)>, ManyError> {
+        if !self.migrations.is_active(&MULTISIG_ACCOUNT_INDEX_MIGRATION) {
+            return Ok(None);
+        }
+
+        let key = key_for_account_multisig(&tx.account, tx_id);
+        if !tx.disabled {
+            return Ok(Some((key, Op::Put(key_for_multisig_transaction(tx_id)))));
+        }
+        // Merk fails to delete keys which do not exist.
+        let indexed = self
+            .persistent_store
+            .get(&key)
+            .map_err(error::storage_get_failed)?
+            .is_some();
+        Ok(indexed.then_some((key, Op::Delete)))
+    }
+
     pub fn create_multisig_transaction(
         &mut self,
         sender: &Address,
         arg: account::features::multisig::SubmitTransactionArgs,
     ) -> Result<Vec<u8>, ManyError> {
-        let event_id = self.new_event_id();
+        // The token is the ID of the submit event. Before the event IDs
+        // migration, it was allocated separately, leaving a gap.
+        let event_id = if self.migrations.is_active(&EVENT_IDS_MIGRATION) {
+            self.next_event_id()
+        } else {
+            self.new_event_id()
+        };
         let account_id = arg.account;
 
         let (account, _) = self.get_account(&account_id)?;
 
-        let is_owner = account.has_role(sender, "owner");
-        account.needs_role(
+        let is_owner = self.has_account_role(&account_id, &account, sender, [account::Role::Owner]);
+        self.needs_account_role(
+            &account_id,
+            &account,
             sender,
             [account::Role::CanMultisigSubmit, account::Role::Owner],
         )?;
@@ -334,10 +477,15 @@ impl LedgerStorage {
         )?;
 
         // If the migration hasn't been applied yet, use the old fields and skip
-        // the new memo field. If it has, ignore the old fields and only use the
-        // new field.
+        // the new memo field. If it has, only store the new field. Once the
+        // Legacy Memo Migration is active, the old fields are bridged into it
+        // for clients that still send them.
         let (memo_, data_, memo) = if self.migrations.is_active(&MEMO_MIGRATION) {
-            (None, None, arg.memo.clone())
+            if self.migrations.is_active(&LEGACY_MEMO_MIGRATION) {
+                (None, None, arg.merged_memo()?)
+            } else {
+                (None, None, arg.memo.clone())
+            }
         } else {
             (arg.memo_, arg.data_, None)
         };
@@ -358,6 +506,7 @@ impl LedgerStorage {
             },
             creation: self.now().as_system_time()?,
             disabled: false,
+            member_approvals: None,
         };
 
         self.commit_multisig_transaction(event_id.as_ref(), &storage)?;
@@ -374,6 +523,16 @@ impl LedgerStorage {
             memo,
         })?;
 
+        self.notify_multisig_members(
+            MultisigNotification {
+                kind: MultisigNotificationKind::Submitted,
+                account: account_id,
+                token: event_id.clone().into(),
+                timeout,
+            },
+            &BTreeSet::from([*sender]),
+        );
+
         Ok(event_id.into())
     }
 
@@ -387,7 +546,115 @@ impl LedgerStorage {
             .map_err(ManyError::deserialization_error)
     }
 
-    pub fn approve_multisig(&mut self, sender: &Address, tx_id: &[u8]) -> Result<bool, ManyError> {
+    /// List the transactions of an account which are still waiting for
+    /// approvals, in the order they were submitted.
+    pub fn list_pending_multisig_transactions(
+        &self,
+        account_id: &Address,
+    ) -> Result<Vec<account::features::multisig::PendingTransaction>, ManyError> {
+        let now = self.now();
+        let mut transactions = vec![];
+
+        let indexed = self.migrations.is_active(&MULTISIG_ACCOUNT_INDEX_MIGRATION);
+        let it = if indexed {
+            LedgerIterator::multisig_of_account(&self.persistent_store, account_id)
+        } else {
+            self.iter_multisig(SortOrder::Ascending)
+        };
+
+        for item in it {
+            let (k, v) = item.map_err(ManyError::unknown)?;
+            // The index points to the storage keys of the transactions.
+            let (k, v) = if indexed {
+                let tx = self
+                    .persistent_store
+                    .get(&v)
+                    .map_err(error::storage_get_failed)?
+                    .ok_or_else(account::features::multisig::errors::transaction_cannot_be_found)?;
+                (v, tx)
+            } else {
+                (k.into_vec(), v)
+            };
+            let storage: MultisigTransactionStorage =
+                minicbor::decode(v.as_slice()).map_err(ManyError::deserialization_error)?;
+
+            // Transactions past their timeout might not be marked as expired yet.
+            if storage.account != *account_id || storage.disabled || now >= storage.info.timeout {
+                continue;
+            }
+
+            transactions.push(account::features::multisig::PendingTransaction {
+                token: token_from_key(&k),
+                transaction: storage.info.transaction,
+                submitter: storage.info.submitter,
+                approvers: storage
+                    .info
+                    .approvers
+                    .into_iter()
+                    .filter(|(_, info)| info.approved)
+                    .map(|(id, _)| id)
+                    .collect(),
+                threshold: storage.info.threshold,
+                timeout: storage.info.timeout,
+            });
+        }
+
+        Ok(transactions)
+    }
+
+    /// Returns the number of approvals of its own members a member account
+    /// needs to approve on its behalf. Accounts without the multisig feature
+    /// approve with any single one.
+    fn member_account_threshold(account: &account::Account) -> u64 {
+        account
+            .features
+            .get::<account::features::multisig::MultisigAccountFeature>()
+            .map_or(1, |f| f.arg.threshold.unwrap_or(MULTISIG_DEFAULT_THRESHOLD))
+    }
+
+    /// Record the approval (or revocation) of `sender` on behalf of the member
+    /// account `member_id`, and returns whether the member account approves.
+    fn update_member_approval(
+        &self,
+        storage: &mut MultisigTransactionStorage,
+        sender: &Address,
+        member_id: &Address,
+        approved: bool,
+    ) -> Result<bool, ManyError> {
+        let (member, _) = self.get_account(member_id)?;
+        if !self.has_account_role(
+            member_id,
+            &member,
+            sender,
+            [account::Role::CanMultisigApprove, account::Role::Owner],
+        ) {
+            return Err(account::features::multisig::errors::user_cannot_approve_transaction());
+        }
+
+        let approvals = storage
+            .member_approvals
+            .get_or_insert_with(BTreeMap::new)
+            .entry(*member_id)
+            .or_default();
+        if approved {
+            approvals.insert(*sender);
+        } else {
+            approvals.remove(sender);
+        }
+        Ok(approvals.len() as u64 >= Self::member_account_threshold(&member))
+    }
+
+    /// Approve a transaction. If `approve_as` is set, the approval is made on
+    /// behalf of that account, which must be a member of the multisig account, and
+    /// the sender must be an owner or an approver of it. If the member account
+    /// is itself a multisig account, it only approves once enough of its own
+    /// members did.
+    pub fn approve_multisig(
+        &mut self,
+        sender: &Address,
+        tx_id: &[u8],
+        approve_as: Option<Address>,
+    ) -> Result<bool, ManyError> {
         let mut storage = self.get_multisig_info(tx_id)?;
         if storage.disabled {
             return Err(account::features::multisig::errors::transaction_expired_or_withdrawn());
@@ -395,22 +662,32 @@ impl LedgerStorage {
 
         let (account, _) = self.get_account(&storage.account)?;
 
-        // Validate the right.
-        if !account.has_role(sender, account::Role::CanMultisigApprove)
-            && !account.has_role(sender, account::Role::CanMultisigSubmit)
-            && !account.has_role(sender, account::Role::Owner)
+        let (approver, approved) = match approve_as {
+            Some(member_id) => (
+                member_id,
+                self.update_member_approval(&mut storage, sender, &member_id, true)?,
+            ),
+            None => (*sender, true),
+        };
+
+        // Validate the right. Approvals are counted for members of the account
+        // only; members of a member account approve on its behalf.
+        if !account.has_role(&approver, account::Role::CanMultisigApprove)
+            && !account.has_role(&approver, account::Role::CanMultisigSubmit)
+            && !account.has_role(&approver, account::Role::Owner)
         {
             return Err(account::features::multisig::errors::user_cannot_approve_transaction());
         }
 
         // Update the entry.
-        storage.info.approvers.entry(*sender).or_default().approved = true;
+        storage.info.approvers.entry(approver).or_default().approved = approved;
 
         self.commit_multisig_transaction(tx_id, &storage)?;
         self.log_event(events::EventInfo::AccountMultisigApprove {
             account: storage.account,
             token: tx_id.to_vec().into(),
             approver: *sender,
+            approved_as: approve_as,
         })?;
 
         // If the transaction executes automatically, calculate number of approvers.
@@ -428,7 +705,15 @@ impl LedgerStorage {
         Ok(false)
     }
 
-    pub fn revoke_multisig(&mut self, sender: &Address, tx_id: &[u8]) -> Result<bool, ManyError> {
+    /// Revoke an approval. If `revoke_as` is set, the approval made by the
+    /// sender on behalf of that member account is revoked, and the member
+    /// account stops approving if it falls below its own threshold.
+    pub fn revoke_multisig(
+        &mut self,
+        sender: &Address,
+        tx_id: &[u8],
+        revoke_as: Option<Address>,
+    ) -> Result<bool, ManyError> {
         let mut storage = self.get_multisig_info(tx_id)?;
         if storage.disabled {
             return Err(account::features::multisig::errors::transaction_expired_or_withdrawn());
@@ -436,13 +721,24 @@ impl LedgerStorage {
 
         let (account, _) = self.get_account(&storage.account)?;
 
-        // We make an exception here for people who already approved.
-        if let Some(info) = storage.info.approvers.get_mut(sender) {
+        if let Some(member_id) = revoke_as {
+            let approved = self.update_member_approval(&mut storage, sender, &member_id, false)?;
+            if let Some(info) = storage.info.approvers.get_mut(&member_id) {
+                info.approved = approved;
+            }
+        } else if let Some(info) = storage.info.approvers.get_mut(sender) {
+            // We make an exception here for people who already approved.
             info.approved = false;
-        } else if account.has_role(sender, account::Role::CanMultisigSubmit)
-            || account.has_role(sender, account::Role::CanMultisigApprove)
-            || account.has_role(sender, account::Role::Owner)
-        {
+        } else if self.has_account_role(
+            &storage.account,
+            &account,
+            sender,
+            [
+                account::Role::CanMultisigSubmit,
+                account::Role::CanMultisigApprove,
+                account::Role::Owner,
+            ],
+        ) {
             storage.info.approvers.entry(*sender).or_default().approved = false;
         } else {
             return Err(account::features::multisig::errors::user_cannot_approve_transaction());
@@ -453,6 +749,7 @@ impl LedgerStorage {
             account: storage.account,
             token: tx_id.to_vec().into(),
             revoker: *sender,
+            revoked_as: revoke_as,
         })?;
         Ok(false)
     }
@@ -471,7 +768,9 @@ impl LedgerStorage {
         let (account, _) = self.get_account(&storage.account)?;
 
         // TODO: Better error message
-        if !(account.has_role(sender, account::Role::Owner) || storage.info.submitter == *sender) {
+        if !(self.has_account_role(&storage.account, &account, sender, [account::Role::Owner])
+            || storage.info.submitter == *sender)
+        {
             return Err(account::features::multisig::errors::cannot_execute_transaction());
         }
 
@@ -489,6 +788,70 @@ impl LedgerStorage {
         }
     }
 
+    /// Returns the response of an executed transaction. Only members of the account
+    /// can see it.
+    pub fn get_multisig_result(
+        &self,
+        sender: &Address,
+        tx_id: &[u8],
+    ) -> Result<account::features::multisig::ResultReturn, ManyError> {
+        let storage = self.get_multisig_info(tx_id)?;
+        let (account, _) = self.get_account(&storage.account)?;
+
+        if !self.has_account_role(
+            &storage.account,
+            &account,
+            sender,
+            [
+                account::Role::CanMultisigApprove,
+                account::Role::CanMultisigSubmit,
+                account::Role::Owner,
+            ],
+        ) {
+            return Err(account::features::multisig::errors::user_cannot_approve_transaction());
+        }
+
+        match storage.info.state {
+            account::features::multisig::MultisigTransactionState::ExecutedAutomatically
+            | account::features::multisig::MultisigTransactionState::ExecutedManually => {}
+            _ => return Err(account::features::multisig::errors::transaction_not_executed()),
+        }
+
+        let response = match self
+            .persistent_store
+            .get(&key_for_multisig_result(tx_id))
+            .map_err(error::storage_get_failed)?
+        {
+            Some(bytes) => minicbor::decode::<ResponseMessage>(&bytes)
+                .map_err(ManyError::deserialization_error)?,
+            // Transactions executed before the results were persisted only have
+            // their response in the event log.
+            None => self.find_multisig_execute_response(tx_id)?,
+        };
+
+        Ok(account::features::multisig::ResultReturn {
+            state: storage.info.state,
+            response,
+        })
+    }
+
+    fn find_multisig_execute_response(&self, tx_id: &[u8]) -> Result<ResponseMessage, ManyError> {
+        for item in self.iter_events(Default::default(), SortOrder::Descending) {
+            let (_, v) = item.map_err(ManyError::unknown)?;
+            let log = minicbor::decode::<events::EventLog>(v.as_slice())
+                .map_err(ManyError::deserialization_error)?;
+            if let events::EventInfo::AccountMultisigExecute {
+                token, response, ..
+            } = log.content
+            {
+                if token.as_slice() == tx_id {
+                    return Ok(response);
+                }
+            }
+        }
+        Err(account::features::multisig::errors::transaction_not_executed())
+    }
+
     pub fn withdraw_multisig(&mut self, sender: &Address, tx_id: &[u8]) -> Result<(), ManyError> {
         let storage = self.get_multisig_info(tx_id)?;
         if storage.disabled {
@@ -498,7 +861,9 @@ impl LedgerStorage {
         // Verify the sender has the rights to the account.
         let (account, _) = self.get_account(&storage.account)?;
 
-        if !(account.has_role(sender, "owner") || storage.info.submitter == *sender) {
+        if !(self.has_account_role(&storage.account, &account, sender, [account::Role::Owner])
+            || storage.info.submitter == *sender)
+        {
             return Err(account::features::multisig::errors::cannot_execute_transaction());
         }
 
@@ -525,14 +890,7 @@ impl LedgerStorage {
         }
         storage.disable(state);
 
-        let v =
-            minicbor::to_vec(storage).map_err(|e| ManyError::serialization_error(e.to_string()))?;
-
-        self.persistent_store
-            .apply(&[(key_for_multisig_transaction(tx_id), Op::Put(v))])
-            .map_err(error::storage_apply_failed)?;
-
-        self.maybe_commit()
+        self.commit_multisig_transaction(tx_id, &storage)
     }
 
     fn execute_multisig_transaction_internal(
@@ -571,6 +929,15 @@ impl LedgerStorage {
             })?
             .unwrap_or(response);
 
+        if self.migrations.is_active(&MULTISIG_RESULTS_MIGRATION) {
+            let v = minicbor::to_vec(&response)
+                .map_err(|e| ManyError::serialization_error(e.to_string()))?;
+            self.persistent_store
+                .apply(&[(key_for_multisig_result(tx_id), Op::Put(v))])
+                .map_err(error::storage_apply_failed)?;
+            self.maybe_commit()?;
+        }
+
         Ok(response)
     }
 }
@@ -0,0 +1,244 @@
+use crate::error;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::account;
+use many_modules::account::features::multisig::{
+    MultisigNotificationKind, NotificationEndpoint, NotificationRegistration,
+};
+use many_types::Timestamp;
+use merk::Op;
+use minicbor::bytes::ByteVec;
+use sha3::{Digest, Sha3_256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+pub(crate) const MULTISIG_NOTIFICATIONS_ROOT: &str = "/multisig_notifications/";
+
+/// Members are notified once when a pending transaction gets within this
+/// amount of time of its timeout.
+pub const MULTISIG_EXPIRY_NOTICE_IN_SECS: u64 = 60 * 60; // An hour.
+
+pub(super) fn key_for_notification_registration(account: &Address, member: &Address) -> Vec<u8> {
+    format!("{MULTISIG_NOTIFICATIONS_ROOT}{account}/{member}").into_bytes()
+}
+
+/// Returns the hash under which an endpoint is registered.
+pub fn notification_endpoint_hash(endpoint: &NotificationEndpoint) -> Result<Vec<u8>, ManyError> {
+    let bytes = minicbor::to_vec(endpoint).map_err(ManyError::serialization_error)?;
+    Ok(Sha3_256::digest(bytes).to_vec())
+}
+
+/// A notice sent to a member of a multisig account.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MultisigNotification {
+    pub kind: MultisigNotificationKind,
+    pub account: Address,
+    pub token: ByteVec,
+    pub timeout: Timestamp,
+}
+
+/// Delivers multisig notices to the endpoints registered by account members.
+///
+/// The persistent store only knows about the endpoint hashes. Implementations
+/// are responsible for keeping the endpoints themselves (outside of the
+/// persistent store) and for delivering the notices.
+pub trait MultisigNotifier: Send + Sync {
+    /// Remember the endpoint for a registration hash.
+    fn register(&self, endpoint_hash: &[u8], endpoint: &NotificationEndpoint);
+
+    /// Deliver a notice to the endpoint registered under a hash. This should
+    /// not block and cannot fail.
+    fn notify(&self, endpoint_hash: &[u8], notification: &MultisigNotification);
+}
+
+/// Notifier state held by the storage. The list of transactions already notified
+/// as approaching expiry is only kept in memory; it does not affect the state hash.
+pub(crate) struct MultisigNotifications {
+    notifier: Arc<dyn MultisigNotifier>,
+    expiry_notified: BTreeSet<Vec<u8>>,
+}
+
+impl LedgerStorage {
+    pub fn set_multisig_notifier(&mut self, notifier: Arc<dyn MultisigNotifier>) {
+        self.multisig_notifications = Some(MultisigNotifications {
+            notifier,
+            expiry_notified: BTreeSet::new(),
+        });
+    }
+
+    fn needs_multisig_member(
+        &self,
+        account_id: &Address,
+        account: &account::Account,
+        sender: &Address,
+    ) -> Result<(), ManyError> {
+        self.needs_account_role(
+            account_id,
+            account,
+            sender,
+            [
+                account::Role::Owner,
+                account::Role::CanMultisigSubmit,
+                account::Role::CanMultisigApprove,
+            ],
+        )
+    }
+
+    pub fn register_multisig_notification(
+        &mut self,
+        sender: &Address,
+        args: account::features::multisig::RegisterNotificationArgs,
+    ) -> Result<(), ManyError> {
+        let (account, _) = self.get_account(&args.account)?;
+        account
+            .features
+            .get::<account::features::multisig::MultisigAccountFeature>()?;
+        self.needs_multisig_member(&args.account, &account, sender)?;
+        if args.endpoint_hash.len() != NotificationEndpoint::HASH_LENGTH {
+            return Err(
+                account::features::multisig::errors::invalid_notification_endpoint(
+                    "invalid endpoint hash",
+                ),
+            );
+        }
+
+        let registration = NotificationRegistration {
+            endpoint_hash: args.endpoint_hash,
+            kinds: args
+                .kinds
+                .filter(|k| !k.is_empty())
+                .unwrap_or_else(MultisigNotificationKind::all),
+        };
+
+        self.persistent_store
+            .apply(&[(
+                key_for_notification_registration(&args.account, sender),
+                Op::Put(self.encode_value(registration)?),
+            )])
+            .map_err(error::storage_apply_failed)?;
+
+        self.maybe_commit()
+    }
+
+    /// Give the endpoint of a registration to the local notifier. The endpoint
+    /// must match the hash the sender registered for the account.
+    pub fn provide_multisig_notification_endpoint(
+        &self,
+        sender: &Address,
+        args: account::features::multisig::ProvideNotificationEndpointArgs,
+    ) -> Result<(), ManyError> {
+        args.endpoint.validate()?;
+        let registration = self
+            .get_multisig_notification(&args.account, sender)?
+            .ok_or_else(account::features::multisig::errors::notification_not_registered)?;
+
+        let endpoint_hash = notification_endpoint_hash(&args.endpoint)?;
+        if registration.endpoint_hash.as_slice() != endpoint_hash.as_slice() {
+            return Err(account::features::multisig::errors::notification_endpoint_mismatch());
+        }
+
+        if let Some(notifications) = &self.multisig_notifications {
+            notifications
+                .notifier
+                .register(&endpoint_hash, &args.endpoint);
+        }
+        Ok(())
+    }
+
+    pub fn unregister_multisig_notification(
+        &mut self,
+        sender: &Address,
+        args: account::features::multisig::UnregisterNotificationArgs,
+    ) -> Result<(), ManyError> {
+        let key = key_for_notification_registration(&args.account, sender);
+        if self
+            .persistent_store
+            .get(&key)
+            .map_err(error::storage_get_failed)?
+            .is_none()
+        {
+            return Err(account::features::multisig::errors::notification_not_registered());
+        }
+
+        self.persistent_store
+            .apply(&[(key, Op::Delete)])
+            .map_err(error::storage_apply_failed)?;
+
+        self.maybe_commit()
+    }
+
+    fn get_multisig_notification(
+        &self,
+        account: &Address,
+        member: &Address,
+    ) -> Result<Option<NotificationRegistration>, ManyError> {
+        self.persistent_store
+            .get(&key_for_notification_registration(account, member))
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+
+    pub fn list_multisig_notifications(
+        &self,
+        account_id: &Address,
+    ) -> Result<BTreeMap<Address, NotificationRegistration>, ManyError> {
+        let (account, _) = self.get_account(account_id)?;
+
+        let mut registrations = BTreeMap::new();
+        for member in account.roles.keys() {
+            if let Some(registration) = self.get_multisig_notification(account_id, member)? {
+                registrations.insert(*member, registration);
+            }
+        }
+        Ok(registrations)
+    }
+
+    /// Send a notice to all members of the account that registered for it,
+    /// except the ones in `skip`. Notices are best effort; failing to send them
+    /// never fails the transaction.
+    pub(crate) fn notify_multisig_members(
+        &self,
+        notification: MultisigNotification,
+        skip: &BTreeSet<Address>,
+    ) {
+        let notifications = match &self.multisig_notifications {
+            Some(n) => n,
+            None => return,
+        };
+
+        let registrations = match self.list_multisig_notifications(&notification.account) {
+            Ok(r) => r,
+            Err(e) => {
+                tracing::warn!("Could not list multisig notifications: {e}");
+                return;
+            }
+        };
+        for (member, registration) in registrations {
+            if skip.contains(&member) || !registration.kinds.contains(&notification.kind) {
+                continue;
+            }
+            notifications
+                .notifier
+                .notify(registration.endpoint_hash.as_slice(), &notification);
+        }
+    }
+
+    /// Returns true if the transaction was not yet notified as approaching expiry,
+    /// and mark it as notified.
+    pub(crate) fn mark_expiry_notified(&mut self, tx_id: &[u8]) -> bool {
+        match &mut self.multisig_notifications {
+            Some(n) => n.expiry_notified.insert(tx_id.to_vec()),
+            None => false,
+        }
+    }
+
+    /// Forget the transactions that are no longer approaching expiry, i.e. the
+    /// ones that expired, were executed or withdrawn.
+    pub(crate) fn retain_expiry_notified(&mut self, expiring: &BTreeSet<Vec<u8>>) {
+        if let Some(n) = &mut self.multisig_notifications {
+            n.expiry_notified.retain(|tx_id| expiring.contains(tx_id));
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use crate::error;
+use crate::migration::labels::deposit;
+use crate::migration::scheduled_send::SCHEDULED_SEND_MIGRATION;
+use crate::storage::iterator::LedgerIterator;
+use crate::storage::{key_for_account_balance, LedgerStorage};
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::events::EventInfo;
+use many_modules::ledger::{scheduled_send_not_found, ScheduleTrigger, ScheduledSend};
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::{Memo, Timestamp};
+use merk::{BatchEntry, Op};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub const SCHEDULED_SENDS_ROOT: &str = "/scheduled_sends/";
+pub const SCHEDULED_SEND_ID_ROOT: &str = "/config/scheduled_send_id";
+pub const SCHEDULED_SENDS_BY_HEIGHT_ROOT: &str = "/scheduled_sends_by_height/";
+pub const SCHEDULED_SENDS_BY_TIME_ROOT: &str = "/scheduled_sends_by_time/";
+
+/// The maximum number of scheduled sends executed in a block. The sends due
+/// beyond it are executed in the following blocks.
+pub const MAX_SCHEDULED_SENDS_PER_BLOCK: usize = 100;
+
+/// Keys are ordered by ID, so sends due in the same block execute in the
+/// order they were scheduled.
+fn key_for_scheduled_send(id: u64) -> Vec<u8> {
+    [SCHEDULED_SENDS_ROOT.as_bytes(), &id.to_be_bytes()].concat()
+}
+
+/// The key of a send in the index of the sends by when they are due, ordered
+/// by height or time, then by ID.
+fn key_for_due_scheduled_send(at: &ScheduleTrigger, id: u64) -> Result<Vec<u8>, ManyError> {
+    let (root, due) = match at {
+        ScheduleTrigger::Height(height) => (SCHEDULED_SENDS_BY_HEIGHT_ROOT, *height),
+        ScheduleTrigger::Time(time) => (SCHEDULED_SENDS_BY_TIME_ROOT, time.secs()),
+        ScheduleTrigger::After(_) => {
+            return Err(ManyError::unknown(
+                "Relative triggers need to be resolved before the send is stored.",
+            ))
+        }
+    };
+    Ok([root.as_bytes(), &due.to_be_bytes(), &id.to_be_bytes()].concat())
+}
+
+impl LedgerStorage {
+    /// The deposit to lock when scheduling a send, from the migration config.
+    pub fn scheduled_send_deposit(&self) -> Result<(Symbol, TokenAmount), ManyError> {
+        self.migrations
+            .values()
+            .find(|m| m.name() == SCHEDULED_SEND_MIGRATION.name())
+            .ok_or_else(|| ManyError::unknown("The scheduled send migration is not configured."))
+            .and_then(|m| deposit(m.name(), &m.metadata().extra))
+    }
+
+    pub fn get_scheduled_send(&self, id: u64) -> Result<Option<ScheduledSend>, ManyError> {
+        self.persistent_store
+            .get(&key_for_scheduled_send(id))
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+
+    pub fn get_scheduled_sends(&self) -> Result<BTreeMap<u64, ScheduledSend>, ManyError> {
+        let mut sends = BTreeMap::new();
+        for item in LedgerIterator::all_scheduled_sends(&self.persistent_store) {
+            let (k, v) = item.map_err(ManyError::unknown)?;
+            let mut id = [0u8; 8];
+            id.copy_from_slice(&k.as_ref()[SCHEDULED_SENDS_ROOT.len()..]);
+            sends.insert(
+                u64::from_be_bytes(id),
+                minicbor::decode(&v).map_err(ManyError::deserialization_error)?,
+            );
+        }
+        Ok(sends)
+    }
+
+    fn next_scheduled_send_id(&self) -> Result<u64, ManyError> {
+        Ok(self
+            .persistent_store
+            .get(SCHEDULED_SEND_ID_ROOT.as_bytes())
+            .map_err(error::storage_get_failed)?
+            .map_or(1u64, |x| {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(x.as_slice());
+                u64::from_be_bytes(bytes)
+            }))
+    }
+
+    /// The scheduled sends due at `height` and `time`, in the order they were
+    /// scheduled, up to [MAX_SCHEDULED_SENDS_PER_BLOCK].
+    pub fn get_due_scheduled_sends(
+        &self,
+        height: u64,
+        time: Timestamp,
+    ) -> Result<Vec<(u64, ScheduledSend)>, ManyError> {
+        let mut ids = BTreeSet::new();
+        for (root, due) in [
+            (SCHEDULED_SENDS_BY_HEIGHT_ROOT, height),
+            (SCHEDULED_SENDS_BY_TIME_ROOT, time.secs()),
+        ] {
+            for item in LedgerIterator::scheduled_sends_due(&self.persistent_store, root, due)
+                .take(MAX_SCHEDULED_SENDS_PER_BLOCK)
+            {
+                let (k, _) = item.map_err(ManyError::unknown)?;
+                let id = k
+                    .len()
+                    .checked_sub(8)
+                    .and_then(|start| <[u8; 8]>::try_from(&k[start..]).ok())
+                    .ok_or_else(|| ManyError::unknown("Invalid scheduled send index key."))?;
+                ids.insert(u64::from_be_bytes(id));
+            }
+        }
+
+        ids.into_iter()
+            .take(MAX_SCHEDULED_SENDS_PER_BLOCK)
+            .map(|id| {
+                self.get_scheduled_send(id)?
+                    .map(|send| (id, send))
+                    .ok_or_else(|| scheduled_send_not_found(id))
+            })
+            .collect()
+    }
+
+    fn apply_scheduled_send_batch(&mut self, mut batch: Vec<BatchEntry>) -> Result<(), ManyError> {
+        // Keys in batch must be sorted.
+        batch.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+        self.persistent_store
+            .apply(&batch)
+            .map_err(error::storage_apply_failed)
+    }
+
+    /// Store a send, withdrawing its deposit from the sender.
+    pub fn add_scheduled_send(&mut self, send: ScheduledSend) -> Result<u64, ManyError> {
+        let id = self.next_scheduled_send_id()?;
+
+        let balance = self.get_balance(&send.sender, &send.deposit_symbol)?;
+        let remaining = balance
+            .checked_sub(&send.deposit)
+            .ok_or_else(error::insufficient_funds)?;
+        self.check_vesting(&send.sender, &send.deposit_symbol, &send.deposit, &balance)?;
+
+        self.apply_scheduled_send_batch(vec![
+            (
+                key_for_account_balance(&send.sender, &send.deposit_symbol),
+                Op::Put(remaining.to_vec()),
+            ),
+            (
+                SCHEDULED_SEND_ID_ROOT.as_bytes().to_vec(),
+                Op::Put((id + 1).to_be_bytes().to_vec()),
+            ),
+            (
+                key_for_due_scheduled_send(&send.at, id)?,
+                Op::Put(key_for_scheduled_send(id)),
+            ),
+            (
+                key_for_scheduled_send(id),
+                Op::Put(self.encode_value(&send)?),
+            ),
+        ])?;
+
+        self.log_event(EventInfo::ScheduledSendAdd {
+            id,
+            sender: send.sender,
+            from: send.from,
+            to: send.to,
+            symbol: send.symbol,
+            amount: send.amount,
+            at: send.at,
+            memo: send.memo,
+            deposit_symbol: send.deposit_symbol,
+            deposit: send.deposit,
+        })?;
+        self.maybe_commit().map(|_| id)
+    }
+
+    /// Remove a send, refunding its deposit to the sender.
+    fn remove_scheduled_send(&mut self, id: u64, send: &ScheduledSend) -> Result<(), ManyError> {
+        let mut balance = self.get_balance(&send.sender, &send.deposit_symbol)?;
+        balance += &send.deposit;
+
+        self.apply_scheduled_send_batch(vec![
+            (
+                key_for_account_balance(&send.sender, &send.deposit_symbol),
+                Op::Put(balance.to_vec()),
+            ),
+            (key_for_due_scheduled_send(&send.at, id)?, Op::Delete),
+            (key_for_scheduled_send(id), Op::Delete),
+        ])
+    }
+
+    pub fn cancel_scheduled_send(
+        &mut self,
+        id: u64,
+        canceller: Address,
+        memo: Option<Memo>,
+    ) -> Result<(), ManyError> {
+        let send = self
+            .get_scheduled_send(id)?
+            .ok_or_else(|| scheduled_send_not_found(id))?;
+        self.remove_scheduled_send(id, &send)?;
+
+        self.log_event(EventInfo::ScheduledSendCancel {
+            id,
+            canceller,
+            memo,
+        })?;
+        self.maybe_commit()
+    }
+
+    /// Remove a scheduled send which was due and log the outcome of its
+    /// execution.
+    pub fn complete_scheduled_send(
+        &mut self,
+        id: u64,
+        send: ScheduledSend,
+        error: Option<ManyError>,
+    ) -> Result<(), ManyError> {
+        self.remove_scheduled_send(id, &send)?;
+
+        self.log_event(EventInfo::ScheduledSendExecute {
+            id,
+            from: send.from,
+            to: send.to,
+            symbol: send.symbol,
+            amount: send.amount,
+            error,
+        })?;
+        self.maybe_commit()
+    }
+}
@@ -0,0 +1,129 @@
+use crate::error;
+use crate::scripts::ScriptEffects;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::events::EventInfo;
+use many_types::Memo;
+use merk::{BatchEntry, Op};
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+use sha3::{Digest, Sha3_256};
+
+pub const SCRIPTS_ROOT: &str = "/scripts/";
+pub const SCRIPT_ID_ROOT: &str = "/config/script_id";
+pub const SCRIPTS_KV_ROOT: &str = "/scripts_kv/";
+
+/// A script as stored, with its code.
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct StoredScript {
+    #[n(0)]
+    pub owner: Address,
+
+    #[n(1)]
+    pub code: ByteVec,
+}
+
+fn key_for_script(id: u64) -> Vec<u8> {
+    [SCRIPTS_ROOT.as_bytes(), &id.to_be_bytes()].concat()
+}
+
+fn key_for_script_value(id: u64, key: &[u8]) -> Vec<u8> {
+    [SCRIPTS_KV_ROOT.as_bytes(), &id.to_be_bytes(), b"/", key].concat()
+}
+
+impl LedgerStorage {
+    pub fn get_script(&self, id: u64) -> Result<Option<StoredScript>, ManyError> {
+        self.persistent_store
+            .get(&key_for_script(id))
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+
+    /// A value in the namespace of a script.
+    pub fn get_script_value(&self, id: u64, key: &[u8]) -> Result<Option<Vec<u8>>, ManyError> {
+        self.persistent_store
+            .get(&key_for_script_value(id, key))
+            .map_err(error::storage_get_failed)
+    }
+
+    fn next_script_id(&self) -> Result<u64, ManyError> {
+        Ok(self
+            .persistent_store
+            .get(SCRIPT_ID_ROOT.as_bytes())
+            .map_err(error::storage_get_failed)?
+            .map_or(1u64, |x| {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(x.as_slice());
+                u64::from_be_bytes(bytes)
+            }))
+    }
+
+    pub fn create_script(
+        &mut self,
+        owner: Address,
+        code: Vec<u8>,
+        memo: Option<Memo>,
+    ) -> Result<u64, ManyError> {
+        let id = self.next_script_id()?;
+        let code_hash = Sha3_256::digest(&code).to_vec();
+        let script = StoredScript {
+            owner,
+            code: code.into(),
+        };
+
+        let mut batch: Vec<BatchEntry> = vec![
+            (
+                SCRIPT_ID_ROOT.as_bytes().to_vec(),
+                Op::Put((id + 1).to_be_bytes().to_vec()),
+            ),
+            (key_for_script(id), Op::Put(self.encode_value(&script)?)),
+        ];
+
+        // Keys in batch must be sorted.
+        batch.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+        self.persistent_store
+            .apply(&batch)
+            .map_err(error::storage_apply_failed)?;
+
+        self.log_event(EventInfo::ScriptDeploy {
+            id,
+            owner,
+            code_hash: code_hash.into(),
+            memo,
+        })?;
+        self.maybe_commit().map(|_| id)
+    }
+
+    /// Apply the writes of a successful invocation, and log the events it
+    /// emitted.
+    pub fn apply_script_effects(
+        &mut self,
+        id: u64,
+        caller: &Address,
+        effects: &ScriptEffects,
+    ) -> Result<(), ManyError> {
+        // The writes are in a BTreeMap, so their keys are already sorted.
+        let batch: Vec<BatchEntry> = effects
+            .writes
+            .iter()
+            .map(|(key, value)| (key_for_script_value(id, key), Op::Put(value.clone())))
+            .collect();
+        if !batch.is_empty() {
+            self.persistent_store
+                .apply(&batch)
+                .map_err(error::storage_apply_failed)?;
+        }
+
+        for data in &effects.events {
+            self.log_event(EventInfo::ScriptEmit {
+                id,
+                caller: *caller,
+                data: data.clone().into(),
+            })?;
+        }
+        self.maybe_commit()
+    }
+}
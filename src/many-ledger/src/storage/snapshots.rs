@@ -0,0 +1,294 @@
+//! Snapshots of the persistent store, so new nodes can start from a recent
+//! state instead of replaying every block (Tendermint state sync).
+//!
+//! Snapshots are RocksDB checkpoints of the store, kept in a directory per
+//! height. Their chunks are the chunks of the Merk tree, which a new node
+//! restores to the exact same tree, and therefore the same app hash.
+use crate::error;
+use crate::storage::{InnerStorage, LedgerStorage};
+use many_error::ManyError;
+use many_modules::abci_backend::{
+    AbciApplySnapshotChunk, AbciApplySnapshotChunkResult, AbciApplySnapshotChunkReturn,
+    AbciListSnapshots, AbciLoadSnapshotChunk, AbciOfferSnapshot, AbciOfferSnapshotResult,
+    AbciSnapshot, AbciSnapshotChunk,
+};
+use merk::restore::Restorer;
+use merk::Merk;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// The format of the chunks of the snapshots, i.e. Merk chunks.
+pub const SNAPSHOT_FORMAT: u32 = 1;
+
+/// Where and how often to take snapshots.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SnapshotConfig {
+    pub path: PathBuf,
+
+    /// Take a snapshot every `interval` blocks.
+    pub interval: u64,
+
+    /// The number of snapshots to keep, older ones are deleted.
+    pub keep: usize,
+}
+
+/// A snapshot being restored.
+pub(super) struct SnapshotRestore {
+    restorer: Restorer,
+    snapshot: AbciSnapshot,
+    next_chunk: u32,
+}
+
+fn restore_path(persistent_path: &Path) -> PathBuf {
+    let mut path = persistent_path.as_os_str().to_owned();
+    path.push(".restore");
+    PathBuf::from(path)
+}
+
+impl SnapshotConfig {
+    fn snapshot_path(&self, height: u64) -> PathBuf {
+        self.path.join(height.to_string())
+    }
+
+    /// The heights of the snapshots, in increasing order.
+    fn heights(&self) -> Result<Vec<u64>, ManyError> {
+        let mut heights = match std::fs::read_dir(&self.path) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u64>().ok())
+                .collect::<Vec<_>>(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+            Err(e) => return Err(error::storage_open_failed(e)),
+        };
+        heights.sort_unstable();
+        Ok(heights)
+    }
+
+    fn open(&self, height: u64) -> Result<Merk, ManyError> {
+        let path = self.snapshot_path(height);
+        if !path.exists() {
+            return Err(error::snapshot_not_found(height));
+        }
+        Merk::open(path).map_err(error::storage_open_failed)
+    }
+}
+
+impl LedgerStorage {
+    /// Take a snapshot of the store every `config.interval` blocks.
+    pub fn set_snapshots(&mut self, config: SnapshotConfig) {
+        self.snapshots = Some(config);
+    }
+
+    /// Take a snapshot of the committed store at `height`, if it is time to,
+    /// and delete the ones over the limit. Failing to do so does not prevent
+    /// the block from being committed.
+    pub(super) fn maybe_take_snapshot(&mut self, height: u64) {
+        let config = match &self.snapshots {
+            Some(config) if config.interval > 0 && height % config.interval == 0 => config,
+            _ => return,
+        };
+
+        let result = std::fs::create_dir_all(&config.path)
+            .map_err(|e| e.to_string())
+            .and_then(|_| {
+                self.persistent_store
+                    .checkpoint(config.snapshot_path(height))
+                    .map_err(|e| e.to_string())
+            });
+        match result {
+            Ok(_) => info!("Took a snapshot at height {height}"),
+            Err(e) => warn!("Unable to take a snapshot at height {height}: {e}"),
+        }
+
+        if let Ok(heights) = config.heights() {
+            let obsolete = heights.len().saturating_sub(config.keep);
+            for height in &heights[..obsolete] {
+                if let Err(e) = std::fs::remove_dir_all(config.snapshot_path(*height)) {
+                    warn!("Unable to delete the snapshot at height {height}: {e}");
+                }
+            }
+        }
+    }
+
+    pub fn list_snapshots(&self) -> Result<AbciListSnapshots, ManyError> {
+        let config = match &self.snapshots {
+            Some(config) => config,
+            None => return Ok(AbciListSnapshots::default()),
+        };
+
+        let snapshots = config
+            .heights()?
+            .into_iter()
+            .map(|height| {
+                let merk = config.open(height)?;
+                let chunks = merk.chunks().map_err(error::storage_get_failed)?.len();
+                Ok(AbciSnapshot {
+                    height,
+                    format: SNAPSHOT_FORMAT,
+                    chunks: chunks as u32,
+                    hash: merk.root_hash().to_vec().into(),
+                    metadata: vec![].into(),
+                })
+            })
+            .collect::<Result<_, ManyError>>()?;
+        Ok(AbciListSnapshots { snapshots })
+    }
+
+    pub fn load_snapshot_chunk(
+        &self,
+        args: AbciLoadSnapshotChunk,
+    ) -> Result<AbciSnapshotChunk, ManyError> {
+        let config = self
+            .snapshots
+            .as_ref()
+            .ok_or_else(|| error::snapshot_not_found(args.height))?;
+        if args.format != SNAPSHOT_FORMAT {
+            return Err(error::snapshot_not_found(args.height));
+        }
+
+        let merk = config.open(args.height)?;
+        let mut chunks = merk.chunks().map_err(error::storage_get_failed)?;
+        if args.chunk as usize >= chunks.len() {
+            return Err(error::snapshot_chunk_not_found(args.height, args.chunk));
+        }
+        let chunk = chunks
+            .chunk(args.chunk as usize)
+            .map_err(error::storage_get_failed)?;
+        Ok(AbciSnapshotChunk {
+            chunk: chunk.into(),
+        })
+    }
+
+    /// Start restoring a snapshot. Only empty stores can be restored.
+    pub fn offer_snapshot(
+        &mut self,
+        args: AbciOfferSnapshot,
+    ) -> Result<AbciOfferSnapshotResult, ManyError> {
+        let AbciOfferSnapshot { snapshot, app_hash } = args;
+        if snapshot.format != SNAPSHOT_FORMAT {
+            return Ok(AbciOfferSnapshotResult::RejectFormat);
+        }
+        if self.get_height()? != 0 {
+            warn!("Refusing to restore a snapshot over an existing state.");
+            return Ok(AbciOfferSnapshotResult::Abort);
+        }
+        let expected_hash = match <[u8; 32]>::try_from(snapshot.hash.as_slice()) {
+            Ok(hash) if snapshot.hash == app_hash && snapshot.chunks > 0 => hash,
+            _ => return Ok(AbciOfferSnapshotResult::Reject),
+        };
+
+        // Drop a previous restoration, if any.
+        self.restore = None;
+        let path = restore_path(&self.persistent_path);
+        if path.exists() {
+            std::fs::remove_dir_all(&path).map_err(error::storage_open_failed)?;
+        }
+
+        let restorer = Merk::restore(path, expected_hash, snapshot.chunks as usize)
+            .map_err(error::storage_open_failed)?;
+        info!(
+            "Restoring the snapshot at height {} ({} chunks)",
+            snapshot.height, snapshot.chunks
+        );
+        self.restore = Some(SnapshotRestore {
+            restorer,
+            snapshot,
+            next_chunk: 0,
+        });
+        Ok(AbciOfferSnapshotResult::Accept)
+    }
+
+    /// Apply the next chunk of the snapshot being restored. The restored store
+    /// replaces the persistent store after its last chunk.
+    pub fn apply_snapshot_chunk(
+        &mut self,
+        args: AbciApplySnapshotChunk,
+    ) -> Result<AbciApplySnapshotChunkReturn, ManyError> {
+        let restore = match &mut self.restore {
+            Some(restore) => restore,
+            None => {
+                return Ok(AbciApplySnapshotChunkReturn::new(
+                    AbciApplySnapshotChunkResult::Abort,
+                ))
+            }
+        };
+
+        // Chunks must be restored in order.
+        if args.index < restore.next_chunk {
+            return Ok(AbciApplySnapshotChunkReturn::new(
+                AbciApplySnapshotChunkResult::Accept,
+            ));
+        }
+        if args.index > restore.next_chunk {
+            return Ok(AbciApplySnapshotChunkReturn {
+                refetch_chunks: vec![restore.next_chunk],
+                ..AbciApplySnapshotChunkReturn::new(AbciApplySnapshotChunkResult::Retry)
+            });
+        }
+
+        let remaining = match restore.restorer.process_chunk(&args.chunk) {
+            Ok(remaining) => remaining,
+            Err(e) => {
+                warn!(
+                    "Invalid snapshot chunk {} from {}: {e}",
+                    args.index, args.sender
+                );
+                return Ok(AbciApplySnapshotChunkReturn {
+                    refetch_chunks: vec![args.index],
+                    reject_senders: vec![args.sender],
+                    ..AbciApplySnapshotChunkReturn::new(AbciApplySnapshotChunkResult::Retry)
+                });
+            }
+        };
+        restore.next_chunk += 1;
+
+        if remaining == 0 {
+            self.finish_restore()?;
+        }
+        Ok(AbciApplySnapshotChunkReturn::new(
+            AbciApplySnapshotChunkResult::Accept,
+        ))
+    }
+
+    fn finish_restore(&mut self) -> Result<(), ManyError> {
+        let SnapshotRestore {
+            restorer, snapshot, ..
+        } = self
+            .restore
+            .take()
+            .ok_or_else(|| ManyError::unknown("No snapshot is being restored."))?;
+        let restored = restorer.finalize().map_err(error::storage_open_failed)?;
+
+        // Replace the empty store by the restored one, then move the restored
+        // one back to the path of the persistent store.
+        let empty = std::mem::replace(
+            &mut self.persistent_store,
+            InnerStorage::from_merk(restored),
+        );
+        empty.destroy().map_err(error::storage_apply_failed)?;
+        let moved = self
+            .persistent_store
+            .checkpoint(&self.persistent_path)
+            .map_err(error::storage_commit_failed)?;
+        let restored =
+            std::mem::replace(&mut self.persistent_store, InnerStorage::from_merk(moved));
+        restored.destroy().map_err(error::storage_apply_failed)?;
+
+        // Reload the state kept in memory.
+        let height = self.get_height()?;
+        if height != snapshot.height {
+            return Err(error::invalid_snapshot(format!(
+                "restored height {height}, expected {}",
+                snapshot.height
+            )));
+        }
+        self.migrations.activate_at_height(height);
+        self.current_hash = None;
+        self.reset_event_ids(height)?;
+
+        info!(
+            "Restored the snapshot at height {height}, hash {}",
+            hex::encode(self.hash())
+        );
+        Ok(())
+    }
+}
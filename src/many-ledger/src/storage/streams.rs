@@ -0,0 +1,175 @@
+use crate::error;
+use crate::storage::{key_for_account_balance, LedgerStorage};
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::events::EventInfo;
+use many_modules::ledger::{nothing_to_withdraw, Stream};
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::Memo;
+use merk::{BatchEntry, Op};
+
+pub const STREAMS_ROOT: &str = "/streams/";
+pub const STREAM_ID_ROOT: &str = "/config/stream_id";
+
+fn key_for_stream(id: u64) -> Vec<u8> {
+    [STREAMS_ROOT.as_bytes(), &id.to_be_bytes()].concat()
+}
+
+impl LedgerStorage {
+    pub fn get_stream(&self, id: u64) -> Result<Option<Stream>, ManyError> {
+        self.persistent_store
+            .get(&key_for_stream(id))
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+
+    fn next_stream_id(&self) -> Result<u64, ManyError> {
+        Ok(self
+            .persistent_store
+            .get(STREAM_ID_ROOT.as_bytes())
+            .map_err(error::storage_get_failed)?
+            .map_or(1u64, |x| {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(x.as_slice());
+                u64::from_be_bytes(bytes)
+            }))
+    }
+
+    /// Add an amount to the balance of an address, as a batch entry.
+    fn stream_payment(
+        &self,
+        address: &Address,
+        symbol: &Symbol,
+        amount: &TokenAmount,
+    ) -> Result<BatchEntry, ManyError> {
+        let mut balance = self.get_balance(address, symbol)?;
+        balance += amount;
+        Ok((
+            key_for_account_balance(address, symbol),
+            Op::Put(balance.to_vec()),
+        ))
+    }
+
+    fn put_stream(
+        &mut self,
+        id: u64,
+        stream: Option<&Stream>,
+        mut batch: Vec<BatchEntry>,
+    ) -> Result<(), ManyError> {
+        batch.push((
+            key_for_stream(id),
+            match stream {
+                Some(stream) => Op::Put(self.encode_value(stream)?),
+                None => Op::Delete,
+            },
+        ));
+
+        // Keys in batch must be sorted.
+        batch.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+        self.persistent_store
+            .apply(&batch)
+            .map_err(error::storage_apply_failed)
+    }
+
+    /// Create a stream, withdrawing its whole deposit from the payer.
+    pub fn create_stream(&mut self, stream: Stream, memo: Option<Memo>) -> Result<u64, ManyError> {
+        let deposit = stream.deposit();
+        let balance = self.get_balance(&stream.payer, &stream.symbol)?;
+        let remaining = balance
+            .checked_sub(&deposit)
+            .ok_or_else(error::insufficient_funds)?;
+        self.check_vesting(&stream.payer, &stream.symbol, &deposit, &balance)?;
+
+        let id = self.next_stream_id()?;
+        self.put_stream(
+            id,
+            Some(&stream),
+            vec![
+                (
+                    key_for_account_balance(&stream.payer, &stream.symbol),
+                    Op::Put(remaining.to_vec()),
+                ),
+                (
+                    STREAM_ID_ROOT.as_bytes().to_vec(),
+                    Op::Put((id + 1).to_be_bytes().to_vec()),
+                ),
+            ],
+        )?;
+
+        self.log_event(EventInfo::StreamCreate {
+            id,
+            payer: stream.payer,
+            payee: stream.payee,
+            symbol: stream.symbol,
+            rate: stream.rate,
+            start: stream.start,
+            end: stream.end,
+            memo,
+        })?;
+        self.maybe_commit().map(|_| id)
+    }
+
+    /// Pay the payee what accrued since the last withdrawal. The stream is
+    /// removed once everything was withdrawn.
+    pub fn withdraw_stream(
+        &mut self,
+        id: u64,
+        mut stream: Stream,
+        memo: Option<Memo>,
+    ) -> Result<TokenAmount, ManyError> {
+        let now = self.now();
+        let amount = stream.withdrawable(now);
+        if amount.is_zero() {
+            return Err(nothing_to_withdraw(id));
+        }
+
+        let batch = vec![self.stream_payment(&stream.payee, &stream.symbol, &amount)?];
+        stream.withdrawn += &amount;
+        let done = stream.withdrawn == stream.deposit();
+        self.put_stream(id, (!done).then_some(&stream), batch)?;
+
+        self.log_event(EventInfo::StreamWithdraw {
+            id,
+            payee: stream.payee,
+            symbol: stream.symbol,
+            amount: amount.clone(),
+            memo,
+        })?;
+        self.maybe_commit().map(|_| amount)
+    }
+
+    /// Remove a stream, paying the payee what accrued and refunding the payer
+    /// the rest of the deposit.
+    pub fn cancel_stream(
+        &mut self,
+        id: u64,
+        stream: Stream,
+        memo: Option<Memo>,
+    ) -> Result<(TokenAmount, TokenAmount), ManyError> {
+        let now = self.now();
+        let paid = stream.withdrawable(now);
+        let mut refunded = stream.deposit();
+        refunded -= &stream.accrued(now);
+
+        let mut batch = Vec::new();
+        if !paid.is_zero() {
+            batch.push(self.stream_payment(&stream.payee, &stream.symbol, &paid)?);
+        }
+        if !refunded.is_zero() {
+            batch.push(self.stream_payment(&stream.payer, &stream.symbol, &refunded)?);
+        }
+        self.put_stream(id, None, batch)?;
+
+        self.log_event(EventInfo::StreamCancel {
+            id,
+            payer: stream.payer,
+            payee: stream.payee,
+            symbol: stream.symbol,
+            paid: paid.clone(),
+            refunded: refunded.clone(),
+            memo,
+        })?;
+        self.maybe_commit().map(|_| (paid, refunded))
+    }
+}
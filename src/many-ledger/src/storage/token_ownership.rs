@@ -0,0 +1,174 @@
+use crate::error;
+use crate::migration::token_ownership_offers::{offer_window, TOKEN_OWNERSHIP_OFFERS_MIGRATION};
+use crate::storage::iterator::LedgerIterator;
+use crate::storage::ledger_tokens::key_for_symbol;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::events::EventInfo;
+use many_modules::ledger::TokenOwnershipOffer;
+use many_types::ledger::{Symbol, TokenInfo};
+use many_types::{Duration, Memo};
+use merk::Op;
+
+pub const TOKEN_OWNERSHIP_OFFERS_ROOT: &str = "/token_ownership_offers/";
+
+fn key_for_ownership_offer(symbol: &Symbol) -> Vec<u8> {
+    format!("{TOKEN_OWNERSHIP_OFFERS_ROOT}{symbol}").into_bytes()
+}
+
+impl LedgerStorage {
+    /// How long the new owner of a token has to accept it, from the migration
+    /// config.
+    pub fn ownership_offer_window(&self) -> Result<Duration, ManyError> {
+        self.migrations
+            .values()
+            .find(|m| m.name() == TOKEN_OWNERSHIP_OFFERS_MIGRATION.name())
+            .ok_or_else(|| {
+                ManyError::unknown("The token ownership offers migration is not configured.")
+            })
+            .and_then(|m| offer_window(&m.metadata().extra))
+    }
+
+    /// The pending ownership offer of a token, expired or not.
+    pub fn get_ownership_offer(
+        &self,
+        symbol: &Symbol,
+    ) -> Result<Option<TokenOwnershipOffer>, ManyError> {
+        self.persistent_store
+            .get(&key_for_ownership_offer(symbol))
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+
+    /// Offer the ownership of a token to a new owner, replacing any pending
+    /// offer of the token.
+    pub fn offer_token_ownership(
+        &mut self,
+        symbol: Symbol,
+        owner: Address,
+        new_owner: Address,
+        memo: Option<Memo>,
+    ) -> Result<TokenOwnershipOffer, ManyError> {
+        let offer = TokenOwnershipOffer {
+            symbol,
+            owner,
+            new_owner,
+            expires: self.now() + self.ownership_offer_window()?,
+        };
+
+        self.persistent_store
+            .apply(&[(
+                key_for_ownership_offer(&symbol),
+                Op::Put(self.encode_value(&offer)?),
+            )])
+            .map_err(error::storage_apply_failed)?;
+
+        self.log_event(EventInfo::TokenOwnershipOffer {
+            symbol,
+            owner,
+            new_owner,
+            expires: offer.expires,
+            memo,
+        })?;
+
+        self.maybe_commit()?;
+        Ok(offer)
+    }
+
+    /// Transfer the ownership of a token to the new owner of its offer. The
+    /// offer must not be expired, and must have been made by the current owner
+    /// of the token.
+    pub fn accept_token_ownership(
+        &mut self,
+        symbol: Symbol,
+        memo: Option<Memo>,
+    ) -> Result<(), ManyError> {
+        let offer = self
+            .get_ownership_offer(&symbol)?
+            .ok_or_else(|| error::ownership_offer_not_found(symbol))?;
+        if offer.expires < self.now() {
+            return Err(error::ownership_offer_expired(symbol));
+        }
+
+        let symbol_key = key_for_symbol(&symbol);
+        let mut info: TokenInfo = self
+            .persistent_store
+            .get(symbol_key.as_bytes())
+            .map_err(error::storage_get_failed)?
+            .ok_or_else(|| error::token_info_not_found(symbol))
+            .and_then(|enc| minicbor::decode(&enc).map_err(ManyError::deserialization_error))?;
+
+        // The ownership changed since the offer was made, e.g. renounced.
+        if info.owner != Some(offer.owner) {
+            return Err(error::ownership_offer_not_found(symbol));
+        }
+        info.owner = Some(offer.new_owner);
+
+        let mut batch = vec![
+            (symbol_key.into_bytes(), Op::Put(self.encode_value(&info)?)),
+            (key_for_ownership_offer(&symbol), Op::Delete),
+        ];
+        // Keys in batch must be sorted.
+        batch.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+        self.persistent_store
+            .apply(&batch)
+            .map_err(error::storage_apply_failed)?;
+
+        self.log_event(EventInfo::TokenOwnershipAccept {
+            symbol,
+            previous_owner: offer.owner,
+            owner: offer.new_owner,
+            memo,
+        })?;
+
+        self.maybe_commit()
+    }
+
+    pub fn cancel_token_ownership_offer(
+        &mut self,
+        symbol: Symbol,
+        memo: Option<Memo>,
+    ) -> Result<(), ManyError> {
+        let offer = self
+            .get_ownership_offer(&symbol)?
+            .ok_or_else(|| error::ownership_offer_not_found(symbol))?;
+
+        self.persistent_store
+            .apply(&[(key_for_ownership_offer(&symbol), Op::Delete)])
+            .map_err(error::storage_apply_failed)?;
+
+        self.log_event(EventInfo::TokenOwnershipCancel {
+            symbol,
+            owner: offer.owner,
+            new_owner: offer.new_owner,
+            memo,
+        })?;
+
+        self.maybe_commit()
+    }
+
+    /// The ownership offers which did not expire, optionally of a single
+    /// token or to a single new owner.
+    pub fn token_ownership_offers(
+        &self,
+        symbol: Option<&Symbol>,
+        new_owner: Option<&Address>,
+    ) -> Result<Vec<TokenOwnershipOffer>, ManyError> {
+        let now = self.now();
+        let mut offers = Vec::new();
+        for item in LedgerIterator::all_token_ownership_offers(&self.persistent_store) {
+            let (_, value) = item.map_err(ManyError::unknown)?;
+            let offer: TokenOwnershipOffer =
+                minicbor::decode(&value).map_err(ManyError::deserialization_error)?;
+            if offer.expires >= now
+                && symbol.map_or(true, |s| *s == offer.symbol)
+                && new_owner.map_or(true, |o| *o == offer.new_owner)
+            {
+                offers.push(offer);
+            }
+        }
+        Ok(offers)
+    }
+}
@@ -0,0 +1,44 @@
+use crate::migration::account_vesting::ACCOUNT_VESTING_MIGRATION;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::account::features::vesting::{errors, AccountVesting, VestingSchedule};
+use many_types::ledger::{Symbol, TokenAmount};
+
+impl LedgerStorage {
+    /// Returns the vesting schedule of an account, if it has one and vesting
+    /// accounts are enabled.
+    pub fn vesting_schedule(&self, id: &Address) -> Option<VestingSchedule> {
+        if !self.migrations.is_active(&ACCOUNT_VESTING_MIGRATION) {
+            return None;
+        }
+        let (account, _) = self.get_account(id).ok()?;
+        account
+            .features
+            .get::<AccountVesting>()
+            .ok()
+            .map(|vesting| vesting.schedule)
+    }
+
+    /// Verify that sending `amount` from an account holding `balance` leaves
+    /// the tokens still locked by its vesting schedule on the account.
+    pub(crate) fn check_vesting(
+        &self,
+        from: &Address,
+        symbol: &Symbol,
+        amount: &TokenAmount,
+        balance: &TokenAmount,
+    ) -> Result<(), ManyError> {
+        let schedule = match self.vesting_schedule(from) {
+            Some(schedule) if &schedule.symbol == symbol => schedule,
+            _ => return Ok(()),
+        };
+
+        let locked = schedule.locked(self.now());
+        let available = balance.saturating_sub(&locked);
+        if amount > &available {
+            return Err(errors::amount_not_vested(available));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,184 @@
+use crate::error;
+use crate::migration::watchlist::WATCHLIST_MIGRATION;
+use crate::storage::iterator::LedgerIterator;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::events::EventInfo;
+use many_modules::watchlist::{errors, WatchlistAction, WatchlistEntry};
+use merk::Op;
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+pub const WATCHLIST_ENTRIES_ROOT: &str = "/watchlist/entries/";
+pub const WATCHLIST_APPROVERS_ROOT: &str = "/watchlist/approvers";
+
+fn key_for_watchlist_entry(address: &Address) -> Vec<u8> {
+    format!("{WATCHLIST_ENTRIES_ROOT}{address}").into_bytes()
+}
+
+/// The decisions of the watchlist on a transfer it allowed. They are only
+/// logged once the transfer succeeds, see [LedgerStorage::log_screening].
+#[must_use]
+#[derive(Debug, Default)]
+pub struct WatchlistScreening(Vec<EventInfo>);
+
+impl LedgerStorage {
+    pub fn get_watchlist_entry(
+        &self,
+        address: &Address,
+    ) -> Result<Option<WatchlistEntry>, ManyError> {
+        self.persistent_store
+            .get(&key_for_watchlist_entry(address))
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+
+    pub fn get_watchlist_entries(&self) -> Result<BTreeMap<Address, WatchlistEntry>, ManyError> {
+        let mut entries = BTreeMap::new();
+        for item in LedgerIterator::all_watchlist_entries(&self.persistent_store) {
+            let (k, v) = item.map_err(ManyError::unknown)?;
+            let address = Address::from_str(
+                std::str::from_utf8(&k.as_ref()[WATCHLIST_ENTRIES_ROOT.len()..])
+                    .map_err(ManyError::deserialization_error)?,
+            )?;
+            entries.insert(
+                address,
+                minicbor::decode(&v).map_err(ManyError::deserialization_error)?,
+            );
+        }
+        Ok(entries)
+    }
+
+    pub fn get_watchlist_approvers(&self) -> Result<BTreeSet<Address>, ManyError> {
+        self.persistent_store
+            .get(WATCHLIST_APPROVERS_ROOT.as_bytes())
+            .map_err(error::storage_get_failed)?
+            .map_or(Ok(BTreeSet::new()), |bytes| {
+                minicbor::decode(&bytes).map_err(ManyError::deserialization_error)
+            })
+    }
+
+    pub fn add_watchlist_entry(
+        &mut self,
+        address: Address,
+        entry: WatchlistEntry,
+    ) -> Result<(), ManyError> {
+        self.persistent_store
+            .apply(&[(
+                key_for_watchlist_entry(&address),
+                Op::Put(self.encode_value(&entry)?),
+            )])
+            .map_err(error::storage_apply_failed)?;
+
+        self.log_event(EventInfo::WatchlistAdd {
+            address,
+            action: entry.action,
+            memo: entry.memo,
+        })?;
+        self.maybe_commit()
+    }
+
+    pub fn remove_watchlist_entry(
+        &mut self,
+        address: Address,
+        memo: Option<many_types::Memo>,
+    ) -> Result<(), ManyError> {
+        if self.get_watchlist_entry(&address)?.is_none() {
+            return Err(errors::address_not_watched(address));
+        }
+
+        self.persistent_store
+            .apply(&[(key_for_watchlist_entry(&address), Op::Delete)])
+            .map_err(error::storage_apply_failed)?;
+
+        self.log_event(EventInfo::WatchlistRemove { address, memo })?;
+        self.maybe_commit()
+    }
+
+    pub fn set_watchlist_approvers(
+        &mut self,
+        approvers: BTreeSet<Address>,
+    ) -> Result<(), ManyError> {
+        self.persistent_store
+            .apply(&[(
+                WATCHLIST_APPROVERS_ROOT.as_bytes().to_vec(),
+                Op::Put(self.encode_value(&approvers)?),
+            )])
+            .map_err(error::storage_apply_failed)?;
+
+        self.log_event(EventInfo::WatchlistSetApprovers { approvers })?;
+        self.maybe_commit()
+    }
+
+    /// Screen a transfer against the watchlist. Every watched address among the
+    /// sender, source and destination makes a decision, and the transfer is
+    /// refused if any of them does not allow it. Mints and burns are screened
+    /// as transfers from and to the token.
+    ///
+    /// The decisions on a refused transfer are logged right away. Those on an
+    /// allowed transfer are returned, to be logged with
+    /// [LedgerStorage::log_screening] once the transfer succeeds.
+    pub fn screen_transfer(
+        &mut self,
+        sender: &Address,
+        from: &Address,
+        to: &Address,
+    ) -> Result<WatchlistScreening, ManyError> {
+        if !self.migrations.is_active(&WATCHLIST_MIGRATION) {
+            return Ok(WatchlistScreening::default());
+        }
+
+        let addresses = BTreeSet::from([*sender, *from, *to]);
+        let mut events = Vec::new();
+        let mut result = Ok(());
+        for address in addresses {
+            let entry = match self.get_watchlist_entry(&address)? {
+                Some(entry) => entry,
+                None => continue,
+            };
+
+            let allowed = match entry.action {
+                WatchlistAction::Alert => true,
+                WatchlistAction::RequireApproval => {
+                    self.get_watchlist_approvers()?.contains(sender)
+                }
+                WatchlistAction::Reject => false,
+            };
+
+            events.push(EventInfo::WatchlistScreen {
+                sender: *sender,
+                from: *from,
+                to: *to,
+                address,
+                action: entry.action,
+                allowed,
+            });
+
+            if !allowed && result.is_ok() {
+                result = Err(match entry.action {
+                    WatchlistAction::RequireApproval => errors::approval_required(address),
+                    _ => errors::transfer_rejected(address),
+                });
+            }
+        }
+
+        if let Err(e) = result {
+            self.log_screening(WatchlistScreening(events))?;
+            return Err(e);
+        }
+        Ok(WatchlistScreening(events))
+    }
+
+    /// Log the decisions of the watchlist on a transfer.
+    pub fn log_screening(&mut self, screening: WatchlistScreening) -> Result<(), ManyError> {
+        if screening.0.is_empty() {
+            return Ok(());
+        }
+        for event in screening.0 {
+            self.log_event(event)?;
+        }
+        self.maybe_commit()
+    }
+}
@@ -96,7 +96,7 @@ impl SomeId {
 #[derive(Debug, Default, Eq, Parameter, PartialEq)]
 #[param(
     name = "error",
-    regex = "(unauthorized)|(missing permission)|(immutable)|(invalid sender)|(unable to distribute zero)|(partial burn disabled)|(missing funds)|(over maximum)|(ticker exists)|(invalid ticker length)|(no token owner)"
+    regex = "(unauthorized)|(missing permission)|(immutable)|(invalid sender)|(unable to distribute zero)|(partial burn disabled)|(missing funds)|(over maximum)|(ticker exists)|(invalid ticker length)|(no token owner)|(invalid attachment)"
 )]
 pub enum SomeError {
     #[default]
@@ -111,6 +111,7 @@ pub enum SomeError {
     TickerExists,
     InvalidTickerLength,
     NoTokenOwner,
+    InvalidAttachment,
 }
 
 impl FromStr for SomeError {
@@ -129,6 +130,7 @@ impl FromStr for SomeError {
             "ticker exists" => Self::TickerExists,
             "invalid ticker length" => Self::InvalidTickerLength,
             "no token owner" => Self::NoTokenOwner,
+            "invalid attachment" => Self::InvalidAttachment,
             _ => unimplemented!(),
         })
     }
@@ -180,6 +182,7 @@ impl SomeError {
             SomeError::TickerExists => error::ticker_exists("").code(),
             SomeError::InvalidTickerLength => error::invalid_ticker_length("").code(),
             SomeError::NoTokenOwner => error::no_token_owner().code(),
+            SomeError::InvalidAttachment => error::invalid_attachment("").code(),
         }
     }
 }
@@ -1,14 +1,18 @@
 pub mod cucumber;
+pub mod server;
 
 use async_channel::unbounded;
 use coset::CborSerializable;
 use itertools::Itertools;
 use many_error::ManyError;
 use many_identity::testing::identity;
-use many_identity::{Address, Identity};
-use many_identity_dsa::ed25519::generate_random_ed25519_identity;
+use many_identity::verifiers::AnonymousVerifier;
+use many_identity::{Address, AnonymousIdentity, Identity};
+use many_identity_dsa::ed25519::generate_ed25519_identity_from_seed;
 use many_ledger::json::InitialStateJson;
 use many_ledger::module::LedgerModuleImpl;
+use many_ledger::registry::{default_modules, registry, LedgerModuleContext};
+use many_ledger::storage::InnerStorage;
 use many_migration::{InnerMigration, MigrationConfig};
 use many_modules::abci_backend::{AbciBlock, ManyAbciModuleBackend};
 use many_modules::account::features::multisig::{
@@ -22,19 +26,21 @@ use many_modules::ledger::extended_info::TokenExtendedInfo;
 use many_modules::ledger::{
     BalanceArgs, LedgerCommandsModuleBackend, LedgerModuleBackend, TokenCreateArgs,
 };
-use many_modules::{account, events, ledger};
+use many_modules::{account, events, ledger, ManyModule};
 use many_protocol::{context::Context, RequestMessage, ResponseMessage};
+use many_server::ManyServer;
 use many_types::ledger::{
     LedgerTokensAddressMap, Symbol, TokenAmount, TokenInfoSummary, TokenMaybeOwner,
 };
 use many_types::Memo;
-use merk::Merk;
 use minicbor::bytes::ByteVec;
 use once_cell::sync::Lazy;
 use proptest::prelude::*;
 use std::{
-    collections::{BTreeMap, BTreeSet},
+    collections::{hash_map::RandomState, BTreeMap, BTreeSet},
+    hash::{BuildHasher, Hasher},
     str::FromStr,
+    sync::{Arc, Mutex},
 };
 
 pub fn default_token_create_args(
@@ -68,45 +74,65 @@ pub fn default_token_create_args(
 }
 
 pub struct MigrationHarness {
-    inner: &'static InnerMigration<merk::Merk, ManyError>,
+    inner: &'static InnerMigration<InnerStorage, ManyError>,
     block_height: u64,
     enabled: bool,
+    extra: BTreeMap<String, serde_json::Value>,
 }
 
 impl MigrationHarness {
+    /// Add a parameter to the `extra` metadata of the migration.
+    pub fn with_extra(mut self, key: impl ToString, value: serde_json::Value) -> Self {
+        self.extra.insert(key.to_string(), value);
+        self
+    }
+
     pub fn to_json_str(&self) -> String {
         let maybe_enabled = if !self.enabled {
             r#", "disabled": true"#
         } else {
             ""
         };
+        let extra: String = self
+            .extra
+            .iter()
+            .map(|(key, value)| format!(r#", "{key}": {value}"#))
+            .collect();
 
         format!(
-            r#"{{ "name": "{}", "block_height": {}, "issue": "" {maybe_enabled} }}"#,
+            r#"{{ "name": "{}", "block_height": {}, "issue": "" {maybe_enabled}{extra} }}"#,
             self.inner.name(),
             self.block_height
         )
     }
 }
 
-impl From<(u64, &'static InnerMigration<merk::Merk, ManyError>)> for MigrationHarness {
-    fn from((block_height, inner): (u64, &'static InnerMigration<Merk, ManyError>)) -> Self {
+impl From<(u64, &'static InnerMigration<InnerStorage, ManyError>)> for MigrationHarness {
+    fn from(
+        (block_height, inner): (u64, &'static InnerMigration<InnerStorage, ManyError>),
+    ) -> Self {
         MigrationHarness {
             inner,
             block_height,
             enabled: true,
+            extra: BTreeMap::new(),
         }
     }
 }
 
-impl From<(u64, &'static InnerMigration<merk::Merk, ManyError>, bool)> for MigrationHarness {
+impl From<(u64, &'static InnerMigration<InnerStorage, ManyError>, bool)> for MigrationHarness {
     fn from(
-        (block_height, inner, enabled): (u64, &'static InnerMigration<Merk, ManyError>, bool),
+        (block_height, inner, enabled): (
+            u64,
+            &'static InnerMigration<InnerStorage, ManyError>,
+            bool,
+        ),
     ) -> Self {
         MigrationHarness {
             inner,
             block_height,
             enabled,
+            extra: BTreeMap::new(),
         }
     }
 }
@@ -166,6 +192,56 @@ pub fn create_account_args(account_type: AccountType) -> account::CreateArgs {
     }
 }
 
+/// The environment variable to set to replay a test with a given seed.
+pub const SEED_ENV_VAR: &str = "MANY_TEST_SEED";
+
+/// The seed to generate the identities of a setup from. It is read from
+/// [SEED_ENV_VAR] if set, or picked randomly otherwise. The seed is printed so
+/// a failing test can be replayed exactly.
+pub fn test_seed() -> u64 {
+    let seed = std::env::var(SEED_ENV_VAR)
+        .map(|seed| seed.parse().expect("The test seed must be a number."))
+        .unwrap_or_else(|_| RandomState::new().build_hasher().finish());
+    eprintln!("{SEED_ENV_VAR}={seed}");
+    seed
+}
+
+/// A block of requests, delivered in order as Tendermint would.
+#[derive(Clone, Debug, Default)]
+pub struct TestBlock {
+    /// The address of the validator proposing the block.
+    pub proposer: Option<ByteVec>,
+
+    /// The time of the block, in seconds. Defaults to a second after the
+    /// previous block.
+    pub time: Option<u64>,
+
+    pub requests: Vec<RequestMessage>,
+}
+
+impl TestBlock {
+    pub fn new(requests: impl IntoIterator<Item = RequestMessage>) -> Self {
+        Self {
+            requests: requests.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_proposer(self, proposer: impl Into<ByteVec>) -> Self {
+        Self {
+            proposer: Some(proposer.into()),
+            ..self
+        }
+    }
+
+    pub fn with_time(self, time: u64) -> Self {
+        Self {
+            time: Some(time),
+            ..self
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Setup {
     pub module_impl: LedgerModuleImpl,
@@ -173,6 +249,9 @@ pub struct Setup {
     pub cred_id: CredentialId,
     pub public_key: PublicKey,
 
+    /// The seed the identity of this setup was generated from.
+    pub seed: u64,
+
     time: Option<u64>,
 }
 
@@ -182,31 +261,62 @@ impl Default for Setup {
     }
 }
 
+/// The staging initial state of the ledger.
+pub(crate) fn staging_state() -> InitialStateJson {
+    InitialStateJson::read("../../staging/ledger_state.json5")
+        .or_else(|_| InitialStateJson::read("staging/ledger_state.json5"))
+        .expect("Could not read initial state.")
+}
+
+/// The configuration enabling `migrations`, or none if there are none.
+fn migration_config(
+    migrations: impl IntoIterator<Item = impl Into<MigrationHarness>>,
+) -> Option<MigrationConfig> {
+    let mut migrations = migrations
+        .into_iter()
+        .map(|x| x.into().to_json_str())
+        .peekable();
+    migrations.peek()?;
+
+    let migrations = format!(r#"{{ "migrations": [{}] }}"#, migrations.join(","));
+    Some(serde_json::from_str(&migrations).unwrap())
+}
+
 impl Setup {
     fn _new(
         blockchain: bool,
         migration_config: Option<MigrationConfig>,
         skip_hash_check: bool, // If true, skip the staging file hash check
     ) -> Self {
-        let id = generate_random_ed25519_identity();
-        let public_key = PublicKey(id.public_key().to_vec().unwrap().into());
-
-        let store_path = tempfile::tempdir().expect("Could not create a temporary dir.");
-        tracing::debug!("Store path: {:?}", store_path.path());
-        let mut state = InitialStateJson::read("../../staging/ledger_state.json5")
-            .or_else(|_| InitialStateJson::read("staging/ledger_state.json5"))
-            .expect("Could not read initial state.");
+        let mut state = staging_state();
 
         if skip_hash_check {
             state.hash = None;
         }
 
+        Setup::_new_with_state(blockchain, migration_config, state, None)
+    }
+
+    fn _new_with_state(
+        blockchain: bool,
+        migration_config: Option<MigrationConfig>,
+        state: InitialStateJson,
+        seed: Option<u64>,
+    ) -> Self {
+        let seed = seed.unwrap_or_else(test_seed);
+        let id = generate_ed25519_identity_from_seed(seed);
+        let public_key = PublicKey(id.public_key().to_vec().unwrap().into());
+
+        let store_path = tempfile::tempdir().expect("Could not create a temporary dir.");
+        tracing::debug!("Store path: {:?}", store_path.path());
+
         Self {
             module_impl: LedgerModuleImpl::new(state, migration_config, store_path, blockchain)
                 .unwrap(),
             id: id.address(),
             cred_id: CredentialId(vec![1; 16].into()),
             public_key,
+            seed,
             time: Some(1_000_000),
         }
     }
@@ -215,24 +325,33 @@ impl Setup {
         Setup::_new(blockchain, None, false)
     }
 
+    /// Create a setup whose identity is generated from `seed`, regardless of
+    /// [SEED_ENV_VAR].
+    pub fn new_with_seed(blockchain: bool, seed: u64) -> Self {
+        Setup::_new_with_state(blockchain, None, staging_state(), Some(seed))
+    }
+
+    /// Create a setup from the staging initial state as changed by `f`, with
+    /// the given migrations. The initial state hash isn't checked since `f`
+    /// changes it.
+    pub fn new_with_state(
+        blockchain: bool,
+        migrations: impl IntoIterator<Item = impl Into<MigrationHarness>>,
+        f: impl FnOnce(&mut InitialStateJson),
+    ) -> Self {
+        let mut state = staging_state();
+        state.hash = None;
+        f(&mut state);
+
+        Setup::_new_with_state(blockchain, migration_config(migrations), state, None)
+    }
+
     pub fn new_with_migrations(
         blockchain: bool,
         migrations: impl IntoIterator<Item = impl Into<MigrationHarness>>,
         skip_hash_check: bool,
     ) -> Self {
-        let migrations = format!(
-            r#"{{ "migrations": [{}] }}"#,
-            migrations
-                .into_iter()
-                .map(|x| x.into().to_json_str())
-                .join(",")
-        );
-
-        Setup::_new(
-            blockchain,
-            Some(serde_json::from_str(&migrations).unwrap()),
-            skip_hash_check,
-        )
+        Setup::_new(blockchain, migration_config(migrations), skip_hash_check)
     }
 
     pub fn set_balance(&mut self, id: Address, amount: u64, symbol: Symbol) {
@@ -327,12 +446,35 @@ impl Setup {
     /// Execute a block begin+inner_f+end+commit.
     /// See https://docs.tendermint.com/master/spec/abci/abci.html#block-execution
     pub fn block<R>(&mut self, inner_f: impl FnOnce(&mut Self) -> R) -> (u64, R) {
-        if let Some(t) = self.time {
-            self.time = Some(t + 1);
-        }
+        self._block(None, None, inner_f)
+    }
+
+    /// Execute a block of requests, in order, through the modules of the
+    /// ledger (validation included) rather than by calling the backends
+    /// directly. Returns the height of the block and the response of each
+    /// request.
+    pub fn block_with(&mut self, block: TestBlock) -> (u64, Vec<Result<Vec<u8>, ManyError>>) {
+        let TestBlock {
+            proposer,
+            time,
+            requests,
+        } = block;
+        self._block(time, proposer, |h| h.execute_requests(requests))
+    }
+
+    fn _block<R>(
+        &mut self,
+        time: Option<u64>,
+        proposer: Option<ByteVec>,
+        inner_f: impl FnOnce(&mut Self) -> R,
+    ) -> (u64, R) {
+        self.time = time.or_else(|| self.time.map(|t| t + 1));
 
         self.module_impl
-            .begin_block(AbciBlock { time: self.time })
+            .begin_block(AbciBlock {
+                time: self.time,
+                proposer,
+            })
             .expect("Could not begin block");
 
         let r = inner_f(self);
@@ -345,6 +487,57 @@ impl Setup {
         (info.height, r)
     }
 
+    /// Execute requests through the modules a ledger server registers by
+    /// default. The modules need to own the module implementation, so it is
+    /// lent to them for the duration of the requests.
+    fn execute_requests(
+        &mut self,
+        requests: Vec<RequestMessage>,
+    ) -> Vec<Result<Vec<u8>, ManyError>> {
+        let scratch = tempfile::tempdir().expect("Could not create a temporary dir.");
+        let placeholder = LedgerModuleImpl::load(None, scratch.path(), false)
+            .expect("Could not create a placeholder ledger.");
+        let module_impl = Arc::new(Mutex::new(std::mem::replace(
+            &mut self.module_impl,
+            placeholder,
+        )));
+
+        let server = ManyServer::new(
+            "many-ledger-test-utils",
+            AnonymousIdentity,
+            AnonymousVerifier,
+            None,
+        );
+        let context = LedgerModuleContext {
+            check_webauthn: false,
+            ..LedgerModuleContext::new(module_impl.clone())
+        };
+        registry()
+            .instantiate(&mut server.lock().unwrap(), &context, &default_modules())
+            .expect("Could not instantiate the ledger modules.");
+
+        let responses = requests
+            .into_iter()
+            .map(|message| {
+                let module = server
+                    .lock()
+                    .unwrap()
+                    .find_module(&message)
+                    .ok_or_else(|| ManyError::invalid_method_name(message.method.clone()))?;
+                module.validate(&message, &coset::CoseSign1::default())?;
+                smol::block_on(module.execute(message))?.data
+            })
+            .collect();
+
+        drop(context);
+        drop(server);
+        self.module_impl = Arc::try_unwrap(module_impl)
+            .unwrap_or_else(|_| panic!("The ledger modules were not dropped."))
+            .into_inner()
+            .unwrap();
+        responses
+    }
+
     pub fn add_roles_as(
         &mut self,
         id: Address,
@@ -443,8 +636,13 @@ impl Setup {
     /// Approve a multisig transaction.
     pub fn multisig_approve(&mut self, id: Address, token: &ByteVec) -> Result<(), ManyError> {
         let token = token.clone();
-        self.module_impl
-            .multisig_approve(&id, account::features::multisig::ApproveArgs { token })?;
+        self.module_impl.multisig_approve(
+            &id,
+            account::features::multisig::ApproveArgs {
+                token,
+                approve_as: None,
+            },
+        )?;
         Ok(())
     }
 
@@ -640,7 +838,10 @@ fn event_from_kind(
                 .unwrap()
                 .token;
             events::AccountMultisigTransaction::AccountMultisigApprove(
-                account::features::multisig::ApproveArgs { token },
+                account::features::multisig::ApproveArgs {
+                    token,
+                    approve_as: None,
+                },
             )
         }
         events::EventKind::AccountMultisigRevoke => {
@@ -662,7 +863,10 @@ fn event_from_kind(
                 .token;
 
             events::AccountMultisigTransaction::AccountMultisigRevoke(
-                account::features::multisig::RevokeArgs { token },
+                account::features::multisig::RevokeArgs {
+                    token,
+                    revoke_as: None,
+                },
             )
         }
         events::EventKind::AccountMultisigExecute => {
@@ -688,6 +892,7 @@ fn event_from_kind(
                     &i,
                     account::features::multisig::ApproveArgs {
                         token: token.clone(),
+                        approve_as: None,
                     },
                 );
             }
@@ -0,0 +1,156 @@
+use coset::CoseSign1;
+use many_error::ManyError;
+use many_identity::verifiers::AnonymousVerifier;
+use many_identity::{Address, Identity};
+use many_identity_dsa::ed25519::generate_ed25519_identity_from_seed;
+use many_identity_dsa::CoseKeyVerifier;
+use many_kvstore::module::KvStoreModuleImpl;
+use many_ledger::module::LedgerModuleImpl;
+use many_ledger::registry::LedgerModuleContext;
+use many_modules::kvstore;
+use many_protocol::{
+    decode_response_from_cose_sign1, encode_cose_sign1_from_request, RequestMessage,
+    RequestMessageBuilder, ResponseMessage,
+};
+use many_server::transport::LowLevelManyRequestHandler;
+use many_server::{ManyServer, ModuleConfig};
+use minicbor::{Decode, Encode};
+use std::sync::{Arc, Mutex};
+
+/// A MANY server with the ledger, kvstore and idstore modules registered, as
+/// the binaries would. Unlike [crate::Setup], requests go through the whole
+/// server: envelopes are signed, verified, validated and routed to the right
+/// module, so tests can exercise interactions between modules.
+pub struct ServerSetup {
+    pub server: Arc<Mutex<ManyServer>>,
+    pub ledger: Arc<Mutex<LedgerModuleImpl>>,
+    pub kvstore: Arc<Mutex<KvStoreModuleImpl>>,
+    pub address: Address,
+}
+
+impl std::fmt::Debug for ServerSetup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServerSetup")
+            .field("address", &self.address)
+            .finish()
+    }
+}
+
+impl Default for ServerSetup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ServerSetup {
+    pub fn new() -> Self {
+        Self::new_with_seed(crate::test_seed())
+    }
+
+    /// Create a server whose identity is generated from `seed`.
+    pub fn new_with_seed(seed: u64) -> Self {
+        let ledger_state = crate::staging_state();
+        let ledger = Arc::new(Mutex::new(
+            LedgerModuleImpl::new(
+                ledger_state,
+                None,
+                tempfile::tempdir().expect("Could not create a temporary dir."),
+                false,
+            )
+            .unwrap(),
+        ));
+
+        let kvstore_state = std::fs::read_to_string("../../staging/kvstore_state.json5")
+            .or_else(|_| std::fs::read_to_string("staging/kvstore_state.json5"))
+            .expect("Could not read initial state.");
+        let kvstore = Arc::new(Mutex::new(
+            KvStoreModuleImpl::new(
+                json5::from_str(&kvstore_state).unwrap(),
+                tempfile::tempdir().expect("Could not create a temporary dir."),
+                false,
+            )
+            .unwrap(),
+        ));
+
+        let identity = generate_ed25519_identity_from_seed(seed);
+        let address = identity.address();
+        let server = ManyServer::simple(
+            "many-ledger-test-utils",
+            identity,
+            (AnonymousVerifier, CoseKeyVerifier),
+            None,
+        );
+
+        {
+            let mut s = server.lock().unwrap();
+            let modules = [
+                "ledger",
+                "ledger.commands",
+                "tokens",
+                "events",
+                "idstore",
+                "account",
+                "account.multisig",
+                "account.hooks",
+            ]
+            .map(ModuleConfig::new);
+            many_ledger::registry::registry()
+                .instantiate(&mut s, &LedgerModuleContext::new(ledger.clone()), &modules)
+                .unwrap();
+
+            // The ledger implements the events and account attributes.
+            s.add_module(kvstore::KvStoreModule::new(kvstore.clone()));
+            s.add_module(kvstore::KvStoreCommandsModule::new(kvstore.clone()));
+            s.add_module(kvstore::KvStoreTransferModule::new(kvstore.clone()));
+        }
+
+        Self {
+            server,
+            ledger,
+            kvstore,
+            address,
+        }
+    }
+
+    /// A request to this server.
+    pub fn request_message(
+        &self,
+        from: Address,
+        method: impl ToString,
+        data: impl Encode<()>,
+    ) -> RequestMessage {
+        RequestMessageBuilder::default()
+            .version(1)
+            .from(from)
+            .to(self.address)
+            .method(method.to_string())
+            .data(minicbor::to_vec(data).unwrap())
+            .build()
+            .unwrap()
+    }
+
+    /// Sign a request and send it to the server.
+    pub fn execute(&self, request: RequestMessage, identity: &impl Identity) -> ResponseMessage {
+        let envelope = encode_cose_sign1_from_request(request, identity).unwrap();
+        self.execute_envelope(envelope)
+    }
+
+    /// Send an envelope to the server as is, for example to test how the
+    /// server handles invalid envelopes.
+    pub fn execute_envelope(&self, envelope: CoseSign1) -> ResponseMessage {
+        let response = smol::block_on(self.server.execute(envelope)).unwrap();
+        decode_response_from_cose_sign1(&response, None, &CoseKeyVerifier).unwrap()
+    }
+
+    /// Call a method as `identity` and decode its return value.
+    pub fn call<R: for<'a> Decode<'a, ()>>(
+        &self,
+        identity: &impl Identity,
+        method: impl ToString,
+        data: impl Encode<()>,
+    ) -> Result<R, ManyError> {
+        let request = self.request_message(identity.address(), method, data);
+        let bytes = self.execute(request, identity).data?;
+        minicbor::decode(&bytes).map_err(ManyError::deserialization_error)
+    }
+}
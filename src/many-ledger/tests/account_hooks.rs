@@ -0,0 +1,142 @@
+use {
+    many_identity::testing::identity,
+    many_identity::Address,
+    many_ledger::migration::account_hooks::ACCOUNT_HOOKS_MIGRATION,
+    many_ledger::storage::hooks::{hook_record_key, HOOK_RECORDS_KEY_PREFIX},
+    many_ledger_test_utils::*,
+    many_modules::account::features::hooks::{
+        errors, AccountHooks, AccountHooksModuleBackend, HookClearArgs, HookRecord,
+        HookRecordsArgs, HookTrigger,
+    },
+    many_modules::account::features::{FeatureInfo, FeatureSet},
+    many_modules::account::{AccountModuleBackend, CreateArgs},
+    many_modules::events::EventInfo,
+};
+
+fn create_hooked_account(
+    setup: &mut Setup,
+    triggers: impl IntoIterator<Item = HookTrigger>,
+) -> Address {
+    let sender = setup.id;
+    let args = CreateArgs {
+        description: None,
+        roles: None,
+        features: FeatureSet::from_iter([AccountHooks::new(triggers).as_feature()]),
+    };
+    AccountModuleBackend::create(&mut setup.module_impl, &sender, args)
+        .unwrap()
+        .id
+}
+
+fn records_args(account: Address) -> HookRecordsArgs {
+    HookRecordsArgs {
+        account,
+        after: None,
+        count: None,
+    }
+}
+
+#[test]
+fn incoming_send() {
+    let mut setup = Setup::new_with_migrations(false, [(0, &ACCOUNT_HOOKS_MIGRATION)], true);
+    let account = create_hooked_account(&mut setup, [HookTrigger::IncomingSend]);
+    setup.set_balance(identity(5), 1_000, *MFX_SYMBOL);
+
+    setup.send_(identity(5), account, 10u32);
+    setup.send_(identity(5), account, 20u32);
+    // Outgoing sends do not fire the hook.
+    setup.send_(account, identity(6), 5u32);
+
+    let records = setup
+        .module_impl
+        .hook_records(&identity(1), records_args(account))
+        .unwrap()
+        .records;
+    assert_eq!(records.len(), 2);
+    assert!(records
+        .iter()
+        .all(|record| record.trigger == HookTrigger::IncomingSend));
+    match &records[1].event.content {
+        EventInfo::Send { from, amount, .. } => {
+            assert_eq!(*from, identity(5));
+            assert_eq!(*amount, 20u32);
+        }
+        _ => unreachable!(),
+    }
+
+    // Records are written in the kvstore namespace of the account.
+    let key = hook_record_key(records[1].event.id.clone());
+    assert!(key.starts_with(HOOK_RECORDS_KEY_PREFIX));
+    let value = setup
+        .module_impl
+        .account_kvstore_get(&account, &key)
+        .unwrap()
+        .unwrap();
+    let record: HookRecord = minicbor::decode(&value).unwrap();
+    assert_eq!(record.event.id, records[1].event.id);
+    assert!(setup
+        .module_impl
+        .account_kvstore_get(&identity(5), &key)
+        .unwrap()
+        .is_none());
+
+    // Pagination.
+    let after = setup
+        .module_impl
+        .hook_records(
+            &identity(1),
+            HookRecordsArgs {
+                after: Some(records[0].event.id.clone()),
+                ..records_args(account)
+            },
+        )
+        .unwrap()
+        .records;
+    assert_eq!(after.len(), 1);
+    assert_eq!(after[0].event.id, records[1].event.id);
+
+    // Only owners can clear the records.
+    let clear_args = HookClearArgs {
+        account,
+        up_to: records[0].event.id.clone(),
+    };
+    assert!(setup
+        .module_impl
+        .hook_clear(&identity(6), clear_args.clone())
+        .is_err());
+    let sender = setup.id;
+    setup.module_impl.hook_clear(&sender, clear_args).unwrap();
+
+    let records = setup
+        .module_impl
+        .hook_records(&identity(1), records_args(account))
+        .unwrap()
+        .records;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].event.id, after[0].event.id);
+}
+
+#[test]
+fn account_without_hooks() {
+    let mut setup = Setup::new_with_migrations(false, [(0, &ACCOUNT_HOOKS_MIGRATION)], true);
+    let account = setup.create_account_(AccountType::Ledger);
+    assert_many_err(
+        setup
+            .module_impl
+            .hook_records(&identity(1), records_args(account)),
+        errors::hooks_not_enabled(account),
+    );
+}
+
+#[test]
+fn disabled_without_migration() {
+    let mut setup = Setup::new(false);
+    let account = create_hooked_account(&mut setup, [HookTrigger::IncomingSend]);
+    setup.set_balance(identity(5), 1_000, *MFX_SYMBOL);
+    setup.send_(identity(5), account, 10u32);
+
+    assert!(setup
+        .module_impl
+        .hook_records(&identity(1), records_args(account))
+        .is_err());
+}
@@ -0,0 +1,137 @@
+use {
+    async_channel::unbounded,
+    many_identity::testing::identity,
+    many_identity::Address,
+    many_ledger::migration::account_remove_features::ACCOUNT_REMOVE_FEATURES_MIGRATION,
+    many_ledger_test_utils::*,
+    many_modules::account::features::multisig::MultisigAccountFeature,
+    many_modules::account::features::{ledger::AccountLedger, FeatureInfo, FeatureSet},
+    many_modules::account::{self, AccountModuleBackend, Role},
+    many_protocol::{context::Context, RequestMessage},
+    std::collections::{BTreeMap, BTreeSet},
+};
+
+fn setup() -> Setup {
+    Setup::new_with_migrations(false, [(0, &ACCOUNT_REMOVE_FEATURES_MIGRATION)], true)
+}
+
+/// Create an account with the multisig and ledger features.
+fn create_account(setup: &mut Setup) -> Address {
+    let sender = setup.id;
+    let args = account::CreateArgs {
+        description: None,
+        roles: Some(BTreeMap::from([
+            (identity(2), BTreeSet::from([Role::CanMultisigApprove])),
+            (
+                identity(3),
+                BTreeSet::from([Role::CanMultisigSubmit, Role::CanLedgerTransact]),
+            ),
+        ])),
+        features: FeatureSet::from_iter([
+            MultisigAccountFeature::default().as_feature(),
+            AccountLedger.as_feature(),
+        ]),
+    };
+    AccountModuleBackend::create(&mut setup.module_impl, &sender, args)
+        .unwrap()
+        .id
+}
+
+fn remove_args(
+    account: Address,
+    features: impl IntoIterator<Item = u32>,
+) -> account::RemoveFeaturesArgs {
+    account::RemoveFeaturesArgs {
+        account,
+        features: features.into_iter().collect(),
+    }
+}
+
+fn account_info(setup: &Setup, account: Address) -> account::InfoReturn {
+    AccountModuleBackend::info(
+        &setup.module_impl,
+        &setup.id,
+        account::InfoArgs { account },
+        Context::new(RequestMessage::default(), unbounded().0),
+    )
+    .unwrap()
+}
+
+#[test]
+fn remove_features() {
+    let mut setup = setup();
+    let account = create_account(&mut setup);
+    let sender = setup.id;
+
+    // Only owners can remove features.
+    assert!(setup
+        .module_impl
+        .remove_features(&identity(3), remove_args(account, [1]))
+        .is_err());
+
+    setup
+        .module_impl
+        .remove_features(&sender, remove_args(account, [1]))
+        .unwrap();
+
+    let info = account_info(&setup, account);
+    assert!(!info.features.has_id(1));
+    assert!(info.features.has_id(0));
+    // The multisig roles are removed with the feature.
+    assert!(!info.roles.contains_key(&identity(2)));
+    assert_eq!(
+        info.roles.get(&identity(3)),
+        Some(&BTreeSet::from([Role::CanLedgerTransact]))
+    );
+    assert!(info.roles[&account].contains(&Role::Owner));
+}
+
+#[test]
+fn remove_features_invalid() {
+    let mut setup = setup();
+    let account = create_account(&mut setup);
+    let sender = setup.id;
+
+    for features in [vec![], vec![2], vec![0, 1]] {
+        assert!(setup
+            .module_impl
+            .remove_features(&sender, remove_args(account, features))
+            .is_err());
+    }
+    let info = account_info(&setup, account);
+    assert!(info.features.has_id(0));
+    assert!(info.features.has_id(1));
+}
+
+#[test]
+fn remove_multisig_with_pending_transactions() {
+    let mut setup = setup();
+    let account = create_account(&mut setup);
+    let sender = setup.id;
+    setup.multisig_send_(account, identity(4), 10u16);
+
+    let result = setup
+        .module_impl
+        .remove_features(&sender, remove_args(account, [1]));
+    assert_eq!(
+        result.unwrap_err().code(),
+        account::errors::feature_in_use(1, "").code()
+    );
+
+    // Other features can still be removed.
+    setup
+        .module_impl
+        .remove_features(&sender, remove_args(account, [0]))
+        .unwrap();
+}
+
+#[test]
+fn remove_features_disabled() {
+    let mut setup = Setup::default();
+    let account = create_account(&mut setup);
+    let sender = setup.id;
+    assert!(setup
+        .module_impl
+        .remove_features(&sender, remove_args(account, [1]))
+        .is_err());
+}
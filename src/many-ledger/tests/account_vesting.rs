@@ -0,0 +1,182 @@
+use {
+    many_identity::testing::identity,
+    many_identity::Address,
+    many_ledger::migration::account_remove_features::ACCOUNT_REMOVE_FEATURES_MIGRATION,
+    many_ledger::migration::account_vesting::ACCOUNT_VESTING_MIGRATION,
+    many_ledger_test_utils::*,
+    many_modules::account::features::vesting::{
+        errors, AccountVesting, AccountVestingModuleBackend, InfoArgs, VestingSchedule,
+    },
+    many_modules::account::features::{
+        ledger::AccountLedger, FeatureInfo, FeatureSet, TryCreateFeature,
+    },
+    many_modules::account::{self, AccountModuleBackend},
+    many_types::Timestamp,
+    std::collections::BTreeSet,
+};
+
+/// Blocks start at 1_000_001 seconds and advance one second each. The
+/// schedule unlocks 10 tokens per second for 10 seconds from the first block.
+fn schedule(cliff: Option<u64>) -> VestingSchedule {
+    VestingSchedule {
+        symbol: *MFX_SYMBOL,
+        total: 100u32.into(),
+        start: Timestamp::new(1_000_001).unwrap(),
+        end: Timestamp::new(1_000_011).unwrap(),
+        cliff: cliff.map(|c| Timestamp::new(c).unwrap()),
+    }
+}
+
+fn create_vesting_account(setup: &mut Setup, schedule: VestingSchedule) -> Address {
+    let sender = setup.id;
+    let args = account::CreateArgs {
+        description: None,
+        roles: None,
+        features: FeatureSet::from_iter([
+            AccountLedger.as_feature(),
+            AccountVesting::new(schedule).as_feature(),
+        ]),
+    };
+    let (_, account) = setup.block(|h| {
+        AccountModuleBackend::create(&mut h.module_impl, &sender, args)
+            .unwrap()
+            .id
+    });
+    // 50 tokens above the schedule can be sent at any time.
+    setup.set_balance(account, 150, *MFX_SYMBOL);
+    account
+}
+
+#[test]
+fn linear() {
+    let mut setup = Setup::new_with_migrations(true, [(0, &ACCOUNT_VESTING_MIGRATION)], true);
+    let owner = setup.id;
+    let account = create_vesting_account(&mut setup, schedule(None));
+
+    setup.block(|h| {
+        let info =
+            AccountVestingModuleBackend::info(&h.module_impl, &identity(5), InfoArgs { account })
+                .unwrap();
+        assert_eq!(info.schedule, schedule(None));
+        assert_eq!(info.unlocked, 10u32);
+        assert_eq!(info.remaining, 90u32);
+
+        assert_many_err(
+            h.send_as(owner, account, identity(5), 61u32, *MFX_SYMBOL),
+            errors::amount_not_vested(60u32),
+        );
+        h.send_as(owner, account, identity(5), 60u32, *MFX_SYMBOL)
+            .unwrap();
+    });
+    assert_eq!(setup.balance_(account), 90u32);
+
+    // Tokens sent to the account are not locked.
+    setup.set_balance(account, 100, *MFX_SYMBOL);
+    setup.block(|h| {
+        h.send_as(owner, account, identity(5), 20u32, *MFX_SYMBOL)
+            .unwrap();
+    });
+
+    for _ in 0..8 {
+        setup.block(|_| {});
+    }
+    setup.block(|h| {
+        h.send_as(owner, account, identity(5), 80u32, *MFX_SYMBOL)
+            .unwrap();
+    });
+    assert!(setup.balance_(account).is_zero());
+}
+
+#[test]
+fn cliff() {
+    let mut setup = Setup::new_with_migrations(true, [(0, &ACCOUNT_VESTING_MIGRATION)], true);
+    let owner = setup.id;
+    let account = create_vesting_account(&mut setup, schedule(Some(1_000_005)));
+
+    setup.block(|h| {
+        assert_many_err(
+            h.send_as(owner, account, identity(5), 51u32, *MFX_SYMBOL),
+            errors::amount_not_vested(50u32),
+        );
+    });
+    for _ in 0..2 {
+        setup.block(|_| {});
+    }
+    // At the cliff, the tokens unlocked since the start are available.
+    setup.block(|h| {
+        h.send_as(owner, account, identity(5), 90u32, *MFX_SYMBOL)
+            .unwrap();
+    });
+}
+
+#[test]
+fn invalid_schedule() {
+    let mut setup = Setup::new_with_migrations(false, [(0, &ACCOUNT_VESTING_MIGRATION)], true);
+    let sender = setup.id;
+    let args = account::CreateArgs {
+        description: None,
+        roles: None,
+        features: FeatureSet::from_iter([
+            AccountVesting::new(schedule(Some(2_000_000))).as_feature()
+        ]),
+    };
+    assert!(AccountModuleBackend::create(&mut setup.module_impl, &sender, args).is_err());
+
+    let account = setup.create_account_(AccountType::Ledger);
+    assert_many_err(
+        AccountVestingModuleBackend::info(&setup.module_impl, &identity(5), InfoArgs { account }),
+        errors::vesting_not_enabled(account),
+    );
+}
+
+#[test]
+fn cannot_remove_while_locked() {
+    let mut setup = Setup::new_with_migrations(
+        true,
+        [
+            (0, &ACCOUNT_VESTING_MIGRATION),
+            (0, &ACCOUNT_REMOVE_FEATURES_MIGRATION),
+        ],
+        true,
+    );
+    let owner = setup.id;
+    let account = create_vesting_account(&mut setup, schedule(None));
+    let args = account::RemoveFeaturesArgs {
+        account,
+        features: BTreeSet::from([AccountVesting::ID]),
+    };
+
+    setup.block(|h| {
+        assert_many_err(
+            h.module_impl.remove_features(&owner, args.clone()),
+            account::errors::feature_in_use(
+                AccountVesting::ID,
+                "the account still has locked tokens",
+            ),
+        );
+    });
+    for _ in 0..10 {
+        setup.block(|_| {});
+    }
+    setup.block(|h| {
+        h.module_impl.remove_features(&owner, args).unwrap();
+    });
+}
+
+#[test]
+fn disabled_without_migration() {
+    let mut setup = Setup::new(true);
+    let owner = setup.id;
+    let account = create_vesting_account(&mut setup, schedule(None));
+
+    setup.block(|h| {
+        assert!(AccountVestingModuleBackend::info(
+            &h.module_impl,
+            &identity(5),
+            InfoArgs { account }
+        )
+        .is_err());
+        h.send_as(owner, account, identity(5), 150u32, *MFX_SYMBOL)
+            .unwrap();
+    });
+}
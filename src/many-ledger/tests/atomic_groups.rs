@@ -0,0 +1,63 @@
+use {
+    many_identity::testing::identity,
+    many_ledger_test_utils::*,
+    many_modules::events::{EventsModuleBackend, InfoArgs},
+};
+
+fn nb_events(setup: &Setup) -> u64 {
+    EventsModuleBackend::info(&setup.module_impl, InfoArgs {})
+        .unwrap()
+        .total
+}
+
+#[test]
+fn rollback() {
+    let mut setup = Setup::new(false);
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+    let events = nb_events(&setup);
+
+    setup.module_impl.begin_atomic_group().unwrap();
+    setup.send_(id, identity(5), 10u16);
+    setup.send_(id, identity(6), 20u16);
+
+    // Writes are visible inside the group.
+    assert_eq!(setup.balance_(id), 970u32);
+    assert_eq!(setup.balance_(identity(5)), 10u16);
+
+    setup.module_impl.rollback_atomic_group().unwrap();
+    assert_eq!(setup.balance_(id), 1_000u32);
+    assert_eq!(setup.balance_(identity(5)), 0u16);
+    assert_eq!(setup.balance_(identity(6)), 0u16);
+    assert_eq!(nb_events(&setup), events);
+
+    // Event IDs are reused after a rollback.
+    setup.send_(id, identity(5), 10u16);
+    assert_eq!(nb_events(&setup), events + 1);
+}
+
+#[test]
+fn commit() {
+    let mut setup = Setup::new(false);
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+    let events = nb_events(&setup);
+
+    setup.module_impl.begin_atomic_group().unwrap();
+    setup.send_(id, identity(5), 10u16);
+    setup.module_impl.commit_atomic_group().unwrap();
+
+    assert_eq!(setup.balance_(id), 990u32);
+    assert_eq!(setup.balance_(identity(5)), 10u16);
+    assert_eq!(nb_events(&setup), events + 1);
+}
+
+#[test]
+fn one_group_at_a_time() {
+    let mut setup = Setup::new(false);
+    assert!(setup.module_impl.commit_atomic_group().is_err());
+    assert!(setup.module_impl.rollback_atomic_group().is_err());
+
+    setup.module_impl.begin_atomic_group().unwrap();
+    assert!(setup.module_impl.begin_atomic_group().is_err());
+}
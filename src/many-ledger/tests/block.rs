@@ -1,6 +1,29 @@
 //! Tests regarding blockchain behaviour.
 use many_identity::testing::identity;
+use many_identity::Address;
 use many_ledger_test_utils::*;
+use many_modules::events::{EventInfo, EventsModuleBackend, ListArgs};
+use many_modules::ledger;
+use many_protocol::{RequestMessage, RequestMessageBuilder};
+use many_types::Timestamp;
+
+fn send_request(from: Address, to: Address, amount: u32) -> RequestMessage {
+    RequestMessageBuilder::default()
+        .from(from)
+        .method("ledger.send".to_string())
+        .data(
+            minicbor::to_vec(ledger::SendArgs {
+                from: None,
+                to,
+                amount: amount.into(),
+                symbol: *MFX_SYMBOL,
+                memo: None,
+            })
+            .unwrap(),
+        )
+        .build()
+        .unwrap()
+}
 
 /// Test that out of order keys at commit in a blockchain don't cause a problem.
 #[test]
@@ -56,3 +79,60 @@ fn query_in_flight() {
 
     assert_eq!(harness.balance_(harness.id), 996u32);
 }
+
+/// Requests of a block are executed in order, as delivered by Tendermint.
+#[test]
+fn block_of_requests() {
+    let mut harness = Setup::new(true);
+    harness.set_balance(harness.id, 150, *MFX_SYMBOL);
+    let id = harness.id;
+
+    let block = TestBlock::new([
+        send_request(id, identity(2), 100),
+        send_request(id, identity(3), 100),
+        send_request(identity(2), identity(3), 50),
+        RequestMessageBuilder::default()
+            .method("ledger.unknown".to_string())
+            .build()
+            .unwrap(),
+    ])
+    .with_proposer(vec![1; 20])
+    .with_time(2_000_000);
+    let (h, responses) = harness.block_with(block);
+    assert_eq!(h, 1);
+    assert_eq!(responses.len(), 4);
+    assert!(responses[0].is_ok());
+    assert_many_err(responses[1].clone(), ledger::insufficient_funds());
+    assert!(responses[2].is_ok());
+    assert!(responses[3].is_err());
+
+    assert_eq!(harness.balance_(id), 50u32);
+    assert_eq!(harness.balance_(identity(2)), 50u32);
+    assert_eq!(harness.balance_(identity(3)), 50u32);
+
+    assert_eq!(
+        harness.module_impl.block_proposer(),
+        Some(&vec![1; 20].into())
+    );
+    let events = harness
+        .module_impl
+        .list(ListArgs::default())
+        .unwrap()
+        .events;
+    let sends: Vec<_> = events
+        .iter()
+        .filter(|e| matches!(e.content, EventInfo::Send { .. }))
+        .collect();
+    assert_eq!(sends.len(), 2);
+    assert!(sends
+        .iter()
+        .all(|e| e.time == Timestamp::new(2_000_000).unwrap()));
+
+    // Blocks without an explicit time follow the previous one.
+    let (h, responses) =
+        harness.block_with(TestBlock::new([send_request(identity(3), identity(2), 10)]));
+    assert_eq!(h, 2);
+    assert!(responses[0].is_ok());
+    assert_eq!(harness.module_impl.block_proposer(), None);
+    assert_eq!(harness.balance_(identity(2)), 60u32);
+}
@@ -0,0 +1,127 @@
+//! Differential tests between the blockchain and non-blockchain modes of the
+//! ledger. The same operations must have the same results in both modes; only
+//! when changes are committed to the persistent storage differs.
+use {
+    many_error::ManyError,
+    many_identity::testing::identity,
+    many_identity::Address,
+    many_ledger_test_utils::*,
+    many_modules::events::{EventInfo, EventsModuleBackend, ListArgs},
+    many_types::ledger::TokenAmount,
+    many_types::SortOrder,
+    proptest::prelude::*,
+    proptest::test_runner::Config,
+};
+
+#[derive(Clone, Debug)]
+enum Operation {
+    /// Indices are in the list of known addresses, which grows as accounts
+    /// are created.
+    Send {
+        sender: usize,
+        from: usize,
+        to: usize,
+        amount: u16,
+    },
+    CreateAccount {
+        owner: usize,
+    },
+}
+
+fn arb_operation() -> impl Strategy<Value = Operation> {
+    prop_oneof![
+        4 => (any::<usize>(), any::<usize>(), any::<usize>(), 0..600u16).prop_map(
+            |(sender, from, to, amount)| Operation::Send {
+                sender,
+                from,
+                to,
+                amount,
+            }
+        ),
+        1 => any::<usize>().prop_map(|owner| Operation::CreateAccount { owner }),
+    ]
+}
+
+/// Everything observable after running operations, which must not depend on
+/// the mode.
+#[derive(Debug, PartialEq)]
+struct Outcome {
+    results: Vec<Result<(), ManyError>>,
+    balances: Vec<(Address, TokenAmount)>,
+    events: Vec<EventInfo>,
+}
+
+fn run(blockchain: bool, blocks: &[Vec<Operation>]) -> Outcome {
+    let mut setup = Setup::new(blockchain);
+    let mut addresses = vec![identity(1), identity(2), identity(3)];
+    for address in &addresses {
+        setup.set_balance(*address, 1_000, *MFX_SYMBOL);
+    }
+
+    let mut results = vec![];
+    for operations in blocks {
+        let execute = |setup: &mut Setup| {
+            for operation in operations {
+                let address = |i: &usize| addresses[i % addresses.len()];
+                let result = match operation {
+                    Operation::Send {
+                        sender,
+                        from,
+                        to,
+                        amount,
+                    } => setup.send_as(
+                        address(sender),
+                        address(from),
+                        address(to),
+                        *amount,
+                        *MFX_SYMBOL,
+                    ),
+                    Operation::CreateAccount { owner } => setup
+                        .create_account_as(address(owner), AccountType::Ledger)
+                        .map(|account| addresses.push(account)),
+                };
+                results.push(result);
+            }
+        };
+
+        if blockchain {
+            setup.block(execute);
+        } else {
+            execute(&mut setup);
+        }
+    }
+
+    let balances = addresses
+        .iter()
+        .map(|address| (*address, setup.balance_(*address)))
+        .collect();
+    let events = setup
+        .module_impl
+        .list(ListArgs {
+            count: None,
+            order: Some(SortOrder::Ascending),
+            filter: None,
+        })
+        .unwrap()
+        .events
+        .into_iter()
+        .map(|event| event.content)
+        .collect();
+
+    Outcome {
+        results,
+        balances,
+        events,
+    }
+}
+
+proptest! {
+    #![proptest_config(Config { cases: 64, ..Config::default() })]
+
+    #[test]
+    fn same_outcome_in_both_modes(
+        blocks in prop::collection::vec(prop::collection::vec(arb_operation(), 0..5), 1..5)
+    ) {
+        prop_assert_eq!(run(false, &blocks), run(true, &blocks));
+    }
+}
@@ -0,0 +1,124 @@
+use async_channel::unbounded;
+use many_identity::testing::identity;
+use many_ledger::migration::canonical_cbor::CANONICAL_CBOR_MIGRATION;
+use many_ledger::storage::extended_info_policy::ExtendedInfoPolicy;
+use many_ledger::storage::scripts::StoredScript;
+use many_ledger_test_utils::*;
+use many_modules::account;
+use many_modules::account::features::multisig::MultisigTransactionState;
+use many_modules::account::features::{ledger::AccountLedger, TryCreateFeature};
+use many_modules::account::AccountModuleBackend;
+use many_modules::events::{EventInfo, EventsModuleBackend, ListArgs};
+use many_protocol::{context::Context, RequestMessage};
+use many_types::cbor::canonical;
+use proptest::prelude::*;
+
+/// Check that a value decodes from its canonical encoding, and encodes back
+/// to the same bytes.
+fn assert_canonical_round_trip<T>(value: &T)
+where
+    T: minicbor::Encode<()> + for<'b> minicbor::Decode<'b, ()>,
+{
+    let bytes = canonical::to_canonical_vec(value).unwrap();
+    assert!(canonical::is_canonical(&bytes));
+    let decoded: T = minicbor::decode(&bytes).unwrap();
+    assert_eq!(canonical::to_canonical_vec(&decoded).unwrap(), bytes);
+}
+
+fn setup() -> Setup {
+    let mut setup = Setup::new_with_migrations(true, [(0, &CANONICAL_CBOR_MIGRATION)], true);
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+    setup
+}
+
+#[test]
+fn multisig_transactions() {
+    let mut setup = setup();
+    let (_, account_id) = setup.block(|h| h.create_account_(AccountType::Multisig));
+    let (_, token) = setup.block(|h| {
+        let id = h.id;
+        h.send_(id, account_id, 100u32);
+        h.multisig_send_(account_id, identity(5), 10u32)
+    });
+    setup.block(|h| {
+        h.multisig_approve_(identity(2), &token);
+        h.multisig_approve_(identity(3), &token);
+        let data = h.multisig_execute_(&token).data;
+        assert!(data.is_ok(), "Err: {}", data.unwrap_err());
+    });
+
+    setup.assert_multisig_info(&token, |i| {
+        assert_eq!(i.state, MultisigTransactionState::ExecutedManually);
+    });
+    assert_eq!(setup.balance_(identity(5)), 10u16);
+
+    let events = setup.module_impl.list(ListArgs::default()).unwrap().events;
+    assert!(events
+        .iter()
+        .any(|e| matches!(e.content, EventInfo::AccountMultisigExecute { .. })));
+}
+
+#[test]
+fn accounts() {
+    let mut setup = setup();
+    let id = setup.id;
+    let (_, account_id) = setup.block(|h| h.create_account_(AccountType::Ledger));
+    setup.block(|h| {
+        AccountModuleBackend::add_roles(
+            &mut h.module_impl,
+            &id,
+            account::AddRolesArgs {
+                account: account_id,
+                roles: account::AddressRoleMap::from([
+                    (identity(9), [account::Role::CanLedgerTransact].into()),
+                    (identity(4), [account::Role::Owner].into()),
+                ]),
+            },
+        )
+        .unwrap();
+    });
+
+    let info = AccountModuleBackend::info(
+        &setup.module_impl,
+        &id,
+        account::InfoArgs {
+            account: account_id,
+        },
+        Context::new(RequestMessage::default(), unbounded().0),
+    )
+    .unwrap();
+    assert_eq!(info.description, Some("Foobar".to_string()));
+    assert!(info.roles[&identity(4)].contains(&account::Role::Owner));
+    assert!(info.roles[&identity(9)].contains(&account::Role::CanLedgerTransact));
+    assert!(info.features.has_id(AccountLedger::ID));
+}
+
+proptest! {
+    #[test]
+    fn canonical_script(owner in 0u32..1000, code in proptest::collection::vec(any::<u8>(), 0..1000)) {
+        assert_canonical_round_trip(&StoredScript {
+            owner: identity(owner),
+            code: code.into(),
+        });
+    }
+
+    #[test]
+    fn canonical_extended_info_policy(
+        max_image_size: u64,
+        content_types in proptest::option::of(
+            proptest::collection::btree_set("[a-z]{1,10}/[a-z+]{1,10}", 0..5),
+        ),
+        unicode_ranges in proptest::option::of(
+            proptest::collection::vec((any::<u32>(), any::<u32>()), 0..5),
+        ),
+        max_memo_size in proptest::option::of(any::<u64>()),
+    ) {
+        assert_canonical_round_trip(&ExtendedInfoPolicy {
+            max_image_size,
+            content_types,
+            unicode_ranges,
+            max_memo_size,
+        });
+    }
+}
@@ -0,0 +1,94 @@
+use async_channel::unbounded;
+use many_identity::testing::identity;
+use many_ledger::migration::data::{ACCOUNT_COUNT_DATA_ATTRIBUTE, ACCOUNT_TOTAL_COUNT_INDEX};
+use many_ledger::migration::data_history::DATA_HISTORY_MIGRATION;
+use many_ledger_test_utils::*;
+use many_modules::data::{
+    data_not_available_at_height, DataGetInfoArgs, DataModuleBackend, DataQueryArgs,
+    DataQueryReturns, DataValue,
+};
+use many_protocol::{context::Context, RequestMessage};
+use many_types::VecOrSingle;
+
+fn context() -> Context {
+    Context::new(RequestMessage::default(), unbounded().0)
+}
+
+fn query(harness: &Setup, height: Option<u64>) -> Result<DataQueryReturns, many_error::ManyError> {
+    harness.module_impl.query(
+        &harness.id,
+        DataQueryArgs {
+            indices: VecOrSingle(vec![ACCOUNT_TOTAL_COUNT_INDEX.clone()]),
+            height,
+        },
+        context(),
+    )
+}
+
+#[test]
+fn historical_queries() {
+    let mut harness = Setup::new_with_migrations(
+        true,
+        [
+            (1, &ACCOUNT_COUNT_DATA_ATTRIBUTE),
+            (1, &DATA_HISTORY_MIGRATION),
+        ],
+        false,
+    );
+    harness.set_balance(harness.id, 1_000_000, *MFX_SYMBOL);
+
+    let (h1, _) = harness.block(|h| h.send_(h.id, identity(2), 1_000u32));
+    let (h2, _) = harness.block(|h| h.send_(h.id, identity(3), 1_000u32));
+    let at_h2 = query(&harness, None).unwrap();
+    assert_eq!(query(&harness, Some(h2)).unwrap(), at_h2);
+
+    // A block without any change to the data attributes does not add a
+    // snapshot, but can still be queried.
+    let (h3, _) = harness.block(|_| {});
+    assert_eq!(query(&harness, Some(h3)).unwrap(), at_h2);
+
+    let (h4, _) = harness.block(|h| h.send_(h.id, identity(4), 1_000u32));
+    let at_h4 = query(&harness, Some(h4)).unwrap();
+    assert_eq!(query(&harness, None).unwrap(), at_h4);
+    assert_ne!(at_h4, at_h2);
+    match (
+        &at_h2[&*ACCOUNT_TOTAL_COUNT_INDEX],
+        &at_h4[&*ACCOUNT_TOTAL_COUNT_INDEX],
+    ) {
+        (DataValue::Counter(c2), DataValue::Counter(c4)) => assert_eq!(*c2 + 1, *c4),
+        _ => unreachable!(),
+    }
+    assert_eq!(query(&harness, Some(h2)).unwrap(), at_h2);
+
+    // Only heights with a snapshot at or before them, and which were
+    // committed, are available.
+    assert!(h1 > 0);
+    assert_many_err(query(&harness, Some(0)), data_not_available_at_height(0));
+    assert_many_err(
+        query(&harness, Some(h4 + 1)),
+        data_not_available_at_height(h4 + 1),
+    );
+
+    let info = harness
+        .module_impl
+        .get_info(
+            &harness.id,
+            DataGetInfoArgs {
+                indices: VecOrSingle(vec![ACCOUNT_TOTAL_COUNT_INDEX.clone()]),
+                height: Some(h2),
+            },
+            context(),
+        )
+        .unwrap();
+    assert!(info.contains_key(&*ACCOUNT_TOTAL_COUNT_INDEX));
+}
+
+#[test]
+fn disabled_without_migration() {
+    let mut harness = Setup::new_with_migrations(true, [(1, &ACCOUNT_COUNT_DATA_ATTRIBUTE)], false);
+    let (height, _) = harness.block(|_| {});
+    assert_many_err(
+        query(&harness, Some(height)),
+        data_not_available_at_height(height),
+    );
+}
@@ -0,0 +1,59 @@
+use {
+    many_error::ManyError,
+    many_identity::delegation::Delegation,
+    many_identity::testing::identity,
+    many_identity::Identity,
+    many_identity_dsa::ed25519::generate_random_ed25519_identity,
+    many_ledger_test_utils::*,
+    many_protocol::context::with_intent,
+    many_protocol::delegation::DelegationChain,
+    many_protocol::{RequestMessage, RequestMessageBuilder},
+    many_types::Timestamp,
+};
+
+fn request(delegation: Option<Delegation>, signer: &impl Identity) -> RequestMessage {
+    let request = RequestMessageBuilder::default()
+        .method("ledger.send".to_string())
+        .timestamp(Timestamp::new(1_000_000).unwrap())
+        .build()
+        .unwrap();
+    match delegation {
+        Some(d) => request.with_attribute(DelegationChain(vec![d.sign(signer).unwrap()]).into()),
+        None => request,
+    }
+}
+
+#[test]
+fn max_amount() {
+    let mut setup = Setup::new(true);
+    let delegator = generate_random_ed25519_identity();
+    let from = delegator.address();
+    setup.set_balance(from, 1_000, *MFX_SYMBOL);
+
+    let delegation = Delegation::new(from, identity(2), 2_000_000).with_max_amount(*MFX_SYMBOL, 10);
+    let limited = request(Some(delegation), &delegator);
+    let unlimited = request(
+        Some(Delegation::new(from, identity(2), 2_000_000)),
+        &delegator,
+    );
+    let direct = request(None, &delegator);
+
+    setup.block(|h| {
+        assert!(with_intent(&limited, || h.send(from, identity(5), 10u32, *MFX_SYMBOL)).is_ok());
+        assert!(with_intent(&limited, || h.send(from, identity(5), 11u32, *MFX_SYMBOL)).is_err());
+        // Limits are per symbol; symbols without a limit cannot be sent.
+        assert_many_err(
+            with_intent(&limited, || h.send(from, identity(5), 1u32, identity(100))),
+            ManyError::invalid_delegation("amount exceeds the delegated maximum of 0"),
+        );
+        assert!(with_intent(&unlimited, || h.send(
+            from,
+            identity(5),
+            100u32,
+            *MFX_SYMBOL
+        ))
+        .is_ok());
+        assert!(with_intent(&direct, || h.send(from, identity(5), 100u32, *MFX_SYMBOL)).is_ok());
+    });
+    assert_eq!(setup.balance(from, *MFX_SYMBOL).unwrap(), 790u32);
+}
@@ -0,0 +1,221 @@
+use many_identity::testing::identity;
+use many_identity::Address;
+use many_ledger::module::LedgerModuleImpl;
+use many_ledger_test_utils::*;
+use many_modules::events::{
+    self, errors, is_public_ip, is_public_target, EventsDurableSubscriptionModuleBackend,
+};
+use many_modules::ledger;
+use many_modules::ledger::LedgerCommandsModuleBackend;
+use std::collections::BTreeSet;
+
+fn send(module_impl: &mut LedgerModuleImpl, from: Address, to: Address) {
+    module_impl
+        .set_balance_only_for_testing(from, 1000, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+    let result = module_impl.send(
+        &from,
+        ledger::SendArgs {
+            from: Some(from),
+            to,
+            amount: 10u16.into(),
+            symbol: *MFX_SYMBOL,
+            memo: None,
+        },
+    );
+    assert!(result.is_ok());
+}
+
+fn create_args(target: &str) -> events::CreateSubscriptionArgs {
+    events::CreateSubscriptionArgs {
+        filter: events::SubscriptionFilter {
+            account: Some(vec![identity(2)].into()),
+            ..Default::default()
+        },
+        target: target.to_string(),
+        after: None,
+    }
+}
+
+fn operators(id: Address) -> BTreeSet<Address> {
+    BTreeSet::from([id])
+}
+
+#[test]
+fn unsupported() {
+    let Setup {
+        mut module_impl,
+        id,
+        ..
+    } = setup();
+    assert_many_err(
+        module_impl.create_subscription(&id, create_args("https://example.com/events")),
+        errors::durable_subscriptions_unsupported(),
+    );
+    assert!(module_impl.pending_deliveries().unwrap().is_empty());
+}
+
+#[test]
+fn manage() {
+    let Setup {
+        mut module_impl,
+        id,
+        ..
+    } = setup();
+    let dir = tempfile::tempdir().unwrap();
+    module_impl
+        .set_durable_subscriptions(dir.path().join("subscriptions.cbor"), operators(id))
+        .unwrap();
+
+    assert_many_err(
+        module_impl.create_subscription(&id, create_args("example.com/events")),
+        errors::invalid_subscription_target("example.com/events"),
+    );
+
+    // Only operators can subscribe, and only to public targets.
+    assert_many_err(
+        module_impl.create_subscription(&identity(5), create_args("https://example.com/events")),
+        errors::not_subscription_operator(),
+    );
+    for target in [
+        "http://localhost/events",
+        "http://127.0.0.1:8000/events",
+        "http://10.0.0.1/events",
+        "http://169.254.169.254/latest/meta-data",
+        "http://[::1]/events",
+        "http://[::ffff:192.168.1.1]/events",
+    ] {
+        assert_many_err(
+            module_impl.create_subscription(&id, create_args(target)),
+            errors::private_subscription_target(target),
+        );
+    }
+
+    let subscription = module_impl
+        .create_subscription(&id, create_args("https://example.com/events"))
+        .unwrap()
+        .id;
+
+    let list = module_impl
+        .list_subscriptions(&id, events::ListSubscriptionsArgs {})
+        .unwrap();
+    assert_eq!(list.subscriptions.len(), 1);
+    assert_eq!(list.subscriptions[&subscription].owner, id);
+    assert!(module_impl
+        .list_subscriptions(&identity(5), events::ListSubscriptionsArgs {})
+        .unwrap()
+        .subscriptions
+        .is_empty());
+
+    assert_many_err(
+        module_impl.delete_subscription(
+            &identity(5),
+            events::DeleteSubscriptionArgs { id: subscription },
+        ),
+        errors::not_subscription_owner(subscription),
+    );
+    module_impl
+        .delete_subscription(&id, events::DeleteSubscriptionArgs { id: subscription })
+        .unwrap();
+    assert!(module_impl
+        .list_subscriptions(&id, events::ListSubscriptionsArgs {})
+        .unwrap()
+        .subscriptions
+        .is_empty());
+    assert_many_err(
+        module_impl.delete_subscription(&id, events::DeleteSubscriptionArgs { id: subscription }),
+        errors::unknown_subscription(subscription),
+    );
+}
+
+#[test]
+fn deliveries() {
+    let Setup {
+        mut module_impl,
+        id,
+        ..
+    } = setup();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("subscriptions.cbor");
+    module_impl
+        .set_durable_subscriptions(&path, operators(id))
+        .unwrap();
+
+    // Events logged before subscribing are not delivered.
+    send(&mut module_impl, id, identity(2));
+    let subscription = module_impl
+        .create_subscription(&id, create_args("https://example.com/events"))
+        .unwrap()
+        .id;
+    assert!(module_impl.pending_deliveries().unwrap().is_empty());
+
+    send(&mut module_impl, id, identity(1));
+    send(&mut module_impl, id, identity(2));
+
+    let deliveries = module_impl.pending_deliveries().unwrap();
+    assert_eq!(deliveries.len(), 1);
+    assert_eq!(deliveries[0].id, subscription);
+    assert_eq!(deliveries[0].target, "https://example.com/events");
+    assert_eq!(deliveries[0].events.len(), 1);
+    assert!(deliveries[0].events[0].is_about(identity(2)));
+    assert_eq!(deliveries[0].cursor, deliveries[0].events[0].id);
+
+    // Events stay pending until they are acknowledged, even after a restart.
+    module_impl
+        .set_durable_subscriptions(&path, operators(id))
+        .unwrap();
+    let pending = module_impl.pending_deliveries().unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].events.len(), 1);
+    assert_eq!(pending[0].cursor, deliveries[0].cursor);
+
+    module_impl
+        .acknowledge_delivery(subscription, deliveries[0].cursor.clone())
+        .unwrap();
+    assert!(module_impl.pending_deliveries().unwrap().is_empty());
+    module_impl
+        .set_durable_subscriptions(&path, operators(id))
+        .unwrap();
+    assert!(module_impl.pending_deliveries().unwrap().is_empty());
+
+    // Cursors never move back.
+    module_impl
+        .acknowledge_delivery(subscription, events::EventId::from(0))
+        .unwrap();
+    assert!(module_impl.pending_deliveries().unwrap().is_empty());
+
+    // Events which do not match the filter are only acknowledged.
+    send(&mut module_impl, id, identity(1));
+    let deliveries = module_impl.pending_deliveries().unwrap();
+    assert_eq!(deliveries.len(), 1);
+    assert!(deliveries[0].events.is_empty());
+}
+
+#[test]
+fn public_addresses() {
+    for ip in ["1.1.1.1", "93.184.216.34", "2606:2800:220:1::1"] {
+        assert!(is_public_ip(ip.parse().unwrap()), "{ip}");
+    }
+    for ip in [
+        "0.0.0.0",
+        "127.0.0.1",
+        "10.1.2.3",
+        "172.16.0.1",
+        "192.168.0.1",
+        "169.254.169.254",
+        "100.64.0.1",
+        "255.255.255.255",
+        "::",
+        "::1",
+        "fd00::1",
+        "fe80::1",
+        "::ffff:127.0.0.1",
+    ] {
+        assert!(!is_public_ip(ip.parse().unwrap()), "{ip}");
+    }
+
+    assert!(is_public_target("https://example.com/events"));
+    assert!(!is_public_target("http://LOCALHOST./events"));
+    assert!(!is_public_target("http://foo.localhost/events"));
+    assert!(!is_public_target("not a url"));
+}
@@ -0,0 +1,88 @@
+use {
+    many_identity::testing::identity,
+    many_ledger::migration::dust::{
+        COMMUNITY_POOL_KEY, DUST_REAPING_MIGRATION, DUST_THRESHOLDS_KEY, INACTIVITY_KEY,
+    },
+    many_ledger_test_utils::*,
+    many_modules::events::{EventInfo, EventsModuleBackend, ListArgs},
+};
+
+/// Balances of MFX below 100 are dust after 5 seconds without activity.
+fn setup() -> Setup {
+    let mut setup = Setup::new_with_migrations(
+        true,
+        [MigrationHarness::from((0, &DUST_REAPING_MIGRATION))
+            .with_extra(
+                DUST_THRESHOLDS_KEY,
+                serde_json::json!({ MFX_SYMBOL.to_string(): 100 }),
+            )
+            .with_extra(INACTIVITY_KEY, serde_json::json!(5))
+            .with_extra(
+                COMMUNITY_POOL_KEY,
+                serde_json::json!(identity(9).to_string()),
+            )],
+        true,
+    );
+    setup.set_balance(identity(5), 50, *MFX_SYMBOL);
+    setup.set_balance(identity(6), 500, *MFX_SYMBOL);
+    setup.set_balance(identity(7), 50, *MFX_SYMBOL);
+    setup
+}
+
+#[test]
+fn reap_inactive_dust() {
+    let mut setup = setup();
+
+    // The first sweep starts tracking the addresses without activity.
+    for _ in 0..3 {
+        setup.block(|_| {});
+    }
+    assert_eq!(setup.balance_(identity(5)), 50u32);
+
+    for _ in 0..5 {
+        setup.block(|_| {});
+    }
+    assert_eq!(setup.balance_(identity(5)), 0u32);
+    assert_eq!(setup.balance_(identity(7)), 0u32);
+    // Balances above the threshold are kept.
+    assert_eq!(setup.balance_(identity(6)), 500u32);
+    assert_eq!(setup.balance_(identity(9)), 100u32);
+}
+
+#[test]
+fn activity_delays_reaping() {
+    let mut setup = setup();
+    for i in 0..8 {
+        setup.block(|h| {
+            if i % 2 == 0 {
+                h.send_(identity(6), identity(5), 1u32);
+            }
+        });
+    }
+    assert_eq!(setup.balance_(identity(5)), 54u32);
+    assert_eq!(setup.balance_(identity(7)), 0u32);
+
+    let events = setup
+        .module_impl
+        .list(ListArgs {
+            count: None,
+            order: None,
+            filter: None,
+        })
+        .unwrap()
+        .events;
+    assert!(events.iter().any(|event| matches!(
+        event.content,
+        EventInfo::DustReap { account, .. } if account == identity(7)
+    )));
+}
+
+#[test]
+fn disabled_without_migration() {
+    let mut setup = Setup::new(true);
+    setup.set_balance(identity(5), 1, *MFX_SYMBOL);
+    for _ in 0..10 {
+        setup.block(|_| {});
+    }
+    assert_eq!(setup.balance_(identity(5)), 1u32);
+}
@@ -0,0 +1,164 @@
+use many_identity::testing::identity;
+use many_identity::Address;
+use many_ledger::json::InitialStateJson;
+use many_ledger::migration::event_ids::EVENT_IDS_MIGRATION;
+use many_ledger::module::LedgerModuleImpl;
+use many_ledger_test_utils::*;
+use many_migration::{Metadata, MigrationConfig};
+use many_modules::abci_backend::{AbciBlock, ManyAbciModuleBackend};
+use many_modules::events::{EventId, EventsModuleBackend, InfoArgs, ListArgs};
+use many_modules::ledger::{LedgerCommandsModuleBackend, SendArgs};
+use many_types::SortOrder;
+use std::path::Path;
+use std::str::FromStr;
+
+/// An account with an initial balance in the staging state.
+fn holder() -> Address {
+    Address::from_str("maffbahksdwaqeenayy2gxke32hgb7aq4ao4wt745lsfs6wijp").unwrap()
+}
+
+fn migration_config() -> Option<MigrationConfig> {
+    Some(MigrationConfig::default().with_migration_opts(
+        &EVENT_IDS_MIGRATION,
+        Metadata {
+            block_height: 0,
+            upper_block_height: None,
+            disabled: false,
+            issue: None,
+            rollout: None,
+            extra: Default::default(),
+        },
+    ))
+}
+
+fn create(path: &Path, blockchain: bool, migrations: Option<MigrationConfig>) -> LedgerModuleImpl {
+    let state = InitialStateJson::read("../../staging/ledger_state.json5")
+        .or_else(|_| InitialStateJson::read("staging/ledger_state.json5"))
+        .expect("Could not read initial state.");
+    LedgerModuleImpl::new(state, migrations, path, blockchain).unwrap()
+}
+
+fn send(module_impl: &mut LedgerModuleImpl) {
+    module_impl
+        .send(
+            &holder(),
+            SendArgs {
+                from: None,
+                to: identity(5),
+                amount: 10u16.into(),
+                symbol: *MFX_SYMBOL,
+                memo: None,
+            },
+        )
+        .unwrap();
+}
+
+fn block(
+    module_impl: &mut LedgerModuleImpl,
+    time: u64,
+    inner_f: impl FnOnce(&mut LedgerModuleImpl),
+) {
+    module_impl
+        .begin_block(AbciBlock {
+            time: Some(time),
+            proposer: None,
+        })
+        .unwrap();
+    inner_f(module_impl);
+    module_impl.end_block().unwrap();
+    module_impl.commit().unwrap();
+}
+
+/// The (height, index) of all events, in order.
+fn ids(module_impl: &LedgerModuleImpl) -> Vec<(u64, u32)> {
+    module_impl
+        .list(ListArgs {
+            count: None,
+            order: Some(SortOrder::Ascending),
+            filter: None,
+        })
+        .unwrap()
+        .events
+        .into_iter()
+        .map(|e| (e.height().unwrap(), e.index().unwrap()))
+        .collect()
+}
+
+#[test]
+/// Verify event IDs are the height of their block and their index in it.
+fn ids_follow_blocks() {
+    let mut setup = Setup::new_with_migrations(true, [(0, &EVENT_IDS_MIGRATION)], true);
+    setup.set_balance(setup.id, 1_000_000, *MFX_SYMBOL);
+
+    setup.block(|h| {
+        h.send_(h.id, identity(2), 10u32);
+        h.send_(h.id, identity(3), 10u32);
+    });
+    setup.block(|_| {});
+    setup.block(|h| h.send_(h.id, identity(2), 10u32));
+
+    assert_eq!(ids(&setup.module_impl), vec![(1, 1), (1, 2), (3, 1)]);
+}
+
+#[test]
+/// Verify the multisig token is the ID of the submit event, without a gap.
+fn multisig_token_is_event_id() {
+    let mut setup = Setup::new_with_migrations(true, [(0, &EVENT_IDS_MIGRATION)], true);
+
+    let (_, token) = setup.block(|h| {
+        let account_id = h.create_account_(AccountType::Multisig);
+        h.multisig_send_(account_id, identity(2), 10u32)
+    });
+
+    assert_eq!(ids(&setup.module_impl), vec![(1, 1), (1, 2)]);
+    let token = EventId::from(token);
+    assert_eq!((token.height(), token.index()), (Some(1), Some(2)));
+}
+
+#[test]
+/// Verify a block replayed after a crash logs the same event IDs.
+fn replay_after_crash() {
+    let path = tempfile::tempdir().unwrap().into_path();
+    {
+        let mut module_impl = create(&path, true, migration_config());
+        block(&mut module_impl, 1, send);
+
+        // Crash in the middle of the second block.
+        module_impl
+            .begin_block(AbciBlock {
+                time: Some(2),
+                proposer: None,
+            })
+            .unwrap();
+        send(&mut module_impl);
+    }
+
+    let mut module_impl = LedgerModuleImpl::load(migration_config(), &path, true).unwrap();
+    assert_eq!(ids(&module_impl), vec![(1, 1)]);
+
+    block(&mut module_impl, 2, send);
+    assert_eq!(ids(&module_impl), vec![(1, 1), (2, 1)]);
+}
+
+#[test]
+/// Verify events logged without a blockchain are not overwritten after a
+/// restart.
+fn restart_without_blockchain() {
+    let path = tempfile::tempdir().unwrap().into_path();
+    {
+        let mut module_impl = create(&path, false, None);
+        send(&mut module_impl);
+        send(&mut module_impl);
+    }
+
+    let mut module_impl = LedgerModuleImpl::load(None, &path, false).unwrap();
+    send(&mut module_impl);
+
+    assert_eq!(ids(&module_impl), vec![(0, 1), (0, 2), (0, 3)]);
+    assert_eq!(
+        EventsModuleBackend::info(&module_impl, InfoArgs {})
+            .unwrap()
+            .total,
+        3
+    );
+}
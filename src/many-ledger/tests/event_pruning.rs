@@ -0,0 +1,61 @@
+use many_identity::testing::identity;
+use many_ledger::migration::event_pruning::{EVENT_PRUNING_MIGRATION, RETENTION_BLOCKS_KEY};
+use many_ledger_test_utils::*;
+use many_modules::events::{EventsArchiveModuleBackend, EventsModuleBackend, ListArgs};
+use many_modules::ledger::{LedgerCommandsModuleBackend, SendArgs};
+use many_modules::EmptyArg;
+
+fn send(setup: &mut Setup) {
+    setup.block(|h| {
+        let sender = h.id;
+        h.module_impl
+            .send(
+                &sender,
+                SendArgs {
+                    from: None,
+                    to: identity(5),
+                    amount: 10u16.into(),
+                    symbol: *MFX_SYMBOL,
+                    memo: None,
+                },
+            )
+            .unwrap();
+    });
+}
+
+#[test]
+fn prune_events() {
+    let mut setup = Setup::new_with_migrations(
+        true,
+        [MigrationHarness::from((3, &EVENT_PRUNING_MIGRATION))
+            .with_extra(RETENTION_BLOCKS_KEY, serde_json::json!(2))],
+        true,
+    );
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+
+    send(&mut setup);
+    send(&mut setup);
+    let info = setup.module_impl.archive_info(EmptyArg).unwrap();
+    assert_eq!(info.archived, 0);
+    assert_eq!(info.retained, 2);
+    assert_eq!(info.retention_blocks, None);
+
+    for _ in 0..4 {
+        send(&mut setup);
+    }
+
+    // Only the events of the last blocks are kept in the live store.
+    let info = setup.module_impl.archive_info(EmptyArg).unwrap();
+    assert!(info.archived > 0);
+    assert_eq!(info.archived + info.retained, 6);
+    assert!(info.retained <= 3);
+    assert_eq!(info.retention_blocks, Some(2));
+
+    let list = setup.module_impl.list(ListArgs::default()).unwrap();
+    assert_eq!(list.events.len() as u64, info.retained);
+    assert_eq!(
+        list.events.first().map(|e| e.id.clone()),
+        info.oldest_retained
+    );
+}
@@ -341,3 +341,105 @@ proptest! {
         assert!(result.events.is_empty());
     }
 }
+
+#[test]
+fn subscribe() {
+    let Setup {
+        mut module_impl,
+        id,
+        ..
+    } = setup();
+    send(&mut module_impl, id, identity(1));
+
+    let subscription = events::EventsSubscriptionModuleBackend::subscribe(
+        &mut module_impl,
+        &id,
+        events::SubscribeArgs {
+            filter: events::SubscriptionFilter {
+                account: Some(vec![identity(2)].into()),
+                ..Default::default()
+            },
+        },
+    )
+    .unwrap()
+    .id;
+
+    // Events logged before subscribing are not returned.
+    send_(&mut module_impl, id, identity(1));
+    send_(&mut module_impl, id, identity(2));
+
+    let poll = |module_impl: &mut LedgerModuleImpl, sender: &Address| {
+        events::EventsSubscriptionModuleBackend::poll(
+            module_impl,
+            sender,
+            events::PollArgs {
+                id: subscription,
+                count: None,
+            },
+        )
+    };
+
+    // Only the subscriber can poll.
+    assert_many_err(
+        poll(&mut module_impl, &identity(5)),
+        events::errors::not_subscription_owner(subscription),
+    );
+
+    let result = poll(&mut module_impl, &id).unwrap();
+    assert_eq!(result.events.len(), 1);
+    assert!(result.events[0].is_about(identity(2)));
+    assert!(poll(&mut module_impl, &id).unwrap().events.is_empty());
+
+    assert_many_err(
+        events::EventsSubscriptionModuleBackend::unsubscribe(
+            &mut module_impl,
+            &identity(5),
+            events::UnsubscribeArgs { id: subscription },
+        ),
+        events::errors::not_subscription_owner(subscription),
+    );
+    events::EventsSubscriptionModuleBackend::unsubscribe(
+        &mut module_impl,
+        &id,
+        events::UnsubscribeArgs { id: subscription },
+    )
+    .unwrap();
+    assert!(poll(&mut module_impl, &id).is_err());
+}
+
+#[test]
+fn subscribe_blockchain() {
+    let mut harness = Setup::new(true);
+    let id = harness.id;
+    let subscription = events::EventsSubscriptionModuleBackend::subscribe(
+        &mut harness.module_impl,
+        &id,
+        events::SubscribeArgs::default(),
+    )
+    .unwrap()
+    .id;
+    let poll_args = events::PollArgs {
+        id: subscription,
+        count: None,
+    };
+
+    harness.set_balance(id, 1000, *MFX_SYMBOL);
+    harness.block(|h| {
+        h.send_(id, identity(1), 10u32);
+
+        // Not committed yet.
+        let result = events::EventsSubscriptionModuleBackend::poll(
+            &mut h.module_impl,
+            &id,
+            poll_args.clone(),
+        )
+        .unwrap();
+        assert!(result.events.is_empty());
+    });
+
+    let result =
+        events::EventsSubscriptionModuleBackend::poll(&mut harness.module_impl, &id, poll_args)
+            .unwrap();
+    assert_eq!(result.events.len(), 1);
+    assert_eq!(result.events[0].kind(), events::EventKind::Send);
+}
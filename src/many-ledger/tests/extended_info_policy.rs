@@ -0,0 +1,112 @@
+use {
+    many_error::ManyError,
+    many_identity::testing::identity,
+    many_ledger::error,
+    many_ledger::json::ExtendedInfoPolicyJson,
+    many_ledger::migration::token_create::TOKEN_CREATE_MIGRATION,
+    many_ledger::migration::tokens::TOKEN_MIGRATION,
+    many_ledger_test_utils::*,
+    many_modules::ledger::extended_info::visual_logo::VisualTokenLogo,
+    many_modules::ledger::extended_info::TokenExtendedInfo,
+    many_modules::ledger::{LedgerTokensModuleBackend, TokenAddExtendedInfoArgs},
+    many_types::ledger::{Symbol, TokenMaybeOwner},
+    std::collections::BTreeSet,
+};
+
+fn setup(policy: ExtendedInfoPolicyJson) -> Setup {
+    Setup::new_with_state(
+        false,
+        [(0, &TOKEN_MIGRATION), (0, &TOKEN_CREATE_MIGRATION)],
+        |state| state.extended_info_policy = Some(policy),
+    )
+}
+
+fn setup_ranges(ranges: Vec<(u32, u32)>) -> Setup {
+    setup(ExtendedInfoPolicyJson {
+        unicode_ranges: Some(ranges),
+        ..Default::default()
+    })
+}
+
+fn create(setup: &mut Setup) -> Result<Symbol, ManyError> {
+    LedgerTokensModuleBackend::create(
+        &mut setup.module_impl,
+        &identity(1),
+        default_token_create_args(Some(TokenMaybeOwner::Left(identity(1))), None),
+    )
+    .map(|r| r.info.symbol)
+}
+
+fn add_logo(
+    setup: &mut Setup,
+    symbol: Symbol,
+    content_type: &str,
+    size: usize,
+) -> Result<(), ManyError> {
+    let mut logos = VisualTokenLogo::new();
+    logos.image_back(content_type, vec![1u8; size]);
+    LedgerTokensModuleBackend::add_extended_info(
+        &mut setup.module_impl,
+        &identity(1),
+        TokenAddExtendedInfoArgs {
+            symbol,
+            extended_info: TokenExtendedInfo::new().with_visual_logo(logos).unwrap(),
+            memo: None,
+        },
+    )
+    .map(|_| ())
+}
+
+#[test]
+/// Verify character logos outside the allowed unicode ranges are rejected.
+fn unicode_ranges() {
+    // The default token logo is U+2211.
+    let mut setup = setup_ranges(vec![(0x41, 0x5A)]);
+    assert_many_err(create(&mut setup), error::logo_char_not_allowed("U+2211"));
+
+    let mut setup = setup_ranges(vec![(0x2200, 0x22FF)]);
+    assert!(create(&mut setup).is_ok());
+}
+
+#[test]
+/// Verify image logos must have an allowed content type and size.
+fn images() {
+    let mut setup = setup(ExtendedInfoPolicyJson {
+        max_image_size: Some(10),
+        content_types: Some(BTreeSet::from(["image/png".to_string()])),
+        ..Default::default()
+    });
+    let symbol = create(&mut setup).unwrap();
+
+    assert!(add_logo(&mut setup, symbol, "image/png", 10).is_ok());
+    assert_many_err(
+        add_logo(&mut setup, symbol, "image/png", 11),
+        error::logo_image_too_large(11, 10),
+    );
+    assert_many_err(
+        add_logo(&mut setup, symbol, "image/svg+xml", 10),
+        error::logo_content_type_not_allowed("image/svg+xml"),
+    );
+}
+
+#[test]
+/// Verify the extended info memo is checked against the policy.
+fn memo() {
+    // The default token memo is "Foofoo".
+    let mut setup = setup(ExtendedInfoPolicyJson {
+        max_memo_size: Some(3),
+        ..Default::default()
+    });
+    assert_many_err(
+        create(&mut setup),
+        error::extended_info_memo_too_large(6, 3),
+    );
+}
+
+#[test]
+/// Verify the default policy accepts the default token.
+fn default_policy() {
+    let mut setup = setup(ExtendedInfoPolicyJson::default());
+    let symbol = create(&mut setup).unwrap();
+    assert!(add_logo(&mut setup, symbol, "image/png", 1024).is_ok());
+}
@@ -23,6 +23,22 @@ Scenario: Add token image logo extended info as myself
 	When I add the extended info to the token as myself
 	Then the token has the png image logo '010203'
 
+@tokens
+Scenario: Add token attachments extended info as myself
+	Given a default token owned by myself
+	And a text/plain attachment "Whitepaper" at index 1
+	When I add the extended info to the token as myself
+	Given a application/json attachment '{"foo": "bar"}' at index 2
+	When I add the extended info to the token as myself
+	Then the token has the text/plain attachment "Whitepaper" at index 1
+	And the token has the application/json attachment '{"foo": "bar"}' at index 2
+
+@tokens
+Scenario: Add token attachment whose content does not match its hash
+	Given a default token owned by myself
+	And a text/plain attachment "Whitepaper" with the hash of "Blackpaper"
+	Then adding extended info to the token as myself fails with invalid attachment
+
 @tokens
 Scenario: Add ext. info to a token owned by myself as anonymous/random
 	Given a default token owned by myself
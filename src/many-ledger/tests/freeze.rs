@@ -0,0 +1,207 @@
+use {
+    many_identity::testing::identity,
+    many_identity::Address,
+    many_ledger::migration::freeze::FREEZE_MIGRATION,
+    many_ledger::migration::tokens::TOKEN_MIGRATION,
+    many_ledger_test_utils::*,
+    many_modules::events::{EventFilter, EventInfo, EventKind, EventsModuleBackend, ListArgs},
+    many_modules::ledger::{
+        address_already_frozen, address_frozen, address_not_frozen, FreezeArgs, FrozenArgs,
+        LedgerFreezeModuleBackend, LedgerMintBurnModuleBackend, TokenMintArgs, UnfreezeArgs,
+    },
+    std::collections::BTreeMap,
+    std::str::FromStr,
+};
+
+/// The server identity in the staging state, which can freeze addresses.
+fn admin() -> Address {
+    Address::from_str("mahukzwuwgt3porn6q4vq4xu3mwy5gyskhouryzbscq7wb2iow").unwrap()
+}
+
+fn setup() -> Setup {
+    Setup::new_with_migrations(false, [(0, &FREEZE_MIGRATION)], true)
+}
+
+fn freeze(setup: &mut Setup, address: Address) {
+    setup
+        .module_impl
+        .freeze(
+            &admin(),
+            FreezeArgs {
+                address,
+                memo: None,
+            },
+        )
+        .unwrap();
+}
+
+fn unfreeze(setup: &mut Setup, address: Address) {
+    setup
+        .module_impl
+        .unfreeze(
+            &admin(),
+            UnfreezeArgs {
+                address,
+                memo: None,
+            },
+        )
+        .unwrap();
+}
+
+#[test]
+fn admin_only() {
+    let mut setup = setup();
+    let args = FreezeArgs {
+        address: identity(5),
+        memo: None,
+    };
+    assert_many_err(
+        setup.module_impl.freeze(&identity(1), args.clone()),
+        many_ledger::error::unauthorized(),
+    );
+    assert!(setup.module_impl.freeze(&admin(), args.clone()).is_ok());
+    assert_many_err(
+        setup.module_impl.freeze(&admin(), args),
+        address_already_frozen(identity(5)),
+    );
+
+    assert_many_err(
+        setup.module_impl.unfreeze(
+            &identity(1),
+            UnfreezeArgs {
+                address: identity(5),
+                memo: None,
+            },
+        ),
+        many_ledger::error::unauthorized(),
+    );
+    assert_many_err(
+        setup.module_impl.unfreeze(
+            &admin(),
+            UnfreezeArgs {
+                address: identity(6),
+                memo: None,
+            },
+        ),
+        address_not_frozen(identity(6)),
+    );
+
+    let frozen = setup
+        .module_impl
+        .frozen(&identity(1), FrozenArgs {})
+        .unwrap();
+    assert_eq!(
+        frozen.addresses.into_iter().collect::<Vec<_>>(),
+        vec![identity(5)]
+    );
+}
+
+#[test]
+fn disabled_without_migration() {
+    let mut setup = Setup::new(false);
+    assert!(setup
+        .module_impl
+        .freeze(
+            &admin(),
+            FreezeArgs {
+                address: identity(5),
+                memo: None,
+            },
+        )
+        .is_err());
+}
+
+#[test]
+fn send() {
+    let mut setup = setup();
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+    setup.set_balance(identity(5), 1_000, *MFX_SYMBOL);
+    freeze(&mut setup, identity(5));
+
+    assert_many_err(
+        setup.send(id, identity(5), 10u16, *MFX_SYMBOL),
+        address_frozen(identity(5)),
+    );
+    assert_many_err(
+        setup.send(identity(5), id, 10u16, *MFX_SYMBOL),
+        address_frozen(identity(5)),
+    );
+    assert_eq!(setup.balance_(id), 1_000u32);
+    assert!(setup.send(id, identity(6), 10u16, *MFX_SYMBOL).is_ok());
+
+    // Unfreezing allows transfers again.
+    unfreeze(&mut setup, identity(5));
+    assert!(setup.send(id, identity(5), 10u16, *MFX_SYMBOL).is_ok());
+    assert_eq!(setup.balance_(identity(5)), 1_010u32);
+}
+
+#[test]
+fn mint() {
+    let mut setup =
+        Setup::new_with_migrations(false, [(0, &FREEZE_MIGRATION), (0, &TOKEN_MIGRATION)], true);
+    freeze(&mut setup, identity(5));
+
+    assert_many_err(
+        setup.module_impl.mint(
+            &admin(),
+            TokenMintArgs {
+                symbol: *MFX_SYMBOL,
+                distribution: BTreeMap::from([
+                    (identity(4), 10u16.into()),
+                    (identity(5), 10u16.into()),
+                ]),
+                memo: None,
+            },
+        ),
+        address_frozen(identity(5)),
+    );
+    assert_eq!(setup.balance_(identity(4)), 0u32);
+}
+
+#[test]
+fn multisig_execute() {
+    let mut setup = setup();
+    let account_id = setup.create_account_(AccountType::Multisig);
+    setup.set_balance(account_id, 1_000, *MFX_SYMBOL);
+
+    let token = setup.multisig_send_(account_id, identity(5), 10u16);
+    setup.multisig_approve_(identity(2), &token);
+    setup.multisig_approve_(identity(3), &token);
+    freeze(&mut setup, account_id);
+
+    let response = setup.multisig_execute_(&token);
+    assert_eq!(response.data, Err(address_frozen(account_id)));
+    assert_eq!(setup.balance_(account_id), 1_000u32);
+}
+
+#[test]
+fn events() {
+    let mut setup = setup();
+    freeze(&mut setup, identity(5));
+    unfreeze(&mut setup, identity(5));
+
+    let events: Vec<EventInfo> = setup
+        .module_impl
+        .list(ListArgs {
+            count: None,
+            order: None,
+            filter: Some(EventFilter {
+                kind: Some(vec![EventKind::AddressFreeze, EventKind::AddressUnfreeze].into()),
+                ..Default::default()
+            }),
+        })
+        .unwrap()
+        .events
+        .into_iter()
+        .map(|e| e.content)
+        .collect();
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().any(|e| matches!(
+        e,
+        EventInfo::AddressFreeze { address, .. } if *address == identity(5)
+    )));
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, EventInfo::AddressUnfreeze { .. })));
+}
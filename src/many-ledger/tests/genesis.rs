@@ -0,0 +1,122 @@
+use many_identity::testing::identity;
+use many_identity::Address;
+use many_ledger::json::InitialStateJson;
+use many_ledger::module::LedgerModuleImpl;
+use many_ledger_test_utils::*;
+use many_ledger_verify::genesis::{verify_genesis, GenesisVerification, GENESIS_HASH_AUX_KEY};
+use many_modules::abci_backend::{AbciBlock, ManyAbciModuleBackend};
+use many_modules::ledger::{LedgerCommandsModuleBackend, SendArgs};
+use many_types::ledger::TokenAmount;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// An account with an initial balance in the staging state.
+fn holder() -> Address {
+    Address::from_str("maffbahksdwaqeenayy2gxke32hgb7aq4ao4wt745lsfs6wijp").unwrap()
+}
+
+fn state() -> InitialStateJson {
+    InitialStateJson::read("../../staging/ledger_state.json5")
+        .or_else(|_| InitialStateJson::read("staging/ledger_state.json5"))
+        .expect("Could not read initial state.")
+}
+
+fn create_store(state: InitialStateJson) -> PathBuf {
+    let path = tempfile::tempdir().unwrap().into_path();
+    LedgerModuleImpl::new(state, None, &path, true).unwrap();
+    path
+}
+
+/// Commit a block with a send, moving the store past its genesis.
+fn commit_block(path: &Path) {
+    let mut module_impl = LedgerModuleImpl::load(None, path, true).unwrap();
+    module_impl
+        .begin_block(AbciBlock {
+            time: Some(1_000_000),
+            proposer: None,
+        })
+        .unwrap();
+    module_impl
+        .send(
+            &holder(),
+            SendArgs {
+                from: None,
+                to: identity(5),
+                amount: 10u16.into(),
+                symbol: *MFX_SYMBOL,
+                memo: None,
+            },
+        )
+        .unwrap();
+    module_impl.end_block().unwrap();
+    module_impl.commit().unwrap();
+}
+
+/// Replay the state into a fresh store and verify `store` against it.
+fn verify(store: &Path, mut state: InitialStateJson) -> GenesisVerification {
+    let state_hash = state.hash.take().map(|h| hex::decode(h).unwrap());
+    let replayed = create_store(state);
+    verify_genesis(store, replayed, state_hash).unwrap()
+}
+
+#[test]
+fn genesis() {
+    let store = create_store(state());
+    let result = verify(&store, state());
+    assert!(result.is_valid());
+    assert!(result.diff.is_empty());
+    assert_eq!(result.store_height, 0);
+    assert_eq!(result.recorded_hash, Some(result.store_hash.clone()));
+}
+
+#[test]
+fn past_genesis() {
+    let store = create_store(state());
+    commit_block(&store);
+
+    let result = verify(&store, state());
+    assert_eq!(result.store_height, 1);
+    assert_ne!(result.store_hash, result.replayed_hash);
+    assert!(result.is_valid());
+}
+
+#[test]
+fn past_genesis_without_recorded_hash() {
+    let store = create_store(state());
+    commit_block(&store);
+    {
+        let mut merk = merk::Merk::open(&store).unwrap();
+        merk.commit(&[(GENESIS_HASH_AUX_KEY.to_vec(), merk::Op::Delete)])
+            .unwrap();
+    }
+
+    let result = verify(&store, state());
+    assert_eq!(result.recorded_hash, None);
+    assert_eq!(result.genesis_hash(), None);
+    assert!(!result.is_valid());
+}
+
+#[test]
+fn modified() {
+    let mut modified = state();
+    modified.hash = None;
+    let (_, balances) = modified.initial.iter_mut().next().unwrap();
+    let (_, amount) = balances.iter_mut().next().unwrap();
+    *amount = TokenAmount::from(1u64);
+    let store = create_store(modified);
+
+    let result = verify(&store, state());
+    assert!(!result.is_valid());
+    let balance = result
+        .diff
+        .iter()
+        .find(|d| d.key.starts_with(b"/balances/"))
+        .unwrap();
+    assert!(balance.to_string().ends_with("!= 1"));
+}
+
+#[test]
+fn missing_store() {
+    let replayed = create_store(state());
+    assert!(verify_genesis("/does/not/exist", replayed, None).is_err());
+}
@@ -1,5 +1,10 @@
 use many_error::ManyError;
 use many_identity::Address;
+use many_ledger::migration::idstore_namespaces::IDSTORE_NAMESPACES_MIGRATION;
+use many_ledger::migration::idstore_wordlists::{
+    CUSTOM_WORDLISTS_KEY, DEFAULT_WORDLIST_KEY, IDSTORE_WORDLISTS_MIGRATION,
+};
+use many_ledger::module::idstore_wordlist::{Bip39Language, Wordlist, Wordlists, WORDLIST_LENGTH};
 use many_ledger::module::LedgerModuleImpl;
 use many_ledger_test_utils::*;
 use many_modules::idstore;
@@ -26,6 +31,8 @@ fn setup_with_args() -> SetupWithArgs {
             address: id,
             cred_id,
             public_key,
+            wordlist: None,
+            namespace: None,
         },
     }
 }
@@ -128,7 +135,7 @@ fn get_from_recall_phrase() {
         ..
     } = setup_with_store();
     let result =
-        module_impl.get_from_recall_phrase(idstore::GetFromRecallPhraseArgs(recall_phrase));
+        module_impl.get_from_recall_phrase(idstore::GetFromRecallPhraseArgs(recall_phrase, None));
     assert!(result.is_ok());
     let get_returns = result.unwrap();
     assert_eq!(get_returns.cred_id, cred_id);
@@ -139,8 +146,10 @@ fn get_from_recall_phrase() {
 /// Verify we can't fetch ID from an invalid recall phrase
 fn get_from_invalid_recall_phrase() {
     let SetupWithStore { module_impl, .. } = setup_with_store();
-    let result = module_impl
-        .get_from_recall_phrase(idstore::GetFromRecallPhraseArgs(vec!["Foo".to_string()]));
+    let result = module_impl.get_from_recall_phrase(idstore::GetFromRecallPhraseArgs(
+        vec!["Foo".to_string()],
+        None,
+    ));
     assert!(result.is_err());
     assert_eq!(
         result.unwrap_err().code(),
@@ -176,3 +185,201 @@ fn get_from_invalid_address() {
         idstore::entry_not_found("".to_string()).code()
     );
 }
+
+#[test]
+/// Verify recall phrases are english until the wordlists migration is active
+fn wordlists_disabled() {
+    let SetupWithArgs {
+        mut module_impl,
+        id,
+        mut args,
+    } = setup_with_args();
+    let english = module_impl.store(&id, args.clone()).unwrap().0;
+    assert_eq!(
+        english,
+        Wordlist::Bip39(Bip39Language::English)
+            .recall_phrase::<2, 2, 6>(&0u16.to_be_bytes())
+            .unwrap()
+    );
+
+    args.wordlist = Some("french".to_string());
+    assert!(module_impl.store(&id, args).is_err());
+
+    // Custom wordlists must be part of the migration config.
+    let mut wordlists = Wordlists::default();
+    wordlists.insert("custom", custom_wordlist());
+    assert!(module_impl.set_idstore_wordlists(wordlists).is_err());
+}
+
+fn custom_wordlist() -> Wordlist {
+    Wordlist::custom((0..WORDLIST_LENGTH).map(|i| format!("w{i}")).collect()).unwrap()
+}
+
+#[test]
+/// Verify recall phrases of every wordlist of the migration config resolve,
+/// and the default one is the one of the migration config
+fn wordlists() {
+    let Setup {
+        mut module_impl,
+        id,
+        cred_id,
+        public_key,
+        ..
+    } = Setup::new_with_migrations(
+        false,
+        [MigrationHarness::from((0, &IDSTORE_WORDLISTS_MIGRATION))
+            .with_extra(DEFAULT_WORDLIST_KEY, serde_json::json!("french"))
+            .with_extra(
+                CUSTOM_WORDLISTS_KEY,
+                serde_json::json!({ "custom": custom_wordlist().hash().unwrap() }),
+            )],
+        true,
+    );
+    let mut args = idstore::StoreArgs {
+        address: id,
+        cred_id,
+        public_key,
+        wordlist: None,
+        namespace: None,
+    };
+
+    // The custom wordlist of the migration config is missing.
+    assert!(module_impl
+        .set_idstore_wordlists(Wordlists::default())
+        .is_err());
+    let mut wordlists = Wordlists::default();
+    wordlists.insert("custom", custom_wordlist());
+    module_impl.set_idstore_wordlists(wordlists).unwrap();
+
+    let french = module_impl.store(&id, args.clone()).unwrap().0;
+    assert_eq!(
+        french,
+        Wordlist::Bip39(Bip39Language::French)
+            .recall_phrase::<2, 2, 6>(&0u16.to_be_bytes())
+            .unwrap()
+    );
+    args.wordlist = Some("english".to_string());
+    let english = module_impl.store(&id, args.clone()).unwrap().0;
+    args.wordlist = Some("custom".to_string());
+    let custom = module_impl.store(&id, args.clone()).unwrap().0;
+    assert!(custom.iter().all(|w| w.starts_with('w')));
+
+    for phrase in [english, french, custom] {
+        let get_returns = module_impl
+            .get_from_recall_phrase(idstore::GetFromRecallPhraseArgs(phrase, None))
+            .unwrap();
+        assert_eq!(get_returns.cred_id, args.cred_id);
+    }
+
+    args.wordlist = Some("klingon".to_string());
+    assert_eq!(
+        module_impl.store(&id, args).unwrap_err().code(),
+        idstore::unknown_wordlist("").code()
+    );
+}
+
+fn setup_namespaces() -> SetupWithArgs {
+    let Setup {
+        module_impl,
+        id,
+        cred_id,
+        public_key,
+        ..
+    } = Setup::new_with_migrations(false, [(0, &IDSTORE_NAMESPACES_MIGRATION)], true);
+    SetupWithArgs {
+        module_impl,
+        id,
+        args: idstore::StoreArgs {
+            address: id,
+            cred_id,
+            public_key,
+            wordlist: None,
+            namespace: None,
+        },
+    }
+}
+
+#[test]
+/// Verify recall phrases of different namespaces don't collide
+fn namespaces() {
+    let SetupWithArgs {
+        mut module_impl,
+        id,
+        mut args,
+    } = setup_namespaces();
+    let default = module_impl.store(&id, args.clone()).unwrap().0;
+
+    // Each namespace has its own seed, so the first phrases are the same.
+    args.namespace = Some("app".to_string());
+    args.cred_id = CredentialId(vec![2; 16].into());
+    let app = module_impl.store(&id, args.clone()).unwrap().0;
+    assert_eq!(default, app);
+
+    let get = |namespace: Option<&str>| {
+        module_impl.get_from_recall_phrase(idstore::GetFromRecallPhraseArgs(
+            default.clone(),
+            namespace.map(String::from),
+        ))
+    };
+    assert_eq!(get(None).unwrap().cred_id, CredentialId(vec![1; 16].into()));
+    assert_eq!(get(Some("app")).unwrap().cred_id, args.cred_id);
+    assert_eq!(
+        get(Some("other")).unwrap_err().code(),
+        idstore::entry_not_found("").code()
+    );
+}
+
+#[test]
+/// Verify namespaces are refused unless valid and enabled
+fn invalid_namespace() {
+    let SetupWithArgs {
+        mut module_impl,
+        id,
+        mut args,
+    } = setup_namespaces();
+    for namespace in ["", "a/b", &"a".repeat(idstore::MAX_NAMESPACE_LENGTH + 1)] {
+        args.namespace = Some(namespace.to_string());
+        assert_eq!(
+            module_impl.store(&id, args.clone()).unwrap_err().code(),
+            idstore::invalid_namespace("").code()
+        );
+    }
+
+    let SetupWithArgs {
+        mut module_impl,
+        id,
+        mut args,
+    } = setup_with_args();
+    args.namespace = Some("app".to_string());
+    assert!(module_impl.store(&id, args).is_err());
+}
+
+#[test]
+/// Verify the statistics of each namespace
+fn stats() {
+    let SetupWithArgs {
+        mut module_impl,
+        id,
+        mut args,
+    } = setup_namespaces();
+    module_impl.store(&id, args.clone()).unwrap();
+    module_impl.store(&id, args.clone()).unwrap();
+    args.namespace = Some("app".to_string());
+    module_impl.store(&id, args).unwrap();
+
+    let stats = |namespace: Option<&str>| {
+        module_impl
+            .stats(idstore::StatsArgs {
+                namespace: namespace.map(String::from),
+            })
+            .unwrap()
+    };
+    assert_eq!(stats(None).count, 2);
+    assert_eq!(stats(Some("app")).count, 1);
+    assert!(stats(Some("app")).last_assignment.is_some());
+    assert_eq!(stats(Some("other")), idstore::StatsReturns::default());
+
+    // Statistics are only available with namespaces.
+    let SetupWithArgs { module_impl, .. } = setup_with_args();
+    assert!(module_impl.stats(idstore::StatsArgs::default()).is_err());
+}
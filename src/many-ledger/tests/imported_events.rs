@@ -0,0 +1,181 @@
+use {
+    many_error::ManyError,
+    many_identity::testing::identity,
+    many_identity::Address,
+    many_ledger::json::{ImportedEventJson, InitialStateJson},
+    many_ledger::migration::event_ids::EVENT_IDS_MIGRATION,
+    many_ledger::module::LedgerModuleImpl,
+    many_ledger::storage::event::IMPORTED_EVENT_ID_LIMIT,
+    many_ledger_test_utils::*,
+    many_migration::MigrationConfig,
+    many_modules::events::{EventId, EventInfo, EventsModuleBackend, ListArgs},
+    many_modules::EmptyArg,
+    many_types::{SortOrder, Timestamp},
+    std::str::FromStr,
+};
+
+/// An account with an initial balance in the staging state.
+fn holder() -> Address {
+    Address::from_str("maffbahksdwaqeenayy2gxke32hgb7aq4ao4wt745lsfs6wijp").unwrap()
+}
+
+fn send_event(to: Address, amount: u64) -> EventInfo {
+    EventInfo::Send {
+        from: holder(),
+        to,
+        symbol: *MFX_SYMBOL,
+        amount: amount.into(),
+        memo: None,
+    }
+}
+
+fn imported(id: u64, time: u64, content: EventInfo) -> ImportedEventJson {
+    ImportedEventJson {
+        id,
+        time,
+        content: hex::encode(minicbor::to_vec(content).unwrap()),
+    }
+}
+
+fn list(setup: &Setup, order: SortOrder) -> Vec<(EventId, Timestamp, EventInfo)> {
+    setup
+        .module_impl
+        .list(ListArgs {
+            count: None,
+            order: Some(order),
+            filter: None,
+        })
+        .unwrap()
+        .events
+        .into_iter()
+        .map(|e| (e.id, e.time, e.content))
+        .collect()
+}
+
+/// A setup with `events` imported at genesis, which requires the event IDs
+/// migration.
+fn setup_with_imports(events: Vec<ImportedEventJson>) -> Setup {
+    Setup::new_with_state(false, [(0, &EVENT_IDS_MIGRATION)], |state| {
+        state.imported_events = Some(events)
+    })
+}
+
+fn event_ids_at_genesis() -> MigrationConfig {
+    serde_json::from_str(&format!(
+        r#"{{ "migrations": [{}] }}"#,
+        MigrationHarness::from((0, &EVENT_IDS_MIGRATION)).to_json_str()
+    ))
+    .unwrap()
+}
+
+fn new_ledger(
+    events: Vec<ImportedEventJson>,
+    migrations: Option<MigrationConfig>,
+) -> Result<LedgerModuleImpl, ManyError> {
+    let mut state = InitialStateJson::read("../../staging/ledger_state.json5").unwrap();
+    state.hash = None;
+    state.imported_events = Some(events);
+
+    let store_path = tempfile::tempdir().unwrap();
+    LedgerModuleImpl::new(state, migrations, store_path, false)
+}
+
+#[test]
+/// Verify imported events are listed with their original IDs and times.
+fn import() {
+    let setup = setup_with_imports(vec![
+        imported(10, 1_000, send_event(identity(5), 100)),
+        imported(20, 2_000, send_event(identity(6), 200)),
+    ]);
+
+    assert_eq!(setup.module_impl.info(EmptyArg).unwrap().total, 2);
+    assert_eq!(
+        list(&setup, SortOrder::Ascending),
+        vec![
+            (
+                EventId::from(10),
+                Timestamp::new(1_000).unwrap(),
+                send_event(identity(5), 100)
+            ),
+            (
+                EventId::from(20),
+                Timestamp::new(2_000).unwrap(),
+                send_event(identity(6), 200)
+            ),
+        ]
+    );
+}
+
+#[test]
+/// Verify imported events do not affect balances.
+fn balances_unchanged() {
+    let setup = setup_with_imports(vec![imported(1, 1_000, send_event(identity(5), 100))]);
+
+    assert_eq!(setup.balance_(holder()), 1_000_000_000u32);
+    assert_eq!(setup.balance_(identity(5)), 0u32);
+}
+
+#[test]
+/// Verify events logged after the import follow the imported ones.
+fn new_events_follow() {
+    let mut setup = setup_with_imports(vec![imported(
+        IMPORTED_EVENT_ID_LIMIT - 1,
+        1_000,
+        send_event(identity(5), 100),
+    )]);
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+    setup.send(id, identity(6), 10u16, *MFX_SYMBOL).unwrap();
+
+    let events = list(&setup, SortOrder::Descending);
+    assert_eq!(events.len(), 2);
+    assert!(matches!(events[0].2, EventInfo::Send { to, .. } if to == identity(6)));
+    assert_eq!(events[1].0, EventId::from(IMPORTED_EVENT_ID_LIMIT - 1));
+}
+
+#[test]
+/// Verify invalid imports are rejected at genesis.
+fn invalid() {
+    for events in [
+        vec![imported(
+            IMPORTED_EVENT_ID_LIMIT,
+            1_000,
+            send_event(identity(5), 1),
+        )],
+        vec![
+            imported(2, 1_000, send_event(identity(5), 1)),
+            imported(2, 1_000, send_event(identity(5), 1)),
+        ],
+        vec![
+            imported(2, 1_000, send_event(identity(5), 1)),
+            imported(1, 1_000, send_event(identity(5), 1)),
+        ],
+        vec![
+            imported(1, 2_000, send_event(identity(5), 1)),
+            imported(2, 1_000, send_event(identity(5), 1)),
+        ],
+    ] {
+        assert!(new_ledger(events, Some(event_ids_at_genesis())).is_err());
+    }
+}
+
+#[test]
+/// Verify imports are refused unless the event IDs migration is active at
+/// genesis, as the IDs of the first blocks could collide with them.
+fn requires_event_ids_migration() {
+    let events = || vec![imported(1, 1_000, send_event(identity(5), 1))];
+
+    assert!(new_ledger(events(), Some(event_ids_at_genesis())).is_ok());
+    assert!(new_ledger(events(), None).is_err());
+    assert!(new_ledger(
+        events(),
+        Some(
+            serde_json::from_str(&format!(
+                r#"{{ "migrations": [{}] }}"#,
+                MigrationHarness::from((10, &EVENT_IDS_MIGRATION)).to_json_str()
+            ))
+            .unwrap()
+        )
+    )
+    .is_err());
+}
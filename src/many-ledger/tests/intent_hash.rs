@@ -0,0 +1,107 @@
+use {
+    many_identity::testing::identity,
+    many_ledger::migration::intent_hashes::INTENT_HASHES_MIGRATION,
+    many_ledger_test_utils::*,
+    many_modules::events::{
+        self, EventInfo, EventsIntentModuleBackend, EventsModuleBackend, FindByIntentHashArgs,
+        ListArgs,
+    },
+    many_protocol::context::with_intent,
+    many_protocol::{RequestMessage, RequestMessageBuilder},
+    many_types::Timestamp,
+};
+
+fn request(nonce: u8) -> RequestMessage {
+    RequestMessageBuilder::default()
+        .method("ledger.send".to_string())
+        .timestamp(Timestamp::new(1_000_000).unwrap())
+        .nonce(vec![nonce])
+        .build()
+        .unwrap()
+}
+
+fn find(setup: &Setup, request: &RequestMessage) -> Vec<EventInfo> {
+    setup
+        .module_impl
+        .find_by_intent_hash(FindByIntentHashArgs {
+            intent_hash: request.intent_hash().unwrap().into(),
+        })
+        .unwrap()
+        .events
+        .into_iter()
+        .map(|e| e.content)
+        .collect()
+}
+
+fn setup(enabled: bool) -> Setup {
+    let mut setup = if enabled {
+        Setup::new_with_migrations(true, [(0, &INTENT_HASHES_MIGRATION)], true)
+    } else {
+        Setup::new(true)
+    };
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+    setup
+}
+
+#[test]
+fn finds_events_by_intent() {
+    let mut setup = setup(true);
+    let (first, second) = (request(1), request(2));
+    setup.block(|h| {
+        let id = h.id;
+        with_intent(&first, || h.send(id, identity(5), 10u32, *MFX_SYMBOL)).unwrap();
+        with_intent(&second, || h.send(id, identity(6), 20u32, *MFX_SYMBOL)).unwrap();
+    });
+
+    let events = find(&setup, &first);
+    assert_eq!(events.len(), 1);
+    assert!(matches!(events[0], EventInfo::Send { to, .. } if to == identity(5)));
+    let events = find(&setup, &second);
+    assert_eq!(events.len(), 1);
+    assert!(matches!(events[0], EventInfo::Send { to, .. } if to == identity(6)));
+    assert!(find(&setup, &request(3)).is_empty());
+
+    let logged = setup.module_impl.list(ListArgs::default()).unwrap().events;
+    assert!(logged
+        .iter()
+        .any(|e| e.intent_hash.as_ref().map(|h| h.to_vec()) == first.intent_hash().ok()));
+}
+
+#[test]
+fn invalid_intent_hash() {
+    let setup = setup(true);
+    for length in [0, 1, 31, 33] {
+        assert_many_err(
+            setup.module_impl.find_by_intent_hash(FindByIntentHashArgs {
+                intent_hash: vec![0; length].into(),
+            }),
+            events::errors::invalid_intent_hash(length),
+        );
+    }
+}
+
+#[test]
+fn no_intent_outside_requests() {
+    let mut setup = setup(true);
+    setup.block(|h| {
+        let id = h.id;
+        h.send(id, identity(5), 10u32, *MFX_SYMBOL).unwrap();
+    });
+
+    let logged = setup.module_impl.list(ListArgs::default()).unwrap().events;
+    assert!(!logged.is_empty());
+    assert!(logged.iter().all(|e| e.intent_hash.is_none()));
+}
+
+#[test]
+fn disabled() {
+    let mut setup = setup(false);
+    let request = request(1);
+    setup.block(|h| {
+        let id = h.id;
+        with_intent(&request, || h.send(id, identity(5), 10u32, *MFX_SYMBOL)).unwrap();
+    });
+
+    assert!(find(&setup, &request).is_empty());
+}
@@ -0,0 +1,114 @@
+use many_identity::testing::identity;
+use many_ledger::migration::token_create::TOKEN_CREATE_MIGRATION;
+use many_ledger::migration::tokens::TOKEN_MIGRATION;
+use many_ledger::storage::invariants::InvariantViolation;
+use many_ledger_test_utils::*;
+use many_modules::account::features::multisig::{self, AccountMultisigModuleBackend};
+use many_modules::account::{self, AccountModuleBackend};
+use many_modules::events::AccountMultisigTransaction;
+use many_modules::ledger::{self, LedgerTokensModuleBackend};
+use many_types::ledger::{TokenAmount, TokenMaybeOwner};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[test]
+fn supply() {
+    let mut setup = Setup::new_with_migrations(
+        true,
+        [
+            MigrationHarness::from((0, &TOKEN_MIGRATION)),
+            MigrationHarness::from((0, &TOKEN_CREATE_MIGRATION)),
+        ],
+        true,
+    );
+    let (_, symbol) = setup.block(|h| {
+        LedgerTokensModuleBackend::create(
+            &mut h.module_impl,
+            &identity(1),
+            default_token_create_args(Some(TokenMaybeOwner::Left(identity(1))), None),
+        )
+        .unwrap()
+        .info
+        .symbol
+    });
+    setup.block(|h| h.send(identity(1), identity(4), 100u64, symbol).unwrap());
+    assert_eq!(setup.module_impl.check_invariants().unwrap(), vec![]);
+
+    // Setting a balance directly does not update the supply.
+    setup.block(|h| {
+        h.module_impl
+            .set_balance_only_for_testing(identity(1), 1000, symbol)
+            .unwrap()
+    });
+    assert_eq!(
+        setup.module_impl.check_invariants().unwrap(),
+        vec![InvariantViolation::SupplyMismatch {
+            symbol,
+            balances: TokenAmount::from(2345u64),
+            escrowed: TokenAmount::zero(),
+            circulating: TokenAmount::from(1368u64),
+        }]
+    );
+}
+
+#[test]
+fn multisig_approvals() {
+    let SetupWithAccount {
+        mut module_impl,
+        id,
+        account_id,
+    } = setup_with_account(AccountType::Multisig);
+    let token = module_impl
+        .multisig_submit_transaction(
+            &id,
+            multisig::SubmitTransactionArgs {
+                account: account_id,
+                memo: None,
+                transaction: Box::new(AccountMultisigTransaction::Send(ledger::SendArgs {
+                    from: Some(account_id),
+                    to: identity(4),
+                    amount: 10u16.into(),
+                    symbol: *MFX_SYMBOL,
+                    memo: None,
+                })),
+                threshold: None,
+                timeout_in_secs: None,
+                execute_automatically: None,
+                data_: None,
+                memo_: None,
+            },
+        )
+        .unwrap()
+        .token;
+    module_impl
+        .multisig_approve(
+            &identity(2),
+            multisig::ApproveArgs {
+                token: token.clone(),
+                approve_as: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(module_impl.check_invariants().unwrap(), vec![]);
+
+    // Removing a member keeps its approval.
+    module_impl
+        .remove_roles(
+            &id,
+            account::RemoveRolesArgs {
+                account: account_id,
+                roles: BTreeMap::from([(
+                    identity(2),
+                    BTreeSet::from([account::Role::CanMultisigApprove]),
+                )]),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        module_impl.check_invariants().unwrap(),
+        vec![InvariantViolation::ApproverNotMember {
+            account: account_id,
+            token: token.into(),
+            approver: identity(2),
+        }]
+    );
+}
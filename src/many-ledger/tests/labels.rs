@@ -0,0 +1,143 @@
+use {
+    many_identity::testing::identity,
+    many_ledger::migration::labels::{
+        ADDRESS_LABELS_MIGRATION, DEPOSIT_AMOUNT_KEY, DEPOSIT_SYMBOL_KEY,
+    },
+    many_ledger_test_utils::*,
+    many_modules::labels::{
+        address_already_labeled, address_not_labeled, invalid_label, label_not_found, label_taken,
+        ClaimArgs, LabelsModuleBackend, LookupArgs, ReleaseArgs, ResolveArgs,
+    },
+};
+
+fn setup() -> Setup {
+    let mut setup = Setup::new_with_migrations(
+        false,
+        [MigrationHarness::from((0, &ADDRESS_LABELS_MIGRATION))
+            .with_extra(
+                DEPOSIT_SYMBOL_KEY,
+                serde_json::json!(MFX_SYMBOL.to_string()),
+            )
+            .with_extra(DEPOSIT_AMOUNT_KEY, serde_json::json!(100))],
+        true,
+    );
+    setup.set_balance(identity(1), 1_000, *MFX_SYMBOL);
+    setup.set_balance(identity(2), 1_000, *MFX_SYMBOL);
+    setup
+}
+
+fn claim_args(label: &str) -> ClaimArgs {
+    ClaimArgs {
+        label: label.to_string(),
+        memo: None,
+    }
+}
+
+#[test]
+fn claim_and_release() {
+    let mut setup = setup();
+    setup
+        .module_impl
+        .claim(&identity(1), claim_args("alice"))
+        .unwrap();
+    assert_eq!(setup.balance_(identity(1)), 900u32);
+
+    let label = setup
+        .module_impl
+        .resolve(
+            &identity(3),
+            ResolveArgs {
+                label: "alice".to_string(),
+            },
+        )
+        .unwrap()
+        .label;
+    assert_eq!(label.owner, identity(1));
+    assert_eq!(label.deposit, 100u32);
+    assert_eq!(
+        setup
+            .module_impl
+            .lookup(
+                &identity(3),
+                LookupArgs {
+                    address: identity(1)
+                }
+            )
+            .unwrap()
+            .label,
+        Some(label)
+    );
+
+    setup
+        .module_impl
+        .release(&identity(1), ReleaseArgs { memo: None })
+        .unwrap();
+    assert_eq!(setup.balance_(identity(1)), 1_000u32);
+    assert_many_err(
+        setup.module_impl.resolve(
+            &identity(3),
+            ResolveArgs {
+                label: "alice".to_string(),
+            },
+        ),
+        label_not_found("alice"),
+    );
+    assert_eq!(
+        setup
+            .module_impl
+            .lookup(
+                &identity(3),
+                LookupArgs {
+                    address: identity(1)
+                }
+            )
+            .unwrap()
+            .label,
+        None
+    );
+}
+
+#[test]
+fn unique_labels() {
+    let mut setup = setup();
+    setup
+        .module_impl
+        .claim(&identity(1), claim_args("alice"))
+        .unwrap();
+    assert_many_err(
+        setup.module_impl.claim(&identity(2), claim_args("alice")),
+        label_taken("alice"),
+    );
+    assert_many_err(
+        setup.module_impl.claim(&identity(1), claim_args("bob")),
+        address_already_labeled(identity(1)),
+    );
+    assert_many_err(
+        setup
+            .module_impl
+            .release(&identity(2), ReleaseArgs { memo: None }),
+        address_not_labeled(identity(2)),
+    );
+}
+
+#[test]
+fn invalid_claims() {
+    let mut setup = setup();
+    assert_many_err(
+        setup.module_impl.claim(&identity(1), claim_args("Alice")),
+        invalid_label("Alice"),
+    );
+    assert_many_err(
+        setup.module_impl.claim(&identity(5), claim_args("carol")),
+        many_ledger::error::insufficient_funds(),
+    );
+}
+
+#[test]
+fn disabled_without_migration() {
+    let mut setup = Setup::new(false);
+    assert!(setup
+        .module_impl
+        .claim(&identity(1), claim_args("alice"))
+        .is_err());
+}
@@ -20,6 +20,14 @@ fn info() {
     assert!(result.is_ok());
 }
 
+#[test]
+fn seeded_setup() {
+    let setup = Setup::new_with_seed(false, 42);
+    assert_eq!(setup.seed, 42);
+    assert_eq!(setup.id, Setup::new_with_seed(false, 42).id);
+    assert_ne!(setup.id, Setup::new_with_seed(false, 43).id);
+}
+
 proptest! {
     #[test]
     fn balance(amount in any::<u64>()) {
@@ -13,6 +13,7 @@ use many_ledger::migration::token_create::TOKEN_CREATE_MIGRATION;
 use many_ledger::migration::tokens::TOKEN_MIGRATION;
 use many_ledger::module::LedgerModuleImpl;
 use many_modules::events::{EventFilter, EventKind, EventsModuleBackend, ListArgs};
+use many_modules::ledger::extended_info::attachment::Attachment;
 use many_modules::ledger::extended_info::visual_logo::VisualTokenLogo;
 use many_modules::ledger::extended_info::TokenExtendedInfo;
 use many_modules::ledger::{LedgerTokensModuleBackend, TokenAddExtendedInfoArgs};
@@ -104,6 +105,27 @@ fn given_string_logo(w: &mut AddExtInfoWorld, content_type: String, data: String
         .expect("Unable to set extended info logo");
 }
 
+#[given(expr = "a {word} attachment {string} at index {int}")]
+fn given_attachment(w: &mut AddExtInfoWorld, content_type: String, data: String, index: u32) {
+    w.args.extended_info = TokenExtendedInfo::new()
+        .with_attachment(index, Attachment::inline(content_type, data.into_bytes()))
+        .expect("Unable to set extended info attachment");
+}
+
+#[given(expr = "a {word} attachment {string} with the hash of {string}")]
+fn given_tampered_attachment(
+    w: &mut AddExtInfoWorld,
+    content_type: String,
+    data: String,
+    hashed: String,
+) {
+    let mut attachment = Attachment::inline(content_type, hashed.into_bytes());
+    attachment.data = Some(data.into_bytes().into());
+    w.args.extended_info = TokenExtendedInfo::new()
+        .with_attachment(0, attachment)
+        .expect("Unable to set extended info attachment");
+}
+
 #[given(expr = "an event memo {string}")]
 fn given_event_memo(w: &mut AddExtInfoWorld, memo: String) {
     w.args.memo = Some(Memo::try_from(memo).unwrap());
@@ -145,6 +167,19 @@ fn then_has_image_logo(w: &mut AddExtInfoWorld, content_type: String, data: Stri
     assert_eq!(w.ext_info.visual_logo().unwrap(), &logo);
 }
 
+#[allow(clippy::needless_pass_by_ref_mut)]
+#[then(expr = "the token has the {word} attachment {string} at index {int}")]
+fn then_has_attachment(w: &mut AddExtInfoWorld, content_type: String, data: String, index: u32) {
+    let attachments = w
+        .ext_info
+        .attachments()
+        .expect("Expected token attachments");
+    assert_eq!(
+        attachments.get(&index),
+        Some(&Attachment::inline(content_type, data.into_bytes()))
+    );
+}
+
 #[then(expr = "adding extended info to the token as {id} fails with {error}")]
 fn then_add_ext_info_token_fail_acl(w: &mut AddExtInfoWorld, id: SomeId, error: SomeError) {
     let id = id.as_address(w);
@@ -0,0 +1,213 @@
+use {
+    async_channel::unbounded,
+    many_identity::testing::identity,
+    many_ledger::json::MemoLimitsJson,
+    many_ledger::migration::typed_memo::TYPED_MEMO_MIGRATION,
+    many_ledger_test_utils::*,
+    many_modules::account::features::multisig::SubmitTransactionArgs,
+    many_modules::events::AccountMultisigTransaction,
+    many_modules::ledger,
+    many_modules::ledger::{LedgerCommandsModuleBackend, LedgerModuleBackend},
+    many_protocol::{context::Context, RequestMessage},
+    many_types::{Memo, MemoLimits},
+};
+
+fn setup_limits() -> Setup {
+    Setup::new_with_state(false, [] as [MigrationHarness; 0], |state| {
+        state.memo_limits = Some(MemoLimitsJson {
+            max_size: 10,
+            max_count: Some(2),
+            max_total_size: Some(16),
+        })
+    })
+}
+
+fn send_with_memo(setup: &mut Setup, memo: Memo) -> Result<(), many_error::ManyError> {
+    let id = setup.id;
+    setup
+        .module_impl
+        .send(
+            &id,
+            ledger::SendArgs {
+                from: Some(id),
+                to: identity(5),
+                amount: 10u16.into(),
+                symbol: *MFX_SYMBOL,
+                memo: Some(memo),
+            },
+        )
+        .map(|_| ())
+}
+
+#[test]
+/// Verify the memo limits are advertised in `ledger.info`.
+fn info() {
+    let setup = Setup::new(false);
+    let info = setup
+        .module_impl
+        .info(
+            &setup.id,
+            ledger::InfoArgs {},
+            Context::new(RequestMessage::default(), unbounded().0),
+        )
+        .unwrap();
+    assert_eq!(info.memo_limits, Some(MemoLimits::default()));
+
+    let setup = setup_limits();
+    let info = setup
+        .module_impl
+        .info(
+            &setup.id,
+            ledger::InfoArgs {},
+            Context::new(RequestMessage::default(), unbounded().0),
+        )
+        .unwrap();
+    assert_eq!(
+        info.memo_limits,
+        Some(MemoLimits {
+            max_size: 10,
+            max_count: Some(2),
+            max_total_size: Some(16),
+        })
+    );
+}
+
+#[test]
+/// Verify memos over the size limit are rejected.
+fn send_memo_too_large() {
+    let mut setup = setup_limits();
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+
+    assert!(send_with_memo(&mut setup, Memo::try_from("0123456789").unwrap()).is_ok());
+    assert_many_err(
+        send_with_memo(&mut setup, Memo::try_from("0123456789A").unwrap()),
+        many_ledger::error::memo_too_large(11, 10),
+    );
+    assert_eq!(setup.balance_(id), 990u32);
+}
+
+#[test]
+/// Verify memos with too many parts are rejected.
+fn send_memo_too_many_parts() {
+    let mut setup = setup_limits();
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+
+    let mut memo = Memo::try_from("Hello").unwrap();
+    memo.push_bytes(vec![1u8, 2, 3]).unwrap();
+    assert!(send_with_memo(&mut setup, memo.clone()).is_ok());
+
+    memo.push_str("World").unwrap();
+    assert_many_err(
+        send_with_memo(&mut setup, memo),
+        many_ledger::error::memo_too_many_parts(3, 2),
+    );
+    assert_eq!(setup.balance_(id), 990u32);
+}
+
+#[test]
+/// Verify memos over the total size limit are rejected.
+fn send_memo_total_too_large() {
+    let mut setup = setup_limits();
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+
+    let mut memo = Memo::try_from("Hello").unwrap();
+    memo.push_bytes(br#"{"a":1}"#.to_vec()).unwrap();
+    assert!(send_with_memo(&mut setup, memo).is_ok());
+
+    let mut memo = Memo::try_from("Hello Many").unwrap();
+    memo.push_bytes(br#"{"a":1}"#.to_vec()).unwrap();
+    assert_many_err(
+        send_with_memo(&mut setup, memo),
+        many_ledger::error::memo_total_too_large(17, 16),
+    );
+
+    assert_eq!(setup.balance_(id), 990u32);
+}
+
+#[test]
+/// Verify typed memo sections are only accepted once the Typed Memo Migration
+/// is active.
+fn send_typed_memo() {
+    let typed_memo = || {
+        let mut memo = Memo::try_from("Hello").unwrap();
+        memo.push_typed("application/json", br#"{"a":1}"#.to_vec())
+            .unwrap();
+        memo
+    };
+
+    let mut setup = Setup::new(false);
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+    assert_many_err(
+        send_with_memo(&mut setup, typed_memo()),
+        many_ledger::error::typed_memo_not_enabled(),
+    );
+
+    let mut setup = Setup::new_with_migrations(false, [(0, &TYPED_MEMO_MIGRATION)], true);
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+    assert!(send_with_memo(&mut setup, typed_memo()).is_ok());
+    assert_eq!(setup.balance_(id), 990u32);
+}
+
+#[test]
+/// Verify memos in multisig transactions are checked on submission.
+fn multisig_memo_too_large() {
+    let mut setup = setup_limits();
+    let account_id = setup.create_account_(AccountType::Multisig);
+    setup.set_balance(account_id, 1_000, *MFX_SYMBOL);
+
+    assert_many_err(
+        setup.create_multisig_as(
+            setup.id,
+            account_id,
+            AccountMultisigTransaction::Send(ledger::SendArgs {
+                from: Some(account_id),
+                to: identity(5),
+                symbol: *MFX_SYMBOL,
+                amount: 10u16.into(),
+                memo: Some(Memo::try_from("This memo is too long").unwrap()),
+            }),
+        ),
+        many_ledger::error::memo_too_large(21, 10),
+    );
+}
+
+#[test]
+/// Verify the memos of every kind of multisig transaction are checked on
+/// submission, including the ones of nested transactions.
+fn multisig_transaction_memo_too_large() {
+    let mut setup = setup_limits();
+    let account_id = setup.create_account_(AccountType::Multisig);
+    let memo = Memo::try_from("This memo is too long").unwrap();
+
+    assert_many_err(
+        setup.create_multisig_as(
+            setup.id,
+            account_id,
+            AccountMultisigTransaction::TokenMint(ledger::TokenMintArgs {
+                symbol: *MFX_SYMBOL,
+                distribution: Default::default(),
+                memo: Some(memo.clone()),
+            }),
+        ),
+        many_ledger::error::memo_too_large(21, 10),
+    );
+    assert_many_err(
+        setup.create_multisig_as(
+            setup.id,
+            account_id,
+            AccountMultisigTransaction::AccountMultisigSubmit(SubmitTransactionArgs::send(
+                account_id,
+                identity(5),
+                *MFX_SYMBOL,
+                10u16.into(),
+                Some(memo),
+            )),
+        ),
+        many_ledger::error::memo_too_large(21, 10),
+    );
+}
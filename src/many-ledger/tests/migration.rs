@@ -35,9 +35,10 @@ fn assert_metrics(harness: &Setup, expected_total: u32, expected_non_zero: u32)
                 &harness.id,
                 DataGetInfoArgs {
                     indices: VecOrSingle(vec![
-                        ACCOUNT_TOTAL_COUNT_INDEX,
-                        NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX
-                    ])
+                        ACCOUNT_TOTAL_COUNT_INDEX.clone(),
+                        NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX.clone()
+                    ]),
+                    height: None,
                 },
                 Context::new(RequestMessage::default(), unbounded().0)
             )
@@ -51,18 +52,19 @@ fn assert_metrics(harness: &Setup, expected_total: u32, expected_non_zero: u32)
             &harness.id,
             DataQueryArgs {
                 indices: VecOrSingle(vec![
-                    ACCOUNT_TOTAL_COUNT_INDEX,
-                    NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX,
+                    ACCOUNT_TOTAL_COUNT_INDEX.clone(),
+                    NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX.clone(),
                 ]),
+                height: None,
             },
             Context::new(RequestMessage::default(), unbounded().0),
         )
         .unwrap();
-    let total: BigInt = query[&ACCOUNT_TOTAL_COUNT_INDEX]
+    let total: BigInt = query[&*ACCOUNT_TOTAL_COUNT_INDEX]
         .clone()
         .try_into()
         .unwrap();
-    let non_zero: BigInt = query[&NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX]
+    let non_zero: BigInt = query[&*NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX]
         .clone()
         .try_into()
         .unwrap();
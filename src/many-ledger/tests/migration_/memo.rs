@@ -1,5 +1,6 @@
 use many_identity::testing::identity;
 use many_identity::Address;
+use many_ledger::migration::legacy_memo::LEGACY_MEMO_MIGRATION;
 use many_ledger::migration::memo::MEMO_MIGRATION;
 use many_ledger_test_utils::*;
 use many_modules::account::features::multisig;
@@ -150,7 +151,11 @@ fn memo_migration_works() {
     }
 
     // Setup starts with 2 accounts because of staging/ledger_state.json5
-    let mut harness = Setup::new_with_migrations(true, [(8, &MEMO_MIGRATION)], false);
+    let mut harness = Setup::new_with_migrations(
+        true,
+        [(8, &MEMO_MIGRATION), (12, &LEGACY_MEMO_MIGRATION)],
+        false,
+    );
     harness.set_balance(harness.id, 1_000_000, *MFX_SYMBOL);
     let (_, account_id) = harness.block(|h| {
         // Create an account.
@@ -260,4 +265,26 @@ fn memo_migration_works() {
         Some("Memo4"),
         Some("Data4"),
     );
+
+    // Legacy fields sent after the migration are ignored...
+    let (height, new_tx_id) = harness.block(|h| {
+        make_multisig_transaction(h, account_id, Some("Legacy5"), Some("Legacy5"), None, None)
+    });
+    assert!(height < 12);
+    check_info(&harness, &new_tx_id, None, None, None, None);
+
+    // ...until the Legacy Memo Migration bridges them into the new memo.
+    while harness.block(|_| {}).0 < 12 {}
+    let (_, new_tx_id) = harness.block(|h| {
+        make_multisig_transaction(h, account_id, Some("Legacy6"), Some("Legacy6"), None, None)
+    });
+
+    check_info(
+        &harness,
+        &new_tx_id,
+        None,
+        None,
+        Some("Legacy6"),
+        Some("Legacy6"),
+    );
 }
@@ -1 +1,3 @@
 mod memo;
+mod multisig_account_index;
+mod visual_logo;
@@ -0,0 +1,57 @@
+use many_identity::testing::identity;
+use many_identity::Address;
+use many_ledger::migration::multisig_account_index::MULTISIG_ACCOUNT_INDEX_MIGRATION;
+use many_ledger_test_utils::*;
+use many_modules::account::features::multisig::{
+    AccountMultisigModuleBackend, ListPendingArgs, WithdrawArgs,
+};
+use minicbor::bytes::ByteVec;
+
+fn list_pending(setup: &Setup, account: Address) -> Vec<ByteVec> {
+    setup
+        .module_impl
+        .multisig_list_pending(&identity(1), ListPendingArgs { account })
+        .unwrap()
+        .transactions
+        .into_iter()
+        .map(|tx| tx.token)
+        .collect()
+}
+
+#[test]
+/// Verify transactions submitted before the migration are indexed, and the
+/// index follows the transactions after it.
+fn multisig_account_index_migration() {
+    let mut setup =
+        Setup::new_with_migrations(true, [(3, &MULTISIG_ACCOUNT_INDEX_MIGRATION)], true);
+    let account_id = setup.create_account_(AccountType::Multisig);
+    let other_account_id = setup.create_account_(AccountType::Multisig);
+
+    let (h, token1) = setup.block(|setup| setup.multisig_send_(account_id, identity(3), 10u32));
+    assert!(h < 3);
+    let (h, other_token) =
+        setup.block(|setup| setup.multisig_send_(other_account_id, identity(3), 10u32));
+    assert!(h < 3);
+
+    let (h, token2) = setup.block(|setup| setup.multisig_send_(account_id, identity(3), 10u32));
+    assert!(h >= 3);
+    assert_eq!(
+        list_pending(&setup, account_id),
+        vec![token1.clone(), token2.clone()]
+    );
+    assert_eq!(list_pending(&setup, other_account_id), vec![other_token]);
+
+    let id = setup.id;
+    setup.block(|setup| {
+        setup
+            .module_impl
+            .multisig_withdraw(&id, WithdrawArgs { token: token1 })
+            .unwrap()
+    });
+    assert_eq!(list_pending(&setup, account_id), vec![token2]);
+
+    setup.inc_time(1_000_000);
+    setup.block(|_| {});
+    assert!(list_pending(&setup, account_id).is_empty());
+    assert!(list_pending(&setup, other_account_id).is_empty());
+}
@@ -0,0 +1,53 @@
+use many_identity::testing::identity;
+use many_ledger::error;
+use many_ledger::migration::token_create::TOKEN_CREATE_MIGRATION;
+use many_ledger::migration::tokens::TOKEN_MIGRATION;
+use many_ledger::migration::visual_logo::VISUAL_LOGO_MIGRATION;
+use many_ledger_test_utils::*;
+use many_modules::ledger::extended_info::visual_logo::{VisualTokenLogo, MAX_LOGOS};
+use many_modules::ledger::extended_info::TokenExtendedInfo;
+use many_modules::ledger::LedgerTokensModuleBackend;
+use many_types::ledger::TokenMaybeOwner;
+
+#[test]
+/// Verify logos are only checked against their limits once the migration is
+/// active.
+fn visual_logo_migration() {
+    let mut setup = Setup::new_with_migrations(
+        true,
+        [
+            (0, &TOKEN_MIGRATION),
+            (0, &TOKEN_CREATE_MIGRATION),
+            (3, &VISUAL_LOGO_MIGRATION),
+        ],
+        true,
+    );
+
+    let mut create = |ticker: &str| {
+        let mut logos = VisualTokenLogo::new();
+        for _ in 0..=MAX_LOGOS {
+            logos.unicode_front('∑');
+        }
+        let mut args = default_token_create_args(Some(TokenMaybeOwner::Left(identity(1))), None);
+        args.summary.ticker = ticker.to_string();
+        args.extended_info = Some(TokenExtendedInfo::new().with_visual_logo(logos).unwrap());
+
+        setup.block(|h| LedgerTokensModuleBackend::create(&mut h.module_impl, &identity(1), args))
+    };
+
+    let (height, result) = create("AAA");
+    assert!(height < 3);
+    assert!(result.is_ok());
+
+    let (height, result) = create("BBB");
+    assert!(height < 3);
+    assert!(result.is_ok());
+
+    let (height, result) = create("CCC");
+    assert_eq!(height, 3);
+    assert!(result.is_err());
+    assert_eq!(
+        result.unwrap_err().code(),
+        error::invalid_visual_logo("").code()
+    );
+}
@@ -3,7 +3,12 @@ use {
     many_error::ManyError,
     many_identity::testing::identity,
     many_identity::Address,
+    many_ledger::migration::multisig_expired_events::MULTISIG_EXPIRED_EVENTS_MIGRATION,
+    many_ledger::migration::multisig_results::MULTISIG_RESULTS_MIGRATION,
     many_ledger::module::LedgerModuleImpl,
+    many_ledger::storage::multisig_notifications::{
+        notification_endpoint_hash, MultisigNotification, MultisigNotifier,
+    },
     many_ledger_test_utils::*,
     many_modules::account::features::multisig::AccountMultisigModuleBackend,
     many_modules::account::features::{multisig, TryCreateFeature},
@@ -203,6 +208,7 @@ proptest! {
             &identity(2),
             multisig::ApproveArgs {
                 token: submit_return.clone().token,
+                approve_as: None,
             },
         );
         assert!(result.is_ok());
@@ -215,6 +221,7 @@ proptest! {
             &identity(3),
             multisig::ApproveArgs {
                 token: submit_return.clone().token,
+                approve_as: None,
             },
         );
         assert!(result.is_ok());
@@ -238,6 +245,7 @@ proptest! {
             &identity(6),
             multisig::ApproveArgs {
                 token: submit_return.clone().token,
+                approve_as: None,
             },
         );
         assert!(result.is_err());
@@ -262,6 +270,7 @@ proptest! {
                 &i,
                 multisig::ApproveArgs {
                     token: token.clone(),
+                    approve_as: None,
                 },
             );
             assert!(result.is_ok());
@@ -271,6 +280,7 @@ proptest! {
                 &i,
                 multisig::RevokeArgs {
                     token: token.clone(),
+                    revoke_as: None,
                 },
             );
             assert!(result.is_ok());
@@ -286,7 +296,7 @@ proptest! {
         let token = result.unwrap().token;
         assert!(get_approbation(&tx_info(&module_impl, id, &token), &id));
 
-        let result = module_impl.multisig_revoke(&identity(6), multisig::RevokeArgs { token });
+        let result = module_impl.multisig_revoke(&identity(6), multisig::RevokeArgs { token, revoke_as: None });
         assert!(result.is_err());
         assert_eq!(
             result.unwrap_err().code(),
@@ -318,6 +328,7 @@ proptest! {
                 &i,
                 account::features::multisig::ApproveArgs {
                     token: token.clone(),
+                    approve_as: None,
                 },
             );
             assert!(result.is_ok());
@@ -449,6 +460,59 @@ fn expires() {
     });
 }
 
+#[test]
+/// Verify pending transactions are listed, and expire with an event.
+fn list_pending_and_expire() {
+    let mut setup =
+        Setup::new_with_migrations(true, [(0, &MULTISIG_EXPIRED_EVENTS_MIGRATION)], true);
+    let account_id = setup.create_account_(AccountType::Multisig);
+    let owner_id = setup.id;
+
+    let (_, token) = setup.block(|setup| setup.multisig_send_(account_id, identity(3), 10u32));
+
+    let list_pending = |setup: &Setup| {
+        setup
+            .module_impl
+            .multisig_list_pending(
+                &identity(1),
+                multisig::ListPendingArgs {
+                    account: account_id,
+                },
+            )
+            .unwrap()
+            .transactions
+    };
+    let pending = list_pending(&setup);
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].token, token);
+    assert_eq!(pending[0].submitter, owner_id);
+    assert_eq!(pending[0].approvers, BTreeSet::from([owner_id]));
+
+    setup.inc_time(1_000_000);
+    setup.block(|_| {});
+    assert!(list_pending(&setup).is_empty());
+
+    let events = events::EventsModuleBackend::list(
+        &setup.module_impl,
+        events::ListArgs {
+            count: None,
+            order: None,
+            filter: Some(events::EventFilter {
+                kind: Some(vec![events::EventKind::AccountMultisigExpired].into()),
+                ..events::EventFilter::default()
+            }),
+        },
+    )
+    .unwrap()
+    .events;
+    assert_eq!(events.len(), 1);
+    assert!(matches!(
+        &events[0].content,
+        events::EventInfo::AccountMultisigExpired { account, token: t, .. }
+            if *account == account_id && *t == token
+    ));
+}
+
 /// Verifies that multiple transactions can be in flight and resolved separately.
 #[test]
 fn multiple_multisig() {
@@ -808,3 +872,380 @@ fn approve_executed_tx() {
     let result = setup.multisig_approve(identity(6), &token);
     assert_many_err(result, multisig::errors::transaction_expired_or_withdrawn());
 }
+
+#[test]
+/// Verify a member of a parent account can approve on behalf of it.
+fn approve_as() {
+    let mut setup = Setup::new(false);
+    let acc1 = setup.create_account_(AccountType::Multisig);
+    let parent = setup.create_account_as_(identity(5), AccountType::Ledger);
+    let other = setup.create_account_as_(identity(5), AccountType::Ledger);
+    setup.add_roles(
+        acc1,
+        BTreeMap::from([(parent, BTreeSet::from([account::Role::CanMultisigApprove]))]),
+    );
+
+    let token = setup.multisig_send_(acc1, identity(1234), 10u16);
+    let mut approve = |id: Address, approve_as: Address| {
+        setup.module_impl.multisig_approve(
+            &id,
+            multisig::ApproveArgs {
+                token: token.clone(),
+                approve_as: Some(approve_as),
+            },
+        )
+    };
+
+    // Only owners and approvers of the parent account can approve as it.
+    assert_many_err(
+        approve(identity(2), parent),
+        multisig::errors::user_cannot_approve_transaction(),
+    );
+    assert_many_err(
+        approve(identity(6), parent),
+        multisig::errors::user_cannot_approve_transaction(),
+    );
+    // The account needs to be a member of the multisig account.
+    assert_many_err(
+        approve(identity(5), other),
+        multisig::errors::user_cannot_approve_transaction(),
+    );
+    assert!(approve(identity(5), parent).is_ok());
+
+    setup.assert_multisig_info(&token, |i| {
+        assert!(get_approbation(&i, &parent));
+        assert!(!i.approvers.contains_key(&identity(5)));
+    });
+
+    let events = events::EventsModuleBackend::list(
+        &setup.module_impl,
+        events::ListArgs {
+            count: None,
+            order: None,
+            filter: None,
+        },
+    )
+    .unwrap()
+    .events;
+    assert!(events.iter().any(|e| matches!(
+        e.content,
+        events::EventInfo::AccountMultisigApprove {
+            approver,
+            approved_as: Some(approved_as),
+            ..
+        } if approver == identity(5) && approved_as == parent
+    )));
+}
+
+#[test]
+/// Verify a multisig member account only approves once its own threshold is
+/// reached, and stops approving when an approval on its behalf is revoked.
+fn approve_as_multisig_member() {
+    let mut setup = Setup::new(false);
+    let acc1 = setup.create_account_(AccountType::Multisig);
+    let member = setup.create_account_as_(identity(5), AccountType::Multisig);
+    setup.add_roles(
+        acc1,
+        BTreeMap::from([(member, BTreeSet::from([account::Role::CanMultisigApprove]))]),
+    );
+    setup
+        .module_impl
+        .multisig_set_defaults(
+            &identity(5),
+            multisig::SetDefaultsArgs {
+                account: member,
+                threshold: Some(2),
+                timeout_in_secs: None,
+                execute_automatically: None,
+            },
+        )
+        .unwrap();
+
+    let token = setup.multisig_send_(acc1, identity(1234), 10u16);
+    let approve = |setup: &mut Setup, id: Address| {
+        setup.module_impl.multisig_approve(
+            &id,
+            multisig::ApproveArgs {
+                token: token.clone(),
+                approve_as: Some(member),
+            },
+        )
+    };
+    let revoke = |setup: &mut Setup, id: Address| {
+        setup.module_impl.multisig_revoke(
+            &id,
+            multisig::RevokeArgs {
+                token: token.clone(),
+                revoke_as: Some(member),
+            },
+        )
+    };
+
+    assert!(approve(&mut setup, identity(5)).is_ok());
+    setup.assert_multisig_info(&token, |i| assert!(!get_approbation(&i, &member)));
+    // Approving twice does not count twice.
+    assert!(approve(&mut setup, identity(5)).is_ok());
+    setup.assert_multisig_info(&token, |i| assert!(!get_approbation(&i, &member)));
+
+    assert!(approve(&mut setup, identity(2)).is_ok());
+    setup.assert_multisig_info(&token, |i| assert!(get_approbation(&i, &member)));
+
+    // Only owners and approvers of the member account can revoke as it.
+    assert_many_err(
+        revoke(&mut setup, identity(6)),
+        multisig::errors::user_cannot_approve_transaction(),
+    );
+    assert!(revoke(&mut setup, identity(2)).is_ok());
+    setup.assert_multisig_info(&token, |i| assert!(!get_approbation(&i, &member)));
+}
+
+fn multisig_result(
+    setup: &Setup,
+    sender: Address,
+    token: &minicbor::bytes::ByteVec,
+) -> Result<multisig::ResultReturn, ManyError> {
+    setup.module_impl.multisig_result(
+        &sender,
+        multisig::ResultArgs {
+            token: token.clone(),
+        },
+    )
+}
+
+fn check_multisig_result(mut setup: Setup) {
+    let acc1 = setup.create_account_(AccountType::Multisig);
+    setup.set_balance(acc1, 1_000_000, *MFX_SYMBOL);
+
+    let token = setup.multisig_send_(acc1, identity(1234), 10u16);
+    assert_many_err(
+        multisig_result(&setup, identity(2), &token),
+        multisig::errors::transaction_not_executed(),
+    );
+
+    setup.multisig_approve_(setup.id, &token);
+    setup.multisig_approve_(identity(2), &token);
+    setup.multisig_approve_(identity(3), &token);
+    let response = setup.multisig_execute_(&token);
+    assert!(response.data.is_ok());
+
+    // Any member can see the response, not only the executer.
+    for member in [setup.id, identity(2), identity(3)] {
+        let result = multisig_result(&setup, member, &token).unwrap();
+        assert_eq!(
+            result.state,
+            multisig::MultisigTransactionState::ExecutedManually
+        );
+        assert_eq!(result.response, response);
+    }
+
+    assert_many_err(
+        multisig_result(&setup, identity(6), &token),
+        multisig::errors::user_cannot_approve_transaction(),
+    );
+}
+
+#[test]
+/// Verify members can retrieve the response of an executed transaction from the event log.
+fn multisig_result_from_events() {
+    check_multisig_result(Setup::new(false));
+}
+
+#[test]
+/// Verify members can retrieve the response of an executed transaction from storage.
+fn multisig_result_from_storage() {
+    check_multisig_result(Setup::new_with_migrations(
+        false,
+        [(0, &MULTISIG_RESULTS_MIGRATION)],
+        true,
+    ));
+}
+
+#[derive(Default)]
+struct RecordingNotifier {
+    endpoints: std::sync::Mutex<Vec<Vec<u8>>>,
+    notices: std::sync::Mutex<Vec<(Vec<u8>, MultisigNotification)>>,
+}
+
+impl MultisigNotifier for RecordingNotifier {
+    fn register(&self, endpoint_hash: &[u8], _endpoint: &multisig::NotificationEndpoint) {
+        self.endpoints.lock().unwrap().push(endpoint_hash.to_vec());
+    }
+
+    fn notify(&self, endpoint_hash: &[u8], notification: &MultisigNotification) {
+        self.notices
+            .lock()
+            .unwrap()
+            .push((endpoint_hash.to_vec(), notification.clone()));
+    }
+}
+
+#[test]
+/// Verify members are notified of submitted and expiring transactions.
+fn notifications() {
+    let mut setup = Setup::new(true);
+    let notifier = std::sync::Arc::new(RecordingNotifier::default());
+    setup.module_impl.set_multisig_notifier(notifier.clone());
+    let account_id = setup.create_account_(AccountType::Multisig);
+
+    let webhook = multisig::NotificationEndpoint::Webhook("https://example.com/2".to_string());
+    let email = multisig::NotificationEndpoint::Email("three@example.com".to_string());
+    let webhook_hash = notification_endpoint_hash(&webhook).unwrap();
+    let email_hash = notification_endpoint_hash(&email).unwrap();
+    setup.block(|setup| {
+        setup
+            .module_impl
+            .multisig_register_notification(
+                &identity(2),
+                multisig::RegisterNotificationArgs {
+                    account: account_id,
+                    endpoint_hash: webhook_hash.clone().into(),
+                    kinds: None,
+                },
+            )
+            .unwrap();
+        setup
+            .module_impl
+            .multisig_register_notification(
+                &identity(3),
+                multisig::RegisterNotificationArgs {
+                    account: account_id,
+                    endpoint_hash: email_hash.clone().into(),
+                    kinds: Some(BTreeSet::from([
+                        multisig::MultisigNotificationKind::ApproachingExpiry,
+                    ])),
+                },
+            )
+            .unwrap();
+
+        // Not a member of the account.
+        assert!(setup
+            .module_impl
+            .multisig_register_notification(
+                &identity(6),
+                multisig::RegisterNotificationArgs {
+                    account: account_id,
+                    endpoint_hash: webhook_hash.clone().into(),
+                    kinds: None,
+                },
+            )
+            .is_err());
+
+        // Not a hash.
+        assert_many_err(
+            setup.module_impl.multisig_register_notification(
+                &identity(2),
+                multisig::RegisterNotificationArgs {
+                    account: account_id,
+                    endpoint_hash: vec![1, 2, 3].into(),
+                    kinds: None,
+                },
+            ),
+            multisig::errors::invalid_notification_endpoint("invalid endpoint hash"),
+        );
+    });
+
+    // The endpoints are given to the node outside of the transactions, and must
+    // match the registered hashes.
+    assert_many_err(
+        setup.module_impl.multisig_provide_notification_endpoint(
+            &identity(2),
+            multisig::ProvideNotificationEndpointArgs {
+                account: account_id,
+                endpoint: email.clone(),
+            },
+        ),
+        multisig::errors::notification_endpoint_mismatch(),
+    );
+    assert_many_err(
+        setup.module_impl.multisig_provide_notification_endpoint(
+            &identity(4),
+            multisig::ProvideNotificationEndpointArgs {
+                account: account_id,
+                endpoint: webhook.clone(),
+            },
+        ),
+        multisig::errors::notification_not_registered(),
+    );
+    for (id, endpoint) in [(2, &webhook), (3, &email)] {
+        setup
+            .module_impl
+            .multisig_provide_notification_endpoint(
+                &identity(id),
+                multisig::ProvideNotificationEndpointArgs {
+                    account: account_id,
+                    endpoint: endpoint.clone(),
+                },
+            )
+            .unwrap();
+    }
+    assert_eq!(
+        *notifier.endpoints.lock().unwrap(),
+        vec![webhook_hash.clone(), email_hash.clone()]
+    );
+
+    let registrations = setup
+        .module_impl
+        .multisig_list_notifications(
+            &identity(6),
+            multisig::ListNotificationsArgs {
+                account: account_id,
+            },
+        )
+        .unwrap()
+        .registrations;
+    assert_eq!(registrations.len(), 2);
+    assert_eq!(
+        registrations[&identity(2)].endpoint_hash.as_slice(),
+        webhook_hash
+    );
+
+    let (_, token) = setup.block(|setup| setup.multisig_send_(account_id, identity(3), 10u32));
+    {
+        let notices = notifier.notices.lock().unwrap();
+        assert_eq!(notices.len(), 1);
+        assert_eq!(notices[0].0, webhook_hash);
+        assert_eq!(
+            notices[0].1.kind,
+            multisig::MultisigNotificationKind::Submitted
+        );
+        assert_eq!(notices[0].1.token, token);
+    }
+
+    // Move close to the expiry. Both members are notified, only once.
+    setup.inc_time(many_ledger::storage::multisig::MULTISIG_DEFAULT_TIMEOUT_IN_SECS - 600);
+    setup.block(|_| {});
+    setup.block(|_| {});
+    {
+        let notices = notifier.notices.lock().unwrap();
+        assert_eq!(notices.len(), 3);
+        let hashes = notices[1..]
+            .iter()
+            .map(|(h, _)| h.clone())
+            .collect::<BTreeSet<_>>();
+        assert_eq!(hashes, BTreeSet::from([webhook_hash, email_hash]));
+        assert!(notices[1..].iter().all(|(_, n)| n.kind
+            == multisig::MultisigNotificationKind::ApproachingExpiry
+            && n.account == account_id));
+    }
+
+    setup.block(|setup| {
+        setup
+            .module_impl
+            .multisig_unregister_notification(
+                &identity(2),
+                multisig::UnregisterNotificationArgs {
+                    account: account_id,
+                },
+            )
+            .unwrap();
+        assert_many_err(
+            setup.module_impl.multisig_unregister_notification(
+                &identity(2),
+                multisig::UnregisterNotificationArgs {
+                    account: account_id,
+                },
+            ),
+            multisig::errors::notification_not_registered(),
+        );
+    });
+}
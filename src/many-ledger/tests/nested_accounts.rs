@@ -0,0 +1,213 @@
+use {
+    many_identity::testing::identity,
+    many_identity::Address,
+    many_ledger::migration::nested_accounts::NESTED_ACCOUNTS_MIGRATION,
+    many_ledger::storage::account::ACCOUNT_MAXIMUM_NESTING_DEPTH,
+    many_ledger_test_utils::*,
+    many_modules::account::features::multisig,
+    many_modules::account::features::multisig::AccountMultisigModuleBackend,
+    many_modules::{account, events, ledger},
+    std::collections::{BTreeMap, BTreeSet},
+};
+
+fn setup_nested() -> Setup {
+    Setup::new_with_migrations(false, [(0, &NESTED_ACCOUNTS_MIGRATION)], true)
+}
+
+/// Make `member` a member of `account_id` with the given role.
+fn add_member(
+    setup: &mut Setup,
+    owner: Address,
+    account_id: Address,
+    member: Address,
+    role: account::Role,
+) {
+    setup.add_roles_as(
+        owner,
+        account_id,
+        BTreeMap::from([(member, BTreeSet::from([role]))]),
+    );
+}
+
+#[test]
+/// Verify owners and members with the same role in a member account can send.
+fn send_through_member_account() {
+    let mut setup = setup_nested();
+    let parent = setup.create_account_(AccountType::Ledger);
+    let member = setup.create_account_as_(identity(5), AccountType::Ledger);
+    let id = setup.id;
+    add_member(
+        &mut setup,
+        id,
+        parent,
+        member,
+        account::Role::CanLedgerTransact,
+    );
+    add_member(
+        &mut setup,
+        identity(5),
+        member,
+        identity(7),
+        account::Role::CanLedgerTransact,
+    );
+    setup.set_balance(parent, 1_000_000, *MFX_SYMBOL);
+
+    // Owner of the member account.
+    assert!(setup
+        .send_as(identity(5), parent, identity(1234), 10u16, *MFX_SYMBOL)
+        .is_ok());
+    // Has the same role in the member account.
+    assert!(setup
+        .send_as(identity(7), parent, identity(1234), 10u16, *MFX_SYMBOL)
+        .is_ok());
+    assert_eq!(setup.balance_(identity(1234)), 20u16);
+
+    assert_many_err(
+        setup.send_as(identity(6), parent, identity(1234), 10u16, *MFX_SYMBOL),
+        account::errors::user_needs_role(account::Role::CanLedgerTransact),
+    );
+}
+
+#[test]
+/// Verify roles are not inherited unless the migration is active.
+fn send_through_member_account_without_migration() {
+    let mut setup = Setup::new(false);
+    let parent = setup.create_account_(AccountType::Ledger);
+    let member = setup.create_account_as_(identity(5), AccountType::Ledger);
+    let id = setup.id;
+    add_member(
+        &mut setup,
+        id,
+        parent,
+        member,
+        account::Role::CanLedgerTransact,
+    );
+    setup.set_balance(parent, 1_000_000, *MFX_SYMBOL);
+
+    assert_many_err(
+        setup.send_as(identity(5), parent, identity(1234), 10u16, *MFX_SYMBOL),
+        account::errors::user_needs_role(account::Role::CanLedgerTransact),
+    );
+}
+
+#[test]
+/// Verify roles are only resolved through a limited number of accounts.
+fn depth_limit() {
+    let mut setup = setup_nested();
+    let root = setup.create_account_(AccountType::Ledger);
+    setup.set_balance(root, 1_000_000, *MFX_SYMBOL);
+
+    // Build a chain of accounts, each owned by the next one's owner.
+    let mut chain = vec![root];
+    for i in 0..=ACCOUNT_MAXIMUM_NESTING_DEPTH {
+        let owner = identity(100 + i as u32);
+        let account_id = setup.create_account_as_(owner, AccountType::Ledger);
+        let previous_owner = if i == 0 {
+            setup.id
+        } else {
+            identity(99 + i as u32)
+        };
+        add_member(
+            &mut setup,
+            previous_owner,
+            *chain.last().unwrap(),
+            account_id,
+            account::Role::CanLedgerTransact,
+        );
+        chain.push(account_id);
+    }
+
+    // The owner of the last account allowed.
+    let owner = identity(99 + ACCOUNT_MAXIMUM_NESTING_DEPTH as u32);
+    assert!(setup
+        .send_as(owner, root, identity(1234), 10u16, *MFX_SYMBOL)
+        .is_ok());
+
+    // One account too deep.
+    let owner = identity(100 + ACCOUNT_MAXIMUM_NESTING_DEPTH as u32);
+    assert_many_err(
+        setup.send_as(owner, root, identity(1234), 10u16, *MFX_SYMBOL),
+        account::errors::user_needs_role(account::Role::CanLedgerTransact),
+    );
+}
+
+#[test]
+/// Verify accounts that are members of each other are resolved.
+fn cycles() {
+    let mut setup = setup_nested();
+    let a = setup.create_account_(AccountType::Ledger);
+    let b = setup.create_account_as_(identity(5), AccountType::Ledger);
+    let id = setup.id;
+    add_member(&mut setup, id, a, b, account::Role::CanLedgerTransact);
+    add_member(
+        &mut setup,
+        identity(5),
+        b,
+        a,
+        account::Role::CanLedgerTransact,
+    );
+    setup.set_balance(a, 1_000_000, *MFX_SYMBOL);
+    setup.set_balance(b, 1_000_000, *MFX_SYMBOL);
+
+    assert!(setup
+        .send_as(identity(5), a, identity(1234), 10u16, *MFX_SYMBOL)
+        .is_ok());
+    assert!(setup
+        .send_as(id, b, identity(1234), 10u16, *MFX_SYMBOL)
+        .is_ok());
+    assert_many_err(
+        setup.send_as(identity(6), a, identity(1234), 10u16, *MFX_SYMBOL),
+        account::errors::user_needs_role(account::Role::CanLedgerTransact),
+    );
+}
+
+#[test]
+/// Verify multisig transactions can be submitted and approved through member
+/// accounts, and that approvals are counted per member account.
+fn multisig_through_member_account() {
+    let mut setup = setup_nested();
+    let account_id = setup.create_account_(AccountType::Multisig);
+    let member = setup.create_account_as_(identity(5), AccountType::Ledger);
+    let id = setup.id;
+    add_member(
+        &mut setup,
+        id,
+        account_id,
+        member,
+        account::Role::CanMultisigSubmit,
+    );
+    setup.set_balance(account_id, 1_000_000, *MFX_SYMBOL);
+
+    let token = setup
+        .create_multisig_as(
+            identity(5),
+            account_id,
+            events::AccountMultisigTransaction::Send(ledger::SendArgs {
+                from: Some(account_id),
+                to: identity(1234),
+                symbol: *MFX_SYMBOL,
+                amount: 10u16.into(),
+                memo: None,
+            }),
+        )
+        .unwrap();
+
+    // Not a member of the multisig account itself.
+    assert_many_err(
+        setup.multisig_approve(identity(5), &token),
+        multisig::errors::user_cannot_approve_transaction(),
+    );
+    setup
+        .module_impl
+        .multisig_approve(
+            &identity(5),
+            multisig::ApproveArgs {
+                token: token.clone(),
+                approve_as: Some(member),
+            },
+        )
+        .unwrap();
+    setup.assert_multisig_info(&token, |info| {
+        assert!(info.approvers[&member].approved);
+    });
+}
@@ -0,0 +1,108 @@
+use async_channel::unbounded;
+use many_identity::testing::identity;
+use many_identity::Address;
+use many_ledger_test_utils::*;
+use many_ledger_verify::proof::verify_balances;
+use many_modules::abci_backend::ManyAbciModuleBackend;
+use many_modules::ledger::{BalanceArgs, BalanceReturns, LedgerModuleBackend};
+use many_protocol::context::{Context, ProofResult};
+use many_protocol::RequestMessage;
+use many_types::ledger::Symbol;
+use many_types::{ProofOperation, PROOF};
+
+/// Request the balances of `symbols` with a proof, and return the balances
+/// with the proof operations.
+fn balance_with_proof(
+    setup: &Setup,
+    account: Address,
+    symbols: Vec<Symbol>,
+) -> (BalanceReturns, Vec<ProofOperation>) {
+    let (transmitter, receiver) = unbounded();
+    let request = RequestMessage::default()
+        .with_from(Address::anonymous())
+        .with_attribute(PROOF);
+    let balances = setup
+        .module_impl
+        .balance(
+            &Address::anonymous(),
+            BalanceArgs {
+                account: Some(account),
+                symbols: Some(symbols.into()),
+            },
+            Context::new(request, transmitter),
+        )
+        .unwrap();
+    match receiver.try_recv() {
+        Ok(ProofResult::Proof(operations)) => (balances, operations),
+        _ => panic!("No proof was sent."),
+    }
+}
+
+#[test]
+fn balances() {
+    let mut setup = Setup::new(false);
+    setup.set_balance(identity(1), 1_000, *MFX_SYMBOL);
+    let root = ManyAbciModuleBackend::info(&setup.module_impl)
+        .unwrap()
+        .hash
+        .to_vec();
+    let other_symbol = identity(100);
+    let symbols = vec![*MFX_SYMBOL, other_symbol];
+
+    let (returns, operations) = balance_with_proof(&setup, identity(1), symbols.clone());
+    assert_eq!(returns.balances.len(), 1);
+    verify_balances(
+        operations.clone(),
+        &root,
+        &identity(1),
+        symbols.clone(),
+        &returns.balances,
+    )
+    .unwrap();
+
+    // A hidden balance is not proven absent.
+    assert!(verify_balances(
+        operations.clone(),
+        &root,
+        &identity(1),
+        symbols.clone(),
+        &Default::default(),
+    )
+    .is_err());
+
+    // A balance which does not exist is not proven.
+    let mut balances = returns.balances.clone();
+    balances.insert(other_symbol, 1u64.into());
+    assert!(verify_balances(
+        operations.clone(),
+        &root,
+        &identity(1),
+        symbols.clone(),
+        &balances
+    )
+    .is_err());
+
+    // The proof does not hold for another root.
+    assert!(verify_balances(
+        operations,
+        &[0; 32],
+        &identity(1),
+        symbols,
+        &returns.balances
+    )
+    .is_err());
+}
+
+#[test]
+fn absent_balances() {
+    let setup = Setup::new(false);
+    let root = ManyAbciModuleBackend::info(&setup.module_impl)
+        .unwrap()
+        .hash
+        .to_vec();
+    let symbols = vec![*MFX_SYMBOL];
+
+    let (returns, operations) = balance_with_proof(&setup, identity(1), symbols.clone());
+    assert!(returns.balances.is_empty());
+    verify_balances(operations, &root, &identity(1), symbols, &returns.balances).unwrap();
+}
@@ -0,0 +1,23 @@
+//! The ledger state read by multi-queries.
+use many_identity::testing::identity;
+use many_ledger::module::query_state::LedgerQueryState;
+use many_ledger_test_utils::*;
+use many_server::server::QueryStateCoordinator;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn query_state() {
+    let mut harness = Setup::new(true);
+    harness.set_balance(harness.id, 1_000_000, *MFX_SYMBOL);
+    let before = harness.module_impl.query_state().unwrap();
+    harness.block(|h| h.send_(h.id, identity(1), 1u32));
+    let after = harness.module_impl.query_state().unwrap();
+    assert_eq!(after.height, before.height + 1);
+    assert_ne!(after.hash, before.hash);
+
+    let coordinator = LedgerQueryState::new(Arc::new(Mutex::new(harness.module_impl))).unwrap();
+    assert!(coordinator.is_query("ledger.balance"));
+    assert!(!coordinator.is_query("ledger.send"));
+    assert!(!coordinator.is_query("base.multiQuery"));
+    assert_eq!(coordinator.state().unwrap(), after);
+}
@@ -0,0 +1,103 @@
+use {
+    coset::{CoseSign1, TaggedCborSerializable},
+    many_identity::testing::identity,
+    many_identity::{Identity, Verifier},
+    many_identity_dsa::ed25519::generate_random_ed25519_identity,
+    many_identity_dsa::CoseKeyVerifier,
+    many_ledger_test_utils::*,
+    many_modules::events::{EventId, EventsModuleBackend, ListArgs},
+    many_modules::ledger::{event_not_found, GetReceiptArgs, LedgerReceiptsModuleBackend, Receipt},
+    many_types::{ProofOperation, SortOrder},
+    sha3::{Digest, Sha3_256},
+    std::sync::Arc,
+};
+
+fn last_event_id(setup: &Setup) -> EventId {
+    setup
+        .module_impl
+        .list(ListArgs {
+            count: Some(1),
+            order: Some(SortOrder::Descending),
+            filter: None,
+        })
+        .unwrap()
+        .events
+        .remove(0)
+        .id
+}
+
+#[test]
+fn receipt_proves_event() {
+    let mut setup = Setup::new(false);
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+    setup.send_(id, identity(5), 10u32);
+    let event_id = last_event_id(&setup);
+
+    let returns = setup
+        .module_impl
+        .get_receipt(
+            &identity(1),
+            GetReceiptArgs {
+                id: event_id.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(returns.receipt.id, event_id);
+    assert!(returns.envelope.is_none());
+
+    // The proof contains the event, which hashes to the receipt's hash.
+    let event = returns
+        .receipt
+        .proof
+        .operations
+        .iter()
+        .find_map(|op| match op {
+            ProofOperation::KeyValuePair(key, value) => {
+                let key: Vec<u8> = key.clone().into();
+                key.ends_with(event_id.as_ref()).then(|| value.clone())
+            }
+            _ => None,
+        })
+        .expect("The proof does not contain the event.");
+    let event: Vec<u8> = event.into();
+    assert_eq!(
+        returns.receipt.event_hash.as_slice(),
+        Sha3_256::digest(event).as_slice()
+    );
+}
+
+#[test]
+fn signed_receipt() {
+    let mut setup = Setup::new(false);
+    let signer = generate_random_ed25519_identity();
+    let address = signer.address();
+    setup.module_impl.set_receipt_signer(Arc::new(signer));
+
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+    setup.send_(id, identity(5), 10u32);
+    let event_id = last_event_id(&setup);
+
+    let returns = setup
+        .module_impl
+        .get_receipt(&identity(1), GetReceiptArgs { id: event_id })
+        .unwrap();
+    let envelope = CoseSign1::from_tagged_slice(&returns.envelope.unwrap()).unwrap();
+    assert_eq!(CoseKeyVerifier.verify_1(&envelope).unwrap(), address);
+
+    let receipt: Receipt = minicbor::decode(&envelope.payload.unwrap()).unwrap();
+    assert_eq!(receipt, returns.receipt);
+}
+
+#[test]
+fn unknown_event() {
+    let setup = Setup::new(false);
+    let id = EventId::from(u64::MAX);
+    assert_many_err(
+        setup
+            .module_impl
+            .get_receipt(&identity(1), GetReceiptArgs { id: id.clone() }),
+        event_not_found(hex::encode(&id)),
+    );
+}
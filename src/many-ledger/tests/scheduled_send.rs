@@ -0,0 +1,269 @@
+use {
+    many_identity::testing::identity,
+    many_identity::Address,
+    many_ledger::migration::labels::{DEPOSIT_AMOUNT_KEY, DEPOSIT_SYMBOL_KEY},
+    many_ledger::migration::scheduled_send::SCHEDULED_SEND_MIGRATION,
+    many_ledger::storage::scheduled_send::MAX_SCHEDULED_SENDS_PER_BLOCK,
+    many_ledger_test_utils::*,
+    many_modules::events::{EventFilter, EventInfo, EventKind, EventsModuleBackend, ListArgs},
+    many_modules::ledger::{
+        schedule_in_the_past, scheduled_send_not_found, CancelScheduledArgs,
+        LedgerScheduleModuleBackend, ScheduleSendArgs, ScheduleTrigger, ScheduledArgs,
+    },
+    many_types::{Duration, Timestamp},
+};
+
+fn setup() -> Setup {
+    let mut setup = Setup::new_with_migrations(
+        true,
+        [MigrationHarness::from((0, &SCHEDULED_SEND_MIGRATION))
+            .with_extra(
+                DEPOSIT_SYMBOL_KEY,
+                serde_json::json!(MFX_SYMBOL.to_string()),
+            )
+            .with_extra(DEPOSIT_AMOUNT_KEY, serde_json::json!(10))],
+        true,
+    );
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+    setup
+}
+
+fn schedule_args(to: Address, amount: u32, at: ScheduleTrigger) -> ScheduleSendArgs {
+    ScheduleSendArgs {
+        from: None,
+        to,
+        amount: amount.into(),
+        symbol: *MFX_SYMBOL,
+        at,
+        memo: None,
+    }
+}
+
+fn schedule(setup: &mut Setup, to: Address, at: ScheduleTrigger) -> u64 {
+    setup
+        .module_impl
+        .schedule_send(&setup.id, schedule_args(to, 100, at))
+        .unwrap()
+        .id
+}
+
+fn events(setup: &Setup, kind: EventKind) -> Vec<EventInfo> {
+    setup
+        .module_impl
+        .list(ListArgs {
+            count: None,
+            order: None,
+            filter: Some(EventFilter {
+                kind: Some(vec![kind].into()),
+                ..Default::default()
+            }),
+        })
+        .unwrap()
+        .events
+        .into_iter()
+        .map(|e| e.content)
+        .collect()
+}
+
+#[test]
+fn executes_at_height() {
+    let mut setup = setup();
+    let (height, _) = setup.block(|_| {});
+    let (_, id) = setup.block(|h| schedule(h, identity(5), ScheduleTrigger::Height(height + 3)));
+
+    setup.block(|_| {});
+    assert_eq!(setup.balance_(identity(5)), 0u32);
+
+    setup.block(|_| {});
+    assert_eq!(setup.balance_(identity(5)), 100u32);
+    assert_eq!(setup.balance_(setup.id), 900u32);
+
+    let scheduled = setup
+        .module_impl
+        .scheduled(&setup.id, ScheduledArgs::default())
+        .unwrap();
+    assert!(!scheduled.sends.contains_key(&id));
+    assert!(matches!(
+        events(&setup, EventKind::ScheduledSendExecute).as_slice(),
+        [EventInfo::ScheduledSendExecute { error: None, .. }]
+    ));
+}
+
+#[test]
+fn executes_at_time() {
+    let mut setup = setup();
+    // Blocks start at 1_000_001 seconds and advance one second each.
+    setup.block(|h| {
+        schedule(
+            h,
+            identity(5),
+            ScheduleTrigger::Time(Timestamp::new(1_000_003).unwrap()),
+        )
+    });
+
+    setup.block(|_| {});
+    assert_eq!(setup.balance_(identity(5)), 0u32);
+
+    setup.block(|_| {});
+    assert_eq!(setup.balance_(identity(5)), 100u32);
+}
+
+#[test]
+fn executes_after_duration() {
+    let mut setup = setup();
+    setup.block(|h| {
+        schedule(
+            h,
+            identity(5),
+            ScheduleTrigger::After(Duration::from_secs(2).unwrap()),
+        )
+    });
+    assert_eq!(setup.balance_(identity(5)), 0u32);
+
+    for _ in 0..3 {
+        setup.block(|_| {});
+    }
+    assert_eq!(setup.balance_(identity(5)), 100u32);
+}
+
+#[test]
+fn rejects_past_trigger() {
+    let mut setup = setup();
+    setup.block(|h| {
+        assert_many_err(
+            h.module_impl.schedule_send(
+                &h.id,
+                schedule_args(
+                    identity(5),
+                    100,
+                    ScheduleTrigger::Time(Timestamp::new(1_000).unwrap()),
+                ),
+            ),
+            schedule_in_the_past(),
+        );
+    });
+}
+
+#[test]
+fn cancel() {
+    let mut setup = setup();
+    let (_, id) = setup.block(|h| schedule(h, identity(5), ScheduleTrigger::Height(1_000)));
+
+    setup.block(|h| {
+        assert_many_err(
+            h.module_impl
+                .cancel_scheduled(&identity(6), CancelScheduledArgs { id, memo: None }),
+            many_ledger::error::unauthorized(),
+        );
+        let sender = h.id;
+        assert!(h
+            .module_impl
+            .cancel_scheduled(&sender, CancelScheduledArgs { id, memo: None })
+            .is_ok());
+        assert_many_err(
+            h.module_impl
+                .cancel_scheduled(&sender, CancelScheduledArgs { id, memo: None }),
+            scheduled_send_not_found(id),
+        );
+    });
+
+    assert_eq!(events(&setup, EventKind::ScheduledSendCancel).len(), 1);
+    assert!(setup
+        .module_impl
+        .scheduled(&setup.id, ScheduledArgs::default())
+        .unwrap()
+        .sends
+        .is_empty());
+}
+
+#[test]
+fn failure_is_logged() {
+    let mut setup = setup();
+    let (height, _) = setup.block(|h| h.send_(h.id, identity(6), 950u32));
+    setup.block(|h| schedule(h, identity(5), ScheduleTrigger::Height(height + 2)));
+    setup.block(|_| {});
+
+    assert_eq!(setup.balance_(identity(5)), 0u32);
+    assert_eq!(
+        events(&setup, EventKind::ScheduledSendExecute),
+        vec![EventInfo::ScheduledSendExecute {
+            id: 1,
+            from: setup.id,
+            to: identity(5),
+            symbol: *MFX_SYMBOL,
+            amount: 100u16.into(),
+            error: Some(many_ledger::error::insufficient_funds()),
+        }]
+    );
+    assert!(setup
+        .module_impl
+        .scheduled(&setup.id, ScheduledArgs::default())
+        .unwrap()
+        .sends
+        .is_empty());
+}
+
+#[test]
+fn deposit() {
+    let mut setup = setup();
+    let (_, id) = setup.block(|h| schedule(h, identity(5), ScheduleTrigger::Height(1_000)));
+    assert_eq!(setup.balance_(setup.id), 990u32);
+
+    setup.block(|h| {
+        let sender = h.id;
+        h.module_impl
+            .cancel_scheduled(&sender, CancelScheduledArgs { id, memo: None })
+            .unwrap();
+    });
+    assert_eq!(setup.balance_(setup.id), 1_000u32);
+
+    setup.block(|h| {
+        assert_many_err(
+            h.module_impl.schedule_send(
+                &identity(6),
+                schedule_args(identity(5), 100, ScheduleTrigger::Height(1_000)),
+            ),
+            many_ledger::error::insufficient_funds(),
+        );
+    });
+}
+
+#[test]
+fn limit_per_block() {
+    let mut setup = setup();
+    let id = setup.id;
+    setup.set_balance(id, 1_000_000, *MFX_SYMBOL);
+    let (height, _) = setup.block(|_| {});
+    setup.block(|h| {
+        for _ in 0..=MAX_SCHEDULED_SENDS_PER_BLOCK {
+            h.module_impl
+                .schedule_send(
+                    &id,
+                    schedule_args(identity(5), 1, ScheduleTrigger::Height(height + 2)),
+                )
+                .unwrap();
+        }
+    });
+
+    setup.block(|_| {});
+    assert_eq!(
+        setup.balance_(identity(5)),
+        MAX_SCHEDULED_SENDS_PER_BLOCK as u32
+    );
+    let remaining = setup
+        .module_impl
+        .scheduled(&id, ScheduledArgs::default())
+        .unwrap()
+        .sends;
+    assert_eq!(
+        remaining.keys().copied().collect::<Vec<_>>(),
+        vec![MAX_SCHEDULED_SENDS_PER_BLOCK as u64 + 1]
+    );
+
+    setup.block(|_| {});
+    assert_eq!(
+        setup.balance_(identity(5)),
+        MAX_SCHEDULED_SENDS_PER_BLOCK as u32 + 1
+    );
+}
@@ -0,0 +1,173 @@
+#![cfg(feature = "scripting")]
+
+use {
+    many_identity::testing::identity,
+    many_ledger::migration::scripts::SCRIPTS_MIGRATION,
+    many_ledger_test_utils::*,
+    many_modules::scripts::{
+        out_of_fuel, script_not_found, ScriptDeployArgs, ScriptInfoArgs, ScriptInvokeArgs,
+        ScriptsModuleBackend,
+    },
+};
+
+/// A module exporting a `run` function which does nothing.
+const NOOP_SCRIPT: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // Magic and version
+    0x01, 0x04, 0x01, 0x60, 0x00, 0x00, // Type: () -> ()
+    0x03, 0x02, 0x01, 0x00, // Function 0 has type 0
+    0x07, 0x07, 0x01, 0x03, b'r', b'u', b'n', 0x00, 0x00, // Export function 0 as `run`
+    0x0a, 0x04, 0x01, 0x02, 0x00, 0x0b, // Code: end
+];
+
+/// A module exporting a `run` function which loops forever.
+const LOOP_SCRIPT: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // Magic and version
+    0x01, 0x04, 0x01, 0x60, 0x00, 0x00, // Type: () -> ()
+    0x03, 0x02, 0x01, 0x00, // Function 0 has type 0
+    0x07, 0x07, 0x01, 0x03, b'r', b'u', b'n', 0x00, 0x00, // Export function 0 as `run`
+    0x0a, 0x09, 0x01, 0x07, 0x00, 0x03, 0x40, 0x0c, 0x00, 0x0b,
+    0x0b, // Code: loop br 0 end end
+];
+
+/// A module exporting a page of memory and a `run` function which sets its output
+/// to `len` bytes at address 0. `len` is a padded LEB128 `i32`.
+fn output_script(len: [u8; 5]) -> Vec<u8> {
+    [
+        &[
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // Magic and version
+            0x01, 0x09, 0x02, 0x60, 0x02, 0x7f, 0x7f, 0x00, 0x60, 0x00,
+            0x00, // Types: (i32, i32) -> (), () -> ()
+            0x02, 0x0e, 0x01, 0x03, b'e', b'n', b'v', 0x06, b'o', b'u', b't', b'p', b'u', b't',
+            0x00, 0x00, // Import `env.output` as function 0
+            0x03, 0x02, 0x01, 0x01, // Function 1 has type 1
+            0x05, 0x03, 0x01, 0x00, 0x01, // One page of memory
+            0x07, 0x10, 0x02, 0x03, b'r', b'u', b'n', 0x00, 0x01, 0x06, b'm', b'e', b'm', b'o',
+            b'r', b'y', 0x02, 0x00, // Export function 1 as `run` and the memory
+            0x0a, 0x0e, 0x01, 0x0c, 0x00, 0x41, 0x00, 0x41, // Code: i32.const 0, i32.const
+        ][..],
+        &len[..],
+        &[0x10, 0x00, 0x0b][..], // call 0, end
+    ]
+    .concat()
+}
+
+fn deploy(setup: &mut Setup, code: &[u8]) -> u64 {
+    setup
+        .module_impl
+        .deploy(
+            &identity(1),
+            ScriptDeployArgs {
+                code: code.to_vec().into(),
+                memo: None,
+            },
+        )
+        .unwrap()
+        .id
+}
+
+fn invoke_args(id: u64, fuel: Option<u64>) -> ScriptInvokeArgs {
+    ScriptInvokeArgs {
+        id,
+        function: "run".to_string(),
+        input: None,
+        fuel,
+    }
+}
+
+#[test]
+fn deploy_and_invoke() {
+    let mut setup = Setup::new_with_migrations(false, [(0, &SCRIPTS_MIGRATION)], true);
+    let id = deploy(&mut setup, NOOP_SCRIPT);
+
+    let script = setup
+        .module_impl
+        .info(&identity(2), ScriptInfoArgs { id })
+        .unwrap()
+        .script;
+    assert_eq!(script.owner, identity(1));
+    assert_eq!(script.size, NOOP_SCRIPT.len() as u64);
+
+    let returns = setup
+        .module_impl
+        .invoke(&identity(2), invoke_args(id, None))
+        .unwrap();
+    assert!(returns.output.is_empty());
+
+    assert_many_err(
+        setup
+            .module_impl
+            .invoke(&identity(2), invoke_args(id + 1, None)),
+        script_not_found(id + 1),
+    );
+}
+
+#[test]
+fn out_of_fuel_invocation() {
+    let mut setup = Setup::new_with_migrations(false, [(0, &SCRIPTS_MIGRATION)], true);
+    let id = deploy(&mut setup, LOOP_SCRIPT);
+    assert_many_err(
+        setup
+            .module_impl
+            .invoke(&identity(2), invoke_args(id, Some(1_000))),
+        out_of_fuel(1_000),
+    );
+}
+
+#[test]
+fn host_memory_access() {
+    let mut setup = Setup::new_with_migrations(false, [(0, &SCRIPTS_MIGRATION)], true);
+
+    // 16 bytes.
+    let id = deploy(&mut setup, &output_script([0x90, 0x80, 0x80, 0x80, 0x00]));
+    let returns = setup
+        .module_impl
+        .invoke(&identity(2), invoke_args(id, None))
+        .unwrap();
+    assert_eq!(returns.output.len(), 16);
+
+    // 60000 bytes, more than the fuel allows copying.
+    let id = deploy(&mut setup, &output_script([0xe0, 0xd4, 0x83, 0x80, 0x00]));
+    assert_many_err(
+        setup
+            .module_impl
+            .invoke(&identity(2), invoke_args(id, Some(1_000))),
+        out_of_fuel(1_000),
+    );
+
+    // 2 GiB, out of the bounds of the memory. Nothing is allocated.
+    let id = deploy(&mut setup, &output_script([0xff, 0xff, 0xff, 0xff, 0x07]));
+    assert!(setup
+        .module_impl
+        .invoke(&identity(2), invoke_args(id, None))
+        .is_err());
+}
+
+#[test]
+fn invalid_script() {
+    let mut setup = Setup::new_with_migrations(false, [(0, &SCRIPTS_MIGRATION)], true);
+    assert!(setup
+        .module_impl
+        .deploy(
+            &identity(1),
+            ScriptDeployArgs {
+                code: vec![1, 2, 3].into(),
+                memo: None,
+            },
+        )
+        .is_err());
+}
+
+#[test]
+fn disabled_without_migration() {
+    let mut setup = Setup::new(false);
+    assert!(setup
+        .module_impl
+        .deploy(
+            &identity(1),
+            ScriptDeployArgs {
+                code: NOOP_SCRIPT.to_vec().into(),
+                memo: None,
+            },
+        )
+        .is_err());
+}
@@ -0,0 +1,115 @@
+use {
+    many_identity::testing::identity,
+    many_identity::Identity,
+    many_identity_dsa::ed25519::generate_random_ed25519_identity,
+    many_ledger_test_utils::server::ServerSetup,
+    many_ledger_test_utils::MFX_SYMBOL,
+    many_modules::base::Endpoints,
+    many_modules::kvstore::{GetArgs, GetReturns, PutArgs},
+    many_modules::ledger::SendArgs,
+    many_modules::EmptyReturn,
+};
+
+#[test]
+fn endpoints() {
+    let setup = ServerSetup::new();
+    let id = generate_random_ed25519_identity();
+    let Endpoints(endpoints) = setup.call(&id, "endpoints", ()).unwrap();
+    for method in [
+        "ledger.send",
+        "kvstore.put",
+        "idstore.store",
+        "account.create",
+    ] {
+        assert!(endpoints.contains(method), "{method} is not registered.");
+    }
+}
+
+#[test]
+fn routes_to_modules() {
+    let setup = ServerSetup::new();
+    let id = generate_random_ed25519_identity();
+
+    let _: EmptyReturn = setup
+        .call(
+            &id,
+            "kvstore.put",
+            PutArgs {
+                key: b"foo".to_vec().into(),
+                value: b"bar".to_vec().into(),
+                alternative_owner: None,
+                expires_at: None,
+            },
+        )
+        .unwrap();
+    let returns: GetReturns = setup
+        .call(
+            &id,
+            "kvstore.get",
+            GetArgs {
+                key: b"foo".to_vec().into(),
+            },
+        )
+        .unwrap();
+    assert_eq!(returns.value, Some(b"bar".to_vec().into()));
+
+    let result: Result<EmptyReturn, _> = setup.call(
+        &id,
+        "ledger.send",
+        SendArgs {
+            from: None,
+            to: identity(1),
+            symbol: *MFX_SYMBOL,
+            amount: 10u32.into(),
+            memo: None,
+        },
+    );
+    assert_eq!(
+        result.unwrap_err().code(),
+        many_ledger::error::insufficient_funds().code()
+    );
+}
+
+#[test]
+fn validates_envelopes() {
+    let setup = ServerSetup::new();
+    let id = generate_random_ed25519_identity();
+
+    // The request is signed by a different identity than its sender.
+    let request = setup.request_message(identity(1), "kvstore.info", ());
+    assert!(setup.execute(request, &id).data.is_err());
+
+    // The request is addressed to another server.
+    let mut request = setup.request_message(id.address(), "kvstore.info", ());
+    request.to = identity(2);
+    assert!(setup.execute(request, &id).data.is_err());
+}
+
+#[test]
+fn default_modules() {
+    use many_identity::verifiers::AnonymousVerifier;
+    use many_identity::AnonymousIdentity;
+    use many_ledger::registry::{default_modules, registry, LedgerModuleContext};
+    use many_server::ManyServer;
+
+    let setup = ServerSetup::new();
+    let server = ManyServer::simple("test", AnonymousIdentity, AnonymousVerifier, None);
+    registry()
+        .instantiate(
+            &mut server.lock().unwrap(),
+            &LedgerModuleContext::new(setup.ledger.clone()),
+            &default_modules(),
+        )
+        .unwrap();
+
+    let Endpoints(endpoints) =
+        many_modules::base::BaseModuleBackend::endpoints(&*server.lock().unwrap()).unwrap();
+    for method in [
+        "ledger.send",
+        "events.list",
+        "idstore.store",
+        "labels.resolve",
+    ] {
+        assert!(endpoints.contains(method), "{method} is not registered.");
+    }
+}
@@ -0,0 +1,150 @@
+//! State sync: restoring the snapshots of a ledger on a new node.
+use many_identity::testing::identity;
+use many_ledger::module::LedgerModuleImpl;
+use many_ledger::storage::snapshots::{SnapshotConfig, SNAPSHOT_FORMAT};
+use many_ledger_test_utils::*;
+use many_modules::abci_backend::{
+    AbciApplySnapshotChunk, AbciApplySnapshotChunkResult, AbciLoadSnapshotChunk, AbciOfferSnapshot,
+    AbciOfferSnapshotResult, AbciSnapshot, ManyAbciModuleBackend,
+};
+use std::path::Path;
+
+fn setup_with_snapshots(path: &Path) -> Setup {
+    let mut harness = Setup::new(true);
+    harness.set_balance(harness.id, 1_000_000, *MFX_SYMBOL);
+    harness.module_impl.set_snapshots(SnapshotConfig {
+        path: path.to_path_buf(),
+        interval: 2,
+        keep: 2,
+    });
+    harness
+}
+
+fn send_blocks(harness: &mut Setup, count: usize) {
+    for _ in 0..count {
+        harness.block(|h| h.send_(h.id, identity(1), 1u32));
+    }
+}
+
+fn offer(module_impl: &mut LedgerModuleImpl, snapshot: &AbciSnapshot) -> AbciOfferSnapshotResult {
+    module_impl
+        .offer_snapshot(AbciOfferSnapshot {
+            snapshot: snapshot.clone(),
+            app_hash: snapshot.hash.clone(),
+        })
+        .unwrap()
+        .result
+}
+
+#[test]
+fn snapshots_are_taken_and_pruned() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut harness = setup_with_snapshots(dir.path());
+    send_blocks(&mut harness, 7);
+
+    let snapshots = harness.module_impl.list_snapshots().unwrap().snapshots;
+    let heights: Vec<u64> = snapshots.iter().map(|s| s.height).collect();
+    assert_eq!(heights, vec![4, 6]);
+    assert!(snapshots.iter().all(|s| s.format == SNAPSHOT_FORMAT));
+}
+
+#[test]
+fn restore() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut harness = setup_with_snapshots(dir.path());
+    send_blocks(&mut harness, 4);
+    let info = ManyAbciModuleBackend::info(&harness.module_impl).unwrap();
+    send_blocks(&mut harness, 1);
+
+    let snapshot = harness
+        .module_impl
+        .list_snapshots()
+        .unwrap()
+        .snapshots
+        .into_iter()
+        .find(|s| s.height == 4)
+        .unwrap();
+    assert_eq!(snapshot.hash, info.hash);
+
+    let store = tempfile::tempdir().unwrap();
+    let mut restored = LedgerModuleImpl::load(None, store.path().join("ledger.db"), true).unwrap();
+    assert_eq!(
+        offer(&mut restored, &snapshot),
+        AbciOfferSnapshotResult::Accept
+    );
+
+    for index in 0..snapshot.chunks {
+        let chunk = harness
+            .module_impl
+            .load_snapshot_chunk(AbciLoadSnapshotChunk {
+                height: snapshot.height,
+                format: snapshot.format,
+                chunk: index,
+            })
+            .unwrap()
+            .chunk;
+        let result = restored
+            .apply_snapshot_chunk(AbciApplySnapshotChunk {
+                index,
+                chunk,
+                sender: "peer".to_string(),
+            })
+            .unwrap();
+        assert_eq!(result.result, AbciApplySnapshotChunkResult::Accept);
+    }
+
+    assert_eq!(ManyAbciModuleBackend::info(&restored).unwrap(), info);
+}
+
+#[test]
+fn refuse_snapshots() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut harness = setup_with_snapshots(dir.path());
+    send_blocks(&mut harness, 2);
+    let snapshot = harness.module_impl.list_snapshots().unwrap().snapshots[0].clone();
+
+    let store = tempfile::tempdir().unwrap();
+    let mut restored = LedgerModuleImpl::load(None, store.path().join("ledger.db"), true).unwrap();
+
+    let other_format = AbciSnapshot {
+        format: SNAPSHOT_FORMAT + 1,
+        ..snapshot.clone()
+    };
+    assert_eq!(
+        offer(&mut restored, &other_format),
+        AbciOfferSnapshotResult::RejectFormat
+    );
+
+    // The app hash of the block does not match the snapshot.
+    let result = restored
+        .offer_snapshot(AbciOfferSnapshot {
+            snapshot: snapshot.clone(),
+            app_hash: vec![0u8; 32].into(),
+        })
+        .unwrap()
+        .result;
+    assert_eq!(result, AbciOfferSnapshotResult::Reject);
+
+    // Snapshots are only restored on empty stores.
+    assert_eq!(
+        offer(&mut harness.module_impl, &snapshot),
+        AbciOfferSnapshotResult::Abort
+    );
+
+    // Chunks are applied in order.
+    assert_eq!(
+        offer(&mut restored, &snapshot),
+        AbciOfferSnapshotResult::Accept
+    );
+    if snapshot.chunks > 1 {
+        let result = restored
+            .apply_snapshot_chunk(AbciApplySnapshotChunk {
+                index: 1,
+                chunk: vec![].into(),
+                sender: "peer".to_string(),
+            })
+            .unwrap();
+        assert_eq!(result.result, AbciApplySnapshotChunkResult::Retry);
+        assert_eq!(result.refetch_chunks, vec![0]);
+    }
+}
@@ -97,6 +97,7 @@ fn load_symbol_meta() {
             upper_block_height: None,
             disabled: false,
             issue: None,
+            rollout: None,
             extra: Default::default(),
         },
     ));
@@ -194,3 +195,51 @@ fn load_symbol_meta() {
     assert_eq!(info.summary.ticker, "MF0".to_string());
     assert_eq!(info.summary.decimals, 9);
 }
+
+/// Listing balances without symbols only returns the symbols held.
+#[test]
+fn balance_of_held_symbols() {
+    let path = tempfile::tempdir().unwrap().into_path();
+    let symbols =
+        BTreeMap::from_iter((1000..1010).map(|i| (identity(i), format!("MF{}", i - 1000))));
+    let balances = BTreeMap::from([
+        (
+            identity(5),
+            BTreeMap::from([
+                (identity(1001), 10u64.into()),
+                (identity(1007), 70u64.into()),
+            ]),
+        ),
+        (
+            identity(6),
+            BTreeMap::from([(identity(1002), 20u64.into())]),
+        ),
+    ]);
+    let _ = LedgerStorage::new(path.clone(), false)
+        .unwrap()
+        .with_balances(&identity(666), &symbols, &balances)
+        .unwrap()
+        .build()
+        .unwrap();
+    let module_impl = LedgerModuleImpl::load(None, path, false).unwrap();
+
+    let balance = |symbols: Option<Vec<Address>>| {
+        module_impl
+            .balance(
+                &identity(5),
+                ledger::BalanceArgs {
+                    account: None,
+                    symbols: symbols.map(Into::into),
+                },
+                Context::new(RequestMessage::default(), unbounded().0),
+            )
+            .unwrap()
+            .balances
+    };
+
+    assert_eq!(balance(None), balances[&identity(5)]);
+    assert_eq!(
+        balance(Some(vec![identity(1007), identity(1002)])),
+        BTreeMap::from([(identity(1007), 70u64.into())])
+    );
+}
@@ -0,0 +1,145 @@
+use {
+    many_identity::testing::identity,
+    many_ledger::migration::streams::STREAMS_MIGRATION,
+    many_ledger_test_utils::*,
+    many_modules::ledger::{
+        nothing_to_withdraw, stream_end_in_the_past, stream_not_found, LedgerStreamsModuleBackend,
+        StreamCancelArgs, StreamCreateArgs, StreamInfoArgs, StreamWithdrawArgs,
+    },
+    many_types::Timestamp,
+};
+
+fn setup() -> Setup {
+    let mut setup = Setup::new_with_migrations(true, [(0, &STREAMS_MIGRATION)], true);
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+    setup
+}
+
+fn create_args(end: u64) -> StreamCreateArgs {
+    StreamCreateArgs {
+        payee: identity(5),
+        symbol: *MFX_SYMBOL,
+        rate: 10u16.into(),
+        end: Timestamp::new(end).unwrap(),
+        memo: None,
+    }
+}
+
+/// Blocks start at 1_000_001 seconds and advance one second each. The stream
+/// created in the first block pays 10 tokens per second for 10 seconds.
+fn create(setup: &mut Setup) -> u64 {
+    let (_, id) = setup.block(|h| {
+        let sender = h.id;
+        h.module_impl
+            .stream_create(&sender, create_args(1_000_011))
+            .unwrap()
+            .id
+    });
+    assert_eq!(setup.balance_(setup.id), 900u32);
+    id
+}
+
+#[test]
+fn withdraw() {
+    let mut setup = setup();
+    let id = create(&mut setup);
+
+    setup.block(|h| {
+        let args = StreamWithdrawArgs { id, memo: None };
+        let returns = h
+            .module_impl
+            .stream_withdraw(&identity(5), args.clone())
+            .unwrap();
+        assert_eq!(returns.amount, 10u32);
+        assert_many_err(
+            h.module_impl.stream_withdraw(&identity(5), args.clone()),
+            nothing_to_withdraw(id),
+        );
+        assert_many_err(
+            h.module_impl.stream_withdraw(&identity(6), args),
+            many_ledger::error::unauthorized(),
+        );
+    });
+    assert_eq!(setup.balance_(identity(5)), 10u32);
+
+    for _ in 0..8 {
+        setup.block(|_| {});
+    }
+
+    // The stream ended, so the rest is withdrawn and the stream removed.
+    setup.block(|h| {
+        let returns = h
+            .module_impl
+            .stream_withdraw(&identity(5), StreamWithdrawArgs { id, memo: None })
+            .unwrap();
+        assert_eq!(returns.amount, 90u32);
+    });
+    assert_eq!(setup.balance_(identity(5)), 100u32);
+    assert_eq!(setup.balance_(setup.id), 900u32);
+    assert_many_err(
+        setup
+            .module_impl
+            .stream_info(&identity(5), StreamInfoArgs { id }),
+        stream_not_found(id),
+    );
+}
+
+#[test]
+fn cancel() {
+    let mut setup = setup();
+    let id = create(&mut setup);
+    setup.block(|_| {});
+    setup.block(|_| {});
+
+    setup.block(|h| {
+        let info = h
+            .module_impl
+            .stream_info(&identity(5), StreamInfoArgs { id })
+            .unwrap();
+        assert_eq!(info.withdrawable, 30u32);
+
+        assert_many_err(
+            h.module_impl
+                .stream_cancel(&identity(6), StreamCancelArgs { id, memo: None }),
+            many_ledger::error::unauthorized(),
+        );
+        let sender = h.id;
+        let returns = h
+            .module_impl
+            .stream_cancel(&sender, StreamCancelArgs { id, memo: None })
+            .unwrap();
+        assert_eq!(returns.paid, 30u32);
+        assert_eq!(returns.refunded, 70u32);
+    });
+
+    assert_eq!(setup.balance_(identity(5)), 30u32);
+    assert_eq!(setup.balance_(setup.id), 970u32);
+}
+
+#[test]
+fn invalid_streams() {
+    let mut setup = setup();
+    setup.block(|h| {
+        let sender = h.id;
+        assert_many_err(
+            h.module_impl.stream_create(&sender, create_args(1_000)),
+            stream_end_in_the_past(),
+        );
+        assert_many_err(
+            h.module_impl
+                .stream_create(&sender, create_args(1_000_000_000)),
+            many_ledger::error::insufficient_funds(),
+        );
+    });
+}
+
+#[test]
+fn disabled_without_migration() {
+    let mut setup = Setup::new(true);
+    let sender = setup.id;
+    assert!(setup
+        .module_impl
+        .stream_create(&sender, create_args(2_000_000))
+        .is_err());
+}
@@ -0,0 +1,92 @@
+use {
+    many_identity::testing::identity,
+    many_identity::Address,
+    many_ledger::migration::token_identity::TOKEN_IDENTITY_MIGRATION,
+    many_ledger::migration::tokens::TOKEN_MIGRATION,
+    many_ledger_test_utils::*,
+    many_modules::events::{EventFilter, EventInfo, EventKind, EventsModuleBackend, ListArgs},
+    many_modules::ledger::{
+        invalid_token_identity, LedgerTokenIdentityModuleBackend, UpdateTokenIdentityArgs,
+    },
+    many_types::SortOrder,
+    std::str::FromStr,
+};
+
+/// The token identity in the staging state.
+fn token_identity() -> Address {
+    Address::from_str("maffbahksdwaqeenayy2gxke32hgb7aq4ao4wt745lsfs6wijp").unwrap()
+}
+
+fn update_args(identity: Address) -> UpdateTokenIdentityArgs {
+    UpdateTokenIdentityArgs {
+        identity,
+        memo: None,
+    }
+}
+
+#[test]
+fn rotate() {
+    let mut setup = Setup::new_with_migrations(
+        false,
+        [(0, &TOKEN_MIGRATION), (0, &TOKEN_IDENTITY_MIGRATION)],
+        true,
+    );
+
+    assert_many_err(
+        setup
+            .module_impl
+            .update_token_identity(&identity(1), update_args(identity(1))),
+        many_ledger::error::invalid_sender(),
+    );
+    assert_many_err(
+        setup
+            .module_impl
+            .update_token_identity(&token_identity(), update_args(Address::anonymous())),
+        invalid_token_identity(Address::anonymous()),
+    );
+
+    setup
+        .module_impl
+        .update_token_identity(&token_identity(), update_args(identity(5)))
+        .unwrap();
+
+    // The previous identity lost its role.
+    assert_many_err(
+        setup
+            .module_impl
+            .update_token_identity(&token_identity(), update_args(identity(6))),
+        many_ledger::error::invalid_sender(),
+    );
+    setup
+        .module_impl
+        .update_token_identity(&identity(5), update_args(identity(6)))
+        .unwrap();
+
+    let events = setup
+        .module_impl
+        .list(ListArgs {
+            count: None,
+            order: Some(SortOrder::Ascending),
+            filter: Some(EventFilter {
+                kind: Some(vec![EventKind::TokenIdentityUpdate].into()),
+                ..EventFilter::default()
+            }),
+        })
+        .unwrap()
+        .events;
+    assert_eq!(events.len(), 2);
+    assert!(matches!(
+        events[1].content,
+        EventInfo::TokenIdentityUpdate { previous, identity: id, .. }
+            if previous == identity(5) && id == identity(6)
+    ));
+}
+
+#[test]
+fn disabled_without_migration() {
+    let mut setup = Setup::new_with_migrations(false, [(0, &TOKEN_MIGRATION)], true);
+    assert!(setup
+        .module_impl
+        .update_token_identity(&token_identity(), update_args(identity(5)))
+        .is_err());
+}
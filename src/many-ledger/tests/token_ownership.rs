@@ -0,0 +1,163 @@
+use {
+    many_identity::testing::identity,
+    many_identity::Address,
+    many_ledger::error,
+    many_ledger::migration::token_create::TOKEN_CREATE_MIGRATION,
+    many_ledger::migration::token_ownership_offers::{
+        OFFER_WINDOW_KEY, TOKEN_OWNERSHIP_OFFERS_MIGRATION,
+    },
+    many_ledger::migration::tokens::TOKEN_MIGRATION,
+    many_ledger_test_utils::*,
+    many_modules::ledger::{
+        LedgerTokensModuleBackend, TokenAcceptOwnershipArgs, TokenCancelOwnershipOfferArgs,
+        TokenInfoArgs, TokenOwnershipOffersArgs, TokenUpdateArgs,
+    },
+    many_types::ledger::{Symbol, TokenMaybeOwner},
+    many_types::Either,
+};
+
+fn setup(offers: bool) -> (Setup, Symbol) {
+    let mut migrations = vec![
+        MigrationHarness::from((0, &TOKEN_MIGRATION)),
+        MigrationHarness::from((0, &TOKEN_CREATE_MIGRATION)),
+    ];
+    if offers {
+        migrations.push(
+            MigrationHarness::from((0, &TOKEN_OWNERSHIP_OFFERS_MIGRATION))
+                .with_extra(OFFER_WINDOW_KEY, serde_json::json!("3s")),
+        );
+    }
+    let mut setup = Setup::new_with_migrations(true, migrations, true);
+    let (_, symbol) = setup.block(|h| {
+        LedgerTokensModuleBackend::create(
+            &mut h.module_impl,
+            &identity(1),
+            default_token_create_args(Some(TokenMaybeOwner::Left(identity(1))), None),
+        )
+        .unwrap()
+        .info
+        .symbol
+    });
+    (setup, symbol)
+}
+
+fn owner(setup: &Setup, symbol: Symbol) -> Option<Address> {
+    LedgerTokensModuleBackend::info(
+        &setup.module_impl,
+        &identity(1),
+        TokenInfoArgs {
+            symbol,
+            extended_info: None,
+        },
+    )
+    .unwrap()
+    .info
+    .owner
+}
+
+fn transfer(setup: &mut Setup, symbol: Symbol, new_owner: Address) {
+    setup.block(|h| {
+        LedgerTokensModuleBackend::update(
+            &mut h.module_impl,
+            &identity(1),
+            TokenUpdateArgs {
+                symbol,
+                name: None,
+                ticker: None,
+                decimals: None,
+                owner: Some(Either::Left(new_owner)),
+                memo: None,
+            },
+        )
+        .unwrap()
+    });
+}
+
+fn accept(setup: &mut Setup, sender: Address, symbol: Symbol) -> Result<(), many_error::ManyError> {
+    setup
+        .block(|h| {
+            h.module_impl
+                .accept_ownership(&sender, TokenAcceptOwnershipArgs { symbol, memo: None })
+        })
+        .1
+        .map(|_| ())
+}
+
+fn offers_to(setup: &Setup, new_owner: Address) -> usize {
+    setup
+        .module_impl
+        .ownership_offers(
+            &identity(1),
+            TokenOwnershipOffersArgs {
+                symbol: None,
+                new_owner: Some(new_owner),
+            },
+        )
+        .unwrap()
+        .offers
+        .len()
+}
+
+#[test]
+fn offer_and_accept() {
+    let (mut setup, symbol) = setup(true);
+    transfer(&mut setup, symbol, identity(2));
+    assert_eq!(owner(&setup, symbol), Some(identity(1)));
+    assert_eq!(offers_to(&setup, identity(2)), 1);
+    assert_eq!(offers_to(&setup, identity(3)), 0);
+
+    assert_many_err(
+        accept(&mut setup, identity(3), symbol),
+        error::unauthorized(),
+    );
+    accept(&mut setup, identity(2), symbol).unwrap();
+    assert_eq!(owner(&setup, symbol), Some(identity(2)));
+    assert_eq!(offers_to(&setup, identity(2)), 0);
+    assert_many_err(
+        accept(&mut setup, identity(2), symbol),
+        error::ownership_offer_not_found(symbol),
+    );
+}
+
+#[test]
+fn offer_expires() {
+    let (mut setup, symbol) = setup(true);
+    transfer(&mut setup, symbol, identity(2));
+    for _ in 0..4 {
+        setup.block(|_| {});
+    }
+    assert_eq!(offers_to(&setup, identity(2)), 0);
+    assert_many_err(
+        accept(&mut setup, identity(2), symbol),
+        error::ownership_offer_expired(symbol),
+    );
+    assert_eq!(owner(&setup, symbol), Some(identity(1)));
+}
+
+#[test]
+fn cancel_offer() {
+    let (mut setup, symbol) = setup(true);
+    transfer(&mut setup, symbol, identity(2));
+    setup
+        .block(|h| {
+            h.module_impl.cancel_ownership_offer(
+                &identity(1),
+                TokenCancelOwnershipOfferArgs { symbol, memo: None },
+            )
+        })
+        .1
+        .unwrap();
+    assert_many_err(
+        accept(&mut setup, identity(2), symbol),
+        error::ownership_offer_not_found(symbol),
+    );
+    assert_eq!(owner(&setup, symbol), Some(identity(1)));
+}
+
+#[test]
+fn instant_transfer_without_migration() {
+    let (mut setup, symbol) = setup(false);
+    transfer(&mut setup, symbol, identity(2));
+    assert_eq!(owner(&setup, symbol), Some(identity(2)));
+    assert!(accept(&mut setup, identity(2), symbol).is_err());
+}
@@ -0,0 +1,281 @@
+use {
+    many_identity::testing::identity,
+    many_identity::Address,
+    many_ledger::migration::tokens::TOKEN_MIGRATION,
+    many_ledger::migration::watchlist::WATCHLIST_MIGRATION,
+    many_ledger_test_utils::*,
+    many_modules::events::{EventFilter, EventInfo, EventKind, EventsModuleBackend, ListArgs},
+    many_modules::ledger::{LedgerMintBurnModuleBackend, TokenBurnArgs, TokenMintArgs},
+    many_modules::watchlist::{
+        errors, AddArgs, InfoArgs, RemoveArgs, SetApproversArgs, WatchlistAction,
+        WatchlistModuleBackend,
+    },
+    std::collections::{BTreeMap, BTreeSet},
+    std::str::FromStr,
+};
+
+/// The server identity in the staging state, which administers the watchlist.
+fn admin() -> Address {
+    Address::from_str("mahukzwuwgt3porn6q4vq4xu3mwy5gyskhouryzbscq7wb2iow").unwrap()
+}
+
+fn setup() -> Setup {
+    Setup::new_with_migrations(false, [(0, &WATCHLIST_MIGRATION)], true)
+}
+
+fn watch(setup: &mut Setup, address: Address, action: WatchlistAction) {
+    setup
+        .module_impl
+        .add(
+            &admin(),
+            AddArgs {
+                address,
+                action,
+                memo: None,
+            },
+        )
+        .unwrap();
+}
+
+fn screen_events(setup: &Setup) -> Vec<EventInfo> {
+    setup
+        .module_impl
+        .list(ListArgs {
+            count: None,
+            order: None,
+            filter: Some(EventFilter {
+                kind: Some(vec![EventKind::WatchlistScreen].into()),
+                ..Default::default()
+            }),
+        })
+        .unwrap()
+        .events
+        .into_iter()
+        .map(|e| e.content)
+        .collect()
+}
+
+#[test]
+fn admin_only() {
+    let mut setup = setup();
+    let args = AddArgs {
+        address: identity(5),
+        action: WatchlistAction::Alert,
+        memo: None,
+    };
+    assert_many_err(
+        setup.module_impl.add(&identity(1), args.clone()),
+        many_ledger::error::unauthorized(),
+    );
+    assert!(setup.module_impl.add(&admin(), args).is_ok());
+
+    assert_many_err(
+        setup.module_impl.remove(
+            &identity(1),
+            RemoveArgs {
+                address: identity(5),
+                memo: None,
+            },
+        ),
+        many_ledger::error::unauthorized(),
+    );
+    assert_many_err(
+        setup.module_impl.set_approvers(
+            &identity(1),
+            SetApproversArgs {
+                approvers: BTreeSet::from([identity(1)]),
+            },
+        ),
+        many_ledger::error::unauthorized(),
+    );
+
+    let info = WatchlistModuleBackend::info(&setup.module_impl, &identity(1), InfoArgs {}).unwrap();
+    assert_eq!(info.entries[&identity(5)].action, WatchlistAction::Alert);
+    assert!(info.approvers.is_empty());
+}
+
+#[test]
+fn disabled_without_migration() {
+    let mut setup = Setup::new(false);
+    assert!(setup
+        .module_impl
+        .add(
+            &admin(),
+            AddArgs {
+                address: identity(5),
+                action: WatchlistAction::Reject,
+                memo: None,
+            },
+        )
+        .is_err());
+
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+    assert!(setup.send(id, identity(5), 10u16, *MFX_SYMBOL).is_ok());
+}
+
+#[test]
+fn alert() {
+    let mut setup = setup();
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+    watch(&mut setup, identity(5), WatchlistAction::Alert);
+
+    assert!(setup.send(id, identity(5), 10u16, *MFX_SYMBOL).is_ok());
+    assert!(setup.send(id, identity(6), 10u16, *MFX_SYMBOL).is_ok());
+
+    let events = screen_events(&setup);
+    assert_eq!(events.len(), 1);
+    assert!(matches!(
+        events[0],
+        EventInfo::WatchlistScreen {
+            address,
+            action: WatchlistAction::Alert,
+            allowed: true,
+            ..
+        } if address == identity(5)
+    ));
+}
+
+#[test]
+fn alert_failed_transfer() {
+    let mut setup = setup();
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+    watch(&mut setup, identity(5), WatchlistAction::Alert);
+
+    assert!(setup.send(id, identity(5), 2_000u16, *MFX_SYMBOL).is_err());
+    assert!(screen_events(&setup).is_empty());
+
+    assert!(setup.send(id, identity(5), 10u16, *MFX_SYMBOL).is_ok());
+    assert_eq!(screen_events(&setup).len(), 1);
+}
+
+#[test]
+fn reject() {
+    let mut setup = setup();
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+    watch(&mut setup, identity(5), WatchlistAction::Reject);
+
+    assert_many_err(
+        setup.send(id, identity(5), 10u16, *MFX_SYMBOL),
+        errors::transfer_rejected(identity(5)),
+    );
+    assert_eq!(setup.balance_(id), 1_000u32);
+
+    let events = screen_events(&setup);
+    assert_eq!(events.len(), 1);
+    assert!(matches!(
+        events[0],
+        EventInfo::WatchlistScreen { allowed: false, .. }
+    ));
+
+    // Removing the entry allows transfers again.
+    setup
+        .module_impl
+        .remove(
+            &admin(),
+            RemoveArgs {
+                address: identity(5),
+                memo: None,
+            },
+        )
+        .unwrap();
+    assert!(setup.send(id, identity(5), 10u16, *MFX_SYMBOL).is_ok());
+}
+
+#[test]
+fn require_approval() {
+    let mut setup = setup();
+    let id = setup.id;
+    setup.set_balance(id, 1_000, *MFX_SYMBOL);
+    setup.set_balance(identity(2), 1_000, *MFX_SYMBOL);
+    watch(&mut setup, identity(5), WatchlistAction::RequireApproval);
+    setup
+        .module_impl
+        .set_approvers(
+            &admin(),
+            SetApproversArgs {
+                approvers: BTreeSet::from([identity(2)]),
+            },
+        )
+        .unwrap();
+
+    assert_many_err(
+        setup.send(id, identity(5), 10u16, *MFX_SYMBOL),
+        errors::approval_required(identity(5)),
+    );
+    assert!(setup
+        .send(identity(2), identity(5), 10u16, *MFX_SYMBOL)
+        .is_ok());
+    assert_eq!(setup.balance_(identity(5)), 10u16);
+
+    let events = screen_events(&setup);
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().any(|e| matches!(
+        e,
+        EventInfo::WatchlistScreen { allowed: true, sender, .. } if *sender == identity(2)
+    )));
+}
+
+#[test]
+fn multisig_send() {
+    let mut setup = setup();
+    let account_id = setup.create_account_(AccountType::Multisig);
+    setup.set_balance(account_id, 1_000, *MFX_SYMBOL);
+    watch(&mut setup, identity(5), WatchlistAction::Reject);
+
+    let token = setup.multisig_send_(account_id, identity(5), 10u16);
+    setup.multisig_approve_(identity(2), &token);
+    setup.multisig_approve_(identity(3), &token);
+    let response = setup.multisig_execute_(&token);
+    assert_eq!(response.data, Err(errors::transfer_rejected(identity(5))));
+    assert_eq!(setup.balance_(account_id), 1_000u32);
+}
+
+#[test]
+fn mint_burn() {
+    let mut setup = Setup::new_with_migrations(
+        false,
+        [(0, &WATCHLIST_MIGRATION), (0, &TOKEN_MIGRATION)],
+        true,
+    );
+    setup.set_balance(identity(5), 1_000, *MFX_SYMBOL);
+    watch(&mut setup, identity(5), WatchlistAction::Reject);
+
+    assert_many_err(
+        setup.module_impl.mint(
+            &admin(),
+            TokenMintArgs {
+                symbol: *MFX_SYMBOL,
+                distribution: BTreeMap::from([
+                    (identity(4), 10u16.into()),
+                    (identity(5), 10u16.into()),
+                ]),
+                memo: None,
+            },
+        ),
+        errors::transfer_rejected(identity(5)),
+    );
+    assert_many_err(
+        setup.module_impl.burn(
+            &admin(),
+            TokenBurnArgs {
+                symbol: *MFX_SYMBOL,
+                distribution: BTreeMap::from([(identity(5), 10u16.into())]),
+                memo: None,
+                error_on_under_burn: None,
+            },
+        ),
+        errors::transfer_rejected(identity(5)),
+    );
+    assert_eq!(setup.balance_(identity(4)), 0u32);
+    assert_eq!(setup.balance_(identity(5)), 1_000u32);
+
+    let events = screen_events(&setup);
+    assert_eq!(events.len(), 2);
+    assert!(events
+        .iter()
+        .all(|e| matches!(e, EventInfo::WatchlistScreen { allowed: false, .. })));
+}
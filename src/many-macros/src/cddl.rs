@@ -0,0 +1,186 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    Data, DeriveInput, Expr, ExprLit, Fields, GenericArgument, Lit, LitInt, Meta, PathArguments,
+    Token, Type,
+};
+
+enum Encoding {
+    Array,
+    Map,
+    Transparent,
+}
+
+fn encoding(input: &DeriveInput) -> syn::Result<Encoding> {
+    let mut encoding = Encoding::Array;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("cbor")) {
+        let list = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+        for meta in list {
+            if meta.path().is_ident("map") {
+                encoding = Encoding::Map;
+            } else if meta.path().is_ident("transparent") {
+                encoding = Encoding::Transparent;
+            }
+        }
+    }
+    Ok(encoding)
+}
+
+/// The `#[cbor(_)]` attribute arguments of a field.
+fn cbor_args(field: &syn::Field) -> syn::Result<Vec<Meta>> {
+    let mut args = Vec::new();
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("cbor")) {
+        args.extend(attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?);
+    }
+    Ok(args)
+}
+
+/// The index of a field, from its `#[n(_)]`, `#[b(_)]` or `#[cbor(n(_))]`
+/// attribute.
+fn index(field: &syn::Field) -> syn::Result<u64> {
+    if let Some(attr) = field
+        .attrs
+        .iter()
+        .find(|a| a.path().is_ident("n") || a.path().is_ident("b"))
+    {
+        return attr.parse_args::<LitInt>()?.base10_parse();
+    }
+    for meta in cbor_args(field)? {
+        if let Meta::List(list) = meta {
+            if list.path.is_ident("n") || list.path.is_ident("b") {
+                return list.parse_args::<LitInt>()?.base10_parse();
+            }
+        }
+    }
+    Err(syn::Error::new(field.span(), "missing `#[n(_)]` attribute"))
+}
+
+/// Whether a field is encoded as a byte string with
+/// `#[cbor(with = "minicbor::bytes")]`.
+fn is_bytes(field: &syn::Field) -> syn::Result<bool> {
+    Ok(cbor_args(field)?.iter().any(|meta| match meta {
+        Meta::NameValue(nv) if nv.path.is_ident("with") => matches!(
+            &nv.value,
+            Expr::Lit(ExprLit { lit: Lit::Str(s), .. }) if s.value() == "minicbor::bytes"
+        ),
+        _ => false,
+    }))
+}
+
+/// An expression describing a field of type `ty`, or its `Option` inner type
+/// if `optional`.
+fn describe_field(field: &syn::Field, ty: &Type, optional: bool) -> syn::Result<TokenStream> {
+    if !is_bytes(field)? {
+        return Ok(describe(ty));
+    }
+    Ok(if optional || option_inner(ty).is_none() {
+        quote! { "bstr".to_string() }
+    } else {
+        quote! { "bstr / null".to_string() }
+    })
+}
+
+/// The first type argument of `ty` if it is an `ident<...>` type.
+fn type_argument<'a>(ty: &'a Type, ident: &str) -> Option<&'a Type> {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident != ident {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => match args.args.first()? {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The `T` of an `Option<T>` type.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    type_argument(ty, "Option")
+}
+
+/// The `T` of a `Result<T, E>` type.
+pub(crate) fn result_ok(ty: &Type) -> Option<&Type> {
+    type_argument(ty, "Result")
+}
+
+/// An expression describing `ty`, with `CddlOrAny` and `CddlUnknown` in scope.
+fn describe(ty: &Type) -> TokenStream {
+    quote! { (&&many_types::cddl::CddlProbe::<#ty>::new()).cddl_or_any() }
+}
+
+pub fn derive_cddl_impl(input: DeriveInput) -> syn::Result<TokenStream> {
+    let data = match &input.data {
+        Data::Struct(data) => data,
+        _ => {
+            return Err(syn::Error::new(
+                input.span(),
+                "`Cddl` can only be derived for structs.",
+            ))
+        }
+    };
+    let encoding = encoding(&input)?;
+
+    let body = match (&data.fields, encoding) {
+        (Fields::Unit, _) => quote! { "null".to_string() },
+        (fields, Encoding::Transparent) => {
+            let field = fields
+                .iter()
+                .next()
+                .ok_or_else(|| syn::Error::new(input.span(), "transparent structs need a field"))?;
+            describe_field(field, &field.ty, false)?
+        }
+        (fields, encoding) => {
+            let mut fields = fields
+                .iter()
+                .map(|f| Ok((index(f)?, f)))
+                .collect::<syn::Result<Vec<_>>>()?;
+            fields.sort_by_key(|(i, _)| *i);
+
+            let entries = fields
+                .iter()
+                .map(|(i, f)| {
+                    Ok(match encoding {
+                        Encoding::Map => match option_inner(&f.ty) {
+                            Some(inner) => {
+                                let inner = describe_field(f, inner, true)?;
+                                quote! { format!("? {}: {}", #i, #inner) }
+                            }
+                            None => {
+                                let ty = describe_field(f, &f.ty, false)?;
+                                quote! { format!("{}: {}", #i, #ty) }
+                            }
+                        },
+                        _ => describe_field(f, &f.ty, false)?,
+                    })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            let (open, close) = match encoding {
+                Encoding::Map => ("{", "}"),
+                _ => ("[", "]"),
+            };
+            quote! {
+                let entries: Vec<String> = vec![ #( #entries ),* ];
+                format!("{}{}{}", #open, entries.join(", "), #close)
+            }
+        }
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics many_types::cddl::Cddl for #ident #ty_generics #where_clause {
+            fn cddl() -> String {
+                #[allow(unused_imports)]
+                use many_types::cddl::{CddlOrAny as _, CddlUnknown as _};
+                #body
+            }
+        }
+    })
+}
@@ -8,6 +8,8 @@ use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::{FnArg, Pat, PatType, ReturnType, Token, TraitItem, TraitItemFn, Type, TypePath};
 
+mod cddl;
+
 #[derive(Deserialize)]
 struct ManyModuleAttributes {
     pub id: Option<u32>,
@@ -20,6 +22,7 @@ struct ManyModuleAttributes {
 struct EndpointManyAttribute {
     deny_anonymous: Option<bool>,
     check_webauthn: Option<bool>,
+    priority: Option<i64>,
 }
 
 impl EndpointManyAttribute {
@@ -47,6 +50,7 @@ impl EndpointManyAttribute {
         Ok(Self {
             deny_anonymous: either(self.deny_anonymous, other.deny_anonymous)?,
             check_webauthn: either(self.check_webauthn, other.check_webauthn)?,
+            priority: either(self.priority, other.priority)?,
         })
     }
 }
@@ -58,12 +62,21 @@ impl syn::parse::Parse for EndpointManyAttribute {
         if arg_name == "deny_anonymous" {
             Ok(Self {
                 deny_anonymous: Some(true),
-                check_webauthn: None,
+                ..Default::default()
             })
         } else if arg_name == "check_webauthn" {
             Ok(Self {
-                deny_anonymous: None,
                 check_webauthn: Some(true),
+                ..Default::default()
+            })
+        } else if arg_name == "priority" {
+            // `priority = <integer>`, the mempool priority of the endpoint.
+            input.parse::<Token![=]>()?;
+            let negative = input.parse::<Option<Token![-]>>()?.is_some();
+            let value: i64 = input.parse::<syn::LitInt>()?.base10_parse()?;
+            Ok(Self {
+                priority: Some(if negative { -value } else { value }),
+                ..Default::default()
             })
         } else {
             Err(syn::Error::new_spanned(arg_name, "unsupported attribute"))
@@ -315,8 +328,11 @@ impl Endpoint {
 
         let check_ty = if let Some((_, ty)) = &self.arg {
             quote_spanned! { span =>
-                minicbor::decode::<'_, #ty>(data)
-                    .map_err(|e| many_error::ManyError::deserialization_error(e.to_string()))?;
+                minicbor::decode::<'_, #ty>(data).map_err(|e| {
+                    many_error::ManyError::deserialization_error(
+                        many_types::cbor::decode_error_details(data, &e),
+                    )
+                })?;
             }
         } else {
             quote! { {} }
@@ -331,6 +347,44 @@ impl Endpoint {
         }
     }
 
+    pub fn priority_pat(&self, namespace: &Option<String>) -> Option<TokenStream> {
+        let priority = self.metadata.priority?;
+        let name = self.name.as_str().to_camel_case();
+        let ep = match namespace {
+            Some(ref namespace) => format!("{namespace}.{name}"),
+            None => name,
+        };
+
+        Some(quote_spanned! { self.span => #ep => #priority, })
+    }
+
+    /// Returns the `(name, EndpointSchema)` pair describing this endpoint.
+    pub fn schema_pat(&self, namespace: &Option<String>, many_modules: &Ident) -> TokenStream {
+        let name = self.name.as_str().to_camel_case();
+        let ep = match namespace {
+            Some(ref namespace) => format!("{namespace}.{name}"),
+            None => name,
+        };
+
+        let argument = match &self.arg {
+            Some((_, ty)) => {
+                quote_spanned! { ty.span() => <#ty as many_types::cddl::Cddl>::cddl() }
+            }
+            None => quote! { "null".to_string() },
+        };
+        let returns = match cddl::result_ok(&self.ret_type) {
+            Some(ty) => quote_spanned! { ty.span() => <#ty as many_types::cddl::Cddl>::cddl() },
+            None => quote! { "any".to_string() },
+        };
+
+        quote! {
+            (#ep.to_string(), #many_modules ::base::EndpointSchema {
+                argument: #argument,
+                returns: #returns,
+            }),
+        }
+    }
+
     pub fn execute_endpoint_pat(&self, namespace: &Option<String>) -> TokenStream {
         let span = self.span;
         let name = self.name.as_str().to_camel_case();
@@ -402,6 +456,13 @@ impl Endpoint {
             }
         };
 
+        // Synchronous calls run with the request as the current intent.
+        let call = if self.is_async {
+            call
+        } else {
+            quote_spanned! { span => many_protocol::context::with_intent(&message, || #call) }
+        };
+
         quote_spanned! { span =>
             #ep => {
                 #backend_decl
@@ -511,6 +572,25 @@ fn many_module_impl(attr: &TokenStream, item: TokenStream) -> Result<TokenStream
         }
     };
 
+    // Modules without priorities keep the default of `ManyModule`.
+    let priority_pat: Vec<TokenStream> = endpoints
+        .iter()
+        .filter_map(|e| e.priority_pat(&namespace))
+        .collect();
+    let priority = if priority_pat.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            fn priority(&self, message: & many_protocol::RequestMessage) -> i64 {
+                match message.method.as_str() {
+                    #(#priority_pat)*
+
+                    _ => 0,
+                }
+            }
+        }
+    };
+
     let execute_endpoint_pat = endpoints.iter().map(|e| e.execute_endpoint_pat(&namespace));
 
     let execute = quote! {
@@ -521,11 +601,13 @@ fn many_module_impl(attr: &TokenStream, item: TokenStream) -> Result<TokenStream
             use {
                 async_channel::unbounded,
                 many_error::ManyError,
-                many_protocol::context::{Context, ProofResult},
-                many_types::PROOF
+                many_protocol::context::{Context, Instrument, ProofResult},
+                many_types::{projection::Projection, PROOF}
             };
             fn decode<'a, T: minicbor::Decode<'a, ()>>(data: &'a [u8]) -> Result<T, ManyError> {
-                minicbor::decode(data).map_err(|e| ManyError::deserialization_error(e.to_string()))
+                minicbor::decode(data).map_err(|e| {
+                    ManyError::deserialization_error(many_types::cbor::decode_error_details(data, &e))
+                })
             }
             fn encode<T: minicbor::Encode<()>>(result: Result<T, ManyError>) -> Result<Vec<u8>, ManyError> {
                 minicbor::to_vec(result?).map_err(|e| ManyError::serialization_error(e.to_string()))
@@ -534,11 +616,21 @@ fn many_module_impl(attr: &TokenStream, item: TokenStream) -> Result<TokenStream
             let data = message.data.as_slice();
             let (transmitter, receiver) = unbounded();
             let ctx = Context::new(message.clone(), transmitter);
-            let result = match message.method.as_str() {
-                #( #execute_endpoint_pat )*
-
-                _ => Err(ManyError::internal_server_error()),
-            }?;
+            let span = ctx.span().clone();
+            let result = async {
+                let result: Result<Vec<u8>, ManyError> = match message.method.as_str() {
+                    #( #execute_endpoint_pat )*
+
+                    _ => Err(ManyError::internal_server_error()),
+                };
+                result
+            }
+            .instrument(span)
+            .await?;
+            let result = match message.attributes.get::<Projection>() {
+                Ok(projection) => projection.apply(&result).unwrap_or(result),
+                Err(_) => result,
+            };
 
             Ok(if message.attributes.contains(&PROOF) {
                 many_protocol::ResponseMessage::from_request(
@@ -556,6 +648,15 @@ fn many_module_impl(attr: &TokenStream, item: TokenStream) -> Result<TokenStream
         }
     };
 
+    let schema_pat = endpoints
+        .iter()
+        .map(|e| e.schema_pat(&namespace, &many_modules));
+    let schemas = quote! {
+        fn schemas(&self) -> std::collections::BTreeMap<String, #many_modules ::base::EndpointSchema> {
+            std::collections::BTreeMap::from([ #( #schema_pat )* ])
+        }
+    };
+
     let attribute = if attrs.id.is_some() {
         quote! { Some(#attr_ident) }
     } else {
@@ -612,6 +713,10 @@ fn many_module_impl(attr: &TokenStream, item: TokenStream) -> Result<TokenStream
 
             #validate
 
+            #priority
+
+            #schemas
+
             #execute
         }
     })
@@ -626,3 +731,14 @@ pub fn many_module(
         .unwrap_or_else(|e| e.to_compile_error())
         .into()
 }
+
+/// Derives `many_types::cddl::Cddl` for a struct encoded with
+/// `minicbor::Encode`, from its `#[n(_)]` and `#[cbor(_)]` attributes. Fields
+/// whose types don't implement `Cddl` are described as `any`.
+#[proc_macro_derive(Cddl)]
+pub fn derive_cddl(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    syn::parse(input)
+        .and_then(cddl::derive_cddl_impl)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
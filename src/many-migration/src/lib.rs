@@ -27,6 +27,10 @@ pub struct Metadata {
 
     pub issue: Option<String>,
 
+    /// Only useful for trigger migrations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollout: Option<Rollout>,
+
     #[serde(flatten)]
     pub extra: HashMap<String, Value>,
 }
@@ -38,6 +42,7 @@ impl Metadata {
             upper_block_height: None,
             disabled: false,
             issue: None,
+            rollout: None,
             extra: Default::default(),
         }
     }
@@ -48,9 +53,52 @@ impl Metadata {
             upper_block_height: None,
             disabled: true,
             issue: None,
+            rollout: None,
             extra: Default::default(),
         }
     }
+
+    pub fn with_rollout(mut self, rollout: Rollout) -> Self {
+        self.rollout = Some(rollout);
+        self
+    }
+}
+
+/// The number of buckets keys are split into for staged rollouts.
+pub const ROLLOUT_BUCKETS: u8 = 100;
+
+/// A staged rollout of a trigger migration. From the height of the migration
+/// until `full_block_height`, the migration is only active for the keys
+/// (e.g. addresses) in the first `percent` buckets. It is active for all keys
+/// from `full_block_height` on.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Rollout {
+    pub percent: u8,
+    pub full_block_height: u64,
+}
+
+impl Rollout {
+    pub fn new(percent: u8, full_block_height: u64) -> Self {
+        Self {
+            percent,
+            full_block_height,
+        }
+    }
+
+    /// The bucket of a key. This must be deterministic across nodes and
+    /// releases, so it uses its own hash (FNV-1a) instead of the `Hasher` of
+    /// the standard library.
+    pub fn bucket(key: &[u8]) -> u8 {
+        let hash = key.iter().fold(0xcbf29ce484222325u64, |hash, b| {
+            (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+        });
+        (hash % ROLLOUT_BUCKETS as u64) as u8
+    }
+
+    /// Whether the rollout includes this key at this height.
+    pub fn includes(&self, key: &[u8], height: u64) -> bool {
+        height >= self.full_block_height || Self::bucket(key) < self.percent
+    }
 }
 
 #[derive(Copy, Clone, Display)]
@@ -269,6 +317,9 @@ pub struct Migration<'a, T, E> {
 
     /// Whether the block height has been reached.
     active: bool,
+
+    /// The last height this migration was activated at.
+    height: u64,
 }
 
 // The Debug derive requires that _all_ parametric types also implement Debug,
@@ -280,6 +331,7 @@ impl<'a, T, E> fmt::Debug for Migration<'a, T, E> {
             .field("metadata", &self.metadata)
             .field("enabled", &self.enabled)
             .field("active", &self.active)
+            .field("height", &self.height)
             .finish()
     }
 }
@@ -303,12 +355,14 @@ impl<'a, T, E> Migration<'a, T, E> {
             metadata,
             enabled,
             active: false,
+            height: 0,
         }
     }
 
     /// Activate this migration at a certain height, returning whether
     /// initialize, update or no follow-up step should be taken.
     fn activate_at_height(&mut self, height: u64) -> Activated {
+        self.height = height;
         if self.migration.is_trigger() {
             self.active = (self.metadata.block_height
                 ..self.metadata.upper_block_height.unwrap_or(u64::MAX))
@@ -329,6 +383,7 @@ impl<'a, T, E> Migration<'a, T, E> {
     /// Activate this migration, during initialization. This does not initialize
     /// or call any side effects.
     fn set_active_at_height(&mut self, height: u64) {
+        self.height = height;
         if self.migration.is_trigger() {
             self.active = (self.metadata.block_height
                 ..self.metadata.upper_block_height.unwrap_or(u64::MAX))
@@ -436,6 +491,19 @@ impl<'a, T, E> Migration<'a, T, E> {
             false
         }
     }
+
+    /// Whether this migration is active for a key (e.g. an address). This is
+    /// the same as `is_active` unless the migration is being rolled out, in
+    /// which case only the keys included in the rollout are.
+    pub fn is_active_for(&self, key: impl AsRef<[u8]>) -> bool {
+        if !self.is_active() {
+            return false;
+        }
+        match &self.metadata.rollout {
+            Some(rollout) if self.is_enabled() => rollout.includes(key.as_ref(), self.height),
+            _ => true,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -534,7 +602,7 @@ impl<'a, T, E> MigrationSet<'a, T, E> {
             .map(|m| (m.name, m))
             .collect::<BTreeMap<&'static str, &'a InnerMigration<T, E>>>();
 
-        let mut inner: BTreeMap<String, Migration<'a, T, E>> = config
+        let inner: BTreeMap<String, Migration<'a, T, E>> = config
             .migrations
             .into_iter()
             .map(|config: SingleMigrationConfig| {
@@ -542,6 +610,21 @@ impl<'a, T, E> MigrationSet<'a, T, E> {
                     .get(config.name.as_str())
                     .ok_or_else(|| format!("Unsupported migration '{}'", config.name))?;
 
+                if let Some(rollout) = &config.metadata.rollout {
+                    if !v.is_trigger() {
+                        return Err(format!(
+                            "Migration '{}' is not a trigger and cannot be rolled out",
+                            config.name
+                        ));
+                    }
+                    if rollout.percent > ROLLOUT_BUCKETS {
+                        return Err(format!(
+                            "Migration '{}' cannot be rolled out to {}%",
+                            config.name, rollout.percent
+                        ));
+                    }
+                }
+
                 Ok((config.name, Migration::new(v, config.metadata)))
             })
             .collect::<Result<BTreeMap<_, _>, String>>()?
@@ -562,12 +645,17 @@ impl<'a, T, E> MigrationSet<'a, T, E> {
             }?;
         }
 
-        // Activate all already active migrations. Do not call initialize though.
-        for v in inner.values_mut().filter(|m| m.is_enabled()) {
+        let mut set = Self { inner };
+        set.activate_at_height(height);
+        Ok(set)
+    }
+
+    /// Activate all the migrations already active at `height`, e.g. after the
+    /// storage was restored at this height. Do not call initialize though.
+    pub fn activate_at_height(&mut self, height: u64) {
+        for v in self.inner.values_mut().filter(|m| m.is_enabled()) {
             v.set_active_at_height(height);
         }
-
-        Ok(Self { inner })
     }
 
     #[inline]
@@ -613,6 +701,16 @@ impl<'a, T, E> MigrationSet<'a, T, E> {
             .map(|m| m.is_active())
             .unwrap_or(false)
     }
+
+    /// Whether a migration is active for a key, e.g. an address, taking its
+    /// staged rollout into account.
+    #[inline]
+    pub fn is_active_for(&self, name: impl AsRef<str>, key: impl AsRef<[u8]>) -> bool {
+        self.inner
+            .get(name.as_ref())
+            .map(|m| m.is_active_for(key))
+            .unwrap_or(false)
+    }
 }
 
 /// Implement necessary BTreeMap<...> methods to have the same interface for
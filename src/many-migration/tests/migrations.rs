@@ -2,7 +2,7 @@
 
 use linkme::distributed_slice;
 use many_migration::{
-    InnerMigration, Metadata, Migration, MigrationConfig, MigrationSet, MigrationType,
+    InnerMigration, Metadata, Migration, MigrationConfig, MigrationSet, MigrationType, Rollout,
 };
 use serde_json::Value;
 use std::collections::{BTreeMap, HashMap};
@@ -505,3 +505,107 @@ fn strict_config_many() {
         r#"Migration Config is missing migrations ["C", "D", "E", "F"]"#.to_string()
     );
 }
+
+#[distributed_slice]
+static ROLLOUT_MIGRATIONS: [InnerMigration<Storage, String>] = [..];
+
+#[distributed_slice(ROLLOUT_MIGRATIONS)]
+static G: InnerMigration<Storage, String> = InnerMigration::new_trigger(false, "G", "G desc");
+
+#[distributed_slice(ROLLOUT_MIGRATIONS)]
+static H: InnerMigration<Storage, String> =
+    InnerMigration::new_initialize(_initialize, "H", "H desc");
+
+#[test]
+fn rollout_buckets() {
+    let keys = (0..10_000u32).map(|i| i.to_be_bytes()).collect::<Vec<_>>();
+    assert!(keys.iter().all(|k| Rollout::bucket(k) < 100));
+    assert_eq!(Rollout::bucket(b"foo"), Rollout::bucket(b"foo"));
+
+    // Buckets should be roughly uniform.
+    let rollout = Rollout::new(10, 100);
+    let included = keys.iter().filter(|k| rollout.includes(k.as_slice(), 50)).count();
+    assert!((800..1200).contains(&included), "{included}");
+    assert!(keys.iter().all(|k| rollout.includes(k, 100)));
+    assert!(!keys.iter().any(|k| Rollout::new(0, 100).includes(k, 99)));
+}
+
+#[test]
+fn rollout() {
+    let config = MigrationConfig::default()
+        .with_migration_opts(&G, Metadata::enabled(10).with_rollout(Rollout::new(50, 20)));
+    let mut storage = Storage::new();
+    let keys = (0..100u32).map(|i| i.to_be_bytes()).collect::<Vec<_>>();
+
+    let mut migration_set = MigrationSet::load(&ROLLOUT_MIGRATIONS, config, 0).unwrap();
+    assert!(!keys.iter().any(|k| migration_set.is_active_for(&G, k)));
+
+    migration_set.update_at_height(&mut storage, 10).unwrap();
+    assert!(migration_set.is_active(&G));
+    let included = keys
+        .iter()
+        .filter(|k| migration_set.is_active_for(&G, k))
+        .collect::<Vec<_>>();
+    assert!(!included.is_empty());
+    assert!(included.len() < keys.len());
+
+    // The same keys stay included until the rollout is complete.
+    migration_set.update_at_height(&mut storage, 19).unwrap();
+    assert_eq!(
+        keys.iter()
+            .filter(|k| migration_set.is_active_for(&G, k))
+            .collect::<Vec<_>>(),
+        included
+    );
+
+    migration_set.update_at_height(&mut storage, 20).unwrap();
+    assert!(keys.iter().all(|k| migration_set.is_active_for(&G, k)));
+
+    // Restoring at a height also restores the stage of the rollout.
+    let config = MigrationConfig::default()
+        .with_migration_opts(&G, Metadata::enabled(10).with_rollout(Rollout::new(50, 20)));
+    let migration_set = MigrationSet::load(&ROLLOUT_MIGRATIONS, config, 15).unwrap();
+    assert_eq!(
+        keys.iter()
+            .filter(|k| migration_set.is_active_for(&G, k))
+            .collect::<Vec<_>>(),
+        included
+    );
+}
+
+#[test]
+fn rollout_config() {
+    let content = r#"{ "migrations": [
+        {
+            "name": "G",
+            "block_height": 10,
+            "rollout": { "percent": 25, "full_block_height": 100 }
+        }
+    ]}
+    "#;
+    let config: MigrationConfig = serde_json::from_str(content).unwrap();
+    let migrations = MigrationSet::load(&ROLLOUT_MIGRATIONS, config, 0).unwrap();
+    assert_eq!(
+        migrations["G"].metadata().rollout,
+        Some(Rollout::new(25, 100))
+    );
+    assert!(migrations["G"].metadata().extra.is_empty());
+
+    let config = MigrationConfig::default().with_migration_opts(
+        &H,
+        Metadata::enabled(10).with_rollout(Rollout::new(25, 100)),
+    );
+    assert_eq!(
+        MigrationSet::load(&ROLLOUT_MIGRATIONS, config, 0).unwrap_err(),
+        "Migration 'H' is not a trigger and cannot be rolled out"
+    );
+
+    let config = MigrationConfig::default().with_migration_opts(
+        &G,
+        Metadata::enabled(10).with_rollout(Rollout::new(101, 100)),
+    );
+    assert_eq!(
+        MigrationSet::load(&ROLLOUT_MIGRATIONS, config, 0).unwrap_err(),
+        "Migration 'G' cannot be rolled out to 101%"
+    );
+}
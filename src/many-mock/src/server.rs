@@ -76,6 +76,8 @@ impl<I: Identity> base::BaseModuleBackend for ManyMockServer<I> {
             extras: Default::default(),
             server_version: None,
             timeout: None,
+            time: None,
+            chain_id: None,
         })
     }
 }
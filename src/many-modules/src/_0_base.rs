@@ -2,9 +2,11 @@ use crate::EmptyReturn;
 use coset::{CborSerializable, CoseKey};
 use derive_builder::Builder;
 use many_identity::Address;
-use many_macros::many_module;
+use many_macros::{many_module, Cddl};
 use many_types::attributes::AttributeSet;
 use many_types::cbor::CborAny;
+use many_types::Timestamp;
+use minicbor::bytes::ByteVec;
 use minicbor::data::Type;
 use minicbor::encode::{Error, Write};
 use minicbor::{Decode, Decoder, Encode, Encoder};
@@ -14,13 +16,138 @@ use many_error::ManyError;
 #[cfg(test)]
 use mockall::{automock, predicate::*};
 
-#[derive(Clone, Debug, Decode, Encode)]
+#[derive(Cddl, Clone, Debug, Decode, Encode)]
 #[cbor(transparent)]
 pub struct Endpoints(#[n(0)] pub BTreeSet<String>);
 
 // TODO: Move this in it's own file, like other modules
 pub type HeartbeatReturn = EmptyReturn;
 
+#[derive(Cddl, Clone, Debug, Decode, Encode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct EchoArgs {
+    /// Returned as is.
+    #[n(0)]
+    pub payload: ByteVec,
+}
+
+#[derive(Cddl, Clone, Debug, Decode, Encode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct EchoReturns {
+    #[n(0)]
+    pub payload: ByteVec,
+
+    /// When the server started handling the request, in milliseconds since
+    /// the Unix epoch.
+    #[n(1)]
+    pub received_ms: u64,
+
+    /// When the server finished handling the request, in milliseconds since
+    /// the Unix epoch.
+    #[n(2)]
+    pub processed_ms: u64,
+}
+
+impl EchoReturns {
+    /// The time the server spent on the request, in milliseconds.
+    pub fn processing_ms(&self) -> u64 {
+        self.processed_ms.saturating_sub(self.received_ms)
+    }
+}
+
+/// The CDDL schemas of an endpoint's argument and return value. Types the
+/// server cannot describe are `any`, followed by a comment naming them.
+#[derive(Cddl, Clone, Debug, Decode, Encode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct EndpointSchema {
+    #[n(0)]
+    pub argument: String,
+
+    #[n(1)]
+    pub returns: String,
+}
+
+#[derive(Cddl, Clone, Debug, Default, Decode, Encode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct DescribeArgs {
+    /// Only describe these endpoints. By default, describe all of them.
+    #[n(0)]
+    pub endpoints: Option<BTreeSet<String>>,
+}
+
+#[derive(Cddl, Clone, Debug, Decode, Encode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct DescribeReturns {
+    #[n(0)]
+    pub endpoints: BTreeMap<String, EndpointSchema>,
+}
+
+/// A read-only endpoint call of a multi-query.
+#[derive(Cddl, Clone, Debug, Decode, Encode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct MultiQueryCall {
+    #[n(0)]
+    pub method: String,
+
+    /// The CBOR encoded argument of the endpoint.
+    #[n(1)]
+    pub data: ByteVec,
+}
+
+#[derive(Cddl, Clone, Debug, Default, Decode, Encode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct MultiQueryArgs {
+    #[n(0)]
+    pub calls: Vec<MultiQueryCall>,
+}
+
+/// The CBOR encoded value returned by a call of a multi-query, or its error.
+#[derive(Cddl, Clone, Debug, Decode, Encode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct MultiQueryResult {
+    #[n(0)]
+    pub data: Option<ByteVec>,
+
+    #[n(1)]
+    pub error: Option<ManyError>,
+}
+
+impl From<Result<Vec<u8>, ManyError>> for MultiQueryResult {
+    fn from(result: Result<Vec<u8>, ManyError>) -> Self {
+        match result {
+            Ok(data) => Self {
+                data: Some(data.into()),
+                error: None,
+            },
+            Err(error) => Self {
+                data: None,
+                error: Some(error),
+            },
+        }
+    }
+}
+
+impl From<MultiQueryResult> for Result<Vec<u8>, ManyError> {
+    fn from(result: MultiQueryResult) -> Self {
+        match result.error {
+            Some(error) => Err(error),
+            None => Ok(result.data.map(Into::into).unwrap_or_default()),
+        }
+    }
+}
+
+#[derive(Cddl, Clone, Debug, Decode, Encode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct MultiQueryReturns {
+    /// The height of the state all the calls were executed against.
+    #[n(0)]
+    pub height: u64,
+
+    /// The results of the calls, in order.
+    #[n(1)]
+    pub results: Vec<MultiQueryResult>,
+}
+
 #[derive(Clone, Debug, Builder)]
 pub struct Status {
     pub version: u8,
@@ -32,9 +159,21 @@ pub struct Status {
     #[builder(setter(into, strip_option), default)]
     pub server_version: Option<String>,
 
+    /// The number of seconds a request timestamp can differ from the server
+    /// time before the request is rejected.
     #[builder(setter(into, strip_option), default)]
     pub timeout: Option<u64>,
 
+    /// The current time of the server, so clients can measure how far their
+    /// clock is from it.
+    #[builder(setter(into, strip_option), default)]
+    pub time: Option<Timestamp>,
+
+    /// The network this server is on. Requests for another network are
+    /// refused.
+    #[builder(setter(into, strip_option), default)]
+    pub chain_id: Option<String>,
+
     #[builder(default)]
     pub extras: BTreeMap<String, CborAny>,
 }
@@ -49,6 +188,17 @@ impl Status {
     }
 }
 
+impl many_types::cddl::Cddl for Status {
+    fn cddl() -> String {
+        format!(
+            "{{0: uint, 1: tstr, ? 2: bstr, 3: {}, 4: {}, ? 5: tstr, ? 7: uint, ? 8: {}, ? 9: tstr, * tstr => any}}",
+            Address::cddl(),
+            AttributeSet::cddl(),
+            Timestamp::cddl()
+        )
+    }
+}
+
 impl<C> Encode<C> for Status {
     fn encode<W: Write>(&self, e: &mut Encoder<W>, _: &mut C) -> Result<(), Error<W::Error>> {
         #[rustfmt::skip]
@@ -73,6 +223,14 @@ impl<C> Encode<C> for Status {
             e.u8(7)?.encode(timeout)?;
         }
 
+        if let Some(ref time) = self.time {
+            e.u8(8)?.encode(time)?;
+        }
+
+        if let Some(ref chain_id) = self.chain_id {
+            e.u8(9)?.str(chain_id)?;
+        }
+
         for (k, v) in &self.extras {
             e.str(k.as_str())?.encode(v)?;
         }
@@ -111,6 +269,8 @@ impl<'b, C> Decode<'b, C> for Status {
                         4 => builder.attributes(d.decode()?),
                         5 => builder.server_version(d.decode::<String>()?),
                         7 => builder.timeout(d.decode::<u64>()?),
+                        8 => builder.time(d.decode::<Timestamp>()?),
+                        9 => builder.chain_id(d.decode::<String>()?),
                         _ => &mut builder,
                     };
                 }
@@ -145,9 +305,26 @@ pub trait BaseModuleBackend: Send {
     fn status(&self) -> Result<Status, ManyError>;
 }
 
+/// Network diagnosis between MANY nodes. Servers built with
+/// `ManyServer::simple` expose it alongside the base module.
+#[many_module(name = EchoModule, namespace = base, many_modules_crate = crate)]
+#[cfg_attr(test, automock)]
+pub trait EchoModuleBackend: Send {
+    fn echo(&self, args: EchoArgs) -> Result<EchoReturns, ManyError>;
+}
+
+/// Endpoint discovery. Servers built with `ManyServer::simple` expose it
+/// alongside the base module.
+#[many_module(name = DescribeModule, namespace = base, many_modules_crate = crate)]
+#[cfg_attr(test, automock)]
+pub trait DescribeModuleBackend: Send {
+    fn describe(&self, args: DescribeArgs) -> Result<DescribeReturns, ManyError>;
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::testutils::call_module;
+    use crate::testutils::{call_module, call_module_cbor};
+    use crate::ManyModule;
     use many_identity::Identity;
     use many_identity_dsa::ed25519::generate_random_ed25519_identity;
     use many_types::attributes::Attribute;
@@ -171,6 +348,8 @@ mod tests {
             }]),
             server_version: Some("1.0.0".to_string()),
             timeout: Some(300),
+            time: Some(Timestamp::new(1_000_000).unwrap()),
+            chain_id: Some("testnet".to_string()),
             extras: BTreeMap::new(),
         };
         mock.expect_status()
@@ -187,6 +366,8 @@ mod tests {
         assert_eq!(status.attributes, results.attributes);
         assert_eq!(status.server_version, results.server_version);
         assert_eq!(status.timeout, results.timeout);
+        assert_eq!(status.time, results.time);
+        assert_eq!(status.chain_id, results.chain_id);
 
         let results = Status::from_bytes(&status.to_bytes().unwrap()).unwrap();
         assert_eq!(status.version, results.version);
@@ -196,6 +377,8 @@ mod tests {
         assert_eq!(status.attributes, results.attributes);
         assert_eq!(status.server_version, results.server_version);
         assert_eq!(status.timeout, results.timeout);
+        assert_eq!(status.time, results.time);
+        assert_eq!(status.chain_id, results.chain_id);
     }
 
     #[test]
@@ -226,4 +409,60 @@ mod tests {
         let _: HeartbeatReturn =
             minicbor::decode(&call_module(1, &module, "heartbeat", "null").unwrap()).unwrap();
     }
+
+    #[test]
+    fn schemas() {
+        let module = super::EchoModule::new(Arc::new(Mutex::new(MockEchoModuleBackend::new())));
+        let schemas = module.schemas();
+        assert_eq!(
+            schemas.get("base.echo"),
+            Some(&EndpointSchema {
+                argument: "{0: bstr}".to_string(),
+                returns: "{0: bstr, 1: uint, 2: uint}".to_string(),
+            })
+        );
+
+        let module = super::BaseModule::new(Arc::new(Mutex::new(MockBaseModuleBackend::new())));
+        let status = &module.schemas()["status"];
+        assert_eq!(status.argument, "null");
+        assert_eq!(
+            status.returns,
+            "{0: uint, 1: tstr, ? 2: bstr, 3: #6.10000(bstr), 4: [* uint / [uint, * any]], \
+             ? 5: tstr, ? 7: uint, ? 8: #6.1(uint), ? 9: tstr, * tstr => any}"
+        );
+    }
+
+    #[test]
+    fn multi_query_result() {
+        let ok: Result<Vec<u8>, ManyError> = Ok(vec![1, 2, 3]);
+        let err: Result<Vec<u8>, ManyError> = Err(ManyError::unknown("error"));
+        for result in [ok, err] {
+            let encoded = minicbor::to_vec(MultiQueryResult::from(result.clone())).unwrap();
+            let decoded: MultiQueryResult = minicbor::decode(&encoded).unwrap();
+            assert_eq!(Result::<Vec<u8>, ManyError>::from(decoded), result);
+        }
+    }
+
+    #[test]
+    fn echo() {
+        let mut mock = MockEchoModuleBackend::new();
+        mock.expect_echo().times(1).returning(|args| {
+            Ok(EchoReturns {
+                payload: args.payload,
+                received_ms: 1_000,
+                processed_ms: 1_002,
+            })
+        });
+        let module = super::EchoModule::new(Arc::new(Mutex::new(mock)));
+        let args = EchoArgs {
+            payload: ByteVec::from(vec![1, 2, 3]),
+        };
+        let results: EchoReturns = minicbor::decode(
+            &call_module_cbor(1, &module, "base.echo", minicbor::to_vec(&args).unwrap()).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(results.payload, args.payload);
+        assert_eq!(results.processing_ms(), 2);
+    }
 }
@@ -1,6 +1,6 @@
 use crate::EmptyReturn;
 use crate::ManyError;
-use many_macros::many_module;
+use many_macros::{many_module, Cddl};
 use minicbor::bytes::ByteVec;
 use minicbor::{Decode, Encode};
 use std::collections::BTreeMap;
@@ -8,14 +8,14 @@ use std::collections::BTreeMap;
 #[cfg(test)]
 use mockall::{automock, predicate::*};
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct EndpointInfo {
     #[n(0)]
     pub is_command: bool,
 }
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct AbciInit {
     /// List the methods supported by this module. For performance reason, this list will be
@@ -28,7 +28,7 @@ pub struct AbciInit {
     pub endpoints: BTreeMap<String, EndpointInfo>,
 }
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct AbciInfo {
     #[n(0)]
@@ -38,14 +38,18 @@ pub struct AbciInfo {
     pub hash: ByteVec,
 }
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct AbciBlock {
     #[n(0)]
     pub time: Option<u64>,
+
+    /// The address of the validator which proposed the block.
+    #[n(1)]
+    pub proposer: Option<ByteVec>,
 }
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct AbciCommitInfo {
     #[n(0)]
@@ -55,6 +59,186 @@ pub struct AbciCommitInfo {
     pub hash: ByteVec,
 }
 
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct AbciCheckTx {
+    /// The transaction, i.e. the COSE envelope of a request.
+    #[n(0)]
+    pub tx: ByteVec,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct AbciCheckTxPriority {
+    /// The mempool priority of the transaction, higher first.
+    #[n(0)]
+    pub priority: i64,
+}
+
+/// A snapshot of the state of the backend, which new nodes can start from
+/// instead of replaying every block.
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct AbciSnapshot {
+    /// The height of the block the snapshot was taken at the end of.
+    #[n(0)]
+    pub height: u64,
+
+    /// The format of the chunks, specific to the backend.
+    #[n(1)]
+    pub format: u32,
+
+    #[n(2)]
+    pub chunks: u32,
+
+    /// The hash of the state, i.e. the app hash of the block.
+    #[n(3)]
+    pub hash: ByteVec,
+
+    #[n(4)]
+    pub metadata: ByteVec,
+}
+
+#[derive(Cddl, Clone, Debug, Default, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct AbciListSnapshots {
+    #[n(0)]
+    pub snapshots: Vec<AbciSnapshot>,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct AbciOfferSnapshot {
+    #[n(0)]
+    pub snapshot: AbciSnapshot,
+
+    /// The app hash of the block at the height of the snapshot, as verified
+    /// by the light client of the node.
+    #[n(1)]
+    pub app_hash: ByteVec,
+}
+
+#[derive(Copy, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(index_only)]
+pub enum AbciOfferSnapshotResult {
+    #[n(0)]
+    Unknown,
+
+    /// Restore this snapshot.
+    #[n(1)]
+    Accept,
+
+    /// Abort state sync.
+    #[n(2)]
+    Abort,
+
+    /// Try another snapshot.
+    #[n(3)]
+    Reject,
+
+    /// Try another snapshot, and refuse all the snapshots of this format.
+    #[n(4)]
+    RejectFormat,
+
+    /// Try another snapshot, and refuse all the snapshots of this sender.
+    #[n(5)]
+    RejectSender,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct AbciOfferSnapshotReturn {
+    #[n(0)]
+    pub result: AbciOfferSnapshotResult,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct AbciLoadSnapshotChunk {
+    #[n(0)]
+    pub height: u64,
+
+    #[n(1)]
+    pub format: u32,
+
+    /// The index of the chunk.
+    #[n(2)]
+    pub chunk: u32,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct AbciSnapshotChunk {
+    #[n(0)]
+    pub chunk: ByteVec,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct AbciApplySnapshotChunk {
+    #[n(0)]
+    pub index: u32,
+
+    #[n(1)]
+    pub chunk: ByteVec,
+
+    /// The peer the chunk was received from.
+    #[n(2)]
+    pub sender: String,
+}
+
+#[derive(Copy, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(index_only)]
+pub enum AbciApplySnapshotChunkResult {
+    #[n(0)]
+    Unknown,
+
+    /// The chunk was applied.
+    #[n(1)]
+    Accept,
+
+    /// Abort state sync.
+    #[n(2)]
+    Abort,
+
+    /// Apply this chunk again.
+    #[n(3)]
+    Retry,
+
+    /// Restore this snapshot again, from its first chunk.
+    #[n(4)]
+    RetrySnapshot,
+
+    /// Try another snapshot.
+    #[n(5)]
+    RejectSnapshot,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct AbciApplySnapshotChunkReturn {
+    #[n(0)]
+    pub result: AbciApplySnapshotChunkResult,
+
+    /// Chunks to fetch and apply again.
+    #[n(1)]
+    pub refetch_chunks: Vec<u32>,
+
+    /// Peers to stop fetching chunks from.
+    #[n(2)]
+    pub reject_senders: Vec<String>,
+}
+
+impl AbciApplySnapshotChunkReturn {
+    pub fn new(result: AbciApplySnapshotChunkResult) -> Self {
+        Self {
+            result,
+            refetch_chunks: vec![],
+            reject_senders: vec![],
+        }
+    }
+}
+
 pub type InitChainReturn = EmptyReturn;
 pub type BeginBlockReturn = EmptyReturn;
 pub type EndBlockReturn = EmptyReturn;
@@ -90,6 +274,51 @@ pub trait ManyAbciModuleBackend: std::fmt::Debug + Send + Sync {
 
     /// Called after a block. The app should take this call and serialize its state.
     fn commit(&mut self) -> Result<AbciCommitInfo, ManyError>;
+
+    /// Called when a node looks for snapshots to start from (state sync).
+    /// Backends without snapshots have none.
+    fn list_snapshots(&self) -> Result<AbciListSnapshots, ManyError> {
+        Ok(AbciListSnapshots::default())
+    }
+
+    /// Called when a new node is offered a snapshot to restore, before its
+    /// chunks are applied.
+    fn offer_snapshot(
+        &mut self,
+        _args: AbciOfferSnapshot,
+    ) -> Result<AbciOfferSnapshotReturn, ManyError> {
+        Ok(AbciOfferSnapshotReturn {
+            result: AbciOfferSnapshotResult::Reject,
+        })
+    }
+
+    /// Called when another node fetches a chunk of one of the snapshots
+    /// returned by `list_snapshots`.
+    fn load_snapshot_chunk(
+        &self,
+        _args: AbciLoadSnapshotChunk,
+    ) -> Result<AbciSnapshotChunk, ManyError> {
+        Err(ManyError::unknown("Snapshots are not supported."))
+    }
+
+    /// Called for every chunk of the accepted snapshot, in order. The state is
+    /// restored once its last chunk is applied.
+    fn apply_snapshot_chunk(
+        &mut self,
+        _args: AbciApplySnapshotChunk,
+    ) -> Result<AbciApplySnapshotChunkReturn, ManyError> {
+        Ok(AbciApplySnapshotChunkReturn::new(
+            AbciApplySnapshotChunkResult::Abort,
+        ))
+    }
+}
+
+/// Mempool priorities of transactions, from the modules of the server
+/// executing them. The ABCI frontend calls it during CheckTx.
+#[many_module(name = AbciPriorityModule, namespace = abci, many_modules_crate = crate)]
+#[cfg_attr(test, automock)]
+pub trait AbciPriorityModuleBackend: Send {
+    fn check_tx_priority(&self, args: AbciCheckTx) -> Result<AbciCheckTxPriority, ManyError>;
 }
 
 #[cfg(test)]
@@ -128,7 +357,10 @@ mod tests {
 
     #[test]
     fn begin_block() {
-        let data = AbciBlock { time: Some(1) };
+        let data = AbciBlock {
+            time: Some(1),
+            proposer: Some(vec![1; 20].into()),
+        };
         let mut mock = MockManyAbciModuleBackend::new();
         mock.expect_begin_block()
             .with(predicate::eq(data.clone()))
@@ -190,4 +422,81 @@ mod tests {
 
         assert_eq!(abci_commit_info, commit_info);
     }
+
+    #[test]
+    fn list_snapshots() {
+        let snapshots = AbciListSnapshots {
+            snapshots: vec![AbciSnapshot {
+                height: 10,
+                format: 1,
+                chunks: 3,
+                hash: vec![15u8; 32].into(),
+                metadata: vec![].into(),
+            }],
+        };
+        let mut mock = MockManyAbciModuleBackend::new();
+        mock.expect_list_snapshots()
+            .times(1)
+            .return_const(Ok(snapshots.clone()));
+        let module = super::AbciModule::new(Arc::new(Mutex::new(mock)));
+        let list: AbciListSnapshots =
+            minicbor::decode(&call_module(1, &module, "abci.listSnapshots", "null").unwrap())
+                .unwrap();
+
+        assert_eq!(list, snapshots);
+    }
+
+    #[test]
+    fn apply_snapshot_chunk() {
+        let data = AbciApplySnapshotChunk {
+            index: 0,
+            chunk: vec![1u8; 8].into(),
+            sender: "peer".to_string(),
+        };
+        let mut mock = MockManyAbciModuleBackend::new();
+        mock.expect_apply_snapshot_chunk()
+            .with(predicate::eq(data.clone()))
+            .times(1)
+            .return_const(Ok(AbciApplySnapshotChunkReturn::new(
+                AbciApplySnapshotChunkResult::Accept,
+            )));
+        let module = super::AbciModule::new(Arc::new(Mutex::new(mock)));
+        let result: AbciApplySnapshotChunkReturn = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "abci.applySnapshotChunk",
+                minicbor::to_vec(data).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(result.result, AbciApplySnapshotChunkResult::Accept);
+    }
+
+    #[test]
+    fn check_tx_priority() {
+        let data = AbciCheckTx {
+            tx: vec![1u8; 8].into(),
+        };
+        let mut mock = MockAbciPriorityModuleBackend::new();
+        mock.expect_check_tx_priority()
+            .with(predicate::eq(data.clone()))
+            .times(1)
+            .return_const(Ok(AbciCheckTxPriority { priority: 10 }));
+        let module = super::AbciPriorityModule::new(Arc::new(Mutex::new(mock)));
+        let priority: AbciCheckTxPriority = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "abci.checkTxPriority",
+                minicbor::to_vec(data).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(priority.priority, 10);
+    }
 }
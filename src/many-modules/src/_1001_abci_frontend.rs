@@ -10,6 +10,7 @@ pub type StatusReturn = EmptyReturn;
 define_attribute_many_error!(
     attribute 1001 => {
         1: pub fn abci_transport_error(details) => "ABCI interface returned an error: {details}.",
+        2: pub fn height_not_reached(required, current) => "The state did not reach the height {required} in time, current height is {current}.",
     }
 );
 
@@ -7,11 +7,13 @@ use mockall::{automock, predicate::*};
 
 pub mod errors;
 mod get;
+mod stats;
 mod store;
 pub mod types;
 
 pub use errors::*;
 pub use get::*;
+pub use stats::*;
 pub use store::*;
 pub use types::*;
 
@@ -25,6 +27,7 @@ pub trait IdStoreModuleBackend: Send {
         args: GetFromRecallPhraseArgs,
     ) -> Result<GetReturns, ManyError>;
     fn get_from_address(&self, args: GetFromAddressArgs) -> Result<GetReturns, ManyError>;
+    fn stats(&self, args: StatsArgs) -> Result<StatsReturns, ManyError>;
 }
 
 #[cfg(test)]
@@ -52,6 +55,8 @@ mod tests {
             address,
             cred_id: CredentialId(ByteVec::from(Vec::from([1u8; 16]))),
             public_key: PublicKey(ByteVec::from(public_key.to_vec().unwrap())),
+            wordlist: Some("french".to_string()),
+            namespace: Some("app".to_string()),
         };
         let ret = StoreReturns(vec!["foo".to_string(), "bar".to_string()]);
         let mut mock: MockIdStoreModuleBackend = MockIdStoreModuleBackend::new();
@@ -89,7 +94,7 @@ mod tests {
     fn get_from_recall_phrase() {
         let id = generate_random_ed25519_identity();
         let public_key = id.public_key();
-        let data = GetFromRecallPhraseArgs(vec!["foo".to_string(), "bar".to_string()]);
+        let data = GetFromRecallPhraseArgs(vec!["foo".to_string(), "bar".to_string()], None);
         let ret = GetReturns {
             cred_id: CredentialId(ByteVec::from(Vec::from([1u8; 16]))),
             public_key: PublicKey(ByteVec::from(public_key.to_vec().unwrap())),
@@ -148,4 +153,29 @@ mod tests {
         assert_eq!(get_returns.cred_id, ret.cred_id);
         assert_eq!(get_returns.public_key, ret.public_key);
     }
+
+    #[test]
+    fn stats() {
+        let data = StatsArgs {
+            namespace: Some("app".to_string()),
+        };
+        let ret = StatsReturns {
+            count: 2,
+            last_assignment: Some(many_types::Timestamp::new(1_000).unwrap()),
+        };
+        let mut mock: MockIdStoreModuleBackend = MockIdStoreModuleBackend::new();
+        mock.expect_stats()
+            .with(predicate::eq(data.clone()))
+            .times(1)
+            .return_const(Ok(ret.clone()));
+
+        let module = super::IdStoreModule::new(Arc::new(Mutex::new(mock)));
+        let stats_returns: StatsReturns = minicbor::decode(
+            &call_module_cbor(1, &module, "idstore.stats", minicbor::to_vec(data).unwrap())
+                .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(stats_returns, ret);
+    }
 }
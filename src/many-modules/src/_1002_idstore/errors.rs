@@ -7,5 +7,8 @@ define_attribute_many_error!(
         3: pub fn invalid_address(addr) => "The identity '{addr}' is invalid.",
         4: pub fn invalid_credential_id(cred_id) => "The credential ID '{cred_id}' is invalid.",
         5: pub fn recall_phrase_generation_failed() => "The recall phrase generation failed.",
+        6: pub fn unknown_wordlist(name) => "Unknown recall phrase wordlist '{name}'.",
+        7: pub fn invalid_namespace(namespace) => "Invalid recall phrase namespace '{namespace}'.",
+        8: pub fn seed_decreased(current, new) => "The recall phrase seed cannot decrease from {current} to {new}.",
     }
 );
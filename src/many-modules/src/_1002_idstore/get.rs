@@ -3,17 +3,19 @@ use super::{
     PublicKey,
 };
 use many_identity::Address;
+use many_macros::Cddl;
 use minicbor::{Decode, Encode};
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+/// A recall phrase and its namespace, the default one if unspecified.
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
-pub struct GetFromRecallPhraseArgs(#[n(0)] pub RecallPhrase);
+pub struct GetFromRecallPhraseArgs(#[n(0)] pub RecallPhrase, #[n(1)] pub Option<String>);
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct GetFromAddressArgs(#[n(0)] pub Address);
 
-#[derive(Clone, Debug, Encode, Decode)]
+#[derive(Cddl, Clone, Debug, Encode, Decode)]
 #[cbor(map)]
 pub struct GetReturns {
     #[n(0)]
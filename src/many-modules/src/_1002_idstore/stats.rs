@@ -0,0 +1,23 @@
+use many_macros::Cddl;
+use many_types::Timestamp;
+use minicbor::{Decode, Encode};
+
+#[derive(Cddl, Clone, Debug, Default, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct StatsArgs {
+    /// The namespace of the statistics, the default one if unspecified.
+    #[n(0)]
+    pub namespace: Option<String>,
+}
+
+#[derive(Cddl, Clone, Debug, Default, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct StatsReturns {
+    /// The number of credentials stored in the namespace.
+    #[n(0)]
+    pub count: u64,
+
+    /// When a recall phrase was last assigned in the namespace, if ever.
+    #[n(1)]
+    pub last_assignment: Option<Timestamp>,
+}
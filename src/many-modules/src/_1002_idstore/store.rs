@@ -1,8 +1,9 @@
 use super::types::{CredentialId, PublicKey, RecallPhrase};
 use many_identity::Address;
+use many_macros::Cddl;
 use minicbor::{Decode, Encode};
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct StoreArgs {
     #[n(0)]
@@ -13,8 +14,18 @@ pub struct StoreArgs {
 
     #[n(2)]
     pub public_key: PublicKey,
+
+    /// The wordlist of the recall phrase, e.g. `english` or `french`. The
+    /// server's default wordlist is used if unspecified.
+    #[n(3)]
+    pub wordlist: Option<String>,
+
+    /// The namespace of the recall phrase, so applications sharing a server
+    /// have their own recall phrases. The default namespace if unspecified.
+    #[n(4)]
+    pub namespace: Option<String>,
 }
 
-#[derive(Clone, Debug, Encode, Decode)]
+#[derive(Cddl, Clone, Debug, Encode, Decode)]
 #[cbor(map)]
 pub struct StoreReturns(#[n(0)] pub RecallPhrase);
@@ -1,11 +1,31 @@
+use super::errors;
+use many_error::ManyError;
+use many_macros::Cddl;
 use minicbor::{bytes::ByteVec, Decode, Encode};
 
 pub type RecallPhrase = Vec<String>;
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(transparent)]
 pub struct CredentialId(#[n(0)] pub ByteVec);
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(transparent)]
 pub struct PublicKey(#[n(0)] pub ByteVec);
+
+/// Maximum length of a recall phrase namespace.
+pub const MAX_NAMESPACE_LENGTH: usize = 64;
+
+/// Verify a recall phrase namespace is valid, i.e. between 1 and
+/// [MAX_NAMESPACE_LENGTH] ASCII alphanumeric characters, `-`, `_` or `.`.
+pub fn check_namespace(namespace: &str) -> Result<(), ManyError> {
+    if namespace.is_empty()
+        || namespace.len() > MAX_NAMESPACE_LENGTH
+        || !namespace
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(errors::invalid_namespace(namespace));
+    }
+    Ok(())
+}
@@ -1,8 +1,8 @@
 use crate::EmptyReturn;
 use many_error::ManyError;
 use many_identity::Address;
-use many_macros::many_module;
-use many_types::{cbor_type_decl, ledger, AttributeRelatedIndex, Memo};
+use many_macros::{many_module, Cddl};
+use many_types::{cbor_type_decl, ledger, AttributeRelatedIndex, Memo, Timestamp};
 use minicbor::{Decode, Encode};
 
 pub mod extended_info;
@@ -51,11 +51,51 @@ cbor_type_decl!(
         1 => extended_info: Vec<AttributeRelatedIndex>, // TODO: This thing should be of at least length 1
         2 => memo: Option<Memo>,
     }
+
+    pub struct TokenAcceptOwnershipArgs {
+        0 => symbol: ledger::Symbol,
+        1 => memo: Option<Memo>,
+    }
+
+    pub struct TokenCancelOwnershipOfferArgs {
+        0 => symbol: ledger::Symbol,
+        1 => memo: Option<Memo>,
+    }
+
+    pub struct TokenOwnershipOffersArgs {
+        0 => symbol: Option<ledger::Symbol>,
+        1 => new_owner: Option<Address>,
+    }
+
+    pub struct TokenOwnershipOffersReturns {
+        0 => offers: Vec<TokenOwnershipOffer>,
+    }
 );
 
 pub type TokenUpdateReturns = EmptyReturn;
 pub type TokenAddExtendedInfoReturns = EmptyReturn;
 pub type TokenRemoveExtendedInfoReturns = EmptyReturn;
+pub type TokenAcceptOwnershipReturns = EmptyReturn;
+pub type TokenCancelOwnershipOfferReturns = EmptyReturn;
+
+/// A pending transfer of the ownership of a token. The new owner must accept
+/// it before it expires, or the token keeps its owner.
+#[derive(Cddl, Clone, Debug, Decode, Encode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct TokenOwnershipOffer {
+    #[n(0)]
+    pub symbol: ledger::Symbol,
+
+    /// The owner of the token when the offer was made.
+    #[n(1)]
+    pub owner: Address,
+
+    #[n(2)]
+    pub new_owner: Address,
+
+    #[n(3)]
+    pub expires: Timestamp,
+}
 
 #[many_module(name = LedgerTokensModule, id = 11, namespace = tokens, many_modules_crate = crate)]
 #[cfg_attr(test, mockall::automock)]
@@ -89,16 +129,39 @@ pub trait LedgerTokensModuleBackend: Send {
         sender: &Address,
         args: TokenRemoveExtendedInfoArgs,
     ) -> Result<TokenRemoveExtendedInfoReturns, ManyError>;
+
+    /// Accept the ownership of a token offered by its owner with `update`.
+    #[many(deny_anonymous)]
+    fn accept_ownership(
+        &mut self,
+        sender: &Address,
+        args: TokenAcceptOwnershipArgs,
+    ) -> Result<TokenAcceptOwnershipReturns, ManyError>;
+
+    #[many(deny_anonymous)]
+    fn cancel_ownership_offer(
+        &mut self,
+        sender: &Address,
+        args: TokenCancelOwnershipOfferArgs,
+    ) -> Result<TokenCancelOwnershipOfferReturns, ManyError>;
+
+    /// The ownership offers which did not expire.
+    fn ownership_offers(
+        &self,
+        sender: &Address,
+        args: TokenOwnershipOffersArgs,
+    ) -> Result<TokenOwnershipOffersReturns, ManyError>;
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::ledger::extended_info::TokenExtendedInfo;
-    use crate::testutils::call_module_cbor;
+    use crate::testutils::{assert_canonical_round_trip, call_module_cbor};
     use many_identity::testing::identity;
     use many_types::ledger::{TokenInfo, TokenInfoSummary, TokenInfoSupply};
     use mockall::predicate::eq;
+    use proptest::prelude::*;
     use std::sync::{Arc, Mutex};
 
     #[test]
@@ -226,4 +289,108 @@ mod tests {
 
         assert_eq!(rm_ext_info_returns, TokenRemoveExtendedInfoReturns {});
     }
+
+    #[test]
+    fn accept_ownership() {
+        let mut mock = MockLedgerTokensModuleBackend::new();
+        let data = TokenAcceptOwnershipArgs {
+            symbol: identity(2),
+            memo: None,
+        };
+        mock.expect_accept_ownership()
+            .with(eq(identity(1)), eq(data.clone()))
+            .times(1)
+            .returning(|_, _| Ok(TokenAcceptOwnershipReturns {}));
+        let module = super::LedgerTokensModule::new(Arc::new(Mutex::new(mock)));
+
+        let accept_returns: TokenAcceptOwnershipReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "tokens.acceptOwnership",
+                minicbor::to_vec(data).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(accept_returns, TokenAcceptOwnershipReturns {});
+    }
+
+    #[test]
+    fn ownership_offers() {
+        let mut mock = MockLedgerTokensModuleBackend::new();
+        let data = TokenOwnershipOffersArgs {
+            symbol: None,
+            new_owner: Some(identity(3)),
+        };
+        let offer = TokenOwnershipOffer {
+            symbol: identity(2),
+            owner: identity(1),
+            new_owner: identity(3),
+            expires: Timestamp::new(1_000).unwrap(),
+        };
+        let returns = TokenOwnershipOffersReturns {
+            offers: vec![offer],
+        };
+        mock.expect_ownership_offers()
+            .with(eq(identity(1)), eq(data.clone()))
+            .times(1)
+            .return_const(Ok(returns.clone()));
+        let module = super::LedgerTokensModule::new(Arc::new(Mutex::new(mock)));
+
+        let offers_returns: TokenOwnershipOffersReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "tokens.ownershipOffers",
+                minicbor::to_vec(data).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(offers_returns, returns);
+    }
+
+    proptest! {
+        #[test]
+        fn canonical_info(
+            name in "[A-Za-z0-9 ]{0,50}",
+            ticker in "[A-Z]{3,5}",
+            decimals: u64,
+            total: u64,
+            maximum in proptest::option::of(any::<u64>()),
+            owner in proptest::option::of(0u32..1000),
+        ) {
+            assert_canonical_round_trip(&TokenInfo {
+                symbol: identity(1000),
+                summary: TokenInfoSummary {
+                    name,
+                    ticker,
+                    decimals,
+                },
+                supply: TokenInfoSupply {
+                    total: total.into(),
+                    circulating: total.into(),
+                    maximum: maximum.map(Into::into),
+                },
+                owner: owner.map(identity),
+            });
+        }
+
+        #[test]
+        fn canonical_ownership_offer(
+            owner in 0u32..1000,
+            new_owner in 0u32..1000,
+            expires in 0..u32::MAX as u64,
+        ) {
+            assert_canonical_round_trip(&TokenOwnershipOffer {
+                symbol: identity(1000),
+                owner: identity(owner),
+                new_owner: identity(new_owner),
+                expires: Timestamp::new(expires).unwrap(),
+            });
+        }
+    }
 }
@@ -1,3 +1,4 @@
+use attachment::{Attachment, Attachments};
 use many_error::ManyError;
 use many_types::{AttributeRelatedIndex, Memo};
 use minicbor::encode::{Error, Write};
@@ -7,6 +8,7 @@ use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 use visual_logo::VisualTokenLogo;
 
+pub mod attachment;
 pub mod visual_logo;
 
 #[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
@@ -14,6 +16,7 @@ pub mod visual_logo;
 pub enum ExtendedInfoKey {
     Memo = 0,
     VisualLogo = 1,
+    Attachments = 2,
 }
 
 impl From<ExtendedInfoKey> for AttributeRelatedIndex {
@@ -37,6 +40,7 @@ impl TryFrom<&AttributeRelatedIndex> for ExtendedInfoKey {
         match value.attribute {
             0 => Ok(Self::Memo),
             1 => Ok(Self::VisualLogo),
+            2 => Ok(Self::Attachments),
             _ => Err(()),
         }
     }
@@ -61,6 +65,7 @@ impl<'b, C> Decode<'b, C> for ExtendedInfoKey {
 enum ExtendedInfo {
     Memo(Arc<Memo>),
     VisualLogo(Arc<VisualTokenLogo>),
+    Attachments(Arc<Attachments>),
 }
 
 impl ExtendedInfo {
@@ -68,6 +73,7 @@ impl ExtendedInfo {
         match self {
             ExtendedInfo::Memo(_) => ExtendedInfoKey::Memo,
             ExtendedInfo::VisualLogo(_) => ExtendedInfoKey::VisualLogo,
+            ExtendedInfo::Attachments(_) => ExtendedInfoKey::Attachments,
         }
     }
 
@@ -77,6 +83,9 @@ impl ExtendedInfo {
             ExtendedInfo::VisualLogo(_) => {
                 AttributeRelatedIndex::new(ExtendedInfoKey::VisualLogo as u32)
             }
+            ExtendedInfo::Attachments(_) => {
+                AttributeRelatedIndex::new(ExtendedInfoKey::Attachments as u32)
+            }
         }
     }
 }
@@ -123,18 +132,32 @@ impl TokenExtendedInfo {
         self.inner.insert(value.as_key(), value);
     }
 
+    /// Whether the extended info contains an index. The index of a single
+    /// attachment is the attachments index followed by its own index.
     pub fn contains_index(&self, index: &AttributeRelatedIndex) -> Result<bool, ManyError> {
         let key = ExtendedInfoKey::try_from(index).map_err(|_| {
             ManyError::unknown("Unable to convert AttributeRelatedIndex to ExtendedInfoKey")
         })?;
-        Ok(self.inner.contains_key(&key))
+        Ok(match (self.inner.get(&key), index.indices()) {
+            (Some(ExtendedInfo::Attachments(a)), [i]) => a.contains_key(i),
+            (info, _) => info.is_some(),
+        })
     }
 
+    /// Remove an index. Removing the index of a single attachment keeps the
+    /// other attachments.
     pub fn remove(&mut self, index: &AttributeRelatedIndex) -> Result<(), ManyError> {
         let key = ExtendedInfoKey::try_from(index).map_err(|_| {
             ManyError::unknown("Unable to convert AttributeRelatedIndex to ExtendedInfoKey")
         })?;
-        self.inner.remove(&key);
+        match (self.inner.get_mut(&key), index.indices()) {
+            (Some(ExtendedInfo::Attachments(a)), [i]) => {
+                Arc::make_mut(a).remove(*i);
+            }
+            _ => {
+                self.inner.remove(&key);
+            }
+        }
         Ok(())
     }
 
@@ -168,6 +191,29 @@ impl TokenExtendedInfo {
         Ok(self)
     }
 
+    pub fn with_attachments(mut self, attachments: Attachments) -> Result<Self, ManyError> {
+        self.insert(ExtendedInfo::Attachments(Arc::new(attachments)));
+        Ok(self)
+    }
+
+    /// Add an attachment, replacing the one at the same index if any.
+    pub fn with_attachment(
+        mut self,
+        index: u32,
+        attachment: Attachment,
+    ) -> Result<Self, ManyError> {
+        match self.inner.get_mut(&ExtendedInfoKey::Attachments) {
+            Some(ExtendedInfo::Attachments(a)) => {
+                Arc::make_mut(a).insert(index, attachment);
+            }
+            _ => {
+                let attachments = Attachments::from_iter([(index, attachment)]);
+                self.insert(ExtendedInfo::Attachments(Arc::new(attachments)));
+            }
+        }
+        Ok(self)
+    }
+
     pub fn memo(&self) -> Option<&Memo> {
         self.inner
             .get(&ExtendedInfoKey::Memo)
@@ -201,6 +247,15 @@ impl TokenExtendedInfo {
                 _ => None,
             })
     }
+
+    pub fn attachments(&self) -> Option<&Attachments> {
+        self.inner
+            .get(&ExtendedInfoKey::Attachments)
+            .and_then(|e| match e {
+                ExtendedInfo::Attachments(a) => Some(a.as_ref()),
+                _ => None,
+            })
+    }
 }
 
 impl Default for TokenExtendedInfo {
@@ -222,6 +277,9 @@ impl<C> Encode<C> for TokenExtendedInfo {
                 ExtendedInfo::VisualLogo(v) => {
                     e.encode_with(v.as_ref(), ctx)?;
                 }
+                ExtendedInfo::Attachments(a) => {
+                    e.encode_with(a.as_ref(), ctx)?;
+                }
             }
         }
         Ok(())
@@ -246,6 +304,10 @@ impl<'b, C> Decode<'b, C> for TokenExtendedInfo {
                     let visual_logo: VisualTokenLogo = d.decode_with(ctx)?;
                     inner.insert(key, ExtendedInfo::VisualLogo(Arc::new(visual_logo)));
                 }
+                ExtendedInfoKey::Attachments => {
+                    let attachments: Attachments = d.decode_with(ctx)?;
+                    inner.insert(key, ExtendedInfo::Attachments(Arc::new(attachments)));
+                }
             }
         }
 
@@ -267,6 +329,8 @@ mod tests {
             .try_with_memo("Foobar".to_string())
             .unwrap()
             .with_visual_logo(logos)
+            .unwrap()
+            .with_attachment(0, Attachment::inline("application/json", b"{}".to_vec()))
             .unwrap();
 
         let enc = minicbor::to_vec(&ext_info).unwrap();
@@ -348,4 +412,32 @@ mod tests {
         assert!(ext_info.visual_logo_mut().is_some());
         assert_eq!(ext_info.visual_logo_mut().unwrap(), &logos);
     }
+
+    #[test]
+    fn remove_attachment() {
+        let attachments_index = AttributeRelatedIndex::from(ExtendedInfoKey::Attachments);
+        let mut ext_info = TokenExtendedInfo::default()
+            .with_attachment(0, Attachment::inline("text/plain", b"foo".to_vec()))
+            .unwrap()
+            .with_attachment(1, Attachment::inline("text/plain", b"bar".to_vec()))
+            .unwrap();
+        assert!(ext_info
+            .contains_index(&attachments_index.clone().with_index(1))
+            .unwrap());
+        assert!(!ext_info
+            .contains_index(&attachments_index.clone().with_index(2))
+            .unwrap());
+
+        ext_info
+            .remove(&attachments_index.clone().with_index(1))
+            .unwrap();
+        assert_eq!(
+            ext_info.attachments().unwrap().keys().collect::<Vec<_>>(),
+            [&0]
+        );
+
+        ext_info.remove(&attachments_index).unwrap();
+        assert!(ext_info.attachments().is_none());
+        assert!(!ext_info.contains_index(&attachments_index).unwrap());
+    }
 }
@@ -0,0 +1,201 @@
+use many_macros::Cddl;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::ops::Deref;
+
+/// Maximum number of attachments of a token.
+pub const MAX_ATTACHMENTS: usize = 16;
+
+/// Maximum size of the content of an attachment stored on the ledger, in bytes.
+pub const MAX_ATTACHMENT_SIZE: usize = 64 * 1024;
+
+/// Maximum length of the content type or URL of an attachment.
+pub const MAX_ATTACHMENT_STRING_LENGTH: usize = 256;
+
+/// Size of the hash of an attachment (SHA-256).
+pub const ATTACHMENT_HASH_SIZE: usize = 32;
+
+/// A typed blob attached to a token, e.g. a whitepaper or JSON metadata,
+/// identified by the SHA-256 hash of its content. The content is either
+/// stored on the ledger, or only referenced with an optional URL of where to
+/// find it.
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct Attachment {
+    /// The MIME type of the content.
+    #[n(0)]
+    pub content_type: String,
+
+    #[n(1)]
+    pub hash: ByteVec,
+
+    #[n(2)]
+    pub data: Option<ByteVec>,
+
+    #[n(3)]
+    pub url: Option<String>,
+}
+
+impl Attachment {
+    pub fn hash_of(data: &[u8]) -> Vec<u8> {
+        Sha256::digest(data).to_vec()
+    }
+
+    /// An attachment stored on the ledger.
+    pub fn inline(content_type: impl AsRef<str>, data: Vec<u8>) -> Self {
+        Self {
+            content_type: content_type.as_ref().into(),
+            hash: Self::hash_of(&data).into(),
+            data: Some(data.into()),
+            url: None,
+        }
+    }
+
+    /// An attachment stored outside the ledger.
+    pub fn reference(content_type: impl AsRef<str>, hash: Vec<u8>, url: Option<String>) -> Self {
+        Self {
+            content_type: content_type.as_ref().into(),
+            hash: hash.into(),
+            data: None,
+            url,
+        }
+    }
+
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    /// Check the attachment against the size limits, and its content against
+    /// its hash. Returns the reason it is invalid.
+    pub fn check(&self) -> Result<(), String> {
+        if self.hash.len() != ATTACHMENT_HASH_SIZE {
+            return Err(format!(
+                "hash must be {ATTACHMENT_HASH_SIZE} bytes, was {}",
+                self.hash.len()
+            ));
+        }
+        if self.content_type.len() > MAX_ATTACHMENT_STRING_LENGTH {
+            return Err(format!(
+                "content type over limit ({MAX_ATTACHMENT_STRING_LENGTH})"
+            ));
+        }
+        if self.url.as_ref().map_or(0, String::len) > MAX_ATTACHMENT_STRING_LENGTH {
+            return Err(format!("URL over limit ({MAX_ATTACHMENT_STRING_LENGTH})"));
+        }
+        if let Some(data) = &self.data {
+            if data.len() > MAX_ATTACHMENT_SIZE {
+                return Err(format!(
+                    "content size ({}) over limit ({MAX_ATTACHMENT_SIZE})",
+                    data.len()
+                ));
+            }
+            if Self::hash_of(data) != self.hash.as_slice() {
+                return Err("content does not match its hash".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The attachments of a token, by index. An attachment can be removed with
+/// the index of the attachments extended info followed by its own index.
+#[derive(Cddl, Default, Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(transparent)]
+pub struct Attachments(#[n(0)] BTreeMap<u32, Attachment>);
+
+impl Attachments {
+    pub fn new() -> Self {
+        Self(Default::default())
+    }
+
+    pub fn insert(&mut self, index: u32, attachment: Attachment) -> Option<Attachment> {
+        self.0.insert(index, attachment)
+    }
+
+    pub fn remove(&mut self, index: u32) -> Option<Attachment> {
+        self.0.remove(&index)
+    }
+
+    /// Check the attachments against the limits. Returns the reason they are
+    /// invalid.
+    pub fn check(&self) -> Result<(), String> {
+        if self.0.len() > MAX_ATTACHMENTS {
+            return Err(format!(
+                "too many attachments ({}), limit is {MAX_ATTACHMENTS}",
+                self.0.len()
+            ));
+        }
+        self.0.values().try_for_each(Attachment::check)
+    }
+}
+
+impl Deref for Attachments {
+    type Target = BTreeMap<u32, Attachment>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl FromIterator<(u32, Attachment)> for Attachments {
+    fn from_iter<T: IntoIterator<Item = (u32, Attachment)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode() {
+        let attachments = Attachments::from_iter([
+            (0, Attachment::inline("application/json", b"{}".to_vec())),
+            (
+                3,
+                Attachment::reference("application/pdf", vec![1u8; 32], None)
+                    .with_url("https://example.com/whitepaper.pdf"),
+            ),
+        ]);
+
+        let enc = minicbor::to_vec(&attachments).unwrap();
+        let res: Attachments = minicbor::decode(&enc).unwrap();
+        assert_eq!(res, attachments);
+    }
+
+    #[test]
+    fn check() {
+        assert!(Attachment::inline("application/json", b"{}".to_vec())
+            .check()
+            .is_ok());
+        assert!(
+            Attachment::reference("application/pdf", vec![1u8; 32], None)
+                .check()
+                .is_ok()
+        );
+
+        let mut tampered = Attachment::inline("application/json", b"{}".to_vec());
+        tampered.data = Some(b"[]".to_vec().into());
+        assert!(tampered.check().is_err());
+
+        assert!(
+            Attachment::reference("application/pdf", vec![1u8; 16], None)
+                .check()
+                .is_err()
+        );
+        assert!(
+            Attachment::inline("application/octet-stream", vec![0; MAX_ATTACHMENT_SIZE + 1])
+                .check()
+                .is_err()
+        );
+        assert!(Attachment::inline("a".repeat(257), vec![]).check().is_err());
+
+        let too_many = (0..=MAX_ATTACHMENTS as u32)
+            .map(|i| (i, Attachment::inline("text/plain", vec![])))
+            .collect::<Attachments>();
+        assert!(too_many.check().is_err());
+    }
+}
@@ -1,10 +1,54 @@
+use many_macros::Cddl;
 use minicbor::{decode, encode, Decode, Decoder, Encode, Encoder};
 use num_enum::TryFromPrimitive;
 use std::cmp::Ordering;
-use std::collections::VecDeque;
+use std::collections::{BTreeSet, VecDeque};
 use std::ops::Deref;
+use std::str::FromStr;
 use std::sync::Arc;
 
+/// Maximum number of logos of a token.
+pub const MAX_LOGOS: usize = 8;
+
+/// Maximum size of an image logo, in bytes.
+pub const MAX_IMAGE_SIZE: usize = 64 * 1024;
+
+/// Maximum length of the content type or URI of a logo.
+pub const MAX_LOGO_STRING_LENGTH: usize = 256;
+
+/// Size of the hash of an image reference (SHA-256).
+pub const IMAGE_REF_HASH_SIZE: usize = 32;
+
+/// Where a logo should be used. A logo without a purpose can be used anywhere.
+#[derive(Copy, Clone, Debug, Decode, Encode, Ord, PartialOrd, Eq, PartialEq)]
+#[cbor(index_only)]
+pub enum LogoPurpose {
+    /// For light backgrounds.
+    #[n(0)]
+    Light,
+
+    /// For dark backgrounds.
+    #[n(1)]
+    Dark,
+
+    /// For small sizes, e.g. icons.
+    #[n(2)]
+    Small,
+}
+
+impl FromStr for LogoPurpose {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "light" => Ok(Self::Light),
+            "dark" => Ok(Self::Dark),
+            "small" => Ok(Self::Small),
+            _ => Err(format!("Unknown logo purpose '{s}'.")),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SingleVisualTokenLogo {
     /// A single character. This is limited to a single character for now.
@@ -12,6 +56,15 @@ pub enum SingleVisualTokenLogo {
     Image {
         content_type: String,
         binary: Arc<Vec<u8>>,
+        purposes: BTreeSet<LogoPurpose>,
+    },
+    /// An image stored outside the ledger, addressed by the SHA-256 hash of
+    /// its content. The URI is a hint of where to find it.
+    ImageRef {
+        content_type: String,
+        hash: Vec<u8>,
+        uri: Option<String>,
+        purposes: BTreeSet<LogoPurpose>,
     },
 }
 
@@ -24,6 +77,9 @@ pub enum SingleVisualTokenLogoKey {
 
     #[n(1)]
     Image = 1,
+
+    #[n(2)]
+    ImageRef = 2,
 }
 
 impl SingleVisualTokenLogo {
@@ -31,6 +87,7 @@ impl SingleVisualTokenLogo {
         match self {
             SingleVisualTokenLogo::UnicodeChar(_) => SingleVisualTokenLogoKey::UnicodeChar,
             SingleVisualTokenLogo::Image { .. } => SingleVisualTokenLogoKey::Image,
+            SingleVisualTokenLogo::ImageRef { .. } => SingleVisualTokenLogoKey::ImageRef,
         }
     }
     pub fn char(c: char) -> Self {
@@ -40,8 +97,78 @@ impl SingleVisualTokenLogo {
         Self::Image {
             content_type: content_type.as_ref().into(),
             binary: Arc::new(binary),
+            purposes: BTreeSet::new(),
+        }
+    }
+    pub fn image_ref(content_type: impl AsRef<str>, hash: Vec<u8>, uri: Option<String>) -> Self {
+        Self::ImageRef {
+            content_type: content_type.as_ref().into(),
+            hash,
+            uri,
+            purposes: BTreeSet::new(),
         }
     }
+
+    /// Set the purposes of an image. Characters have no purpose.
+    pub fn with_purposes(mut self, new_purposes: impl IntoIterator<Item = LogoPurpose>) -> Self {
+        match &mut self {
+            Self::UnicodeChar(_) => {}
+            Self::Image { purposes, .. } | Self::ImageRef { purposes, .. } => {
+                *purposes = new_purposes.into_iter().collect();
+            }
+        }
+        self
+    }
+
+    pub fn purposes(&self) -> Option<&BTreeSet<LogoPurpose>> {
+        match self {
+            Self::UnicodeChar(_) => None,
+            Self::Image { purposes, .. } | Self::ImageRef { purposes, .. } => Some(purposes),
+        }
+    }
+
+    fn check(&self) -> Result<(), String> {
+        let content_type = match self {
+            Self::UnicodeChar(_) => return Ok(()),
+            Self::Image {
+                content_type,
+                binary,
+                ..
+            } => {
+                if binary.len() > MAX_IMAGE_SIZE {
+                    return Err(format!(
+                        "image size ({}) over limit ({MAX_IMAGE_SIZE})",
+                        binary.len()
+                    ));
+                }
+                content_type
+            }
+            Self::ImageRef {
+                content_type,
+                hash,
+                uri,
+                ..
+            } => {
+                if hash.len() != IMAGE_REF_HASH_SIZE {
+                    return Err(format!(
+                        "image hash must be {IMAGE_REF_HASH_SIZE} bytes, was {}",
+                        hash.len()
+                    ));
+                }
+                if uri.as_ref().map_or(0, String::len) > MAX_LOGO_STRING_LENGTH {
+                    return Err(format!("URI over limit ({MAX_LOGO_STRING_LENGTH})"));
+                }
+                content_type
+            }
+        };
+
+        if content_type.len() > MAX_LOGO_STRING_LENGTH {
+            return Err(format!(
+                "content type over limit ({MAX_LOGO_STRING_LENGTH})"
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl<C> Encode<C> for SingleVisualTokenLogo {
@@ -61,14 +188,40 @@ impl<C> Encode<C> for SingleVisualTokenLogo {
             SingleVisualTokenLogo::Image {
                 content_type,
                 binary,
+                purposes,
             } => {
-                e.map(3)?
+                // Logos without purposes keep their original encoding.
+                e.map(if purposes.is_empty() { 3 } else { 4 })?
                     .u8(0)?
                     .u8(SingleVisualTokenLogoKey::Image as u8)?
                     .u8(1)?
                     .str(content_type)?
                     .u8(2)?
                     .bytes(binary)?;
+                if !purposes.is_empty() {
+                    e.u8(3)?.encode(purposes)?;
+                }
+            }
+            SingleVisualTokenLogo::ImageRef {
+                content_type,
+                hash,
+                uri,
+                purposes,
+            } => {
+                let len = 3 + u64::from(!purposes.is_empty()) + u64::from(uri.is_some());
+                e.map(len)?
+                    .u8(0)?
+                    .u8(SingleVisualTokenLogoKey::ImageRef as u8)?
+                    .u8(1)?
+                    .str(content_type)?
+                    .u8(2)?
+                    .bytes(hash)?;
+                if !purposes.is_empty() {
+                    e.u8(3)?.encode(purposes)?;
+                }
+                if let Some(uri) = uri {
+                    e.u8(4)?.str(uri)?;
+                }
             }
         }
         Ok(())
@@ -99,9 +252,12 @@ impl<'b, C> Decode<'b, C> for SingleVisualTokenLogo {
                     })?))
                 }
             }
-            SingleVisualTokenLogoKey::Image => {
+            SingleVisualTokenLogoKey::Image | SingleVisualTokenLogoKey::ImageRef => {
+                let is_ref = key == SingleVisualTokenLogoKey::ImageRef;
                 let mut content_type = None;
-                let mut binary = None;
+                let mut data = None;
+                let mut purposes = BTreeSet::new();
+                let mut uri = None;
                 let l_ = l; // Silence warning
                 for _ in 1..l_ {
                     match d.u8()? {
@@ -109,7 +265,13 @@ impl<'b, C> Decode<'b, C> for SingleVisualTokenLogo {
                             content_type = Some(d.str()?.to_string());
                         }
                         2 => {
-                            binary = Some(d.bytes()?.to_vec());
+                            data = Some(d.bytes()?.to_vec());
+                        }
+                        3 => {
+                            purposes = d.decode()?;
+                        }
+                        4 if is_ref => {
+                            uri = Some(d.str()?.to_string());
                         }
                         i => {
                             return Err(decode::Error::message(format!("Unknown key {i}")));
@@ -117,10 +279,21 @@ impl<'b, C> Decode<'b, C> for SingleVisualTokenLogo {
                     }
                     l -= 1;
                 }
-                Ok(Self::image(
-                    content_type.ok_or_else(|| decode::Error::message("Missing content type."))?,
-                    binary.ok_or_else(|| decode::Error::message("Missing binary data."))?,
-                ))
+                let content_type =
+                    content_type.ok_or_else(|| decode::Error::message("Missing content type."))?;
+                let this = if is_ref {
+                    Self::image_ref(
+                        content_type,
+                        data.ok_or_else(|| decode::Error::message("Missing image hash."))?,
+                        uri,
+                    )
+                } else {
+                    Self::image(
+                        content_type,
+                        data.ok_or_else(|| decode::Error::message("Missing binary data."))?,
+                    )
+                };
+                Ok(this.with_purposes(purposes))
             }
         }?;
         if l != 1 {
@@ -131,7 +304,7 @@ impl<'b, C> Decode<'b, C> for SingleVisualTokenLogo {
     }
 }
 
-#[derive(Default, Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[derive(Cddl, Default, Clone, Debug, Encode, Decode, PartialEq, Eq)]
 #[cbor(transparent)]
 pub struct VisualTokenLogo(#[n(0)] VecDeque<SingleVisualTokenLogo>);
 
@@ -154,6 +327,38 @@ impl VisualTokenLogo {
             .push_back(SingleVisualTokenLogo::image(content_type, data))
     }
 
+    pub fn push_front(&mut self, logo: SingleVisualTokenLogo) {
+        self.0.push_front(logo)
+    }
+    pub fn push_back(&mut self, logo: SingleVisualTokenLogo) {
+        self.0.push_back(logo)
+    }
+
+    /// The logo to use for a purpose: the first logo with this purpose, or
+    /// else the first logo without any purpose.
+    pub fn for_purpose(&self, purpose: LogoPurpose) -> Option<&SingleVisualTokenLogo> {
+        self.0
+            .iter()
+            .find(|logo| logo.purposes().map_or(false, |p| p.contains(&purpose)))
+            .or_else(|| {
+                self.0
+                    .iter()
+                    .find(|logo| logo.purposes().map_or(true, BTreeSet::is_empty))
+            })
+    }
+
+    /// Check the logos against the size limits. Returns the reason they are
+    /// invalid.
+    pub fn check(&self) -> Result<(), String> {
+        if self.0.len() > MAX_LOGOS {
+            return Err(format!(
+                "too many logos ({}), limit is {MAX_LOGOS}",
+                self.0.len()
+            ));
+        }
+        self.0.iter().try_for_each(SingleVisualTokenLogo::check)
+    }
+
     pub fn sort(
         &mut self,
         sorting_fn: impl Fn(&SingleVisualTokenLogo, &SingleVisualTokenLogo) -> Ordering,
@@ -198,6 +403,7 @@ mod tests {
             SingleVisualTokenLogo::Image {
                 content_type,
                 binary,
+                ..
             } => {
                 assert_eq!(content_type, "png");
                 assert_eq!(*binary, vec![1u8; 10]);
@@ -268,4 +474,79 @@ mod tests {
             SingleVisualTokenLogo::Image { .. }
         ));
     }
+
+    #[test]
+    fn encode_decode_purposes() {
+        let logo = SingleVisualTokenLogo::image("png", vec![1u8; 10]);
+        let tagged = logo
+            .clone()
+            .with_purposes([LogoPurpose::Dark, LogoPurpose::Small]);
+
+        // Images without purposes keep their encoding.
+        assert_eq!(
+            hex::encode(minicbor::to_vec(&logo).unwrap()),
+            format!("a300010163706e67024a{}", "01".repeat(10))
+        );
+
+        let enc = minicbor::to_vec(&tagged).unwrap();
+        let res: SingleVisualTokenLogo = minicbor::decode(&enc).unwrap();
+        assert_eq!(res, tagged);
+        assert_eq!(
+            res.purposes(),
+            Some(&BTreeSet::from([LogoPurpose::Dark, LogoPurpose::Small]))
+        );
+    }
+
+    #[test]
+    fn encode_decode_image_ref() {
+        let logo = SingleVisualTokenLogo::image_ref(
+            "image/svg+xml",
+            vec![3u8; IMAGE_REF_HASH_SIZE],
+            Some("https://example.com/logo.svg".to_string()),
+        )
+        .with_purposes([LogoPurpose::Light]);
+
+        let enc = minicbor::to_vec(&logo).unwrap();
+        let res: SingleVisualTokenLogo = minicbor::decode(&enc).unwrap();
+        assert_eq!(res, logo);
+
+        let logo = SingleVisualTokenLogo::image_ref("image/png", vec![3u8; 32], None);
+        let enc = minicbor::to_vec(&logo).unwrap();
+        let res: SingleVisualTokenLogo = minicbor::decode(&enc).unwrap();
+        assert_eq!(res, logo);
+    }
+
+    #[test]
+    fn for_purpose() {
+        let mut logos = VisualTokenLogo::new();
+        logos.unicode_back('∑');
+        logos.push_back(
+            SingleVisualTokenLogo::image("png", vec![1u8; 10]).with_purposes([LogoPurpose::Dark]),
+        );
+
+        assert_eq!(logos.for_purpose(LogoPurpose::Dark), Some(&logos[1]));
+        assert_eq!(logos.for_purpose(LogoPurpose::Light), Some(&logos[0]));
+    }
+
+    #[test]
+    fn check() {
+        let mut logos = VisualTokenLogo::new();
+        logos.unicode_back('∑');
+        logos.image_back("png", vec![1u8; MAX_IMAGE_SIZE]);
+        assert!(logos.check().is_ok());
+
+        let mut too_big = logos.clone();
+        too_big.image_back("png", vec![1u8; MAX_IMAGE_SIZE + 1]);
+        assert!(too_big.check().is_err());
+
+        let mut bad_hash = logos.clone();
+        bad_hash.push_back(SingleVisualTokenLogo::image_ref("png", vec![1u8; 20], None));
+        assert!(bad_hash.check().is_err());
+
+        let mut too_many = logos;
+        for _ in 0..MAX_LOGOS {
+            too_many.unicode_back('π');
+        }
+        assert!(too_many.check().is_err());
+    }
 }
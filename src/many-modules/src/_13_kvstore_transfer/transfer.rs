@@ -1,11 +1,12 @@
 use crate::events::AddressContainer;
 use crate::EmptyReturn;
 use many_identity::Address;
+use many_macros::Cddl;
 use minicbor::bytes::ByteVec;
 use minicbor::{Decode, Encode};
 use std::collections::BTreeSet;
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct TransferArgs {
     #[n(0)]
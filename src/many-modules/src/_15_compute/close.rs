@@ -1,9 +1,10 @@
 use crate::EmptyReturn;
+use many_macros::Cddl;
 use minicbor::{Decode, Encode};
 
 pub type CloseReturns = EmptyReturn;
 
-#[derive(Clone, Decode, Encode)]
+#[derive(Cddl, Clone, Decode, Encode)]
 #[cbor(map)]
 pub struct CloseArgs {
     #[n(0)]
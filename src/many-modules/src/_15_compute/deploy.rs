@@ -1,7 +1,8 @@
+use many_macros::Cddl;
 use many_types::compute::{ByteUnits, DeploymentMeta, Region};
 use minicbor::{Decode, Encode};
 
-#[derive(Clone, Debug, Decode, Encode)]
+#[derive(Cddl, Clone, Debug, Decode, Encode)]
 #[cbor(map)]
 pub struct DeployArgs {
     #[n(0)]
@@ -22,6 +23,6 @@ pub struct DeployArgs {
     pub region: Region,
 }
 
-#[derive(Clone, Decode, Encode)]
+#[derive(Cddl, Clone, Decode, Encode)]
 #[cbor(transparent)]
 pub struct DeployReturns(#[n(0)] pub DeploymentMeta);
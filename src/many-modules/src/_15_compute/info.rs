@@ -1,10 +1,11 @@
 use crate::EmptyArg;
+use many_macros::Cddl;
 use minicbor::bytes::ByteVec;
 use minicbor::{Decode, Encode};
 
 pub type InfoArg = EmptyArg;
 
-#[derive(Clone, Decode, Encode)]
+#[derive(Cddl, Clone, Decode, Encode)]
 #[cbor(map)]
 pub struct InfoReturns {
     #[n(0)]
@@ -1,9 +1,10 @@
 use many_identity::Address;
+use many_macros::Cddl;
 use many_types::compute::{ComputeListFilter, DeploymentMeta};
 use many_types::SortOrder;
 use minicbor::{Decode, Encode};
 
-#[derive(Clone, Decode, Encode)]
+#[derive(Cddl, Clone, Decode, Encode)]
 #[cbor(map)]
 pub struct ListArgs {
     #[n(0)]
@@ -16,7 +17,7 @@ pub struct ListArgs {
     pub filter: Option<ComputeListFilter>,
 }
 
-#[derive(Clone, Decode, Encode)]
+#[derive(Cddl, Clone, Decode, Encode)]
 #[cbor(map)]
 pub struct ListReturns {
     #[n(0)]
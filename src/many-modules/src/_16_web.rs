@@ -2,11 +2,19 @@ use many_error::ManyError;
 use many_identity::Address;
 use many_macros::many_module;
 
+pub mod config;
+pub mod content_hash;
 pub mod info;
 pub mod list;
+pub mod records;
+pub mod resolve;
 
+pub use config::*;
+pub use content_hash::*;
 pub use info::*;
 pub use list::*;
+pub use records::*;
+pub use resolve::*;
 
 #[cfg(test)]
 use mockall::{automock, predicate::*};
@@ -17,12 +25,29 @@ pub trait WebModuleBackend: Send {
     fn info(&self, sender: &Address, args: InfoArg) -> Result<InfoReturns, ManyError>;
 
     fn list(&self, sender: &Address, args: ListArgs) -> Result<ListReturns, ManyError>;
+
+    fn config(&self, sender: &Address, args: ConfigArgs) -> Result<ConfigReturns, ManyError>;
+
+    fn content_hash(
+        &self,
+        sender: &Address,
+        args: ContentHashArgs,
+    ) -> Result<ContentHashReturns, ManyError>;
+
+    fn records(&self, sender: &Address, args: RecordsArgs) -> Result<RecordsReturns, ManyError>;
+
+    fn resolve(&self, sender: &Address, args: ResolveArgs) -> Result<ResolveReturns, ManyError>;
 }
 
 #[cfg(test)]
 mod tests {
     use crate::testutils::call_module_cbor;
-    use crate::web::{InfoReturns, ListReturns, MockWebModuleBackend};
+    use crate::web::{
+        ConfigArgs, ConfigReturns, ContentHashArgs, ContentHashReturns, InfoReturns, ListReturns,
+        MockWebModuleBackend, RecordsArgs, RecordsReturns, ResolveArgs, ResolveReturns,
+    };
+    use many_identity::testing::identity;
+    use many_types::web::{WebRecord, WebRedirect, WebSiteConfig, WebSiteRedirect, WebSiteRef};
     use std::sync::{Arc, Mutex};
 
     #[test]
@@ -77,4 +102,143 @@ mod tests {
         .unwrap();
         assert_eq!(list.deployments, vec![])
     }
+
+    #[test]
+    fn config() {
+        let mut mock = MockWebModuleBackend::new();
+        mock.expect_config().times(1).returning(|_sender, _args| {
+            Ok(ConfigReturns {
+                config: WebSiteConfig {
+                    redirects: vec![WebRedirect {
+                        from: "/old".to_string(),
+                        to: "/new".to_string(),
+                        status: 301,
+                    }],
+                    not_found: Some("/404.html".to_string()),
+                },
+            })
+        });
+        let module = super::WebModule::new(Arc::new(Mutex::new(mock)));
+
+        let config: ConfigReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "web.config",
+                minicbor::to_vec(ConfigArgs {
+                    owner: identity(1),
+                    site_name: "foobar".to_string(),
+                })
+                .unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(config.config.redirect_for("/old").unwrap().to, "/new");
+        assert_eq!(config.config.not_found, Some("/404.html".to_string()));
+    }
+
+    #[test]
+    fn content_hash() {
+        let mut mock = MockWebModuleBackend::new();
+        mock.expect_content_hash()
+            .times(1)
+            .returning(|_sender, args| {
+                assert_eq!(args.path, Some("/index.html".to_string()));
+                Ok(ContentHashReturns {
+                    hash: vec![1, 2, 3].into(),
+                    files: None,
+                })
+            });
+        let module = super::WebModule::new(Arc::new(Mutex::new(mock)));
+
+        let content_hash: ContentHashReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "web.contentHash",
+                minicbor::to_vec(ContentHashArgs {
+                    owner: identity(1),
+                    site_name: "foobar".to_string(),
+                    path: Some("/index.html".to_string()),
+                })
+                .unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(content_hash.hash, vec![1, 2, 3].into());
+        assert_eq!(content_hash.files, None);
+    }
+
+    #[test]
+    fn records() {
+        let mut mock = MockWebModuleBackend::new();
+        mock.expect_records().times(1).returning(|_sender, _args| {
+            Ok(RecordsReturns {
+                records: vec![
+                    WebRecord::Content(WebSiteRef {
+                        owner: identity(2),
+                        site_name: "other".to_string(),
+                    }),
+                    WebRecord::Txt("Hello".to_string()),
+                ],
+            })
+        });
+        let module = super::WebModule::new(Arc::new(Mutex::new(mock)));
+
+        let records: RecordsReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "web.records",
+                minicbor::to_vec(RecordsArgs {
+                    owner: identity(1),
+                    site_name: "foobar".to_string(),
+                })
+                .unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(records.records.len(), 2);
+        assert_eq!(records.records[1], WebRecord::Txt("Hello".to_string()));
+    }
+
+    #[test]
+    fn resolve() {
+        let mut mock = MockWebModuleBackend::new();
+        mock.expect_resolve().times(1).returning(|_sender, args| {
+            Ok(ResolveReturns {
+                site: WebSiteRef {
+                    owner: args.owner,
+                    site_name: args.site_name,
+                },
+                redirect: Some(WebSiteRedirect {
+                    to: "https://example.com".to_string(),
+                    status: 302,
+                }),
+                txt: vec![],
+            })
+        });
+        let module = super::WebModule::new(Arc::new(Mutex::new(mock)));
+
+        let resolved: ResolveReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "web.resolve",
+                minicbor::to_vec(ResolveArgs {
+                    owner: identity(1),
+                    site_name: "foobar".to_string(),
+                })
+                .unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(resolved.site.owner, identity(1));
+        assert_eq!(resolved.site.site_name, "foobar");
+        assert_eq!(resolved.redirect.unwrap().status, 302);
+    }
 }
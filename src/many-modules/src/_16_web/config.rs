@@ -0,0 +1,21 @@
+use many_identity::Address;
+use many_macros::Cddl;
+use many_types::web::WebSiteConfig;
+use minicbor::{Decode, Encode};
+
+#[derive(Cddl, Clone, Debug, Decode, Encode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ConfigArgs {
+    #[n(0)]
+    pub owner: Address,
+
+    #[n(1)]
+    pub site_name: String,
+}
+
+#[derive(Cddl, Clone, Debug, Decode, Encode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ConfigReturns {
+    #[n(0)]
+    pub config: WebSiteConfig,
+}
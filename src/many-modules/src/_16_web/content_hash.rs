@@ -0,0 +1,34 @@
+use many_identity::Address;
+use many_macros::Cddl;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+use std::collections::BTreeMap;
+
+#[derive(Cddl, Clone, Debug, Decode, Encode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ContentHashArgs {
+    #[n(0)]
+    pub owner: Address,
+
+    #[n(1)]
+    pub site_name: String,
+
+    /// The path of a file of the website. If absent, the root hash of the
+    /// website is returned.
+    #[n(2)]
+    pub path: Option<String>,
+}
+
+#[derive(Cddl, Clone, Debug, Decode, Encode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ContentHashReturns {
+    /// The SHA-256 hash of the file, or the root hash of the website, i.e. the
+    /// SHA-256 hash of the CBOR encoding of `files`.
+    #[n(0)]
+    pub hash: ByteVec,
+
+    /// The hash of every file of the website, by path. Only returned with the
+    /// root hash.
+    #[n(1)]
+    pub files: Option<BTreeMap<String, ByteVec>>,
+}
@@ -1,10 +1,11 @@
 use crate::EmptyArg;
+use many_macros::Cddl;
 use minicbor::bytes::ByteVec;
 use minicbor::{Decode, Encode};
 
 pub type InfoArg = EmptyArg;
 
-#[derive(Clone, Debug, Decode, Encode, PartialEq, Eq)]
+#[derive(Cddl, Clone, Debug, Decode, Encode, PartialEq, Eq)]
 #[cbor(map)]
 pub struct InfoReturns {
     #[n(0)]
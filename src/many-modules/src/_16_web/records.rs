@@ -0,0 +1,21 @@
+use many_identity::Address;
+use many_macros::Cddl;
+use many_types::web::WebRecord;
+use minicbor::{Decode, Encode};
+
+#[derive(Cddl, Clone, Debug, Decode, Encode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct RecordsArgs {
+    #[n(0)]
+    pub owner: Address,
+
+    #[n(1)]
+    pub site_name: String,
+}
+
+#[derive(Cddl, Clone, Debug, Decode, Encode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct RecordsReturns {
+    #[n(0)]
+    pub records: Vec<WebRecord>,
+}
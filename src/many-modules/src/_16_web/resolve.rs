@@ -0,0 +1,31 @@
+use many_identity::Address;
+use many_macros::Cddl;
+use many_types::web::{WebSiteRedirect, WebSiteRef};
+use minicbor::{Decode, Encode};
+
+#[derive(Cddl, Clone, Debug, Decode, Encode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ResolveArgs {
+    #[n(0)]
+    pub owner: Address,
+
+    #[n(1)]
+    pub site_name: String,
+}
+
+#[derive(Cddl, Clone, Debug, Decode, Encode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ResolveReturns {
+    /// The website whose content is served, after following the content
+    /// records.
+    #[n(0)]
+    pub site: WebSiteRef,
+
+    /// Redirect every request instead of serving content.
+    #[n(1)]
+    pub redirect: Option<WebSiteRedirect>,
+
+    /// The text records of the requested website.
+    #[n(2)]
+    pub txt: Vec<String>,
+}
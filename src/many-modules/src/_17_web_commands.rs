@@ -4,10 +4,14 @@ use many_macros::many_module;
 
 pub mod deploy;
 pub mod remove;
+pub mod set_config;
+pub mod set_records;
 pub mod update;
 
 pub use deploy::*;
 pub use remove::*;
+pub use set_config::*;
+pub use set_records::*;
 pub use update::*;
 
 #[cfg(test)]
@@ -24,6 +28,20 @@ pub trait WebCommandsModuleBackend: Send {
 
     #[many(deny_anonymous)]
     fn update(&mut self, sender: &Address, args: UpdateArgs) -> Result<UpdateReturns, ManyError>;
+
+    #[many(deny_anonymous)]
+    fn set_config(
+        &mut self,
+        sender: &Address,
+        args: SetConfigArgs,
+    ) -> Result<SetConfigReturns, ManyError>;
+
+    #[many(deny_anonymous)]
+    fn set_records(
+        &mut self,
+        sender: &Address,
+        args: SetRecordsArgs,
+    ) -> Result<SetRecordsReturns, ManyError>;
 }
 
 #[cfg(test)]
@@ -31,10 +49,11 @@ mod tests {
     use crate::testutils::call_module_cbor;
     use crate::web::{
         DeployArgs, DeployReturns, MockWebCommandsModuleBackend, RemoveArgs, RemoveReturns,
-        UpdateArgs, UpdateReturns,
+        SetConfigArgs, SetConfigReturns, SetRecordsArgs, SetRecordsReturns, UpdateArgs,
+        UpdateReturns,
     };
     use many_identity::testing::identity;
-    use many_types::web::{WebDeploymentInfo, WebDeploymentSource};
+    use many_types::web::{WebDeploymentInfo, WebDeploymentSource, WebSiteConfig};
     use mockall::predicate;
     use std::sync::{Arc, Mutex};
 
@@ -125,4 +144,57 @@ mod tests {
         .unwrap();
         assert_eq!(deploy.info.url, Some("foobar".to_string()));
     }
+
+    #[test]
+    fn set_config() {
+        let mut mock = MockWebCommandsModuleBackend::new();
+        let data = SetConfigArgs {
+            owner: None,
+            site_name: "foobar".to_string(),
+            config: WebSiteConfig {
+                redirects: vec!["/old=/new,302".parse().unwrap()],
+                not_found: None,
+            },
+        };
+        mock.expect_set_config()
+            .with(predicate::eq(identity(1)), predicate::eq(data.clone()))
+            .times(1)
+            .returning(|_sender, _args| Ok(SetConfigReturns {}));
+        let module = super::WebCommandsModule::new(Arc::new(Mutex::new(mock)));
+
+        let _: SetConfigReturns = minicbor::decode(
+            &call_module_cbor(1, &module, "web.setConfig", minicbor::to_vec(data).unwrap())
+                .unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn set_records() {
+        let mut mock = MockWebCommandsModuleBackend::new();
+        let data = SetRecordsArgs {
+            owner: None,
+            site_name: "foobar".to_string(),
+            records: vec![
+                "txt:Hello".parse().unwrap(),
+                "redirect:https://example.com,302".parse().unwrap(),
+            ],
+        };
+        mock.expect_set_records()
+            .with(predicate::eq(identity(1)), predicate::eq(data.clone()))
+            .times(1)
+            .returning(|_sender, _args| Ok(SetRecordsReturns {}));
+        let module = super::WebCommandsModule::new(Arc::new(Mutex::new(mock)));
+
+        let _: SetRecordsReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "web.setRecords",
+                minicbor::to_vec(data).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    }
 }
@@ -1,9 +1,10 @@
 use many_identity::Address;
+use many_macros::Cddl;
 use many_types::web::{WebDeploymentInfo, WebDeploymentSource};
 use many_types::Memo;
 use minicbor::{Decode, Encode};
 
-#[derive(Clone, Debug, Decode, Encode, PartialEq, Eq)]
+#[derive(Cddl, Clone, Debug, Decode, Encode, PartialEq, Eq)]
 #[cbor(map)]
 pub struct DeployArgs {
     #[n(0)]
@@ -25,7 +26,7 @@ pub struct DeployArgs {
     pub domain: Option<String>,
 }
 
-#[derive(Clone, Debug, Decode, Encode, PartialEq, Eq)]
+#[derive(Cddl, Clone, Debug, Decode, Encode, PartialEq, Eq)]
 #[cbor(map)]
 pub struct DeployReturns {
     #[n(0)]
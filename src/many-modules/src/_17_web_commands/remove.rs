@@ -1,9 +1,10 @@
 use crate::EmptyReturn;
 use many_identity::Address;
+use many_macros::Cddl;
 use many_types::Memo;
 use minicbor::{Decode, Encode};
 
-#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, PartialEq, Eq)]
 #[cbor(map)]
 pub struct RemoveArgs {
     #[n(0)]
@@ -0,0 +1,20 @@
+use crate::EmptyReturn;
+use many_identity::Address;
+use many_macros::Cddl;
+use many_types::web::WebSiteConfig;
+use minicbor::{Decode, Encode};
+
+#[derive(Cddl, Clone, Debug, Decode, Encode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct SetConfigArgs {
+    #[n(0)]
+    pub owner: Option<Address>,
+
+    #[n(1)]
+    pub site_name: String,
+
+    #[n(2)]
+    pub config: WebSiteConfig,
+}
+
+pub type SetConfigReturns = EmptyReturn;
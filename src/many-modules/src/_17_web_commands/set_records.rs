@@ -0,0 +1,21 @@
+use crate::EmptyReturn;
+use many_identity::Address;
+use many_macros::Cddl;
+use many_types::web::WebRecord;
+use minicbor::{Decode, Encode};
+
+#[derive(Cddl, Clone, Debug, Decode, Encode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct SetRecordsArgs {
+    #[n(0)]
+    pub owner: Option<Address>,
+
+    #[n(1)]
+    pub site_name: String,
+
+    /// The records of the website, replacing the existing ones.
+    #[n(2)]
+    pub records: Vec<WebRecord>,
+}
+
+pub type SetRecordsReturns = EmptyReturn;
@@ -0,0 +1,206 @@
+use crate::EmptyReturn;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::{many_module, Cddl};
+use many_types::Memo;
+use minicbor::{Decode, Encode};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+pub mod errors {
+    use many_error::define_attribute_many_error;
+    define_attribute_many_error!(
+        attribute 18 => {
+            1: pub fn transfer_rejected(address) => "Transfers involving {address} are rejected.",
+            2: pub fn approval_required(address)
+                => "Transfers involving {address} need to be sent by a watchlist approver.",
+            3: pub fn address_not_watched(address) => "Address {address} is not on the watchlist.",
+        }
+    );
+}
+
+/// What happens to a transfer where the sender, source or destination is on
+/// the watchlist. Every decision is logged as an event.
+#[derive(Copy, Clone, Debug, Encode, Decode, Ord, PartialOrd, Eq, PartialEq)]
+#[cbor(index_only)]
+pub enum WatchlistAction {
+    /// The transfer is allowed.
+    #[n(0)]
+    Alert,
+
+    /// The transfer is only allowed if sent by a watchlist approver.
+    #[n(1)]
+    RequireApproval,
+
+    /// The transfer is rejected.
+    #[n(2)]
+    Reject,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct WatchlistEntry {
+    #[n(0)]
+    pub action: WatchlistAction,
+
+    #[n(1)]
+    pub memo: Option<Memo>,
+}
+
+#[derive(Cddl, Clone, Debug, Default, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct InfoArgs {}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct InfoReturn {
+    #[n(0)]
+    pub entries: BTreeMap<Address, WatchlistEntry>,
+
+    #[n(1)]
+    pub approvers: BTreeSet<Address>,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct AddArgs {
+    #[n(0)]
+    pub address: Address,
+
+    #[n(1)]
+    pub action: WatchlistAction,
+
+    #[n(2)]
+    pub memo: Option<Memo>,
+}
+
+pub type AddReturn = EmptyReturn;
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct RemoveArgs {
+    #[n(0)]
+    pub address: Address,
+
+    #[n(1)]
+    pub memo: Option<Memo>,
+}
+
+pub type RemoveReturn = EmptyReturn;
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct SetApproversArgs {
+    #[n(0)]
+    pub approvers: BTreeSet<Address>,
+}
+
+pub type SetApproversReturn = EmptyReturn;
+
+/// A watchlist of addresses for compliance screening, maintained by the
+/// server administrator.
+#[many_module(name = WatchlistModule, id = 18, namespace = watchlist, many_modules_crate = crate)]
+#[cfg_attr(test, automock)]
+pub trait WatchlistModuleBackend: Send {
+    fn info(&self, sender: &Address, args: InfoArgs) -> Result<InfoReturn, ManyError>;
+    fn add(&mut self, sender: &Address, args: AddArgs) -> Result<AddReturn, ManyError>;
+    fn remove(&mut self, sender: &Address, args: RemoveArgs) -> Result<RemoveReturn, ManyError>;
+    fn set_approvers(
+        &mut self,
+        sender: &Address,
+        args: SetApproversArgs,
+    ) -> Result<SetApproversReturn, ManyError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::{assert_canonical_round_trip, call_module_cbor};
+    use many_identity::testing::identity;
+    use proptest::prelude::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn add() {
+        let mut mock = MockWatchlistModuleBackend::new();
+        let data = AddArgs {
+            address: identity(5),
+            action: WatchlistAction::RequireApproval,
+            memo: None,
+        };
+        mock.expect_add()
+            .with(eq(identity(1)), eq(data.clone()))
+            .times(1)
+            .returning(|_, _| Ok(EmptyReturn));
+        let module = super::WatchlistModule::new(Arc::new(Mutex::new(mock)));
+
+        let add_returns: AddReturn = minicbor::decode(
+            &call_module_cbor(1, &module, "watchlist.add", minicbor::to_vec(data).unwrap())
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(add_returns, EmptyReturn);
+    }
+
+    #[test]
+    fn info() {
+        let mut mock = MockWatchlistModuleBackend::new();
+        mock.expect_info()
+            .with(eq(identity(1)), eq(InfoArgs {}))
+            .times(1)
+            .returning(|_, _| {
+                Ok(InfoReturn {
+                    entries: BTreeMap::from([(
+                        identity(5),
+                        WatchlistEntry {
+                            action: WatchlistAction::Reject,
+                            memo: None,
+                        },
+                    )]),
+                    approvers: BTreeSet::from([identity(2)]),
+                })
+            });
+        let module = super::WatchlistModule::new(Arc::new(Mutex::new(mock)));
+
+        let info_returns: InfoReturn = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "watchlist.info",
+                minicbor::to_vec(InfoArgs {}).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            info_returns.entries[&identity(5)].action,
+            WatchlistAction::Reject
+        );
+        assert!(info_returns.approvers.contains(&identity(2)));
+    }
+
+    proptest! {
+        #[test]
+        fn canonical_entry(
+            action in proptest::sample::select(vec![
+                WatchlistAction::Alert,
+                WatchlistAction::RequireApproval,
+                WatchlistAction::Reject,
+            ]),
+            memo in proptest::option::of("[A-Za-z0-9 ]{0,100}"),
+        ) {
+            assert_canonical_round_trip(&WatchlistEntry {
+                action,
+                memo: memo.map(|m| Memo::try_from(m).unwrap()),
+            });
+        }
+
+        #[test]
+        fn canonical_approvers(approvers in proptest::collection::btree_set(0u32..1000, 0..10)) {
+            let approvers: BTreeSet<Address> = approvers.into_iter().map(identity).collect();
+            assert_canonical_round_trip(&approvers);
+        }
+    }
+}
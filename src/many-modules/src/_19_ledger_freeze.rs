@@ -0,0 +1,148 @@
+use crate::EmptyReturn;
+use many_error::{define_attribute_many_error, ManyError};
+use many_identity::Address;
+use many_macros::{many_module, Cddl};
+use many_types::Memo;
+use minicbor::{Decode, Encode};
+use std::collections::BTreeSet;
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+define_attribute_many_error!(
+    attribute 19 => {
+        1: pub fn address_frozen(address) => "Address {address} is frozen.",
+        2: pub fn address_not_frozen(address) => "Address {address} is not frozen.",
+        3: pub fn address_already_frozen(address) => "Address {address} is already frozen.",
+    }
+);
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct FreezeArgs {
+    #[n(0)]
+    pub address: Address,
+
+    #[n(1)]
+    pub memo: Option<Memo>,
+}
+
+pub type FreezeReturns = EmptyReturn;
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct UnfreezeArgs {
+    #[n(0)]
+    pub address: Address,
+
+    #[n(1)]
+    pub memo: Option<Memo>,
+}
+
+pub type UnfreezeReturns = EmptyReturn;
+
+#[derive(Cddl, Clone, Debug, Default, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct FrozenArgs {}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct FrozenReturns {
+    #[n(0)]
+    pub addresses: BTreeSet<Address>,
+}
+
+/// Freeze addresses of the ledger. A frozen address cannot send or receive
+/// tokens, be minted tokens, or execute multisig transactions.
+#[many_module(name = LedgerFreezeModule, id = 19, namespace = ledger, many_modules_crate = crate)]
+#[cfg_attr(test, automock)]
+pub trait LedgerFreezeModuleBackend: Send {
+    fn freeze(&mut self, sender: &Address, args: FreezeArgs) -> Result<FreezeReturns, ManyError>;
+    fn unfreeze(
+        &mut self,
+        sender: &Address,
+        args: UnfreezeArgs,
+    ) -> Result<UnfreezeReturns, ManyError>;
+    fn frozen(&self, sender: &Address, args: FrozenArgs) -> Result<FrozenReturns, ManyError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::call_module_cbor;
+    use many_identity::testing::identity;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn freeze() {
+        let mut mock = MockLedgerFreezeModuleBackend::new();
+        let data = FreezeArgs {
+            address: identity(5),
+            memo: None,
+        };
+        mock.expect_freeze()
+            .with(eq(identity(1)), eq(data.clone()))
+            .times(1)
+            .returning(|_, _| Ok(EmptyReturn));
+        let module = super::LedgerFreezeModule::new(Arc::new(Mutex::new(mock)));
+
+        let freeze_returns: FreezeReturns = minicbor::decode(
+            &call_module_cbor(1, &module, "ledger.freeze", minicbor::to_vec(data).unwrap())
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(freeze_returns, EmptyReturn);
+    }
+
+    #[test]
+    fn unfreeze() {
+        let mut mock = MockLedgerFreezeModuleBackend::new();
+        let data = UnfreezeArgs {
+            address: identity(5),
+            memo: None,
+        };
+        mock.expect_unfreeze()
+            .with(eq(identity(1)), eq(data.clone()))
+            .times(1)
+            .returning(|_, _| Ok(EmptyReturn));
+        let module = super::LedgerFreezeModule::new(Arc::new(Mutex::new(mock)));
+
+        let unfreeze_returns: UnfreezeReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "ledger.unfreeze",
+                minicbor::to_vec(data).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(unfreeze_returns, EmptyReturn);
+    }
+
+    #[test]
+    fn frozen() {
+        let mut mock = MockLedgerFreezeModuleBackend::new();
+        mock.expect_frozen()
+            .with(eq(identity(1)), eq(FrozenArgs {}))
+            .times(1)
+            .returning(|_, _| {
+                Ok(FrozenReturns {
+                    addresses: BTreeSet::from([identity(5)]),
+                })
+            });
+        let module = super::LedgerFreezeModule::new(Arc::new(Mutex::new(mock)));
+
+        let frozen_returns: FrozenReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "ledger.frozen",
+                minicbor::to_vec(FrozenArgs {}).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(frozen_returns.addresses.contains(&identity(5)));
+    }
+}
@@ -1,5 +1,5 @@
 use many_error::{define_attribute_many_error, ManyError};
-use many_macros::many_module;
+use many_macros::{many_module, Cddl};
 use many_types::blockchain::{
     Block, BlockIdentifier, RangeBlockQuery, SingleBlockQuery, SingleTransactionQuery, Transaction,
 };
@@ -20,7 +20,7 @@ define_attribute_many_error!(
     }
 );
 
-#[derive(Clone, Encode, Decode)]
+#[derive(Cddl, Clone, Encode, Decode)]
 #[cbor(map)]
 pub struct InfoReturns {
     #[n(0)]
@@ -33,35 +33,35 @@ pub struct InfoReturns {
     pub retained_height: Option<u64>,
 }
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct BlockArgs {
     #[n(0)]
     pub query: SingleBlockQuery,
 }
 
-#[derive(Clone, Encode, Decode)]
+#[derive(Cddl, Clone, Encode, Decode)]
 #[cbor(map)]
 pub struct BlockReturns {
     #[n(0)]
     pub block: Block,
 }
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct TransactionArgs {
     #[n(0)]
     pub query: SingleTransactionQuery,
 }
 
-#[derive(Clone, Encode, Decode)]
+#[derive(Cddl, Clone, Encode, Decode)]
 #[cbor(map)]
 pub struct TransactionReturns {
     #[n(0)]
     pub txn: Transaction,
 }
 
-#[derive(Clone, Debug, Default, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Default, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct ListArgs {
     #[n(0)]
@@ -74,7 +74,7 @@ pub struct ListArgs {
     pub filter: Option<RangeBlockQuery>,
 }
 
-#[derive(Clone, Encode, Decode)]
+#[derive(Cddl, Clone, Encode, Decode)]
 #[cbor(map)]
 pub struct ListReturns {
     #[n(0)]
@@ -84,28 +84,28 @@ pub struct ListReturns {
     pub blocks: Vec<Block>,
 }
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct RequestArgs {
     #[n(0)]
     pub query: SingleTransactionQuery,
 }
 
-#[derive(Clone, Encode, Decode)]
+#[derive(Cddl, Clone, Encode, Decode)]
 #[cbor(map)]
 pub struct RequestReturns {
     #[cbor(n(0), with = "minicbor::bytes")]
     pub request: Vec<u8>,
 }
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct ResponseArgs {
     #[n(0)]
     pub query: SingleTransactionQuery,
 }
 
-#[derive(Clone, Encode, Decode)]
+#[derive(Cddl, Clone, Encode, Decode)]
 #[cbor(map)]
 pub struct ResponseReturns {
     #[cbor(n(0), with = "minicbor::bytes")]
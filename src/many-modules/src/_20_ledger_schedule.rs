@@ -0,0 +1,309 @@
+use crate::EmptyReturn;
+use many_error::{define_attribute_many_error, ManyError};
+use many_identity::Address;
+use many_macros::{many_module, Cddl};
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::{Duration, Memo, Timestamp};
+use minicbor::{Decode, Encode};
+use std::collections::BTreeMap;
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+define_attribute_many_error!(
+    attribute 20 => {
+        1: pub fn scheduled_send_not_found(id) => "Scheduled send {id} was not found.",
+        2: pub fn schedule_in_the_past() => "Sends cannot be scheduled in the past.",
+    }
+);
+
+/// When a scheduled send executes.
+#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+pub enum ScheduleTrigger {
+    /// The first block whose time is at or after this timestamp.
+    #[n(0)]
+    Time(#[n(0)] Timestamp),
+
+    /// The block at this height.
+    #[n(1)]
+    Height(#[n(0)] u64),
+
+    /// Some time after the send is scheduled. The ledger stores this as a
+    /// [ScheduleTrigger::Time] relative to the block the send is scheduled in.
+    #[n(2)]
+    After(#[n(0)] Duration),
+}
+
+impl ScheduleTrigger {
+    /// Whether a send with this trigger is due in a block at `height` and `time`.
+    pub fn is_due(&self, height: u64, time: Timestamp) -> bool {
+        match self {
+            ScheduleTrigger::Time(t) => *t <= time,
+            ScheduleTrigger::Height(h) => *h <= height,
+            ScheduleTrigger::After(_) => false,
+        }
+    }
+
+    /// Replaces a relative trigger by the absolute time it refers to.
+    pub fn resolve(self, now: Timestamp) -> Self {
+        match self {
+            ScheduleTrigger::After(d) => ScheduleTrigger::Time(now + d),
+            x => x,
+        }
+    }
+}
+
+/// A send waiting for its trigger. The funds are not reserved; a send that
+/// cannot be executed when due is dropped and its error is logged. The
+/// sender locks a deposit until the send is executed or cancelled.
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ScheduledSend {
+    #[n(0)]
+    pub sender: Address,
+
+    #[n(1)]
+    pub from: Address,
+
+    #[n(2)]
+    pub to: Address,
+
+    #[n(3)]
+    pub symbol: Symbol,
+
+    #[n(4)]
+    pub amount: TokenAmount,
+
+    #[n(5)]
+    pub at: ScheduleTrigger,
+
+    #[n(6)]
+    pub memo: Option<Memo>,
+
+    #[n(7)]
+    pub deposit_symbol: Symbol,
+
+    #[n(8)]
+    pub deposit: TokenAmount,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ScheduleSendArgs {
+    #[n(0)]
+    pub from: Option<Address>,
+
+    #[n(1)]
+    pub to: Address,
+
+    #[n(2)]
+    pub amount: TokenAmount,
+
+    #[n(3)]
+    pub symbol: Symbol,
+
+    #[n(4)]
+    pub at: ScheduleTrigger,
+
+    #[n(5)]
+    pub memo: Option<Memo>,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ScheduleSendReturns {
+    #[n(0)]
+    pub id: u64,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct CancelScheduledArgs {
+    #[n(0)]
+    pub id: u64,
+
+    #[n(1)]
+    pub memo: Option<Memo>,
+}
+
+pub type CancelScheduledReturns = EmptyReturn;
+
+#[derive(Cddl, Clone, Debug, Default, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ScheduledArgs {
+    /// Only list the sends from this address.
+    #[n(0)]
+    pub from: Option<Address>,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ScheduledReturns {
+    #[n(0)]
+    pub sends: BTreeMap<u64, ScheduledSend>,
+}
+
+/// Schedule sends to execute at the beginning of a future block.
+#[many_module(name = LedgerScheduleModule, id = 20, namespace = ledger, many_modules_crate = crate)]
+#[cfg_attr(test, automock)]
+pub trait LedgerScheduleModuleBackend: Send {
+    fn schedule_send(
+        &mut self,
+        sender: &Address,
+        args: ScheduleSendArgs,
+    ) -> Result<ScheduleSendReturns, ManyError>;
+    fn cancel_scheduled(
+        &mut self,
+        sender: &Address,
+        args: CancelScheduledArgs,
+    ) -> Result<CancelScheduledReturns, ManyError>;
+    fn scheduled(
+        &self,
+        sender: &Address,
+        args: ScheduledArgs,
+    ) -> Result<ScheduledReturns, ManyError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::{assert_canonical_round_trip, call_module_cbor};
+    use many_identity::testing::identity;
+    use proptest::prelude::*;
+    use std::sync::{Arc, Mutex};
+
+    fn scheduled_send() -> ScheduledSend {
+        ScheduledSend {
+            sender: identity(1),
+            from: identity(1),
+            to: identity(2),
+            symbol: identity(3),
+            amount: TokenAmount::from(100u16),
+            at: ScheduleTrigger::Height(10),
+            memo: None,
+            deposit_symbol: identity(3),
+            deposit: TokenAmount::from(1u16),
+        }
+    }
+
+    #[test]
+    fn is_due() {
+        let time = Timestamp::new(1000).unwrap();
+        assert!(ScheduleTrigger::Height(10).is_due(10, time));
+        assert!(!ScheduleTrigger::Height(11).is_due(10, time));
+        assert!(ScheduleTrigger::Time(Timestamp::new(999).unwrap()).is_due(0, time));
+        assert!(!ScheduleTrigger::Time(Timestamp::new(1001).unwrap()).is_due(0, time));
+    }
+
+    #[test]
+    fn resolve() {
+        let time = Timestamp::new(1000).unwrap();
+        let after = ScheduleTrigger::After(Duration::from_minutes(1).unwrap());
+        assert!(!after.is_due(u64::MAX, time));
+        assert_eq!(
+            after.resolve(time),
+            ScheduleTrigger::Time(Timestamp::new(1060).unwrap())
+        );
+        assert_eq!(
+            ScheduleTrigger::Height(10).resolve(time),
+            ScheduleTrigger::Height(10)
+        );
+    }
+
+    #[test]
+    fn schedule_send() {
+        let mut mock = MockLedgerScheduleModuleBackend::new();
+        let data = ScheduleSendArgs {
+            from: None,
+            to: identity(2),
+            amount: TokenAmount::from(100u16),
+            symbol: identity(3),
+            at: ScheduleTrigger::Time(Timestamp::new(1000).unwrap()),
+            memo: None,
+        };
+        mock.expect_schedule_send()
+            .with(eq(identity(1)), eq(data.clone()))
+            .times(1)
+            .returning(|_, _| Ok(ScheduleSendReturns { id: 1 }));
+        let module = super::LedgerScheduleModule::new(Arc::new(Mutex::new(mock)));
+
+        let schedule_returns: ScheduleSendReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "ledger.scheduleSend",
+                minicbor::to_vec(data).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(schedule_returns.id, 1);
+    }
+
+    #[test]
+    fn cancel_scheduled() {
+        let mut mock = MockLedgerScheduleModuleBackend::new();
+        let data = CancelScheduledArgs { id: 1, memo: None };
+        mock.expect_cancel_scheduled()
+            .with(eq(identity(1)), eq(data.clone()))
+            .times(1)
+            .returning(|_, _| Ok(EmptyReturn));
+        let module = super::LedgerScheduleModule::new(Arc::new(Mutex::new(mock)));
+
+        let cancel_returns: CancelScheduledReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "ledger.cancelScheduled",
+                minicbor::to_vec(data).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(cancel_returns, EmptyReturn);
+    }
+
+    #[test]
+    fn scheduled() {
+        let mut mock = MockLedgerScheduleModuleBackend::new();
+        mock.expect_scheduled()
+            .with(eq(identity(1)), eq(ScheduledArgs::default()))
+            .times(1)
+            .returning(|_, _| {
+                Ok(ScheduledReturns {
+                    sends: BTreeMap::from([(1, scheduled_send())]),
+                })
+            });
+        let module = super::LedgerScheduleModule::new(Arc::new(Mutex::new(mock)));
+
+        let scheduled_returns: ScheduledReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "ledger.scheduled",
+                minicbor::to_vec(ScheduledArgs::default()).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(scheduled_returns.sends.get(&1), Some(&scheduled_send()));
+    }
+
+    proptest! {
+        #[test]
+        fn canonical_scheduled_send(
+            amount: u64,
+            height: u64,
+            deposit: u64,
+            memo in proptest::option::of("[A-Za-z0-9 ]{0,100}"),
+        ) {
+            assert_canonical_round_trip(&ScheduledSend {
+                amount: amount.into(),
+                at: ScheduleTrigger::Height(height),
+                memo: memo.map(|m| Memo::try_from(m).unwrap()),
+                deposit: deposit.into(),
+                ..scheduled_send()
+            });
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use crate::events::EventId;
+use many_error::{define_attribute_many_error, ManyError};
+use many_identity::Address;
+use many_macros::{many_module, Cddl};
+use many_types::proof::Proof;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+define_attribute_many_error!(
+    attribute 21 => {
+        1: pub fn event_not_found(id) => "Event {id} was not found.",
+    }
+);
+
+/// Proof that an event is part of the ledger state at a block height.
+///
+/// To verify a receipt offline, check the signature of its envelope against
+/// the server's public key, check that the proof contains the event key with
+/// a value hashing to `event_hash`, and that the proof resolves to `app_hash`,
+/// which is the app hash of the chain at `height`.
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct Receipt {
+    #[n(0)]
+    pub id: EventId,
+
+    /// SHA3-256 of the event, as encoded in the ledger state.
+    #[n(1)]
+    pub event_hash: ByteVec,
+
+    #[n(2)]
+    pub height: u64,
+
+    #[n(3)]
+    pub app_hash: ByteVec,
+
+    /// Inclusion proof of the event in the state at `app_hash`.
+    #[n(4)]
+    pub proof: Proof,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct GetReceiptArgs {
+    #[n(0)]
+    pub id: EventId,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct GetReceiptReturns {
+    #[n(0)]
+    pub receipt: Receipt,
+
+    /// A tagged COSE_Sign1 envelope signed by the server, whose payload is
+    /// the CBOR encoded receipt. Absent if the server does not sign receipts.
+    #[n(1)]
+    pub envelope: Option<ByteVec>,
+}
+
+/// Receipts of ledger transactions, which payers can keep and verify later
+/// without trusting the server that issued them.
+#[many_module(name = LedgerReceiptsModule, id = 21, namespace = ledger, many_modules_crate = crate)]
+#[cfg_attr(test, automock)]
+pub trait LedgerReceiptsModuleBackend: Send {
+    fn get_receipt(
+        &self,
+        sender: &Address,
+        args: GetReceiptArgs,
+    ) -> Result<GetReceiptReturns, ManyError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::call_module_cbor;
+    use many_identity::testing::identity;
+    use many_types::ProofOperation;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn get_receipt() {
+        let mut mock = MockLedgerReceiptsModuleBackend::new();
+        let data = GetReceiptArgs {
+            id: EventId::from(1u64),
+        };
+        let receipt = Receipt {
+            id: EventId::from(1u64),
+            event_hash: ByteVec::from(vec![1; 32]),
+            height: 10,
+            app_hash: ByteVec::from(vec![2; 32]),
+            proof: Proof {
+                operations: vec![ProofOperation::NodeHash(vec![3; 32])],
+            },
+        };
+        let returns = GetReceiptReturns {
+            receipt,
+            envelope: None,
+        };
+        mock.expect_get_receipt()
+            .with(eq(identity(1)), eq(data.clone()))
+            .times(1)
+            .return_const(Ok(returns.clone()));
+        let module = super::LedgerReceiptsModule::new(Arc::new(Mutex::new(mock)));
+
+        let receipt_returns: GetReceiptReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "ledger.getReceipt",
+                minicbor::to_vec(data).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(receipt_returns, returns);
+    }
+}
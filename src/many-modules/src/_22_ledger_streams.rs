@@ -0,0 +1,335 @@
+use many_error::{define_attribute_many_error, ManyError};
+use many_identity::Address;
+use many_macros::{many_module, Cddl};
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::{Memo, Timestamp};
+use minicbor::{Decode, Encode};
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+define_attribute_many_error!(
+    attribute 22 => {
+        1: pub fn stream_not_found(id) => "Stream {id} was not found.",
+        2: pub fn stream_end_in_the_past() => "A stream must end in the future.",
+        3: pub fn stream_rate_is_zero() => "The rate of a stream cannot be zero.",
+        4: pub fn nothing_to_withdraw(id) => "Nothing to withdraw from stream {id}.",
+    }
+);
+
+/// A stream of tokens from a payer to a payee, paid every second between its
+/// start and end. The payer deposits the whole amount when the stream is
+/// created, and the payee withdraws what accrued so far.
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct Stream {
+    #[n(0)]
+    pub payer: Address,
+
+    #[n(1)]
+    pub payee: Address,
+
+    #[n(2)]
+    pub symbol: Symbol,
+
+    /// The amount paid every second.
+    #[n(3)]
+    pub rate: TokenAmount,
+
+    #[n(4)]
+    pub start: Timestamp,
+
+    #[n(5)]
+    pub end: Timestamp,
+
+    /// The amount already withdrawn by the payee.
+    #[n(6)]
+    pub withdrawn: TokenAmount,
+}
+
+impl Stream {
+    /// The amount paid over the whole stream.
+    pub fn deposit(&self) -> TokenAmount {
+        self.rate.clone() * self.end.secs().saturating_sub(self.start.secs())
+    }
+
+    /// The amount paid between the start of the stream and `now`.
+    pub fn accrued(&self, now: Timestamp) -> TokenAmount {
+        self.rate.clone() * now.min(self.end).secs().saturating_sub(self.start.secs())
+    }
+
+    /// The amount the payee can withdraw at `now`.
+    pub fn withdrawable(&self, now: Timestamp) -> TokenAmount {
+        self.accrued(now).saturating_sub(&self.withdrawn)
+    }
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct StreamCreateArgs {
+    #[n(0)]
+    pub payee: Address,
+
+    #[n(1)]
+    pub symbol: Symbol,
+
+    #[n(2)]
+    pub rate: TokenAmount,
+
+    #[n(3)]
+    pub end: Timestamp,
+
+    #[n(4)]
+    pub memo: Option<Memo>,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct StreamCreateReturns {
+    #[n(0)]
+    pub id: u64,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct StreamWithdrawArgs {
+    #[n(0)]
+    pub id: u64,
+
+    #[n(1)]
+    pub memo: Option<Memo>,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct StreamWithdrawReturns {
+    #[n(0)]
+    pub amount: TokenAmount,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct StreamCancelArgs {
+    #[n(0)]
+    pub id: u64,
+
+    #[n(1)]
+    pub memo: Option<Memo>,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct StreamCancelReturns {
+    /// The amount paid to the payee, which had accrued but was not withdrawn.
+    #[n(0)]
+    pub paid: TokenAmount,
+
+    /// The amount returned to the payer, which had not accrued yet.
+    #[n(1)]
+    pub refunded: TokenAmount,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct StreamInfoArgs {
+    #[n(0)]
+    pub id: u64,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct StreamInfoReturns {
+    #[n(0)]
+    pub stream: Stream,
+
+    #[n(1)]
+    pub withdrawable: TokenAmount,
+}
+
+/// Payment streams, paying a payee continuously over time.
+#[many_module(name = LedgerStreamsModule, id = 22, namespace = ledger, many_modules_crate = crate)]
+#[cfg_attr(test, automock)]
+pub trait LedgerStreamsModuleBackend: Send {
+    fn stream_create(
+        &mut self,
+        sender: &Address,
+        args: StreamCreateArgs,
+    ) -> Result<StreamCreateReturns, ManyError>;
+    fn stream_withdraw(
+        &mut self,
+        sender: &Address,
+        args: StreamWithdrawArgs,
+    ) -> Result<StreamWithdrawReturns, ManyError>;
+    fn stream_cancel(
+        &mut self,
+        sender: &Address,
+        args: StreamCancelArgs,
+    ) -> Result<StreamCancelReturns, ManyError>;
+    fn stream_info(
+        &self,
+        sender: &Address,
+        args: StreamInfoArgs,
+    ) -> Result<StreamInfoReturns, ManyError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::{assert_canonical_round_trip, call_module_cbor};
+    use many_identity::testing::identity;
+    use proptest::prelude::*;
+    use std::sync::{Arc, Mutex};
+
+    fn stream() -> Stream {
+        Stream {
+            payer: identity(1),
+            payee: identity(2),
+            symbol: identity(3),
+            rate: TokenAmount::from(10u16),
+            start: Timestamp::new(1_000).unwrap(),
+            end: Timestamp::new(1_100).unwrap(),
+            withdrawn: TokenAmount::from(200u16),
+        }
+    }
+
+    #[test]
+    fn accrual() {
+        let stream = stream();
+        assert_eq!(stream.deposit(), 1_000u16);
+        assert_eq!(stream.accrued(Timestamp::new(999).unwrap()), 0u16);
+        assert_eq!(stream.accrued(Timestamp::new(1_050).unwrap()), 500u16);
+        assert_eq!(stream.accrued(Timestamp::new(2_000).unwrap()), 1_000u16);
+        assert_eq!(stream.withdrawable(Timestamp::new(1_050).unwrap()), 300u16);
+        assert_eq!(stream.withdrawable(Timestamp::new(1_010).unwrap()), 0u16);
+    }
+
+    #[test]
+    fn stream_create() {
+        let mut mock = MockLedgerStreamsModuleBackend::new();
+        let data = StreamCreateArgs {
+            payee: identity(2),
+            symbol: identity(3),
+            rate: TokenAmount::from(10u16),
+            end: Timestamp::new(1_100).unwrap(),
+            memo: None,
+        };
+        mock.expect_stream_create()
+            .with(eq(identity(1)), eq(data.clone()))
+            .times(1)
+            .returning(|_, _| Ok(StreamCreateReturns { id: 1 }));
+        let module = super::LedgerStreamsModule::new(Arc::new(Mutex::new(mock)));
+
+        let create_returns: StreamCreateReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "ledger.streamCreate",
+                minicbor::to_vec(data).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(create_returns.id, 1);
+    }
+
+    #[test]
+    fn stream_withdraw() {
+        let mut mock = MockLedgerStreamsModuleBackend::new();
+        let data = StreamWithdrawArgs { id: 1, memo: None };
+        mock.expect_stream_withdraw()
+            .with(eq(identity(2)), eq(data.clone()))
+            .times(1)
+            .returning(|_, _| {
+                Ok(StreamWithdrawReturns {
+                    amount: TokenAmount::from(300u16),
+                })
+            });
+        let module = super::LedgerStreamsModule::new(Arc::new(Mutex::new(mock)));
+
+        let withdraw_returns: StreamWithdrawReturns = minicbor::decode(
+            &call_module_cbor(
+                2,
+                &module,
+                "ledger.streamWithdraw",
+                minicbor::to_vec(data).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(withdraw_returns.amount, 300u16);
+    }
+
+    #[test]
+    fn stream_cancel() {
+        let mut mock = MockLedgerStreamsModuleBackend::new();
+        let data = StreamCancelArgs { id: 1, memo: None };
+        mock.expect_stream_cancel()
+            .with(eq(identity(1)), eq(data.clone()))
+            .times(1)
+            .returning(|_, _| {
+                Ok(StreamCancelReturns {
+                    paid: TokenAmount::from(300u16),
+                    refunded: TokenAmount::from(500u16),
+                })
+            });
+        let module = super::LedgerStreamsModule::new(Arc::new(Mutex::new(mock)));
+
+        let cancel_returns: StreamCancelReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "ledger.streamCancel",
+                minicbor::to_vec(data).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(cancel_returns.paid, 300u16);
+        assert_eq!(cancel_returns.refunded, 500u16);
+    }
+
+    #[test]
+    fn stream_info() {
+        let mut mock = MockLedgerStreamsModuleBackend::new();
+        mock.expect_stream_info()
+            .with(eq(identity(1)), eq(StreamInfoArgs { id: 1 }))
+            .times(1)
+            .returning(|_, _| {
+                Ok(StreamInfoReturns {
+                    stream: stream(),
+                    withdrawable: TokenAmount::from(300u16),
+                })
+            });
+        let module = super::LedgerStreamsModule::new(Arc::new(Mutex::new(mock)));
+
+        let info_returns: StreamInfoReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "ledger.streamInfo",
+                minicbor::to_vec(StreamInfoArgs { id: 1 }).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(info_returns.stream, stream());
+    }
+
+    proptest! {
+        #[test]
+        fn canonical_stream(
+            rate: u64,
+            start in 0..u32::MAX as u64,
+            duration in 0..u32::MAX as u64,
+            withdrawn: u64,
+        ) {
+            assert_canonical_round_trip(&Stream {
+                rate: rate.into(),
+                start: Timestamp::new(start).unwrap(),
+                end: Timestamp::new(start + duration).unwrap(),
+                withdrawn: withdrawn.into(),
+                ..stream()
+            });
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use crate::EmptyReturn;
+use many_error::{define_attribute_many_error, ManyError};
+use many_identity::Address;
+use many_macros::{many_module, Cddl};
+use many_types::Memo;
+use minicbor::{Decode, Encode};
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+define_attribute_many_error!(
+    attribute 23 => {
+        1: pub fn invalid_token_identity(identity) => "Address {identity} cannot be the token identity.",
+    }
+);
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct UpdateTokenIdentityArgs {
+    #[n(0)]
+    pub identity: Address,
+
+    #[n(1)]
+    pub memo: Option<Memo>,
+}
+
+pub type UpdateTokenIdentityReturns = EmptyReturn;
+
+/// Rotation of the token identity, the authority allowed to administer tokens.
+#[many_module(name = LedgerTokenIdentityModule, id = 23, namespace = ledger, many_modules_crate = crate)]
+#[cfg_attr(test, automock)]
+pub trait LedgerTokenIdentityModuleBackend: Send {
+    fn update_token_identity(
+        &mut self,
+        sender: &Address,
+        args: UpdateTokenIdentityArgs,
+    ) -> Result<UpdateTokenIdentityReturns, ManyError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::call_module_cbor;
+    use many_identity::testing::identity;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn update_token_identity() {
+        let mut mock = MockLedgerTokenIdentityModuleBackend::new();
+        let data = UpdateTokenIdentityArgs {
+            identity: identity(2),
+            memo: None,
+        };
+        mock.expect_update_token_identity()
+            .with(eq(identity(1)), eq(data.clone()))
+            .times(1)
+            .returning(|_, _| Ok(EmptyReturn));
+        let module = super::LedgerTokenIdentityModule::new(Arc::new(Mutex::new(mock)));
+
+        let update_returns: UpdateTokenIdentityReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "ledger.updateTokenIdentity",
+                minicbor::to_vec(data).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(update_returns, EmptyReturn);
+    }
+}
@@ -0,0 +1,166 @@
+use many_error::{define_attribute_many_error, ManyError};
+use many_identity::Address;
+use many_macros::{many_module, Cddl};
+use many_types::Memo;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+define_attribute_many_error!(
+    attribute 24 => {
+        1: pub fn script_not_found(id) => "Script {id} was not found.",
+        2: pub fn script_too_large(size, max) => "Script is {size} bytes, more than the maximum of {max} bytes.",
+        3: pub fn invalid_script(reason) => "Invalid script: {reason}.",
+        4: pub fn script_failed(reason) => "Script execution failed: {reason}.",
+        5: pub fn out_of_fuel(fuel) => "Script ran out of fuel after {fuel} units.",
+    }
+);
+
+/// The maximum size of the WASM code of a script, in bytes.
+pub const SCRIPT_MAX_SIZE: usize = 64 * 1024;
+
+/// The fuel given to an invocation which does not specify any.
+pub const SCRIPT_DEFAULT_FUEL: u64 = 1_000_000;
+
+/// The maximum fuel an invocation can use.
+pub const SCRIPT_MAX_FUEL: u64 = 10_000_000;
+
+/// A WASM program stored on the server.
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct Script {
+    #[n(0)]
+    pub owner: Address,
+
+    /// The SHA3-256 hash of the WASM code.
+    #[n(1)]
+    pub code_hash: ByteVec,
+
+    /// The size of the WASM code, in bytes.
+    #[n(2)]
+    pub size: u64,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ScriptDeployArgs {
+    /// The WASM code of the script, in binary format.
+    #[n(0)]
+    pub code: ByteVec,
+
+    #[n(1)]
+    pub memo: Option<Memo>,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ScriptDeployReturns {
+    #[n(0)]
+    pub id: u64,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ScriptInvokeArgs {
+    #[n(0)]
+    pub id: u64,
+
+    /// The exported function to call. It takes no parameters and returns
+    /// nothing; the input and output go through the host API.
+    #[n(1)]
+    pub function: String,
+
+    #[n(2)]
+    pub input: Option<ByteVec>,
+
+    /// The fuel available to the invocation. Defaults to
+    /// [SCRIPT_DEFAULT_FUEL], and cannot exceed [SCRIPT_MAX_FUEL].
+    #[n(3)]
+    pub fuel: Option<u64>,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ScriptInvokeReturns {
+    #[n(0)]
+    pub output: ByteVec,
+
+    #[n(1)]
+    pub fuel_used: u64,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ScriptInfoArgs {
+    #[n(0)]
+    pub id: u64,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ScriptInfoReturns {
+    #[n(0)]
+    pub script: Script,
+}
+
+/// Experimental. Small WASM programs executed with metered fuel against a
+/// constrained host API.
+#[many_module(name = ScriptsModule, id = 24, namespace = scripts, many_modules_crate = crate)]
+#[cfg_attr(test, automock)]
+pub trait ScriptsModuleBackend: Send {
+    fn deploy(
+        &mut self,
+        sender: &Address,
+        args: ScriptDeployArgs,
+    ) -> Result<ScriptDeployReturns, ManyError>;
+    fn invoke(
+        &mut self,
+        sender: &Address,
+        args: ScriptInvokeArgs,
+    ) -> Result<ScriptInvokeReturns, ManyError>;
+    fn info(&self, sender: &Address, args: ScriptInfoArgs) -> Result<ScriptInfoReturns, ManyError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::call_module_cbor;
+    use many_identity::testing::identity;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn invoke() {
+        let mut mock = MockScriptsModuleBackend::new();
+        let data = ScriptInvokeArgs {
+            id: 1,
+            function: "run".to_string(),
+            input: Some(vec![1, 2, 3].into()),
+            fuel: None,
+        };
+        mock.expect_invoke()
+            .with(eq(identity(1)), eq(data.clone()))
+            .times(1)
+            .returning(|_, _| {
+                Ok(ScriptInvokeReturns {
+                    output: vec![3, 2, 1].into(),
+                    fuel_used: 100,
+                })
+            });
+        let module = super::ScriptsModule::new(Arc::new(Mutex::new(mock)));
+
+        let invoke_returns: ScriptInvokeReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "scripts.invoke",
+                minicbor::to_vec(data).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(invoke_returns.output.as_slice(), &[3, 2, 1]);
+        assert_eq!(invoke_returns.fuel_used, 100);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::EmptyReturn;
+use many_error::{define_attribute_many_error, ManyError};
+use many_identity::Address;
+use many_macros::{many_module, Cddl};
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::Memo;
+use minicbor::{Decode, Encode};
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+define_attribute_many_error!(
+    attribute 25 => {
+        1: pub fn invalid_label(label) => "Invalid label '{label}': labels are 3 to 32 lowercase letters, digits or dashes, and cannot start or end with a dash.",
+        2: pub fn label_taken(label) => "Label '{label}' is already claimed.",
+        3: pub fn label_not_found(label) => "Label '{label}' was not found.",
+        4: pub fn address_already_labeled(address) => "Address {address} already has a label.",
+        5: pub fn address_not_labeled(address) => "Address {address} does not have a label.",
+    }
+);
+
+/// The minimum length of a label.
+pub const LABEL_MIN_LENGTH: usize = 3;
+
+/// The maximum length of a label.
+pub const LABEL_MAX_LENGTH: usize = 32;
+
+/// Check that a label is well formed.
+pub fn validate_label(label: &str) -> Result<(), ManyError> {
+    let valid = (LABEL_MIN_LENGTH..=LABEL_MAX_LENGTH).contains(&label.len())
+        && label
+            .bytes()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == b'-')
+        && !label.starts_with('-')
+        && !label.ends_with('-');
+    if valid {
+        Ok(())
+    } else {
+        Err(invalid_label(label))
+    }
+}
+
+/// A claimed label, with the deposit locked until it is released.
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct Label {
+    #[n(0)]
+    pub label: String,
+
+    #[n(1)]
+    pub owner: Address,
+
+    #[n(2)]
+    pub symbol: Symbol,
+
+    #[n(3)]
+    pub deposit: TokenAmount,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ClaimArgs {
+    #[n(0)]
+    pub label: String,
+
+    #[n(1)]
+    pub memo: Option<Memo>,
+}
+
+pub type ClaimReturns = EmptyReturn;
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ReleaseArgs {
+    #[n(0)]
+    pub memo: Option<Memo>,
+}
+
+pub type ReleaseReturns = EmptyReturn;
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ResolveArgs {
+    #[n(0)]
+    pub label: String,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ResolveReturns {
+    #[n(0)]
+    pub label: Label,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct LookupArgs {
+    #[n(0)]
+    pub address: Address,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct LookupReturns {
+    #[n(0)]
+    pub label: Option<Label>,
+}
+
+/// Human-readable labels, claimed by address owners against a deposit. A
+/// label is unique per chain and an address has at most one label.
+#[many_module(name = LabelsModule, id = 25, namespace = labels, many_modules_crate = crate)]
+#[cfg_attr(test, automock)]
+pub trait LabelsModuleBackend: Send {
+    /// Claim a label for the sender, locking the deposit.
+    fn claim(&mut self, sender: &Address, args: ClaimArgs) -> Result<ClaimReturns, ManyError>;
+
+    /// Release the label of the sender, refunding the deposit.
+    fn release(&mut self, sender: &Address, args: ReleaseArgs)
+        -> Result<ReleaseReturns, ManyError>;
+
+    /// The address a label belongs to.
+    fn resolve(&self, sender: &Address, args: ResolveArgs) -> Result<ResolveReturns, ManyError>;
+
+    /// The label of an address, if any.
+    fn lookup(&self, sender: &Address, args: LookupArgs) -> Result<LookupReturns, ManyError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::{assert_canonical_round_trip, call_module_cbor};
+    use many_identity::testing::identity;
+    use proptest::prelude::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn labels() {
+        assert!(validate_label("alice").is_ok());
+        assert!(validate_label("bob-42").is_ok());
+        assert!(validate_label("ab").is_err());
+        assert!(validate_label("Alice").is_err());
+        assert!(validate_label("-alice").is_err());
+        assert!(validate_label("alice-").is_err());
+        assert!(validate_label("alice.many").is_err());
+        assert!(validate_label(&"a".repeat(LABEL_MAX_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn resolve() {
+        let mut mock = MockLabelsModuleBackend::new();
+        let data = ResolveArgs {
+            label: "alice".to_string(),
+        };
+        mock.expect_resolve()
+            .with(eq(identity(1)), eq(data.clone()))
+            .times(1)
+            .returning(|_, args| {
+                Ok(ResolveReturns {
+                    label: Label {
+                        label: args.label,
+                        owner: identity(2),
+                        symbol: identity(3),
+                        deposit: 10u16.into(),
+                    },
+                })
+            });
+        let module = super::LabelsModule::new(Arc::new(Mutex::new(mock)));
+
+        let resolve_returns: ResolveReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "labels.resolve",
+                minicbor::to_vec(data).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(resolve_returns.label.owner, identity(2));
+        assert_eq!(resolve_returns.label.label, "alice");
+    }
+
+    proptest! {
+        #[test]
+        fn canonical_label(label in "[a-z0-9]{3,63}", owner in 0u32..1000, deposit: u64) {
+            assert_canonical_round_trip(&Label {
+                label,
+                owner: identity(owner),
+                symbol: identity(1000),
+                deposit: deposit.into(),
+            });
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use many_error::{define_attribute_many_error, ManyError};
+use many_identity::Address;
+use many_macros::{many_module, Cddl};
+use many_types::Timestamp;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+define_attribute_many_error!(
+    attribute 26 => {
+        1: pub fn not_an_operator(address) => "Address {address} is not an operator of this server.",
+    }
+);
+
+/// A request the server refused before executing it.
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct Rejection {
+    #[n(0)]
+    pub time: Timestamp,
+
+    /// Why the request was refused.
+    #[n(1)]
+    pub reason: String,
+
+    /// The sender of the request, if it could be decoded. It might not be
+    /// verified, e.g. if the signature of the envelope was invalid.
+    #[n(2)]
+    pub sender: Option<Address>,
+
+    #[n(3)]
+    pub method: Option<String>,
+
+    /// The intent hash of the request, as used by `events.findByIntentHash`.
+    #[n(4)]
+    pub correlation_id: Option<ByteVec>,
+}
+
+#[derive(Cddl, Clone, Debug, Default, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ListArgs {
+    /// Return at most this many rejections, the most recent ones.
+    #[n(0)]
+    pub count: Option<u64>,
+
+    /// Only return the rejections of this sender.
+    #[n(1)]
+    pub sender: Option<Address>,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ListReturns {
+    /// The rejections, oldest first.
+    #[n(0)]
+    pub rejections: Vec<Rejection>,
+
+    /// The number of rejections no longer kept by the server.
+    #[n(1)]
+    pub dropped: u64,
+}
+
+/// Recent requests refused by the server or its consensus layer, so operators
+/// can find why a transaction did not go through. Only the operators of the
+/// server can list them.
+#[many_module(name = RejectionsModule, id = 26, namespace = rejections, many_modules_crate = crate)]
+#[cfg_attr(test, automock)]
+pub trait RejectionsModuleBackend: Send {
+    fn list(&self, sender: &Address, args: ListArgs) -> Result<ListReturns, ManyError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::call_module_cbor;
+    use many_identity::testing::identity;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn list() {
+        let mut mock = MockRejectionsModuleBackend::new();
+        let data = ListArgs {
+            count: Some(1),
+            sender: Some(identity(2)),
+        };
+        mock.expect_list()
+            .with(eq(identity(1)), eq(data.clone()))
+            .times(1)
+            .returning(|_, args| {
+                Ok(ListReturns {
+                    rejections: vec![Rejection {
+                        time: Timestamp::new(1_000).unwrap(),
+                        reason: "Invalid signature.".to_string(),
+                        sender: args.sender,
+                        method: Some("ledger.send".to_string()),
+                        correlation_id: Some(ByteVec::from(vec![1, 2, 3])),
+                    }],
+                    dropped: 4,
+                })
+            });
+        let module = super::RejectionsModule::new(Arc::new(Mutex::new(mock)));
+
+        let list_returns: ListReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "rejections.list",
+                minicbor::to_vec(data).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(list_returns.dropped, 4);
+        assert_eq!(list_returns.rejections[0].sender, Some(identity(2)));
+    }
+}
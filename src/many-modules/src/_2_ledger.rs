@@ -77,6 +77,7 @@ mod tests {
                 hash: ByteVec::from(vec![10u8; 8]),
                 local_names: BTreeMap::from([(*SYMBOL, SYMBOL_NAME.to_string())]),
                 tokens: Default::default(),
+                memo_limits: None,
             }));
         let module = super::LedgerModule::new(Arc::new(Mutex::new(mock)));
 
@@ -1,9 +1,10 @@
 use many_identity::Address;
+use many_macros::Cddl;
 use many_types::{ledger, VecOrSingle};
 use minicbor::{Decode, Encode};
 use std::collections::BTreeMap;
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct BalanceArgs {
     #[n(0)]
@@ -13,7 +14,7 @@ pub struct BalanceArgs {
     pub symbols: Option<VecOrSingle<ledger::Symbol>>,
 }
 
-#[derive(Clone, Encode, Decode)]
+#[derive(Cddl, Clone, Encode, Decode)]
 #[cbor(map)]
 pub struct BalanceReturns {
     #[n(0)]
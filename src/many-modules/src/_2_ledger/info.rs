@@ -1,12 +1,13 @@
 use crate::EmptyArg;
-use many_types::ledger;
+use many_macros::Cddl;
+use many_types::{ledger, MemoLimits};
 use minicbor::bytes::ByteVec;
 use minicbor::{Decode, Encode};
 use std::collections::BTreeMap;
 
 pub type InfoArgs = EmptyArg;
 
-#[derive(Clone, Debug, Decode, Encode)]
+#[derive(Cddl, Clone, Debug, Decode, Encode)]
 #[cbor(map)]
 pub struct InfoReturns {
     // TODO: remove this. Kept for backward compatibility.
@@ -25,4 +26,8 @@ pub struct InfoReturns {
 
     #[n(5)]
     pub tokens: BTreeMap<ledger::Symbol, ledger::TokenInfoSummary>,
+
+    /// The limits this server enforces on memos.
+    #[n(6)]
+    pub memo_limits: Option<MemoLimits>,
 }
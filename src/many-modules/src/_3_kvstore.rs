@@ -3,6 +3,7 @@ use crate::kvstore::list::ListReturns;
 use many_error::ManyError;
 use many_identity::Address;
 use many_macros::many_module;
+use many_protocol::context::Context;
 use minicbor::{decode, encode};
 
 #[cfg(test)]
@@ -11,18 +12,32 @@ use mockall::{automock, predicate::*};
 pub mod get;
 pub mod info;
 pub mod list;
+pub mod multi_get;
 pub mod query;
 pub use get::*;
 pub use info::*;
+pub use multi_get::*;
 pub use query::*;
 
 #[many_module(name = KvStoreModule, id = 3, namespace = kvstore, many_modules_crate = crate)]
 #[cfg_attr(test, automock)]
 pub trait KvStoreModuleBackend: Send {
     fn info(&self, sender: &Address, args: InfoArg) -> Result<InfoReturns, ManyError>;
-    fn get(&self, sender: &Address, args: GetArgs) -> Result<GetReturns, ManyError>;
-    fn query(&self, sender: &Address, args: QueryArgs) -> Result<QueryReturns, ManyError>;
+    fn get(
+        &self,
+        sender: &Address,
+        args: GetArgs,
+        context: Context,
+    ) -> Result<GetReturns, ManyError>;
+    fn query(
+        &self,
+        sender: &Address,
+        args: QueryArgs,
+        context: Context,
+    ) -> Result<QueryReturns, ManyError>;
     fn list(&self, sender: &Address, args: ListArgs) -> Result<ListReturns, ManyError>;
+    fn multi_get(&self, sender: &Address, args: MultiGetArgs)
+        -> Result<MultiGetReturns, ManyError>;
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -135,9 +150,13 @@ mod tests {
         };
         let mut mock = MockKvStoreModuleBackend::new();
         mock.expect_get()
-            .with(predicate::eq(identity(1)), predicate::eq(data.clone()))
+            .with(
+                predicate::eq(identity(1)),
+                predicate::eq(data.clone()),
+                predicate::always(),
+            )
             .times(1)
-            .returning(|_id, _args| {
+            .returning(|_id, _args, _| {
                 Ok(GetReturns {
                     value: Some(ByteVec::from(vec![1, 2, 3, 4])),
                 })
@@ -152,6 +171,39 @@ mod tests {
         assert_eq!(get_returns.value, Some(ByteVec::from(vec![1, 2, 3, 4])));
     }
 
+    #[test]
+    fn multi_get() {
+        let data = MultiGetArgs {
+            keys: vec![ByteVec::from(vec![5]), ByteVec::from(vec![6])],
+        };
+        let mut mock = MockKvStoreModuleBackend::new();
+        mock.expect_multi_get()
+            .with(predicate::eq(identity(1)), predicate::eq(data.clone()))
+            .times(1)
+            .returning(|_id, _args| {
+                Ok(MultiGetReturns {
+                    values: vec![Some(ByteVec::from(vec![1, 2])), None],
+                })
+            });
+        let module = super::KvStoreModule::new(Arc::new(Mutex::new(mock)));
+
+        let multi_get_returns: MultiGetReturns = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "kvstore.multiGet",
+                minicbor::to_vec(data).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            multi_get_returns.values,
+            vec![Some(ByteVec::from(vec![1, 2])), None]
+        );
+    }
+
     #[test]
     fn query() {
         let data = QueryArgs {
@@ -159,13 +211,19 @@ mod tests {
         };
         let mut mock = MockKvStoreModuleBackend::new();
         mock.expect_query()
-            .with(predicate::eq(identity(1)), predicate::eq(data.clone()))
+            .with(
+                predicate::eq(identity(1)),
+                predicate::eq(data.clone()),
+                predicate::always(),
+            )
             .times(1)
-            .returning(|_id, _args| {
+            .returning(|_id, _args, _| {
                 Ok(QueryReturns {
                     owner: identity(666),
                     disabled: None,
                     previous_owner: None,
+                    expires_at: None,
+                    hash: None,
                 })
             });
         let module = super::KvStoreModule::new(Arc::new(Mutex::new(mock)));
@@ -185,6 +243,8 @@ mod tests {
         mock.expect_list().times(1).returning(|_id, _args| {
             Ok(ListReturns {
                 keys: vec![vec![1].into(), vec![2].into()],
+                values: None,
+                next: None,
             })
         });
         let module = super::KvStoreModule::new(Arc::new(Mutex::new(mock)));
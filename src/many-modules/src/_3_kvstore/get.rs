@@ -1,14 +1,15 @@
+use many_macros::Cddl;
 use minicbor::bytes::ByteVec;
 use minicbor::{Decode, Encode};
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct GetArgs {
     #[n(0)]
     pub key: ByteVec,
 }
 
-#[derive(Clone, Debug, Encode, Decode)]
+#[derive(Cddl, Clone, Debug, Encode, Decode)]
 #[cbor(map)]
 pub struct GetReturns {
     #[n(0)]
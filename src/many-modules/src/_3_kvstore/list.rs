@@ -1,11 +1,18 @@
 use crate::kvstore::KeyFilterType;
+use many_macros::Cddl;
 use many_types::SortOrder;
 use minicbor::bytes::ByteVec;
 use minicbor::{Decode, Encode};
 
-#[derive(Clone, Decode, Encode)]
+/// Maximum number of keys returned by a single call to `kvstore.list`. Use
+/// the `next` cursor to list more.
+pub const MAXIMUM_LIST_COUNT: u64 = 1000;
+
+#[derive(Cddl, Clone, Decode, Encode)]
 #[cbor(map)]
 pub struct ListArgs {
+    /// The maximum number of keys to return, at most [MAXIMUM_LIST_COUNT]
+    /// (the default).
     #[n(0)]
     pub count: Option<u64>,
 
@@ -14,11 +21,34 @@ pub struct ListArgs {
 
     #[n(2)]
     pub filter: Option<Vec<KeyFilterType>>,
+
+    /// Only list the keys starting with these bytes.
+    #[n(3)]
+    pub prefix: Option<ByteVec>,
+
+    /// Start listing after this key, in the order requested. This is the
+    /// `next` cursor returned by a previous call.
+    #[n(4)]
+    pub start_after: Option<ByteVec>,
+
+    /// Also return the value of every key listed.
+    #[n(5)]
+    pub with_values: Option<bool>,
 }
 
-#[derive(Clone, Decode, Encode)]
+#[derive(Cddl, Clone, Decode, Encode)]
 #[cbor(map)]
 pub struct ListReturns {
     #[n(0)]
     pub keys: Vec<ByteVec>,
+
+    /// The values of the keys listed, in the same order, if `with_values` was
+    /// set. Disabled keys have no value.
+    #[n(1)]
+    pub values: Option<Vec<Option<ByteVec>>>,
+
+    /// The last key listed, if there are more keys to list. Pass it as
+    /// `start_after` to get the next page.
+    #[n(2)]
+    pub next: Option<ByteVec>,
 }
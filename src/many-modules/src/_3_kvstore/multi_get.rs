@@ -0,0 +1,23 @@
+use many_macros::Cddl;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+
+/// The maximum number of keys read by `kvstore.multiGet` or written by
+/// `kvstore.multiPut` in a single call.
+pub const KVSTORE_MULTI_MAX_KEYS: usize = 256;
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct MultiGetArgs {
+    #[n(0)]
+    pub keys: Vec<ByteVec>,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct MultiGetReturns {
+    /// The values, in the same order as the keys. Missing, disabled and
+    /// expired keys have no value.
+    #[n(0)]
+    pub values: Vec<Option<ByteVec>>,
+}
@@ -1,17 +1,18 @@
 use many_error::Reason;
 use many_identity::Address;
-use many_types::Either;
+use many_macros::Cddl;
+use many_types::{Either, Timestamp};
 use minicbor::bytes::ByteVec;
 use minicbor::{Decode, Encode};
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct QueryArgs {
     #[n(0)]
     pub key: ByteVec,
 }
 
-#[derive(Clone, Debug, Encode, Decode)]
+#[derive(Cddl, Clone, Debug, Encode, Decode)]
 #[cbor(map)]
 pub struct QueryReturns {
     #[n(0)]
@@ -22,4 +23,11 @@ pub struct QueryReturns {
 
     #[n(2)]
     pub previous_owner: Option<Address>,
+
+    #[n(3)]
+    pub expires_at: Option<Timestamp>,
+
+    /// The SHA3-256 hash of the value, to pass to `kvstore.cas`.
+    #[n(4)]
+    pub hash: Option<ByteVec>,
 }
@@ -1,28 +1,53 @@
 use crate as module;
 use crate::account::features::multisig::MultisigTransactionState;
 use crate::account::AddressRoleMap;
+use crate::events::{AddressContainer, EventId};
 use many_error::{ManyError, Reason};
 use many_identity::Address;
-use many_macros::many_module;
+use many_macros::{many_module, Cddl};
 use many_protocol::ResponseMessage;
+use many_types::diag;
 use many_types::ledger;
 use many_types::ledger::{Symbol, TokenAmount};
 use many_types::legacy::{DataLegacy, MemoLegacy};
 use many_types::{AttributeRelatedIndex, CborRange, Either, Memo, Timestamp, VecOrSingle};
 use minicbor::bytes::ByteVec;
 use minicbor::{encode, Decode, Decoder, Encode, Encoder};
-use num_bigint::BigUint;
 use std::collections::{BTreeMap, BTreeSet};
-use std::sync::Arc;
 
 #[cfg(test)]
 use mockall::{automock, predicate::*};
 
+mod archive;
+mod durable;
 mod info;
+mod intent;
 mod list;
+mod subscribe;
 
+pub use archive::*;
+pub use durable::*;
 pub use info::*;
+pub use intent::*;
 pub use list::*;
+pub use subscribe::*;
+
+pub mod errors {
+    use many_error::define_attribute_many_error;
+    define_attribute_many_error!(
+        attribute 4 => {
+            1: pub fn unknown_subscription(id) => "Unknown subscription {id}.",
+            2: pub fn too_many_subscriptions() => "Too many subscriptions on this server.",
+            3: pub fn durable_subscriptions_unsupported() => "This server does not keep durable subscriptions.",
+            4: pub fn not_subscription_owner(id) => "Only the owner of subscription {id} can manage it.",
+            5: pub fn invalid_subscription_target(target) => "Invalid subscription target '{target}', expected an HTTP(S) URL.",
+            6: pub fn invalid_intent_hash(length) => "Invalid intent hash of {length} bytes, expected 32.",
+            7: pub fn too_many_sender_subscriptions(maximum) => "Senders can have at most {maximum} subscriptions.",
+            8: pub fn not_subscription_operator() => "Only the operators of this server can create durable subscriptions.",
+            9: pub fn private_subscription_target(target) => "Subscription target '{target}' is not a public address.",
+        }
+    );
+}
 
 #[many_module(name = EventsModule, id = 4, namespace = events, many_modules_crate = crate)]
 #[cfg_attr(test, automock)]
@@ -31,105 +56,75 @@ pub trait EventsModuleBackend: Send {
     fn list(&self, args: ListArgs) -> Result<ListReturns, ManyError>;
 }
 
-#[derive(Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
-#[repr(transparent)]
-pub struct EventId(ByteVec);
-
-impl From<ByteVec> for EventId {
-    fn from(t: ByteVec) -> EventId {
-        EventId(t)
-    }
-}
-
-impl From<EventId> for ByteVec {
-    fn from(id: EventId) -> Self {
-        id.0
-    }
-}
-
-impl AsRef<[u8]> for EventId {
-    fn as_ref(&self) -> &[u8] {
-        self.0.as_ref()
-    }
-}
-
-impl From<Vec<u8>> for EventId {
-    fn from(t: Vec<u8>) -> EventId {
-        EventId(ByteVec::from(t))
-    }
-}
-
-impl From<u64> for EventId {
-    fn from(v: u64) -> EventId {
-        EventId(ByteVec::from(v.to_be_bytes().to_vec()))
-    }
-}
-
-impl From<BigUint> for EventId {
-    fn from(b: BigUint) -> EventId {
-        EventId(ByteVec::from(b.to_bytes_be()))
-    }
-}
-
-impl std::ops::Add<ByteVec> for EventId {
-    type Output = EventId;
-
-    fn add(self, rhs: ByteVec) -> Self::Output {
-        (BigUint::from_bytes_be(&self.0) + BigUint::from_bytes_be(&rhs)).into()
-    }
-}
-
-impl std::ops::Add<u32> for EventId {
-    type Output = EventId;
-
-    fn add(self, rhs: u32) -> Self::Output {
-        (BigUint::from_bytes_be(&self.0) + rhs).into()
-    }
-}
-
-impl std::ops::AddAssign<u32> for EventId {
-    fn add_assign(&mut self, other: u32) {
-        *self = self.clone() + other;
-    }
-}
-
-impl std::ops::Sub<ByteVec> for EventId {
-    type Output = EventId;
-
-    fn sub(self, rhs: ByteVec) -> Self::Output {
-        (BigUint::from_bytes_be(&self.0) - BigUint::from_bytes_be(&rhs)).into()
-    }
-}
-
-impl std::ops::Sub<u32> for EventId {
-    type Output = EventId;
-
-    fn sub(self, rhs: u32) -> Self::Output {
-        (BigUint::from_bytes_be(&self.0) - rhs).into()
-    }
+/// Subscriptions to new events. Clients subscribe with a filter, then poll for
+/// the events committed since their last poll instead of listing all events.
+/// Only the sender of `events.subscribe` can poll and remove a subscription.
+#[many_module(name = EventsSubscriptionModule, namespace = events, many_modules_crate = crate)]
+#[cfg_attr(test, automock)]
+pub trait EventsSubscriptionModuleBackend: Send {
+    #[many(deny_anonymous)]
+    fn subscribe(
+        &mut self,
+        sender: &Address,
+        args: SubscribeArgs,
+    ) -> Result<SubscribeReturn, ManyError>;
+
+    #[many(deny_anonymous)]
+    fn poll(&mut self, sender: &Address, args: PollArgs) -> Result<PollReturn, ManyError>;
+
+    #[many(deny_anonymous)]
+    fn unsubscribe(
+        &mut self,
+        sender: &Address,
+        args: UnsubscribeArgs,
+    ) -> Result<UnsubscribeReturn, ManyError>;
 }
 
-impl<C> Encode<C> for EventId {
-    fn encode<W: encode::Write>(
+/// Subscriptions kept by the server across restarts, for clients which cannot
+/// miss an event, e.g. payment processors. Servers post the matching events to
+/// the target of a subscription until it acknowledges them.
+#[many_module(name = EventsDurableSubscriptionModule, namespace = events, many_modules_crate = crate)]
+#[cfg_attr(test, automock)]
+pub trait EventsDurableSubscriptionModuleBackend: Send {
+    #[many(deny_anonymous)]
+    fn create_subscription(
+        &mut self,
+        sender: &Address,
+        args: CreateSubscriptionArgs,
+    ) -> Result<CreateSubscriptionReturn, ManyError>;
+
+    #[many(deny_anonymous)]
+    fn list_subscriptions(
         &self,
-        e: &mut Encoder<W>,
-        _: &mut C,
-    ) -> Result<(), encode::Error<W::Error>> {
-        e.bytes(&self.0)?;
-        Ok(())
-    }
+        sender: &Address,
+        args: ListSubscriptionsArgs,
+    ) -> Result<ListSubscriptionsReturns, ManyError>;
+
+    #[many(deny_anonymous)]
+    fn delete_subscription(
+        &mut self,
+        sender: &Address,
+        args: DeleteSubscriptionArgs,
+    ) -> Result<DeleteSubscriptionReturn, ManyError>;
 }
 
-impl<'b, C> Decode<'b, C> for EventId {
-    fn decode(d: &mut Decoder<'b>, _: &mut C) -> Result<Self, minicbor::decode::Error> {
-        Ok(EventId(ByteVec::from(d.bytes()?.to_vec())))
-    }
+/// Information about the events moved out of the live store, for servers that
+/// only keep recent events.
+#[many_module(name = EventsArchiveModule, namespace = events, many_modules_crate = crate)]
+#[cfg_attr(test, automock)]
+pub trait EventsArchiveModuleBackend: Send {
+    fn archive_info(&self, args: ArchiveInfoArgs) -> Result<ArchiveInfoReturn, ManyError>;
 }
 
-impl From<EventId> for Vec<u8> {
-    fn from(t: EventId) -> Vec<u8> {
-        t.0.to_vec()
-    }
+/// Lookup of the events caused by a request, for clients which kept the
+/// request they signed but not its response.
+#[many_module(name = EventsIntentModule, namespace = events, many_modules_crate = crate)]
+#[cfg_attr(test, automock)]
+pub trait EventsIntentModuleBackend: Send {
+    fn find_by_intent_hash(
+        &self,
+        args: FindByIntentHashArgs,
+    ) -> Result<FindByIntentHashReturns, ManyError>;
 }
 
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
@@ -293,44 +288,6 @@ impl<'b> Decode<'b, EventFilterAttributeSpecificIndex> for EventFilterAttributeS
     }
 }
 
-/// A trait that can apply to
-pub trait AddressContainer {
-    fn addresses(&self) -> BTreeSet<Address>;
-}
-
-impl<T: AddressContainer> AddressContainer for Box<T> {
-    fn addresses(&self) -> BTreeSet<Address> {
-        self.as_ref().addresses()
-    }
-}
-
-impl<T: AddressContainer> AddressContainer for Arc<T> {
-    fn addresses(&self) -> BTreeSet<Address> {
-        self.as_ref().addresses()
-    }
-}
-
-impl AddressContainer for Address {
-    fn addresses(&self) -> BTreeSet<Address> {
-        BTreeSet::from([*self])
-    }
-}
-
-impl<I: AddressContainer> AddressContainer for Option<I> {
-    fn addresses(&self) -> BTreeSet<Address> {
-        match self {
-            Some(t) => t.addresses(),
-            None => BTreeSet::new(),
-        }
-    }
-}
-
-impl<V> AddressContainer for BTreeMap<Address, V> {
-    fn addresses(&self) -> BTreeSet<Address> {
-        self.keys().cloned().collect()
-    }
-}
-
 macro_rules! define_event_kind {
     ( $( [ $index: literal $(, $sub: literal )* ] $name: ident { $( $idx: literal | $fname: ident : $type: ty, )* }, )* ) => {
         #[derive(
@@ -497,6 +454,12 @@ macro_rules! event_info_count_field {
             None => 0u64,
         }
     };
+    (@single $name: ident [ optional $( $tag: ident )* ]) => {
+        match $name {
+            Some(_) => 1u64,
+            None => 0u64,
+        }
+    };
     (@single $name: ident [ $head: ident $( $tail: ident )* ]) => {
         event_info_count_field!(@single $name [ $( $tail )* ] )
     };
@@ -516,6 +479,11 @@ macro_rules! encode_event_info_field {
             $e.u8($idx)?.encode(field)?;
         }
     };
+    (@inner $e: ident $idx: literal $name: ident [ optional $( $tail: ident )* ]) => {
+        if let Some(field) = $name {
+            $e.u8($idx)?.encode(field)?;
+        }
+    };
     (@inner $e: ident $idx: literal $name: ident [ $head: ident $( $tail: ident )* ]) => {
         encode_event_info_field!($e $idx $name [ $( $tail )* ])
     };
@@ -535,6 +503,12 @@ macro_rules! encode_event_info_unpack_decode {
             None => Ok(None),
         }
     };
+    (@inner $name: ident $idx: literal [optional $( $tail: ident )*]) => {
+        match $name {
+            Some(x) => Ok(x),
+            None => Ok(None),
+        }
+    };
     (@inner $name: ident $idx: literal [$head: ident $( $tail: ident )*]) => {
         encode_event_info_unpack_decode!( $name $idx [$( $tail )*] )
     };
@@ -615,6 +589,16 @@ macro_rules! define_multisig_event {
         BTreeSet::new()
     };
 
+    (@memo $arg: ident [ memo $( $struct_tag: ident )* ]) => {
+        $arg .memo.as_ref()
+    };
+    (@memo $arg: ident [ $struct_tag: ident $( $last: ident )* ]) => {
+        define_multisig_event!(@memo $arg [ $( $last )* ])
+    };
+    (@memo $arg: ident []) => {
+        None
+    };
+
     ( $( $name: ident $(: $arg: ty $([ $( $struct_tag: ident )* ])? )?, )* ) => {
         #[derive(Clone, Debug, Eq, PartialEq)]
         #[non_exhaustive]
@@ -626,6 +610,20 @@ macro_rules! define_multisig_event {
             pub fn is_about(&self, id: Address) -> bool {
                 self.addresses().contains(&id)
             }
+
+            /// The memo of the arguments of this transaction, if they have one.
+            pub fn memo(&self) -> Option<&Memo> {
+                match self {
+                    $(
+                    $( AccountMultisigTransaction :: $name(arg) => {
+                        let _: $arg;  // We do this to remove a macro error for not using $arg.
+                        let _ = arg;  // Same, but at rustc level (after macro expansions).
+
+                        define_multisig_event!(@memo arg [ $( $( $struct_tag )* )? ])
+                    }, )?
+                    )*
+                }
+            }
         }
 
         impl AddressContainer for AccountMultisigTransaction {
@@ -702,12 +700,32 @@ macro_rules! define_event {
         define_event_info!( $( $name { $( $idx | $fname : $type $([ $( $tag )* ])?, )* }, )* );
 
         define_multisig_event!( $( $name $(: $method_arg $([ $( $struct_tag )* ])? )?, )* );
+
+        /// The schema of an [EventInfo], to print it with [many_types::diag].
+        pub const EVENT_INFO_SCHEMA: diag::Schema = diag::Schema::Variants(&[
+            $(
+                diag::Variant {
+                    kind: &[ $index $(, $sub )* ],
+                    name: stringify!($name),
+                    fields: &[
+                        diag::Field { key: 0, name: "type", schema: &diag::Schema::Any },
+                        $( diag::Field { key: $idx, name: stringify!($fname), schema: &diag::Schema::Any }, )*
+                    ],
+                },
+            )*
+        ]);
     }
 }
 
 // We flatten the attribute related index here, but it is unflattened when serializing.
 define_event! {
-    [6, 0]      Send (crate::ledger::SendArgs [ addresses ]) {
+    [2, 0]      DustReap {
+        1     | account:                Address                                [ id ],
+        2     | symbol:                 Symbol                                 [ id ],
+        3     | amount:                 TokenAmount,
+        4     | pool:                   Address                                [ id ],
+    },
+    [6, 0]      Send (crate::ledger::SendArgs [ addresses memo ]) {
         1     | from:                   Address                                [ id ],
         2     | to:                     Address                                [ id ],
         3     | symbol:                 Symbol                                 [ id ],
@@ -749,9 +767,15 @@ define_event! {
         2     | roles:                  AddressRoleMap                         [ id ],
         3     | features:               crate::account::features::FeatureSet,
     },
-    [9, 1, 0]   AccountMultisigSubmit (crate::account::features::multisig::SubmitTransactionArgs [ addresses ]) {
+    [9, 6]      AccountRemoveFeatures {
+        1     | account:                Address                                [ id ],
+        2     | features:               BTreeSet<crate::account::features::FeatureId>,
+        3     | roles:                  AddressRoleMap                         [ id ],
+    },
+    [9, 1, 0]   AccountMultisigSubmit (crate::account::features::multisig::SubmitTransactionArgs [ addresses memo ]) {
         1     | submitter:              Address                                [ id ],
         2     | account:                Address                                [ id ],
+        // `memo_` and `data_` are deprecated, use `memo` instead.
         3     | memo_:                  Option<MemoLegacy<String>>,
         4     | transaction:            Box<AccountMultisigTransaction>        [ id ],
         5     | token:                  Option<ByteVec>,
@@ -765,11 +789,13 @@ define_event! {
         1     | account:                Address                                [ id ],
         2     | token:                  ByteVec,
         3     | approver:               Address                                [ id ],
+        4     | approved_as:            Option<Address>                        [ id optional ],
     },
     [9, 1, 2]   AccountMultisigRevoke (crate::account::features::multisig::RevokeArgs) {
         1     | account:                Address                                [ id ],
         2     | token:                  ByteVec,
         3     | revoker:                Address                                [ id ],
+        4     | revoked_as:             Option<Address>                        [ id optional ],
     },
     [9, 1, 3]   AccountMultisigExecute (crate::account::features::multisig::ExecuteArgs) {
         1     | account:                Address                                [ id ],
@@ -794,7 +820,7 @@ define_event! {
         2     | token:                  ByteVec,
         3     | time:                   Timestamp,
     },
-    [11, 0]     TokenCreate (module::ledger::TokenCreateArgs) {
+    [11, 0]     TokenCreate (module::ledger::TokenCreateArgs [ memo ]) {
         1     | summary:                ledger::TokenInfoSummary,
         2     | symbol:                 Address                                [ id ],
         3     | owner:                  Option<ledger::TokenMaybeOwner>        [ maybe_owner ],
@@ -803,7 +829,7 @@ define_event! {
         6     | extended_info:          Option<module::ledger::extended_info::TokenExtendedInfo>,
         7     | memo:                   Option<Memo>                           [ memo ],
     },
-    [11, 1]     TokenUpdate (module::ledger::TokenUpdateArgs) {
+    [11, 1]     TokenUpdate (module::ledger::TokenUpdateArgs [ memo ]) {
         1     | symbol:                 Address                                [ id ],
         2     | name:                   Option<String>,
         3     | ticker:                 Option<String>,
@@ -811,22 +837,41 @@ define_event! {
         5     | owner:                  Option<ledger::TokenMaybeOwner>        [ maybe_owner ],
         6     | memo:                   Option<Memo>                           [ memo ],
     },
-    [11, 2]     TokenAddExtendedInfo (module::ledger::TokenAddExtendedInfoArgs) {
+    [11, 2]     TokenAddExtendedInfo (module::ledger::TokenAddExtendedInfoArgs [ memo ]) {
         1     | symbol:                 Address                                [ id ],
         2     | extended_info:          Vec<AttributeRelatedIndex>,
         3     | memo:                   Option<Memo>                           [ memo ],
     },
-    [11, 3]     TokenRemoveExtendedInfo (module::ledger::TokenRemoveExtendedInfoArgs) {
+    [11, 3]     TokenRemoveExtendedInfo (module::ledger::TokenRemoveExtendedInfoArgs [ memo ]) {
         1     | symbol:                 Address                                [ id ],
         2     | extended_info:          Vec<AttributeRelatedIndex>,
         3     | memo:                   Option<Memo>                           [ memo ],
     },
-    [12, 0]     TokenMint (module::ledger::TokenMintArgs) {
+    [11, 4]     TokenOwnershipOffer {
+        1     | symbol:                 Address                                [ id ],
+        2     | owner:                  Address                                [ id ],
+        3     | new_owner:              Address                                [ id ],
+        4     | expires:                Timestamp,
+        5     | memo:                   Option<Memo>                           [ memo ],
+    },
+    [11, 5]     TokenOwnershipAccept {
+        1     | symbol:                 Address                                [ id ],
+        2     | previous_owner:         Address                                [ id ],
+        3     | owner:                  Address                                [ id ],
+        4     | memo:                   Option<Memo>                           [ memo ],
+    },
+    [11, 6]     TokenOwnershipCancel {
+        1     | symbol:                 Address                                [ id ],
+        2     | owner:                  Address                                [ id ],
+        3     | new_owner:              Address                                [ id ],
+        4     | memo:                   Option<Memo>                           [ memo ],
+    },
+    [12, 0]     TokenMint (module::ledger::TokenMintArgs [ memo ]) {
         1     | symbol:                 Address                                [ id ],
         2     | distribution:           ledger::LedgerTokensAddressMap         [ id ],
         3     | memo:                   Option<Memo>                           [ memo ],
     },
-    [12, 1]     TokenBurn (module::ledger::TokenBurnArgs) {
+    [12, 1]     TokenBurn (module::ledger::TokenBurnArgs [ memo ]) {
         1     | symbol:                 Address                                [ id ],
         2     | distribution:           ledger::LedgerTokensAddressMap         [ id ],
         3     | memo:                   Option<Memo>                           [ memo ],
@@ -836,7 +881,7 @@ define_event! {
         2     | owner:                  Address                                [ id ],
         3     | new_owner:              Address                                [ id ],
     },
-    [17, 0]     WebDeploy (module::web::DeployArgs) {
+    [17, 0]     WebDeploy (module::web::DeployArgs [ memo ]) {
         1     | owner:                  Address                                [ id ],
         2     | site_name:              String,
         3     | site_description:       Option<String>,
@@ -844,12 +889,12 @@ define_event! {
         5     | memo:                   Option<Memo>                           [ memo ],
         6     | domain:                 Option<String>,
     },
-    [17, 1]     WebRemove (module::web::RemoveArgs) {
+    [17, 1]     WebRemove (module::web::RemoveArgs [ memo ]) {
         1     | owner:                  Address                                [ id ],
         2     | site_name:              String,
         3     | memo:                   Option<Memo>                           [ memo ],
     },
-    [17, 2]     WebUpdate (module::web::UpdateArgs) {
+    [17, 2]     WebUpdate (module::web::UpdateArgs [ memo ]) {
         1     | owner:                  Address                                [ id ],
         2     | site_name:              String,
         3     | site_description:       Option<String>,
@@ -857,10 +902,176 @@ define_event! {
         5     | memo:                   Option<Memo>                           [ memo ],
         6     | domain:                 Option<String>,
     },
+    [18, 0]     WatchlistAdd {
+        1     | address:                Address                                [ id ],
+        2     | action:                 module::watchlist::WatchlistAction,
+        3     | memo:                   Option<Memo>                           [ memo ],
+    },
+    [18, 1]     WatchlistRemove {
+        1     | address:                Address                                [ id ],
+        2     | memo:                   Option<Memo>                           [ memo ],
+    },
+    [18, 2]     WatchlistSetApprovers {
+        1     | approvers:              BTreeSet<Address>                      [ id ],
+    },
+    [18, 3]     WatchlistScreen {
+        1     | sender:                 Address                                [ id ],
+        2     | from:                   Address                                [ id ],
+        3     | to:                     Address                                [ id ],
+        4     | address:                Address                                [ id ],
+        5     | action:                 module::watchlist::WatchlistAction,
+        6     | allowed:                bool,
+    },
+    [19, 0]     AddressFreeze {
+        1     | address:                Address                                [ id ],
+        2     | memo:                   Option<Memo>                           [ memo ],
+    },
+    [19, 1]     AddressUnfreeze {
+        1     | address:                Address                                [ id ],
+        2     | memo:                   Option<Memo>                           [ memo ],
+    },
+    [20, 0]     ScheduledSendAdd {
+        1     | id:                     u64,
+        2     | sender:                 Address                                [ id ],
+        3     | from:                   Address                                [ id ],
+        4     | to:                     Address                                [ id ],
+        5     | symbol:                 Symbol                                 [ id ],
+        6     | amount:                 TokenAmount,
+        7     | at:                     module::ledger::ScheduleTrigger,
+        8     | memo:                   Option<Memo>                           [ memo ],
+        9     | deposit_symbol:         Symbol                                 [ id ],
+        10    | deposit:                TokenAmount,
+    },
+    [20, 1]     ScheduledSendExecute {
+        1     | id:                     u64,
+        2     | from:                   Address                                [ id ],
+        3     | to:                     Address                                [ id ],
+        4     | symbol:                 Symbol                                 [ id ],
+        5     | amount:                 TokenAmount,
+        6     | error:                  Option<ManyError>,
+    },
+    [20, 2]     ScheduledSendCancel {
+        1     | id:                     u64,
+        2     | canceller:              Address                                [ id ],
+        3     | memo:                   Option<Memo>                           [ memo ],
+    },
+    [22, 0]     StreamCreate {
+        1     | id:                     u64,
+        2     | payer:                  Address                                [ id ],
+        3     | payee:                  Address                                [ id ],
+        4     | symbol:                 Symbol                                 [ id ],
+        5     | rate:                   TokenAmount,
+        6     | start:                  Timestamp,
+        7     | end:                    Timestamp,
+        8     | memo:                   Option<Memo>                           [ memo ],
+    },
+    [22, 1]     StreamWithdraw {
+        1     | id:                     u64,
+        2     | payee:                  Address                                [ id ],
+        3     | symbol:                 Symbol                                 [ id ],
+        4     | amount:                 TokenAmount,
+        5     | memo:                   Option<Memo>                           [ memo ],
+    },
+    [22, 2]     StreamCancel {
+        1     | id:                     u64,
+        2     | payer:                  Address                                [ id ],
+        3     | payee:                  Address                                [ id ],
+        4     | symbol:                 Symbol                                 [ id ],
+        5     | paid:                   TokenAmount,
+        6     | refunded:               TokenAmount,
+        7     | memo:                   Option<Memo>                           [ memo ],
+    },
+    [23, 0]     TokenIdentityUpdate {
+        1     | previous:               Address                                [ id ],
+        2     | identity:               Address                                [ id ],
+        3     | memo:                   Option<Memo>                           [ memo ],
+    },
+    [24, 0]     ScriptDeploy {
+        1     | id:                     u64,
+        2     | owner:                  Address                                [ id ],
+        3     | code_hash:              ByteVec,
+        4     | memo:                   Option<Memo>                           [ memo ],
+    },
+    [24, 1]     ScriptEmit {
+        1     | id:                     u64,
+        2     | caller:                 Address                                [ id ],
+        3     | data:                   ByteVec,
+    },
+    [25, 0]     LabelClaim {
+        1     | label:                  String,
+        2     | owner:                  Address                                [ id ],
+        3     | symbol:                 Symbol                                 [ id ],
+        4     | deposit:                TokenAmount,
+        5     | memo:                   Option<Memo>                           [ memo ],
+    },
+    [25, 1]     LabelRelease {
+        1     | label:                  String,
+        2     | owner:                  Address                                [ id ],
+        3     | symbol:                 Symbol                                 [ id ],
+        4     | refund:                 TokenAmount,
+        5     | memo:                   Option<Memo>                           [ memo ],
+    },
 }
 
+impl EventInfo {
+    /// Move the deprecated memo and data fields of the event into its memo, so
+    /// only the new form is encoded. Other events are returned as is.
+    pub fn without_legacy_memo(self) -> Result<Self, ManyError> {
+        match self {
+            EventInfo::AccountMultisigSubmit {
+                submitter,
+                account,
+                memo_,
+                transaction,
+                token,
+                threshold,
+                timeout,
+                execute_automatically,
+                data_,
+                memo,
+            } => Ok(EventInfo::AccountMultisigSubmit {
+                submitter,
+                account,
+                memo_: None,
+                transaction,
+                token,
+                threshold,
+                timeout,
+                execute_automatically,
+                data_: None,
+                memo: Memo::merge_legacy(memo, memo_, data_)?,
+            }),
+            other => Ok(other),
+        }
+    }
+}
+
+/// The schema of an [EventLog], to print it with [many_types::diag].
+pub const EVENT_LOG_SCHEMA: diag::Schema = diag::Schema::Map(&[
+    diag::Field {
+        key: 0,
+        name: "id",
+        schema: &diag::Schema::Any,
+    },
+    diag::Field {
+        key: 1,
+        name: "time",
+        schema: &diag::Schema::Any,
+    },
+    diag::Field {
+        key: 2,
+        name: "content",
+        schema: &EVENT_INFO_SCHEMA,
+    },
+    diag::Field {
+        key: 3,
+        name: "intent_hash",
+        schema: &diag::Schema::Any,
+    },
+]);
+
 /// An Event that happened on the server and that is part of the log.
-#[derive(Debug, Encode, Decode)]
+#[derive(Cddl, Clone, Debug, Encode, Decode)]
 #[cbor(map)]
 pub struct EventLog {
     #[n(0)]
@@ -871,6 +1082,11 @@ pub struct EventLog {
 
     #[n(2)]
     pub content: EventInfo,
+
+    /// The intent hash of the request that caused this event, if any. See
+    /// `many_protocol::RequestMessage::intent_hash`.
+    #[n(3)]
+    pub intent_hash: Option<ByteVec>,
 }
 
 impl EventLog {
@@ -881,6 +1097,16 @@ impl EventLog {
     pub fn is_about(&self, id: Address) -> bool {
         self.content.is_about(id)
     }
+
+    /// The height of the block which logged this event. See [EventId::height].
+    pub fn height(&self) -> Option<u64> {
+        self.id.height()
+    }
+
+    /// The position of this event in its block. See [EventId::index].
+    pub fn index(&self) -> Option<u32> {
+        self.id.index()
+    }
 }
 
 #[cfg(test)]
@@ -890,67 +1116,6 @@ mod test {
     use crate::ledger::SendArgs;
     use many_identity::testing::identity;
 
-    #[test]
-    fn eventid_from_bytevec() {
-        let b = ByteVec::from(vec![1, 2, 3, 4, 5]);
-        let t = EventId::from(b.clone());
-
-        assert_eq!(b.as_slice(), Into::<Vec<u8>>::into(t));
-    }
-
-    #[test]
-    fn eventid_from_biguint() {
-        let v = u64::MAX;
-        let t = EventId::from(BigUint::from(v));
-
-        assert_eq!(v.to_be_bytes(), Into::<Vec<u8>>::into(t).as_slice());
-    }
-
-    #[test]
-    fn eventid_from_u64() {
-        let v = u64::MAX;
-        let t = EventId::from(v);
-
-        assert_eq!(v.to_be_bytes(), Into::<Vec<u8>>::into(t).as_slice());
-    }
-
-    #[test]
-    fn eventid_add() {
-        let v = u64::MAX;
-        let mut t = EventId::from(v) + 1;
-
-        assert_eq!(
-            Into::<Vec<u8>>::into(t.clone()),
-            (BigUint::from(u64::MAX) + 1u32).to_bytes_be()
-        );
-        t += 1;
-        assert_eq!(
-            Into::<Vec<u8>>::into(t),
-            (BigUint::from(u64::MAX) + 2u32).to_bytes_be()
-        );
-
-        let b = ByteVec::from(v.to_be_bytes().to_vec());
-        let t2 = EventId::from(v) + b;
-
-        assert_eq!(
-            Into::<Vec<u8>>::into(t2),
-            (BigUint::from(v) * 2u64).to_bytes_be()
-        );
-    }
-
-    #[test]
-    fn eventid_sub() {
-        let v = u64::MAX;
-        let t = EventId::from(v) - 1;
-
-        assert_eq!(Into::<Vec<u8>>::into(t), (v - 1).to_be_bytes());
-
-        let b = ByteVec::from(1u64.to_be_bytes().to_vec());
-        let t2 = EventId::from(v) - b;
-
-        assert_eq!(Into::<Vec<u8>>::into(t2), (v - 1).to_be_bytes());
-    }
-
     #[test]
     fn event_info_addresses() {
         let i0 = identity(0);
@@ -1190,6 +1355,31 @@ mod test {
         assert_eq!(event.memo().unwrap(), "Foo");
     }
 
+    #[test]
+    fn transaction_memo() {
+        let memo = Memo::try_from("Foo").unwrap();
+        let send = AccountMultisigTransaction::Send(SendArgs {
+            from: None,
+            to: Default::default(),
+            amount: Default::default(),
+            symbol: Default::default(),
+            memo: Some(memo.clone()),
+        });
+        assert_eq!(send.memo(), Some(&memo));
+
+        let mint = AccountMultisigTransaction::TokenMint(crate::ledger::TokenMintArgs {
+            symbol: Default::default(),
+            distribution: Default::default(),
+            memo: Some(memo.clone()),
+        });
+        assert_eq!(mint.memo(), Some(&memo));
+
+        let disable = AccountMultisigTransaction::AccountDisable(crate::account::DisableArgs {
+            account: identity(0),
+        });
+        assert_eq!(disable.memo(), None);
+    }
+
     #[test]
     fn memo_does_not_return_legacy() {
         let i0 = identity(0);
@@ -1214,11 +1404,28 @@ mod test {
             memo: None,
         };
         assert_eq!(event.memo(), None);
+
+        // Unless the legacy fields are merged into the memo.
+        let event = event.without_legacy_memo().unwrap();
+        assert_eq!(
+            event.memo(),
+            Some(
+                &Memo::try_from_iter([
+                    Either::Left("Hello".to_string()),
+                    Either::Right(b"World".to_vec())
+                ])
+                .unwrap()
+            )
+        );
+        if let EventInfo::AccountMultisigSubmit { memo_, data_, .. } = event {
+            assert!(memo_.is_none() && data_.is_none());
+        }
     }
 
     mod event_info {
         use super::super::*;
         use crate::ledger::SendArgs;
+        use crate::testutils::assert_canonical_round_trip;
         use many_identity::testing::identity;
         use many_types::cbor::CborAny;
         use many_types::Memo;
@@ -1358,6 +1565,28 @@ mod test {
                     }))
                 );
             }
+
+            #[test]
+            fn canonical_event_log(
+                id: u64,
+                memo in string_regex("[A-Za-z0-9\\., ]{0,100}").unwrap(),
+                amount: u64,
+                intent_hash in proptest::option::of(proptest::collection::vec(any::<u8>(), 32)),
+            ) {
+                let memo = memo.try_into().unwrap();
+                assert_canonical_round_trip(&EventLog {
+                    id: EventId::from(id),
+                    time: Timestamp::now(),
+                    content: _create_event_info(memo, AccountMultisigTransaction::Send(SendArgs {
+                        from: Some(identity(2)),
+                        to: identity(3),
+                        symbol: identity(4),
+                        amount: amount.into(),
+                        memo: None,
+                    })),
+                    intent_hash: intent_hash.map(Into::into),
+                });
+            }
         }
     }
 }
@@ -1414,6 +1643,7 @@ mod tests {
                             amount: TokenAmount::from(1000u64),
                             memo: None,
                         },
+                        intent_hash: None,
                     }],
                 })
             });
@@ -1446,6 +1676,29 @@ f756e742077697468204944207b69647d20756e6b6e6f776e2e02a162696478326d61666\
         }
     }
 
+    #[test]
+    fn event_log_diag() {
+        let event = minicbor::to_vec(EventLog {
+            id: EventId::from(vec![1]),
+            time: Timestamp::new(1700000000).unwrap(),
+            content: EventInfo::Send {
+                from: Address::anonymous(),
+                to: Address::anonymous(),
+                symbol: Default::default(),
+                amount: TokenAmount::from(1000u64),
+                memo: None,
+            },
+            intent_hash: None,
+        })
+        .unwrap();
+
+        let diag = diag::to_diag(&event, &EVENT_LOG_SCHEMA).unwrap();
+        assert!(diag.contains("1 /time/: 1(1700000000) /2023-11-14T22:13:20Z/"));
+        assert!(diag.contains("2 /content/: /Send/ {"));
+        assert!(diag.contains("0 /type/: ["));
+        assert!(diag.contains("4 /amount/: 1000"));
+    }
+
     #[test]
     fn encode_decode_event_filter() {
         let state_key = EventFilterAttributeSpecificIndex::MultisigTransactionState;
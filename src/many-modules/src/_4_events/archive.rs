@@ -0,0 +1,27 @@
+use crate::events::EventId;
+use crate::EmptyArg;
+use many_macros::Cddl;
+use minicbor::{Decode, Encode};
+
+pub type ArchiveInfoArgs = EmptyArg;
+
+#[derive(Cddl, Clone, Debug, Decode, Encode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ArchiveInfoReturn {
+    /// The number of events still in the live store, which can be listed.
+    #[n(0)]
+    pub retained: u64,
+
+    /// The number of events moved out of the live store.
+    #[n(1)]
+    pub archived: u64,
+
+    /// The oldest event still in the live store.
+    #[n(2)]
+    pub oldest_retained: Option<EventId>,
+
+    /// The number of blocks of events kept in the live store, if events are
+    /// archived.
+    #[n(3)]
+    pub retention_blocks: Option<u64>,
+}
@@ -0,0 +1,162 @@
+use crate::events::{EventId, EventLog, SubscriptionFilter, SubscriptionId};
+use crate::{EmptyArg, EmptyReturn};
+use many_identity::Address;
+use many_macros::Cddl;
+use minicbor::{Decode, Encode};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+/// A subscription kept by the server across restarts. The events matching its
+/// filter are posted to its target, in order, until the target acknowledges
+/// them with a successful response.
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct DurableSubscription {
+    /// The identity which created the subscription, and the only one which
+    /// can manage it.
+    #[n(0)]
+    pub owner: Address,
+
+    #[n(1)]
+    pub filter: SubscriptionFilter,
+
+    /// The HTTP(S) URL the events are posted to.
+    #[n(2)]
+    pub target: String,
+
+    /// The last event acknowledged by the target, if any. Only the events
+    /// after it are delivered.
+    #[n(3)]
+    pub cursor: Option<EventId>,
+}
+
+impl DurableSubscription {
+    /// Whether the target of a subscription is a URL the server can post to.
+    pub fn is_valid_target(target: &str) -> bool {
+        target.starts_with("http://") || target.starts_with("https://")
+    }
+}
+
+/// Whether an IP address is reachable from the public internet. Servers only
+/// post to such addresses, so subscription targets and notification endpoints
+/// cannot reach the server itself or its private network, e.g. a cloud
+/// metadata service.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(a == 0
+                || ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                // Shared address space, 100.64.0.0/10.
+                || (a == 100 && b & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ip(ip.into()),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_unspecified()
+                    || ip.is_loopback()
+                    || ip.is_multicast()
+                    // Unique local, fc00::/7, and link local, fe80::/10.
+                    || first & 0xfe00 == 0xfc00
+                    || first & 0xffc0 == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Whether the host of a target URL can be public. Names must be resolved and
+/// checked again with [is_public_ip] every time something is posted to them.
+pub fn is_public_target(target: &str) -> bool {
+    let url = match url::Url::parse(target) {
+        Ok(url) => url,
+        Err(_) => return false,
+    };
+    let host = match url.host_str() {
+        Some(host) => host.trim_start_matches('[').trim_end_matches(']'),
+        None => return false,
+    };
+    match host.parse::<IpAddr>() {
+        Ok(ip) => is_public_ip(ip),
+        Err(_) => {
+            let host = host.trim_end_matches('.').to_ascii_lowercase();
+            host != "localhost" && !host.ends_with(".localhost")
+        }
+    }
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct CreateSubscriptionArgs {
+    #[n(0)]
+    pub filter: SubscriptionFilter,
+
+    #[n(1)]
+    pub target: String,
+
+    /// Deliver the events after this one. By default, only the events logged
+    /// after the subscription is created are delivered.
+    #[n(2)]
+    pub after: Option<EventId>,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct CreateSubscriptionReturn {
+    #[n(0)]
+    pub id: SubscriptionId,
+}
+
+pub type ListSubscriptionsArgs = EmptyArg;
+
+#[derive(Cddl, Clone, Debug, Default, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ListSubscriptionsReturns {
+    /// The subscriptions owned by the sender.
+    #[n(0)]
+    pub subscriptions: BTreeMap<SubscriptionId, DurableSubscription>,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct DeleteSubscriptionArgs {
+    #[n(0)]
+    pub id: SubscriptionId,
+}
+
+pub type DeleteSubscriptionReturn = EmptyReturn;
+
+/// The CBOR body of the requests posted to the target of a durable
+/// subscription. The same events can be delivered more than once, e.g. if the
+/// server restarts before receiving the response of the target.
+#[derive(Cddl, Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct SubscriptionDelivery {
+    #[n(0)]
+    pub id: SubscriptionId,
+
+    /// The events matching the filter of the subscription, oldest first.
+    #[n(1)]
+    pub events: Vec<EventLog>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_target() {
+        assert!(DurableSubscription::is_valid_target(
+            "http://localhost:8000/events"
+        ));
+        assert!(DurableSubscription::is_valid_target("https://example.com"));
+        assert!(!DurableSubscription::is_valid_target("example.com"));
+        assert!(!DurableSubscription::is_valid_target("ftp://example.com"));
+        assert!(!DurableSubscription::is_valid_target(""));
+    }
+}
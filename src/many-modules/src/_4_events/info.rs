@@ -1,10 +1,11 @@
 use crate::events::EventKind;
 use crate::EmptyArg;
+use many_macros::Cddl;
 use minicbor::{Decode, Encode};
 
 pub type InfoArgs = EmptyArg;
 
-#[derive(Decode, Encode)]
+#[derive(Cddl, Decode, Encode)]
 #[cbor(map)]
 pub struct InfoReturn {
     #[n(0)]
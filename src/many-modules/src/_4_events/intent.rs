@@ -0,0 +1,28 @@
+use crate::events::EventLog;
+use many_macros::Cddl;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+
+/// The length of an intent hash, in bytes.
+pub const INTENT_HASH_LENGTH: usize = 32;
+
+/// Maximum number of events returned by `events.findByIntentHash`.
+pub const MAXIMUM_INTENT_EVENTS: usize = 100;
+
+#[derive(Cddl, Clone, Debug, Decode, Encode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct FindByIntentHashArgs {
+    /// The intent hash of the request, see
+    /// `many_protocol::RequestMessage::intent_hash`.
+    #[n(0)]
+    pub intent_hash: ByteVec,
+}
+
+#[derive(Cddl, Clone, Debug, Decode, Encode)]
+#[cbor(map)]
+pub struct FindByIntentHashReturns {
+    /// The events caused by the request, in order, at most
+    /// [MAXIMUM_INTENT_EVENTS].
+    #[n(0)]
+    pub events: Vec<EventLog>,
+}
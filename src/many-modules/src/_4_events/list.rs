@@ -1,8 +1,9 @@
 use crate::events;
+use many_macros::Cddl;
 use many_types::SortOrder;
 use minicbor::{Decode, Encode};
 
-#[derive(Clone, Debug, Default, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Default, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct ListArgs {
     #[n(0)]
@@ -15,7 +16,7 @@ pub struct ListArgs {
     pub filter: Option<events::EventFilter>,
 }
 
-#[derive(Encode, Decode)]
+#[derive(Cddl, Encode, Decode)]
 #[cbor(map)]
 pub struct ListReturns {
     #[n(0)]
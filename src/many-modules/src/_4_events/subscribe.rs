@@ -0,0 +1,361 @@
+use crate::events::{EventKind, EventLog};
+use crate::EmptyReturn;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::Cddl;
+use many_types::VecOrSingle;
+use minicbor::{Decode, Encode};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Maximum number of subscriptions a server keeps at the same time.
+pub const MAXIMUM_SUBSCRIPTION_COUNT: usize = 1000;
+
+/// Maximum number of subscriptions of a single sender.
+pub const MAXIMUM_SUBSCRIPTION_COUNT_PER_SENDER: usize = 10;
+
+/// Maximum number of events kept for a subscription between two polls. Older
+/// events are dropped when this is reached.
+pub const MAXIMUM_SUBSCRIPTION_QUEUE_SIZE: usize = 1000;
+
+/// Maximum number of events returned by a single poll.
+pub const MAXIMUM_POLL_COUNT: usize = 100;
+
+pub type SubscriptionId = u64;
+
+#[derive(Cddl, Clone, Debug, Default, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct SubscriptionFilter {
+    #[n(0)]
+    pub account: Option<VecOrSingle<Address>>,
+
+    #[n(1)]
+    pub kind: Option<VecOrSingle<EventKind>>,
+
+    #[n(2)]
+    pub symbol: Option<VecOrSingle<Address>>,
+}
+
+impl SubscriptionFilter {
+    pub fn matches(&self, event: &EventLog) -> bool {
+        let is_about = |ids: &Option<VecOrSingle<Address>>| {
+            ids.as_ref()
+                .map_or(true, |ids| ids.0.iter().any(|id| event.is_about(*id)))
+        };
+
+        is_about(&self.account)
+            && is_about(&self.symbol)
+            && self
+                .kind
+                .as_ref()
+                .map_or(true, |kinds| kinds.0.contains(&event.kind()))
+    }
+}
+
+#[derive(Cddl, Clone, Debug, Default, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct SubscribeArgs {
+    #[n(0)]
+    pub filter: SubscriptionFilter,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct SubscribeReturn {
+    #[n(0)]
+    pub id: SubscriptionId,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct PollArgs {
+    #[n(0)]
+    pub id: SubscriptionId,
+
+    #[n(1)]
+    pub count: Option<u64>,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct PollReturn {
+    /// Events committed since the last poll, oldest first.
+    #[n(0)]
+    pub events: Vec<EventLog>,
+
+    /// Number of events that were dropped because they were not polled in time.
+    #[n(1)]
+    pub dropped: u64,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct UnsubscribeArgs {
+    #[n(0)]
+    pub id: SubscriptionId,
+}
+
+pub type UnsubscribeReturn = EmptyReturn;
+
+#[derive(Debug)]
+struct Subscription {
+    owner: Address,
+    filter: SubscriptionFilter,
+    queue: VecDeque<EventLog>,
+    dropped: u64,
+}
+
+/// The subscriptions of a server, and the events they have not polled yet.
+/// Backends publish events to it as they are committed. Subscriptions can
+/// only be polled and removed by the sender which created them.
+#[derive(Debug, Default)]
+pub struct EventSubscriptions {
+    next_id: SubscriptionId,
+    subscriptions: BTreeMap<SubscriptionId, Subscription>,
+}
+
+impl EventSubscriptions {
+    pub fn is_empty(&self) -> bool {
+        self.subscriptions.is_empty()
+    }
+
+    pub fn subscribe(
+        &mut self,
+        owner: &Address,
+        filter: SubscriptionFilter,
+    ) -> Result<SubscriptionId, ManyError> {
+        if self.subscriptions.len() >= MAXIMUM_SUBSCRIPTION_COUNT {
+            return Err(super::errors::too_many_subscriptions());
+        }
+        let owned = self
+            .subscriptions
+            .values()
+            .filter(|s| &s.owner == owner)
+            .count();
+        if owned >= MAXIMUM_SUBSCRIPTION_COUNT_PER_SENDER {
+            return Err(super::errors::too_many_sender_subscriptions(
+                MAXIMUM_SUBSCRIPTION_COUNT_PER_SENDER,
+            ));
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.subscriptions.insert(
+            id,
+            Subscription {
+                owner: *owner,
+                filter,
+                queue: VecDeque::new(),
+                dropped: 0,
+            },
+        );
+        Ok(id)
+    }
+
+    fn get_owned(
+        &mut self,
+        owner: &Address,
+        id: SubscriptionId,
+    ) -> Result<&mut Subscription, ManyError> {
+        let subscription = self
+            .subscriptions
+            .get_mut(&id)
+            .ok_or_else(|| super::errors::unknown_subscription(id))?;
+        if &subscription.owner != owner {
+            return Err(super::errors::not_subscription_owner(id));
+        }
+        Ok(subscription)
+    }
+
+    pub fn unsubscribe(&mut self, owner: &Address, id: SubscriptionId) -> Result<(), ManyError> {
+        self.get_owned(owner, id)?;
+        self.subscriptions.remove(&id);
+        Ok(())
+    }
+
+    pub fn poll(
+        &mut self,
+        owner: &Address,
+        id: SubscriptionId,
+        count: Option<u64>,
+    ) -> Result<PollReturn, ManyError> {
+        let subscription = self.get_owned(owner, id)?;
+
+        let count = count.map_or(MAXIMUM_POLL_COUNT, |c| {
+            std::cmp::min(c as usize, MAXIMUM_POLL_COUNT)
+        });
+        let count = std::cmp::min(count, subscription.queue.len());
+
+        Ok(PollReturn {
+            events: subscription.queue.drain(..count).collect(),
+            dropped: std::mem::take(&mut subscription.dropped),
+        })
+    }
+
+    /// Queue newly committed events for the subscriptions they match.
+    pub fn publish<'a>(&mut self, events: impl IntoIterator<Item = &'a EventLog>) {
+        for event in events {
+            for subscription in self.subscriptions.values_mut() {
+                if !subscription.filter.matches(event) {
+                    continue;
+                }
+                if subscription.queue.len() >= MAXIMUM_SUBSCRIPTION_QUEUE_SIZE {
+                    subscription.queue.pop_front();
+                    subscription.dropped += 1;
+                }
+                subscription.queue.push_back(event.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{EventId, EventInfo};
+    use many_identity::testing::identity;
+    use many_types::Timestamp;
+
+    fn send(id: u64, from: Address, to: Address) -> EventLog {
+        EventLog {
+            id: EventId::from(id),
+            time: Timestamp::new(1000).unwrap(),
+            content: EventInfo::Send {
+                from,
+                to,
+                symbol: Default::default(),
+                amount: Default::default(),
+                memo: None,
+            },
+            intent_hash: None,
+        }
+    }
+
+    #[test]
+    fn filter() {
+        let event = send(1, identity(1), identity(2));
+
+        assert!(SubscriptionFilter::default().matches(&event));
+        assert!(SubscriptionFilter {
+            account: Some(VecOrSingle(vec![identity(2), identity(3)])),
+            kind: Some(VecOrSingle(vec![EventKind::Send])),
+            ..Default::default()
+        }
+        .matches(&event));
+        assert!(!SubscriptionFilter {
+            account: Some(VecOrSingle(vec![identity(3)])),
+            ..Default::default()
+        }
+        .matches(&event));
+        assert!(!SubscriptionFilter {
+            kind: Some(VecOrSingle(vec![EventKind::AccountCreate])),
+            ..Default::default()
+        }
+        .matches(&event));
+    }
+
+    #[test]
+    fn publish_and_poll() {
+        let mut subscriptions = EventSubscriptions::default();
+        let owner = identity(9);
+        let all = subscriptions.subscribe(&owner, Default::default()).unwrap();
+        let some = subscriptions
+            .subscribe(
+                &owner,
+                SubscriptionFilter {
+                    account: Some(VecOrSingle(vec![identity(3)])),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        subscriptions.publish(&[
+            send(1, identity(1), identity(2)),
+            send(2, identity(1), identity(3)),
+        ]);
+
+        let polled = subscriptions.poll(&owner, all, Some(1)).unwrap();
+        assert_eq!(polled.events.len(), 1);
+        assert_eq!(polled.events[0].id, EventId::from(1));
+        let polled = subscriptions.poll(&owner, all, None).unwrap();
+        assert_eq!(polled.events.len(), 1);
+        assert_eq!(polled.events[0].id, EventId::from(2));
+        assert!(subscriptions
+            .poll(&owner, all, None)
+            .unwrap()
+            .events
+            .is_empty());
+
+        let polled = subscriptions.poll(&owner, some, None).unwrap();
+        assert_eq!(polled.events.len(), 1);
+        assert_eq!(polled.events[0].id, EventId::from(2));
+
+        subscriptions.unsubscribe(&owner, some).unwrap();
+        assert!(subscriptions.poll(&owner, some, None).is_err());
+        assert!(subscriptions.unsubscribe(&owner, some).is_err());
+    }
+
+    #[test]
+    fn owned() {
+        let mut subscriptions = EventSubscriptions::default();
+        let id = subscriptions
+            .subscribe(&identity(1), Default::default())
+            .unwrap();
+        subscriptions.publish(&[send(1, identity(1), identity(2))]);
+
+        let not_owner = super::super::errors::not_subscription_owner(id);
+        assert_eq!(
+            subscriptions.poll(&identity(2), id, None).unwrap_err(),
+            not_owner
+        );
+        assert_eq!(
+            subscriptions.unsubscribe(&identity(2), id).unwrap_err(),
+            not_owner
+        );
+        assert_eq!(
+            subscriptions
+                .poll(&identity(1), id, None)
+                .unwrap()
+                .events
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn per_sender_limit() {
+        let mut subscriptions = EventSubscriptions::default();
+        for _ in 0..MAXIMUM_SUBSCRIPTION_COUNT_PER_SENDER {
+            subscriptions
+                .subscribe(&identity(1), Default::default())
+                .unwrap();
+        }
+        assert_eq!(
+            subscriptions
+                .subscribe(&identity(1), Default::default())
+                .unwrap_err(),
+            super::super::errors::too_many_sender_subscriptions(
+                MAXIMUM_SUBSCRIPTION_COUNT_PER_SENDER
+            )
+        );
+        assert!(subscriptions
+            .subscribe(&identity(2), Default::default())
+            .is_ok());
+    }
+
+    #[test]
+    fn queue_overflow() {
+        let mut subscriptions = EventSubscriptions::default();
+        let owner = identity(9);
+        let id = subscriptions.subscribe(&owner, Default::default()).unwrap();
+
+        let events: Vec<EventLog> = (0..MAXIMUM_SUBSCRIPTION_QUEUE_SIZE as u64 + 5)
+            .map(|i| send(i, identity(1), identity(2)))
+            .collect();
+        subscriptions.publish(&events);
+
+        let polled = subscriptions.poll(&owner, id, None).unwrap();
+        assert_eq!(polled.dropped, 5);
+        assert_eq!(polled.events[0].id, EventId::from(5));
+        assert_eq!(subscriptions.poll(&owner, id, None).unwrap().dropped, 0);
+    }
+}
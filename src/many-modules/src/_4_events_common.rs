@@ -0,0 +1,266 @@
+//! Event types referenced by the modules whose transactions are events. They
+//! are available without the `events` feature.
+use many_identity::Address;
+use minicbor::bytes::ByteVec;
+use minicbor::{encode, Decode, Decoder, Encode, Encoder};
+use num_bigint::BigUint;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+#[derive(Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+#[repr(transparent)]
+pub struct EventId(ByteVec);
+
+/// Event IDs allocated by a block are `(height << EVENT_ID_HEIGHT_SHIFT) + index`,
+/// where `index` starts at 1 for the first event of the block.
+pub const EVENT_ID_HEIGHT_SHIFT: u64 = 32;
+
+impl EventId {
+    pub fn new(height: u64, index: u32) -> Self {
+        EventId::from(height << EVENT_ID_HEIGHT_SHIFT | u64::from(index))
+    }
+
+    fn as_u64(&self) -> Option<u64> {
+        let bytes: &[u8] = self.0.as_ref();
+        if bytes.len() > 8 {
+            return None;
+        }
+        let mut be = [0u8; 8];
+        be[8 - bytes.len()..].copy_from_slice(bytes);
+        Some(u64::from_be_bytes(be))
+    }
+
+    /// The height part of the ID, or None if this is not a 64 bits ID.
+    pub fn height(&self) -> Option<u64> {
+        self.as_u64().map(|id| id >> EVENT_ID_HEIGHT_SHIFT)
+    }
+
+    /// The index part of the ID, or None if this is not a 64 bits ID.
+    pub fn index(&self) -> Option<u32> {
+        self.as_u64().map(|id| id as u32)
+    }
+}
+
+impl From<ByteVec> for EventId {
+    fn from(t: ByteVec) -> EventId {
+        EventId(t)
+    }
+}
+
+impl From<EventId> for ByteVec {
+    fn from(id: EventId) -> Self {
+        id.0
+    }
+}
+
+impl AsRef<[u8]> for EventId {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+impl From<Vec<u8>> for EventId {
+    fn from(t: Vec<u8>) -> EventId {
+        EventId(ByteVec::from(t))
+    }
+}
+
+impl From<u64> for EventId {
+    fn from(v: u64) -> EventId {
+        EventId(ByteVec::from(v.to_be_bytes().to_vec()))
+    }
+}
+
+impl From<BigUint> for EventId {
+    fn from(b: BigUint) -> EventId {
+        EventId(ByteVec::from(b.to_bytes_be()))
+    }
+}
+
+impl std::ops::Add<ByteVec> for EventId {
+    type Output = EventId;
+
+    fn add(self, rhs: ByteVec) -> Self::Output {
+        (BigUint::from_bytes_be(&self.0) + BigUint::from_bytes_be(&rhs)).into()
+    }
+}
+
+impl std::ops::Add<u32> for EventId {
+    type Output = EventId;
+
+    fn add(self, rhs: u32) -> Self::Output {
+        (BigUint::from_bytes_be(&self.0) + rhs).into()
+    }
+}
+
+impl std::ops::AddAssign<u32> for EventId {
+    fn add_assign(&mut self, other: u32) {
+        *self = self.clone() + other;
+    }
+}
+
+impl std::ops::Sub<ByteVec> for EventId {
+    type Output = EventId;
+
+    fn sub(self, rhs: ByteVec) -> Self::Output {
+        (BigUint::from_bytes_be(&self.0) - BigUint::from_bytes_be(&rhs)).into()
+    }
+}
+
+impl std::ops::Sub<u32> for EventId {
+    type Output = EventId;
+
+    fn sub(self, rhs: u32) -> Self::Output {
+        (BigUint::from_bytes_be(&self.0) - rhs).into()
+    }
+}
+
+impl<C> Encode<C> for EventId {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _: &mut C,
+    ) -> Result<(), encode::Error<W::Error>> {
+        e.bytes(&self.0)?;
+        Ok(())
+    }
+}
+
+impl<'b, C> Decode<'b, C> for EventId {
+    fn decode(d: &mut Decoder<'b>, _: &mut C) -> Result<Self, minicbor::decode::Error> {
+        Ok(EventId(ByteVec::from(d.bytes()?.to_vec())))
+    }
+}
+
+impl From<EventId> for Vec<u8> {
+    fn from(t: EventId) -> Vec<u8> {
+        t.0.to_vec()
+    }
+}
+
+/// A trait that can apply to
+pub trait AddressContainer {
+    fn addresses(&self) -> BTreeSet<Address>;
+}
+
+impl<T: AddressContainer> AddressContainer for Box<T> {
+    fn addresses(&self) -> BTreeSet<Address> {
+        self.as_ref().addresses()
+    }
+}
+
+impl<T: AddressContainer> AddressContainer for Arc<T> {
+    fn addresses(&self) -> BTreeSet<Address> {
+        self.as_ref().addresses()
+    }
+}
+
+impl AddressContainer for Address {
+    fn addresses(&self) -> BTreeSet<Address> {
+        BTreeSet::from([*self])
+    }
+}
+
+impl<I: AddressContainer> AddressContainer for Option<I> {
+    fn addresses(&self) -> BTreeSet<Address> {
+        match self {
+            Some(t) => t.addresses(),
+            None => BTreeSet::new(),
+        }
+    }
+}
+
+impl<V> AddressContainer for BTreeMap<Address, V> {
+    fn addresses(&self) -> BTreeSet<Address> {
+        self.keys().cloned().collect()
+    }
+}
+
+impl AddressContainer for BTreeSet<Address> {
+    fn addresses(&self) -> BTreeSet<Address> {
+        self.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eventid_from_bytevec() {
+        let b = ByteVec::from(vec![1, 2, 3, 4, 5]);
+        let t = EventId::from(b.clone());
+
+        assert_eq!(b.as_slice(), Into::<Vec<u8>>::into(t));
+    }
+
+    #[test]
+    fn eventid_from_biguint() {
+        let v = u64::MAX;
+        let t = EventId::from(BigUint::from(v));
+
+        assert_eq!(v.to_be_bytes(), Into::<Vec<u8>>::into(t).as_slice());
+    }
+
+    #[test]
+    fn eventid_from_u64() {
+        let v = u64::MAX;
+        let t = EventId::from(v);
+
+        assert_eq!(v.to_be_bytes(), Into::<Vec<u8>>::into(t).as_slice());
+    }
+
+    #[test]
+    fn eventid_add() {
+        let v = u64::MAX;
+        let mut t = EventId::from(v) + 1;
+
+        assert_eq!(
+            Into::<Vec<u8>>::into(t.clone()),
+            (BigUint::from(u64::MAX) + 1u32).to_bytes_be()
+        );
+        t += 1;
+        assert_eq!(
+            Into::<Vec<u8>>::into(t),
+            (BigUint::from(u64::MAX) + 2u32).to_bytes_be()
+        );
+
+        let b = ByteVec::from(v.to_be_bytes().to_vec());
+        let t2 = EventId::from(v) + b;
+
+        assert_eq!(
+            Into::<Vec<u8>>::into(t2),
+            (BigUint::from(v) * 2u64).to_bytes_be()
+        );
+    }
+
+    #[test]
+    fn eventid_sub() {
+        let v = u64::MAX;
+        let t = EventId::from(v) - 1;
+
+        assert_eq!(Into::<Vec<u8>>::into(t), (v - 1).to_be_bytes());
+
+        let b = ByteVec::from(1u64.to_be_bytes().to_vec());
+        let t2 = EventId::from(v) - b;
+
+        assert_eq!(Into::<Vec<u8>>::into(t2), (v - 1).to_be_bytes());
+    }
+
+    #[test]
+    fn eventid_height_index() {
+        let t = EventId::new(9400, 3);
+        assert_eq!(t, EventId::from((9400u64 << 32) + 3));
+        assert_eq!(t.height(), Some(9400));
+        assert_eq!(t.index(), Some(3));
+
+        // IDs computed with BigUint are not padded.
+        let t = EventId::from(0u64) + 1;
+        assert_eq!(t.height(), Some(0));
+        assert_eq!(t.index(), Some(1));
+
+        let t = EventId::from(u64::MAX) + 1;
+        assert_eq!(t.height(), None);
+        assert_eq!(t.index(), None);
+    }
+}
@@ -4,7 +4,7 @@ pub mod query;
 pub mod types;
 pub use get_info::*;
 pub use info::*;
-use many_error::ManyError;
+use many_error::{define_attribute_many_error, ManyError};
 use many_identity::Address;
 use many_macros::many_module;
 use many_protocol::context::Context;
@@ -14,6 +14,12 @@ pub use types::*;
 #[cfg(test)]
 use mockall::{automock, predicate::*};
 
+define_attribute_many_error!(
+    attribute 5 => {
+        1: pub fn data_not_available_at_height(height) => "Data attributes are not available at height {height}.",
+    }
+);
+
 #[many_module(name = DataModule, id = 5, namespace = data, many_modules_crate = crate)]
 #[cfg_attr(test, automock)]
 pub trait DataModuleBackend: Send {
@@ -61,7 +67,10 @@ mod tests {
         let account_total_count = account_total_count();
         let non_zero_account_total_count = non_zero_account_total_count();
         let info_returns = DataInfoReturns {
-            indices: vec![account_total_count, non_zero_account_total_count],
+            indices: vec![
+                account_total_count.clone(),
+                non_zero_account_total_count.clone(),
+            ],
         };
 
         let mut mock = MockDataModuleBackend::new();
@@ -83,7 +92,11 @@ mod tests {
         let account_total_count = account_total_count();
         let non_zero_account_total_count = non_zero_account_total_count();
         let args = DataGetInfoArgs {
-            indices: VecOrSingle(vec![account_total_count, non_zero_account_total_count]),
+            indices: VecOrSingle(vec![
+                account_total_count.clone(),
+                non_zero_account_total_count.clone(),
+            ]),
+            height: None,
         };
 
         // Returns
@@ -96,8 +109,8 @@ mod tests {
             shortname: "nonZeroAccountTotalCount".into(),
         };
         let mut returns = DataGetInfoReturns::new();
-        returns.insert(account_total_count, atc.clone());
-        returns.insert(non_zero_account_total_count, nzatc.clone());
+        returns.insert(account_total_count.clone(), atc.clone());
+        returns.insert(non_zero_account_total_count.clone(), nzatc.clone());
 
         let mut mock = MockDataModuleBackend::new();
         mock.expect_get_info()
@@ -120,15 +133,19 @@ mod tests {
         let account_total_count = account_total_count();
         let non_zero_account_total_count = non_zero_account_total_count();
         let args = DataQueryArgs {
-            indices: VecOrSingle(vec![account_total_count, non_zero_account_total_count]),
+            indices: VecOrSingle(vec![
+                account_total_count.clone(),
+                non_zero_account_total_count.clone(),
+            ]),
+            height: Some(10),
         };
 
         // Returns
         let mut returns = DataQueryReturns::new();
         let act_value = DataValue::Counter(10);
         let nzatc_value = DataValue::Counter(1);
-        returns.insert(account_total_count, act_value.clone());
-        returns.insert(non_zero_account_total_count, nzatc_value.clone());
+        returns.insert(account_total_count.clone(), act_value.clone());
+        returns.insert(non_zero_account_total_count.clone(), nzatc_value.clone());
 
         let mut mock = MockDataModuleBackend::new();
         mock.expect_query().times(1).return_const(Ok(returns));
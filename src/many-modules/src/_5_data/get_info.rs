@@ -1,3 +1,4 @@
+use many_macros::Cddl;
 use std::collections::BTreeMap;
 
 use many_types::VecOrSingle;
@@ -5,10 +6,14 @@ use minicbor::{Decode, Encode};
 
 use crate::data::{DataIndex, DataInfo};
 
-#[derive(Clone, Encode, Decode)]
+#[derive(Cddl, Clone, Encode, Decode)]
 pub struct DataGetInfoArgs {
     #[n(0)]
     pub indices: VecOrSingle<DataIndex>,
+
+    /// Only return the attributes which had a value at the end of this block.
+    #[n(1)]
+    pub height: Option<u64>,
 }
 
 pub type DataGetInfoReturns = BTreeMap<DataIndex, DataInfo>;
@@ -1,9 +1,10 @@
 use crate::{data::DataIndex, EmptyArg};
+use many_macros::Cddl;
 use minicbor::{Decode, Encode};
 
 pub type DataInfoArgs = EmptyArg;
 
-#[derive(Clone, Decode, Encode)]
+#[derive(Cddl, Clone, Decode, Encode)]
 pub struct DataInfoReturns {
     #[n(0)]
     pub indices: Vec<DataIndex>,
@@ -1,3 +1,4 @@
+use many_macros::Cddl;
 use std::collections::BTreeMap;
 
 use many_types::VecOrSingle;
@@ -5,10 +6,15 @@ use minicbor::{Decode, Encode};
 
 use crate::data::{DataIndex, DataValue};
 
-#[derive(Clone, Encode, Decode)]
+#[derive(Cddl, Clone, Encode, Decode)]
 pub struct DataQueryArgs {
     #[n(0)]
     pub indices: VecOrSingle<DataIndex>,
+
+    /// Return the values as of the end of this block, instead of the latest
+    /// values.
+    #[n(1)]
+    pub height: Option<u64>,
 }
 
 pub type DataQueryReturns = BTreeMap<DataIndex, DataValue>;
@@ -1,3 +1,4 @@
+use many_macros::Cddl;
 use many_types::AttributeRelatedIndex;
 use minicbor::{Decode, Encode};
 use num_bigint::BigInt;
@@ -12,7 +13,7 @@ pub enum DataType {
     Gauge,
 }
 
-#[derive(Clone, Decode, Encode, Debug)]
+#[derive(Clone, Decode, Encode, Debug, PartialEq)]
 pub enum DataValue {
     #[n(0)]
     Counter(#[n(0)] DataValueTypeCounter),
@@ -20,6 +21,16 @@ pub enum DataValue {
     Gauge(#[n(0)] DataValueTypeGauge),
 }
 
+impl many_types::cddl::Cddl for DataValue {
+    fn cddl() -> String {
+        format!(
+            "[0, [{}]] / [1, [{}]]",
+            DataValueTypeCounter::cddl(),
+            DataValueTypeGauge::cddl()
+        )
+    }
+}
+
 impl TryFrom<DataValue> for BigInt {
     type Error = String;
 
@@ -33,7 +44,7 @@ impl TryFrom<DataValue> for BigInt {
 
 pub type DataValueTypeCounter = u64;
 
-#[derive(Clone, Decode, Encode, Debug)]
+#[derive(Clone, Decode, Encode, Debug, PartialEq)]
 pub enum DataValueTypeGauge {
     #[n(0)]
     Int(#[n(0)] i64),
@@ -43,6 +54,13 @@ pub enum DataValueTypeGauge {
     BigInt(#[cbor(n(0), decode_with = "decode_bigint", encode_with = "encode_bigint")] BigInt),
 }
 
+impl many_types::cddl::Cddl for DataValueTypeGauge {
+    fn cddl() -> String {
+        // Big integers are encoded as their signed big endian bytes.
+        "[0, [int]] / [1, [float]] / [2, [[* uint]]]".to_string()
+    }
+}
+
 impl TryFrom<DataValueTypeGauge> for BigInt {
     type Error = String;
 
@@ -74,7 +92,7 @@ fn encode_bigint<C, W: minicbor::encode::Write>(
     Ok(())
 }
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 pub struct DataInfo {
     #[n(0)]
     pub r#type: DataType,
@@ -1,11 +1,12 @@
 use crate::events::AddressContainer;
 use crate::EmptyReturn;
 use many_identity::Address;
+use many_macros::Cddl;
 use many_types::{ledger, Memo};
 use minicbor::{Decode, Encode};
 use std::collections::BTreeSet;
 
-#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Debug, Clone, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct SendArgs {
     #[n(0)]
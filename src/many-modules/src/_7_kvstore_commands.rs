@@ -5,9 +5,13 @@ use many_macros::many_module;
 #[cfg(test)]
 use mockall::{automock, predicate::*};
 
+mod cas;
 mod disable;
+mod multi_put;
 mod put;
+pub use cas::*;
 pub use disable::*;
+pub use multi_put::*;
 pub use put::*;
 
 #[many_module(name = KvStoreCommandsModule, id = 7, namespace = kvstore, many_modules_crate = crate)]
@@ -18,6 +22,16 @@ pub trait KvStoreCommandsModuleBackend: Send {
 
     #[many(deny_anonymous)]
     fn disable(&mut self, sender: &Address, args: DisableArgs) -> Result<DisableReturn, ManyError>;
+
+    #[many(deny_anonymous)]
+    fn cas(&mut self, sender: &Address, args: CasArgs) -> Result<CasReturn, ManyError>;
+
+    #[many(deny_anonymous)]
+    fn multi_put(
+        &mut self,
+        sender: &Address,
+        args: MultiPutArgs,
+    ) -> Result<MultiPutReturn, ManyError>;
 }
 
 #[cfg(test)]
@@ -35,6 +49,7 @@ mod tests {
             key: ByteVec::from(vec![1]),
             value: ByteVec::from(vec![2]),
             alternative_owner: None,
+            expires_at: None,
         };
 
         let mut mock = MockKvStoreCommandsModuleBackend::new();
@@ -79,4 +94,64 @@ mod tests {
         )
         .unwrap();
     }
+
+    #[test]
+    fn cas() {
+        let data = CasArgs {
+            key: ByteVec::from(vec![1]),
+            value: ByteVec::from(vec![3]),
+            expected_value: Some(ByteVec::from(vec![2])),
+            expected_hash: None,
+            alternative_owner: None,
+        };
+
+        let mut mock = MockKvStoreCommandsModuleBackend::new();
+        mock.expect_cas()
+            .with(predicate::eq(identity(1)), predicate::eq(data.clone()))
+            .times(1)
+            .returning(|_sender, _args| Ok(CasReturn {}));
+        let module = super::KvStoreCommandsModule::new(Arc::new(Mutex::new(mock)));
+
+        let _: CasReturn = minicbor::decode(
+            &call_module_cbor(1, &module, "kvstore.cas", minicbor::to_vec(data).unwrap()).unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn multi_put() {
+        let data = MultiPutArgs {
+            entries: vec![
+                MultiPutEntry {
+                    key: ByteVec::from(vec![1]),
+                    value: ByteVec::from(vec![2]),
+                    expires_at: None,
+                },
+                MultiPutEntry {
+                    key: ByteVec::from(vec![3]),
+                    value: ByteVec::from(vec![4]),
+                    expires_at: None,
+                },
+            ],
+            alternative_owner: None,
+        };
+
+        let mut mock = MockKvStoreCommandsModuleBackend::new();
+        mock.expect_multi_put()
+            .with(predicate::eq(identity(1)), predicate::eq(data.clone()))
+            .times(1)
+            .returning(|_sender, _args| Ok(MultiPutReturn {}));
+        let module = super::KvStoreCommandsModule::new(Arc::new(Mutex::new(mock)));
+
+        let _: MultiPutReturn = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "kvstore.multiPut",
+                minicbor::to_vec(data).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    }
 }
@@ -0,0 +1,32 @@
+use super::put::{decode_key, decode_value};
+use crate::EmptyReturn;
+use many_identity::Address;
+use many_macros::Cddl;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+
+/// Put a value only if the current value of the key is the one expected. If
+/// neither `expected_value` nor `expected_hash` is set, the key must not exist.
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct CasArgs {
+    #[n(0)]
+    #[cbor(decode_with = "decode_key")]
+    pub key: ByteVec,
+
+    #[n(1)]
+    #[cbor(decode_with = "decode_value")]
+    pub value: ByteVec,
+
+    #[n(2)]
+    pub expected_value: Option<ByteVec>,
+
+    /// The hash of the expected value, as returned by `kvstore.query`.
+    #[n(3)]
+    pub expected_hash: Option<ByteVec>,
+
+    #[n(4)]
+    pub alternative_owner: Option<Address>,
+}
+
+pub type CasReturn = EmptyReturn;
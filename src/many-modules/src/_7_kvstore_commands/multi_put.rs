@@ -0,0 +1,36 @@
+use super::put::{decode_key, decode_value};
+use crate::EmptyReturn;
+use many_identity::Address;
+use many_macros::Cddl;
+use many_types::Timestamp;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct MultiPutEntry {
+    #[n(0)]
+    #[cbor(decode_with = "decode_key")]
+    pub key: ByteVec,
+
+    #[n(1)]
+    #[cbor(decode_with = "decode_value")]
+    pub value: ByteVec,
+
+    #[n(2)]
+    pub expires_at: Option<Timestamp>,
+}
+
+/// Put multiple values at once. All entries are validated before any is
+/// written, so either all of them are put or none is.
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct MultiPutArgs {
+    #[n(0)]
+    pub entries: Vec<MultiPutEntry>,
+
+    #[n(1)]
+    pub alternative_owner: Option<Address>,
+}
+
+pub type MultiPutReturn = EmptyReturn;
@@ -1,5 +1,7 @@
 use crate::EmptyReturn;
 use many_identity::Address;
+use many_macros::Cddl;
+use many_types::Timestamp;
 use minicbor::bytes::ByteVec;
 use minicbor::data::Type;
 use minicbor::{Decode, Encode};
@@ -9,7 +11,7 @@ use minicbor::{Decode, Encode};
 const KVSTORE_KEY_MAX_SIZE: usize = 254;
 const KVSTORE_VALUE_MAX_SIZE: usize = 524288; // 512KiB
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct PutArgs {
     #[n(0)]
@@ -22,10 +24,18 @@ pub struct PutArgs {
 
     #[n(2)]
     pub alternative_owner: Option<Address>,
+
+    /// When set, the key is disabled at this time and removed from the store
+    /// at the end of the block.
+    #[n(3)]
+    pub expires_at: Option<Timestamp>,
 }
 
 /// Data decoder. Check if the key is less than or equal to the maximum allowed size
-fn decode_key<C>(d: &mut minicbor::Decoder, _: &mut C) -> Result<ByteVec, minicbor::decode::Error> {
+pub(super) fn decode_key<C>(
+    d: &mut minicbor::Decoder,
+    _: &mut C,
+) -> Result<ByteVec, minicbor::decode::Error> {
     match d.datatype()? {
         Type::Bytes => {
             let data = d.bytes()?;
@@ -41,7 +51,7 @@ fn decode_key<C>(d: &mut minicbor::Decoder, _: &mut C) -> Result<ByteVec, minicb
 }
 
 /// Data decoder. Check if the value is less than or equal to the maximum allowed size
-fn decode_value<C>(
+pub(super) fn decode_value<C>(
     d: &mut minicbor::Decoder,
     _: &mut C,
 ) -> Result<ByteVec, minicbor::decode::Error> {
@@ -70,6 +80,7 @@ mod tests {
             key: ByteVec::from(vec![1u8; KVSTORE_KEY_MAX_SIZE + 1]),
             value: ByteVec::from(vec![2]),
             alternative_owner: None,
+            expires_at: None,
         };
 
         let enc = minicbor::to_vec(tx).unwrap();
@@ -87,6 +98,7 @@ mod tests {
             key: ByteVec::from(vec![1]),
             value: ByteVec::from(vec![1u8; KVSTORE_VALUE_MAX_SIZE + 1]),
             alternative_owner: None,
+            expires_at: None,
         };
 
         let enc = minicbor::to_vec(tx).unwrap();
@@ -2,7 +2,7 @@ use crate::ResponseMessage;
 use coset::{CborSerializable, CoseSign1};
 use many_error::ManyError;
 use many_identity::Address;
-use many_macros::many_module;
+use many_macros::{many_module, Cddl};
 use minicbor::data::Type;
 use minicbor::encode::{Error, Write};
 use minicbor::{Decode, Decoder, Encode, Encoder};
@@ -111,7 +111,7 @@ pub mod attributes {
     }
 }
 
-#[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Debug, Clone, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct StatusArgs {
     #[n(0)]
@@ -152,6 +152,12 @@ impl StatusReturn {
     }
 }
 
+impl many_types::cddl::Cddl for StatusReturn {
+    fn cddl() -> String {
+        "{0: uint, ? 1: bstr}".to_string()
+    }
+}
+
 impl<C> Encode<C> for StatusReturn {
     fn encode<W: Write>(&self, e: &mut Encoder<W>, _: &mut C) -> Result<(), Error<W::Error>> {
         if let StatusReturn::Done { response } = self {
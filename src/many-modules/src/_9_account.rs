@@ -2,7 +2,7 @@ use crate::events::AddressContainer;
 use crate::EmptyReturn;
 use many_error::{ManyError, Reason};
 use many_identity::Address;
-use many_macros::many_module;
+use many_macros::{many_module, Cddl};
 use many_protocol::context::Context;
 use many_types::{Either, VecOrSingle};
 use minicbor::{decode, encode, Decode, Decoder, Encode, Encoder};
@@ -172,7 +172,7 @@ impl AccountMap {
 }
 
 /// A generic Account type. This is useful as utility for managing accounts in your backend.
-#[derive(Clone, Debug, Encode, Decode)]
+#[derive(Cddl, Clone, Debug, Encode, Decode)]
 #[cbor(map)]
 pub struct Account {
     #[n(0)]
@@ -283,9 +283,46 @@ impl Account {
     pub fn feature<F: features::TryCreateFeature>(&self) -> Option<F> {
         self.features.get::<F>().ok()
     }
+
+    /// Remove features from the account. Fails without changing the account if
+    /// it does not have one of the features, or if it would be left without
+    /// any feature.
+    pub fn remove_features(
+        &mut self,
+        ids: &BTreeSet<features::FeatureId>,
+    ) -> Result<(), ManyError> {
+        if ids.is_empty() {
+            return Err(errors::empty_feature());
+        }
+        if let Some(id) = ids.iter().find(|id| !self.features.has_id(**id)) {
+            return Err(errors::unknown_feature(*id));
+        }
+        if self.features.iter().all(|f| ids.contains(&f.id())) {
+            return Err(errors::account_needs_feature());
+        }
+        for id in ids {
+            self.features.remove(*id);
+        }
+        Ok(())
+    }
+
+    /// Remove the roles which are not in `allowed`, e.g. roles of features
+    /// that were removed. Returns the roles removed from each address.
+    pub fn retain_roles(&mut self, allowed: &BTreeSet<Role>) -> AddressRoleMap {
+        let mut removed = AddressRoleMap::new();
+        for (id, roles) in self.roles.iter_mut() {
+            let obsolete: BTreeSet<Role> = roles.difference(allowed).copied().collect();
+            if !obsolete.is_empty() {
+                roles.retain(|r| allowed.contains(r));
+                removed.insert(*id, obsolete);
+            }
+        }
+        self.roles.retain(|_, roles| !roles.is_empty());
+        removed
+    }
 }
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct CreateArgs {
     #[n(0)]
@@ -304,14 +341,14 @@ impl AddressContainer for CreateArgs {
     }
 }
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct CreateReturn {
     #[n(0)]
     pub id: Address,
 }
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct SetDescriptionArgs {
     #[n(0)]
@@ -329,21 +366,21 @@ impl AddressContainer for SetDescriptionArgs {
 
 pub type SetDescriptionReturn = EmptyReturn;
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct ListRolesArgs {
     #[n(0)]
     pub account: Address,
 }
 
-#[derive(Clone, Encode, Decode)]
+#[derive(Cddl, Clone, Encode, Decode)]
 #[cbor(map)]
 pub struct ListRolesReturn {
     #[n(0)]
     pub roles: BTreeSet<Role>,
 }
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct GetRolesArgs {
     #[n(0)]
@@ -353,14 +390,14 @@ pub struct GetRolesArgs {
     pub identities: VecOrSingle<Address>,
 }
 
-#[derive(Clone, Debug, Encode, Decode)]
+#[derive(Cddl, Clone, Debug, Encode, Decode)]
 #[cbor(map)]
 pub struct GetRolesReturn {
     #[n(0)]
     pub roles: AddressRoleMap,
 }
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct AddRolesArgs {
     #[n(0)]
@@ -380,7 +417,7 @@ impl AddressContainer for AddRolesArgs {
 
 pub type AddRolesReturn = EmptyReturn;
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct RemoveRolesArgs {
     #[n(0)]
@@ -400,14 +437,14 @@ impl AddressContainer for RemoveRolesArgs {
 
 pub type RemoveRolesReturn = EmptyReturn;
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct InfoArgs {
     #[n(0)]
     pub account: Address,
 }
 
-#[derive(Clone, Debug, Encode, Decode)]
+#[derive(Cddl, Clone, Debug, Encode, Decode)]
 #[cbor(map)]
 pub struct InfoReturn {
     #[n(0)]
@@ -423,7 +460,7 @@ pub struct InfoReturn {
     pub disabled: Option<Either<bool, Reason<u64>>>,
 }
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct DisableArgs {
     #[n(0)]
@@ -438,7 +475,7 @@ impl AddressContainer for DisableArgs {
 
 pub type DisableReturn = EmptyReturn;
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct AddFeaturesArgs {
     #[n(0)]
@@ -461,6 +498,24 @@ impl AddressContainer for AddFeaturesArgs {
 
 pub type AddFeaturesReturn = EmptyReturn;
 
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct RemoveFeaturesArgs {
+    #[n(0)]
+    pub account: Address,
+
+    #[n(1)]
+    pub features: BTreeSet<features::FeatureId>,
+}
+
+impl AddressContainer for RemoveFeaturesArgs {
+    fn addresses(&self) -> BTreeSet<Address> {
+        BTreeSet::from([self.account])
+    }
+}
+
+pub type RemoveFeaturesReturn = EmptyReturn;
+
 #[many_module(name = AccountModule, id = 9, namespace = account, many_modules_crate = crate)]
 #[cfg_attr(test, mockall::automock)]
 pub trait AccountModuleBackend: Send {
@@ -468,6 +523,7 @@ pub trait AccountModuleBackend: Send {
     fn create(&mut self, sender: &Address, args: CreateArgs) -> Result<CreateReturn, ManyError>;
 
     /// Set the description of an account.
+    #[many(priority = 10)]
     fn set_description(
         &mut self,
         sender: &Address,
@@ -491,6 +547,7 @@ pub trait AccountModuleBackend: Send {
     ) -> Result<GetRolesReturn, ManyError>;
 
     /// Add roles to identities for an account.
+    #[many(priority = 10)]
     fn add_roles(
         &mut self,
         sender: &Address,
@@ -498,6 +555,7 @@ pub trait AccountModuleBackend: Send {
     ) -> Result<AddRolesReturn, ManyError>;
 
     /// Remove roles from an identity for an account.
+    #[many(priority = 10)]
     fn remove_roles(
         &mut self,
         sender: &Address,
@@ -513,21 +571,34 @@ pub trait AccountModuleBackend: Send {
     ) -> Result<InfoReturn, ManyError>;
 
     /// Disable or delete an account.
+    #[many(priority = 10)]
     fn disable(&mut self, sender: &Address, args: DisableArgs) -> Result<DisableReturn, ManyError>;
 
     /// Add additional features to an account.
+    #[many(priority = 10)]
     fn add_features(
         &mut self,
         sender: &Address,
         args: AddFeaturesArgs,
     ) -> Result<AddFeaturesReturn, ManyError>;
+
+    /// Remove features from an account, with the roles and the storage
+    /// specific to them.
+    #[many(priority = 10)]
+    fn remove_features(
+        &mut self,
+        sender: &Address,
+        args: RemoveFeaturesArgs,
+    ) -> Result<RemoveFeaturesReturn, ManyError>;
 }
 
 #[cfg(test)]
 mod module_tests {
     use super::*;
     use crate::testutils::call_module;
+    use crate::ManyModule;
     use many_identity::testing::identity;
+    use many_protocol::RequestMessage;
     use std::sync::{Arc, Mutex, RwLock};
 
     // TODO: split this to get easier to maintain tests.
@@ -693,6 +764,19 @@ mod module_tests {
         let account_map = account_map.read().unwrap();
         assert!(account_map.inner.is_empty());
     }
+
+    #[test]
+    fn priority() {
+        let module =
+            super::AccountModule::new(Arc::new(Mutex::new(MockAccountModuleBackend::new())));
+        let priority = |method: &str| {
+            module.priority(&RequestMessage::default().with_method(method.to_string()))
+        };
+        assert_eq!(priority("account.addRoles"), 10);
+        assert_eq!(priority("account.disable"), 10);
+        assert_eq!(priority("account.info"), 0);
+        assert_eq!(priority("ledger.send"), 0);
+    }
 }
 
 #[test]
@@ -743,3 +827,86 @@ fn remove_empty_role() {
     account.remove_role(&identity(1), Role::CanMultisigSubmit);
     assert!(!account.roles.contains_key(&identity(1)));
 }
+
+#[test]
+fn remove_features() {
+    use features::Feature;
+    use many_identity::testing::identity;
+
+    let owner = identity(0);
+    let mut account = Account::create(
+        &owner,
+        CreateArgs {
+            description: None,
+            roles: Some(AddressRoleMap::from([(
+                identity(1),
+                BTreeSet::from([Role::CanLedgerTransact, Role::CanMultisigApprove]),
+            )])),
+            features: features::FeatureSet::from_iter([
+                Feature::with_id(0),
+                Feature::with_id(1).with_argument(many_types::cbor::CborAny::Int(2)),
+            ]),
+        },
+    );
+
+    assert!(account.remove_features(&BTreeSet::new()).is_err());
+    assert!(account.remove_features(&BTreeSet::from([1, 2])).is_err());
+    assert!(account.remove_features(&BTreeSet::from([0, 1])).is_err());
+    assert!(account.features.has_id(1));
+
+    account.remove_features(&BTreeSet::from([1])).unwrap();
+    assert!(!account.features.has_id(1));
+    assert!(account.features.has_id(0));
+
+    let removed = account.retain_roles(&BTreeSet::from([Role::Owner, Role::CanLedgerTransact]));
+    assert_eq!(
+        removed,
+        AddressRoleMap::from([(identity(1), BTreeSet::from([Role::CanMultisigApprove]))])
+    );
+    assert!(account.has_role(&owner, Role::Owner));
+    assert_eq!(
+        account.get_roles(&identity(1)),
+        BTreeSet::from([Role::CanLedgerTransact])
+    );
+
+    account.retain_roles(&BTreeSet::from([Role::Owner]));
+    assert!(!account.roles.contains_key(&identity(1)));
+}
+
+#[cfg(test)]
+mod canonical_tests {
+    use super::*;
+    use crate::testutils::assert_canonical_round_trip;
+    use many_identity::testing::identity;
+    use proptest::prelude::*;
+    use strum::IntoEnumIterator;
+
+    fn arb_account() -> impl Strategy<Value = Account> {
+        let roles = proptest::collection::btree_set(
+            proptest::sample::select(Role::iter().collect::<Vec<_>>()),
+            1..5,
+        );
+        (
+            proptest::option::of("[A-Za-z0-9 ]{0,50}"),
+            proptest::collection::btree_map((0u32..1000).prop_map(identity), roles, 0..10),
+            proptest::collection::btree_set(any::<features::FeatureId>(), 0..5),
+            proptest::option::of(any::<bool>()),
+        )
+            .prop_map(|(description, roles, features, disabled)| Account {
+                description,
+                roles,
+                features: features
+                    .into_iter()
+                    .map(features::Feature::with_id)
+                    .collect(),
+                disabled: disabled.map(Either::Left),
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn canonical_account(account in arb_account()) {
+            assert_canonical_round_trip(&account);
+        }
+    }
+}
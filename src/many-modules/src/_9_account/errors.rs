@@ -7,5 +7,8 @@ define_attribute_many_error!(
         3: pub fn user_needs_role(role) => "Sender needs role '{role}' to perform this operation.",
         4: pub fn account_must_own_itself() => "Unable to remove owner role from the account itself.",
         5: pub fn empty_feature() => "At least one feature must be selected.",
+        6: pub fn unknown_feature(id) => "Account does not have feature {id}.",
+        7: pub fn account_needs_feature() => "Unable to remove all the features of an account.",
+        8: pub fn feature_in_use(id, reason) => "Unable to remove feature {id}: {reason}.",
     }
 );
@@ -1,19 +1,22 @@
 use crate::account::Role;
 use crate::Attribute;
 use many_error::ManyError;
+use many_macros::Cddl;
 use many_types::cbor::CborAny;
 use minicbor::{Decode, Encode};
 use std::collections::BTreeSet;
 
+pub mod hooks;
 pub mod kvstore;
 pub mod ledger;
 pub mod multisig;
 pub mod tokens;
+pub mod vesting;
 
 pub type FeatureId = u32;
 
 /// An Account Feature.
-#[derive(Encode, Decode, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Cddl, Encode, Decode, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
 #[repr(transparent)]
 #[cbor(transparent)]
 pub struct Feature(#[n(0)] Attribute);
@@ -54,7 +57,7 @@ impl Feature {
 /// assert!(feature_set.get_feature(1).is_some());
 /// assert!(feature_set.get_feature(2).is_none());
 /// ```
-#[derive(Encode, Decode, Clone, Debug, Default, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Cddl, Encode, Decode, Clone, Debug, Default, Ord, PartialOrd, Eq, PartialEq)]
 #[cbor(transparent)]
 pub struct FeatureSet(#[n(0)] BTreeSet<Feature>);
 
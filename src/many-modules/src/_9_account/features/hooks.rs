@@ -0,0 +1,249 @@
+use crate::account::features::{Feature, FeatureId, TryCreateFeature};
+use crate::account::Role;
+use crate::events::{EventId, EventInfo, EventLog};
+use crate::EmptyReturn;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::{many_module, Cddl};
+use many_types::cbor::CborAny;
+use minicbor::{Decode, Encode};
+use num_enum::TryFromPrimitive;
+use std::collections::BTreeSet;
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+pub mod errors {
+    use many_error::define_attribute_many_error;
+    define_attribute_many_error!(
+        attribute 9 => {
+            200: pub fn hooks_not_enabled(id) => "Account {id} does not have the hooks feature.",
+        }
+    );
+}
+
+/// The maximum number of records returned by `account.hookRecords`.
+pub const HOOK_RECORDS_MAX_COUNT: u64 = 100;
+
+/// An event on an account which writes a record in the account's kvstore
+/// namespace, in the same transaction as the event itself.
+#[derive(Copy, Clone, Debug, Decode, Encode, Ord, PartialOrd, Eq, PartialEq, TryFromPrimitive)]
+#[repr(u8)]
+#[cbor(index_only)]
+pub enum HookTrigger {
+    /// Tokens were sent to the account.
+    #[n(0)]
+    IncomingSend = 0,
+
+    /// Tokens were sent from the account.
+    #[n(1)]
+    OutgoingSend = 1,
+}
+
+impl HookTrigger {
+    /// Whether this trigger fires for an event on `account`.
+    pub fn matches(&self, account: &Address, event: &EventInfo) -> bool {
+        match (self, event) {
+            (HookTrigger::IncomingSend, EventInfo::Send { to, .. }) => to == account,
+            (HookTrigger::OutgoingSend, EventInfo::Send { from, .. }) => from == account,
+            _ => false,
+        }
+    }
+}
+
+/// Account hooks. The arguments of the feature are the triggers registered by
+/// the account owner.
+pub struct AccountHooks {
+    pub triggers: BTreeSet<HookTrigger>,
+}
+
+impl AccountHooks {
+    pub fn new(triggers: impl IntoIterator<Item = HookTrigger>) -> Self {
+        Self {
+            triggers: triggers.into_iter().collect(),
+        }
+    }
+
+    /// The first trigger firing for an event on `account`, if any.
+    pub fn trigger_for(&self, account: &Address, event: &EventInfo) -> Option<HookTrigger> {
+        self.triggers
+            .iter()
+            .find(|trigger| trigger.matches(account, event))
+            .copied()
+    }
+}
+
+impl TryCreateFeature for AccountHooks {
+    const ID: FeatureId = 4;
+
+    fn try_create(f: &Feature) -> Result<Self, ManyError> {
+        let arguments = f.arguments();
+        if arguments.is_empty() {
+            return Err(ManyError::invalid_attribute_arguments());
+        }
+
+        arguments
+            .iter()
+            .map(|argument| match argument {
+                CborAny::Int(x) => u8::try_from(*x)
+                    .ok()
+                    .and_then(|x| HookTrigger::try_from(x).ok())
+                    .ok_or_else(ManyError::invalid_attribute_arguments),
+                _ => Err(ManyError::invalid_attribute_arguments()),
+            })
+            .collect::<Result<BTreeSet<_>, _>>()
+            .map(|triggers| Self { triggers })
+    }
+}
+
+impl super::FeatureInfo for AccountHooks {
+    fn as_feature(&self) -> Feature {
+        Feature::new(
+            Self::ID,
+            self.triggers
+                .iter()
+                .map(|trigger| CborAny::Int(*trigger as i64))
+                .collect(),
+        )
+    }
+
+    fn roles() -> BTreeSet<Role> {
+        BTreeSet::new()
+    }
+}
+
+/// A record written by a hook. Records are keyed by the ID of the event which
+/// fired the hook.
+#[derive(Cddl, Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct HookRecord {
+    #[n(0)]
+    pub trigger: HookTrigger,
+
+    #[n(1)]
+    pub event: EventLog,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct HookRecordsArgs {
+    #[n(0)]
+    pub account: Address,
+
+    /// Only return the records of events after this one.
+    #[n(1)]
+    pub after: Option<EventId>,
+
+    #[n(2)]
+    pub count: Option<u64>,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct HookRecordsReturn {
+    #[n(0)]
+    pub records: Vec<HookRecord>,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct HookClearArgs {
+    #[n(0)]
+    pub account: Address,
+
+    /// Remove the records of all events up to, and including, this one.
+    #[n(1)]
+    pub up_to: EventId,
+}
+
+pub type HookClearReturn = EmptyReturn;
+
+/// Records written by account hooks. Anyone can read the records of an
+/// account, but only its owners can clear them.
+#[many_module(name = AccountHooksModule, namespace = account, many_modules_crate = crate)]
+#[cfg_attr(test, automock)]
+pub trait AccountHooksModuleBackend: Send {
+    fn hook_records(
+        &self,
+        sender: &Address,
+        args: HookRecordsArgs,
+    ) -> Result<HookRecordsReturn, ManyError>;
+    fn hook_clear(
+        &mut self,
+        sender: &Address,
+        args: HookClearArgs,
+    ) -> Result<HookClearReturn, ManyError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::features::FeatureInfo;
+    use crate::testutils::call_module_cbor;
+    use many_identity::testing::identity;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn feature_arguments() {
+        let hooks = AccountHooks::new([HookTrigger::OutgoingSend, HookTrigger::IncomingSend]);
+        let feature = hooks.as_feature();
+        assert_eq!(feature.arguments(), &vec![CborAny::Int(0), CborAny::Int(1)]);
+        assert_eq!(
+            AccountHooks::try_create(&feature).unwrap().triggers,
+            hooks.triggers
+        );
+
+        assert!(AccountHooks::try_create(&Feature::with_id(AccountHooks::ID)).is_err());
+        assert!(AccountHooks::try_create(
+            &Feature::with_id(AccountHooks::ID).with_argument(CborAny::Int(99))
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn triggers() {
+        let event = EventInfo::Send {
+            from: identity(1),
+            to: identity(2),
+            symbol: identity(3),
+            amount: 10u16.into(),
+            memo: None,
+        };
+        let hooks = AccountHooks::new([HookTrigger::IncomingSend]);
+        assert_eq!(
+            hooks.trigger_for(&identity(2), &event),
+            Some(HookTrigger::IncomingSend)
+        );
+        assert_eq!(hooks.trigger_for(&identity(1), &event), None);
+        assert_eq!(
+            AccountHooks::new([HookTrigger::OutgoingSend]).trigger_for(&identity(1), &event),
+            Some(HookTrigger::OutgoingSend)
+        );
+    }
+
+    #[test]
+    fn hook_clear() {
+        let mut mock = MockAccountHooksModuleBackend::new();
+        let data = HookClearArgs {
+            account: identity(2),
+            up_to: EventId::from(5u64),
+        };
+        mock.expect_hook_clear()
+            .with(eq(identity(1)), eq(data.clone()))
+            .times(1)
+            .returning(|_, _| Ok(EmptyReturn));
+        let module = super::AccountHooksModule::new(Arc::new(Mutex::new(mock)));
+
+        let clear_returns: HookClearReturn = minicbor::decode(
+            &call_module_cbor(
+                1,
+                &module,
+                "account.hookClear",
+                minicbor::to_vec(data).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(clear_returns, EmptyReturn);
+    }
+}
@@ -1,11 +1,11 @@
 use crate::account::features::{Feature, FeatureId, TryCreateFeature};
 use crate::account::Role;
-use crate::events::{AccountMultisigTransaction, AddressContainer};
+use crate::events::{is_public_target, AccountMultisigTransaction, AddressContainer};
 use crate::ledger::SendArgs;
 use crate::EmptyReturn;
 use many_error::ManyError;
 use many_identity::Address;
-use many_macros::many_module;
+use many_macros::{many_module, Cddl};
 use many_protocol::ResponseMessage;
 use many_types::cbor::CborAny;
 use many_types::ledger::TokenAmount;
@@ -24,11 +24,15 @@ pub mod errors {
             102: pub fn transaction_type_unsupported() => "This transaction is not supported.",
             103: pub fn cannot_execute_transaction() => "This transaction cannot be executed yet.",
             104: pub fn transaction_expired_or_withdrawn() => "This transaction expired or was withdrawn.",
+            105: pub fn notification_not_registered() => "No notification endpoint registered for this member.",
+            106: pub fn invalid_notification_endpoint(reason) => "Invalid notification endpoint: {reason}.",
+            107: pub fn transaction_not_executed() => "This transaction was not executed.",
+            108: pub fn notification_endpoint_mismatch() => "The endpoint does not match the registered endpoint hash.",
         }
     );
 }
 
-#[derive(Default, Clone, Debug, Encode, Decode)]
+#[derive(Cddl, Default, Clone, Debug, Encode, Decode)]
 #[cbor(map)]
 pub struct MultisigAccountFeatureArg {
     #[n(0)]
@@ -122,12 +126,13 @@ impl super::FeatureInfo for MultisigAccountFeature {
     }
 }
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct SubmitTransactionArgs {
     #[n(0)]
     pub account: Address,
 
+    /// Deprecated, use `memo` instead.
     #[n(1)]
     pub memo_: Option<MemoLegacy<String>>,
 
@@ -143,6 +148,7 @@ pub struct SubmitTransactionArgs {
     #[n(5)]
     pub execute_automatically: Option<bool>,
 
+    /// Deprecated, use `memo` instead.
     #[n(6)]
     pub data_: Option<DataLegacy>,
 
@@ -175,6 +181,12 @@ impl SubmitTransactionArgs {
             memo: None,
         }
     }
+
+    /// The memo of the transaction, merged with the legacy memo and data
+    /// fields if it is missing.
+    pub fn merged_memo(&self) -> Result<Option<Memo>, ManyError> {
+        Memo::merge_legacy(self.memo.clone(), self.memo_.clone(), self.data_.clone())
+    }
 }
 
 impl AddressContainer for SubmitTransactionArgs {
@@ -185,21 +197,21 @@ impl AddressContainer for SubmitTransactionArgs {
     }
 }
 
-#[derive(Clone, Debug, Encode, Decode)]
+#[derive(Cddl, Clone, Debug, Encode, Decode)]
 #[cbor(map)]
 pub struct SubmitTransactionReturn {
     #[n(0)]
     pub token: ByteVec,
 }
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct InfoArgs {
     #[n(0)]
     pub token: ByteVec,
 }
 
-#[derive(Clone, Debug, Default, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Default, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct ApproverInfo {
     #[n(0)]
@@ -246,9 +258,10 @@ impl<'b, C> Decode<'b, C> for MultisigTransactionState {
     }
 }
 
-#[derive(Debug, Clone, Encode, Decode)]
+#[derive(Cddl, Debug, Clone, Encode, Decode)]
 #[cbor(map)]
 pub struct InfoReturn {
+    /// Deprecated, use `memo` instead.
     #[n(0)]
     pub memo_: Option<legacy::MemoLegacy<String>>,
 
@@ -270,6 +283,7 @@ pub struct InfoReturn {
     #[n(6)]
     pub timeout: Timestamp,
 
+    /// Deprecated, use `memo` instead.
     #[n(7)]
     pub data_: Option<DataLegacy>,
 
@@ -280,7 +294,26 @@ pub struct InfoReturn {
     pub memo: Option<Memo>,
 }
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+impl InfoReturn {
+    /// The memo of the transaction, merged with the legacy memo and data
+    /// fields if it is missing.
+    pub fn merged_memo(&self) -> Result<Option<Memo>, ManyError> {
+        Memo::merge_legacy(self.memo.clone(), self.memo_.clone(), self.data_.clone())
+    }
+
+    /// Move the legacy memo and data fields into the memo, so only the new
+    /// form is encoded.
+    pub fn without_legacy_memo(self) -> Result<Self, ManyError> {
+        Ok(Self {
+            memo: self.merged_memo()?,
+            memo_: None,
+            data_: None,
+            ..self
+        })
+    }
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct SetDefaultsArgs {
     #[n(0)]
@@ -304,32 +337,43 @@ impl AddressContainer for SetDefaultsArgs {
 
 pub type SetDefaultsReturn = EmptyReturn;
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct ApproveArgs {
     #[n(0)]
     pub token: ByteVec,
+
+    /// Approve on behalf of an account that is a member of the multisig account.
+    /// The sender must be an owner or an approver of that account. A multisig
+    /// member account approves once its own threshold is reached.
+    #[n(1)]
+    pub approve_as: Option<Address>,
 }
 
 pub type ApproveReturn = EmptyReturn;
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct RevokeArgs {
     #[n(0)]
     pub token: ByteVec,
+
+    /// Revoke the approval made on behalf of a member account of the multisig
+    /// account.
+    #[n(1)]
+    pub revoke_as: Option<Address>,
 }
 
 pub type RevokeReturn = EmptyReturn;
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct ExecuteArgs {
     #[n(0)]
     pub token: ByteVec,
 }
 
-#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
 #[cbor(map)]
 pub struct WithdrawArgs {
     #[n(0)]
@@ -338,6 +382,200 @@ pub struct WithdrawArgs {
 
 pub type WithdrawReturn = EmptyReturn;
 
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ResultArgs {
+    #[n(0)]
+    pub token: ByteVec,
+}
+
+/// The response of an executed transaction, as it was returned to the executer.
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ResultReturn {
+    #[n(0)]
+    pub state: MultisigTransactionState,
+
+    #[n(1)]
+    pub response: ResponseMessage,
+}
+
+/// The kind of multisig notices a member can opt into.
+#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Encode, Decode)]
+#[cbor(index_only)]
+pub enum MultisigNotificationKind {
+    #[n(0)]
+    Submitted,
+
+    #[n(1)]
+    ApproachingExpiry,
+}
+
+impl MultisigNotificationKind {
+    pub fn all() -> BTreeSet<Self> {
+        BTreeSet::from([Self::Submitted, Self::ApproachingExpiry])
+    }
+}
+
+/// An endpoint where a member wants to receive multisig notices. Endpoints are never
+/// part of a transaction or of the server state; only their hash is.
+#[derive(Clone, Debug, Eq, PartialEq, Encode, Decode)]
+pub enum NotificationEndpoint {
+    #[n(0)]
+    Email(#[n(0)] String),
+
+    #[n(1)]
+    Webhook(#[n(0)] String),
+}
+
+impl NotificationEndpoint {
+    pub const MAXIMUM_LENGTH: usize = 2048;
+    pub const HASH_LENGTH: usize = 32;
+
+    /// Verify the endpoint is an email address or an HTTP(S) URL. Webhooks
+    /// must not point to a private address, see [is_public_target].
+    pub fn validate(&self) -> Result<(), ManyError> {
+        let (value, valid) = match self {
+            NotificationEndpoint::Email(email) => (email, email.contains('@')),
+            NotificationEndpoint::Webhook(url) => (
+                url,
+                url.starts_with("https://") || url.starts_with("http://"),
+            ),
+        };
+
+        if value.len() > Self::MAXIMUM_LENGTH {
+            Err(errors::invalid_notification_endpoint(
+                "endpoint is too long",
+            ))
+        } else if !valid {
+            Err(errors::invalid_notification_endpoint(match self {
+                NotificationEndpoint::Email(_) => "not an email address",
+                NotificationEndpoint::Webhook(_) => "not an HTTP(S) URL",
+            }))
+        } else if matches!(self, NotificationEndpoint::Webhook(url) if !is_public_target(url)) {
+            Err(errors::invalid_notification_endpoint(
+                "not a public address",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A notification registration, as it is stored and listed.
+#[derive(Cddl, Clone, Debug, Eq, PartialEq, Encode, Decode)]
+#[cbor(map)]
+pub struct NotificationRegistration {
+    #[n(0)]
+    pub endpoint_hash: ByteVec,
+
+    #[n(1)]
+    pub kinds: BTreeSet<MultisigNotificationKind>,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct RegisterNotificationArgs {
+    #[n(0)]
+    pub account: Address,
+
+    /// The hash of the endpoint. The endpoint itself is sent separately to the
+    /// node with `multisigProvideNotificationEndpoint`.
+    #[n(1)]
+    pub endpoint_hash: ByteVec,
+
+    /// The notices to receive. All of them if unspecified.
+    #[n(2)]
+    pub kinds: Option<BTreeSet<MultisigNotificationKind>>,
+}
+
+impl AddressContainer for RegisterNotificationArgs {
+    fn addresses(&self) -> BTreeSet<Address> {
+        BTreeSet::from([self.account])
+    }
+}
+
+pub type RegisterNotificationReturn = EmptyReturn;
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ProvideNotificationEndpointArgs {
+    #[n(0)]
+    pub account: Address,
+
+    #[n(1)]
+    pub endpoint: NotificationEndpoint,
+}
+
+pub type ProvideNotificationEndpointReturn = EmptyReturn;
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct UnregisterNotificationArgs {
+    #[n(0)]
+    pub account: Address,
+}
+
+impl AddressContainer for UnregisterNotificationArgs {
+    fn addresses(&self) -> BTreeSet<Address> {
+        BTreeSet::from([self.account])
+    }
+}
+
+pub type UnregisterNotificationReturn = EmptyReturn;
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ListNotificationsArgs {
+    #[n(0)]
+    pub account: Address,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ListNotificationsReturn {
+    #[n(0)]
+    pub registrations: BTreeMap<Address, NotificationRegistration>,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ListPendingArgs {
+    #[n(0)]
+    pub account: Address,
+}
+
+/// A transaction waiting for approvals, as listed by `multisigListPending`.
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct PendingTransaction {
+    #[n(0)]
+    pub token: ByteVec,
+
+    #[n(1)]
+    pub transaction: AccountMultisigTransaction,
+
+    #[n(2)]
+    pub submitter: Address,
+
+    /// The members who approved the transaction so far.
+    #[n(3)]
+    pub approvers: BTreeSet<Address>,
+
+    #[n(4)]
+    pub threshold: u64,
+
+    #[n(5)]
+    pub timeout: Timestamp,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct ListPendingReturn {
+    #[n(0)]
+    pub transactions: Vec<PendingTransaction>,
+}
+
 #[many_module(name = AccountMultisigModule, namespace = account, many_modules_crate = crate)]
 pub trait AccountMultisigModuleBackend: Send {
     fn multisig_submit_transaction(
@@ -361,6 +599,7 @@ pub trait AccountMultisigModuleBackend: Send {
         sender: &Address,
         args: RevokeArgs,
     ) -> Result<RevokeReturn, ManyError>;
+    #[many(priority = 20)]
     fn multisig_execute(
         &mut self,
         sender: &Address,
@@ -371,4 +610,158 @@ pub trait AccountMultisigModuleBackend: Send {
         sender: &Address,
         args: WithdrawArgs,
     ) -> Result<WithdrawReturn, ManyError>;
+    fn multisig_result(
+        &self,
+        sender: &Address,
+        args: ResultArgs,
+    ) -> Result<ResultReturn, ManyError>;
+    fn multisig_register_notification(
+        &mut self,
+        sender: &Address,
+        args: RegisterNotificationArgs,
+    ) -> Result<RegisterNotificationReturn, ManyError>;
+    #[many(deny_anonymous)]
+    fn multisig_provide_notification_endpoint(
+        &self,
+        sender: &Address,
+        args: ProvideNotificationEndpointArgs,
+    ) -> Result<ProvideNotificationEndpointReturn, ManyError>;
+    fn multisig_unregister_notification(
+        &mut self,
+        sender: &Address,
+        args: UnregisterNotificationArgs,
+    ) -> Result<UnregisterNotificationReturn, ManyError>;
+    fn multisig_list_notifications(
+        &self,
+        sender: &Address,
+        args: ListNotificationsArgs,
+    ) -> Result<ListNotificationsReturn, ManyError>;
+    fn multisig_list_pending(
+        &self,
+        sender: &Address,
+        args: ListPendingArgs,
+    ) -> Result<ListPendingReturn, ManyError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::assert_canonical_round_trip;
+    use many_identity::testing::identity;
+    use proptest::prelude::*;
+
+    #[test]
+    fn notification_endpoint_validate() {
+        assert!(NotificationEndpoint::Email("foo@example.com".to_string())
+            .validate()
+            .is_ok());
+        assert!(
+            NotificationEndpoint::Webhook("https://example.com/hook".to_string())
+                .validate()
+                .is_ok()
+        );
+        assert!(NotificationEndpoint::Email("example.com".to_string())
+            .validate()
+            .is_err());
+        assert!(
+            NotificationEndpoint::Webhook("ftp://example.com".to_string())
+                .validate()
+                .is_err()
+        );
+        for url in [
+            "http://localhost:8000/hook",
+            "http://127.0.0.1/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://[::1]/hook",
+        ] {
+            assert!(
+                NotificationEndpoint::Webhook(url.to_string())
+                    .validate()
+                    .is_err(),
+                "{url}"
+            );
+        }
+        assert!(NotificationEndpoint::Webhook(format!(
+            "https://{}",
+            "a".repeat(NotificationEndpoint::MAXIMUM_LENGTH)
+        ))
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn notification_endpoint_encode_decode() {
+        let endpoint = NotificationEndpoint::Webhook("https://example.com/hook".to_string());
+        let bytes = minicbor::to_vec(&endpoint).unwrap();
+        assert_eq!(
+            minicbor::decode::<NotificationEndpoint>(&bytes).unwrap(),
+            endpoint
+        );
+    }
+
+    fn arb_info() -> impl Strategy<Value = InfoReturn> {
+        (
+            0u32..1000,
+            any::<u64>(),
+            proptest::collection::btree_map(0u32..1000, any::<bool>(), 0..10),
+            any::<u64>(),
+            any::<bool>(),
+            0..u32::MAX as u64,
+            proptest::option::of("[A-Za-z0-9 ]{0,100}"),
+        )
+            .prop_map(
+                |(
+                    submitter,
+                    amount,
+                    approvers,
+                    threshold,
+                    execute_automatically,
+                    timeout,
+                    memo,
+                )| {
+                    InfoReturn {
+                        memo_: None,
+                        transaction: AccountMultisigTransaction::Send(SendArgs {
+                            from: Some(identity(submitter)),
+                            to: identity(submitter + 1),
+                            symbol: identity(1000),
+                            amount: amount.into(),
+                            memo: None,
+                        }),
+                        submitter: identity(submitter),
+                        approvers: approvers
+                            .into_iter()
+                            .map(|(id, approved)| (identity(id), ApproverInfo { approved }))
+                            .collect(),
+                        threshold,
+                        execute_automatically,
+                        timeout: Timestamp::new(timeout).unwrap(),
+                        data_: None,
+                        state: MultisigTransactionState::Pending,
+                        memo: memo.map(|m| Memo::try_from(m).unwrap()),
+                    }
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn canonical_info(info in arb_info()) {
+            assert_canonical_round_trip(&info);
+        }
+
+        #[test]
+        fn canonical_registration(
+            endpoint_hash in proptest::collection::vec(any::<u8>(), 32),
+            kinds in proptest::sample::subsequence(
+                MultisigNotificationKind::all().into_iter().collect::<Vec<_>>(),
+                0..=2,
+            ),
+        ) {
+            assert_canonical_round_trip(&NotificationRegistration {
+                endpoint_hash: endpoint_hash.into(),
+                kinds: kinds.into_iter().collect(),
+            });
+        }
+    }
 }
@@ -0,0 +1,235 @@
+use crate::account::features::{Feature, FeatureId, TryCreateFeature};
+use crate::account::Role;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::{many_module, Cddl};
+use many_types::cbor::CborAny;
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::Timestamp;
+use minicbor::{Decode, Encode};
+use std::collections::BTreeSet;
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+pub mod errors {
+    use many_error::define_attribute_many_error;
+    define_attribute_many_error!(
+        attribute 9 => {
+            300: pub fn vesting_not_enabled(id) => "Account {id} does not have the vesting feature.",
+            301: pub fn invalid_vesting_schedule(reason) => "Invalid vesting schedule: {reason}.",
+            302: pub fn amount_not_vested(available) => "Only {available} tokens are vested and available on this account.",
+        }
+    );
+}
+
+/// Tokens of an account which unlock over time. Nothing is unlocked before
+/// the cliff, then the tokens unlock linearly between the start and the end
+/// of the schedule. A schedule whose cliff is its end unlocks everything at
+/// once.
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct VestingSchedule {
+    #[n(0)]
+    pub symbol: Symbol,
+
+    /// The amount of tokens locked by the schedule.
+    #[n(1)]
+    pub total: TokenAmount,
+
+    #[n(2)]
+    pub start: Timestamp,
+
+    #[n(3)]
+    pub end: Timestamp,
+
+    #[n(4)]
+    pub cliff: Option<Timestamp>,
+}
+
+impl VestingSchedule {
+    pub fn validate(&self) -> Result<(), ManyError> {
+        if self.total.is_zero() {
+            return Err(errors::invalid_vesting_schedule("the total is zero"));
+        }
+        if self.end < self.start {
+            return Err(errors::invalid_vesting_schedule(
+                "the end is before the start",
+            ));
+        }
+        if let Some(cliff) = self.cliff {
+            if cliff < self.start || cliff > self.end {
+                return Err(errors::invalid_vesting_schedule(
+                    "the cliff is outside of the schedule",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The amount unlocked at `now`.
+    pub fn vested(&self, now: Timestamp) -> TokenAmount {
+        if now < self.cliff.unwrap_or(self.start) {
+            return TokenAmount::zero();
+        }
+        if now >= self.end {
+            return self.total.clone();
+        }
+        let elapsed = now.secs().saturating_sub(self.start.secs());
+        let duration = self.end.secs() - self.start.secs();
+        TokenAmount::from(self.total.as_ref() * elapsed / duration)
+    }
+
+    /// The amount still locked at `now`.
+    pub fn locked(&self, now: Timestamp) -> TokenAmount {
+        &self.total - self.vested(now)
+    }
+}
+
+/// Vesting account. The argument of the feature is its schedule. Tokens of
+/// the account above the locked amount can be sent freely.
+pub struct AccountVesting {
+    pub schedule: VestingSchedule,
+}
+
+impl AccountVesting {
+    pub fn new(schedule: VestingSchedule) -> Self {
+        Self { schedule }
+    }
+}
+
+impl TryCreateFeature for AccountVesting {
+    const ID: FeatureId = 5;
+
+    fn try_create(f: &Feature) -> Result<Self, ManyError> {
+        let argument = match f.arguments().as_slice() {
+            [argument @ CborAny::Map(_)] => argument,
+            _ => return Err(ManyError::invalid_attribute_arguments()),
+        };
+        let bytes = minicbor::to_vec(argument).map_err(ManyError::serialization_error)?;
+        let schedule: VestingSchedule =
+            minicbor::decode(&bytes).map_err(|_| ManyError::invalid_attribute_arguments())?;
+        schedule.validate()?;
+        Ok(Self { schedule })
+    }
+}
+
+impl super::FeatureInfo for AccountVesting {
+    fn as_feature(&self) -> Feature {
+        // A schedule always encodes to a map.
+        let argument = minicbor::to_vec(&self.schedule)
+            .ok()
+            .and_then(|bytes| minicbor::decode::<CborAny>(&bytes).ok())
+            .unwrap_or(CborAny::Null);
+        Feature::with_id(Self::ID).with_argument(argument)
+    }
+
+    fn roles() -> BTreeSet<Role> {
+        BTreeSet::new()
+    }
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct InfoArgs {
+    #[n(0)]
+    pub account: Address,
+}
+
+#[derive(Cddl, Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct InfoReturn {
+    #[n(0)]
+    pub schedule: VestingSchedule,
+
+    /// The amount unlocked at the time of the last block.
+    #[n(1)]
+    pub unlocked: TokenAmount,
+
+    /// The amount still locked at the time of the last block.
+    #[n(2)]
+    pub remaining: TokenAmount,
+}
+
+/// The vesting schedules of accounts.
+#[many_module(name = AccountVestingModule, namespace = vesting, many_modules_crate = crate)]
+#[cfg_attr(test, automock)]
+pub trait AccountVestingModuleBackend: Send {
+    fn info(&self, sender: &Address, args: InfoArgs) -> Result<InfoReturn, ManyError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::features::FeatureInfo;
+    use crate::testutils::call_module_cbor;
+    use many_identity::testing::identity;
+    use std::sync::{Arc, Mutex};
+
+    fn schedule(cliff: Option<u64>) -> VestingSchedule {
+        VestingSchedule {
+            symbol: identity(3),
+            total: TokenAmount::from(1_000u16),
+            start: Timestamp::new(1_000).unwrap(),
+            end: Timestamp::new(1_100).unwrap(),
+            cliff: cliff.map(|c| Timestamp::new(c).unwrap()),
+        }
+    }
+
+    #[test]
+    fn vested() {
+        let linear = schedule(None);
+        assert_eq!(linear.vested(Timestamp::new(999).unwrap()), 0u16);
+        assert_eq!(linear.vested(Timestamp::new(1_025).unwrap()), 250u16);
+        assert_eq!(linear.locked(Timestamp::new(1_025).unwrap()), 750u16);
+        assert_eq!(linear.vested(Timestamp::new(2_000).unwrap()), 1_000u16);
+
+        let cliff = schedule(Some(1_050));
+        assert_eq!(cliff.vested(Timestamp::new(1_049).unwrap()), 0u16);
+        assert_eq!(cliff.vested(Timestamp::new(1_050).unwrap()), 500u16);
+
+        let all_at_once = schedule(Some(1_100));
+        assert_eq!(all_at_once.vested(Timestamp::new(1_099).unwrap()), 0u16);
+        assert_eq!(all_at_once.vested(Timestamp::new(1_100).unwrap()), 1_000u16);
+    }
+
+    #[test]
+    fn feature_arguments() {
+        let vesting = AccountVesting::new(schedule(Some(1_050)));
+        let feature = vesting.as_feature();
+        assert!(matches!(feature.arguments().as_slice(), [CborAny::Map(_)]));
+        assert_eq!(
+            AccountVesting::try_create(&feature).unwrap().schedule,
+            vesting.schedule
+        );
+
+        assert!(AccountVesting::try_create(&Feature::with_id(AccountVesting::ID)).is_err());
+        let invalid = AccountVesting::new(schedule(Some(2_000))).as_feature();
+        assert!(AccountVesting::try_create(&invalid).is_err());
+    }
+
+    #[test]
+    fn info() {
+        let mut mock = MockAccountVestingModuleBackend::new();
+        let data = InfoArgs {
+            account: identity(2),
+        };
+        mock.expect_info()
+            .with(eq(identity(1)), eq(data.clone()))
+            .times(1)
+            .returning(|_, _| {
+                Ok(InfoReturn {
+                    schedule: schedule(None),
+                    unlocked: TokenAmount::from(250u16),
+                    remaining: TokenAmount::from(750u16),
+                })
+            });
+        let module = super::AccountVestingModule::new(Arc::new(Mutex::new(mock)));
+
+        let info_returns: InfoReturn = minicbor::decode(
+            &call_module_cbor(1, &module, "vesting.info", minicbor::to_vec(data).unwrap()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(info_returns.remaining, 750u16);
+    }
+}
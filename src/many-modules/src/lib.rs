@@ -7,17 +7,26 @@ use many_protocol::{RequestMessage, ResponseMessage};
 use many_types::attributes::Attribute;
 use minicbor::encode::{Error, Write};
 use minicbor::{Decoder, Encoder};
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 
 macro_rules! reexport_module {
-    ( $( $rename: ident: $name: ident $(+ $more: ident)*; )* ) => {
+    ( $( $rename: ident: $(#[$attr: meta])* $name: ident $(+ $(#[$more_attr: meta])* $more: ident)*; )* ) => {
         $(
+            $(#[$attr])*
             mod $name;
-            $(mod $more;)*
+            $(
+                $(#[$more_attr])*
+                mod $more;
+            )*
 
             pub mod $rename {
+                $(#[$attr])*
                 pub use super::$name::*;
-                $(pub use super::$more::*;)*
+                $(
+                    $(#[$more_attr])*
+                    pub use super::$more::*;
+                )*
             }
         )*
     };
@@ -26,16 +35,42 @@ macro_rules! reexport_module {
 reexport_module!(
     base: _0_base;
     blockchain: _1_blockchain;
-    ledger: _2_ledger + _6_ledger_commands + _11_ledger_tokens + _12_ledger_mintburn;
-    events: _4_events;
+    events: _4_events_common + #[cfg(feature = "events")] _4_events;
     data: _5_data;
-    kvstore: _3_kvstore + _7_kvstore_commands + _13_kvstore_transfer;
     r#async: _8_async;
-    account: _9_account;
     compute: _15_compute;
     web: _16_web + _17_web_commands;
+    watchlist: _18_watchlist;
+    scripts: _24_scripts;
+    labels: _25_labels;
+    rejections: _26_rejections;
+);
+
+#[cfg(feature = "ledger")]
+reexport_module!(
+    ledger: _2_ledger + _6_ledger_commands + _11_ledger_tokens + _12_ledger_mintburn + _19_ledger_freeze + _20_ledger_schedule + _21_ledger_receipts + _22_ledger_streams + _23_ledger_token_identity;
+);
+
+#[cfg(feature = "kvstore")]
+reexport_module!(
+    kvstore: _3_kvstore + _7_kvstore_commands + _13_kvstore_transfer;
+);
+
+// Multisig transactions are events, and events reference account types, so the
+// account module is compiled with the event log.
+#[cfg(feature = "events")]
+reexport_module!(
+    account: _9_account;
+);
+
+#[cfg(feature = "abci")]
+reexport_module!(
     abci_backend: _1000_abci_backend;
     abci_frontend: _1001_abci_frontend;
+);
+
+#[cfg(feature = "idstore")]
+reexport_module!(
     idstore: _1002_idstore;
 );
 
@@ -46,6 +81,12 @@ reexport_module!(
 #[derive(Debug, Eq, PartialEq)]
 pub struct EmptyReturn;
 
+impl many_types::cddl::Cddl for EmptyReturn {
+    fn cddl() -> String {
+        "null".to_string()
+    }
+}
+
 impl<C> minicbor::Encode<C> for EmptyReturn {
     fn encode<W: Write>(&self, e: &mut Encoder<W>, _: &mut C) -> Result<(), Error<W::Error>> {
         // We encode nothing as a null so it's a value.
@@ -65,6 +106,12 @@ impl<'b, C> minicbor::Decode<'b, C> for EmptyReturn {
 #[derive(Debug, Eq, PartialEq)]
 pub struct EmptyArg;
 
+impl many_types::cddl::Cddl for EmptyArg {
+    fn cddl() -> String {
+        "null".to_string()
+    }
+}
+
 impl<C> minicbor::Encode<C> for EmptyArg {
     fn encode<W: Write>(&self, e: &mut Encoder<W>, _: &mut C) -> Result<(), Error<W::Error>> {
         // We encode nothing as a null so it's a value.
@@ -110,6 +157,19 @@ pub trait ManyModule: Sync + Send + Debug {
         Ok(())
     }
 
+    /// The mempool priority of a message, higher first. Consensus servers use
+    /// it to order transactions in CheckTx. Endpoints declare theirs with
+    /// `#[many(priority = N)]`.
+    fn priority(&self, _message: &RequestMessage) -> i64 {
+        0
+    }
+
+    /// The CDDL schemas of the argument and return value of each endpoint of
+    /// this module.
+    fn schemas(&self) -> BTreeMap<String, base::EndpointSchema> {
+        BTreeMap::new()
+    }
+
     /// Execute a message and returns its response.
     async fn execute(&self, message: RequestMessage) -> Result<ResponseMessage, ManyError>;
 }
@@ -120,6 +180,7 @@ pub(crate) mod testutils {
     use many_error::ManyError;
     use many_identity::testing::identity;
     use many_protocol::RequestMessage;
+    use many_types::cbor::canonical;
 
     pub fn call_module(
         key: u32,
@@ -165,4 +226,16 @@ pub(crate) mod testutils {
         let response = smol::block_on(async { module.execute(message).await })?;
         response.data
     }
+
+    /// Check that a value decodes from its canonical encoding, and encodes
+    /// back to the same bytes.
+    pub fn assert_canonical_round_trip<T>(value: &T)
+    where
+        T: minicbor::Encode<()> + for<'b> minicbor::Decode<'b, ()>,
+    {
+        let bytes = canonical::to_canonical_vec(value).unwrap();
+        assert!(canonical::is_canonical(&bytes));
+        let decoded: T = minicbor::decode(&bytes).unwrap();
+        assert_eq!(canonical::to_canonical_vec(&decoded).unwrap(), bytes);
+    }
 }
@@ -0,0 +1,247 @@
+use many_protocol::ManyUrl;
+use many_server::ModuleConfig;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+fn default_name() -> String {
+    "many-node".to_string()
+}
+
+fn default_addr() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 8000))
+}
+
+fn default_domain() -> String {
+    "localhost:8880".to_string()
+}
+
+/// The configuration file of a node, in JSON5. Relative paths are relative to
+/// the directory of the configuration file. For example:
+///
+/// ```json5
+/// {
+///   pem: "id.pem",
+///   storage: "/var/lib/many",
+///   cache: { ttl: 600 },
+///   ledger: { state: "ledger_state.json5" },
+///   kvstore: { state: "kvstore_state.json5", modules: ["kvstore", "kvstore.commands"] },
+/// }
+/// ```
+///
+/// Each backend section is optional, and has its own storage in a directory
+/// of `storage` named after the section. An id store node is a ledger section
+/// serving only the `idstore` module.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NodeConfig {
+    /// The name of the server, returned by `status`.
+    #[serde(default = "default_name")]
+    pub name: String,
+
+    /// The location of a PEM file for the identity of this server.
+    pub pem: PathBuf,
+
+    /// The address and port to bind to for the MANY Http server.
+    #[serde(default = "default_addr")]
+    pub addr: SocketAddr,
+
+    /// The directory containing the persistent stores of all the backends
+    /// and the request cache.
+    pub storage: PathBuf,
+
+    /// Uses an ABCI application module. Only one backend can be configured
+    /// when this is set, as the blockchain has a single application state.
+    #[serde(default)]
+    pub abci: bool,
+
+    /// Application absolute URLs allowed to communicate with this server. Any
+    /// application will be able to communicate with this server if empty.
+    #[serde(default)]
+    pub allow_origin: Option<Vec<ManyUrl>>,
+
+    /// Only these addresses will be able to execute commands, e.g., send,
+    /// put, ... Any addresses will be able to execute queries.
+    #[serde(default)]
+    pub allow_addrs: Option<BTreeSet<many_identity::Address>>,
+
+    /// Endpoints to disable on this server, e.g. `idstore.store`.
+    #[serde(default)]
+    pub disable_endpoints: Vec<String>,
+
+    /// Validate duplicate messages with a request cache, if set.
+    #[serde(default)]
+    pub cache: Option<CacheConfig>,
+
+    #[serde(default)]
+    pub ledger: Option<LedgerConfig>,
+
+    #[serde(default)]
+    pub kvstore: Option<BackendConfig>,
+
+    #[serde(default)]
+    pub web: Option<WebConfig>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CacheConfig {
+    /// Remove requests from the cache after this many seconds. Requests are
+    /// kept forever if unspecified.
+    #[serde(default)]
+    pub ttl: Option<u64>,
+
+    /// Answer requests that were already executed with their original
+    /// response, instead of a duplicated message error.
+    #[serde(default)]
+    pub replay_responses: bool,
+}
+
+/// A backend with its initial state and the modules it serves.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct BackendConfig {
+    /// Path of a state file, used if the backend has no persistent store yet.
+    #[serde(default)]
+    pub state: Option<PathBuf>,
+
+    /// The modules to serve. If unspecified, the default modules of the
+    /// backend that are not served by a previous backend.
+    #[serde(default)]
+    pub modules: Option<Vec<ModuleConfig>>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct LedgerConfig {
+    #[serde(flatten)]
+    pub backend: BackendConfig,
+
+    /// Path to a JSON file containing the configurations for the migrations.
+    /// Migrations are DISABLED unless this is set.
+    #[serde(default)]
+    pub migrations: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct WebConfig {
+    #[serde(flatten)]
+    pub backend: BackendConfig,
+
+    /// The domain the websites are served under.
+    #[serde(default = "default_domain")]
+    pub domain: String,
+}
+
+impl NodeConfig {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+        let config = Self::from_str(&content)?;
+        Ok(config.relative_to(path.parent().unwrap_or_else(|| Path::new(""))))
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(content: &str) -> Result<Self, String> {
+        let config: Self =
+            json5::from_str(content).map_err(|e| format!("Invalid configuration: {e}"))?;
+        if config.ledger.is_none() && config.kvstore.is_none() && config.web.is_none() {
+            return Err("No backend configured.".to_string());
+        }
+        if config.abci
+            && [
+                config.ledger.is_some(),
+                config.kvstore.is_some(),
+                config.web.is_some(),
+            ]
+            .into_iter()
+            .filter(|x| *x)
+            .count()
+                > 1
+        {
+            return Err("Only one backend can be configured with ABCI.".to_string());
+        }
+        Ok(config)
+    }
+
+    /// Resolve the relative paths of the configuration against `dir`.
+    fn relative_to(mut self, dir: &Path) -> Self {
+        let mut paths = vec![&mut self.pem, &mut self.storage];
+        if let Some(ledger) = &mut self.ledger {
+            paths.extend(ledger.backend.state.as_mut());
+            paths.extend(ledger.migrations.as_mut());
+        }
+        if let Some(kvstore) = &mut self.kvstore {
+            paths.extend(kvstore.state.as_mut());
+        }
+        if let Some(web) = &mut self.web {
+            paths.extend(web.backend.state.as_mut());
+        }
+        for path in paths {
+            *path = dir.join(&*path);
+        }
+        self
+    }
+
+    pub fn ledger_storage(&self) -> PathBuf {
+        self.storage.join("ledger")
+    }
+
+    pub fn kvstore_storage(&self) -> PathBuf {
+        self.storage.join("kvstore")
+    }
+
+    pub fn web_storage(&self) -> PathBuf {
+        self.storage.join("web")
+    }
+
+    pub fn cache_storage(&self) -> PathBuf {
+        self.storage.join("cache")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let config = NodeConfig::from_str(
+            r#"{
+                pem: "id.pem",
+                storage: "data",
+                cache: {},
+                ledger: { state: "ledger.json5", migrations: "migrations.json" },
+                kvstore: { modules: ["kvstore", { name: "kvstore.commands" }] },
+            }"#,
+        )
+        .unwrap()
+        .relative_to(Path::new("/etc/many"));
+
+        assert_eq!(config.name, "many-node");
+        assert_eq!(config.addr, default_addr());
+        assert_eq!(config.pem, Path::new("/etc/many/id.pem"));
+        assert_eq!(config.ledger_storage(), Path::new("/etc/many/data/ledger"));
+        assert_eq!(config.cache_storage(), Path::new("/etc/many/data/cache"));
+        let ledger = config.ledger.unwrap();
+        assert_eq!(
+            ledger.backend.state,
+            Some(PathBuf::from("/etc/many/ledger.json5"))
+        );
+        assert!(ledger.backend.modules.is_none());
+        let modules = config.kvstore.unwrap().modules.unwrap();
+        assert_eq!(modules[1].name(), "kvstore.commands");
+        assert!(config.web.is_none());
+    }
+
+    #[test]
+    fn invalid() {
+        for content in [
+            r#"{ pem: "id.pem", storage: "data" }"#,
+            r#"{ pem: "id.pem", storage: "data", ledger: {}, unknown: 1 }"#,
+            r#"{ pem: "id.pem", storage: "data", abci: true, ledger: {}, web: {} }"#,
+        ] {
+            assert!(NodeConfig::from_str(content).is_err());
+        }
+    }
+}
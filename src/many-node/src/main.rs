@@ -0,0 +1,328 @@
+use clap::Parser;
+use many_cli_helpers::CommonCliFlags;
+use many_identity::verifiers::AnonymousVerifier;
+use many_identity::Identity;
+use many_identity_dsa::{CoseKeyIdentity, CoseKeyVerifier};
+use many_identity_webauthn::WebAuthnVerifier;
+use many_ledger::module::atomic::LedgerAtomicGroups;
+use many_ledger::module::query_state::LedgerQueryState;
+use many_ledger::module::LedgerModuleImpl;
+use many_ledger::registry::LedgerModuleContext;
+use many_migration::MigrationConfig;
+use many_modules::abci_backend;
+use many_server::transport::http::HttpServer;
+use many_server::{ManyServer, ModuleConfig};
+use many_server_cache::{ReplayPolicy, RequestCacheValidator, RocksDbCacheBackend};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, info, warn};
+
+use crate::config::NodeConfig;
+
+mod config;
+
+#[derive(Parser, Debug)]
+#[clap(args_override_self(true))]
+struct Opts {
+    #[clap(flatten)]
+    common_flags: CommonCliFlags,
+
+    /// Path to the JSON5 configuration file of the node.
+    // The field needs to be an Option for the clap derive to work properly.
+    #[clap(long, short, required = true)]
+    config: Option<PathBuf>,
+
+    /// Delete the storage directory to start from a clean state.
+    /// If this is not specified the initial states will not be used.
+    #[clap(long)]
+    clean: bool,
+
+    /// List the modules supported by each backend of this binary.
+    #[clap(long, exclusive = true)]
+    list_modules: bool,
+}
+
+/// Whether the persistent store of a backend exists, else the path of its
+/// initial state.
+fn initial_state(persistent: &Path, state: Option<&PathBuf>) -> Option<PathBuf> {
+    if persistent.exists() {
+        if let Some(state) = state {
+            warn!(
+                r#"
+                An existing persistent store {} was found and a staging file {state:?} was given.
+                Ignoring staging file and loading existing persistent store.
+                "#,
+                persistent.display()
+            );
+        }
+        None
+    } else {
+        Some(
+            state
+                .unwrap_or_else(|| {
+                    panic!(
+                        "Persistent store {} or staging file not found.",
+                        persistent.display()
+                    )
+                })
+                .clone(),
+        )
+    }
+}
+
+fn read_json5<T: serde::de::DeserializeOwned>(path: PathBuf) -> T {
+    let content = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Could not read {}: {e}", path.display()));
+    json5::from_str(&content).unwrap_or_else(|e| panic!("Invalid {}: {e}", path.display()))
+}
+
+/// The modules of a backend to add to the server. Modules listed by a
+/// previous backend are skipped from the defaults, and cannot be listed
+/// explicitly; two backends cannot serve the same attribute.
+fn modules(
+    backend: &str,
+    configured: Option<&Vec<ModuleConfig>>,
+    defaults: Vec<ModuleConfig>,
+    served: &mut BTreeSet<String>,
+) -> Vec<ModuleConfig> {
+    let modules: Vec<ModuleConfig> = match configured {
+        Some(modules) => {
+            if let Some(m) = modules.iter().find(|m| served.contains(m.name())) {
+                panic!(
+                    "Module '{}' of the {backend} backend is already served by another backend.",
+                    m.name()
+                );
+            }
+            modules.clone()
+        }
+        None => defaults
+            .into_iter()
+            .filter(|m| {
+                let skip = served.contains(m.name());
+                if skip {
+                    info!(
+                        module = m.name(),
+                        backend, "Module served by another backend"
+                    );
+                }
+                !skip
+            })
+            .collect(),
+    };
+    served.extend(modules.iter().map(|m| m.name().to_string()));
+    modules
+}
+
+fn main() {
+    let Opts {
+        common_flags,
+        config,
+        clean,
+        list_modules,
+    } = Opts::parse();
+
+    common_flags.init_logging().unwrap();
+
+    debug!("{:?}", Opts::parse());
+    info!(
+        version = env!("CARGO_PKG_VERSION"),
+        git_sha = env!("VERGEN_GIT_SHA")
+    );
+
+    if list_modules {
+        let backends: [(&str, Vec<&str>); 3] = [
+            (
+                "ledger",
+                many_ledger::registry::registry().names().collect(),
+            ),
+            (
+                "kvstore",
+                many_kvstore::registry::registry().names().collect(),
+            ),
+            ("web", many_web::registry::registry().names().collect()),
+        ];
+        for (backend, names) in backends {
+            for name in names {
+                println!("{backend}: {name}");
+            }
+        }
+        return;
+    }
+
+    // Safe unwrap.
+    // At this point the Options should contain a value.
+    let config = NodeConfig::from_file(config.unwrap()).expect("Could not load configuration.");
+    debug!("{config:?}");
+
+    if clean {
+        // Delete the persistent storage.
+        // Ignore NotFound errors.
+        match std::fs::remove_dir_all(config.storage.as_path()) {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                panic!("Error: {e}")
+            }
+        }
+    }
+    std::fs::create_dir_all(&config.storage).expect("Could not create the storage directory.");
+
+    let pem = std::fs::read_to_string(&config.pem).expect("Could not read PEM file.");
+    let key = CoseKeyIdentity::from_pem(pem).expect("Could not generate identity from PEM file.");
+    info!(address = key.address().to_string().as_str());
+
+    let many = ManyServer::simple(
+        config.name.clone(),
+        key.clone(),
+        (
+            AnonymousVerifier,
+            CoseKeyVerifier,
+            WebAuthnVerifier::new(config.allow_origin.clone()),
+        ),
+        Some(env!("CARGO_PKG_VERSION").to_string()),
+    );
+
+    {
+        let mut s = many.lock().unwrap();
+        let mut served = BTreeSet::new();
+
+        if let Some(ledger) = &config.ledger {
+            let persistent = config.ledger_storage();
+            let migrations = ledger.migrations.clone().map(|path| {
+                let config: MigrationConfig = read_json5(path);
+                config.strict()
+            });
+            let mut module_impl = match initial_state(&persistent, ledger.backend.state.as_ref()) {
+                None => LedgerModuleImpl::load(migrations, persistent, config.abci),
+                Some(state) => LedgerModuleImpl::new(
+                    many_ledger::json::InitialStateJson::read(state)
+                        .expect("Could not read state file."),
+                    migrations,
+                    persistent,
+                    config.abci,
+                ),
+            }
+            .expect("Could not load the ledger.");
+            module_impl.set_receipt_signer(Arc::new(key.clone()));
+            let module_impl = Arc::new(Mutex::new(module_impl));
+
+            let mut context = LedgerModuleContext::new(module_impl.clone());
+            context.allow_addrs = config.allow_addrs.clone();
+            let modules = modules(
+                "ledger",
+                ledger.backend.modules.as_ref(),
+                many_ledger::registry::default_modules(),
+                &mut served,
+            );
+            many_ledger::registry::registry()
+                .instantiate(&mut s, &context, &modules)
+                .expect("Could not add the ledger modules to the server.");
+            s.set_atomic_group_coordinator(
+                LedgerAtomicGroups::new(module_impl.clone())
+                    .expect("Could not list the ledger endpoints."),
+            );
+            s.set_query_state_coordinator(
+                LedgerQueryState::new(module_impl.clone())
+                    .expect("Could not list the ledger queries."),
+            );
+
+            if config.abci {
+                s.add_module(abci_backend::AbciModule::new(module_impl));
+            }
+        }
+
+        if let Some(kvstore) = &config.kvstore {
+            use many_kvstore::module::KvStoreModuleImpl;
+            use many_kvstore::registry::{self, KvStoreModuleContext};
+
+            let persistent = config.kvstore_storage();
+            let module_impl = match initial_state(&persistent, kvstore.state.as_ref()) {
+                None => KvStoreModuleImpl::load(persistent, config.abci),
+                Some(state) => KvStoreModuleImpl::new(read_json5(state), persistent, config.abci),
+            }
+            .expect("Could not load the kvstore.");
+            let module_impl = Arc::new(Mutex::new(module_impl));
+
+            let mut context = KvStoreModuleContext::new(module_impl.clone());
+            context.allow_addrs = config.allow_addrs.clone();
+            let modules = modules(
+                "kvstore",
+                kvstore.modules.as_ref(),
+                registry::default_modules(),
+                &mut served,
+            );
+            registry::registry()
+                .instantiate(&mut s, &context, &modules)
+                .expect("Could not add the kvstore modules to the server.");
+
+            if config.abci {
+                s.add_module(abci_backend::AbciModule::new(module_impl));
+            }
+        }
+
+        if let Some(web) = &config.web {
+            use many_web::module::WebModuleImpl;
+            use many_web::registry::{self, WebModuleContext};
+
+            many_web::DOMAIN.set(web.domain.clone()).unwrap();
+            let persistent = config.web_storage();
+            let module_impl = match initial_state(&persistent, web.backend.state.as_ref()) {
+                None => WebModuleImpl::load(persistent, config.abci),
+                Some(state) => WebModuleImpl::new(read_json5(state), persistent, config.abci),
+            }
+            .expect("Could not load the web store.");
+            let module_impl = Arc::new(Mutex::new(module_impl));
+
+            let mut context = WebModuleContext::new(module_impl.clone());
+            context.allow_addrs = config.allow_addrs.clone();
+            let modules = modules(
+                "web",
+                web.backend.modules.as_ref(),
+                registry::default_modules(),
+                &mut served,
+            );
+            registry::registry()
+                .instantiate(&mut s, &context, &modules)
+                .expect("Could not add the web modules to the server.");
+
+            if config.abci {
+                s.add_module(abci_backend::AbciModule::new(module_impl));
+            }
+        }
+
+        if config.abci {
+            s.set_timeout(u64::MAX);
+        }
+
+        for method in &config.disable_endpoints {
+            s.disable_endpoint(method);
+        }
+
+        if let Some(cache) = &config.cache {
+            let validator =
+                RequestCacheValidator::new(RocksDbCacheBackend::new(config.cache_storage()))
+                    .with_replay_policy(if cache.replay_responses {
+                        ReplayPolicy::ReturnCachedResponse
+                    } else {
+                        ReplayPolicy::Reject
+                    });
+            s.add_validator(match cache.ttl {
+                Some(ttl) => validator.with_ttl(std::time::Duration::from_secs(ttl)),
+                None => validator,
+            });
+        }
+    }
+
+    let mut many_server = HttpServer::new(many);
+
+    signal_hook::flag::register(signal_hook::consts::SIGTERM, many_server.term_signal())
+        .expect("Could not register signal handler");
+    signal_hook::flag::register(signal_hook::consts::SIGHUP, many_server.term_signal())
+        .expect("Could not register signal handler");
+    signal_hook::flag::register(signal_hook::consts::SIGINT, many_server.term_signal())
+        .expect("Could not register signal handler");
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(many_server.bind(config.addr)).unwrap();
+}
@@ -0,0 +1,256 @@
+use crate::{RequestMessage, ResponseMessage};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use coset::CoseSign1;
+use many_error::ManyError;
+use many_identity::{Address, Verifier};
+use minicbor::data::{Tag, Type};
+use minicbor::encode::{Error, Write};
+use minicbor::{Decode, Decoder, Encode, Encoder};
+
+/// Maximum number of messages in a single batch.
+pub const MAXIMUM_BATCH_SIZE: usize = 100;
+
+const REQUEST_BATCH_TAG: Tag = Tag::Unassigned(10003);
+const RESPONSE_BATCH_TAG: Tag = Tag::Unassigned(10004);
+const ATOMIC_REQUEST_BATCH_TAG: Tag = Tag::Unassigned(10005);
+
+/// Returns true if a payload is a batch of requests.
+pub fn is_request_batch(payload: &[u8]) -> bool {
+    matches!(
+        Decoder::new(payload).tag().ok(),
+        Some(REQUEST_BATCH_TAG | ATOMIC_REQUEST_BATCH_TAG)
+    )
+}
+
+/// A list of requests sent in a single envelope. All requests must be from
+/// the identity that signed the envelope. They are executed in order, and
+/// the server replies with a [ResponseMessageBatch] of the same length.
+#[derive(Clone, Debug, Default)]
+pub struct RequestMessageBatch {
+    pub messages: Vec<RequestMessage>,
+
+    /// Execute the requests as an atomic group; if one of them fails, the
+    /// changes of all of them are discarded and they all fail.
+    pub atomic: bool,
+}
+
+impl RequestMessageBatch {
+    /// Make this batch an atomic group.
+    pub fn into_atomic(self) -> Self {
+        Self {
+            atomic: true,
+            ..self
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        minicbor::to_vec(self).map_err(|e| format!("{e}"))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        minicbor::decode(bytes).map_err(|e| format!("{e}"))
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+impl FromIterator<RequestMessage> for RequestMessageBatch {
+    fn from_iter<T: IntoIterator<Item = RequestMessage>>(iter: T) -> Self {
+        Self {
+            messages: iter.into_iter().collect(),
+            atomic: false,
+        }
+    }
+}
+
+impl<C> Encode<C> for RequestMessageBatch {
+    fn encode<W: Write>(&self, e: &mut Encoder<W>, ctx: &mut C) -> Result<(), Error<W::Error>> {
+        let tag = if self.atomic {
+            ATOMIC_REQUEST_BATCH_TAG
+        } else {
+            REQUEST_BATCH_TAG
+        };
+        e.tag(tag)?.encode_with(&self.messages, ctx)?;
+        Ok(())
+    }
+}
+
+impl<'b, C> Decode<'b, C> for RequestMessageBatch {
+    fn decode(d: &mut Decoder<'b>, ctx: &mut C) -> Result<Self, minicbor::decode::Error> {
+        let atomic = match d.tag()? {
+            REQUEST_BATCH_TAG => false,
+            ATOMIC_REQUEST_BATCH_TAG => true,
+            _ => return Err(minicbor::decode::Error::message("Invalid tag.")),
+        };
+        if d.datatype()? != Type::Array && d.datatype()? != Type::ArrayIndef {
+            return Err(minicbor::decode::Error::message("Expected an array."));
+        }
+        Ok(Self {
+            messages: d.decode_with(ctx)?,
+            atomic,
+        })
+    }
+}
+
+/// The responses to a [RequestMessageBatch], in the same order as the requests.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ResponseMessageBatch(pub Vec<ResponseMessage>);
+
+impl ResponseMessageBatch {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        minicbor::to_vec(self).map_err(|e| format!("{e}"))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        minicbor::decode(bytes).map_err(|e| format!("{e}"))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn decode_and_verify(
+        envelope: &CoseSign1,
+        verifier: &impl Verifier,
+    ) -> Result<Self, ManyError> {
+        let address = verifier.verify_1(envelope)?;
+
+        // Shortcut everything if the address is illegal.
+        if address.is_illegal() {
+            return Err(ManyError::invalid_from_identity());
+        }
+
+        let payload = crate::compression::decode_payload(envelope)?;
+        let batch =
+            ResponseMessageBatch::from_bytes(&payload).map_err(ManyError::deserialization_error)?;
+
+        if batch.0.iter().any(|message| address != message.from) {
+            Err(ManyError::invalid_from_identity())
+        } else {
+            Ok(batch)
+        }
+    }
+
+    /// Check that all responses are for `to`.
+    pub fn validate_to(&self, to: &Address) -> Result<(), ManyError> {
+        if self.0.iter().any(|m| m.to.unwrap_or_default() != *to) {
+            Err(ManyError::invalid_to_identity())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl FromIterator<ResponseMessage> for ResponseMessageBatch {
+    fn from_iter<T: IntoIterator<Item = ResponseMessage>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<C> Encode<C> for ResponseMessageBatch {
+    fn encode<W: Write>(&self, e: &mut Encoder<W>, ctx: &mut C) -> Result<(), Error<W::Error>> {
+        e.tag(RESPONSE_BATCH_TAG)?.encode_with(&self.0, ctx)?;
+        Ok(())
+    }
+}
+
+impl<'b, C> Decode<'b, C> for ResponseMessageBatch {
+    fn decode(d: &mut Decoder<'b>, ctx: &mut C) -> Result<Self, minicbor::decode::Error> {
+        if d.tag()? != RESPONSE_BATCH_TAG {
+            return Err(minicbor::decode::Error::message("Invalid tag."));
+        }
+        if d.datatype()? != Type::Array && d.datatype()? != Type::ArrayIndef {
+            return Err(minicbor::decode::Error::message("Expected an array."));
+        }
+        Ok(Self(d.decode_with(ctx)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use many_identity::verifiers::AnonymousVerifier;
+    use many_identity::AnonymousIdentity;
+    use std::str::FromStr;
+
+    fn request(method: &str) -> RequestMessage {
+        RequestMessage {
+            method: method.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let batch = RequestMessageBatch::from_iter([request("status"), request("endpoints")]);
+        let bytes = batch.to_bytes().unwrap();
+        assert!(is_request_batch(&bytes));
+        assert!(!is_request_batch(&request("status").to_bytes().unwrap()));
+
+        let decoded = RequestMessageBatch::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert!(!decoded.atomic);
+        assert_eq!(decoded.messages[0].method, "status");
+        assert_eq!(decoded.messages[1].method, "endpoints");
+
+        let atomic = RequestMessageBatch::from_iter([request("status")]).into_atomic();
+        let bytes = atomic.to_bytes().unwrap();
+        assert!(is_request_batch(&bytes));
+        assert!(RequestMessageBatch::from_bytes(&bytes).unwrap().atomic);
+
+        let responses = ResponseMessageBatch::from_iter([
+            ResponseMessage::default(),
+            ResponseMessage::error(Address::anonymous(), Some(1), ManyError::unknown("")),
+        ]);
+        let bytes = responses.to_bytes().unwrap();
+        assert!(!is_request_batch(&bytes));
+        let decoded = ResponseMessageBatch::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded.0[0].data, responses.0[0].data);
+        assert_eq!(decoded.0[1].data, responses.0[1].data);
+        assert_eq!(decoded.0[1].id, Some(1));
+    }
+
+    #[test]
+    fn decode_envelope() {
+        let batch = RequestMessageBatch::from_iter([request("status"), request("endpoints")]);
+        let envelope =
+            crate::encode_cose_sign1_from_request_batch(batch, &AnonymousIdentity).unwrap();
+        let decoded =
+            crate::decode_request_batch_from_cose_sign1(&envelope, &AnonymousVerifier).unwrap();
+        assert_eq!(decoded.len(), 2);
+
+        // Requests from a different identity are refused.
+        let batch = RequestMessageBatch::from_iter([
+            request("status"),
+            request("status").with_from(
+                Address::from_str("mahek5lid7ek7ckhq7j77nfwgk3vkspnyppm2u467ne5mwiqys").unwrap(),
+            ),
+        ]);
+        let envelope =
+            crate::encode_cose_sign1_from_request_batch(batch, &AnonymousIdentity).unwrap();
+        assert!(
+            crate::decode_request_batch_from_cose_sign1(&envelope, &AnonymousVerifier).is_err()
+        );
+
+        // Empty batches are refused.
+        let envelope =
+            crate::encode_cose_sign1_from_request_batch(Default::default(), &AnonymousIdentity)
+                .unwrap();
+        assert!(
+            crate::decode_request_batch_from_cose_sign1(&envelope, &AnonymousVerifier).is_err()
+        );
+    }
+}
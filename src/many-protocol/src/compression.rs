@@ -0,0 +1,342 @@
+//! Compression of envelope payloads.
+//!
+//! A compressed payload is declared by a `content-encoding` protected header,
+//! so the encoding is covered by the signature. The signature is verified on
+//! the compressed bytes, and the payload is decompressed before it is decoded.
+//!
+//! Senders advertise the encodings they can decode with an `accept-encoding`
+//! protected header on their requests. A response is only compressed if it is
+//! larger than [COMPRESSION_THRESHOLD] and the request accepted an encoding.
+//!
+//! Clients can compress their requests the same way, with an encoding they
+//! know the server supports. Servers should bound the decompressed size of
+//! requests with [decode_payload_within] and decode the bytes it returns.
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+use coset::cbor::value::Value;
+use coset::{CoseSign1, Header, HeaderBuilder, Label};
+use many_error::ManyError;
+
+/// Protected header declaring the encoding of the payload.
+pub const CONTENT_ENCODING: &str = "content-encoding";
+
+/// Protected header listing the encodings a sender can decode.
+pub const ACCEPT_ENCODING: &str = "accept-encoding";
+
+/// Payloads smaller than this are never compressed; the savings would not be
+/// worth the CPU.
+pub const COMPRESSION_THRESHOLD: usize = 16 * 1024;
+
+/// Maximum size of a decompressed payload, to protect against decompression
+/// bombs.
+pub const MAXIMUM_DECOMPRESSED_SIZE: usize = 64 * 1024 * 1024;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum ContentEncoding {
+    Zstd,
+    Deflate,
+}
+
+impl ContentEncoding {
+    /// The encodings this build can compress and decompress, in order of
+    /// preference.
+    pub fn supported() -> &'static [ContentEncoding] {
+        #[cfg(feature = "compression")]
+        {
+            &[Self::Zstd, Self::Deflate]
+        }
+        #[cfg(not(feature = "compression"))]
+        {
+            &[]
+        }
+    }
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Zstd => "zstd",
+            Self::Deflate => "deflate",
+        }
+    }
+
+    #[cfg(feature = "compression")]
+    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>, ManyError> {
+        use std::io::Write;
+
+        match self {
+            Self::Zstd => zstd::stream::encode_all(data, 0).map_err(ManyError::unknown),
+            Self::Deflate => {
+                let mut encoder =
+                    flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data).map_err(ManyError::unknown)?;
+                encoder.finish().map_err(ManyError::unknown)
+            }
+        }
+    }
+
+    #[cfg(not(feature = "compression"))]
+    pub fn compress(&self, _data: &[u8]) -> Result<Vec<u8>, ManyError> {
+        Err(ManyError::unsupported_content_encoding(self))
+    }
+
+    /// Decompresses `data`, failing if the result would be larger than
+    /// `limit` bytes.
+    #[cfg(feature = "compression")]
+    pub fn decompress(&self, data: &[u8], limit: usize) -> Result<Vec<u8>, ManyError> {
+        use std::io::Read;
+
+        let reader: Box<dyn Read + '_> = match self {
+            Self::Zstd => Box::new(
+                zstd::stream::Decoder::new(data).map_err(ManyError::deserialization_error)?,
+            ),
+            Self::Deflate => Box::new(flate2::read::DeflateDecoder::new(data)),
+        };
+
+        let mut out = Vec::new();
+        reader
+            .take(limit as u64 + 1)
+            .read_to_end(&mut out)
+            .map_err(ManyError::deserialization_error)?;
+        if out.len() > limit {
+            Err(ManyError::message_too_long(limit))
+        } else {
+            Ok(out)
+        }
+    }
+
+    #[cfg(not(feature = "compression"))]
+    pub fn decompress(&self, _data: &[u8], _limit: usize) -> Result<Vec<u8>, ManyError> {
+        Err(ManyError::unsupported_content_encoding(self))
+    }
+}
+
+impl Display for ContentEncoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ContentEncoding {
+    type Err = ManyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "zstd" => Ok(Self::Zstd),
+            "deflate" => Ok(Self::Deflate),
+            x => Err(ManyError::unsupported_content_encoding(x)),
+        }
+    }
+}
+
+fn header_value<'a>(header: &'a Header, name: &str) -> Option<&'a Value> {
+    header
+        .rest
+        .iter()
+        .find(|(k, _)| k == &Label::Text(name.to_string()))
+        .map(|(_, v)| v)
+}
+
+/// The encoding of an envelope's payload, or [None] if it is not compressed.
+pub fn content_encoding(envelope: &CoseSign1) -> Result<Option<ContentEncoding>, ManyError> {
+    match header_value(&envelope.protected.header, CONTENT_ENCODING) {
+        None => Ok(None),
+        Some(Value::Text(encoding)) => encoding.parse().map(Some),
+        Some(_) => Err(ManyError::deserialization_error(
+            "Invalid content-encoding header.",
+        )),
+    }
+}
+
+/// The encodings the sender of an envelope accepts, that this build supports.
+/// Unknown encodings are ignored.
+pub fn accepted_encodings(envelope: &CoseSign1) -> Vec<ContentEncoding> {
+    match header_value(&envelope.protected.header, ACCEPT_ENCODING) {
+        Some(Value::Array(encodings)) => encodings
+            .iter()
+            .filter_map(|e| e.as_text()?.parse().ok())
+            .filter(|e| ContentEncoding::supported().contains(e))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The payload of an envelope, decompressed if it has a content encoding.
+/// This does not verify the envelope.
+pub fn decode_payload(envelope: &CoseSign1) -> Result<Cow<'_, [u8]>, ManyError> {
+    let payload = envelope
+        .payload
+        .as_ref()
+        .ok_or_else(ManyError::empty_envelope)?;
+    match content_encoding(envelope)? {
+        None => Ok(Cow::Borrowed(payload)),
+        Some(encoding) => encoding
+            .decompress(payload, MAXIMUM_DECOMPRESSED_SIZE)
+            .map(Cow::Owned),
+    }
+}
+
+/// The payload of an envelope like [decode_payload], refusing it if it is
+/// larger than `limit` bytes once decompressed.
+pub fn decode_payload_within(
+    envelope: &CoseSign1,
+    limit: usize,
+) -> Result<Cow<'_, [u8]>, ManyError> {
+    let payload = envelope
+        .payload
+        .as_ref()
+        .ok_or_else(ManyError::empty_envelope)?;
+    match content_encoding(envelope)? {
+        None if payload.len() > limit => Err(ManyError::message_too_long(limit)),
+        None => Ok(Cow::Borrowed(payload)),
+        Some(encoding) => encoding.decompress(payload, limit).map(Cow::Owned),
+    }
+}
+
+/// Compresses a payload with the first of the `accepted` encodings, if it is
+/// large enough and compressing it actually makes it smaller.
+pub(crate) fn encode_payload(
+    payload: Vec<u8>,
+    accepted: &[ContentEncoding],
+) -> Result<(Vec<u8>, Option<ContentEncoding>), ManyError> {
+    let encoding = match accepted.first() {
+        Some(encoding) if payload.len() >= COMPRESSION_THRESHOLD => *encoding,
+        _ => return Ok((payload, None)),
+    };
+
+    let compressed = encoding.compress(&payload)?;
+    if compressed.len() < payload.len() {
+        Ok((compressed, Some(encoding)))
+    } else {
+        Ok((payload, None))
+    }
+}
+
+/// Adds a `content-encoding` header to a protected header being built.
+pub(crate) fn with_content_encoding(
+    builder: HeaderBuilder,
+    encoding: Option<ContentEncoding>,
+) -> HeaderBuilder {
+    match encoding {
+        Some(encoding) => builder.text_value(
+            CONTENT_ENCODING.to_string(),
+            Value::Text(encoding.to_string()),
+        ),
+        None => builder,
+    }
+}
+
+/// Adds an `accept-encoding` header listing the encodings supported by this
+/// build, if any.
+pub(crate) fn with_accept_encoding(builder: HeaderBuilder) -> HeaderBuilder {
+    let supported = ContentEncoding::supported();
+    if supported.is_empty() {
+        builder
+    } else {
+        builder.text_value(
+            ACCEPT_ENCODING.to_string(),
+            Value::Array(
+                supported
+                    .iter()
+                    .map(|e| Value::Text(String::from(e.as_str())))
+                    .collect(),
+            ),
+        )
+    }
+}
+
+#[cfg(all(test, feature = "compression"))]
+mod tests {
+    use super::*;
+    use coset::CoseSign1Builder;
+
+    fn envelope(payload: Vec<u8>, accepted: &[ContentEncoding]) -> CoseSign1 {
+        let (payload, encoding) = encode_payload(payload, accepted).unwrap();
+        CoseSign1Builder::new()
+            .protected(with_content_encoding(HeaderBuilder::new(), encoding).build())
+            .payload(payload)
+            .build()
+    }
+
+    #[test]
+    fn roundtrip() {
+        let payload = vec![42u8; COMPRESSION_THRESHOLD * 4];
+        for encoding in ContentEncoding::supported() {
+            let envelope = envelope(payload.clone(), &[*encoding]);
+            assert_eq!(content_encoding(&envelope).unwrap(), Some(*encoding));
+            assert!(envelope.payload.as_ref().unwrap().len() < payload.len());
+            assert_eq!(decode_payload(&envelope).unwrap().as_ref(), payload);
+        }
+    }
+
+    #[test]
+    fn small_payloads_are_not_compressed() {
+        let payload = vec![42u8; 100];
+        let envelope = envelope(payload.clone(), ContentEncoding::supported());
+        assert_eq!(content_encoding(&envelope).unwrap(), None);
+        assert_eq!(envelope.payload.unwrap(), payload);
+    }
+
+    #[test]
+    fn not_accepted() {
+        let payload = vec![42u8; COMPRESSION_THRESHOLD * 4];
+        let envelope = envelope(payload.clone(), &[]);
+        assert_eq!(content_encoding(&envelope).unwrap(), None);
+        assert_eq!(envelope.payload.unwrap(), payload);
+    }
+
+    #[test]
+    fn decompression_limit() {
+        for encoding in ContentEncoding::supported() {
+            let compressed = encoding.compress(&[0u8; 1000]).unwrap();
+            assert!(encoding.decompress(&compressed, 1000).is_ok());
+            assert!(encoding.decompress(&compressed, 999).is_err());
+        }
+    }
+
+    #[test]
+    fn payload_size() {
+        let payload = vec![42u8; COMPRESSION_THRESHOLD * 4];
+        for encoding in ContentEncoding::supported() {
+            let envelope = envelope(payload.clone(), &[*encoding]);
+            assert_eq!(
+                decode_payload_within(&envelope, payload.len()).unwrap(),
+                payload.as_slice()
+            );
+            assert!(decode_payload_within(&envelope, payload.len() - 1).is_err());
+        }
+
+        let envelope = envelope(payload.clone(), &[]);
+        assert_eq!(
+            decode_payload_within(&envelope, payload.len()).unwrap(),
+            payload.as_slice()
+        );
+        assert!(decode_payload_within(&envelope, payload.len() - 1).is_err());
+    }
+
+    #[test]
+    fn unknown_encoding() {
+        let envelope = CoseSign1Builder::new()
+            .protected(
+                HeaderBuilder::new()
+                    .text_value(CONTENT_ENCODING.to_string(), Value::Text("br".to_string()))
+                    .build(),
+            )
+            .payload(vec![1, 2, 3])
+            .build();
+        assert!(decode_payload(&envelope).is_err());
+    }
+
+    #[test]
+    fn accepted() {
+        let envelope = CoseSign1Builder::new()
+            .protected(
+                with_accept_encoding(HeaderBuilder::new())
+                    .text_value("other".to_string(), Value::Bool(true))
+                    .build(),
+            )
+            .build();
+        assert_eq!(accepted_encodings(&envelope), ContentEncoding::supported());
+    }
+}
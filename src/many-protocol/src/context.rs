@@ -1,14 +1,30 @@
 use {
+    crate::delegation::{DelegationChain, DELEGATION},
     crate::RequestMessage,
     async_channel::Sender,
     many_error::ManyError,
+    many_identity::Address,
     many_types::{attributes::Attribute, cbor::CborAny, proof::Proof, ProofOperation, PROOF},
+    std::cell::Cell,
+    std::collections::BTreeMap,
+    std::future::Future,
+    std::pin::Pin,
+    std::task::Poll,
+    tracing::Span,
 };
 
+pub use tracing::Instrument;
+
+/// The context of a request being executed by a module.
+///
+/// Every context has a tracing span with the request ID, sender and method,
+/// which the module execution runs in. Backends can use it as the parent of
+/// their own spans to tie them to the original envelope.
 #[derive(Clone, Debug)]
 pub struct Context {
     request: RequestMessage,
     transmitter: Sender<ProofResult>,
+    span: Span,
 }
 
 pub enum ProofResult {
@@ -31,14 +47,65 @@ impl IntoIterator for ProofResult {
     }
 }
 
+thread_local! {
+    static BLOCK_HEIGHT: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// A future executing requests at a block height. See [at_block_height].
+pub struct AtBlockHeight<F> {
+    future: Pin<Box<F>>,
+    height: Option<u64>,
+}
+
+impl<F: Future> Future for AtBlockHeight<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let outer = BLOCK_HEIGHT.with(|h| h.replace(this.height));
+        let poll = this.future.as_mut().poll(cx);
+        BLOCK_HEIGHT.with(|h| h.set(outer));
+        poll
+    }
+}
+
+/// Execute the requests of `future` at a block height. Every context created
+/// while polling it records `height` in its span, whichever endpoint it is
+/// for.
+pub fn at_block_height<F: Future>(height: Option<u64>, future: F) -> AtBlockHeight<F> {
+    AtBlockHeight {
+        future: Box::pin(future),
+        height,
+    }
+}
+
 impl Context {
     pub fn new(request: RequestMessage, transmitter: Sender<ProofResult>) -> Self {
+        let span = tracing::info_span!(
+            "request",
+            id = ?request.id,
+            from = %request.from(),
+            method = request.method.as_str(),
+            height = BLOCK_HEIGHT.with(Cell::get),
+        );
         Self {
             request,
             transmitter,
+            span,
         }
     }
 
+    /// The tracing span of this request.
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// Record the block height the request is executed at in its span, if it
+    /// is not executed within [at_block_height].
+    pub fn record_block_height(&self, height: u64) {
+        self.span.record("height", height);
+    }
+
     pub fn prove<
         P: IntoIterator<Item = ProofOperation>,
         Prover: FnOnce() -> Result<P, ManyError>,
@@ -66,6 +133,61 @@ impl Context {
     }
 }
 
+std::thread_local! {
+    static INTENT_HASH: std::cell::RefCell<Option<Vec<u8>>> = Default::default();
+    static DELEGATED_MAX_AMOUNTS: std::cell::RefCell<Option<BTreeMap<Address, u64>>> = Default::default();
+}
+
+/// Run `f` with the intent hash of `request` as the current intent, so the
+/// events it logs can refer to the request. See [RequestMessage::intent_hash].
+/// The maximum amounts of its delegation chain are also made available, see
+/// [current_delegated_max_amount].
+///
+/// The intent is thread local; `f` must not yield to an executor.
+pub fn with_intent<R>(request: &RequestMessage, f: impl FnOnce() -> R) -> R {
+    let previous = INTENT_HASH.with(|h| h.replace(request.intent_hash().ok()));
+    let previous_max = DELEGATED_MAX_AMOUNTS.with(|m| m.replace(delegated_max_amounts(request)));
+    let result = f();
+    INTENT_HASH.with(|h| h.replace(previous));
+    DELEGATED_MAX_AMOUNTS.with(|m| m.replace(previous_max));
+    result
+}
+
+/// Run `f` without a current intent, e.g. for work done on behalf of the
+/// server rather than of a request.
+pub fn without_intent<R>(f: impl FnOnce() -> R) -> R {
+    let previous = INTENT_HASH.with(|h| h.take());
+    let previous_max = DELEGATED_MAX_AMOUNTS.with(|m| m.take());
+    let result = f();
+    INTENT_HASH.with(|h| h.replace(previous));
+    DELEGATED_MAX_AMOUNTS.with(|m| m.replace(previous_max));
+    result
+}
+
+fn delegated_max_amounts(request: &RequestMessage) -> Option<BTreeMap<Address, u64>> {
+    let attr = request.attributes.get_attribute(DELEGATION.id)?;
+    // Requests with an invalid chain are refused when decoded, but should
+    // not be able to send anything regardless.
+    DelegationChain::try_from(attr.clone())
+        .and_then(|chain| chain.max_amounts())
+        .unwrap_or_else(|_| Some(BTreeMap::new()))
+}
+
+/// The maximum amount of `symbol` the request being executed can send, if it
+/// is sent with a delegation chain limiting it.
+pub fn current_delegated_max_amount(symbol: &Address) -> Option<u64> {
+    DELEGATED_MAX_AMOUNTS.with(|m| {
+        m.borrow()
+            .as_ref()
+            .map(|max_amounts| max_amounts.get(symbol).copied().unwrap_or(0))
+    })
+}
+
+/// The intent hash of the request being executed, if any.
+pub fn current_intent_hash() -> Option<Vec<u8>> {
+    INTENT_HASH.with(|h| h.borrow().clone())
+}
+
 impl AsRef<Context> for Context {
     fn as_ref(&self) -> &Self {
         self
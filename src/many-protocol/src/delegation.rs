@@ -0,0 +1,299 @@
+use crate::RequestMessage;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use coset::{CoseSign1, TaggedCborSerializable};
+use many_error::ManyError;
+use many_identity::delegation::Delegation;
+use many_identity::{Address, Verifier};
+use many_types::attributes::{Attribute, AttributeSet, TryFromAttributeSet};
+use many_types::cbor::CborAny;
+
+/// Request attribute allowing the signer of a request to send it on behalf of
+/// the `from` identity. Its arguments are the tagged COSE envelopes of the
+/// delegation certificates, from the one signed by `from` to the one
+/// delegating to the signer.
+///
+/// Servers refuse the request unless every certificate allows its endpoint
+/// and had not expired at the request's timestamp. Certificates limiting the
+/// amounts sent are only accepted on [AMOUNT_LIMITED_ENDPOINTS].
+pub const DELEGATION: Attribute = Attribute::id(6);
+
+/// The endpoints enforcing the maximum amounts of delegation certificates.
+pub const AMOUNT_LIMITED_ENDPOINTS: &[&str] = &["ledger.send"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DelegationChain(pub Vec<CoseSign1>);
+
+impl DelegationChain {
+    /// The certificates of the chain, without verifying them.
+    pub fn delegations(&self) -> Result<Vec<Delegation>, ManyError> {
+        self.0.iter().map(Delegation::from_envelope).collect()
+    }
+
+    /// The maximum amounts per symbol allowed by all the certificates of the
+    /// chain, if any limits them. Symbols missing from the result cannot be sent.
+    pub fn max_amounts(&self) -> Result<Option<BTreeMap<Address, u64>>, ManyError> {
+        Ok(self
+            .delegations()?
+            .into_iter()
+            .filter_map(|d| d.max_amounts)
+            .reduce(|limits, max_amounts| {
+                limits
+                    .into_iter()
+                    .filter_map(|(symbol, amount)| {
+                        max_amounts
+                            .get(&symbol)
+                            .map(|other| (symbol, amount.min(*other)))
+                    })
+                    .collect()
+            }))
+    }
+}
+
+impl From<DelegationChain> for Attribute {
+    fn from(chain: DelegationChain) -> Attribute {
+        Attribute::new(
+            DELEGATION.id,
+            chain
+                .0
+                .into_iter()
+                .filter_map(|envelope| envelope.to_tagged_vec().ok())
+                .map(CborAny::Bytes)
+                .collect(),
+        )
+    }
+}
+
+impl TryFrom<Attribute> for DelegationChain {
+    type Error = ManyError;
+
+    fn try_from(value: Attribute) -> Result<Self, Self::Error> {
+        if value.id != DELEGATION.id {
+            return Err(ManyError::invalid_attribute_id(value.id));
+        }
+
+        value
+            .into_arguments()
+            .into_iter()
+            .map(|argument| match argument {
+                CborAny::Bytes(bytes) => CoseSign1::from_tagged_slice(&bytes)
+                    .map_err(|_| ManyError::invalid_attribute_arguments()),
+                _ => Err(ManyError::invalid_attribute_arguments()),
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl TryFromAttributeSet for DelegationChain {
+    fn try_from_set(set: &AttributeSet) -> Result<Self, ManyError> {
+        match set.get_attribute(DELEGATION.id) {
+            Some(attr) => DelegationChain::try_from(attr.clone()),
+            None => Err(ManyError::attribute_not_found(DELEGATION.id.to_string())),
+        }
+    }
+}
+
+/// Verify the delegation chain of a request signed by `signer`, and return the
+/// identity the request is sent on behalf of. Requests without a chain are
+/// sent on behalf of their signer.
+pub(crate) fn verify_request(
+    message: &RequestMessage,
+    signer: Address,
+    verifier: &impl Verifier,
+) -> Result<Address, ManyError> {
+    let chain = match message.attributes.get_attribute(DELEGATION.id) {
+        Some(attr) => DelegationChain::try_from(attr.clone())?,
+        None => return Ok(signer),
+    };
+    let delegations = verifier.verify_delegation(&chain.0, &signer)?;
+
+    let timestamp = message
+        .timestamp
+        .ok_or_else(|| ManyError::required_field_missing("timestamp"))?;
+    for delegation in &delegations {
+        if delegation.expiration < timestamp.secs() {
+            return Err(ManyError::invalid_delegation("certificate expired"));
+        }
+        if !delegation.allows_endpoint(&message.method) {
+            return Err(ManyError::invalid_delegation(format!(
+                "endpoint '{}' not allowed",
+                message.method
+            )));
+        }
+        if delegation.max_amounts.is_some()
+            && !AMOUNT_LIMITED_ENDPOINTS.contains(&message.method.as_str())
+        {
+            return Err(ManyError::invalid_delegation(format!(
+                "endpoint '{}' does not enforce maximum amounts",
+                message.method
+            )));
+        }
+    }
+
+    delegations
+        .first()
+        .map(|root| root.from)
+        .ok_or_else(|| ManyError::invalid_delegation("empty chain"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RequestMessageBuilder;
+    use crate::{decode_request_from_cose_sign1, encode_cose_sign1_from_request};
+    use coset::CoseKey;
+    use many_identity::delegation::DelegatedVerifier;
+    use many_identity::Identity;
+    use many_types::Timestamp;
+
+    fn identity(seed: u8) -> Address {
+        let mut bytes = [0u8; 29];
+        bytes[0] = 1;
+        bytes[28] = seed;
+        Address::from_bytes(&bytes).unwrap()
+    }
+
+    /// Signs by setting the key ID of envelopes to its address.
+    struct KeyIdIdentity(Address);
+
+    impl Identity for KeyIdIdentity {
+        fn address(&self) -> Address {
+            self.0
+        }
+
+        fn public_key(&self) -> Option<CoseKey> {
+            None
+        }
+
+        fn sign_1(&self, mut envelope: CoseSign1) -> Result<CoseSign1, ManyError> {
+            envelope.protected.header.key_id = self.0.to_vec();
+            Ok(envelope)
+        }
+    }
+
+    /// Resolves the key ID of envelopes, without verifying any signature.
+    struct KeyIdVerifier;
+
+    impl Verifier for KeyIdVerifier {
+        fn verify_1(&self, envelope: &CoseSign1) -> Result<Address, ManyError> {
+            Address::from_bytes(&envelope.protected.header.key_id)
+        }
+    }
+
+    fn chain(delegations: &[Delegation]) -> DelegationChain {
+        DelegationChain(
+            delegations
+                .iter()
+                .map(|d| d.sign(&KeyIdIdentity(d.from)).unwrap())
+                .collect(),
+        )
+    }
+
+    fn envelope(method: &str, chain: DelegationChain) -> CoseSign1 {
+        let message = RequestMessageBuilder::default()
+            .from(identity(1))
+            .method(method.to_string())
+            .timestamp(Timestamp::new(1_000).unwrap())
+            .build()
+            .unwrap()
+            .with_attribute(chain.into());
+        encode_cose_sign1_from_request(message, &KeyIdIdentity(identity(3))).unwrap()
+    }
+
+    #[test]
+    fn attribute() {
+        let chain = chain(&[Delegation::new(identity(1), identity(2), 1_000)]);
+        let attr: Attribute = chain.clone().into();
+        assert_eq!(attr.id, DELEGATION.id);
+        // Decoded headers keep their original bytes, so compare the encodings.
+        let decoded = DelegationChain::try_from(attr.clone()).unwrap();
+        assert_eq!(Attribute::from(decoded), attr);
+
+        let invalid = DELEGATION.with_argument(CborAny::Bytes(vec![1, 2, 3]));
+        assert!(DelegationChain::try_from(invalid).is_err());
+        assert!(AttributeSet::new().get::<DelegationChain>().is_err());
+    }
+
+    #[test]
+    fn max_amounts() {
+        let limited = chain(&[
+            Delegation::new(identity(1), identity(2), 1_000)
+                .with_max_amount(identity(9), 100)
+                .with_max_amount(identity(10), 5),
+            Delegation::new(identity(2), identity(3), 1_000),
+            Delegation::new(identity(3), identity(4), 1_000)
+                .with_max_amount(identity(9), 10)
+                .with_max_amount(identity(11), 5),
+        ]);
+        // Only the symbols allowed by every limiting certificate can be sent.
+        assert_eq!(
+            limited.max_amounts(),
+            Ok(Some(BTreeMap::from([(identity(9), 10)])))
+        );
+
+        let unlimited = chain(&[Delegation::new(identity(1), identity(2), 1_000)]);
+        assert_eq!(unlimited.max_amounts(), Ok(None));
+    }
+
+    #[test]
+    fn decode_amount_limited() {
+        let verifier = DelegatedVerifier::new(KeyIdVerifier);
+        let chain = chain(&[
+            Delegation::new(identity(1), identity(2), 1_000).with_max_amount(identity(9), 10),
+            Delegation::new(identity(2), identity(3), 2_000),
+        ]);
+
+        assert!(
+            decode_request_from_cose_sign1(&envelope("ledger.send", chain.clone()), &verifier)
+                .is_ok()
+        );
+        // Other endpoints could move funds without checking the limit.
+        assert_eq!(
+            decode_request_from_cose_sign1(&envelope("tokens.mint", chain), &verifier).unwrap_err(),
+            ManyError::invalid_delegation(
+                "endpoint 'tokens.mint' does not enforce maximum amounts"
+            )
+        );
+    }
+
+    #[test]
+    fn decode_delegated_request() {
+        let verifier = DelegatedVerifier::new(KeyIdVerifier);
+        let chain = chain(&[
+            Delegation::new(identity(1), identity(2), 1_000).with_endpoints(["ledger.send"]),
+            Delegation::new(identity(2), identity(3), 2_000),
+        ]);
+
+        let request =
+            decode_request_from_cose_sign1(&envelope("ledger.send", chain.clone()), &verifier)
+                .unwrap();
+        assert_eq!(request.from, Some(identity(1)));
+
+        // Endpoint not allowed by the first certificate.
+        assert!(
+            decode_request_from_cose_sign1(&envelope("ledger.burn", chain.clone()), &verifier)
+                .is_err()
+        );
+        // Verifiers refuse delegation unless they support it.
+        assert!(
+            decode_request_from_cose_sign1(&envelope("ledger.send", chain), &KeyIdVerifier)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn decode_expired() {
+        let verifier = DelegatedVerifier::new(KeyIdVerifier);
+        let chain = chain(&[
+            Delegation::new(identity(1), identity(2), 999),
+            Delegation::new(identity(2), identity(3), 2_000),
+        ]);
+        assert_eq!(
+            decode_request_from_cose_sign1(&envelope("ledger.send", chain), &verifier).unwrap_err(),
+            ManyError::invalid_delegation("certificate expired")
+        );
+    }
+}
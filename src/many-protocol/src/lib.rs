@@ -1,29 +1,71 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::vec::Vec;
 use coset::CoseSign1;
-use coset::CoseSign1Builder;
+use coset::{CoseSign1Builder, HeaderBuilder};
 use many_error::ManyError;
 use many_identity::{Address, Identity, Verifier};
+use many_types::Timestamp;
 
+pub mod batch;
+pub mod compression;
+#[cfg(feature = "std")]
 pub mod context;
+pub mod delegation;
 pub mod request;
 pub mod response;
 
+pub use batch::{RequestMessageBatch, ResponseMessageBatch};
+pub use compression::ContentEncoding;
 pub use request::{RequestMessage, RequestMessageBuilder};
 pub use response::{ResponseMessage, ResponseMessageBuilder};
 
+#[cfg(feature = "std")]
 pub type ManyUrl = url::Url;
 
+/// The timestamp to encode in a message, which is the current time if the
+/// message does not have one. Without `std` there is no clock, so messages
+/// must have a timestamp.
+fn message_timestamp<E>(
+    timestamp: Option<Timestamp>,
+) -> Result<Timestamp, minicbor::encode::Error<E>> {
+    #[cfg(feature = "std")]
+    {
+        Ok(timestamp.unwrap_or_else(Timestamp::now))
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        timestamp.ok_or_else(|| minicbor::encode::Error::message("Missing message timestamp."))
+    }
+}
+
 pub fn decode_request_from_cose_sign1(
     envelope: &CoseSign1,
     verifier: &impl Verifier,
 ) -> Result<RequestMessage, ManyError> {
-    let from_id = verifier.verify_1(envelope)?;
+    let payload = compression::decode_payload(envelope)?;
+    decode_request_from_payload(envelope, &payload, verifier)
+}
 
-    if from_id.is_illegal() {
+/// Like [decode_request_from_cose_sign1], with the `payload` of the envelope
+/// already decompressed (see [compression::decode_payload_within]).
+pub fn decode_request_from_payload(
+    envelope: &CoseSign1,
+    payload: &[u8],
+    verifier: &impl Verifier,
+) -> Result<RequestMessage, ManyError> {
+    let signer = verifier.verify_1(envelope)?;
+
+    if signer.is_illegal() {
         return Err(ManyError::invalid_from_identity());
     }
 
-    // Check the `from` field.
-    let message: RequestMessage = envelope.try_into()?;
+    // Check the `from` field, which can be a delegator of the signer.
+    let message = RequestMessage::from_bytes(payload).map_err(ManyError::deserialization_error)?;
+    let from_id = delegation::verify_request(&message, signer, verifier)?;
     let message_from = message.from.unwrap_or_default();
     if !from_id.matches(&message_from) || message_from.is_illegal() {
         Err(ManyError::invalid_from_identity())
@@ -32,6 +74,51 @@ pub fn decode_request_from_cose_sign1(
     }
 }
 
+/// Returns true if the envelope carries a [RequestMessageBatch].
+pub fn is_request_batch_envelope(envelope: &CoseSign1) -> bool {
+    compression::decode_payload(envelope).map_or(false, |p| batch::is_request_batch(&p))
+}
+
+pub fn decode_request_batch_from_cose_sign1(
+    envelope: &CoseSign1,
+    verifier: &impl Verifier,
+) -> Result<RequestMessageBatch, ManyError> {
+    let payload = compression::decode_payload(envelope)?;
+    decode_request_batch_from_payload(envelope, &payload, verifier)
+}
+
+/// Like [decode_request_batch_from_cose_sign1], with the `payload` of the
+/// envelope already decompressed (see [compression::decode_payload_within]).
+pub fn decode_request_batch_from_payload(
+    envelope: &CoseSign1,
+    payload: &[u8],
+    verifier: &impl Verifier,
+) -> Result<RequestMessageBatch, ManyError> {
+    let from_id = verifier.verify_1(envelope)?;
+
+    if from_id.is_illegal() {
+        return Err(ManyError::invalid_from_identity());
+    }
+
+    let batch =
+        RequestMessageBatch::from_bytes(payload).map_err(ManyError::deserialization_error)?;
+    if batch.is_empty() || batch.len() > batch::MAXIMUM_BATCH_SIZE {
+        return Err(ManyError::deserialization_error(format!(
+            "Batches must contain between 1 and {} messages.",
+            batch::MAXIMUM_BATCH_SIZE
+        )));
+    }
+
+    // Check the `from` field of every message.
+    for message in &batch.messages {
+        let message_from = message.from.unwrap_or_default();
+        if !from_id.matches(&message_from) || message_from.is_illegal() {
+            return Err(ManyError::invalid_from_identity());
+        }
+    }
+    Ok(batch)
+}
+
 pub fn decode_response_from_cose_sign1(
     envelope: &CoseSign1,
     to: Option<Address>,
@@ -49,11 +136,19 @@ pub fn decode_response_from_cose_sign1(
     Ok(message)
 }
 
+/// Sign a payload, compressing it with the first of the `accepted` encodings
+/// if it is large enough. See [compression].
 fn encode_cose_sign1_from_payload(
     payload: Vec<u8>,
     identity: &impl Identity,
+    accepted: &[ContentEncoding],
+    protected: HeaderBuilder,
 ) -> Result<CoseSign1, ManyError> {
-    let sign1 = CoseSign1Builder::default().payload(payload).build();
+    let (payload, encoding) = compression::encode_payload(payload, accepted)?;
+    let sign1 = CoseSign1Builder::default()
+        .protected(compression::with_content_encoding(protected, encoding).build())
+        .payload(payload)
+        .build();
     identity.sign_1(sign1)
 }
 
@@ -61,18 +156,111 @@ pub fn encode_cose_sign1_from_response(
     response: ResponseMessage,
     identity: &impl Identity,
 ) -> Result<CoseSign1, ManyError> {
-    encode_cose_sign1_from_payload(response.to_bytes().unwrap(), identity)
+    encode_cose_sign1_from_payload(
+        response.to_bytes().unwrap(),
+        identity,
+        &[],
+        HeaderBuilder::new(),
+    )
+}
+
+/// Like [encode_cose_sign1_from_response], but compresses a large response
+/// with an encoding accepted by the sender of the `request` envelope.
+pub fn encode_cose_sign1_from_response_for(
+    response: ResponseMessage,
+    identity: &impl Identity,
+    request: &CoseSign1,
+) -> Result<CoseSign1, ManyError> {
+    encode_cose_sign1_from_payload(
+        response.to_bytes().unwrap(),
+        identity,
+        &compression::accepted_encodings(request),
+        HeaderBuilder::new(),
+    )
 }
 
 pub fn encode_cose_sign1_from_request(
     request: RequestMessage,
     identity: &impl Identity,
+) -> Result<CoseSign1, ManyError> {
+    encode_cose_sign1_from_request_compressed(request, identity, &[])
+}
+
+/// Like [encode_cose_sign1_from_request], but compresses a large request with
+/// the first of the `encodings`, which the server must support.
+pub fn encode_cose_sign1_from_request_compressed(
+    request: RequestMessage,
+    identity: &impl Identity,
+    encodings: &[ContentEncoding],
 ) -> Result<CoseSign1, ManyError> {
     // We don't allow illegal from fields in requests.
     if request.from == Some(Address::ILLEGAL) {
         Err(ManyError::invalid_from_identity())
     } else {
-        encode_cose_sign1_from_payload(request.to_bytes().unwrap(), identity)
+        encode_cose_sign1_from_payload(
+            request.to_bytes().unwrap(),
+            identity,
+            encodings,
+            compression::with_accept_encoding(HeaderBuilder::new()),
+        )
+    }
+}
+
+pub fn encode_cose_sign1_from_response_batch(
+    responses: ResponseMessageBatch,
+    identity: &impl Identity,
+) -> Result<CoseSign1, ManyError> {
+    encode_cose_sign1_from_payload(
+        responses.to_bytes().unwrap(),
+        identity,
+        &[],
+        HeaderBuilder::new(),
+    )
+}
+
+/// Like [encode_cose_sign1_from_response_batch], but compresses large
+/// responses with an encoding accepted by the sender of the `request` envelope.
+pub fn encode_cose_sign1_from_response_batch_for(
+    responses: ResponseMessageBatch,
+    identity: &impl Identity,
+    request: &CoseSign1,
+) -> Result<CoseSign1, ManyError> {
+    encode_cose_sign1_from_payload(
+        responses.to_bytes().unwrap(),
+        identity,
+        &compression::accepted_encodings(request),
+        HeaderBuilder::new(),
+    )
+}
+
+pub fn encode_cose_sign1_from_request_batch(
+    requests: RequestMessageBatch,
+    identity: &impl Identity,
+) -> Result<CoseSign1, ManyError> {
+    encode_cose_sign1_from_request_batch_compressed(requests, identity, &[])
+}
+
+/// Like [encode_cose_sign1_from_request_batch], but compresses a large batch
+/// with the first of the `encodings`, which the server must support.
+pub fn encode_cose_sign1_from_request_batch_compressed(
+    requests: RequestMessageBatch,
+    identity: &impl Identity,
+    encodings: &[ContentEncoding],
+) -> Result<CoseSign1, ManyError> {
+    // We don't allow illegal from fields in requests.
+    if requests
+        .messages
+        .iter()
+        .any(|r| r.from == Some(Address::ILLEGAL))
+    {
+        Err(ManyError::invalid_from_identity())
+    } else {
+        encode_cose_sign1_from_payload(
+            requests.to_bytes().unwrap(),
+            identity,
+            encodings,
+            compression::with_accept_encoding(HeaderBuilder::new()),
+        )
     }
 }
 
@@ -88,6 +276,8 @@ fn encode_illegal() {
         id: None,
         nonce: None,
         attributes: Default::default(),
+        chain_id: None,
+        payload_hash: None,
     };
 
     assert!(encode_cose_sign1_from_request(message, &many_identity::AnonymousIdentity).is_err());
@@ -112,8 +302,47 @@ fn decode_illegal() {
         id: None,
         nonce: None,
         attributes: Default::default(),
+        chain_id: None,
+        payload_hash: None,
     };
     let envelope =
         encode_cose_sign1_from_request(message, &many_identity::AnonymousIdentity).unwrap();
     assert!(decode_request_from_cose_sign1(&envelope, &IllegalVerifier).is_err());
 }
+
+#[test]
+fn intent_hash_of_signed_payload() {
+    use sha3::{Digest, Sha3_256};
+
+    // An indefinite map is valid, but is not how messages are re-encoded.
+    let mut e = minicbor::Encoder::new(Vec::new());
+    e.tag(minicbor::data::Tag::Unassigned(10001))
+        .unwrap()
+        .begin_map()
+        .unwrap()
+        .i8(request::RequestMessageCborKey::Endpoint as i8)
+        .unwrap()
+        .str("status")
+        .unwrap()
+        .end()
+        .unwrap();
+    let bytes = e.into_writer();
+
+    let message = RequestMessage::from_bytes(&bytes).unwrap();
+    let reencoded = message.to_bytes().unwrap();
+    assert_ne!(reencoded, bytes);
+    assert_eq!(
+        message.intent_hash().unwrap(),
+        Sha3_256::digest(&bytes).to_vec()
+    );
+
+    // Messages built locally are hashed from their encoding.
+    let message = RequestMessage {
+        payload_hash: None,
+        ..message
+    };
+    assert_eq!(
+        message.intent_hash().unwrap(),
+        Sha3_256::digest(&reencoded).to_vec()
+    );
+}
@@ -1,3 +1,7 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
 use coset::CoseSign1;
 use derive_builder::Builder;
 use many_error::ManyError;
@@ -8,7 +12,7 @@ use minicbor::data::{Tag, Type};
 use minicbor::encode::{Error, Write};
 use minicbor::{Decode, Decoder, Encode, Encoder};
 use num_derive::{FromPrimitive, ToPrimitive};
-use std::convert::TryFrom;
+#[cfg(feature = "std")]
 use std::time::SystemTime;
 
 #[derive(FromPrimitive, ToPrimitive)]
@@ -23,10 +27,12 @@ pub enum RequestMessageCborKey {
     Id,
     Nonce,
     Attributes,
+    ChainId,
 }
 
 #[derive(Clone, Default, Builder)]
 #[builder(setter(strip_option), default)]
+#[cfg_attr(not(feature = "std"), builder(no_std))]
 pub struct RequestMessage {
     pub version: Option<u8>,
     pub from: Option<Address>,
@@ -41,10 +47,19 @@ pub struct RequestMessage {
     pub id: Option<u64>,
     pub nonce: Option<Vec<u8>>,
     pub attributes: AttributeSet,
+
+    /// The network this request is for. Servers on another network refuse
+    /// it, so a request signed for a testnet cannot be replayed on mainnet.
+    pub chain_id: Option<String>,
+
+    /// The SHA3-256 of the bytes this message was decoded from, i.e. of the
+    /// payload its sender signed. This is not encoded, and is [None] for
+    /// messages built locally.
+    pub payload_hash: Option<Vec<u8>>,
 }
 
-impl std::fmt::Debug for RequestMessage {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for RequestMessage {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let anon = Address::anonymous();
         let data = hex::encode(&self.data);
 
@@ -64,6 +79,9 @@ impl std::fmt::Debug for RequestMessage {
         if !self.attributes.is_empty() {
             s.field("attributes", &self.attributes);
         }
+        if let Some(chain_id) = &self.chain_id {
+            s.field("chain_id", chain_id);
+        }
 
         s.finish()
     }
@@ -73,11 +91,7 @@ impl TryFrom<CoseSign1> for RequestMessage {
     type Error = ManyError;
 
     fn try_from(envelope: CoseSign1) -> Result<Self, Self::Error> {
-        envelope
-            .payload
-            .as_ref()
-            .ok_or_else(ManyError::empty_envelope)
-            .and_then(|payload| Self::from_bytes(payload).map_err(ManyError::deserialization_error))
+        Self::try_from(&envelope)
     }
 }
 
@@ -85,11 +99,9 @@ impl<'a> TryFrom<&'a CoseSign1> for RequestMessage {
     type Error = ManyError;
 
     fn try_from(envelope: &'a CoseSign1) -> Result<Self, Self::Error> {
-        envelope
-            .payload
-            .as_ref()
-            .ok_or_else(ManyError::empty_envelope)
-            .and_then(|payload| Self::from_bytes(payload).map_err(ManyError::deserialization_error))
+        crate::compression::decode_payload(envelope).and_then(|payload| {
+            Self::from_bytes(&payload).map_err(ManyError::deserialization_error)
+        })
     }
 }
 
@@ -122,12 +134,26 @@ impl RequestMessage {
         minicbor::decode(bytes).map_err(|e| format!("{e}"))
     }
 
+    /// The SHA3-256 of the payload the sender signed, or of the encoded
+    /// message if it was built locally. Servers record it in the events a
+    /// request causes, so clients can find them from the request they signed.
+    pub fn intent_hash(&self) -> Result<Vec<u8>, String> {
+        use sha3::{Digest, Sha3_256};
+        match &self.payload_hash {
+            Some(hash) => Ok(hash.clone()),
+            None => self
+                .to_bytes()
+                .map(|bytes| Sha3_256::digest(bytes).to_vec()),
+        }
+    }
+
     pub fn from(&self) -> Address {
         self.from.unwrap_or_default()
     }
 
     /// Validate that the timestamp of a message is within a timeout, either in the future
     /// or the past.
+    #[cfg(feature = "std")]
     pub fn validate_time(&self, now: SystemTime, timeout_in_secs: u64) -> Result<(), ManyError> {
         if timeout_in_secs == 0 {
             return Err(ManyError::timestamp_out_of_range());
@@ -154,6 +180,18 @@ impl RequestMessage {
 
         Ok(())
     }
+
+    /// Validate that the message is for the chain `chain_id`. Messages without
+    /// a chain ID are only accepted if `allow_missing` is true, e.g. while
+    /// clients transition to sending one.
+    pub fn validate_chain_id(&self, chain_id: &str, allow_missing: bool) -> Result<(), ManyError> {
+        match &self.chain_id {
+            Some(id) if id == chain_id => Ok(()),
+            Some(id) => Err(ManyError::invalid_chain_id(chain_id, id)),
+            None if allow_missing => Ok(()),
+            None => Err(ManyError::required_field_missing("chainId")),
+        }
+    }
 }
 
 impl<C> Encode<C> for RequestMessage {
@@ -165,7 +203,8 @@ impl<C> Encode<C> for RequestMessage {
             + u64::from(!self.data.is_empty())
             + u64::from(self.id.is_some())
             + u64::from(self.nonce.is_some())
-            + u64::from(!self.attributes.is_empty());
+            + u64::from(!self.attributes.is_empty())
+            + u64::from(self.chain_id.is_some());
         e.map(l)?;
 
         // Skip version for this version of the protocol. This message implementation
@@ -192,7 +231,7 @@ impl<C> Encode<C> for RequestMessage {
         }
 
         e.i8(RequestMessageCborKey::Timestamp as i8)?;
-        e.encode(self.timestamp.unwrap_or_else(Timestamp::now))?;
+        e.encode(crate::message_timestamp(self.timestamp)?)?;
 
         if let Some(ref id) = self.id {
             e.i8(RequestMessageCborKey::Id as i8)?.u64(*id)?;
@@ -207,12 +246,17 @@ impl<C> Encode<C> for RequestMessage {
                 .encode(&self.attributes)?;
         }
 
+        if let Some(ref chain_id) = self.chain_id {
+            e.i8(RequestMessageCborKey::ChainId as i8)?.str(chain_id)?;
+        }
+
         Ok(())
     }
 }
 
 impl<'b, C> Decode<'b, C> for RequestMessage {
     fn decode(d: &mut Decoder<'b>, _: &mut C) -> Result<Self, minicbor::decode::Error> {
+        let start = d.position();
         if d.tag()? != Tag::Unassigned(10001) {
             return Err(minicbor::decode::Error::message(
                 "Invalid tag, expected 10001 for a message.",
@@ -249,6 +293,7 @@ impl<'b, C> Decode<'b, C> for RequestMessage {
                 Some(RequestMessageCborKey::Id) => builder.id(d.u64()?),
                 Some(RequestMessageCborKey::Nonce) => builder.nonce(d.bytes()?.to_vec()),
                 Some(RequestMessageCborKey::Attributes) => builder.attributes(d.decode()?),
+                Some(RequestMessageCborKey::ChainId) => builder.chain_id(d.decode()?),
             };
 
             i += 1;
@@ -257,6 +302,10 @@ impl<'b, C> Decode<'b, C> for RequestMessage {
             }
         }
 
+        // Hash the bytes as they were signed, not a re-encoding of them.
+        use sha3::{Digest, Sha3_256};
+        builder.payload_hash(Sha3_256::digest(&d.input()[start..d.position()]).to_vec());
+
         builder
             .build()
             .map_err(|_e| minicbor::decode::Error::message("could not build"))
@@ -1,4 +1,8 @@
 use crate::RequestMessage;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
 use coset::CoseSign1;
 use derive_builder::Builder;
 use many_error::ManyError;
@@ -27,6 +31,7 @@ pub enum ResponseMessageCborKey {
 /// A MANY message response.
 #[derive(Clone, Debug, Builder, Eq, PartialEq)]
 #[builder(setter(strip_option), default)]
+#[cfg_attr(not(feature = "std"), builder(no_std))]
 pub struct ResponseMessage {
     pub version: Option<u8>,
     pub from: Address,
@@ -95,12 +100,9 @@ impl ResponseMessage {
             return Err(ManyError::invalid_from_identity());
         }
 
-        let payload = envelope
-            .payload
-            .as_ref()
-            .ok_or_else(ManyError::empty_envelope)?;
+        let payload = crate::compression::decode_payload(envelope)?;
         let message =
-            ResponseMessage::from_bytes(payload).map_err(ManyError::deserialization_error)?;
+            ResponseMessage::from_bytes(&payload).map_err(ManyError::deserialization_error)?;
 
         if address != message.from {
             Err(ManyError::invalid_from_identity())
@@ -128,6 +130,17 @@ impl ResponseMessage {
     }
 }
 
+impl many_types::cddl::Cddl for ResponseMessage {
+    fn cddl() -> String {
+        format!(
+            "#6.10002({{? 1: {0}, ? 2: {0}, 4: bstr / any, 5: {1}, ? 6: uint, ? 8: {2}}})",
+            Address::cddl(),
+            Timestamp::cddl(),
+            AttributeSet::cddl()
+        )
+    }
+}
+
 impl<C> Encode<C> for ResponseMessage {
     fn encode<W: Write>(&self, e: &mut Encoder<W>, _: &mut C) -> Result<(), Error<W::Error>> {
         e.tag(Tag::Unassigned(10002))?;
@@ -160,7 +173,7 @@ impl<C> Encode<C> for ResponseMessage {
         };
 
         e.i8(ResponseMessageCborKey::Timestamp as i8)?;
-        e.encode(self.timestamp.unwrap_or_else(Timestamp::now))?;
+        e.encode(crate::message_timestamp(self.timestamp)?)?;
 
         if let Some(ref id) = self.id {
             e.i8(ResponseMessageCborKey::Id as i8)?.u64(*id)?;
@@ -205,8 +218,12 @@ impl<'b, C> Decode<'b, C> for ResponseMessage {
                     _ => &mut builder,
                 },
                 Some(ResponseMessageCborKey::Timestamp) => builder.timestamp(d.decode()?),
+                Some(ResponseMessageCborKey::Id) => builder.id(d.decode()?),
                 Some(ResponseMessageCborKey::Attributes) => builder.attributes(d.decode()?),
-                _ => &mut builder,
+                _ => {
+                    d.skip()?;
+                    &mut builder
+                }
             };
 
             i += 1;
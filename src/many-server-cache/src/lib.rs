@@ -3,23 +3,60 @@ use many_error::ManyError;
 use many_protocol::ResponseMessage;
 use many_server::RequestValidator;
 use sha2::Digest;
+use std::collections::{BTreeMap, VecDeque};
 use std::path::Path;
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How often expired requests are removed from the cache, at most.
+pub const DEFAULT_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
 
 /// Implement this trait to provide a cache backend for the cache validator.
 pub trait RequestCacheBackend: Send + Sync {
     /// Returns true if the request was cached.
     fn has(&self, request: &[u8]) -> bool;
 
-    /// Add the request to the cache. This cannot fail.
-    fn put(&mut self, request: &[u8]);
+    /// Add the request to the cache, executed at `time`. This cannot fail.
+    fn put(&mut self, request: &[u8], time: SystemTime);
+
+    /// Add the request to the cache with its encoded response. Backends that
+    /// cannot store responses only add the request.
+    fn put_response(&mut self, request: &[u8], _response: &[u8], time: SystemTime) {
+        self.put(request, time)
+    }
+
+    /// Returns an error if the cache cannot take new requests, not counting
+    /// the requests added before `expired_before`.
+    fn check_capacity(&self, _expired_before: Option<SystemTime>) -> Result<(), ManyError> {
+        Ok(())
+    }
+
+    /// Returns the encoded response stored with the request, if any.
+    fn get_response(&self, _request: &[u8]) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Remove the requests added before `before`. Returns the number of
+    /// requests removed.
+    fn evict(&mut self, _before: SystemTime) -> Result<usize, ManyError> {
+        Ok(0)
+    }
+
+    /// Persist the cache to disk, if applicable.
+    fn flush(&mut self) -> Result<(), ManyError> {
+        Ok(())
+    }
+}
+
+fn secs_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
 }
 
 impl RequestCacheBackend for () {
     fn has(&self, _request: &[u8]) -> bool {
         false
     }
-    fn put(&mut self, _request: &[u8]) {}
+    fn put(&mut self, _request: &[u8], _time: SystemTime) {}
 }
 
 impl<T: RequestCacheBackend + ?Sized> RequestCacheBackend for Arc<RwLock<T>> {
@@ -27,13 +64,61 @@ impl<T: RequestCacheBackend + ?Sized> RequestCacheBackend for Arc<RwLock<T>> {
         self.read().unwrap().has(request)
     }
 
-    fn put(&mut self, request: &[u8]) {
-        self.write().unwrap().put(request)
+    fn put(&mut self, request: &[u8], time: SystemTime) {
+        self.write().unwrap().put(request, time)
+    }
+
+    fn put_response(&mut self, request: &[u8], response: &[u8], time: SystemTime) {
+        self.write().unwrap().put_response(request, response, time)
+    }
+
+    fn check_capacity(&self, expired_before: Option<SystemTime>) -> Result<(), ManyError> {
+        self.read().unwrap().check_capacity(expired_before)
+    }
+
+    fn get_response(&self, request: &[u8]) -> Option<Vec<u8>> {
+        self.read().unwrap().get_response(request)
+    }
+
+    fn evict(&mut self, before: SystemTime) -> Result<usize, ManyError> {
+        self.write().unwrap().evict(before)
+    }
+
+    fn flush(&mut self) -> Result<(), ManyError> {
+        self.write().unwrap().flush()
     }
 }
 
+/// What the validator does with a request that was already executed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ReplayPolicy {
+    /// Reject the request with a `duplicated_message` error.
+    #[default]
+    Reject,
+
+    /// Return the response of the original execution, so clients retrying
+    /// a request can learn its outcome. Responses are stored in the cache
+    /// with their request. Requests cached without a response are rejected.
+    ReturnCachedResponse,
+}
+
+fn request_hash(envelope: &CoseSign1) -> Result<Vec<u8>, ManyError> {
+    let payload = envelope
+        .payload
+        .as_ref()
+        .ok_or_else(ManyError::empty_envelope)?;
+    let mut hasher = sha2::Sha512::default();
+    hasher.update(payload);
+    Ok(hasher.finalize().to_vec())
+}
+
 pub struct RequestCacheValidator<T: RequestCacheBackend> {
     backend: T,
+    replay_policy: ReplayPolicy,
+    ttl: Option<Duration>,
+    sweep_interval: Duration,
+    last_sweep: Instant,
+    time_fn: Option<Arc<dyn Fn() -> Result<SystemTime, ManyError> + Send + Sync>>,
 }
 
 unsafe impl<T: RequestCacheBackend + Send> Send for RequestCacheValidator<T> {}
@@ -41,41 +126,116 @@ unsafe impl<T: RequestCacheBackend + Sync> Sync for RequestCacheValidator<T> {}
 
 impl<T: RequestCacheBackend> RequestCacheValidator<T> {
     pub fn new(backend: T) -> Self {
-        Self { backend }
+        Self {
+            backend,
+            replay_policy: ReplayPolicy::default(),
+            ttl: None,
+            sweep_interval: DEFAULT_SWEEP_INTERVAL,
+            last_sweep: Instant::now(),
+            time_fn: None,
+        }
+    }
+
+    /// Use `time_fn` instead of the system clock to timestamp and expire the
+    /// requests, e.g. the time of the current block.
+    pub fn with_time_fn<F>(mut self, time_fn: F) -> Self
+    where
+        F: Fn() -> Result<SystemTime, ManyError> + Send + Sync + 'static,
+    {
+        self.time_fn = Some(Arc::new(time_fn));
+        self
+    }
+
+    fn now(&self) -> Result<SystemTime, ManyError> {
+        self.time_fn
+            .as_ref()
+            .map_or_else(|| Ok(SystemTime::now()), |f| f())
+    }
+
+    /// Returns the time before which requests are expired, if they expire.
+    fn expired_before(&self) -> Result<Option<SystemTime>, ManyError> {
+        match self.ttl {
+            Some(ttl) => Ok(self.now()?.checked_sub(ttl)),
+            None => Ok(None),
+        }
+    }
+
+    /// Remove requests from the cache after `ttl`. Requests are kept forever
+    /// by default.
+    ///
+    /// The TTL must be longer than the timeout of the server, otherwise a
+    /// request could be evicted while it is still valid and be replayed.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Set what happens to requests that were already executed.
+    pub fn with_replay_policy(mut self, policy: ReplayPolicy) -> Self {
+        self.replay_policy = policy;
+        self
+    }
+
+    /// Set how often expired requests are removed, at most.
+    pub fn with_sweep_interval(mut self, interval: Duration) -> Self {
+        self.sweep_interval = interval;
+        self
+    }
+
+    /// Remove the expired requests from the cache, if the sweep interval
+    /// has passed since the last sweep.
+    fn maybe_sweep(&mut self) -> Result<(), ManyError> {
+        if self.ttl.is_none() || self.last_sweep.elapsed() < self.sweep_interval {
+            return Ok(());
+        }
+        self.last_sweep = Instant::now();
+
+        match self.expired_before()? {
+            Some(before) => self.backend.evict(before).map(|_| ()),
+            None => Ok(()),
+        }
     }
 }
 
 impl<T: RequestCacheBackend> RequestValidator for RequestCacheValidator<T> {
     fn validate_envelope(&self, envelope: &CoseSign1) -> Result<(), ManyError> {
-        let payload = envelope
-            .payload
-            .as_ref()
-            .ok_or_else(ManyError::empty_envelope)?;
-        let mut hasher = sha2::Sha512::default();
-        hasher.update(payload);
-        let hash = hasher.finalize();
+        let hash = request_hash(envelope)?;
 
-        if self.backend.has(hash.as_ref()) {
+        if self.backend.has(&hash) {
             Err(ManyError::duplicated_message())
         } else {
-            Ok(())
+            self.backend.check_capacity(self.expired_before()?)
         }
     }
 
+    fn cached_response(&self, envelope: &CoseSign1) -> Option<ResponseMessage> {
+        if self.replay_policy != ReplayPolicy::ReturnCachedResponse {
+            return None;
+        }
+        let hash = request_hash(envelope).ok()?;
+        let bytes = self.backend.get_response(&hash)?;
+        ResponseMessage::from_bytes(&bytes).ok()
+    }
+
     fn message_executed(
         &mut self,
         envelope: &CoseSign1,
-        _response: &ResponseMessage,
+        response: &ResponseMessage,
     ) -> Result<(), ManyError> {
-        let payload = envelope
-            .payload
-            .as_ref()
-            .ok_or_else(ManyError::empty_envelope)?;
-        let mut hasher = sha2::Sha512::default();
-        hasher.update(payload);
-        let hash = hasher.finalize();
-        self.backend.put(hash.as_ref());
-        Ok(())
+        let hash = request_hash(envelope)?;
+        let now = self.now()?;
+        match self.replay_policy {
+            ReplayPolicy::Reject => self.backend.put(&hash, now),
+            ReplayPolicy::ReturnCachedResponse => match response.to_bytes() {
+                Ok(bytes) => self.backend.put_response(&hash, &bytes, now),
+                Err(_) => self.backend.put(&hash, now),
+            },
+        }
+        self.maybe_sweep()
+    }
+
+    fn flush(&mut self) -> Result<(), ManyError> {
+        self.backend.flush()
     }
 }
 
@@ -90,15 +250,110 @@ impl RocksDbCacheBackend {
     }
 }
 
+/// Prefix of the keys indexing the requests by the time they were added.
+const TIME_INDEX_PREFIX: &[u8] = b"/time/";
+
+/// Set once every request in the database is indexed by time.
+const TIME_INDEX_MARKER: &[u8] = b"/time_indexed";
+
+fn time_index_key(time: u64, key: &[u8]) -> Vec<u8> {
+    [TIME_INDEX_PREFIX, &time.to_be_bytes(), key].concat()
+}
+
+fn stored_time(value: &[u8]) -> u64 {
+    value
+        .get(..8)
+        .and_then(|time| <[u8; 8]>::try_from(time).ok())
+        .map_or(0, u64::from_be_bytes)
+}
+
+impl RocksDbCacheBackend {
+    /// Index the requests stored before the time index existed. Requests
+    /// stored without a time are indexed at the epoch, so the first sweep
+    /// evicts them.
+    fn index_by_time(&mut self) -> Result<(), ManyError> {
+        let mut batch = rocksdb::WriteBatch::default();
+        for item in self.db.iterator(rocksdb::IteratorMode::Start) {
+            let (key, value) = item.map_err(ManyError::unknown)?;
+            if !key.starts_with(TIME_INDEX_PREFIX) {
+                batch.put(time_index_key(stored_time(&value), &key), []);
+            }
+        }
+        batch.put(TIME_INDEX_MARKER, []);
+        self.db.write(batch).map_err(ManyError::unknown)
+    }
+}
+
+/// Requests are stored with the time they were added, in seconds since the
+/// UNIX epoch, followed by their response if any. They are also indexed by
+/// time, so evicting them does not go through the whole cache.
 impl RequestCacheBackend for RocksDbCacheBackend {
     fn has(&self, key: &[u8]) -> bool {
         self.db.get(key).unwrap().is_some()
     }
-    fn put(&mut self, key: &[u8]) {
+    fn put(&mut self, key: &[u8], time: SystemTime) {
+        self.put_response(key, &[], time)
+    }
+    fn put_response(&mut self, key: &[u8], response: &[u8], time: SystemTime) {
+        let time = secs_since_epoch(time);
+        let mut value = time.to_be_bytes().to_vec();
+        value.extend_from_slice(response);
         let mut batch = rocksdb::WriteBatch::default();
-        batch.put(key, b"");
+        batch.put(key, value);
+        batch.put(time_index_key(time, key), []);
         self.db.write(batch).unwrap();
     }
+    fn get_response(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.db
+            .get(key)
+            .unwrap()
+            .filter(|value| value.len() > 8)
+            .map(|value| value[8..].to_vec())
+    }
+    fn evict(&mut self, before: SystemTime) -> Result<usize, ManyError> {
+        if self
+            .db
+            .get(TIME_INDEX_MARKER)
+            .map_err(ManyError::unknown)?
+            .is_none()
+        {
+            self.index_by_time()?;
+        }
+
+        let before = secs_since_epoch(before);
+        let mut batch = rocksdb::WriteBatch::default();
+        let mut count = 0;
+        let it = self.db.iterator(rocksdb::IteratorMode::From(
+            TIME_INDEX_PREFIX,
+            rocksdb::Direction::Forward,
+        ));
+        for item in it {
+            let (index_key, _) = item.map_err(ManyError::unknown)?;
+            let indexed = match index_key.strip_prefix(TIME_INDEX_PREFIX) {
+                Some(indexed) if indexed.len() >= 8 => indexed,
+                _ => break,
+            };
+            let time = stored_time(indexed);
+            if time >= before {
+                break;
+            }
+
+            // The request might have been added again since it was indexed.
+            let key = &indexed[8..];
+            let value = self.db.get(key).map_err(ManyError::unknown)?;
+            if value.map_or(false, |value| stored_time(&value) == time) {
+                batch.delete(key);
+                count += 1;
+            }
+            batch.delete(index_key);
+        }
+
+        self.db.write(batch).map_err(ManyError::unknown)?;
+        Ok(count)
+    }
+    fn flush(&mut self) -> Result<(), ManyError> {
+        self.db.flush().map_err(ManyError::unknown)
+    }
 }
 
 #[derive(Clone)]
@@ -118,7 +373,220 @@ impl RequestCacheBackend for SharedRocksDbCacheBackend {
     fn has(&self, key: &[u8]) -> bool {
         self.inner.read().unwrap().has(key)
     }
-    fn put(&mut self, key: &[u8]) {
-        self.inner.write().unwrap().put(key)
+    fn put(&mut self, key: &[u8], time: SystemTime) {
+        self.inner.write().unwrap().put(key, time)
+    }
+    fn put_response(&mut self, key: &[u8], response: &[u8], time: SystemTime) {
+        self.inner
+            .write()
+            .unwrap()
+            .put_response(key, response, time)
+    }
+    fn get_response(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.inner.read().unwrap().get_response(key)
+    }
+    fn evict(&mut self, before: SystemTime) -> Result<usize, ManyError> {
+        self.inner.write().unwrap().evict(before)
+    }
+    fn flush(&mut self) -> Result<(), ManyError> {
+        self.inner.write().unwrap().flush()
+    }
+}
+
+/// A cache kept in memory, which is lost when the server restarts.
+///
+/// With a maximum number of entries, new requests are refused while the cache
+/// is full of requests that have not expired yet. Removing them could allow
+/// them to be replayed.
+#[derive(Debug, Default)]
+pub struct InMemoryCacheBackend {
+    entries: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+    order: VecDeque<(SystemTime, Vec<u8>)>,
+    max_entries: Option<usize>,
+}
+
+impl InMemoryCacheBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn insert(&mut self, key: &[u8], response: Option<Vec<u8>>, time: SystemTime) {
+        if self.entries.contains_key(key) {
+            return;
+        }
+        self.entries.insert(key.to_vec(), response);
+        // Keep the order sorted by time, even if the time source goes back.
+        let index = self.order.partition_point(|(t, _)| *t <= time);
+        self.order.insert(index, (time, key.to_vec()));
+    }
+}
+
+impl RequestCacheBackend for InMemoryCacheBackend {
+    fn has(&self, key: &[u8]) -> bool {
+        self.entries.contains_key(key)
+    }
+    fn put(&mut self, key: &[u8], time: SystemTime) {
+        self.insert(key, None, time)
+    }
+    fn put_response(&mut self, key: &[u8], response: &[u8], time: SystemTime) {
+        self.insert(key, Some(response.to_vec()), time)
+    }
+    fn check_capacity(&self, expired_before: Option<SystemTime>) -> Result<(), ManyError> {
+        let max = match self.max_entries {
+            Some(max) => max,
+            None => return Ok(()),
+        };
+        let expired = expired_before.map_or(0, |before| {
+            self.order.partition_point(|(time, _)| *time < before)
+        });
+        if self.order.len() - expired >= max {
+            Err(ManyError::request_cache_full(max))
+        } else {
+            Ok(())
+        }
+    }
+    fn get_response(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.entries.get(key).cloned().flatten()
+    }
+    fn evict(&mut self, before: SystemTime) -> Result<usize, ManyError> {
+        let mut count = 0;
+        while matches!(self.order.front(), Some((time, _)) if *time < before) {
+            if let Some((_, key)) = self.order.pop_front() {
+                self.entries.remove(&key);
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_memory_max_entries() {
+        let now = SystemTime::now();
+        let mut backend = InMemoryCacheBackend::new().with_max_entries(2);
+        backend.put(b"a", now);
+        assert!(backend.check_capacity(None).is_ok());
+        backend.put(b"b", now);
+        backend.put(b"b", now);
+        assert_eq!(backend.len(), 2);
+
+        // Requests that have not expired are kept and new ones are refused.
+        assert!(backend.check_capacity(None).is_err());
+        assert!(backend.check_capacity(Some(now)).is_err());
+        assert!(backend.has(b"a"));
+        assert!(backend.has(b"b"));
+
+        // Expired requests do not count.
+        let later = now + Duration::from_secs(1);
+        assert!(backend.check_capacity(Some(later)).is_ok());
+    }
+
+    #[test]
+    fn refuse_when_full() {
+        let envelope = |payload: u8| CoseSign1 {
+            payload: Some(vec![payload]),
+            ..Default::default()
+        };
+        let response = ResponseMessage::default();
+        let time = Arc::new(RwLock::new(UNIX_EPOCH + Duration::from_secs(1_000)));
+
+        let mut validator =
+            RequestCacheValidator::new(InMemoryCacheBackend::new().with_max_entries(1))
+                .with_ttl(Duration::from_secs(10))
+                .with_time_fn({
+                    let time = time.clone();
+                    move || Ok(*time.read().unwrap())
+                });
+        validator.message_executed(&envelope(1), &response).unwrap();
+        assert!(validator.validate_envelope(&envelope(2)).is_err());
+
+        // The cache uses the time given, not the system clock.
+        *time.write().unwrap() += Duration::from_secs(11);
+        assert!(validator.validate_envelope(&envelope(2)).is_ok());
+        assert!(validator.validate_envelope(&envelope(1)).is_err());
+    }
+
+    #[test]
+    fn in_memory_evict() {
+        let mut backend = InMemoryCacheBackend::new();
+        backend.put(b"a", SystemTime::now());
+        backend.put(b"b", SystemTime::now());
+
+        assert_eq!(backend.evict(UNIX_EPOCH).unwrap(), 0);
+        let later = SystemTime::now() + Duration::from_secs(1);
+        assert_eq!(backend.evict(later).unwrap(), 2);
+        assert!(backend.is_empty());
+    }
+
+    #[test]
+    fn rocksdb_evict() {
+        let path = std::env::temp_dir().join(format!(
+            "many-server-cache-{}",
+            secs_since_epoch(SystemTime::now()) ^ std::process::id() as u64
+        ));
+        {
+            let mut backend = RocksDbCacheBackend::new(&path);
+            backend.put(b"a", SystemTime::now());
+            backend.put_response(b"r", b"response", SystemTime::now());
+            assert_eq!(backend.get_response(b"a"), None);
+            assert_eq!(backend.get_response(b"r"), Some(b"response".to_vec()));
+            // Entries from before the TTL were stored without a time.
+            backend.db.put(b"legacy", b"").unwrap();
+
+            let epoch = UNIX_EPOCH + Duration::from_secs(1);
+            assert_eq!(backend.evict(epoch).unwrap(), 1);
+            assert!(!backend.has(b"legacy"));
+            assert!(backend.has(b"a"));
+
+            let later = SystemTime::now() + Duration::from_secs(2);
+            assert_eq!(backend.evict(later).unwrap(), 2);
+            assert!(!backend.has(b"a"));
+        }
+        let _ = std::fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn replay_cached_response() {
+        let envelope = CoseSign1 {
+            payload: Some(vec![1, 2, 3]),
+            ..Default::default()
+        };
+        let response = ResponseMessage {
+            id: Some(1),
+            data: Ok(vec![4, 5, 6]),
+            ..Default::default()
+        };
+
+        let mut reject = RequestCacheValidator::new(InMemoryCacheBackend::new());
+        reject.message_executed(&envelope, &response).unwrap();
+        assert!(reject.validate_envelope(&envelope).is_err());
+        assert!(reject.cached_response(&envelope).is_none());
+
+        let mut replay = RequestCacheValidator::new(InMemoryCacheBackend::new())
+            .with_replay_policy(ReplayPolicy::ReturnCachedResponse);
+        assert!(replay.cached_response(&envelope).is_none());
+        replay.message_executed(&envelope, &response).unwrap();
+        assert!(replay.validate_envelope(&envelope).is_err());
+
+        let cached = replay.cached_response(&envelope).unwrap();
+        assert_eq!(cached.id, response.id);
+        assert_eq!(cached.data, response.data);
     }
 }
@@ -1,8 +1,10 @@
+pub mod registry;
 pub mod server;
 pub mod transport;
 pub mod validator;
 
 pub use many_error::ManyError;
 pub use many_identity::Address;
-pub use server::ManyServer;
+pub use registry::{ModuleConfig, ModuleRegistry};
+pub use server::{ManyServer, MissingChainId};
 pub use validator::RequestValidator;
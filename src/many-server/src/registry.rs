@@ -0,0 +1,203 @@
+//! A registry of the modules a server binary can serve, by name. Binaries
+//! register a constructor for each module they support, then add the modules
+//! listed in their configuration to the server, instead of hardcoding their
+//! module list.
+use crate::ManyServer;
+use many_error::ManyError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A module to add to a server, as listed in a configuration file. Either the
+/// name of the module, or an object with its name and options:
+///
+/// ```json
+/// ["ledger", { "name": "events", "options": { "archive": true } }]
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ModuleConfig {
+    Name(String),
+    WithOptions {
+        name: String,
+        #[serde(default)]
+        options: Value,
+    },
+}
+
+impl ModuleConfig {
+    pub fn new(name: impl ToString) -> Self {
+        Self::Name(name.to_string())
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Name(name) | Self::WithOptions { name, .. } => name,
+        }
+    }
+
+    /// The options of the module, or `null` if it has none.
+    pub fn options(&self) -> &Value {
+        static NO_OPTIONS: Value = Value::Null;
+        match self {
+            Self::Name(_) => &NO_OPTIONS,
+            Self::WithOptions { options, .. } => options,
+        }
+    }
+}
+
+type Constructor<C> =
+    Box<dyn Fn(&mut ManyServer, &C, &Value) -> Result<(), ManyError> + Send + Sync>;
+
+/// Constructors of modules, by name. A constructor receives the server to add
+/// modules to, a context shared by all the constructors of a binary (e.g. its
+/// storage), and the options of the module from the configuration.
+pub struct ModuleRegistry<C> {
+    constructors: BTreeMap<String, Constructor<C>>,
+}
+
+impl<C> Default for ModuleRegistry<C> {
+    fn default() -> Self {
+        Self {
+            constructors: BTreeMap::new(),
+        }
+    }
+}
+
+impl<C> ModuleRegistry<C> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the constructor of a module, replacing any previous
+    /// constructor with the same name.
+    pub fn register(
+        &mut self,
+        name: impl ToString,
+        constructor: impl Fn(&mut ManyServer, &C, &Value) -> Result<(), ManyError>
+            + Send
+            + Sync
+            + 'static,
+    ) -> &mut Self {
+        self.constructors
+            .insert(name.to_string(), Box::new(constructor));
+        self
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.constructors.contains_key(name)
+    }
+
+    /// The names of all registered modules, in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.constructors.keys().map(String::as_str)
+    }
+
+    /// Add the `modules` to the server, in order. Fails before adding any
+    /// module if one of them is unknown or listed twice.
+    pub fn instantiate(
+        &self,
+        server: &mut ManyServer,
+        context: &C,
+        modules: &[ModuleConfig],
+    ) -> Result<(), ManyError> {
+        let mut seen = BTreeSet::new();
+        for config in modules {
+            let name = config.name();
+            if !self.contains(name) {
+                return Err(ManyError::unknown(format!(
+                    "Unknown module '{name}'. Known modules: {}.",
+                    self.names().collect::<Vec<_>>().join(", ")
+                )));
+            }
+            if !seen.insert(name) {
+                return Err(ManyError::unknown(format!(
+                    "Module '{name}' is listed more than once."
+                )));
+            }
+        }
+
+        for config in modules {
+            tracing::debug!(module = config.name(), "Adding module");
+            (self.constructors[config.name()])(server, context, config.options())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use many_identity::AnonymousIdentity;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Context {
+        constructed: Mutex<Vec<(String, Value)>>,
+    }
+
+    fn registry() -> ModuleRegistry<Context> {
+        let mut registry = ModuleRegistry::new();
+        for name in ["a", "b"] {
+            registry.register(name, move |_, context: &Context, options| {
+                context
+                    .constructed
+                    .lock()
+                    .unwrap()
+                    .push((name.to_string(), options.clone()));
+                Ok(())
+            });
+        }
+        registry
+    }
+
+    #[test]
+    fn config() {
+        let modules: Vec<ModuleConfig> =
+            serde_json::from_str(r#"["a", { "name": "b", "options": { "x": 1 } }]"#).unwrap();
+        assert_eq!(modules[0], ModuleConfig::new("a"));
+        assert_eq!(modules[0].options(), &Value::Null);
+        assert_eq!(modules[1].name(), "b");
+        assert_eq!(modules[1].options()["x"], 1);
+    }
+
+    #[test]
+    fn instantiate() {
+        let server = ManyServer::test(AnonymousIdentity);
+        let context = Context::default();
+        let modules = [
+            ModuleConfig::new("b"),
+            ModuleConfig::WithOptions {
+                name: "a".to_string(),
+                options: Value::Bool(true),
+            },
+        ];
+
+        registry()
+            .instantiate(&mut server.lock().unwrap(), &context, &modules)
+            .unwrap();
+        assert_eq!(
+            context.constructed.into_inner().unwrap(),
+            vec![
+                ("b".to_string(), Value::Null),
+                ("a".to_string(), Value::Bool(true))
+            ]
+        );
+    }
+
+    #[test]
+    fn instantiate_invalid() {
+        let server = ManyServer::test(AnonymousIdentity);
+        let context = Context::default();
+
+        for modules in [
+            vec![ModuleConfig::new("a"), ModuleConfig::new("c")],
+            vec![ModuleConfig::new("a"), ModuleConfig::new("a")],
+        ] {
+            assert!(registry()
+                .instantiate(&mut server.lock().unwrap(), &context, &modules)
+                .is_err());
+        }
+        assert!(context.constructed.into_inner().unwrap().is_empty());
+    }
+}
@@ -1,17 +1,35 @@
 use crate::transport::LowLevelManyRequestHandler;
 use crate::RequestValidator;
 use async_trait::async_trait;
-use coset::{CoseKey, CoseSign1};
+use coset::{CborSerializable, CoseKey, CoseSign1};
 use many_error::ManyError;
 use many_identity::{Identity, Verifier};
-use many_modules::{base, ManyModule, ManyModuleInfo};
-use many_protocol::{RequestMessage, ResponseMessage};
+use many_modules::{abci_backend, base, ManyModule, ManyModuleInfo};
+use many_protocol::context::at_block_height;
+use many_protocol::{compression, RequestMessage, ResponseMessage, ResponseMessageBatch};
 use many_types::attributes::Attribute;
+use many_types::Timestamp;
 use std::cell::RefCell;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{Debug, Formatter};
 use std::sync::{Arc, Mutex};
-use std::time::SystemTime;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+mod atomic;
+#[cfg(feature = "memory-tracking")]
+mod memory;
+mod multi_query;
+mod rejections;
+mod timeout;
+pub use atomic::AtomicGroupCoordinator;
+#[cfg(feature = "memory-tracking")]
+pub use memory::{memory_metrics, MemoryMetrics, TrackingAllocator};
+pub use multi_query::{
+    MultiQueryModule, QueryState, QueryStateCoordinator, MULTI_QUERY_MAX_ATTEMPTS,
+    MULTI_QUERY_METHOD,
+};
+pub use rejections::{RejectionLog, REJECTION_LOG_DEFAULT_CAPACITY};
+pub use timeout::{EndpointTimeout, TimeoutAction};
 
 trait ManyServerFallback: LowLevelManyRequestHandler + base::BaseModuleBackend {}
 
@@ -22,9 +40,42 @@ pub struct ManyModuleList {}
 
 pub const MANYSERVER_DEFAULT_TIMEOUT: u64 = 300;
 
+/// The default maximum size of a request payload once decompressed, in bytes.
+/// This is the maximum size of an HTTP request, so compressing a request
+/// does not allow larger requests.
+pub const MANYSERVER_DEFAULT_MAX_REQUEST_SIZE: usize = 5 * 1024 * 1024;
+
+/// Whether a server with a chain ID accepts requests without one.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MissingChainId {
+    /// Always accepted. Servers executing blockchain transactions must not
+    /// depend on their clock, so the transition is left to the frontend which
+    /// checks transactions before they are added to a block.
+    Accept,
+
+    /// Accepted until this time of the server clock, so clients have time to
+    /// add a chain ID, and refused afterward.
+    AcceptUntil(SystemTime),
+
+    /// Refused.
+    Refuse,
+}
+
+impl MissingChainId {
+    /// Whether a request without a chain ID is accepted at `now`.
+    pub fn is_accepted(&self, now: SystemTime) -> bool {
+        match self {
+            MissingChainId::Accept => true,
+            MissingChainId::AcceptUntil(until) => now < *until,
+            MissingChainId::Refuse => false,
+        }
+    }
+}
+
 pub struct ManyServer {
     modules: Vec<Arc<dyn ManyModule + Send>>,
     method_cache: BTreeSet<String>,
+    disabled_endpoints: BTreeSet<String>,
     identity: Box<dyn Identity>,
     identity_verifier: Box<dyn Verifier>,
     validator: RefCell<Box<dyn RequestValidator + Send>>,
@@ -32,9 +83,24 @@ pub struct ManyServer {
     name: String,
     version: Option<String>,
     timeout: u64,
+    max_request_size: usize,
     fallback: Option<Arc<dyn ManyServerFallback + Send + 'static>>,
+    endpoint_timeouts: BTreeMap<String, EndpointTimeout>,
+    #[cfg(feature = "memory-tracking")]
+    memory_budget: Option<usize>,
+    #[cfg(feature = "memory-tracking")]
+    endpoint_memory_budgets: BTreeMap<String, usize>,
+    atomic_groups: Option<Arc<dyn AtomicGroupCoordinator>>,
+    /// Requests execute holding this lock shared, and atomic groups holding it
+    /// exclusively, so a rollback never discards the writes of other requests.
+    execution: Arc<tokio::sync::RwLock<()>>,
+    query_state: Option<Arc<dyn QueryStateCoordinator>>,
+    rejections: Option<Arc<Mutex<RejectionLog>>>,
+    chain_id: Option<String>,
+    missing_chain_id: MissingChainId,
 
     time_fn: Option<Arc<dyn Fn() -> Result<SystemTime, ManyError> + Send + Sync>>,
+    block_height_fn: Option<Arc<dyn Fn() -> Option<u64> + Send + Sync>>,
 }
 
 impl ManyServer {
@@ -61,6 +127,9 @@ impl ManyServer {
             let mut s2 = s.lock().unwrap();
             s2.version = version;
             s2.add_module(base::BaseModule::new(s.clone()));
+            s2.add_module(base::EchoModule::new(s.clone()));
+            s2.add_module(base::DescribeModule::new(s.clone()));
+            s2.add_module(MultiQueryModule::new(s.clone()));
         }
 
         s
@@ -80,10 +149,24 @@ impl ManyServer {
             validator: RefCell::new(Box::new(())),
             public_key,
             timeout: MANYSERVER_DEFAULT_TIMEOUT,
+            max_request_size: MANYSERVER_DEFAULT_MAX_REQUEST_SIZE,
             fallback: None,
+            endpoint_timeouts: Default::default(),
+            #[cfg(feature = "memory-tracking")]
+            memory_budget: None,
+            #[cfg(feature = "memory-tracking")]
+            endpoint_memory_budgets: Default::default(),
+            atomic_groups: None,
+            execution: Default::default(),
+            query_state: None,
+            rejections: None,
+            chain_id: None,
+            missing_chain_id: MissingChainId::Refuse,
             method_cache: Default::default(),
+            disabled_endpoints: Default::default(),
             version: None,
             time_fn: None,
+            block_height_fn: None,
         }))
     }
 
@@ -91,6 +174,127 @@ impl ManyServer {
         self.timeout = timeout_in_secs;
     }
 
+    /// Refuse requests for another network than `chain_id`. Requests without
+    /// a chain ID are accepted according to `missing`.
+    pub fn set_chain_id(&mut self, chain_id: impl ToString, missing: MissingChainId) {
+        self.chain_id = Some(chain_id.to_string());
+        self.missing_chain_id = missing;
+    }
+
+    /// Refuse requests whose payload is larger than `size` bytes once
+    /// decompressed.
+    pub fn set_max_request_size(&mut self, size: usize) {
+        self.max_request_size = size;
+    }
+
+    /// The response refusing an envelope whose payload could not be
+    /// decompressed, or is too large once decompressed.
+    fn refuse_payload(&self, e: ManyError) -> Result<CoseSign1, String> {
+        if let Some(log) = &self.rejections {
+            log.lock().unwrap().record(None, &e);
+        }
+        let response = ResponseMessage::error(self.identity.address(), None, e);
+        many_protocol::encode_cose_sign1_from_response(response, &self.identity)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Limit the execution time of an endpoint. Endpoints without a limit
+    /// can run forever.
+    pub fn set_endpoint_timeout(
+        &mut self,
+        method: impl ToString,
+        timeout: EndpointTimeout,
+    ) -> &mut Self {
+        self.endpoint_timeouts.insert(method.to_string(), timeout);
+        self
+    }
+
+    /// Reject requests which allocate more than `bytes` during their
+    /// execution, unless their endpoint has its own budget. This requires the
+    /// [TrackingAllocator] to be installed.
+    ///
+    /// Execution is cancelled at the first await point over the budget, or
+    /// its response is replaced by an error if it completes. Memory usage is
+    /// not deterministic, so this should not be used on servers executing
+    /// commands through consensus.
+    ///
+    /// Servers with a budget return the memory metrics of their endpoints in
+    /// the `memory` extra of their status.
+    #[cfg(feature = "memory-tracking")]
+    pub fn set_memory_budget(&mut self, bytes: Option<usize>) -> &mut Self {
+        self.memory_budget = bytes;
+        self
+    }
+
+    /// Reject requests to an endpoint which allocate more than `bytes` during
+    /// their execution, instead of the budget of the server.
+    #[cfg(feature = "memory-tracking")]
+    pub fn set_endpoint_memory_budget(&mut self, method: impl ToString, bytes: usize) -> &mut Self {
+        self.endpoint_memory_budgets
+            .insert(method.to_string(), bytes);
+        self
+    }
+
+    /// Accept atomic groups of requests, using `coordinator` to stage their
+    /// storage writes. Without a coordinator, atomic groups are refused.
+    pub fn set_atomic_group_coordinator(
+        &mut self,
+        coordinator: impl AtomicGroupCoordinator + 'static,
+    ) -> &mut Self {
+        self.atomic_groups = Some(Arc::new(coordinator));
+        self
+    }
+
+    /// Accept multi-queries, using `coordinator` to know which endpoints they
+    /// can call and whether the state changed while executing them. Without a
+    /// coordinator, multi-queries are refused.
+    pub fn set_query_state_coordinator(
+        &mut self,
+        coordinator: impl QueryStateCoordinator + 'static,
+    ) -> &mut Self {
+        self.query_state = Some(Arc::new(coordinator));
+        self
+    }
+
+    /// Record the requests refused by this server in `log`, and let its
+    /// operators list them with `rejections.list`. The identity of the server
+    /// is always an operator.
+    pub fn set_rejection_log(&mut self, log: Arc<Mutex<RejectionLog>>) -> &mut Self {
+        log.lock().unwrap().add_operator(self.identity.address());
+        self.rejections = Some(log.clone());
+        self.add_module(many_modules::rejections::RejectionsModule::new(log))
+    }
+
+    fn record_rejection(&self, envelope: &CoseSign1, response: &ResponseMessage) {
+        if let (Some(log), Err(e)) = (&self.rejections, &response.data) {
+            log.lock().unwrap().record_envelope(envelope, e);
+        }
+    }
+
+    /// Refuse requests to an endpoint, as if no module implemented it. The
+    /// endpoint is also removed from `base.endpoints`.
+    pub fn disable_endpoint(&mut self, method: impl ToString) -> &mut Self {
+        let method = method.to_string();
+        if self.fallback.is_none() && !self.method_cache.contains(&method) {
+            tracing::warn!(method = method.as_str(), "Disabling an unknown endpoint");
+        }
+        self.disabled_endpoints.insert(method);
+        self
+    }
+
+    fn endpoint_limits(&self, method: &str) -> EndpointLimits {
+        EndpointLimits {
+            block_height: self.block_height_fn.as_ref().and_then(|f| f()),
+            timeout: self.endpoint_timeouts.get(method).copied(),
+            #[cfg(feature = "memory-tracking")]
+            memory_budget: self
+                .endpoint_memory_budgets
+                .get(method)
+                .copied()
+                .or(self.memory_budget),
+        }
+    }
+
     pub fn set_time_fn<T>(&mut self, time_fn: T)
     where
         T: Fn() -> Result<SystemTime, ManyError> + Send + Sync + 'static,
@@ -98,6 +302,15 @@ impl ManyServer {
         self.time_fn = Some(Arc::new(time_fn));
     }
 
+    /// Record the block height returned by `block_height_fn` in the tracing
+    /// span of every request this server executes.
+    pub fn set_block_height_fn<T>(&mut self, block_height_fn: T)
+    where
+        T: Fn() -> Option<u64> + Send + Sync + 'static,
+    {
+        self.block_height_fn = Some(Arc::new(block_height_fn));
+    }
+
     pub fn set_fallback_module<M>(&mut self, module: M) -> &mut Self
     where
         M: LowLevelManyRequestHandler + base::BaseModuleBackend + 'static,
@@ -177,6 +390,185 @@ impl ManyServer {
             .find(|x| x.info().endpoints.contains(&message.method))
             .cloned()
     }
+
+    /// The mempool priority of a message, from the module implementing its
+    /// endpoint. Messages without a module have no priority.
+    pub fn priority(&self, message: &RequestMessage) -> i64 {
+        self.find_module(message)
+            .map_or(0, |module| module.priority(message))
+    }
+
+    fn now(&self) -> Result<SystemTime, ManyError> {
+        self.time_fn
+            .as_ref()
+            .map_or_else(|| Ok(SystemTime::now()), |f| f())
+    }
+
+    /// Validate a request message received in `envelope`, and find the module
+    /// that should execute it.
+    fn validate_message(
+        &self,
+        message: &RequestMessage,
+        envelope: &CoseSign1,
+    ) -> Result<Option<Arc<dyn ManyModule + Send>>, ManyError> {
+        let now = self.now()?;
+
+        if self.disabled_endpoints.contains(&message.method) {
+            return Err(ManyError::invalid_method_name(message.method.clone()));
+        }
+
+        self.validator.borrow().validate_request(message)?;
+        message.validate_time(now, self.timeout)?;
+        if let Some(chain_id) = &self.chain_id {
+            message.validate_chain_id(chain_id, self.missing_chain_id.is_accepted(now))?;
+        }
+        self.validate_id(message)?;
+
+        let maybe_module = self.find_module(message);
+        if let Some(ref m) = maybe_module {
+            m.validate(message, envelope)?;
+        };
+        if message.method == MULTI_QUERY_METHOD {
+            self.validate_multi_query(message, envelope)?;
+        }
+        Ok(maybe_module)
+    }
+
+    fn message_executed(&self, envelope: &CoseSign1, response: &ResponseMessage) {
+        let _ = self
+            .validator
+            .borrow_mut()
+            .message_executed(envelope, response)
+            .map_err(|e| {
+                // There's nothing we can do here, since the backend has
+                // already executed the message and updated its test.
+                panic!(
+                    "message_executed failed: {e}\n\
+                    The backend and tendermint states might be inconsistent \
+                    and would need to revert to a previous block."
+                );
+            });
+    }
+}
+
+/// The limits enforced when executing a message, and the height it executes at.
+#[derive(Clone, Copy, Debug)]
+struct EndpointLimits {
+    /// The block height the message executes at, recorded in its span.
+    block_height: Option<u64>,
+    timeout: Option<EndpointTimeout>,
+    #[cfg(feature = "memory-tracking")]
+    memory_budget: Option<usize>,
+}
+
+/// Execute a message with a module, enforcing the limits of its endpoint.
+/// Incidents are logged with the method, sender and ID of the message.
+#[cfg(feature = "memory-tracking")]
+async fn execute_module(
+    module: &Arc<dyn ManyModule + Send>,
+    message: RequestMessage,
+    limits: EndpointLimits,
+) -> Result<ResponseMessage, ManyError> {
+    let method = message.method.clone();
+    let from = message.from();
+    let id = message.id;
+
+    let measurement = memory::measure(
+        at_block_height(
+            limits.block_height,
+            execute_with_timeout(module, message, limits.timeout),
+        ),
+        limits.memory_budget,
+    )
+    .await;
+    let exceeded = limits
+        .memory_budget
+        .map_or(false, |budget| measurement.peak > budget);
+    memory::record_request(&method, measurement.peak, exceeded);
+
+    match measurement.output {
+        Some(result) if !exceeded => result,
+        _ => {
+            let budget = limits.memory_budget.unwrap_or_default();
+            tracing::error!(
+                method = method.as_str(),
+                from = from.to_string().as_str(),
+                id = ?id,
+                budget_bytes = budget,
+                peak_bytes = measurement.peak,
+                "Execution rejected after exceeding its memory budget"
+            );
+            Err(ManyError::memory_budget_exceeded(method, budget))
+        }
+    }
+}
+
+/// Execute a message with a module, enforcing the limits of its endpoint.
+/// Incidents are logged with the method, sender and ID of the message.
+#[cfg(not(feature = "memory-tracking"))]
+async fn execute_module(
+    module: &Arc<dyn ManyModule + Send>,
+    message: RequestMessage,
+    limits: EndpointLimits,
+) -> Result<ResponseMessage, ManyError> {
+    at_block_height(
+        limits.block_height,
+        execute_with_timeout(module, message, limits.timeout),
+    )
+    .await
+}
+
+/// Execute a message with a module, enforcing the time limit of its endpoint.
+async fn execute_with_timeout(
+    module: &Arc<dyn ManyModule + Send>,
+    message: RequestMessage,
+    timeout: Option<EndpointTimeout>,
+) -> Result<ResponseMessage, ManyError> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return module.execute(message).await,
+    };
+
+    let method = message.method.clone();
+    let from = message.from();
+    let id = message.id;
+    let start = Instant::now();
+
+    match timeout.action {
+        TimeoutAction::Cancel => {
+            match tokio::time::timeout(timeout.limit, module.execute(message)).await {
+                Ok(result) => result,
+                Err(_) => {
+                    tracing::error!(
+                        method = method.as_str(),
+                        from = from.to_string().as_str(),
+                        id = ?id,
+                        limit_ms = timeout.limit.as_millis() as u64,
+                        "Execution cancelled after exceeding its time limit"
+                    );
+                    Err(ManyError::execution_timeout(
+                        method,
+                        timeout.limit.as_millis(),
+                    ))
+                }
+            }
+        }
+        TimeoutAction::Flag => {
+            let result = module.execute(message).await;
+            let elapsed = start.elapsed();
+            if elapsed > timeout.limit {
+                tracing::warn!(
+                    method = method.as_str(),
+                    from = from.to_string().as_str(),
+                    id = ?id,
+                    limit_ms = timeout.limit.as_millis() as u64,
+                    elapsed_ms = elapsed.as_millis() as u64,
+                    "Execution exceeded its time limit"
+                );
+            }
+            result
+        }
+    }
 }
 
 impl Debug for ManyServer {
@@ -196,7 +588,12 @@ impl base::BaseModuleBackend for ManyServer {
                 .collect();
         }
 
-        Ok(base::Endpoints(endpoints))
+        Ok(base::Endpoints(
+            endpoints
+                .difference(&self.disabled_endpoints)
+                .cloned()
+                .collect(),
+        ))
     }
 
     fn status(&self) -> Result<base::Status, ManyError> {
@@ -206,6 +603,8 @@ impl base::BaseModuleBackend for ManyServer {
             .filter_map(|m| m.info().attribute.clone())
             .collect();
 
+        let now = self.now()?;
+
         let mut builder = base::StatusBuilder::default();
 
         builder
@@ -213,8 +612,17 @@ impl base::BaseModuleBackend for ManyServer {
             .version(1)
             .identity(self.identity.address())
             .timeout(self.timeout)
+            .time(Timestamp::from_system_time(now)?)
             .extras(BTreeMap::new());
 
+        #[cfg(feature = "memory-tracking")]
+        if self.memory_budget.is_some() || !self.endpoint_memory_budgets.is_empty() {
+            builder.extras(BTreeMap::from([(
+                "memory".to_string(),
+                memory::metrics_to_cbor(&memory_metrics()),
+            )]));
+        }
+
         if let Some(ref pk) = self.public_key {
             builder.public_key(pk.clone());
         }
@@ -223,6 +631,10 @@ impl base::BaseModuleBackend for ManyServer {
             builder.server_version(sv);
         }
 
+        if let Some(chain_id) = self.chain_id.clone() {
+            builder.chain_id(chain_id);
+        }
+
         if let Some(fb) = &self.fallback {
             let fb_status = fb.status()?;
             if fb_status.identity != self.identity.address()
@@ -243,6 +655,10 @@ impl base::BaseModuleBackend for ManyServer {
                 builder.server_version(sv);
             }
 
+            if let (None, Some(chain_id)) = (&self.chain_id, fb_status.chain_id) {
+                builder.chain_id(chain_id);
+            }
+
             builder.name(fb_status.name).extras(fb_status.extras);
 
             attributes = attributes.into_iter().chain(fb_status.attributes).collect();
@@ -256,20 +672,89 @@ impl base::BaseModuleBackend for ManyServer {
     }
 }
 
+impl base::EchoModuleBackend for ManyServer {
+    fn echo(&self, args: base::EchoArgs) -> Result<base::EchoReturns, ManyError> {
+        fn millis(t: SystemTime) -> Result<u64, ManyError> {
+            t.duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .map_err(|e| ManyError::unknown(e.to_string()))
+        }
+
+        let received_ms = millis(self.now()?)?;
+        let base::EchoArgs { payload } = args;
+        Ok(base::EchoReturns {
+            payload,
+            received_ms,
+            processed_ms: millis(self.now()?)?,
+        })
+    }
+}
+
+impl base::DescribeModuleBackend for ManyServer {
+    fn describe(&self, args: base::DescribeArgs) -> Result<base::DescribeReturns, ManyError> {
+        let endpoints = self
+            .modules
+            .iter()
+            .flat_map(|m| m.schemas())
+            .filter(|(name, _)| !self.disabled_endpoints.contains(name))
+            .filter(|(name, _)| args.endpoints.as_ref().map_or(true, |e| e.contains(name)))
+            .collect();
+        Ok(base::DescribeReturns { endpoints })
+    }
+}
+
+impl abci_backend::AbciPriorityModuleBackend for ManyServer {
+    fn check_tx_priority(
+        &self,
+        args: abci_backend::AbciCheckTx,
+    ) -> Result<abci_backend::AbciCheckTxPriority, ManyError> {
+        let envelope = CoseSign1::from_slice(&args.tx).map_err(ManyError::deserialization_error)?;
+        let message = RequestMessage::try_from(&envelope)?;
+        Ok(abci_backend::AbciCheckTxPriority {
+            priority: self.priority(&message),
+        })
+    }
+}
+
 #[async_trait]
 impl LowLevelManyRequestHandler for Arc<Mutex<ManyServer>> {
     async fn execute(&self, envelope: CoseSign1) -> Result<CoseSign1, String> {
+        // The payload is decompressed once, outside the lock, and bounded
+        // before anything is decoded.
+        let max_request_size = self.lock().unwrap().max_request_size;
+        let payload = match compression::decode_payload_within(&envelope, max_request_size) {
+            Ok(payload) => payload,
+            Err(e) => return self.lock().unwrap().refuse_payload(e),
+        };
+        if many_protocol::batch::is_request_batch(&payload) {
+            return execute_batch(self, &envelope, &payload).await;
+        }
+
         let request = {
             let this = self.lock().unwrap();
             {
                 let validator = this.validator.borrow();
+                let request = many_protocol::decode_request_from_payload(
+                    &envelope,
+                    &payload,
+                    &this.identity_verifier,
+                );
 
-                validator.validate_envelope(&envelope).and_then(|_| {
-                    many_protocol::decode_request_from_cose_sign1(
-                        &envelope,
-                        &this.identity_verifier,
-                    )
-                })
+                // A request that was already executed can be answered with its
+                // original response, if a validator kept it.
+                if request.is_ok() {
+                    if let Some(mut response) = validator.cached_response(&envelope) {
+                        response.from = this.identity.address();
+                        return many_protocol::encode_cose_sign1_from_response_for(
+                            response,
+                            &this.identity,
+                            &envelope,
+                        )
+                        .map_err(|e| e.to_string());
+                    }
+                }
+
+                validator.validate_envelope(&envelope).and(request)
             }
         };
         let mut id = None;
@@ -280,24 +765,9 @@ impl LowLevelManyRequestHandler for Arc<Mutex<ManyServer>> {
 
             (|| {
                 let message = request?;
-
-                let now = this
-                    .time_fn
-                    .as_ref()
-                    .map_or_else(|| Ok(SystemTime::now()), |f| f())?;
-
-                this.validator.borrow().validate_request(&message)?;
-                message.validate_time(now, this.timeout)?;
-
+                let maybe_module = this.validate_message(&message, &envelope)?;
                 id = message.id;
 
-                this.validate_id(&message)?;
-
-                let maybe_module = this.find_module(&message);
-                if let Some(ref m) = maybe_module {
-                    m.validate(&message, &envelope)?;
-                };
-
                 Ok((address, message, maybe_module, this.fallback.clone()))
             })()
             .map_err(|many_err| ResponseMessage::error(address, id, many_err))
@@ -306,28 +776,30 @@ impl LowLevelManyRequestHandler for Arc<Mutex<ManyServer>> {
         match response {
             Ok((address, message, maybe_module, fallback)) => match (maybe_module, fallback) {
                 (Some(m), _) => {
-                    let mut response = match m.execute(message.clone()).await {
-                        Ok(response) => response,
-                        Err(many_err) => ResponseMessage::error(address, id, many_err),
+                    let (limits, execution) = {
+                        let this = self.lock().unwrap();
+                        (
+                            this.endpoint_limits(&message.method),
+                            this.execution.clone(),
+                        )
+                    };
+                    let mut response = {
+                        let _shared = execution.read().await;
+                        match execute_module(&m, message, limits).await {
+                            Ok(response) => response,
+                            Err(many_err) => ResponseMessage::error(address, id, many_err),
+                        }
                     };
                     response.from = address;
 
                     let this = self.lock().unwrap();
-                    let _ = this
-                        .validator
-                        .borrow_mut()
-                        .message_executed(&envelope, &response)
-                        .map_err(|e| {
-                            // There's nothing we can do here, since the backend has
-                            // already executed the message and updated its test.
-                            panic!(
-                                "message_executed failed: {e}\n\
-                                The backend and tendermint states might be inconsistent \
-                                and would need to revert to a previous block."
-                            );
-                        });
-                    many_protocol::encode_cose_sign1_from_response(response, &this.identity)
-                        .map_err(|e| e.to_string())
+                    this.message_executed(&envelope, &response);
+                    many_protocol::encode_cose_sign1_from_response_for(
+                        response,
+                        &this.identity,
+                        &envelope,
+                    )
+                    .map_err(|e| e.to_string())
                 }
                 (None, Some(fb)) => {
                     LowLevelManyRequestHandler::execute(fb.as_ref(), envelope).await
@@ -345,11 +817,186 @@ impl LowLevelManyRequestHandler for Arc<Mutex<ManyServer>> {
             },
             Err(response) => {
                 let this = self.lock().unwrap();
+                this.record_rejection(&envelope, &response);
                 many_protocol::encode_cose_sign1_from_response(response, &this.identity)
                     .map_err(|e| e.to_string())
             }
         }
     }
+
+    async fn flush(&self) -> Result<(), String> {
+        let fallback = {
+            let this = self.lock().unwrap();
+            this.validator
+                .borrow_mut()
+                .flush()
+                .map_err(|e| e.to_string())?;
+            this.fallback.clone()
+        };
+
+        match fallback {
+            Some(fb) => LowLevelManyRequestHandler::flush(fb.as_ref()).await,
+            None => Ok(()),
+        }
+    }
+}
+
+/// Execute the requests of a batch envelope in order. A request that fails does
+/// not prevent the following ones from executing; its response is an error.
+/// Batched requests are never sent to the fallback module.
+///
+/// An atomic group executes alone, and can only contain methods whose writes
+/// its coordinator stages. It stops at the first request that fails, and its
+/// storage writes are discarded. The other requests then fail with
+/// [ManyError::atomic_group_aborted].
+async fn execute_batch(
+    server: &Arc<Mutex<ManyServer>>,
+    envelope: &CoseSign1,
+    payload: &[u8],
+) -> Result<CoseSign1, String> {
+    let (batch, execution) = {
+        let this = server.lock().unwrap();
+        let validator = this.validator.borrow();
+        let batch = validator
+            .validate_envelope(envelope)
+            .and_then(|_| {
+                many_protocol::decode_request_batch_from_payload(
+                    envelope,
+                    payload,
+                    &this.identity_verifier,
+                )
+            })
+            .and_then(|batch| match (&this.atomic_groups, batch.atomic) {
+                (None, true) => Err(ManyError::atomic_groups_unsupported()),
+                (Some(coordinator), true)
+                    if !batch
+                        .messages
+                        .iter()
+                        .all(|m| coordinator.supports(&m.method)) =>
+                {
+                    Err(ManyError::atomic_groups_unsupported())
+                }
+                _ => Ok(batch),
+            });
+        (batch, this.execution.clone())
+    };
+
+    // The group starts once every other request is done executing.
+    let atomic = batch.as_ref().map_or(false, |batch| batch.atomic);
+    let _exclusive = if atomic {
+        Some(execution.write().await)
+    } else {
+        None
+    };
+    let batch = batch.and_then(|batch| {
+        if atomic {
+            let this = server.lock().unwrap();
+            if let Some(coordinator) = &this.atomic_groups {
+                coordinator.begin()?;
+            }
+        }
+        Ok(batch)
+    });
+    let batch = match batch {
+        Ok(batch) => batch,
+        Err(many_err) => {
+            let this = server.lock().unwrap();
+            let response = ResponseMessage::error(this.identity.address(), None, many_err);
+            this.record_rejection(envelope, &response);
+            return many_protocol::encode_cose_sign1_from_response(response, &this.identity)
+                .map_err(|e| e.to_string());
+        }
+    };
+
+    let batch_ids: Vec<_> = batch.messages.iter().map(|m| m.id).collect();
+    let mut responses = Vec::with_capacity(batch.len());
+    for message in batch.messages {
+        let id = message.id;
+        let (address, maybe_module, limits) = {
+            let this = server.lock().unwrap();
+            let address = this.identity.address();
+            let limits = this.endpoint_limits(&message.method);
+            let maybe_module = this.validate_message(&message, envelope);
+            if let (Some(log), Err(e)) = (&this.rejections, &maybe_module) {
+                log.lock().unwrap().record(Some(&message), e);
+            }
+            (address, maybe_module, limits)
+        };
+
+        let response = match maybe_module {
+            Ok(Some(m)) => {
+                let _shared = if atomic {
+                    None
+                } else {
+                    Some(execution.read().await)
+                };
+                let mut response = match execute_module(&m, message, limits).await {
+                    Ok(response) => response,
+                    Err(many_err) => ResponseMessage::error(address, id, many_err),
+                };
+                response.from = address;
+                if !atomic {
+                    server.lock().unwrap().message_executed(envelope, &response);
+                }
+                response
+            }
+            Ok(None) => ResponseMessage::error(address, id, ManyError::could_not_route_message()),
+            Err(many_err) => ResponseMessage::error(address, id, many_err),
+        };
+        responses.push(response);
+
+        if atomic && responses.last().map_or(false, |r| r.data.is_err()) {
+            break;
+        }
+    }
+
+    let this = server.lock().unwrap();
+    if atomic {
+        let address = this.identity.address();
+        let coordinator = this
+            .atomic_groups
+            .as_ref()
+            .expect("Atomic groups are only executed with a coordinator.");
+        let failed = responses.iter().position(|r| r.data.is_err());
+        let result = match failed {
+            Some(_) => coordinator.rollback(),
+            None => coordinator.commit(),
+        };
+
+        // If the coordinator failed, the storage is in an unknown state.
+        if let Err(many_err) = result {
+            tracing::error!("Atomic group coordinator failed: {}", many_err);
+            let response = ResponseMessage::error(address, None, many_err);
+            return many_protocol::encode_cose_sign1_from_response(response, &this.identity)
+                .map_err(|e| e.to_string());
+        }
+
+        if let Some(index) = failed {
+            let failed_response = responses.swap_remove(index);
+            responses = batch_ids
+                .into_iter()
+                .enumerate()
+                .map(|(i, id)| {
+                    if i == index {
+                        failed_response.clone()
+                    } else {
+                        ResponseMessage::error(address, id, ManyError::atomic_group_aborted(index))
+                    }
+                })
+                .collect();
+        }
+
+        for response in &responses {
+            this.message_executed(envelope, response);
+        }
+    }
+
+    many_protocol::encode_cose_sign1_from_response_batch_for(
+        ResponseMessageBatch(responses),
+        &this.identity,
+        envelope,
+    )
+    .map_err(|e| e.to_string())
 }
 
 #[cfg(test)]
@@ -364,7 +1011,8 @@ mod tests {
     use many_identity_dsa::ed25519::generate_random_ed25519_identity;
     use many_modules::base::Status;
     use many_protocol::{
-        decode_response_from_cose_sign1, encode_cose_sign1_from_request, RequestMessageBuilder,
+        decode_response_from_cose_sign1, encode_cose_sign1_from_request,
+        encode_cose_sign1_from_request_batch, RequestMessageBatch, RequestMessageBuilder,
     };
     use many_types::Timestamp;
     use proptest::prelude::*;
@@ -417,10 +1065,125 @@ mod tests {
             assert!(status.attributes.has_id(0));
             assert_eq!(status.server_version, Some(version.to_string()));
             assert_eq!(status.timeout, Some(MANYSERVER_DEFAULT_TIMEOUT));
+            assert!(status.time.is_some());
             assert_eq!(status.extras, BTreeMap::new());
         }
     }
 
+    #[test]
+    fn status_time() {
+        let server = ManyServer::test(AnonymousIdentity);
+        server
+            .lock()
+            .unwrap()
+            .set_time_fn(|| Ok(SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000)));
+
+        let status = base::BaseModuleBackend::status(&*server.lock().unwrap()).unwrap();
+        assert_eq!(status.time, Some(Timestamp::new(1_000_000).unwrap()));
+    }
+
+    #[test]
+    fn describe() {
+        let server = ManyServer::test(AnonymousIdentity);
+        let server = server.lock().unwrap();
+
+        let all = base::DescribeModuleBackend::describe(&*server, Default::default()).unwrap();
+        assert!(all.endpoints.contains_key("status"));
+        assert!(all.endpoints.contains_key("base.echo"));
+        assert!(all.endpoints.contains_key("base.describe"));
+
+        let echo = base::DescribeModuleBackend::describe(
+            &*server,
+            base::DescribeArgs {
+                endpoints: Some(BTreeSet::from(["base.echo".to_string()])),
+            },
+        )
+        .unwrap();
+        assert_eq!(echo.endpoints.len(), 1);
+        assert_eq!(echo.endpoints["base.echo"].argument, "{0: bstr}");
+    }
+
+    #[test]
+    fn compresses_large_responses() {
+        use many_protocol::compression::content_encoding;
+
+        let server = ManyServer::test(AnonymousIdentity);
+        let payload = vec![7u8; 100_000];
+        let echo = |envelope: CoseSign1| {
+            let response_e = smol::block_on(server.execute(envelope)).unwrap();
+            let response =
+                decode_response_from_cose_sign1(&response_e, None, &AcceptAllVerifier).unwrap();
+            let returns: base::EchoReturns = minicbor::decode(&response.data.unwrap()).unwrap();
+            assert_eq!(returns.payload.as_slice(), payload.as_slice());
+            content_encoding(&response_e).unwrap()
+        };
+
+        let request = RequestMessageBuilder::default()
+            .method("base.echo".to_string())
+            .data(
+                minicbor::to_vec(base::EchoArgs {
+                    payload: payload.clone().into(),
+                })
+                .unwrap(),
+            )
+            .timestamp(Timestamp::now())
+            .build()
+            .unwrap();
+
+        // Requests advertise the encodings they accept.
+        let envelope = encode_cose_sign1_from_request(request.clone(), &AnonymousIdentity).unwrap();
+        assert_eq!(echo(envelope), Some(many_protocol::ContentEncoding::Zstd));
+
+        // A peer that does not accept any encoding gets an uncompressed response.
+        let envelope = coset::CoseSign1Builder::new()
+            .payload(request.to_bytes().unwrap())
+            .build();
+        assert_eq!(echo(envelope), None);
+    }
+
+    #[test]
+    fn decompresses_requests() {
+        use many_protocol::encode_cose_sign1_from_request_compressed;
+
+        let server = ManyServer::test(AnonymousIdentity);
+        let payload = vec![7u8; 100_000];
+        let request = RequestMessageBuilder::default()
+            .method("base.echo".to_string())
+            .data(
+                minicbor::to_vec(base::EchoArgs {
+                    payload: payload.clone().into(),
+                })
+                .unwrap(),
+            )
+            .timestamp(Timestamp::now())
+            .build()
+            .unwrap();
+        let envelope = encode_cose_sign1_from_request_compressed(
+            request,
+            &AnonymousIdentity,
+            &[many_protocol::ContentEncoding::Zstd],
+        )
+        .unwrap();
+        assert!(envelope.payload.as_ref().unwrap().len() < payload.len());
+
+        let execute = |envelope: CoseSign1| {
+            let response_e = smol::block_on(server.execute(envelope)).unwrap();
+            decode_response_from_cose_sign1(&response_e, None, &AcceptAllVerifier)
+                .unwrap()
+                .data
+        };
+        let returns: base::EchoReturns =
+            minicbor::decode(&execute(envelope.clone()).unwrap()).unwrap();
+        assert_eq!(returns.payload.as_slice(), payload.as_slice());
+
+        // The limit applies to the decompressed payload.
+        server.lock().unwrap().set_max_request_size(payload.len());
+        assert_eq!(
+            execute(envelope).unwrap_err().code(),
+            ManyError::message_too_long(0).code()
+        );
+    }
+
     #[test]
     fn validate_from_anonymous_fail() {
         let request: RequestMessage = RequestMessageBuilder::default()
@@ -455,6 +1218,34 @@ mod tests {
         assert!(response.data.is_err());
     }
 
+    #[test]
+    fn disabled_endpoint() {
+        let id = generate_random_ed25519_identity();
+        let server = ManyServer::test(AnonymousIdentity);
+        server.lock().unwrap().disable_endpoint("status");
+
+        let endpoints = base::BaseModuleBackend::endpoints(&*server.lock().unwrap()).unwrap();
+        assert!(!endpoints.0.contains("status"));
+        assert!(endpoints.0.contains("endpoints"));
+
+        let request: RequestMessage = RequestMessageBuilder::default()
+            .version(1)
+            .from(id.address())
+            .to(Address::anonymous())
+            .method("status".to_string())
+            .data("null".as_bytes().to_vec())
+            .build()
+            .unwrap();
+        let envelope = encode_cose_sign1_from_request(request, &id).unwrap();
+        let response_e = smol::block_on(server.execute(envelope)).unwrap();
+        let response =
+            decode_response_from_cose_sign1(&response_e, None, &AcceptAllVerifier).unwrap();
+        assert_eq!(
+            response.data,
+            Err(ManyError::invalid_method_name("status".to_string()))
+        );
+    }
+
     #[test]
     fn validate_time() {
         let timestamp = SystemTime::now();
@@ -489,6 +1280,70 @@ mod tests {
             .is_err());
     }
 
+    #[test]
+    fn chain_id() {
+        let now = SystemTime::now();
+        let request = |chain_id: Option<&str>| {
+            let mut builder = RequestMessageBuilder::default();
+            builder
+                .from(Address::anonymous())
+                .method("heartbeat".to_string())
+                .timestamp(Timestamp::from_system_time(now).unwrap());
+            if let Some(chain_id) = chain_id {
+                builder.chain_id(chain_id.to_string());
+            }
+            builder.build().unwrap()
+        };
+        let execute = |server: &Arc<Mutex<ManyServer>>, request: RequestMessage| {
+            let envelope = encode_cose_sign1_from_request(request, &AnonymousIdentity).unwrap();
+            let response = smol::block_on(server.execute(envelope)).unwrap();
+            decode_response_from_cose_sign1(&response, None, &AcceptAllVerifier)
+                .unwrap()
+                .data
+                .map(|_| ())
+        };
+
+        let server = ManyServer::test(AnonymousIdentity);
+        server.lock().unwrap().set_chain_id(
+            "mainnet",
+            MissingChainId::AcceptUntil(now + Duration::from_secs(60)),
+        );
+        assert_eq!(execute(&server, request(Some("mainnet"))), Ok(()));
+        assert_eq!(execute(&server, request(None)), Ok(()));
+        assert_eq!(
+            execute(&server, request(Some("testnet"))),
+            Err(ManyError::invalid_chain_id("mainnet", "testnet"))
+        );
+        assert_eq!(
+            base::BaseModuleBackend::status(&*server.lock().unwrap())
+                .unwrap()
+                .chain_id,
+            Some("mainnet".to_string())
+        );
+
+        // After the transition, the chain ID is required.
+        server
+            .lock()
+            .unwrap()
+            .set_chain_id("mainnet", MissingChainId::AcceptUntil(now));
+        assert_eq!(
+            execute(&server, request(None)),
+            Err(ManyError::required_field_missing("chainId"))
+        );
+        assert_eq!(execute(&server, request(Some("mainnet"))), Ok(()));
+
+        // Block executors do not depend on their clock.
+        server
+            .lock()
+            .unwrap()
+            .set_chain_id("mainnet", MissingChainId::Accept);
+        assert_eq!(execute(&server, request(None)), Ok(()));
+        assert_eq!(
+            execute(&server, request(Some("testnet"))),
+            Err(ManyError::invalid_chain_id("mainnet", "testnet"))
+        );
+    }
+
     #[test]
     fn server_manages_time() {
         fn create_request(timestamp: SystemTime, nonce: u8) -> CoseSign1 {
@@ -637,4 +1492,580 @@ mod tests {
             decode_response_from_cose_sign1(&response_e, None, &AcceptAllVerifier).unwrap();
         assert!(response.data.is_err());
     }
+
+    #[test]
+    fn server_records_rejections() {
+        use many_modules::rejections::{ListArgs, RejectionsModuleBackend};
+
+        fn create_request(method: &str) -> CoseSign1 {
+            let request: RequestMessage = RequestMessageBuilder::default()
+                .method(method.to_string())
+                .timestamp(Timestamp::now())
+                .build()
+                .unwrap();
+            encode_cose_sign1_from_request(request, &AnonymousIdentity).unwrap()
+        }
+
+        let server = ManyServer::test(AnonymousIdentity);
+        let log = Arc::new(Mutex::new(RejectionLog::new(10)));
+        {
+            let mut server = server.lock().unwrap();
+            server.set_rejection_log(log.clone());
+            server.disable_endpoint("heartbeat");
+        }
+
+        for method in ["status", "heartbeat", "endpoints"] {
+            smol::block_on(server.execute(create_request(method))).unwrap();
+        }
+        let batch = encode_cose_sign1_from_request_batch(
+            RequestMessageBatch::from_iter([RequestMessageBuilder::default()
+                .method("heartbeat".to_string())
+                .timestamp(Timestamp::now())
+                .build()
+                .unwrap()]),
+            &AnonymousIdentity,
+        )
+        .unwrap();
+        smol::block_on(server.execute(batch)).unwrap();
+
+        // The server identity is an operator.
+        let rejections = log
+            .lock()
+            .unwrap()
+            .list(&Address::anonymous(), ListArgs::default())
+            .unwrap()
+            .rejections;
+        assert_eq!(rejections.len(), 2);
+        for rejection in rejections {
+            assert_eq!(rejection.method.as_deref(), Some("heartbeat"));
+            assert!(rejection.correlation_id.is_some());
+        }
+        assert!(server
+            .lock()
+            .unwrap()
+            .method_cache
+            .contains("rejections.list"));
+    }
+
+    #[test]
+    fn server_replays_cached_response() {
+        let request: RequestMessage = RequestMessageBuilder::default()
+            .method("status".to_string())
+            .timestamp(Timestamp::now())
+            .build()
+            .unwrap();
+        let envelope = encode_cose_sign1_from_request(request, &AnonymousIdentity).unwrap();
+
+        let server = ManyServer::test(AnonymousIdentity);
+
+        struct Validator(Mutex<Option<ResponseMessage>>);
+        impl RequestValidator for Arc<Validator> {
+            fn validate_envelope(&self, _envelope: &CoseSign1) -> Result<(), ManyError> {
+                match *self.0.lock().unwrap() {
+                    Some(_) => Err(ManyError::duplicated_message()),
+                    None => Ok(()),
+                }
+            }
+            fn cached_response(&self, _envelope: &CoseSign1) -> Option<ResponseMessage> {
+                self.0.lock().unwrap().clone()
+            }
+            fn message_executed(
+                &mut self,
+                _envelope: &CoseSign1,
+                response: &ResponseMessage,
+            ) -> Result<(), ManyError> {
+                *self.0.lock().unwrap() = Some(response.clone());
+                Ok(())
+            }
+        }
+
+        let validator = Arc::new(Validator(Mutex::new(None)));
+        {
+            let mut server = server.lock().unwrap();
+            server.add_validator(validator.clone());
+        }
+
+        let first = smol::block_on(server.execute(envelope.clone())).unwrap();
+        let first = decode_response_from_cose_sign1(&first, None, &AcceptAllVerifier).unwrap();
+        assert!(first.data.is_ok());
+
+        let replay = smol::block_on(server.execute(envelope)).unwrap();
+        let replay = decode_response_from_cose_sign1(&replay, None, &AcceptAllVerifier).unwrap();
+        assert_eq!(replay.data, first.data);
+    }
+
+    #[test]
+    fn server_executes_batch() {
+        let request = |method: &str, id: u64| {
+            RequestMessageBuilder::default()
+                .method(method.to_string())
+                .timestamp(Timestamp::now())
+                .id(id)
+                .build()
+                .unwrap()
+        };
+        let batch = RequestMessageBatch::from_iter([
+            request("status", 1),
+            request("unknown", 2),
+            request("endpoints", 3),
+        ]);
+        let envelope = encode_cose_sign1_from_request_batch(batch, &AnonymousIdentity).unwrap();
+
+        let server = ManyServer::test(AnonymousIdentity);
+        let response_e = smol::block_on(server.execute(envelope)).unwrap();
+        let responses =
+            ResponseMessageBatch::decode_and_verify(&response_e, &AcceptAllVerifier).unwrap();
+
+        assert_eq!(responses.len(), 3);
+        assert_eq!(
+            responses.0.iter().map(|r| r.id).collect::<Vec<_>>(),
+            vec![Some(1), Some(2), Some(3)]
+        );
+        assert!(responses.0[0].data.is_ok());
+        assert_eq!(
+            responses.0[1].data.as_ref().unwrap_err().code(),
+            ManyError::could_not_route_message().code()
+        );
+        assert!(responses.0[2].data.is_ok());
+    }
+
+    /// A counter whose increments can be staged by an atomic group.
+    #[derive(Clone, Debug, Default)]
+    struct Counter {
+        value: Arc<Mutex<u32>>,
+        staged: Arc<Mutex<Option<u32>>>,
+    }
+
+    impl AtomicGroupCoordinator for Counter {
+        fn supports(&self, method: &str) -> bool {
+            method == "inc" || method == "fail"
+        }
+
+        fn begin(&self) -> Result<(), ManyError> {
+            *self.staged.lock().unwrap() = Some(*self.value.lock().unwrap());
+            Ok(())
+        }
+
+        fn commit(&self) -> Result<(), ManyError> {
+            self.staged.lock().unwrap().take();
+            Ok(())
+        }
+
+        fn rollback(&self) -> Result<(), ManyError> {
+            if let Some(value) = self.staged.lock().unwrap().take() {
+                *self.value.lock().unwrap() = value;
+            }
+            Ok(())
+        }
+    }
+
+    /// A module which increments a counter, or fails.
+    #[derive(Debug)]
+    struct CounterModule(ManyModuleInfo, Counter);
+
+    #[async_trait]
+    impl ManyModule for CounterModule {
+        fn info(&self) -> &ManyModuleInfo {
+            &self.0
+        }
+
+        fn priority(&self, message: &RequestMessage) -> i64 {
+            if message.method == "inc" {
+                5
+            } else {
+                0
+            }
+        }
+
+        async fn execute(&self, message: RequestMessage) -> Result<ResponseMessage, ManyError> {
+            if message.method == "fail" {
+                return Err(ManyError::unknown("fail"));
+            }
+            *self.1.value.lock().unwrap() += 1;
+            Ok(ResponseMessage::from_request(
+                &message,
+                &message.to,
+                Ok(vec![]),
+            ))
+        }
+    }
+
+    #[test]
+    fn check_tx_priority() {
+        use many_modules::abci_backend::{AbciCheckTx, AbciPriorityModuleBackend};
+
+        let server = ManyServer::test(AnonymousIdentity);
+        server.lock().unwrap().add_module(CounterModule(
+            ManyModuleInfo {
+                name: "CounterModule".to_string(),
+                attribute: None,
+                endpoints: vec!["inc".to_string()],
+            },
+            Counter::default(),
+        ));
+
+        let priority = |method: &str| {
+            let message = RequestMessageBuilder::default()
+                .method(method.to_string())
+                .build()
+                .unwrap();
+            let envelope = encode_cose_sign1_from_request(message, &AnonymousIdentity).unwrap();
+            server
+                .lock()
+                .unwrap()
+                .check_tx_priority(AbciCheckTx {
+                    tx: envelope.to_vec().unwrap().into(),
+                })
+                .unwrap()
+                .priority
+        };
+        assert_eq!(priority("inc"), 5);
+        assert_eq!(priority("status"), 0);
+        assert_eq!(priority("unknown"), 0);
+    }
+
+    #[test]
+    fn server_executes_atomic_group() {
+        let request = |method: &str, id: u64| {
+            RequestMessageBuilder::default()
+                .method(method.to_string())
+                .timestamp(Timestamp::now())
+                .id(id)
+                .build()
+                .unwrap()
+        };
+        let execute = |server: &Arc<Mutex<ManyServer>>, methods: &[&str]| {
+            let batch = RequestMessageBatch::from_iter(
+                methods
+                    .iter()
+                    .enumerate()
+                    .map(|(i, method)| request(method, i as u64)),
+            )
+            .into_atomic();
+            let envelope = encode_cose_sign1_from_request_batch(batch, &AnonymousIdentity).unwrap();
+            smol::block_on(server.execute(envelope)).unwrap()
+        };
+
+        // Servers without a coordinator refuse atomic groups.
+        let server = ManyServer::test(AnonymousIdentity);
+        let response_e = execute(&server, &["status"]);
+        let response =
+            decode_response_from_cose_sign1(&response_e, None, &AcceptAllVerifier).unwrap();
+        assert_eq!(
+            response.data.unwrap_err().code(),
+            ManyError::atomic_groups_unsupported().code()
+        );
+
+        let counter = Counter::default();
+        {
+            let mut server = server.lock().unwrap();
+            server.add_module(CounterModule(
+                ManyModuleInfo {
+                    name: "CounterModule".to_string(),
+                    attribute: None,
+                    endpoints: vec!["inc".to_string(), "fail".to_string()],
+                },
+                counter.clone(),
+            ));
+            server.set_atomic_group_coordinator(counter.clone());
+        }
+
+        let response_e = execute(&server, &["inc", "inc"]);
+        let responses =
+            ResponseMessageBatch::decode_and_verify(&response_e, &AcceptAllVerifier).unwrap();
+        assert!(responses.0.iter().all(|r| r.data.is_ok()));
+        assert_eq!(*counter.value.lock().unwrap(), 2);
+
+        // A failed request discards the changes of the whole group.
+        let response_e = execute(&server, &["inc", "fail", "inc"]);
+        let responses =
+            ResponseMessageBatch::decode_and_verify(&response_e, &AcceptAllVerifier).unwrap();
+        assert_eq!(responses.len(), 3);
+        assert_eq!(
+            responses.0.iter().map(|r| r.id).collect::<Vec<_>>(),
+            vec![Some(0), Some(1), Some(2)]
+        );
+        assert_eq!(
+            responses.0[1].data.as_ref().unwrap_err().code(),
+            ManyError::unknown("").code()
+        );
+        for i in [0, 2] {
+            assert_eq!(
+                responses.0[i].data.as_ref().unwrap_err().code(),
+                ManyError::atomic_group_aborted(1).code()
+            );
+        }
+        assert_eq!(*counter.value.lock().unwrap(), 2);
+
+        // Groups cannot contain methods whose writes are not staged.
+        let response_e = execute(&server, &["inc", "status"]);
+        let response =
+            decode_response_from_cose_sign1(&response_e, None, &AcceptAllVerifier).unwrap();
+        assert_eq!(
+            response.data.unwrap_err().code(),
+            ManyError::atomic_groups_unsupported().code()
+        );
+        assert_eq!(*counter.value.lock().unwrap(), 2);
+    }
+
+    impl QueryStateCoordinator for Counter {
+        /// Increments are queries, so that the state changes while
+        /// multi-queries execute.
+        fn is_query(&self, method: &str) -> bool {
+            method != "fail"
+        }
+
+        fn state(&self) -> Result<QueryState, ManyError> {
+            Ok(QueryState {
+                height: 1,
+                hash: self.value.lock().unwrap().to_be_bytes().to_vec(),
+            })
+        }
+    }
+
+    #[test]
+    fn server_executes_multi_query() {
+        use many_modules::base::{MultiQueryArgs, MultiQueryCall, MultiQueryReturns};
+
+        let execute = |server: &Arc<Mutex<ManyServer>>, methods: &[&str]| {
+            let args = MultiQueryArgs {
+                calls: methods
+                    .iter()
+                    .map(|method| MultiQueryCall {
+                        method: method.to_string(),
+                        data: minicbor::to_vec(()).unwrap().into(),
+                    })
+                    .collect(),
+            };
+            let request = RequestMessageBuilder::default()
+                .method(MULTI_QUERY_METHOD.to_string())
+                .data(minicbor::to_vec(args).unwrap())
+                .timestamp(Timestamp::now())
+                .build()
+                .unwrap();
+            let envelope = encode_cose_sign1_from_request(request, &AnonymousIdentity).unwrap();
+            let response_e = smol::block_on(server.execute(envelope)).unwrap();
+            decode_response_from_cose_sign1(&response_e, None, &AcceptAllVerifier)
+                .unwrap()
+                .data
+        };
+
+        // Servers without a coordinator refuse multi-queries.
+        let server = ManyServer::test(AnonymousIdentity);
+        assert_eq!(
+            execute(&server, &["status"]).unwrap_err().code(),
+            ManyError::multi_queries_unsupported().code()
+        );
+
+        let counter = Counter::default();
+        {
+            let mut server = server.lock().unwrap();
+            server.add_module(CounterModule(
+                ManyModuleInfo {
+                    name: "CounterModule".to_string(),
+                    attribute: None,
+                    endpoints: vec!["inc".to_string(), "fail".to_string()],
+                },
+                counter.clone(),
+            ));
+            server.set_query_state_coordinator(counter.clone());
+        }
+
+        let data = execute(&server, &["status", "endpoints"]).unwrap();
+        let returns: MultiQueryReturns = minicbor::decode(&data).unwrap();
+        assert_eq!(returns.height, 1);
+        assert_eq!(returns.results.len(), 2);
+        let endpoints: base::Endpoints = minicbor::decode(
+            &Result::<Vec<u8>, ManyError>::from(returns.results[1].clone()).unwrap(),
+        )
+        .unwrap();
+        assert!(endpoints.0.contains(MULTI_QUERY_METHOD));
+
+        // Only queries can be called.
+        assert_eq!(
+            execute(&server, &["status", "fail"]).unwrap_err().code(),
+            ManyError::multi_query_command("").code()
+        );
+        assert_eq!(
+            execute(&server, &["unknown"]).unwrap_err().code(),
+            ManyError::could_not_route_message().code()
+        );
+
+        // The calls are executed again while the state changes.
+        assert_eq!(
+            execute(&server, &["status", "inc"]).unwrap_err().code(),
+            ManyError::multi_query_state_changed(0).code()
+        );
+        assert_eq!(
+            *counter.value.lock().unwrap(),
+            MULTI_QUERY_MAX_ATTEMPTS as u32
+        );
+    }
+
+    /// A module with a single endpoint which takes 200 milliseconds.
+    #[derive(Debug)]
+    struct SlowModule(ManyModuleInfo);
+
+    #[async_trait]
+    impl ManyModule for SlowModule {
+        fn info(&self) -> &ManyModuleInfo {
+            &self.0
+        }
+
+        async fn execute(&self, message: RequestMessage) -> Result<ResponseMessage, ManyError> {
+            smol::Timer::after(Duration::from_millis(200)).await;
+            Ok(ResponseMessage::from_request(
+                &message,
+                &message.to,
+                Ok(vec![]),
+            ))
+        }
+    }
+
+    fn execute_slow(timeout: Option<EndpointTimeout>) -> ResponseMessage {
+        let server = ManyServer::test(AnonymousIdentity);
+        {
+            let mut server = server.lock().unwrap();
+            server.add_module(SlowModule(ManyModuleInfo {
+                name: "SlowModule".to_string(),
+                attribute: None,
+                endpoints: vec!["slow".to_string()],
+            }));
+            if let Some(timeout) = timeout {
+                server.set_endpoint_timeout("slow", timeout);
+            }
+        }
+
+        let request = RequestMessageBuilder::default()
+            .method("slow".to_string())
+            .timestamp(Timestamp::now())
+            .build()
+            .unwrap();
+        let envelope = encode_cose_sign1_from_request(request, &AnonymousIdentity).unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        let response_e = runtime.block_on(server.execute(envelope)).unwrap();
+        decode_response_from_cose_sign1(&response_e, None, &AcceptAllVerifier).unwrap()
+    }
+
+    #[test]
+    fn endpoint_timeout() {
+        assert!(execute_slow(None).data.is_ok());
+        assert!(
+            execute_slow(Some(EndpointTimeout::cancel(Duration::from_secs(10))))
+                .data
+                .is_ok()
+        );
+
+        let response = execute_slow(Some(EndpointTimeout::cancel(Duration::from_millis(10))));
+        assert_eq!(
+            response.data.unwrap_err().code(),
+            ManyError::execution_timeout("", "").code()
+        );
+
+        // Flagged endpoints always complete.
+        let response = execute_slow(Some(EndpointTimeout::flag(Duration::from_millis(10))));
+        assert!(response.data.is_ok());
+    }
+
+    /// A module with a single endpoint which holds 1 MB across an await point.
+    #[cfg(feature = "memory-tracking")]
+    #[derive(Debug)]
+    struct GreedyModule(ManyModuleInfo);
+
+    #[cfg(feature = "memory-tracking")]
+    #[async_trait]
+    impl ManyModule for GreedyModule {
+        fn info(&self) -> &ManyModuleInfo {
+            &self.0
+        }
+
+        async fn execute(&self, message: RequestMessage) -> Result<ResponseMessage, ManyError> {
+            let buffer = vec![1u8; 1_000_000];
+            smol::future::yield_now().await;
+            Ok(ResponseMessage::from_request(
+                &message,
+                &message.to,
+                Ok(buffer[..4].to_vec()),
+            ))
+        }
+    }
+
+    #[cfg(feature = "memory-tracking")]
+    #[test]
+    fn memory_budget() {
+        let execute_greedy = |budget: Option<usize>| {
+            let server = ManyServer::test(AnonymousIdentity);
+            {
+                let mut server = server.lock().unwrap();
+                server.add_module(GreedyModule(ManyModuleInfo {
+                    name: "GreedyModule".to_string(),
+                    attribute: None,
+                    endpoints: vec!["greedy".to_string()],
+                }));
+                server.set_memory_budget(budget);
+            }
+
+            let request = RequestMessageBuilder::default()
+                .method("greedy".to_string())
+                .timestamp(Timestamp::now())
+                .build()
+                .unwrap();
+            let envelope = encode_cose_sign1_from_request(request, &AnonymousIdentity).unwrap();
+            let response_e = smol::block_on(server.execute(envelope)).unwrap();
+            decode_response_from_cose_sign1(&response_e, None, &AcceptAllVerifier).unwrap()
+        };
+
+        assert!(execute_greedy(None).data.is_ok());
+        assert!(execute_greedy(Some(10_000_000)).data.is_ok());
+
+        let rejected_before = memory_metrics()["greedy"].rejected;
+        let response = execute_greedy(Some(100_000));
+        assert_eq!(
+            response.data.unwrap_err().code(),
+            ManyError::memory_budget_exceeded("", "").code()
+        );
+        assert!(memory_metrics()["greedy"].rejected > rejected_before);
+        assert!(memory_metrics()["greedy"].max_peak_bytes >= 1_000_000);
+    }
+
+    #[cfg(feature = "memory-tracking")]
+    #[test]
+    fn endpoint_memory_budget() {
+        use many_types::cbor::CborAny;
+
+        let server = ManyServer::test(AnonymousIdentity);
+        {
+            let mut server = server.lock().unwrap();
+            server.add_module(GreedyModule(ManyModuleInfo {
+                name: "GreedyModule".to_string(),
+                attribute: None,
+                endpoints: vec!["greedy".to_string(), "greedy2".to_string()],
+            }));
+            server.set_memory_budget(Some(100_000));
+            server.set_endpoint_memory_budget("greedy2", 10_000_000);
+        }
+
+        let execute = |method: &str| {
+            let request = RequestMessageBuilder::default()
+                .method(method.to_string())
+                .timestamp(Timestamp::now())
+                .build()
+                .unwrap();
+            let envelope = encode_cose_sign1_from_request(request, &AnonymousIdentity).unwrap();
+            let response_e = smol::block_on(server.execute(envelope)).unwrap();
+            decode_response_from_cose_sign1(&response_e, None, &AcceptAllVerifier).unwrap()
+        };
+
+        assert!(execute("greedy").data.is_err());
+        assert!(execute("greedy2").data.is_ok());
+
+        let status = base::BaseModuleBackend::status(&*server.lock().unwrap()).unwrap();
+        let Some(CborAny::Map(memory)) = status.extras.get("memory") else {
+            panic!("No memory metrics in {:?}", status.extras);
+        };
+        assert!(memory.contains_key(&CborAny::String("greedy2".to_string())));
+    }
 }
@@ -0,0 +1,21 @@
+use many_error::ManyError;
+
+/// Stages the storage writes of the requests of an atomic group, so they can
+/// be discarded if one of the requests fails.
+///
+/// The server executes an atomic group alone; no other request executes until
+/// it is committed or rolled back. Groups can only contain the methods whose
+/// writes the coordinator stages.
+pub trait AtomicGroupCoordinator: Send + Sync {
+    /// Whether the writes of a method are staged by this coordinator.
+    fn supports(&self, method: &str) -> bool;
+
+    /// Start staging the storage writes.
+    fn begin(&self) -> Result<(), ManyError>;
+
+    /// Keep the staged storage writes.
+    fn commit(&self) -> Result<(), ManyError>;
+
+    /// Discard the staged storage writes.
+    fn rollback(&self) -> Result<(), ManyError>;
+}
@@ -0,0 +1,264 @@
+use many_types::cbor::CborAny;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+
+/// An allocator that measures the memory allocated while executing requests.
+///
+/// Measurements only happen when a binary installs it as its global
+/// allocator; otherwise every request measures zero bytes and memory budgets
+/// are never exceeded. `many-ledger` and `many-kvstore` install it when built
+/// with their `memory-tracking` feature.
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: many_server::server::TrackingAllocator =
+///     many_server::server::TrackingAllocator::system();
+/// ```
+#[derive(Debug, Default)]
+pub struct TrackingAllocator<A = System>(A);
+
+impl TrackingAllocator<System> {
+    pub const fn system() -> Self {
+        Self(System)
+    }
+}
+
+impl<A> TrackingAllocator<A> {
+    pub const fn new(inner: A) -> Self {
+        Self(inner)
+    }
+}
+
+/// The allocations of the request being polled on this thread. These are
+/// const-initialized without a destructor, so using them from the allocator
+/// does not allocate.
+#[derive(Clone, Copy, Debug, Default)]
+struct Usage {
+    current: usize,
+    peak: usize,
+}
+
+thread_local! {
+    static TRACKING: Cell<bool> = const { Cell::new(false) };
+    static USAGE: Cell<Usage> = const { Cell::new(Usage { current: 0, peak: 0 }) };
+}
+
+fn record_alloc(size: usize) {
+    let _ = TRACKING.try_with(|tracking| {
+        if tracking.get() {
+            let _ = USAGE.try_with(|usage| {
+                let mut u = usage.get();
+                u.current = u.current.saturating_add(size);
+                u.peak = u.peak.max(u.current);
+                usage.set(u);
+            });
+        }
+    });
+}
+
+fn record_dealloc(size: usize) {
+    let _ = TRACKING.try_with(|tracking| {
+        if tracking.get() {
+            let _ = USAGE.try_with(|usage| {
+                let mut u = usage.get();
+                // Memory allocated before the request started can be freed by it.
+                u.current = u.current.saturating_sub(size);
+                usage.set(u);
+            });
+        }
+    });
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for TrackingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.0.alloc(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.0.dealloc(ptr, layout);
+        record_dealloc(layout.size());
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.0.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = self.0.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record_dealloc(layout.size());
+            record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+static METRICS: Mutex<BTreeMap<String, MemoryMetrics>> = Mutex::new(BTreeMap::new());
+
+/// Memory metrics of the requests to an endpoint, executed by all servers of
+/// this process.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MemoryMetrics {
+    /// The number of requests measured.
+    pub requests: u64,
+
+    /// The number of requests rejected for exceeding their memory budget.
+    pub rejected: u64,
+
+    /// The sum of the peak allocations of the requests, in bytes.
+    pub total_peak_bytes: u64,
+
+    /// The highest peak allocation of a single request, in bytes.
+    pub max_peak_bytes: usize,
+}
+
+/// The memory metrics of every endpoint which executed a request.
+pub fn memory_metrics() -> BTreeMap<String, MemoryMetrics> {
+    METRICS.lock().unwrap().clone()
+}
+
+/// The memory metrics of every endpoint, as returned by `status` in its
+/// `memory` extra.
+pub(crate) fn metrics_to_cbor(metrics: &BTreeMap<String, MemoryMetrics>) -> CborAny {
+    let int = |i: u64| CborAny::Int(i.try_into().unwrap_or(i64::MAX));
+    CborAny::Map(
+        metrics
+            .iter()
+            .map(|(method, m)| {
+                (
+                    CborAny::String(method.clone()),
+                    CborAny::Map(BTreeMap::from([
+                        (CborAny::String("requests".to_string()), int(m.requests)),
+                        (CborAny::String("rejected".to_string()), int(m.rejected)),
+                        (
+                            CborAny::String("totalPeakBytes".to_string()),
+                            int(m.total_peak_bytes),
+                        ),
+                        (
+                            CborAny::String("maxPeakBytes".to_string()),
+                            int(m.max_peak_bytes as u64),
+                        ),
+                    ])),
+                )
+            })
+            .collect(),
+    )
+}
+
+/// Record the peak allocation of a request. This must not be called while a
+/// request is measured, since it allocates.
+pub(crate) fn record_request(method: &str, peak: usize, rejected: bool) {
+    let mut metrics = METRICS.lock().unwrap();
+    let m = metrics.entry(method.to_string()).or_default();
+    m.requests += 1;
+    if rejected {
+        m.rejected += 1;
+    }
+    m.total_peak_bytes = m.total_peak_bytes.saturating_add(peak as u64);
+    m.max_peak_bytes = m.max_peak_bytes.max(peak);
+}
+
+/// A future that measures the memory allocated while it is polled.
+///
+/// The usage is swapped in and out of the thread on every poll, so the
+/// measurement follows the future if it moves between threads. Allocations
+/// made by other threads on its behalf (e.g. a thread pool) are not counted.
+pub(crate) struct Measured<F> {
+    future: Pin<Box<F>>,
+    usage: Usage,
+    budget: Option<usize>,
+}
+
+/// The result of a measured future. `None` if it was cancelled for exceeding
+/// its budget.
+pub(crate) struct Measurement<T> {
+    pub output: Option<T>,
+    pub peak: usize,
+}
+
+impl<F: Future> Future for Measured<F> {
+    type Output = Measurement<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let was_tracking = TRACKING.with(|t| t.replace(true));
+        let outer = USAGE.with(|u| u.replace(this.usage));
+
+        let poll = this.future.as_mut().poll(cx);
+
+        this.usage = USAGE.with(|u| u.replace(outer));
+        TRACKING.with(|t| t.set(was_tracking));
+
+        let peak = this.usage.peak;
+        match poll {
+            Poll::Ready(output) => Poll::Ready(Measurement {
+                output: Some(output),
+                peak,
+            }),
+            // Stop polling (cancel) at the first await point over budget.
+            Poll::Pending if this.budget.map_or(false, |b| peak > b) => {
+                Poll::Ready(Measurement { output: None, peak })
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+pub(crate) fn measure<F: Future>(future: F, budget: Option<usize>) -> Measured<F> {
+    Measured {
+        future: Box::pin(future),
+        usage: Usage::default(),
+        budget,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[global_allocator]
+    static ALLOCATOR: TrackingAllocator = TrackingAllocator::system();
+
+    #[test]
+    fn measures_peak() {
+        let m = smol::block_on(measure(
+            async {
+                let v = vec![0u8; 100_000];
+                drop(v);
+                let v = vec![0u8; 10_000];
+                v.len()
+            },
+            None,
+        ));
+        assert_eq!(m.output, Some(10_000));
+        assert!(m.peak >= 100_000);
+        assert!(m.peak < 110_000);
+    }
+
+    #[test]
+    fn cancels_over_budget() {
+        let m = smol::block_on(measure(
+            async {
+                let v = vec![0u8; 100_000];
+                smol::future::yield_now().await;
+                v.len()
+            },
+            Some(50_000),
+        ));
+        assert_eq!(m.output, None);
+        assert!(m.peak >= 100_000);
+    }
+}
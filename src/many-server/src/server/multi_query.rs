@@ -0,0 +1,187 @@
+use super::{execute_module, ManyServer};
+use coset::CoseSign1;
+use many_error::ManyError;
+use many_modules::base::{
+    EndpointSchema, MultiQueryArgs, MultiQueryCall, MultiQueryResult, MultiQueryReturns,
+};
+use many_modules::{ManyModule, ManyModuleInfo};
+use many_protocol::{RequestMessage, ResponseMessage};
+use many_types::cddl::Cddl;
+use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+
+pub const MULTI_QUERY_METHOD: &str = "base.multiQuery";
+
+/// The number of times the calls of a multi-query are executed before giving
+/// up, if the state keeps changing while they execute.
+pub const MULTI_QUERY_MAX_ATTEMPTS: usize = 3;
+
+static MULTI_QUERY_MODULE_INFO: Lazy<ManyModuleInfo> = Lazy::new(|| ManyModuleInfo {
+    name: "MultiQueryModule".to_string(),
+    attribute: None,
+    endpoints: vec![MULTI_QUERY_METHOD.to_string()],
+});
+
+/// A version of the state read by queries.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QueryState {
+    /// The height of the latest block.
+    pub height: u64,
+
+    /// The hash of the state, which changes whenever the state does.
+    pub hash: Vec<u8>,
+}
+
+/// Tells which endpoints only read the state, and which state they read, so
+/// the calls of a multi-query can be executed against the same state.
+pub trait QueryStateCoordinator: Send + Sync {
+    /// Whether `method` only reads the state.
+    fn is_query(&self, method: &str) -> bool;
+
+    /// The current state.
+    fn state(&self) -> Result<QueryState, ManyError>;
+}
+
+/// The request message of a call of a multi-query, sent by the sender of the
+/// multi-query.
+fn call_message(message: &RequestMessage, call: MultiQueryCall) -> RequestMessage {
+    RequestMessage {
+        method: call.method,
+        data: call.data.into(),
+        attributes: Default::default(),
+        ..message.clone()
+    }
+}
+
+fn decode_args(message: &RequestMessage) -> Result<MultiQueryArgs, ManyError> {
+    minicbor::decode(&message.data).map_err(|e| {
+        ManyError::deserialization_error(many_types::cbor::decode_error_details(&message.data, &e))
+    })
+}
+
+impl ManyServer {
+    /// Validate the calls of a multi-query as if they were sent in `envelope`.
+    /// Only read-only endpoints of the modules of this server can be called.
+    pub(super) fn validate_multi_query(
+        &self,
+        message: &RequestMessage,
+        envelope: &CoseSign1,
+    ) -> Result<(), ManyError> {
+        let coordinator = self
+            .query_state
+            .as_ref()
+            .ok_or_else(ManyError::multi_queries_unsupported)?;
+
+        for call in decode_args(message)?.calls {
+            let call = call_message(message, call);
+            if self.disabled_endpoints.contains(&call.method) {
+                return Err(ManyError::invalid_method_name(call.method));
+            }
+            if !coordinator.is_query(&call.method) {
+                return Err(ManyError::multi_query_command(call.method));
+            }
+            self.find_module(&call)
+                .ok_or_else(ManyError::could_not_route_message)?
+                .validate(&call, envelope)?;
+        }
+        Ok(())
+    }
+}
+
+/// Executes several read-only endpoint calls against the same state, so
+/// clients get a consistent view of it without racing blocks. The calls are
+/// executed again if the state changed while they were executing.
+///
+/// Servers built with `ManyServer::simple` expose it alongside the base
+/// module. It needs a [QueryStateCoordinator].
+pub struct MultiQueryModule {
+    server: Arc<Mutex<ManyServer>>,
+}
+
+impl MultiQueryModule {
+    pub fn new(server: Arc<Mutex<ManyServer>>) -> Self {
+        Self { server }
+    }
+}
+
+impl Debug for MultiQueryModule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("MultiQueryModule")
+    }
+}
+
+#[async_trait::async_trait]
+impl ManyModule for MultiQueryModule {
+    fn info(&self) -> &ManyModuleInfo {
+        &MULTI_QUERY_MODULE_INFO
+    }
+
+    /// The calls are validated by the server, which needs to be locked to
+    /// find their modules.
+    fn validate(&self, message: &RequestMessage, _envelope: &CoseSign1) -> Result<(), ManyError> {
+        decode_args(message).map(|_| ())
+    }
+
+    fn schemas(&self) -> BTreeMap<String, EndpointSchema> {
+        BTreeMap::from([(
+            MULTI_QUERY_METHOD.to_string(),
+            EndpointSchema {
+                argument: MultiQueryArgs::cddl(),
+                returns: MultiQueryReturns::cddl(),
+            },
+        )])
+    }
+
+    async fn execute(&self, message: RequestMessage) -> Result<ResponseMessage, ManyError> {
+        let args = decode_args(&message)?;
+        let (coordinator, calls) = {
+            let server = self.server.lock().unwrap();
+            let coordinator = server
+                .query_state
+                .clone()
+                .ok_or_else(ManyError::multi_queries_unsupported)?;
+            let calls = args
+                .calls
+                .into_iter()
+                .map(|call| {
+                    let call = call_message(&message, call);
+                    let module = server
+                        .find_module(&call)
+                        .ok_or_else(ManyError::could_not_route_message)?;
+                    Ok((module, server.endpoint_limits(&call.method), call))
+                })
+                .collect::<Result<Vec<_>, ManyError>>()?;
+            (coordinator, calls)
+        };
+
+        for _ in 0..MULTI_QUERY_MAX_ATTEMPTS {
+            let state = coordinator.state()?;
+            let mut results = Vec::with_capacity(calls.len());
+            for (module, limits, call) in &calls {
+                let result = execute_module(module, call.clone(), *limits)
+                    .await
+                    .and_then(|response| response.data);
+                results.push(MultiQueryResult::from(result));
+            }
+
+            if coordinator.state()? == state {
+                let returns = MultiQueryReturns {
+                    height: state.height,
+                    results,
+                };
+                let data = minicbor::to_vec(returns).map_err(ManyError::serialization_error)?;
+                return Ok(ResponseMessage::from_request(
+                    &message,
+                    &message.to,
+                    Ok(data),
+                ));
+            }
+        }
+
+        Err(ManyError::multi_query_state_changed(
+            MULTI_QUERY_MAX_ATTEMPTS,
+        ))
+    }
+}
@@ -0,0 +1,217 @@
+use coset::CoseSign1;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::rejections::{self, ListArgs, ListReturns, Rejection};
+use many_protocol::RequestMessage;
+use many_types::Timestamp;
+use minicbor::bytes::ByteVec;
+use std::collections::{BTreeSet, VecDeque};
+
+/// The number of rejections kept by [RejectionLog::default].
+pub const REJECTION_LOG_DEFAULT_CAPACITY: usize = 1000;
+
+/// The most recent requests refused by a server, oldest first. When full, the
+/// oldest rejection is dropped for each new one.
+///
+/// The log serves `rejections.list` to its operators. It can be shared between
+/// a server and the consensus layer in front of it, so rejections by both are
+/// listed together.
+#[derive(Debug)]
+pub struct RejectionLog {
+    capacity: usize,
+    rejections: VecDeque<Rejection>,
+    dropped: u64,
+    operators: BTreeSet<Address>,
+}
+
+impl Default for RejectionLog {
+    fn default() -> Self {
+        Self::new(REJECTION_LOG_DEFAULT_CAPACITY)
+    }
+}
+
+impl RejectionLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            rejections: VecDeque::with_capacity(capacity),
+            dropped: 0,
+            operators: BTreeSet::new(),
+        }
+    }
+
+    /// Allow these addresses to list the rejections, in addition to the
+    /// identity of the server.
+    pub fn with_operators(mut self, operators: impl IntoIterator<Item = Address>) -> Self {
+        self.operators.extend(operators);
+        self
+    }
+
+    pub fn add_operator(&mut self, operator: Address) {
+        self.operators.insert(operator);
+    }
+
+    pub fn len(&self) -> usize {
+        self.rejections.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rejections.is_empty()
+    }
+
+    /// Record a rejected request. The message is `None` if it could not be
+    /// decoded.
+    pub fn record(&mut self, message: Option<&RequestMessage>, reason: impl ToString) {
+        if self.capacity == 0 {
+            self.dropped += 1;
+            return;
+        }
+        if self.rejections.len() == self.capacity {
+            self.rejections.pop_front();
+            self.dropped += 1;
+        }
+
+        let rejection = Rejection {
+            time: Timestamp::now(),
+            reason: reason.to_string(),
+            sender: message.and_then(|m| m.from),
+            method: message.map(|m| m.method.clone()),
+            correlation_id: message
+                .and_then(|m| m.intent_hash().ok())
+                .map(ByteVec::from),
+        };
+        tracing::debug!(
+            reason = rejection.reason.as_str(),
+            method = ?rejection.method,
+            "Request rejected"
+        );
+        self.rejections.push_back(rejection);
+    }
+
+    /// Record a rejected request from its envelope, without verifying it.
+    pub fn record_envelope(&mut self, envelope: &CoseSign1, reason: impl ToString) {
+        let message = RequestMessage::try_from(envelope).ok();
+        self.record(message.as_ref(), reason);
+    }
+
+    fn verify_operator(&self, sender: &Address) -> Result<(), ManyError> {
+        if self.operators.contains(sender) {
+            Ok(())
+        } else {
+            Err(rejections::not_an_operator(sender.to_string()))
+        }
+    }
+}
+
+impl rejections::RejectionsModuleBackend for RejectionLog {
+    fn list(&self, sender: &Address, args: ListArgs) -> Result<ListReturns, ManyError> {
+        self.verify_operator(sender)?;
+
+        let ListArgs { count, sender } = args;
+        let mut rejections: Vec<Rejection> = self
+            .rejections
+            .iter()
+            .rev()
+            .filter(|r| sender.is_none() || r.sender == sender)
+            .take(count.map_or(usize::MAX, |c| c as usize))
+            .cloned()
+            .collect();
+        rejections.reverse();
+
+        Ok(ListReturns {
+            rejections,
+            dropped: self.dropped,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use many_identity::testing::identity;
+    use many_modules::rejections::RejectionsModuleBackend;
+    use many_protocol::RequestMessageBuilder;
+
+    fn message(from: Address, method: &str) -> RequestMessage {
+        RequestMessageBuilder::default()
+            .from(from)
+            .method(method.to_string())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn ring_buffer() {
+        let mut log = RejectionLog::new(2).with_operators([identity(0)]);
+        log.record(Some(&message(identity(1), "ledger.send")), "first");
+        log.record(None, "second");
+        log.record(Some(&message(identity(2), "ledger.send")), "third");
+        assert_eq!(log.len(), 2);
+
+        let ListReturns {
+            rejections,
+            dropped,
+        } = log.list(&identity(0), ListArgs::default()).unwrap();
+        assert_eq!(dropped, 1);
+        assert_eq!(
+            rejections
+                .iter()
+                .map(|r| r.reason.as_str())
+                .collect::<Vec<_>>(),
+            ["second", "third"]
+        );
+        assert_eq!(rejections[0].sender, None);
+        assert_eq!(rejections[1].sender, Some(identity(2)));
+        assert_eq!(rejections[1].method.as_deref(), Some("ledger.send"));
+        assert_eq!(
+            rejections[1].correlation_id.as_ref().map(|id| id.to_vec()),
+            message(identity(2), "ledger.send").intent_hash().ok()
+        );
+    }
+
+    #[test]
+    fn list_filters() {
+        let mut log = RejectionLog::default().with_operators([identity(0)]);
+        for i in 0..5 {
+            log.record(Some(&message(identity(1 + i % 2), "ledger.send")), i);
+        }
+
+        let returns = log
+            .list(
+                &identity(0),
+                ListArgs {
+                    count: Some(2),
+                    sender: Some(identity(1)),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            returns
+                .rejections
+                .iter()
+                .map(|r| r.reason.as_str())
+                .collect::<Vec<_>>(),
+            ["2", "4"]
+        );
+
+        let returns = log
+            .list(
+                &identity(0),
+                ListArgs {
+                    count: Some(0),
+                    sender: None,
+                },
+            )
+            .unwrap();
+        assert!(returns.rejections.is_empty());
+    }
+
+    #[test]
+    fn operators_only() {
+        let log = RejectionLog::default().with_operators([identity(0)]);
+        assert!(log.list(&identity(1), ListArgs::default()).is_err());
+        assert!(log
+            .list(&Address::anonymous(), ListArgs::default())
+            .is_err());
+    }
+}
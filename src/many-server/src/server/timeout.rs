@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+/// What to do when the execution of an endpoint exceeds its time limit.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TimeoutAction {
+    /// Stop polling the execution and return a timeout error. Cancellation
+    /// happens at an await point; a backend that blocks cannot be interrupted.
+    /// The server must run on a Tokio runtime with its time driver enabled.
+    Cancel,
+
+    /// Let the execution complete, but log it. Use this for endpoints whose
+    /// execution must be deterministic across nodes (e.g. commands executed
+    /// through consensus), where cancelling could fork the state.
+    Flag,
+}
+
+/// The time limit of an endpoint.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EndpointTimeout {
+    pub limit: Duration,
+    pub action: TimeoutAction,
+}
+
+impl EndpointTimeout {
+    pub fn cancel(limit: Duration) -> Self {
+        Self {
+            limit,
+            action: TimeoutAction::Cancel,
+        }
+    }
+
+    pub fn flag(limit: Duration) -> Self {
+        Self {
+            limit,
+            action: TimeoutAction::Flag,
+        }
+    }
+}
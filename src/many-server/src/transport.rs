@@ -9,6 +9,11 @@ pub mod http;
 #[async_trait]
 pub trait LowLevelManyRequestHandler: Send + Sync + Debug {
     async fn execute(&self, envelope: CoseSign1) -> Result<CoseSign1, String>;
+
+    /// Persist any buffered state before the transport exits.
+    async fn flush(&self) -> Result<(), String> {
+        Ok(())
+    }
 }
 
 /// A simpler version of the [ManyRequestHandler] which only deals with methods and payloads.
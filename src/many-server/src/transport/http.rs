@@ -1,18 +1,135 @@
 use crate::transport::LowLevelManyRequestHandler;
 use anyhow::anyhow;
 use coset::{CoseSign1, TaggedCborSerializable};
+use many_protocol::RequestMessage;
+use many_types::priority::PriorityHint;
+use std::cmp::Reverse;
 use std::fmt::Debug;
 use std::io::Cursor;
 use std::net::ToSocketAddrs;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tiny_http::{Request, Response};
 use tracing::info;
 
 /// Maximum of 5MB per HTTP request.
 const READ_BUFFER_LEN: usize = 1024 * 1024 * 5;
 
+/// Default time given to queued requests when draining the server.
+pub const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The maximum number of queued requests ordered by their priority hint at
+/// once. A low priority request waits for at most this many requests.
+const MAX_PRIORITIZED_REQUESTS: usize = 64;
+
+/// The maximum number of body bytes of the requests ordered by their priority
+/// hint at once. Requests are read before being ordered, so this bounds the
+/// memory they use.
+const MAX_PRIORITIZED_BYTES: usize = 4 * READ_BUFFER_LEN;
+
+type HttpResponse = Response<Cursor<Vec<u8>>>;
+
+/// The priority hint of a request envelope. Batches, and envelopes whose
+/// message cannot be decoded, have a normal priority.
+fn priority_hint(envelope: &CoseSign1) -> PriorityHint {
+    if many_protocol::is_request_batch_envelope(envelope) {
+        return PriorityHint::default();
+    }
+    RequestMessage::try_from(envelope).map_or_else(
+        |_| PriorityHint::default(),
+        |message| PriorityHint::of(&message.attributes),
+    )
+}
+
+/// Decode the envelope in the body of a request, or the response refusing it.
+fn decode_envelope(bytes: &[u8]) -> Result<CoseSign1, HttpResponse> {
+    tracing::debug!("request  len={}", bytes.len());
+    tracing::trace!("request  {}", hex::encode(bytes));
+
+    CoseSign1::from_tagged_slice(bytes).map_err(|e| {
+        tracing::error!(
+            r#"Error decoding envelope. Error description="{}""#,
+            e.to_string()
+        );
+        Response::empty(500u16).with_data(Cursor::new(vec![]), Some(0))
+    })
+}
+
+/// Read the body of a request, or the response refusing it.
+fn read_body(request: &mut Request) -> Result<Vec<u8>, HttpResponse> {
+    match request.body_length() {
+        Some(x) if x > READ_BUFFER_LEN => {
+            // This is a transport error, and as such an HTTP error.
+            // Return a "413: Content Too Large" error.
+            tracing::error!("413: Content Too Large : {x} bytes");
+            return Err(Response::empty(413u16).with_data(Cursor::new(vec![]), Some(0)));
+        }
+        _ => {}
+    }
+
+    let mut v = Vec::new();
+    let _ = request.as_reader().read_to_end(&mut v);
+    Ok(v)
+}
+
+/// A request taken from the queue, with its envelope decoded once.
+struct QueuedRequest {
+    request: Request,
+    envelope: Result<CoseSign1, HttpResponse>,
+    priority: PriorityHint,
+    len: usize,
+}
+
+impl QueuedRequest {
+    fn read(mut request: Request) -> Self {
+        let (envelope, len) = match read_body(&mut request) {
+            Ok(bytes) => (decode_envelope(&bytes), bytes.len()),
+            Err(response) => (Err(response), 0),
+        };
+        let priority = envelope
+            .as_ref()
+            .map_or_else(|_| PriorityHint::default(), priority_hint);
+        Self {
+            request,
+            envelope,
+            priority,
+            len,
+        }
+    }
+}
+
+/// A signal to drain and stop a server. Cloning it returns a handle to the
+/// same signal.
+#[derive(Clone, Debug, Default)]
+pub struct Drain(Arc<AtomicBool>);
+
+impl Drain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the server to stop accepting requests and exit.
+    pub fn drain(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// The underlying flag, e.g. to register it with `signal_hook`.
+    pub fn signal(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.0)
+    }
+}
+
+impl From<Arc<AtomicBool>> for Drain {
+    fn from(signal: Arc<AtomicBool>) -> Self {
+        Self(signal)
+    }
+}
+
 #[derive(Debug)]
 pub struct HttpServer<E: LowLevelManyRequestHandler> {
     executor: E,
@@ -27,36 +144,14 @@ impl<E: LowLevelManyRequestHandler> HttpServer<E> {
         }
     }
 
-    async fn handle_request(&self, request: &mut Request) -> Response<std::io::Cursor<Vec<u8>>> {
-        match request.body_length() {
-            Some(x) if x > READ_BUFFER_LEN => {
-                // This is a transport error, and as such an HTTP error.
-                // Return a "413: Content Too Large" error.
-                tracing::error!("413: Content Too Large : {x} bytes");
-                return Response::empty(413u16).with_data(Cursor::new(vec![]), Some(0));
-            }
-            _ => {}
+    async fn handle_request(&self, request: &mut Request) -> HttpResponse {
+        match read_body(request).and_then(|bytes| decode_envelope(&bytes)) {
+            Ok(envelope) => self.handle_envelope(envelope).await,
+            Err(response) => response,
         }
+    }
 
-        let mut v = Vec::new();
-        let _ = request.as_reader().read_to_end(&mut v);
-
-        let bytes = &v;
-
-        tracing::debug!("request  len={}", bytes.len());
-        tracing::trace!("request  {}", hex::encode(bytes));
-
-        let envelope = match CoseSign1::from_tagged_slice(bytes) {
-            Ok(cs) => cs,
-            Err(e) => {
-                tracing::error!(
-                    r#"Error decoding envelope. Error description="{}""#,
-                    e.to_string()
-                );
-                return Response::empty(500u16).with_data(Cursor::new(vec![]), Some(0));
-            }
-        };
-
+    async fn handle_envelope(&self, envelope: CoseSign1) -> HttpResponse {
         let response = self
             .executor
             .execute(envelope)
@@ -81,25 +176,224 @@ impl<E: LowLevelManyRequestHandler> HttpServer<E> {
         Arc::clone(&self.term_signal)
     }
 
+    /// Serve requests until the term signal is set, then drain the server
+    /// with the default timeout. See [HttpServer::bind_with_shutdown].
     pub async fn bind<A: ToSocketAddrs>(&self, addr: A) -> Result<(), anyhow::Error> {
+        self.bind_with_shutdown(
+            addr,
+            Drain::from(Arc::clone(&self.term_signal)),
+            DEFAULT_DRAIN_TIMEOUT,
+        )
+        .await
+    }
+
+    /// Serve requests until `drain` is signaled. The requests queued at the
+    /// same time are executed by priority, see [PriorityHint]. Requests with
+    /// the same priority keep the order they were received in.
+    ///
+    /// Once drained, the server stops listening, and executes the requests it
+    /// already received until `timeout` expires; the remaining ones get a
+    /// "503: Service Unavailable" response. A request being executed is never
+    /// interrupted. Finally, the executor is flushed.
+    pub async fn bind_with_shutdown<A: ToSocketAddrs>(
+        &self,
+        addr: A,
+        drain: Drain,
+        timeout: Duration,
+    ) -> Result<(), anyhow::Error> {
         let server = tiny_http::Server::http(addr).map_err(|e| anyhow!("{}", e))?;
 
-        loop {
-            if let Some(mut request) = server.recv_timeout(Duration::from_millis(100))? {
-                let response = self.handle_request(&mut request).await;
+        while !drain.is_draining() {
+            if let Some(request) = server.recv_timeout(Duration::from_millis(100))? {
+                let mut queued = vec![QueuedRequest::read(request)];
+                let mut buffered = queued[0].len;
+                while queued.len() < MAX_PRIORITIZED_REQUESTS && buffered < MAX_PRIORITIZED_BYTES {
+                    match server.try_recv()? {
+                        Some(request) => {
+                            let request = QueuedRequest::read(request);
+                            buffered += request.len;
+                            queued.push(request);
+                        }
+                        None => break,
+                    }
+                }
+
+                // The sort is stable.
+                queued.sort_by_key(|q| Reverse(q.priority));
+                for QueuedRequest {
+                    request, envelope, ..
+                } in queued
+                {
+                    let response = match envelope {
+                        Ok(envelope) => self.handle_envelope(envelope).await,
+                        Err(response) => response,
+                    };
 
-                // If there's a transport error (e.g. connection closed) on the response itself,
-                // we don't actually care and just continue waiting for the next request.
-                let _ = request.respond(response);
+                    // If there's a transport error (e.g. connection closed) on the response itself,
+                    // we don't actually care and just continue waiting for the next request.
+                    let _ = request.respond(response);
+                }
             }
+        }
 
-            // Check for the term signal and break out.
-            if self.term_signal.load(Ordering::Relaxed) {
-                info!("Server shutting down gracefully...");
-                break;
+        info!("Server draining...");
+        let deadline = Instant::now() + timeout;
+
+        // Take the requests already received, and stop listening so no new
+        // request is accepted while they are executed.
+        let mut received = Vec::new();
+        while Instant::now() < deadline {
+            match server.try_recv()? {
+                Some(request) => received.push(request),
+                None => break,
             }
         }
+        drop(server);
 
+        for mut request in received {
+            let response = if Instant::now() < deadline {
+                self.handle_request(&mut request).await
+            } else {
+                Response::empty(503u16).with_data(Cursor::new(vec![]), Some(0))
+            };
+            let _ = request.respond(response);
+        }
+
+        self.executor.flush().await.map_err(|e| anyhow!("{}", e))?;
+        info!("Server shut down gracefully.");
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use many_types::attributes::Attribute;
+    use many_types::cbor::CborAny;
+    use many_types::priority::PRIORITY;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::atomic::AtomicUsize;
+
+    /// Echoes envelopes, and drains the server after the first request.
+    #[derive(Debug)]
+    struct Executor {
+        drain: Drain,
+        executed: AtomicUsize,
+        flushed: AtomicBool,
+    }
+
+    #[async_trait]
+    impl LowLevelManyRequestHandler for Arc<Executor> {
+        async fn execute(&self, envelope: CoseSign1) -> Result<CoseSign1, String> {
+            self.executed.fetch_add(1, Ordering::Relaxed);
+            self.drain.drain();
+            Ok(envelope)
+        }
+
+        async fn flush(&self) -> Result<(), String> {
+            self.flushed.store(true, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    fn executor() -> Arc<Executor> {
+        Arc::new(Executor {
+            drain: Drain::new(),
+            executed: AtomicUsize::new(0),
+            flushed: AtomicBool::new(false),
+        })
+    }
+
+    fn free_addr() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().to_string()
+    }
+
+    #[test]
+    fn priority_hints() {
+        use many_identity::AnonymousIdentity;
+        use many_protocol::RequestMessageBuilder;
+
+        let envelope = |attributes: Vec<Attribute>| {
+            let mut message = RequestMessageBuilder::default()
+                .method("echo".to_string())
+                .build()
+                .unwrap();
+            for attribute in attributes {
+                message = message.with_attribute(attribute);
+            }
+            many_protocol::encode_cose_sign1_from_request(message, &AnonymousIdentity).unwrap()
+        };
+
+        assert_eq!(priority_hint(&envelope(vec![])), PriorityHint::Normal);
+        assert_eq!(
+            priority_hint(&envelope(vec![PriorityHint::Low.into()])),
+            PriorityHint::Low
+        );
+        assert_eq!(
+            priority_hint(&envelope(vec![PRIORITY.with_argument(CborAny::Int(5))])),
+            PriorityHint::Normal
+        );
+        assert!(decode_envelope(b"not an envelope").is_err());
+    }
+
+    #[test]
+    fn drained_before_start() {
+        let executor = executor();
+        executor.drain.drain();
+        let server = HttpServer::new(executor.clone());
+
+        smol::block_on(server.bind_with_shutdown(
+            free_addr(),
+            executor.drain.clone(),
+            Duration::from_secs(1),
+        ))
+        .unwrap();
+        assert!(executor.flushed.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn in_flight_request_completes() {
+        let executor = executor();
+        let server = HttpServer::new(executor.clone());
+        let addr = free_addr();
+
+        let client = {
+            let addr = addr.clone();
+            std::thread::spawn(move || {
+                let body = CoseSign1::default().to_tagged_vec().unwrap();
+                let mut stream = loop {
+                    match TcpStream::connect(&addr) {
+                        Ok(stream) => break stream,
+                        Err(_) => std::thread::sleep(Duration::from_millis(10)),
+                    }
+                };
+                write!(
+                    stream,
+                    "POST / HTTP/1.1\r\nHost: {addr}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )
+                .unwrap();
+                stream.write_all(&body).unwrap();
+
+                // The body is CBOR, so only the status line is text.
+                let mut response = Vec::new();
+                let _ = stream.read_to_end(&mut response);
+                String::from_utf8_lossy(&response).to_string()
+            })
+        };
+
+        smol::block_on(server.bind_with_shutdown(
+            addr,
+            executor.drain.clone(),
+            Duration::from_secs(1),
+        ))
+        .unwrap();
+
+        assert!(client.join().unwrap().starts_with("HTTP/1.1 200"));
+        assert_eq!(executor.executed.load(Ordering::Relaxed), 1);
+        assert!(executor.flushed.load(Ordering::Relaxed));
+    }
+}
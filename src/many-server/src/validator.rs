@@ -14,6 +14,14 @@ pub trait RequestValidator {
     fn validate_request(&self, _request: &RequestMessage) -> Result<(), ManyError> {
         Ok(())
     }
+
+    /// Returns the response of a request that was already executed, to send
+    /// back instead of executing it again. This is called after verifying the
+    /// signature of the envelope, but before validating it.
+    fn cached_response(&self, _envelope: &CoseSign1) -> Option<ResponseMessage> {
+        None
+    }
+
     fn message_executed(
         &mut self,
         _request_envelope: &CoseSign1,
@@ -21,6 +29,11 @@ pub trait RequestValidator {
     ) -> Result<(), ManyError> {
         Ok(())
     }
+
+    /// Persist any buffered state. This is called when the server shuts down.
+    fn flush(&mut self) -> Result<(), ManyError> {
+        Ok(())
+    }
 }
 
 /// A RequestValidator that does not run message_executed(), but only validate
@@ -40,6 +53,12 @@ impl<T: RequestValidator> RequestValidator for ValidateOnlyRequestValidator<T> {
     fn validate_request(&self, request: &RequestMessage) -> Result<(), ManyError> {
         self.0.validate_request(request)
     }
+    fn cached_response(&self, envelope: &CoseSign1) -> Option<ResponseMessage> {
+        self.0.cached_response(envelope)
+    }
+    fn flush(&mut self) -> Result<(), ManyError> {
+        self.0.flush()
+    }
 }
 
 impl RequestValidator for () {}
@@ -51,6 +70,9 @@ impl<A: RequestValidator + ?Sized> RequestValidator for Box<A> {
     fn validate_request(&self, request: &RequestMessage) -> Result<(), ManyError> {
         self.as_ref().validate_request(request)
     }
+    fn cached_response(&self, envelope: &CoseSign1) -> Option<ResponseMessage> {
+        self.as_ref().cached_response(envelope)
+    }
     fn message_executed(
         &mut self,
         request_envelope: &CoseSign1,
@@ -58,6 +80,9 @@ impl<A: RequestValidator + ?Sized> RequestValidator for Box<A> {
     ) -> Result<(), ManyError> {
         self.as_mut().message_executed(request_envelope, response)
     }
+    fn flush(&mut self) -> Result<(), ManyError> {
+        self.as_mut().flush()
+    }
 }
 
 impl<A, B> RequestValidator for (A, B)
@@ -73,6 +98,11 @@ where
         self.0.validate_request(request)?;
         self.1.validate_request(request)
     }
+    fn cached_response(&self, envelope: &CoseSign1) -> Option<ResponseMessage> {
+        self.0
+            .cached_response(envelope)
+            .or_else(|| self.1.cached_response(envelope))
+    }
     fn message_executed(
         &mut self,
         envelope: &CoseSign1,
@@ -81,4 +111,8 @@ where
         self.0.message_executed(envelope, response)?;
         self.1.message_executed(envelope, response)
     }
+    fn flush(&mut self) -> Result<(), ManyError> {
+        self.0.flush()?;
+        self.1.flush()
+    }
 }
@@ -1,11 +1,13 @@
 use crate::cbor::CborAny;
+use alloc::collections::BTreeSet;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::Debug;
 use many_error::ManyError;
 use minicbor::data::Type;
 use minicbor::encode::{Error, Write};
 use minicbor::{Decode, Decoder, Encode, Encoder};
-use std::cmp::Ordering;
-use std::collections::BTreeSet;
-use std::fmt::Debug;
 
 /// An attribute ID.
 pub type AttributeId = u32;
@@ -14,6 +16,12 @@ pub type AttributeId = u32;
 #[cbor(transparent)]
 pub struct AttributeSet(#[n(0)] BTreeSet<Attribute>);
 
+impl crate::cddl::Cddl for AttributeSet {
+    fn cddl() -> alloc::string::String {
+        <BTreeSet<Attribute> as crate::cddl::Cddl>::cddl()
+    }
+}
+
 impl AttributeSet {
     pub fn new() -> Self {
         Default::default()
@@ -43,7 +51,7 @@ impl AttributeSet {
         TryFromAttributeSet::try_from_set(self)
     }
 
-    pub fn iter(&self) -> std::collections::btree_set::Iter<Attribute> {
+    pub fn iter(&self) -> alloc::collections::btree_set::Iter<Attribute> {
         self.0.iter()
     }
 }
@@ -54,7 +62,7 @@ pub trait TryFromAttributeSet: Sized {
 
 impl IntoIterator for AttributeSet {
     type Item = Attribute;
-    type IntoIter = std::collections::btree_set::IntoIter<Attribute>;
+    type IntoIter = alloc::collections::btree_set::IntoIter<Attribute>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.0.into_iter()
@@ -79,6 +87,12 @@ pub struct Attribute {
     pub arguments: Vec<CborAny>,
 }
 
+impl crate::cddl::Cddl for Attribute {
+    fn cddl() -> alloc::string::String {
+        "uint / [uint, * any]".into()
+    }
+}
+
 impl Attribute {
     pub const fn id(id: AttributeId) -> Self {
         Self {
@@ -1,4 +1,6 @@
 use crate::{CborRange, Timestamp};
+use alloc::vec;
+use alloc::vec::Vec;
 use minicbor::encode::{Error, Write};
 use minicbor::{decode, Decode, Decoder, Encode, Encoder};
 
@@ -1,9 +1,15 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use base64::{engine::general_purpose, Engine as _};
+use core::fmt::{Debug, Formatter};
 use minicbor::data::{Tag, Type};
 use minicbor::encode::{Error, Write};
 use minicbor::{Decode, Decoder, Encode, Encoder};
-use std::collections::BTreeMap;
-use std::fmt::{Debug, Formatter};
+
+pub mod canonical;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CborNull;
@@ -39,7 +45,7 @@ pub enum CborAny {
 }
 
 impl Debug for CborAny {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
             CborAny::Bool(b) => write!(f, "{b}"),
             CborAny::Int(i) => write!(f, "{i}"),
@@ -147,7 +153,7 @@ impl<R, T: AsRef<R>> AsRef<R> for Base64Encoder<T> {
     }
 }
 
-impl<C, T: std::ops::Deref<Target = [u8]>> Encode<C> for Base64Encoder<T> {
+impl<C, T: core::ops::Deref<Target = [u8]>> Encode<C> for Base64Encoder<T> {
     fn encode<W: Write>(&self, e: &mut Encoder<W>, _: &mut C) -> Result<(), Error<W::Error>> {
         e.str(&general_purpose::STANDARD.encode(self.0.as_ref()))?;
         Ok(())
@@ -197,11 +203,172 @@ where
     }
 }
 
+/// A segment of a [CborPath].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CborPathSegment {
+    Key(CborAny),
+    Index(u64),
+}
+
+/// A pointer to an item inside a CBOR value, as a list of map keys and array
+/// indices. It displays like a JSON pointer, e.g. `/1/0/2`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CborPath(pub Vec<CborPathSegment>);
+
+impl core::fmt::Display for CborPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str("/");
+        }
+        for segment in &self.0 {
+            match segment {
+                CborPathSegment::Key(CborAny::String(s)) => write!(f, "/{s}")?,
+                CborPathSegment::Key(CborAny::Int(i)) => write!(f, "/{i}")?,
+                CborPathSegment::Key(key) => write!(f, "/{key:?}")?,
+                CborPathSegment::Index(i) => write!(f, "/{i}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Maximum depth walked when looking for the item at a position. Deeper
+/// items are reported at their ancestor of that depth.
+const MAXIMUM_PATH_DEPTH: usize = 64;
+
+impl CborPath {
+    /// Returns the path to the innermost item of `bytes` that contains the byte
+    /// at `position`. If `bytes` is malformed, this is the path to the item
+    /// that could not be read.
+    pub fn at_position(bytes: &[u8], position: usize) -> Self {
+        let mut path = Self::default();
+        let _ = path.walk(&mut Decoder::new(bytes), position, 0);
+        path
+    }
+
+    /// Walk the item at the decoder's position. Returns true if it contains
+    /// `position`, in which case `self` points to it.
+    fn walk(
+        &mut self,
+        d: &mut Decoder,
+        position: usize,
+        depth: usize,
+    ) -> Result<bool, minicbor::decode::Error> {
+        if depth >= MAXIMUM_PATH_DEPTH {
+            return Ok(true);
+        }
+        match d.datatype()? {
+            Type::Array | Type::ArrayIndef => {
+                let len = d.array()?;
+                let mut i = 0;
+                while len.map_or(true, |len| i < len) {
+                    if position < d.position() {
+                        return Ok(true);
+                    }
+                    if len.is_none() && d.datatype()? == Type::Break {
+                        d.set_position(d.position() + 1);
+                        break;
+                    }
+                    self.0.push(CborPathSegment::Index(i));
+                    if self.walk(d, position, depth + 1)? {
+                        return Ok(true);
+                    }
+                    self.0.pop();
+                    i += 1;
+                }
+            }
+            Type::Map | Type::MapIndef => {
+                let len = d.map()?;
+                let mut i = 0;
+                while len.map_or(true, |len| i < len) {
+                    if position < d.position() {
+                        return Ok(true);
+                    }
+                    if len.is_none() && d.datatype()? == Type::Break {
+                        d.set_position(d.position() + 1);
+                        break;
+                    }
+                    self.0.push(CborPathSegment::Key(d.decode()?));
+                    if position < d.position() || self.walk(d, position, depth + 1)? {
+                        return Ok(true);
+                    }
+                    self.0.pop();
+                    i += 1;
+                }
+            }
+            Type::Tag => {
+                d.tag()?;
+                if position < d.position() {
+                    return Ok(true);
+                }
+                return self.walk(d, position, depth + 1);
+            }
+            _ => d.skip()?,
+        }
+        Ok(position < d.position())
+    }
+}
+
+/// Describe an error returned when decoding `bytes`, including the path to the
+/// item that failed to decode when it is known.
+pub fn decode_error_details(bytes: &[u8], error: &minicbor::decode::Error) -> String {
+    // minicbor only exposes the position of an error in its message.
+    let position = error
+        .to_string()
+        .split("position ")
+        .nth(1)
+        .and_then(|rest| rest.split(|c: char| !c.is_ascii_digit()).next())
+        .and_then(|p| p.parse().ok());
+    match position {
+        Some(position) => format!("{error} (at {})", CborPath::at_position(bytes, position)),
+        None => error.to_string(),
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
     use proptest::prelude::*;
 
+    #[test]
+    fn cbor_path() {
+        // {0: "a", 1: [1, 2, "x"]}
+        let bytes = hex::decode("a2006161018301026178").unwrap();
+        let path = |p| CborPath::at_position(&bytes, p).to_string();
+        assert_eq!(path(0), "/");
+        assert_eq!(path(1), "/0");
+        assert_eq!(path(2), "/0");
+        assert_eq!(path(5), "/1");
+        assert_eq!(path(6), "/1/0");
+        assert_eq!(path(8), "/1/2");
+        assert_eq!(path(9), "/1/2");
+    }
+
+    #[test]
+    fn cbor_path_depth() {
+        let mut bytes = vec![0x81; 1000];
+        bytes.push(0x00);
+        let path = CborPath::at_position(&bytes, 1000);
+        assert_eq!(path.0.len(), MAXIMUM_PATH_DEPTH);
+    }
+
+    #[test]
+    fn decode_error_path() {
+        #[derive(Debug, Decode)]
+        #[cbor(map)]
+        #[allow(dead_code)]
+        struct Args {
+            #[n(0)]
+            name: String,
+            #[n(1)]
+            values: Vec<u32>,
+        }
+
+        let bytes = hex::decode("a2006161018301026178").unwrap();
+        let error = minicbor::decode::<Args>(&bytes).unwrap_err();
+        assert!(decode_error_details(&bytes, &error).ends_with("(at /1/2)"));
+    }
+
     #[test]
     fn cbor_null() {
         let null = CborNull;
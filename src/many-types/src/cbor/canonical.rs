@@ -0,0 +1,400 @@
+//! Deterministic encoding of CBOR values, following the core deterministic
+//! encoding requirements of RFC 8949 (section 4.2.1):
+//!
+//! - integers, lengths and tags use the shortest head possible;
+//! - strings, arrays and maps have a definite length;
+//! - map keys are sorted by the bytewise order of their encoding, and are
+//!   unique;
+//! - floating point values use the shortest encoding that preserves their
+//!   value, and NaN is always encoded as `f97e00`.
+//!
+//! minicbor encodes maps in their iteration order and decoders accept any
+//! valid encoding, so the same value can have multiple encodings. Values that
+//! are hashed or stored in the state of a node must be canonicalized so
+//! every node agrees on their bytes.
+use alloc::format;
+use alloc::vec::Vec;
+use many_error::ManyError;
+use minicbor::Encode;
+
+/// Nested arrays, maps and tags deeper than this are refused.
+const MAX_DEPTH: usize = 256;
+
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_TEXT: u8 = 3;
+const MAJOR_ARRAY: u8 = 4;
+const MAJOR_MAP: u8 = 5;
+const MAJOR_TAG: u8 = 6;
+const MAJOR_SIMPLE: u8 = 7;
+
+const BREAK: u8 = 0xff;
+
+/// Re-encode a CBOR item (and all its sub-items) canonically.
+pub fn canonicalize(bytes: &[u8]) -> Result<Vec<u8>, ManyError> {
+    let mut reader = Reader { bytes, position: 0 };
+    let mut out = Vec::with_capacity(bytes.len());
+    reader.item(&mut out, 0)?;
+    if reader.position != bytes.len() {
+        return Err(ManyError::deserialization_error(format!(
+            "trailing bytes at position {}",
+            reader.position
+        )));
+    }
+    Ok(out)
+}
+
+/// Whether `bytes` is a single CBOR item, encoded canonically.
+pub fn is_canonical(bytes: &[u8]) -> bool {
+    canonicalize(bytes).map_or(false, |canonical| canonical == bytes)
+}
+
+/// Encode a value canonically.
+pub fn to_canonical_vec<T: Encode<()>>(value: T) -> Result<Vec<u8>, ManyError> {
+    let bytes = minicbor::to_vec(value).map_err(ManyError::serialization_error)?;
+    canonicalize(&bytes)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+fn error(position: usize, message: &str) -> ManyError {
+    ManyError::deserialization_error(format!("{message} at position {position}"))
+}
+
+fn write_head(out: &mut Vec<u8>, major: u8, argument: u64) {
+    let major = major << 5;
+    match argument {
+        0..=23 => out.push(major | argument as u8),
+        24..=0xff => out.extend_from_slice(&[major | 24, argument as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend_from_slice(&(argument as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(major | 26);
+            out.extend_from_slice(&(argument as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend_from_slice(&argument.to_be_bytes());
+        }
+    }
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: u64) -> Result<&'a [u8], ManyError> {
+        let end = usize::try_from(len)
+            .ok()
+            .and_then(|len| self.position.checked_add(len))
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| error(self.position, "unexpected end of input"))?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn peek(&self) -> Result<u8, ManyError> {
+        self.bytes
+            .get(self.position)
+            .copied()
+            .ok_or_else(|| error(self.position, "unexpected end of input"))
+    }
+
+    fn uint(&mut self, len: u64) -> Result<u64, ManyError> {
+        Ok(self
+            .take(len)?
+            .iter()
+            .fold(0u64, |acc, b| (acc << 8) | *b as u64))
+    }
+
+    /// Read the head of an item: its major type, additional information and
+    /// argument. The argument is `None` for indefinite lengths.
+    fn head(&mut self) -> Result<(u8, u8, Option<u64>), ManyError> {
+        let start = self.position;
+        let initial = self.take(1)?[0];
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        let argument = match info {
+            0..=23 => Some(info as u64),
+            24 => Some(self.uint(1)?),
+            25 => Some(self.uint(2)?),
+            26 => Some(self.uint(4)?),
+            27 => Some(self.uint(8)?),
+            31 if matches!(major, MAJOR_BYTES..=MAJOR_MAP) => None,
+            31 if initial == BREAK => return Err(error(start, "unexpected break")),
+            _ => return Err(error(start, "invalid additional information")),
+        };
+        Ok((major, info, argument))
+    }
+
+    fn at_break(&mut self) -> Result<bool, ManyError> {
+        if self.peek()? == BREAK {
+            self.position += 1;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn item(&mut self, out: &mut Vec<u8>, depth: usize) -> Result<(), ManyError> {
+        let start = self.position;
+        if depth > MAX_DEPTH {
+            return Err(error(start, "nesting too deep"));
+        }
+
+        let (major, info, argument) = self.head()?;
+        match (major, argument) {
+            (0 | 1, Some(argument)) => write_head(out, major, argument),
+            (MAJOR_BYTES | MAJOR_TEXT, _) => {
+                let content = match argument {
+                    Some(len) => self.take(len)?.to_vec(),
+                    None => self.chunks(major)?,
+                };
+                if major == MAJOR_TEXT && core::str::from_utf8(&content).is_err() {
+                    return Err(error(start, "invalid UTF-8 string"));
+                }
+                write_head(out, major, content.len() as u64);
+                out.extend_from_slice(&content);
+            }
+            (MAJOR_ARRAY, _) => {
+                let mut items = Vec::new();
+                let mut len = 0u64;
+                loop {
+                    match argument {
+                        Some(n) if len == n => break,
+                        None if self.at_break()? => break,
+                        _ => {}
+                    }
+                    self.item(&mut items, depth + 1)?;
+                    len += 1;
+                }
+                write_head(out, major, len);
+                out.extend_from_slice(&items);
+            }
+            (MAJOR_MAP, _) => {
+                let mut entries = Vec::new();
+                loop {
+                    match argument {
+                        Some(n) if entries.len() as u64 == n => break,
+                        None if self.at_break()? => break,
+                        _ => {}
+                    }
+                    let mut key = Vec::new();
+                    let mut value = Vec::new();
+                    self.item(&mut key, depth + 1)?;
+                    self.item(&mut value, depth + 1)?;
+                    entries.push((key, value));
+                }
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                if entries.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+                    return Err(error(start, "duplicate map key"));
+                }
+                write_head(out, major, entries.len() as u64);
+                for (key, value) in entries {
+                    out.extend_from_slice(&key);
+                    out.extend_from_slice(&value);
+                }
+            }
+            (MAJOR_TAG, Some(tag)) => {
+                write_head(out, major, tag);
+                self.item(out, depth + 1)?;
+            }
+            (MAJOR_SIMPLE, Some(argument)) => match info {
+                0..=23 => out.push(0xe0 | info),
+                24 if argument >= 32 => out.extend_from_slice(&[0xf8, argument as u8]),
+                25 => float(out, f16_to_f64(argument as u16)),
+                26 => float(out, f32::from_bits(argument as u32) as f64),
+                27 => float(out, f64::from_bits(argument)),
+                _ => return Err(error(start, "invalid simple value")),
+            },
+            _ => return Err(error(start, "invalid item")),
+        }
+        Ok(())
+    }
+
+    /// The concatenated chunks of an indefinite length string.
+    fn chunks(&mut self, major: u8) -> Result<Vec<u8>, ManyError> {
+        let mut content = Vec::new();
+        while !self.at_break()? {
+            let start = self.position;
+            match self.head()? {
+                (m, _, Some(len)) if m == major => content.extend_from_slice(self.take(len)?),
+                _ => return Err(error(start, "invalid string chunk")),
+            }
+        }
+        Ok(content)
+    }
+}
+
+/// Write a float with the shortest encoding preserving its value.
+fn float(out: &mut Vec<u8>, value: f64) {
+    if value.is_nan() {
+        out.extend_from_slice(&[0xf9, 0x7e, 0x00]);
+    } else if let Some(half) = f64_to_f16(value) {
+        out.push(0xf9);
+        out.extend_from_slice(&half.to_be_bytes());
+    } else if (value as f32) as f64 == value {
+        out.push(0xfa);
+        out.extend_from_slice(&(value as f32).to_bits().to_be_bytes());
+    } else {
+        out.push(0xfb);
+        out.extend_from_slice(&value.to_bits().to_be_bytes());
+    }
+}
+
+fn f16_to_f64(half: u16) -> f64 {
+    let sign = ((half & 0x8000) as u32) << 16;
+    let exponent = ((half >> 10) & 0x1f) as u32;
+    let mantissa = (half & 0x3ff) as u32;
+    let magnitude = match exponent {
+        // Subnormal, i.e. mantissa * 2^-24.
+        0 => return sign_of(half) * (mantissa as f64) / 16_777_216.0,
+        0x1f if mantissa == 0 => 0x7f80_0000,
+        0x1f => 0x7fc0_0000,
+        _ => ((exponent + 127 - 15) << 23) | (mantissa << 13),
+    };
+    f32::from_bits(sign | magnitude) as f64
+}
+
+fn sign_of(half: u16) -> f64 {
+    if half & 0x8000 == 0 {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+/// The half precision encoding of a value, if it is exact.
+fn f64_to_f16(value: f64) -> Option<u16> {
+    let single = value as f32;
+    if single as f64 != value {
+        return None;
+    }
+    let bits = single.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    if value.is_infinite() {
+        return Some(sign | 0x7c00);
+    }
+    if value == 0.0 {
+        return Some(sign);
+    }
+
+    let exponent = ((bits >> 23) & 0xff) as i32 - 127;
+    let mantissa = bits & 0x7f_ffff;
+    match exponent {
+        16.. => None,
+        -14..=15 if mantissa & 0x1fff == 0 => {
+            Some(sign | (((exponent + 15) as u16) << 10) | (mantissa >> 13) as u16)
+        }
+        -14..=15 => None,
+        _ => {
+            // Subnormal half precision, i.e. a multiple of 2^-24.
+            let shift = (-1 - exponent) as u32;
+            let mantissa = mantissa | 0x80_0000;
+            if shift >= 24 || mantissa & ((1 << shift) - 1) != 0 {
+                None
+            } else {
+                Some(sign | (mantissa >> shift) as u16)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::collections::BTreeMap;
+    use alloc::string::String;
+    use alloc::vec;
+
+    fn canonical_hex(input: &str) -> Result<String, ManyError> {
+        canonicalize(&hex::decode(input).unwrap()).map(hex::encode)
+    }
+
+    #[test]
+    fn shortest_heads() {
+        assert_eq!(canonical_hex("1900ff").unwrap(), "18ff");
+        assert_eq!(canonical_hex("1b0000000000000001").unwrap(), "01");
+        assert_eq!(canonical_hex("3a0000ffff").unwrap(), "39ffff");
+        assert_eq!(canonical_hex("d900016161").unwrap(), "c16161");
+        assert_eq!(canonical_hex("780161").unwrap(), "6161");
+    }
+
+    #[test]
+    fn indefinite_lengths() {
+        assert_eq!(canonical_hex("9f0102ff").unwrap(), "820102");
+        assert_eq!(canonical_hex("7f61616162ff").unwrap(), "626162");
+        assert_eq!(canonical_hex("5f4101ff").unwrap(), "4101");
+        assert_eq!(canonical_hex("bf0102ff").unwrap(), "a10102");
+        // Chunks must be of the same type.
+        assert!(canonical_hex("7f4101ff").is_err());
+    }
+
+    #[test]
+    fn sorted_maps() {
+        // {"b": 1, "a": 2}
+        assert_eq!(canonical_hex("a2616201616102").unwrap(), "a2616102616201");
+        // {-1: 0, 10: 0}
+        assert_eq!(canonical_hex("a220000a00").unwrap(), "a20a002000");
+        // {1: 1, 1: 2}
+        assert!(canonical_hex("a201010102").is_err());
+
+        // Shorter keys come first, unlike the order of a BTreeMap.
+        let map = BTreeMap::from([("aa", 1u8), ("b", 2u8)]);
+        assert_eq!(
+            hex::encode(to_canonical_vec(&map).unwrap()),
+            "a261620262616101"
+        );
+        assert!(!is_canonical(&minicbor::to_vec(&map).unwrap()));
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(canonical_hex("fb3ff0000000000000").unwrap(), "f93c00");
+        assert_eq!(canonical_hex("fa3fc00000").unwrap(), "f93e00");
+        assert_eq!(canonical_hex("fb3e70000000000000").unwrap(), "f90001");
+        assert_eq!(canonical_hex("fb8000000000000000").unwrap(), "f98000");
+        assert_eq!(canonical_hex("fb7ff0000000000000").unwrap(), "f97c00");
+        assert_eq!(canonical_hex("fb7ff8000000000001").unwrap(), "f97e00");
+        assert_eq!(canonical_hex("f90001").unwrap(), "f90001");
+        assert_eq!(canonical_hex("fa47c35000").unwrap(), "fa47c35000");
+        assert_eq!(
+            canonical_hex("fb3ff199999999999a").unwrap(),
+            "fb3ff199999999999a"
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        // Trailing bytes.
+        assert!(canonical_hex("0101").is_err());
+        // Unexpected break.
+        assert!(canonical_hex("ff").is_err());
+        assert!(canonical_hex("8201ff").is_err());
+        // Truncated.
+        assert!(canonical_hex("8201").is_err());
+        assert!(canonical_hex("62ff").is_err());
+        // Invalid UTF-8.
+        assert!(canonical_hex("61ff").is_err());
+        // Reserved additional information.
+        assert!(canonical_hex("1c").is_err());
+        // Simple values under 32 must be encoded in the head.
+        assert!(canonical_hex("f801").is_err());
+
+        let deep = [[0x81u8].repeat(MAX_DEPTH + 1), vec![0]].concat();
+        assert!(canonicalize(&deep).is_err());
+    }
+}
+
+#[cfg(all(test, feature = "proptest"))]
+proptest::proptest! {
+    #[test]
+    fn canonical_round_trip(value in super::tests::arb_cbor()) {
+        use crate::cbor::CborAny;
+
+        let canonical = to_canonical_vec(&value).unwrap();
+        proptest::prop_assert_eq!(minicbor::decode::<CborAny>(&canonical).unwrap(), value);
+        proptest::prop_assert!(is_canonical(&canonical));
+    }
+}
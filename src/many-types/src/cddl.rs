@@ -0,0 +1,187 @@
+//! CDDL descriptions of the types exchanged with MANY endpoints, so servers
+//! can describe their endpoints without an out-of-band specification.
+//!
+//! Structs deriving `minicbor::Encode` can derive [Cddl] with
+//! `many_macros::Cddl`, which reads the same `#[n(_)]` and `#[cbor(_)]`
+//! attributes.
+use crate::identity::Address;
+use crate::Timestamp;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use minicbor::bytes::{ByteArray, ByteVec};
+
+/// A type with a known CDDL description of its CBOR encoding.
+pub trait Cddl {
+    fn cddl() -> String;
+}
+
+macro_rules! cddl {
+    ( $( $($ty: ty),+ => $cddl: literal; )* ) => {
+        $($(
+            impl Cddl for $ty {
+                fn cddl() -> String {
+                    $cddl.to_string()
+                }
+            }
+        )+)*
+    };
+}
+
+cddl!(
+    () => "null";
+    bool => "bool";
+    u8, u16, u32, u64 => "uint";
+    i8, i16, i32, i64 => "int";
+    f32, f64 => "float";
+    String, str => "tstr";
+    ByteVec => "bstr";
+    Address => "#6.10000(bstr)";
+    Timestamp => "#6.1(uint)";
+);
+
+impl<const N: usize> Cddl for ByteArray<N> {
+    fn cddl() -> String {
+        format!("bstr .size {N}")
+    }
+}
+
+impl<T: Cddl + ?Sized> Cddl for &T {
+    fn cddl() -> String {
+        T::cddl()
+    }
+}
+
+impl<T: Cddl> Cddl for Option<T> {
+    fn cddl() -> String {
+        format!("{} / null", T::cddl())
+    }
+}
+
+impl<T: Cddl> Cddl for Vec<T> {
+    fn cddl() -> String {
+        format!("[* {}]", T::cddl())
+    }
+}
+
+impl<T: Cddl> Cddl for crate::VecOrSingle<T> {
+    fn cddl() -> String {
+        format!("{0} / [* {0}]", T::cddl())
+    }
+}
+
+impl<T: Cddl> Cddl for BTreeSet<T> {
+    fn cddl() -> String {
+        format!("[* {}]", T::cddl())
+    }
+}
+
+impl<K: Cddl, V: Cddl> Cddl for BTreeMap<K, V> {
+    fn cddl() -> String {
+        format!("{{ * {} => {} }}", K::cddl(), V::cddl())
+    }
+}
+
+/// Describes a map with the given entries.
+pub fn map(entries: &[String]) -> String {
+    format!("{{{}}}", entries.join(", "))
+}
+
+/// Describes `T` with its [Cddl] implementation if it has one, or as `any`
+/// annotated with its Rust type name otherwise. The `Cddl` derive uses this
+/// for fields, so that a struct can be described before all its field types
+/// implement [Cddl]:
+///
+/// ```
+/// use many_types::cddl::{CddlOrAny, CddlProbe, CddlUnknown};
+/// struct Undescribed;
+///
+/// assert_eq!((&&CddlProbe::<u32>::new()).cddl_or_any(), "uint");
+/// assert!((&&CddlProbe::<Undescribed>::new())
+///     .cddl_or_any()
+///     .starts_with("any"));
+/// ```
+pub struct CddlProbe<T: ?Sized>(PhantomData<T>);
+
+impl<T: ?Sized> CddlProbe<T> {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+pub trait CddlOrAny {
+    fn cddl_or_any(&self) -> String;
+
+    /// The entry of a map field with this type and index.
+    fn map_entry(&self, index: u64) -> String {
+        format!("{index}: {}", self.cddl_or_any())
+    }
+}
+
+impl<T: Cddl + ?Sized> CddlOrAny for &CddlProbe<T> {
+    fn cddl_or_any(&self) -> String {
+        T::cddl()
+    }
+}
+
+pub trait CddlUnknown {
+    fn cddl_or_any(&self) -> String;
+
+    /// The entry of a map field with this type and index.
+    fn map_entry(&self, index: u64) -> String {
+        format!("{index}: {}", self.cddl_or_any())
+    }
+}
+
+/// Describes optional map fields as optional entries, i.e.
+/// `(&&&CddlProbe::<Option<T>>::new()).map_entry(0)` is `? 0: T`. Used by
+/// [cbor_type_decl](crate::cbor_type_decl).
+pub trait CddlOptional {
+    fn map_entry(&self, index: u64) -> String;
+}
+
+impl<T: Cddl> CddlOptional for &&CddlProbe<Option<T>> {
+    fn map_entry(&self, index: u64) -> String {
+        format!("? {index}: {}", T::cddl())
+    }
+}
+
+impl<T: ?Sized> CddlUnknown for CddlProbe<T> {
+    fn cddl_or_any(&self) -> String {
+        format!("any ; {}", core::any::type_name::<T>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn containers() {
+        assert_eq!(<Option<u64>>::cddl(), "uint / null");
+        assert_eq!(<Vec<String>>::cddl(), "[* tstr]");
+        assert_eq!(
+            <BTreeMap<Address, BTreeSet<bool>>>::cddl(),
+            "{ * #6.10000(bstr) => [* bool] }"
+        );
+        assert_eq!(<ByteArray<32>>::cddl(), "bstr .size 32");
+    }
+
+    #[test]
+    #[allow(clippy::needless_borrow)]
+    fn map_entries() {
+        struct Undescribed;
+
+        assert_eq!(
+            (&&&CddlProbe::<Option<u8>>::new()).map_entry(1),
+            "? 1: uint"
+        );
+        assert_eq!((&&&CddlProbe::<bool>::new()).map_entry(2), "2: bool");
+        assert!((&&&CddlProbe::<Undescribed>::new())
+            .map_entry(3)
+            .starts_with("3: any"));
+    }
+}
@@ -0,0 +1,642 @@
+//! Pretty-printing of CBOR in diagnostic notation, annotated with the field
+//! names of known structures.
+//!
+//! Plain diagnostic notation only shows the numeric keys of maps. Given a
+//! [Schema], the keys of known structures are followed by their name in a
+//! comment, and addresses, big numbers and timestamps by their decoded value:
+//!
+//! ```text
+//! 10001({
+//!   1 /from/: 10000(h'0100...') /maffbahksdwaqeenayy2gxke32hgb7aq4ao4wt745lsfs6wijp/,
+//!   3 /method/: "ledger.info",
+//!   5 /timestamp/: 1(1700000000) /2023-11-14T22:13:20Z/,
+//! })
+//! ```
+use crate::AttributeRelatedIndex;
+use many_error::ManyError;
+use many_identity::Address;
+use minicbor::data::Type;
+use minicbor::{decode, Decoder};
+use num_bigint::BigUint;
+use std::fmt::{Display, Formatter, Write};
+
+/// The shape of a CBOR value, used to name its fields.
+#[derive(Copy, Clone, Debug)]
+pub enum Schema {
+    /// Any value, printed without field names.
+    Any,
+
+    /// A byte string containing a CBOR value.
+    Cbor(&'static Schema),
+
+    /// A map with named keys.
+    Map(&'static [Field]),
+
+    /// An array whose items have the same schema.
+    Array(&'static Schema),
+
+    /// A map whose fields depend on the kind in its key 0, e.g. an event.
+    Variants(&'static [Variant]),
+
+    /// The first schema that matches the type of the value, e.g. the result
+    /// of a response is either a byte string or an error map.
+    OneOf(&'static [Schema]),
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Field {
+    pub key: i64,
+    pub name: &'static str,
+    pub schema: &'static Schema,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Variant {
+    /// The flattened attribute related index in key 0 of the map.
+    pub kind: &'static [u32],
+    pub name: &'static str,
+    pub fields: &'static [Field],
+}
+
+/// Maximum nesting of arrays, maps, tags and embedded CBOR that is printed.
+const MAXIMUM_DEPTH: usize = 64;
+
+const TAG_TIMESTAMP: u64 = 1;
+const TAG_POS_BIGNUM: u64 = 2;
+const TAG_ADDRESS: u64 = 10000;
+const TAG_REQUEST: u64 = 10001;
+const TAG_RESPONSE: u64 = 10002;
+
+pub const MANY_ERROR: Schema = Schema::Map(&[
+    Field {
+        key: 0,
+        name: "code",
+        schema: &Schema::Any,
+    },
+    Field {
+        key: 1,
+        name: "message",
+        schema: &Schema::Any,
+    },
+    Field {
+        key: 2,
+        name: "arguments",
+        schema: &Schema::Any,
+    },
+]);
+
+pub const REQUEST_MESSAGE: Schema = Schema::Map(&[
+    Field {
+        key: 0,
+        name: "version",
+        schema: &Schema::Any,
+    },
+    Field {
+        key: 1,
+        name: "from",
+        schema: &Schema::Any,
+    },
+    Field {
+        key: 2,
+        name: "to",
+        schema: &Schema::Any,
+    },
+    Field {
+        key: 3,
+        name: "method",
+        schema: &Schema::Any,
+    },
+    Field {
+        key: 4,
+        name: "data",
+        schema: &Schema::Cbor(&Schema::Any),
+    },
+    Field {
+        key: 5,
+        name: "timestamp",
+        schema: &Schema::Any,
+    },
+    Field {
+        key: 6,
+        name: "id",
+        schema: &Schema::Any,
+    },
+    Field {
+        key: 7,
+        name: "nonce",
+        schema: &Schema::Any,
+    },
+    Field {
+        key: 8,
+        name: "attributes",
+        schema: &Schema::Any,
+    },
+]);
+
+pub const RESPONSE_MESSAGE: Schema = Schema::Map(&[
+    Field {
+        key: 0,
+        name: "version",
+        schema: &Schema::Any,
+    },
+    Field {
+        key: 1,
+        name: "from",
+        schema: &Schema::Any,
+    },
+    Field {
+        key: 2,
+        name: "to",
+        schema: &Schema::Any,
+    },
+    Field {
+        key: 4,
+        name: "result",
+        schema: &Schema::OneOf(&[Schema::Cbor(&Schema::Any), MANY_ERROR]),
+    },
+    Field {
+        key: 5,
+        name: "timestamp",
+        schema: &Schema::Any,
+    },
+    Field {
+        key: 6,
+        name: "id",
+        schema: &Schema::Any,
+    },
+    Field {
+        key: 8,
+        name: "attributes",
+        schema: &Schema::Any,
+    },
+]);
+
+pub const TOKEN_INFO: Schema = Schema::Map(&[
+    Field {
+        key: 0,
+        name: "symbol",
+        schema: &Schema::Any,
+    },
+    Field {
+        key: 1,
+        name: "summary",
+        schema: &Schema::Map(&[
+            Field {
+                key: 0,
+                name: "name",
+                schema: &Schema::Any,
+            },
+            Field {
+                key: 1,
+                name: "ticker",
+                schema: &Schema::Any,
+            },
+            Field {
+                key: 2,
+                name: "decimals",
+                schema: &Schema::Any,
+            },
+        ]),
+    },
+    Field {
+        key: 2,
+        name: "supply",
+        schema: &Schema::Map(&[
+            Field {
+                key: 0,
+                name: "total",
+                schema: &Schema::Any,
+            },
+            Field {
+                key: 1,
+                name: "circulating",
+                schema: &Schema::Any,
+            },
+            Field {
+                key: 2,
+                name: "maximum",
+                schema: &Schema::Any,
+            },
+        ]),
+    },
+    Field {
+        key: 3,
+        name: "owner",
+        schema: &Schema::Any,
+    },
+]);
+
+impl Schema {
+    /// Whether a value of this type can be described by this schema.
+    fn matches(&self, datatype: Type) -> bool {
+        match self {
+            Schema::Any => true,
+            Schema::Cbor(_) => matches!(datatype, Type::Bytes),
+            Schema::Map(_) | Schema::Variants(_) => matches!(datatype, Type::Map | Type::MapIndef),
+            Schema::Array(_) => matches!(datatype, Type::Array | Type::ArrayIndef),
+            Schema::OneOf(schemas) => schemas.iter().any(|s| s.matches(datatype)),
+        }
+    }
+}
+
+/// Format the CBOR value in `bytes` in diagnostic notation, annotated using
+/// `schema`.
+pub fn to_diag(bytes: &[u8], schema: &Schema) -> Result<String, ManyError> {
+    let mut printer = Printer::default();
+    let mut d = Decoder::new(bytes);
+    printer.value(&mut d, schema).map_err(|e| {
+        ManyError::deserialization_error(crate::cbor::decode_error_details(bytes, &e))
+    })?;
+    if d.position() != bytes.len() {
+        return Err(ManyError::deserialization_error(
+            "Trailing bytes after the CBOR value.",
+        ));
+    }
+    Ok(printer.out)
+}
+
+/// Displays CBOR bytes with [to_diag], or in hexadecimal if they are invalid.
+/// Useful in error and assertion messages.
+pub struct Diag<'a>(pub &'a [u8], pub &'a Schema);
+
+impl Display for Diag<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match to_diag(self.0, self.1) {
+            Ok(diag) => f.write_str(&diag),
+            Err(_) => write!(f, "h'{}'", hex::encode(self.0)),
+        }
+    }
+}
+
+/// The number of a tag from the bytes of its header.
+fn tag_number(header: &[u8]) -> u64 {
+    let n = match header[0] & 0x1f {
+        24 => 1,
+        25 => 2,
+        26 => 4,
+        27 => 8,
+        x => return u64::from(x),
+    };
+    header[1..=n]
+        .iter()
+        .fold(0, |acc, byte| (acc << 8) | u64::from(*byte))
+}
+
+/// Format seconds since the epoch as an RFC 3339 date in UTC.
+fn format_timestamp(secs: u64) -> String {
+    let (days, secs) = (secs / 86400, secs % 86400);
+
+    // See http://howardhinnant.github.io/date_algorithms.html#civil_from_days.
+    let z = days as i64 + 719468;
+    let era = z / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+#[derive(Default)]
+struct Printer {
+    out: String,
+    indentation: String,
+    depth: usize,
+}
+
+impl Printer {
+    fn newline(&mut self) {
+        self.out.push('\n');
+        self.out.push_str(&self.indentation);
+    }
+
+    fn enter(&mut self) -> Result<(), decode::Error> {
+        if self.depth >= MAXIMUM_DEPTH {
+            return Err(decode::Error::message("CBOR value is nested too deeply."));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    fn indent(&mut self) {
+        self.indentation.push_str("  ");
+    }
+
+    fn dedent(&mut self) {
+        self.indentation.truncate(self.indentation.len() - 2);
+    }
+
+    /// Print the raw item at the decoder's position, e.g. a scalar.
+    fn raw(&mut self, d: &mut Decoder) -> Result<(), decode::Error> {
+        if d.datatype()? == Type::Bytes {
+            let bytes = d.bytes()?;
+            let _ = write!(self.out, "h'{}'", hex::encode(bytes));
+            return Ok(());
+        }
+        let start = d.position();
+        d.skip()?;
+        let _ = write!(
+            self.out,
+            "{}",
+            minicbor::display(&d.input()[start..d.position()])
+        );
+        Ok(())
+    }
+
+    fn value(&mut self, d: &mut Decoder, schema: &Schema) -> Result<(), decode::Error> {
+        self.enter()?;
+        let result = self.value_inner(d, schema);
+        self.leave();
+        result
+    }
+
+    fn value_inner(&mut self, d: &mut Decoder, schema: &Schema) -> Result<(), decode::Error> {
+        let datatype = d.datatype()?;
+        let schema = match schema {
+            Schema::OneOf(schemas) => schemas
+                .iter()
+                .find(|s| s.matches(datatype))
+                .unwrap_or(&Schema::Any),
+            s => s,
+        };
+
+        match (datatype, schema) {
+            (Type::Tag, _) => self.tagged(d, schema),
+            (Type::Map | Type::MapIndef, Schema::Map(fields)) => self.map(d, fields),
+            (Type::Map | Type::MapIndef, Schema::Variants(variants)) => {
+                let variant = Self::variant(d, variants);
+                if let Some(variant) = variant {
+                    let _ = write!(self.out, "/{}/ ", variant.name);
+                }
+                self.map(d, variant.map_or(&[], |v| v.fields))
+            }
+            (Type::Map | Type::MapIndef, _) => self.map(d, &[]),
+            (Type::Array | Type::ArrayIndef, _) => {
+                let item: &Schema = match schema {
+                    Schema::Array(item) => item,
+                    _ => &Schema::Any,
+                };
+                self.array(d, item)
+            }
+            (Type::Bytes, Schema::Cbor(inner)) => {
+                // Print the embedded value in place, and roll back to the raw
+                // bytes if it is not valid CBOR.
+                let start = d.position();
+                let bytes = d.bytes()?;
+                let (out_len, indentation_len) = (self.out.len(), self.indentation.len());
+                self.out.push_str("<<");
+                let mut inner_d = Decoder::new(bytes);
+                match self.value(&mut inner_d, inner) {
+                    Ok(()) if inner_d.position() == bytes.len() => self.out.push_str(">>"),
+                    _ => {
+                        self.out.truncate(out_len);
+                        self.indentation.truncate(indentation_len);
+                        d.set_position(start);
+                        self.raw(d)?;
+                    }
+                }
+                Ok(())
+            }
+            _ => self.raw(d),
+        }
+    }
+
+    /// The variant of the map at the decoder's position, from its key 0.
+    fn variant(d: &Decoder, variants: &'static [Variant]) -> Option<&'static Variant> {
+        let mut probe = d.clone();
+        let len = probe.map().ok()?;
+        let mut i = 0;
+        while len.map_or(true, |len| i < len) {
+            if probe.datatype().ok()? == Type::Break {
+                return None;
+            }
+            if probe.clone().i64().ok() == Some(0) {
+                probe.skip().ok()?;
+                let kind: AttributeRelatedIndex = probe.decode().ok()?;
+                return variants
+                    .iter()
+                    .find(|v| v.kind == kind.flattened().as_slice());
+            }
+            probe.skip().ok()?;
+            probe.skip().ok()?;
+            i += 1;
+        }
+        None
+    }
+
+    fn tagged(&mut self, d: &mut Decoder, schema: &Schema) -> Result<(), decode::Error> {
+        let start = d.position();
+        d.tag()?;
+        let tag = tag_number(&d.input()[start..d.position()]);
+
+        let annotation = match tag {
+            TAG_TIMESTAMP => d.clone().u64().ok().map(format_timestamp),
+            TAG_POS_BIGNUM => d
+                .clone()
+                .bytes()
+                .ok()
+                .map(|b| BigUint::from_bytes_be(b).to_string()),
+            TAG_ADDRESS => d
+                .clone()
+                .bytes()
+                .ok()
+                .and_then(|b| Address::from_bytes(b).ok())
+                .map(|a| a.to_string()),
+            _ => None,
+        };
+        let schema = match tag {
+            TAG_REQUEST => &REQUEST_MESSAGE,
+            TAG_RESPONSE => &RESPONSE_MESSAGE,
+            _ => schema,
+        };
+
+        let _ = write!(self.out, "{tag}(");
+        self.value(d, schema)?;
+        self.out.push(')');
+        if let Some(annotation) = annotation {
+            let _ = write!(self.out, " /{annotation}/");
+        }
+        Ok(())
+    }
+
+    fn array(&mut self, d: &mut Decoder, item: &Schema) -> Result<(), decode::Error> {
+        let len = d.array()?;
+        if len == Some(0) {
+            self.out.push_str("[]");
+            return Ok(());
+        }
+
+        self.out.push('[');
+        self.indent();
+        let mut i = 0;
+        while len.map_or(true, |len| i < len) {
+            if len.is_none() && d.datatype()? == Type::Break {
+                d.set_position(d.position() + 1);
+                break;
+            }
+            self.newline();
+            self.value(d, item)?;
+            self.out.push(',');
+            i += 1;
+        }
+        self.dedent();
+        self.newline();
+        self.out.push(']');
+        Ok(())
+    }
+
+    fn map(&mut self, d: &mut Decoder, fields: &[Field]) -> Result<(), decode::Error> {
+        let len = d.map()?;
+        if len == Some(0) {
+            self.out.push_str("{}");
+            return Ok(());
+        }
+
+        self.out.push('{');
+        self.indent();
+        let mut i = 0;
+        while len.map_or(true, |len| i < len) {
+            if len.is_none() && d.datatype()? == Type::Break {
+                d.set_position(d.position() + 1);
+                break;
+            }
+            self.newline();
+            let field = d
+                .clone()
+                .i64()
+                .ok()
+                .and_then(|key| fields.iter().find(|f| f.key == key));
+            self.value(d, &Schema::Any)?;
+            let schema = match field {
+                Some(field) => {
+                    let _ = write!(self.out, " /{}/", field.name);
+                    field.schema
+                }
+                None => &Schema::Any,
+            };
+            self.out.push_str(": ");
+            self.value(d, schema)?;
+            self.out.push(',');
+            i += 1;
+        }
+        self.dedent();
+        self.newline();
+        self.out.push('}');
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn diag(diag: &str, schema: &Schema) -> String {
+        to_diag(&cbor_diag::parse_diag(diag).unwrap().to_bytes(), schema).unwrap()
+    }
+
+    #[test]
+    fn request() {
+        let from = Address::from_str("maffbahksdwaqeenayy2gxke32hgb7aq4ao4wt745lsfs6wijp").unwrap();
+        let request = format!(
+            r#"10001({{ 1: 10000(h'{}'), 3: "ledger.balance", 4: h'a0', 5: 1(1700000000) }})"#,
+            hex::encode(from.to_vec())
+        );
+        assert_eq!(
+            diag(&request, &Schema::Any),
+            format!(
+                "10001({{\n  \
+                   1 /from/: 10000(h'{}') /{from}/,\n  \
+                   3 /method/: \"ledger.balance\",\n  \
+                   4 /data/: <<{{}}>>,\n  \
+                   5 /timestamp/: 1(1700000000) /2023-11-14T22:13:20Z/,\n\
+                 }})",
+                hex::encode(from.to_vec())
+            )
+        );
+    }
+
+    #[test]
+    fn token_info() {
+        let info = r#"{ 1: { 0: "Token", 1: "TOK", 2: 9 }, 2: { 0: 2(h'010000000000000000') } }"#;
+        assert_eq!(
+            diag(info, &TOKEN_INFO),
+            "{\n  \
+               1 /summary/: {\n    \
+                 0 /name/: \"Token\",\n    \
+                 1 /ticker/: \"TOK\",\n    \
+                 2 /decimals/: 9,\n  \
+               },\n  \
+               2 /supply/: {\n    \
+                 0 /total/: 2(h'010000000000000000') /18446744073709551616/,\n  \
+               },\n\
+             }"
+        );
+    }
+
+    #[test]
+    fn variants() {
+        const VARIANTS: Schema = Schema::Variants(&[Variant {
+            kind: &[6, 0],
+            name: "Send",
+            fields: &[Field {
+                key: 4,
+                name: "amount",
+                schema: &Schema::Any,
+            }],
+        }]);
+        assert_eq!(
+            diag("{ 0: [6, 0], 4: 10 }", &VARIANTS),
+            "/Send/ {\n  0: [\n    6,\n    0,\n  ],\n  4 /amount/: 10,\n}"
+        );
+        assert_eq!(
+            diag("{ 0: 1, 4: 10 }", &VARIANTS),
+            "{\n  0: 1,\n  4: 10,\n}"
+        );
+    }
+
+    #[test]
+    fn response_error() {
+        assert_eq!(
+            diag(r#"10002({ 4: { 0: 1, 1: "oops" } })"#, &Schema::Any),
+            "10002({\n  4 /result/: {\n    0 /code/: 1,\n    1 /message/: \"oops\",\n  },\n})"
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(to_diag(&[0x82, 0x01], &Schema::Any).is_err());
+        assert!(to_diag(&[0x01, 0x01], &Schema::Any).is_err());
+        assert_eq!(Diag(&[0x82, 0x01], &Schema::Any).to_string(), "h'8201'");
+    }
+
+    #[test]
+    fn depth() {
+        let nested = |depth| {
+            let mut bytes = vec![0x81; depth];
+            bytes.push(0x00);
+            bytes
+        };
+        assert!(to_diag(&nested(MAXIMUM_DEPTH - 1), &Schema::Any).is_ok());
+        assert!(to_diag(&nested(MAXIMUM_DEPTH), &Schema::Any).is_err());
+    }
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(951782400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_timestamp(1700000000), "2023-11-14T22:13:20Z");
+    }
+}
@@ -0,0 +1,365 @@
+use crate::Timestamp;
+use alloc::format;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+use many_error::ManyError;
+use minicbor::data::Type;
+use minicbor::encode::{Error, Write};
+use minicbor::{decode, Decode, Decoder, Encode, Encoder};
+
+/// The unit a [Duration] is expressed in on the wire.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[repr(u8)]
+pub enum DurationUnit {
+    Seconds = 0,
+    Minutes = 1,
+    Hours = 2,
+    Days = 3,
+}
+
+impl DurationUnit {
+    const ALL: [DurationUnit; 4] = [Self::Days, Self::Hours, Self::Minutes, Self::Seconds];
+
+    /// Number of seconds in one of this unit.
+    pub const fn secs(&self) -> u64 {
+        match self {
+            Self::Seconds => 1,
+            Self::Minutes => 60,
+            Self::Hours => 60 * 60,
+            Self::Days => 24 * 60 * 60,
+        }
+    }
+
+    pub const fn suffix(&self) -> &'static str {
+        match self {
+            Self::Seconds => "s",
+            Self::Minutes => "m",
+            Self::Hours => "h",
+            Self::Days => "d",
+        }
+    }
+
+    fn from_suffix(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|u| u.suffix() == s)
+    }
+}
+
+impl TryFrom<u8> for DurationUnit {
+    type Error = ManyError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Seconds),
+            1 => Ok(Self::Minutes),
+            2 => Ok(Self::Hours),
+            3 => Ok(Self::Days),
+            x => Err(ManyError::unknown(format!("Unknown duration unit: {x}."))),
+        }
+    }
+}
+
+/// A length of time with a one second resolution, used by APIs that accept
+/// intervals (schedules, timeouts, retention periods, ...).
+///
+/// Durations are encoded in CBOR as `[value, unit]`, using the largest unit
+/// that represents the value exactly. For compatibility with fields that were
+/// previously plain integers, a bare unsigned integer decodes as a number of
+/// seconds.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Duration(u64);
+
+impl Duration {
+    /// The longest duration accepted, roughly 1000 years. This keeps any
+    /// duration addable to a [Timestamp] without overflowing.
+    pub const MAX: Duration = Duration(1000 * 365 * DurationUnit::Days.secs());
+
+    pub const ZERO: Duration = Duration(0);
+
+    pub fn new(value: u64, unit: DurationUnit) -> Result<Self, ManyError> {
+        value
+            .checked_mul(unit.secs())
+            .ok_or_else(|| ManyError::unknown("Duration overflow."))
+            .and_then(Self::from_secs)
+    }
+
+    pub fn from_secs(secs: u64) -> Result<Self, ManyError> {
+        if secs > Self::MAX.0 {
+            Err(ManyError::unknown(format!(
+                "Duration of {secs} seconds is out of range."
+            )))
+        } else {
+            Ok(Self(secs))
+        }
+    }
+
+    pub fn from_minutes(minutes: u64) -> Result<Self, ManyError> {
+        Self::new(minutes, DurationUnit::Minutes)
+    }
+
+    pub fn from_hours(hours: u64) -> Result<Self, ManyError> {
+        Self::new(hours, DurationUnit::Hours)
+    }
+
+    pub fn from_days(days: u64) -> Result<Self, ManyError> {
+        Self::new(days, DurationUnit::Days)
+    }
+
+    pub const fn secs(&self) -> u64 {
+        self.0
+    }
+
+    pub const fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns the value and largest unit that represent this duration exactly.
+    pub fn as_unit(&self) -> (u64, DurationUnit) {
+        DurationUnit::ALL
+            .into_iter()
+            .filter(|_| self.0 != 0)
+            .find(|u| self.0 % u.secs() == 0)
+            .map(|u| (self.0 / u.secs(), u))
+            .unwrap_or((self.0, DurationUnit::Seconds))
+    }
+
+    /// Validates that this duration lies within `[min, max]`.
+    pub fn check_range(&self, min: Duration, max: Duration) -> Result<(), ManyError> {
+        if *self < min || *self > max {
+            Err(ManyError::unknown(format!(
+                "Duration {self} must be between {min} and {max}."
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn checked_add(self, rhs: Duration) -> Option<Duration> {
+        self.0
+            .checked_add(rhs.0)
+            .and_then(|s| Self::from_secs(s).ok())
+    }
+
+    pub fn saturating_sub(self, rhs: Duration) -> Duration {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+
+    /// The duration elapsed between two timestamps, or zero if `later` is
+    /// before `earlier`.
+    pub fn between(earlier: Timestamp, later: Timestamp) -> Duration {
+        Self(later.secs().saturating_sub(earlier.secs())).min(Self::MAX)
+    }
+}
+
+impl core::ops::Add<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn add(self, rhs: Duration) -> Self::Output {
+        self + rhs.0
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Duration> for std::time::Duration {
+    fn from(value: Duration) -> Self {
+        std::time::Duration::from_secs(value.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFrom<std::time::Duration> for Duration {
+    type Error = ManyError;
+
+    /// Sub-second precision is truncated.
+    fn try_from(value: std::time::Duration) -> Result<Self, Self::Error> {
+        Self::from_secs(value.as_secs())
+    }
+}
+
+impl Display for Duration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let (value, unit) = self.as_unit();
+        write!(f, "{value}{}", unit.suffix())
+    }
+}
+
+/// Parses either a number of seconds (`"90"`) or a value followed by a unit
+/// suffix (`"90s"`, `"15m"`, `"12h"`, `"30d"`).
+impl FromStr for Duration {
+    type Err = ManyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (value, suffix) = s.split_at(split);
+        let value: u64 = value
+            .parse()
+            .map_err(|_| ManyError::unknown(format!("Invalid duration: {s:?}.")))?;
+        let unit = if suffix.is_empty() {
+            DurationUnit::Seconds
+        } else {
+            DurationUnit::from_suffix(suffix)
+                .ok_or_else(|| ManyError::unknown(format!("Invalid duration unit: {suffix:?}.")))?
+        };
+        Self::new(value, unit)
+    }
+}
+
+impl crate::cddl::Cddl for Duration {
+    fn cddl() -> alloc::string::String {
+        "uint / [uint, 0..3]".into()
+    }
+}
+
+impl<C> Encode<C> for Duration {
+    fn encode<W: Write>(&self, e: &mut Encoder<W>, _: &mut C) -> Result<(), Error<W::Error>> {
+        let (value, unit) = self.as_unit();
+        e.array(2)?.u64(value)?.u8(unit as u8)?;
+        Ok(())
+    }
+}
+
+impl<'b, C> Decode<'b, C> for Duration {
+    fn decode(d: &mut Decoder<'b>, _: &mut C) -> Result<Self, decode::Error> {
+        let result = match d.datatype()? {
+            Type::U8 | Type::U16 | Type::U32 | Type::U64 => Self::from_secs(d.u64()?),
+            Type::Array => {
+                if d.array()? != Some(2) {
+                    return Err(decode::Error::message("Invalid duration array length."));
+                }
+                let value = d.u64()?;
+                DurationUnit::try_from(d.u8()?).and_then(|unit| Self::new(value, unit))
+            }
+            x => return Err(decode::Error::type_mismatch(x)),
+        };
+        result.map_err(|_| decode::Error::message("Duration out of range."))
+    }
+}
+
+#[cfg(feature = "std")]
+impl serde::Serialize for Duration {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Accepts either an integer number of seconds or a string with a unit
+/// suffix, so configuration files can say `"30d"`.
+#[cfg(feature = "std")]
+impl<'de> serde::Deserialize<'de> for Duration {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Secs(u64),
+            Str(alloc::string::String),
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Secs(secs) => Self::from_secs(secs),
+            Repr::Str(s) => s.parse(),
+        }
+        .map_err(|e| serde::de::Error::custom(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn units() {
+        assert_eq!(Duration::new(2, DurationUnit::Hours).unwrap().secs(), 7200);
+        assert_eq!(Duration::from_days(1).unwrap().secs(), 86400);
+        assert_eq!(
+            Duration::from_secs(7200).unwrap().as_unit(),
+            (2, DurationUnit::Hours)
+        );
+        assert_eq!(
+            Duration::from_secs(90).unwrap().as_unit(),
+            (90, DurationUnit::Seconds)
+        );
+        assert_eq!(Duration::ZERO.as_unit(), (0, DurationUnit::Seconds));
+    }
+
+    #[test]
+    fn range() {
+        assert!(Duration::from_secs(Duration::MAX.secs() + 1).is_err());
+        assert!(Duration::new(u64::MAX, DurationUnit::Days).is_err());
+
+        let min = Duration::from_minutes(1).unwrap();
+        let max = Duration::from_hours(1).unwrap();
+        assert!(Duration::from_secs(59)
+            .unwrap()
+            .check_range(min, max)
+            .is_err());
+        assert!(Duration::from_secs(60)
+            .unwrap()
+            .check_range(min, max)
+            .is_ok());
+        assert!(Duration::from_secs(3601)
+            .unwrap()
+            .check_range(min, max)
+            .is_err());
+    }
+
+    #[test]
+    fn parse_display() {
+        for (s, secs, display) in [
+            ("90", 90, "90s"),
+            ("120s", 120, "2m"),
+            ("15m", 900, "15m"),
+            ("12h", 43200, "12h"),
+            ("30d", 2_592_000, "30d"),
+        ] {
+            let d: Duration = s.parse().unwrap();
+            assert_eq!(d.secs(), secs);
+            assert_eq!(d.to_string(), display);
+        }
+        assert!("".parse::<Duration>().is_err());
+        assert!("5w".parse::<Duration>().is_err());
+        assert!("h".parse::<Duration>().is_err());
+    }
+
+    #[test]
+    fn cbor() {
+        let d = Duration::from_hours(36).unwrap();
+        let bytes = minicbor::to_vec(d).unwrap();
+        assert_eq!(minicbor::display(&bytes).to_string(), "[36, 2]");
+        assert_eq!(minicbor::decode::<Duration>(&bytes).unwrap(), d);
+    }
+
+    #[test]
+    fn cbor_plain_integer() {
+        let bytes = minicbor::to_vec(3600u64).unwrap();
+        assert_eq!(
+            minicbor::decode::<Duration>(&bytes).unwrap(),
+            Duration::from_hours(1).unwrap()
+        );
+    }
+
+    #[test]
+    fn cbor_invalid() {
+        let bytes = minicbor::to_vec((1u64, 9u8)).unwrap();
+        assert!(minicbor::decode::<Duration>(&bytes).is_err());
+        let bytes = minicbor::to_vec(u64::MAX).unwrap();
+        assert!(minicbor::decode::<Duration>(&bytes).is_err());
+    }
+
+    #[test]
+    fn std_conversion() {
+        let d = Duration::from_minutes(5).unwrap();
+        let std: std::time::Duration = d.into();
+        assert_eq!(std.as_secs(), 300);
+        assert_eq!(Duration::try_from(std).unwrap(), d);
+    }
+
+    #[test]
+    fn timestamp_add() {
+        let t = Timestamp::new(1000).unwrap() + Duration::from_minutes(1).unwrap();
+        assert_eq!(t.secs(), 1060);
+        assert_eq!(
+            Duration::between(Timestamp::new(1000).unwrap(), t),
+            Duration::from_secs(60).unwrap()
+        );
+    }
+}
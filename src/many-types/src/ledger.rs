@@ -1,9 +1,10 @@
 use crate::{cbor::CborNull, cbor_type_decl, Either, Percent};
+use many_error::ManyError;
 use many_identity::Address;
 use minicbor::data::{Tag, Type};
 use minicbor::{encode, Decode, Decoder, Encode, Encoder};
 use num_bigint::{BigInt, BigUint};
-use num_traits::{Num, ToPrimitive};
+use num_traits::{CheckedDiv, CheckedSub, Num, ToPrimitive};
 use serde::de::Unexpected;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
@@ -70,6 +71,96 @@ impl TokenAmount {
     pub fn to_vec(&self) -> Vec<u8> {
         self.0.to_bytes_be()
     }
+
+    /// Amounts are unbounded, so additions never overflow. This exists for
+    /// symmetry with the other checked operations.
+    pub fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        Some(Self(&self.0 + &rhs.0))
+    }
+
+    /// Returns `None` if `rhs` is larger than `self`.
+    pub fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        self.0.checked_sub(&rhs.0).map(Self)
+    }
+
+    /// Amounts are unbounded, so multiplications never overflow. This exists
+    /// for symmetry with the other checked operations.
+    pub fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        Some(Self(&self.0 * &rhs.0))
+    }
+
+    /// Returns `None` if `rhs` is zero. The result is rounded down.
+    pub fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        self.0.checked_div(&rhs.0).map(Self)
+    }
+
+    pub fn saturating_add(&self, rhs: &Self) -> Self {
+        Self(&self.0 + &rhs.0)
+    }
+
+    /// Returns zero if `rhs` is larger than `self`.
+    pub fn saturating_sub(&self, rhs: &Self) -> Self {
+        self.checked_sub(rhs).unwrap_or_default()
+    }
+
+    pub fn saturating_mul(&self, rhs: &Self) -> Self {
+        Self(&self.0 * &rhs.0)
+    }
+
+    /// Format the amount of a token with `decimals` decimals, e.g. `1.5` for
+    /// an amount of `1500000` with 6 decimals. Trailing zeros are omitted.
+    /// ```
+    /// use many_types::ledger::TokenAmount;
+    /// assert_eq!(TokenAmount::from(1_500_000u64).to_decimal_string(6), "1.5");
+    /// assert_eq!(TokenAmount::from(42u64).to_decimal_string(6), "0.000042");
+    /// assert_eq!(TokenAmount::from(42u64).to_decimal_string(0), "42");
+    /// ```
+    pub fn to_decimal_string(&self, decimals: u64) -> String {
+        let digits = self.0.to_str_radix(10);
+        let decimals = decimals as usize;
+        if decimals == 0 {
+            return digits;
+        }
+
+        let digits = format!("{digits:0>width$}", width = decimals + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - decimals);
+        match fraction.trim_end_matches('0') {
+            "" => integer.to_string(),
+            fraction => format!("{integer}.{fraction}"),
+        }
+    }
+
+    /// Parse the amount of a token with `decimals` decimals, e.g. `1.5` is an
+    /// amount of `1500000` with 6 decimals. This is the reverse of
+    /// [TokenAmount::to_decimal_string].
+    pub fn from_decimal_str(s: &str, decimals: u64) -> Result<Self, ManyError> {
+        let (integer, fraction) = s.split_once('.').unwrap_or((s, ""));
+        if (integer.is_empty() && fraction.is_empty())
+            || !integer
+                .bytes()
+                .chain(fraction.bytes())
+                .all(|b| b.is_ascii_digit())
+        {
+            return Err(ManyError::unknown(format!("Invalid token amount: {s:?}.")));
+        }
+        if fraction.len() as u64 > decimals {
+            return Err(ManyError::unknown(format!(
+                "Token amount {s:?} has more than {decimals} decimals."
+            )));
+        }
+
+        let digits = format!("{integer}{fraction:0<width$}", width = decimals as usize);
+        TokenAmountStorage::from_str_radix(&digits, 10)
+            .map(Self)
+            .map_err(|e| ManyError::unknown(format!("Invalid token amount {s:?}: {e}.")))
+    }
+}
+
+impl Percent {
+    /// Apply the percent to an amount of tokens, rounding down.
+    pub fn apply_to(&self, amount: TokenAmount) -> TokenAmount {
+        amount * *self
+    }
 }
 
 impl std::ops::Mul<Percent> for TokenAmount {
@@ -235,6 +326,12 @@ impl Display for TokenAmount {
     }
 }
 
+impl crate::cddl::Cddl for TokenAmount {
+    fn cddl() -> String {
+        "uint / #6.2(bstr)".to_string()
+    }
+}
+
 impl<C> Encode<C> for TokenAmount {
     fn encode<W: encode::Write>(
         &self,
@@ -449,4 +546,99 @@ mod test {
         d *= &b;
         assert_eq!(d, TokenAmount::from(2523930316u64));
     }
+
+    #[test]
+    fn token_amount_checked() {
+        let a = TokenAmount::from(12345u64);
+        let b = TokenAmount::from(56789u64);
+        let zero = TokenAmount::zero();
+
+        assert_eq!(a.checked_add(&b), Some(TokenAmount::from(69134u64)));
+        assert_eq!(b.checked_sub(&a), Some(TokenAmount::from(44444u64)));
+        assert_eq!(a.checked_sub(&b), None);
+        assert_eq!(a.checked_sub(&a), Some(zero.clone()));
+        assert_eq!(a.checked_mul(&b), Some(TokenAmount::from(701060205u64)));
+        assert_eq!(b.checked_div(&a), Some(TokenAmount::from(4u64)));
+        assert_eq!(a.checked_div(&zero), None);
+
+        assert_eq!(a.saturating_add(&b), TokenAmount::from(69134u64));
+        assert_eq!(b.saturating_sub(&a), TokenAmount::from(44444u64));
+        assert_eq!(a.saturating_sub(&b), zero);
+        assert_eq!(a.saturating_mul(&b), TokenAmount::from(701060205u64));
+
+        let max = TokenAmount::from(u128::MAX);
+        assert_eq!(
+            max.checked_add(&TokenAmount::from(1u8)),
+            Some(TokenAmount::from(BigUint::from(u128::MAX) + 1u8))
+        );
+    }
+
+    #[test]
+    fn percent_apply_to() {
+        let amount = TokenAmount::from(5_000_000u64);
+        assert_eq!(Percent::ZERO.apply_to(amount.clone()), TokenAmount::zero());
+        assert_eq!(Percent::ONE_HUNDRED.apply_to(amount.clone()), amount);
+        assert_eq!(
+            Percent::new(0, 0x800000).apply_to(amount.clone()),
+            TokenAmount::from(9765u64)
+        );
+        assert_eq!(
+            "12.5%".parse::<Percent>().unwrap().apply_to(amount),
+            TokenAmount::from(625_000u64)
+        );
+
+        // Rounded down.
+        let ten = "10%".parse::<Percent>().unwrap();
+        assert_eq!(
+            ten.apply_to(TokenAmount::from(100u64)),
+            TokenAmount::from(9u64)
+        );
+        assert_eq!(ten.apply_to_u64(100), 9);
+    }
+
+    #[test]
+    fn token_amount_decimal_string() {
+        for (amount, decimals, string) in [
+            (0u64, 0, "0"),
+            (0, 9, "0"),
+            (1, 9, "0.000000001"),
+            (1_000_000_000, 9, "1"),
+            (1_500_000_000, 9, "1.5"),
+            (123_456_789_012, 9, "123.456789012"),
+            (100, 2, "1"),
+            (101, 2, "1.01"),
+            (42, 0, "42"),
+        ] {
+            let amount = TokenAmount::from(amount);
+            assert_eq!(amount.to_decimal_string(decimals), string);
+            assert_eq!(
+                TokenAmount::from_decimal_str(string, decimals).unwrap(),
+                amount
+            );
+        }
+
+        let big = TokenAmount::from(BigUint::from(u128::MAX) * 1000u32);
+        let string = big.to_decimal_string(3);
+        assert_eq!(string, u128::MAX.to_string());
+        assert_eq!(TokenAmount::from_decimal_str(&string, 3).unwrap(), big);
+
+        assert_eq!(
+            TokenAmount::from_decimal_str("1.50", 9).unwrap(),
+            TokenAmount::from(1_500_000_000u64)
+        );
+        assert_eq!(
+            TokenAmount::from_decimal_str(".5", 1).unwrap(),
+            TokenAmount::from(5u64)
+        );
+        assert_eq!(
+            TokenAmount::from_decimal_str("5.", 1).unwrap(),
+            TokenAmount::from(50u64)
+        );
+        for invalid in ["", ".", "1.2.3", "-1", "1,5", " 1", "1e9", "0.123"] {
+            assert!(
+                TokenAmount::from_decimal_str(invalid, 2).is_err(),
+                "{invalid}"
+            );
+        }
+    }
 }
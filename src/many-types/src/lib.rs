@@ -1,30 +1,55 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::{Debug, Formatter};
+use core::ops::{Bound, RangeBounds, Shl};
+use core::str::FromStr;
 use many_error::ManyError;
 use minicbor::data::{Tag, Type};
 use minicbor::encode::{Error, Write};
 use minicbor::{decode, Decode, Decoder, Encode, Encoder};
-use std::collections::BTreeSet;
-use std::fmt::{Debug, Formatter};
-use std::ops::{Bound, RangeBounds, Shl};
-use std::str::FromStr;
 
 pub mod attributes;
 pub mod blockchain;
 pub mod cbor;
+pub mod cddl;
+#[cfg(feature = "std")]
 pub mod compute;
+#[cfg(feature = "std")]
+pub mod diag;
+pub mod duration;
+#[cfg(feature = "std")]
 pub mod either;
 pub mod identity {
     pub use many_identity::*;
 }
+#[cfg(feature = "std")]
 pub mod ledger;
+#[cfg(feature = "std")]
 pub mod memo;
+pub mod min_height;
+pub mod priority;
+pub mod projection;
 pub mod proof;
+#[cfg(feature = "std")]
 pub mod web;
 
 use attributes::AttributeId;
+pub use duration::{Duration, DurationUnit};
+#[cfg(feature = "std")]
 pub use either::Either;
-pub use memo::Memo;
+#[cfg(feature = "std")]
+pub use memo::{Memo, MemoLimits, MemoSection};
 pub use proof::{ProofOperation, PROOF};
 
+#[cfg(feature = "std")]
 pub mod legacy {
     pub use crate::memo::DataLegacy;
     pub use crate::memo::MemoLegacy;
@@ -55,23 +80,126 @@ macro_rules! cbor_type_decl {
                     #[n( $fidx )] pub $fname: $ftype,
                 )+
             }
+
+            impl $crate::cddl::Cddl for $name {
+                fn cddl() -> String {
+                    #[allow(unused_imports)]
+                    use $crate::cddl::{CddlOptional as _, CddlOrAny as _, CddlUnknown as _};
+                    $crate::cddl::map(&[
+                        $( (&&&$crate::cddl::CddlProbe::<$ftype>::new()).map_entry($fidx) ),+
+                    ])
+                }
+            }
         )*
     };
 }
 
 /// A deterministic (fixed point) percent value that can be multiplied with
 /// numbers and rounded down.
+///
+/// The value is a ratio, i.e. `Percent::new(1, 0)` is 100%. It is formatted
+/// and parsed as a percentage, e.g. `12.5%`, with every decimal of the fixed
+/// point value so the formatting round-trips.
 #[repr(transparent)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 #[must_use]
 pub struct Percent(pub fixed::types::U32F32);
 
+/// The number of fractional bits of a `Percent`.
+const PERCENT_FRACTION_BITS: u32 = 32;
+
 impl Percent {
+    pub const ZERO: Percent = Percent(fixed::types::U32F32::ZERO);
+    pub const ONE_HUNDRED: Percent = Percent(fixed::types::U32F32::ONE);
+
     pub fn new(i: u32, fraction: u32) -> Self {
         Self(fixed::types::U32F32::from_bits(
             u64::from(i).shl(32) + u64::from(fraction),
         ))
     }
+
+    /// The percent `numerator / denominator`, rounded down. `None` if the
+    /// denominator is zero or the ratio does not fit.
+    pub fn from_ratio(numerator: u64, denominator: u64) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+        let bits = (u128::from(numerator) << PERCENT_FRACTION_BITS) / u128::from(denominator);
+        u64::try_from(bits)
+            .ok()
+            .map(|bits| Self(fixed::types::U32F32::from_bits(bits)))
+    }
+
+    /// Apply the percent to a number, rounding down.
+    pub fn apply_to_u64(&self, n: u64) -> u64 {
+        let result = (u128::from(n) * u128::from(self.0.to_bits())) >> PERCENT_FRACTION_BITS;
+        u64::try_from(result).unwrap_or(u64::MAX)
+    }
+}
+
+impl core::fmt::Display for Percent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        const MASK: u128 = (1 << PERCENT_FRACTION_BITS) - 1;
+
+        // Every fixed point value has a finite decimal representation.
+        let hundredths = u128::from(self.0.to_bits()) * 100;
+        write!(f, "{}", hundredths >> PERCENT_FRACTION_BITS)?;
+        let mut fraction = hundredths & MASK;
+        if fraction != 0 {
+            write!(f, ".")?;
+        }
+        while fraction != 0 {
+            fraction *= 10;
+            write!(f, "{}", fraction >> PERCENT_FRACTION_BITS)?;
+            fraction &= MASK;
+        }
+        write!(f, "%")
+    }
+}
+
+impl FromStr for Percent {
+    type Err = ManyError;
+
+    /// Parse a percentage, e.g. `12.5%` or `12.5`, rounding down to the
+    /// precision of the fixed point value.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ManyError::unknown(format!("Invalid percent {s:?}."));
+
+        let number = s.trim().trim_end_matches('%').trim_end();
+        let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+        if (integer.is_empty() && fraction.is_empty())
+            || !integer
+                .bytes()
+                .chain(fraction.bytes())
+                .all(|b| b.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        // Digits after the 32nd decimal cannot change the value once rounded
+        // down, as 2^32 / 10^32 == 1 / 5^32.
+        let fraction = &fraction[..fraction.len().min(PERCENT_FRACTION_BITS as usize)];
+        let digits = fraction.len() as u32;
+        let fraction = if fraction.is_empty() {
+            0
+        } else {
+            fraction.parse::<u128>().map_err(|_| invalid())?
+        };
+        let fraction = (fraction << (PERCENT_FRACTION_BITS - digits)) / 5u128.pow(digits);
+
+        let integer = if integer.is_empty() {
+            0
+        } else {
+            integer.parse::<u128>().map_err(|_| invalid())?
+        };
+        let bits = integer
+            .checked_mul(1 << PERCENT_FRACTION_BITS)
+            .and_then(|i| i.checked_add(fraction))
+            .map(|hundredths| hundredths / 100)
+            .and_then(|bits| u64::try_from(bits).ok())
+            .ok_or_else(invalid)?;
+        Ok(Self(fixed::types::U32F32::from_bits(bits)))
+    }
 }
 
 impl<C> Encode<C> for Percent {
@@ -162,6 +290,7 @@ where
 pub struct Timestamp(u64);
 
 impl Timestamp {
+    #[cfg(feature = "std")]
     pub fn now() -> Self {
         Self::new(
             std::time::SystemTime::now()
@@ -176,6 +305,7 @@ impl Timestamp {
         Ok(Self(secs))
     }
 
+    #[cfg(feature = "std")]
     pub fn from_system_time(t: std::time::SystemTime) -> Result<Self, ManyError> {
         let d = t.duration_since(std::time::UNIX_EPOCH).map_err(|_| {
             ManyError::unknown("duration value can not represent system time".to_string())
@@ -183,6 +313,7 @@ impl Timestamp {
         Ok(Self(d.as_secs()))
     }
 
+    #[cfg(feature = "std")]
     pub fn as_system_time(&self) -> Result<std::time::SystemTime, ManyError> {
         std::time::UNIX_EPOCH
             .checked_add(std::time::Duration::new(self.0, 0))
@@ -196,7 +327,7 @@ impl Timestamp {
     }
 }
 
-impl std::ops::Add<u64> for Timestamp {
+impl core::ops::Add<u64> for Timestamp {
     type Output = Timestamp;
 
     fn add(self, rhs: u64) -> Self::Output {
@@ -264,7 +395,7 @@ impl<T> RangeBounds<T> for CborRange<T> {
 }
 
 impl<T: Debug> Debug for CborRange<T> {
-    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
         self.start.fmt(fmt)?;
         write!(fmt, "..")?;
         self.end.fmt(fmt)?;
@@ -291,6 +422,228 @@ impl<T: PartialOrd<T>> CborRange<T> {
     }
 }
 
+/// The order of two start bounds; the greater one starts after the other.
+fn cmp_start_bounds<T: Ord>(a: &Bound<T>, b: &Bound<T>) -> Ordering {
+    match (a, b) {
+        (Bound::Unbounded, Bound::Unbounded) => Ordering::Equal,
+        (Bound::Unbounded, _) => Ordering::Less,
+        (_, Bound::Unbounded) => Ordering::Greater,
+        (Bound::Included(x), Bound::Included(y)) | (Bound::Excluded(x), Bound::Excluded(y)) => {
+            x.cmp(y)
+        }
+        (Bound::Included(x), Bound::Excluded(y)) => x.cmp(y).then(Ordering::Less),
+        (Bound::Excluded(x), Bound::Included(y)) => x.cmp(y).then(Ordering::Greater),
+    }
+}
+
+/// The order of two end bounds; the greater one ends after the other.
+fn cmp_end_bounds<T: Ord>(a: &Bound<T>, b: &Bound<T>) -> Ordering {
+    match (a, b) {
+        (Bound::Unbounded, Bound::Unbounded) => Ordering::Equal,
+        (Bound::Unbounded, _) => Ordering::Greater,
+        (_, Bound::Unbounded) => Ordering::Less,
+        (Bound::Included(x), Bound::Included(y)) | (Bound::Excluded(x), Bound::Excluded(y)) => {
+            x.cmp(y)
+        }
+        (Bound::Included(x), Bound::Excluded(y)) => x.cmp(y).then(Ordering::Greater),
+        (Bound::Excluded(x), Bound::Included(y)) => x.cmp(y).then(Ordering::Less),
+    }
+}
+
+impl<T: Ord + Clone> CborRange<T> {
+    /// The range of any other range type, e.g. `CborRange::from_bounds(&(1..5))`.
+    pub fn from_bounds(bounds: &impl RangeBounds<T>) -> Self {
+        Self {
+            start: bounds.start_bound().cloned(),
+            end: bounds.end_bound().cloned(),
+        }
+    }
+
+    /// Whether the range cannot contain any value. The order of `T` is
+    /// considered dense, i.e. `(1, 2)` (both excluded) is not empty.
+    pub fn is_empty(&self) -> bool {
+        match (&self.start, &self.end) {
+            (Bound::Included(start), Bound::Included(end)) => start > end,
+            (Bound::Included(start), Bound::Excluded(end))
+            | (Bound::Excluded(start), Bound::Included(end))
+            | (Bound::Excluded(start), Bound::Excluded(end)) => start >= end,
+            _ => false,
+        }
+    }
+
+    /// Whether a value is before the start of the range.
+    fn is_before_start(&self, value: &T) -> bool {
+        match &self.start {
+            Bound::Included(start) => value < start,
+            Bound::Excluded(start) => value <= start,
+            Bound::Unbounded => false,
+        }
+    }
+
+    /// Whether a value is after the end of the range.
+    fn is_after_end(&self, value: &T) -> bool {
+        match &self.end {
+            Bound::Included(end) => value > end,
+            Bound::Excluded(end) => value >= end,
+            Bound::Unbounded => false,
+        }
+    }
+
+    /// The values contained in both ranges. It can be empty.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let start = match cmp_start_bounds(&self.start, &other.start) {
+            Ordering::Less => &other.start,
+            _ => &self.start,
+        };
+        let end = match cmp_end_bounds(&self.end, &other.end) {
+            Ordering::Greater => &other.end,
+            _ => &self.end,
+        };
+        Self {
+            start: start.clone(),
+            end: end.clone(),
+        }
+    }
+
+    /// The values contained in either range, if they overlap or touch.
+    /// `None` if there are values between the two ranges.
+    pub fn union(&self, other: &Self) -> Option<Self> {
+        if self.is_empty() {
+            return Some(other.clone());
+        }
+        if other.is_empty() {
+            return Some(self.clone());
+        }
+
+        let (first, second) = match cmp_start_bounds(&self.start, &other.start) {
+            Ordering::Greater => (other, self),
+            _ => (self, other),
+        };
+        let contiguous = match (&first.end, &second.start) {
+            (Bound::Unbounded, _) | (_, Bound::Unbounded) => true,
+            (Bound::Excluded(end), Bound::Excluded(start)) => start < end,
+            (Bound::Included(end), Bound::Included(start))
+            | (Bound::Included(end), Bound::Excluded(start))
+            | (Bound::Excluded(end), Bound::Included(start)) => start <= end,
+        };
+        if !contiguous {
+            return None;
+        }
+
+        let end = match cmp_end_bounds(&first.end, &second.end) {
+            Ordering::Less => &second.end,
+            _ => &first.end,
+        };
+        Some(Self {
+            start: first.start.clone(),
+            end: end.clone(),
+        })
+    }
+
+    /// Restrict the range to a window, e.g. the heights up to the latest
+    /// block (`..=latest`).
+    pub fn clamp(&self, window: &impl RangeBounds<T>) -> Self {
+        self.intersection(&Self::from_bounds(window))
+    }
+
+    /// Iterate over the items of `iter` whose key is in the range, up to
+    /// `count` items. `iter` is sorted by key in `order`, e.g. the keys of a
+    /// storage. Items without a key, e.g. errors, are returned as is.
+    ///
+    /// The items before the range are skipped, and the iteration stops at the
+    /// first item after it. [RangePage::rest] is the range of the next page.
+    pub fn page<I, F>(self, iter: I, order: SortOrder, count: usize, key: F) -> RangePage<I, T, F>
+    where
+        I: Iterator,
+        F: FnMut(&I::Item) -> Option<T>,
+    {
+        RangePage {
+            inner: iter,
+            range: self,
+            key,
+            descending: order == SortOrder::Descending,
+            remaining: count,
+            last: None,
+        }
+    }
+}
+
+/// A page of the items of a sorted iterator, see [CborRange::page].
+#[must_use]
+pub struct RangePage<I, T, F> {
+    inner: I,
+    range: CborRange<T>,
+    key: F,
+    descending: bool,
+    remaining: usize,
+    last: Option<T>,
+}
+
+impl<I, T: Ord + Clone, F> RangePage<I, T, F> {
+    /// The range of the keys after the ones returned so far, in the order of
+    /// the iteration. This is the range of the next page once this one is
+    /// exhausted.
+    pub fn rest(&self) -> CborRange<T> {
+        match (&self.last, self.descending) {
+            (None, _) => self.range.clone(),
+            (Some(last), false) => CborRange {
+                start: Bound::Excluded(last.clone()),
+                end: self.range.end.clone(),
+            },
+            (Some(last), true) => CborRange {
+                start: self.range.start.clone(),
+                end: Bound::Excluded(last.clone()),
+            },
+        }
+    }
+}
+
+impl<I, T, F> Iterator for RangePage<I, T, F>
+where
+    I: Iterator,
+    T: Ord + Clone,
+    F: FnMut(&I::Item) -> Option<T>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining > 0 {
+            let item = self.inner.next()?;
+            let key = match (self.key)(&item) {
+                Some(key) => key,
+                None => {
+                    self.remaining -= 1;
+                    return Some(item);
+                }
+            };
+
+            let (not_reached, passed) = if self.descending {
+                (
+                    self.range.is_after_end(&key),
+                    self.range.is_before_start(&key),
+                )
+            } else {
+                (
+                    self.range.is_before_start(&key),
+                    self.range.is_after_end(&key),
+                )
+            };
+            if not_reached {
+                continue;
+            }
+            if passed {
+                self.remaining = 0;
+                return None;
+            }
+
+            self.remaining -= 1;
+            self.last = Some(key);
+            return Some(item);
+        }
+        None
+    }
+}
+
 impl<T, C> Encode<C> for CborRange<T>
 where
     T: Encode<C>,
@@ -428,21 +781,20 @@ impl<'b, C> Decode<'b, C> for SortOrder {
     }
 }
 
-#[derive(Copy, Clone, Default, Ord, PartialOrd, Eq, PartialEq)]
-enum AttributeRelatedIndexInner {
-    #[default]
-    None,
-    One([u32; 1]),
-    Two([u32; 2]),
-    Three([u32; 3]),
-    Four([u32; 4]),
-}
+/// The indices of an [AttributeRelatedIndex]. Most have at most 4 indices,
+/// which are kept inline.
+type AttributeRelatedIndices = smallvec::SmallVec<[u32; 4]>;
 
-#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+/// An attribute, or an index nested in an attribute, e.g. `2.0.1` is the index
+/// `1` of the index `0` of the attribute `2`.
+///
+/// It is encoded as the attribute alone, or nested pairs of the attribute or
+/// an index and the following ones, e.g. `[2, [0, 1]]`.
+#[derive(Clone, Eq, PartialEq)]
 #[must_use]
 pub struct AttributeRelatedIndex {
     pub attribute: AttributeId,
-    indices: AttributeRelatedIndexInner,
+    indices: AttributeRelatedIndices,
 }
 
 impl AttributeRelatedIndex {
@@ -450,38 +802,18 @@ impl AttributeRelatedIndex {
     pub const fn new(attribute: AttributeId) -> Self {
         Self {
             attribute,
-            indices: AttributeRelatedIndexInner::None,
+            indices: AttributeRelatedIndices::new_const(),
         }
     }
 
     #[inline]
-    pub const fn with_index(self, index: u32) -> Self {
-        let indices = match self.indices {
-            AttributeRelatedIndexInner::None => AttributeRelatedIndexInner::One([index]),
-            AttributeRelatedIndexInner::One(a) => AttributeRelatedIndexInner::Two([a[0], index]),
-            AttributeRelatedIndexInner::Two(a) => {
-                AttributeRelatedIndexInner::Three([a[0], a[1], index])
-            }
-            AttributeRelatedIndexInner::Three(a) => {
-                AttributeRelatedIndexInner::Four([a[0], a[1], a[2], index])
-            }
-            AttributeRelatedIndexInner::Four(a) => AttributeRelatedIndexInner::Four(a),
-        };
-
-        Self {
-            attribute: self.attribute,
-            indices,
-        }
+    pub fn with_index(mut self, index: u32) -> Self {
+        self.indices.push(index);
+        self
     }
 
-    pub const fn indices(&self) -> &[u32] {
-        match &self.indices {
-            AttributeRelatedIndexInner::None => &[],
-            AttributeRelatedIndexInner::One(a) => a,
-            AttributeRelatedIndexInner::Two(a) => a,
-            AttributeRelatedIndexInner::Three(a) => a,
-            AttributeRelatedIndexInner::Four(a) => a,
-        }
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
     }
 
     pub fn flattened(&self) -> Vec<u32> {
@@ -489,8 +821,25 @@ impl AttributeRelatedIndex {
     }
 }
 
+impl Ord for AttributeRelatedIndex {
+    /// Shallower indices are ordered first, so the order of the keys of maps
+    /// (e.g. data attributes) does not depend on how deep indices can be.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.attribute
+            .cmp(&other.attribute)
+            .then_with(|| self.indices.len().cmp(&other.indices.len()))
+            .then_with(|| self.indices.cmp(&other.indices))
+    }
+}
+
+impl PartialOrd for AttributeRelatedIndex {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Debug for AttributeRelatedIndex {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         let mut tuple = f.debug_tuple("AttributeRelatedIndex");
 
         tuple.field(&self.attribute);
@@ -503,33 +852,56 @@ impl Debug for AttributeRelatedIndex {
     }
 }
 
+impl core::fmt::Display for AttributeRelatedIndex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.attribute)?;
+        for x in self.indices() {
+            write!(f, ".{x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for AttributeRelatedIndex {
+    type Err = ManyError;
+
+    /// Parse a dotted path, e.g. `2.0.1`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ManyError::unknown(format!("Invalid attribute related index {s:?}."));
+        let path = s
+            .split('.')
+            .map(|x| x.parse::<u32>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (attribute, indices) = path.split_first().ok_or_else(invalid)?;
+        Ok(Self {
+            attribute: *attribute,
+            indices: AttributeRelatedIndices::from_slice(indices),
+        })
+    }
+}
+
+impl cddl::Cddl for AttributeRelatedIndex {
+    fn cddl() -> String {
+        // The nested pairs are recursive, which can't be written inline.
+        "uint / [uint, uint / [uint, any]]".into()
+    }
+}
+
 impl<C> Encode<C> for AttributeRelatedIndex {
     fn encode<W: Write>(&self, e: &mut Encoder<W>, _: &mut C) -> Result<(), Error<W::Error>> {
-        match self.indices() {
-            [] => {
+        let (last, nested) = match self.indices.split_last() {
+            Some((last, indices)) => (*last, indices),
+            None => {
                 e.encode(self.attribute)?;
+                return Ok(());
             }
-            a => {
-                e.array(2)?.encode(self.attribute)?;
-
-                let mut chunks = a.chunks(2).peekable();
-                while let Some(chunk) = chunks.next() {
-                    match chunk {
-                        [a] => {
-                            e.u32(*a)?;
-                        }
-                        [a, b] => {
-                            if chunks.peek().is_none() {
-                                e.array(2)?.u32(*a)?.u32(*b)?;
-                            } else {
-                                e.array(2)?.u32(chunk[0])?.array(2)?.u32(chunk[1])?;
-                            }
-                        }
-                        _ => unreachable!(),
-                    }
-                }
-            }
+        };
+
+        e.array(2)?.encode(self.attribute)?;
+        for index in nested {
+            e.array(2)?.u32(*index)?;
         }
+        e.u32(last)?;
         Ok(())
     }
 }
@@ -560,10 +932,203 @@ impl<'b, C> Decode<'b, C> for AttributeRelatedIndex {
     }
 }
 
+#[test]
+fn percent_display() {
+    assert_eq!(Percent::ZERO.to_string(), "0%");
+    assert_eq!(Percent::ONE_HUNDRED.to_string(), "100%");
+    assert_eq!(Percent::new(0, 0x8000_0000).to_string(), "50%");
+    assert_eq!(Percent::new(0, 0x800000).to_string(), "0.1953125%");
+    assert_eq!(Percent::new(2, 0x4000_0000).to_string(), "225%");
+}
+
+#[test]
+fn percent_from_str() {
+    assert_eq!(
+        Percent::from_str("50%").unwrap(),
+        Percent::new(0, 0x8000_0000)
+    );
+    assert_eq!(
+        Percent::from_str(" 50 ").unwrap(),
+        Percent::new(0, 0x8000_0000)
+    );
+    assert_eq!(
+        Percent::from_str("225%").unwrap(),
+        Percent::new(2, 0x4000_0000)
+    );
+    assert_eq!(
+        Percent::from_str(".5%").unwrap(),
+        Percent::new(0, 0x0147_AE14)
+    );
+    assert_eq!(Percent::from_str("0%").unwrap(), Percent::ZERO);
+
+    // Rounded down.
+    assert_eq!(
+        Percent::from_str("10%").unwrap(),
+        Percent::new(0, 0x1999_9999)
+    );
+
+    for s in [
+        "",
+        "%",
+        ".",
+        "-1%",
+        "1.2.3%",
+        "1e2%",
+        "5 0%",
+        "99999999999999%",
+    ] {
+        assert!(Percent::from_str(s).is_err(), "{s:?}");
+    }
+
+    for p in [
+        Percent::ZERO,
+        Percent::ONE_HUNDRED,
+        Percent::new(0, 1),
+        Percent::new(0, 0x800000),
+        Percent::new(u32::MAX, u32::MAX),
+    ] {
+        assert_eq!(Percent::from_str(&p.to_string()).unwrap(), p);
+    }
+}
+
+#[test]
+fn percent_ord() {
+    assert!(Percent::ZERO < Percent::new(0, 1));
+    assert!(Percent::from_str("12.5%").unwrap() < Percent::from_str("12.51%").unwrap());
+    assert_eq!(Percent::from_ratio(1, 8), Percent::from_str("12.5%").ok());
+    assert_eq!(Percent::from_ratio(1, 0), None);
+    assert_eq!(Percent::from_ratio(u64::MAX, 1), None);
+
+    // Percents can be keys of CBOR maps.
+    let map = alloc::collections::BTreeMap::from([
+        (Percent::ONE_HUNDRED, 2u8),
+        (Percent::ZERO, 0u8),
+        (Percent::new(0, 0x8000_0000), 1u8),
+    ]);
+    let bytes = minicbor::to_vec(&map).unwrap();
+    assert_eq!(
+        minicbor::display(&bytes).to_string(),
+        "{0: 0, 2147483648: 1, 4294967296: 2}"
+    );
+    assert_eq!(
+        minicbor::decode::<alloc::collections::BTreeMap<Percent, u8>>(&bytes).unwrap(),
+        map
+    );
+}
+
+#[test]
+fn cbor_range_is_empty() {
+    assert!(!CborRange::<u64>::default().is_empty());
+    assert!(!CborRange::from_bounds(&(1u64..=1)).is_empty());
+    assert!(!CborRange::from_bounds(&(1u64..2)).is_empty());
+    assert!(CborRange::from_bounds(&(1u64..1)).is_empty());
+    assert!(CborRange {
+        start: Bound::Included(2u64),
+        end: Bound::Included(1),
+    }
+    .is_empty());
+    assert!(CborRange {
+        start: Bound::Excluded(1u64),
+        end: Bound::Included(1),
+    }
+    .is_empty());
+}
+
+#[test]
+fn cbor_range_intersection() {
+    let a = CborRange::from_bounds(&(1u64..10));
+    let b = CborRange::from_bounds(&(5u64..=20));
+    assert_eq!(a.intersection(&b), CborRange::from_bounds(&(5u64..10)));
+    assert_eq!(b.intersection(&a), CborRange::from_bounds(&(5u64..10)));
+    assert_eq!(a.intersection(&CborRange::default()), a);
+
+    // Excluded bounds are tighter than included ones on the same value.
+    let c = CborRange::from_bounds(&(1u64..=10));
+    assert_eq!(a.intersection(&c), a);
+    assert!(a
+        .intersection(&CborRange::from_bounds(&(10u64..)))
+        .is_empty());
+
+    assert_eq!(
+        CborRange::from_bounds(&(5u64..)).clamp(&(..=8u64)),
+        CborRange::from_bounds(&(5u64..=8))
+    );
+}
+
+#[test]
+fn cbor_range_union() {
+    let a = CborRange::from_bounds(&(1u64..5));
+    assert_eq!(
+        a.union(&CborRange::from_bounds(&(3u64..8))),
+        Some(CborRange::from_bounds(&(1u64..8)))
+    );
+    assert_eq!(
+        a.union(&CborRange::from_bounds(&(5u64..8))),
+        Some(CborRange::from_bounds(&(1u64..8)))
+    );
+    assert_eq!(
+        CborRange::from_bounds(&(5u64..8)).union(&a),
+        Some(CborRange::from_bounds(&(1u64..8)))
+    );
+    assert_eq!(a.union(&CborRange::from_bounds(&(6u64..8))), None);
+    assert_eq!(
+        a.union(&CborRange {
+            start: Bound::Excluded(5),
+            end: Bound::Unbounded,
+        }),
+        None
+    );
+    assert_eq!(a.union(&CborRange::from_bounds(&(2u64..3))), Some(a));
+    assert_eq!(a.union(&CborRange::from_bounds(&(9u64..9))), Some(a));
+    assert_eq!(a.union(&CborRange::default()), Some(CborRange::default()));
+}
+
+#[test]
+fn cbor_range_page() {
+    let keys = [1u64, 3, 4, 6, 7, 9, 12];
+    let range = CborRange::from_bounds(&(3u64..=9));
+
+    let mut page = range.page(keys.iter(), SortOrder::Ascending, 3, |k| Some(**k));
+    assert_eq!(page.by_ref().copied().collect::<Vec<_>>(), vec![3, 4, 6]);
+    let rest = page.rest();
+    assert_eq!(
+        rest,
+        CborRange {
+            start: Bound::Excluded(6),
+            end: Bound::Included(9),
+        }
+    );
+    let page = rest.page(keys.iter(), SortOrder::Ascending, 3, |k| Some(**k));
+    assert_eq!(page.copied().collect::<Vec<_>>(), vec![7, 9]);
+
+    let mut page = range.page(keys.iter().rev(), SortOrder::Descending, 2, |k| Some(**k));
+    assert_eq!(page.by_ref().copied().collect::<Vec<_>>(), vec![9, 7]);
+    assert_eq!(
+        page.rest(),
+        CborRange {
+            start: Bound::Included(3),
+            end: Bound::Excluded(7),
+        }
+    );
+
+    // Items without a key are returned as is.
+    let items = [Ok(1u64), Err("error"), Ok(2), Ok(3)];
+    let page = CborRange::from_bounds(&(2u64..)).page(
+        items.iter(),
+        SortOrder::Indeterminate,
+        10,
+        |item| item.as_ref().ok().copied(),
+    );
+    assert_eq!(
+        page.copied().collect::<Vec<_>>(),
+        vec![Err("error"), Ok(2), Ok(3)]
+    );
+}
+
 #[test]
 fn attribute_related_index_encode_0() {
     let i = AttributeRelatedIndex::new(1);
-    let b = minicbor::to_vec(i).unwrap();
+    let b = minicbor::to_vec(&i).unwrap();
     assert_eq!(minicbor::display(&b).to_string(), "1");
     assert_eq!(minicbor::decode::<AttributeRelatedIndex>(&b).unwrap(), i);
 }
@@ -571,7 +1136,7 @@ fn attribute_related_index_encode_0() {
 #[test]
 fn attribute_related_index_encode_1() {
     let i = AttributeRelatedIndex::new(2).with_index(3);
-    let b = minicbor::to_vec(i).unwrap();
+    let b = minicbor::to_vec(&i).unwrap();
     assert_eq!(minicbor::display(&b).to_string(), "[2, 3]");
     assert_eq!(minicbor::decode::<AttributeRelatedIndex>(&b).unwrap(), i);
 }
@@ -579,7 +1144,7 @@ fn attribute_related_index_encode_1() {
 #[test]
 fn attribute_related_index_encode_2() {
     let i = AttributeRelatedIndex::new(4).with_index(5).with_index(6);
-    let b = minicbor::to_vec(i).unwrap();
+    let b = minicbor::to_vec(&i).unwrap();
     assert_eq!(minicbor::display(&b).to_string(), "[4, [5, 6]]");
     assert_eq!(minicbor::decode::<AttributeRelatedIndex>(&b).unwrap(), i);
 }
@@ -590,7 +1155,7 @@ fn attribute_related_index_encode_3() {
         .with_index(8)
         .with_index(9)
         .with_index(10);
-    let b = minicbor::to_vec(i).unwrap();
+    let b = minicbor::to_vec(&i).unwrap();
     assert_eq!(minicbor::display(&b).to_string(), "[7, [8, [9, 10]]]");
     assert_eq!(minicbor::decode::<AttributeRelatedIndex>(&b).unwrap(), i);
 }
@@ -602,7 +1167,7 @@ fn attribute_related_index_encode_4() {
         .with_index(13)
         .with_index(14)
         .with_index(15);
-    let b = minicbor::to_vec(i).unwrap();
+    let b = minicbor::to_vec(&i).unwrap();
     assert_eq!(
         minicbor::display(&b).to_string(),
         "[11, [12, [13, [14, 15]]]]"
@@ -612,19 +1177,63 @@ fn attribute_related_index_encode_4() {
 
 #[test]
 fn attribute_related_index_encode_5() {
-    // Should ignore the fifth index, as we only support 4.
+    // Indices deeper than 4 are kept.
     let i = AttributeRelatedIndex::new(16)
         .with_index(17)
         .with_index(18)
         .with_index(19)
         .with_index(20)
         .with_index(21);
-    let b = minicbor::to_vec(i).unwrap();
+    let b = minicbor::to_vec(&i).unwrap();
     assert_eq!(
         minicbor::display(&b).to_string(),
-        "[16, [17, [18, [19, 20]]]]"
+        "[16, [17, [18, [19, [20, 21]]]]]"
     );
     assert_eq!(minicbor::decode::<AttributeRelatedIndex>(&b).unwrap(), i);
+    assert_eq!(i.indices(), &[17, 18, 19, 20, 21]);
+}
+
+#[test]
+fn attribute_related_index_path() {
+    for (path, index) in [
+        ("1", AttributeRelatedIndex::new(1)),
+        (
+            "2.0.1",
+            AttributeRelatedIndex::new(2).with_index(0).with_index(1),
+        ),
+        (
+            "3.1.4.1.5.9",
+            AttributeRelatedIndex::new(3)
+                .with_index(1)
+                .with_index(4)
+                .with_index(1)
+                .with_index(5)
+                .with_index(9),
+        ),
+    ] {
+        assert_eq!(AttributeRelatedIndex::from_str(path).unwrap(), index);
+        assert_eq!(index.to_string(), path);
+    }
+
+    for path in ["", ".", "1.", ".1", "1..2", "1.-2", "a.b", "1.2 "] {
+        assert!(AttributeRelatedIndex::from_str(path).is_err(), "{path:?}");
+    }
+}
+
+#[test]
+fn attribute_related_index_order() {
+    // Shallower indices first, as when the depth was limited.
+    let mut indices = vec![
+        AttributeRelatedIndex::new(2).with_index(0),
+        AttributeRelatedIndex::new(1).with_index(1).with_index(0),
+        AttributeRelatedIndex::new(1).with_index(2),
+        AttributeRelatedIndex::new(1),
+    ];
+    indices.sort();
+    assert_eq!(
+        indices.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        vec!["1", "1.2", "1.1.0", "2.0"]
+    );
 }
 
 #[test]
@@ -5,7 +5,10 @@ use minicbor::data::Type;
 use minicbor::{decode, encode, Decode, Decoder, Encode, Encoder};
 use std::borrow::Cow;
 
-const MEMO_DATA_DEFAULT_MAX_SIZE: usize = 4000; // 4kB
+pub const MEMO_DATA_DEFAULT_MAX_SIZE: usize = 4000; // 4kB
+
+/// The maximum length of the content type of a typed section of a memo.
+pub const MEMO_CONTENT_TYPE_MAX_LENGTH: usize = 255;
 
 mod legacy;
 pub use legacy::Data as DataLegacy;
@@ -15,6 +18,33 @@ pub use legacy::Memo as MemoLegacy;
 enum MemoInner<const MAX_LENGTH: usize> {
     String(String),
     ByteString(ByteVec),
+    /// Binary data with a MIME content type, e.g. `application/json`.
+    Typed {
+        content_type: String,
+        data: ByteVec,
+    },
+}
+
+/// A section of a memo, as returned by [Memo::iter].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MemoSection<'a> {
+    String(&'a str),
+    Bytes(&'a [u8]),
+    Typed {
+        content_type: &'a str,
+        data: &'a [u8],
+    },
+}
+
+/// Whether a content type is a valid MIME type, i.e. `type/subtype` with
+/// optional parameters, made of visible ASCII characters.
+fn is_valid_content_type(content_type: &str) -> bool {
+    content_type.len() <= MEMO_CONTENT_TYPE_MAX_LENGTH
+        && content_type.bytes().all(|b| b.is_ascii_graphic())
+        && matches!(
+            content_type.split_once('/'),
+            Some((ty, subtype)) if !ty.is_empty() && !subtype.is_empty()
+        )
 }
 
 impl<const M: usize> MemoInner<M> {
@@ -24,6 +54,31 @@ impl<const M: usize> MemoInner<M> {
             _ => None,
         }
     }
+
+    pub fn typed(content_type: String, data: ByteVec) -> Result<Self, ManyError> {
+        if !is_valid_content_type(&content_type) {
+            return Err(ManyError::unknown(format!(
+                "Invalid memo content type: {content_type:?}"
+            )));
+        }
+        if data.len() > M {
+            return Err(ManyError::unknown(format!(
+                "Data size ({}) over limit ({})",
+                data.len(),
+                M
+            )));
+        }
+        Ok(Self::Typed { content_type, data })
+    }
+
+    /// The size of the data of this section, in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            Self::String(s) => s.len(),
+            Self::ByteString(bstr) => bstr.len(),
+            Self::Typed { data, .. } => data.len(),
+        }
+    }
 }
 
 macro_rules! declare_try_from {
@@ -86,6 +141,9 @@ impl<C, const M: usize> Encode<C> for MemoInner<M> {
         match self {
             MemoInner::String(str) => e.str(str),
             MemoInner::ByteString(bstr) => e.bytes(bstr.as_slice()),
+            MemoInner::Typed { content_type, data } => {
+                e.array(2)?.str(content_type)?.bytes(data.as_slice())
+            }
         }
         .map(|_| ())
     }
@@ -96,6 +154,16 @@ impl<'b, C, const M: usize> Decode<'b, C> for MemoInner<M> {
         match d.datatype()? {
             Type::Bytes => Self::try_from(d.bytes()?.to_vec()).map_err(decode::Error::message),
             Type::String => Self::try_from(d.str()?).map_err(decode::Error::message),
+            Type::Array => {
+                if d.array()? != Some(2) {
+                    return Err(decode::Error::message(
+                        "Typed memo sections must be a content type and data.",
+                    ));
+                }
+                let content_type = d.str()?.to_string();
+                let data = d.bytes()?.to_vec();
+                Self::typed(content_type, data.into()).map_err(decode::Error::message)
+            }
             // Type::BytesIndef => {}
             // Type::StringIndef => {}
             _ => Err(decode::Error::type_mismatch(Type::String)),
@@ -103,6 +171,34 @@ impl<'b, C, const M: usize> Decode<'b, C> for MemoInner<M> {
     }
 }
 
+/// Limits a server enforces on memos, on top of the size limit of the type.
+/// Servers advertise them so clients can validate memos before submitting.
+#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct MemoLimits {
+    /// Maximum size of each string or bytestring of a memo, in bytes.
+    #[n(0)]
+    pub max_size: u64,
+
+    /// Maximum number of strings and bytestrings in a memo. No limit if unset.
+    #[n(1)]
+    pub max_count: Option<u64>,
+
+    /// Maximum size of all the parts of a memo, in bytes. No limit if unset.
+    #[n(2)]
+    pub max_total_size: Option<u64>,
+}
+
+impl Default for MemoLimits {
+    fn default() -> Self {
+        Self {
+            max_size: MEMO_DATA_DEFAULT_MAX_SIZE as u64,
+            max_count: None,
+            max_total_size: None,
+        }
+    }
+}
+
 /// A memo contains a human-readable portion and/or a machine readable portion.
 /// It is meant to be a note regarding a message, transaction, info or any
 /// type that requires meta information.
@@ -142,6 +238,19 @@ impl<const M: usize> Memo<M> {
         Ok(())
     }
 
+    /// Adds binary data with its MIME content type at the end.
+    pub fn push_typed<'a>(
+        &mut self,
+        content_type: impl Into<String>,
+        data: impl Into<Cow<'a, [u8]>>,
+    ) -> Result<(), ManyError> {
+        self.inner.push(MemoInner::<M>::typed(
+            content_type.into(),
+            ByteVec::from(data.into().into_owned()),
+        )?);
+        Ok(())
+    }
+
     #[inline]
     pub fn len(&self) -> usize {
         self.inner.len()
@@ -152,6 +261,32 @@ impl<const M: usize> Memo<M> {
         self.inner.is_empty()
     }
 
+    /// Returns the size of the largest part of the memo.
+    pub fn max_part_size(&self) -> usize {
+        self.inner
+            .iter()
+            .map(MemoInner::size)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Returns the size of all the parts of the memo.
+    pub fn total_size(&self) -> usize {
+        self.inner.iter().map(MemoInner::size).sum()
+    }
+
+    /// Returns an iterator over all sections of the memo, in order.
+    pub fn iter(&self) -> impl Iterator<Item = MemoSection<'_>> {
+        self.inner.iter().map(|inner| match inner {
+            MemoInner::String(s) => MemoSection::String(s.as_str()),
+            MemoInner::ByteString(bstr) => MemoSection::Bytes(bstr.as_slice()),
+            MemoInner::Typed { content_type, data } => MemoSection::Typed {
+                content_type: content_type.as_str(),
+                data: data.as_slice(),
+            },
+        })
+    }
+
     /// Returns an iterator over all strings of the memo.
     pub fn iter_str(&self) -> impl Iterator<Item = &String> {
         self.inner.iter().filter_map(MemoInner::as_string)
@@ -160,10 +295,32 @@ impl<const M: usize> Memo<M> {
     /// Returns an iterator over all bytestrings of the memo.
     pub fn iter_bytes(&self) -> impl Iterator<Item = &[u8]> {
         self.inner.iter().filter_map(|inner| match inner {
-            MemoInner::String(_) => None,
             MemoInner::ByteString(bstr) => Some(bstr.as_slice()),
+            _ => None,
         })
     }
+
+    /// Returns an iterator over all typed sections of the memo, as their
+    /// content type and data.
+    pub fn iter_typed(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.inner.iter().filter_map(|inner| match inner {
+            MemoInner::Typed { content_type, data } => {
+                Some((content_type.as_str(), data.as_slice()))
+            }
+            _ => None,
+        })
+    }
+
+    /// Returns an iterator over the data of the typed sections of the memo
+    /// with this content type.
+    pub fn iter_content_type<'a>(
+        &'a self,
+        content_type: &'a str,
+    ) -> impl Iterator<Item = &'a [u8]> + 'a {
+        self.iter_typed()
+            .filter(move |(ty, _)| ty.eq_ignore_ascii_case(content_type))
+            .map(|(_, data)| data)
+    }
 }
 
 // This helps comparisons.
@@ -269,6 +426,40 @@ impl From<DataLegacy> for Memo<MEMO_DATA_DEFAULT_MAX_SIZE> {
     }
 }
 
+impl Memo<MEMO_DATA_DEFAULT_MAX_SIZE> {
+    /// Merge the legacy memo and data fields into a single memo. The legacy
+    /// memo comes first, followed by the legacy data as a bytestring.
+    pub fn from_legacy<S: AsRef<str>>(
+        memo: Option<MemoLegacy<S>>,
+        data: Option<DataLegacy>,
+    ) -> Result<Option<Self>, ManyError> {
+        Ok(match (memo, data) {
+            (Some(m), Some(d)) => {
+                let mut memo = Self::from(m);
+                memo.push_bytes(d.as_bytes().to_vec())?;
+                Some(memo)
+            }
+            (Some(m), None) => Some(Self::from(m)),
+            (None, Some(d)) => Some(Self::from(d)),
+            (None, None) => None,
+        })
+    }
+
+    /// The memo of a type that has both a memo and the legacy memo and data
+    /// fields. The memo takes precedence, and the legacy fields are only
+    /// used when it is missing.
+    pub fn merge_legacy<S: AsRef<str>>(
+        memo: Option<Self>,
+        memo_legacy: Option<MemoLegacy<S>>,
+        data_legacy: Option<DataLegacy>,
+    ) -> Result<Option<Self>, ManyError> {
+        match memo {
+            Some(memo) => Ok(Some(memo)),
+            None => Self::from_legacy(memo_legacy, data_legacy),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -344,6 +535,42 @@ mod tests {
         )))
     }
 
+    #[test]
+    fn merge_legacy() {
+        let memo_legacy = || Some(MemoLegacy::try_from("Hello".to_string()).unwrap());
+        let data_legacy = || Some(DataLegacy::try_from(vec![1, 2, 3]).unwrap());
+
+        assert_eq!(
+            Memo::merge_legacy::<String>(None, None, None).unwrap(),
+            None
+        );
+        assert_eq!(
+            Memo::merge_legacy(None, memo_legacy(), None).unwrap(),
+            Some(Memo::try_from("Hello").unwrap())
+        );
+        assert_eq!(
+            Memo::merge_legacy::<String>(None, None, data_legacy()).unwrap(),
+            Some(Memo::try_from(vec![1, 2, 3]).unwrap())
+        );
+        assert_eq!(
+            Memo::merge_legacy(None, memo_legacy(), data_legacy()).unwrap(),
+            Some(
+                Memo::try_from_iter([
+                    Either::Left("Hello".to_string()),
+                    Either::Right(vec![1, 2, 3])
+                ])
+                .unwrap()
+            )
+        );
+
+        // The new memo takes precedence over the legacy fields.
+        let memo = Memo::try_from("World").unwrap();
+        assert_eq!(
+            Memo::merge_legacy(Some(memo.clone()), memo_legacy(), data_legacy()).unwrap(),
+            Some(memo)
+        );
+    }
+
     #[test]
     fn memo_decode_empty() {
         let cbor = " [] ";
@@ -524,5 +751,105 @@ mod tests {
         assert_ne!(memo, *"Hello Other");
         assert_eq!(memo.iter_str().count(), 2);
         assert_eq!(memo.iter_bytes().count(), 1);
+        assert_eq!(memo.max_part_size(), 11);
+        assert_eq!(memo.total_size(), 28);
+    }
+
+    #[test]
+    fn typed_decode_ok() {
+        let cbor = r#" [ "Hello", [ "application/json", h'7b7d' ], h'01' ] "#;
+        let bytes = cbor_diag::parse_diag(cbor).unwrap().to_bytes();
+
+        let memo = minicbor::decode::<Memo>(&bytes).unwrap();
+        assert_eq!(memo.len(), 3);
+        assert_eq!(memo.iter_str().collect::<Vec<&String>>(), &["Hello"]);
+        assert_eq!(memo.iter_bytes().collect::<Vec<&[u8]>>(), &[&[1u8][..]]);
+        assert_eq!(
+            memo.iter_typed().collect::<Vec<_>>(),
+            &[("application/json", &b"{}"[..])]
+        );
+        assert_eq!(minicbor::to_vec(&memo).unwrap(), bytes);
+    }
+
+    #[test]
+    fn typed_decode_too_large() {
+        let data = hex::encode(vec![1u8; MEMO_DATA_DEFAULT_MAX_SIZE + 1]);
+        let cbor = format!(r#" [ [ "image/png", h'{data}' ] ] "#);
+        let bytes = cbor_diag::parse_diag(cbor).unwrap().to_bytes();
+
+        let result = minicbor::decode::<Memo>(&bytes);
+        assert!(result.unwrap_err().to_string().contains(&format!(
+            "Data size ({}) over limit ({MEMO_DATA_DEFAULT_MAX_SIZE})",
+            MEMO_DATA_DEFAULT_MAX_SIZE + 1
+        )))
+    }
+
+    #[test]
+    fn typed_decode_invalid() {
+        for cbor in [
+            r#" [ [ "text", h'00' ] ] "#,
+            r#" [ [ "text/", h'00' ] ] "#,
+            r#" [ [ "text plain/x", h'00' ] ] "#,
+            r#" [ [ "text/plain" ] ] "#,
+            r#" [ [ "text/plain", h'00', h'00' ] ] "#,
+            r#" [ [ "text/plain", "00" ] ] "#,
+        ] {
+            let bytes = cbor_diag::parse_diag(cbor).unwrap().to_bytes();
+            assert!(minicbor::decode::<Memo>(&bytes).is_err(), "{cbor}");
+        }
+    }
+
+    #[test]
+    fn typed_mut() {
+        let mut memo: Memo = Memo::try_from("Hello").unwrap();
+        memo.push_typed("application/json", br#"{"a":1}"#.to_vec())
+            .unwrap();
+        memo.push_typed("text/plain;charset=utf-8", b"World".to_vec())
+            .unwrap();
+        memo.push_typed("Application/JSON", b"[]".to_vec()).unwrap();
+        assert_eq!(memo.len(), 4);
+
+        assert!(memo.push_typed("", b"".to_vec()).is_err());
+        assert!(memo
+            .push_typed("a/".repeat(MEMO_CONTENT_TYPE_MAX_LENGTH), b"".to_vec())
+            .is_err());
+        assert!(memo
+            .push_typed("image/png", vec![0u8; MEMO_DATA_DEFAULT_MAX_SIZE + 1])
+            .is_err());
+        assert_eq!(memo.len(), 4);
+
+        assert_eq!(
+            memo.iter_content_type("application/json")
+                .collect::<Vec<_>>(),
+            &[&br#"{"a":1}"#[..], &b"[]"[..]]
+        );
+        assert_eq!(memo.iter_typed().count(), 3);
+        assert_eq!(memo.iter_str().count(), 1);
+        assert_eq!(memo.iter_bytes().count(), 0);
+        assert_eq!(memo.max_part_size(), 7);
+        assert_eq!(memo.total_size(), 19);
+
+        let bytes = minicbor::to_vec(&memo).unwrap();
+        assert_eq!(minicbor::decode::<Memo>(&bytes).unwrap(), memo);
+    }
+
+    #[test]
+    fn iter_in_order() {
+        let mut memo: Memo = Memo::try_from(b"\x01".to_vec()).unwrap();
+        memo.push_str("Hello".to_string()).unwrap();
+        memo.push_typed("text/plain", b"World".to_vec()).unwrap();
+        memo.push_bytes(b"\x02".to_vec()).unwrap();
+        assert_eq!(
+            memo.iter().collect::<Vec<_>>(),
+            vec![
+                MemoSection::Bytes(b"\x01"),
+                MemoSection::String("Hello"),
+                MemoSection::Typed {
+                    content_type: "text/plain",
+                    data: b"World"
+                },
+                MemoSection::Bytes(b"\x02"),
+            ]
+        );
     }
 }
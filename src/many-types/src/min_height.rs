@@ -0,0 +1,70 @@
+use crate::attributes::{Attribute, AttributeSet, TryFromAttributeSet};
+use crate::cbor::CborAny;
+use alloc::string::ToString;
+use alloc::vec;
+use many_error::ManyError;
+
+/// Request attribute asking the server to answer a query from a state at
+/// least at the given block height. Its only argument is the height.
+///
+/// A client that just committed a transaction can use the height of its
+/// block to avoid reading a stale state from a replica lagging behind. The
+/// server waits a bounded amount of time for its state to reach the height,
+/// and returns an error with its current height if it does not.
+pub const MIN_HEIGHT: Attribute = Attribute::id(5);
+
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct MinHeight(pub u64);
+
+impl From<MinHeight> for Attribute {
+    fn from(h: MinHeight) -> Attribute {
+        Attribute::new(MIN_HEIGHT.id, vec![CborAny::Int(h.0 as i64)])
+    }
+}
+
+impl TryFrom<Attribute> for MinHeight {
+    type Error = ManyError;
+
+    fn try_from(value: Attribute) -> Result<Self, Self::Error> {
+        if value.id != MIN_HEIGHT.id {
+            return Err(ManyError::invalid_attribute_id(value.id));
+        }
+
+        match value.into_arguments().as_slice() {
+            [CborAny::Int(height)] if *height >= 0 => Ok(Self(*height as u64)),
+            _ => Err(ManyError::invalid_attribute_arguments()),
+        }
+    }
+}
+
+impl TryFromAttributeSet for MinHeight {
+    fn try_from_set(set: &AttributeSet) -> Result<Self, ManyError> {
+        match set.get_attribute(MIN_HEIGHT.id) {
+            Some(attr) => MinHeight::try_from(attr.clone()),
+            None => Err(ManyError::attribute_not_found(MIN_HEIGHT.id.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_attribute() {
+        let attr: Attribute = MinHeight(42).into();
+        assert_eq!(attr.id, MIN_HEIGHT.id);
+        assert_eq!(MinHeight::try_from(attr), Ok(MinHeight(42)));
+
+        assert!(MinHeight::try_from(MIN_HEIGHT).is_err());
+        assert!(MinHeight::try_from(MIN_HEIGHT.with_argument(CborAny::Int(-1))).is_err());
+        assert!(MinHeight::try_from(Attribute::id(4).with_argument(CborAny::Int(1))).is_err());
+    }
+
+    #[test]
+    fn from_set() {
+        let set = AttributeSet::from_iter([Attribute::from(MinHeight(7))]);
+        assert_eq!(set.get::<MinHeight>(), Ok(MinHeight(7)));
+        assert!(AttributeSet::new().get::<MinHeight>().is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use crate::attributes::{Attribute, AttributeSet, TryFromAttributeSet};
+use crate::cbor::CborAny;
+use alloc::string::ToString;
+use alloc::vec;
+use many_error::ManyError;
+
+/// Request attribute hinting at how urgent a query is for its client. Its
+/// only argument is the priority, `0` for low and `1` for normal.
+///
+/// Batch clients (e.g. indexers) can mark their queries as low priority, so
+/// servers answer the interactive traffic received at the same time first.
+/// This is a hint: servers may ignore it, and it never changes the response
+/// of a request. Requests without the attribute have a normal priority.
+pub const PRIORITY: Attribute = Attribute::id(7);
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum PriorityHint {
+    Low = 0,
+    #[default]
+    Normal = 1,
+}
+
+impl PriorityHint {
+    /// The priority of a request with these attributes. An invalid hint is
+    /// ignored.
+    pub fn of(attributes: &AttributeSet) -> Self {
+        attributes.get::<PriorityHint>().unwrap_or_default()
+    }
+}
+
+impl From<PriorityHint> for Attribute {
+    fn from(p: PriorityHint) -> Attribute {
+        Attribute::new(PRIORITY.id, vec![CborAny::Int(p as i64)])
+    }
+}
+
+impl TryFrom<Attribute> for PriorityHint {
+    type Error = ManyError;
+
+    fn try_from(value: Attribute) -> Result<Self, Self::Error> {
+        if value.id != PRIORITY.id {
+            return Err(ManyError::invalid_attribute_id(value.id));
+        }
+
+        match value.into_arguments().as_slice() {
+            [CborAny::Int(0)] => Ok(Self::Low),
+            [CborAny::Int(1)] => Ok(Self::Normal),
+            _ => Err(ManyError::invalid_attribute_arguments()),
+        }
+    }
+}
+
+impl TryFromAttributeSet for PriorityHint {
+    fn try_from_set(set: &AttributeSet) -> Result<Self, ManyError> {
+        match set.get_attribute(PRIORITY.id) {
+            Some(attr) => PriorityHint::try_from(attr.clone()),
+            None => Err(ManyError::attribute_not_found(PRIORITY.id.to_string())),
+        }
+    }
+}
+
+impl core::str::FromStr for PriorityHint {
+    type Err = ManyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "low" => Ok(Self::Low),
+            "normal" => Ok(Self::Normal),
+            _ => Err(ManyError::unknown(alloc::format!(
+                "Invalid priority {s:?}, expected 'low' or 'normal'."
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_attribute() {
+        for p in [PriorityHint::Low, PriorityHint::Normal] {
+            let attr: Attribute = p.into();
+            assert_eq!(attr.id, PRIORITY.id);
+            assert_eq!(PriorityHint::try_from(attr), Ok(p));
+        }
+
+        assert!(PriorityHint::try_from(PRIORITY).is_err());
+        assert!(PriorityHint::try_from(PRIORITY.with_argument(CborAny::Int(2))).is_err());
+        assert!(PriorityHint::try_from(Attribute::id(5).with_argument(CborAny::Int(0))).is_err());
+    }
+
+    #[test]
+    fn of() {
+        let set = AttributeSet::from_iter([Attribute::from(PriorityHint::Low)]);
+        assert_eq!(PriorityHint::of(&set), PriorityHint::Low);
+        assert_eq!(PriorityHint::of(&AttributeSet::new()), PriorityHint::Normal);
+
+        let invalid = AttributeSet::from_iter([PRIORITY.with_argument(CborAny::Int(-1))]);
+        assert_eq!(PriorityHint::of(&invalid), PriorityHint::Normal);
+        assert!(PriorityHint::Low < PriorityHint::Normal);
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!("low".parse::<PriorityHint>(), Ok(PriorityHint::Low));
+        assert_eq!("normal".parse::<PriorityHint>(), Ok(PriorityHint::Normal));
+        assert!("high".parse::<PriorityHint>().is_err());
+    }
+}
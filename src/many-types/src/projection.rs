@@ -0,0 +1,284 @@
+use crate::attributes::{Attribute, AttributeSet, TryFromAttributeSet};
+use crate::cbor::CborAny;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use many_error::ManyError;
+
+/// Request attribute asking the server to only return a subset of the fields
+/// of a response. Its first argument is the [ProjectionMode], followed by one
+/// array of map keys per field path. Arrays are traversed transparently, so
+/// `[1, 2]` on `{ 1: [{ 1: .., 2: .. }, ..] }` refers to the `2` key of every
+/// element.
+///
+/// Servers that cannot apply a projection return the full response.
+pub const PROJECTION: Attribute = Attribute::id(4);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProjectionMode {
+    /// Only keep the fields listed.
+    Include = 0,
+
+    /// Remove the fields listed.
+    Exclude = 1,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Projection {
+    pub mode: ProjectionMode,
+    pub paths: Vec<Vec<CborAny>>,
+}
+
+impl Projection {
+    pub fn include<P: IntoIterator<Item = Vec<CborAny>>>(paths: P) -> Self {
+        Self {
+            mode: ProjectionMode::Include,
+            paths: paths.into_iter().collect(),
+        }
+    }
+
+    pub fn exclude<P: IntoIterator<Item = Vec<CborAny>>>(paths: P) -> Self {
+        Self {
+            mode: ProjectionMode::Exclude,
+            paths: paths.into_iter().collect(),
+        }
+    }
+
+    /// Apply this projection to a CBOR encoded value. Returns [None] if the
+    /// value cannot be projected (e.g. a path goes through a value that is
+    /// not a map), in which case the full value should be used instead.
+    pub fn apply(&self, bytes: &[u8]) -> Option<Vec<u8>> {
+        let value: CborAny = minicbor::decode(bytes).ok()?;
+        let paths: Vec<&[CborAny]> = self.paths.iter().map(Vec::as_slice).collect();
+        let projected = match self.mode {
+            ProjectionMode::Include => include(value, &paths)?,
+            ProjectionMode::Exclude => exclude(value, &paths)?,
+        };
+        minicbor::to_vec(projected).ok()
+    }
+}
+
+fn tails<'a>(paths: &[&'a [CborAny]], key: &CborAny) -> Vec<&'a [CborAny]> {
+    paths
+        .iter()
+        .filter_map(|p| p.split_first())
+        .filter(|(head, _)| *head == key)
+        .map(|(_, tail)| tail)
+        .collect()
+}
+
+fn include(value: CborAny, paths: &[&[CborAny]]) -> Option<CborAny> {
+    if paths.iter().any(|p| p.is_empty()) {
+        return Some(value);
+    }
+
+    match value {
+        CborAny::Map(map) => {
+            let mut result = BTreeMap::new();
+            for (key, value) in map {
+                let tails = tails(paths, &key);
+                if !tails.is_empty() {
+                    result.insert(key, include(value, &tails)?);
+                }
+            }
+            Some(CborAny::Map(result))
+        }
+        CborAny::Array(array) => Some(CborAny::Array(
+            array
+                .into_iter()
+                .map(|v| include(v, paths))
+                .collect::<Option<_>>()?,
+        )),
+        CborAny::Tagged(tag, inner) => {
+            Some(CborAny::Tagged(tag, Box::new(include(*inner, paths)?)))
+        }
+        _ => None,
+    }
+}
+
+fn exclude(value: CborAny, paths: &[&[CborAny]]) -> Option<CborAny> {
+    match value {
+        CborAny::Map(map) => {
+            let mut result = BTreeMap::new();
+            for (key, value) in map {
+                let tails = tails(paths, &key);
+                if tails.is_empty() {
+                    result.insert(key, value);
+                } else if tails.iter().all(|t| !t.is_empty()) {
+                    result.insert(key, exclude(value, &tails)?);
+                }
+            }
+            Some(CborAny::Map(result))
+        }
+        CborAny::Array(array) => Some(CborAny::Array(
+            array
+                .into_iter()
+                .map(|v| exclude(v, paths))
+                .collect::<Option<_>>()?,
+        )),
+        CborAny::Tagged(tag, inner) => {
+            Some(CborAny::Tagged(tag, Box::new(exclude(*inner, paths)?)))
+        }
+        _ => None,
+    }
+}
+
+impl From<Projection> for Attribute {
+    fn from(p: Projection) -> Attribute {
+        Attribute::new(
+            PROJECTION.id,
+            core::iter::once(CborAny::Int(p.mode as i64))
+                .chain(p.paths.into_iter().map(CborAny::Array))
+                .collect(),
+        )
+    }
+}
+
+impl TryFrom<Attribute> for Projection {
+    type Error = ManyError;
+
+    fn try_from(value: Attribute) -> Result<Self, Self::Error> {
+        if value.id != PROJECTION.id {
+            return Err(ManyError::invalid_attribute_id(value.id));
+        }
+
+        let mut arguments = value.into_arguments().into_iter();
+        let mode = match arguments.next() {
+            Some(CborAny::Int(0)) => ProjectionMode::Include,
+            Some(CborAny::Int(1)) => ProjectionMode::Exclude,
+            _ => return Err(ManyError::invalid_attribute_arguments()),
+        };
+        let paths = arguments
+            .map(|arg| match arg {
+                CborAny::Array(path) if !path.is_empty() => Ok(path),
+                _ => Err(ManyError::invalid_attribute_arguments()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if paths.is_empty() {
+            return Err(ManyError::invalid_attribute_arguments());
+        }
+
+        Ok(Self { mode, paths })
+    }
+}
+
+impl TryFromAttributeSet for Projection {
+    fn try_from_set(set: &AttributeSet) -> Result<Self, ManyError> {
+        match set.get_attribute(PROJECTION.id) {
+            Some(attr) => Projection::try_from(attr.clone()),
+            None => Err(ManyError::attribute_not_found(PROJECTION.id.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minicbor::data::Tag;
+    use std::collections::BTreeMap;
+
+    fn key(i: i64) -> CborAny {
+        CborAny::Int(i)
+    }
+
+    fn map<const N: usize>(entries: [(i64, CborAny); N]) -> CborAny {
+        CborAny::Map(BTreeMap::from_iter(
+            entries.into_iter().map(|(k, v)| (key(k), v)),
+        ))
+    }
+
+    fn event(id: i64) -> CborAny {
+        map([
+            (0, CborAny::Int(id)),
+            (
+                1,
+                CborAny::Tagged(Tag::Timestamp, Box::new(CborAny::Int(1000))),
+            ),
+            (
+                2,
+                map([(0, CborAny::Int(6)), (1, CborAny::Bytes(vec![1; 32]))]),
+            ),
+        ])
+    }
+
+    fn list() -> Vec<u8> {
+        minicbor::to_vec(map([
+            (0, CborAny::Int(2)),
+            (1, CborAny::Array(vec![event(1), event(2)])),
+        ]))
+        .unwrap()
+    }
+
+    fn decode(bytes: &[u8]) -> CborAny {
+        minicbor::decode(bytes).unwrap()
+    }
+
+    #[test]
+    fn include() {
+        let projection = Projection::include([vec![key(1), key(0)], vec![key(1), key(2), key(0)]]);
+        let projected = projection.apply(&list()).unwrap();
+
+        let event = |id| map([(0, CborAny::Int(id)), (2, map([(0, CborAny::Int(6))]))]);
+        assert_eq!(
+            decode(&projected),
+            map([(1, CborAny::Array(vec![event(1), event(2)]))])
+        );
+    }
+
+    #[test]
+    fn exclude() {
+        let projection = Projection::exclude([vec![key(1), key(2)]]);
+        let projected = projection.apply(&list()).unwrap();
+
+        let event = |id| {
+            map([
+                (0, CborAny::Int(id)),
+                (
+                    1,
+                    CborAny::Tagged(Tag::Timestamp, Box::new(CborAny::Int(1000))),
+                ),
+            ])
+        };
+        assert_eq!(
+            decode(&projected),
+            map([
+                (0, CborAny::Int(2)),
+                (1, CborAny::Array(vec![event(1), event(2)])),
+            ])
+        );
+    }
+
+    #[test]
+    fn unknown_path() {
+        // Going through a scalar cannot be projected.
+        assert_eq!(
+            Projection::include([vec![key(0), key(1)]]).apply(&list()),
+            None
+        );
+        assert_eq!(
+            Projection::exclude([vec![key(0), key(1)]]).apply(&list()),
+            None
+        );
+        // Missing keys are fine.
+        assert_eq!(
+            decode(&Projection::exclude([vec![key(5)]]).apply(&list()).unwrap()),
+            decode(&list())
+        );
+    }
+
+    #[test]
+    fn attribute() {
+        let projection = Projection::exclude([vec![key(1), key(2)], vec![key(3)]]);
+        let attr: Attribute = projection.clone().into();
+        assert_eq!(attr.id, PROJECTION.id);
+        assert_eq!(Projection::try_from(attr).unwrap(), projection);
+
+        let set = AttributeSet::from_iter([Attribute::new(
+            PROJECTION.id,
+            vec![CborAny::Int(0), CborAny::Int(1)],
+        )]);
+        assert!(set.get::<Projection>().is_err());
+        assert!(AttributeSet::new().get::<Projection>().is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use {
     crate::{attributes::Attribute, cbor::CborAny},
+    alloc::{format, vec::Vec},
     derive_more::{From, Into},
     many_error::ManyError,
     minicbor::{
@@ -52,3 +52,145 @@ pub enum WebDeploymentSource {
     #[n(0)]
     Archive(#[n(0)] ByteVec),
 }
+
+/// Redirect requests for a path of a website.
+#[derive(Clone, Debug, Decode, Encode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct WebRedirect {
+    /// The path to redirect, starting with `/`.
+    #[n(0)]
+    pub from: String,
+
+    /// A path of the website or an absolute URL.
+    #[n(1)]
+    pub to: String,
+
+    /// The HTTP status code of the redirect.
+    #[n(2)]
+    pub status: u16,
+}
+
+impl WebRedirect {
+    pub const DEFAULT_STATUS: u16 = 301;
+}
+
+/// Parse a redirect from `FROM=TO[,STATUS]`. The status defaults to 301.
+impl FromStr for WebRedirect {
+    type Err = ManyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (from, to) = s
+            .split_once('=')
+            .ok_or_else(|| ManyError::unknown("invalid redirect, expected FROM=TO[,STATUS]"))?;
+        let (to, status) = match to.rsplit_once(',') {
+            Some((to, status)) => match status.parse() {
+                Ok(status) => (to, status),
+                Err(_) => return Err(ManyError::unknown("invalid redirect status")),
+            },
+            None => (to, Self::DEFAULT_STATUS),
+        };
+        Ok(Self {
+            from: from.to_string(),
+            to: to.to_string(),
+            status,
+        })
+    }
+}
+
+/// The server-side configuration of a website.
+#[derive(Clone, Debug, Default, Decode, Encode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct WebSiteConfig {
+    #[n(0)]
+    pub redirects: Vec<WebRedirect>,
+
+    /// The path of the document served, with a 404 status, for paths which
+    /// are not found.
+    #[n(1)]
+    pub not_found: Option<String>,
+}
+
+impl WebSiteConfig {
+    /// The redirect of a path, if any. The first matching redirect is used.
+    pub fn redirect_for(&self, path: &str) -> Option<&WebRedirect> {
+        self.redirects.iter().find(|r| r.from == path)
+    }
+}
+
+/// A website, by owner and name.
+#[derive(Clone, Debug, Decode, Encode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct WebSiteRef {
+    #[n(0)]
+    pub owner: Address,
+
+    #[n(1)]
+    pub site_name: String,
+}
+
+/// Redirect every request of a website.
+#[derive(Clone, Debug, Decode, Encode, Eq, PartialEq)]
+#[cbor(map)]
+pub struct WebSiteRedirect {
+    /// An absolute HTTP(S) URL.
+    #[n(0)]
+    pub to: String,
+
+    /// The HTTP status code of the redirect.
+    #[n(1)]
+    pub status: u16,
+}
+
+/// A DNS-like record of a website, resolved by the HTTP frontend.
+#[derive(Clone, Debug, Decode, Encode, Eq, PartialEq)]
+#[cbor(map)]
+pub enum WebRecord {
+    /// Serve the content of another website, like an `A` record.
+    #[n(0)]
+    Content(#[n(0)] WebSiteRef),
+
+    /// Free-form text, like a `TXT` record.
+    #[n(1)]
+    Txt(#[n(0)] String),
+
+    /// Redirect every request of the website.
+    #[n(2)]
+    Redirect(#[n(0)] WebSiteRedirect),
+}
+
+/// Parse a record from `content:OWNER/SITE_NAME`, `txt:TEXT` or
+/// `redirect:URL[,STATUS]`. The status of redirects defaults to 301.
+impl FromStr for WebRecord {
+    type Err = ManyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some(("content", target)) => {
+                let (owner, site_name) = target.split_once('/').ok_or_else(|| {
+                    ManyError::unknown("invalid content record, expected content:OWNER/SITE_NAME")
+                })?;
+                Ok(Self::Content(WebSiteRef {
+                    owner: Address::from_str(owner)?,
+                    site_name: site_name.to_string(),
+                }))
+            }
+            Some(("txt", text)) => Ok(Self::Txt(text.to_string())),
+            Some(("redirect", to)) => {
+                let (to, status) = match to.rsplit_once(',') {
+                    Some((to, status)) => match status.parse() {
+                        Ok(status) => (to, status),
+                        Err(_) => return Err(ManyError::unknown("invalid redirect status")),
+                    },
+                    None => (to, WebRedirect::DEFAULT_STATUS),
+                };
+                Ok(Self::Redirect(WebSiteRedirect {
+                    to: to.to_string(),
+                    status,
+                }))
+            }
+            _ => Err(ManyError::unknown(
+                "invalid record, expected content:OWNER/SITE_NAME, txt:TEXT or redirect:URL[,STATUS]",
+            )),
+        }
+    }
+}
@@ -23,6 +23,14 @@ define_attribute_many_error!(
         18: pub fn invalid_domain(domain) => "Invalid domain: {domain}.",
         19: pub fn page_size_too_large(size) => "Page size too large: {size}.",
         20: pub fn domain_already_in_use(domain) => "Domain already in use: {domain}.",
+        21: pub fn too_many_redirects(max) => "Too many redirects, limit is {max}.",
+        22: pub fn invalid_redirect(from, to) => "Invalid redirect from '{from}' to '{to}'.",
+        23: pub fn invalid_redirect_status(status) => "Invalid redirect status code: {status}.",
+        24: pub fn missing_not_found_document(path) => "Missing 404 document: {path}.",
+        25: pub fn too_many_records(max) => "Too many records, limit is {max}.",
+        26: pub fn invalid_record(reason) => "Invalid record: {reason}.",
+        27: pub fn too_many_record_indirections(max)
+            => "Too many content records to follow, limit is {max}.",
     }
 );
 
@@ -2,6 +2,7 @@ use std::sync::OnceLock;
 
 pub mod error;
 pub mod module;
+pub mod registry;
 pub mod storage;
 
 pub static DOMAIN: OnceLock<String> = OnceLock::new();
@@ -8,7 +8,7 @@ use many_modules::{abci_backend, events, kvstore, web};
 use many_protocol::ManyUrl;
 use many_server::transport::http::HttpServer;
 use many_server::ManyServer;
-use many_server_cache::{RequestCacheValidator, RocksDbCacheBackend};
+use many_server_cache::{ReplayPolicy, RequestCacheValidator, RocksDbCacheBackend};
 use std::collections::BTreeSet;
 use std::net::SocketAddr;
 use std::path::PathBuf;
@@ -63,12 +63,30 @@ struct Opts {
     #[clap(long)]
     allow_addrs: Option<PathBuf>,
 
+    /// Endpoints to disable on this server, e.g. `idstore.store`. Requests to
+    /// them are refused and they are not listed by `endpoints`.
+    /// Multiple occurences of this argument can be given.
+    #[clap(long = "disable-endpoint")]
+    disable_endpoint: Vec<String>,
+
     /// Database path to the request cache to validate duplicate messages.
     /// If unspecified, the server will not verify transactions for duplicate
     /// messages.
     #[clap(long)]
     cache_db: Option<PathBuf>,
 
+    /// Remove requests from the cache after this many seconds. This must be
+    /// longer than the timeout of the server, or requests could be replayed.
+    /// Requests are kept forever by default.
+    #[clap(long, requires("cache-db"))]
+    cache_ttl: Option<u64>,
+
+    /// Answer requests that were already executed with their original
+    /// response, instead of a duplicated message error. Responses are kept
+    /// in the cache with their request.
+    #[clap(long, requires("cache-db"))]
+    cache_replay_responses: bool,
+
     #[clap(long, default_value = "localhost:8880")]
     domain: String,
 }
@@ -84,7 +102,10 @@ fn main() {
         clean,
         allow_origin,
         allow_addrs,
+        disable_endpoint,
         cache_db,
+        cache_ttl,
+        cache_replay_responses,
         domain,
         ..
     } = Opts::parse();
@@ -180,10 +201,25 @@ fn main() {
         if abci {
             s.set_timeout(u64::MAX);
             s.add_module(abci_backend::AbciModule::new(module));
+            s.add_module(abci_backend::AbciPriorityModule::new(many.clone()));
+        }
+
+        for method in disable_endpoint {
+            s.disable_endpoint(method);
         }
 
         if let Some(p) = cache_db {
-            s.add_validator(RequestCacheValidator::new(RocksDbCacheBackend::new(p)));
+            let cache = RequestCacheValidator::new(RocksDbCacheBackend::new(p)).with_replay_policy(
+                if cache_replay_responses {
+                    ReplayPolicy::ReturnCachedResponse
+                } else {
+                    ReplayPolicy::Reject
+                },
+            );
+            s.add_validator(match cache_ttl {
+                Some(ttl) => cache.with_ttl(std::time::Duration::from_secs(ttl)),
+                None => cache,
+            });
         }
     }
     let mut many_server = HttpServer::new(many);
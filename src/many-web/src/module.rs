@@ -1,5 +1,5 @@
 use crate::error;
-use crate::storage::{url_for_website, WebStorage, HTTP_ROOT};
+use crate::storage::{site_root_hash, url_for_website, WebStorage, HTTP_ROOT};
 use base64::{engine::general_purpose, Engine as _};
 use many_error::ManyError;
 use many_identity::Address;
@@ -9,10 +9,15 @@ use many_modules::abci_backend::{
 };
 use many_modules::kvstore::{GetArgs, GetReturns, KvStoreModuleBackend, QueryArgs, QueryReturns};
 use many_modules::web::{
-    DeployArgs, DeployReturns, InfoArg, InfoReturns, ListArgs, ListReturns, RemoveArgs,
-    RemoveReturns, UpdateArgs, UpdateReturns, WebCommandsModuleBackend, WebModuleBackend,
+    ConfigArgs, ConfigReturns, ContentHashArgs, ContentHashReturns, DeployArgs, DeployReturns,
+    InfoArg, InfoReturns, ListArgs, ListReturns, RecordsArgs, RecordsReturns, RemoveArgs,
+    RemoveReturns, ResolveArgs, ResolveReturns, SetConfigArgs, SetConfigReturns, SetRecordsArgs,
+    SetRecordsReturns, UpdateArgs, UpdateReturns, WebCommandsModuleBackend, WebModuleBackend,
+};
+use many_protocol::context::Context;
+use many_types::web::{
+    WebDeploymentInfo, WebDeploymentSource, WebRecord, WebSiteConfig, WebSiteRef,
 };
-use many_types::web::{WebDeploymentInfo, WebDeploymentSource};
 use many_types::Timestamp;
 use sha2::Digest;
 use std::collections::BTreeMap;
@@ -23,6 +28,11 @@ use tracing::{info, trace};
 use trust_dns_resolver::Name;
 
 const MAXIMUM_WEB_COUNT: usize = 100;
+const MAXIMUM_REDIRECT_COUNT: usize = 100;
+const REDIRECT_STATUSES: [u16; 5] = [301, 302, 303, 307, 308];
+const MAXIMUM_RECORD_COUNT: usize = 20;
+const MAXIMUM_TXT_LENGTH: usize = 255;
+const MAXIMUM_RECORD_INDIRECTIONS: usize = 8;
 
 pub mod allow_addrs;
 pub mod events;
@@ -87,6 +97,12 @@ impl ManyAbciModuleBackend for WebModuleImpl {
                 ("web.remove".to_string(), EndpointInfo { is_command: true }),
                 ("web.update".to_string(), EndpointInfo { is_command: true }),
                 ("web.list".to_string(), EndpointInfo { is_command: false }),
+                ("web.config".to_string(), EndpointInfo { is_command: false }),
+                ("web.setConfig".to_string(), EndpointInfo { is_command: true }),
+                ("web.contentHash".to_string(), EndpointInfo { is_command: false }),
+                ("web.records".to_string(), EndpointInfo { is_command: false }),
+                ("web.resolve".to_string(), EndpointInfo { is_command: false }),
+                ("web.setRecords".to_string(), EndpointInfo { is_command: true }),
                 // KvStore
                 ("kvstore.get".to_string(), EndpointInfo { is_command: false }),
                 ("kvstore.info".to_string(), EndpointInfo { is_command: false }),
@@ -170,6 +186,107 @@ fn extract_valid_domain(domain: Option<String>) -> Result<Option<String>, ManyEr
     }
 }
 
+/// Redirects go to a path of the website or to an HTTP(S) URL. The 404
+/// document must be a file of the website.
+fn check_site_config(
+    storage: &WebStorage,
+    owner: &Address,
+    site_name: &str,
+    config: &WebSiteConfig,
+) -> Result<(), ManyError> {
+    if config.redirects.len() > MAXIMUM_REDIRECT_COUNT {
+        return Err(error::too_many_redirects(MAXIMUM_REDIRECT_COUNT));
+    }
+
+    for redirect in &config.redirects {
+        is_alphanumeric_or_symbols(&redirect.from)?;
+        is_alphanumeric_or_symbols(&redirect.to)?;
+        let valid_to = ["/", "https://", "http://"]
+            .iter()
+            .any(|prefix| redirect.to.starts_with(prefix));
+        if !redirect.from.starts_with('/') || !valid_to || redirect.from == redirect.to {
+            return Err(error::invalid_redirect(&redirect.from, &redirect.to));
+        }
+        if !REDIRECT_STATUSES.contains(&redirect.status) {
+            return Err(error::invalid_redirect_status(redirect.status));
+        }
+    }
+
+    if let Some(not_found) = &config.not_found {
+        if !not_found.starts_with('/') || !storage.site_file_exists(owner, site_name, not_found)? {
+            return Err(error::missing_not_found_document(not_found));
+        }
+    }
+    Ok(())
+}
+
+/// A website has at most one content or redirect record, like a `CNAME`
+/// record. Content records point to another existing website, and redirects
+/// go to an HTTP(S) URL.
+fn check_site_records(
+    storage: &WebStorage,
+    owner: &Address,
+    site_name: &str,
+    records: &[WebRecord],
+) -> Result<(), ManyError> {
+    if records.len() > MAXIMUM_RECORD_COUNT {
+        return Err(error::too_many_records(MAXIMUM_RECORD_COUNT));
+    }
+
+    let mut has_target = false;
+    for record in records {
+        match record {
+            WebRecord::Content(WebSiteRef {
+                owner: target_owner,
+                site_name: target_name,
+            }) => {
+                if has_target {
+                    return Err(error::invalid_record(
+                        "a website has at most one content or redirect record",
+                    ));
+                }
+                has_target = true;
+                if target_owner == owner && target_name == site_name {
+                    return Err(error::invalid_record("a website cannot point to itself"));
+                }
+                if !storage.site_exists(target_owner, target_name)? {
+                    return Err(error::nonexistent_site(target_name));
+                }
+            }
+            WebRecord::Txt(text) => {
+                is_alphanumeric_or_symbols(text)?;
+                if text.len() > MAXIMUM_TXT_LENGTH {
+                    return Err(error::invalid_record(format!(
+                        "text records are limited to {MAXIMUM_TXT_LENGTH} bytes"
+                    )));
+                }
+            }
+            WebRecord::Redirect(redirect) => {
+                if has_target {
+                    return Err(error::invalid_record(
+                        "a website has at most one content or redirect record",
+                    ));
+                }
+                has_target = true;
+                is_alphanumeric_or_symbols(&redirect.to)?;
+                if !["https://", "http://"]
+                    .iter()
+                    .any(|prefix| redirect.to.starts_with(prefix))
+                {
+                    return Err(error::invalid_record(format!(
+                        "redirects go to an HTTP(S) URL, not '{}'",
+                        redirect.to
+                    )));
+                }
+                if !REDIRECT_STATUSES.contains(&redirect.status) {
+                    return Err(error::invalid_redirect_status(redirect.status));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 fn _transform_site_name(site_name: String) -> String {
     site_name.to_lowercase().trim().replace(' ', "_")
 }
@@ -238,6 +355,110 @@ impl WebModuleBackend for WebModuleImpl {
             deployments,
         })
     }
+
+    fn config(&self, _sender: &Address, args: ConfigArgs) -> Result<ConfigReturns, ManyError> {
+        let ConfigArgs { owner, site_name } = args;
+
+        let site_name = _transform_site_name(site_name);
+        if !self.storage.site_exists(&owner, &site_name)? {
+            return Err(error::nonexistent_site(site_name));
+        }
+
+        Ok(ConfigReturns {
+            config: self.storage.get_site_config(&owner, &site_name)?,
+        })
+    }
+
+    fn content_hash(
+        &self,
+        _sender: &Address,
+        args: ContentHashArgs,
+    ) -> Result<ContentHashReturns, ManyError> {
+        let ContentHashArgs {
+            owner,
+            site_name,
+            path,
+        } = args;
+
+        let site_name = _transform_site_name(site_name);
+        if !self.storage.site_exists(&owner, &site_name)? {
+            return Err(error::nonexistent_site(site_name));
+        }
+
+        let mut files = self.storage.get_content_hashes(&owner, &site_name)?;
+        match path {
+            Some(path) => Ok(ContentHashReturns {
+                hash: files
+                    .remove(path.trim_start_matches('/'))
+                    .ok_or_else(|| error::key_not_found(path))?,
+                files: None,
+            }),
+            None => Ok(ContentHashReturns {
+                hash: site_root_hash(&files)?,
+                files: Some(files),
+            }),
+        }
+    }
+
+    fn records(&self, _sender: &Address, args: RecordsArgs) -> Result<RecordsReturns, ManyError> {
+        let RecordsArgs { owner, site_name } = args;
+
+        let site_name = _transform_site_name(site_name);
+        if !self.storage.site_exists(&owner, &site_name)? {
+            return Err(error::nonexistent_site(site_name));
+        }
+
+        Ok(RecordsReturns {
+            records: self.storage.get_site_records(&owner, &site_name)?,
+        })
+    }
+
+    /// Follow the content records of a website, until a website without one
+    /// or a redirect. The text records are the ones of the requested website.
+    fn resolve(&self, _sender: &Address, args: ResolveArgs) -> Result<ResolveReturns, ManyError> {
+        let ResolveArgs { owner, site_name } = args;
+
+        let mut site = WebSiteRef {
+            owner,
+            site_name: _transform_site_name(site_name),
+        };
+        let mut txt = None;
+        for _ in 0..=MAXIMUM_RECORD_INDIRECTIONS {
+            if !self.storage.site_exists(&site.owner, &site.site_name)? {
+                return Err(error::nonexistent_site(site.site_name));
+            }
+
+            let mut redirect = None;
+            let mut next = None;
+            let mut texts = vec![];
+            for record in self
+                .storage
+                .get_site_records(&site.owner, &site.site_name)?
+            {
+                match record {
+                    WebRecord::Content(target) => next = Some(target),
+                    WebRecord::Txt(text) => texts.push(text),
+                    WebRecord::Redirect(r) => redirect = Some(r),
+                }
+            }
+            let txt = txt.get_or_insert(texts);
+
+            match next {
+                Some(next) if redirect.is_none() => site = next,
+                _ => {
+                    return Ok(ResolveReturns {
+                        site,
+                        redirect,
+                        txt: txt.clone(),
+                    })
+                }
+            }
+        }
+
+        Err(error::too_many_record_indirections(
+            MAXIMUM_RECORD_INDIRECTIONS,
+        ))
+    }
 }
 
 impl WebCommandsModuleBackend for WebModuleImpl {
@@ -419,6 +640,75 @@ impl WebCommandsModuleBackend for WebModuleImpl {
             },
         })
     }
+
+    fn set_config(
+        &mut self,
+        sender: &Address,
+        args: SetConfigArgs,
+    ) -> Result<SetConfigReturns, ManyError> {
+        let SetConfigArgs {
+            owner,
+            site_name,
+            config,
+        } = args;
+
+        // Check that the sender is the owner, for now.
+        // TODO: Support accounts
+        if let Some(owner) = owner {
+            if sender != &owner {
+                return Err(error::invalid_owner(owner));
+            }
+        }
+
+        let site_name = _transform_site_name(site_name);
+        if !self.storage.site_exists(sender, &site_name)? {
+            return Err(error::nonexistent_site(site_name));
+        }
+
+        check_site_config(&self.storage, sender, &site_name, &config)?;
+        self.storage.set_site_config(sender, &site_name, &config)?;
+        Ok(SetConfigReturns {})
+    }
+
+    fn set_records(
+        &mut self,
+        sender: &Address,
+        args: SetRecordsArgs,
+    ) -> Result<SetRecordsReturns, ManyError> {
+        let SetRecordsArgs {
+            owner,
+            site_name,
+            records,
+        } = args;
+
+        // Check that the sender is the owner, for now.
+        // TODO: Support accounts
+        if let Some(owner) = owner {
+            if sender != &owner {
+                return Err(error::invalid_owner(owner));
+            }
+        }
+
+        let site_name = _transform_site_name(site_name);
+        if !self.storage.site_exists(sender, &site_name)? {
+            return Err(error::nonexistent_site(site_name));
+        }
+
+        let records = records
+            .into_iter()
+            .map(|record| match record {
+                WebRecord::Content(target) => WebRecord::Content(WebSiteRef {
+                    owner: target.owner,
+                    site_name: _transform_site_name(target.site_name),
+                }),
+                record => record,
+            })
+            .collect::<Vec<_>>();
+        check_site_records(&self.storage, sender, &site_name, &records)?;
+        self.storage
+            .set_site_records(sender, &site_name, &records)?;
+        Ok(SetRecordsReturns {})
+    }
 }
 
 impl KvStoreModuleBackend for WebModuleImpl {
@@ -432,7 +722,12 @@ impl KvStoreModuleBackend for WebModuleImpl {
         })
     }
 
-    fn get(&self, _sender: &Address, args: GetArgs) -> Result<GetReturns, ManyError> {
+    fn get(
+        &self,
+        _sender: &Address,
+        args: GetArgs,
+        _context: Context,
+    ) -> Result<GetReturns, ManyError> {
         let GetArgs { key } = args;
 
         if !key.starts_with(HTTP_ROOT.as_ref()) {
@@ -454,7 +749,12 @@ impl KvStoreModuleBackend for WebModuleImpl {
     }
 
     // We do not expose this endpoint
-    fn query(&self, _sender: &Address, _args: QueryArgs) -> Result<QueryReturns, ManyError> {
+    fn query(
+        &self,
+        _sender: &Address,
+        _args: QueryArgs,
+        _context: Context,
+    ) -> Result<QueryReturns, ManyError> {
         Err(ManyError::unknown("Unimplemented"))
     }
 
@@ -466,6 +766,15 @@ impl KvStoreModuleBackend for WebModuleImpl {
     ) -> Result<many_modules::kvstore::list::ListReturns, ManyError> {
         Err(ManyError::unknown("Unimplemented"))
     }
+
+    // We do not expose this endpoint
+    fn multi_get(
+        &self,
+        _sender: &Address,
+        _args: many_modules::kvstore::MultiGetArgs,
+    ) -> Result<many_modules::kvstore::MultiGetReturns, ManyError> {
+        Err(ManyError::unknown("Unimplemented"))
+    }
 }
 
 #[cfg(test)]
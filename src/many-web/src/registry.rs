@@ -0,0 +1,63 @@
+//! The modules a web server can serve, by name.
+use crate::module::allow_addrs::AllowAddrsModule;
+use crate::module::WebModuleImpl;
+use many_identity::Address;
+use many_modules::{events, kvstore, web};
+use many_server::{ModuleConfig, ModuleRegistry};
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+
+/// What the web modules are constructed from.
+pub struct WebModuleContext {
+    pub module_impl: Arc<Mutex<WebModuleImpl>>,
+
+    /// If set, only these addresses can execute web commands.
+    pub allow_addrs: Option<BTreeSet<Address>>,
+}
+
+impl WebModuleContext {
+    pub fn new(module_impl: Arc<Mutex<WebModuleImpl>>) -> Self {
+        Self {
+            module_impl,
+            allow_addrs: None,
+        }
+    }
+}
+
+/// The names of the modules served by default, in the order they are added.
+pub fn default_modules() -> Vec<ModuleConfig> {
+    ["web.commands", "web", "kvstore", "events"]
+        .into_iter()
+        .map(ModuleConfig::new)
+        .collect()
+}
+
+pub fn registry() -> ModuleRegistry<WebModuleContext> {
+    let mut registry = ModuleRegistry::new();
+    registry.register("web.commands", |server, context: &WebModuleContext, _| {
+        let inner = web::WebCommandsModule::new(context.module_impl.clone());
+        match &context.allow_addrs {
+            Some(allow_addrs) => server.add_module(AllowAddrsModule {
+                inner,
+                allow_addrs: allow_addrs.clone(),
+            }),
+            None => server.add_module(inner),
+        };
+        Ok(())
+    });
+    registry.register("web", |server, context, _| {
+        server.add_module(web::WebModule::new(context.module_impl.clone()));
+        Ok(())
+    });
+    // Only implements `kvstore.get` and `kvstore.info`, to serve the files of
+    // the websites.
+    registry.register("kvstore", |server, context, _| {
+        server.add_module(kvstore::KvStoreModule::new(context.module_impl.clone()));
+        Ok(())
+    });
+    registry.register("events", |server, context, _| {
+        server.add_module(events::EventsModule::new(context.module_impl.clone()));
+        Ok(())
+    });
+    registry
+}
@@ -1,13 +1,17 @@
 use crate::error;
 use crate::storage::iterator::WebIterator;
 use base64::engine::general_purpose;
+use base64::Engine as _;
 use many_error::ManyError;
 use many_identity::Address;
 use many_modules::abci_backend::AbciCommitInfo;
 use many_modules::events::{EventId, EventInfo};
-use many_types::web::{WebDeploymentFilter, WebDeploymentInfo};
+use many_types::web::{WebDeploymentFilter, WebDeploymentInfo, WebRecord, WebSiteConfig};
 use many_types::{Memo, SortOrder, Timestamp};
 use merk::{BatchEntry, Op};
+use minicbor::bytes::ByteVec;
+use sha2::Digest;
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::Write;
 use std::path::Path;
@@ -19,6 +23,9 @@ pub mod iterator;
 
 pub const HTTP_ROOT: &str = "/http"; // Where website files are stored.
 const META_ROOT: &str = "/meta"; // Where website metadata are stored.
+const SITE_CONFIG_ROOT: &str = "/site_config"; // Where website configurations are stored.
+const CONTENT_HASH_ROOT: &str = "/content_hash"; // Where website content hashes are stored.
+const SITE_RECORDS_ROOT: &str = "/site_records"; // Where website records are stored.
 
 fn key_for_website(owner: &Address, site_name: &str) -> Vec<u8> {
     format!("{HTTP_ROOT}/{owner}/{site_name}/").into_bytes()
@@ -32,6 +39,25 @@ fn key_for_website_meta(owner: &Address, site_name: &str) -> String {
     format!("{META_ROOT}/{owner}/{site_name}")
 }
 
+fn key_for_website_config(owner: &Address, site_name: &str) -> String {
+    format!("{SITE_CONFIG_ROOT}/{owner}/{site_name}")
+}
+
+fn key_for_website_records(owner: &Address, site_name: &str) -> String {
+    format!("{SITE_RECORDS_ROOT}/{owner}/{site_name}")
+}
+
+fn key_for_website_content_hashes(owner: &Address, site_name: &str) -> String {
+    format!("{CONTENT_HASH_ROOT}/{owner}/{site_name}")
+}
+
+/// The root hash of a website, i.e. the SHA-256 hash of the CBOR encoding of
+/// the hashes of its files, by path.
+pub fn site_root_hash(files: &BTreeMap<String, ByteVec>) -> Result<ByteVec, ManyError> {
+    let bytes = minicbor::to_vec(files).map_err(ManyError::serialization_error)?;
+    Ok(sha2::Sha256::digest(bytes).to_vec().into())
+}
+
 pub fn url_for_website(owner: &Address, site_name: &str) -> String {
     let domain = crate::DOMAIN.get_or_init(|| "localhost:8880".to_string());
     format!("https://{site_name}-{owner}.{domain}")
@@ -195,6 +221,110 @@ impl WebStorage {
         Ok(meta)
     }
 
+    /// Returns true if the website has a file at `path`, relative to its root.
+    pub fn site_file_exists(
+        &self,
+        owner: &Address,
+        site_name: &str,
+        path: &str,
+    ) -> Result<bool, ManyError> {
+        let key = key_for_website_file(owner, site_name, path.trim_start_matches('/'));
+        Ok(self.get(key.as_bytes())?.is_some())
+    }
+
+    /// The configuration of a website. Websites without a configuration have
+    /// the default one.
+    pub fn get_site_config(
+        &self,
+        owner: &Address,
+        site_name: &str,
+    ) -> Result<WebSiteConfig, ManyError> {
+        self.get(key_for_website_config(owner, site_name).as_bytes())?
+            .map_or(Ok(WebSiteConfig::default()), |config| {
+                minicbor::decode(&config).map_err(ManyError::deserialization_error)
+            })
+    }
+
+    pub fn set_site_config(
+        &mut self,
+        owner: &Address,
+        site_name: &str,
+        config: &WebSiteConfig,
+    ) -> Result<(), ManyError> {
+        self.persistent_store
+            .apply(&[(
+                key_for_website_config(owner, site_name).into_bytes(),
+                Op::Put(minicbor::to_vec(config).map_err(ManyError::serialization_error)?),
+            )])
+            .map_err(error::storage_apply_failed)?;
+
+        self.maybe_commit()
+    }
+
+    /// The records of a website. Websites without records have none.
+    pub fn get_site_records(
+        &self,
+        owner: &Address,
+        site_name: &str,
+    ) -> Result<Vec<WebRecord>, ManyError> {
+        self.get(key_for_website_records(owner, site_name).as_bytes())?
+            .map_or(Ok(vec![]), |records| {
+                minicbor::decode(&records).map_err(ManyError::deserialization_error)
+            })
+    }
+
+    pub fn set_site_records(
+        &mut self,
+        owner: &Address,
+        site_name: &str,
+        records: &[WebRecord],
+    ) -> Result<(), ManyError> {
+        let key = key_for_website_records(owner, site_name).into_bytes();
+        let op = if records.is_empty() {
+            if self.get(&key)?.is_none() {
+                return Ok(());
+            }
+            Op::Delete
+        } else {
+            Op::Put(minicbor::to_vec(records).map_err(ManyError::serialization_error)?)
+        };
+        self.persistent_store
+            .apply(&[(key, op)])
+            .map_err(error::storage_apply_failed)?;
+
+        self.maybe_commit()
+    }
+
+    /// The SHA-256 hash of every file of a website, by path. Websites deployed
+    /// before content hashes were stored have them computed from their files.
+    pub fn get_content_hashes(
+        &self,
+        owner: &Address,
+        site_name: &str,
+    ) -> Result<BTreeMap<String, ByteVec>, ManyError> {
+        if let Some(hashes) =
+            self.get(key_for_website_content_hashes(owner, site_name).as_bytes())?
+        {
+            return minicbor::decode(&hashes).map_err(ManyError::deserialization_error);
+        }
+
+        let prefix = key_for_website(owner, site_name);
+        let mut hashes = BTreeMap::new();
+        for item in WebIterator::website_files(&self.persistent_store, owner, &site_name) {
+            let (key, value) = item.map_err(error::storage_get_failed)?;
+            let file_path = std::str::from_utf8(&key[prefix.len()..])
+                .map_err(|_| error::unable_to_convert_to_str())?;
+            let content = general_purpose::STANDARD
+                .decode(value)
+                .map_err(ManyError::deserialization_error)?;
+            hashes.insert(
+                file_path.to_string(),
+                sha2::Sha256::digest(content).to_vec().into(),
+            );
+        }
+        Ok(hashes)
+    }
+
     fn inc_height(&mut self) -> Result<u64, ManyError> {
         let current_height = self.get_height()?;
         self.persistent_store
@@ -266,6 +396,7 @@ impl WebStorage {
         domain: &Option<String>,
     ) -> Result<Vec<BatchEntry>, ManyError> {
         let mut batch: Vec<BatchEntry> = Vec::new();
+        let mut hashes: BTreeMap<String, ByteVec> = BTreeMap::new();
 
         // Walk the directory tree, ignoring hidden files and directories.
         // Add each file content to the batch as base64
@@ -288,10 +419,15 @@ impl WebStorage {
                 .ok_or_else(error::unable_to_convert_to_str)?;
             trace!("Found file {}", file_path);
 
+            let content = fs::read(entry_path).map_err(error::io_error)?;
+            hashes.insert(
+                file_path.to_string(),
+                sha2::Sha256::digest(&content).to_vec().into(),
+            );
+
             trace!("Encoding file");
             let mut enc = base64::write::EncoderWriter::new(Vec::new(), &general_purpose::STANDARD);
-            enc.write_all(&fs::read(entry_path).map_err(error::io_error)?)
-                .map_err(error::io_error)?;
+            enc.write_all(&content).map_err(error::io_error)?;
 
             trace!("Finished encoding file");
             let data = enc.finish().map_err(ManyError::unknown)?;
@@ -323,6 +459,12 @@ impl WebStorage {
             ),
         ));
 
+        trace!("Adding website content hashes to batch");
+        batch.push((
+            key_for_website_content_hashes(owner, site_name).into_bytes(),
+            Op::Put(minicbor::to_vec(&hashes).map_err(ManyError::serialization_error)?),
+        ));
+
         trace!("Sorting batch");
         batch.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
 
@@ -383,6 +525,14 @@ impl WebStorage {
             Op::Delete,
         ));
 
+        // Websites deployed before content hashes were stored have none.
+        let key_hashes = key_for_website_content_hashes(owner, site_name).into_bytes();
+        if self.get(&key_hashes)?.is_some() {
+            trace!("Removing website content hashes");
+            batch.push((key_hashes, Op::Delete));
+            batch.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+        }
+
         Ok(batch)
     }
 
@@ -392,7 +542,18 @@ impl WebStorage {
         site_name: String,
         memo: Option<Memo>,
     ) -> Result<(), ManyError> {
-        let batch = self._remove_website(owner, &site_name)?;
+        let mut batch = self._remove_website(owner, &site_name)?;
+
+        // The configuration and the records are kept when a website is
+        // updated, so they are only removed here.
+        let key_config = key_for_website_config(owner, &site_name).into_bytes();
+        if self.get(&key_config)?.is_some() {
+            batch.push((key_config, Op::Delete));
+        }
+        let key_records = key_for_website_records(owner, &site_name).into_bytes();
+        if self.get(&key_records)?.is_some() {
+            batch.push((key_records, Op::Delete));
+        }
 
         self.persistent_store
             .apply(&batch)
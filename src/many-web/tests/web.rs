@@ -1,15 +1,24 @@
+use async_channel::unbounded;
 use cucumber::gherkin::Step;
 use cucumber::{given, then, when, World as _};
+use many_error::ManyError;
 use many_identity::testing::identity;
 use many_identity::Address;
 use many_modules::kvstore::{GetArgs, KvStoreModuleBackend};
 use many_modules::web::{
-    DeployArgs, ListArgs, UpdateArgs, WebCommandsModuleBackend, WebModuleBackend,
+    ConfigArgs, ContentHashArgs, ContentHashReturns, DeployArgs, ListArgs, ResolveArgs,
+    ResolveReturns, SetConfigArgs, SetRecordsArgs, UpdateArgs, WebCommandsModuleBackend,
+    WebModuleBackend,
+};
+use many_protocol::{context::Context, RequestMessage};
+use many_types::web::{
+    WebDeploymentFilter, WebDeploymentSource, WebRecord, WebRedirect, WebSiteConfig,
+    WebSiteRedirect, WebSiteRef,
 };
-use many_types::web::{WebDeploymentFilter, WebDeploymentSource};
 use many_types::Memo;
 use many_web::module::{InitialStateJson, WebModuleImpl};
 use many_web::storage::HTTP_ROOT;
+use sha2::Digest;
 use std::path::Path;
 use tempfile::Builder;
 
@@ -23,6 +32,8 @@ struct World {
     module: WebModuleImpl,
     memo: Option<Memo>,
     domain: Option<String>,
+    config: WebSiteConfig,
+    records: Vec<WebRecord>,
 }
 
 impl World {
@@ -43,6 +54,8 @@ impl World {
             .expect("Unable to create web module"),
             memo: None,
             domain: None,
+            config: WebSiteConfig::default(),
+            records: vec![],
         }
     }
 }
@@ -80,6 +93,104 @@ fn given_site_owner(w: &mut World, seed: u32) {
     w.owner = Some(identity(seed));
 }
 
+#[given(expr = "a website redirect from {string} to {string} with status {int}")]
+fn given_site_redirect(w: &mut World, from: String, to: String, status: u16) {
+    w.config.redirects.push(WebRedirect { from, to, status });
+}
+
+#[given(expr = "a website 404 document {string}")]
+fn given_site_not_found(w: &mut World, path: String) {
+    w.config.not_found = Some(path);
+}
+
+fn set_config(w: &mut World, seed: u32) -> Result<(), ManyError> {
+    w.module
+        .set_config(
+            &identity(seed),
+            SetConfigArgs {
+                owner: w.owner,
+                site_name: w.site_name.clone(),
+                config: w.config.clone(),
+            },
+        )
+        .map(|_| ())
+}
+
+#[given(expr = "a website text record {string}")]
+fn given_site_txt_record(w: &mut World, text: String) {
+    w.records.push(WebRecord::Txt(text));
+}
+
+#[given(expr = "a website content record to website {string} of identity {int}")]
+fn given_site_content_record(w: &mut World, site_name: String, seed: u32) {
+    w.records.push(WebRecord::Content(WebSiteRef {
+        owner: identity(seed),
+        site_name,
+    }));
+}
+
+#[given(expr = "a website redirect record to {string} with status {int}")]
+fn given_site_redirect_record(w: &mut World, to: String, status: u16) {
+    w.records
+        .push(WebRecord::Redirect(WebSiteRedirect { to, status }));
+}
+
+fn set_records(w: &mut World, seed: u32) -> Result<(), ManyError> {
+    w.module
+        .set_records(
+            &identity(seed),
+            SetRecordsArgs {
+                owner: w.owner,
+                site_name: w.site_name.clone(),
+                records: w.records.clone(),
+            },
+        )
+        .map(|_| ())
+}
+
+fn resolve(w: &World, site_name: String, seed: u32) -> ResolveReturns {
+    WebModuleBackend::resolve(
+        &w.module,
+        &identity(0),
+        ResolveArgs {
+            owner: identity(seed),
+            site_name,
+        },
+    )
+    .expect("Unable to resolve website")
+}
+
+fn config(w: &World, site_name: String, seed: u32) -> WebSiteConfig {
+    WebModuleBackend::config(
+        &w.module,
+        &identity(0),
+        ConfigArgs {
+            owner: identity(seed),
+            site_name,
+        },
+    )
+    .expect("Unable to get website config")
+    .config
+}
+
+fn content_hash(
+    w: &World,
+    site_name: String,
+    seed: u32,
+    path: Option<String>,
+) -> ContentHashReturns {
+    WebModuleBackend::content_hash(
+        &w.module,
+        &identity(0),
+        ContentHashArgs {
+            owner: identity(seed),
+            site_name,
+            path,
+        },
+    )
+    .expect("Unable to get website content hash")
+}
+
 #[when(expr = "the website is deployed as identity {int}")]
 fn when_deploy(w: &mut World, seed: u32) {
     w.module
@@ -141,6 +252,7 @@ fn then_live(w: &mut World, step: &Step, file: String, site_name: String, seed:
                     .into_bytes()
                     .into(),
             },
+            Context::new(RequestMessage::default(), unbounded().0),
         )
         .expect("Website not found");
     assert_eq!(
@@ -161,6 +273,7 @@ fn then_empty(w: &mut World, file: String, site_name: String, seed: u32) {
                     .into_bytes()
                     .into(),
             },
+            Context::new(RequestMessage::default(), unbounded().0),
         )
         .expect("Website not found");
     assert_eq!(ret.value, None);
@@ -263,6 +376,127 @@ fn then_list_not_filtered(w: &mut World, seed: u32, site_name: String) {
         .any(|v| v.site_name != site_name));
 }
 
+#[when(expr = "the website config is set as identity {int}")]
+fn when_set_config(w: &mut World, seed: u32) {
+    set_config(w, seed).expect("Setting website config failed");
+}
+
+#[then(
+    expr = "{string} of website {string} for identity {int} redirects to {string} with status {int}"
+)]
+fn then_redirect(
+    w: &mut World,
+    from: String,
+    site_name: String,
+    seed: u32,
+    to: String,
+    status: u16,
+) {
+    let config = config(w, site_name, seed);
+    let redirect = config.redirect_for(&from).expect("Redirect not found");
+    assert_eq!(redirect.to, to);
+    assert_eq!(redirect.status, status);
+}
+
+#[then(expr = "the 404 document of website {string} for identity {int} is {string}")]
+fn then_not_found(w: &mut World, site_name: String, seed: u32, path: String) {
+    assert_eq!(config(w, site_name, seed).not_found, Some(path));
+}
+
+#[then(
+    expr = "the content hash of {string} of website {string} for identity {int} matches its value"
+)]
+fn then_content_hash(w: &mut World, file: String, site_name: String, seed: u32) {
+    let value = w
+        .module
+        .get(
+            &identity(0),
+            GetArgs {
+                key: format!("{}/{}/{}/{}", HTTP_ROOT, identity(seed), site_name, file)
+                    .into_bytes()
+                    .into(),
+            },
+            Context::new(RequestMessage::default(), unbounded().0),
+        )
+        .expect("Website not found")
+        .value
+        .expect("Key is empty");
+    let ret = content_hash(w, site_name, seed, Some(format!("/{file}")));
+    assert_eq!(
+        ret.hash.as_slice(),
+        sha2::Sha256::digest(value.as_slice()).as_slice()
+    );
+    assert_eq!(ret.files, None);
+}
+
+#[then(expr = "the root hash of website {string} for identity {int} matches its files")]
+fn then_root_hash(w: &mut World, site_name: String, seed: u32) {
+    let ret = content_hash(w, site_name, seed, None);
+    let files = ret.files.expect("Files are empty");
+    assert!(files.contains_key("index.html"));
+    assert_eq!(
+        ret.hash.as_slice(),
+        sha2::Sha256::digest(minicbor::to_vec(&files).unwrap()).as_slice()
+    );
+}
+
+#[when(expr = "the website records are set as identity {int}")]
+fn when_set_records(w: &mut World, seed: u32) {
+    set_records(w, seed).expect("Setting website records failed");
+}
+
+#[then(
+    expr = "website {string} for identity {int} resolves to website {string} for identity {int}"
+)]
+fn then_resolves_to(w: &mut World, site_name: String, seed: u32, target: String, target_seed: u32) {
+    let resolved = resolve(w, site_name, seed);
+    assert_eq!(
+        resolved.site,
+        WebSiteRef {
+            owner: identity(target_seed),
+            site_name: target,
+        }
+    );
+    assert_eq!(resolved.redirect, None);
+}
+
+#[then(expr = "website {string} for identity {int} has the text record {string}")]
+fn then_txt_record(w: &mut World, site_name: String, seed: u32, text: String) {
+    assert!(resolve(w, site_name, seed).txt.contains(&text));
+}
+
+#[then(
+    expr = "website {string} for identity {int} redirects every request to {string} with status {int}"
+)]
+fn then_redirects_every_request(
+    w: &mut World,
+    site_name: String,
+    seed: u32,
+    to: String,
+    status: u16,
+) {
+    assert_eq!(
+        resolve(w, site_name, seed).redirect,
+        Some(WebSiteRedirect { to, status })
+    );
+}
+
+#[then(expr = "setting the website records as identity {int} fails with {string}")]
+fn then_set_records_failed(w: &mut World, seed: u32, error: String) {
+    assert!(matches!(
+        set_records(w, seed),
+        Err(e) if e.to_string() == error
+    ));
+}
+
+#[then(expr = "setting the website config fails with {string}")]
+fn then_set_config_failed(w: &mut World, error: String) {
+    assert!(matches!(
+        set_config(w, 0),
+        Err(e) if e.to_string() == error
+    ));
+}
+
 #[then(expr = "the website deployment fails with {string}")]
 fn then_deployment_failed(w: &mut World, error: String) {
     assert!(matches!(
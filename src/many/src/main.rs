@@ -3,12 +3,17 @@ use async_recursion::async_recursion;
 use base64::{engine::general_purpose, Engine as _};
 use clap::{ArgGroup, Parser};
 use coset::{CborSerializable, CoseSign1};
+use many_cli_helpers::config::CliConfig;
 use many_cli_helpers::error::ClientServerError;
+use many_cli_helpers::plugin;
 use many_client::ManyClient;
 use many_identity::verifiers::AnonymousVerifier;
 use many_identity::{Address, AnonymousIdentity, Identity};
 use many_identity_dsa::{CoseKeyIdentity, CoseKeyVerifier};
-use many_identity_hsm::{Hsm, HsmIdentity, HsmMechanismType, HsmSessionType, HsmUserType};
+use many_identity_hsm::{
+    parse_mechanism, Hsm, HsmIdentity, HsmMechanismType, HsmSessionType, HsmUserType,
+};
+use many_identity_ledger_hw::{DerivationPath, LedgerHwDevice, LedgerHwIdentity};
 use many_identity_webauthn::WebAuthnIdentity;
 use many_mock::{parse_mockfile, server::ManyMockServer, MockEntries};
 use many_modules::r#async::attributes::AsyncAttribute;
@@ -19,8 +24,11 @@ use many_protocol::{
 };
 use many_server::transport::http::HttpServer;
 use many_server::ManyServer;
-use many_types::{attributes::Attribute, Timestamp};
+use many_types::min_height::MinHeight;
+use many_types::priority::PriorityHint;
+use many_types::{attributes::Attribute, diag, Timestamp, PROOF};
 use std::convert::TryFrom;
+use std::ffi::OsString;
 use std::io::{stderr, IsTerminal};
 use std::net::SocketAddr;
 use std::path::PathBuf;
@@ -30,6 +38,9 @@ use std::time::{Duration, SystemTime};
 use tracing::{error, info, trace};
 use url::Url;
 
+mod offline;
+mod ping;
+
 #[derive(Parser)]
 struct Opts {
     #[clap(flatten)]
@@ -49,18 +60,46 @@ enum SubCommand {
     /// Display the textual ID of a public key located on an HSM.
     HsmId(HsmIdOpt),
 
+    /// Display the textual ID of a public key located on a Ledger device.
+    LedgerHwId(LedgerHwIdOpt),
+
     /// Display the textual ID of a webauthn key.
     WebauthnId(WebauthnIdOpt),
 
     /// Creates a message and output it.
     Message(Box<MessageOpt>),
 
+    /// Sign a request exported with `many message --unsigned`.
+    Sign(SignOpt),
+
+    /// Combine signed requests into a single file, to be submitted together.
+    Combine(CombineOpt),
+
+    /// Submit signed requests to a server, in order.
+    Submit(SubmitOpt),
+
+    /// Print a CBOR value, e.g. a request or an event, in diagnostic notation
+    /// with the names of the fields of known structures.
+    Inspect(InspectOpt),
+
     /// Starts a base server that can also be used for reverse proxying
     /// to another MANY server.
     Server(ServerOpt),
 
     /// Get the token ID per string of a ledger's token.
     GetTokenId(GetTokenIdOpt),
+
+    /// Measure the latency to a server by probing its `base.echo` endpoint.
+    Ping(PingOpt),
+
+    /// List the subcommands provided by the bundled tools (ledger, kvstore,
+    /// idstore, web) and by plugins (executables named `many-<cmd>` in the
+    /// PATH).
+    Plugins,
+
+    /// Any other subcommand is run by the bundled tool or plugin of that name.
+    #[clap(external_subcommand)]
+    External(Vec<String>),
 }
 
 #[derive(Parser)]
@@ -84,6 +123,24 @@ struct HsmIdOpt {
     /// HSM PKCS#11 key ID
     keyid: String,
 
+    /// HSM PKCS#11 mechanism of the key, `ecdsa` or `eddsa`.
+    #[clap(long, default_value = "ecdsa", value_parser = parse_mechanism)]
+    mechanism: HsmMechanismType,
+
+    /// Allow to generate the identity with a specific subresource ID.
+    subid: Option<u32>,
+}
+
+#[derive(Parser)]
+struct LedgerHwIdOpt {
+    /// The derivation path of the key on the device.
+    #[clap(long, default_value_t)]
+    path: DerivationPath,
+
+    /// Show the key on the device and wait for the user to confirm it.
+    #[clap(long)]
+    confirm: bool,
+
     /// Allow to generate the identity with a specific subresource ID.
     subid: Option<u32>,
 }
@@ -113,7 +170,7 @@ struct WebauthnIdOpt {
     ),
     group(
         ArgGroup::new("action")
-            .args(&["server", "hex", "base64"])
+            .args(&["server", "hex", "base64", "out"])
             .required(true)
     )
 )]
@@ -166,6 +223,20 @@ struct MessageOpt {
     #[clap(long)]
     base64: bool,
 
+    /// Write the message bytes (CBOR) to a file.
+    #[clap(long)]
+    out: Option<PathBuf>,
+
+    /// Export the request without signing it, to be signed later with
+    /// `many sign`.
+    #[clap(long, conflicts_with("server"))]
+    unsigned: bool,
+
+    /// The identity the unsigned request is from. By default, the address of
+    /// the key.
+    #[clap(long, requires("unsigned"))]
+    from: Option<Address>,
+
     /// If used, send the message from hexadecimal to the server and wait for
     /// the response.
     #[clap(long, requires("server"))]
@@ -192,6 +263,18 @@ struct MessageOpt {
     #[clap(long, conflicts_with("pem"))]
     keyid: Option<String>,
 
+    /// HSM PKCS#11 mechanism of the key, `ecdsa` or `eddsa`.
+    #[clap(long, default_value = "ecdsa", value_parser = parse_mechanism)]
+    mechanism: HsmMechanismType,
+
+    /// Sign the message with a key on a Ledger device.
+    #[clap(long, conflicts_with_all(&["pem", "module", "webauthn"]))]
+    ledger_hw: bool,
+
+    /// The derivation path of the key on the Ledger device.
+    #[clap(long, requires("ledger-hw"), default_value_t)]
+    ledger_hw_path: DerivationPath,
+
     /// The method to call.
     method: Option<String>,
 
@@ -203,6 +286,132 @@ struct MessageOpt {
     /// the specification for more information.
     #[clap(long)]
     proof: Option<bool>,
+
+    /// Only answer the query from a state at least at this block height,
+    /// e.g. the height of a transaction just committed. The server waits a
+    /// bounded time for the height, and returns an error if it is not
+    /// reached.
+    #[clap(long)]
+    min_height: Option<u64>,
+
+    /// Hint at the priority of the request, `low` or `normal`. Servers may
+    /// answer low priority requests after the other ones received at the
+    /// same time.
+    #[clap(long)]
+    priority: Option<PriorityHint>,
+
+    /// The network the request is for. Servers on another network refuse it.
+    #[clap(long)]
+    chain_id: Option<String>,
+}
+
+#[derive(Parser)]
+#[clap(
+    group(
+        ArgGroup::new("hsm")
+            .multiple(true)
+            .args(&["module", "slot", "keyid"])
+            .requires_all(&["module", "slot", "keyid"])
+    ),
+    group(
+        ArgGroup::new("key")
+            .args(&["pem", "module", "ledger-hw"])
+            .required(true)
+    )
+)]
+struct SignOpt {
+    /// The unsigned request, as raw CBOR, hexadecimal or base64.
+    file: PathBuf,
+
+    /// A pem file to sign the request.
+    #[clap(long)]
+    pem: Option<PathBuf>,
+
+    /// HSM PKCS#11 module path
+    #[clap(long)]
+    module: Option<PathBuf>,
+
+    /// HSM PKCS#11 slot ID
+    #[clap(long)]
+    slot: Option<u64>,
+
+    /// HSM PKCS#11 key ID
+    #[clap(long)]
+    keyid: Option<String>,
+
+    /// HSM PKCS#11 mechanism of the key, `ecdsa` or `eddsa`.
+    #[clap(long, default_value = "ecdsa", value_parser = parse_mechanism)]
+    mechanism: HsmMechanismType,
+
+    /// Sign the request with a key on a Ledger device.
+    #[clap(long)]
+    ledger_hw: bool,
+
+    /// The derivation path of the key on the Ledger device.
+    #[clap(long, requires("ledger-hw"), default_value_t)]
+    ledger_hw_path: DerivationPath,
+
+    /// Write the signed request (CBOR) to a file. By default, prints it out in
+    /// hexadecimal.
+    #[clap(long)]
+    out: Option<PathBuf>,
+
+    /// Print out the signed request in base64 instead of hexadecimal.
+    #[clap(long, conflicts_with("out"))]
+    base64: bool,
+}
+
+#[derive(Parser)]
+struct CombineOpt {
+    /// The signed requests, or combinations of signed requests, in order.
+    #[clap(required = true)]
+    files: Vec<PathBuf>,
+
+    /// Write the combination (CBOR) to a file. By default, prints it out in
+    /// hexadecimal.
+    #[clap(long)]
+    out: Option<PathBuf>,
+
+    /// Print out the combination in base64 instead of hexadecimal.
+    #[clap(long, conflicts_with("out"))]
+    base64: bool,
+}
+
+#[derive(Parser)]
+struct InspectOpt {
+    /// The value, as raw CBOR, hexadecimal or base64. Signed envelopes are
+    /// printed with their payload.
+    file: PathBuf,
+
+    /// The type of the value. Requests and responses are recognized by their
+    /// tag.
+    #[clap(long = "type", arg_enum, default_value = "any")]
+    value_type: InspectType,
+}
+
+#[derive(clap::ArgEnum, Clone, Debug)]
+enum InspectType {
+    Any,
+    Error,
+    Event,
+    TokenInfo,
+}
+
+#[derive(Parser)]
+struct SubmitOpt {
+    /// The server to connect to.
+    #[clap(long)]
+    server: Url,
+
+    /// A signed request, or a combination of signed requests, as raw CBOR,
+    /// hexadecimal or base64.
+    #[clap(long)]
+    from_file: PathBuf,
+
+    /// Show the async token and exit right away. By default, will poll for the
+    /// result of the async operation.
+    #[clap(long)]
+    r#async: bool,
 }
 
 #[derive(Parser)]
@@ -235,6 +444,24 @@ struct GetTokenIdOpt {
     symbol: String,
 }
 
+#[derive(Parser)]
+struct PingOpt {
+    /// The server to probe.
+    server: url::Url,
+
+    /// The number of probes to send.
+    #[clap(long, short = 'n', default_value = "10")]
+    count: u32,
+
+    /// The size of the payload echoed by the server, in bytes.
+    #[clap(long, default_value = "32")]
+    size: usize,
+
+    /// Milliseconds to wait between probes.
+    #[clap(long, default_value = "1000")]
+    interval: u64,
+}
+
 #[async_recursion(?Send)]
 async fn show_response<'a>(
     response: &'a ResponseMessage,
@@ -308,6 +535,24 @@ async fn show_response<'a>(
     Ok(())
 }
 
+fn request_attributes(
+    proof: bool,
+    min_height: Option<u64>,
+    priority: Option<PriorityHint>,
+) -> Vec<Attribute> {
+    let mut attributes = vec![];
+    if proof {
+        attributes.push(PROOF);
+    }
+    if let Some(height) = min_height {
+        attributes.push(MinHeight(height).into());
+    }
+    if let Some(priority) = priority {
+        attributes.push(priority.into());
+    }
+    attributes
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn message(
     s: Url,
@@ -317,7 +562,8 @@ async fn message(
     data: Vec<u8>,
     timestamp: Option<SystemTime>,
     r#async: bool,
-    proof: bool,
+    attributes: Vec<Attribute>,
+    chain_id: Option<String>,
 ) -> Result<(), ClientServerError> {
     let address = key.address();
     let client = ManyClient::new(s, to, key).unwrap();
@@ -333,20 +579,16 @@ async fn message(
         .method(method)
         .data(data)
         .nonce(nonce.to_vec())
-        .attributes(
-            if proof {
-                vec![Attribute::id(3)]
-            } else {
-                vec![]
-            }
-            .into_iter()
-            .collect(),
-        );
+        .attributes(attributes.into_iter().collect());
 
     if let Some(ts) = timestamp {
         builder.timestamp(Timestamp::from_system_time(ts)?);
     }
 
+    if let Some(chain_id) = chain_id {
+        builder.chain_id(chain_id);
+    }
+
     let message: RequestMessage = builder
         .build()
         .map_err(|e| anyhow!("Could not build request: {e}"))?;
@@ -363,11 +605,21 @@ async fn message_from_hex(
     hex: String,
     r#async: bool,
 ) -> Result<(), ClientServerError> {
-    let client = ManyClient::new(s.clone(), to, key).unwrap();
-
     let data = hex::decode(hex).map_err(|e| anyhow!(e))?;
     let envelope = CoseSign1::from_slice(&data).map_err(|e| anyhow!(e))?;
 
+    send_envelope(s, to, key, envelope, r#async).await
+}
+
+async fn send_envelope(
+    s: Url,
+    to: Address,
+    key: impl Identity,
+    envelope: CoseSign1,
+    r#async: bool,
+) -> Result<(), ClientServerError> {
+    let client = ManyClient::new(s.clone(), to, key).unwrap();
+
     let cose_sign1 = many_client::client::send_envelope(s, envelope).await?;
     let response =
         ResponseMessage::decode_and_verify(&cose_sign1, &(AnonymousVerifier, CoseKeyVerifier))?;
@@ -375,6 +627,36 @@ async fn message_from_hex(
     show_response(&response, client, r#async).await
 }
 
+fn hsm_identity(
+    module: PathBuf,
+    slot: u64,
+    keyid: String,
+    mechanism: HsmMechanismType,
+) -> HsmIdentity {
+    trace!("Getting user PIN");
+    let pin = rpassword::prompt_password("Please enter the HSM user PIN: ")
+        .expect("I/O error when reading HSM PIN");
+    let keyid = hex::decode(keyid).expect("Failed to decode keyid to hex");
+
+    {
+        let mut hsm = Hsm::get_instance().expect("HSM mutex poisoned");
+        hsm.init(module, keyid)
+            .expect("Failed to initialize HSM module");
+
+        // The session will stay open until the application terminates
+        hsm.open_session(slot, HsmSessionType::RO, Some(HsmUserType::User), Some(pin))
+            .expect("Failed to open HSM session");
+    }
+
+    HsmIdentity::new(mechanism).expect("Unable to create CoseKeyIdentity from HSM")
+}
+
+fn ledger_hw_identity(path: DerivationPath) -> LedgerHwIdentity {
+    let device = LedgerHwDevice::connect().expect("Unable to connect to the Ledger device");
+    LedgerHwIdentity::new(device, path, false)
+        .expect("Unable to create identity from the Ledger device")
+}
+
 async fn create_webauthn_identity(
     rp: ManyUrl,
     origin: Option<ManyUrl>,
@@ -389,7 +671,10 @@ async fn create_webauthn_identity(
         client
             .call(
                 "idstore.getFromRecallPhrase",
-                idstore::GetFromRecallPhraseArgs(phrase.split(' ').map(String::from).collect()),
+                idstore::GetFromRecallPhraseArgs(
+                    phrase.split(' ').map(String::from).collect(),
+                    None,
+                ),
             )
             .await
             .unwrap()
@@ -422,14 +707,24 @@ async fn create_webauthn_identity(
 
 #[tokio::main]
 async fn main() {
+    let config = CliConfig::load();
+    let args = std::env::args_os().collect();
     let Opts {
         verbosity,
         subcommand,
-    } = Opts::parse();
+    } = Opts::parse_from(match &config {
+        Ok(config) => config.expand_aliases(args),
+        Err(_) => args,
+    });
     tracing_subscriber::fmt()
         .with_max_level(verbosity.level())
         .init();
 
+    let config = config.unwrap_or_else(|e| {
+        error!("{e}");
+        process::exit(2);
+    });
+
     match subcommand {
         SubCommand::Id(o) => {
             if let Ok(data) = hex::decode(&o.arg) {
@@ -482,7 +777,7 @@ async fn main() {
                     .expect("Failed to open HSM session");
             }
 
-            let mut id = HsmIdentity::new(HsmMechanismType::ECDSA)
+            let mut id = HsmIdentity::new(o.mechanism)
                 .expect("Unable to create CoseKeyIdentity from HSM")
                 .address();
 
@@ -494,6 +789,20 @@ async fn main() {
 
             println!("{id}");
         }
+        SubCommand::LedgerHwId(o) => {
+            let device = LedgerHwDevice::connect().expect("Unable to connect to the Ledger device");
+            let mut id = LedgerHwIdentity::new(device, o.path, o.confirm)
+                .expect("Unable to create identity from the Ledger device")
+                .address();
+
+            if let Some(subid) = o.subid {
+                id = id
+                    .with_subresource_id(subid)
+                    .expect("Invalid subresource id");
+            }
+
+            println!("{id}");
+        }
         SubCommand::WebauthnId(o) => {
             let identity = create_webauthn_identity(o.rp, None, o.phrase, o.address, None).await;
             println!("{}", identity.address());
@@ -512,28 +821,13 @@ async fn main() {
             let from_identity: Box<dyn Identity> = if let (Some(module), Some(slot), Some(keyid)) =
                 (o.module, o.slot, o.keyid)
             {
-                trace!("Getting user PIN");
-                let pin = rpassword::prompt_password("Please enter the HSM user PIN: ")
-                    .expect("I/O error when reading HSM PIN");
-                let keyid = hex::decode(keyid).expect("Failed to decode keyid to hex");
-
-                {
-                    let mut hsm = Hsm::get_instance().expect("HSM mutex poisoned");
-                    hsm.init(module, keyid)
-                        .expect("Failed to initialize HSM module");
-
-                    // The session will stay open until the application terminates
-                    hsm.open_session(slot, HsmSessionType::RO, Some(HsmUserType::User), Some(pin))
-                        .expect("Failed to open HSM session");
-                }
-
-                // Only ECDSA is supported at the moment. It should be easy to add support for
-                // new EC mechanisms.
-                Box::new(
-                    HsmIdentity::new(HsmMechanismType::ECDSA)
-                        .expect("Unable to create CoseKeyIdentity from HSM"),
-                )
-            } else if let Some(p) = o.pem {
+                Box::new(hsm_identity(module, slot, keyid, o.mechanism))
+            } else if o.ledger_hw {
+                Box::new(ledger_hw_identity(o.ledger_hw_path))
+            } else if let Some(p) = o.pem.or_else(|| {
+                // The default key only applies when no other identity was asked for.
+                (!o.webauthn).then(|| config.default_pem()).flatten()
+            }) {
                 // If `pem` is not provided, use anonymous and don't sign.
                 Box::new(CoseKeyIdentity::from_pem(std::fs::read_to_string(p).unwrap()).unwrap())
             } else if o.webauthn {
@@ -566,7 +860,8 @@ async fn main() {
                         data,
                         timestamp,
                         o.r#async,
-                        o.proof.unwrap_or_default(),
+                        request_attributes(o.proof.unwrap_or_default(), o.min_height, o.priority),
+                        o.chain_id,
                     )
                     .await
                 };
@@ -582,27 +877,36 @@ async fn main() {
                 let mut builder = RequestMessageBuilder::default();
                 builder
                     .version(1)
-                    .from(from_identity.address())
+                    .from(o.from.unwrap_or_else(|| from_identity.address()))
                     .to(to_identity)
                     .method(o.method.expect("--method is required"))
                     .data(data)
                     .attributes(
-                        match o.proof {
-                            Some(false) | None => vec![],
-                            Some(true) => vec![Attribute::id(3)],
-                        }
-                        .into_iter()
-                        .collect(),
+                        request_attributes(o.proof.unwrap_or_default(), o.min_height, o.priority)
+                            .into_iter()
+                            .collect(),
                     );
                 if let Some(ts) = timestamp {
                     builder.timestamp(Timestamp::from_system_time(ts).unwrap());
                 }
+                if let Some(chain_id) = o.chain_id {
+                    builder.chain_id(chain_id);
+                }
 
                 let message = builder.build().unwrap();
 
-                let cose = encode_cose_sign1_from_request(message, &from_identity).unwrap();
-                let bytes = cose.to_vec().unwrap();
-                if o.hex {
+                let bytes = if o.unsigned {
+                    message.to_bytes().unwrap()
+                } else {
+                    let cose = encode_cose_sign1_from_request(message, &from_identity).unwrap();
+                    cose.to_vec().unwrap()
+                };
+                if let Some(out) = o.out {
+                    if let Err(e) = offline::output(&bytes, Some(&out), false) {
+                        error!("{e}");
+                        process::exit(1);
+                    }
+                } else if o.hex {
                     println!("{}", hex::encode(&bytes));
                 } else if o.base64 {
                     println!("{}", general_purpose::STANDARD.encode(&bytes));
@@ -611,6 +915,77 @@ async fn main() {
                 }
             }
         }
+        SubCommand::Sign(o) => {
+            let identity: Box<dyn Identity> = if let (Some(module), Some(slot), Some(keyid)) =
+                (o.module, o.slot, o.keyid)
+            {
+                Box::new(hsm_identity(module, slot, keyid, o.mechanism))
+            } else if o.ledger_hw {
+                Box::new(ledger_hw_identity(o.ledger_hw_path))
+            } else {
+                let pem = o.pem.expect("Must specify a key");
+                Box::new(CoseKeyIdentity::from_pem(std::fs::read_to_string(pem).unwrap()).unwrap())
+            };
+
+            let result = offline::read_file(&o.file)
+                .and_then(|request| offline::sign(&request, identity))
+                .and_then(|bytes| offline::output(&bytes, o.out.as_deref(), o.base64));
+            if let Err(e) = result {
+                error!("{e}");
+                process::exit(1);
+            }
+        }
+        SubCommand::Inspect(o) => {
+            let schema = match o.value_type {
+                InspectType::Any => &diag::Schema::Any,
+                InspectType::Error => &diag::MANY_ERROR,
+                InspectType::Event => &many_modules::events::EVENT_LOG_SCHEMA,
+                InspectType::TokenInfo => &diag::TOKEN_INFO,
+            };
+            match offline::read_file(&o.file).and_then(|bytes| offline::inspect(&bytes, schema)) {
+                Ok(diag) => println!("{diag}"),
+                Err(e) => {
+                    error!("{e}");
+                    process::exit(1);
+                }
+            }
+        }
+        SubCommand::Combine(o) => {
+            let result = o
+                .files
+                .iter()
+                .map(|f| offline::read_file(f))
+                .collect::<Result<Vec<_>, _>>()
+                .and_then(|files| offline::combine(&files))
+                .and_then(|bytes| offline::output(&bytes, o.out.as_deref(), o.base64));
+            if let Err(e) = result {
+                error!("{e}");
+                process::exit(1);
+            }
+        }
+        SubCommand::Submit(o) => {
+            let envelopes = offline::read_file(&o.from_file)
+                .and_then(|bytes| offline::envelopes(&bytes))
+                .unwrap_or_else(|e| {
+                    error!("{e}");
+                    process::exit(1);
+                });
+
+            for envelope in envelopes {
+                let result = send_envelope(
+                    o.server.clone(),
+                    Address::anonymous(),
+                    AnonymousIdentity,
+                    envelope,
+                    o.r#async,
+                )
+                .await;
+                if let Err(err) = result {
+                    error!("{err}");
+                    process::exit(1);
+                }
+            }
+        }
         SubCommand::Server(o) => {
             let pem = std::fs::read_to_string(&o.pem).expect("Could not read PEM file.");
             let key = Arc::new(
@@ -663,5 +1038,33 @@ async fn main() {
 
             println!("{id}");
         }
+        SubCommand::Ping(o) => {
+            let interval = Duration::from_millis(o.interval);
+            if let Err(e) = ping::ping(o.server, o.count, o.size, interval).await {
+                error!("{e}");
+                process::exit(1);
+            }
+        }
+        SubCommand::Plugins => {
+            for (command, path) in plugin::list() {
+                println!("{command}\t{}", path.display());
+            }
+        }
+        SubCommand::External(args) => {
+            let (command, args) = args.split_first().expect("Subcommand is required");
+            let path = plugin::find(command).unwrap_or_else(|| {
+                error!("Unknown subcommand '{command}'. No plugin named 'many-{command}' found.");
+                process::exit(2);
+            });
+            let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+
+            match plugin::run(&path, &args, &config) {
+                Ok(status) => process::exit(status.code().unwrap_or(1)),
+                Err(e) => {
+                    error!("{e}");
+                    process::exit(1);
+                }
+            }
+        }
     }
 }
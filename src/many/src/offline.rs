@@ -0,0 +1,112 @@
+//! Offline signing. A request is exported unsigned on a connected machine, signed
+//! on a machine holding the key (which can be air-gapped), and then submitted
+//! separately. Signed envelopes from multiple keys can be combined to be
+//! submitted together.
+use anyhow::anyhow;
+use base64::{engine::general_purpose, Engine as _};
+use coset::{CborSerializable, CoseSign1};
+use many_identity::Identity;
+use many_protocol::compression::decode_payload;
+use many_protocol::{encode_cose_sign1_from_request, RequestMessage};
+use many_types::diag::{to_diag, Schema};
+use minicbor::bytes::ByteVec;
+use std::path::Path;
+
+/// Read the content of a file, either raw CBOR or its hexadecimal or base64
+/// representation.
+pub fn read_file(path: &Path) -> Result<Vec<u8>, anyhow::Error> {
+    let content =
+        std::fs::read(path).map_err(|e| anyhow!("Could not read {}: {e}", path.display()))?;
+
+    // Raw CBOR maps, arrays and tags cannot start a valid UTF-8 string.
+    if let Ok(text) = std::str::from_utf8(&content) {
+        let text = text.trim();
+        if let Ok(bytes) = hex::decode(text) {
+            return Ok(bytes);
+        }
+        if let Ok(bytes) = general_purpose::STANDARD.decode(text) {
+            return Ok(bytes);
+        }
+    }
+    Ok(content)
+}
+
+/// Write the bytes to a file if `out` is set, otherwise print them in
+/// hexadecimal (or base64).
+pub fn output(bytes: &[u8], out: Option<&Path>, base64: bool) -> Result<(), anyhow::Error> {
+    if let Some(out) = out {
+        std::fs::write(out, bytes).map_err(|e| anyhow!("Could not write {}: {e}", out.display()))
+    } else {
+        if base64 {
+            println!("{}", general_purpose::STANDARD.encode(bytes));
+        } else {
+            println!("{}", hex::encode(bytes));
+        }
+        Ok(())
+    }
+}
+
+/// Sign an unsigned request. A request without a `from` is signed as the key's
+/// address, otherwise both need to match.
+pub fn sign(request: &[u8], identity: impl Identity) -> Result<Vec<u8>, anyhow::Error> {
+    let mut message = RequestMessage::from_bytes(request)
+        .map_err(|e| anyhow!("Invalid unsigned request: {e}"))?;
+
+    let address = identity.address();
+    match message.from {
+        Some(from) if !from.is_anonymous() && from != address => {
+            return Err(anyhow!(
+                "The request is from {from}, but the key's address is {address}."
+            ));
+        }
+        _ => message.from = Some(address),
+    }
+
+    let envelope = encode_cose_sign1_from_request(message, &identity)?;
+    envelope.to_vec().map_err(|e| anyhow!("{e}"))
+}
+
+/// Combine signed envelopes (or combinations of envelopes) to be submitted in
+/// order.
+pub fn combine(files: &[Vec<u8>]) -> Result<Vec<u8>, anyhow::Error> {
+    let mut all = vec![];
+    for bytes in files {
+        for envelope in envelopes(bytes)? {
+            let bytes = envelope.to_vec().map_err(|e| anyhow!("{e}"))?;
+            all.push(ByteVec::from(bytes));
+        }
+    }
+    minicbor::to_vec(all).map_err(|e| anyhow!("{e}"))
+}
+
+/// Decode a signed envelope, or a combination of envelopes.
+pub fn envelopes(bytes: &[u8]) -> Result<Vec<CoseSign1>, anyhow::Error> {
+    if let Ok(envelope) = CoseSign1::from_slice(bytes) {
+        return Ok(vec![envelope]);
+    }
+
+    minicbor::decode::<Vec<ByteVec>>(bytes)
+        .map_err(|_| anyhow!("Not a signed envelope, or a combination of envelopes."))?
+        .iter()
+        .map(|b| CoseSign1::from_slice(b).map_err(|e| anyhow!("Invalid envelope: {e}")))
+        .collect()
+}
+
+/// Format a CBOR value in diagnostic notation with the field names of
+/// `schema`. Signed envelopes, or combinations of envelopes, are formatted
+/// with their payload.
+pub fn inspect(bytes: &[u8], schema: &Schema) -> Result<String, anyhow::Error> {
+    let envelopes = match envelopes(bytes) {
+        Ok(envelopes) => envelopes,
+        Err(_) => return to_diag(bytes, schema).map_err(|e| anyhow!("{e}")),
+    };
+
+    envelopes
+        .iter()
+        .map(|envelope| {
+            let payload = decode_payload(envelope).map_err(|e| anyhow!("{e}"))?;
+            to_diag(&payload, schema).map_err(|e| anyhow!("{e}"))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(|payloads| payloads.join("\n"))
+}
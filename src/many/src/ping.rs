@@ -0,0 +1,94 @@
+//! Network diagnosis. Probes a server's `base.echo` endpoint and reports the
+//! round-trip latency, and the part of it spent by the server handling the
+//! request.
+use anyhow::anyhow;
+use many_client::client::echo::EchoArgs;
+use many_client::{EchoClient, ManyClient};
+use many_identity::{Address, AnonymousIdentity};
+use minicbor::bytes::ByteVec;
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// Latencies of all the probes that got a response.
+#[derive(Default)]
+struct Samples {
+    round_trip: Vec<Duration>,
+    server: Vec<Duration>,
+}
+
+/// Nearest-rank percentile of sorted samples.
+fn percentile(sorted: &[Duration], p: u32) -> Duration {
+    let rank = (sorted.len() * p as usize + 99) / 100;
+    sorted[rank.saturating_sub(1)]
+}
+
+fn summary(name: &str, samples: &mut [Duration]) {
+    if samples.is_empty() {
+        return;
+    }
+    samples.sort();
+    println!(
+        "{name:<10} min {:?}, p50 {:?}, p90 {:?}, p99 {:?}, max {:?}",
+        samples[0],
+        percentile(samples, 50),
+        percentile(samples, 90),
+        percentile(samples, 99),
+        samples[samples.len() - 1],
+    );
+}
+
+/// Send `count` probes of `size` bytes to the server, `interval` apart, and
+/// print the latency of each followed by percentiles. Fails if no probe
+/// succeeded.
+pub async fn ping(
+    server: Url,
+    count: u32,
+    size: usize,
+    interval: Duration,
+) -> Result<(), anyhow::Error> {
+    let client = EchoClient::new(
+        ManyClient::new(server.clone(), Address::anonymous(), AnonymousIdentity)
+            .map_err(|e| anyhow!("{e}"))?,
+    );
+    let payload = ByteVec::from((0..size).map(|i| i as u8).collect::<Vec<u8>>());
+    let mut samples = Samples::default();
+
+    println!("PING {server}: {size} bytes of payload");
+    for seq in 0..count {
+        if seq > 0 {
+            tokio::time::sleep(interval).await;
+        }
+
+        let start = Instant::now();
+        let result = client
+            .echo(EchoArgs {
+                payload: payload.clone(),
+            })
+            .await;
+        let round_trip = start.elapsed();
+
+        match result {
+            Ok(returns) if returns.payload == payload => {
+                let server = Duration::from_millis(returns.processing_ms());
+                println!("seq={seq} time={round_trip:?} server={server:?}");
+                samples.round_trip.push(round_trip);
+                samples.server.push(server);
+            }
+            Ok(_) => println!("seq={seq} error: payload was not echoed back unchanged"),
+            Err(e) => println!("seq={seq} error: {e}"),
+        }
+    }
+
+    println!(
+        "--- {count} probes sent, {} responses received ---",
+        samples.round_trip.len()
+    );
+    summary("round-trip", &mut samples.round_trip);
+    summary("server", &mut samples.server);
+
+    if samples.round_trip.is_empty() {
+        Err(anyhow!("No response from {server}"))
+    } else {
+        Ok(())
+    }
+}
@@ -7,7 +7,9 @@ use many_modules::r#async::{StatusArgs, StatusReturn};
 use many_modules::web::ListArgs;
 use many_modules::{r#async, web};
 use many_protocol::ResponseMessage;
-use many_types::web::{WebDeploymentFilter, WebDeploymentSource};
+use many_types::web::{
+    WebDeploymentFilter, WebDeploymentSource, WebRecord, WebRedirect, WebSiteConfig,
+};
 use many_types::{Memo, SortOrder};
 use std::path::PathBuf;
 use std::time::Duration;
@@ -48,6 +50,23 @@ enum SubCommand {
 
     /// Update website
     Update(UpdateOpt),
+
+    /// Show the redirects and 404 document of a website
+    Config(ConfigOpt),
+
+    /// Set the redirects and 404 document of a website, replacing the
+    /// existing ones
+    SetConfig(SetConfigOpt),
+
+    /// Show the content hash of a file of a website, or the root hash of the
+    /// website and the hash of every file
+    ContentHash(ContentHashOpt),
+
+    /// Show the records of a website
+    Records(RecordsOpt),
+
+    /// Set the records of a website, replacing the existing ones
+    SetRecords(SetRecordsOpt),
 }
 
 #[derive(Debug, Parser)]
@@ -114,6 +133,71 @@ struct RemoveOpt {
     memo: Option<Memo>,
 }
 
+#[derive(Debug, Parser)]
+struct ConfigOpt {
+    /// Site name
+    site_name: String,
+
+    /// MANY address of the website owner
+    owner: Address,
+}
+
+#[derive(Debug, Parser)]
+struct SetConfigOpt {
+    /// Site name
+    site_name: String,
+
+    /// MANY address of the website owner
+    #[clap(long)]
+    owner: Option<Address>,
+
+    /// A redirect, as `FROM=TO[,STATUS]`. FROM is a path of the website and
+    /// TO a path or a URL. The status defaults to 301. Can be repeated.
+    #[clap(long = "redirect")]
+    redirects: Vec<WebRedirect>,
+
+    /// Path of the document to serve when a path is not found
+    #[clap(long)]
+    not_found: Option<String>,
+}
+
+#[derive(Debug, Parser)]
+struct ContentHashOpt {
+    /// Site name
+    site_name: String,
+
+    /// MANY address of the website owner
+    owner: Address,
+
+    /// Path of a file of the website
+    #[clap(long)]
+    path: Option<String>,
+}
+
+#[derive(Debug, Parser)]
+struct RecordsOpt {
+    /// Site name
+    site_name: String,
+
+    /// MANY address of the website owner
+    owner: Address,
+}
+
+#[derive(Debug, Parser)]
+struct SetRecordsOpt {
+    /// Site name
+    site_name: String,
+
+    /// MANY address of the website owner
+    #[clap(long)]
+    owner: Option<Address>,
+
+    /// A record, as `content:OWNER/SITE`, `txt:TEXT` or
+    /// `redirect:URL[,STATUS]`. Can be repeated.
+    #[clap(long = "record")]
+    records: Vec<WebRecord>,
+}
+
 #[derive(Debug, Parser)]
 struct ListOpt {
     /// Count
@@ -231,6 +315,94 @@ fn list(
     Ok(())
 }
 
+fn config(
+    client: ManyClient<impl Identity>,
+    site_name: String,
+    owner: Address,
+) -> Result<(), ManyError> {
+    let response = client.call("web.config", web::ConfigArgs { owner, site_name })?;
+    let payload = wait_response(client, response)?;
+    println!(
+        "{}",
+        cbor_diag::parse_bytes(payload).unwrap().to_diag_pretty()
+    );
+    Ok(())
+}
+
+fn set_config(
+    client: ManyClient<impl Identity>,
+    site_name: String,
+    owner: Option<Address>,
+    config: WebSiteConfig,
+) -> Result<(), ManyError> {
+    let arguments = web::SetConfigArgs {
+        owner,
+        site_name,
+        config,
+    };
+    let response = client.call("web.setConfig", arguments)?;
+    let payload = wait_response(client, response)?;
+    println!(
+        "{}",
+        cbor_diag::parse_bytes(payload).unwrap().to_diag_pretty()
+    );
+    Ok(())
+}
+
+fn records(
+    client: ManyClient<impl Identity>,
+    site_name: String,
+    owner: Address,
+) -> Result<(), ManyError> {
+    let response = client.call("web.records", web::RecordsArgs { owner, site_name })?;
+    let payload = wait_response(client, response)?;
+    println!(
+        "{}",
+        cbor_diag::parse_bytes(payload).unwrap().to_diag_pretty()
+    );
+    Ok(())
+}
+
+fn set_records(
+    client: ManyClient<impl Identity>,
+    site_name: String,
+    owner: Option<Address>,
+    records: Vec<WebRecord>,
+) -> Result<(), ManyError> {
+    let arguments = web::SetRecordsArgs {
+        owner,
+        site_name,
+        records,
+    };
+    let response = client.call("web.setRecords", arguments)?;
+    let payload = wait_response(client, response)?;
+    println!(
+        "{}",
+        cbor_diag::parse_bytes(payload).unwrap().to_diag_pretty()
+    );
+    Ok(())
+}
+
+fn content_hash(
+    client: ManyClient<impl Identity>,
+    site_name: String,
+    owner: Address,
+    path: Option<String>,
+) -> Result<(), ManyError> {
+    let arguments = web::ContentHashArgs {
+        owner,
+        site_name,
+        path,
+    };
+    let response = client.call("web.contentHash", arguments)?;
+    let payload = wait_response(client, response)?;
+    println!(
+        "{}",
+        cbor_diag::parse_bytes(payload).unwrap().to_diag_pretty()
+    );
+    Ok(())
+}
+
 pub(crate) fn wait_response(
     client: ManyClient<impl Identity>,
     response: ResponseMessage,
@@ -360,6 +532,32 @@ fn main() {
             memo,
             domain,
         ),
+        SubCommand::Config(ConfigOpt { site_name, owner }) => config(client, site_name, owner),
+        SubCommand::SetConfig(SetConfigOpt {
+            site_name,
+            owner,
+            redirects,
+            not_found,
+        }) => set_config(
+            client,
+            site_name,
+            owner,
+            WebSiteConfig {
+                redirects,
+                not_found,
+            },
+        ),
+        SubCommand::ContentHash(ContentHashOpt {
+            site_name,
+            owner,
+            path,
+        }) => content_hash(client, site_name, owner, path),
+        SubCommand::Records(RecordsOpt { site_name, owner }) => records(client, site_name, owner),
+        SubCommand::SetRecords(SetRecordsOpt {
+            site_name,
+            owner,
+            records,
+        }) => set_records(client, site_name, owner, records),
     };
 
     if let Err(err) = result {
@@ -208,6 +208,37 @@ function teardown() {
   assert_output --partial "778899"
 }
 
+@test "$SUITE: can list keys under a prefix" {
+  call_kvstore --pem=1 --port=8000 put "dir/112233" "foobar"
+  call_kvstore --pem=1 --port=8000 put "dir/445566" "foobar2"
+  call_kvstore --pem=1 --port=8000 put "778899" "foobar3"
+
+  call_kvstore --pem=1 --port=8000 list "dir/" --values
+  assert_output --partial "dir/112233: foobar"
+  assert_output --partial "dir/445566: foobar2"
+  refute_output --partial "778899"
+
+  call_kvstore --pem=1 --port=8000 list "dir/" --count 1
+  assert_output --partial "dir/112233"
+  refute_output --partial "dir/445566"
+  assert_output --partial "--start-after dir/112233"
+
+  call_kvstore --pem=1 --port=8000 list "dir/" --start-after "dir/112233"
+  refute_output --partial "dir/112233"
+  assert_output --partial "dir/445566"
+}
+
+@test "$SUITE: can put multiple keys from a JSON file" {
+  batch_file="$(mktemp)"
+  echo '{"batch/1": "foo", "batch/2": "bar"}' > "$batch_file"
+  call_kvstore --pem=1 --port=8000 put --from-json "$batch_file"
+
+  call_kvstore --pem=1 --port=8000 get "batch/1"
+  assert_output --partial "foo"
+  call_kvstore --pem=1 --port=8000 get "batch/2"
+  assert_output --partial "bar"
+}
+
 @test "$SUITE: can list disabled key" {
   call_kvstore --pem=1 --port=8000 put "112233" "foobar"
   call_kvstore --pem=1 --port=8000 put "445566" "foobar2"
@@ -38,3 +38,27 @@ function teardown() {
     call_ledger --pem=4 --port=8000 send --account="$account_id" "$(identity 4)" 2000 MFX
     assert_output --partial "Sender needs role 'canLedgerTransact' to perform this operation."
 }
+
+@test "$SUITE: ledger can send tokens signed offline" {
+    many message --unsigned --from "$(identity 1)" --out "$BATS_TEST_ROOTDIR/send1.cbor" \
+        ledger.send "{ 1: \"$(identity 4)\", 2: 1000, 3: \"$MFX_ADDRESS\" }"
+    many message --unsigned --from "$(identity 2)" --hex \
+        ledger.send "{ 1: \"$(identity 4)\", 2: 2000, 3: \"$MFX_ADDRESS\" }" > "$BATS_TEST_ROOTDIR/send2.hex"
+
+    # The key needs to match the address the request is from.
+    run many sign --pem "$(pem 2)" "$BATS_TEST_ROOTDIR/send1.cbor"
+    assert_failure
+
+    many sign --pem "$(pem 1)" --out "$BATS_TEST_ROOTDIR/signed1.cbor" "$BATS_TEST_ROOTDIR/send1.cbor"
+    many sign --pem "$(pem 2)" --out "$BATS_TEST_ROOTDIR/signed2.cbor" "$BATS_TEST_ROOTDIR/send2.hex"
+    many combine --out "$BATS_TEST_ROOTDIR/combined.cbor" \
+        "$BATS_TEST_ROOTDIR/signed1.cbor" "$BATS_TEST_ROOTDIR/signed2.cbor"
+
+    many submit --server http://localhost:8000 --from-file "$BATS_TEST_ROOTDIR/combined.cbor"
+    check_consistency --pem=4 --balance=3000 --id="$(identity 4)" 8000
+}
+
+@test "$SUITE: ledger balance proofs are verified against the expected root" {
+    call_ledger --pem=1 --port=8000 balance --proof --expected-root "$(printf '%064d' 0)"
+    assert_output --partial "The proof is invalid"
+}
@@ -226,6 +226,31 @@ function teardown() {
     assert_output --partial "Invalid Identity; the sender cannot be anonymous."
 }
 
+@test "$SUITE: can set and get extended info" {
+    create_token --pem=1 --port=8000 --ext_info_type="memo"
+
+    call_ledger --pem=1 --port=8000 token ext-info set-memo "${SYMBOL}" "\"New memo\"" --yes
+    assert_output --partial "- \"My memo\""
+    assert_output --partial "+ \"New memo\""
+
+    call_ledger --pem=1 --port=8000 token ext-info set-logo --yes "${SYMBOL}" unicode "'∑'"
+    assert_output --partial "- (none)"
+    assert_output --partial "+ unicode '∑'"
+
+    call_ledger --port=8000 token ext-info get "${SYMBOL}"
+    assert_output --partial "Memo: \"New memo\""
+    assert_output --partial "Logo: unicode '∑'"
+}
+
+@test "$SUITE: doesn't set extended info without confirmation" {
+    create_token --pem=1 --port=8000 --ext_info_type="memo"
+
+    call_ledger --pem=1 --port=8000 token ext-info set-memo "${SYMBOL}" "\"New memo\"" "< /dev/null"
+    assert_output --partial "+ \"New memo\""
+    call_ledger --port=8000 token ext-info get "${SYMBOL}"
+    assert_output --partial "Memo: \"My memo\""
+}
+
 @test "$SUITE: can update token, token owner is account, caller is account owner" {
     token_account --perm="canTokensUpdate"
     call_ledger --pem=1 --port=8000 token update --name "\"New name\"" "${SYMBOL}"